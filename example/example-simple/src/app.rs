@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use rlink::core::backend::{CheckpointBackend, KeyedStateBackend};
+use rlink::core::checkpoint::CheckpointConfig;
 use rlink::core::data_stream::{TDataStream, TKeyedStream, TWindowedStream};
 use rlink::core::env::{StreamApp, StreamExecutionEnvironment};
 use rlink::core::properties::{Properties, SystemProperties};
@@ -26,7 +27,12 @@ impl StreamApp for SimpleStreamApp {
         properties.set_application_name("rlink-simple");
 
         properties.set_keyed_state_backend(KeyedStateBackend::Memory);
-        properties.set_checkpoint_interval(Duration::from_secs(15));
+        properties.set_checkpoint_config(
+            CheckpointConfig::new(Duration::from_secs(15))
+                .min_pause(Duration::from_secs(5))
+                .timeout(Duration::from_secs(60))
+                .tolerable_failures(3),
+        );
         properties.set_checkpoint(CheckpointBackend::Memory);
         properties.set_pub_sub_channel_size(10000);
     }
@@ -0,0 +1,89 @@
+use rlink::core;
+use rlink::core::element::{FnSchema, Record};
+use rlink::core::function::Context;
+
+use crate::nfa::{Nfa, PartialMatch};
+
+/// The events of a match or of a timed out partial match, grouped by the stages in the order of
+/// the pattern. The stages without events are absent.
+#[derive(Debug)]
+pub struct PatternMatch {
+    events: Vec<(String, Vec<Record>)>,
+}
+
+impl PatternMatch {
+    pub(crate) fn new(nfa: &Nfa, partial_match: &PartialMatch) -> Self {
+        let mut events: Vec<(String, Vec<Record>)> = Vec::new();
+        for event in &partial_match.events {
+            let record = Record::from_values(event.values.as_slice(), event.timestamp);
+            let name = nfa.stage_name(event.stage);
+            match events.last_mut() {
+                Some((n, records)) if n.as_str() == name => records.push(record),
+                _ => events.push((name.to_string(), vec![record])),
+            }
+        }
+        PatternMatch { events }
+    }
+
+    /// The names of the stages taking events
+    pub fn stages(&self) -> Vec<&str> {
+        self.events.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn get(&self, stage: &str) -> Option<&Vec<Record>> {
+        self.events
+            .iter()
+            .find(|(name, _)| name.as_str() == stage)
+            .map(|(_, records)| records)
+    }
+
+    pub fn get_mut(&mut self, stage: &str) -> Option<&mut Vec<Record>> {
+        self.events
+            .iter_mut()
+            .find(|(name, _)| name.as_str() == stage)
+            .map(|(_, records)| records)
+    }
+
+    pub fn first_timestamp(&self) -> u64 {
+        self.events
+            .first()
+            .and_then(|(_, records)| records.first())
+            .map(|x| x.timestamp())
+            .unwrap_or_default()
+    }
+
+    pub fn last_timestamp(&self) -> u64 {
+        self.events
+            .last()
+            .and_then(|(_, records)| records.last())
+            .map(|x| x.timestamp())
+            .unwrap_or_default()
+    }
+}
+
+/// Produce the output records of the matches of a `Pattern`, see `TPatternStream::pattern`
+pub trait PatternProcessFunction
+where
+    Self: Send + Sync,
+{
+    fn open(&mut self, _context: &Context) -> core::Result<()> {
+        Ok(())
+    }
+
+    /// The output records of a match, the records without timestamp take the timestamp of the
+    /// last event of the match
+    fn process_match(&mut self, pattern_match: &mut PatternMatch) -> Vec<Record>;
+
+    /// The output records of a partial match timed out by the `within` of the pattern, the
+    /// records can be routed to a side output with `OutputTag::output`. The records without
+    /// timestamp take the timestamp the partial match is timed out at.
+    fn process_timed_out_match(&mut self, _pattern_match: &mut PatternMatch) -> Vec<Record> {
+        vec![]
+    }
+
+    fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema;
+}
@@ -0,0 +1,18 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod function;
+pub mod pattern;
+pub mod stream;
+
+mod nfa;
+
+pub use function::{PatternMatch, PatternProcessFunction};
+pub use pattern::{Contiguity, Pattern};
+pub use stream::{CepProcessFunction, TPatternStream};
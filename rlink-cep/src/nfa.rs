@@ -0,0 +1,347 @@
+use rlink::core::element::Record;
+
+use crate::pattern::{Contiguity, Pattern, Stage, StageKind};
+
+/// An event taken by a stage of the partial match
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub(crate) struct MatchedEvent {
+    pub stage: usize,
+    pub timestamp: u64,
+    pub values: Vec<u8>,
+}
+
+/// The state of a partial match kept in the keyed state
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub(crate) struct PartialMatch {
+    /// the index of the stage taking the latest event
+    pub stage: usize,
+    /// the count of the events taken by the stage
+    pub count: u32,
+    /// the `until` condition of the stage holds, the stage takes no more events
+    pub closed: bool,
+    /// the next event must be taken, or the partial match is discarded
+    pub strict: bool,
+    pub events: Vec<MatchedEvent>,
+}
+
+impl PartialMatch {
+    pub fn start_timestamp(&self) -> Option<u64> {
+        self.events.first().map(|x| x.timestamp)
+    }
+
+    fn take(&self, stage: usize, count: u32, event: &MatchedEvent) -> Self {
+        let mut events = self.events.clone();
+        events.push(MatchedEvent {
+            stage,
+            timestamp: event.timestamp,
+            values: event.values.clone(),
+        });
+        PartialMatch {
+            stage,
+            count,
+            closed: false,
+            strict: false,
+            events,
+        }
+    }
+}
+
+/// The matches and the timed out partial matches produced by the events
+#[derive(Debug, Default)]
+pub(crate) struct NfaOutput {
+    pub matched: Vec<PartialMatch>,
+    pub timed_out: Vec<PartialMatch>,
+}
+
+/// The non-deterministic automaton of a `Pattern`. The events of a key are advanced one by one
+/// in event time order, each of them may start a new partial match and is taken by the
+/// partial matches with the skip-till-next-match strategy: an accepted event is always taken,
+/// the others are skipped unless the contiguity is strict.
+#[derive(Debug)]
+pub(crate) struct Nfa {
+    stages: Vec<Stage>,
+    within: Option<u64>,
+}
+
+impl Nfa {
+    pub fn new(pattern: &Pattern) -> Self {
+        Nfa {
+            stages: pattern.stages().to_vec(),
+            within: pattern.within_millis(),
+        }
+    }
+
+    pub fn within(&self) -> Option<u64> {
+        self.within
+    }
+
+    pub fn stage_name(&self, stage: usize) -> &str {
+        self.stages[stage].name.as_str()
+    }
+
+    /// Advance the partial matches of the current key by the event
+    pub fn advance(
+        &self,
+        partial_matches: Vec<PartialMatch>,
+        record: &mut Record,
+    ) -> (Vec<PartialMatch>, NfaOutput) {
+        let event = MatchedEvent {
+            stage: 0,
+            timestamp: record.timestamp(),
+            values: record.as_buffer().as_slice().to_vec(),
+        };
+        let accepted: Vec<bool> = self.stages.iter().map(|x| x.accept(record)).collect();
+        let stopped: Vec<bool> = self.stages.iter().map(|x| x.stop(record)).collect();
+
+        let (partial_matches, timed_out) = self.expire(partial_matches, event.timestamp);
+        let mut output = NfaOutput {
+            matched: vec![],
+            timed_out,
+        };
+
+        let mut next = Vec::new();
+        for partial_match in partial_matches {
+            self.step(
+                partial_match,
+                &event,
+                &accepted,
+                &stopped,
+                &mut next,
+                &mut output,
+            );
+        }
+        // the event may start a new partial match
+        self.step(
+            PartialMatch::default(),
+            &event,
+            &accepted,
+            &stopped,
+            &mut next,
+            &mut output,
+        );
+
+        (next, output)
+    }
+
+    /// Split the partial matches started at least `within` before the `timestamp`
+    pub fn expire(
+        &self,
+        partial_matches: Vec<PartialMatch>,
+        timestamp: u64,
+    ) -> (Vec<PartialMatch>, Vec<PartialMatch>) {
+        match self.within {
+            Some(within) => partial_matches.into_iter().partition(|x| {
+                x.start_timestamp()
+                    .map(|start| start + within > timestamp)
+                    .unwrap_or(true)
+            }),
+            None => (partial_matches, vec![]),
+        }
+    }
+
+    fn step(
+        &self,
+        mut partial_match: PartialMatch,
+        event: &MatchedEvent,
+        accepted: &[bool],
+        stopped: &[bool],
+        next: &mut Vec<PartialMatch>,
+        output: &mut NfaOutput,
+    ) {
+        let index = partial_match.stage;
+        let stage = &self.stages[index];
+        let mut successors = Vec::new();
+
+        // take the event by the current stage
+        if !partial_match.closed
+            && accepted[index]
+            && !stopped[index]
+            && stage.can_take(partial_match.count)
+        {
+            successors.push(partial_match.take(index, partial_match.count + 1, event));
+        }
+
+        // take the event by the following stages, the optional stages can be passed over
+        let mut discarded = false;
+        if partial_match.count >= stage.min {
+            for (i, following) in self.stages.iter().enumerate().skip(index + 1) {
+                if following.kind == StageKind::NotFollowedBy {
+                    if accepted[i] {
+                        discarded = true;
+                        break;
+                    }
+                    continue;
+                }
+
+                if accepted[i] {
+                    successors.push(partial_match.take(i, 1, event));
+                }
+                if following.min > 0 {
+                    break;
+                }
+            }
+        }
+
+        if successors.is_empty() {
+            // skip the event, the new partial match is dropped
+            let fresh = partial_match.events.is_empty();
+            if !fresh && !discarded && !partial_match.strict {
+                partial_match.closed |= stopped[index];
+                next.push(partial_match);
+            }
+            return;
+        }
+
+        for mut successor in successors {
+            if self.is_complete(&successor) {
+                output.matched.push(successor.clone());
+                if successor.stage + 1 == self.stages.len()
+                    && !self.stages[successor.stage].can_take(successor.count)
+                {
+                    continue;
+                }
+            }
+            successor.strict = self.is_strict(&successor);
+            next.push(successor);
+        }
+    }
+
+    /// Whether the stages after the partial match are all optional
+    fn is_complete(&self, partial_match: &PartialMatch) -> bool {
+        partial_match.count >= self.stages[partial_match.stage].min
+            && self.stages[partial_match.stage + 1..]
+                .iter()
+                .all(|x| x.kind == StageKind::Positive && x.min == 0)
+    }
+
+    /// Whether the next event must be taken by the next stage
+    fn is_strict(&self, partial_match: &PartialMatch) -> bool {
+        let stage = &self.stages[partial_match.stage];
+        if partial_match.count < stage.min || stage.can_take(partial_match.count) {
+            return false;
+        }
+        self.stages
+            .get(partial_match.stage + 1)
+            .map(|x| x.contiguity == Contiguity::Strict && x.min > 0)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::nfa::{Nfa, PartialMatch};
+    use crate::pattern::Pattern;
+
+    fn event(kind: &str, timestamp: u64) -> Record {
+        let schema = Schema::new(vec![Field::new("kind", DataType::String)]);
+        let mut record = Record::new();
+        record
+            .as_writer(schema.as_type_ids())
+            .set_str(kind)
+            .unwrap();
+        record.set_timestamp(timestamp);
+        record
+    }
+
+    fn is(kind: &'static str) -> impl Fn(&mut Record) -> bool + Send + Sync + 'static {
+        move |record: &mut Record| {
+            let schema = Schema::new(vec![Field::new("kind", DataType::String)]);
+            let reader = record.as_reader(schema.as_type_ids());
+            reader.get_str(0).unwrap() == kind
+        }
+    }
+
+    /// Run the events through the pattern, returns the timestamps of the matched events
+    fn run(pattern: Pattern, events: &[(&str, u64)]) -> (Vec<Vec<u64>>, Vec<Vec<u64>>) {
+        pattern.validate().unwrap();
+        let nfa = Nfa::new(&pattern);
+
+        let mut partial_matches: Vec<PartialMatch> = vec![];
+        let mut matched = vec![];
+        let mut timed_out = vec![];
+        let timestamps = |x: PartialMatch| x.events.iter().map(|e| e.timestamp).collect();
+        for (kind, timestamp) in events {
+            let (next, output) = nfa.advance(partial_matches, &mut event(kind, *timestamp));
+            partial_matches = next;
+            matched.extend(output.matched.into_iter().map(timestamps));
+            timed_out.extend(output.timed_out.into_iter().map(timestamps));
+        }
+        (matched, timed_out)
+    }
+
+    #[test]
+    pub fn followed_by_test() {
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .followed_by("b")
+            .condition(is("b"));
+        let (matched, _) = run(pattern, &[("a", 1), ("c", 2), ("b", 3), ("b", 4)]);
+        assert_eq!(matched, vec![vec![1, 3]]);
+
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .next("b")
+            .condition(is("b"));
+        let (matched, _) = run(pattern, &[("a", 1), ("c", 2), ("b", 3), ("a", 4), ("b", 5)]);
+        assert_eq!(matched, vec![vec![4, 5]]);
+    }
+
+    #[test]
+    pub fn quantifier_test() {
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .times(2)
+            .followed_by("b")
+            .condition(is("b"));
+        let (matched, _) = run(pattern, &[("a", 1), ("a", 2), ("a", 3), ("b", 4)]);
+        assert_eq!(matched, vec![vec![1, 2, 4], vec![2, 3, 4]]);
+
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .one_or_more()
+            .until(is("x"))
+            .followed_by("b")
+            .condition(is("b"));
+        let (matched, _) = run(pattern, &[("a", 1), ("x", 2), ("a", 3), ("b", 4)]);
+        assert_eq!(matched, vec![vec![1, 4], vec![3, 4]]);
+
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .followed_by("c")
+            .condition(is("c"))
+            .optional()
+            .followed_by("b")
+            .condition(is("b"));
+        let (matched, _) = run(pattern, &[("a", 1), ("b", 2), ("a", 3), ("c", 4), ("b", 5)]);
+        assert_eq!(matched, vec![vec![1, 2], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    pub fn not_followed_by_test() {
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .not_followed_by("c")
+            .condition(is("c"))
+            .followed_by("b")
+            .condition(is("b"));
+        let (matched, _) = run(pattern, &[("a", 1), ("c", 2), ("a", 3), ("b", 4)]);
+        assert_eq!(matched, vec![vec![3, 4]]);
+    }
+
+    #[test]
+    pub fn within_test() {
+        let pattern = Pattern::begin("a")
+            .condition(is("a"))
+            .followed_by("b")
+            .condition(is("b"))
+            .within(Duration::from_millis(10));
+        let (matched, timed_out) = run(pattern, &[("a", 1), ("a", 8), ("b", 11)]);
+        assert_eq!(matched, vec![vec![8, 11]]);
+        assert_eq!(timed_out, vec![vec![1]]);
+    }
+}
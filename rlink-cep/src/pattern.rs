@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rlink::core::element::Record;
+
+/// The condition of a stage, evaluated on each event of the key
+pub type Condition = Arc<dyn Fn(&mut Record) -> bool + Send + Sync>;
+
+/// How a stage follows the previous one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Contiguity {
+    /// the first event of the stage must be the event right after the previous stage
+    Strict,
+    /// the events not accepted between the previous stage and the stage are skipped
+    Relaxed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StageKind {
+    /// the events accepted by the stage are the part of the match
+    Positive,
+    /// the event accepted by the stage discards the partial match
+    NotFollowedBy,
+}
+
+#[derive(Clone)]
+pub(crate) struct Stage {
+    pub(crate) name: String,
+    pub(crate) kind: StageKind,
+    pub(crate) contiguity: Contiguity,
+    pub(crate) condition: Option<Condition>,
+    pub(crate) until: Option<Condition>,
+    pub(crate) min: u32,
+    /// `None` for the unbounded stage
+    pub(crate) max: Option<u32>,
+}
+
+impl Stage {
+    fn new(name: &str, kind: StageKind, contiguity: Contiguity) -> Self {
+        Stage {
+            name: name.to_string(),
+            kind,
+            contiguity,
+            condition: None,
+            until: None,
+            min: 1,
+            max: Some(1),
+        }
+    }
+
+    /// Whether the event is accepted by the stage, the stage without condition accepts all events
+    pub(crate) fn accept(&self, record: &mut Record) -> bool {
+        self.condition.as_ref().map(|c| c(record)).unwrap_or(true)
+    }
+
+    /// Whether the event stops the looping of the stage
+    pub(crate) fn stop(&self, record: &mut Record) -> bool {
+        self.until.as_ref().map(|c| c(record)).unwrap_or(false)
+    }
+
+    /// Whether the stage can take one more event after taking `count` events
+    pub(crate) fn can_take(&self, count: u32) -> bool {
+        self.max.map(|max| count < max).unwrap_or(true)
+    }
+
+    fn is_mandatory(&self) -> bool {
+        self.kind == StageKind::Positive && self.min > 0
+    }
+}
+
+impl Debug for Stage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Stage")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("contiguity", &self.contiguity)
+            .field("min", &self.min)
+            .field("max", &self.max)
+            .finish()
+    }
+}
+
+/// The sequence of the stages matched on the events of a key in event time order, e.g. three
+/// failed logins followed by a successful one within 10 minutes:
+///
+/// ```ignore
+/// let pattern = Pattern::begin("fail")
+///     .condition(|record| is_failed(record))
+///     .times(3)
+///     .followed_by("success")
+///     .condition(|record| !is_failed(record))
+///     .within(Duration::from_secs(600));
+/// ```
+///
+/// The quantifiers and the conditions apply to the latest stage. A looping stage takes every
+/// event it accepts and skips the others until the `until` condition holds, a match is emitted
+/// each time the last mandatory stage is satisfied.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    stages: Vec<Stage>,
+    within: Option<Duration>,
+}
+
+impl Pattern {
+    pub fn begin(name: &str) -> Self {
+        Pattern {
+            stages: vec![Stage::new(name, StageKind::Positive, Contiguity::Relaxed)],
+            within: None,
+        }
+    }
+
+    /// Append a stage whose first event must be right after the previous stage
+    pub fn next(self, name: &str) -> Self {
+        self.stage(Stage::new(name, StageKind::Positive, Contiguity::Strict))
+    }
+
+    /// Append a stage skipping the events not accepted after the previous stage
+    pub fn followed_by(self, name: &str) -> Self {
+        self.stage(Stage::new(name, StageKind::Positive, Contiguity::Relaxed))
+    }
+
+    /// Append a stage that must not be accepted between the previous stage and the next one,
+    /// the stage must be followed by a mandatory stage
+    pub fn not_followed_by(self, name: &str) -> Self {
+        self.stage(Stage::new(
+            name,
+            StageKind::NotFollowedBy,
+            Contiguity::Relaxed,
+        ))
+    }
+
+    fn stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    fn last_stage(&mut self) -> &mut Stage {
+        self.stages.last_mut().unwrap()
+    }
+
+    fn last_positive_stage(&mut self, quantifier: &str) -> &mut Stage {
+        let stage = self.last_stage();
+        if stage.kind != StageKind::Positive {
+            panic!(
+                "`{}` is not allowed on the stage `{}`",
+                quantifier, stage.name
+            );
+        }
+        stage
+    }
+
+    /// The condition of the latest stage, the conditions of a stage are combined by `and`
+    pub fn condition<F>(mut self, condition: F) -> Self
+    where
+        F: Fn(&mut Record) -> bool + Send + Sync + 'static,
+    {
+        let stage = self.last_stage();
+        stage.condition = match stage.condition.take() {
+            Some(c) => Some(Arc::new(move |record: &mut Record| {
+                c(record) && condition(record)
+            })),
+            None => Some(Arc::new(condition)),
+        };
+        self
+    }
+
+    /// Stop the looping of the latest stage at the first event holds the condition, the event
+    /// isn't taken by the stage but can be taken by the next one
+    pub fn until<F>(mut self, condition: F) -> Self
+    where
+        F: Fn(&mut Record) -> bool + Send + Sync + 'static,
+    {
+        self.last_positive_stage("until").until = Some(Arc::new(condition));
+        self
+    }
+
+    /// The latest stage takes exactly `times` events
+    pub fn times(self, times: u32) -> Self {
+        self.times_range(times, times)
+    }
+
+    /// The latest stage takes from `min` to `max` events
+    pub fn times_range(mut self, min: u32, max: u32) -> Self {
+        if min == 0 || min > max {
+            panic!("illegal times range [{}, {}]", min, max);
+        }
+        let stage = self.last_positive_stage("times");
+        stage.min = min;
+        stage.max = Some(max);
+        self
+    }
+
+    /// The latest stage takes at least `times` events
+    pub fn times_or_more(mut self, times: u32) -> Self {
+        if times == 0 {
+            panic!("illegal times 0");
+        }
+        let stage = self.last_positive_stage("times_or_more");
+        stage.min = times;
+        stage.max = None;
+        self
+    }
+
+    pub fn one_or_more(self) -> Self {
+        self.times_or_more(1)
+    }
+
+    /// The latest stage may take no event
+    pub fn optional(mut self) -> Self {
+        self.last_positive_stage("optional").min = 0;
+        self
+    }
+
+    /// The maximum interval between the first event and the last event of a match, the partial
+    /// matches exceeding the interval are timed out
+    pub fn within(mut self, within: Duration) -> Self {
+        self.within = Some(within);
+        self
+    }
+
+    pub(crate) fn stages(&self) -> &[Stage] {
+        self.stages.as_slice()
+    }
+
+    pub(crate) fn within_millis(&self) -> Option<u64> {
+        self.within.map(|x| x.as_millis() as u64)
+    }
+
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        for stage in &self.stages {
+            if !names.insert(stage.name.as_str()) {
+                return Err(anyhow!("duplicate stage `{}`", stage.name));
+            }
+        }
+
+        for (index, stage) in self.stages.iter().enumerate() {
+            if stage.kind != StageKind::NotFollowedBy {
+                continue;
+            }
+            if index == 0 {
+                return Err(anyhow!("the pattern can't begin with `not_followed_by`"));
+            }
+            if !self.stages[index + 1..].iter().any(|x| x.is_mandatory()) {
+                return Err(anyhow!(
+                    "the stage `{}` must be followed by a mandatory stage",
+                    stage.name
+                ));
+            }
+        }
+
+        if !self.stages.iter().any(|x| x.is_mandatory()) {
+            return Err(anyhow!("the pattern has no mandatory stage"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::pattern::{Contiguity, Pattern, StageKind};
+
+    #[test]
+    pub fn pattern_test() {
+        let pattern = Pattern::begin("a")
+            .times_or_more(2)
+            .next("b")
+            .not_followed_by("c")
+            .followed_by("d")
+            .optional()
+            .followed_by("e")
+            .within(Duration::from_secs(60));
+        pattern.validate().unwrap();
+
+        let stages = pattern.stages();
+        assert_eq!((stages[0].min, stages[0].max), (2, None));
+        assert_eq!(stages[1].contiguity, Contiguity::Strict);
+        assert_eq!(stages[2].kind, StageKind::NotFollowedBy);
+        assert_eq!((stages[3].min, stages[3].max), (0, Some(1)));
+        assert_eq!(pattern.within_millis(), Some(60_000));
+
+        assert!(Pattern::begin("a").followed_by("a").validate().is_err());
+        assert!(Pattern::begin("a").not_followed_by("b").validate().is_err());
+        assert!(Pattern::begin("a")
+            .not_followed_by("b")
+            .followed_by("c")
+            .optional()
+            .validate()
+            .is_err());
+        assert!(Pattern::begin("a").optional().validate().is_err());
+    }
+}
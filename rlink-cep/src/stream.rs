@@ -0,0 +1,226 @@
+use rlink::core;
+use rlink::core::data_stream::{DataStream, KeyedStream, TKeyedStream};
+use rlink::core::element::{FnSchema, Record};
+use rlink::core::function::{Context, KeyedProcessFunction, NamedFunction, SendableElementStream};
+use rlink::core::state::{ListState, ListStateDescriptor, ValueState, ValueStateDescriptor};
+use rlink::core::timer::{TimeDomain, TimerService};
+use rlink::utils::stream::MemoryStream;
+
+use crate::function::{PatternMatch, PatternProcessFunction};
+use crate::nfa::{Nfa, PartialMatch};
+use crate::pattern::Pattern;
+
+/// the events waiting for the watermark
+const BUFFERED_EVENTS: &str = "cep.buffered_events";
+/// the partial matches of the key
+const PARTIAL_MATCHES: &str = "cep.partial_matches";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BufferedEvent {
+    timestamp: u64,
+    values: Vec<u8>,
+}
+
+pub trait TPatternStream {
+    /// Match the pattern on the events of each key in event time order, the output records of
+    /// the matches are produced by the `function`
+    fn pattern<F>(self, pattern: Pattern, function: F) -> DataStream
+    where
+        F: PatternProcessFunction + 'static;
+}
+
+impl TPatternStream for KeyedStream {
+    fn pattern<F>(self, pattern: Pattern, function: F) -> DataStream
+    where
+        F: PatternProcessFunction + 'static,
+    {
+        self.process(CepProcessFunction::new(pattern, function))
+    }
+}
+
+/// Match a `Pattern` on the keyed stream. The events are buffered in the keyed state until the
+/// watermark passes them, then advanced through the `Nfa` in event time order. The events
+/// behind the watermark are dropped. The partial matches are kept in the keyed state and timed
+/// out by the event time timers at the end of their `within`.
+pub struct CepProcessFunction<F>
+where
+    F: PatternProcessFunction,
+{
+    nfa: Nfa,
+    function: F,
+    parallelism: u16,
+
+    buffered_events: Option<ListState<BufferedEvent>>,
+    partial_matches: Option<ValueState<Vec<PartialMatch>>>,
+}
+
+impl<F> CepProcessFunction<F>
+where
+    F: PatternProcessFunction,
+{
+    pub fn new(pattern: Pattern, function: F) -> Self {
+        pattern.validate().expect("illegal pattern");
+        CepProcessFunction {
+            nfa: Nfa::new(&pattern),
+            function,
+            parallelism: 0,
+            buffered_events: None,
+            partial_matches: None,
+        }
+    }
+
+    /// The parallelism of the operator, inherit the parallelism of the upstream by default
+    pub fn parallelism(mut self, parallelism: u16) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Take the buffered events at or before the `timestamp` in event time order
+    fn take_events(&self, timestamp: u64) -> anyhow::Result<Vec<BufferedEvent>> {
+        let buffered_events = self.buffered_events.as_ref().unwrap();
+        let (mut events, pending): (Vec<BufferedEvent>, Vec<BufferedEvent>) = buffered_events
+            .get()?
+            .into_iter()
+            .partition(|x| x.timestamp <= timestamp);
+        if pending.is_empty() {
+            buffered_events.clear();
+        } else {
+            buffered_events.update(pending)?;
+        }
+
+        events.sort_by_key(|x| x.timestamp);
+        Ok(events)
+    }
+
+    fn fire(
+        &mut self,
+        timestamp: u64,
+        timer_service: &mut TimerService,
+    ) -> anyhow::Result<Vec<Record>> {
+        let partial_matches_state = self.partial_matches.as_ref().unwrap();
+        let mut partial_matches = partial_matches_state.value()?.unwrap_or_default();
+
+        let mut output = Vec::new();
+        for event in self.take_events(timestamp)? {
+            let mut record = Record::from_values(event.values.as_slice(), event.timestamp);
+            let (next, nfa_output) = self.nfa.advance(partial_matches, &mut record);
+            partial_matches = next;
+
+            for partial_match in nfa_output.matched {
+                let mut pattern_match = PatternMatch::new(&self.nfa, &partial_match);
+                let records = self.function.process_match(&mut pattern_match);
+                output.extend(with_timestamp(records, pattern_match.last_timestamp()));
+            }
+            self.time_out(nfa_output.timed_out, event.timestamp, &mut output);
+        }
+
+        let (partial_matches, timed_out) = self.nfa.expire(partial_matches, timestamp);
+        self.time_out(timed_out, timestamp, &mut output);
+
+        if let Some(within) = self.nfa.within() {
+            for partial_match in &partial_matches {
+                let start = partial_match.start_timestamp().unwrap();
+                timer_service.register_event_time_timer(start + within);
+            }
+        }
+
+        let partial_matches_state = self.partial_matches.as_ref().unwrap();
+        if partial_matches.is_empty() {
+            partial_matches_state.clear();
+        } else {
+            partial_matches_state.update(partial_matches)?;
+        }
+
+        Ok(output)
+    }
+
+    fn time_out(&mut self, timed_out: Vec<PartialMatch>, timestamp: u64, output: &mut Vec<Record>) {
+        for partial_match in timed_out {
+            let mut pattern_match = PatternMatch::new(&self.nfa, &partial_match);
+            let records = self.function.process_timed_out_match(&mut pattern_match);
+            output.extend(with_timestamp(records, timestamp));
+        }
+    }
+}
+
+fn with_timestamp(records: Vec<Record>, timestamp: u64) -> impl Iterator<Item = Record> {
+    records.into_iter().map(move |mut record| {
+        if record.timestamp() == 0 {
+            record.set_timestamp(timestamp);
+        }
+        record
+    })
+}
+
+#[async_trait]
+impl<F> KeyedProcessFunction for CepProcessFunction<F>
+where
+    F: PatternProcessFunction,
+{
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let runtime_context = context.runtime_context();
+        self.buffered_events =
+            Some(runtime_context.list_state(&ListStateDescriptor::new(BUFFERED_EVENTS)));
+        self.partial_matches =
+            Some(runtime_context.value_state(&ValueStateDescriptor::new(PARTIAL_MATCHES)));
+        self.function.open(context)
+    }
+
+    async fn process_element(
+        &mut self,
+        mut record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let timestamp = record.timestamp();
+        if timestamp <= timer_service.current_watermark() {
+            debug!("drop the late event at {}", timestamp);
+            return Box::pin(MemoryStream::new(vec![]));
+        }
+
+        let event = BufferedEvent {
+            timestamp,
+            values: record.as_buffer().as_slice().to_vec(),
+        };
+        self.buffered_events
+            .as_ref()
+            .unwrap()
+            .add(event)
+            .expect("buffer the event error");
+        timer_service.register_event_time_timer(timestamp);
+
+        Box::pin(MemoryStream::new(vec![]))
+    }
+
+    async fn on_timer(
+        &mut self,
+        timestamp: u64,
+        _time_domain: TimeDomain,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let records = self
+            .fire(timestamp, timer_service)
+            .expect("match the pattern error");
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.function.close()
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        self.function.schema(input_schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl<F> NamedFunction for CepProcessFunction<F>
+where
+    F: PatternProcessFunction,
+{
+    fn name(&self) -> &str {
+        "CepProcessFunction"
+    }
+}
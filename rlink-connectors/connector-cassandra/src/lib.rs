@@ -0,0 +1,30 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod sink;
+
+pub use sink::output_format::CassandraOutputFormat;
+
+pub const CASSANDRA: &str = "cassandra";
+pub const NODES: &str = "nodes";
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+pub const LOCAL_DC: &str = "local.dc";
+
+pub const KEYSPACE: &str = "keyspace";
+pub const TABLE: &str = "table";
+/// The `column:field` pairs separated by `,`, all fields of the schema by default
+pub const COLUMNS: &str = "columns";
+pub const CONSISTENCY: &str = "consistency";
+pub const TTL: &str = "ttl";
+pub const MAX_IN_FLIGHT: &str = "max.in.flight";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_MAX_IN_FLIGHT: usize = 256;
+pub const SINK_MAX_RETRIES: usize = 3;
@@ -0,0 +1,185 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+use scylla::frame::types::Consistency;
+
+use crate::sink::statement::parse_consistency;
+use crate::{
+    CassandraOutputFormat, CASSANDRA, COLUMNS, CONSISTENCY, KEYSPACE, LOCAL_DC, MAX_IN_FLIGHT,
+    MAX_RETRIES, NODES, PASSWORD, TABLE, TTL, USERNAME,
+};
+
+pub struct CassandraOutputFormatBuilder {
+    nodes: Vec<String>,
+    keyspace: String,
+    table: String,
+    user: Option<(String, String)>,
+    local_dc: Option<String>,
+    columns: Vec<(String, String)>,
+    consistency: Option<Consistency>,
+    ttl: Option<Duration>,
+    max_in_flight: Option<usize>,
+    max_retries: Option<usize>,
+}
+
+impl CassandraOutputFormatBuilder {
+    /// The `nodes` are the contact points in the format of `host:port`
+    pub fn new(nodes: Vec<String>, keyspace: &str, table: &str) -> Self {
+        CassandraOutputFormatBuilder {
+            nodes,
+            keyspace: keyspace.to_string(),
+            table: table.to_string(),
+            user: None,
+            local_dc: None,
+            columns: vec![],
+            consistency: None,
+            ttl: None,
+            max_in_flight: None,
+            max_retries: None,
+        }
+    }
+
+    pub fn user(mut self, username: &str, password: &str) -> Self {
+        self.user = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Route the statements to the nodes of the `local_dc` only
+    pub fn local_dc(mut self, local_dc: &str) -> Self {
+        self.local_dc = Some(local_dc.to_string());
+        self
+    }
+
+    /// Bind the value of the `field` to the `column`, all fields are bound to the columns with
+    /// the same names if no column is given
+    pub fn column(mut self, column: &str, field: &str) -> Self {
+        self.columns.push((column.to_string(), field.to_string()));
+        self
+    }
+
+    /// The consistency level of the statements, `LOCAL_QUORUM` by default
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    /// Insert the rows with `USING TTL`
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// The max number of the statements executing concurrently
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Retry a failed statement up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> CassandraOutputFormat {
+        info!("build cassandra sink with: {:?}", &self);
+
+        let mut output_format = CassandraOutputFormat::new(self.nodes, self.keyspace, self.table);
+
+        if let Some((username, password)) = self.user {
+            output_format = output_format.user(username, password);
+        }
+        if let Some(local_dc) = self.local_dc {
+            output_format = output_format.local_dc(local_dc);
+        }
+        for (column, field) in self.columns {
+            output_format = output_format.column(column, field);
+        }
+        if let Some(consistency) = self.consistency {
+            output_format = output_format.consistency(consistency);
+        }
+        if let Some(ttl) = self.ttl {
+            output_format = output_format.ttl(ttl);
+        }
+        if let Some(max_in_flight) = self.max_in_flight {
+            output_format = output_format.max_in_flight(max_in_flight);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for CassandraOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CassandraOutputFormatBuilder")
+            .field("nodes", &self.nodes)
+            .field("keyspace", &self.keyspace)
+            .field("table", &self.table)
+            .field(
+                "username",
+                &self.user.as_ref().map(|(username, _)| username),
+            )
+            .field("local_dc", &self.local_dc)
+            .field("columns", &self.columns)
+            .field("consistency", &self.consistency)
+            .field("ttl", &self.ttl)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for CassandraOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let cassandra_properties = properties.to_sub_properties(CASSANDRA);
+        let nodes = cassandra_properties
+            .get_string(NODES)?
+            .split(',')
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+            .collect();
+        let keyspace = properties.get_string(KEYSPACE)?;
+        let table = properties.get_string(TABLE)?;
+
+        let mut builder =
+            CassandraOutputFormatBuilder::new(nodes, keyspace.as_str(), table.as_str());
+
+        if let Ok(username) = cassandra_properties.get_string(USERNAME) {
+            let password = cassandra_properties.get_string(PASSWORD)?;
+            builder = builder.user(username.as_str(), password.as_str());
+        }
+        if let Ok(local_dc) = cassandra_properties.get_string(LOCAL_DC) {
+            builder = builder.local_dc(local_dc.as_str());
+        }
+
+        if let Ok(columns) = properties.get_string(COLUMNS) {
+            for pair in columns.split(',').filter(|x| !x.trim().is_empty()) {
+                let (column, field) = pair
+                    .split_once(':')
+                    .ok_or_else(|| anyhow!("invalid column mapping `{}`", pair))?;
+                builder = builder.column(column.trim(), field.trim());
+            }
+        }
+        if let Ok(consistency) = properties.get_string(CONSISTENCY) {
+            builder = builder.consistency(parse_consistency(consistency.as_str())?);
+        }
+        if let Ok(ttl) = properties.get_duration(TTL) {
+            builder = builder.ttl(ttl);
+        }
+        if let Ok(max_in_flight) = properties.get_usize(MAX_IN_FLIGHT) {
+            builder = builder.max_in_flight(max_in_flight);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod builder;
+pub mod output_format;
+pub mod statement;
@@ -0,0 +1,255 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::flush_on_checkpoint;
+use scylla::frame::types::Consistency;
+use scylla::frame::value::SerializedValues;
+use scylla::prepared_statement::PreparedStatement;
+use scylla::transport::load_balancing::{
+    ChildLoadBalancingPolicy, DcAwareRoundRobinPolicy, RoundRobinPolicy, TokenAwarePolicy,
+};
+use scylla::{Session, SessionBuilder};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use crate::sink::statement::InsertStatement;
+use crate::{SINK_MAX_IN_FLIGHT, SINK_MAX_RETRIES};
+
+/// Write the records to the table by the prepared `INSERT` statement.
+///
+/// The statements are routed to the replicas of the partition key and executed concurrently,
+/// at most `max_in_flight` statements are executed at the same time. All statements are
+/// completed before the checkpoint completes, the inserts are idempotent so the records
+/// replayed after a restart overwrite the same rows.
+#[derive(NamedFunction)]
+pub struct CassandraOutputFormat {
+    nodes: Vec<String>,
+    keyspace: String,
+    table: String,
+    user: Option<(String, String)>,
+    local_dc: Option<String>,
+    columns: Vec<(String, String)>,
+    consistency: Consistency,
+    ttl: Option<Duration>,
+    max_in_flight: usize,
+    max_retries: usize,
+
+    statement: Option<InsertStatement>,
+    writer: Option<Arc<StatementWriter>>,
+    in_flight: Arc<Semaphore>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl CassandraOutputFormat {
+    pub fn new(nodes: Vec<String>, keyspace: String, table: String) -> Self {
+        CassandraOutputFormat {
+            nodes,
+            keyspace,
+            table,
+            user: None,
+            local_dc: None,
+            columns: vec![],
+            consistency: Consistency::LocalQuorum,
+            ttl: None,
+            max_in_flight: SINK_MAX_IN_FLIGHT,
+            max_retries: SINK_MAX_RETRIES,
+            statement: None,
+            writer: None,
+            in_flight: Arc::new(Semaphore::new(SINK_MAX_IN_FLIGHT)),
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn user(mut self, username: String, password: String) -> Self {
+        self.user = Some((username, password));
+        self
+    }
+
+    pub fn local_dc(mut self, local_dc: String) -> Self {
+        self.local_dc = Some(local_dc);
+        self
+    }
+
+    pub fn column(mut self, column: String, field: String) -> Self {
+        self.columns.push((column, field));
+        self
+    }
+
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self.in_flight = Arc::new(Semaphore::new(self.max_in_flight));
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    async fn connect(&self) -> anyhow::Result<Session> {
+        let child_policy: Box<dyn ChildLoadBalancingPolicy> = match &self.local_dc {
+            Some(local_dc) => Box::new(DcAwareRoundRobinPolicy::new(local_dc.clone())),
+            None => Box::new(RoundRobinPolicy::new()),
+        };
+
+        let mut builder = SessionBuilder::new()
+            .known_nodes(self.nodes.as_slice())
+            .load_balancing(Arc::new(TokenAwarePolicy::new(child_policy)));
+        if let Some((username, password)) = &self.user {
+            builder = builder.user(username, password);
+        }
+
+        let session = builder.build().await?;
+        Ok(session)
+    }
+
+    /// Wait for the in-flight statements, the first error is returned if any failed
+    async fn flush(&self) -> anyhow::Result<()> {
+        let permits = self
+            .in_flight
+            .acquire_many(self.max_in_flight as u32)
+            .await?;
+        drop(permits);
+
+        match self.error.lock().unwrap().as_ref() {
+            Some(e) => Err(anyhow!("{}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Execute the prepared statement with retries
+pub(crate) struct StatementWriter {
+    session: Session,
+    prepared: PreparedStatement,
+    max_retries: usize,
+    metrics: SinkMetrics,
+}
+
+impl StatementWriter {
+    async fn execute(&self, values: SerializedValues) -> anyhow::Result<()> {
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            match self.session.execute(&self.prepared, &values).await {
+                Ok(_) => {
+                    self.metrics.flushed(1, begin.elapsed());
+                    return Ok(());
+                }
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    self.metrics.retry();
+                    warn!(
+                        "execute statement error, retry({}/{}). {}",
+                        attempt, self.max_retries, e
+                    );
+                    tokio::time::sleep(Duration::from_millis(100 * (1 << attempt.min(6)))).await;
+                }
+                Err(e) => {
+                    self.metrics.failure();
+                    return Err(anyhow!(
+                        "execute statement error after {} retries. {}",
+                        self.max_retries,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for CassandraOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let statement = InsertStatement::new(
+            context.input_schema.clone().into(),
+            self.keyspace.as_str(),
+            self.table.as_str(),
+            self.columns.as_slice(),
+            self.ttl,
+        )?;
+
+        let session = self.connect().await?;
+        // the partition key of the prepared statement routes the statement to the replicas
+        let mut prepared = session
+            .prepare(statement.cql())
+            .await
+            .map_err(|e| anyhow!("prepare `{}` error. {}", statement.cql(), e))?;
+        prepared.set_consistency(self.consistency);
+
+        self.statement = Some(statement);
+        self.writer = Some(Arc::new(StatementWriter {
+            session,
+            prepared,
+            max_retries: self.max_retries,
+            metrics: SinkMetrics::new("Cassandra", context.task_id.to_tags()),
+        }));
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Some(e) = self.error.lock().unwrap().as_ref() {
+            panic!("cassandra sink error. {}", e);
+        }
+
+        let mut record = element.into_record();
+        let values = match self.statement.as_ref().unwrap().bind(&mut record) {
+            Ok(values) => values,
+            Err(e) => {
+                error!("bind record error, the record is discarded. {}", e);
+                return;
+            }
+        };
+
+        let permit = self.in_flight.clone().acquire_owned().await.unwrap();
+        let writer = self.writer.as_ref().unwrap().clone();
+        let error = self.error.clone();
+        tokio::spawn(async move {
+            if let Err(e) = writer.execute(values).await {
+                error!("{}", e);
+                error.lock().unwrap().get_or_insert(e.to_string());
+            }
+            drop(permit);
+        });
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.flush().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for CassandraOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// Wait for the in-flight statements on the checkpoint
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        flush_on_checkpoint(context, self.flush()).await;
+        None
+    }
+}
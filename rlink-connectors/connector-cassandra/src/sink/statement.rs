@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+use scylla::frame::types::Consistency;
+use scylla::frame::value::SerializedValues;
+
+/// Parse the consistency level, e.g. `local_quorum` or `LOCAL_QUORUM`
+pub fn parse_consistency(consistency: &str) -> anyhow::Result<Consistency> {
+    let consistency = match consistency.to_uppercase().as_str() {
+        "ANY" => Consistency::Any,
+        "ONE" => Consistency::One,
+        "TWO" => Consistency::Two,
+        "THREE" => Consistency::Three,
+        "QUORUM" => Consistency::Quorum,
+        "ALL" => Consistency::All,
+        "LOCAL_QUORUM" => Consistency::LocalQuorum,
+        "EACH_QUORUM" => Consistency::EachQuorum,
+        "LOCAL_ONE" => Consistency::LocalOne,
+        _ => return Err(anyhow!("unknown consistency level `{}`", consistency)),
+    };
+    Ok(consistency)
+}
+
+/// The `INSERT` statement binding the fields of the schema to the columns
+#[derive(Clone, Debug)]
+pub(crate) struct InsertStatement {
+    schema: Schema,
+    /// the indexes of the bound fields, in the order of the columns
+    fields: Vec<usize>,
+    cql: String,
+}
+
+impl InsertStatement {
+    /// Bind the `field` to the `column` of the `columns`, or all fields to the columns with
+    /// the same names if empty
+    pub fn new(
+        schema: Schema,
+        keyspace: &str,
+        table: &str,
+        columns: &[(String, String)],
+        ttl: Option<Duration>,
+    ) -> anyhow::Result<Self> {
+        let columns: Vec<(String, String)> = if columns.is_empty() {
+            schema
+                .fields()
+                .iter()
+                .map(|x| (x.name().to_string(), x.name().to_string()))
+                .collect()
+        } else {
+            columns.to_vec()
+        };
+
+        let mut fields = Vec::with_capacity(columns.len());
+        for (_column, field) in &columns {
+            let i = schema
+                .index_of(field.as_str())
+                .ok_or_else(|| anyhow!("field `{}` not found in the input schema", field))?;
+            fields.push(i);
+        }
+
+        let names: Vec<&str> = columns.iter().map(|(column, _)| column.as_str()).collect();
+        let markers: Vec<&str> = columns.iter().map(|_| "?").collect();
+        let mut cql = format!(
+            "INSERT INTO {}.{} ({}) VALUES ({})",
+            keyspace,
+            table,
+            names.join(", "),
+            markers.join(", ")
+        );
+        if let Some(ttl) = ttl {
+            cql.push_str(format!(" USING TTL {}", ttl.as_secs()).as_str());
+        }
+
+        Ok(InsertStatement {
+            schema,
+            fields,
+            cql,
+        })
+    }
+
+    pub fn cql(&self) -> &str {
+        self.cql.as_str()
+    }
+
+    /// The bound values of the record. The unsigned integers are bound to the wider signed
+    /// columns, `UInt64` to `bigint` and wraps over `i64::MAX`
+    pub fn bind(&self, record: &mut Record) -> anyhow::Result<SerializedValues> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        let mut values = SerializedValues::with_capacity(self.fields.len());
+        for i in &self.fields {
+            let i = *i;
+            match self.schema.field(i).data_type() {
+                DataType::Boolean => values.add_value(&reader.get_bool(i)?)?,
+                DataType::Int8 => values.add_value(&reader.get_i8(i)?)?,
+                DataType::UInt8 => values.add_value(&(reader.get_u8(i)? as i16))?,
+                DataType::Int16 => values.add_value(&reader.get_i16(i)?)?,
+                DataType::UInt16 => values.add_value(&(reader.get_u16(i)? as i32))?,
+                DataType::Int32 => values.add_value(&reader.get_i32(i)?)?,
+                DataType::UInt32 => values.add_value(&(reader.get_u32(i)? as i64))?,
+                DataType::Int64 => values.add_value(&reader.get_i64(i)?)?,
+                DataType::UInt64 => values.add_value(&(reader.get_u64(i)? as i64))?,
+                DataType::Float32 => values.add_value(&reader.get_f32(i)?)?,
+                DataType::Float64 => values.add_value(&reader.get_f64(i)?)?,
+                DataType::Binary => values.add_value(&reader.get_binary(i)?.to_vec())?,
+                DataType::String => values.add_value(&reader.get_str(i)?)?,
+            }
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+    use scylla::frame::types::Consistency;
+
+    use crate::sink::statement::{parse_consistency, InsertStatement};
+
+    #[test]
+    pub fn insert_statement_test() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+            Field::new("score", DataType::UInt32),
+        ]);
+
+        let statement = InsertStatement::new(schema.clone(), "ks", "users", &[], None).unwrap();
+        assert_eq!(
+            statement.cql(),
+            "INSERT INTO ks.users (id, name, score) VALUES (?, ?, ?)"
+        );
+
+        let columns = vec![
+            ("user_id".to_string(), "id".to_string()),
+            ("user_name".to_string(), "name".to_string()),
+        ];
+        let statement = InsertStatement::new(
+            schema.clone(),
+            "ks",
+            "users",
+            columns.as_slice(),
+            Some(Duration::from_secs(3600)),
+        )
+        .unwrap();
+        assert_eq!(
+            statement.cql(),
+            "INSERT INTO ks.users (user_id, user_name) VALUES (?, ?) USING TTL 3600"
+        );
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_i64(7).unwrap();
+        writer.set_str("rlink").unwrap();
+        writer.set_u32(10).unwrap();
+        assert_eq!(statement.bind(&mut record).unwrap().len(), 2);
+
+        let columns = vec![("uid".to_string(), "uid".to_string())];
+        assert!(InsertStatement::new(schema, "ks", "users", columns.as_slice(), None).is_err());
+
+        assert_eq!(
+            parse_consistency("local_quorum").unwrap(),
+            Consistency::LocalQuorum
+        );
+        assert!(parse_consistency("most").is_err());
+    }
+}
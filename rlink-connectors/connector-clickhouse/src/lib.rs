@@ -8,6 +8,23 @@ extern crate async_trait;
 extern crate anyhow;
 
 pub mod clickhouse_sink;
+pub mod sink;
+
+pub use sink::output_format::ClickhouseOutputFormat;
+
+pub const CLICKHOUSE: &str = "clickhouse";
+pub const URL: &str = "url";
+
+pub const TABLE: &str = "table";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 10000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 1000;
+pub const SINK_MAX_RETRIES: usize = 5;
 
 use chrono::{DateTime, NaiveDateTime, TimeZone};
 use chrono_tz::Asia;
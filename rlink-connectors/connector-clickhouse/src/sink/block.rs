@@ -0,0 +1,92 @@
+use clickhouse_rs::Block;
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+
+/// The values of a column, the `Boolean` is `UInt8` in ClickHouse
+enum Column {
+    UInt8(Vec<u8>),
+    Int8(Vec<i8>),
+    Int16(Vec<i16>),
+    UInt16(Vec<u16>),
+    Int32(Vec<i32>),
+    UInt32(Vec<u32>),
+    Int64(Vec<i64>),
+    UInt64(Vec<u64>),
+    Float32(Vec<f32>),
+    Float64(Vec<f64>),
+    String(Vec<String>),
+    Binary(Vec<Vec<u8>>),
+}
+
+impl Column {
+    fn with_capacity(data_type: &DataType, capacity: usize) -> Self {
+        match data_type {
+            DataType::Boolean | DataType::UInt8 => Column::UInt8(Vec::with_capacity(capacity)),
+            DataType::Int8 => Column::Int8(Vec::with_capacity(capacity)),
+            DataType::Int16 => Column::Int16(Vec::with_capacity(capacity)),
+            DataType::UInt16 => Column::UInt16(Vec::with_capacity(capacity)),
+            DataType::Int32 => Column::Int32(Vec::with_capacity(capacity)),
+            DataType::UInt32 => Column::UInt32(Vec::with_capacity(capacity)),
+            DataType::Int64 => Column::Int64(Vec::with_capacity(capacity)),
+            DataType::UInt64 => Column::UInt64(Vec::with_capacity(capacity)),
+            DataType::Float32 => Column::Float32(Vec::with_capacity(capacity)),
+            DataType::Float64 => Column::Float64(Vec::with_capacity(capacity)),
+            DataType::String => Column::String(Vec::with_capacity(capacity)),
+            DataType::Binary => Column::Binary(Vec::with_capacity(capacity)),
+        }
+    }
+}
+
+/// Convert the records into a columnar block, the column names are the field names of the
+/// `schema` and the column types of the table must be the same as the field types
+pub(crate) fn to_block(schema: &Schema, records: &mut [Record]) -> std::io::Result<Block> {
+    let mut columns: Vec<Column> = schema
+        .fields()
+        .iter()
+        .map(|x| Column::with_capacity(x.data_type(), records.len()))
+        .collect();
+
+    for record in records.iter_mut() {
+        let reader = record.as_reader(schema.as_type_ids());
+        for (i, (column, field)) in columns.iter_mut().zip(schema.fields()).enumerate() {
+            match column {
+                Column::UInt8(values) => match field.data_type() {
+                    DataType::Boolean => values.push(reader.get_bool(i)? as u8),
+                    _ => values.push(reader.get_u8(i)?),
+                },
+                Column::Int8(values) => values.push(reader.get_i8(i)?),
+                Column::Int16(values) => values.push(reader.get_i16(i)?),
+                Column::UInt16(values) => values.push(reader.get_u16(i)?),
+                Column::Int32(values) => values.push(reader.get_i32(i)?),
+                Column::UInt32(values) => values.push(reader.get_u32(i)?),
+                Column::Int64(values) => values.push(reader.get_i64(i)?),
+                Column::UInt64(values) => values.push(reader.get_u64(i)?),
+                Column::Float32(values) => values.push(reader.get_f32(i)?),
+                Column::Float64(values) => values.push(reader.get_f64(i)?),
+                Column::String(values) => values.push(reader.get_str(i)?.to_string()),
+                Column::Binary(values) => values.push(reader.get_binary(i)?.to_vec()),
+            }
+        }
+    }
+
+    let mut block = Block::new();
+    for (column, field) in columns.into_iter().zip(schema.fields()) {
+        let name = field.name();
+        block = match column {
+            Column::UInt8(values) => block.column(name, values),
+            Column::Int8(values) => block.column(name, values),
+            Column::Int16(values) => block.column(name, values),
+            Column::UInt16(values) => block.column(name, values),
+            Column::Int32(values) => block.column(name, values),
+            Column::UInt32(values) => block.column(name, values),
+            Column::Int64(values) => block.column(name, values),
+            Column::UInt64(values) => block.column(name, values),
+            Column::Float32(values) => block.column(name, values),
+            Column::Float64(values) => block.column(name, values),
+            Column::String(values) => block.column(name, values),
+            Column::Binary(values) => block.column(name, values),
+        };
+    }
+
+    Ok(block)
+}
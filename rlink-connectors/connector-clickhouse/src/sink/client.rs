@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use clickhouse_rs::{Options, Pool};
+use reqwest::Url;
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+
+use crate::sink::block::to_block;
+use crate::sink::error::{parse_exception_code, InsertError};
+use crate::sink::row_binary::to_row_binary;
+
+/// Insert over the HTTP interface in the `RowBinary` format
+pub(crate) struct HttpClient {
+    client: reqwest::Client,
+    url: Url,
+    user: Option<(String, Option<String>)>,
+}
+
+impl HttpClient {
+    fn new(url: &str) -> anyhow::Result<Self> {
+        let mut url = Url::parse(url)?;
+
+        // the credentials are sent by the basic authentication
+        let user = if url.username().is_empty() {
+            None
+        } else {
+            Some((
+                url.username().to_string(),
+                url.password().map(|x| x.to_string()),
+            ))
+        };
+        url.set_username("")
+            .map_err(|_e| anyhow!("invalid clickhouse url"))?;
+        url.set_password(None)
+            .map_err(|_e| anyhow!("invalid clickhouse url"))?;
+
+        Ok(HttpClient {
+            client: reqwest::Client::new(),
+            url,
+            user,
+        })
+    }
+
+    async fn insert(&self, table: &str, body: Vec<u8>) -> Result<(), InsertError> {
+        let mut url = self.url.clone();
+        url.query_pairs_mut().append_pair(
+            "query",
+            format!("INSERT INTO {} FORMAT RowBinary", table).as_str(),
+        );
+
+        let mut request = self.client.post(url).body(body);
+        if let Some((username, password)) = &self.user {
+            request = request.basic_auth(username, password.as_ref());
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let header_code = response
+            .headers()
+            .get("X-ClickHouse-Exception-Code")
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.parse().ok());
+        let message = response.text().await.unwrap_or_default();
+        match header_code.or_else(|| parse_exception_code(message.as_str())) {
+            Some(code) => Err(InsertError::Server { code, message }),
+            // the error of the proxy or the load balancer
+            None if status.is_server_error() => Err(InsertError::Connection(format!(
+                "status {}. {}",
+                status.as_u16(),
+                message
+            ))),
+            None => Err(InsertError::Client(format!(
+                "status {}. {}",
+                status.as_u16(),
+                message
+            ))),
+        }
+    }
+}
+
+/// The client of the native TCP protocol(`tcp://`) or the HTTP interface(`http(s)://`)
+pub(crate) enum InsertClient {
+    Native(Pool),
+    Http(HttpClient),
+}
+
+impl InsertClient {
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(InsertClient::Http(HttpClient::new(url)?))
+        } else {
+            let options = Options::from_str(url)?;
+            Ok(InsertClient::Native(Pool::new(options)))
+        }
+    }
+
+    pub async fn insert(
+        &self,
+        table: &str,
+        schema: &Schema,
+        records: &mut [Record],
+    ) -> Result<(), InsertError> {
+        match self {
+            InsertClient::Native(pool) => {
+                let block = to_block(schema, records)?;
+                let mut client = pool.get_handle().await?;
+                client.insert(table, block).await?;
+                Ok(())
+            }
+            InsertClient::Http(client) => {
+                let mut body = Vec::with_capacity(records.iter().map(|x| x.len()).sum());
+                to_row_binary(schema, records, &mut body)?;
+                client.insert(table, body).await
+            }
+        }
+    }
+}
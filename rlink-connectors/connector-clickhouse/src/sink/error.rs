@@ -0,0 +1,125 @@
+use std::fmt::{Display, Formatter};
+
+/// Error codes of the server which are transient, mostly raised by the distributed table or
+/// the replicas, see `src/Common/ErrorCodes.cpp` of ClickHouse
+const RETRYABLE_CODES: [u32; 16] = [
+    3,   // UNEXPECTED_END_OF_FILE
+    32,  // ATTEMPT_TO_READ_AFTER_EOF
+    159, // TIMEOUT_EXCEEDED
+    164, // READONLY
+    202, // TOO_MANY_SIMULTANEOUS_QUERIES
+    203, // NO_FREE_CONNECTION
+    209, // SOCKET_TIMEOUT
+    210, // NETWORK_ERROR
+    236, // ABORTED
+    242, // TABLE_IS_READ_ONLY
+    252, // TOO_MANY_PARTS
+    279, // ALL_CONNECTION_TRIES_FAILED
+    285, // TOO_FEW_LIVE_REPLICAS
+    319, // UNKNOWN_STATUS_OF_INSERT
+    425, // SYSTEM_ERROR
+    999, // KEEPER_EXCEPTION
+];
+
+#[derive(Debug)]
+pub enum InsertError {
+    /// The exception of the server
+    Server { code: u32, message: String },
+    /// The connection is broken or not established
+    Connection(String),
+    /// The block or the request is invalid, e.g. the type mismatch
+    Client(String),
+}
+
+impl InsertError {
+    /// Whether the insert may succeed on retry
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            InsertError::Server { code, .. } => RETRYABLE_CODES.contains(code),
+            InsertError::Connection(_) => true,
+            InsertError::Client(_) => false,
+        }
+    }
+}
+
+impl Display for InsertError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InsertError::Server { code, message } => {
+                write!(f, "server exception(code: {}). {}", code, message)
+            }
+            InsertError::Connection(message) => write!(f, "connection error. {}", message),
+            InsertError::Client(message) => write!(f, "client error. {}", message),
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+impl From<clickhouse_rs::errors::Error> for InsertError {
+    fn from(e: clickhouse_rs::errors::Error) -> Self {
+        use clickhouse_rs::errors::Error;
+
+        match e {
+            Error::Server(e) => InsertError::Server {
+                code: e.code,
+                message: e.message,
+            },
+            Error::Connection(_) | Error::Io(_) => InsertError::Connection(e.to_string()),
+            e => InsertError::Client(e.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for InsertError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_connect() || e.is_timeout() || e.is_request() {
+            InsertError::Connection(e.to_string())
+        } else {
+            InsertError::Client(e.to_string())
+        }
+    }
+}
+
+impl From<std::io::Error> for InsertError {
+    fn from(e: std::io::Error) -> Self {
+        InsertError::Client(format!("read record error. {}", e))
+    }
+}
+
+/// Parse the code of the exception in the http response, e.g. `Code: 60. DB::Exception: ...`
+pub(crate) fn parse_exception_code(message: &str) -> Option<u32> {
+    let code = message.trim_start().strip_prefix("Code:")?;
+    let end = code.find('.').unwrap_or(code.len());
+    code[..end].trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sink::error::{parse_exception_code, InsertError};
+
+    #[test]
+    pub fn retryable_test() {
+        assert_eq!(
+            parse_exception_code("Code: 252. DB::Exception: Too many parts (300)."),
+            Some(252)
+        );
+        assert_eq!(parse_exception_code("Unknown error"), None);
+
+        let e = InsertError::Server {
+            code: 252,
+            message: "".to_string(),
+        };
+        assert!(e.is_retryable());
+
+        // UNKNOWN_TABLE
+        let e = InsertError::Server {
+            code: 60,
+            message: "".to_string(),
+        };
+        assert!(!e.is_retryable());
+
+        assert!(InsertError::Connection("".to_string()).is_retryable());
+        assert!(!InsertError::Client("".to_string()).is_retryable());
+    }
+}
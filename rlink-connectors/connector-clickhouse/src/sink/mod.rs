@@ -0,0 +1,8 @@
+pub mod builder;
+pub mod error;
+pub mod output_format;
+
+pub(crate) mod block;
+pub(crate) mod client;
+pub(crate) mod row_binary;
+pub(crate) mod writer;
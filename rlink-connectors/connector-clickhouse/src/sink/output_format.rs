@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::{Element, FnSchema};
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::metrics::Tag;
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
+
+use crate::sink::client::InsertClient;
+use crate::sink::writer::ClickhouseWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// Insert the records into the `table`, the columns are the field names of the input schema.
+///
+/// The `url` is the native protocol(`tcp://`) or the HTTP interface(`http(s)://`), the tasks
+/// are spread over the comma separated urls.
+#[derive(NamedFunction)]
+pub struct ClickhouseOutputFormat {
+    url: String,
+    table: String,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+
+    writer: Option<BatchWriterHandle>,
+}
+
+impl ClickhouseOutputFormat {
+    pub fn new(url: String, table: String, buffer_size: usize) -> Self {
+        ClickhouseOutputFormat {
+            url,
+            table,
+            buffer_size,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            writer: None,
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[async_trait]
+impl OutputFormat for ClickhouseOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let urls: Vec<&str> = self.url.split(',').map(|x| x.trim()).collect();
+        let url = urls[context.task_id.task_number() as usize % urls.len()];
+        let client = InsertClient::new(url)?;
+        let schema: Schema = context.input_schema.clone().into();
+
+        let mut tags = context.task_id.to_tags();
+        tags.push(Tag::new("table", self.table.as_str()));
+
+        let writer = ClickhouseWriter::new(client, self.table.clone(), schema, self.max_retries);
+        let writer = BatchWriterThread::new("Clickhouse", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), tags, self.buffer_size);
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write clickhouse record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::Empty
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for ClickhouseOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered rows are inserted on the checkpoint
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
+        None
+    }
+}
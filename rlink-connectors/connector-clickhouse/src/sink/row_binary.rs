@@ -0,0 +1,81 @@
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+
+fn put_leb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn put_bytes(buf: &mut Vec<u8>, value: &[u8]) {
+    put_leb128(buf, value.len() as u64);
+    buf.extend_from_slice(value);
+}
+
+/// Encode the records in the `RowBinary` format, the numbers are little endian and the strings
+/// are prefixed by the LEB128 length. The column types of the table must be the same as the
+/// field types of the `schema`.
+pub(crate) fn to_row_binary(
+    schema: &Schema,
+    records: &mut [Record],
+    buf: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    for record in records.iter_mut() {
+        let reader = record.as_reader(schema.as_type_ids());
+        for (i, field) in schema.fields().iter().enumerate() {
+            match field.data_type() {
+                DataType::Boolean => buf.push(reader.get_bool(i)? as u8),
+                DataType::Int8 => buf.extend_from_slice(&reader.get_i8(i)?.to_le_bytes()),
+                DataType::UInt8 => buf.push(reader.get_u8(i)?),
+                DataType::Int16 => buf.extend_from_slice(&reader.get_i16(i)?.to_le_bytes()),
+                DataType::UInt16 => buf.extend_from_slice(&reader.get_u16(i)?.to_le_bytes()),
+                DataType::Int32 => buf.extend_from_slice(&reader.get_i32(i)?.to_le_bytes()),
+                DataType::UInt32 => buf.extend_from_slice(&reader.get_u32(i)?.to_le_bytes()),
+                DataType::Int64 => buf.extend_from_slice(&reader.get_i64(i)?.to_le_bytes()),
+                DataType::UInt64 => buf.extend_from_slice(&reader.get_u64(i)?.to_le_bytes()),
+                DataType::Float32 => buf.extend_from_slice(&reader.get_f32(i)?.to_le_bytes()),
+                DataType::Float64 => buf.extend_from_slice(&reader.get_f64(i)?.to_le_bytes()),
+                DataType::String => put_bytes(buf, reader.get_str(i)?.as_bytes()),
+                DataType::Binary => put_bytes(buf, reader.get_binary(i)?),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::row_binary::{put_leb128, to_row_binary};
+
+    #[test]
+    pub fn row_binary_test() {
+        let mut buf = Vec::new();
+        put_leb128(&mut buf, 300);
+        assert_eq!(buf, vec![0xac, 0x02]);
+
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::UInt32),
+            Field::new("name", DataType::String),
+            Field::new("valid", DataType::Boolean),
+        ]);
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_u32(258).unwrap();
+        writer.set_str("ab").unwrap();
+        writer.set_bool(true).unwrap();
+
+        let mut buf = Vec::new();
+        to_row_binary(&schema, &mut [record], &mut buf).unwrap();
+        assert_eq!(buf, vec![0x02, 0x01, 0x00, 0x00, 0x02, b'a', b'b', 0x01]);
+    }
+}
@@ -0,0 +1,83 @@
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use tokio::time::Instant;
+
+use crate::sink::client::InsertClient;
+
+pub(crate) struct ClickhouseWriter {
+    client: InsertClient,
+    table: String,
+    schema: Schema,
+    max_retries: usize,
+
+    rows: Vec<Record>,
+}
+
+impl ClickhouseWriter {
+    pub fn new(client: InsertClient, table: String, schema: Schema, max_retries: usize) -> Self {
+        ClickhouseWriter {
+            client,
+            table,
+            schema,
+            max_retries,
+            rows: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchWriter for ClickhouseWriter {
+    fn push(&mut self, record: Record) -> anyhow::Result<()> {
+        self.rows.push(record);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Insert the buffered rows as a block, the transient errors of the server, e.g. the
+    /// unavailable replicas of the distributed table, are retried with the backoff
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            match self
+                .client
+                .insert(self.table.as_str(), &self.schema, &mut self.rows)
+                .await
+            {
+                Ok(()) => {
+                    metrics.flushed(self.rows.len() as u64, begin.elapsed());
+                    self.rows.clear();
+                    return Ok(());
+                }
+                Err(e) if e.is_retryable() && attempt < self.max_retries => {
+                    attempt += 1;
+                    metrics.retry();
+                    warn!(
+                        "insert {} rows into `{}` error, retry({}/{}). {}",
+                        self.rows.len(),
+                        self.table,
+                        attempt,
+                        self.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(backoff(attempt)).await;
+                }
+                Err(e) => {
+                    metrics.failure();
+                    return Err(anyhow!(
+                        "insert {} rows into `{}` error after {} retries. {}",
+                        self.rows.len(),
+                        self.table,
+                        attempt,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod writer;
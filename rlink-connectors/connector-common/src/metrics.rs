@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use rlink::metrics::{register_counter, register_histogram, Counter, Histogram, Tag};
+
+/// Metrics of the batching writer of a sink, named by the prefix of the connector, e.g.
+/// `Mongodb.Sink.Records`, and tagged by the task
+#[derive(Clone)]
+pub struct SinkMetrics {
+    /// records written successfully
+    records: Counter,
+    /// records discarded, e.g. the malformed or the duplicate ones
+    dropped: Counter,
+    /// failed batches retried
+    retries: Counter,
+    /// batches given up after the retries
+    failures: Counter,
+    /// millis of a batch
+    latency: Histogram,
+}
+
+impl SinkMetrics {
+    pub fn new(prefix: &str, tags: Vec<Tag>) -> Self {
+        SinkMetrics {
+            records: register_counter(format!("{}.Sink.Records", prefix), tags.clone()),
+            dropped: register_counter(format!("{}.Sink.Dropped", prefix), tags.clone()),
+            retries: register_counter(format!("{}.Sink.Retries", prefix), tags.clone()),
+            failures: register_counter(format!("{}.Sink.Failures", prefix), tags.clone()),
+            latency: register_histogram(format!("{}.Sink.Latency", prefix), tags),
+        }
+    }
+
+    pub fn flushed(&self, records: u64, latency: Duration) {
+        self.records.increment(records);
+        self.latency.record(latency.as_secs_f64() * 1000f64);
+    }
+
+    pub fn dropped(&self, records: u64) {
+        self.dropped.increment(records);
+    }
+
+    pub fn retry(&self) {
+        self.retries.increment(1);
+    }
+
+    pub fn failure(&self) {
+        self.failures.increment(1);
+    }
+}
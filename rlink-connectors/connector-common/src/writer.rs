@@ -0,0 +1,210 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rlink::channel::named_channel;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::checkpoint::FunctionSnapshotContext;
+use rlink::core::element::Record;
+use rlink::metrics::Tag;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::metrics::SinkMetrics;
+
+enum WriterCommand {
+    Write(Record),
+    /// Flush the buffered records and report the result, sent on checkpoint
+    Flush(oneshot::Sender<anyhow::Result<()>>),
+}
+
+/// The batch of a sink, the records are buffered by `push` and written by `flush` once the
+/// batch is full, the batch interval elapses or a checkpoint is triggered
+#[async_trait]
+pub trait BatchWriter: Send + 'static {
+    /// Connect to the external system, it's called in the writer task before the first record
+    async fn open(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Buffer the record, a malformed record is discarded with the returned error
+    fn push(&mut self, record: Record) -> anyhow::Result<()>;
+
+    /// The number of the buffered records
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write and clear the buffered records, the retryable errors are retried by the writer
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()>;
+}
+
+/// Run the `BatchWriter` in a task fed by a channel, so the sink isn't blocked by the writes.
+///
+/// The task exits on the first failed flush, then the writes and the flushes of the
+/// `BatchWriterHandle` fail, which fails the sink and restarts the job from the last checkpoint
+pub struct BatchWriterThread<W> {
+    writer: W,
+    metric_prefix: String,
+    batch_size: usize,
+    batch_interval: Duration,
+}
+
+impl<W> BatchWriterThread<W>
+where
+    W: BatchWriter,
+{
+    /// `metric_prefix` names the `SinkMetrics` of the writer, e.g. `Mongodb`
+    pub fn new(metric_prefix: &str, writer: W) -> Self {
+        BatchWriterThread {
+            writer,
+            metric_prefix: metric_prefix.to_string(),
+            batch_size: 1000,
+            batch_interval: Duration::from_secs(1),
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    /// Spawn the writer task, the channel to it is named by `name` and `tags`
+    pub fn spawn(self, name: &str, tags: Vec<Tag>, buffer_size: usize) -> BatchWriterHandle {
+        let (sender, receiver) = named_channel(name, tags.clone(), buffer_size);
+        let metrics = SinkMetrics::new(self.metric_prefix.as_str(), tags);
+        tokio::spawn(self.run(receiver, metrics));
+        BatchWriterHandle { sender }
+    }
+
+    async fn run(mut self, mut receiver: ChannelReceiver<WriterCommand>, metrics: SinkMetrics) {
+        if let Err(e) = self.writer.open().await {
+            error!("open {} writer error. {}", self.metric_prefix, e);
+            return;
+        }
+
+        let mut deadline = Instant::now() + self.batch_interval;
+        loop {
+            let command = match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(command)) => Some(command),
+                Ok(None) => break,
+                Err(_elapsed) => None,
+            };
+
+            match command {
+                Some(WriterCommand::Write(record)) => {
+                    if let Err(e) = self.writer.push(record) {
+                        metrics.dropped(1);
+                        error!("read record error, the record is discarded. {}", e);
+                    }
+                    if self.writer.len() < self.batch_size {
+                        continue;
+                    }
+                }
+                Some(WriterCommand::Flush(sender)) => {
+                    let result = self.flush(&metrics).await;
+                    let failed = result.is_err();
+                    sender.send(result).ok();
+                    if failed {
+                        return;
+                    }
+                    deadline = Instant::now() + self.batch_interval;
+                    continue;
+                }
+                None => {}
+            }
+
+            if let Err(e) = self.flush(&metrics).await {
+                error!("{}", e);
+                return;
+            }
+            deadline = Instant::now() + self.batch_interval;
+        }
+
+        if let Err(e) = self.flush(&metrics).await {
+            error!("{}", e);
+        }
+        info!("{} writer channel closed", self.metric_prefix);
+    }
+
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        if self.writer.is_empty() {
+            return Ok(());
+        }
+        self.writer.flush(metrics).await
+    }
+}
+
+/// The sender side of a `BatchWriterThread`
+#[derive(Clone)]
+pub struct BatchWriterHandle {
+    sender: ChannelSender<WriterCommand>,
+}
+
+impl BatchWriterHandle {
+    pub async fn write(&self, record: Record) -> anyhow::Result<()> {
+        self.sender
+            .send(WriterCommand::Write(record))
+            .await
+            .map_err(|_e| anyhow!("the writer is closed"))
+    }
+
+    /// Flush the buffered records and wait for the result
+    pub async fn flush(&self) -> anyhow::Result<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(WriterCommand::Flush(sender))
+            .await
+            .map_err(|_e| anyhow!("the writer is closed"))?;
+        receiver
+            .await
+            .map_err(|_e| anyhow!("the writer is closed"))?
+    }
+
+    /// Flush the buffered records in the `snapshot_state` of the sink, see `flush_on_checkpoint`
+    pub async fn checkpoint(&self, context: &FunctionSnapshotContext) {
+        flush_on_checkpoint(context, self.flush()).await
+    }
+}
+
+/// Complete the writes of the sink before the checkpoint completes, so the records written
+/// before the barrier are never lost on restart. The failed `flush` panics to fail the
+/// checkpoint, then the job restarts from the last completed checkpoint and replays the records
+/// after it, the sink decides whether the replayed records are written twice
+pub async fn flush_on_checkpoint<F>(context: &FunctionSnapshotContext, flush: F)
+where
+    F: Future<Output = anyhow::Result<()>>,
+{
+    if let Err(e) = flush.await {
+        panic!(
+            "flush on checkpoint({:?}) error. {}",
+            context.checkpoint_id, e
+        );
+    }
+}
+
+/// The backoff before the `attempt`th retry, it doubles up to 6.4s
+pub fn backoff(attempt: usize) -> Duration {
+    Duration::from_millis(100 * (1 << attempt.min(6)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::writer::backoff;
+
+    #[test]
+    pub fn backoff_test() {
+        assert_eq!(backoff(1), Duration::from_millis(200));
+        assert_eq!(backoff(6), Duration::from_millis(6400));
+        assert_eq!(backoff(10), Duration::from_millis(6400));
+    }
+}
@@ -0,0 +1,33 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod sink;
+
+pub use sink::builder::DeltaOutputFormatBuilder;
+pub use sink::output_format::DeltaOutputFormat;
+
+pub const DELTA: &str = "delta";
+/// The location of the table, e.g. `s3://bucket/path/table` or `file:///path/table`
+pub const TABLE_URI: &str = "table.uri";
+/// The prefix of the storage options, e.g. `storage.AWS_REGION`
+pub const STORAGE: &str = "storage";
+/// The app id of the table transactions, the application id by default
+pub const APP_ID: &str = "app.id";
+/// Create the table by the record schema if not exists
+pub const CREATE_TABLE: &str = "create.table";
+/// The partition columns of the created table, separated by `,`
+pub const PARTITION_COLUMNS: &str = "partition.columns";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_BATCH_SIZE: usize = 4096;
+pub const SINK_MAX_RETRIES: usize = 15;
@@ -0,0 +1,43 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SINK_ROWS: &str = "Delta.Sink.Rows";
+pub const SINK_DATA_FILES: &str = "Delta.Sink.DataFiles";
+pub const SINK_COMMITS: &str = "Delta.Sink.Commits";
+pub const SINK_DUPLICATES: &str = "Delta.Sink.Duplicates";
+
+/// Metrics of the sink, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SinkMetrics {
+    /// rows written to the data files
+    rows: Counter,
+    /// data files added to the table
+    data_files: Counter,
+    /// transactions committed to the table
+    commits: Counter,
+    /// commits skipped since the transaction version is already committed
+    duplicates: Counter,
+}
+
+impl SinkMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SinkMetrics {
+            rows: register_counter(SINK_ROWS, tags.clone()),
+            data_files: register_counter(SINK_DATA_FILES, tags.clone()),
+            commits: register_counter(SINK_COMMITS, tags.clone()),
+            duplicates: register_counter(SINK_DUPLICATES, tags),
+        }
+    }
+
+    pub fn written(&self) {
+        self.rows.increment(1);
+    }
+
+    pub fn committed(&self, data_files: usize) {
+        self.commits.increment(1);
+        self.data_files.increment(data_files as u64);
+    }
+
+    pub fn duplicate(&self) {
+        self.duplicates.increment(1);
+    }
+}
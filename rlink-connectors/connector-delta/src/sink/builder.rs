@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    DeltaOutputFormat, APP_ID, BATCH_SIZE, CREATE_TABLE, DELTA, MAX_RETRIES, PARTITION_COLUMNS,
+    STORAGE, TABLE_URI,
+};
+
+pub struct DeltaOutputFormatBuilder {
+    table_uri: String,
+    storage_options: HashMap<String, String>,
+    app_id: Option<String>,
+    create_table: bool,
+    partition_columns: Vec<String>,
+    batch_size: Option<usize>,
+    max_retries: Option<usize>,
+}
+
+impl DeltaOutputFormatBuilder {
+    /// The `table_uri` is the location of the table, e.g. `s3://bucket/path/table`
+    pub fn new(table_uri: &str) -> Self {
+        DeltaOutputFormatBuilder {
+            table_uri: table_uri.to_string(),
+            storage_options: HashMap::new(),
+            app_id: None,
+            create_table: false,
+            partition_columns: vec![],
+            batch_size: None,
+            max_retries: None,
+        }
+    }
+
+    /// The option of the object store, e.g. `AWS_REGION` or `AWS_S3_ALLOW_UNSAFE_RENAME`
+    pub fn storage_option(mut self, key: &str, value: &str) -> Self {
+        self.storage_options
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Identify the transactions of the job, must be stable across the restarts. The
+    /// application id by default
+    pub fn app_id(mut self, app_id: &str) -> Self {
+        self.app_id = Some(app_id.to_string());
+        self
+    }
+
+    /// Create the table by the record schema if not exists
+    pub fn create_table(mut self, create_table: bool) -> Self {
+        self.create_table = create_table;
+        self
+    }
+
+    /// Partition the created table by the record fields
+    pub fn partition_columns(mut self, partition_columns: Vec<String>) -> Self {
+        self.partition_columns = partition_columns;
+        self
+    }
+
+    /// Write the buffered records to the data files once `batch_size` rows are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Retry the conflicted commit up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> DeltaOutputFormat {
+        info!("build delta sink with: {:?}", &self);
+
+        let mut output_format = DeltaOutputFormat::new(self.table_uri)
+            .create_table(self.create_table)
+            .partition_columns(self.partition_columns);
+
+        for (key, value) in self.storage_options {
+            output_format = output_format.storage_option(key, value);
+        }
+        if let Some(app_id) = self.app_id {
+            output_format = output_format.app_id(app_id);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for DeltaOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeltaOutputFormatBuilder")
+            .field("table_uri", &self.table_uri)
+            .field(
+                "storage_options",
+                &self.storage_options.keys().collect::<Vec<&String>>(),
+            )
+            .field("app_id", &self.app_id)
+            .field("create_table", &self.create_table)
+            .field("partition_columns", &self.partition_columns)
+            .field("batch_size", &self.batch_size)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for DeltaOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let delta_properties = properties.to_sub_properties(DELTA);
+        let table_uri = delta_properties.get_string(TABLE_URI)?;
+
+        let mut builder = DeltaOutputFormatBuilder::new(table_uri.as_str());
+
+        for (key, value) in delta_properties.to_sub_properties(STORAGE).as_map() {
+            builder = builder.storage_option(key.as_str(), value.as_str());
+        }
+        if let Ok(app_id) = delta_properties.get_string(APP_ID) {
+            builder = builder.app_id(app_id.as_str());
+        }
+        if let Ok(create_table) = delta_properties.get_bool(CREATE_TABLE) {
+            builder = builder.create_table(create_table);
+        }
+        if let Ok(partition_columns) = delta_properties.get_string(PARTITION_COLUMNS) {
+            let partition_columns = partition_columns
+                .split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect();
+            builder = builder.partition_columns(partition_columns);
+        }
+        if let Ok(batch_size) = delta_properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(max_retries) = delta_properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod builder;
+pub mod output_format;
+pub mod schema;
+
+pub(crate) mod table;
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use deltalake::kernel::Add;
+use deltalake::writer::{DeltaWriter, RecordBatchWriter};
+use deltalake::DeltaTable;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::utils::date_time::current_timestamp_millis;
+
+use crate::metrics::SinkMetrics;
+use crate::sink::schema::RecordBatchBuilder;
+use crate::sink::table::{self, load_or_create};
+use crate::{SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// The data files written before a checkpoint, committed in the transaction of the version
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct DeltaSinkState {
+    version: i64,
+    adds: Vec<Add>,
+}
+
+/// Append the records to a delta table as the parquet files.
+///
+/// The data files written before a checkpoint are committed in a transaction when the
+/// barrier of the next checkpoint arrives, or on restore from the checkpoint. The
+/// transaction is identified by the app id of the task and the checkpoint id as the version,
+/// so the transactions replayed after a restart are not committed twice. The app id must be
+/// stable across the restarts, it's the application id by default.
+#[derive(NamedFunction)]
+pub struct DeltaOutputFormat {
+    table_uri: String,
+    storage_options: HashMap<String, String>,
+    app_id: Option<String>,
+    create_table: bool,
+    partition_columns: Vec<String>,
+    batch_size: usize,
+    max_retries: usize,
+
+    txn_app_id: String,
+    table: Option<DeltaTable>,
+    writer: Option<RecordBatchWriter>,
+    batch_builder: Option<RecordBatchBuilder>,
+    committable: Option<DeltaSinkState>,
+    metrics: Option<SinkMetrics>,
+}
+
+impl DeltaOutputFormat {
+    pub fn new(table_uri: String) -> Self {
+        DeltaOutputFormat {
+            table_uri,
+            storage_options: HashMap::new(),
+            app_id: None,
+            create_table: false,
+            partition_columns: vec![],
+            batch_size: SINK_BATCH_SIZE,
+            max_retries: SINK_MAX_RETRIES,
+            txn_app_id: String::new(),
+            table: None,
+            writer: None,
+            batch_builder: None,
+            committable: None,
+            metrics: None,
+        }
+    }
+
+    /// The option of the object store, e.g. `AWS_REGION` or `AWS_S3_ALLOW_UNSAFE_RENAME`
+    pub fn storage_option(mut self, key: String, value: String) -> Self {
+        self.storage_options.insert(key, value);
+        self
+    }
+
+    pub fn app_id(mut self, app_id: String) -> Self {
+        self.app_id = Some(app_id);
+        self
+    }
+
+    pub fn create_table(mut self, create_table: bool) -> Self {
+        self.create_table = create_table;
+        self
+    }
+
+    pub fn partition_columns(mut self, partition_columns: Vec<String>) -> Self {
+        self.partition_columns = partition_columns;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Retry the conflicted commit up to `max_retries` times
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Write the buffered records to the data files
+    async fn write_batch(&mut self) -> anyhow::Result<()> {
+        let batch_builder = self.batch_builder.as_mut().unwrap();
+        if batch_builder.is_empty() {
+            return Ok(());
+        }
+
+        let batch = batch_builder.take()?;
+        self.writer.as_mut().unwrap().write(batch).await?;
+        Ok(())
+    }
+
+    /// Close the data files, the data files are not committed
+    async fn flush(&mut self) -> anyhow::Result<Vec<Add>> {
+        self.write_batch().await?;
+        let adds = self.writer.as_mut().unwrap().flush().await?;
+        Ok(adds)
+    }
+
+    async fn commit(&mut self, state: Option<DeltaSinkState>) -> anyhow::Result<()> {
+        if let Some(state) = state {
+            table::commit(
+                self.table.as_mut().unwrap(),
+                self.txn_app_id.as_str(),
+                state.version,
+                state.adds,
+                self.max_retries,
+                self.metrics.as_ref().unwrap(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputFormat for DeltaOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let schema: Schema = context.input_schema.clone().into();
+
+        let table = load_or_create(
+            self.table_uri.as_str(),
+            &self.storage_options,
+            &schema,
+            self.partition_columns.as_slice(),
+            self.create_table,
+        )
+        .await?;
+        let writer = RecordBatchWriter::for_table(&table)
+            .map_err(|e| anyhow!("create delta writer error. {}", e))?;
+
+        self.txn_app_id = format!(
+            "{}-{}-{}",
+            self.app_id.as_ref().unwrap_or(&context.application_id),
+            context.task_id.job_id().0,
+            context.task_id.task_number()
+        );
+        self.batch_builder = Some(RecordBatchBuilder::new(schema, writer.arrow_schema()));
+        self.writer = Some(writer);
+        self.table = Some(table);
+        self.metrics = Some(SinkMetrics::new(context.task_id.to_tags()));
+
+        // commit the data files of the restored checkpoint
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+        let committable = self.committable.take();
+        self.commit(committable).await?;
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        let mut record = element.into_record();
+        let batch_builder = self.batch_builder.as_mut().unwrap();
+        if let Err(e) = batch_builder.append(&mut record) {
+            error!("read record error, the record is discarded. {}", e);
+            return;
+        }
+        self.metrics.as_ref().unwrap().written();
+
+        if batch_builder.len() >= self.batch_size {
+            self.write_batch()
+                .await
+                .expect("write delta data files error");
+        }
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        let adds = self.flush().await?;
+        let committable = self.committable.take();
+        self.commit(committable).await?;
+        self.commit(Some(DeltaSinkState {
+            version: current_timestamp_millis() as i64,
+            adds,
+        }))
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for DeltaOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            if !handle.handle.is_empty() {
+                let state: DeltaSinkState = serde_json::from_str(handle.handle.as_str())
+                    .expect("parse delta sink state error");
+                info!(
+                    "restore {} data files from checkpoint({:?})",
+                    state.adds.len(),
+                    context.checkpoint_id
+                );
+                self.committable = Some(state);
+            }
+        }
+    }
+
+    /// Close the data files, and commit the data files of the last checkpoint which must be
+    /// completed when the barrier of the next arrives
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let adds = match self.flush().await {
+            Ok(adds) => adds,
+            Err(e) => panic!(
+                "close data files on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        };
+
+        let committable = self.committable.take();
+        if let Err(e) = self.commit(committable).await {
+            panic!(
+                "commit on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+
+        let state = DeltaSinkState {
+            version: context.checkpoint_id.0 as i64,
+            adds,
+        };
+        let handle = serde_json::to_string(&state).unwrap();
+        self.committable = Some(state);
+
+        Some(CheckpointHandle { handle })
+    }
+}
@@ -0,0 +1,270 @@
+//! The records are mapped to the table columns by name, the record values are cast to the
+//! types of the table columns, the table columns missing in the record are null.
+
+use std::sync::Arc;
+
+use deltalake::arrow::array::{
+    new_null_array, ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array, StringArray,
+    TimestampMicrosecondArray, UInt64Array,
+};
+use deltalake::arrow::compute::cast;
+use deltalake::arrow::datatypes::{DataType as ArrowDataType, SchemaRef, TimeUnit};
+use deltalake::arrow::record_batch::RecordBatch;
+use deltalake::kernel::{DataType as DeltaDataType, PrimitiveType, StructField};
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{BufferReader, Record};
+
+/// Derive the table columns from the record schema, all columns are nullable. The unsigned
+/// integers are widened to the signed, the `UInt64` is stored as `long`
+pub fn to_delta_columns(schema: &Schema) -> Vec<StructField> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let primitive_type = match field.data_type() {
+                DataType::Boolean => PrimitiveType::Boolean,
+                DataType::Int8 => PrimitiveType::Byte,
+                DataType::UInt8 | DataType::Int16 => PrimitiveType::Short,
+                DataType::UInt16 | DataType::Int32 => PrimitiveType::Integer,
+                DataType::UInt32 | DataType::Int64 | DataType::UInt64 => PrimitiveType::Long,
+                DataType::Float32 => PrimitiveType::Float,
+                DataType::Float64 => PrimitiveType::Double,
+                DataType::Binary => PrimitiveType::Binary,
+                DataType::String => PrimitiveType::String,
+            };
+            StructField::new(field.name(), DeltaDataType::Primitive(primitive_type), true)
+        })
+        .collect()
+}
+
+/// The values of a record field, the integers and the floats are widened
+enum Values {
+    Boolean(Vec<bool>),
+    Int(Vec<i64>),
+    UInt(Vec<u64>),
+    Float(Vec<f64>),
+    String(Vec<String>),
+    Binary(Vec<Vec<u8>>),
+}
+
+impl Values {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Boolean => Values::Boolean(vec![]),
+            DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+                Values::Int(vec![])
+            }
+            DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+                Values::UInt(vec![])
+            }
+            DataType::Float32 | DataType::Float64 => Values::Float(vec![]),
+            DataType::String => Values::String(vec![]),
+            DataType::Binary => Values::Binary(vec![]),
+        }
+    }
+
+    fn push(
+        &mut self,
+        reader: &BufferReader,
+        index: usize,
+        data_type: &DataType,
+    ) -> anyhow::Result<()> {
+        match self {
+            Values::Boolean(v) => v.push(reader.get_bool(index)?),
+            Values::Int(v) => v.push(match data_type {
+                DataType::Int8 => reader.get_i8(index)? as i64,
+                DataType::Int16 => reader.get_i16(index)? as i64,
+                DataType::Int32 => reader.get_i32(index)? as i64,
+                _ => reader.get_i64(index)?,
+            }),
+            Values::UInt(v) => v.push(match data_type {
+                DataType::UInt8 => reader.get_u8(index)? as u64,
+                DataType::UInt16 => reader.get_u16(index)? as u64,
+                DataType::UInt32 => reader.get_u32(index)? as u64,
+                _ => reader.get_u64(index)?,
+            }),
+            Values::Float(v) => v.push(match data_type {
+                DataType::Float32 => reader.get_f32(index)? as f64,
+                _ => reader.get_f64(index)?,
+            }),
+            Values::String(v) => v.push(reader.get_str(index)?.to_string()),
+            Values::Binary(v) => v.push(reader.get_binary(index)?.to_vec()),
+        }
+        Ok(())
+    }
+
+    /// Take the values as the array of the `target` type, the integers are the millis of
+    /// the timestamps
+    fn take(&mut self, target: &ArrowDataType) -> anyhow::Result<ArrayRef> {
+        if let ArrowDataType::Timestamp(TimeUnit::Microsecond, timezone) = target {
+            let micros: Vec<i64> = match self {
+                Values::Int(v) => std::mem::take(v).into_iter().map(|x| x * 1000).collect(),
+                Values::UInt(v) => std::mem::take(v)
+                    .into_iter()
+                    .map(|x| x as i64 * 1000)
+                    .collect(),
+                _ => return Err(anyhow!("only the integers can be cast to {:?}", target)),
+            };
+            let array = TimestampMicrosecondArray::from(micros).with_timezone_opt(timezone.clone());
+            return Ok(Arc::new(array));
+        }
+
+        let array: ArrayRef = match self {
+            Values::Boolean(v) => Arc::new(BooleanArray::from(std::mem::take(v))),
+            Values::Int(v) => Arc::new(Int64Array::from(std::mem::take(v))),
+            Values::UInt(v) => Arc::new(UInt64Array::from(std::mem::take(v))),
+            Values::Float(v) => Arc::new(Float64Array::from(std::mem::take(v))),
+            Values::String(v) => Arc::new(StringArray::from(std::mem::take(v))),
+            Values::Binary(v) => {
+                let values = std::mem::take(v);
+                Arc::new(BinaryArray::from_iter_values(values.iter()))
+            }
+        };
+        Ok(cast(&array, target)?)
+    }
+}
+
+/// The table column and the record field of the same name
+struct Column {
+    record_field: Option<(usize, DataType)>,
+    values: Option<Values>,
+}
+
+/// Buffer the records as the columns of the table
+pub(crate) struct RecordBatchBuilder {
+    schema: Schema,
+    arrow_schema: SchemaRef,
+    columns: Vec<Column>,
+    rows: usize,
+}
+
+impl RecordBatchBuilder {
+    pub fn new(schema: Schema, arrow_schema: SchemaRef) -> Self {
+        let columns = arrow_schema
+            .fields()
+            .iter()
+            .map(|field| match schema.column_with_name(field.name()) {
+                Some((index, record_field)) => Column {
+                    record_field: Some((index, record_field.data_type().clone())),
+                    values: Some(Values::new(record_field.data_type())),
+                },
+                None => Column {
+                    record_field: None,
+                    values: None,
+                },
+            })
+            .collect();
+
+        RecordBatchBuilder {
+            schema,
+            arrow_schema,
+            columns,
+            rows: 0,
+        }
+    }
+
+    pub fn append(&mut self, record: &mut Record) -> anyhow::Result<()> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+        for column in &mut self.columns {
+            if let (Some((index, data_type)), Some(values)) =
+                (column.record_field.as_ref(), column.values.as_mut())
+            {
+                values.push(&reader, *index, data_type)?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Take the buffered records as a batch
+    pub fn take(&mut self) -> anyhow::Result<RecordBatch> {
+        let rows = std::mem::take(&mut self.rows);
+        let arrays = self
+            .arrow_schema
+            .fields()
+            .iter()
+            .zip(self.columns.iter_mut())
+            .map(|(field, column)| match column.values.as_mut() {
+                Some(values) => values.take(field.data_type()),
+                None => Ok(new_null_array(field.data_type(), rows)),
+            })
+            .collect::<anyhow::Result<Vec<ArrayRef>>>()?;
+        Ok(RecordBatch::try_new(self.arrow_schema.clone(), arrays)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use deltalake::arrow::array::{Array, Int32Array, StringArray, TimestampMicrosecondArray};
+    use deltalake::arrow::datatypes::{
+        DataType as ArrowDataType, Field, Schema as ArrowSchema, TimeUnit,
+    };
+    use rlink::core::data_types::{DataType, Field as RecordField, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::schema::RecordBatchBuilder;
+
+    #[test]
+    pub fn record_batch_builder_test() {
+        let schema = Schema::new(vec![
+            RecordField::new("id", DataType::Int64),
+            RecordField::new("name", DataType::String),
+            RecordField::new("ts", DataType::UInt64),
+        ]);
+        let arrow_schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", ArrowDataType::Int32, true),
+            Field::new(
+                "ts",
+                ArrowDataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                true,
+            ),
+            Field::new("missing", ArrowDataType::Utf8, true),
+            Field::new("name", ArrowDataType::Utf8, true),
+        ]));
+
+        let mut builder = RecordBatchBuilder::new(schema.clone(), arrow_schema);
+        for id in 0..3 {
+            let mut record = Record::new();
+            let mut writer = record.as_writer(schema.as_type_ids());
+            writer.set_i64(id).unwrap();
+            writer.set_str("rlink").unwrap();
+            writer.set_u64(1_700_000_000_000).unwrap();
+            builder.append(&mut record).unwrap();
+        }
+        assert_eq!(builder.len(), 3);
+
+        let batch = builder.take().unwrap();
+        assert!(builder.is_empty());
+        assert_eq!(batch.num_rows(), 3);
+
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(ids.value(2), 2);
+        let ts = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(ts.value(0), 1_700_000_000_000_000);
+        assert_eq!(batch.column(2).null_count(), 3);
+        let names = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(1), "rlink");
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::Once;
+
+use deltalake::kernel::{Action, Add, Transaction};
+use deltalake::operations::transaction::CommitBuilder;
+use deltalake::protocol::{DeltaOperation, SaveMode};
+use deltalake::{DeltaOps, DeltaTable, DeltaTableBuilder, DeltaTableError};
+use rlink::core::data_types::Schema;
+
+use crate::metrics::SinkMetrics;
+use crate::sink::schema::to_delta_columns;
+
+static REGISTER_HANDLERS: Once = Once::new();
+
+/// Register the object stores of the `s3://`, `gs://` and `az://` tables
+fn register_handlers() {
+    REGISTER_HANDLERS.call_once(|| {
+        deltalake::aws::register_handlers(None);
+        deltalake::gcp::register_handlers(None);
+        deltalake::azure::register_handlers(None);
+    });
+}
+
+/// Load the table, or create it by the record `schema` and the `partition_columns` if
+/// `create` is enabled
+pub(crate) async fn load_or_create(
+    table_uri: &str,
+    storage_options: &HashMap<String, String>,
+    schema: &Schema,
+    partition_columns: &[String],
+    create: bool,
+) -> anyhow::Result<DeltaTable> {
+    register_handlers();
+
+    let mut table = DeltaTableBuilder::from_uri(table_uri)
+        .with_storage_options(storage_options.clone())
+        .build()?;
+    match table.load().await {
+        Ok(()) => return Ok(table),
+        Err(DeltaTableError::NotATable(_)) if create => {}
+        Err(e) => return Err(anyhow!("load delta table {} error. {}", table_uri, e)),
+    }
+
+    let table = DeltaOps::try_from_uri_with_storage_options(table_uri, storage_options.clone())
+        .await?
+        .create()
+        .with_columns(to_delta_columns(schema))
+        .with_partition_columns(partition_columns.to_vec())
+        .await?;
+    info!("delta table {} created", table_uri);
+    Ok(table)
+}
+
+/// Commit the data files in a transaction of the `app_id` and `version`. The commit is
+/// skipped if the table has the transaction of the `app_id` not before the `version`, e.g.
+/// committed before the restart
+pub(crate) async fn commit(
+    table: &mut DeltaTable,
+    app_id: &str,
+    version: i64,
+    adds: Vec<Add>,
+    max_retries: usize,
+    metrics: &SinkMetrics,
+) -> anyhow::Result<()> {
+    if adds.is_empty() {
+        return Ok(());
+    }
+
+    table.update().await?;
+    let committed_version = table
+        .get_app_transaction_version()
+        .get(app_id)
+        .map(|txn| txn.version);
+    if let Some(committed_version) = committed_version {
+        if committed_version >= version {
+            info!(
+                "the transaction {} of {} is already committed, the committed is {}",
+                version, app_id, committed_version
+            );
+            metrics.duplicate();
+            return Ok(());
+        }
+    }
+
+    let data_files = adds.len();
+    let mut actions: Vec<Action> = adds.into_iter().map(Action::Add).collect();
+    actions.push(Action::Txn(Transaction::new(app_id, version)));
+
+    let operation = DeltaOperation::Write {
+        mode: SaveMode::Append,
+        partition_by: None,
+        predicate: None,
+    };
+    let commit = CommitBuilder::default()
+        .with_actions(actions)
+        .with_max_retries(max_retries)
+        .build(Some(table.snapshot()?), table.log_store(), operation)
+        .await?;
+
+    info!(
+        "the transaction {} of {} committed with {} data files, table version {}",
+        version, app_id, data_files, commit.version
+    );
+    metrics.committed(data_files);
+    Ok(())
+}
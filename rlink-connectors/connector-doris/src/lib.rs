@@ -0,0 +1,31 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod sink;
+
+pub use sink::output_format::DorisOutputFormat;
+
+pub const DORIS: &str = "doris";
+pub const URL: &str = "url";
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+
+pub const DATABASE: &str = "database";
+pub const TABLE: &str = "table";
+/// The prefix of the stream load headers, e.g. `load.max_filter_ratio`
+pub const LOAD_PROPERTIES: &str = "load";
+pub const LABEL_PREFIX: &str = "label.prefix";
+pub const MAX_BYTES: &str = "max.bytes";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_LABEL_PREFIX: &str = "rlink";
+pub const SINK_MAX_BYTES: usize = 64 * 1024 * 1024;
+pub const SINK_MAX_RETRIES: usize = 3;
@@ -0,0 +1,145 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    DorisOutputFormat, DATABASE, DORIS, LABEL_PREFIX, LOAD_PROPERTIES, MAX_BYTES, MAX_RETRIES,
+    PASSWORD, TABLE, URL, USERNAME,
+};
+
+pub struct DorisOutputFormatBuilder {
+    url: String,
+    database: String,
+    table: String,
+    username: String,
+    password: Option<String>,
+    properties: Vec<(String, String)>,
+    label_prefix: Option<String>,
+    max_bytes: Option<usize>,
+    max_retries: Option<usize>,
+}
+
+impl DorisOutputFormatBuilder {
+    /// The `url` is the http address of the FE, e.g. `http://127.0.0.1:8030`
+    pub fn new(url: &str, database: &str, table: &str, username: &str) -> Self {
+        DorisOutputFormatBuilder {
+            url: url.to_string(),
+            database: database.to_string(),
+            table: table.to_string(),
+            username: username.to_string(),
+            password: None,
+            properties: vec![],
+            label_prefix: None,
+            max_bytes: None,
+            max_retries: None,
+        }
+    }
+
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Send the header of the stream load
+    pub fn property(mut self, key: &str, value: &str) -> Self {
+        self.properties.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Prepend the `label_prefix` to the labels, must be unique for the sinks of a table
+    pub fn label_prefix(mut self, label_prefix: &str) -> Self {
+        self.label_prefix = Some(label_prefix.to_string());
+        self
+    }
+
+    /// Load once the buffered json exceeds `max_bytes` between the checkpoints
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Retry a failed load up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> DorisOutputFormat {
+        info!("build doris sink with: {:?}", &self);
+
+        let mut output_format =
+            DorisOutputFormat::new(self.url, self.database, self.table, self.username);
+
+        if let Some(password) = self.password {
+            output_format = output_format.password(password);
+        }
+        for (key, value) in self.properties {
+            output_format = output_format.property(key, value);
+        }
+        if let Some(label_prefix) = self.label_prefix {
+            output_format = output_format.label_prefix(label_prefix);
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            output_format = output_format.max_bytes(max_bytes);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for DorisOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DorisOutputFormatBuilder")
+            .field("url", &self.url)
+            .field("database", &self.database)
+            .field("table", &self.table)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("properties", &self.properties)
+            .field("label_prefix", &self.label_prefix)
+            .field("max_bytes", &self.max_bytes)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for DorisOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let doris_properties = properties.to_sub_properties(DORIS);
+        let url = doris_properties.get_string(URL)?;
+        let username = doris_properties.get_string(USERNAME)?;
+        let database = properties.get_string(DATABASE)?;
+        let table = properties.get_string(TABLE)?;
+
+        let mut builder = DorisOutputFormatBuilder::new(
+            url.as_str(),
+            database.as_str(),
+            table.as_str(),
+            username.as_str(),
+        );
+
+        if let Ok(password) = doris_properties.get_string(PASSWORD) {
+            builder = builder.password(password.as_str());
+        }
+        for (key, value) in properties.to_sub_properties(LOAD_PROPERTIES).as_map() {
+            builder = builder.property(key.as_str(), value.as_str());
+        }
+        if let Ok(label_prefix) = properties.get_string(LABEL_PREFIX) {
+            builder = builder.label_prefix(label_prefix.as_str());
+        }
+        if let Ok(max_bytes) = properties.get_usize(MAX_BYTES) {
+            builder = builder.max_bytes(max_bytes);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
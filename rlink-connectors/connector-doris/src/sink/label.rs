@@ -0,0 +1,97 @@
+use rlink::core::runtime::CheckpointId;
+
+/// The max length of the label accepted by the FE
+const LABEL_MAX_LEN: usize = 128;
+
+/// Generate the labels of the loads between two checkpoints.
+///
+/// The label is `{prefix}_{application}_{job}_{task}_{checkpoint}_{seq}`, the `checkpoint` is
+/// the checkpoint the task starts or restores from and the `seq` counts the loads since then.
+/// The task restored from the checkpoint replays the same records and generates the same
+/// labels, so the loads committed before the failure are rejected as duplicates by the FE.
+#[derive(Clone, Debug)]
+pub(crate) struct LabelGenerator {
+    prefix: String,
+    checkpoint_id: CheckpointId,
+    seq: usize,
+}
+
+impl LabelGenerator {
+    pub fn new(
+        label_prefix: &str,
+        application_id: &str,
+        job_id: u32,
+        task_number: u16,
+        checkpoint_id: CheckpointId,
+    ) -> Self {
+        let prefix = format!(
+            "{}_{}_{}_{}",
+            sanitize(label_prefix),
+            sanitize(application_id),
+            job_id,
+            task_number
+        );
+        LabelGenerator {
+            prefix,
+            checkpoint_id,
+            seq: 0,
+        }
+    }
+
+    /// The label of the next load
+    pub fn next_label(&mut self) -> String {
+        let suffix = format!("_{}_{}", self.checkpoint_id.0, self.seq);
+        self.seq += 1;
+
+        // keep the suffix identifying the load if the prefix is too long
+        let prefix_len = self.prefix.len().min(LABEL_MAX_LEN - suffix.len());
+        format!("{}{}", &self.prefix[..prefix_len], suffix)
+    }
+
+    /// Start the labels of the loads after the `checkpoint_id`
+    pub fn checkpoint(&mut self, checkpoint_id: CheckpointId) {
+        self.checkpoint_id = checkpoint_id;
+        self.seq = 0;
+    }
+}
+
+/// Replace the characters not allowed in the label with `-`
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::runtime::CheckpointId;
+
+    use crate::sink::label::LabelGenerator;
+
+    #[test]
+    pub fn label_generator_test() {
+        let mut generator = LabelGenerator::new("rlink", "app.1/a", 2, 3, CheckpointId(100));
+        assert_eq!(generator.next_label(), "rlink_app-1-a_2_3_100_0");
+        assert_eq!(generator.next_label(), "rlink_app-1-a_2_3_100_1");
+
+        generator.checkpoint(CheckpointId(200));
+        assert_eq!(generator.next_label(), "rlink_app-1-a_2_3_200_0");
+
+        // the same labels after the restore
+        let mut generator = LabelGenerator::new("rlink", "app.1/a", 2, 3, CheckpointId(100));
+        assert_eq!(generator.next_label(), "rlink_app-1-a_2_3_100_0");
+
+        let application_id = "a".repeat(200);
+        let mut generator =
+            LabelGenerator::new("rlink", application_id.as_str(), 2, 3, CheckpointId(100));
+        let label = generator.next_label();
+        assert_eq!(label.len(), 128);
+        assert!(label.ends_with("_100_0"));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod builder;
+pub mod output_format;
+
+pub(crate) mod json;
+pub(crate) mod label;
+pub(crate) mod stream_load;
@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::metrics::SinkMetrics;
+use tokio::time::Instant;
+
+use crate::sink::json::write_json;
+use crate::sink::label::LabelGenerator;
+use crate::sink::stream_load::{LoadStatus, StreamLoadClient};
+use crate::{SINK_LABEL_PREFIX, SINK_MAX_BYTES, SINK_MAX_RETRIES};
+
+/// Write the records to the table of Doris or StarRocks by the stream load.
+///
+/// The records are buffered as a json array and loaded on checkpoint, or once the buffer
+/// exceeds the `max_bytes`. Every load is labeled by the checkpoint it follows, so the loads
+/// replayed after a restart are deduplicated by the FE. The records must be replayed in the
+/// same order for the loads split by the `max_bytes` to get the same labels.
+#[derive(NamedFunction)]
+pub struct DorisOutputFormat {
+    url: String,
+    database: String,
+    table: String,
+    username: String,
+    password: Option<String>,
+    properties: Vec<(String, String)>,
+
+    label_prefix: String,
+    max_bytes: usize,
+    max_retries: usize,
+
+    schema: Schema,
+    buffer: Vec<u8>,
+    rows: usize,
+    labels: Option<LabelGenerator>,
+    client: Option<StreamLoadClient>,
+    metrics: Option<SinkMetrics>,
+}
+
+impl DorisOutputFormat {
+    pub fn new(url: String, database: String, table: String, username: String) -> Self {
+        DorisOutputFormat {
+            url,
+            database,
+            table,
+            username,
+            password: None,
+            properties: vec![],
+            label_prefix: SINK_LABEL_PREFIX.to_string(),
+            max_bytes: SINK_MAX_BYTES,
+            max_retries: SINK_MAX_RETRIES,
+            schema: Schema::empty(),
+            buffer: vec![],
+            rows: 0,
+            labels: None,
+            client: None,
+            metrics: None,
+        }
+    }
+
+    pub fn password(mut self, password: String) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    /// Send the `key: value` header of the stream load, e.g. `columns` or `max_filter_ratio`
+    pub fn property(mut self, key: String, value: String) -> Self {
+        self.properties.push((key, value));
+        self
+    }
+
+    pub fn label_prefix(mut self, label_prefix: String) -> Self {
+        self.label_prefix = label_prefix;
+        self
+    }
+
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes.max(1);
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Load the buffered rows, a failed load is retried with the same label
+    async fn load(&mut self) -> anyhow::Result<()> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+
+        self.buffer.push(b']');
+        let body = std::mem::take(&mut self.buffer);
+        let rows = std::mem::take(&mut self.rows);
+
+        let label = self.labels.as_mut().unwrap().next_label();
+        let client = self.client.as_ref().unwrap();
+        let metrics = self.metrics.as_ref().unwrap();
+
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            let error = match client.load(label.as_str(), body.clone()).await {
+                Ok(LoadStatus::Loaded(loaded_rows)) => {
+                    info!("load {} rows with label {}", loaded_rows, label);
+                    metrics.flushed(rows as u64, begin.elapsed());
+                    return Ok(());
+                }
+                Ok(LoadStatus::Duplicated) => {
+                    info!("label {} is already loaded, skip {} rows", label, rows);
+                    metrics.dropped(rows as u64);
+                    return Ok(());
+                }
+                Ok(LoadStatus::Running) => format!("the load of label {} is running", label),
+                Ok(LoadStatus::Failed(message)) => message,
+                Err(e) => e.to_string(),
+            };
+
+            if attempt >= self.max_retries {
+                metrics.failure();
+                return Err(anyhow!(
+                    "load {} rows with label {} error after {} retries. {}",
+                    rows,
+                    label,
+                    self.max_retries,
+                    error
+                ));
+            }
+
+            attempt += 1;
+            metrics.retry();
+            warn!(
+                "load {} rows with label {} error, retry({}/{}). {}",
+                rows, label, attempt, self.max_retries, error
+            );
+            tokio::time::sleep(Duration::from_millis(100 * (1 << attempt.min(6)))).await;
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for DorisOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.clone().into();
+
+        let client = StreamLoadClient::new(
+            self.url.as_str(),
+            self.database.as_str(),
+            self.table.as_str(),
+            self.username.as_str(),
+            self.password.as_deref(),
+            self.properties.as_slice(),
+        )?;
+        self.client = Some(client);
+
+        // the checkpoint id is the restored checkpoint, or the default on the first start
+        self.labels = Some(LabelGenerator::new(
+            self.label_prefix.as_str(),
+            context.application_id.as_str(),
+            context.task_id.job_id().0,
+            context.task_id.task_number(),
+            context.checkpoint_id,
+        ));
+        self.metrics = Some(SinkMetrics::new("Doris", context.task_id.to_tags()));
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        let mut record = element.into_record();
+
+        let len = self.buffer.len();
+        self.buffer.push(if self.rows == 0 { b'[' } else { b',' });
+        if let Err(e) = write_json(&self.schema, &mut record, &mut self.buffer) {
+            error!("read record error, the record is discarded. {}", e);
+            self.buffer.truncate(len);
+            return;
+        }
+        self.rows += 1;
+
+        if self.buffer.len() >= self.max_bytes {
+            self.load().await.expect("doris stream load error");
+        }
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.load().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for DorisOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered rows are loaded before the checkpoint completes, the loads after the
+    /// checkpoint are labeled by its id
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        if let Err(e) = self.load().await {
+            panic!(
+                "load on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+        self.labels
+            .as_mut()
+            .unwrap()
+            .checkpoint(context.checkpoint_id);
+
+        None
+    }
+}
@@ -0,0 +1,172 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, EXPECT, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{StatusCode, Url};
+
+/// The response of the stream load
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct LoadResponse {
+    pub status: String,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub existing_job_status: Option<String>,
+    #[serde(default)]
+    pub number_loaded_rows: u64,
+    #[serde(default, rename = "ErrorURL")]
+    pub error_url: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum LoadStatus {
+    /// the rows are loaded by this request
+    Loaded(u64),
+    /// the label is loaded by a previous request
+    Duplicated,
+    /// the load of the label is still running, retry later to get the result
+    Running,
+    Failed(String),
+}
+
+impl From<LoadResponse> for LoadStatus {
+    fn from(response: LoadResponse) -> Self {
+        match response.status.as_str() {
+            // the data is committed and will be visible after the publish
+            "Success" | "Publish Timeout" => LoadStatus::Loaded(response.number_loaded_rows),
+            "Label Already Exists" => match response.existing_job_status.as_deref() {
+                Some("FINISHED") | Some("VISIBLE") | Some("COMMITTED") => LoadStatus::Duplicated,
+                Some("RUNNING") | Some("PREPARE") => LoadStatus::Running,
+                _ => LoadStatus::Failed(response.message),
+            },
+            _ => match response.error_url {
+                Some(error_url) => {
+                    LoadStatus::Failed(format!("{}, see {}", response.message, error_url))
+                }
+                None => LoadStatus::Failed(response.message),
+            },
+        }
+    }
+}
+
+/// The client of the stream load api of the FE, `PUT /api/{db}/{table}/_stream_load`
+pub(crate) struct StreamLoadClient {
+    client: reqwest::Client,
+    url: Url,
+    username: String,
+    password: Option<String>,
+    headers: HeaderMap,
+}
+
+impl StreamLoadClient {
+    pub fn new(
+        url: &str,
+        database: &str,
+        table: &str,
+        username: &str,
+        password: Option<&str>,
+        properties: &[(String, String)],
+    ) -> anyhow::Result<Self> {
+        let url =
+            Url::parse(url)?.join(format!("/api/{}/{}/_stream_load", database, table).as_str())?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(EXPECT, HeaderValue::from_static("100-continue"));
+        headers.insert("format", HeaderValue::from_static("json"));
+        headers.insert("strip_outer_array", HeaderValue::from_static("true"));
+        for (key, value) in properties {
+            headers.insert(
+                HeaderName::from_bytes(key.as_bytes())?,
+                HeaderValue::from_str(value.as_str())?,
+            );
+        }
+
+        // the redirect to the BE is followed manually, the `Authorization` header is dropped
+        // by the client when redirecting to another host
+        let client = reqwest::Client::builder()
+            .redirect(Policy::none())
+            .build()?;
+
+        Ok(StreamLoadClient {
+            client,
+            url,
+            username: username.to_string(),
+            password: password.map(|x| x.to_string()),
+            headers,
+        })
+    }
+
+    async fn put(&self, url: Url, label: &str, body: Vec<u8>) -> anyhow::Result<reqwest::Response> {
+        let response = self
+            .client
+            .put(url)
+            .headers(self.headers.clone())
+            .header("label", label)
+            .basic_auth(self.username.as_str(), self.password.as_ref())
+            .body(body)
+            .send()
+            .await?;
+        Ok(response)
+    }
+
+    /// Load the json array `body` with the `label`, the load of a loaded label is rejected
+    pub async fn load(&self, label: &str, body: Vec<u8>) -> anyhow::Result<LoadStatus> {
+        let mut response = self.put(self.url.clone(), label, body.clone()).await?;
+        if response.status() == StatusCode::TEMPORARY_REDIRECT {
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|x| x.to_str().ok())
+                .ok_or_else(|| anyhow!("redirect without location"))?;
+            let url = Url::parse(location)?;
+            response = self.put(url, label, body).await?;
+        }
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(anyhow!("status {}. {}", status.as_u16(), text));
+        }
+
+        let response: LoadResponse = serde_json::from_str(text.as_str())
+            .map_err(|e| anyhow!("parse the response `{}` error. {}", text, e))?;
+        Ok(LoadStatus::from(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sink::stream_load::{LoadResponse, LoadStatus};
+
+    fn status(response: &str) -> LoadStatus {
+        let response: LoadResponse = serde_json::from_str(response).unwrap();
+        LoadStatus::from(response)
+    }
+
+    #[test]
+    pub fn load_status_test() {
+        assert_eq!(
+            status(
+                r#"{"TxnId":1,"Label":"l","Status":"Success","Message":"OK","NumberLoadedRows":10}"#
+            ),
+            LoadStatus::Loaded(10)
+        );
+        assert_eq!(
+            status(r#"{"Status":"Publish Timeout","NumberLoadedRows":3}"#),
+            LoadStatus::Loaded(3)
+        );
+        assert_eq!(
+            status(r#"{"Status":"Label Already Exists","ExistingJobStatus":"FINISHED"}"#),
+            LoadStatus::Duplicated
+        );
+        assert_eq!(
+            status(r#"{"Status":"Label Already Exists","ExistingJobStatus":"RUNNING"}"#),
+            LoadStatus::Running
+        );
+        assert_eq!(
+            status(
+                r#"{"Status":"Fail","Message":"too many filtered rows","ErrorURL":"http://be/e"}"#
+            ),
+            LoadStatus::Failed("too many filtered rows, see http://be/e".to_string())
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::elasticsearch_sink::ElasticsearchOutputFormat;
+use crate::failure_handler::{FailureHandler, IgnoreFailureHandler};
+use crate::index_pattern::IndexPattern;
+use crate::{
+    ADDRESS, BATCH_INTERVAL, BATCH_SIZE, BUFFER_SIZE, ELASTICSEARCH, ID_FIELD, IGNORE_FAILURE,
+    INDEX, MAX_RETRIES, TIMESTAMP_FIELD,
+};
+
+/// Build the sink writing the records with the field names of the input schema
+pub struct ElasticsearchOutputFormatBuilder {
+    address: String,
+    headers: HashMap<String, String>,
+    index: IndexPattern,
+    id_field: Option<String>,
+    timestamp_field: Option<String>,
+    failure_handler: Option<Box<dyn FailureHandler>>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+    batch_interval: Option<Duration>,
+    max_retries: Option<usize>,
+}
+
+impl ElasticsearchOutputFormatBuilder {
+    /// The `index` may have the time based suffix, e.g. `logs-{%Y.%m.%d}`
+    pub fn new(address: &str, index: &str) -> anyhow::Result<Self> {
+        Ok(ElasticsearchOutputFormatBuilder {
+            address: address.to_string(),
+            headers: HashMap::new(),
+            index: IndexPattern::try_from(index)?,
+            id_field: None,
+            timestamp_field: None,
+            failure_handler: None,
+            buffer_size: None,
+            batch_size: None,
+            batch_interval: None,
+            max_retries: None,
+        })
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Use the value of the field as the document id for the idempotent writes
+    pub fn id_field(mut self, id_field: &str) -> Self {
+        self.id_field = Some(id_field.to_string());
+        self
+    }
+
+    /// Format the index by the millis of the field instead of the processing time
+    pub fn timestamp_field(mut self, timestamp_field: &str) -> Self {
+        self.timestamp_field = Some(timestamp_field.to_string());
+        self
+    }
+
+    /// Handle the rejected documents, the sink fails on any rejected document by default
+    pub fn failure_handler(mut self, failure_handler: Box<dyn FailureHandler>) -> Self {
+        self.failure_handler = Some(failure_handler);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Flush once `batch_size` documents are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Flush the buffered documents at least every `batch_interval`
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = Some(batch_interval);
+        self
+    }
+
+    /// Retry the failed bulk request and the documents rejected by `429` up to `max_retries`
+    /// times
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> ElasticsearchOutputFormat {
+        info!("build elasticsearch sink with: {:?}", &self);
+
+        let mut output_format =
+            ElasticsearchOutputFormat::with_index(self.address.as_str(), self.headers, self.index);
+
+        if let Some(id_field) = self.id_field {
+            output_format = output_format.id_field(id_field.as_str());
+        }
+        if let Some(timestamp_field) = self.timestamp_field {
+            output_format = output_format.timestamp_field(timestamp_field.as_str());
+        }
+        if let Some(failure_handler) = self.failure_handler {
+            output_format = output_format.failure_handler(failure_handler);
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            output_format = output_format.buffer_size(buffer_size);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(batch_interval) = self.batch_interval {
+            output_format = output_format.batch_interval(batch_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for ElasticsearchOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // the headers may carry the credentials
+        let headers: Vec<&String> = self.headers.keys().collect();
+
+        f.debug_struct("ElasticsearchOutputFormatBuilder")
+            .field("address", &self.address)
+            .field("headers", &headers)
+            .field("index", &self.index)
+            .field("id_field", &self.id_field)
+            .field("timestamp_field", &self.timestamp_field)
+            .field("failure_handler", &self.failure_handler.is_some())
+            .field("buffer_size", &self.buffer_size)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for ElasticsearchOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let elasticsearch_properties = properties.to_sub_properties(ELASTICSEARCH);
+        let address = elasticsearch_properties.get_string(ADDRESS)?;
+        let index = properties.get_string(INDEX)?;
+
+        let mut builder = ElasticsearchOutputFormatBuilder::new(address.as_str(), index.as_str())?;
+
+        if let Ok(id_field) = properties.get_string(ID_FIELD) {
+            builder = builder.id_field(id_field.as_str());
+        }
+        if let Ok(timestamp_field) = properties.get_string(TIMESTAMP_FIELD) {
+            builder = builder.timestamp_field(timestamp_field.as_str());
+        }
+        if let Ok(true) = properties.get_bool(IGNORE_FAILURE) {
+            builder = builder.failure_handler(Box::new(IgnoreFailureHandler::default()));
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(batch_interval) = properties.get_duration(BATCH_INTERVAL) {
+            builder = builder.batch_interval(batch_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
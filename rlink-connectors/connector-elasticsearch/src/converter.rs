@@ -0,0 +1,115 @@
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+use rlink::utils::date_time::current_timestamp_millis;
+use serde_json::{Map, Value};
+
+use crate::elasticsearch_sink::{ElasticsearchConverter, ElasticsearchModel};
+use crate::index_pattern::IndexPattern;
+
+/// Convert the record to the document with the field names of the `schema`.
+///
+/// The value of the `id_field` is the document id, so the replayed records overwrite the
+/// documents written before the restart. The index name is formatted by the millis of the
+/// `timestamp_field`, or the processing time if absent.
+pub struct RecordConverter {
+    schema: Schema,
+    index: IndexPattern,
+    id_field: Option<usize>,
+    timestamp_field: Option<usize>,
+}
+
+impl RecordConverter {
+    pub fn new(
+        schema: Schema,
+        index: IndexPattern,
+        id_field: Option<&str>,
+        timestamp_field: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let id_field = match id_field {
+            Some(name) => Some(
+                schema
+                    .index_of(name)
+                    .ok_or_else(|| anyhow!("id field `{}` not found in the schema", name))?,
+            ),
+            None => None,
+        };
+
+        let timestamp_field = match timestamp_field {
+            Some(name) => {
+                let (i, field) = schema
+                    .column_with_name(name)
+                    .ok_or_else(|| anyhow!("timestamp field `{}` not found in the schema", name))?;
+                if !field.is_numeric() {
+                    return Err(anyhow!("timestamp field `{}` is not numeric", name));
+                }
+                Some(i)
+            }
+            None => None,
+        };
+
+        Ok(RecordConverter {
+            schema,
+            index,
+            id_field,
+            timestamp_field,
+        })
+    }
+
+    fn read_document(&self, record: &mut Record) -> std::io::Result<Map<String, Value>> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        let mut document = Map::with_capacity(self.schema.fields().len());
+        for (i, field) in self.schema.fields().iter().enumerate() {
+            let value = match field.data_type() {
+                DataType::Boolean => Value::from(reader.get_bool(i)?),
+                DataType::Int8 => Value::from(reader.get_i8(i)?),
+                DataType::UInt8 => Value::from(reader.get_u8(i)?),
+                DataType::Int16 => Value::from(reader.get_i16(i)?),
+                DataType::UInt16 => Value::from(reader.get_u16(i)?),
+                DataType::Int32 => Value::from(reader.get_i32(i)?),
+                DataType::UInt32 => Value::from(reader.get_u32(i)?),
+                DataType::Int64 => Value::from(reader.get_i64(i)?),
+                DataType::UInt64 => Value::from(reader.get_u64(i)?),
+                DataType::Float32 => Value::from(reader.get_f32(i)?),
+                DataType::Float64 => Value::from(reader.get_f64(i)?),
+                DataType::Binary => {
+                    Value::from(String::from_utf8_lossy(reader.get_binary(i)?).to_string())
+                }
+                DataType::String => Value::from(reader.get_str(i)?),
+            };
+            document.insert(field.name().to_string(), value);
+        }
+
+        Ok(document)
+    }
+}
+
+impl ElasticsearchConverter for RecordConverter {
+    fn to_json(&self, record: &mut Record) -> ElasticsearchModel {
+        let body = self
+            .read_document(record)
+            .expect("read record with the input schema error");
+
+        let id = self
+            .id_field
+            .map(|i| match &body[self.schema.field(i).name()] {
+                Value::String(id) => id.clone(),
+                id => id.to_string(),
+            });
+
+        let timestamp = match self.timestamp_field {
+            Some(i) => body[self.schema.field(i).name()]
+                .as_f64()
+                .map(|x| x as i64)
+                .unwrap_or_default(),
+            None => current_timestamp_millis() as i64,
+        };
+
+        ElasticsearchModel {
+            index: self.index.format(timestamp),
+            es_type: "",
+            id,
+            body: Value::Object(body),
+        }
+    }
+}
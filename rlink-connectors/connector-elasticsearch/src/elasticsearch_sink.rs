@@ -1,26 +1,26 @@
-use std::borrow::BorrowMut;
 use std::collections::HashMap;
-use std::fmt::Debug;
-use std::sync::Arc;
 use std::time::Duration;
 
-use elasticsearch::http::headers::HeaderMap;
-use elasticsearch::http::request::JsonBody;
-use elasticsearch::http::transport::{SingleNodeConnectionPool, TransportBuilder};
-use elasticsearch::http::Url;
-use elasticsearch::{BulkParts, Elasticsearch};
-use rlink::channel::receiver::ChannelReceiver;
-use rlink::channel::sender::ChannelSender;
-use rlink::channel::{named_channel, TryRecvError};
 use rlink::core;
 use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
 use rlink::core::element::{Element, FnSchema, Record};
 use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
 use serde_json::Value;
 
+use crate::converter::RecordConverter;
+use crate::failure_handler::{FailFailureHandler, FailureHandler};
+use crate::index_pattern::IndexPattern;
+use crate::writer::ElasticsearchWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_CHANNEL_SIZE, SINK_MAX_RETRIES};
+
+#[derive(Clone, Debug)]
 pub struct ElasticsearchModel {
     pub index: String,
+    /// the mapping type, omitted if empty
     pub es_type: &'static str,
+    /// the document id, the document with the same id is overwritten
+    pub id: Option<String>,
     pub body: Value,
 }
 
@@ -44,6 +44,10 @@ impl Index {
         self.index.insert("_type".to_string(), type_value);
     }
 
+    pub fn set_id(&mut self, id_value: String) {
+        self.index.insert("_id".to_string(), id_value);
+    }
+
     pub fn to_json(&self) -> Result<Value, serde_json::Error> {
         serde_json::to_value(self)
     }
@@ -58,8 +62,19 @@ pub struct ElasticsearchOutputFormat {
     address: String,
     headers: HashMap<String, String>,
 
-    builder: Arc<Box<dyn ElasticsearchConverter>>,
-    sender: Option<ChannelSender<Record>>,
+    converter: Option<Box<dyn ElasticsearchConverter>>,
+    /// the index of the `RecordConverter`, used if no converter is given
+    index: Option<IndexPattern>,
+    id_field: Option<String>,
+    timestamp_field: Option<String>,
+    failure_handler: Option<Box<dyn FailureHandler>>,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+
+    writer: Option<BatchWriterHandle>,
 }
 
 impl ElasticsearchOutputFormat {
@@ -67,45 +82,125 @@ impl ElasticsearchOutputFormat {
         address: &str,
         headers: HashMap<String, String>,
         builder: Box<dyn ElasticsearchConverter>,
+    ) -> Self {
+        ElasticsearchOutputFormat::create(address, headers, Some(builder), None)
+    }
+
+    /// Write the records to the `index` with the field names of the input schema
+    pub fn with_index(
+        address: &str,
+        headers: HashMap<String, String>,
+        index: IndexPattern,
+    ) -> Self {
+        ElasticsearchOutputFormat::create(address, headers, None, Some(index))
+    }
+
+    fn create(
+        address: &str,
+        headers: HashMap<String, String>,
+        converter: Option<Box<dyn ElasticsearchConverter>>,
+        index: Option<IndexPattern>,
     ) -> Self {
         ElasticsearchOutputFormat {
             address: address.to_string(),
             headers,
-            builder: Arc::new(builder),
-            sender: None,
+            converter,
+            index,
+            id_field: None,
+            timestamp_field: None,
+            failure_handler: None,
+            buffer_size: SINK_CHANNEL_SIZE,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            writer: None,
         }
     }
+
+    /// The field of the document id, used with the `index`
+    pub fn id_field(mut self, id_field: &str) -> Self {
+        self.id_field = Some(id_field.to_string());
+        self
+    }
+
+    /// The field of the millis formatting the `index`, used with the `index`
+    pub fn timestamp_field(mut self, timestamp_field: &str) -> Self {
+        self.timestamp_field = Some(timestamp_field.to_string());
+        self
+    }
+
+    pub fn failure_handler(mut self, failure_handler: Box<dyn FailureHandler>) -> Self {
+        self.failure_handler = Some(failure_handler);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
 }
 
 #[async_trait]
 impl OutputFormat for ElasticsearchOutputFormat {
     async fn open(&mut self, context: &Context) -> core::Result<()> {
-        let (sender, receiver) = named_channel(self.name(), context.task_id.to_tags(), 10000);
-        self.sender = Some(sender);
-
-        let mut write_thead = ElasticsearchWriteThread::new(
-            self.address.as_str(),
-            self.headers.clone(),
-            receiver,
-            3000,
-        )
-        .expect("build elasticsearch connection error");
-
-        let convert = self.builder.clone();
-        tokio::spawn(async move {
-            write_thead.run(convert).await;
-        });
+        let converter = match (self.converter.take(), self.index.as_ref()) {
+            (Some(converter), _) => converter,
+            (None, Some(index)) => {
+                let converter = RecordConverter::new(
+                    context.input_schema.clone().into(),
+                    index.clone(),
+                    self.id_field.as_deref(),
+                    self.timestamp_field.as_deref(),
+                )?;
+                Box::new(converter) as Box<dyn ElasticsearchConverter>
+            }
+            (None, None) => {
+                return Err(core::Error::from("no converter or index given".to_string()))
+            }
+        };
+        let failure_handler = self
+            .failure_handler
+            .take()
+            .unwrap_or_else(|| Box::new(FailFailureHandler::default()));
+
+        let writer =
+            ElasticsearchWriter::new(self.address.as_str(), self.headers.clone(), converter)?
+                .failure_handler(failure_handler)
+                .max_retries(self.max_retries);
+        let writer = BatchWriterThread::new("Elasticsearch", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), context.task_id.to_tags(), self.buffer_size);
+        self.writer = Some(writer);
 
         Ok(())
     }
 
     async fn write_element(&mut self, element: Element) {
-        self.sender
-            .as_ref()
-            .unwrap()
-            .send(element.into_record())
-            .await
-            .unwrap();
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write elasticsearch record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
     }
 
     async fn close(&mut self) -> core::Result<()> {
@@ -126,123 +221,13 @@ impl CheckpointFunction for ElasticsearchOutputFormat {
     ) {
     }
 
+    /// The buffered documents are indexed on the checkpoint, the replayed records overwrite the
+    /// documents with the same id
     async fn snapshot_state(
         &mut self,
-        _context: &FunctionSnapshotContext,
+        context: &FunctionSnapshotContext,
     ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
         None
     }
 }
-
-pub struct ElasticsearchWriteThread {
-    client: Elasticsearch,
-    batch_size: usize,
-    receiver: ChannelReceiver<Record>,
-}
-
-impl ElasticsearchWriteThread {
-    pub fn new(
-        address: &str,
-        headers: HashMap<String, String>,
-        receiver: ChannelReceiver<Record>,
-        batch_size: usize,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut header_map = HeaderMap::new();
-        if headers.contains_key("stoken") {
-            let val = headers.get("stoken").unwrap();
-            header_map.insert("stoken", val.as_str().parse().unwrap());
-        }
-
-        let url = Url::parse(address)?;
-        let conn_pool = SingleNodeConnectionPool::new(url);
-        let transport = TransportBuilder::new(conn_pool)
-            .headers(header_map)
-            .build()?;
-        let client = Elasticsearch::new(transport);
-
-        Ok(ElasticsearchWriteThread {
-            client,
-            batch_size,
-            receiver,
-        })
-    }
-
-    pub async fn run(&mut self, converters: Arc<Box<dyn ElasticsearchConverter>>) {
-        let converter = converters.clone();
-        self.run0(converter).await;
-    }
-
-    pub async fn run0(&mut self, converter: Arc<Box<dyn ElasticsearchConverter>>) {
-        loop {
-            match self.batch_send(&converter).await {
-                Ok(len) => {
-                    if len == 0 {
-                        tokio::time::sleep(Duration::from_secs(1)).await;
-                    }
-                }
-                Err(e) => {
-                    error!("write elasticsearch error. {}", e);
-                    tokio::time::sleep(Duration::from_millis(100)).await;
-                }
-            }
-        }
-    }
-
-    async fn batch_send(
-        &mut self,
-        converter: &Box<dyn ElasticsearchConverter>,
-    ) -> anyhow::Result<usize> {
-        let mut bulk_bodies = Vec::with_capacity(self.batch_size);
-        for _ in 0..self.batch_size {
-            match self.receiver.try_recv() {
-                Ok(mut record) => {
-                    let ElasticsearchModel {
-                        index,
-                        es_type,
-                        body,
-                    } = converter.to_json(record.borrow_mut());
-
-                    let mut index_model = Index::new();
-                    index_model.set_index(index.clone());
-                    index_model.set_type(es_type.to_string());
-                    bulk_bodies.push(JsonBody::new(index_model.to_json().unwrap()));
-
-                    bulk_bodies.push(JsonBody::new(body));
-                }
-                Err(TryRecvError::Empty) => {
-                    break;
-                }
-                Err(TryRecvError::Disconnected) => {
-                    return Err(anyhow!("clickhouse channel has been disconnected"));
-                }
-            }
-        }
-
-        let len = bulk_bodies.len();
-        self.flush(bulk_bodies).await?;
-
-        Ok(len)
-    }
-
-    async fn flush(&self, body_bulk: Vec<JsonBody<Value>>) -> anyhow::Result<bool> {
-        if body_bulk.len() == 0 {
-            return Ok(true);
-        }
-        let response = self
-            .client
-            .bulk(BulkParts::None)
-            .body(body_bulk)
-            .send()
-            .await?;
-        let response_body = response.json::<Value>().await?;
-        let errors = response_body["errors"]
-            .as_bool()
-            .ok_or(anyhow!("no errors field in es response"))?;
-
-        if errors {
-            Err(anyhow!("elasticsearch send error, {}", response_body))
-        } else {
-            Ok(true)
-        }
-    }
-}
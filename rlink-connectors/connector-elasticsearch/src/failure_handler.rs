@@ -0,0 +1,55 @@
+use serde_json::Value;
+
+use crate::elasticsearch_sink::ElasticsearchModel;
+
+/// Handle the document rejected by the bulk request, e.g. the mapping conflict(400) or the
+/// version conflict(409). The documents rejected by `429` are retried before handed over.
+pub trait FailureHandler: Send + Sync {
+    /// An `Err` fails the sink, the job restarts from the last checkpoint
+    fn on_failure(
+        &self,
+        document: &ElasticsearchModel,
+        status: u16,
+        error: &Value,
+    ) -> anyhow::Result<()>;
+}
+
+/// Fail the sink on any rejected document
+#[derive(Clone, Debug, Default)]
+pub struct FailFailureHandler {}
+
+impl FailureHandler for FailFailureHandler {
+    fn on_failure(
+        &self,
+        document: &ElasticsearchModel,
+        status: u16,
+        error: &Value,
+    ) -> anyhow::Result<()> {
+        Err(anyhow!(
+            "document(index: {}, id: {:?}) rejected with status {}. {}",
+            document.index,
+            document.id,
+            status,
+            error
+        ))
+    }
+}
+
+/// Log and drop the rejected document
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreFailureHandler {}
+
+impl FailureHandler for IgnoreFailureHandler {
+    fn on_failure(
+        &self,
+        document: &ElasticsearchModel,
+        status: u16,
+        error: &Value,
+    ) -> anyhow::Result<()> {
+        warn!(
+            "document(index: {}, id: {:?}) rejected with status {}, dropped. {}",
+            document.index, document.id, status, error
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use std::convert::TryFrom;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{TimeZone, Utc};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// the `strftime` format of the time, e.g. `%Y.%m.%d`
+    DateTime(String),
+}
+
+/// The index name with the time based suffix, the `strftime` formats in the braces are
+/// replaced by the UTC time of the document, e.g. `logs-{%Y.%m.%d}` is `logs-2021.08.01`
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexPattern {
+    segments: Vec<Segment>,
+}
+
+impl IndexPattern {
+    /// The index name of the document at `timestamp` millis
+    pub fn format(&self, timestamp: i64) -> String {
+        let date_time = Utc.timestamp_millis(timestamp);
+
+        let mut index = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => index.push_str(literal),
+                Segment::DateTime(fmt) => {
+                    index.push_str(date_time.format(fmt).to_string().as_str())
+                }
+            }
+        }
+        index
+    }
+}
+
+impl TryFrom<&str> for IndexPattern {
+    type Error = anyhow::Error;
+
+    fn try_from(pattern: &str) -> Result<Self, Self::Error> {
+        let mut segments = Vec::new();
+
+        let mut rest = pattern;
+        while let Some(begin) = rest.find('{') {
+            let end = rest[begin..]
+                .find('}')
+                .map(|x| x + begin)
+                .ok_or_else(|| anyhow!("unclosed `{{` in index pattern `{}`", pattern))?;
+
+            let fmt = &rest[begin + 1..end];
+            if fmt.is_empty() {
+                return Err(anyhow!("empty time format in index pattern `{}`", pattern));
+            }
+            // the invalid format panics on formatting
+            if StrftimeItems::new(fmt).any(|x| matches!(x, Item::Error)) {
+                return Err(anyhow!(
+                    "invalid time format `{}` in index pattern `{}`",
+                    fmt,
+                    pattern
+                ));
+            }
+
+            if begin > 0 {
+                segments.push(Segment::Literal(rest[..begin].to_string()));
+            }
+            segments.push(Segment::DateTime(fmt.to_string()));
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        if segments.is_empty() {
+            return Err(anyhow!("empty index pattern"));
+        }
+
+        Ok(IndexPattern { segments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::index_pattern::IndexPattern;
+
+    #[test]
+    pub fn index_pattern_test() {
+        // 2021-08-01T23:30:00Z
+        let timestamp = 1627860600000;
+
+        let pattern = IndexPattern::try_from("logs-{%Y.%m.%d}").unwrap();
+        assert_eq!(pattern.format(timestamp), "logs-2021.08.01");
+
+        let pattern = IndexPattern::try_from("{%Y}-logs-{%H}h").unwrap();
+        assert_eq!(pattern.format(timestamp), "2021-logs-23h");
+
+        let pattern = IndexPattern::try_from("logs").unwrap();
+        assert_eq!(pattern.format(timestamp), "logs");
+
+        assert!(IndexPattern::try_from("logs-{%Y").is_err());
+        assert!(IndexPattern::try_from("logs-{}").is_err());
+        assert!(IndexPattern::try_from("logs-{%Q}").is_err());
+        assert!(IndexPattern::try_from("").is_err());
+    }
+}
@@ -9,11 +9,30 @@ extern crate anyhow;
 #[macro_use]
 extern crate async_trait;
 
+pub mod builder;
+pub mod converter;
 pub mod elasticsearch_sink;
+pub mod failure_handler;
+pub mod index_pattern;
 
-// pub static ES_DATA_TYPES: [u8; 2] = [
-//     // topic
-//     rlink::core::element::types::BYTES,
-//     // body
-//     rlink::core::element::types::BYTES,
-// ];
+mod writer;
+
+pub use builder::ElasticsearchOutputFormatBuilder;
+pub use elasticsearch_sink::ElasticsearchOutputFormat;
+
+pub const ELASTICSEARCH: &str = "elasticsearch";
+pub const ADDRESS: &str = "address";
+
+pub const INDEX: &str = "index";
+pub const ID_FIELD: &str = "id.field";
+pub const TIMESTAMP_FIELD: &str = "timestamp.field";
+pub const IGNORE_FAILURE: &str = "ignore.failure";
+pub const BUFFER_SIZE: &str = "buffer.size";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_CHANNEL_SIZE: usize = 10000;
+pub const SINK_BATCH_SIZE: usize = 3000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 1000;
+pub const SINK_MAX_RETRIES: usize = 3;
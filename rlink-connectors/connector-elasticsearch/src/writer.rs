@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use elasticsearch::http::headers::{HeaderMap, HeaderName, HeaderValue};
+use elasticsearch::http::request::JsonBody;
+use elasticsearch::http::transport::{SingleNodeConnectionPool, TransportBuilder};
+use elasticsearch::http::Url;
+use elasticsearch::{BulkParts, Elasticsearch};
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use serde_json::Value;
+use tokio::time::Instant;
+
+use crate::elasticsearch_sink::{ElasticsearchConverter, ElasticsearchModel, Index};
+use crate::failure_handler::{FailFailureHandler, FailureHandler};
+use crate::SINK_MAX_RETRIES;
+
+/// The status of the document rejected by the full queue of the node
+const STATUS_TOO_MANY_REQUESTS: u16 = 429;
+
+/// The result of a document in the bulk request
+enum ItemResult {
+    Ok,
+    Failed { status: u16, error: Value },
+}
+
+pub(crate) struct ElasticsearchWriter {
+    client: Elasticsearch,
+    converter: Box<dyn ElasticsearchConverter>,
+    failure_handler: Box<dyn FailureHandler>,
+    max_retries: usize,
+
+    documents: Vec<ElasticsearchModel>,
+}
+
+impl ElasticsearchWriter {
+    pub fn new(
+        address: &str,
+        headers: HashMap<String, String>,
+        converter: Box<dyn ElasticsearchConverter>,
+    ) -> anyhow::Result<Self> {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value.as_str())?,
+            );
+        }
+
+        let url = Url::parse(address)?;
+        let conn_pool = SingleNodeConnectionPool::new(url);
+        let transport = TransportBuilder::new(conn_pool)
+            .headers(header_map)
+            .build()?;
+        let client = Elasticsearch::new(transport);
+
+        Ok(ElasticsearchWriter {
+            client,
+            converter,
+            failure_handler: Box::new(FailFailureHandler::default()),
+            max_retries: SINK_MAX_RETRIES,
+            documents: Vec::new(),
+        })
+    }
+
+    pub fn failure_handler(mut self, failure_handler: Box<dyn FailureHandler>) -> Self {
+        self.failure_handler = failure_handler;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Send the documents in a bulk request, the results are in the order of the documents
+    async fn bulk(&self, documents: &[ElasticsearchModel]) -> anyhow::Result<Vec<ItemResult>> {
+        let mut body = Vec::with_capacity(documents.len() * 2);
+        for document in documents {
+            let mut index = Index::new();
+            index.set_index(document.index.clone());
+            // the mapping types are removed since Elasticsearch 8 and OpenSearch 2
+            if !document.es_type.is_empty() {
+                index.set_type(document.es_type.to_string());
+            }
+            if let Some(id) = &document.id {
+                index.set_id(id.clone());
+            }
+
+            body.push(JsonBody::new(index.to_json()?));
+            body.push(JsonBody::new(document.body.clone()));
+        }
+
+        let response = self.client.bulk(BulkParts::None).body(body).send().await?;
+        let status = response.status_code();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "bulk request error, status {}. {}",
+                status.as_u16(),
+                text
+            ));
+        }
+
+        let response_body = response.json::<Value>().await?;
+        let errors = response_body["errors"]
+            .as_bool()
+            .ok_or_else(|| anyhow!("no errors field in es response"))?;
+        if !errors {
+            return Ok(documents.iter().map(|_| ItemResult::Ok).collect());
+        }
+
+        let items = response_body["items"]
+            .as_array()
+            .ok_or_else(|| anyhow!("no items field in es response"))?;
+        if items.len() != documents.len() {
+            return Err(anyhow!(
+                "{} items in es response, expect {}",
+                items.len(),
+                documents.len()
+            ));
+        }
+
+        let results = items
+            .iter()
+            .map(|item| {
+                // the item is keyed by the action, e.g. `{"index": {"status": 201, ...}}`
+                let item = item.as_object().and_then(|x| x.values().next());
+                let status = item.and_then(|x| x["status"].as_u64()).unwrap_or_default() as u16;
+                if (200..300).contains(&status) {
+                    ItemResult::Ok
+                } else {
+                    ItemResult::Failed {
+                        status,
+                        error: item.map(|x| x["error"].clone()).unwrap_or_default(),
+                    }
+                }
+            })
+            .collect();
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl BatchWriter for ElasticsearchWriter {
+    fn push(&mut self, mut record: Record) -> anyhow::Result<()> {
+        self.documents.push(self.converter.to_json(&mut record));
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Retry the failed request and the documents rejected by `429` with the backoff, other
+    /// rejected documents are handed over to the failure handler
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let mut documents = std::mem::take(&mut self.documents);
+        let mut attempt = 0;
+        while !documents.is_empty() {
+            let begin = Instant::now();
+            match self.bulk(&documents).await {
+                Ok(results) => {
+                    let mut written = 0;
+                    let mut retries = Vec::new();
+                    for (document, result) in documents.drain(..).zip(results) {
+                        match result {
+                            ItemResult::Ok => written += 1,
+                            ItemResult::Failed { status, .. }
+                                if status == STATUS_TOO_MANY_REQUESTS
+                                    && attempt < self.max_retries =>
+                            {
+                                retries.push(document);
+                            }
+                            ItemResult::Failed { status, error } => {
+                                metrics.dropped(1);
+                                self.failure_handler.on_failure(&document, status, &error)?;
+                            }
+                        }
+                    }
+                    metrics.flushed(written, begin.elapsed());
+                    documents = retries;
+                }
+                Err(e) if attempt < self.max_retries => {
+                    warn!(
+                        "bulk {} documents error, retry({}/{}). {}",
+                        documents.len(),
+                        attempt + 1,
+                        self.max_retries,
+                        e
+                    );
+                }
+                Err(e) => {
+                    metrics.failure();
+                    return Err(anyhow!(
+                        "bulk {} documents error after {} retries. {}",
+                        documents.len(),
+                        self.max_retries,
+                        e
+                    ));
+                }
+            }
+
+            if !documents.is_empty() {
+                attempt += 1;
+                metrics.retry();
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+        }
+
+        Ok(())
+    }
+}
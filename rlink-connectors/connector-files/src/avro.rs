@@ -0,0 +1,182 @@
+//! Avro records mapped to the rlink `Schema` fields by name. rlink records have no null, so
+//! a null value is read as the zero value of the field type.
+
+use apache_avro::types::Value;
+use apache_avro::Schema as AvroSchema;
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::{BufferReader, BufferWriter, Record};
+
+/// The name of the avro record derived from the rlink schema
+pub const AVRO_RECORD_NAME: &str = "rlink_record";
+
+/// Derive the avro record schema from the rlink schema, the unsigned integers are widened
+/// to the signed of avro, the `UInt64` is stored as `long`
+pub fn to_avro_schema(schema: &Schema) -> AvroSchema {
+    let fields: Vec<serde_json::Value> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let avro_type = match field.data_type() {
+                DataType::Boolean => "boolean",
+                DataType::Int8
+                | DataType::UInt8
+                | DataType::Int16
+                | DataType::UInt16
+                | DataType::Int32 => "int",
+                DataType::UInt32 | DataType::Int64 | DataType::UInt64 => "long",
+                DataType::Float32 => "float",
+                DataType::Float64 => "double",
+                DataType::Binary => "bytes",
+                DataType::String => "string",
+            };
+            serde_json::json!({"name": field.name(), "type": avro_type})
+        })
+        .collect();
+
+    let json = serde_json::json!({
+        "type": "record",
+        "name": AVRO_RECORD_NAME,
+        "fields": fields,
+    });
+    AvroSchema::parse(&json).expect("the field name is not a valid avro name")
+}
+
+fn unwrap_union(value: &Value) -> &Value {
+    match value {
+        Value::Union(_, value) => unwrap_union(value.as_ref()),
+        _ => value,
+    }
+}
+
+fn as_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Null => Some(0),
+        Value::Boolean(v) => Some(*v as i64),
+        Value::Int(v) | Value::Date(v) | Value::TimeMillis(v) => Some(*v as i64),
+        Value::Long(v)
+        | Value::TimeMicros(v)
+        | Value::TimestampMillis(v)
+        | Value::TimestampMicros(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Float(v) => Some(*v as f64),
+        Value::Double(v) => Some(*v),
+        _ => as_i64(value).map(|v| v as f64),
+    }
+}
+
+fn write_field(writer: &mut BufferWriter, field: &Field, value: &Value) -> anyhow::Result<()> {
+    let value = unwrap_union(value);
+    let illegal = || {
+        anyhow!(
+            "avro value {:?} can't be converted to the field `{}` of {:?}",
+            value,
+            field.name(),
+            field.data_type()
+        )
+    };
+
+    match field.data_type() {
+        DataType::Boolean => match value {
+            Value::Boolean(v) => writer.set_bool(*v)?,
+            Value::Null => writer.set_bool(false)?,
+            _ => return Err(illegal()),
+        },
+        DataType::Int8 => writer.set_i8(as_i64(value).ok_or_else(illegal)? as i8)?,
+        DataType::UInt8 => writer.set_u8(as_i64(value).ok_or_else(illegal)? as u8)?,
+        DataType::Int16 => writer.set_i16(as_i64(value).ok_or_else(illegal)? as i16)?,
+        DataType::UInt16 => writer.set_u16(as_i64(value).ok_or_else(illegal)? as u16)?,
+        DataType::Int32 => writer.set_i32(as_i64(value).ok_or_else(illegal)? as i32)?,
+        DataType::UInt32 => writer.set_u32(as_i64(value).ok_or_else(illegal)? as u32)?,
+        DataType::Int64 => writer.set_i64(as_i64(value).ok_or_else(illegal)?)?,
+        DataType::UInt64 => writer.set_u64(as_i64(value).ok_or_else(illegal)? as u64)?,
+        DataType::Float32 => writer.set_f32(as_f64(value).ok_or_else(illegal)? as f32)?,
+        DataType::Float64 => writer.set_f64(as_f64(value).ok_or_else(illegal)?)?,
+        DataType::String => match value {
+            Value::String(v) | Value::Enum(_, v) => writer.set_str(v.as_str())?,
+            Value::Uuid(v) => writer.set_str(v.to_string().as_str())?,
+            Value::Null => writer.set_str("")?,
+            _ => return Err(illegal()),
+        },
+        DataType::Binary => match value {
+            Value::Bytes(v) | Value::Fixed(_, v) => writer.set_binary(v.as_slice())?,
+            Value::String(v) => writer.set_binary(v.as_bytes())?,
+            Value::Null => writer.set_binary(&[])?,
+            _ => return Err(illegal()),
+        },
+    }
+
+    Ok(())
+}
+
+/// Convert the decoded avro record into a rlink record of the `schema`
+pub fn avro_to_record(value: &Value, schema: &Schema) -> anyhow::Result<Record> {
+    let avro_fields = match value {
+        Value::Record(fields) => fields,
+        _ => return Err(anyhow!("avro record expected, found {:?}", value)),
+    };
+
+    let mut record = Record::new();
+    let mut writer = record.as_writer(schema.as_type_ids());
+    for field in schema.fields() {
+        let value = avro_fields
+            .iter()
+            .find(|(name, _)| name.eq(field.name()))
+            .map(|(_, value)| value)
+            .unwrap_or(&Value::Null);
+        write_field(&mut writer, field, value)?;
+    }
+
+    Ok(record)
+}
+
+fn read_field(reader: &BufferReader, index: usize, field: &Field) -> anyhow::Result<Value> {
+    let value = match field.data_type() {
+        DataType::Boolean => Value::Boolean(reader.get_bool(index)?),
+        DataType::Int8 => Value::Int(reader.get_i8(index)? as i32),
+        DataType::UInt8 => Value::Int(reader.get_u8(index)? as i32),
+        DataType::Int16 => Value::Int(reader.get_i16(index)? as i32),
+        DataType::UInt16 => Value::Int(reader.get_u16(index)? as i32),
+        DataType::Int32 => Value::Int(reader.get_i32(index)?),
+        DataType::UInt32 => Value::Long(reader.get_u32(index)? as i64),
+        DataType::Int64 => Value::Long(reader.get_i64(index)?),
+        DataType::UInt64 => Value::Long(reader.get_u64(index)? as i64),
+        DataType::Float32 => Value::Float(reader.get_f32(index)?),
+        DataType::Float64 => Value::Double(reader.get_f64(index)?),
+        DataType::String => Value::String(reader.get_str(index)?.to_string()),
+        DataType::Binary => Value::Bytes(reader.get_binary(index)?.to_vec()),
+    };
+    Ok(value)
+}
+
+/// Convert the rlink record of the `schema` into an avro record of the `avro_schema`,
+/// the avro fields missing in the `schema` are null
+pub fn record_to_avro(
+    record: &mut Record,
+    schema: &Schema,
+    avro_schema: &AvroSchema,
+) -> anyhow::Result<Value> {
+    let avro_fields = match avro_schema {
+        AvroSchema::Record { fields, .. } => fields,
+        _ => return Err(anyhow!("avro record schema expected")),
+    };
+
+    let reader = record.as_reader(schema.as_type_ids());
+    let mut values = Vec::with_capacity(avro_fields.len());
+    for avro_field in avro_fields {
+        let value = match schema.index_of(avro_field.name.as_str()) {
+            Some(index) => read_field(&reader, index, schema.field(index))?,
+            None => Value::Null,
+        };
+        values.push((avro_field.name.clone(), value));
+    }
+
+    // adapt the values to the schema, eg: wrap the values of the nullable fields into unions
+    Value::Record(values)
+        .resolve(avro_schema)
+        .map_err(|e| anyhow!("resolve the avro record error. {}", e))
+}
@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod avro;
+pub mod metrics;
+pub mod sink;
+pub mod source;
+
+pub use sink::builder::FileOutputFormatBuilder;
+pub use sink::output_format::FileOutputFormat;
+pub use source::builder::FileInputFormatBuilder;
+pub use source::input_format::FileInputFormat;
+
+pub const FILE: &str = "file";
+/// a local directory or an object store url, e.g. `s3://bucket/path`
+pub const PATH: &str = "path";
+/// the options of the object store, e.g. `file.store.aws.region`
+pub const STORE: &str = "store";
+/// the local directory of the in-progress files of the object store
+pub const STAGING_DIR: &str = "staging.dir";
+
+/// `{%Y-%m-%d}` for the time of the record and `{field}` for the value of the field,
+/// e.g. `dt={%Y-%m-%d}/hour={%H}`
+pub const BUCKET: &str = "bucket";
+pub const TIMESTAMP_FIELD: &str = "timestamp.field";
+/// `json`, `csv`, `parquet` or `avro` of the sink, `json`, `csv` or `avro` of the source
+pub const FORMAT: &str = "format";
+pub const CSV_DELIMITER: &str = "csv.delimiter";
+/// whether the first line of the csv files of the source is the header
+pub const CSV_HEADER: &str = "csv.header";
+/// `uncompressed`, `snappy`, `gzip`, `lz4` or `zstd`
+pub const PARQUET_COMPRESSION: &str = "parquet.compression";
+pub const PARQUET_ROW_GROUP_SIZE: &str = "parquet.row.group.size";
+/// the json of the avro schema, the writer schema of the sink and the reader schema of the
+/// source
+pub const AVRO_SCHEMA: &str = "avro.schema";
+/// `null`, `deflate` or `snappy`
+pub const AVRO_CODEC: &str = "avro.codec";
+pub const BUFFER_SIZE: &str = "buffer.size";
+/// the millis to scan the paths of the source for the new files
+pub const MONITOR_INTERVAL: &str = "monitor.interval";
+pub const PART_SIZE: &str = "rolling.part.size";
+pub const ROLLOVER_INTERVAL: &str = "rolling.interval";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 50000;
+
+pub const SINK_PART_SIZE: u64 = 128 * 1024 * 1024;
+pub const SINK_ROLLOVER_INTERVAL_MILLIS: u64 = 15 * 60 * 1000;
@@ -0,0 +1,38 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SINK_ROWS: &str = "File.Sink.Rows";
+pub const SINK_BYTES: &str = "File.Sink.Bytes";
+pub const SINK_FINISHED_FILES: &str = "File.Sink.FinishedFiles";
+
+/// Metrics of the sink, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SinkMetrics {
+    /// rows written to the part files
+    rows: Counter,
+    /// bytes of the rolled part files
+    bytes: Counter,
+    /// part files moved to finished
+    finished_files: Counter,
+}
+
+impl SinkMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SinkMetrics {
+            rows: register_counter(SINK_ROWS, tags.clone()),
+            bytes: register_counter(SINK_BYTES, tags.clone()),
+            finished_files: register_counter(SINK_FINISHED_FILES, tags),
+        }
+    }
+
+    pub fn written(&self) {
+        self.rows.increment(1);
+    }
+
+    pub fn rolled(&self, bytes: u64) {
+        self.bytes.increment(bytes);
+    }
+
+    pub fn finished(&self, files: usize) {
+        self.finished_files.increment(files as u64);
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use apache_avro::types::Value;
+use apache_avro::{Codec, Schema as AvroSchema};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+
+use crate::avro::{record_to_avro, to_avro_schema};
+use crate::sink::format::{FileFormat, PartWriter};
+
+/// The uncompressed bytes of a data block by default
+pub const AVRO_BLOCK_SIZE: usize = 64 * 1024;
+
+const MAGIC: &[u8; 4] = b"Obj\x01";
+
+/// Parse the codec, one of `null`, `deflate` and `snappy`
+pub fn parse_codec(codec: &str) -> anyhow::Result<Codec> {
+    match codec.to_lowercase().as_str() {
+        "null" | "none" => Ok(Codec::Null),
+        "deflate" => Ok(Codec::Deflate),
+        "snappy" => Ok(Codec::Snappy),
+        _ => Err(anyhow!("unknown avro codec `{}`", codec)),
+    }
+}
+
+fn codec_name(codec: Codec) -> &'static str {
+    match codec {
+        Codec::Deflate => "deflate",
+        Codec::Snappy => "snappy",
+        _ => "null",
+    }
+}
+
+/// The avro object container files, the header contains the schema and the records are
+/// written in the compressed blocks of about `block_size` bytes
+#[derive(Clone, Debug)]
+pub struct AvroFormat {
+    avro_schema: Option<AvroSchema>,
+    codec: Codec,
+    block_size: usize,
+}
+
+impl AvroFormat {
+    pub fn new() -> Self {
+        AvroFormat {
+            avro_schema: None,
+            codec: Codec::Snappy,
+            block_size: AVRO_BLOCK_SIZE,
+        }
+    }
+
+    /// The schema of the files, the fields are mapped to the record by name. The schema is
+    /// derived from the record schema if absent
+    pub fn avro_schema(mut self, avro_schema: AvroSchema) -> Self {
+        self.avro_schema = Some(avro_schema);
+        self
+    }
+
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+}
+
+impl Default for AvroFormat {
+    fn default() -> Self {
+        AvroFormat::new()
+    }
+}
+
+impl FileFormat for AvroFormat {
+    fn suffix(&self) -> &str {
+        ".avro"
+    }
+
+    fn create_writer(&self, schema: &Schema, file: File) -> std::io::Result<Box<dyn PartWriter>> {
+        let avro_schema = self
+            .avro_schema
+            .clone()
+            .unwrap_or_else(|| to_avro_schema(schema));
+
+        let mut writer = AvroWriter {
+            schema: schema.clone(),
+            avro_schema,
+            codec: self.codec,
+            block_size: self.block_size,
+            sync_marker: rand::random(),
+            block: Vec::new(),
+            block_count: 0,
+            writer: BufWriter::new(file),
+            len: 0,
+        };
+        writer.write_header()?;
+        Ok(Box::new(writer))
+    }
+}
+
+struct AvroWriter {
+    schema: Schema,
+    avro_schema: AvroSchema,
+    codec: Codec,
+    block_size: usize,
+    sync_marker: [u8; 16],
+    /// the encoded records of the current block
+    block: Vec<u8>,
+    block_count: i64,
+    writer: BufWriter<File>,
+    /// the bytes written to the file
+    len: u64,
+}
+
+impl AvroWriter {
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.writer.write_all(buf)?;
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "avro.schema".to_string(),
+            Value::Bytes(serde_json::to_vec(&self.avro_schema)?),
+        );
+        metadata.insert(
+            "avro.codec".to_string(),
+            Value::Bytes(codec_name(self.codec).as_bytes().to_vec()),
+        );
+        let metadata = encode(
+            &AvroSchema::Map(Box::new(AvroSchema::Bytes)),
+            Value::Map(metadata),
+        )?;
+
+        self.write_all(MAGIC)?;
+        self.write_all(metadata.as_slice())?;
+        let sync_marker = self.sync_marker;
+        self.write_all(&sync_marker)
+    }
+
+    /// Write the block: the count of the records, the size of the compressed data, the data
+    /// and the sync marker
+    fn write_block(&mut self) -> std::io::Result<()> {
+        if self.block_count == 0 {
+            return Ok(());
+        }
+
+        let mut data = std::mem::take(&mut self.block);
+        self.codec.compress(&mut data).map_err(to_io_error)?;
+
+        let count = encode(&AvroSchema::Long, Value::Long(self.block_count))?;
+        let size = encode(&AvroSchema::Long, Value::Long(data.len() as i64))?;
+        self.write_all(count.as_slice())?;
+        self.write_all(size.as_slice())?;
+        self.write_all(data.as_slice())?;
+        let sync_marker = self.sync_marker;
+        self.write_all(&sync_marker)?;
+
+        self.block_count = 0;
+        Ok(())
+    }
+}
+
+impl PartWriter for AvroWriter {
+    fn write(&mut self, record: &mut Record) -> std::io::Result<()> {
+        let value = record_to_avro(record, &self.schema, &self.avro_schema).map_err(to_io_error)?;
+        let datum = encode(&self.avro_schema, value)?;
+        self.block.extend_from_slice(datum.as_slice());
+        self.block_count += 1;
+
+        if self.block.len() >= self.block_size {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.len + self.block.len() as u64
+    }
+
+    fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+        self.write_block()?;
+        let file = self.writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()
+    }
+}
+
+fn encode(schema: &AvroSchema, value: Value) -> std::io::Result<Vec<u8>> {
+    apache_avro::to_avro_datum(schema, value).map_err(to_io_error)
+}
+
+fn to_io_error<E: Into<Box<dyn std::error::Error + Send + Sync>>>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use apache_avro::Codec;
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::avro::avro_to_record;
+    use crate::sink::avro::AvroFormat;
+    use crate::sink::format::FileFormat;
+
+    #[test]
+    pub fn avro_format_test() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+            Field::new("flag", DataType::Boolean),
+        ]);
+
+        for codec in [Codec::Null, Codec::Deflate, Codec::Snappy] {
+            let path = std::env::temp_dir().join(format!(
+                "rlink-avro-{}-{:?}.avro",
+                std::process::id(),
+                codec
+            ));
+            let file = File::create(path.as_path()).unwrap();
+            let mut writer = AvroFormat::new()
+                .codec(codec)
+                .block_size(16)
+                .create_writer(&schema, file)
+                .unwrap();
+            for id in 0..10 {
+                let mut record = Record::new();
+                let mut record_writer = record.as_writer(schema.as_type_ids());
+                record_writer.set_i64(id).unwrap();
+                record_writer.set_str("rlink").unwrap();
+                record_writer.set_bool(id % 2 == 0).unwrap();
+                writer.write(&mut record).unwrap();
+            }
+            writer.finish().unwrap();
+
+            let reader = apache_avro::Reader::new(File::open(path.as_path()).unwrap()).unwrap();
+            let ids: Vec<i64> = reader
+                .map(|value| {
+                    let mut record = avro_to_record(&value.unwrap(), &schema).unwrap();
+                    let reader = record.as_reader(schema.as_type_ids());
+                    reader.get_i64(0).unwrap()
+                })
+                .collect();
+            assert_eq!(ids, (0..10).collect::<Vec<i64>>());
+
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{TimeZone, Utc};
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{BufferReader, Record};
+use rlink::utils::date_time::current_timestamp_millis;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// the `strftime` format of the time, e.g. `%Y-%m-%d`
+    DateTime(String),
+    /// the index of the field
+    Field(usize),
+}
+
+/// Assign the records to the bucket directories by the pattern, the `strftime` formats in the
+/// braces are replaced by the UTC time of the record and the field names in the braces by the
+/// values of the fields, e.g. `dt={%Y-%m-%d}/hour={%H}` is `dt=2024-05-01/hour=13`
+#[derive(Clone, Debug)]
+pub struct BucketAssigner {
+    schema: Schema,
+    segments: Vec<Segment>,
+    timestamp: Option<usize>,
+}
+
+impl BucketAssigner {
+    /// The time is the millis of the `timestamp_field`, or the processing time if absent
+    pub fn new(
+        schema: Schema,
+        pattern: &str,
+        timestamp_field: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let timestamp = match timestamp_field {
+            Some(name) => {
+                let (i, field) = schema
+                    .column_with_name(name)
+                    .ok_or_else(|| anyhow!("timestamp field `{}` not found in the schema", name))?;
+                if !field.is_numeric() || matches!(field.data_type(), DataType::Boolean) {
+                    return Err(anyhow!("timestamp field `{}` is not numeric", name));
+                }
+                Some(i)
+            }
+            None => None,
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+        while let Some(begin) = rest.find('{') {
+            let end = rest[begin..]
+                .find('}')
+                .map(|x| x + begin)
+                .ok_or_else(|| anyhow!("unclosed `{{` in bucket pattern `{}`", pattern))?;
+
+            let name = &rest[begin + 1..end];
+            let segment = if name.starts_with('%') {
+                // the invalid format panics on formatting
+                if StrftimeItems::new(name).any(|x| matches!(x, Item::Error)) {
+                    return Err(anyhow!(
+                        "invalid time format `{}` in bucket pattern `{}`",
+                        name,
+                        pattern
+                    ));
+                }
+                Segment::DateTime(name.to_string())
+            } else {
+                let i = schema.index_of(name).ok_or_else(|| {
+                    anyhow!("field `{}` of bucket pattern not found in the schema", name)
+                })?;
+                Segment::Field(i)
+            };
+
+            if begin > 0 {
+                segments.push(Segment::Literal(rest[..begin].to_string()));
+            }
+            segments.push(segment);
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(BucketAssigner {
+            schema,
+            segments,
+            timestamp,
+        })
+    }
+
+    /// The relative directory of the record, empty if the pattern is empty
+    pub fn bucket(&self, record: &mut Record) -> std::io::Result<String> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        let timestamp = match self.timestamp {
+            Some(i) => read_millis(&reader, i, self.schema.field(i).data_type())?,
+            None => current_timestamp_millis() as i64,
+        };
+        let date_time = Utc.timestamp_millis(timestamp);
+
+        let mut bucket = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => bucket.push_str(literal),
+                Segment::DateTime(fmt) => {
+                    bucket.push_str(date_time.format(fmt).to_string().as_str())
+                }
+                Segment::Field(i) => {
+                    let value = read_string(&reader, *i, self.schema.field(*i).data_type())?;
+                    // the value never escapes the base path
+                    bucket.push_str(
+                        value
+                            .replace(|c: char| c == '/' || c == '\\', "_")
+                            .replace("..", "_")
+                            .as_str(),
+                    );
+                }
+            }
+        }
+        Ok(bucket)
+    }
+}
+
+fn read_millis(reader: &BufferReader, i: usize, data_type: &DataType) -> std::io::Result<i64> {
+    let value = match data_type {
+        DataType::Int8 => reader.get_i8(i)? as i64,
+        DataType::UInt8 => reader.get_u8(i)? as i64,
+        DataType::Int16 => reader.get_i16(i)? as i64,
+        DataType::UInt16 => reader.get_u16(i)? as i64,
+        DataType::Int32 => reader.get_i32(i)? as i64,
+        DataType::UInt32 => reader.get_u32(i)? as i64,
+        DataType::Int64 => reader.get_i64(i)?,
+        DataType::UInt64 => reader.get_u64(i)? as i64,
+        DataType::Float32 => reader.get_f32(i)? as i64,
+        DataType::Float64 => reader.get_f64(i)? as i64,
+        DataType::Boolean | DataType::Binary | DataType::String => unreachable!(),
+    };
+    Ok(value)
+}
+
+fn read_string(reader: &BufferReader, i: usize, data_type: &DataType) -> std::io::Result<String> {
+    let value = match data_type {
+        DataType::Boolean => reader.get_bool(i)?.to_string(),
+        DataType::Int8 => reader.get_i8(i)?.to_string(),
+        DataType::UInt8 => reader.get_u8(i)?.to_string(),
+        DataType::Int16 => reader.get_i16(i)?.to_string(),
+        DataType::UInt16 => reader.get_u16(i)?.to_string(),
+        DataType::Int32 => reader.get_i32(i)?.to_string(),
+        DataType::UInt32 => reader.get_u32(i)?.to_string(),
+        DataType::Int64 => reader.get_i64(i)?.to_string(),
+        DataType::UInt64 => reader.get_u64(i)?.to_string(),
+        DataType::Float32 => reader.get_f32(i)?.to_string(),
+        DataType::Float64 => reader.get_f64(i)?.to_string(),
+        DataType::Binary => String::from_utf8_lossy(reader.get_binary(i)?).to_string(),
+        DataType::String => reader.get_str(i)?.to_string(),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::bucket::BucketAssigner;
+
+    #[test]
+    pub fn bucket_assigner_test() {
+        let schema = Schema::new(vec![
+            Field::new("ts", DataType::Int64),
+            Field::new("region", DataType::String),
+        ]);
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        // 2024-05-01T13:30:00Z
+        writer.set_i64(1714570200000).unwrap();
+        writer.set_str("us/east").unwrap();
+
+        let assigner =
+            BucketAssigner::new(schema.clone(), "dt={%Y-%m-%d}/hour={%H}", Some("ts")).unwrap();
+        assert_eq!(
+            assigner.bucket(&mut record).unwrap(),
+            "dt=2024-05-01/hour=13"
+        );
+
+        let assigner =
+            BucketAssigner::new(schema.clone(), "region={region}/{%Y%m%d}", Some("ts")).unwrap();
+        assert_eq!(
+            assigner.bucket(&mut record).unwrap(),
+            "region=us_east/20240501"
+        );
+
+        let assigner = BucketAssigner::new(schema.clone(), "", None).unwrap();
+        assert_eq!(assigner.bucket(&mut record).unwrap(), "");
+
+        assert!(BucketAssigner::new(schema.clone(), "dt={%Y-%m-%d", None).is_err());
+        assert!(BucketAssigner::new(schema.clone(), "{country}", None).is_err());
+        assert!(BucketAssigner::new(schema, "{%Y}", Some("region")).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use apache_avro::Schema as AvroSchema;
+use rlink::core::properties::Properties;
+
+use crate::sink::avro::{parse_codec, AvroFormat};
+use crate::sink::format::{CsvFormat, FileFormat, JsonLinesFormat};
+use crate::sink::parquet::{parse_compression, ParquetFormat};
+use crate::{
+    FileOutputFormat, AVRO_CODEC, AVRO_SCHEMA, BUCKET, CSV_DELIMITER, FILE, FORMAT,
+    PARQUET_COMPRESSION, PARQUET_ROW_GROUP_SIZE, PART_SIZE, PATH, ROLLOVER_INTERVAL, STAGING_DIR,
+    STORE, TIMESTAMP_FIELD,
+};
+
+pub struct FileOutputFormatBuilder {
+    base_path: String,
+    format: Box<dyn FileFormat>,
+    store_options: HashMap<String, String>,
+    staging_dir: Option<PathBuf>,
+    bucket_pattern: Option<String>,
+    timestamp_field: Option<String>,
+    part_size: Option<u64>,
+    rollover_interval: Option<Duration>,
+}
+
+impl FileOutputFormatBuilder {
+    /// Write the json lines files to the `base_path` by default, the `base_path` is a local
+    /// directory or an object store url, e.g. `s3://bucket/path`
+    pub fn new(base_path: &str) -> Self {
+        FileOutputFormatBuilder {
+            base_path: base_path.to_string(),
+            format: Box::new(JsonLinesFormat::default()),
+            store_options: HashMap::new(),
+            staging_dir: None,
+            bucket_pattern: None,
+            timestamp_field: None,
+            part_size: None,
+            rollover_interval: None,
+        }
+    }
+
+    pub fn format(mut self, format: Box<dyn FileFormat>) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The option of the object store, e.g. `aws.region`, see `rlink::storage::object_storage`
+    pub fn store_option(mut self, key: &str, value: &str) -> Self {
+        self.store_options
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// The local directory of the in-progress files of the object store, the temp directory
+    /// by default
+    pub fn staging_dir(mut self, staging_dir: &str) -> Self {
+        self.staging_dir = Some(PathBuf::from(staging_dir));
+        self
+    }
+
+    /// The pattern of the bucket directories, e.g. `dt={%Y-%m-%d}/hour={%H}`
+    pub fn bucket_pattern(mut self, bucket_pattern: &str) -> Self {
+        self.bucket_pattern = Some(bucket_pattern.to_string());
+        self
+    }
+
+    /// The millis of the record for the time of the bucket pattern, the processing time if
+    /// absent
+    pub fn timestamp_field(mut self, timestamp_field: &str) -> Self {
+        self.timestamp_field = Some(timestamp_field.to_string());
+        self
+    }
+
+    /// Roll the part file once it exceeds `part_size` bytes
+    pub fn part_size(mut self, part_size: u64) -> Self {
+        self.part_size = Some(part_size);
+        self
+    }
+
+    /// Roll the part file once it's opened longer than the `rollover_interval`
+    pub fn rollover_interval(mut self, rollover_interval: Duration) -> Self {
+        self.rollover_interval = Some(rollover_interval);
+        self
+    }
+
+    pub fn build(self) -> FileOutputFormat {
+        info!("build file sink with: {:?}", &self);
+
+        let mut output_format =
+            FileOutputFormat::new(self.base_path, self.format).store_options(self.store_options);
+
+        if let Some(staging_dir) = self.staging_dir {
+            output_format = output_format.staging_dir(staging_dir);
+        }
+
+        if let Some(bucket_pattern) = self.bucket_pattern {
+            output_format = output_format.bucket_pattern(bucket_pattern);
+        }
+        if let Some(timestamp_field) = self.timestamp_field {
+            output_format = output_format.timestamp_field(timestamp_field);
+        }
+        if let Some(part_size) = self.part_size {
+            output_format = output_format.part_size(part_size);
+        }
+        if let Some(rollover_interval) = self.rollover_interval {
+            output_format = output_format.rollover_interval(rollover_interval);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for FileOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileOutputFormatBuilder")
+            .field("base_path", &self.base_path)
+            .field("format", &self.format.suffix())
+            .field(
+                "store_options",
+                &self.store_options.keys().collect::<Vec<&String>>(),
+            )
+            .field("staging_dir", &self.staging_dir)
+            .field("bucket_pattern", &self.bucket_pattern)
+            .field("timestamp_field", &self.timestamp_field)
+            .field("part_size", &self.part_size)
+            .field("rollover_interval", &self.rollover_interval)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for FileOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let file_properties = properties.to_sub_properties(FILE);
+        let base_path = file_properties.get_string(PATH)?;
+
+        let mut builder = FileOutputFormatBuilder::new(base_path.as_str());
+
+        let store_properties = file_properties.to_sub_properties(STORE);
+        for (key, value) in store_properties.as_map() {
+            builder = builder.store_option(key.as_str(), value.as_str());
+        }
+        if let Ok(staging_dir) = file_properties.get_string(STAGING_DIR) {
+            builder = builder.staging_dir(staging_dir.as_str());
+        }
+
+        if let Ok(format) = properties.get_string(FORMAT) {
+            let format: Box<dyn FileFormat> = match format.to_lowercase().as_str() {
+                "json" => Box::new(JsonLinesFormat::default()),
+                "csv" => {
+                    let delimiter = properties
+                        .get_string(CSV_DELIMITER)
+                        .ok()
+                        .and_then(|x| x.chars().next())
+                        .unwrap_or(',');
+                    Box::new(CsvFormat::new(delimiter))
+                }
+                "parquet" => {
+                    let mut format = ParquetFormat::new();
+                    if let Ok(compression) = properties.get_string(PARQUET_COMPRESSION) {
+                        format = format.compression(parse_compression(compression.as_str())?);
+                    }
+                    if let Ok(row_group_size) = properties.get_usize(PARQUET_ROW_GROUP_SIZE) {
+                        format = format.row_group_size(row_group_size);
+                    }
+                    Box::new(format)
+                }
+                "avro" => {
+                    let mut format = AvroFormat::new();
+                    if let Ok(avro_schema) = properties.get_string(AVRO_SCHEMA) {
+                        format = format.avro_schema(AvroSchema::parse_str(avro_schema.as_str())?);
+                    }
+                    if let Ok(codec) = properties.get_string(AVRO_CODEC) {
+                        format = format.codec(parse_codec(codec.as_str())?);
+                    }
+                    Box::new(format)
+                }
+                _ => return Err(anyhow!("unknown file format `{}`", format)),
+            };
+            builder = builder.format(format);
+        }
+        if let Ok(bucket_pattern) = properties.get_string(BUCKET) {
+            builder = builder.bucket_pattern(bucket_pattern.as_str());
+        }
+        if let Ok(timestamp_field) = properties.get_string(TIMESTAMP_FIELD) {
+            builder = builder.timestamp_field(timestamp_field.as_str());
+        }
+        if let Ok(part_size) = properties.get_u64(PART_SIZE) {
+            builder = builder.part_size(part_size);
+        }
+        if let Ok(rollover_interval) = properties.get_duration(ROLLOVER_INTERVAL) {
+            builder = builder.rollover_interval(rollover_interval);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{BufferReader, Record};
+use serde_json::{Map, Value};
+
+/// The writer of a part file
+pub trait PartWriter: Send + Sync {
+    fn write(&mut self, record: &mut Record) -> std::io::Result<()>;
+
+    /// The bytes written to the part file
+    fn bytes_written(&self) -> u64;
+
+    /// Flush the buffered data and sync the file, the file is complete after this
+    fn finish(self: Box<Self>) -> std::io::Result<()>;
+}
+
+/// The encoding of the part files
+pub trait FileFormat: Send + Sync {
+    /// The suffix of the finished part files, e.g. `.json`
+    fn suffix(&self) -> &str;
+
+    fn create_writer(&self, schema: &Schema, file: File) -> std::io::Result<Box<dyn PartWriter>>;
+}
+
+/// The buffered file writer counting the bytes
+struct CountingWriter {
+    inner: BufWriter<File>,
+    len: u64,
+}
+
+impl CountingWriter {
+    fn new(file: File) -> Self {
+        CountingWriter {
+            inner: BufWriter::new(file),
+            len: 0,
+        }
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.inner.write_all(buf)?;
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        let file = self.inner.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()
+    }
+}
+
+/// A json object per line with the field names of the schema
+#[derive(Clone, Debug, Default)]
+pub struct JsonLinesFormat {}
+
+impl FileFormat for JsonLinesFormat {
+    fn suffix(&self) -> &str {
+        ".json"
+    }
+
+    fn create_writer(&self, schema: &Schema, file: File) -> std::io::Result<Box<dyn PartWriter>> {
+        Ok(Box::new(JsonLinesWriter {
+            schema: schema.clone(),
+            writer: CountingWriter::new(file),
+            line: Vec::new(),
+        }))
+    }
+}
+
+struct JsonLinesWriter {
+    schema: Schema,
+    writer: CountingWriter,
+    line: Vec<u8>,
+}
+
+impl PartWriter for JsonLinesWriter {
+    fn write(&mut self, record: &mut Record) -> std::io::Result<()> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        let mut row = Map::with_capacity(self.schema.fields().len());
+        for (i, field) in self.schema.fields().iter().enumerate() {
+            row.insert(
+                field.name().to_string(),
+                read_json(&reader, i, field.data_type())?,
+            );
+        }
+
+        self.line.clear();
+        serde_json::to_writer(&mut self.line, &row)?;
+        self.line.push(b'\n');
+        self.writer.write_all(self.line.as_slice())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.writer.len
+    }
+
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        self.writer.finish()
+    }
+}
+
+/// The delimited values without the header, the values with the delimiter, the quote or the
+/// line break are quoted
+#[derive(Clone, Debug)]
+pub struct CsvFormat {
+    delimiter: char,
+}
+
+impl CsvFormat {
+    pub fn new(delimiter: char) -> Self {
+        CsvFormat { delimiter }
+    }
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        CsvFormat::new(',')
+    }
+}
+
+impl FileFormat for CsvFormat {
+    fn suffix(&self) -> &str {
+        ".csv"
+    }
+
+    fn create_writer(&self, schema: &Schema, file: File) -> std::io::Result<Box<dyn PartWriter>> {
+        Ok(Box::new(CsvWriter {
+            schema: schema.clone(),
+            delimiter: self.delimiter,
+            writer: CountingWriter::new(file),
+            line: String::new(),
+        }))
+    }
+}
+
+struct CsvWriter {
+    schema: Schema,
+    delimiter: char,
+    writer: CountingWriter,
+    line: String,
+}
+
+impl PartWriter for CsvWriter {
+    fn write(&mut self, record: &mut Record) -> std::io::Result<()> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        self.line.clear();
+        for (i, field) in self.schema.fields().iter().enumerate() {
+            if i > 0 {
+                self.line.push(self.delimiter);
+            }
+            let value = match read_json(&reader, i, field.data_type())? {
+                Value::String(s) => s,
+                value => value.to_string(),
+            };
+            if value.contains(|c| c == self.delimiter || c == '"' || c == '\n' || c == '\r') {
+                self.line.push('"');
+                self.line.push_str(value.replace('"', "\"\"").as_str());
+                self.line.push('"');
+            } else {
+                self.line.push_str(value.as_str());
+            }
+        }
+        self.line.push('\n');
+        self.writer.write_all(self.line.as_bytes())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.writer.len
+    }
+
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        self.writer.finish()
+    }
+}
+
+fn read_json(reader: &BufferReader, i: usize, data_type: &DataType) -> std::io::Result<Value> {
+    let value = match data_type {
+        DataType::Boolean => Value::from(reader.get_bool(i)?),
+        DataType::Int8 => Value::from(reader.get_i8(i)?),
+        DataType::UInt8 => Value::from(reader.get_u8(i)?),
+        DataType::Int16 => Value::from(reader.get_i16(i)?),
+        DataType::UInt16 => Value::from(reader.get_u16(i)?),
+        DataType::Int32 => Value::from(reader.get_i32(i)?),
+        DataType::UInt32 => Value::from(reader.get_u32(i)?),
+        DataType::Int64 => Value::from(reader.get_i64(i)?),
+        DataType::UInt64 => Value::from(reader.get_u64(i)?),
+        DataType::Float32 => Value::from(reader.get_f32(i)?),
+        DataType::Float64 => Value::from(reader.get_f64(i)?),
+        DataType::Binary => Value::from(String::from_utf8_lossy(reader.get_binary(i)?).to_string()),
+        DataType::String => Value::from(reader.get_str(i)?),
+    };
+    Ok(value)
+}
@@ -0,0 +1,9 @@
+pub mod avro;
+pub mod bucket;
+pub mod builder;
+pub mod format;
+pub mod output_format;
+pub mod parquet;
+
+pub(crate) mod part;
+pub(crate) mod store;
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::utils::date_time::current_timestamp_millis;
+
+use crate::metrics::SinkMetrics;
+use crate::sink::bucket::BucketAssigner;
+use crate::sink::format::FileFormat;
+use crate::sink::part::{InProgressPart, PartFile};
+use crate::sink::store::{create_store, PartStore};
+use crate::{SINK_PART_SIZE, SINK_ROLLOVER_INTERVAL_MILLIS};
+
+/// The state of the sink in the checkpoint
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct FileSinkState {
+    /// the finished paths of the pending files of the checkpoint
+    pending: Vec<PathBuf>,
+    next_part: u64,
+}
+
+/// Write the records to the part files in the bucket directories of the `base_path`, the
+/// `base_path` is a local directory or an object store url, e.g. `s3://bucket/path`.
+///
+/// The part file is written as in-progress, rolled to pending by the size, the time and on
+/// every checkpoint, and moved to finished once the checkpoint of it completes. Only the
+/// finished files are visible to the readers, so the output is exactly-once.
+///
+/// The pending files of a checkpoint are finished when the next checkpoint is taken, or on
+/// the restore from the checkpoint.
+#[derive(NamedFunction)]
+pub struct FileOutputFormat {
+    base_path: String,
+    store_options: HashMap<String, String>,
+    staging_dir: PathBuf,
+    bucket_pattern: String,
+    timestamp_field: Option<String>,
+    format: Box<dyn FileFormat>,
+    part_size: u64,
+    rollover_interval: Duration,
+
+    schema: Schema,
+    store: Option<Box<dyn PartStore>>,
+    /// the directory of the buckets in the store
+    root: PathBuf,
+    bucket_assigner: Option<BucketAssigner>,
+    task_number: u16,
+    next_part: u64,
+    in_progress: HashMap<String, InProgressPart>,
+    /// the files rolled since the last checkpoint
+    pending: Vec<PartFile>,
+    /// the files of the last checkpoint, finished once the checkpoint completes
+    committable: Vec<PartFile>,
+    metrics: Option<SinkMetrics>,
+}
+
+impl FileOutputFormat {
+    pub fn new(base_path: String, format: Box<dyn FileFormat>) -> Self {
+        FileOutputFormat {
+            base_path,
+            store_options: HashMap::new(),
+            staging_dir: std::env::temp_dir().join("rlink-file-sink"),
+            bucket_pattern: "".to_string(),
+            timestamp_field: None,
+            format,
+            part_size: SINK_PART_SIZE,
+            rollover_interval: Duration::from_millis(SINK_ROLLOVER_INTERVAL_MILLIS),
+            schema: Schema::empty(),
+            store: None,
+            root: PathBuf::new(),
+            bucket_assigner: None,
+            task_number: 0,
+            next_part: 0,
+            in_progress: HashMap::new(),
+            pending: vec![],
+            committable: vec![],
+            metrics: None,
+        }
+    }
+
+    /// The credentials and the retry policy of the object store, see
+    /// `rlink::storage::object_storage`
+    pub fn store_options(mut self, store_options: HashMap<String, String>) -> Self {
+        self.store_options = store_options;
+        self
+    }
+
+    /// The local directory of the in-progress files of the object store
+    pub fn staging_dir(mut self, staging_dir: PathBuf) -> Self {
+        self.staging_dir = staging_dir;
+        self
+    }
+
+    pub fn bucket_pattern(mut self, bucket_pattern: String) -> Self {
+        self.bucket_pattern = bucket_pattern;
+        self
+    }
+
+    pub fn timestamp_field(mut self, timestamp_field: String) -> Self {
+        self.timestamp_field = Some(timestamp_field);
+        self
+    }
+
+    pub fn part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size;
+        self
+    }
+
+    pub fn rollover_interval(mut self, rollover_interval: Duration) -> Self {
+        self.rollover_interval = rollover_interval;
+        self
+    }
+
+    fn create_part(&mut self, bucket: &str) -> std::io::Result<InProgressPart> {
+        let name = format!(
+            "part-{}-{}{}",
+            self.task_number,
+            self.next_part,
+            self.format.suffix()
+        );
+        self.next_part += 1;
+
+        let part_file = PartFile::new(&self.root.join(bucket), name.as_str());
+        let path = self.store.as_ref().unwrap().in_progress_path(&part_file);
+        InProgressPart::create(
+            part_file,
+            path,
+            self.format.as_ref(),
+            &self.schema,
+            current_timestamp_millis(),
+        )
+    }
+
+    async fn roll(&mut self, part: InProgressPart) -> anyhow::Result<()> {
+        let bytes = part.bytes_written();
+        let part_file = part.close()?;
+        self.store.as_ref().unwrap().stage(&part_file).await?;
+        self.metrics.as_ref().unwrap().rolled(bytes);
+        self.pending.push(part_file);
+        Ok(())
+    }
+
+    /// Roll the files exceeding the size or the time
+    async fn roll_if_needed(&mut self, bucket: &str) -> anyhow::Result<()> {
+        let now = current_timestamp_millis();
+        let rollover_interval = self.rollover_interval.as_millis() as u64;
+        let need_roll = match self.in_progress.get(bucket) {
+            Some(part) => {
+                part.bytes_written() >= self.part_size
+                    || now.saturating_sub(part.created_at()) >= rollover_interval
+            }
+            None => false,
+        };
+        if need_roll {
+            let part = self.in_progress.remove(bucket).unwrap();
+            self.roll(part).await?;
+        }
+        Ok(())
+    }
+
+    async fn roll_all(&mut self) -> anyhow::Result<()> {
+        let parts: Vec<InProgressPart> = self.in_progress.drain().map(|(_, part)| part).collect();
+        for part in parts {
+            self.roll(part).await?;
+        }
+        Ok(())
+    }
+
+    async fn commit(&mut self, part_files: &[PartFile]) -> anyhow::Result<()> {
+        let store = self.store.as_ref().unwrap();
+        for part_file in part_files {
+            store.commit(part_file).await?;
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.finished(part_files.len());
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, element: Element) -> anyhow::Result<()> {
+        let mut record = element.into_record();
+        let bucket = self.bucket_assigner.as_ref().unwrap().bucket(&mut record)?;
+
+        self.roll_if_needed(bucket.as_str()).await?;
+        if !self.in_progress.contains_key(&bucket) {
+            let part = self.create_part(bucket.as_str())?;
+            self.in_progress.insert(bucket.clone(), part);
+        }
+
+        self.in_progress
+            .get_mut(&bucket)
+            .unwrap()
+            .write(&mut record)?;
+        self.metrics.as_ref().unwrap().written();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputFormat for FileOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.clone().into();
+        self.bucket_assigner = Some(BucketAssigner::new(
+            self.schema.clone(),
+            self.bucket_pattern.as_str(),
+            self.timestamp_field.as_deref(),
+        )?);
+        self.task_number = context.task_id.task_number();
+
+        let staging_dir = self.staging_dir.join(context.application_id.as_str());
+        let (store, root) =
+            create_store(self.base_path.as_str(), &self.store_options, staging_dir)?;
+        self.store = Some(store);
+        self.root = root;
+        self.metrics = Some(SinkMetrics::new(context.task_id.to_tags()));
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        self.write(element).await.expect("write part file error");
+    }
+
+    /// All files are finished at the end of the bounded input
+    async fn close(&mut self) -> core::Result<()> {
+        self.roll_all().await?;
+
+        let mut part_files = std::mem::take(&mut self.committable);
+        part_files.append(&mut self.pending);
+        self.commit(part_files.as_slice()).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for FileOutputFormat {
+    /// Finish the pending files of the restored checkpoint, the in-progress and the pending
+    /// files written after the checkpoint are left hidden and the records are replayed
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        let state = match handle {
+            Some(handle) if !handle.handle.is_empty() => {
+                serde_json::from_str::<FileSinkState>(handle.handle.as_str())
+                    .expect("parse file sink state error")
+            }
+            _ => return,
+        };
+        info!(
+            "restore file sink from checkpoint({:?}), pending files: {}",
+            context.checkpoint_id,
+            state.pending.len()
+        );
+
+        let part_files: Vec<PartFile> = state
+            .pending
+            .into_iter()
+            .map(PartFile::from_finished)
+            .collect();
+        self.commit(part_files.as_slice())
+            .await
+            .expect("finish the pending files error");
+        self.next_part = state.next_part;
+    }
+
+    /// Roll all in-progress files to pending, and finish the pending files of the last
+    /// checkpoint which must be completed when the barrier of the next arrives
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        if let Err(e) = self.roll_all().await {
+            panic!(
+                "roll part files on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+
+        let committable = std::mem::take(&mut self.committable);
+        if let Err(e) = self.commit(committable.as_slice()).await {
+            panic!(
+                "finish part files on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+        self.committable = std::mem::take(&mut self.pending);
+
+        let state = FileSinkState {
+            pending: self
+                .committable
+                .iter()
+                .map(|x| x.finished().to_path_buf())
+                .collect(),
+            next_part: self.next_part,
+        };
+        Some(CheckpointHandle {
+            handle: serde_json::to_string(&state).unwrap(),
+        })
+    }
+}
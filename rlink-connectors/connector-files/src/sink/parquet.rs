@@ -0,0 +1,398 @@
+use std::fs::File;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
+    Int64Array, Int8Array, StringArray, UInt16Array, UInt32Array, UInt64Array, UInt8Array,
+};
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{BufferReader, Record};
+
+use crate::sink::format::{FileFormat, PartWriter};
+
+/// The rows of a row group by default
+pub const PARQUET_ROW_GROUP_SIZE: usize = 128 * 1024;
+
+/// Parse the compression codec, one of `uncompressed`, `snappy`, `gzip`, `lz4` and `zstd`
+pub fn parse_compression(codec: &str) -> anyhow::Result<Compression> {
+    let compression = match codec.to_lowercase().as_str() {
+        "uncompressed" | "none" => Compression::UNCOMPRESSED,
+        "snappy" => Compression::SNAPPY,
+        "gzip" => Compression::GZIP,
+        "lz4" => Compression::LZ4,
+        "zstd" => Compression::ZSTD,
+        _ => return Err(anyhow!("unknown parquet compression `{}`", codec)),
+    };
+    Ok(compression)
+}
+
+/// Map the record schema to the arrow schema, all fields are non-nullable
+pub fn to_arrow_schema(schema: &Schema) -> arrow::datatypes::Schema {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let data_type = match field.data_type() {
+                DataType::Boolean => arrow::datatypes::DataType::Boolean,
+                DataType::Int8 => arrow::datatypes::DataType::Int8,
+                DataType::UInt8 => arrow::datatypes::DataType::UInt8,
+                DataType::Int16 => arrow::datatypes::DataType::Int16,
+                DataType::UInt16 => arrow::datatypes::DataType::UInt16,
+                DataType::Int32 => arrow::datatypes::DataType::Int32,
+                DataType::UInt32 => arrow::datatypes::DataType::UInt32,
+                DataType::Int64 => arrow::datatypes::DataType::Int64,
+                DataType::UInt64 => arrow::datatypes::DataType::UInt64,
+                DataType::Float32 => arrow::datatypes::DataType::Float32,
+                DataType::Float64 => arrow::datatypes::DataType::Float64,
+                DataType::Binary => arrow::datatypes::DataType::Binary,
+                DataType::String => arrow::datatypes::DataType::Utf8,
+            };
+            arrow::datatypes::Field::new(field.name(), data_type, false)
+        })
+        .collect();
+    arrow::datatypes::Schema::new(fields)
+}
+
+/// The parquet files, the records are buffered in memory and written as a row group once
+/// `row_group_size` rows are buffered or the file is finished
+#[derive(Clone, Debug)]
+pub struct ParquetFormat {
+    compression: Compression,
+    row_group_size: usize,
+}
+
+impl ParquetFormat {
+    pub fn new() -> Self {
+        ParquetFormat {
+            compression: Compression::SNAPPY,
+            row_group_size: PARQUET_ROW_GROUP_SIZE,
+        }
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+}
+
+impl Default for ParquetFormat {
+    fn default() -> Self {
+        ParquetFormat::new()
+    }
+}
+
+impl FileFormat for ParquetFormat {
+    fn suffix(&self) -> &str {
+        ".parquet"
+    }
+
+    fn create_writer(&self, schema: &Schema, file: File) -> std::io::Result<Box<dyn PartWriter>> {
+        let arrow_schema: SchemaRef = Arc::new(to_arrow_schema(schema));
+        let props = WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_max_row_group_size(self.row_group_size)
+            .build();
+
+        let written = Arc::new(AtomicU64::new(0));
+        let sync_file = file.try_clone()?;
+        let output = CountingFile {
+            file,
+            written: written.clone(),
+        };
+        let writer =
+            ArrowWriter::try_new(output, arrow_schema.clone(), Some(props)).map_err(to_io_error)?;
+
+        Ok(Box::new(ParquetWriter {
+            schema: schema.clone(),
+            arrow_schema,
+            row_group_size: self.row_group_size,
+            columns: schema
+                .fields()
+                .iter()
+                .map(|x| ColumnBuffer::new(x.data_type()))
+                .collect(),
+            buffered_rows: 0,
+            buffered_bytes: 0,
+            writer: Mutex::new(writer),
+            written,
+            sync_file,
+        }))
+    }
+}
+
+/// The file counting the bytes written by the arrow writer
+struct CountingFile {
+    file: File,
+    written: Arc<AtomicU64>,
+}
+
+impl Write for CountingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.file.write(buf)?;
+        self.written.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// The values of a column in the buffered row group
+enum ColumnBuffer {
+    Boolean(Vec<bool>),
+    Int8(Vec<i8>),
+    UInt8(Vec<u8>),
+    Int16(Vec<i16>),
+    UInt16(Vec<u16>),
+    Int32(Vec<i32>),
+    UInt32(Vec<u32>),
+    Int64(Vec<i64>),
+    UInt64(Vec<u64>),
+    Float32(Vec<f32>),
+    Float64(Vec<f64>),
+    Binary(Vec<Vec<u8>>),
+    String(Vec<String>),
+}
+
+impl ColumnBuffer {
+    fn new(data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Boolean => ColumnBuffer::Boolean(vec![]),
+            DataType::Int8 => ColumnBuffer::Int8(vec![]),
+            DataType::UInt8 => ColumnBuffer::UInt8(vec![]),
+            DataType::Int16 => ColumnBuffer::Int16(vec![]),
+            DataType::UInt16 => ColumnBuffer::UInt16(vec![]),
+            DataType::Int32 => ColumnBuffer::Int32(vec![]),
+            DataType::UInt32 => ColumnBuffer::UInt32(vec![]),
+            DataType::Int64 => ColumnBuffer::Int64(vec![]),
+            DataType::UInt64 => ColumnBuffer::UInt64(vec![]),
+            DataType::Float32 => ColumnBuffer::Float32(vec![]),
+            DataType::Float64 => ColumnBuffer::Float64(vec![]),
+            DataType::Binary => ColumnBuffer::Binary(vec![]),
+            DataType::String => ColumnBuffer::String(vec![]),
+        }
+    }
+
+    /// Append the value of the field `i` and return the estimated bytes of it
+    fn push(&mut self, reader: &BufferReader, i: usize) -> std::io::Result<u64> {
+        let bytes = match self {
+            ColumnBuffer::Boolean(v) => {
+                v.push(reader.get_bool(i)?);
+                1
+            }
+            ColumnBuffer::Int8(v) => {
+                v.push(reader.get_i8(i)?);
+                1
+            }
+            ColumnBuffer::UInt8(v) => {
+                v.push(reader.get_u8(i)?);
+                1
+            }
+            ColumnBuffer::Int16(v) => {
+                v.push(reader.get_i16(i)?);
+                2
+            }
+            ColumnBuffer::UInt16(v) => {
+                v.push(reader.get_u16(i)?);
+                2
+            }
+            ColumnBuffer::Int32(v) => {
+                v.push(reader.get_i32(i)?);
+                4
+            }
+            ColumnBuffer::UInt32(v) => {
+                v.push(reader.get_u32(i)?);
+                4
+            }
+            ColumnBuffer::Int64(v) => {
+                v.push(reader.get_i64(i)?);
+                8
+            }
+            ColumnBuffer::UInt64(v) => {
+                v.push(reader.get_u64(i)?);
+                8
+            }
+            ColumnBuffer::Float32(v) => {
+                v.push(reader.get_f32(i)?);
+                4
+            }
+            ColumnBuffer::Float64(v) => {
+                v.push(reader.get_f64(i)?);
+                8
+            }
+            ColumnBuffer::Binary(v) => {
+                let value = reader.get_binary(i)?.to_vec();
+                let len = value.len() as u64;
+                v.push(value);
+                len
+            }
+            ColumnBuffer::String(v) => {
+                let value = reader.get_str(i)?.to_string();
+                let len = value.len() as u64;
+                v.push(value);
+                len
+            }
+        };
+        Ok(bytes)
+    }
+
+    /// Move the buffered values to the arrow array
+    fn take(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuffer::Boolean(v) => Arc::new(BooleanArray::from(std::mem::take(v))),
+            ColumnBuffer::Int8(v) => Arc::new(Int8Array::from(std::mem::take(v))),
+            ColumnBuffer::UInt8(v) => Arc::new(UInt8Array::from(std::mem::take(v))),
+            ColumnBuffer::Int16(v) => Arc::new(Int16Array::from(std::mem::take(v))),
+            ColumnBuffer::UInt16(v) => Arc::new(UInt16Array::from(std::mem::take(v))),
+            ColumnBuffer::Int32(v) => Arc::new(Int32Array::from(std::mem::take(v))),
+            ColumnBuffer::UInt32(v) => Arc::new(UInt32Array::from(std::mem::take(v))),
+            ColumnBuffer::Int64(v) => Arc::new(Int64Array::from(std::mem::take(v))),
+            ColumnBuffer::UInt64(v) => Arc::new(UInt64Array::from(std::mem::take(v))),
+            ColumnBuffer::Float32(v) => Arc::new(Float32Array::from(std::mem::take(v))),
+            ColumnBuffer::Float64(v) => Arc::new(Float64Array::from(std::mem::take(v))),
+            ColumnBuffer::Binary(v) => {
+                let values = std::mem::take(v);
+                Arc::new(BinaryArray::from_iter_values(values.iter()))
+            }
+            ColumnBuffer::String(v) => Arc::new(StringArray::from(std::mem::take(v))),
+        }
+    }
+}
+
+struct ParquetWriter {
+    schema: Schema,
+    arrow_schema: SchemaRef,
+    row_group_size: usize,
+    columns: Vec<ColumnBuffer>,
+    buffered_rows: usize,
+    /// the estimated bytes of the buffered rows
+    buffered_bytes: u64,
+    /// the arrow writer isn't `Sync`
+    writer: Mutex<ArrowWriter<CountingFile>>,
+    /// the bytes flushed to the file
+    written: Arc<AtomicU64>,
+    sync_file: File,
+}
+
+impl ParquetWriter {
+    /// Write the buffered rows as a row group
+    fn flush_row_group(&mut self) -> std::io::Result<()> {
+        if self.buffered_rows == 0 {
+            return Ok(());
+        }
+
+        let arrays: Vec<ArrayRef> = self.columns.iter_mut().map(|x| x.take()).collect();
+        let batch = RecordBatch::try_new(self.arrow_schema.clone(), arrays).map_err(to_io_error)?;
+
+        let writer = self.writer.get_mut().unwrap();
+        writer.write(&batch).map_err(to_io_error)?;
+        writer.flush().map_err(to_io_error)?;
+
+        self.buffered_rows = 0;
+        self.buffered_bytes = 0;
+        Ok(())
+    }
+}
+
+impl PartWriter for ParquetWriter {
+    fn write(&mut self, record: &mut Record) -> std::io::Result<()> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            self.buffered_bytes += column.push(&reader, i)?;
+        }
+        self.buffered_rows += 1;
+
+        if self.buffered_rows >= self.row_group_size {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.written.load(Ordering::Relaxed) + self.buffered_bytes
+    }
+
+    /// Write the last row group and the footer
+    fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+        self.flush_row_group()?;
+
+        let ParquetWriter {
+            writer, sync_file, ..
+        } = *self;
+        writer.into_inner().unwrap().close().map_err(to_io_error)?;
+        sync_file.sync_all()
+    }
+}
+
+fn to_io_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::format::FileFormat;
+    use crate::sink::parquet::{to_arrow_schema, ParquetFormat};
+
+    #[test]
+    pub fn parquet_format_test() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+            Field::new("score", DataType::Float64),
+        ]);
+        let arrow_schema = to_arrow_schema(&schema);
+        assert_eq!(
+            arrow_schema.field(1).data_type(),
+            &arrow::datatypes::DataType::Utf8
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("rlink-parquet-{}.parquet", std::process::id()));
+        let file = File::create(path.as_path()).unwrap();
+        let mut writer = ParquetFormat::default()
+            .row_group_size(2)
+            .create_writer(&schema, file)
+            .unwrap();
+        for id in 0..5 {
+            let mut record = Record::new();
+            let mut record_writer = record.as_writer(schema.as_type_ids());
+            record_writer.set_i64(id).unwrap();
+            record_writer.set_str("rlink").unwrap();
+            record_writer.set_f64(0.5).unwrap();
+            writer.write(&mut record).unwrap();
+        }
+        assert!(writer.bytes_written() > 0);
+        writer.finish().unwrap();
+
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(path.as_path()).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+        let rows: usize = reader
+            .build()
+            .unwrap()
+            .map(|batch| batch.unwrap().num_rows())
+            .sum();
+        assert_eq!(rows, 5);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+
+use crate::sink::format::{FileFormat, PartWriter};
+
+/// The states of the part file, the in-progress and the pending files are hidden by the `.`
+/// prefix so the readers of the directory only see the finished files
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PartFile {
+    /// the path of the finished file
+    path: PathBuf,
+}
+
+impl PartFile {
+    pub fn new(dir: &Path, name: &str) -> Self {
+        PartFile {
+            path: dir.join(name),
+        }
+    }
+
+    pub fn from_finished(path: PathBuf) -> Self {
+        PartFile { path }
+    }
+
+    fn hidden(&self, suffix: &str) -> PathBuf {
+        let name = self
+            .path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.path.with_file_name(format!(".{}{}", name, suffix))
+    }
+
+    pub fn in_progress(&self) -> PathBuf {
+        self.hidden(".inprogress")
+    }
+
+    pub fn pending(&self) -> PathBuf {
+        self.hidden(".pending")
+    }
+
+    pub fn finished(&self) -> &Path {
+        self.path.as_path()
+    }
+}
+
+/// The part file being written
+pub(crate) struct InProgressPart {
+    part_file: PartFile,
+    writer: Box<dyn PartWriter>,
+    /// millis of the creation
+    created_at: u64,
+}
+
+impl InProgressPart {
+    /// Create the in-progress part in the local `path`
+    pub fn create(
+        part_file: PartFile,
+        path: PathBuf,
+        format: &dyn FileFormat,
+        schema: &Schema,
+        created_at: u64,
+    ) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // the file left by the failed attempt is overwritten
+        let file: File = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_path())?;
+        let writer = format.create_writer(schema, file)?;
+
+        Ok(InProgressPart {
+            part_file,
+            writer,
+            created_at,
+        })
+    }
+
+    pub fn write(&mut self, record: &mut Record) -> std::io::Result<()> {
+        self.writer.write(record)
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.writer.bytes_written()
+    }
+
+    pub fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Complete the file, it's moved to pending by the `PartStore` then
+    pub fn close(self) -> std::io::Result<PartFile> {
+        self.writer.finish()?;
+        Ok(self.part_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::format::JsonLinesFormat;
+    use crate::sink::part::{InProgressPart, PartFile};
+    use crate::sink::store::{LocalPartStore, PartStore};
+
+    #[tokio::test]
+    pub async fn part_file_test() {
+        let part_file = PartFile::new(&PathBuf::from("/data/dt=2024-05-01"), "part-1-0.json");
+        assert_eq!(
+            part_file.in_progress(),
+            PathBuf::from("/data/dt=2024-05-01/.part-1-0.json.inprogress")
+        );
+        assert_eq!(
+            part_file.pending(),
+            PathBuf::from("/data/dt=2024-05-01/.part-1-0.json.pending")
+        );
+        assert_eq!(
+            part_file.finished(),
+            PathBuf::from("/data/dt=2024-05-01/part-1-0.json").as_path()
+        );
+
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+        ]);
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_i64(1).unwrap();
+        writer.set_str("rlink").unwrap();
+
+        let dir = std::env::temp_dir().join(format!("rlink-file-sink-{}", std::process::id()));
+        let part_file = PartFile::new(&dir.join("dt=2024-05-01"), "part-1-0.json");
+        let store = LocalPartStore {};
+        let mut part = InProgressPart::create(
+            part_file.clone(),
+            store.in_progress_path(&part_file),
+            &JsonLinesFormat::default(),
+            &schema,
+            0,
+        )
+        .unwrap();
+        part.write(&mut record).unwrap();
+        let pending = part.close().unwrap();
+        store.stage(&pending).await.unwrap();
+        assert!(pending.pending().exists());
+
+        store.commit(&pending).await.unwrap();
+        // commit again after the restore
+        store.commit(&pending).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(part_file.finished()).unwrap(),
+            "{\"id\":1,\"name\":\"rlink\"}\n"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use object_store::ObjectStore;
+use rlink::storage::object_storage::{self, ObjectStoreUrl};
+use tokio::io::AsyncWriteExt;
+
+use crate::sink::part::PartFile;
+
+/// The storage of the part files, the part file is written to a local file then moved to
+/// pending by `stage` and to finished by `commit`
+#[async_trait]
+pub(crate) trait PartStore: Send + Sync {
+    /// The local file of the in-progress part
+    fn in_progress_path(&self, part_file: &PartFile) -> PathBuf;
+
+    /// Move the complete in-progress file to pending
+    async fn stage(&self, part_file: &PartFile) -> anyhow::Result<()>;
+
+    /// Move the pending file to finished, committing twice is safe
+    async fn commit(&self, part_file: &PartFile) -> anyhow::Result<()>;
+}
+
+/// Create the store and the base path of the part files in it by the `base_path`, the
+/// `base_path` is a local directory or an object store url, e.g. `s3://bucket/path`
+pub(crate) fn create_store(
+    base_path: &str,
+    options: &HashMap<String, String>,
+    staging_dir: PathBuf,
+) -> anyhow::Result<(Box<dyn PartStore>, PathBuf)> {
+    if !base_path.contains("://") {
+        return Ok((Box::new(LocalPartStore {}), PathBuf::from(base_path)));
+    }
+
+    let url = ObjectStoreUrl::parse(base_path)?;
+    if url.scheme == "file" {
+        let path = PathBuf::from(format!("/{}", url.path));
+        return Ok((Box::new(LocalPartStore {}), path));
+    }
+
+    let (store, path) = object_storage::parse_url(base_path, options)?;
+    let store = ObjectPartStore { store, staging_dir };
+    Ok((Box::new(store), PathBuf::from(path.to_string())))
+}
+
+/// The part files in the local file system, the files are moved by renaming
+pub(crate) struct LocalPartStore {}
+
+#[async_trait]
+impl PartStore for LocalPartStore {
+    fn in_progress_path(&self, part_file: &PartFile) -> PathBuf {
+        part_file.in_progress()
+    }
+
+    async fn stage(&self, part_file: &PartFile) -> anyhow::Result<()> {
+        std::fs::rename(part_file.in_progress(), part_file.pending())?;
+        Ok(())
+    }
+
+    async fn commit(&self, part_file: &PartFile) -> anyhow::Result<()> {
+        let pending = part_file.pending();
+        if pending.exists() {
+            std::fs::rename(pending, part_file.finished())?;
+            Ok(())
+        } else if part_file.finished().exists() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "pending file of {:?} not found",
+                part_file.finished()
+            ))
+        }
+    }
+}
+
+/// The part files in the object store. The in-progress file is written in the local
+/// `staging_dir` and uploaded to the pending object by the multipart upload, the pending
+/// object is copied to the finished since the object store can't rename
+pub(crate) struct ObjectPartStore {
+    store: Arc<dyn ObjectStore>,
+    staging_dir: PathBuf,
+}
+
+impl ObjectPartStore {
+    async fn upload(
+        &self,
+        local: &Path,
+        location: &object_store::path::Path,
+    ) -> anyhow::Result<()> {
+        let (multipart_id, mut writer) = self.store.put_multipart(location).await?;
+        let result = async {
+            let mut file = tokio::fs::File::open(local).await?;
+            tokio::io::copy(&mut file, &mut writer).await?;
+            writer.shutdown().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            if let Err(abort_err) = self.store.abort_multipart(location, &multipart_id).await {
+                warn!(
+                    "abort multipart upload of {} error. {}",
+                    location, abort_err
+                );
+            }
+            return Err(anyhow!("upload {} error. {}", location, e));
+        }
+        Ok(())
+    }
+}
+
+fn object_path(path: &Path) -> object_store::path::Path {
+    object_store::path::Path::from(path.to_string_lossy().as_ref())
+}
+
+#[async_trait]
+impl PartStore for ObjectPartStore {
+    fn in_progress_path(&self, part_file: &PartFile) -> PathBuf {
+        self.staging_dir.join(part_file.in_progress())
+    }
+
+    async fn stage(&self, part_file: &PartFile) -> anyhow::Result<()> {
+        let local = self.in_progress_path(part_file);
+        self.upload(local.as_path(), &object_path(&part_file.pending()))
+            .await?;
+        tokio::fs::remove_file(local).await?;
+        Ok(())
+    }
+
+    async fn commit(&self, part_file: &PartFile) -> anyhow::Result<()> {
+        let pending = object_path(&part_file.pending());
+        let finished = object_path(part_file.finished());
+        match self.store.copy(&pending, &finished).await {
+            Ok(()) => {
+                self.store.delete(&pending).await?;
+                Ok(())
+            }
+            Err(object_store::Error::NotFound { .. }) => {
+                self.store.head(&finished).await?;
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
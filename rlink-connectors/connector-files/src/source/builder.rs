@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use apache_avro::Schema as AvroSchema;
+use rlink::core::data_types::Schema;
+use rlink::core::properties::Properties;
+
+use crate::source::decoder::{AvroDecoder, CsvDecoder, FileDecoder, JsonLinesDecoder};
+use crate::source::input_format::FileInputFormat;
+use crate::{
+    AVRO_SCHEMA, BUFFER_SIZE, CSV_DELIMITER, CSV_HEADER, FILE, FORMAT, MONITOR_INTERVAL, PATH,
+    STORE,
+};
+
+pub struct FileInputFormatBuilder {
+    paths: Vec<String>,
+    decoder: Option<Box<dyn FileDecoder>>,
+    buffer_size: Option<usize>,
+    store_options: HashMap<String, String>,
+    monitor_interval: Option<Duration>,
+}
+
+impl FileInputFormatBuilder {
+    /// Read the files of the `paths`, the path is a local file or directory, or an object
+    /// store url, e.g. `s3://bucket/path`. The directories are listed recursively
+    pub fn new(paths: Vec<String>) -> Self {
+        FileInputFormatBuilder {
+            paths,
+            decoder: None,
+            buffer_size: None,
+            store_options: HashMap::new(),
+            monitor_interval: None,
+        }
+    }
+
+    pub fn decoder(mut self, decoder: Box<dyn FileDecoder>) -> Self {
+        self.decoder = Some(decoder);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The option of the object store, e.g. `aws.region`, see `rlink::storage::object_storage`
+    pub fn store_option(mut self, key: &str, value: &str) -> Self {
+        self.store_options
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Scan the paths for the new files on every `monitor_interval`, the source never ends
+    pub fn monitor_interval(mut self, monitor_interval: Duration) -> Self {
+        self.monitor_interval = Some(monitor_interval);
+        self
+    }
+
+    /// The records are decoded to the `schema`, the avro files are read by default
+    pub fn build(self, schema: Schema, parallelism: u16) -> FileInputFormat {
+        info!("build file source with: {:?}", &self);
+
+        let decoder = self
+            .decoder
+            .unwrap_or_else(|| Box::new(AvroDecoder::default()));
+        let mut input_format = FileInputFormat::new(self.paths, decoder, schema, parallelism)
+            .store_options(self.store_options);
+        if let Some(monitor_interval) = self.monitor_interval {
+            input_format = input_format.monitor_interval(monitor_interval);
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            input_format = input_format.buffer_size(buffer_size);
+        }
+
+        input_format
+    }
+}
+
+impl Debug for FileInputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileInputFormatBuilder")
+            .field("paths", &self.paths)
+            .field("buffer_size", &self.buffer_size)
+            .field(
+                "store_options",
+                &self.store_options.keys().collect::<Vec<&String>>(),
+            )
+            .field("monitor_interval", &self.monitor_interval)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for FileInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let file_properties = properties.to_sub_properties(FILE);
+        let paths = file_properties
+            .get_string(PATH)?
+            .split(',')
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+            .collect();
+
+        let mut builder = FileInputFormatBuilder::new(paths);
+
+        let store_properties = file_properties.to_sub_properties(STORE);
+        for (key, value) in store_properties.as_map() {
+            builder = builder.store_option(key.as_str(), value.as_str());
+        }
+
+        if let Ok(format) = properties.get_string(FORMAT) {
+            let decoder: Box<dyn FileDecoder> = match format.to_lowercase().as_str() {
+                "avro" => {
+                    let mut decoder = AvroDecoder::new();
+                    if let Ok(avro_schema) = properties.get_string(AVRO_SCHEMA) {
+                        decoder =
+                            decoder.reader_schema(AvroSchema::parse_str(avro_schema.as_str())?);
+                    }
+                    Box::new(decoder)
+                }
+                "csv" => {
+                    let delimiter = properties
+                        .get_string(CSV_DELIMITER)
+                        .ok()
+                        .and_then(|x| x.chars().next())
+                        .unwrap_or(',');
+                    let has_header = properties.get_bool(CSV_HEADER).unwrap_or(false);
+                    Box::new(CsvDecoder::new(delimiter).has_header(has_header))
+                }
+                "json" => Box::new(JsonLinesDecoder::default()),
+                _ => return Err(anyhow!("unknown file format `{}`", format)),
+            };
+            builder = builder.decoder(decoder);
+        }
+        if let Ok(monitor_interval) = properties.get_duration(MONITOR_INTERVAL) {
+            builder = builder.monitor_interval(monitor_interval);
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// The read progress of the files assigned to the task
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FileSourceState {
+    /// the files read to the end
+    pub finished: BTreeSet<String>,
+    /// the count of the records emitted by the file being read
+    pub offsets: HashMap<String, u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FileSourceStateRecorder {
+    state: Arc<Mutex<FileSourceState>>,
+}
+
+impl FileSourceStateRecorder {
+    pub fn new() -> Self {
+        FileSourceStateRecorder::default()
+    }
+
+    /// The record at the `offset`(starts with 0) of the file is emitted
+    pub fn update(&self, path: &str, offset: u64) {
+        let mut state = self.state.lock().unwrap();
+        match state.offsets.get_mut(path) {
+            Some(last) => *last = offset + 1,
+            None => {
+                state.offsets.insert(path.to_string(), offset + 1);
+            }
+        }
+    }
+
+    pub fn finish(&self, path: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.offsets.remove(path);
+        state.finished.insert(path.to_string());
+    }
+
+    pub fn state(&self) -> FileSourceState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn snapshot(&self) -> String {
+        serde_json::to_string(&*self.state.lock().unwrap()).unwrap()
+    }
+
+    /// Merge the state of a task's snapshot, the snapshots of all tasks are merged since the
+    /// files are reassigned when the parallelism is changed. The latest offset wins if a file is
+    /// in the snapshots of several tasks, and the finished files are never read again
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let snapshot: FileSourceState = serde_json::from_str(snapshot_handle)?;
+        let mut state = self.state.lock().unwrap();
+        let FileSourceState { finished, offsets } = &mut *state;
+        finished.extend(snapshot.finished);
+        for (path, offset) in snapshot.offsets {
+            let current = offsets.entry(path).or_insert(0);
+            *current = offset.max(*current);
+        }
+        offsets.retain(|path, _| !finished.contains(path));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::FileSourceStateRecorder;
+
+    #[test]
+    pub fn state_snapshot_test() {
+        let recorder = FileSourceStateRecorder::new();
+        recorder.update("/data/a.avro", 0);
+        recorder.update("/data/a.avro", 1);
+        recorder.finish("/data/a.avro");
+        recorder.update("/data/b.avro", 9);
+        let snapshot = recorder.snapshot();
+
+        let restored = FileSourceStateRecorder::new();
+        restored.update_from_snapshot(snapshot.as_str()).unwrap();
+        let state = restored.state();
+        assert!(state.finished.contains("/data/a.avro"));
+        assert!(!state.offsets.contains_key("/data/a.avro"));
+        assert_eq!(state.offsets.get("/data/b.avro"), Some(&10));
+
+        // the stale state of the other task is merged
+        let other = FileSourceStateRecorder::new();
+        other.update("/data/a.avro", 0);
+        other.update("/data/b.avro", 3);
+        other.update("/data/c.avro", 4);
+        restored
+            .update_from_snapshot(other.snapshot().as_str())
+            .unwrap();
+        let state = restored.state();
+        assert!(!state.offsets.contains_key("/data/a.avro"));
+        assert_eq!(state.offsets.get("/data/b.avro"), Some(&10));
+        assert_eq!(state.offsets.get("/data/c.avro"), Some(&5));
+    }
+}
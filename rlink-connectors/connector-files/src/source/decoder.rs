@@ -0,0 +1,341 @@
+use std::io::{BufRead, BufReader, Read};
+
+use apache_avro::Schema as AvroSchema;
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::{BufferWriter, Record};
+use serde_json::{Map, Value};
+
+use crate::avro::avro_to_record;
+
+/// The records decoded from a file
+pub type RecordIter = Box<dyn Iterator<Item = anyhow::Result<Record>> + Send>;
+
+/// The decoding of the files read by the source
+pub trait FileDecoder: Send + Sync {
+    fn decode(&self, schema: &Schema, reader: Box<dyn Read + Send>) -> anyhow::Result<RecordIter>;
+}
+
+/// The avro object container files, the records are read by the schema embedded in the
+/// file, and resolved to the `reader_schema` if present
+#[derive(Clone, Debug, Default)]
+pub struct AvroDecoder {
+    reader_schema: Option<AvroSchema>,
+}
+
+impl AvroDecoder {
+    pub fn new() -> Self {
+        AvroDecoder {
+            reader_schema: None,
+        }
+    }
+
+    pub fn reader_schema(mut self, reader_schema: AvroSchema) -> Self {
+        self.reader_schema = Some(reader_schema);
+        self
+    }
+}
+
+impl FileDecoder for AvroDecoder {
+    fn decode(&self, schema: &Schema, reader: Box<dyn Read + Send>) -> anyhow::Result<RecordIter> {
+        let reader = apache_avro::Reader::new(BufReader::new(reader))?;
+        let schema = schema.clone();
+        let reader_schema = self.reader_schema.clone();
+
+        let iter = reader.map(move |value| {
+            let mut value = value?;
+            if let Some(reader_schema) = &reader_schema {
+                value = value.resolve(reader_schema)?;
+            }
+            avro_to_record(&value, &schema)
+        });
+        Ok(Box::new(iter))
+    }
+}
+
+/// A json object per line, the fields are mapped by name and the absent or null values are
+/// the zero values of the field types. The blank lines are skipped
+#[derive(Clone, Debug, Default)]
+pub struct JsonLinesDecoder {}
+
+impl FileDecoder for JsonLinesDecoder {
+    fn decode(&self, schema: &Schema, reader: Box<dyn Read + Send>) -> anyhow::Result<RecordIter> {
+        let schema = schema.clone();
+        let iter = BufReader::new(reader)
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(move |line| {
+                let object: Map<String, Value> = serde_json::from_str(line?.as_str())?;
+                let mut record = Record::new();
+                let mut writer = record.as_writer(schema.as_type_ids());
+                for field in schema.fields() {
+                    let value = object.get(field.name()).unwrap_or(&Value::Null);
+                    write_json(&mut writer, field, value)?;
+                }
+                Ok(record)
+            });
+        Ok(Box::new(iter))
+    }
+}
+
+/// The delimited values in the order of the schema fields, the values may be quoted by `"`
+/// with the `""` escaping
+#[derive(Clone, Debug)]
+pub struct CsvDecoder {
+    delimiter: char,
+    has_header: bool,
+}
+
+impl CsvDecoder {
+    pub fn new(delimiter: char) -> Self {
+        CsvDecoder {
+            delimiter,
+            has_header: false,
+        }
+    }
+
+    /// Skip the first line of the file
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+}
+
+impl Default for CsvDecoder {
+    fn default() -> Self {
+        CsvDecoder::new(',')
+    }
+}
+
+impl FileDecoder for CsvDecoder {
+    fn decode(&self, schema: &Schema, reader: Box<dyn Read + Send>) -> anyhow::Result<RecordIter> {
+        let schema = schema.clone();
+        let rows = CsvRows {
+            lines: BufReader::new(reader).lines(),
+            delimiter: self.delimiter,
+        };
+        let iter = rows.skip(self.has_header as usize).map(move |values| {
+            let values = values?;
+            if values.len() != schema.fields().len() {
+                return Err(anyhow!(
+                    "{} values found, but {} fields expected",
+                    values.len(),
+                    schema.fields().len()
+                ));
+            }
+
+            let mut record = Record::new();
+            let mut writer = record.as_writer(schema.as_type_ids());
+            for (field, value) in schema.fields().iter().zip(values.iter()) {
+                write_str(&mut writer, field, value.as_str())?;
+            }
+            Ok(record)
+        });
+        Ok(Box::new(iter))
+    }
+}
+
+/// The rows of the csv, a quoted value may contain the line breaks
+struct CsvRows<R: BufRead> {
+    lines: std::io::Lines<R>,
+    delimiter: char,
+}
+
+impl<R: BufRead> Iterator for CsvRows<R> {
+    type Item = anyhow::Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = match self.lines.next()? {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e.into())),
+        };
+        while line.trim().is_empty() {
+            line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+        }
+
+        let mut values = Vec::new();
+        let mut value = String::new();
+        let mut quoted = false;
+        loop {
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                if quoted {
+                    if c == '"' {
+                        if chars.peek() == Some(&'"') {
+                            chars.next();
+                            value.push('"');
+                        } else {
+                            quoted = false;
+                        }
+                    } else {
+                        value.push(c);
+                    }
+                } else if c == '"' {
+                    quoted = true;
+                } else if c == self.delimiter {
+                    values.push(std::mem::take(&mut value));
+                } else {
+                    value.push(c);
+                }
+            }
+
+            if !quoted {
+                break;
+            }
+            // the quoted value continues on the next line
+            value.push('\n');
+            line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e.into())),
+                None => return Some(Err(anyhow!("unterminated quoted value"))),
+            };
+        }
+        values.push(value);
+
+        Some(Ok(values))
+    }
+}
+
+fn write_str(writer: &mut BufferWriter, field: &Field, value: &str) -> anyhow::Result<()> {
+    let illegal = || {
+        anyhow!(
+            "`{}` can't be converted to the field `{}` of {:?}",
+            value,
+            field.name(),
+            field.data_type()
+        )
+    };
+    let number = if value.is_empty() { "0" } else { value.trim() };
+
+    match field.data_type() {
+        DataType::Boolean => {
+            writer.set_bool(number.eq_ignore_ascii_case("true") || number == "1")?
+        }
+        DataType::Int8 => writer.set_i8(number.parse().map_err(|_e| illegal())?)?,
+        DataType::UInt8 => writer.set_u8(number.parse().map_err(|_e| illegal())?)?,
+        DataType::Int16 => writer.set_i16(number.parse().map_err(|_e| illegal())?)?,
+        DataType::UInt16 => writer.set_u16(number.parse().map_err(|_e| illegal())?)?,
+        DataType::Int32 => writer.set_i32(number.parse().map_err(|_e| illegal())?)?,
+        DataType::UInt32 => writer.set_u32(number.parse().map_err(|_e| illegal())?)?,
+        DataType::Int64 => writer.set_i64(number.parse().map_err(|_e| illegal())?)?,
+        DataType::UInt64 => writer.set_u64(number.parse().map_err(|_e| illegal())?)?,
+        DataType::Float32 => writer.set_f32(number.parse().map_err(|_e| illegal())?)?,
+        DataType::Float64 => writer.set_f64(number.parse().map_err(|_e| illegal())?)?,
+        DataType::String => writer.set_str(value)?,
+        DataType::Binary => writer.set_binary(value.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn write_json(writer: &mut BufferWriter, field: &Field, value: &Value) -> anyhow::Result<()> {
+    let illegal = || {
+        anyhow!(
+            "json value {} can't be converted to the field `{}` of {:?}",
+            value,
+            field.name(),
+            field.data_type()
+        )
+    };
+    let as_i64 = || match value {
+        Value::Null => Some(0),
+        Value::Bool(v) => Some(*v as i64),
+        Value::Number(v) => v.as_i64().or_else(|| v.as_f64().map(|x| x as i64)),
+        _ => None,
+    };
+    let as_f64 = || match value {
+        Value::Number(v) => v.as_f64(),
+        _ => as_i64().map(|x| x as f64),
+    };
+
+    match field.data_type() {
+        DataType::Boolean => match value {
+            Value::Bool(v) => writer.set_bool(*v)?,
+            Value::Null => writer.set_bool(false)?,
+            _ => return Err(illegal()),
+        },
+        DataType::Int8 => writer.set_i8(as_i64().ok_or_else(illegal)? as i8)?,
+        DataType::UInt8 => writer.set_u8(as_i64().ok_or_else(illegal)? as u8)?,
+        DataType::Int16 => writer.set_i16(as_i64().ok_or_else(illegal)? as i16)?,
+        DataType::UInt16 => writer.set_u16(as_i64().ok_or_else(illegal)? as u16)?,
+        DataType::Int32 => writer.set_i32(as_i64().ok_or_else(illegal)? as i32)?,
+        DataType::UInt32 => writer.set_u32(as_i64().ok_or_else(illegal)? as u32)?,
+        DataType::Int64 => writer.set_i64(as_i64().ok_or_else(illegal)?)?,
+        DataType::UInt64 => match value {
+            Value::Number(v) if v.is_u64() => writer.set_u64(v.as_u64().unwrap())?,
+            _ => writer.set_u64(as_i64().ok_or_else(illegal)? as u64)?,
+        },
+        DataType::Float32 => writer.set_f32(as_f64().ok_or_else(illegal)? as f32)?,
+        DataType::Float64 => writer.set_f64(as_f64().ok_or_else(illegal)?)?,
+        DataType::String => match value {
+            Value::String(v) => writer.set_str(v.as_str())?,
+            Value::Null => writer.set_str("")?,
+            _ => writer.set_str(value.to_string().as_str())?,
+        },
+        DataType::Binary => match value {
+            Value::String(v) => writer.set_binary(v.as_bytes())?,
+            Value::Null => writer.set_binary(&[])?,
+            _ => writer.set_binary(value.to_string().as_bytes())?,
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+
+    use crate::source::decoder::{CsvDecoder, FileDecoder, JsonLinesDecoder};
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+            Field::new("score", DataType::Float64),
+        ])
+    }
+
+    #[test]
+    pub fn csv_decoder_test() {
+        let schema = schema();
+        let data = "id,name,score\n1,rlink,0.5\n\n2,\"a, \"\"quoted\"\"\nname\",1\n";
+        let records: Vec<_> = CsvDecoder::default()
+            .has_header(true)
+            .decode(
+                &schema,
+                Box::new(std::io::Cursor::new(data.as_bytes().to_vec())),
+            )
+            .unwrap()
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+
+        let mut record = records[1].clone();
+        let reader = record.as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_i64(0).unwrap(), 2);
+        assert_eq!(reader.get_str(1).unwrap(), "a, \"quoted\"\nname");
+        assert_eq!(reader.get_f64(2).unwrap(), 1.0);
+    }
+
+    #[test]
+    pub fn json_lines_decoder_test() {
+        let schema = schema();
+        let data = "{\"id\":1,\"name\":\"rlink\",\"score\":0.5}\n\n{\"id\":2,\"score\":null}\n";
+        let records: Vec<_> = JsonLinesDecoder::default()
+            .decode(
+                &schema,
+                Box::new(std::io::Cursor::new(data.as_bytes().to_vec())),
+            )
+            .unwrap()
+            .map(|x| x.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+
+        let mut record = records[1].clone();
+        let reader = record.as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_i64(0).unwrap(), 2);
+        assert_eq!(reader.get_str(1).unwrap(), "");
+        assert_eq!(reader.get_f64(2).unwrap(), 0.0);
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::state::{ListState, ListStateDescriptor, OperatorStateStore};
+use rlink::metrics::Tag;
+
+use crate::source::checkpoint::FileSourceStateRecorder;
+use crate::source::decoder::FileDecoder;
+use crate::source::reader::FileReaderThread;
+use crate::source::store::SourcePaths;
+use crate::source::stream::FileRecordStream;
+use crate::SOURCE_CHANNEL_SIZE;
+
+/// the union list state of the file source states of all tasks
+const FILE_SOURCE_STATES: &str = "file_source_states";
+
+/// Read the files of the `paths`, the path is a local file or directory, or an object store
+/// url, e.g. `s3://bucket/path`. The files are assigned to the tasks by the hash of the path,
+/// and the read offsets of the files are checkpointed as the union operator state, so the task
+/// resumes from the record after the checkpoint, even if the files are reassigned by the
+/// changed parallelism.
+///
+/// The paths are read once by default, or scanned on every `monitor_interval` for the new
+/// files and the source never ends
+#[derive(NamedFunction)]
+pub struct FileInputFormat {
+    paths: Vec<String>,
+    decoder: Arc<dyn FileDecoder>,
+    schema: Schema,
+    parallelism: u16,
+    buffer_size: usize,
+    store_options: HashMap<String, String>,
+    monitor_interval: Option<Duration>,
+
+    source_paths: Option<Arc<SourcePaths>>,
+    task_number: u16,
+    num_tasks: u16,
+    tags: Vec<Tag>,
+    state_recorder: FileSourceStateRecorder,
+    operator_state: OperatorStateStore,
+}
+
+impl FileInputFormat {
+    pub fn new(
+        paths: Vec<String>,
+        decoder: Box<dyn FileDecoder>,
+        schema: Schema,
+        parallelism: u16,
+    ) -> Self {
+        FileInputFormat {
+            paths,
+            decoder: Arc::from(decoder),
+            schema,
+            parallelism,
+            buffer_size: SOURCE_CHANNEL_SIZE,
+            store_options: HashMap::new(),
+            monitor_interval: None,
+            source_paths: None,
+            task_number: 0,
+            num_tasks: 1,
+            tags: vec![],
+            state_recorder: FileSourceStateRecorder::new(),
+            operator_state: OperatorStateStore::new(),
+        }
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// The credentials and the retry policy of the object store, see
+    /// `rlink::storage::object_storage`
+    pub fn store_options(mut self, store_options: HashMap<String, String>) -> Self {
+        self.store_options = store_options;
+        self
+    }
+
+    pub fn monitor_interval(mut self, monitor_interval: Duration) -> Self {
+        self.monitor_interval = Some(monitor_interval);
+        self
+    }
+
+    fn source_state(&self) -> ListState<String> {
+        self.operator_state
+            .union_list_state(&ListStateDescriptor::new(FILE_SOURCE_STATES))
+    }
+}
+
+#[async_trait]
+impl InputFormat for FileInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        self.task_number = context.task_id.task_number();
+        self.num_tasks = context.task_id.num_tasks();
+        self.source_paths = Some(Arc::new(SourcePaths::parse(
+            self.paths.as_slice(),
+            &self.store_options,
+        )?));
+
+        self.tags = context.task_id.to_tags();
+        self.operator_state.restore_from(context)?;
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("FileSource_Handover", self.tags.clone(), self.buffer_size);
+
+        FileReaderThread::new(
+            self.source_paths.clone().unwrap(),
+            self.schema.clone(),
+            self.decoder.clone(),
+            sender,
+        )
+        .assignment(self.task_number, self.num_tasks)
+        .restored(self.state_recorder.state())
+        .monitor_interval(self.monitor_interval)
+        .spawn();
+
+        Box::pin(FileRecordStream::new(receiver, self.state_recorder.clone()))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    /// the paths are read once unless they're monitored for the new files
+    fn bounded(&self) -> bool {
+        self.monitor_interval.is_none()
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for FileInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+        let snapshots = self
+            .source_state()
+            .get()
+            .expect("get file source states error");
+        for snapshot in &snapshots {
+            self.state_recorder
+                .update_from_snapshot(snapshot.as_str())
+                .expect("parse file source state error");
+        }
+        if !snapshots.is_empty() {
+            info!(
+                "restore file source from the checkpoint({:?}) of {} tasks",
+                context.checkpoint_id,
+                snapshots.len()
+            );
+        }
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let handle = self
+            .source_state()
+            .update(vec![self.state_recorder.snapshot()])
+            .and_then(|_| self.operator_state.snapshot());
+        match handle {
+            Ok(handle) => Some(handle),
+            Err(e) => panic!(
+                "snapshot file source state on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        }
+    }
+}
+
+impl InputSplitSource for FileInputFormat {}
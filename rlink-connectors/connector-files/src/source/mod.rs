@@ -0,0 +1,8 @@
+pub mod builder;
+pub mod decoder;
+pub mod input_format;
+pub mod stream;
+
+pub(crate) mod checkpoint;
+pub(crate) mod reader;
+pub(crate) mod store;
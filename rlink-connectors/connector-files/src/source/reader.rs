@@ -0,0 +1,141 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rlink::channel::sender::ChannelSender;
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+use rlink::utils::hash::hash_code;
+use tokio::runtime::Handle;
+
+use crate::source::checkpoint::FileSourceState;
+use crate::source::decoder::FileDecoder;
+use crate::source::store::SourcePaths;
+
+pub(crate) enum FileEvent {
+    Record {
+        path: Arc<String>,
+        /// the index of the record in the file
+        offset: u64,
+        record: Record,
+    },
+    /// the file is read to the end
+    Finished(Arc<String>),
+}
+
+/// Read the files assigned to the task on a thread of its own. The file is assigned by the
+/// hash of the path, so the assignment of a file is stable as the new files are discovered.
+///
+/// The finished files of the restored state are skipped, and the records before the
+/// restored offset are skipped. The paths are scanned again on every `monitor_interval`
+/// for the new files, or only once if absent.
+pub(crate) struct FileReaderThread {
+    paths: Arc<SourcePaths>,
+    schema: Schema,
+    decoder: Arc<dyn FileDecoder>,
+    sender: ChannelSender<FileEvent>,
+    handle: Handle,
+
+    task_number: u16,
+    num_tasks: u16,
+    restored: FileSourceState,
+    monitor_interval: Option<Duration>,
+}
+
+impl FileReaderThread {
+    /// Create in the runtime, the async io of the thread is run by the runtime
+    pub fn new(
+        paths: Arc<SourcePaths>,
+        schema: Schema,
+        decoder: Arc<dyn FileDecoder>,
+        sender: ChannelSender<FileEvent>,
+    ) -> Self {
+        FileReaderThread {
+            paths,
+            schema,
+            decoder,
+            sender,
+            handle: Handle::current(),
+            task_number: 0,
+            num_tasks: 1,
+            restored: FileSourceState::default(),
+            monitor_interval: None,
+        }
+    }
+
+    pub fn assignment(mut self, task_number: u16, num_tasks: u16) -> Self {
+        self.task_number = task_number;
+        self.num_tasks = num_tasks.max(1);
+        self
+    }
+
+    pub fn restored(mut self, restored: FileSourceState) -> Self {
+        self.restored = restored;
+        self
+    }
+
+    pub fn monitor_interval(mut self, monitor_interval: Option<Duration>) -> Self {
+        self.monitor_interval = monitor_interval;
+        self
+    }
+
+    pub fn spawn(self) {
+        rlink::utils::thread::spawn("file-source-reader", move || {
+            if let Err(e) = self.run() {
+                error!("read files error. {}", e);
+            }
+        });
+    }
+
+    fn send(&self, event: FileEvent) -> anyhow::Result<()> {
+        self.handle
+            .block_on(self.sender.send(event))
+            .map_err(|_e| anyhow!("the file source is closed"))
+    }
+
+    fn is_assigned(&self, file: &str) -> bool {
+        let hash = hash_code(file.as_bytes()).unwrap_or_default();
+        hash % self.num_tasks as u32 == self.task_number as u32
+    }
+
+    fn run(&self) -> anyhow::Result<()> {
+        let mut finished: BTreeSet<String> = self.restored.finished.clone();
+        loop {
+            for (index, file) in self.paths.list(&self.handle)? {
+                if finished.contains(&file) || !self.is_assigned(file.as_str()) {
+                    continue;
+                }
+
+                self.read(index, file.as_str())?;
+                finished.insert(file);
+            }
+
+            match self.monitor_interval {
+                Some(monitor_interval) => std::thread::sleep(monitor_interval),
+                None => return Ok(()),
+            }
+        }
+    }
+
+    fn read(&self, index: usize, file: &str) -> anyhow::Result<()> {
+        let path = Arc::new(file.to_string());
+        let skip = self.restored.offsets.get(file).cloned().unwrap_or_default();
+        info!("read file {} from offset {}", file, skip);
+
+        let reader = self.paths.open(&self.handle, index, file)?;
+        let records = self.decoder.decode(&self.schema, reader)?;
+        for (offset, record) in records.enumerate() {
+            let offset = offset as u64;
+            if offset < skip {
+                continue;
+            }
+            let record = record.map_err(|e| anyhow!("decode {} error. {}", file, e))?;
+            self.send(FileEvent::Record {
+                path: path.clone(),
+                offset,
+                record,
+            })?;
+        }
+        self.send(FileEvent::Finished(path))
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::{StreamExt, TryStreamExt};
+use object_store::{ObjectMeta, ObjectStore};
+use rlink::storage::object_storage::{self, ObjectStoreUrl};
+use tokio::runtime::Handle;
+use tokio_util::io::{StreamReader, SyncIoBridge};
+
+/// The file or the object is hidden if the name or any parent of it starts with `.` or `_`,
+/// e.g. the in-progress files of the file sink
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.') || name.starts_with('_')
+}
+
+/// A path of the source, a local file or directory, or an object store url
+pub(crate) enum SourcePath {
+    Local(PathBuf),
+    Object {
+        /// the `scheme://authority` of the url
+        root: String,
+        store: Arc<dyn ObjectStore>,
+        prefix: object_store::path::Path,
+    },
+}
+
+impl SourcePath {
+    pub fn parse(path: &str, options: &HashMap<String, String>) -> anyhow::Result<Self> {
+        if !path.contains("://") {
+            return Ok(SourcePath::Local(PathBuf::from(path)));
+        }
+
+        let url = ObjectStoreUrl::parse(path)?;
+        if url.scheme == "file" {
+            return Ok(SourcePath::Local(PathBuf::from(format!("/{}", url.path))));
+        }
+
+        let (store, prefix) = object_storage::parse_url(path, options)?;
+        Ok(SourcePath::Object {
+            root: url.root(),
+            store,
+            prefix,
+        })
+    }
+
+    /// List the visible files, the directories are listed recursively. The files of the
+    /// object store are the urls of the objects
+    pub fn list(&self, handle: &Handle) -> anyhow::Result<Vec<String>> {
+        match self {
+            SourcePath::Local(path) => {
+                let mut files = Vec::new();
+                visit(path.as_path(), &mut files)?;
+                Ok(files)
+            }
+            SourcePath::Object {
+                root,
+                store,
+                prefix,
+            } => {
+                // only the parts under the prefix are checked for the hidden
+                let depth = prefix.parts().count();
+                let prefix = if prefix.as_ref().is_empty() {
+                    None
+                } else {
+                    Some(prefix)
+                };
+                let objects = handle.block_on(async {
+                    let objects: Vec<ObjectMeta> = store.list(prefix).await?.try_collect().await?;
+                    Ok::<_, object_store::Error>(objects)
+                })?;
+
+                let files = objects
+                    .into_iter()
+                    .filter(|x| {
+                        !x.location
+                            .parts()
+                            .skip(depth)
+                            .any(|part| is_hidden(part.as_ref()))
+                    })
+                    .map(|x| format!("{}/{}", root, x.location))
+                    .collect();
+                Ok(files)
+            }
+        }
+    }
+
+    /// Open the file of the `list`
+    pub fn open(&self, handle: &Handle, file: &str) -> anyhow::Result<Box<dyn Read + Send>> {
+        match self {
+            SourcePath::Local(_) => Ok(Box::new(std::fs::File::open(file)?)),
+            SourcePath::Object { root, store, .. } => {
+                let location = file
+                    .strip_prefix(root.as_str())
+                    .map(|x| x.trim_start_matches('/'))
+                    .ok_or_else(|| anyhow!("{} is not in {}", file, root))?;
+                let location = object_store::path::Path::from(location);
+
+                let stream = handle
+                    .block_on(store.get(&location))?
+                    .into_stream()
+                    .map(|x| x.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+                let reader = StreamReader::new(stream.boxed());
+                Ok(Box::new(SyncIoBridge::new_with_handle(
+                    reader,
+                    handle.clone(),
+                )))
+            }
+        }
+    }
+}
+
+fn visit(path: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            if is_hidden(entry.file_name().to_string_lossy().as_ref()) {
+                continue;
+            }
+            visit(entry.path().as_path(), files)?;
+        }
+    } else {
+        files.push(path.to_string_lossy().to_string());
+    }
+    Ok(())
+}
+
+/// The paths of the source
+pub(crate) struct SourcePaths {
+    paths: Vec<SourcePath>,
+}
+
+impl SourcePaths {
+    pub fn parse(paths: &[String], options: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let paths = paths
+            .iter()
+            .map(|x| SourcePath::parse(x.as_str(), options))
+            .collect::<anyhow::Result<Vec<SourcePath>>>()?;
+        Ok(SourcePaths { paths })
+    }
+
+    /// List the files of all paths in order, the index of the path is returned with the file
+    pub fn list(&self, handle: &Handle) -> anyhow::Result<Vec<(usize, String)>> {
+        let mut files = Vec::new();
+        for (index, path) in self.paths.iter().enumerate() {
+            for file in path.list(handle)? {
+                files.push((index, file));
+            }
+        }
+        files.sort_by(|x, y| x.1.cmp(&y.1));
+        files.dedup_by(|x, y| x.1 == y.1);
+        Ok(files)
+    }
+
+    pub fn open(
+        &self,
+        handle: &Handle,
+        index: usize,
+        file: &str,
+    ) -> anyhow::Result<Box<dyn Read + Send>> {
+        self.paths[index].open(handle, file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::source::store::SourcePaths;
+
+    #[tokio::test(flavor = "multi_thread")]
+    pub async fn list_files_test() {
+        let dir = std::env::temp_dir().join(format!("rlink-file-source-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("dt=2024-05-02")).unwrap();
+        std::fs::create_dir_all(dir.join("dt=2024-05-01")).unwrap();
+        for name in [
+            "dt=2024-05-02/part-0-1.avro",
+            "dt=2024-05-01/part-0-0.avro",
+            "dt=2024-05-01/.part-0-2.avro.inprogress",
+            "_SUCCESS",
+        ] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let paths =
+            SourcePaths::parse(&[dir.to_string_lossy().to_string()], &HashMap::new()).unwrap();
+        let handle = tokio::runtime::Handle::current();
+        let files = tokio::task::spawn_blocking(move || paths.list(&handle).unwrap())
+            .await
+            .unwrap();
+        let expected: Vec<(usize, String)> =
+            ["dt=2024-05-01/part-0-0.avro", "dt=2024-05-02/part-0-1.avro"]
+                .iter()
+                .map(|x| (0, dir.join(x).to_string_lossy().to_string()))
+                .collect();
+        assert_eq!(files, expected);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
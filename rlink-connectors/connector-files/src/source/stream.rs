@@ -0,0 +1,54 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::FileSourceStateRecorder;
+use crate::source::reader::FileEvent;
+
+/// The records of the files, the read progress is recorded once the record is emitted
+pub struct FileRecordStream {
+    receiver: ChannelReceiver<FileEvent>,
+    state_recorder: FileSourceStateRecorder,
+}
+
+impl FileRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<FileEvent>,
+        state_recorder: FileSourceStateRecorder,
+    ) -> Self {
+        FileRecordStream {
+            receiver,
+            state_recorder,
+        }
+    }
+}
+
+impl ElementStream for FileRecordStream {}
+
+impl Stream for FileRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().receiver.poll_recv(cx) {
+                Poll::Ready(Some(FileEvent::Record {
+                    path,
+                    offset,
+                    record,
+                })) => {
+                    self.state_recorder.update(path.as_str(), offset);
+                    return Poll::Ready(Some(Element::Record(record)));
+                }
+                Poll::Ready(Some(FileEvent::Finished(path))) => {
+                    self.state_recorder.finish(path.as_str());
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
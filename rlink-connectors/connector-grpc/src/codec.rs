@@ -0,0 +1,124 @@
+//! The messages are passed through as the encoded protobuf bytes, so the connectors work with
+//! any message type without the generated code. The messages are converted from and to the
+//! records by the `MessageDecoder` and the `MessageEncoder`.
+
+use std::sync::Arc;
+
+use bytes::{Buf, BufMut, Bytes};
+use rlink::core::element::Record;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::Status;
+
+/// Decode the protobuf bytes of a message into a record, the message is discarded if failed
+pub type MessageDecoder = Arc<dyn Fn(&[u8]) -> anyhow::Result<Record> + Send + Sync>;
+
+/// Encode a record into the protobuf bytes of a message
+pub type MessageEncoder = Arc<dyn Fn(&mut Record) -> anyhow::Result<Vec<u8>> + Send + Sync>;
+
+/// Decode the bytes as the prost message `M`, then convert it into a record by `f`
+pub fn prost_decoder<M, F>(f: F) -> MessageDecoder
+where
+    M: prost::Message + Default,
+    F: Fn(M) -> anyhow::Result<Record> + Send + Sync + 'static,
+{
+    Arc::new(move |bytes| f(M::decode(bytes)?))
+}
+
+/// Convert a record into the prost message `M` by `f`, then encode it
+pub fn prost_encoder<M, F>(f: F) -> MessageEncoder
+where
+    M: prost::Message,
+    F: Fn(&mut Record) -> anyhow::Result<M> + Send + Sync + 'static,
+{
+    Arc::new(move |record| Ok(f(record)?.encode_to_vec()))
+}
+
+/// The codec of the encoded messages
+#[derive(Clone, Debug, Default)]
+pub struct BytesCodec {}
+
+impl Codec for BytesCodec {
+    type Encode = Bytes;
+    type Decode = Bytes;
+    type Encoder = BytesCodec;
+    type Decoder = BytesCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        BytesCodec {}
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        BytesCodec {}
+    }
+}
+
+impl Encoder for BytesCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        dst.put(item);
+        Ok(())
+    }
+}
+
+impl Decoder for BytesCodec {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        Ok(Some(src.copy_to_bytes(src.remaining())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::codec::{prost_decoder, prost_encoder};
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Event {
+        #[prost(int64, tag = "1")]
+        id: i64,
+        #[prost(string, tag = "2")]
+        name: String,
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+        ])
+    }
+
+    #[test]
+    pub fn prost_codec_test() {
+        let encoder = prost_encoder(|record: &mut Record| {
+            let reader = record.as_reader(schema().as_type_ids());
+            Ok(Event {
+                id: reader.get_i64(0)?,
+                name: reader.get_str(1)?.to_string(),
+            })
+        });
+        let decoder = prost_decoder(|event: Event| {
+            let mut record = Record::new();
+            let mut writer = record.as_writer(schema().as_type_ids());
+            writer.set_i64(event.id)?;
+            writer.set_str(event.name.as_str())?;
+            Ok(record)
+        });
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema().as_type_ids());
+        writer.set_i64(7).unwrap();
+        writer.set_str("rlink").unwrap();
+
+        let bytes = encoder(&mut record).unwrap();
+        let mut decoded = decoder(bytes.as_slice()).unwrap();
+        let reader = decoded.as_reader(schema().as_type_ids());
+        assert_eq!(reader.get_i64(0).unwrap(), 7);
+        assert_eq!(reader.get_str(1).unwrap(), "rlink");
+    }
+}
@@ -0,0 +1,35 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod codec;
+pub mod metrics;
+pub mod sink;
+pub mod source;
+
+pub use sink::builder::GrpcOutputFormatBuilder;
+pub use sink::output_format::GrpcOutputFormat;
+pub use source::builder::GrpcInputFormatBuilder;
+pub use source::input_format::GrpcInputFormat;
+
+pub const GRPC: &str = "grpc";
+/// `consume` to call the remote service, or `serve` to accept the streams of the clients
+pub const MODE: &str = "mode";
+/// the remote service, e.g. `http://127.0.0.1:50051`
+pub const ENDPOINT: &str = "endpoint";
+/// the listen address of the `serve` mode, e.g. `0.0.0.0:50051`
+pub const ADDR: &str = "addr";
+/// the method path, e.g. `/rlink.example.Events/Subscribe`
+pub const PATH: &str = "path";
+pub const MAX_RETRIES: &str = "max.retries";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 10000;
+pub const SOURCE_MAX_RETRIES: usize = 3;
+
+pub const SINK_BUFFER_SIZE: usize = 1024;
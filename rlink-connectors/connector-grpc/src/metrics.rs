@@ -0,0 +1,55 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SOURCE_MESSAGES: &str = "Grpc.Source.Messages";
+pub const SOURCE_ERRORS: &str = "Grpc.Source.Errors";
+pub const SINK_MESSAGES: &str = "Grpc.Sink.Messages";
+pub const SINK_STREAMS: &str = "Grpc.Sink.Streams";
+
+/// Metrics of the source, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// messages received
+    messages: Counter,
+    /// messages failed to decode, and the broken streams
+    errors: Counter,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            messages: register_counter(SOURCE_MESSAGES, tags.clone()),
+            errors: register_counter(SOURCE_ERRORS, tags),
+        }
+    }
+
+    pub fn received(&self) {
+        self.messages.increment(1);
+    }
+
+    pub fn error(&self) {
+        self.errors.increment(1);
+    }
+}
+
+/// Metrics of the sink, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SinkMetrics {
+    /// messages acknowledged by the remote service
+    messages: Counter,
+    /// the streams completed, one per checkpoint
+    streams: Counter,
+}
+
+impl SinkMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SinkMetrics {
+            messages: register_counter(SINK_MESSAGES, tags.clone()),
+            streams: register_counter(SINK_STREAMS, tags),
+        }
+    }
+
+    pub fn completed(&self, messages: usize) {
+        self.messages.increment(messages as u64);
+        self.streams.increment(1);
+    }
+}
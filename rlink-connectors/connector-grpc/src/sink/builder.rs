@@ -0,0 +1,61 @@
+use std::convert::TryFrom;
+
+use rlink::core::properties::Properties;
+
+use crate::codec::MessageEncoder;
+use crate::sink::output_format::GrpcOutputFormat;
+use crate::{BUFFER_SIZE, ENDPOINT, GRPC, PATH};
+
+#[derive(Debug)]
+pub struct GrpcOutputFormatBuilder {
+    endpoint: String,
+    path: String,
+    buffer_size: Option<usize>,
+}
+
+impl GrpcOutputFormatBuilder {
+    /// Push to the client streaming method `path` of the remote service at the `endpoint`,
+    /// e.g. `http://127.0.0.1:50051`
+    pub fn new(endpoint: &str, path: &str) -> Self {
+        GrpcOutputFormatBuilder {
+            endpoint: endpoint.to_string(),
+            path: path.to_string(),
+            buffer_size: None,
+        }
+    }
+
+    /// The messages buffered before the sink is blocked by the flow control
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The records are encoded by the `encoder`, see `prost_encoder`
+    pub fn build(self, encoder: MessageEncoder) -> GrpcOutputFormat {
+        info!("build grpc sink with: {:?}", &self);
+
+        let mut output_format = GrpcOutputFormat::new(self.endpoint, self.path, encoder);
+        if let Some(buffer_size) = self.buffer_size {
+            output_format = output_format.buffer_size(buffer_size);
+        }
+
+        output_format
+    }
+}
+
+impl TryFrom<Properties> for GrpcOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let grpc_properties = properties.to_sub_properties(GRPC);
+        let endpoint = grpc_properties.get_string(ENDPOINT)?;
+        let path = grpc_properties.get_string(PATH)?;
+
+        let mut builder = GrpcOutputFormatBuilder::new(endpoint.as_str(), path.as_str());
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,170 @@
+use std::convert::TryFrom;
+
+use bytes::Bytes;
+use http::uri::PathAndQuery;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::client::Grpc;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Response, Status};
+
+use crate::codec::{BytesCodec, MessageEncoder};
+use crate::metrics::SinkMetrics;
+use crate::SINK_BUFFER_SIZE;
+
+/// The client streaming call of a checkpoint
+struct StreamCall {
+    sender: mpsc::Sender<Bytes>,
+    messages: usize,
+    response: JoinHandle<Result<Response<Bytes>, Status>>,
+}
+
+/// Push the records as the protobuf messages to the client streaming method `path` of the
+/// remote service, e.g. `/rlink.example.Events/Push`.
+///
+/// The messages are sent over a call per checkpoint, the call is completed on the checkpoint
+/// and the response of the remote service acknowledges all messages of the call, so the
+/// records are delivered at least once. The messages are pulled from a channel of
+/// `buffer_size` by the http2 flow control, the sink is blocked once the channel is full.
+#[derive(NamedFunction)]
+pub struct GrpcOutputFormat {
+    endpoint: String,
+    path: String,
+    encoder: MessageEncoder,
+    buffer_size: usize,
+
+    channel: Option<Channel>,
+    call: Option<StreamCall>,
+    metrics: Option<SinkMetrics>,
+}
+
+impl GrpcOutputFormat {
+    pub fn new(endpoint: String, path: String, encoder: MessageEncoder) -> Self {
+        GrpcOutputFormat {
+            endpoint,
+            path,
+            encoder,
+            buffer_size: SINK_BUFFER_SIZE,
+            channel: None,
+            call: None,
+            metrics: None,
+        }
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size.max(1);
+        self
+    }
+
+    fn start_call(&self) -> StreamCall {
+        let (sender, receiver) = mpsc::channel(self.buffer_size);
+        let mut grpc = Grpc::new(self.channel.clone().unwrap());
+        let path = PathAndQuery::try_from(self.path.as_str()).unwrap();
+
+        let response = tokio::spawn(async move {
+            grpc.ready()
+                .await
+                .map_err(|e| Status::unavailable(e.to_string()))?;
+            grpc.client_streaming(
+                Request::new(ReceiverStream::new(receiver)),
+                path,
+                BytesCodec::default(),
+            )
+            .await
+        });
+
+        StreamCall {
+            sender,
+            messages: 0,
+            response,
+        }
+    }
+
+    /// Complete the call and wait for the response of the remote service
+    async fn finish(&mut self) -> anyhow::Result<()> {
+        if let Some(call) = self.call.take() {
+            drop(call.sender);
+            call.response
+                .await?
+                .map_err(|e| anyhow!("call {}{} error. {}", self.endpoint, self.path, e))?;
+            self.metrics.as_ref().unwrap().completed(call.messages);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputFormat for GrpcOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        PathAndQuery::try_from(self.path.as_str())
+            .map_err(|e| anyhow!("illegal grpc path {}. {}", self.path, e))?;
+        let channel = Endpoint::from_shared(self.endpoint.clone())
+            .map_err(anyhow::Error::from)?
+            .connect()
+            .await
+            .map_err(|e| anyhow!("connect {} error. {}", self.endpoint, e))?;
+
+        self.channel = Some(channel);
+        self.metrics = Some(SinkMetrics::new(context.task_id.to_tags()));
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        let mut record = element.into_record();
+        let message = match (self.encoder)(&mut record) {
+            Ok(message) => Bytes::from(message),
+            Err(e) => {
+                error!("encode record error, the record is discarded. {}", e);
+                return;
+            }
+        };
+
+        if self.call.is_none() {
+            self.call = Some(self.start_call());
+        }
+        let sent = self.call.as_ref().unwrap().sender.send(message).await;
+        if sent.is_err() {
+            // the call is terminated by the remote service
+            match self.finish().await {
+                Ok(()) => panic!("the call {}{} is closed", self.endpoint, self.path),
+                Err(e) => panic!("grpc sink error. {}", e),
+            }
+        }
+        self.call.as_mut().unwrap().messages += 1;
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.finish().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for GrpcOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The call is completed before the checkpoint completes, the next call is started by
+    /// the next record
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        if let Err(e) = self.finish().await {
+            panic!(
+                "complete the call on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+        None
+    }
+}
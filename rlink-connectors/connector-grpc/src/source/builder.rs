@@ -0,0 +1,117 @@
+use std::convert::TryFrom;
+
+use bytes::Bytes;
+use rlink::core::data_types::Schema;
+use rlink::core::properties::Properties;
+
+use crate::codec::MessageDecoder;
+use crate::source::input_format::{GrpcInputFormat, GrpcSourceMode};
+use crate::{ADDR, BUFFER_SIZE, ENDPOINT, GRPC, MAX_RETRIES, MODE, PATH};
+
+#[derive(Debug)]
+pub struct GrpcInputFormatBuilder {
+    mode: GrpcSourceMode,
+    path: String,
+    max_retries: Option<usize>,
+    buffer_size: Option<usize>,
+}
+
+impl GrpcInputFormatBuilder {
+    /// Call the method `path` of the remote service at the `endpoint`, e.g.
+    /// `http://127.0.0.1:50051`, with an empty subscription request by default
+    pub fn consume(endpoint: &str, path: &str) -> Self {
+        GrpcInputFormatBuilder::new(
+            GrpcSourceMode::Consume {
+                endpoint: endpoint.to_string(),
+                request: Bytes::new(),
+            },
+            path,
+        )
+    }
+
+    /// Serve the method `path` on the `addr`, e.g. `0.0.0.0:50051`
+    pub fn serve(addr: &str, path: &str) -> Self {
+        GrpcInputFormatBuilder::new(
+            GrpcSourceMode::Serve {
+                addr: addr.to_string(),
+            },
+            path,
+        )
+    }
+
+    fn new(mode: GrpcSourceMode, path: &str) -> Self {
+        GrpcInputFormatBuilder {
+            mode,
+            path: path.to_string(),
+            max_retries: None,
+            buffer_size: None,
+        }
+    }
+
+    /// The encoded subscription request of the `consume` mode
+    pub fn request(mut self, request: Vec<u8>) -> Self {
+        if let GrpcSourceMode::Consume { request: r, .. } = &mut self.mode {
+            *r = Bytes::from(request);
+        }
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The messages are decoded to the records of the `schema` by the `decoder`, see
+    /// `prost_decoder`
+    pub fn build(self, schema: Schema, decoder: MessageDecoder) -> GrpcInputFormat {
+        info!("build grpc source with: {:?}", &self);
+
+        let mut input_format = GrpcInputFormat::new(self.mode, self.path, decoder, schema);
+        if let Some(max_retries) = self.max_retries {
+            input_format = input_format.max_retries(max_retries);
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            input_format = input_format.buffer_size(buffer_size);
+        }
+
+        input_format
+    }
+}
+
+impl TryFrom<Properties> for GrpcInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let grpc_properties = properties.to_sub_properties(GRPC);
+        let path = grpc_properties.get_string(PATH)?;
+        let mode = grpc_properties
+            .get_string(MODE)
+            .unwrap_or_else(|_| "consume".to_string());
+
+        let mut builder = match mode.to_lowercase().as_str() {
+            "consume" => {
+                let endpoint = grpc_properties.get_string(ENDPOINT)?;
+                GrpcInputFormatBuilder::consume(endpoint.as_str(), path.as_str())
+            }
+            "serve" => {
+                let addr = grpc_properties.get_string(ADDR)?;
+                GrpcInputFormatBuilder::serve(addr.as_str(), path.as_str())
+            }
+            _ => return Err(anyhow!("unknown grpc source mode `{}`", mode)),
+        };
+
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
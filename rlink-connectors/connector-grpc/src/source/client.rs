@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use http::uri::PathAndQuery;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::element::Element;
+use tonic::client::Grpc;
+use tonic::transport::Endpoint;
+use tonic::Request;
+
+use crate::codec::{BytesCodec, MessageDecoder};
+use crate::metrics::SourceMetrics;
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Call the bidirectional streaming method of the remote service with the subscription
+/// `request`, and hand over the messages of the response stream. The request stream is kept
+/// open as long as the response stream, and the call is made again up to `max_retries` times
+/// once the stream is broken
+pub(crate) struct StreamConsumer {
+    pub endpoint: String,
+    pub path: PathAndQuery,
+    pub request: Bytes,
+    pub max_retries: usize,
+    pub decoder: MessageDecoder,
+    pub sender: ChannelSender<Element>,
+    pub metrics: SourceMetrics,
+}
+
+impl StreamConsumer {
+    pub fn spawn(self) {
+        tokio::spawn(self.run());
+    }
+
+    async fn run(self) {
+        let mut attempt = 0;
+        loop {
+            match self.consume(&mut attempt).await {
+                Ok(()) => info!("stream {}{} closed", self.endpoint, self.path),
+                Err(e) => {
+                    self.metrics.error();
+                    warn!("consume {}{} error. {}", self.endpoint, self.path, e);
+                }
+            }
+
+            if self.sender.is_closed() || attempt >= self.max_retries {
+                return;
+            }
+            attempt += 1;
+            info!(
+                "reconnect {}{} ({}/{})",
+                self.endpoint, self.path, attempt, self.max_retries
+            );
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+        }
+    }
+
+    async fn consume(&self, attempt: &mut usize) -> anyhow::Result<()> {
+        let channel = Endpoint::from_shared(self.endpoint.clone())?
+            .connect()
+            .await?;
+        let mut grpc = Grpc::new(channel);
+        grpc.ready().await?;
+
+        let requests = futures::stream::once(futures::future::ready(self.request.clone()))
+            .chain(futures::stream::pending());
+        let response = grpc
+            .streaming(
+                Request::new(requests),
+                self.path.clone(),
+                BytesCodec::default(),
+            )
+            .await?;
+        info!("stream {}{} opened", self.endpoint, self.path);
+        *attempt = 0;
+
+        let mut stream = response.into_inner();
+        while let Some(message) = stream.message().await? {
+            self.metrics.received();
+            let record = match (self.decoder)(message.as_ref()) {
+                Ok(record) => record,
+                Err(e) => {
+                    self.metrics.error();
+                    warn!("decode message error, the message is discarded. {}", e);
+                    continue;
+                }
+            };
+            self.sender
+                .send(Element::Record(record))
+                .await
+                .map_err(|_e| anyhow!("the grpc source is closed"))?;
+        }
+        Ok(())
+    }
+}
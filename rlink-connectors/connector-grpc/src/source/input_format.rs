@@ -0,0 +1,151 @@
+use std::convert::TryFrom;
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use http::uri::PathAndQuery;
+use rlink::channel::named_channel;
+use rlink::channel::utils::ChannelStream;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::metrics::Tag;
+
+use crate::codec::MessageDecoder;
+use crate::metrics::SourceMetrics;
+use crate::source::client::StreamConsumer;
+use crate::source::server::StreamServer;
+use crate::{SOURCE_CHANNEL_SIZE, SOURCE_MAX_RETRIES};
+
+#[derive(Clone, Debug)]
+pub enum GrpcSourceMode {
+    /// call the remote service at the endpoint, e.g. `http://127.0.0.1:50051`, with the
+    /// subscription request
+    Consume { endpoint: String, request: Bytes },
+    /// serve on the address, e.g. `0.0.0.0:50051`, for the clients to push the messages
+    Serve { addr: String },
+}
+
+/// Receive the protobuf messages of a bidirectional grpc stream of the method `path`, e.g.
+/// `/rlink.example.Events/Subscribe`, and decode them into the records by the `decoder`.
+///
+/// The source runs in a single task and is not checkpointed, the messages are acknowledged
+/// by the transport only
+#[derive(NamedFunction)]
+pub struct GrpcInputFormat {
+    mode: GrpcSourceMode,
+    path: String,
+    decoder: MessageDecoder,
+    schema: Schema,
+    max_retries: usize,
+    buffer_size: usize,
+
+    tags: Vec<Tag>,
+}
+
+impl GrpcInputFormat {
+    pub fn new(
+        mode: GrpcSourceMode,
+        path: String,
+        decoder: MessageDecoder,
+        schema: Schema,
+    ) -> Self {
+        GrpcInputFormat {
+            mode,
+            path,
+            decoder,
+            schema,
+            max_retries: SOURCE_MAX_RETRIES,
+            buffer_size: SOURCE_CHANNEL_SIZE,
+            tags: vec![],
+        }
+    }
+
+    /// Call the remote service again up to `max_retries` times once the stream is broken
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+}
+
+#[async_trait]
+impl InputFormat for GrpcInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        PathAndQuery::try_from(self.path.as_str())
+            .map_err(|e| anyhow!("illegal grpc path {}. {}", self.path, e))?;
+        if let GrpcSourceMode::Serve { addr } = &self.mode {
+            addr.parse::<SocketAddr>()
+                .map_err(|e| anyhow!("illegal grpc source address {}. {}", addr, e))?;
+        }
+        self.tags = context.task_id.to_tags();
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("GrpcSource_Handover", self.tags.clone(), self.buffer_size);
+        let metrics = SourceMetrics::new(self.tags.clone());
+
+        match self.mode.clone() {
+            GrpcSourceMode::Consume { endpoint, request } => StreamConsumer {
+                endpoint,
+                path: PathAndQuery::try_from(self.path.as_str()).unwrap(),
+                request,
+                max_retries: self.max_retries,
+                decoder: self.decoder.clone(),
+                sender,
+                metrics,
+            }
+            .spawn(),
+            GrpcSourceMode::Serve { addr } => StreamServer {
+                addr: addr.parse().unwrap(),
+                path: self.path.clone(),
+                decoder: self.decoder.clone(),
+                sender,
+                metrics,
+            }
+            .spawn(),
+        }
+
+        Box::pin(ChannelStream::new(receiver))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        1
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for GrpcInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+impl InputSplitSource for GrpcInputFormat {}
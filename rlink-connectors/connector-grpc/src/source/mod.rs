@@ -0,0 +1,5 @@
+pub mod builder;
+pub mod input_format;
+
+pub(crate) mod client;
+pub(crate) mod server;
@@ -0,0 +1,121 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use hyper::service::{make_service_fn, service_fn};
+use rlink::channel::sender::ChannelSender;
+use rlink::core::element::Element;
+use tonic::body::BoxBody;
+use tonic::server::{Grpc, StreamingService};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::codec::{BytesCodec, MessageDecoder};
+use crate::metrics::SourceMetrics;
+
+/// The handover of the messages pushed by the clients
+#[derive(Clone)]
+struct Ingest {
+    decoder: MessageDecoder,
+    sender: ChannelSender<Element>,
+    metrics: SourceMetrics,
+}
+
+impl Ingest {
+    /// Hand over the message, the ack is sent back once the message is accepted by the
+    /// source, so the clients are throttled by the backpressure of the source
+    async fn accept(self, message: Result<Bytes, Status>) -> Result<Bytes, Status> {
+        let message = message?;
+        self.metrics.received();
+
+        let record = (self.decoder)(message.as_ref()).map_err(|e| {
+            self.metrics.error();
+            Status::invalid_argument(format!("decode message error. {}", e))
+        })?;
+        self.sender
+            .send(Element::Record(record))
+            .await
+            .map_err(|_e| Status::unavailable("the grpc source is closed"))?;
+
+        // an empty message, e.g. `google.protobuf.Empty`
+        Ok(Bytes::new())
+    }
+}
+
+impl StreamingService<Bytes> for Ingest {
+    type Response = Bytes;
+    type ResponseStream = BoxStream<'static, Result<Bytes, Status>>;
+    type Future = BoxFuture<'static, Result<Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, request: Request<Streaming<Bytes>>) -> Self::Future {
+        let ingest = self.clone();
+        let acks = request
+            .into_inner()
+            .then(move |message| ingest.clone().accept(message))
+            .boxed();
+        Box::pin(futures::future::ready(Ok(Response::new(acks))))
+    }
+}
+
+/// Serve the bidirectional streaming method of the `path` on the `addr`, the clients push
+/// the messages and receive an empty message as the ack of each
+pub(crate) struct StreamServer {
+    pub addr: SocketAddr,
+    pub path: String,
+    pub decoder: MessageDecoder,
+    pub sender: ChannelSender<Element>,
+    pub metrics: SourceMetrics,
+}
+
+impl StreamServer {
+    pub fn spawn(self) {
+        tokio::spawn(self.run());
+    }
+
+    async fn run(self) {
+        let addr = self.addr;
+        let path = Arc::new(self.path);
+        let ingest = Ingest {
+            decoder: self.decoder,
+            sender: self.sender,
+            metrics: self.metrics,
+        };
+
+        let make_service = make_service_fn(move |_conn| {
+            let path = path.clone();
+            let ingest = ingest.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: http::Request<hyper::Body>| {
+                    let path = path.clone();
+                    let ingest = ingest.clone();
+                    async move { Ok::<_, Infallible>(handle(path.as_str(), ingest, request).await) }
+                }))
+            }
+        });
+
+        info!("serve grpc source on {}", addr);
+        if let Err(e) = hyper::Server::bind(&addr)
+            .http2_only(true)
+            .serve(make_service)
+            .await
+        {
+            error!("serve grpc source on {} error. {}", addr, e);
+        }
+    }
+}
+
+async fn handle(
+    path: &str,
+    ingest: Ingest,
+    request: http::Request<hyper::Body>,
+) -> http::Response<BoxBody> {
+    if request.uri().path() != path {
+        return Status::unimplemented(format!("{} is not found", request.uri().path())).to_http();
+    }
+
+    let mut grpc = Grpc::new(BytesCodec::default());
+    grpc.streaming(ingest, request).await
+}
@@ -0,0 +1,64 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod lookup;
+pub mod metrics;
+pub mod sink;
+pub mod source;
+
+pub use lookup::HttpLookupBackend;
+pub use sink::builder::HttpOutputFormatBuilder;
+pub use sink::output_format::HttpOutputFormat;
+pub use source::builder::HttpInputFormatBuilder;
+pub use source::input_format::HttpInputFormat;
+
+pub const HTTP: &str = "http";
+pub const URL: &str = "url";
+/// `GET` or `POST`
+pub const METHOD: &str = "method";
+/// the request headers, e.g. `http.headers.Accept`
+pub const HEADERS: &str = "headers";
+pub const BODY: &str = "body";
+/// the basic auth
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+pub const BEARER_TOKEN: &str = "bearer.token";
+
+/// the millis between the polls
+pub const POLL_INTERVAL: &str = "poll.interval";
+/// the json pointer of the records array in the response, e.g. `/data/items`
+pub const RECORDS_POINTER: &str = "records.pointer";
+/// the query parameter of the page cursor, e.g. `page_token`
+pub const CURSOR_PARAM: &str = "cursor.param";
+/// the json pointer of the next page cursor in the response, e.g. `/next_page_token`
+pub const CURSOR_POINTER: &str = "cursor.pointer";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+/// `json` for a record per request, or `ndjson` for the batches of the json lines
+pub const FORMAT: &str = "format";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const MAX_IN_FLIGHT: &str = "max.in.flight";
+pub const MAX_RETRIES: &str = "max.retries";
+/// the millis of the first retry backoff, doubled on every retry
+pub const RETRY_BACKOFF: &str = "retry.backoff";
+/// the millis of the request timeout
+pub const TIMEOUT: &str = "timeout";
+/// `log` to log the permanently failed payloads and go on, the job fails by default
+pub const DEAD_LETTER: &str = "dead.letter";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 10000;
+pub const SOURCE_POLL_INTERVAL_MILLIS: u64 = 10 * 1000;
+
+pub const SINK_BATCH_SIZE: usize = 500;
+pub const SINK_MAX_IN_FLIGHT: usize = 8;
+pub const SINK_MAX_RETRIES: usize = 5;
+pub const SINK_INITIAL_BACKOFF_MILLIS: u64 = 200;
+pub const SINK_TIMEOUT_MILLIS: u64 = 30 * 1000;
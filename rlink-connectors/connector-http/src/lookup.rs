@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use reqwest::{Client, Method, StatusCode};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+use rlink::core::function::Context;
+use rlink::functions::lookup::LookupBackend;
+use serde_json::Value;
+
+use crate::sink::json::to_json_object;
+use crate::source::decoder::{JsonResponseDecoder, ResponseDecoder};
+use crate::source::request::{HttpRequest, HttpResponse};
+use crate::SINK_TIMEOUT_MILLIS;
+
+/// The backend of the `LookupFunction`, request the record of the key from a http service.
+///
+/// The key fields are sent as the query parameters of the `GET` request, or as a json object
+/// in the body of the `POST` request. The first record decoded from the response is the
+/// looked up record, the key is not found if the status is `404` or no record is decoded
+pub struct HttpLookupBackend {
+    request: HttpRequest,
+    schema: Schema,
+    decoder: Box<dyn ResponseDecoder>,
+    timeout: Duration,
+
+    key_schema: Schema,
+    client: Option<Client>,
+}
+
+impl HttpLookupBackend {
+    pub fn new(request: HttpRequest, schema: Schema) -> Self {
+        HttpLookupBackend {
+            request,
+            schema,
+            decoder: Box::new(JsonResponseDecoder::new()),
+            timeout: Duration::from_millis(SINK_TIMEOUT_MILLIS),
+            key_schema: Schema::empty(),
+            client: None,
+        }
+    }
+
+    pub fn decoder(mut self, decoder: Box<dyn ResponseDecoder>) -> Self {
+        self.decoder = decoder;
+        self
+    }
+
+    /// The timeout of a request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl LookupBackend for HttpLookupBackend {
+    async fn open(&mut self, key_schema: &Schema, _context: &Context) -> anyhow::Result<()> {
+        let client = Client::builder().timeout(self.timeout).build()?;
+        self.client = Some(client);
+        self.key_schema = key_schema.clone();
+        Ok(())
+    }
+
+    async fn lookup(&self, mut key: Record) -> anyhow::Result<Option<Record>> {
+        let key = to_json_object(&self.key_schema, &mut key)?;
+
+        let mut request = self.request.build(self.client.as_ref().unwrap());
+        request = if self.request.method == Method::GET {
+            let params: Vec<(String, String)> = key
+                .into_iter()
+                .map(|(name, value)| match value {
+                    Value::String(value) => (name, value),
+                    value => (name, value.to_string()),
+                })
+                .collect();
+            request.query(&params)
+        } else {
+            request.json(&key)
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let headers = response.headers().clone();
+        let body = response.bytes().await?.to_vec();
+        if !status.is_success() {
+            return Err(anyhow!(
+                "lookup {} error, status {}. {}",
+                self.request.url,
+                status,
+                String::from_utf8_lossy(body.as_slice())
+            ));
+        }
+
+        let response = HttpResponse {
+            status: status.as_u16(),
+            headers,
+            body,
+        };
+        let records = self.decoder.decode(&self.schema, &response)?;
+        Ok(records.into_iter().next())
+    }
+
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use rlink::metrics::{register_counter, register_histogram, Counter, Histogram, Tag};
+
+pub const SOURCE_REQUESTS: &str = "Http.Source.Requests";
+pub const SOURCE_ERRORS: &str = "Http.Source.Errors";
+pub const SOURCE_RECORDS: &str = "Http.Source.Records";
+pub const SOURCE_LATENCY: &str = "Http.Source.Latency";
+
+/// Metrics of the source, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// pages requested successfully
+    requests: Counter,
+    /// failed polls, retried on the next interval
+    errors: Counter,
+    /// records decoded from the responses
+    records: Counter,
+    /// millis of a request
+    latency: Histogram,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            requests: register_counter(SOURCE_REQUESTS, tags.clone()),
+            errors: register_counter(SOURCE_ERRORS, tags.clone()),
+            records: register_counter(SOURCE_RECORDS, tags.clone()),
+            latency: register_histogram(SOURCE_LATENCY, tags),
+        }
+    }
+
+    pub fn requested(&self, records: usize, latency: Duration) {
+        self.requests.increment(1);
+        self.records.increment(records as u64);
+        self.latency.record(latency.as_secs_f64() * 1000f64);
+    }
+
+    pub fn error(&self) {
+        self.errors.increment(1);
+    }
+}
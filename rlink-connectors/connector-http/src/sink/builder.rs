@@ -0,0 +1,202 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::sink::dead_letter::{DeadLetterHandler, LogDeadLetterHandler};
+use crate::sink::output_format::{BodyFormat, HttpOutputFormat};
+use crate::source::request::{HttpAuth, HttpRequest};
+use crate::{
+    BATCH_SIZE, BEARER_TOKEN, DEAD_LETTER, FORMAT, HEADERS, HTTP, MAX_IN_FLIGHT, MAX_RETRIES,
+    PASSWORD, RETRY_BACKOFF, TIMEOUT, URL, USERNAME,
+};
+
+pub struct HttpOutputFormatBuilder {
+    request: HttpRequest,
+    format: BodyFormat,
+    batch_size: Option<usize>,
+    max_in_flight: Option<usize>,
+    max_retries: Option<usize>,
+    initial_backoff: Option<Duration>,
+    timeout: Option<Duration>,
+    dead_letter_handler: Option<Box<dyn DeadLetterHandler>>,
+}
+
+impl HttpOutputFormatBuilder {
+    /// `POST` a record per request to the `url` by default
+    pub fn new(url: &str) -> Self {
+        HttpOutputFormatBuilder {
+            request: HttpRequest::post(url),
+            format: BodyFormat::Json,
+            batch_size: None,
+            max_in_flight: None,
+            max_retries: None,
+            initial_backoff: None,
+            timeout: None,
+            dead_letter_handler: None,
+        }
+    }
+
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.request
+            .headers
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn basic_auth(mut self, username: &str, password: Option<&str>) -> Self {
+        self.request.auth = Some(HttpAuth::Basic {
+            username: username.to_string(),
+            password: password.map(|x| x.to_string()),
+        });
+        self
+    }
+
+    pub fn bearer_auth(mut self, token: &str) -> Self {
+        self.request.auth = Some(HttpAuth::Bearer(token.to_string()));
+        self
+    }
+
+    pub fn format(mut self, format: BodyFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The records per request of the `NdJson` format
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Retry the `5xx`, `429` and the connection errors up to `max_retries` times
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = Some(initial_backoff);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn dead_letter_handler(mut self, dead_letter_handler: Box<dyn DeadLetterHandler>) -> Self {
+        self.dead_letter_handler = Some(dead_letter_handler);
+        self
+    }
+
+    pub fn build(self) -> HttpOutputFormat {
+        info!("build http sink with: {:?}", &self);
+
+        let mut output_format = HttpOutputFormat::new(self.request, self.format);
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(max_in_flight) = self.max_in_flight {
+            output_format = output_format.max_in_flight(max_in_flight);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+        if let Some(initial_backoff) = self.initial_backoff {
+            output_format = output_format.initial_backoff(initial_backoff);
+        }
+        if let Some(timeout) = self.timeout {
+            output_format = output_format.timeout(timeout);
+        }
+        if let Some(dead_letter_handler) = self.dead_letter_handler {
+            output_format = output_format.dead_letter_handler(dead_letter_handler);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for HttpOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpOutputFormatBuilder")
+            .field("url", &self.request.url)
+            .field(
+                "headers",
+                &self
+                    .request
+                    .headers
+                    .iter()
+                    .map(|(key, _)| key)
+                    .collect::<Vec<&String>>(),
+            )
+            .field("auth", &self.request.auth)
+            .field("format", &self.format)
+            .field("batch_size", &self.batch_size)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("max_retries", &self.max_retries)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("timeout", &self.timeout)
+            .field("dead_letter_handler", &self.dead_letter_handler.is_some())
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for HttpOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let http_properties = properties.to_sub_properties(HTTP);
+        let url = http_properties.get_string(URL)?;
+
+        let mut builder = HttpOutputFormatBuilder::new(url.as_str());
+
+        for (key, value) in http_properties.to_sub_properties(HEADERS).as_map() {
+            builder = builder.header(key.as_str(), value.as_str());
+        }
+        if let Ok(username) = http_properties.get_string(USERNAME) {
+            let password = http_properties.get_string(PASSWORD).ok();
+            builder = builder.basic_auth(username.as_str(), password.as_deref());
+        } else if let Ok(token) = http_properties.get_string(BEARER_TOKEN) {
+            builder = builder.bearer_auth(token.as_str());
+        }
+
+        if let Ok(format) = properties.get_string(FORMAT) {
+            let format = match format.to_lowercase().as_str() {
+                "json" => BodyFormat::Json,
+                "ndjson" => BodyFormat::NdJson,
+                _ => return Err(anyhow!("unknown http body format `{}`", format)),
+            };
+            builder = builder.format(format);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(max_in_flight) = properties.get_usize(MAX_IN_FLIGHT) {
+            builder = builder.max_in_flight(max_in_flight);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+        if let Ok(initial_backoff) = properties.get_duration(RETRY_BACKOFF) {
+            builder = builder.initial_backoff(initial_backoff);
+        }
+        if let Ok(timeout) = properties.get_duration(TIMEOUT) {
+            builder = builder.timeout(timeout);
+        }
+        if let Ok(dead_letter) = properties.get_string(DEAD_LETTER) {
+            match dead_letter.to_lowercase().as_str() {
+                "log" => builder = builder.dead_letter_handler(Box::new(LogDeadLetterHandler {})),
+                "fail" => {}
+                _ => return Err(anyhow!("unknown dead letter handler `{}`", dead_letter)),
+            }
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,32 @@
+/// A payload failed permanently, by a `4xx` response or after the retries
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    /// the request body, a json object or the json lines of the records
+    pub body: Vec<u8>,
+    pub rows: usize,
+    /// the status of the last response, `None` if the request failed without a response
+    pub status: Option<u16>,
+    pub error: String,
+}
+
+/// Handle the permanently failed payloads, e.g. write them to a dead letter queue. The sink
+/// fails without a dead letter handler
+pub trait DeadLetterHandler: Send + Sync {
+    fn handle(&self, dead_letter: DeadLetter);
+}
+
+/// Log the failed payloads and go on
+#[derive(Clone, Debug, Default)]
+pub struct LogDeadLetterHandler {}
+
+impl DeadLetterHandler for LogDeadLetterHandler {
+    fn handle(&self, dead_letter: DeadLetter) {
+        error!(
+            "discard {} rows, status {:?}. {}, payload: {}",
+            dead_letter.rows,
+            dead_letter.status,
+            dead_letter.error,
+            String::from_utf8_lossy(dead_letter.body.as_slice())
+        );
+    }
+}
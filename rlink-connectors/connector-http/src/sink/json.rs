@@ -0,0 +1,46 @@
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+use serde_json::{Map, Value};
+
+/// Append the record to the `buffer` as a json object with the field names of the `schema`
+pub(crate) fn write_json(
+    schema: &Schema,
+    record: &mut Record,
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<()> {
+    let row = to_json_object(schema, record)?;
+    serde_json::to_writer(buffer, &row)?;
+    Ok(())
+}
+
+/// The record as a json object with the field names of the `schema`
+pub(crate) fn to_json_object(
+    schema: &Schema,
+    record: &mut Record,
+) -> std::io::Result<Map<String, Value>> {
+    let reader = record.as_reader(schema.as_type_ids());
+
+    let mut row = Map::with_capacity(schema.fields().len());
+    for (i, field) in schema.fields().iter().enumerate() {
+        let value = match field.data_type() {
+            DataType::Boolean => Value::from(reader.get_bool(i)?),
+            DataType::Int8 => Value::from(reader.get_i8(i)?),
+            DataType::UInt8 => Value::from(reader.get_u8(i)?),
+            DataType::Int16 => Value::from(reader.get_i16(i)?),
+            DataType::UInt16 => Value::from(reader.get_u16(i)?),
+            DataType::Int32 => Value::from(reader.get_i32(i)?),
+            DataType::UInt32 => Value::from(reader.get_u32(i)?),
+            DataType::Int64 => Value::from(reader.get_i64(i)?),
+            DataType::UInt64 => Value::from(reader.get_u64(i)?),
+            DataType::Float32 => Value::from(reader.get_f32(i)?),
+            DataType::Float64 => Value::from(reader.get_f64(i)?),
+            DataType::Binary => {
+                Value::from(String::from_utf8_lossy(reader.get_binary(i)?).to_string())
+            }
+            DataType::String => Value::from(reader.get_str(i)?),
+        };
+        row.insert(field.name().to_string(), value);
+    }
+
+    Ok(row)
+}
@@ -0,0 +1,6 @@
+pub mod builder;
+pub mod dead_letter;
+pub mod output_format;
+
+pub(crate) mod json;
+pub(crate) mod sender;
@@ -0,0 +1,293 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Client;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::metrics::SinkMetrics;
+use tokio::sync::Semaphore;
+
+use crate::sink::dead_letter::DeadLetterHandler;
+use crate::sink::json::write_json;
+use crate::sink::sender::PayloadSender;
+use crate::source::request::HttpRequest;
+use crate::{
+    SINK_BATCH_SIZE, SINK_INITIAL_BACKOFF_MILLIS, SINK_MAX_IN_FLIGHT, SINK_MAX_RETRIES,
+    SINK_TIMEOUT_MILLIS,
+};
+
+/// The body of the requests
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BodyFormat {
+    /// a json object of a record per request
+    Json,
+    /// the json lines of up to `batch_size` records per request
+    NdJson,
+}
+
+impl BodyFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            BodyFormat::Json => "application/json",
+            BodyFormat::NdJson => "application/x-ndjson",
+        }
+    }
+}
+
+/// Send the records to an http endpoint as the json bodies, e.g. a webhook.
+///
+/// At most `max_in_flight` requests are sent concurrently over the pooled connections, the
+/// failed requests are retried with the exponential backoff on the `5xx`, `429` and the
+/// connection errors. The permanently failed payloads are handed to the dead letter handler,
+/// or fail the job without one. All requests are completed before the checkpoint completes,
+/// so the records are delivered at least once.
+#[derive(NamedFunction)]
+pub struct HttpOutputFormat {
+    request: HttpRequest,
+    format: BodyFormat,
+    batch_size: usize,
+    max_in_flight: usize,
+    max_retries: usize,
+    initial_backoff: Duration,
+    timeout: Duration,
+    dead_letter_handler: Option<Arc<dyn DeadLetterHandler>>,
+
+    schema: Schema,
+    buffer: Vec<u8>,
+    rows: usize,
+    sender: Option<Arc<PayloadSender>>,
+    in_flight: Arc<Semaphore>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl HttpOutputFormat {
+    pub fn new(request: HttpRequest, format: BodyFormat) -> Self {
+        HttpOutputFormat {
+            request,
+            format,
+            batch_size: SINK_BATCH_SIZE,
+            max_in_flight: SINK_MAX_IN_FLIGHT,
+            max_retries: SINK_MAX_RETRIES,
+            initial_backoff: Duration::from_millis(SINK_INITIAL_BACKOFF_MILLIS),
+            timeout: Duration::from_millis(SINK_TIMEOUT_MILLIS),
+            dead_letter_handler: None,
+            schema: Schema::empty(),
+            buffer: vec![],
+            rows: 0,
+            sender: None,
+            in_flight: Arc::new(Semaphore::new(SINK_MAX_IN_FLIGHT)),
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The records per request of the `NdJson` format
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self.in_flight = Arc::new(Semaphore::new(self.max_in_flight));
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// The backoff of the first retry, doubled on every retry
+    pub fn initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn dead_letter_handler(mut self, dead_letter_handler: Box<dyn DeadLetterHandler>) -> Self {
+        self.dead_letter_handler = Some(Arc::from(dead_letter_handler));
+        self
+    }
+
+    /// Send the buffered records in the background once a permit is acquired
+    async fn send(&mut self) {
+        if self.rows == 0 {
+            return;
+        }
+
+        let body = std::mem::take(&mut self.buffer);
+        let rows = std::mem::take(&mut self.rows);
+
+        let permit = self.in_flight.clone().acquire_owned().await.unwrap();
+        let sender = self.sender.as_ref().unwrap().clone();
+        let error = self.error.clone();
+        tokio::spawn(async move {
+            if let Err(e) = sender.send(body, rows).await {
+                error!("{}", e);
+                error.lock().unwrap().get_or_insert(e.to_string());
+            }
+            drop(permit);
+        });
+    }
+
+    /// Send the buffered records and wait for the in-flight requests, the first error is
+    /// returned if any failed
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        self.send().await;
+
+        let permits = self
+            .in_flight
+            .acquire_many(self.max_in_flight as u32)
+            .await?;
+        drop(permits);
+
+        match self.error.lock().unwrap().as_ref() {
+            Some(e) => Err(anyhow!("{}", e)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for HttpOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.clone().into();
+
+        let client = Client::builder()
+            .pool_max_idle_per_host(self.max_in_flight)
+            .timeout(self.timeout)
+            .build()
+            .map_err(anyhow::Error::from)?;
+        let sender = PayloadSender::new(
+            client,
+            self.request.clone(),
+            self.format.content_type(),
+            self.max_retries,
+            self.initial_backoff,
+            SinkMetrics::new("Http", context.task_id.to_tags()),
+        )
+        .dead_letter_handler(self.dead_letter_handler.clone());
+        self.sender = Some(Arc::new(sender));
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Some(e) = self.error.lock().unwrap().as_ref() {
+            panic!("http sink error. {}", e);
+        }
+
+        let mut record = element.into_record();
+        let len = self.buffer.len();
+        if let Err(e) = write_json(&self.schema, &mut record, &mut self.buffer) {
+            self.buffer.truncate(len);
+            error!("write json error, the record is discarded. {}", e);
+            return;
+        }
+        self.rows += 1;
+
+        match self.format {
+            BodyFormat::Json => self.send().await,
+            BodyFormat::NdJson => {
+                self.buffer.push(b'\n');
+                if self.rows >= self.batch_size {
+                    self.send().await;
+                }
+            }
+        }
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.flush().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for HttpOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered records are sent and the in-flight requests are completed before the
+    /// checkpoint completes
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        if let Err(e) = self.flush().await {
+            panic!(
+                "flush on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use reqwest::Client;
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::{Element, Record};
+    use rlink::core::function::OutputFormat;
+    use rlink_connector_common::metrics::SinkMetrics;
+
+    use crate::sink::output_format::{BodyFormat, HttpOutputFormat};
+    use crate::sink::sender::tests::serve;
+    use crate::sink::sender::PayloadSender;
+    use crate::source::request::HttpRequest;
+
+    fn element(a: i64) -> Element {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_i64(a).unwrap();
+        Element::Record(record)
+    }
+
+    #[tokio::test]
+    pub async fn nd_json_batch_test() {
+        let (url, bodies) = serve(vec![200, 200]).await;
+
+        let mut sink = HttpOutputFormat::new(HttpRequest::post(url.as_str()), BodyFormat::NdJson)
+            .batch_size(2);
+        sink.schema = Schema::new(vec![Field::new("a", DataType::Int64)]);
+        sink.sender = Some(Arc::new(PayloadSender::new(
+            Client::new(),
+            sink.request.clone(),
+            sink.format.content_type(),
+            0,
+            Duration::from_millis(1),
+            SinkMetrics::new("Http", vec![]),
+        )));
+
+        for a in 0..3 {
+            sink.write_element(element(a)).await;
+        }
+        // the full batch is sent on the write, the rest is sent on the flush
+        sink.flush().await.unwrap();
+
+        let mut bodies = bodies.lock().unwrap().clone();
+        bodies.sort();
+        assert_eq!(
+            bodies,
+            vec![
+                "{\"a\":0}\n{\"a\":1}\n".to_string(),
+                "{\"a\":2}\n".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, StatusCode};
+use rlink_connector_common::metrics::SinkMetrics;
+use tokio::time::Instant;
+
+use crate::sink::dead_letter::{DeadLetter, DeadLetterHandler};
+use crate::source::request::HttpRequest;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Send the payloads with the exponential backoff retries on the `5xx`, `429` and the
+/// connection errors. The other responses than `2xx` fail the payload at once
+pub(crate) struct PayloadSender {
+    client: Client,
+    request: HttpRequest,
+    content_type: &'static str,
+    max_retries: usize,
+    initial_backoff: Duration,
+    dead_letter_handler: Option<Arc<dyn DeadLetterHandler>>,
+    metrics: SinkMetrics,
+}
+
+impl PayloadSender {
+    pub fn new(
+        client: Client,
+        request: HttpRequest,
+        content_type: &'static str,
+        max_retries: usize,
+        initial_backoff: Duration,
+        metrics: SinkMetrics,
+    ) -> Self {
+        PayloadSender {
+            client,
+            request,
+            content_type,
+            max_retries,
+            initial_backoff,
+            dead_letter_handler: None,
+            metrics,
+        }
+    }
+
+    pub fn dead_letter_handler(
+        mut self,
+        dead_letter_handler: Option<Arc<dyn DeadLetterHandler>>,
+    ) -> Self {
+        self.dead_letter_handler = dead_letter_handler;
+        self
+    }
+
+    /// Send the payload of the `rows`, the failed payload is handed to the dead letter
+    /// handler, or returned as an error without the handler
+    pub async fn send(&self, body: Vec<u8>, rows: usize) -> anyhow::Result<()> {
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            let (status, error) = match self.request(body.clone()).await {
+                Ok((status, _message)) if status.is_success() => {
+                    self.metrics.flushed(rows as u64, begin.elapsed());
+                    return Ok(());
+                }
+                Ok((status, message)) => (Some(status), message),
+                Err(e) => (None, e.to_string()),
+            };
+
+            let retryable = match status {
+                Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+                None => true,
+            };
+            if retryable && attempt < self.max_retries {
+                let backoff = self
+                    .initial_backoff
+                    .saturating_mul(1 << attempt.min(16))
+                    .min(MAX_BACKOFF);
+                attempt += 1;
+                self.metrics.retry();
+                warn!(
+                    "send {} rows to {} error, retry({}/{}) after {:?}. {}",
+                    rows, self.request.url, attempt, self.max_retries, backoff, error
+                );
+                tokio::time::sleep(backoff).await;
+                continue;
+            }
+
+            return self.dead_letter(DeadLetter {
+                body,
+                rows,
+                status: status.map(|x| x.as_u16()),
+                error,
+            });
+        }
+    }
+
+    /// The status and the body of the response, the body is read to the end so the
+    /// connection is reused by the pool
+    async fn request(&self, body: Vec<u8>) -> reqwest::Result<(StatusCode, String)> {
+        let response = self
+            .request
+            .build(&self.client)
+            .header(CONTENT_TYPE, self.content_type)
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status();
+        let message = response.text().await?;
+        Ok((status, message))
+    }
+
+    fn dead_letter(&self, dead_letter: DeadLetter) -> anyhow::Result<()> {
+        match &self.dead_letter_handler {
+            Some(handler) => {
+                self.metrics.dropped(dead_letter.rows as u64);
+                handler.handle(dead_letter);
+                Ok(())
+            }
+            None => Err(anyhow!(
+                "send {} rows to {} error, status {:?}. {}",
+                dead_letter.rows,
+                self.request.url,
+                dead_letter.status,
+                dead_letter.error
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use reqwest::Client;
+    use rlink_connector_common::metrics::SinkMetrics;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::sink::dead_letter::{DeadLetter, DeadLetterHandler};
+    use crate::sink::sender::PayloadSender;
+    use crate::source::request::HttpRequest;
+
+    /// Respond the requests by the `statuses` in turn on a local port, the bodies of the
+    /// requests are collected
+    pub(crate) async fn serve(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+
+        let requests = bodies.clone();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut stream, _addr) = listener.accept().await.unwrap();
+                let body = read_body(&mut stream).await;
+                requests.lock().unwrap().push(body);
+
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, bodies)
+    }
+
+    async fn read_body(stream: &mut TcpStream) -> String {
+        let mut buffer = Vec::new();
+        let mut body_offset = None;
+        let mut content_length = 0;
+        loop {
+            if let Some(offset) = body_offset {
+                if buffer.len() >= offset + content_length {
+                    let body = &buffer[offset..offset + content_length];
+                    return String::from_utf8_lossy(body).to_string();
+                }
+            }
+
+            let mut chunk = [0u8; 1024];
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "the request is incomplete");
+            buffer.extend_from_slice(&chunk[..n]);
+
+            if body_offset.is_none() {
+                if let Some(pos) = buffer.windows(4).position(|x| x == b"\r\n\r\n") {
+                    let headers = String::from_utf8_lossy(&buffer[..pos]).to_lowercase();
+                    content_length = headers
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map(|x| x.trim().parse().unwrap())
+                        .unwrap_or(0);
+                    body_offset = Some(pos + 4);
+                }
+            }
+        }
+    }
+
+    fn sender(url: &str, max_retries: usize) -> PayloadSender {
+        PayloadSender::new(
+            Client::new(),
+            HttpRequest::post(url),
+            "application/json",
+            max_retries,
+            Duration::from_millis(1),
+            SinkMetrics::new("Http", vec![]),
+        )
+    }
+
+    struct CollectDeadLetterHandler {
+        dead_letters: Arc<Mutex<Vec<DeadLetter>>>,
+    }
+
+    impl DeadLetterHandler for CollectDeadLetterHandler {
+        fn handle(&self, dead_letter: DeadLetter) {
+            self.dead_letters.lock().unwrap().push(dead_letter);
+        }
+    }
+
+    #[tokio::test]
+    pub async fn retry_test() {
+        let (url, bodies) = serve(vec![503, 429, 200]).await;
+        sender(url.as_str(), 2)
+            .send(br#"{"a":1}"#.to_vec(), 1)
+            .await
+            .unwrap();
+        assert_eq!(bodies.lock().unwrap().len(), 3);
+
+        // the client errors aren't retried, and fail the payload without the handler
+        let (url, bodies) = serve(vec![400]).await;
+        let result = sender(url.as_str(), 2)
+            .send(br#"{"a":1}"#.to_vec(), 1)
+            .await;
+        assert!(result.is_err());
+        assert_eq!(bodies.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    pub async fn dead_letter_test() {
+        let (url, bodies) = serve(vec![503, 503]).await;
+        let dead_letters = Arc::new(Mutex::new(Vec::new()));
+        let handler = CollectDeadLetterHandler {
+            dead_letters: dead_letters.clone(),
+        };
+        sender(url.as_str(), 1)
+            .dead_letter_handler(Some(Arc::new(handler)))
+            .send(br#"{"a":1}"#.to_vec(), 1)
+            .await
+            .unwrap();
+        assert_eq!(bodies.lock().unwrap().len(), 2);
+
+        let dead_letters = dead_letters.lock().unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].status, Some(503));
+        assert_eq!(dead_letters[0].body, br#"{"a":1}"#.to_vec());
+    }
+}
@@ -0,0 +1,181 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use reqwest::Method;
+use rlink::core::data_types::Schema;
+use rlink::core::properties::Properties;
+
+use crate::source::decoder::{JsonResponseDecoder, ResponseDecoder};
+use crate::source::input_format::HttpInputFormat;
+use crate::source::pagination::{Pagination, QueryCursorPagination};
+use crate::source::request::{HttpAuth, HttpRequest};
+use crate::{
+    BEARER_TOKEN, BODY, BUFFER_SIZE, CURSOR_PARAM, CURSOR_POINTER, HEADERS, HTTP, METHOD, PASSWORD,
+    POLL_INTERVAL, RECORDS_POINTER, URL, USERNAME,
+};
+
+pub struct HttpInputFormatBuilder {
+    request: HttpRequest,
+    decoder: Option<Box<dyn ResponseDecoder>>,
+    pagination: Option<Box<dyn Pagination>>,
+    interval: Option<Duration>,
+    buffer_size: Option<usize>,
+}
+
+impl HttpInputFormatBuilder {
+    /// `GET` the `url` on every poll by default
+    pub fn new(url: &str) -> Self {
+        HttpInputFormatBuilder {
+            request: HttpRequest::get(url),
+            decoder: None,
+            pagination: None,
+            interval: None,
+            buffer_size: None,
+        }
+    }
+
+    /// `POST` the `body` on every poll
+    pub fn post(mut self, body: &str) -> Self {
+        self.request.method = Method::POST;
+        self.request.body = Some(body.to_string());
+        self
+    }
+
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.request
+            .headers
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn basic_auth(mut self, username: &str, password: Option<&str>) -> Self {
+        self.request.auth = Some(HttpAuth::Basic {
+            username: username.to_string(),
+            password: password.map(|x| x.to_string()),
+        });
+        self
+    }
+
+    pub fn bearer_auth(mut self, token: &str) -> Self {
+        self.request.auth = Some(HttpAuth::Bearer(token.to_string()));
+        self
+    }
+
+    pub fn decoder(mut self, decoder: Box<dyn ResponseDecoder>) -> Self {
+        self.decoder = Some(decoder);
+        self
+    }
+
+    /// Request the pages of the paginated api one by one on every poll
+    pub fn pagination(mut self, pagination: Box<dyn Pagination>) -> Self {
+        self.pagination = Some(pagination);
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The records are decoded to the `schema`, the response is decoded as json by default
+    pub fn build(self, schema: Schema) -> HttpInputFormat {
+        info!("build http source with: {:?}", &self);
+
+        let decoder = self
+            .decoder
+            .unwrap_or_else(|| Box::new(JsonResponseDecoder::default()));
+        let mut input_format = HttpInputFormat::new(self.request, decoder, schema);
+        if let Some(pagination) = self.pagination {
+            input_format = input_format.pagination(pagination);
+        }
+        if let Some(interval) = self.interval {
+            input_format = input_format.interval(interval);
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            input_format = input_format.buffer_size(buffer_size);
+        }
+
+        input_format
+    }
+}
+
+impl Debug for HttpInputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpInputFormatBuilder")
+            .field("url", &self.request.url)
+            .field("method", &self.request.method)
+            .field(
+                "headers",
+                &self
+                    .request
+                    .headers
+                    .iter()
+                    .map(|(key, _)| key)
+                    .collect::<Vec<&String>>(),
+            )
+            .field("auth", &self.request.auth)
+            .field("pagination", &self.pagination.is_some())
+            .field("interval", &self.interval)
+            .field("buffer_size", &self.buffer_size)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for HttpInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let http_properties = properties.to_sub_properties(HTTP);
+        let url = http_properties.get_string(URL)?;
+
+        let mut builder = HttpInputFormatBuilder::new(url.as_str());
+
+        if let Ok(method) = http_properties.get_string(METHOD) {
+            match method.to_uppercase().as_str() {
+                "GET" => {}
+                "POST" => {
+                    let body = http_properties.get_string(BODY).unwrap_or_default();
+                    builder = builder.post(body.as_str());
+                }
+                _ => return Err(anyhow!("unsupported http method `{}`", method)),
+            }
+        }
+        for (key, value) in http_properties.to_sub_properties(HEADERS).as_map() {
+            builder = builder.header(key.as_str(), value.as_str());
+        }
+        if let Ok(username) = http_properties.get_string(USERNAME) {
+            let password = http_properties.get_string(PASSWORD).ok();
+            builder = builder.basic_auth(username.as_str(), password.as_deref());
+        } else if let Ok(token) = http_properties.get_string(BEARER_TOKEN) {
+            builder = builder.bearer_auth(token.as_str());
+        }
+
+        let mut decoder = JsonResponseDecoder::new();
+        if let Ok(records_pointer) = properties.get_string(RECORDS_POINTER) {
+            decoder = decoder.records_pointer(records_pointer.as_str());
+        }
+        builder = builder.decoder(Box::new(decoder));
+
+        if let Ok(cursor_param) = properties.get_string(CURSOR_PARAM) {
+            let cursor_pointer = properties.get_string(CURSOR_POINTER)?;
+            builder = builder.pagination(Box::new(QueryCursorPagination::new(
+                cursor_param.as_str(),
+                cursor_pointer.as_str(),
+            )));
+        }
+        if let Ok(interval) = properties.get_duration(POLL_INTERVAL) {
+            builder = builder.interval(interval);
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::{Arc, Mutex};
+
+/// The page cursor of the source
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct HttpSourceState {
+    /// the cursor of the page being emitted, the first page if `None`
+    pub cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HttpSourceStateRecorder {
+    state: Arc<Mutex<HttpSourceState>>,
+}
+
+impl HttpSourceStateRecorder {
+    pub fn new() -> Self {
+        HttpSourceStateRecorder::default()
+    }
+
+    /// All records before the page of the `cursor` are emitted
+    pub fn update(&self, cursor: Option<String>) {
+        self.state.lock().unwrap().cursor = cursor;
+    }
+
+    pub fn state(&self) -> HttpSourceState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn snapshot(&self) -> String {
+        serde_json::to_string(&*self.state.lock().unwrap()).unwrap()
+    }
+
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let state: HttpSourceState = serde_json::from_str(snapshot_handle)?;
+        *self.state.lock().unwrap() = state;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::HttpSourceStateRecorder;
+
+    #[test]
+    pub fn state_snapshot_test() {
+        let recorder = HttpSourceStateRecorder::new();
+        recorder.update(Some("page-2".to_string()));
+        let snapshot = recorder.snapshot();
+
+        let restored = HttpSourceStateRecorder::new();
+        restored.update_from_snapshot(snapshot.as_str()).unwrap();
+        assert_eq!(restored.state().cursor, Some("page-2".to_string()));
+    }
+}
@@ -0,0 +1,150 @@
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::{BufferWriter, Record};
+use serde_json::Value;
+
+use crate::source::request::HttpResponse;
+
+/// Decode the response of a page into the records
+pub trait ResponseDecoder: Send + Sync {
+    fn decode(&self, schema: &Schema, response: &HttpResponse) -> anyhow::Result<Vec<Record>>;
+}
+
+/// The records are the json objects of the array at the `records_pointer` of the response,
+/// or a single object. The fields are mapped by name and the absent or null values are the
+/// zero values of the field types
+#[derive(Clone, Debug, Default)]
+pub struct JsonResponseDecoder {
+    records_pointer: Option<String>,
+}
+
+impl JsonResponseDecoder {
+    pub fn new() -> Self {
+        JsonResponseDecoder {
+            records_pointer: None,
+        }
+    }
+
+    /// The json pointer of the records, e.g. `/data/items`, the whole response by default
+    pub fn records_pointer(mut self, records_pointer: &str) -> Self {
+        self.records_pointer = Some(records_pointer.to_string());
+        self
+    }
+}
+
+impl ResponseDecoder for JsonResponseDecoder {
+    fn decode(&self, schema: &Schema, response: &HttpResponse) -> anyhow::Result<Vec<Record>> {
+        let body: Value = serde_json::from_slice(response.body.as_slice())?;
+        let value = match &self.records_pointer {
+            Some(pointer) => match body.pointer(pointer.as_str()) {
+                Some(value) => value,
+                None => return Ok(vec![]),
+            },
+            None => &body,
+        };
+
+        let objects = match value {
+            Value::Array(values) => values.iter().collect(),
+            Value::Object(_) => vec![value],
+            Value::Null => vec![],
+            _ => return Err(anyhow!("the records must be the json objects, {}", value)),
+        };
+
+        objects
+            .into_iter()
+            .map(|object| {
+                let mut record = Record::new();
+                let mut writer = record.as_writer(schema.as_type_ids());
+                for field in schema.fields() {
+                    let value = object.get(field.name()).unwrap_or(&Value::Null);
+                    write_json(&mut writer, field, value)?;
+                }
+                Ok(record)
+            })
+            .collect()
+    }
+}
+
+fn write_json(writer: &mut BufferWriter, field: &Field, value: &Value) -> anyhow::Result<()> {
+    let illegal = || {
+        anyhow!(
+            "json value {} can't be converted to the field `{}` of {:?}",
+            value,
+            field.name(),
+            field.data_type()
+        )
+    };
+    let as_i64 = || match value {
+        Value::Null => Some(0),
+        Value::Bool(v) => Some(*v as i64),
+        Value::Number(v) => v.as_i64().or_else(|| v.as_f64().map(|x| x as i64)),
+        _ => None,
+    };
+    let as_f64 = || match value {
+        Value::Number(v) => v.as_f64(),
+        _ => as_i64().map(|x| x as f64),
+    };
+
+    match field.data_type() {
+        DataType::Boolean => match value {
+            Value::Bool(v) => writer.set_bool(*v)?,
+            Value::Null => writer.set_bool(false)?,
+            _ => return Err(illegal()),
+        },
+        DataType::Int8 => writer.set_i8(as_i64().ok_or_else(illegal)? as i8)?,
+        DataType::UInt8 => writer.set_u8(as_i64().ok_or_else(illegal)? as u8)?,
+        DataType::Int16 => writer.set_i16(as_i64().ok_or_else(illegal)? as i16)?,
+        DataType::UInt16 => writer.set_u16(as_i64().ok_or_else(illegal)? as u16)?,
+        DataType::Int32 => writer.set_i32(as_i64().ok_or_else(illegal)? as i32)?,
+        DataType::UInt32 => writer.set_u32(as_i64().ok_or_else(illegal)? as u32)?,
+        DataType::Int64 => writer.set_i64(as_i64().ok_or_else(illegal)?)?,
+        DataType::UInt64 => match value {
+            Value::Number(v) if v.is_u64() => writer.set_u64(v.as_u64().unwrap())?,
+            _ => writer.set_u64(as_i64().ok_or_else(illegal)? as u64)?,
+        },
+        DataType::Float32 => writer.set_f32(as_f64().ok_or_else(illegal)? as f32)?,
+        DataType::Float64 => writer.set_f64(as_f64().ok_or_else(illegal)?)?,
+        DataType::String => match value {
+            Value::String(v) => writer.set_str(v.as_str())?,
+            Value::Null => writer.set_str("")?,
+            _ => writer.set_str(value.to_string().as_str())?,
+        },
+        DataType::Binary => match value {
+            Value::String(v) => writer.set_binary(v.as_bytes())?,
+            Value::Null => writer.set_binary(&[])?,
+            _ => writer.set_binary(value.to_string().as_bytes())?,
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderMap;
+    use rlink::core::data_types::{DataType, Field, Schema};
+
+    use crate::source::decoder::{JsonResponseDecoder, ResponseDecoder};
+    use crate::source::request::HttpResponse;
+
+    #[test]
+    pub fn json_response_decoder_test() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+        ]);
+        let response = HttpResponse {
+            status: 200,
+            headers: HeaderMap::new(),
+            body: br#"{"data":{"items":[{"id":1,"name":"a"},{"id":2}]}}"#.to_vec(),
+        };
+
+        let decoder = JsonResponseDecoder::new().records_pointer("/data/items");
+        let mut records = decoder.decode(&schema, &response).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let reader = records[0].as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_i64(0).unwrap(), 1);
+        assert_eq!(reader.get_str(1).unwrap(), "a");
+        let reader = records[1].as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_str(1).unwrap(), "");
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::metrics::Tag;
+
+use crate::metrics::SourceMetrics;
+use crate::source::checkpoint::HttpSourceStateRecorder;
+use crate::source::decoder::ResponseDecoder;
+use crate::source::pagination::Pagination;
+use crate::source::poller::HttpPoller;
+use crate::source::request::HttpRequest;
+use crate::source::stream::HttpRecordStream;
+use crate::{SOURCE_CHANNEL_SIZE, SOURCE_POLL_INTERVAL_MILLIS};
+
+/// Poll an http endpoint on every `interval` and decode the responses into the records. The
+/// paginated apis are requested page by page by the `pagination`, and the cursor of the
+/// page being emitted is checkpointed, so a restored task resumes from the page. The records
+/// of the page are emitted again after the restore.
+///
+/// The source runs in a single task and never ends
+#[derive(NamedFunction)]
+pub struct HttpInputFormat {
+    request: HttpRequest,
+    decoder: Arc<dyn ResponseDecoder>,
+    schema: Schema,
+    pagination: Option<Arc<dyn Pagination>>,
+    interval: Duration,
+    buffer_size: usize,
+
+    client: Option<Client>,
+    tags: Vec<Tag>,
+    metrics: Option<SourceMetrics>,
+    state_recorder: HttpSourceStateRecorder,
+}
+
+impl HttpInputFormat {
+    pub fn new(request: HttpRequest, decoder: Box<dyn ResponseDecoder>, schema: Schema) -> Self {
+        HttpInputFormat {
+            request,
+            decoder: Arc::from(decoder),
+            schema,
+            pagination: None,
+            interval: Duration::from_millis(SOURCE_POLL_INTERVAL_MILLIS),
+            buffer_size: SOURCE_CHANNEL_SIZE,
+            client: None,
+            tags: vec![],
+            metrics: None,
+            state_recorder: HttpSourceStateRecorder::new(),
+        }
+    }
+
+    pub fn pagination(mut self, pagination: Box<dyn Pagination>) -> Self {
+        self.pagination = Some(Arc::from(pagination));
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+}
+
+#[async_trait]
+impl InputFormat for HttpInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        self.client = Some(Client::builder().build().map_err(anyhow::Error::from)?);
+        self.tags = context.task_id.to_tags();
+        self.metrics = Some(SourceMetrics::new(self.tags.clone()));
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let metrics = self.metrics.clone().unwrap();
+        let (sender, receiver) =
+            named_channel("HttpSource_Handover", self.tags.clone(), self.buffer_size);
+
+        HttpPoller::new(
+            self.client.clone().unwrap(),
+            self.request.clone(),
+            self.schema.clone(),
+            self.decoder.clone(),
+            sender,
+            metrics,
+        )
+        .pagination(self.pagination.clone())
+        .interval(self.interval)
+        .restored(self.state_recorder.state().cursor)
+        .spawn();
+
+        Box::pin(HttpRecordStream::new(receiver, self.state_recorder.clone()))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        1
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for HttpInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            if !handle.handle.is_empty() {
+                self.state_recorder
+                    .update_from_snapshot(handle.handle.as_str())
+                    .expect("parse http source state error");
+                info!(
+                    "restore http source from checkpoint({:?}), cursor {:?}",
+                    context.checkpoint_id,
+                    self.state_recorder.state().cursor
+                );
+            }
+        }
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        Some(CheckpointHandle {
+            handle: self.state_recorder.snapshot(),
+        })
+    }
+}
+
+impl InputSplitSource for HttpInputFormat {}
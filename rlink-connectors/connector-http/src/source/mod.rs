@@ -0,0 +1,9 @@
+pub mod builder;
+pub mod decoder;
+pub mod input_format;
+pub mod pagination;
+pub mod request;
+pub mod stream;
+
+pub(crate) mod checkpoint;
+pub(crate) mod poller;
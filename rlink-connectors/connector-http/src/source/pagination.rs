@@ -0,0 +1,87 @@
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+use crate::source::request::HttpResponse;
+
+/// The cursor pagination of the paginated apis, the pages are requested one by one until the
+/// response has no next cursor
+pub trait Pagination: Send + Sync {
+    /// Apply the `cursor` to the request of the next page
+    fn apply(&self, request: RequestBuilder, cursor: &str) -> RequestBuilder;
+
+    /// The cursor of the next page, `None` if the response is the last page
+    fn next_cursor(&self, response: &HttpResponse) -> anyhow::Result<Option<String>>;
+}
+
+/// The cursor is read from the json response by the `cursor_pointer`, and sent as the query
+/// parameter `param`, e.g. `?page_token=xxx`. The null, empty or absent cursor ends the pages
+#[derive(Clone, Debug)]
+pub struct QueryCursorPagination {
+    param: String,
+    cursor_pointer: String,
+}
+
+impl QueryCursorPagination {
+    pub fn new(param: &str, cursor_pointer: &str) -> Self {
+        QueryCursorPagination {
+            param: param.to_string(),
+            cursor_pointer: cursor_pointer.to_string(),
+        }
+    }
+}
+
+impl Pagination for QueryCursorPagination {
+    fn apply(&self, request: RequestBuilder, cursor: &str) -> RequestBuilder {
+        request.query(&[(self.param.as_str(), cursor)])
+    }
+
+    fn next_cursor(&self, response: &HttpResponse) -> anyhow::Result<Option<String>> {
+        let body: Value = serde_json::from_slice(response.body.as_slice())?;
+        let cursor = match body.pointer(self.cursor_pointer.as_str()) {
+            Some(Value::String(cursor)) if !cursor.is_empty() => Some(cursor.clone()),
+            Some(Value::Number(cursor)) => Some(cursor.to_string()),
+            Some(Value::Null) | Some(Value::String(_)) | None => None,
+            Some(value) => return Err(anyhow!("illegal cursor {}", value)),
+        };
+        Ok(cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderMap;
+
+    use crate::source::pagination::{Pagination, QueryCursorPagination};
+    use crate::source::request::HttpResponse;
+
+    fn response(body: &str) -> HttpResponse {
+        HttpResponse {
+            status: 200,
+            headers: HeaderMap::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    pub fn query_cursor_pagination_test() {
+        let pagination = QueryCursorPagination::new("page_token", "/meta/next");
+
+        let cursor = pagination
+            .next_cursor(&response(r#"{"meta":{"next":"abc"},"data":[]}"#))
+            .unwrap();
+        assert_eq!(cursor, Some("abc".to_string()));
+
+        let cursor = pagination
+            .next_cursor(&response(r#"{"meta":{"next":2},"data":[]}"#))
+            .unwrap();
+        assert_eq!(cursor, Some("2".to_string()));
+
+        let cursor = pagination
+            .next_cursor(&response(r#"{"meta":{"next":null},"data":[]}"#))
+            .unwrap();
+        assert_eq!(cursor, None);
+
+        let cursor = pagination.next_cursor(&response(r#"{"data":[]}"#)).unwrap();
+        assert_eq!(cursor, None);
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+
+use crate::metrics::SourceMetrics;
+use crate::source::decoder::ResponseDecoder;
+use crate::source::pagination::Pagination;
+use crate::source::request::HttpRequest;
+use crate::SOURCE_POLL_INTERVAL_MILLIS;
+
+pub(crate) enum HttpEvent {
+    Record(Record),
+    /// all records of a page are sent, with the cursor of the next page, `None` if the pages
+    /// of the poll are finished
+    PageEnd(Option<String>),
+}
+
+/// Poll the endpoint on every `interval`. A poll requests the pages one by one from the
+/// `cursor` until the last page, and the next poll starts from the first page again. The
+/// failed poll is retried on the next interval from the page failed.
+pub(crate) struct HttpPoller {
+    client: Client,
+    request: HttpRequest,
+    schema: Schema,
+    decoder: Arc<dyn ResponseDecoder>,
+    pagination: Option<Arc<dyn Pagination>>,
+    interval: Duration,
+    sender: ChannelSender<HttpEvent>,
+    metrics: SourceMetrics,
+
+    cursor: Option<String>,
+}
+
+impl HttpPoller {
+    pub fn new(
+        client: Client,
+        request: HttpRequest,
+        schema: Schema,
+        decoder: Arc<dyn ResponseDecoder>,
+        sender: ChannelSender<HttpEvent>,
+        metrics: SourceMetrics,
+    ) -> Self {
+        HttpPoller {
+            client,
+            request,
+            schema,
+            decoder,
+            pagination: None,
+            interval: Duration::from_millis(SOURCE_POLL_INTERVAL_MILLIS),
+            sender,
+            metrics,
+            cursor: None,
+        }
+    }
+
+    pub fn pagination(mut self, pagination: Option<Arc<dyn Pagination>>) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Resume the pages from the `cursor` of the restored state
+    pub fn restored(mut self, cursor: Option<String>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(self.run());
+    }
+
+    async fn run(mut self) {
+        loop {
+            if let Err(e) = self.poll().await {
+                self.metrics.error();
+                warn!(
+                    "poll {} error, retry on the next interval. {}",
+                    self.request.url, e
+                );
+            }
+            if self.sender.is_closed() {
+                info!("the http source of {} is closed", self.request.url);
+                return;
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    async fn poll(&mut self) -> anyhow::Result<()> {
+        loop {
+            let begin = Instant::now();
+            let response = self
+                .request
+                .send(
+                    &self.client,
+                    self.pagination.as_deref(),
+                    self.cursor.as_deref(),
+                )
+                .await?;
+            let records = self.decoder.decode(&self.schema, &response)?;
+            let next_cursor = match &self.pagination {
+                Some(pagination) => pagination.next_cursor(&response)?,
+                None => None,
+            };
+            // guard against the api returning the same cursor forever
+            let next_cursor = next_cursor.filter(|next| Some(next) != self.cursor.as_ref());
+            self.metrics.requested(records.len(), begin.elapsed());
+
+            for record in records {
+                self.send(HttpEvent::Record(record)).await?;
+            }
+            self.send(HttpEvent::PageEnd(next_cursor.clone())).await?;
+
+            self.cursor = next_cursor;
+            if self.cursor.is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn send(&self, event: HttpEvent) -> anyhow::Result<()> {
+        self.sender
+            .send(event)
+            .await
+            .map_err(|_e| anyhow!("the http source is closed"))
+    }
+}
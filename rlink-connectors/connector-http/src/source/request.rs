@@ -0,0 +1,116 @@
+use std::fmt::{Debug, Formatter};
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, RequestBuilder};
+
+use crate::source::pagination::Pagination;
+
+#[derive(Clone)]
+pub enum HttpAuth {
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    Bearer(String),
+}
+
+impl Debug for HttpAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpAuth::Basic { username, .. } => {
+                f.debug_struct("Basic").field("username", username).finish()
+            }
+            HttpAuth::Bearer(_) => f.write_str("Bearer"),
+        }
+    }
+}
+
+/// The request sent on every poll of the source, the page cursor is applied by the
+/// pagination. The request of the sink carries the records as the body
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub url: String,
+    pub method: Method,
+    pub headers: Vec<(String, String)>,
+    pub auth: Option<HttpAuth>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        HttpRequest::new(url, Method::GET)
+    }
+
+    pub fn post(url: &str) -> Self {
+        HttpRequest::new(url, Method::POST)
+    }
+
+    fn new(url: &str, method: Method) -> Self {
+        HttpRequest {
+            url: url.to_string(),
+            method,
+            headers: vec![],
+            auth: None,
+            body: None,
+        }
+    }
+
+    pub(crate) fn build(&self, client: &Client) -> RequestBuilder {
+        let mut request = client.request(self.method.clone(), self.url.as_str());
+        for (key, value) in &self.headers {
+            request = request.header(key.as_str(), value.as_str());
+        }
+        request = match &self.auth {
+            Some(HttpAuth::Basic { username, password }) => {
+                request.basic_auth(username, password.as_ref())
+            }
+            Some(HttpAuth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        };
+        if let Some(body) = &self.body {
+            request = request.body(body.clone());
+        }
+        request
+    }
+
+    /// Send the request of the page of the `cursor`, the first page if `None`. The response
+    /// of the status other than `2xx` is an error
+    pub(crate) async fn send(
+        &self,
+        client: &Client,
+        pagination: Option<&dyn Pagination>,
+        cursor: Option<&str>,
+    ) -> anyhow::Result<HttpResponse> {
+        let mut request = self.build(client);
+        if let (Some(pagination), Some(cursor)) = (pagination, cursor) {
+            request = pagination.apply(request, cursor);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?.to_vec();
+        if !status.is_success() {
+            return Err(anyhow!(
+                "request {} error, status {}. {}",
+                self.url,
+                status,
+                String::from_utf8_lossy(body.as_slice())
+            ));
+        }
+
+        Ok(HttpResponse {
+            status: status.as_u16(),
+            headers,
+            body,
+        })
+    }
+}
+
+/// The successful response of a page
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
@@ -0,0 +1,50 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::HttpSourceStateRecorder;
+use crate::source::poller::HttpEvent;
+
+/// The records of the pages, the cursor is recorded once all records of the previous page are
+/// emitted
+pub struct HttpRecordStream {
+    receiver: ChannelReceiver<HttpEvent>,
+    state_recorder: HttpSourceStateRecorder,
+}
+
+impl HttpRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<HttpEvent>,
+        state_recorder: HttpSourceStateRecorder,
+    ) -> Self {
+        HttpRecordStream {
+            receiver,
+            state_recorder,
+        }
+    }
+}
+
+impl ElementStream for HttpRecordStream {}
+
+impl Stream for HttpRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().receiver.poll_recv(cx) {
+                Poll::Ready(Some(HttpEvent::Record(record))) => {
+                    return Poll::Ready(Some(Element::Record(record)));
+                }
+                Poll::Ready(Some(HttpEvent::PageEnd(cursor))) => {
+                    self.state_recorder.update(cursor);
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod sink;
+
+pub use sink::builder::IcebergOutputFormatBuilder;
+pub use sink::output_format::IcebergOutputFormat;
+
+pub const ICEBERG: &str = "iceberg";
+/// The prefix of the rest catalog properties, e.g. `catalog.uri` and `catalog.credential`
+pub const CATALOG: &str = "catalog";
+pub const URI: &str = "uri";
+pub const WAREHOUSE: &str = "warehouse";
+/// The namespace of the table, the levels are separated by `.`
+pub const NAMESPACE: &str = "namespace";
+pub const TABLE: &str = "table";
+/// Create the table by the record schema if not exists
+pub const CREATE_TABLE: &str = "create.table";
+/// The partition fields of the created table, e.g. `ts:day,id:bucket[16]`
+pub const PARTITION: &str = "partition";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const MAX_RETRIES: &str = "max.retries";
+
+/// The snapshot summary property identifying the commit of a task on a checkpoint
+pub const COMMIT_ID_PROPERTY: &str = "rlink.commit-id";
+
+pub const SINK_BATCH_SIZE: usize = 4096;
+pub const SINK_MAX_RETRIES: usize = 5;
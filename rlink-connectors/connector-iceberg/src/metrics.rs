@@ -0,0 +1,43 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SINK_ROWS: &str = "Iceberg.Sink.Rows";
+pub const SINK_DATA_FILES: &str = "Iceberg.Sink.DataFiles";
+pub const SINK_COMMITS: &str = "Iceberg.Sink.Commits";
+pub const SINK_COMMIT_RETRIES: &str = "Iceberg.Sink.CommitRetries";
+
+/// Metrics of the sink, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SinkMetrics {
+    /// rows written to the data files
+    rows: Counter,
+    /// data files committed to the table
+    data_files: Counter,
+    /// snapshots committed to the table
+    commits: Counter,
+    /// failed commits retried, e.g. conflicted with the commits of the other tasks
+    commit_retries: Counter,
+}
+
+impl SinkMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SinkMetrics {
+            rows: register_counter(SINK_ROWS, tags.clone()),
+            data_files: register_counter(SINK_DATA_FILES, tags.clone()),
+            commits: register_counter(SINK_COMMITS, tags.clone()),
+            commit_retries: register_counter(SINK_COMMIT_RETRIES, tags),
+        }
+    }
+
+    pub fn written(&self) {
+        self.rows.increment(1);
+    }
+
+    pub fn committed(&self, data_files: usize) {
+        self.commits.increment(1);
+        self.data_files.increment(data_files as u64);
+    }
+
+    pub fn retry(&self) {
+        self.commit_retries.increment(1);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    IcebergOutputFormat, BATCH_SIZE, CATALOG, CREATE_TABLE, ICEBERG, MAX_RETRIES, NAMESPACE,
+    PARTITION, TABLE, URI, WAREHOUSE,
+};
+
+pub struct IcebergOutputFormatBuilder {
+    uri: String,
+    namespace: String,
+    table: String,
+    warehouse: Option<String>,
+    catalog_properties: HashMap<String, String>,
+    create_table: bool,
+    partition_fields: Vec<(String, String)>,
+    batch_size: Option<usize>,
+    max_retries: Option<usize>,
+}
+
+impl IcebergOutputFormatBuilder {
+    /// The `uri` is the address of the rest catalog, e.g. `http://127.0.0.1:8181`, the
+    /// levels of the `namespace` are separated by `.`
+    pub fn new(uri: &str, namespace: &str, table: &str) -> Self {
+        IcebergOutputFormatBuilder {
+            uri: uri.to_string(),
+            namespace: namespace.to_string(),
+            table: table.to_string(),
+            warehouse: None,
+            catalog_properties: HashMap::new(),
+            create_table: false,
+            partition_fields: vec![],
+            batch_size: None,
+            max_retries: None,
+        }
+    }
+
+    pub fn warehouse(mut self, warehouse: &str) -> Self {
+        self.warehouse = Some(warehouse.to_string());
+        self
+    }
+
+    /// The property of the rest catalog, e.g. `credential` or `token`
+    pub fn catalog_property(mut self, key: &str, value: &str) -> Self {
+        self.catalog_properties
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Create the table by the record schema if not exists, the fields of the records are
+    /// the optional fields of the table
+    pub fn create_table(mut self, create_table: bool) -> Self {
+        self.create_table = create_table;
+        self
+    }
+
+    /// Partition the created table by the record `field`, e.g. `("ts", "day")` or
+    /// `("id", "bucket[16]")`. The existing table is written by its default partition spec
+    pub fn partition_field(mut self, field: &str, transform: &str) -> Self {
+        self.partition_fields
+            .push((field.to_string(), transform.to_string()));
+        self
+    }
+
+    /// Write the buffered records of a partition once `batch_size` rows are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Retry a failed commit up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> IcebergOutputFormat {
+        info!("build iceberg sink with: {:?}", &self);
+
+        let mut output_format = IcebergOutputFormat::new(self.uri, self.namespace, self.table)
+            .create_table(self.create_table);
+
+        if let Some(warehouse) = self.warehouse {
+            output_format = output_format.warehouse(warehouse);
+        }
+        for (key, value) in self.catalog_properties {
+            output_format = output_format.catalog_property(key, value);
+        }
+        for (field, transform) in self.partition_fields {
+            output_format = output_format.partition_field(field, transform);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for IcebergOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IcebergOutputFormatBuilder")
+            .field("uri", &self.uri)
+            .field("namespace", &self.namespace)
+            .field("table", &self.table)
+            .field("warehouse", &self.warehouse)
+            .field(
+                "catalog_properties",
+                &self.catalog_properties.keys().collect::<Vec<&String>>(),
+            )
+            .field("create_table", &self.create_table)
+            .field("partition_fields", &self.partition_fields)
+            .field("batch_size", &self.batch_size)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for IcebergOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let iceberg_properties = properties.to_sub_properties(ICEBERG);
+        let catalog_properties = iceberg_properties.to_sub_properties(CATALOG);
+        let uri = catalog_properties.get_string(URI)?;
+        let namespace = iceberg_properties.get_string(NAMESPACE)?;
+        let table = iceberg_properties.get_string(TABLE)?;
+
+        let mut builder =
+            IcebergOutputFormatBuilder::new(uri.as_str(), namespace.as_str(), table.as_str());
+
+        for (key, value) in catalog_properties.as_map() {
+            match key.as_str() {
+                URI => {}
+                WAREHOUSE => builder = builder.warehouse(value.as_str()),
+                _ => builder = builder.catalog_property(key.as_str(), value.as_str()),
+            }
+        }
+        if let Ok(create_table) = iceberg_properties.get_bool(CREATE_TABLE) {
+            builder = builder.create_table(create_table);
+        }
+        if let Ok(partition) = iceberg_properties.get_string(PARTITION) {
+            // e.g. `ts:day,id:bucket[16]`
+            for partition_field in partition.split(',').filter(|x| !x.trim().is_empty()) {
+                let (field, transform) = partition_field
+                    .split_once(':')
+                    .unwrap_or((partition_field, "identity"));
+                builder = builder.partition_field(field.trim(), transform.trim());
+            }
+        }
+        if let Ok(batch_size) = iceberg_properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(max_retries) = iceberg_properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
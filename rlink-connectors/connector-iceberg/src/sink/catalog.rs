@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use iceberg::spec::{Transform, UnboundPartitionSpec};
+use iceberg::table::Table;
+use iceberg::transaction::Transaction;
+use iceberg::{Catalog, NamespaceIdent, TableCreation, TableIdent};
+use iceberg_catalog_rest::{RestCatalog, RestCatalogConfig};
+use rlink::core::data_types::Schema;
+
+use crate::metrics::SinkMetrics;
+use crate::sink::checkpoint::Committable;
+use crate::sink::schema::to_iceberg_schema;
+use crate::COMMIT_ID_PROPERTY;
+
+/// The table of the rest catalog
+pub(crate) struct IcebergTable {
+    catalog: RestCatalog,
+    ident: TableIdent,
+}
+
+impl IcebergTable {
+    pub fn new(
+        uri: &str,
+        warehouse: Option<&str>,
+        properties: &HashMap<String, String>,
+        namespace: &str,
+        table: &str,
+    ) -> anyhow::Result<Self> {
+        let config = match warehouse {
+            Some(warehouse) => RestCatalogConfig::builder()
+                .uri(uri.to_string())
+                .warehouse(warehouse.to_string())
+                .props(properties.clone())
+                .build(),
+            None => RestCatalogConfig::builder()
+                .uri(uri.to_string())
+                .props(properties.clone())
+                .build(),
+        };
+
+        let namespace = NamespaceIdent::from_strs(namespace.split('.'))?;
+        Ok(IcebergTable {
+            catalog: RestCatalog::new(config),
+            ident: TableIdent::new(namespace, table.to_string()),
+        })
+    }
+
+    /// Load the table, or create it by the record `schema` and the `partition_fields` of
+    /// `(field, transform)` if `create` is enabled
+    pub async fn load_or_create(
+        &self,
+        schema: &Schema,
+        partition_fields: &[(String, String)],
+        create: bool,
+    ) -> anyhow::Result<Table> {
+        if self.catalog.table_exists(&self.ident).await? {
+            return Ok(self.catalog.load_table(&self.ident).await?);
+        }
+        if !create {
+            return Err(anyhow!("iceberg table {} not found", self.ident));
+        }
+
+        let iceberg_schema = to_iceberg_schema(schema)?;
+        let mut partition_spec = UnboundPartitionSpec::builder();
+        for (field, transform) in partition_fields {
+            let source = iceberg_schema
+                .field_by_name(field.as_str())
+                .ok_or_else(|| anyhow!("partition field `{}` not found", field))?;
+            // e.g. `ts_day` of `day`, `id_bucket` of `bucket[16]`
+            let name = match transform.split('[').next() {
+                Some("identity") | None => field.clone(),
+                Some(kind) => format!("{}_{}", field, kind),
+            };
+            partition_spec = partition_spec.add_partition_field(
+                source.id,
+                name,
+                Transform::from_str(transform.as_str())?,
+            )?;
+        }
+
+        let namespace = self.ident.namespace();
+        if !self.catalog.namespace_exists(namespace).await? {
+            self.catalog
+                .create_namespace(namespace, HashMap::new())
+                .await?;
+        }
+
+        let creation = TableCreation::builder()
+            .name(self.ident.name().to_string())
+            .schema(iceberg_schema)
+            .partition_spec(partition_spec.build())
+            .build();
+        let table = self.catalog.create_table(namespace, creation).await?;
+        info!("iceberg table {} created", self.ident);
+        Ok(table)
+    }
+
+    /// Commit the data files as a snapshot, the commit is skipped if the snapshot of the
+    /// `commit_id` is found, e.g. committed before the restart. The conflicted commits of
+    /// the parallel tasks are retried on the reloaded table
+    pub async fn commit(
+        &self,
+        committable: &Committable,
+        max_retries: usize,
+        metrics: &SinkMetrics,
+    ) -> anyhow::Result<()> {
+        if committable.data_files.is_empty() {
+            return Ok(());
+        }
+
+        let mut attempt = 0;
+        loop {
+            let error = match self.try_commit(committable).await {
+                Ok(()) => {
+                    metrics.committed(committable.data_files.len());
+                    return Ok(());
+                }
+                Err(e) => e,
+            };
+
+            if attempt >= max_retries {
+                return Err(anyhow!(
+                    "commit {} error after {} retries. {}",
+                    committable.commit_id,
+                    max_retries,
+                    error
+                ));
+            }
+
+            attempt += 1;
+            metrics.retry();
+            warn!(
+                "commit {} error, retry({}/{}). {}",
+                committable.commit_id, attempt, max_retries, error
+            );
+            tokio::time::sleep(Duration::from_millis(100 * (1 << attempt.min(6)))).await;
+        }
+    }
+
+    async fn try_commit(&self, committable: &Committable) -> anyhow::Result<()> {
+        let table = self.catalog.load_table(&self.ident).await?;
+        let committed = table.metadata().snapshots().any(|snapshot| {
+            snapshot
+                .summary()
+                .additional_properties
+                .get(COMMIT_ID_PROPERTY)
+                .map_or(false, |x| x.eq(&committable.commit_id))
+        });
+        if committed {
+            info!("{} is already committed", committable.commit_id);
+            return Ok(());
+        }
+
+        let transaction = Transaction::new(&table);
+        let mut action = transaction.fast_append(None, vec![])?;
+        action.set_snapshot_properties(HashMap::from([(
+            COMMIT_ID_PROPERTY.to_string(),
+            committable.commit_id.clone(),
+        )]))?;
+        action.add_data_files(committable.data_files.clone())?;
+        action.apply().await?.commit(&self.catalog).await?;
+
+        info!(
+            "{} committed with {} data files",
+            committable.commit_id,
+            committable.data_files.len()
+        );
+        Ok(())
+    }
+}
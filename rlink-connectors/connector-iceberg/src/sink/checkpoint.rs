@@ -0,0 +1,82 @@
+use iceberg::spec::{
+    DataContentType, DataFile, DataFileBuilder, DataFileFormat, Literal, Struct, StructType, Type,
+};
+
+/// The data files closed on a checkpoint, committed as a snapshot identified by `commit_id`
+#[derive(Clone, Debug)]
+pub(crate) struct Committable {
+    pub commit_id: String,
+    pub data_files: Vec<DataFile>,
+}
+
+/// The data file in the checkpoint, the column statistics are not kept, so the data files
+/// committed after the restore have no statistics
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PendingDataFile {
+    path: String,
+    record_count: u64,
+    file_size_in_bytes: u64,
+    /// the json of the partition value
+    partition: serde_json::Value,
+}
+
+/// The data files of the last checkpoint, not committed yet
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct IcebergSinkState {
+    commit_id: String,
+    data_files: Vec<PendingDataFile>,
+}
+
+impl IcebergSinkState {
+    pub fn new(committable: &Committable, partition_type: &StructType) -> anyhow::Result<Self> {
+        let partition_type = Type::Struct(partition_type.clone());
+        let data_files = committable
+            .data_files
+            .iter()
+            .map(|data_file| {
+                let partition = Literal::Struct(data_file.partition().clone())
+                    .try_into_json(&partition_type)?;
+                Ok(PendingDataFile {
+                    path: data_file.file_path().to_string(),
+                    record_count: data_file.record_count(),
+                    file_size_in_bytes: data_file.file_size_in_bytes(),
+                    partition,
+                })
+            })
+            .collect::<anyhow::Result<Vec<PendingDataFile>>>()?;
+
+        Ok(IcebergSinkState {
+            commit_id: committable.commit_id.clone(),
+            data_files,
+        })
+    }
+
+    pub fn to_committable(&self, partition_type: &StructType) -> anyhow::Result<Committable> {
+        let partition_type = Type::Struct(partition_type.clone());
+        let data_files = self
+            .data_files
+            .iter()
+            .map(|pending| {
+                let partition =
+                    match Literal::try_from_json(pending.partition.clone(), &partition_type)? {
+                        Some(Literal::Struct(partition)) => partition,
+                        _ => Struct::empty(),
+                    };
+                let data_file = DataFileBuilder::default()
+                    .content(DataContentType::Data)
+                    .file_path(pending.path.clone())
+                    .file_format(DataFileFormat::Parquet)
+                    .partition(partition)
+                    .record_count(pending.record_count)
+                    .file_size_in_bytes(pending.file_size_in_bytes)
+                    .build()?;
+                Ok(data_file)
+            })
+            .collect::<anyhow::Result<Vec<DataFile>>>()?;
+
+        Ok(Committable {
+            commit_id: self.commit_id.clone(),
+            data_files,
+        })
+    }
+}
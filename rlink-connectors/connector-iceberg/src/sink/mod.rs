@@ -0,0 +1,7 @@
+pub mod builder;
+pub mod output_format;
+pub mod schema;
+
+pub(crate) mod catalog;
+pub(crate) mod checkpoint;
+pub(crate) mod writer;
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+
+use iceberg::spec::StructType;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+
+use crate::metrics::SinkMetrics;
+use crate::sink::catalog::IcebergTable;
+use crate::sink::checkpoint::{Committable, IcebergSinkState};
+use crate::sink::writer::TaskWriter;
+use crate::{SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// Write the records to an iceberg table of the rest catalog.
+///
+/// The records are written to a parquet data file per partition of the default partition
+/// spec, and the data files are closed on every checkpoint. The data files of a checkpoint
+/// are committed as a single snapshot when the barrier of the next checkpoint arrives, or on
+/// restore from the checkpoint. The snapshot is tagged by the commit id of the task and the
+/// checkpoint, so the snapshot is committed once.
+#[derive(NamedFunction)]
+pub struct IcebergOutputFormat {
+    uri: String,
+    warehouse: Option<String>,
+    catalog_properties: HashMap<String, String>,
+    namespace: String,
+    table: String,
+    create_table: bool,
+    partition_fields: Vec<(String, String)>,
+    batch_size: usize,
+    max_retries: usize,
+
+    commit_id_prefix: String,
+    partition_type: StructType,
+    iceberg_table: Option<IcebergTable>,
+    writer: Option<TaskWriter>,
+    committable: Option<Committable>,
+    metrics: Option<SinkMetrics>,
+}
+
+impl IcebergOutputFormat {
+    pub fn new(uri: String, namespace: String, table: String) -> Self {
+        IcebergOutputFormat {
+            uri,
+            warehouse: None,
+            catalog_properties: HashMap::new(),
+            namespace,
+            table,
+            create_table: false,
+            partition_fields: vec![],
+            batch_size: SINK_BATCH_SIZE,
+            max_retries: SINK_MAX_RETRIES,
+            commit_id_prefix: String::new(),
+            partition_type: StructType::new(vec![]),
+            iceberg_table: None,
+            writer: None,
+            committable: None,
+            metrics: None,
+        }
+    }
+
+    pub fn warehouse(mut self, warehouse: String) -> Self {
+        self.warehouse = Some(warehouse);
+        self
+    }
+
+    /// The property of the rest catalog, e.g. `credential` or `token`
+    pub fn catalog_property(mut self, key: String, value: String) -> Self {
+        self.catalog_properties.insert(key, value);
+        self
+    }
+
+    /// Create the table by the record schema if not exists
+    pub fn create_table(mut self, create_table: bool) -> Self {
+        self.create_table = create_table;
+        self
+    }
+
+    /// The partition field of the created table, the `transform` is one of `identity`,
+    /// `year`, `month`, `day`, `hour`, `bucket[N]` and `truncate[W]`
+    pub fn partition_field(mut self, field: String, transform: String) -> Self {
+        self.partition_fields.push((field, transform));
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    async fn commit(&self, committable: Option<Committable>) -> anyhow::Result<()> {
+        if let Some(committable) = committable {
+            self.iceberg_table
+                .as_ref()
+                .unwrap()
+                .commit(
+                    &committable,
+                    self.max_retries,
+                    self.metrics.as_ref().unwrap(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputFormat for IcebergOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let schema: Schema = context.input_schema.clone().into();
+
+        let iceberg_table = IcebergTable::new(
+            self.uri.as_str(),
+            self.warehouse.as_deref(),
+            &self.catalog_properties,
+            self.namespace.as_str(),
+            self.table.as_str(),
+        )?;
+        let table = iceberg_table
+            .load_or_create(&schema, self.partition_fields.as_slice(), self.create_table)
+            .await?;
+
+        let metadata = table.metadata();
+        self.partition_type = metadata
+            .default_partition_spec()
+            .partition_type(metadata.current_schema())
+            .map_err(|e| anyhow!("partition type error. {}", e))?;
+
+        self.commit_id_prefix = format!(
+            "{}-{}-{}",
+            context.application_id,
+            context.task_id.job_id().0,
+            context.task_id.task_number()
+        );
+        self.writer = Some(TaskWriter::new(
+            &table,
+            schema,
+            self.commit_id_prefix.clone(),
+            self.batch_size,
+        )?);
+        self.iceberg_table = Some(iceberg_table);
+        self.metrics = Some(SinkMetrics::new(context.task_id.to_tags()));
+
+        // commit the data files of the restored checkpoint
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+        let committable = self.committable.take();
+        self.commit(committable).await?;
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        let mut record = element.into_record();
+        if let Err(e) = self.writer.as_mut().unwrap().write(&mut record).await {
+            error!("write record error, the record is discarded. {}", e);
+            return;
+        }
+        self.metrics.as_ref().unwrap().written();
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        let data_files = self.writer.as_mut().unwrap().close().await?;
+        let committable = self.committable.take();
+        self.commit(committable).await?;
+        self.commit(Some(Committable {
+            commit_id: format!("{}-end", self.commit_id_prefix),
+            data_files,
+        }))
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for IcebergOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            if !handle.handle.is_empty() {
+                let state: IcebergSinkState = serde_json::from_str(handle.handle.as_str())
+                    .expect("parse iceberg sink state error");
+                let committable = state
+                    .to_committable(&self.partition_type)
+                    .expect("restore the data files error");
+                info!(
+                    "restore {} data files from checkpoint({:?})",
+                    committable.data_files.len(),
+                    context.checkpoint_id
+                );
+                self.committable = Some(committable);
+            }
+        }
+    }
+
+    /// Close the data files, and commit the data files of the last checkpoint which must be
+    /// completed when the barrier of the next arrives
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let data_files = match self.writer.as_mut().unwrap().close().await {
+            Ok(data_files) => data_files,
+            Err(e) => panic!(
+                "close data files on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        };
+
+        let committable = self.committable.take();
+        if let Err(e) = self.commit(committable).await {
+            panic!(
+                "commit on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+
+        let committable = Committable {
+            commit_id: format!("{}-{}", self.commit_id_prefix, context.checkpoint_id.0),
+            data_files,
+        };
+        let state = IcebergSinkState::new(&committable, &self.partition_type)
+            .expect("serialize the data files error");
+        self.committable = Some(committable);
+
+        Some(CheckpointHandle {
+            handle: serde_json::to_string(&state).unwrap(),
+        })
+    }
+}
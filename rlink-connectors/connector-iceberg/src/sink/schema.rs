@@ -0,0 +1,231 @@
+//! The records are mapped to the table fields by name, the record values are converted to
+//! the types of the table fields, the table fields missing in the record are null.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Float32Array, Float64Array, Int32Array,
+    Int64Array, StringArray, TimestampMicrosecondArray,
+};
+use iceberg::spec::{
+    Datum, NestedField, PrimitiveLiteral, PrimitiveType, Schema as IcebergSchema, Type,
+};
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::BufferReader;
+
+/// Derive the iceberg schema from the record schema, the field ids are assigned in order
+/// from 1 and all fields are optional. The unsigned integers are widened to the signed, the
+/// `UInt32` and `UInt64` are stored as `long`
+pub fn to_iceberg_schema(schema: &Schema) -> anyhow::Result<IcebergSchema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let primitive_type = match field.data_type() {
+                DataType::Boolean => PrimitiveType::Boolean,
+                DataType::Int8
+                | DataType::UInt8
+                | DataType::Int16
+                | DataType::UInt16
+                | DataType::Int32 => PrimitiveType::Int,
+                DataType::UInt32 | DataType::Int64 | DataType::UInt64 => PrimitiveType::Long,
+                DataType::Float32 => PrimitiveType::Float,
+                DataType::Float64 => PrimitiveType::Double,
+                DataType::Binary => PrimitiveType::Binary,
+                DataType::String => PrimitiveType::String,
+            };
+            Arc::new(NestedField::optional(
+                index as i32 + 1,
+                field.name(),
+                Type::Primitive(primitive_type),
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(IcebergSchema::builder().with_fields(fields).build()?)
+}
+
+enum Value {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+fn read_value(reader: &BufferReader, index: usize, data_type: &DataType) -> anyhow::Result<Value> {
+    let value = match data_type {
+        DataType::Boolean => Value::Bool(reader.get_bool(index)?),
+        DataType::Int8 => Value::Int(reader.get_i8(index)? as i64),
+        DataType::UInt8 => Value::Int(reader.get_u8(index)? as i64),
+        DataType::Int16 => Value::Int(reader.get_i16(index)? as i64),
+        DataType::UInt16 => Value::Int(reader.get_u16(index)? as i64),
+        DataType::Int32 => Value::Int(reader.get_i32(index)? as i64),
+        DataType::UInt32 => Value::Int(reader.get_u32(index)? as i64),
+        DataType::Int64 => Value::Int(reader.get_i64(index)?),
+        DataType::UInt64 => Value::Int(reader.get_u64(index)? as i64),
+        DataType::Float32 => Value::Float(reader.get_f32(index)? as f64),
+        DataType::Float64 => Value::Float(reader.get_f64(index)?),
+        DataType::String => Value::Str(reader.get_str(index)?.to_string()),
+        DataType::Binary => Value::Bytes(reader.get_binary(index)?.to_vec()),
+    };
+    Ok(value)
+}
+
+/// Read the field `index` of the record as the value of the table field type. The integers
+/// are the days of the `date`, and the millis of the `timestamp` and `timestamptz`
+pub fn read_datum(
+    reader: &BufferReader,
+    index: usize,
+    data_type: &DataType,
+    primitive_type: &PrimitiveType,
+) -> anyhow::Result<Datum> {
+    let value = read_value(reader, index, data_type)?;
+    let datum = match (primitive_type, value) {
+        (PrimitiveType::Boolean, Value::Bool(v)) => Datum::bool(v),
+        (PrimitiveType::Int, Value::Int(v)) => Datum::int(i32::try_from(v)?),
+        (PrimitiveType::Long, Value::Int(v)) => Datum::long(v),
+        (PrimitiveType::Float, Value::Int(v)) => Datum::float(v as f32),
+        (PrimitiveType::Float, Value::Float(v)) => Datum::float(v as f32),
+        (PrimitiveType::Double, Value::Int(v)) => Datum::double(v as f64),
+        (PrimitiveType::Double, Value::Float(v)) => Datum::double(v),
+        (PrimitiveType::Date, Value::Int(v)) => Datum::date(i32::try_from(v)?),
+        (PrimitiveType::Timestamp, Value::Int(v)) => Datum::timestamp_micros(v * 1000),
+        (PrimitiveType::Timestamptz, Value::Int(v)) => Datum::timestamptz_micros(v * 1000),
+        (PrimitiveType::String, Value::Str(v)) => Datum::string(v),
+        (PrimitiveType::String, Value::Int(v)) => Datum::string(v),
+        (PrimitiveType::String, Value::Float(v)) => Datum::string(v),
+        (PrimitiveType::String, Value::Bool(v)) => Datum::string(v),
+        (PrimitiveType::Binary, Value::Bytes(v)) => Datum::binary(v),
+        (PrimitiveType::Binary, Value::Str(v)) => Datum::binary(v.into_bytes()),
+        (primitive_type, _) => {
+            return Err(anyhow!(
+                "{:?} can't be converted to the iceberg type {}",
+                data_type,
+                primitive_type
+            ))
+        }
+    };
+    Ok(datum)
+}
+
+/// Build the arrow array of the values read by `read_datum` of the same type
+pub fn to_array(
+    primitive_type: &PrimitiveType,
+    values: &[Option<Datum>],
+) -> anyhow::Result<ArrayRef> {
+    let literals = || values.iter().map(|x| x.as_ref().map(|x| x.literal()));
+
+    let array: ArrayRef = match primitive_type {
+        PrimitiveType::Boolean => Arc::new(BooleanArray::from_iter(literals().map(|x| {
+            x.and_then(|x| match x {
+                PrimitiveLiteral::Boolean(v) => Some(*v),
+                _ => None,
+            })
+        }))),
+        PrimitiveType::Int => Arc::new(Int32Array::from_iter(literals().map(as_i32))),
+        PrimitiveType::Date => Arc::new(Date32Array::from_iter(literals().map(as_i32))),
+        PrimitiveType::Long => Arc::new(Int64Array::from_iter(literals().map(as_i64))),
+        PrimitiveType::Timestamp => {
+            Arc::new(TimestampMicrosecondArray::from_iter(literals().map(as_i64)))
+        }
+        PrimitiveType::Timestamptz => Arc::new(
+            TimestampMicrosecondArray::from_iter(literals().map(as_i64)).with_timezone("+00:00"),
+        ),
+        PrimitiveType::Float => Arc::new(Float32Array::from_iter(literals().map(|x| {
+            x.and_then(|x| match x {
+                PrimitiveLiteral::Float(v) => Some(v.0),
+                _ => None,
+            })
+        }))),
+        PrimitiveType::Double => Arc::new(Float64Array::from_iter(literals().map(|x| {
+            x.and_then(|x| match x {
+                PrimitiveLiteral::Double(v) => Some(v.0),
+                _ => None,
+            })
+        }))),
+        PrimitiveType::String => Arc::new(StringArray::from_iter(literals().map(|x| {
+            x.and_then(|x| match x {
+                PrimitiveLiteral::String(v) => Some(v.as_str()),
+                _ => None,
+            })
+        }))),
+        PrimitiveType::Binary => Arc::new(BinaryArray::from_iter(literals().map(|x| {
+            x.and_then(|x| match x {
+                PrimitiveLiteral::Binary(v) => Some(v.as_slice()),
+                _ => None,
+            })
+        }))),
+        _ => {
+            return Err(anyhow!(
+                "the iceberg type {} is not supported",
+                primitive_type
+            ))
+        }
+    };
+    Ok(array)
+}
+
+fn as_i32(literal: Option<&PrimitiveLiteral>) -> Option<i32> {
+    match literal {
+        Some(PrimitiveLiteral::Int(v)) => Some(*v),
+        _ => None,
+    }
+}
+
+fn as_i64(literal: Option<&PrimitiveLiteral>) -> Option<i64> {
+    match literal {
+        Some(PrimitiveLiteral::Long(v)) => Some(*v),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Array, Int64Array, StringArray};
+    use iceberg::spec::{PrimitiveType, Type};
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::schema::{read_datum, to_array, to_iceberg_schema};
+
+    #[test]
+    pub fn schema_mapping_test() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::UInt32),
+            Field::new("name", DataType::String),
+            Field::new("ts", DataType::Int64),
+        ]);
+
+        let iceberg_schema = to_iceberg_schema(&schema).unwrap();
+        let id = iceberg_schema.field_by_name("id").unwrap();
+        assert_eq!(id.id, 1);
+        assert_eq!(*id.field_type, Type::Primitive(PrimitiveType::Long));
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_u32(7).unwrap();
+        writer.set_str("rlink").unwrap();
+        writer.set_i64(1_700_000_000_000).unwrap();
+
+        let reader = record.as_reader(schema.as_type_ids());
+        let ids = vec![
+            Some(read_datum(&reader, 0, &DataType::UInt32, &PrimitiveType::Long).unwrap()),
+            None,
+        ];
+        let names = vec![Some(
+            read_datum(&reader, 1, &DataType::String, &PrimitiveType::String).unwrap(),
+        )];
+        assert!(read_datum(&reader, 1, &DataType::String, &PrimitiveType::Long).is_err());
+
+        let ids = to_array(&PrimitiveType::Long, ids.as_slice()).unwrap();
+        let ids = ids.as_any().downcast_ref::<Int64Array>().unwrap();
+        assert_eq!(ids.value(0), 7);
+        assert!(ids.is_null(1));
+
+        let names = to_array(&PrimitiveType::String, names.as_slice()).unwrap();
+        let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(names.value(0), "rlink");
+    }
+}
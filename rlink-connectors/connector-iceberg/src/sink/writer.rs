@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_schema::Schema as ArrowSchema;
+use iceberg::arrow::schema_to_arrow_schema;
+use iceberg::io::FileIO;
+use iceberg::spec::{
+    DataFile, DataFileFormat, Datum, Literal, PrimitiveType, SchemaRef, Struct, Type,
+};
+use iceberg::table::Table;
+use iceberg::transform::{create_transform_function, BoxedTransformFunction};
+use iceberg::writer::base_writer::data_file_writer::{DataFileWriter, DataFileWriterBuilder};
+use iceberg::writer::file_writer::location_generator::{
+    DefaultFileNameGenerator, DefaultLocationGenerator,
+};
+use iceberg::writer::file_writer::ParquetWriterBuilder;
+use iceberg::writer::{IcebergWriter, IcebergWriterBuilder};
+use parquet::file::properties::WriterProperties;
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+
+use crate::sink::schema::{read_datum, to_array};
+
+type ParquetDataFileWriter =
+    DataFileWriter<ParquetWriterBuilder<DefaultLocationGenerator, DefaultFileNameGenerator>>;
+
+/// The table field and the record field of the same name
+struct TableColumn {
+    record_index: Option<usize>,
+    data_type: Option<DataType>,
+    primitive_type: PrimitiveType,
+}
+
+/// The partition field of the default partition spec
+struct PartitionColumn {
+    source: TableColumn,
+    transform: BoxedTransformFunction,
+}
+
+/// The data file of a partition, the records are buffered as the columns and written as a
+/// batch once `batch_size` rows are buffered
+struct PartitionWriter {
+    columns: Vec<Vec<Option<Datum>>>,
+    rows: usize,
+    writer: ParquetDataFileWriter,
+}
+
+/// Write the records of a task to a data file per partition, the data files are closed on
+/// the checkpoint
+pub(crate) struct TaskWriter {
+    schema: Schema,
+    table_schema: SchemaRef,
+    arrow_schema: Arc<ArrowSchema>,
+    columns: Vec<TableColumn>,
+    partition_columns: Vec<PartitionColumn>,
+
+    file_io: FileIO,
+    location_generator: DefaultLocationGenerator,
+    file_name_prefix: String,
+    batch_size: usize,
+
+    partitions: HashMap<Struct, PartitionWriter>,
+}
+
+impl TaskWriter {
+    pub fn new(
+        table: &Table,
+        schema: Schema,
+        file_name_prefix: String,
+        batch_size: usize,
+    ) -> anyhow::Result<Self> {
+        let metadata = table.metadata();
+        let table_schema = metadata.current_schema().clone();
+        let arrow_schema = Arc::new(schema_to_arrow_schema(table_schema.as_ref())?);
+
+        let column = |name: &str, field_type: &Type| -> anyhow::Result<TableColumn> {
+            let primitive_type = match field_type {
+                Type::Primitive(primitive_type) => primitive_type.clone(),
+                _ => return Err(anyhow!("the nested field `{}` is not supported", name)),
+            };
+            let record_field = schema.column_with_name(name);
+            Ok(TableColumn {
+                record_index: record_field.map(|(index, _)| index),
+                data_type: record_field.map(|(_, field)| field.data_type().clone()),
+                primitive_type,
+            })
+        };
+
+        let columns = table_schema
+            .as_struct()
+            .fields()
+            .iter()
+            .map(|field| column(field.name.as_str(), field.field_type.as_ref()))
+            .collect::<anyhow::Result<Vec<TableColumn>>>()?;
+
+        let partition_columns = metadata
+            .default_partition_spec()
+            .fields()
+            .iter()
+            .map(|partition_field| {
+                let source = table_schema
+                    .field_by_id(partition_field.source_id)
+                    .ok_or_else(|| {
+                        anyhow!("partition source {} not found", partition_field.source_id)
+                    })?;
+                Ok(PartitionColumn {
+                    source: column(source.name.as_str(), source.field_type.as_ref())?,
+                    transform: create_transform_function(&partition_field.transform)?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<PartitionColumn>>>()?;
+
+        Ok(TaskWriter {
+            schema,
+            table_schema,
+            arrow_schema,
+            columns,
+            partition_columns,
+            file_io: table.file_io().clone(),
+            location_generator: DefaultLocationGenerator::new(metadata.clone())?,
+            file_name_prefix,
+            batch_size: batch_size.max(1),
+            partitions: HashMap::new(),
+        })
+    }
+
+    pub async fn write(&mut self, record: &mut Record) -> anyhow::Result<()> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+        let read = |column: &TableColumn| -> anyhow::Result<Option<Datum>> {
+            match (column.record_index, column.data_type.as_ref()) {
+                (Some(index), Some(data_type)) => Ok(Some(read_datum(
+                    &reader,
+                    index,
+                    data_type,
+                    &column.primitive_type,
+                )?)),
+                _ => Ok(None),
+            }
+        };
+
+        let mut partition = Vec::with_capacity(self.partition_columns.len());
+        for partition_column in &self.partition_columns {
+            let value = match read(&partition_column.source)? {
+                Some(value) => partition_column.transform.transform_literal(&value)?,
+                None => None,
+            };
+            partition.push(value.map(|x| Literal::Primitive(x.literal().clone())));
+        }
+        let values = self
+            .columns
+            .iter()
+            .map(read)
+            .collect::<anyhow::Result<Vec<Option<Datum>>>>()?;
+
+        let partition = Struct::from_iter(partition);
+        if !self.partitions.contains_key(&partition) {
+            let writer = self.create_writer(partition.clone()).await?;
+            self.partitions.insert(partition.clone(), writer);
+        }
+
+        let partition_writer = self.partitions.get_mut(&partition).unwrap();
+        for (column, value) in partition_writer.columns.iter_mut().zip(values) {
+            column.push(value);
+        }
+        partition_writer.rows += 1;
+
+        if partition_writer.rows >= self.batch_size {
+            Self::flush(&self.columns, &self.arrow_schema, partition_writer).await?;
+        }
+        Ok(())
+    }
+
+    /// Close the data files of all partitions
+    pub async fn close(&mut self) -> anyhow::Result<Vec<DataFile>> {
+        let mut data_files = Vec::new();
+        for (_partition, mut partition_writer) in self.partitions.drain() {
+            Self::flush(&self.columns, &self.arrow_schema, &mut partition_writer).await?;
+            data_files.extend(partition_writer.writer.close().await?);
+        }
+        Ok(data_files)
+    }
+
+    async fn create_writer(&self, partition: Struct) -> anyhow::Result<PartitionWriter> {
+        let file_name_generator = DefaultFileNameGenerator::new(
+            format!("{}-{}", self.file_name_prefix, uuid::Uuid::new_v4()),
+            None,
+            DataFileFormat::Parquet,
+        );
+        let parquet_writer_builder = ParquetWriterBuilder::new(
+            WriterProperties::builder().build(),
+            self.table_schema.clone(),
+            self.file_io.clone(),
+            self.location_generator.clone(),
+            file_name_generator,
+        );
+        let partition = if self.partition_columns.is_empty() {
+            None
+        } else {
+            Some(partition)
+        };
+        let writer = DataFileWriterBuilder::new(parquet_writer_builder, partition)
+            .build()
+            .await?;
+
+        Ok(PartitionWriter {
+            columns: self.columns.iter().map(|_| Vec::new()).collect(),
+            rows: 0,
+            writer,
+        })
+    }
+
+    async fn flush(
+        columns: &[TableColumn],
+        arrow_schema: &Arc<ArrowSchema>,
+        partition_writer: &mut PartitionWriter,
+    ) -> anyhow::Result<()> {
+        if partition_writer.rows == 0 {
+            return Ok(());
+        }
+
+        let arrays = columns
+            .iter()
+            .zip(partition_writer.columns.iter_mut())
+            .map(|(column, values)| {
+                let array = to_array(&column.primitive_type, values.as_slice());
+                values.clear();
+                array
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        partition_writer.rows = 0;
+
+        let batch = RecordBatch::try_new(arrow_schema.clone(), arrays)?;
+        partition_writer.writer.write(batch).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod sink;
+
+pub use sink::output_format::InfluxdbOutputFormat;
+
+pub const INFLUXDB: &str = "influxdb";
+pub const URL: &str = "url";
+pub const TOKEN: &str = "token";
+pub const ORG: &str = "org";
+pub const BUCKET: &str = "bucket";
+
+pub const MEASUREMENT: &str = "measurement";
+/// The field names separated by `,`
+pub const TAG_FIELDS: &str = "tag.fields";
+/// The field names separated by `,`, all fields except the tags and the timestamp by default
+pub const VALUE_FIELDS: &str = "value.fields";
+pub const TIMESTAMP_FIELD: &str = "timestamp.field";
+/// `drop` or `retry`
+pub const PARTIAL_WRITE_POLICY: &str = "partial.write.policy";
+pub const BUFFER_SIZE: &str = "buffer.size";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 5000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 1000;
+pub const SINK_MAX_RETRIES: usize = 3;
@@ -0,0 +1,223 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::sink::output_format::PartialWritePolicy;
+use crate::{
+    InfluxdbOutputFormat, BATCH_INTERVAL, BATCH_SIZE, BUCKET, BUFFER_SIZE, INFLUXDB, MAX_RETRIES,
+    MEASUREMENT, ORG, PARTIAL_WRITE_POLICY, SINK_CHANNEL_SIZE, TAG_FIELDS, TIMESTAMP_FIELD, TOKEN,
+    URL, VALUE_FIELDS,
+};
+
+pub struct InfluxdbOutputFormatBuilder {
+    url: String,
+    org: String,
+    bucket: String,
+    measurement: String,
+    token: Option<String>,
+    tag_fields: Vec<String>,
+    value_fields: Vec<String>,
+    timestamp_field: Option<String>,
+    partial_write_policy: Option<PartialWritePolicy>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+    batch_interval: Option<Duration>,
+    max_retries: Option<usize>,
+}
+
+impl InfluxdbOutputFormatBuilder {
+    /// The `url` is the address of the server, e.g. `http://127.0.0.1:8086`
+    pub fn new(url: &str, org: &str, bucket: &str, measurement: &str) -> Self {
+        InfluxdbOutputFormatBuilder {
+            url: url.to_string(),
+            org: org.to_string(),
+            bucket: bucket.to_string(),
+            measurement: measurement.to_string(),
+            token: None,
+            tag_fields: vec![],
+            value_fields: vec![],
+            timestamp_field: None,
+            partial_write_policy: None,
+            buffer_size: None,
+            batch_size: None,
+            batch_interval: None,
+            max_retries: None,
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Write the field as the tag of the point
+    pub fn tag_field(mut self, field: &str) -> Self {
+        self.tag_fields.push(field.to_string());
+        self
+    }
+
+    /// Write the field as the field of the point, all fields except the tags and the timestamp
+    /// if no field is given
+    pub fn value_field(mut self, field: &str) -> Self {
+        self.value_fields.push(field.to_string());
+        self
+    }
+
+    /// The millis of the point, the points are stamped by the server if absent
+    pub fn timestamp_field(mut self, field: &str) -> Self {
+        self.timestamp_field = Some(field.to_string());
+        self
+    }
+
+    pub fn partial_write_policy(mut self, partial_write_policy: PartialWritePolicy) -> Self {
+        self.partial_write_policy = Some(partial_write_policy);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Flush once `batch_size` points are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Flush the buffered points at least every `batch_interval`
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = Some(batch_interval);
+        self
+    }
+
+    /// Retry a failed batch up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> InfluxdbOutputFormat {
+        info!("build influxdb sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let mut output_format = InfluxdbOutputFormat::new(
+            self.url,
+            self.org,
+            self.bucket,
+            self.measurement,
+            buffer_size,
+        )
+        .tag_fields(self.tag_fields)
+        .value_fields(self.value_fields);
+
+        if let Some(token) = self.token {
+            output_format = output_format.token(token);
+        }
+        if let Some(timestamp_field) = self.timestamp_field {
+            output_format = output_format.timestamp_field(timestamp_field);
+        }
+        if let Some(partial_write_policy) = self.partial_write_policy {
+            output_format = output_format.partial_write_policy(partial_write_policy);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(batch_interval) = self.batch_interval {
+            output_format = output_format.batch_interval(batch_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for InfluxdbOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InfluxdbOutputFormatBuilder")
+            .field("url", &self.url)
+            .field("org", &self.org)
+            .field("bucket", &self.bucket)
+            .field("measurement", &self.measurement)
+            .field("token", &self.token.as_ref().map(|_| "***"))
+            .field("tag_fields", &self.tag_fields)
+            .field("value_fields", &self.value_fields)
+            .field("timestamp_field", &self.timestamp_field)
+            .field("partial_write_policy", &self.partial_write_policy)
+            .field("buffer_size", &self.buffer_size)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+fn split_fields(fields: &str) -> impl Iterator<Item = &str> {
+    fields
+        .split(',')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+}
+
+impl TryFrom<Properties> for InfluxdbOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let influxdb_properties = properties.to_sub_properties(INFLUXDB);
+        let url = influxdb_properties.get_string(URL)?;
+        let org = influxdb_properties.get_string(ORG)?;
+        let bucket = influxdb_properties.get_string(BUCKET)?;
+        let measurement = properties.get_string(MEASUREMENT)?;
+
+        let mut builder = InfluxdbOutputFormatBuilder::new(
+            url.as_str(),
+            org.as_str(),
+            bucket.as_str(),
+            measurement.as_str(),
+        );
+
+        if let Ok(token) = influxdb_properties.get_string(TOKEN) {
+            builder = builder.token(token.as_str());
+        }
+        if let Ok(tag_fields) = properties.get_string(TAG_FIELDS) {
+            for field in split_fields(tag_fields.as_str()) {
+                builder = builder.tag_field(field);
+            }
+        }
+        if let Ok(value_fields) = properties.get_string(VALUE_FIELDS) {
+            for field in split_fields(value_fields.as_str()) {
+                builder = builder.value_field(field);
+            }
+        }
+        if let Ok(timestamp_field) = properties.get_string(TIMESTAMP_FIELD) {
+            builder = builder.timestamp_field(timestamp_field.as_str());
+        }
+        if let Ok(policy) = properties.get_string(PARTIAL_WRITE_POLICY) {
+            let policy = match policy.to_lowercase().as_str() {
+                "drop" => PartialWritePolicy::Drop,
+                "retry" => PartialWritePolicy::Retry,
+                _ => return Err(anyhow!("unknown partial write policy `{}`", policy)),
+            };
+            builder = builder.partial_write_policy(policy);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(batch_interval) = properties.get_duration(BATCH_INTERVAL) {
+            builder = builder.batch_interval(batch_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
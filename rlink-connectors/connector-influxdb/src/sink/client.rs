@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::{StatusCode, Url};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum WriteError {
+    /// the server is unavailable or busy, retry after the duration if given
+    Retryable(String, Option<Duration>),
+    /// some or all points are rejected, the valid points of the batch are written
+    PartialWrite(String),
+    Fatal(String),
+}
+
+impl WriteError {
+    fn from_status(status: StatusCode, retry_after: Option<Duration>, message: String) -> Self {
+        let message = format!("status {}. {}", status.as_u16(), message);
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+                WriteError::PartialWrite(message)
+            }
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+                WriteError::Retryable(message, retry_after)
+            }
+            status if status.is_server_error() => WriteError::Retryable(message, None),
+            _ => WriteError::Fatal(message),
+        }
+    }
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteError::Retryable(message, _) => write!(f, "{}", message),
+            WriteError::PartialWrite(message) => write!(f, "partial write. {}", message),
+            WriteError::Fatal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// The client of the v2 write api, `POST /api/v2/write`
+pub(crate) struct WriteClient {
+    client: reqwest::Client,
+    url: Url,
+    token: Option<String>,
+}
+
+impl WriteClient {
+    pub fn new(url: &str, org: &str, bucket: &str, token: Option<&str>) -> anyhow::Result<Self> {
+        let mut url = Url::parse(url)?.join("/api/v2/write")?;
+        url.query_pairs_mut()
+            .append_pair("org", org)
+            .append_pair("bucket", bucket)
+            .append_pair("precision", "ms");
+
+        Ok(WriteClient {
+            client: reqwest::Client::new(),
+            url,
+            token: token.map(|x| x.to_string()),
+        })
+    }
+
+    pub async fn write(&self, body: Vec<u8>) -> Result<(), WriteError> {
+        let mut request = self.client.post(self.url.clone()).body(body);
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Token {}", token));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| WriteError::Retryable(e.to_string(), None))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.parse().ok())
+            .map(Duration::from_secs);
+        let message = response.text().await.unwrap_or_default();
+        Err(WriteError::from_status(status, retry_after, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::StatusCode;
+
+    use crate::sink::client::WriteError;
+
+    #[test]
+    pub fn write_error_test() {
+        let message = r#"{"code":"invalid","message":"partial write: field type conflict"}"#;
+        assert!(matches!(
+            WriteError::from_status(StatusCode::BAD_REQUEST, None, message.to_string()),
+            WriteError::PartialWrite(_)
+        ));
+        assert_eq!(
+            WriteError::from_status(
+                StatusCode::TOO_MANY_REQUESTS,
+                Some(Duration::from_secs(3)),
+                "".to_string()
+            ),
+            WriteError::Retryable("status 429. ".to_string(), Some(Duration::from_secs(3)))
+        );
+        assert!(matches!(
+            WriteError::from_status(StatusCode::BAD_GATEWAY, None, "".to_string()),
+            WriteError::Retryable(_, None)
+        ));
+        assert!(matches!(
+            WriteError::from_status(StatusCode::UNAUTHORIZED, None, "".to_string()),
+            WriteError::Fatal(_)
+        ));
+    }
+}
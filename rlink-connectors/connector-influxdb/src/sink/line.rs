@@ -0,0 +1,234 @@
+use std::io::Write;
+
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{BufferReader, Record};
+
+/// Map the fields of the schema to the points of the line protocol,
+/// `measurement,tag=value field=value timestamp`, with the timestamp in millis.
+#[derive(Clone, Debug)]
+pub struct LineBuilder {
+    schema: Schema,
+    measurement: String,
+    tags: Vec<usize>,
+    fields: Vec<usize>,
+    timestamp: Option<usize>,
+}
+
+impl LineBuilder {
+    /// The `value_fields` are all fields except the tags and the timestamp if empty. Without
+    /// the `timestamp_field` the points are stamped by the server, and the replayed points are
+    /// not deduplicated.
+    pub fn new(
+        schema: Schema,
+        measurement: &str,
+        tag_fields: &[String],
+        value_fields: &[String],
+        timestamp_field: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let index_of = |name: &str| {
+            schema
+                .index_of(name)
+                .ok_or_else(|| anyhow!("field `{}` not found in the input schema", name))
+        };
+
+        let timestamp = match timestamp_field {
+            Some(name) => {
+                let i = index_of(name)?;
+                let field = schema.field(i);
+                if !field.is_numeric() || matches!(field.data_type(), DataType::Boolean) {
+                    return Err(anyhow!("timestamp field `{}` is not numeric", name));
+                }
+                Some(i)
+            }
+            None => None,
+        };
+
+        let mut tags = Vec::with_capacity(tag_fields.len());
+        for name in tag_fields {
+            tags.push(index_of(name)?);
+        }
+
+        let fields: Vec<usize> = if value_fields.is_empty() {
+            (0..schema.fields().len())
+                .filter(|i| !tags.contains(i) && Some(*i) != timestamp)
+                .collect()
+        } else {
+            let mut fields = Vec::with_capacity(value_fields.len());
+            for name in value_fields {
+                fields.push(index_of(name)?);
+            }
+            fields
+        };
+        if fields.is_empty() {
+            return Err(anyhow!("no field of the point"));
+        }
+
+        Ok(LineBuilder {
+            schema,
+            measurement: measurement.to_string(),
+            tags,
+            fields,
+            timestamp,
+        })
+    }
+
+    /// Append the line of the record to the `buffer`, the empty tags are omitted
+    pub fn write(&self, record: &mut Record, buffer: &mut Vec<u8>) -> std::io::Result<()> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        escape(&self.measurement, &[',', ' '], buffer);
+        for i in &self.tags {
+            let value = self.tag_value(&reader, *i)?;
+            if value.is_empty() {
+                continue;
+            }
+            buffer.push(b',');
+            escape(self.schema.field(*i).name(), &[',', '=', ' '], buffer);
+            buffer.push(b'=');
+            escape(value.as_str(), &[',', '=', ' '], buffer);
+        }
+
+        for (n, i) in self.fields.iter().enumerate() {
+            buffer.push(if n == 0 { b' ' } else { b',' });
+            escape(self.schema.field(*i).name(), &[',', '=', ' '], buffer);
+            buffer.push(b'=');
+            self.write_field(&reader, *i, buffer)?;
+        }
+
+        if let Some(i) = self.timestamp {
+            let timestamp = match self.schema.field(i).data_type() {
+                DataType::Int8 => reader.get_i8(i)? as i64,
+                DataType::UInt8 => reader.get_u8(i)? as i64,
+                DataType::Int16 => reader.get_i16(i)? as i64,
+                DataType::UInt16 => reader.get_u16(i)? as i64,
+                DataType::Int32 => reader.get_i32(i)? as i64,
+                DataType::UInt32 => reader.get_u32(i)? as i64,
+                DataType::Int64 => reader.get_i64(i)?,
+                DataType::UInt64 => reader.get_u64(i)? as i64,
+                DataType::Float32 => reader.get_f32(i)? as i64,
+                DataType::Float64 => reader.get_f64(i)? as i64,
+                _ => unreachable!(),
+            };
+            write!(buffer, " {}", timestamp)?;
+        }
+
+        buffer.push(b'\n');
+        Ok(())
+    }
+
+    fn tag_value(&self, reader: &BufferReader, i: usize) -> std::io::Result<String> {
+        let value = match self.schema.field(i).data_type() {
+            DataType::Boolean => reader.get_bool(i)?.to_string(),
+            DataType::Int8 => reader.get_i8(i)?.to_string(),
+            DataType::UInt8 => reader.get_u8(i)?.to_string(),
+            DataType::Int16 => reader.get_i16(i)?.to_string(),
+            DataType::UInt16 => reader.get_u16(i)?.to_string(),
+            DataType::Int32 => reader.get_i32(i)?.to_string(),
+            DataType::UInt32 => reader.get_u32(i)?.to_string(),
+            DataType::Int64 => reader.get_i64(i)?.to_string(),
+            DataType::UInt64 => reader.get_u64(i)?.to_string(),
+            DataType::Float32 => reader.get_f32(i)?.to_string(),
+            DataType::Float64 => reader.get_f64(i)?.to_string(),
+            DataType::Binary => String::from_utf8_lossy(reader.get_binary(i)?).to_string(),
+            DataType::String => reader.get_str(i)?.to_string(),
+        };
+        Ok(value)
+    }
+
+    fn write_field(
+        &self,
+        reader: &BufferReader,
+        i: usize,
+        buffer: &mut Vec<u8>,
+    ) -> std::io::Result<()> {
+        match self.schema.field(i).data_type() {
+            DataType::Boolean => write!(buffer, "{}", reader.get_bool(i)?),
+            DataType::Int8 => write!(buffer, "{}i", reader.get_i8(i)?),
+            DataType::Int16 => write!(buffer, "{}i", reader.get_i16(i)?),
+            DataType::Int32 => write!(buffer, "{}i", reader.get_i32(i)?),
+            DataType::Int64 => write!(buffer, "{}i", reader.get_i64(i)?),
+            DataType::UInt8 => write!(buffer, "{}u", reader.get_u8(i)?),
+            DataType::UInt16 => write!(buffer, "{}u", reader.get_u16(i)?),
+            DataType::UInt32 => write!(buffer, "{}u", reader.get_u32(i)?),
+            DataType::UInt64 => write!(buffer, "{}u", reader.get_u64(i)?),
+            DataType::Float32 => write!(buffer, "{}", reader.get_f32(i)?),
+            DataType::Float64 => write!(buffer, "{}", reader.get_f64(i)?),
+            DataType::Binary => {
+                let value = String::from_utf8_lossy(reader.get_binary(i)?).to_string();
+                write_string(value.as_str(), buffer);
+                Ok(())
+            }
+            DataType::String => {
+                write_string(reader.get_str(i)?, buffer);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Escape the `chars` with the backslash
+fn escape(s: &str, chars: &[char], buffer: &mut Vec<u8>) {
+    for c in s.chars() {
+        if chars.contains(&c) {
+            buffer.push(b'\\');
+        }
+        let mut b = [0; 4];
+        buffer.extend_from_slice(c.encode_utf8(&mut b).as_bytes());
+    }
+}
+
+/// The double quoted string field value
+fn write_string(s: &str, buffer: &mut Vec<u8>) {
+    buffer.push(b'"');
+    escape(s, &['"', '\\'], buffer);
+    buffer.push(b'"');
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::line::LineBuilder;
+
+    #[test]
+    pub fn line_builder_test() {
+        let schema = Schema::new(vec![
+            Field::new("host", DataType::String),
+            Field::new("region", DataType::String),
+            Field::new("value", DataType::Float64),
+            Field::new("count", DataType::Int64),
+            Field::new("message", DataType::String),
+            Field::new("ts", DataType::UInt64),
+        ]);
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_str("server 1").unwrap();
+        writer.set_str("").unwrap();
+        writer.set_f64(0.5).unwrap();
+        writer.set_i64(3).unwrap();
+        writer.set_str("say \"hi\"").unwrap();
+        writer.set_u64(1600000000000).unwrap();
+
+        let tags = vec!["host".to_string(), "region".to_string()];
+        let builder =
+            LineBuilder::new(schema.clone(), "cpu load", tags.as_slice(), &[], Some("ts")).unwrap();
+        let mut buffer = vec![];
+        builder.write(&mut record, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "cpu\\ load,host=server\\ 1 value=0.5,count=3i,message=\"say \\\"hi\\\"\" 1600000000000\n"
+        );
+
+        let fields = vec!["count".to_string()];
+        let builder =
+            LineBuilder::new(schema.clone(), "cpu", &[], fields.as_slice(), None).unwrap();
+        let mut buffer = vec![];
+        builder.write(&mut record, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "cpu count=3i\n");
+
+        assert!(LineBuilder::new(schema.clone(), "cpu", &[], &[], Some("host")).is_err());
+        assert!(LineBuilder::new(schema, "cpu", &[], &[], Some("time")).is_err());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod builder;
+pub mod line;
+pub mod output_format;
+
+pub(crate) mod client;
+pub(crate) mod writer;
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
+
+use crate::sink::client::WriteClient;
+use crate::sink::line::LineBuilder;
+use crate::sink::writer::InfluxdbWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// How to handle the batch with the points rejected by the server
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PartialWritePolicy {
+    /// log and drop the rejected points, the valid points of the batch are written
+    Drop,
+    /// retry the batch and fail the job after the retries
+    Retry,
+}
+
+/// Write the records to the bucket as the points of the line protocol
+#[derive(NamedFunction)]
+pub struct InfluxdbOutputFormat {
+    url: String,
+    org: String,
+    bucket: String,
+    token: Option<String>,
+
+    measurement: String,
+    tag_fields: Vec<String>,
+    value_fields: Vec<String>,
+    timestamp_field: Option<String>,
+    partial_write_policy: PartialWritePolicy,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+
+    writer: Option<BatchWriterHandle>,
+}
+
+impl InfluxdbOutputFormat {
+    pub fn new(
+        url: String,
+        org: String,
+        bucket: String,
+        measurement: String,
+        buffer_size: usize,
+    ) -> Self {
+        InfluxdbOutputFormat {
+            url,
+            org,
+            bucket,
+            token: None,
+            measurement,
+            tag_fields: vec![],
+            value_fields: vec![],
+            timestamp_field: None,
+            partial_write_policy: PartialWritePolicy::Retry,
+            buffer_size,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            writer: None,
+        }
+    }
+
+    pub fn token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn tag_fields(mut self, tag_fields: Vec<String>) -> Self {
+        self.tag_fields = tag_fields;
+        self
+    }
+
+    pub fn value_fields(mut self, value_fields: Vec<String>) -> Self {
+        self.value_fields = value_fields;
+        self
+    }
+
+    pub fn timestamp_field(mut self, timestamp_field: String) -> Self {
+        self.timestamp_field = Some(timestamp_field);
+        self
+    }
+
+    pub fn partial_write_policy(mut self, partial_write_policy: PartialWritePolicy) -> Self {
+        self.partial_write_policy = partial_write_policy;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[async_trait]
+impl OutputFormat for InfluxdbOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let line_builder = LineBuilder::new(
+            context.input_schema.clone().into(),
+            self.measurement.as_str(),
+            self.tag_fields.as_slice(),
+            self.value_fields.as_slice(),
+            self.timestamp_field.as_deref(),
+        )?;
+        let client = WriteClient::new(
+            self.url.as_str(),
+            self.org.as_str(),
+            self.bucket.as_str(),
+            self.token.as_deref(),
+        )?;
+
+        let writer = InfluxdbWriter::new(
+            client,
+            line_builder,
+            self.partial_write_policy,
+            self.max_retries,
+        );
+        let writer = BatchWriterThread::new("Influxdb", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), context.task_id.to_tags(), self.buffer_size);
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write influxdb record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for InfluxdbOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered lines are written on the checkpoint, the replayed points with the timestamp
+    /// overwrite the same points
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
+        None
+    }
+}
@@ -0,0 +1,111 @@
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use tokio::time::Instant;
+
+use crate::sink::client::{WriteClient, WriteError};
+use crate::sink::line::LineBuilder;
+use crate::sink::output_format::PartialWritePolicy;
+
+pub(crate) struct InfluxdbWriter {
+    client: WriteClient,
+    line_builder: LineBuilder,
+    partial_write_policy: PartialWritePolicy,
+    max_retries: usize,
+
+    lines: Vec<u8>,
+    points: usize,
+}
+
+impl InfluxdbWriter {
+    pub fn new(
+        client: WriteClient,
+        line_builder: LineBuilder,
+        partial_write_policy: PartialWritePolicy,
+        max_retries: usize,
+    ) -> Self {
+        InfluxdbWriter {
+            client,
+            line_builder,
+            partial_write_policy,
+            max_retries,
+            lines: Vec::new(),
+            points: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl BatchWriter for InfluxdbWriter {
+    fn push(&mut self, mut record: Record) -> anyhow::Result<()> {
+        let len = self.lines.len();
+        match self.line_builder.write(&mut record, &mut self.lines) {
+            Ok(()) => {
+                self.points += 1;
+                Ok(())
+            }
+            Err(e) => {
+                self.lines.truncate(len);
+                Err(e.into())
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.points
+    }
+
+    /// Write the buffered lines. The rejected points are dropped or retried by the
+    /// `partial_write_policy`, the points with the timestamp overwrite the same points on retry
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let points = self.points;
+
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            let (error, retry_after) = match self.client.write(self.lines.clone()).await {
+                Ok(()) => {
+                    metrics.flushed(points as u64, begin.elapsed());
+                    break;
+                }
+                Err(WriteError::PartialWrite(e))
+                    if self.partial_write_policy == PartialWritePolicy::Drop =>
+                {
+                    // the number of the rejected points isn't reported, the batch is counted
+                    warn!("drop the rejected points of {} points. {}", points, e);
+                    metrics.dropped(1);
+                    break;
+                }
+                Err(WriteError::Fatal(e)) => {
+                    metrics.failure();
+                    return Err(anyhow!("write {} points error. {}", points, e));
+                }
+                Err(WriteError::Retryable(e, retry_after)) => (e, retry_after),
+                Err(e) => (e.to_string(), None),
+            };
+
+            if attempt >= self.max_retries {
+                metrics.failure();
+                return Err(anyhow!(
+                    "write {} points error after {} retries. {}",
+                    points,
+                    self.max_retries,
+                    error
+                ));
+            }
+
+            attempt += 1;
+            metrics.retry();
+            warn!(
+                "write {} points error, retry({}/{}). {}",
+                points, attempt, self.max_retries, error
+            );
+            let backoff = backoff(attempt);
+            tokio::time::sleep(retry_after.unwrap_or(backoff).max(backoff)).await;
+        }
+
+        self.lines.clear();
+        self.points = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use std::convert::TryFrom;
+
+/// The database the sink writes to, detected by the scheme of the url
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Dialect {
+    Postgres,
+    MySql,
+}
+
+impl TryFrom<&str> for Dialect {
+    type Error = anyhow::Error;
+
+    fn try_from(url: &str) -> Result<Self, Self::Error> {
+        let scheme = url.split("://").next().unwrap_or_default();
+        match scheme.to_lowercase().as_str() {
+            "postgres" | "postgresql" => Ok(Dialect::Postgres),
+            "mysql" | "mariadb" => Ok(Dialect::MySql),
+            _ => Err(anyhow!("unsupported jdbc url `{}`", scheme)),
+        }
+    }
+}
+
+/// How the rows are written to the table
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum WriteMode {
+    #[default]
+    Insert,
+    /// Update the row if the key conflicts, the key columns must be the primary key or an
+    /// unique index of the table
+    Upsert { key_columns: Vec<String> },
+}
+
+impl Dialect {
+    pub fn quote(&self, identifier: &str) -> String {
+        match self {
+            Dialect::Postgres => format!("\"{}\"", identifier.replace('"', "\"\"")),
+            Dialect::MySql => format!("`{}`", identifier.replace('`', "``")),
+        }
+    }
+
+    fn placeholder(&self, index: usize) -> String {
+        match self {
+            Dialect::Postgres => format!("${}", index + 1),
+            Dialect::MySql => "?".to_string(),
+        }
+    }
+
+    /// The statement writing `rows` rows of the `columns` in one round trip
+    pub fn insert_statement(
+        &self,
+        table: &str,
+        columns: &[String],
+        rows: usize,
+        write_mode: &WriteMode,
+    ) -> String {
+        let column_list: Vec<String> = columns.iter().map(|x| self.quote(x)).collect();
+
+        let values: Vec<String> = (0..rows)
+            .map(|row| {
+                let placeholders: Vec<String> = (0..columns.len())
+                    .map(|column| self.placeholder(row * columns.len() + column))
+                    .collect();
+                format!("({})", placeholders.join(", "))
+            })
+            .collect();
+
+        let mut sql = format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            table,
+            column_list.join(", "),
+            values.join(", ")
+        );
+
+        if let WriteMode::Upsert { key_columns } = write_mode {
+            let updates: Vec<&String> = columns
+                .iter()
+                .filter(|x| !key_columns.contains(x))
+                .collect();
+
+            match self {
+                Dialect::Postgres => {
+                    let keys: Vec<String> = key_columns.iter().map(|x| self.quote(x)).collect();
+                    if updates.is_empty() {
+                        sql.push_str(
+                            format!(" ON CONFLICT ({}) DO NOTHING", keys.join(", ")).as_str(),
+                        );
+                    } else {
+                        let sets: Vec<String> = updates
+                            .iter()
+                            .map(|x| format!("{0} = EXCLUDED.{0}", self.quote(x)))
+                            .collect();
+                        sql.push_str(
+                            format!(
+                                " ON CONFLICT ({}) DO UPDATE SET {}",
+                                keys.join(", "),
+                                sets.join(", ")
+                            )
+                            .as_str(),
+                        );
+                    }
+                }
+                Dialect::MySql => {
+                    // a no-op update keeps the existing row when all columns are the key
+                    let updates = if updates.is_empty() {
+                        key_columns.iter().take(1).collect()
+                    } else {
+                        updates
+                    };
+                    let sets: Vec<String> = updates
+                        .iter()
+                        .map(|x| format!("{0} = VALUES({0})", self.quote(x)))
+                        .collect();
+                    sql.push_str(format!(" ON DUPLICATE KEY UPDATE {}", sets.join(", ")).as_str());
+                }
+            }
+        }
+
+        sql
+    }
+
+    /// The statement selecting the `columns` of a row by the values of the `key_columns`
+    pub fn select_statement(
+        &self,
+        table: &str,
+        columns: &[String],
+        key_columns: &[String],
+    ) -> String {
+        let column_list: Vec<String> = columns.iter().map(|x| self.quote(x)).collect();
+        let conditions: Vec<String> = key_columns
+            .iter()
+            .enumerate()
+            .map(|(i, x)| format!("{} = {}", self.quote(x), self.placeholder(i)))
+            .collect();
+
+        format!(
+            "SELECT {} FROM {} WHERE {} LIMIT 1",
+            column_list.join(", "),
+            table,
+            conditions.join(" AND ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::dialect::{Dialect, WriteMode};
+
+    #[test]
+    pub fn insert_statement_test() {
+        let columns = vec!["id".to_string(), "name".to_string(), "amount".to_string()];
+        let upsert = WriteMode::Upsert {
+            key_columns: vec!["id".to_string()],
+        };
+
+        let dialect = Dialect::try_from("postgres://localhost/rlink").unwrap();
+        assert_eq!(
+            dialect.insert_statement("orders", &columns, 2, &WriteMode::Insert),
+            "INSERT INTO orders (\"id\", \"name\", \"amount\") VALUES ($1, $2, $3), ($4, $5, $6)"
+        );
+        assert_eq!(
+            dialect.insert_statement("orders", &columns, 1, &upsert),
+            "INSERT INTO orders (\"id\", \"name\", \"amount\") VALUES ($1, $2, $3) \
+             ON CONFLICT (\"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\", \"amount\" = EXCLUDED.\"amount\""
+        );
+
+        let dialect = Dialect::try_from("mysql://localhost/rlink").unwrap();
+        assert_eq!(
+            dialect.insert_statement("orders", &columns, 1, &upsert),
+            "INSERT INTO orders (`id`, `name`, `amount`) VALUES (?, ?, ?) \
+             ON DUPLICATE KEY UPDATE `name` = VALUES(`name`), `amount` = VALUES(`amount`)"
+        );
+
+        assert!(Dialect::try_from("sqlite://rlink.db").is_err());
+    }
+
+    #[test]
+    pub fn select_statement_test() {
+        let columns = vec!["name".to_string(), "level".to_string()];
+        let key_columns = vec!["id".to_string(), "region".to_string()];
+
+        let dialect = Dialect::Postgres;
+        assert_eq!(
+            dialect.select_statement("users", &columns, &key_columns),
+            "SELECT \"name\", \"level\" FROM users WHERE \"id\" = $1 AND \"region\" = $2 LIMIT 1"
+        );
+
+        let dialect = Dialect::MySql;
+        assert_eq!(
+            dialect.select_statement("users", &columns, &key_columns),
+            "SELECT `name`, `level` FROM users WHERE `id` = ? AND `region` = ? LIMIT 1"
+        );
+    }
+}
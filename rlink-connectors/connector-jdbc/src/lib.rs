@@ -0,0 +1,33 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod dialect;
+pub mod lookup;
+pub mod sink;
+
+pub use lookup::JdbcLookupBackend;
+pub use sink::output_format::JdbcOutputFormat;
+
+pub const JDBC: &str = "jdbc";
+pub const URL: &str = "url";
+pub const MAX_CONNECTIONS: &str = "max.connections";
+
+pub const TABLE: &str = "table";
+pub const KEY_COLUMNS: &str = "key.columns";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 1000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 1000;
+pub const SINK_MAX_RETRIES: usize = 3;
+pub const SINK_MAX_CONNECTIONS: u32 = 2;
+pub const LOOKUP_MAX_CONNECTIONS: u32 = 8;
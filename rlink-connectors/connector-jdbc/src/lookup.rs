@@ -0,0 +1,93 @@
+use std::convert::TryFrom;
+
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+use rlink::core::function::Context;
+use rlink::functions::lookup::LookupBackend;
+
+use crate::dialect::Dialect;
+use crate::sink::pool::{JdbcPool, Statement};
+use crate::sink::value::read_row;
+use crate::LOOKUP_MAX_CONNECTIONS;
+
+/// The backend of the `LookupFunction`, select the columns of the `schema` from the row of
+/// the `table` whose `key_columns` equal the key fields, in the order of the key fields
+pub struct JdbcLookupBackend {
+    url: String,
+    table: String,
+    key_columns: Vec<String>,
+    schema: Schema,
+    max_connections: u32,
+
+    key_schema: Schema,
+    sql: String,
+    pool: Option<JdbcPool>,
+}
+
+impl JdbcLookupBackend {
+    pub fn new(url: &str, table: &str, key_columns: Vec<String>, schema: Schema) -> Self {
+        JdbcLookupBackend {
+            url: url.to_string(),
+            table: table.to_string(),
+            key_columns,
+            schema,
+            max_connections: LOOKUP_MAX_CONNECTIONS,
+            key_schema: Schema::empty(),
+            sql: String::new(),
+            pool: None,
+        }
+    }
+
+    /// The connections shared by the concurrent lookups of a task
+    pub fn max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+}
+
+#[async_trait]
+impl LookupBackend for JdbcLookupBackend {
+    async fn open(&mut self, key_schema: &Schema, _context: &Context) -> anyhow::Result<()> {
+        if key_schema.fields().len() != self.key_columns.len() {
+            return Err(anyhow!(
+                "{} key fields for the key columns {:?}",
+                key_schema.fields().len(),
+                self.key_columns
+            ));
+        }
+
+        let dialect = Dialect::try_from(self.url.as_str())?;
+        let columns: Vec<String> = self
+            .schema
+            .fields()
+            .iter()
+            .map(|x| x.name().to_string())
+            .collect();
+        self.sql = dialect.select_statement(
+            self.table.as_str(),
+            columns.as_slice(),
+            self.key_columns.as_slice(),
+        );
+        self.key_schema = key_schema.clone();
+
+        let pool = JdbcPool::connect(dialect, self.url.as_str(), self.max_connections).await?;
+        self.pool = Some(pool);
+        Ok(())
+    }
+
+    async fn lookup(&self, mut key: Record) -> anyhow::Result<Option<Record>> {
+        let statement = Statement {
+            sql: self.sql.clone(),
+            values: read_row(&mut key, &self.key_schema)?,
+        };
+        self.pool
+            .as_ref()
+            .unwrap()
+            .fetch_record(&statement, &self.schema)
+            .await
+    }
+
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,167 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::dialect::{Dialect, WriteMode};
+use crate::{
+    JdbcOutputFormat, BATCH_INTERVAL, BATCH_SIZE, BUFFER_SIZE, JDBC, KEY_COLUMNS, MAX_CONNECTIONS,
+    MAX_RETRIES, SINK_CHANNEL_SIZE, TABLE, URL,
+};
+
+pub struct JdbcOutputFormatBuilder {
+    url: String,
+    table: String,
+    write_mode: WriteMode,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+    batch_interval: Option<Duration>,
+    max_retries: Option<usize>,
+    max_connections: Option<u32>,
+}
+
+impl JdbcOutputFormatBuilder {
+    /// The database is detected by the scheme of the `url`, `postgres://` or `mysql://`
+    pub fn new(url: &str, table: &str) -> anyhow::Result<Self> {
+        Dialect::try_from(url)?;
+
+        Ok(JdbcOutputFormatBuilder {
+            url: url.to_string(),
+            table: table.to_string(),
+            write_mode: WriteMode::default(),
+            buffer_size: None,
+            batch_size: None,
+            batch_interval: None,
+            max_retries: None,
+            max_connections: None,
+        })
+    }
+
+    pub fn write_mode(mut self, write_mode: WriteMode) -> Self {
+        self.write_mode = write_mode;
+        self
+    }
+
+    /// Upsert the rows on the conflict of the `key_columns`
+    pub fn key_columns(self, key_columns: Vec<String>) -> Self {
+        self.write_mode(WriteMode::Upsert { key_columns })
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Flush once `batch_size` rows are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Flush the buffered rows at least every `batch_interval`
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = Some(batch_interval);
+        self
+    }
+
+    /// Retry a failed flush up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    pub fn build(self) -> JdbcOutputFormat {
+        info!("build jdbc sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let mut output_format =
+            JdbcOutputFormat::new(self.url, self.table, self.write_mode, buffer_size);
+
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(batch_interval) = self.batch_interval {
+            output_format = output_format.batch_interval(batch_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+        if let Some(max_connections) = self.max_connections {
+            output_format = output_format.max_connections(max_connections);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for JdbcOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // the url may carry the password
+        let url = match self.url.split_once('@') {
+            Some((_, host)) => {
+                let scheme = self.url.split("://").next().unwrap_or_default();
+                format!("{}://***@{}", scheme, host)
+            }
+            None => self.url.clone(),
+        };
+
+        f.debug_struct("JdbcOutputFormatBuilder")
+            .field("url", &url)
+            .field("table", &self.table)
+            .field("write_mode", &self.write_mode)
+            .field("buffer_size", &self.buffer_size)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .field("max_connections", &self.max_connections)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for JdbcOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let jdbc_properties = properties.to_sub_properties(JDBC);
+        let url = jdbc_properties.get_string(URL)?;
+        let table = properties.get_string(TABLE)?;
+
+        let mut builder = JdbcOutputFormatBuilder::new(url.as_str(), table.as_str())?;
+
+        if let Ok(max_connections) = jdbc_properties.get_u32(MAX_CONNECTIONS) {
+            builder = builder.max_connections(max_connections);
+        }
+
+        if let Ok(key_columns) = properties.get_string(KEY_COLUMNS) {
+            let key_columns: Vec<String> = key_columns
+                .split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect();
+            if !key_columns.is_empty() {
+                builder = builder.key_columns(key_columns);
+            }
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(batch_interval) = properties.get_duration(BATCH_INTERVAL) {
+            builder = builder.batch_interval(batch_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
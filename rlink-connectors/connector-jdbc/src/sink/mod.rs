@@ -0,0 +1,5 @@
+pub mod builder;
+pub mod output_format;
+pub(crate) mod pool;
+pub mod value;
+pub(crate) mod writer;
@@ -0,0 +1,144 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::metrics::Tag;
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
+
+use crate::dialect::{Dialect, WriteMode};
+use crate::sink::writer::JdbcWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_MAX_CONNECTIONS, SINK_MAX_RETRIES};
+
+/// Write the records to the `table`, the columns are the field names of the input schema
+#[derive(NamedFunction)]
+pub struct JdbcOutputFormat {
+    url: String,
+    table: String,
+    write_mode: WriteMode,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+    max_connections: u32,
+
+    writer: Option<BatchWriterHandle>,
+}
+
+impl JdbcOutputFormat {
+    pub fn new(url: String, table: String, write_mode: WriteMode, buffer_size: usize) -> Self {
+        JdbcOutputFormat {
+            url,
+            table,
+            write_mode,
+            buffer_size,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            max_connections: SINK_MAX_CONNECTIONS,
+            writer: None,
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn max_connections(mut self, max_connections: u32) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+}
+
+#[async_trait]
+impl OutputFormat for JdbcOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let dialect = Dialect::try_from(self.url.as_str())?;
+        let schema: Schema = context.input_schema.clone().into();
+
+        if let WriteMode::Upsert { key_columns } = &self.write_mode {
+            for key_column in key_columns {
+                if !schema
+                    .fields()
+                    .iter()
+                    .any(|x| x.name() == key_column.as_str())
+                {
+                    return Err(core::Error::from(format!(
+                        "key column `{}` not found in the input schema",
+                        key_column
+                    )));
+                }
+            }
+        }
+
+        let mut tags = context.task_id.to_tags();
+        tags.push(Tag::new("table", self.table.as_str()));
+
+        let writer = JdbcWriter::new(
+            self.url.clone(),
+            dialect,
+            self.table.clone(),
+            schema,
+            self.write_mode.clone(),
+            self.max_retries,
+            self.max_connections,
+        );
+        let writer = BatchWriterThread::new("Jdbc", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), tags, self.buffer_size);
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write jdbc record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for JdbcOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered rows are written on the checkpoint
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
+        None
+    }
+}
@@ -0,0 +1,139 @@
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::Row;
+
+use crate::dialect::Dialect;
+use crate::sink::value::JdbcValue;
+
+/// A statement and its flattened parameters
+pub(crate) struct Statement {
+    pub sql: String,
+    pub values: Vec<JdbcValue>,
+}
+
+macro_rules! bind_values {
+    ($query:expr, $values:expr) => {{
+        let mut query = $query;
+        for value in $values {
+            query = match value {
+                JdbcValue::Bool(v) => query.bind(*v),
+                JdbcValue::I16(v) => query.bind(*v),
+                JdbcValue::I32(v) => query.bind(*v),
+                JdbcValue::I64(v) => query.bind(*v),
+                JdbcValue::F32(v) => query.bind(*v),
+                JdbcValue::F64(v) => query.bind(*v),
+                JdbcValue::String(v) => query.bind(v.as_str()),
+                JdbcValue::Bytes(v) => query.bind(v.as_slice()),
+            };
+        }
+        query
+    }};
+}
+
+macro_rules! execute_in_transaction {
+    ($pool:expr, $statements:expr) => {{
+        let mut transaction = $pool.begin().await?;
+        for statement in $statements {
+            bind_values!(sqlx::query(statement.sql.as_str()), statement.values.iter())
+                .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+    }};
+}
+
+/// Read the columns of the row as the fields of the `schema`, the columns are decoded as the
+/// widened types the sink binds, e.g. `Int8` from a `SMALLINT`
+macro_rules! read_record {
+    ($row:expr, $schema:expr) => {{
+        let row = $row;
+        let mut record = Record::new();
+        let mut writer = record.as_writer($schema.as_type_ids());
+        for (i, field) in $schema.fields().iter().enumerate() {
+            match field.data_type() {
+                DataType::Boolean => writer.set_bool(row.try_get(i)?),
+                DataType::Int8 => writer.set_i8(row.try_get::<i16, _>(i)? as i8),
+                DataType::UInt8 => writer.set_u8(row.try_get::<i16, _>(i)? as u8),
+                DataType::Int16 => writer.set_i16(row.try_get(i)?),
+                DataType::UInt16 => writer.set_u16(row.try_get::<i32, _>(i)? as u16),
+                DataType::Int32 => writer.set_i32(row.try_get(i)?),
+                DataType::UInt32 => writer.set_u32(row.try_get::<i64, _>(i)? as u32),
+                DataType::Int64 => writer.set_i64(row.try_get(i)?),
+                DataType::UInt64 => writer.set_u64(row.try_get::<i64, _>(i)? as u64),
+                DataType::Float32 => writer.set_f32(row.try_get(i)?),
+                DataType::Float64 => writer.set_f64(row.try_get(i)?),
+                DataType::Binary => writer.set_binary(row.try_get::<Vec<u8>, _>(i)?.as_slice()),
+                DataType::String => writer.set_str(row.try_get::<String, _>(i)?.as_str()),
+            }?;
+        }
+        record
+    }};
+}
+
+macro_rules! fetch_record {
+    ($pool:expr, $statement:expr, $schema:expr) => {{
+        let row = bind_values!(
+            sqlx::query($statement.sql.as_str()),
+            $statement.values.iter()
+        )
+        .fetch_optional($pool)
+        .await?;
+        match row {
+            Some(row) => Some(read_record!(row, $schema)),
+            None => None,
+        }
+    }};
+}
+
+pub(crate) enum JdbcPool {
+    Postgres(PgPool),
+    MySql(MySqlPool),
+}
+
+impl JdbcPool {
+    pub async fn connect(
+        dialect: Dialect,
+        url: &str,
+        max_connections: u32,
+    ) -> anyhow::Result<Self> {
+        let pool = match dialect {
+            Dialect::Postgres => JdbcPool::Postgres(
+                PgPoolOptions::new()
+                    .max_connections(max_connections)
+                    .connect(url)
+                    .await?,
+            ),
+            Dialect::MySql => JdbcPool::MySql(
+                MySqlPoolOptions::new()
+                    .max_connections(max_connections)
+                    .connect(url)
+                    .await?,
+            ),
+        };
+        Ok(pool)
+    }
+
+    /// Execute the statements in one transaction, so a retried batch is never half written
+    pub async fn execute(&self, statements: &[Statement]) -> anyhow::Result<()> {
+        match self {
+            JdbcPool::Postgres(pool) => execute_in_transaction!(pool, statements),
+            JdbcPool::MySql(pool) => execute_in_transaction!(pool, statements),
+        }
+        Ok(())
+    }
+
+    /// Fetch the first row of the statement as a record of the `schema`
+    pub async fn fetch_record(
+        &self,
+        statement: &Statement,
+        schema: &Schema,
+    ) -> anyhow::Result<Option<Record>> {
+        let record = match self {
+            JdbcPool::Postgres(pool) => fetch_record!(pool, statement, schema),
+            JdbcPool::MySql(pool) => fetch_record!(pool, statement, schema),
+        };
+        Ok(record)
+    }
+}
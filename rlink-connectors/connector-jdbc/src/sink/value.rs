@@ -0,0 +1,43 @@
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+
+/// A column value bound to the statement. The unsigned types are widened to the signed ones
+/// since Postgres has no unsigned column, `u64` is bound as `i64` and overflows above `i64::MAX`
+#[derive(Clone, Debug, PartialEq)]
+pub enum JdbcValue {
+    Bool(bool),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+/// Read the fields of the record in the order of the `schema`
+pub(crate) fn read_row(record: &mut Record, schema: &Schema) -> anyhow::Result<Vec<JdbcValue>> {
+    let reader = record.as_reader(schema.as_type_ids());
+
+    let mut row = Vec::with_capacity(schema.fields().len());
+    for (i, field) in schema.fields().iter().enumerate() {
+        let value = match field.data_type() {
+            DataType::Boolean => JdbcValue::Bool(reader.get_bool(i)?),
+            DataType::Int8 => JdbcValue::I16(reader.get_i8(i)? as i16),
+            DataType::UInt8 => JdbcValue::I16(reader.get_u8(i)? as i16),
+            DataType::Int16 => JdbcValue::I16(reader.get_i16(i)?),
+            DataType::UInt16 => JdbcValue::I32(reader.get_u16(i)? as i32),
+            DataType::Int32 => JdbcValue::I32(reader.get_i32(i)?),
+            DataType::UInt32 => JdbcValue::I64(reader.get_u32(i)? as i64),
+            DataType::Int64 => JdbcValue::I64(reader.get_i64(i)?),
+            DataType::UInt64 => JdbcValue::I64(reader.get_u64(i)? as i64),
+            DataType::Float32 => JdbcValue::F32(reader.get_f32(i)?),
+            DataType::Float64 => JdbcValue::F64(reader.get_f64(i)?),
+            DataType::Binary => JdbcValue::Bytes(reader.get_binary(i)?.to_vec()),
+            DataType::String => JdbcValue::String(reader.get_str(i)?.to_string()),
+        };
+        row.push(value);
+    }
+
+    Ok(row)
+}
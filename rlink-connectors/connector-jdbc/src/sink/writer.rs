@@ -0,0 +1,132 @@
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use tokio::time::Instant;
+
+use crate::dialect::{Dialect, WriteMode};
+use crate::sink::pool::{JdbcPool, Statement};
+use crate::sink::value::{read_row, JdbcValue};
+
+/// The bind parameters limit of a statement, shared by Postgres and MySQL
+const MAX_STATEMENT_PARAMETERS: usize = 65535;
+
+pub(crate) struct JdbcWriter {
+    url: String,
+    dialect: Dialect,
+    table: String,
+    schema: Schema,
+    write_mode: WriteMode,
+    max_retries: usize,
+    max_connections: u32,
+
+    pool: Option<JdbcPool>,
+    rows: Vec<Vec<JdbcValue>>,
+}
+
+impl JdbcWriter {
+    pub fn new(
+        url: String,
+        dialect: Dialect,
+        table: String,
+        schema: Schema,
+        write_mode: WriteMode,
+        max_retries: usize,
+        max_connections: u32,
+    ) -> Self {
+        JdbcWriter {
+            url,
+            dialect,
+            table,
+            schema,
+            write_mode,
+            max_retries,
+            max_connections: max_connections.max(1),
+            pool: None,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Split the rows into statements under the bind parameters limit
+    fn statements(&self, rows: &[Vec<JdbcValue>]) -> Vec<Statement> {
+        let columns: Vec<String> = self
+            .schema
+            .fields()
+            .iter()
+            .map(|x| x.name().to_string())
+            .collect();
+        let rows_per_statement = (MAX_STATEMENT_PARAMETERS / columns.len().max(1)).max(1);
+
+        rows.chunks(rows_per_statement)
+            .map(|chunk| Statement {
+                sql: self.dialect.insert_statement(
+                    self.table.as_str(),
+                    &columns,
+                    chunk.len(),
+                    &self.write_mode,
+                ),
+                values: chunk.iter().flatten().cloned().collect(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl BatchWriter for JdbcWriter {
+    async fn open(&mut self) -> anyhow::Result<()> {
+        let pool = JdbcPool::connect(self.dialect, self.url.as_str(), self.max_connections)
+            .await
+            .map_err(|e| anyhow!("connect to the database error. {}", e))?;
+        self.pool = Some(pool);
+        Ok(())
+    }
+
+    fn push(&mut self, mut record: Record) -> anyhow::Result<()> {
+        let row = read_row(&mut record, &self.schema)?;
+        self.rows.push(row);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let pool = self.pool.as_ref().unwrap();
+        let statements = self.statements(&self.rows);
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            match pool.execute(&statements).await {
+                Ok(()) => {
+                    metrics.flushed(self.rows.len() as u64, begin.elapsed());
+                    self.rows.clear();
+                    return Ok(());
+                }
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    metrics.retry();
+                    warn!(
+                        "flush {} rows to `{}` error, retry({}/{}). {}",
+                        self.rows.len(),
+                        self.table,
+                        attempt,
+                        self.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(backoff(attempt)).await;
+                }
+                Err(e) => {
+                    metrics.failure();
+                    return Err(anyhow!(
+                        "flush {} rows to `{}` error after {} retries. {}",
+                        self.rows.len(),
+                        self.table,
+                        self.max_retries,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+}
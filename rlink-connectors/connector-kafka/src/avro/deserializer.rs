@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use apache_avro::Schema as AvroSchema;
+use rlink::core::data_types::Schema;
+use rlink::core::element::{FnSchema, Record};
+
+use crate::avro::registry::SchemaRegistryClient;
+use crate::avro::{avro_to_record, decode_wire_format};
+use crate::source::deserializer::{KafkaRecordDeserializer, KafkaRecordDeserializerBuilder};
+
+/// Decode the avro payload by the writer schema registered in the Schema Registry
+pub struct AvroRecordDeserializer {
+    registry: Arc<SchemaRegistryClient>,
+    schema: Schema,
+    reader_schema: Option<Arc<AvroSchema>>,
+}
+
+impl AvroRecordDeserializer {
+    fn decode(&self, payload: &[u8]) -> anyhow::Result<Record> {
+        let (schema_id, mut datum) = decode_wire_format(payload)?;
+        let writer_schema = self
+            .registry
+            .cached_schema(schema_id)
+            .ok_or(anyhow!("the writer schema {} isn't fetched", schema_id))?;
+
+        let value = apache_avro::from_avro_datum(
+            writer_schema.as_ref(),
+            &mut datum,
+            self.reader_schema.as_ref().map(|x| x.as_ref()),
+        )?;
+        avro_to_record(&value, &self.schema)
+    }
+}
+
+#[async_trait]
+impl KafkaRecordDeserializer for AvroRecordDeserializer {
+    async fn prepare(&mut self, payload: &[u8]) -> anyhow::Result<()> {
+        let (schema_id, _datum) = decode_wire_format(payload)?;
+        self.registry.schema_by_id(schema_id).await?;
+        Ok(())
+    }
+
+    fn deserialize(
+        &mut self,
+        _timestamp: i64,
+        _key: &[u8],
+        payload: &[u8],
+        topic: &str,
+        partition: i32,
+        offset: i64,
+    ) -> Vec<Record> {
+        match self.decode(payload) {
+            Ok(record) => vec![record],
+            Err(e) => {
+                warn!(
+                    "avro decode error, the message is skipped. topic: {}, partition: {}, offset: {}, error: {}",
+                    topic, partition, offset, e
+                );
+                vec![]
+            }
+        }
+    }
+}
+
+pub struct AvroRecordDeserializerBuilder {
+    registry: Arc<SchemaRegistryClient>,
+    schema: Schema,
+    reader_schema: Option<Arc<AvroSchema>>,
+}
+
+impl AvroRecordDeserializerBuilder {
+    /// `schema` is the schema of the output records, the avro fields are mapped by name
+    pub fn new(registry: Arc<SchemaRegistryClient>, schema: Schema) -> Self {
+        AvroRecordDeserializerBuilder {
+            registry,
+            schema,
+            reader_schema: None,
+        }
+    }
+
+    /// Resolve the writer schema to the reader schema, eg: fill the defaults of new fields
+    pub fn reader_schema(mut self, reader_schema: &str) -> anyhow::Result<Self> {
+        self.reader_schema = Some(Arc::new(AvroSchema::parse_str(reader_schema)?));
+        Ok(self)
+    }
+}
+
+impl KafkaRecordDeserializerBuilder for AvroRecordDeserializerBuilder {
+    fn build(&self) -> Box<dyn KafkaRecordDeserializer> {
+        Box::new(AvroRecordDeserializer {
+            registry: self.registry.clone(),
+            schema: self.schema.clone(),
+            reader_schema: self.reader_schema.clone(),
+        })
+    }
+
+    fn schema(&self) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+}
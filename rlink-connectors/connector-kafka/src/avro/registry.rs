@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use apache_avro::Schema as AvroSchema;
+use rlink::utils::http::client::{get, post};
+
+/// How the subject is named when the schema is registered or looked up
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum SubjectNameStrategy {
+    /// `{topic}-key` or `{topic}-value`, the default of the Confluent serializers
+    #[default]
+    TopicName,
+    /// the full name of the avro record
+    RecordName,
+    /// `{topic}-{record full name}`
+    TopicRecordName,
+}
+
+impl SubjectNameStrategy {
+    pub fn subject(
+        &self,
+        topic: &str,
+        schema: &AvroSchema,
+        is_key: bool,
+    ) -> anyhow::Result<String> {
+        let record_name = || match schema {
+            AvroSchema::Record { name, .. } => Ok(name.fullname(None)),
+            _ => Err(anyhow!(
+                "the subject name strategy requires a record schema"
+            )),
+        };
+
+        match self {
+            Self::TopicName => {
+                let suffix = if is_key { "key" } else { "value" };
+                Ok(format!("{}-{}", topic, suffix))
+            }
+            Self::RecordName => record_name(),
+            Self::TopicRecordName => Ok(format!("{}-{}", topic, record_name()?)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SchemaPayload {
+    schema: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SchemaIdPayload {
+    id: u32,
+}
+
+/// Client of the Confluent Schema Registry, the schemas and ids are cached once fetched
+/// because they are immutable in the registry
+pub struct SchemaRegistryClient {
+    url: String,
+    schemas: Mutex<HashMap<u32, Arc<AvroSchema>>>,
+    subject_ids: Mutex<HashMap<String, u32>>,
+}
+
+impl SchemaRegistryClient {
+    pub fn new(url: &str) -> Self {
+        SchemaRegistryClient {
+            url: url.trim_end_matches('/').to_string(),
+            schemas: Mutex::new(HashMap::new()),
+            subject_ids: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// Get the writer schema fetched by `schema_by_id` before
+    pub fn cached_schema(&self, id: u32) -> Option<Arc<AvroSchema>> {
+        self.schemas.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Get the writer schema by the id in the message
+    pub async fn schema_by_id(&self, id: u32) -> anyhow::Result<Arc<AvroSchema>> {
+        if let Some(schema) = self.cached_schema(id) {
+            return Ok(schema);
+        }
+
+        let url = format!("{}/schemas/ids/{}", self.url, id);
+        let resp = get(url.as_str())
+            .await
+            .map_err(|e| anyhow!("fetch schema {} error. {}", id, e))?;
+        let payload: SchemaPayload = serde_json::from_str(resp.as_str())
+            .map_err(|e| anyhow!("illegal schema {} response `{}`. {}", id, resp, e))?;
+        let schema = Arc::new(AvroSchema::parse_str(payload.schema.as_str())?);
+
+        self.schemas.lock().unwrap().insert(id, schema.clone());
+        Ok(schema)
+    }
+
+    /// Register the schema under the subject, return the id of the new or existing schema
+    pub async fn register(&self, subject: &str, schema: &str) -> anyhow::Result<u32> {
+        let url = format!("{}/subjects/{}/versions", self.url, subject);
+        self.subject_id(url, subject, schema).await
+    }
+
+    /// Look up the id of the schema already registered under the subject
+    pub async fn lookup(&self, subject: &str, schema: &str) -> anyhow::Result<u32> {
+        let url = format!("{}/subjects/{}", self.url, subject);
+        self.subject_id(url, subject, schema).await
+    }
+
+    async fn subject_id(&self, url: String, subject: &str, schema: &str) -> anyhow::Result<u32> {
+        let cache_key = format!("{}:{}", subject, schema);
+        let cached = self.subject_ids.lock().unwrap().get(&cache_key).cloned();
+        if let Some(id) = cached {
+            return Ok(id);
+        }
+
+        let body = serde_json::to_string(&SchemaPayload {
+            schema: schema.to_string(),
+        })?;
+        let payload: SchemaIdPayload = post(url, body)
+            .await
+            .map_err(|e| anyhow!("resolve the schema id of subject {} error. {}", subject, e))?;
+
+        self.subject_ids
+            .lock()
+            .unwrap()
+            .insert(cache_key, payload.id);
+        Ok(payload.id)
+    }
+}
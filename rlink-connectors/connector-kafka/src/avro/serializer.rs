@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use apache_avro::Schema as AvroSchema;
+use rlink::core::data_types::Schema;
+use rlink::core::element::{FnSchema, Record};
+use rlink::utils::date_time::current_timestamp_millis;
+
+use crate::avro::registry::{SchemaRegistryClient, SubjectNameStrategy};
+use crate::avro::{encode_wire_format, record_to_avro};
+use crate::build_kafka_record;
+use crate::sink::serializer::KafkaRecordSerializer;
+
+/// Encode the sink's input records into avro payloads of the Schema Registry wire format
+pub struct AvroRecordSerializer {
+    registry: Arc<SchemaRegistryClient>,
+    topic: String,
+    avro_schema_str: String,
+    avro_schema: AvroSchema,
+    strategy: SubjectNameStrategy,
+    auto_register: bool,
+
+    key_field: Option<String>,
+    timestamp_field: Option<String>,
+
+    input_schema: Schema,
+    schema_id: u32,
+}
+
+impl AvroRecordSerializer {
+    pub fn new(
+        registry: Arc<SchemaRegistryClient>,
+        topic: &str,
+        avro_schema: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(AvroRecordSerializer {
+            registry,
+            topic: topic.to_string(),
+            avro_schema_str: avro_schema.to_string(),
+            avro_schema: AvroSchema::parse_str(avro_schema)?,
+            strategy: SubjectNameStrategy::default(),
+            auto_register: true,
+            key_field: None,
+            timestamp_field: None,
+            input_schema: Schema::empty(),
+            schema_id: 0,
+        })
+    }
+
+    pub fn subject_name_strategy(mut self, strategy: SubjectNameStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Register the schema when the sink opened, otherwise the schema must be registered
+    /// already. default `true`
+    pub fn auto_register(mut self, auto_register: bool) -> Self {
+        self.auto_register = auto_register;
+        self
+    }
+
+    /// The `String` or `Binary` field used as the message key
+    pub fn key_field(mut self, field: &str) -> Self {
+        self.key_field = Some(field.to_string());
+        self
+    }
+
+    /// The `Int64` or `UInt64` field used as the message timestamp, default the current time
+    pub fn timestamp_field(mut self, field: &str) -> Self {
+        self.timestamp_field = Some(field.to_string());
+        self
+    }
+
+    fn field_index(&self, field: &Option<String>) -> anyhow::Result<Option<usize>> {
+        match field {
+            Some(name) => self
+                .input_schema
+                .index_of(name.as_str())
+                .map(Some)
+                .ok_or(anyhow!("field `{}` not found in the input schema", name)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl KafkaRecordSerializer for AvroRecordSerializer {
+    async fn open(&mut self, input_schema: &FnSchema) -> anyhow::Result<()> {
+        self.input_schema = input_schema.first().clone();
+
+        let subject = self
+            .strategy
+            .subject(self.topic.as_str(), &self.avro_schema, false)?;
+        let schema = self.avro_schema_str.as_str();
+        self.schema_id = if self.auto_register {
+            self.registry.register(subject.as_str(), schema).await?
+        } else {
+            self.registry.lookup(subject.as_str(), schema).await?
+        };
+
+        info!(
+            "avro serializer opened. subject: {}, schema id: {}",
+            subject, self.schema_id
+        );
+        Ok(())
+    }
+
+    fn serialize(&mut self, record: &mut Record) -> anyhow::Result<Record> {
+        let value = record_to_avro(record, &self.input_schema, &self.avro_schema)?;
+        let datum = apache_avro::to_avro_datum(&self.avro_schema, value)?;
+        let payload = encode_wire_format(self.schema_id, datum);
+
+        let key_index = self.field_index(&self.key_field)?;
+        let timestamp_index = self.field_index(&self.timestamp_field)?;
+
+        let reader = record.as_reader(self.input_schema.as_type_ids());
+        let key = match key_index {
+            Some(index) => reader.get_bytes_raw(index)?,
+            None => &[],
+        };
+        let timestamp = match timestamp_index {
+            Some(index) => reader.get_i64(index)?,
+            None => current_timestamp_millis() as i64,
+        };
+
+        let kafka_record = build_kafka_record(
+            timestamp,
+            key,
+            payload.as_slice(),
+            self.topic.as_str(),
+            0,
+            0,
+        )?;
+        Ok(kafka_record)
+    }
+}
@@ -9,6 +9,9 @@ extern crate anyhow;
 #[macro_use]
 extern crate async_trait;
 
+pub mod avro;
+pub mod metrics;
+pub mod security;
 pub mod sink;
 pub mod source;
 
@@ -16,6 +19,7 @@ pub mod buffer_gen {
     include!(concat!(env!("OUT_DIR"), "/buffer_gen/mod.rs"));
 }
 
+pub use security::KafkaSecurityConfig;
 pub use sink::output_format::KafkaOutputFormat;
 pub use source::input_format::KafkaInputFormat;
 
@@ -28,6 +32,8 @@ pub const BOOTSTRAP_SERVERS: &str = "bootstrap.servers";
 pub const GROUP_ID: &str = "group.id";
 
 pub const TOPICS: &str = "topics";
+pub const TOPIC_PATTERN: &str = "topic.pattern";
+pub const TOPIC_DISCOVERY_INTERVAL: &str = "topic.discovery.interval";
 pub const BUFFER_SIZE: &str = "buffer.size";
 
 pub const OFFSET: &str = "offset";
@@ -35,11 +41,57 @@ pub const OFFSET_TYPE: &str = "type";
 pub const OFFSET_BEGIN: &str = "begin";
 pub const OFFSET_END: &str = "end";
 
+pub const STARTUP: &str = "startup";
+pub const STARTUP_MODE: &str = "mode";
+pub const STARTUP_OFFSET: &str = "offset";
+pub const STARTUP_TIMESTAMP: &str = "timestamp";
+
+pub const BOUNDED: &str = "bounded";
+pub const BOUNDED_TYPE: &str = "type";
+pub const BOUNDED_OFFSET: &str = "offset";
+pub const BOUNDED_TIMESTAMP: &str = "timestamp";
+
+pub const RATE_LIMIT: &str = "rate.limit";
+pub const RATE_LIMIT_SCOPE: &str = "scope";
+pub const RATE_LIMIT_RECORDS: &str = "records";
+pub const RATE_LIMIT_BYTES: &str = "bytes";
+
+pub const SECURITY: &str = "security";
+pub const SECURITY_PROTOCOL: &str = "protocol";
+pub const SECURITY_SASL_MECHANISM: &str = "sasl.mechanism";
+pub const SECURITY_SASL_USERNAME: &str = "sasl.username";
+pub const SECURITY_SASL_PASSWORD: &str = "sasl.password";
+pub const SECURITY_SASL_SERVICE_NAME: &str = "sasl.service.name";
+pub const SECURITY_SASL_PRINCIPAL: &str = "sasl.principal";
+pub const SECURITY_SASL_KEYTAB: &str = "sasl.keytab";
+pub const SECURITY_SASL_OAUTH_TOKEN_ENDPOINT: &str = "sasl.oauth.token.endpoint";
+pub const SECURITY_SASL_OAUTH_CLIENT_ID: &str = "sasl.oauth.client.id";
+pub const SECURITY_SASL_OAUTH_CLIENT_SECRET: &str = "sasl.oauth.client.secret";
+pub const SECURITY_SASL_OAUTH_SCOPE: &str = "sasl.oauth.scope";
+pub const SECURITY_SSL_TRUSTSTORE_LOCATION: &str = "ssl.truststore.location";
+pub const SECURITY_SSL_KEYSTORE_LOCATION: &str = "ssl.keystore.location";
+pub const SECURITY_SSL_KEYSTORE_PASSWORD: &str = "ssl.keystore.password";
+pub const SECURITY_SSL_CERTIFICATE_LOCATION: &str = "ssl.certificate.location";
+pub const SECURITY_SSL_KEY_LOCATION: &str = "ssl.key.location";
+pub const SECURITY_SSL_KEY_PASSWORD: &str = "ssl.key.password";
+pub const SECURITY_SSL_ENDPOINT_IDENTIFICATION: &str = "ssl.endpoint.identification";
+
+pub const MAX_IN_FLIGHT: &str = "max.in.flight";
+
+pub const RETRY_MAX: &str = "retry.max";
+pub const RETRY_BACKOFF: &str = "retry.backoff";
+pub const RETRY_MAX_BACKOFF: &str = "retry.backoff.max";
+pub const DEAD_LETTER_TOPIC: &str = "dead.letter.topic";
+
+pub const CLUSTERS: &str = "clusters";
+pub const CLUSTER_MIRROR: &str = "cluster.mirror";
+
 pub const INPUT_FORMAT_FN_NAME_DEFAULT: &str = "KafkaInputFormat";
 pub const OUTPUT_FORMAT_FN_NAME_DEFAULT: &str = "KafkaOutputFormat";
 
 pub const SOURCE_CHANNEL_SIZE: usize = 50000;
 pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_MAX_IN_FLIGHT: usize = 10000;
 
 pub fn build_kafka_record(
     timestamp: i64,
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use rlink::metrics::{
+    register_counter, register_gauge, register_histogram, Counter, Gauge, Histogram, Tag,
+};
+
+pub const CONSUMER_LAG: &str = "Kafka.Consumer.Lag";
+pub const CONSUMER_FETCH_LATENCY: &str = "Kafka.Consumer.FetchLatency";
+pub const PRODUCER_LATENCY: &str = "Kafka.Producer.Latency";
+pub const PRODUCER_IN_FLIGHT: &str = "Kafka.Producer.InFlight";
+pub const PRODUCER_DRAIN: &str = "Kafka.Producer.Drain";
+pub const PRODUCER_DISCARD: &str = "Kafka.Producer.Discard";
+pub const PRODUCER_DEAD_LETTER: &str = "Kafka.Producer.DeadLetter";
+
+/// Metrics of a consumed partition, tagged by the task, topic and partition
+#[derive(Clone)]
+pub(crate) struct ConsumerMetrics {
+    /// messages between the consumed offset and the high watermark of the partition
+    lag: Gauge,
+    /// millis waiting for the next message from librdkafka
+    fetch_latency: Histogram,
+}
+
+impl ConsumerMetrics {
+    pub fn new(mut tags: Vec<Tag>, topic: &str, partition: i32) -> Self {
+        tags.push(Tag::new("topic", topic));
+        tags.push(Tag::new("partition", partition));
+
+        ConsumerMetrics {
+            lag: register_gauge(CONSUMER_LAG, tags.clone()),
+            fetch_latency: register_histogram(CONSUMER_FETCH_LATENCY, tags),
+        }
+    }
+
+    pub fn lag(&self, lag: i64) {
+        self.lag.set(lag.max(0) as f64);
+    }
+
+    pub fn fetch_latency(&self, latency: Duration) {
+        self.fetch_latency.record(latency.as_secs_f64() * 1000f64);
+    }
+}
+
+/// Metrics of the producer task, tagged by the task and topic
+#[derive(Clone)]
+pub(crate) struct ProducerMetrics {
+    /// millis from the record being enqueued to the delivery report
+    latency: Histogram,
+    in_flight: Gauge,
+    drain: Counter,
+    discard: Counter,
+    dead_letter: Counter,
+}
+
+impl ProducerMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        ProducerMetrics {
+            latency: register_histogram(PRODUCER_LATENCY, tags.clone()),
+            in_flight: register_gauge(PRODUCER_IN_FLIGHT, tags.clone()),
+            drain: register_counter(PRODUCER_DRAIN, tags.clone()),
+            discard: register_counter(PRODUCER_DISCARD, tags.clone()),
+            dead_letter: register_counter(PRODUCER_DEAD_LETTER, tags),
+        }
+    }
+
+    pub fn latency(&self, latency: Duration) {
+        self.latency.record(latency.as_secs_f64() * 1000f64);
+    }
+
+    pub fn in_flight(&self, size: usize) {
+        self.in_flight.set(size as f64);
+    }
+
+    pub fn drain(&self) {
+        self.drain.increment(1);
+    }
+
+    pub fn discard(&self) {
+        self.discard.increment(1);
+    }
+
+    pub fn dead_letter(&self) {
+        self.dead_letter.increment(1);
+    }
+}
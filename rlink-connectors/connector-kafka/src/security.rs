@@ -0,0 +1,664 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use rdkafka::ClientConfig;
+use rlink::core::properties::Properties;
+
+use crate::{
+    SECURITY_PROTOCOL, SECURITY_SASL_KEYTAB, SECURITY_SASL_MECHANISM,
+    SECURITY_SASL_OAUTH_CLIENT_ID, SECURITY_SASL_OAUTH_CLIENT_SECRET, SECURITY_SASL_OAUTH_SCOPE,
+    SECURITY_SASL_OAUTH_TOKEN_ENDPOINT, SECURITY_SASL_PASSWORD, SECURITY_SASL_PRINCIPAL,
+    SECURITY_SASL_SERVICE_NAME, SECURITY_SASL_USERNAME, SECURITY_SSL_CERTIFICATE_LOCATION,
+    SECURITY_SSL_ENDPOINT_IDENTIFICATION, SECURITY_SSL_KEYSTORE_LOCATION,
+    SECURITY_SSL_KEYSTORE_PASSWORD, SECURITY_SSL_KEY_LOCATION, SECURITY_SSL_KEY_PASSWORD,
+    SECURITY_SSL_TRUSTSTORE_LOCATION,
+};
+
+const REDACTED: &str = "******";
+
+/// librdkafka configuration keys whose values must never be written to logs
+const SECRET_KEYS: [&str; 7] = [
+    "sasl.password",
+    "sasl.oauthbearer.client.secret",
+    "sasl.oauthbearer.config",
+    "ssl.key.password",
+    "ssl.key.pem",
+    "ssl.keystore.password",
+    "ssl_key",
+];
+
+/// Returns a copy of the kafka configuration map with all secret values masked
+pub fn redact_conf_map(conf_map: &HashMap<String, String>) -> HashMap<String, String> {
+    conf_map
+        .iter()
+        .map(|(key, value)| {
+            if SECRET_KEYS.contains(&key.as_str()) {
+                (key.clone(), REDACTED.to_string())
+            } else {
+                (key.clone(), value.clone())
+            }
+        })
+        .collect()
+}
+
+/// Returns a copy of the `ClientConfig` with all secret values masked, only for logging
+pub fn redact_client_config(client_config: &ClientConfig) -> ClientConfig {
+    let mut client_config = client_config.clone();
+    for key in SECRET_KEYS {
+        if client_config.get(key).is_some() {
+            client_config.set(key, REDACTED);
+        }
+    }
+    client_config
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SecurityProtocol {
+    Plaintext,
+    Ssl,
+    SaslPlaintext,
+    SaslSsl,
+}
+
+impl SecurityProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Plaintext => "plaintext",
+            Self::Ssl => "ssl",
+            Self::SaslPlaintext => "sasl_plaintext",
+            Self::SaslSsl => "sasl_ssl",
+        }
+    }
+
+    fn is_sasl(&self) -> bool {
+        matches!(self, Self::SaslPlaintext | Self::SaslSsl)
+    }
+
+    fn is_ssl(&self) -> bool {
+        matches!(self, Self::Ssl | Self::SaslSsl)
+    }
+}
+
+impl TryFrom<&str> for SecurityProtocol {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "plaintext" => Ok(Self::Plaintext),
+            "ssl" => Ok(Self::Ssl),
+            "sasl_plaintext" => Ok(Self::SaslPlaintext),
+            "sasl_ssl" => Ok(Self::SaslSsl),
+            _ => Err(anyhow!("unknown security protocol {}", value)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum SaslConfig {
+    Plain {
+        username: String,
+        password: String,
+    },
+    ScramSha256 {
+        username: String,
+        password: String,
+    },
+    ScramSha512 {
+        username: String,
+        password: String,
+    },
+    Gssapi {
+        service_name: String,
+        principal: Option<String>,
+        keytab: Option<String>,
+    },
+    /// OAuth bearer token retrieved from an OIDC token endpoint with client credentials
+    OAuthBearer {
+        token_endpoint_url: String,
+        client_id: String,
+        client_secret: String,
+        scope: Option<String>,
+    },
+}
+
+impl SaslConfig {
+    pub fn mechanism(&self) -> &'static str {
+        match self {
+            Self::Plain { .. } => "PLAIN",
+            Self::ScramSha256 { .. } => "SCRAM-SHA-256",
+            Self::ScramSha512 { .. } => "SCRAM-SHA-512",
+            Self::Gssapi { .. } => "GSSAPI",
+            Self::OAuthBearer { .. } => "OAUTHBEARER",
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Plain { username, password }
+            | Self::ScramSha256 { username, password }
+            | Self::ScramSha512 { username, password } => {
+                if username.is_empty() || password.is_empty() {
+                    return Err(anyhow!(
+                        "sasl mechanism {} requires `username` and `password`",
+                        self.mechanism()
+                    ));
+                }
+            }
+            Self::Gssapi {
+                service_name,
+                keytab,
+                principal,
+            } => {
+                if service_name.is_empty() {
+                    return Err(anyhow!("sasl mechanism GSSAPI requires `service_name`"));
+                }
+                if keytab.is_some() && principal.is_none() {
+                    return Err(anyhow!("sasl GSSAPI `keytab` requires `principal`"));
+                }
+            }
+            Self::OAuthBearer {
+                token_endpoint_url,
+                client_id,
+                client_secret,
+                ..
+            } => {
+                if token_endpoint_url.is_empty() || client_id.is_empty() || client_secret.is_empty()
+                {
+                    return Err(anyhow!(
+                        "sasl mechanism OAUTHBEARER requires `token_endpoint_url`, `client_id` and `client_secret`"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_conf_map(&self, conf_map: &mut HashMap<String, String>) {
+        conf_map.insert("sasl.mechanisms".to_string(), self.mechanism().to_string());
+        match self {
+            Self::Plain { username, password }
+            | Self::ScramSha256 { username, password }
+            | Self::ScramSha512 { username, password } => {
+                conf_map.insert("sasl.username".to_string(), username.clone());
+                conf_map.insert("sasl.password".to_string(), password.clone());
+            }
+            Self::Gssapi {
+                service_name,
+                principal,
+                keytab,
+            } => {
+                conf_map.insert(
+                    "sasl.kerberos.service.name".to_string(),
+                    service_name.clone(),
+                );
+                if let Some(principal) = principal {
+                    conf_map.insert("sasl.kerberos.principal".to_string(), principal.clone());
+                }
+                if let Some(keytab) = keytab {
+                    conf_map.insert("sasl.kerberos.keytab".to_string(), keytab.clone());
+                }
+            }
+            Self::OAuthBearer {
+                token_endpoint_url,
+                client_id,
+                client_secret,
+                scope,
+            } => {
+                conf_map.insert("sasl.oauthbearer.method".to_string(), "oidc".to_string());
+                conf_map.insert(
+                    "sasl.oauthbearer.token.endpoint.url".to_string(),
+                    token_endpoint_url.clone(),
+                );
+                conf_map.insert("sasl.oauthbearer.client.id".to_string(), client_id.clone());
+                conf_map.insert(
+                    "sasl.oauthbearer.client.secret".to_string(),
+                    client_secret.clone(),
+                );
+                if let Some(scope) = scope {
+                    conf_map.insert("sasl.oauthbearer.scope".to_string(), scope.clone());
+                }
+            }
+        }
+    }
+}
+
+impl Debug for SaslConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plain { username, .. }
+            | Self::ScramSha256 { username, .. }
+            | Self::ScramSha512 { username, .. } => f
+                .debug_struct(self.mechanism())
+                .field("username", username)
+                .field("password", &REDACTED)
+                .finish(),
+            Self::Gssapi {
+                service_name,
+                principal,
+                keytab,
+            } => f
+                .debug_struct(self.mechanism())
+                .field("service_name", service_name)
+                .field("principal", principal)
+                .field("keytab", keytab)
+                .finish(),
+            Self::OAuthBearer {
+                token_endpoint_url,
+                client_id,
+                scope,
+                ..
+            } => f
+                .debug_struct(self.mechanism())
+                .field("token_endpoint_url", token_endpoint_url)
+                .field("client_id", client_id)
+                .field("client_secret", &REDACTED)
+                .field("scope", scope)
+                .finish(),
+        }
+    }
+}
+
+/// SSL settings. librdkafka reads PEM files for the truststore(CA) and the client certificate,
+/// and PKCS#12 files for the keystore.
+#[derive(Clone, Default)]
+pub struct SslConfig {
+    truststore_location: Option<String>,
+    certificate_location: Option<String>,
+    key_location: Option<String>,
+    key_password: Option<String>,
+    keystore_location: Option<String>,
+    keystore_password: Option<String>,
+    endpoint_identification: Option<bool>,
+}
+
+impl SslConfig {
+    fn is_empty(&self) -> bool {
+        self.truststore_location.is_none()
+            && self.certificate_location.is_none()
+            && self.key_location.is_none()
+            && self.key_password.is_none()
+            && self.keystore_location.is_none()
+            && self.keystore_password.is_none()
+            && self.endpoint_identification.is_none()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.certificate_location.is_some() != self.key_location.is_some() {
+            return Err(anyhow!(
+                "ssl `certificate_location` and `key_location` must be set together"
+            ));
+        }
+        if self.key_password.is_some() && self.key_location.is_none() {
+            return Err(anyhow!("ssl `key_password` requires `key_location`"));
+        }
+        if self.keystore_password.is_some() && self.keystore_location.is_none() {
+            return Err(anyhow!(
+                "ssl `keystore_password` requires `keystore_location`"
+            ));
+        }
+        Ok(())
+    }
+
+    fn write_conf_map(&self, conf_map: &mut HashMap<String, String>) {
+        let entries = [
+            ("ssl.ca.location", &self.truststore_location),
+            ("ssl.certificate.location", &self.certificate_location),
+            ("ssl.key.location", &self.key_location),
+            ("ssl.key.password", &self.key_password),
+            ("ssl.keystore.location", &self.keystore_location),
+            ("ssl.keystore.password", &self.keystore_password),
+        ];
+        for (key, value) in entries {
+            if let Some(value) = value {
+                conf_map.insert(key.to_string(), value.clone());
+            }
+        }
+
+        if let Some(endpoint_identification) = self.endpoint_identification {
+            let algorithm = if endpoint_identification {
+                "https"
+            } else {
+                "none"
+            };
+            conf_map.insert(
+                "ssl.endpoint.identification.algorithm".to_string(),
+                algorithm.to_string(),
+            );
+        }
+    }
+}
+
+impl Debug for SslConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SslConfig")
+            .field("truststore_location", &self.truststore_location)
+            .field("certificate_location", &self.certificate_location)
+            .field("key_location", &self.key_location)
+            .field(
+                "key_password",
+                &self.key_password.as_ref().map(|_| REDACTED),
+            )
+            .field("keystore_location", &self.keystore_location)
+            .field(
+                "keystore_password",
+                &self.keystore_password.as_ref().map(|_| REDACTED),
+            )
+            .field("endpoint_identification", &self.endpoint_identification)
+            .finish()
+    }
+}
+
+/// Typed security settings for kafka clients, shared by the source and the sink builders.
+/// Secrets are masked in the `Debug` output.
+#[derive(Clone, Debug)]
+pub struct KafkaSecurityConfig {
+    protocol: SecurityProtocol,
+    sasl: Option<SaslConfig>,
+    ssl: SslConfig,
+}
+
+impl KafkaSecurityConfig {
+    pub fn builder(protocol: SecurityProtocol) -> KafkaSecurityConfigBuilder {
+        KafkaSecurityConfigBuilder::new(protocol)
+    }
+
+    pub fn protocol(&self) -> SecurityProtocol {
+        self.protocol
+    }
+
+    pub fn sasl(&self) -> Option<&SaslConfig> {
+        self.sasl.as_ref()
+    }
+
+    /// Convert to librdkafka configuration entries
+    pub fn to_conf_map(&self) -> HashMap<String, String> {
+        let mut conf_map = HashMap::new();
+        conf_map.insert(
+            "security.protocol".to_string(),
+            self.protocol.as_str().to_string(),
+        );
+
+        if let Some(sasl) = &self.sasl {
+            sasl.write_conf_map(&mut conf_map);
+        }
+        self.ssl.write_conf_map(&mut conf_map);
+
+        conf_map
+    }
+
+    /// Write the security entries into `ClientConfig`, the typed settings override the raw
+    /// key/value settings
+    pub fn apply(&self, client_config: &mut ClientConfig) {
+        for (key, val) in self.to_conf_map() {
+            client_config.set(key.as_str(), val.as_str());
+        }
+    }
+}
+
+impl TryFrom<Properties> for KafkaSecurityConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let protocol = properties.get_string(SECURITY_PROTOCOL)?;
+        let mut builder =
+            KafkaSecurityConfigBuilder::new(SecurityProtocol::try_from(protocol.as_str())?);
+
+        if let Ok(mechanism) = properties.get_string(SECURITY_SASL_MECHANISM) {
+            let get = |key: &str| properties.get_string(key);
+            builder = match mechanism.to_uppercase().as_str() {
+                "PLAIN" => builder.sasl_plain(
+                    get(SECURITY_SASL_USERNAME)?.as_str(),
+                    get(SECURITY_SASL_PASSWORD)?.as_str(),
+                ),
+                "SCRAM-SHA-256" => builder.sasl_scram_sha256(
+                    get(SECURITY_SASL_USERNAME)?.as_str(),
+                    get(SECURITY_SASL_PASSWORD)?.as_str(),
+                ),
+                "SCRAM-SHA-512" => builder.sasl_scram_sha512(
+                    get(SECURITY_SASL_USERNAME)?.as_str(),
+                    get(SECURITY_SASL_PASSWORD)?.as_str(),
+                ),
+                "GSSAPI" => builder.sasl_gssapi(
+                    get(SECURITY_SASL_SERVICE_NAME)
+                        .unwrap_or("kafka".to_string())
+                        .as_str(),
+                    get(SECURITY_SASL_PRINCIPAL).ok(),
+                    get(SECURITY_SASL_KEYTAB).ok(),
+                ),
+                "OAUTHBEARER" => builder.sasl_oauth_bearer(
+                    get(SECURITY_SASL_OAUTH_TOKEN_ENDPOINT)?.as_str(),
+                    get(SECURITY_SASL_OAUTH_CLIENT_ID)?.as_str(),
+                    get(SECURITY_SASL_OAUTH_CLIENT_SECRET)?.as_str(),
+                    get(SECURITY_SASL_OAUTH_SCOPE).ok(),
+                ),
+                _ => return Err(anyhow!("unknown sasl mechanism {}", mechanism)),
+            };
+        }
+
+        if let Ok(location) = properties.get_string(SECURITY_SSL_TRUSTSTORE_LOCATION) {
+            builder = builder.ssl_truststore(location.as_str());
+        }
+        if let Ok(location) = properties.get_string(SECURITY_SSL_KEYSTORE_LOCATION) {
+            let password = properties.get_string(SECURITY_SSL_KEYSTORE_PASSWORD).ok();
+            builder = builder.ssl_keystore(location.as_str(), password);
+        }
+        if let Ok(certificate_location) = properties.get_string(SECURITY_SSL_CERTIFICATE_LOCATION) {
+            let key_location = properties.get_string(SECURITY_SSL_KEY_LOCATION)?;
+            let key_password = properties.get_string(SECURITY_SSL_KEY_PASSWORD).ok();
+            builder = builder.ssl_certificate(
+                certificate_location.as_str(),
+                key_location.as_str(),
+                key_password,
+            );
+        }
+        if let Ok(enable) = properties.get_bool(SECURITY_SSL_ENDPOINT_IDENTIFICATION) {
+            builder = builder.ssl_endpoint_identification(enable);
+        }
+
+        builder.build()
+    }
+}
+
+#[derive(Debug)]
+pub struct KafkaSecurityConfigBuilder {
+    protocol: SecurityProtocol,
+    sasl: Option<SaslConfig>,
+    ssl: SslConfig,
+}
+
+impl KafkaSecurityConfigBuilder {
+    pub fn new(protocol: SecurityProtocol) -> Self {
+        KafkaSecurityConfigBuilder {
+            protocol,
+            sasl: None,
+            ssl: SslConfig::default(),
+        }
+    }
+
+    pub fn sasl_plain(mut self, username: &str, password: &str) -> Self {
+        self.sasl = Some(SaslConfig::Plain {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        self
+    }
+
+    pub fn sasl_scram_sha256(mut self, username: &str, password: &str) -> Self {
+        self.sasl = Some(SaslConfig::ScramSha256 {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        self
+    }
+
+    pub fn sasl_scram_sha512(mut self, username: &str, password: &str) -> Self {
+        self.sasl = Some(SaslConfig::ScramSha512 {
+            username: username.to_string(),
+            password: password.to_string(),
+        });
+        self
+    }
+
+    pub fn sasl_gssapi(
+        mut self,
+        service_name: &str,
+        principal: Option<String>,
+        keytab: Option<String>,
+    ) -> Self {
+        self.sasl = Some(SaslConfig::Gssapi {
+            service_name: service_name.to_string(),
+            principal,
+            keytab,
+        });
+        self
+    }
+
+    pub fn sasl_oauth_bearer(
+        mut self,
+        token_endpoint_url: &str,
+        client_id: &str,
+        client_secret: &str,
+        scope: Option<String>,
+    ) -> Self {
+        self.sasl = Some(SaslConfig::OAuthBearer {
+            token_endpoint_url: token_endpoint_url.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            scope,
+        });
+        self
+    }
+
+    /// PEM file of the CA certificate(s) used to verify the broker's key
+    pub fn ssl_truststore(mut self, location: &str) -> Self {
+        self.ssl.truststore_location = Some(location.to_string());
+        self
+    }
+
+    /// PKCS#12 keystore of the client's key and certificate
+    pub fn ssl_keystore(mut self, location: &str, password: Option<String>) -> Self {
+        self.ssl.keystore_location = Some(location.to_string());
+        self.ssl.keystore_password = password;
+        self
+    }
+
+    /// PEM files of the client's public certificate and private key
+    pub fn ssl_certificate(
+        mut self,
+        certificate_location: &str,
+        key_location: &str,
+        key_password: Option<String>,
+    ) -> Self {
+        self.ssl.certificate_location = Some(certificate_location.to_string());
+        self.ssl.key_location = Some(key_location.to_string());
+        self.ssl.key_password = key_password;
+        self
+    }
+
+    pub fn ssl_endpoint_identification(mut self, enable: bool) -> Self {
+        self.ssl.endpoint_identification = Some(enable);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<KafkaSecurityConfig> {
+        match &self.sasl {
+            Some(sasl) => {
+                if !self.protocol.is_sasl() {
+                    return Err(anyhow!(
+                        "sasl mechanism {} is not allowed with security protocol `{}`",
+                        sasl.mechanism(),
+                        self.protocol.as_str()
+                    ));
+                }
+                sasl.validate()?;
+            }
+            None => {
+                if self.protocol.is_sasl() {
+                    return Err(anyhow!(
+                        "security protocol `{}` requires a sasl mechanism",
+                        self.protocol.as_str()
+                    ));
+                }
+            }
+        }
+
+        if !self.ssl.is_empty() && !self.protocol.is_ssl() {
+            return Err(anyhow!(
+                "ssl settings are not allowed with security protocol `{}`",
+                self.protocol.as_str()
+            ));
+        }
+        self.ssl.validate()?;
+
+        Ok(KafkaSecurityConfig {
+            protocol: self.protocol,
+            sasl: self.sasl,
+            ssl: self.ssl,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::security::{redact_conf_map, KafkaSecurityConfig, SecurityProtocol};
+
+    #[test]
+    pub fn security_validate_test() {
+        let config = KafkaSecurityConfig::builder(SecurityProtocol::SaslSsl)
+            .sasl_scram_sha512("rlink", "secret")
+            .ssl_truststore("/etc/kafka/ca.pem")
+            .build()
+            .unwrap();
+
+        let conf_map = config.to_conf_map();
+        assert_eq!(conf_map.get("security.protocol").unwrap(), "sasl_ssl");
+        assert_eq!(conf_map.get("sasl.mechanisms").unwrap(), "SCRAM-SHA-512");
+        assert_eq!(
+            conf_map.get("ssl.ca.location").unwrap(),
+            "/etc/kafka/ca.pem"
+        );
+
+        assert!(
+            KafkaSecurityConfig::builder(SecurityProtocol::SaslPlaintext)
+                .build()
+                .is_err()
+        );
+        assert!(KafkaSecurityConfig::builder(SecurityProtocol::Plaintext)
+            .sasl_plain("rlink", "secret")
+            .build()
+            .is_err());
+        assert!(
+            KafkaSecurityConfig::builder(SecurityProtocol::SaslPlaintext)
+                .sasl_plain("rlink", "")
+                .build()
+                .is_err()
+        );
+        assert!(KafkaSecurityConfig::builder(SecurityProtocol::Plaintext)
+            .ssl_truststore("/etc/kafka/ca.pem")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    pub fn security_redact_test() {
+        let config = KafkaSecurityConfig::builder(SecurityProtocol::SaslPlaintext)
+            .sasl_plain("rlink", "secret")
+            .build()
+            .unwrap();
+        assert!(!format!("{:?}", config).contains("secret"));
+
+        let mut conf_map = HashMap::new();
+        conf_map.insert("sasl.password".to_string(), "secret".to_string());
+        conf_map.insert(
+            "bootstrap.servers".to_string(),
+            "localhost:9092".to_string(),
+        );
+        let conf_map = redact_conf_map(&conf_map);
+        assert_ne!(conf_map.get("sasl.password").unwrap(), "secret");
+        assert_eq!(conf_map.get("bootstrap.servers").unwrap(), "localhost:9092");
+    }
+}
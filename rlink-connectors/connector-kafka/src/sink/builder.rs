@@ -1,19 +1,34 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
 
 use rdkafka::ClientConfig;
 use rlink::core::properties::Properties;
 
+use crate::security::{redact_conf_map, KafkaSecurityConfig};
+use crate::sink::dead_letter::{DeadLetterQueue, KafkaDeadLetterHandler, RetryPolicy};
+use crate::sink::partitioner::KafkaPartitioner;
+use crate::sink::router::{KafkaClusterRouter, MirrorClusterRouter, DEFAULT_CLUSTER};
+use crate::sink::serializer::KafkaRecordSerializer;
 use crate::{
-    KafkaOutputFormat, BOOTSTRAP_SERVERS, BUFFER_SIZE, KAFKA, SINK_CHANNEL_SIZE,
-    SOURCE_CHANNEL_SIZE, TOPICS,
+    KafkaOutputFormat, BOOTSTRAP_SERVERS, BUFFER_SIZE, CLUSTERS, CLUSTER_MIRROR, DEAD_LETTER_TOPIC,
+    KAFKA, MAX_IN_FLIGHT, RETRY_BACKOFF, RETRY_MAX, RETRY_MAX_BACKOFF, SECURITY, SINK_CHANNEL_SIZE,
+    SINK_MAX_IN_FLIGHT, SOURCE_CHANNEL_SIZE, TOPICS,
 };
 
-#[derive(Debug)]
 pub struct KafkaOutputFormatBuilder {
     conf_map: HashMap<String, String>,
     topics: Option<String>,
     buffer_size: Option<usize>,
+    max_in_flight: Option<usize>,
+    security: Option<KafkaSecurityConfig>,
+    retry_policy: RetryPolicy,
+    dead_letter: Option<DeadLetterQueue>,
+    partitioner: Option<Box<dyn KafkaPartitioner>>,
+    clusters: Vec<(String, HashMap<String, String>)>,
+    router: Option<Box<dyn KafkaClusterRouter>>,
+    serializer: Option<Box<dyn KafkaRecordSerializer>>,
 }
 
 impl KafkaOutputFormatBuilder {
@@ -22,6 +37,14 @@ impl KafkaOutputFormatBuilder {
             conf_map,
             topics,
             buffer_size: None,
+            max_in_flight: None,
+            security: None,
+            retry_policy: RetryPolicy::no_retry(),
+            dead_letter: None,
+            partitioner: None,
+            clusters: vec![],
+            router: None,
+            serializer: None,
         }
     }
 
@@ -30,6 +53,62 @@ impl KafkaOutputFormatBuilder {
         self
     }
 
+    /// The max number of records waiting for the delivery report, the upstream is
+    /// back-pressured when it is reached
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    pub fn security(mut self, security: KafkaSecurityConfig) -> Self {
+        self.security = Some(security);
+        self
+    }
+
+    /// Retry the records failed to produce before routing them to the dead letter queue
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Route the records failed to produce to the secondary topic instead of discarding them
+    pub fn dead_letter_topic(mut self, topic: &str) -> Self {
+        self.dead_letter = Some(DeadLetterQueue::Topic(topic.to_string()));
+        self
+    }
+
+    /// Hand the records failed to produce over to the callback instead of discarding them
+    pub fn dead_letter_handler(mut self, handler: Box<dyn KafkaDeadLetterHandler>) -> Self {
+        self.dead_letter = Some(DeadLetterQueue::Handler(handler));
+        self
+    }
+
+    /// Shard the records by a business key instead of the default key hashing
+    pub fn partitioner(mut self, partitioner: Box<dyn KafkaPartitioner>) -> Self {
+        self.partitioner = Some(partitioner);
+        self
+    }
+
+    /// Add a secondary cluster, the `security` only applies to the default cluster, so the
+    /// `conf_map` should contain all settings of the cluster
+    pub fn cluster(mut self, name: &str, conf_map: HashMap<String, String>) -> Self {
+        self.clusters.push((name.to_string(), conf_map));
+        self
+    }
+
+    /// Route the records across the default and secondary clusters,
+    /// all records go to the default cluster without a router
+    pub fn router(mut self, router: Box<dyn KafkaClusterRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Convert the input records into `KafkaMessage` records in the sink, eg: avro encoding
+    pub fn serializer(mut self, serializer: Box<dyn KafkaRecordSerializer>) -> Self {
+        self.serializer = Some(serializer);
+        self
+    }
+
     pub fn build(self) -> KafkaOutputFormat {
         info!("build kafka sink with: {:?}", &self);
 
@@ -37,10 +116,36 @@ impl KafkaOutputFormatBuilder {
         for (key, val) in &self.conf_map {
             client_config.set(key.as_str(), val.as_str());
         }
+        if let Some(security) = &self.security {
+            security.apply(&mut client_config);
+        }
 
         let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+        let max_in_flight = self.max_in_flight.unwrap_or(SINK_MAX_IN_FLIGHT);
+
+        let clusters = self
+            .clusters
+            .iter()
+            .map(|(name, conf_map)| {
+                let mut client_config = ClientConfig::new();
+                for (key, val) in conf_map {
+                    client_config.set(key.as_str(), val.as_str());
+                }
+                (name.clone(), client_config)
+            })
+            .collect();
 
-        KafkaOutputFormat::new(client_config, self.topics, buffer_size)
+        KafkaOutputFormat::new(
+            client_config,
+            self.topics,
+            buffer_size,
+            max_in_flight,
+            self.retry_policy,
+            self.dead_letter,
+            self.partitioner,
+        )
+        .clusters(clusters, self.router)
+        .serializer(self.serializer)
     }
 }
 
@@ -72,8 +177,83 @@ impl TryFrom<Properties> for KafkaOutputFormatBuilder {
             .get_usize(BUFFER_SIZE)
             .unwrap_or(SINK_CHANNEL_SIZE);
 
-        let builder = KafkaOutputFormatBuilder::new(client_config, topic).buffer_size(buffer_size);
+        let mut builder =
+            KafkaOutputFormatBuilder::new(client_config, topic).buffer_size(buffer_size);
+
+        let security_properties = properties.to_sub_properties(SECURITY);
+        if !security_properties.is_empty() {
+            let security = KafkaSecurityConfig::try_from(security_properties)?;
+            builder = builder.security(security);
+        }
+
+        if let Ok(max_in_flight) = properties.get_usize(MAX_IN_FLIGHT) {
+            builder = builder.max_in_flight(max_in_flight);
+        }
+
+        if let Ok(max_retries) = properties.get_u32(RETRY_MAX) {
+            let backoff = properties
+                .get_duration(RETRY_BACKOFF)
+                .unwrap_or(Duration::from_millis(100));
+            let max_backoff = properties
+                .get_duration(RETRY_MAX_BACKOFF)
+                .unwrap_or(Duration::from_secs(5));
+            builder = builder.retry_policy(RetryPolicy::new(max_retries, backoff, max_backoff));
+        }
+
+        if let Ok(dead_letter_topic) = properties.get_string(DEAD_LETTER_TOPIC) {
+            builder = builder.dead_letter_topic(dead_letter_topic.as_str());
+        }
+
+        // `clusters.{name}.{kafka config}`
+        let mut clusters: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for (key, val) in properties.to_sub_properties(CLUSTERS).as_map() {
+            let (name, config_key) = key
+                .split_once(".")
+                .ok_or(anyhow!("illegal kafka cluster config `{}`", key))?;
+            if name.eq(DEFAULT_CLUSTER) {
+                return Err(anyhow!(
+                    "the cluster name `{}` is reserved",
+                    DEFAULT_CLUSTER
+                ));
+            }
+            clusters
+                .entry(name.to_string())
+                .or_default()
+                .insert(config_key.to_string(), val.clone());
+        }
+        for (name, conf_map) in clusters {
+            builder = builder.cluster(name.as_str(), conf_map);
+        }
+
+        if properties.get_bool(CLUSTER_MIRROR).unwrap_or(false) {
+            builder = builder.router(Box::new(MirrorClusterRouter));
+        }
 
         Ok(builder)
     }
 }
+
+impl Debug for KafkaOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaOutputFormatBuilder")
+            .field("conf_map", &redact_conf_map(&self.conf_map))
+            .field("topics", &self.topics)
+            .field("buffer_size", &self.buffer_size)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("security", &self.security)
+            .field("retry_policy", &self.retry_policy)
+            .field("dead_letter", &self.dead_letter)
+            .field("partitioner", &self.partitioner.is_some())
+            .field(
+                "clusters",
+                &self
+                    .clusters
+                    .iter()
+                    .map(|(name, conf_map)| (name, redact_conf_map(conf_map)))
+                    .collect::<Vec<_>>(),
+            )
+            .field("router", &self.router.is_some())
+            .field("serializer", &self.serializer.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,78 @@
+use std::cmp::min;
+use std::time::Duration;
+
+use rdkafka::error::KafkaError;
+use rlink::core::element::Record;
+
+/// Retry settings of the records failed to produce
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// The backoff doubles after every attempt until it reaches `max_backoff`
+    pub fn new(max_retries: u32, backoff: Duration, max_backoff: Duration) -> Self {
+        RetryPolicy {
+            max_retries,
+            backoff,
+            max_backoff,
+        }
+    }
+
+    pub fn no_retry() -> Self {
+        RetryPolicy::new(0, Duration::from_millis(0), Duration::from_millis(0))
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// The delay before the `attempt`(start with 0) retry
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        min(self.backoff.saturating_mul(factor), self.max_backoff)
+    }
+}
+
+/// User callback of the records which are still failed after all retries
+pub trait KafkaDeadLetterHandler: Send + Sync {
+    /// `record` is the `KafkaMessage` record handed over to the sink
+    fn handle(&mut self, record: Record, error: &KafkaError);
+}
+
+/// Where the records go which are still failed after all retries
+pub enum DeadLetterQueue {
+    /// produce to a secondary topic with the same key, payload and timestamp, the original topic
+    /// and the error are added to the headers
+    Topic(String),
+    Handler(Box<dyn KafkaDeadLetterHandler>),
+}
+
+impl std::fmt::Debug for DeadLetterQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Topic(topic) => f.debug_tuple("Topic").field(topic).finish(),
+            Self::Handler(_) => f.write_str("Handler"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::sink::dead_letter::RetryPolicy;
+
+    #[test]
+    pub fn retry_backoff_test() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(100), Duration::from_secs(1));
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40), Duration::from_secs(1));
+    }
+}
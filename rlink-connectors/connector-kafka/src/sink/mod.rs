@@ -1,3 +1,7 @@
 pub mod builder;
+pub mod dead_letter;
 pub mod output_format;
+pub mod partitioner;
 pub mod producer;
+pub mod router;
+pub mod serializer;
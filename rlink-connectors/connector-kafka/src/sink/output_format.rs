@@ -7,7 +7,12 @@ use rlink::core::element::{Element, Record};
 use rlink::core::function::{Context, NamedFunction, OutputFormat};
 use rlink::metrics::Tag;
 
+use crate::buffer_gen::kafka_message;
+use crate::sink::dead_letter::{DeadLetterQueue, RetryPolicy};
+use crate::sink::partitioner::KafkaPartitioner;
 use crate::sink::producer::KafkaProducerThread;
+use crate::sink::router::KafkaClusterRouter;
+use crate::sink::serializer::KafkaRecordSerializer;
 
 #[derive(NamedFunction)]
 pub struct KafkaOutputFormat {
@@ -15,36 +20,98 @@ pub struct KafkaOutputFormat {
     topic: Option<String>,
 
     buffer_size: usize,
+    max_in_flight: usize,
     handover: Option<ChannelSender<Record>>,
+
+    retry_policy: RetryPolicy,
+    dead_letter: Option<DeadLetterQueue>,
+    partitioner: Option<Box<dyn KafkaPartitioner>>,
+
+    /// the secondary clusters by name
+    clusters: Vec<(String, ClientConfig)>,
+    router: Option<Box<dyn KafkaClusterRouter>>,
+
+    serializer: Option<Box<dyn KafkaRecordSerializer>>,
 }
 
 impl KafkaOutputFormat {
-    pub fn new(client_config: ClientConfig, topic: Option<String>, buffer_size: usize) -> Self {
+    pub fn new(
+        client_config: ClientConfig,
+        topic: Option<String>,
+        buffer_size: usize,
+        max_in_flight: usize,
+        retry_policy: RetryPolicy,
+        dead_letter: Option<DeadLetterQueue>,
+        partitioner: Option<Box<dyn KafkaPartitioner>>,
+    ) -> Self {
         KafkaOutputFormat {
             client_config,
             topic,
             buffer_size,
+            max_in_flight,
             handover: None,
+            retry_policy,
+            dead_letter,
+            partitioner,
+            clusters: vec![],
+            router: None,
+            serializer: None,
         }
     }
+
+    pub fn serializer(mut self, serializer: Option<Box<dyn KafkaRecordSerializer>>) -> Self {
+        self.serializer = serializer;
+        self
+    }
+
+    /// Produce to the secondary clusters besides the default one, the records are routed
+    /// across all clusters by the `router`
+    pub fn clusters(
+        mut self,
+        clusters: Vec<(String, ClientConfig)>,
+        router: Option<Box<dyn KafkaClusterRouter>>,
+    ) -> Self {
+        self.clusters = clusters;
+        self.router = router;
+        self
+    }
 }
 
 #[async_trait]
 impl OutputFormat for KafkaOutputFormat {
     async fn open(&mut self, context: &Context) -> core::Result<()> {
+        if let Some(serializer) = self.serializer.as_mut() {
+            serializer.open(&context.input_schema).await?;
+        }
+
         let mut tags = context.task_id.to_tags();
         tags.push(Tag::new(
             "topic",
             self.topic.as_ref().map(|x| x.as_str()).unwrap_or(""),
         ));
 
-        let (sender, receiver) = named_channel(self.name(), tags, self.buffer_size);
+        let (sender, receiver) = named_channel(self.name(), tags.clone(), self.buffer_size);
         self.handover = Some(sender);
 
         let topic = self.topic.clone();
         let client_config = self.client_config.clone();
+        let max_in_flight = self.max_in_flight;
+        let retry_policy = self.retry_policy.clone();
+        let dead_letter = self.dead_letter.take();
+        let partitioner = self.partitioner.take();
+        let clusters = self.clusters.clone();
+        let router = self.router.take();
         tokio::spawn(async move {
-            let mut kafka_consumer = KafkaProducerThread::new(topic, client_config, receiver);
+            let mut kafka_consumer = KafkaProducerThread::new(topic, client_config, receiver)
+                .max_in_flight(max_in_flight)
+                .retry_policy(retry_policy)
+                .dead_letter(dead_letter)
+                .partitioner(partitioner)
+                .router(router)
+                .tags(tags);
+            for (name, client_config) in clusters {
+                kafka_consumer = kafka_consumer.cluster(name.as_str(), client_config);
+            }
             kafka_consumer.run().await;
         });
 
@@ -52,12 +119,33 @@ impl OutputFormat for KafkaOutputFormat {
     }
 
     async fn write_element(&mut self, element: Element) {
-        self.handover
-            .as_ref()
-            .unwrap()
-            .send(element.into_record())
-            .await
-            .unwrap();
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write kafka record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let mut record = element.into_record();
+        if let Some(serializer) = self.serializer.as_mut() {
+            record = serializer
+                .serialize(&mut record)
+                .map_err(|e| anyhow!("serialize kafka record error. {}", e))?;
+        }
+
+        // the malformed record is reported here rather than in the producer thread, so it's
+        // handled by the error policy of the sink, see `SinkStream::error_policy`
+        let entity = kafka_message::Entity::parse(record.as_buffer())
+            .map_err(|e| anyhow!("illegal `KafkaRecord`. {:?}", e))?;
+        if self.topic.is_none() && entity.topic.is_empty() {
+            return Err(core::Error::from("topic not found in `KafkaRecord`"));
+        }
+
+        // the producer thread has exited, it's a failure of the task rather than of the record, so
+        // the task is failed and restarted by the restart strategy
+        if self.handover.as_ref().unwrap().send(record).await.is_err() {
+            panic!("the kafka producer handover is closed");
+        }
+        Ok(())
     }
 
     async fn close(&mut self) -> core::Result<()> {
@@ -0,0 +1,10 @@
+use rlink::core::element::Record;
+
+/// Choose the partition of the record instead of the default key hashing of rdkafka
+pub trait KafkaPartitioner: Send + Sync {
+    /// `record` is the `KafkaMessage` record handed over to the sink, `partition_num` is the
+    /// partition count of the target `topic`.
+    ///
+    /// Returns the partition id, or `None` to fall back to the default partitioner.
+    fn partition(&mut self, record: &mut Record, topic: &str, partition_num: i32) -> Option<i32>;
+}
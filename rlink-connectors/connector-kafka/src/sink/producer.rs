@@ -1,22 +1,76 @@
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
+use rdkafka::error::KafkaError;
+use rdkafka::message::OwnedHeaders;
+use rdkafka::producer::{DeliveryFuture, FutureProducer, FutureRecord, Producer};
+use rdkafka::types::RDKafkaErrorCode;
 use rdkafka::ClientConfig;
 use rlink::channel::receiver::ChannelReceiver;
 use rlink::channel::TryRecvError;
 use rlink::core::element::Record;
+use rlink::metrics::Tag;
+use tokio::task::JoinHandle;
 
 use crate::buffer_gen::kafka_message;
+use crate::metrics::ProducerMetrics;
+use crate::sink::dead_letter::{DeadLetterQueue, RetryPolicy};
+use crate::sink::partitioner::KafkaPartitioner;
+use crate::sink::router::{ClusterRoute, KafkaClusterRouter, DEFAULT_CLUSTER};
+use crate::SINK_MAX_IN_FLIGHT;
+
+const PRODUCE_BATCH_SIZE: usize = 3000;
+
+/// the cached partition count of the topics is refreshed after the interval, so the partitions
+/// added to the topics are picked up by the `partitioner`
+const PARTITION_NUM_REFRESH_INTERVAL: Duration = Duration::from_secs(300);
+
+/// A record waiting for the delivery report of the cluster
+struct InFlightRecord {
+    cluster: usize,
+    record: Record,
+    delivery_future: DeliveryFuture,
+    send_time: Instant,
+}
+
+type InFlightQueue = VecDeque<InFlightRecord>;
+
+/// The cached partition count of a topic in a cluster
+struct PartitionNum {
+    partition_num: i32,
+    fetch_time: Instant,
+    /// the fetch in the background refreshing the stale partition count
+    refreshing: Option<JoinHandle<Result<i32, KafkaError>>>,
+}
+
+struct KafkaCluster {
+    name: String,
+    producer: FutureProducer,
+}
 
 pub struct KafkaProducerThread {
     topic: Option<String>,
-    producer: FutureProducer,
+    /// the first one is the `DEFAULT_CLUSTER`
+    clusters: Vec<KafkaCluster>,
+    router: Option<Box<dyn KafkaClusterRouter>>,
     receiver: ChannelReceiver<Record>,
+    max_in_flight: usize,
+
+    retry_policy: RetryPolicy,
+    dead_letter: Option<DeadLetterQueue>,
+
+    partitioner: Option<Box<dyn KafkaPartitioner>>,
+    /// cache of partition count by cluster and topic, only for `partitioner`
+    partition_nums: HashMap<(usize, String), PartitionNum>,
 
     drain_counter: Arc<AtomicU64>,
     discard_counter: Arc<AtomicU64>,
+    dead_letter_counter: Arc<AtomicU64>,
+
+    tags: Vec<Tag>,
+    metrics: Option<ProducerMetrics>,
 }
 
 impl KafkaProducerThread {
@@ -29,104 +83,476 @@ impl KafkaProducerThread {
 
         KafkaProducerThread {
             topic,
-            producer,
+            clusters: vec![KafkaCluster {
+                name: DEFAULT_CLUSTER.to_string(),
+                producer,
+            }],
+            router: None,
             receiver,
+            max_in_flight: SINK_MAX_IN_FLIGHT,
+            retry_policy: RetryPolicy::no_retry(),
+            dead_letter: None,
+            partitioner: None,
+            partition_nums: HashMap::new(),
             drain_counter: Arc::new(AtomicU64::new(0)),
             discard_counter: Arc::new(AtomicU64::new(0)),
+            dead_letter_counter: Arc::new(AtomicU64::new(0)),
+            tags: vec![],
+            metrics: None,
         }
     }
 
-    pub async fn run(&mut self) {
-        let idle_delay_10 = Duration::from_millis(10);
-        let idle_delay_300 = Duration::from_millis(300);
-        let mut idle_counter = 0;
+    /// Add a secondary cluster, the records are routed across the clusters by the `router`
+    pub fn cluster(mut self, name: &str, client_config: ClientConfig) -> Self {
+        let producer: FutureProducer = client_config.create().expect("Producer creation failed");
+        self.clusters.push(KafkaCluster {
+            name: name.to_string(),
+            producer,
+        });
+        self
+    }
 
-        let batch = 3000;
+    pub fn router(mut self, router: Option<Box<dyn KafkaClusterRouter>>) -> Self {
+        self.router = router;
+        self
+    }
 
-        loop {
-            let mut future_queue = Vec::with_capacity(batch);
-            let mut discard_counter = 0;
-            for _n in 0..batch {
-                match self.receiver.try_recv() {
-                    Ok(mut record) => {
-                        let kafka_message::Entity {
-                            timestamp,
-                            key,
-                            payload,
-                            topic,
-                            ..
-                        } = kafka_message::Entity::parse(record.as_buffer()).unwrap();
-
-                        let topic = match self.topic.as_ref() {
-                            Some(topic) => topic.as_str(),
-                            None => topic,
-                        };
-                        if topic.is_empty() {
-                            panic!("topic not found in `KafkaRecord`");
-                        }
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
 
-                        let future_record = FutureRecord::to(topic)
-                            .payload(payload)
-                            .timestamp(timestamp as i64)
-                            .key(key);
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
 
-                        match self.producer.send_result(future_record) {
-                            Ok(delivery_future) => future_queue.push(delivery_future),
-                            Err((e, _future_record)) => {
-                                error!("send error. {}", e);
-                                discard_counter += 1;
+    pub fn dead_letter(mut self, dead_letter: Option<DeadLetterQueue>) -> Self {
+        self.dead_letter = dead_letter;
+        self
+    }
+
+    pub fn partitioner(mut self, partitioner: Option<Box<dyn KafkaPartitioner>>) -> Self {
+        self.partitioner = partitioner;
+        self
+    }
+
+    /// Tags of the producer metrics, the metrics are registered when the thread runs
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    fn on_drain(&self, send_time: Option<Instant>) {
+        self.drain_counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics.drain();
+            if let Some(send_time) = send_time {
+                metrics.latency(send_time.elapsed());
+            }
+        }
+    }
+
+    fn on_discard(&self) {
+        self.discard_counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics.discard();
+        }
+    }
+
+    fn on_dead_letter(&self) {
+        self.dead_letter_counter.fetch_add(1, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics.dead_letter();
+        }
+    }
+
+    fn on_in_flight(&self, in_flight: &InFlightQueue) {
+        if let Some(metrics) = &self.metrics {
+            metrics.in_flight(in_flight.len());
+        }
+    }
+
+    /// The partition count of the topic, only the first lookup of the topic waits for the
+    /// metadata. The stale count is still used while it's refreshed in the background
+    async fn partition_num(&mut self, cluster: usize, topic: &str) -> Result<i32, KafkaError> {
+        let cache_key = (cluster, topic.to_string());
+        if let Some(cached) = self.partition_nums.get_mut(&cache_key) {
+            match cached.refreshing.take() {
+                Some(refreshing) if refreshing.is_finished() => {
+                    match refreshing.await.unwrap_or(Err(KafkaError::Canceled)) {
+                        Ok(partition_num) => {
+                            if partition_num != cached.partition_num {
+                                info!(
+                                    "the partition count of the topic `{}` changed: {} -> {}",
+                                    topic, cached.partition_num, partition_num
+                                );
                             }
+                            cached.partition_num = partition_num;
                         }
+                        Err(e) => warn!(
+                            "refresh the partition count of the topic `{}` error: {:?}",
+                            topic, e
+                        ),
                     }
-                    Err(TryRecvError::Empty) => {
-                        break;
+                    cached.fetch_time = Instant::now();
+                }
+                Some(refreshing) => cached.refreshing = Some(refreshing),
+                None if cached.fetch_time.elapsed() >= PARTITION_NUM_REFRESH_INTERVAL => {
+                    let producer = &self.clusters[cluster].producer;
+                    cached.refreshing = Some(fetch_partition_num(producer, topic));
+                }
+                None => {}
+            }
+            return Ok(cached.partition_num);
+        }
+
+        let partition_num = fetch_partition_num(&self.clusters[cluster].producer, topic)
+            .await
+            .unwrap_or(Err(KafkaError::Canceled))?;
+        self.partition_nums.insert(
+            cache_key,
+            PartitionNum {
+                partition_num,
+                fetch_time: Instant::now(),
+                refreshing: None,
+            },
+        );
+        Ok(partition_num)
+    }
+
+    async fn partition(
+        &mut self,
+        cluster: usize,
+        record: &mut Record,
+    ) -> Result<Option<i32>, KafkaError> {
+        if self.partitioner.is_none() {
+            return Ok(None);
+        }
+
+        let topic = match self.topic.as_ref() {
+            Some(topic) => topic.clone(),
+            None => parse_record(record)?.topic.to_string(),
+        };
+        let partition_num = self.partition_num(cluster, topic.as_str()).await?;
+
+        let partitioner = self.partitioner.as_mut().unwrap();
+        Ok(partitioner.partition(record, topic.as_str(), partition_num))
+    }
+
+    async fn send_record(
+        &mut self,
+        cluster: usize,
+        record: &mut Record,
+    ) -> Result<DeliveryFuture, KafkaError> {
+        let partition = self.partition(cluster, record).await?;
+
+        let kafka_message::Entity {
+            timestamp,
+            key,
+            payload,
+            topic,
+            ..
+        } = parse_record(record)?;
+
+        let topic = match self.topic.as_ref() {
+            Some(topic) => topic.as_str(),
+            None => topic,
+        };
+        if topic.is_empty() {
+            return Err(KafkaError::MessageProduction(
+                RDKafkaErrorCode::UnknownTopic,
+            ));
+        }
+
+        let mut future_record = FutureRecord::to(topic)
+            .payload(payload)
+            .timestamp(timestamp as i64)
+            .key(key);
+        if let Some(partition) = partition {
+            future_record = future_record.partition(partition);
+        }
+
+        self.clusters[cluster]
+            .producer
+            .send_result(future_record)
+            .map_err(|(e, _future_record)| e)
+    }
+
+    /// retry the failed record by the `RetryPolicy`, then route it to the dead letter queue
+    async fn handle_failure(&mut self, cluster: usize, mut record: Record, mut error: KafkaError) {
+        for attempt in 0..self.retry_policy.max_retries() {
+            tokio::time::sleep(self.retry_policy.backoff(attempt)).await;
+
+            let result = match self.send_record(cluster, &mut record).await {
+                Ok(delivery_future) => match delivery_future.await {
+                    Ok(Ok((_, _))) => Ok(()),
+                    Ok(Err((err, _msg))) => Err(err),
+                    Err(_e) => Err(KafkaError::Canceled),
+                },
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(()) => {
+                    self.on_drain(None);
+                    return;
+                }
+                Err(e) => {
+                    warn!(
+                        "produce retry {} error: {:?}, cluster: {}",
+                        attempt + 1,
+                        e,
+                        self.clusters[cluster].name
+                    );
+                    error = e;
+                }
+            }
+        }
+
+        self.send_dead_letter(cluster, record, error).await;
+    }
+
+    /// the dead letter topic is produced in the same cluster as the failed record
+    async fn send_dead_letter(&mut self, cluster: usize, mut record: Record, error: KafkaError) {
+        match self.dead_letter.as_mut() {
+            Some(DeadLetterQueue::Topic(dead_letter_topic)) => {
+                let kafka_message::Entity {
+                    timestamp,
+                    key,
+                    payload,
+                    topic,
+                    ..
+                } = match parse_record(&mut record) {
+                    Ok(entity) => entity,
+                    Err(e) => {
+                        error!(
+                            "the record can't be produced to the dead letter topic: {:?}, original error: {:?}",
+                            e, error
+                        );
+                        self.on_discard();
+                        return;
                     }
-                    Err(TryRecvError::Disconnected) => {
-                        panic!("kafka recv channel disconnected");
+                };
+
+                let original_topic = self.topic.as_ref().map(|x| x.as_str()).unwrap_or(topic);
+                let error_msg = error.to_string();
+                let headers = OwnedHeaders::new()
+                    .add("rlink.dlq.topic", original_topic)
+                    .add("rlink.dlq.error", error_msg.as_str());
+
+                let future_record = FutureRecord::to(dead_letter_topic.as_str())
+                    .payload(payload)
+                    .timestamp(timestamp as i64)
+                    .key(key)
+                    .headers(headers);
+
+                match self.clusters[cluster]
+                    .producer
+                    .send(future_record, Duration::from_secs(0))
+                    .await
+                {
+                    Ok((_, _)) => self.on_dead_letter(),
+                    Err((e, _msg)) => {
+                        error!(
+                            "produce to dead letter topic error: {:?}, original error: {:?}",
+                            e, error
+                        );
+                        self.on_discard();
                     }
                 }
             }
+            Some(DeadLetterQueue::Handler(handler)) => {
+                handler.handle(record, &error);
+                self.on_dead_letter();
+            }
+            None => {
+                error!("produce error: {:?}", error);
+                self.on_discard();
+            }
+        }
+    }
 
-            if future_queue.len() == 0 {
-                idle_counter += 1;
-                if idle_counter < 30 {
-                    tokio::time::sleep(idle_delay_10).await;
-                } else {
-                    tokio::time::sleep(idle_delay_300).await;
+    /// Resolve the index of the clusters which the record is routed to
+    fn route(&mut self, record: &mut Record) -> Vec<usize> {
+        let router = match self.router.as_mut() {
+            Some(router) => router,
+            None => return vec![0],
+        };
+
+        match router.route(record) {
+            ClusterRoute::All => (0..self.clusters.len()).collect(),
+            ClusterRoute::Cluster(name) => {
+                match self.clusters.iter().position(|c| c.name.eq(&name)) {
+                    Some(index) => vec![index],
+                    None => {
+                        error!(
+                            "kafka cluster `{}` not found, the record is discarded",
+                            name
+                        );
+                        self.on_discard();
+                        vec![]
+                    }
                 }
-            } else {
-                idle_counter = 0;
-                self.producer.flush(Duration::from_secs(3));
-
-                let mut drain_counter = 0;
-                for future in future_queue {
-                    match future.await {
-                        Ok(result) => match result {
-                            Ok((_, _)) => drain_counter += 1,
-                            Err((err, _msg)) => {
-                                error!("produce error: {:?}", err);
-                                discard_counter += 1;
-                            }
-                        },
-                        Err(e) => {
-                            error!("produce `Canceled` error. {}", e);
-                            discard_counter += 1;
-                        }
+            }
+        }
+    }
+
+    async fn produce(&mut self, mut record: Record, in_flight: &mut InFlightQueue) {
+        let clusters = self.route(&mut record);
+        if let Some((last, mirrors)) = clusters.split_last() {
+            for cluster in mirrors {
+                self.produce_to(*cluster, record.clone(), in_flight).await;
+            }
+            self.produce_to(*last, record, in_flight).await;
+        }
+    }
+
+    async fn produce_to(
+        &mut self,
+        cluster: usize,
+        mut record: Record,
+        in_flight: &mut InFlightQueue,
+    ) {
+        // bound the in-flight records, the handover channel will fill up and
+        // the upstream is back-pressured while waiting here
+        while in_flight.len() >= self.max_in_flight {
+            let in_flight_record = in_flight.pop_front().unwrap();
+            self.await_delivery(in_flight_record).await;
+        }
+
+        match self.send_record(cluster, &mut record).await {
+            Ok(delivery_future) => in_flight.push_back(InFlightRecord {
+                cluster,
+                record,
+                delivery_future,
+                send_time: Instant::now(),
+            }),
+            Err(KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull)) => {
+                // the local queue of librdkafka is full, wait for the in-flight records and resend
+                self.await_in_flight(in_flight).await;
+                match self.send_record(cluster, &mut record).await {
+                    Ok(delivery_future) => in_flight.push_back(InFlightRecord {
+                        cluster,
+                        record,
+                        delivery_future,
+                        send_time: Instant::now(),
+                    }),
+                    Err(e) => {
+                        error!("send error. {}", e);
+                        self.handle_failure(cluster, record, e).await;
                     }
                 }
+            }
+            Err(e) => {
+                error!("send error. {}", e);
+                self.handle_failure(cluster, record, e).await;
+            }
+        }
+    }
+
+    async fn await_delivery(&mut self, in_flight_record: InFlightRecord) {
+        let InFlightRecord {
+            cluster,
+            record,
+            delivery_future,
+            send_time,
+        } = in_flight_record;
+
+        match delivery_future.await {
+            Ok(Ok((_, _))) => self.on_drain(Some(send_time)),
+            Ok(Err((err, _msg))) => {
+                error!("produce error: {:?}", err);
+                self.handle_failure(cluster, record, err).await;
+            }
+            Err(e) => {
+                error!("produce `Canceled` error. {}", e);
+                self.handle_failure(cluster, record, KafkaError::Canceled)
+                    .await;
+            }
+        }
+    }
+
+    async fn await_in_flight(&mut self, in_flight: &mut InFlightQueue) {
+        while let Some(in_flight_record) = in_flight.pop_front() {
+            self.await_delivery(in_flight_record).await;
+        }
+        self.on_in_flight(in_flight);
+    }
+
+    pub async fn run(&mut self) {
+        self.metrics = Some(ProducerMetrics::new(self.tags.clone()));
+        let mut in_flight = InFlightQueue::with_capacity(self.max_in_flight);
+
+        // wait for the first record, then drain the channel up to the batch size
+        while let Some(record) = self.receiver.recv().await {
+            self.produce(record, &mut in_flight).await;
 
-                self.drain_counter
-                    .fetch_add(drain_counter as u64, Ordering::Relaxed);
+            let mut drained = false;
+            for _n in 1..PRODUCE_BATCH_SIZE {
+                match self.receiver.try_recv() {
+                    Ok(record) => self.produce(record, &mut in_flight).await,
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => {
+                        drained = true;
+                        break;
+                    }
+                }
             }
 
-            if discard_counter > 0 {
-                self.discard_counter
-                    .fetch_add(discard_counter as u64, Ordering::Relaxed);
+            self.on_in_flight(&in_flight);
+
+            // no more records for now, settle the delivery of the in-flight records
+            // before waiting for the next one
+            if drained {
+                self.await_in_flight(&mut in_flight).await;
             }
         }
+
+        self.await_in_flight(&mut in_flight).await;
+        info!(
+            "kafka recv channel closed. drain: {}, discard: {}, dead letter: {}",
+            self.drain_counter.load(Ordering::Relaxed),
+            self.discard_counter.load(Ordering::Relaxed),
+            self.dead_letter_counter.load(Ordering::Relaxed)
+        );
     }
 }
 
+/// Fetch the partition count of the topic, the metadata request blocks so it runs on the
+/// blocking threads of the runtime
+fn fetch_partition_num(
+    producer: &FutureProducer,
+    topic: &str,
+) -> JoinHandle<Result<i32, KafkaError>> {
+    let producer = producer.clone();
+    let topic = topic.to_string();
+    tokio::task::spawn_blocking(move || {
+        let metadata = producer
+            .client()
+            .fetch_metadata(Some(topic.as_str()), Duration::from_secs(3))?;
+        let partition_num = metadata
+            .topics()
+            .get(0)
+            .map(|t| t.partitions().len() as i32)
+            .unwrap_or(0);
+        if partition_num == 0 {
+            return Err(KafkaError::MetadataFetch(
+                RDKafkaErrorCode::UnknownTopicOrPartition,
+            ));
+        }
+        Ok(partition_num)
+    })
+}
+
+/// The records are checked by `KafkaOutputFormat` before they're handed over, a malformed one
+/// is failed as a non-retriable produce error
+fn parse_record(record: &mut Record) -> Result<kafka_message::Entity, KafkaError> {
+    kafka_message::Entity::parse(record.as_buffer()).map_err(|e| {
+        error!("illegal `KafkaRecord`. {:?}", e);
+        KafkaError::MessageProduction(RDKafkaErrorCode::InvalidRecord)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::Ordering;
@@ -0,0 +1,28 @@
+use rlink::core::element::Record;
+
+/// The name of the cluster configured by the sink's `conf_map`
+pub const DEFAULT_CLUSTER: &str = "default";
+
+/// Which clusters a record is produced to
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClusterRoute {
+    /// mirror the record to every cluster, eg: primary + DR
+    All,
+    /// the named cluster only
+    Cluster(String),
+}
+
+/// Route the records across the clusters of a multi-cluster kafka sink
+pub trait KafkaClusterRouter: Send + Sync {
+    /// `record` is the `KafkaMessage` record handed over to the sink
+    fn route(&mut self, record: &mut Record) -> ClusterRoute;
+}
+
+/// Mirror every record to all clusters
+pub struct MirrorClusterRouter;
+
+impl KafkaClusterRouter for MirrorClusterRouter {
+    fn route(&mut self, _record: &mut Record) -> ClusterRoute {
+        ClusterRoute::All
+    }
+}
@@ -0,0 +1,14 @@
+use rlink::core::element::{FnSchema, Record};
+
+/// Convert the records handed over to the sink into `KafkaMessage` records,
+/// the sink takes the `KafkaMessage` records as is without a serializer
+#[async_trait]
+pub trait KafkaRecordSerializer: Send + Sync {
+    /// Called when the sink opened, `input_schema` is the schema of the sink's input records
+    async fn open(&mut self, _input_schema: &FnSchema) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the `KafkaMessage` record built by `build_kafka_record`
+    fn serialize(&mut self, record: &mut Record) -> anyhow::Result<Record>;
+}
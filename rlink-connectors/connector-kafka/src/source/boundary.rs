@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use rdkafka::consumer::{BaseConsumer, Consumer, DefaultConsumerContext};
+use rlink::core::properties::Properties;
+
+use crate::source::offset_for_time;
+use crate::source::offset_range::{
+    decode_partition_offsets, encode_partition_offsets, PartitionOffset,
+};
+use crate::{BOUNDED_OFFSET, BOUNDED_TIMESTAMP, BOUNDED_TYPE};
+
+/// The end position of a bounded kafka source. Each partition is consumed until the
+/// resolved end offset(inclusive) is reached, then the source finishes and the runtime
+/// emits a `MAX_WATERMARK` with the end `StreamStatus` downstream.
+#[derive(Clone, Debug)]
+pub enum OffsetBoundary {
+    /// the latest offsets of each partition when the input splits are created, so all tasks
+    /// stop at the offsets resolved once for the job
+    Latest,
+    /// inclusive end offsets of each topic's partitions
+    Offsets(HashMap<String, Vec<PartitionOffset>>),
+    /// stop before the first message whose timestamp(millis) is greater than or equal to it
+    Timestamp(u64),
+}
+
+impl OffsetBoundary {
+    /// Resolve the last offset to consume for the topic partition.
+    /// A result less than the begin offset means there is nothing to consume.
+    pub(crate) fn end_offset(
+        &self,
+        consumer: &BaseConsumer<DefaultConsumerContext>,
+        topic: &str,
+        partition: i32,
+    ) -> anyhow::Result<i64> {
+        let timeout = Duration::from_secs(3);
+        match self {
+            Self::Latest => {
+                let (_low, high) = consumer.fetch_watermarks(topic, partition, timeout)?;
+                Ok(high - 1)
+            }
+            Self::Offsets(offsets) => offsets
+                .get(topic)
+                .and_then(|partition_offsets| {
+                    partition_offsets
+                        .iter()
+                        .find(|x| x.partition == partition)
+                        .map(|x| x.offset)
+                })
+                .ok_or(anyhow!(
+                    "bounded offset of topic({}) partition({}) not found",
+                    topic,
+                    partition
+                )),
+            Self::Timestamp(timestamp) => {
+                match offset_for_time(consumer, topic, partition, *timestamp)? {
+                    Some(offset) => Ok(offset - 1),
+                    // no message at or after the timestamp, all existing messages are in range
+                    None => {
+                        let (_low, high) = consumer.fetch_watermarks(topic, partition, timeout)?;
+                        Ok(high - 1)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Into<Properties> for OffsetBoundary {
+    fn into(self) -> Properties {
+        let mut properties = Properties::new();
+        match self {
+            Self::Latest => {
+                properties.set_str(BOUNDED_TYPE, "latest");
+            }
+            Self::Offsets(offsets) => {
+                properties.set_str(BOUNDED_TYPE, "direct");
+                for (topic, po) in offsets {
+                    properties.set_string(
+                        format!("{}.{}", BOUNDED_OFFSET, topic),
+                        encode_partition_offsets(po),
+                    );
+                }
+            }
+            Self::Timestamp(timestamp) => {
+                properties.set_str(BOUNDED_TYPE, "timestamp");
+                properties.set_u64(BOUNDED_TIMESTAMP, timestamp);
+            }
+        }
+
+        properties
+    }
+}
+
+impl TryFrom<Properties> for OffsetBoundary {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let bounded_type = properties.get_string(BOUNDED_TYPE)?;
+        match bounded_type.as_str() {
+            "latest" => Ok(Self::Latest),
+            "direct" => {
+                let mut map = HashMap::new();
+                for (topic, offset_str) in properties.to_sub_properties(BOUNDED_OFFSET).as_map() {
+                    map.insert(topic.clone(), decode_partition_offsets(offset_str)?);
+                }
+                Ok(Self::Offsets(map))
+            }
+            "timestamp" => {
+                let timestamp = properties.get_u64(BOUNDED_TIMESTAMP)?;
+                Ok(Self::Timestamp(timestamp))
+            }
+            _ => Err(anyhow!("unknown bounded type {}", bounded_type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    use rlink::core::properties::Properties;
+
+    use crate::source::boundary::OffsetBoundary;
+    use crate::source::offset_range::PartitionOffset;
+
+    #[test]
+    pub fn offset_boundary_properties_test() {
+        let mut offsets = HashMap::new();
+        offsets.insert(
+            "topic-0".to_string(),
+            vec![PartitionOffset::new(3, 71), PartitionOffset::new(1, 121)],
+        );
+
+        let properties: Properties = OffsetBoundary::Offsets(offsets).into();
+        let boundary = OffsetBoundary::try_from(properties).unwrap();
+        match &boundary {
+            OffsetBoundary::Offsets(offsets) => {
+                let po = offsets.get("topic-0").unwrap();
+                assert_eq!(po.len(), 2);
+                assert_eq!((po[0].partition(), po[0].offset()), (1, 121));
+                assert_eq!((po[1].partition(), po[1].offset()), (3, 71));
+            }
+            _ => panic!("unexpected boundary {:?}", boundary),
+        }
+
+        let mut properties = Properties::new();
+        properties.set_str("type", "direct");
+        properties.set_str("offset.topic-0", "121,71");
+        assert!(OffsetBoundary::try_from(properties).is_err());
+    }
+}
@@ -1,29 +1,39 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
 
 use rdkafka::ClientConfig;
 use rlink::core::element::FnSchema;
 use rlink::core::properties::{Properties, PARALLELISM};
 
 use crate::buffer_gen::kafka_message;
+use crate::security::{redact_conf_map, KafkaSecurityConfig};
+use crate::source::boundary::OffsetBoundary;
 use crate::source::deserializer::{
     DefaultKafkaRecordDeserializer, DefaultKafkaRecordDeserializerBuilder,
     KafkaRecordDeserializerBuilder,
 };
+use crate::source::discovery::TopicPattern;
 use crate::source::offset_range::OffsetRange;
+use crate::source::rate_limit::RateLimit;
+use crate::source::startup_mode::StartupMode;
 use crate::{
-    KafkaInputFormat, BOOTSTRAP_SERVERS, BUFFER_SIZE, GROUP_ID, KAFKA, OFFSET, SOURCE_CHANNEL_SIZE,
-    TOPICS,
+    KafkaInputFormat, BOOTSTRAP_SERVERS, BOUNDED, BUFFER_SIZE, GROUP_ID, KAFKA, OFFSET, RATE_LIMIT,
+    SECURITY, SOURCE_CHANNEL_SIZE, STARTUP, TOPICS, TOPIC_DISCOVERY_INTERVAL, TOPIC_PATTERN,
 };
 
-#[derive(Debug)]
 pub struct KafkaInputFormatBuilder {
     fn_name: Option<String>,
     parallelism: u16,
     conf_map: HashMap<String, String>,
     topics: Vec<String>,
+    topic_pattern: Option<TopicPattern>,
     buffer_size: Option<usize>,
     offset_range: OffsetRange,
+    startup_mode: StartupMode,
+    bounded: Option<OffsetBoundary>,
+    rate_limit: Option<RateLimit>,
+    security: Option<KafkaSecurityConfig>,
 }
 
 impl KafkaInputFormatBuilder {
@@ -33,8 +43,13 @@ impl KafkaInputFormatBuilder {
             parallelism,
             conf_map,
             topics,
+            topic_pattern: None,
             buffer_size: None,
             offset_range: OffsetRange::None,
+            startup_mode: StartupMode::default(),
+            bounded: None,
+            rate_limit: None,
+            security: None,
         }
     }
 
@@ -43,6 +58,13 @@ impl KafkaInputFormatBuilder {
         self
     }
 
+    /// Consume all topics matching the pattern instead of the `topics`, the topics created
+    /// after the job started are picked up and assigned across the subtasks periodically
+    pub fn topic_pattern(mut self, topic_pattern: TopicPattern) -> Self {
+        self.topic_pattern = Some(topic_pattern);
+        self
+    }
+
     pub fn buffer_size(mut self, size: usize) -> Self {
         self.buffer_size = Some(size);
         self
@@ -53,6 +75,30 @@ impl KafkaInputFormatBuilder {
         self
     }
 
+    /// Where to begin consuming when there is no offset restored from the checkpoint,
+    /// the `offset_range` takes precedence over it
+    pub fn startup_mode(mut self, startup_mode: StartupMode) -> Self {
+        self.startup_mode = startup_mode;
+        self
+    }
+
+    /// Consume each partition up to the `boundary`, then finish the source
+    pub fn bounded(mut self, boundary: OffsetBoundary) -> Self {
+        self.bounded = Some(boundary);
+        self
+    }
+
+    /// Throttle the records and bytes consumed per second
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn security(mut self, security: KafkaSecurityConfig) -> Self {
+        self.security = Some(security);
+        self
+    }
+
     pub fn build(
         self,
         deserializer_builder: Option<Box<dyn KafkaRecordDeserializerBuilder>>,
@@ -63,6 +109,9 @@ impl KafkaInputFormatBuilder {
         for (key, val) in &self.conf_map {
             client_config.set(key.as_str(), val.as_str());
         }
+        if let Some(security) = &self.security {
+            security.apply(&mut client_config);
+        }
 
         let fn_name = self.fn_name.unwrap_or("KafkaInputFormat".to_string());
         let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
@@ -81,12 +130,16 @@ impl KafkaInputFormatBuilder {
         KafkaInputFormat::new(
             client_config,
             self.topics,
+            self.topic_pattern,
             buffer_size,
             self.offset_range,
+            self.startup_mode,
+            self.bounded,
             deserializer_builder,
             self.parallelism,
             fn_name,
         )
+        .rate_limit(self.rate_limit)
     }
 }
 
@@ -106,13 +159,30 @@ impl TryFrom<Properties> for KafkaInputFormatBuilder {
             kafka_properties.as_map().clone()
         };
 
-        let topics = properties.get_string(TOPICS)?;
-        let topics: Vec<String> = topics.trim().split(",").map(|x| x.to_string()).collect();
-        if topics.len() == 0 {
+        let topic_pattern = match properties.get_string(TOPIC_PATTERN) {
+            Ok(pattern) => {
+                let mut topic_pattern = TopicPattern::new(pattern.as_str())?;
+                if let Ok(interval) = properties.get_duration(TOPIC_DISCOVERY_INTERVAL) {
+                    topic_pattern = topic_pattern.discovery_interval(interval);
+                }
+                Some(topic_pattern)
+            }
+            Err(_) => None,
+        };
+
+        let topics: Vec<String> = match properties.get_string(TOPICS) {
+            Ok(topics) => topics.trim().split(",").map(|x| x.to_string()).collect(),
+            Err(_) if topic_pattern.is_some() => vec![],
+            Err(e) => return Err(e),
+        };
+        if topics.is_empty() && topic_pattern.is_none() {
             return Err(anyhow!("`topics` not found"));
         }
 
         let mut builder = KafkaInputFormatBuilder::new(client_config, topics, parallelism);
+        if let Some(topic_pattern) = topic_pattern {
+            builder = builder.topic_pattern(topic_pattern);
+        }
 
         builder = builder.fn_name(properties.name());
 
@@ -122,8 +192,50 @@ impl TryFrom<Properties> for KafkaInputFormatBuilder {
 
         let offset_properties = properties.to_sub_properties(OFFSET);
         let offset_range = OffsetRange::try_from(offset_properties)?;
-        let builder = builder.offset_range(offset_range);
+        builder = builder.offset_range(offset_range);
+
+        let startup_properties = properties.to_sub_properties(STARTUP);
+        if !startup_properties.is_empty() {
+            let startup_mode = StartupMode::try_from(startup_properties)?;
+            builder = builder.startup_mode(startup_mode);
+        }
+
+        let bounded_properties = properties.to_sub_properties(BOUNDED);
+        if !bounded_properties.is_empty() {
+            let boundary = OffsetBoundary::try_from(bounded_properties)?;
+            builder = builder.bounded(boundary);
+        }
+
+        let rate_limit_properties = properties.to_sub_properties(RATE_LIMIT);
+        if !rate_limit_properties.is_empty() {
+            let rate_limit = RateLimit::try_from(rate_limit_properties)?;
+            builder = builder.rate_limit(rate_limit);
+        }
+
+        let security_properties = properties.to_sub_properties(SECURITY);
+        if !security_properties.is_empty() {
+            let security = KafkaSecurityConfig::try_from(security_properties)?;
+            builder = builder.security(security);
+        }
 
         Ok(builder)
     }
 }
+
+impl Debug for KafkaInputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaInputFormatBuilder")
+            .field("fn_name", &self.fn_name)
+            .field("parallelism", &self.parallelism)
+            .field("conf_map", &redact_conf_map(&self.conf_map))
+            .field("topics", &self.topics)
+            .field("topic_pattern", &self.topic_pattern)
+            .field("buffer_size", &self.buffer_size)
+            .field("offset_range", &self.offset_range)
+            .field("startup_mode", &self.startup_mode)
+            .field("bounded", &self.bounded)
+            .field("rate_limit", &self.rate_limit)
+            .field("security", &self.security)
+            .finish()
+    }
+}
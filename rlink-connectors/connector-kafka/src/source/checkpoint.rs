@@ -1,9 +1,18 @@
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicI64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::function::Context;
 use rlink::core::runtime::TaskId;
+use rlink::core::state::{ListState, ListStateDescriptor, OperatorStateStore};
 
+/// the union list state of the offset snapshots of all tasks
+const KAFKA_OFFSETS: &str = "kafka_offsets";
+
+/// The offsets are checkpointed as the union operator state, every task restores from the
+/// offsets of all tasks, so the partitions reassigned by the changed parallelism or the topic
+/// discovery are resumed by their new tasks
 #[derive(Debug, Clone)]
 pub struct KafkaCheckpointFunction {
     pub(crate) state_recorder: Option<KafkaSourceStateRecorder>,
@@ -13,6 +22,7 @@ pub struct KafkaCheckpointFunction {
     pub(crate) task_id: TaskId,
     topic: String,
     partition: i32,
+    operator_state: OperatorStateStore,
 }
 
 impl KafkaCheckpointFunction {
@@ -23,12 +33,24 @@ impl KafkaCheckpointFunction {
             task_id,
             topic: topic.to_string(),
             partition,
+            operator_state: OperatorStateStore::new(),
         }
     }
 
     pub fn as_state_mut(&mut self) -> &mut KafkaSourceStateRecorder {
         self.state_recorder.as_mut().unwrap()
     }
+
+    /// Restore the offsets of all tasks from the checkpoint of the `context`, it's called
+    /// before `initialize_state`
+    pub fn restore_from(&self, context: &Context) -> anyhow::Result<()> {
+        self.operator_state.restore_from(context)
+    }
+
+    fn offset_state(&self) -> ListState<String> {
+        self.operator_state
+            .union_list_state(&ListStateDescriptor::new(KAFKA_OFFSETS))
+    }
 }
 
 #[async_trait]
@@ -36,39 +58,49 @@ impl CheckpointFunction for KafkaCheckpointFunction {
     async fn initialize_state(
         &mut self,
         context: &FunctionSnapshotContext,
-        handle: &Option<CheckpointHandle>,
+        _handle: &Option<CheckpointHandle>,
     ) {
-        self.state_recorder = Some(KafkaSourceStateRecorder::new(
-            self.topic.as_str(),
-            self.partition,
-        ));
+        let state_recorder = KafkaSourceStateRecorder::new(self.topic.as_str(), self.partition);
         info!("Checkpoint initialize, context: {:?}", context);
 
-        if context.checkpoint_id.is_default() || handle.is_none() {
-            return;
+        let snapshots = self.offset_state().get().expect("get kafka offsets error");
+        for snapshot in &snapshots {
+            state_recorder
+                .update_from_snapshot(snapshot.as_str())
+                .unwrap();
         }
-
-        let handle = handle.as_ref().unwrap();
-
-        let state_cache = self.state_recorder.as_mut().unwrap();
-        state_cache
-            .update_from_snapshot(handle.handle.as_str())
-            .unwrap();
-
-        info!(
-            "load state value from checkpoint({:?}): {:?}",
-            context.checkpoint_id, handle.handle
-        );
+        if !snapshots.is_empty() {
+            info!(
+                "load offset {:?} from the checkpoint({:?}) of {} tasks",
+                state_recorder.get(),
+                context.checkpoint_id,
+                snapshots.len()
+            );
+        }
+        self.state_recorder = Some(state_recorder);
     }
 
     async fn snapshot_state(
         &mut self,
         context: &FunctionSnapshotContext,
     ) -> Option<CheckpointHandle> {
-        let handle = self.state_recorder.as_ref().unwrap().snapshot();
-        debug!("Checkpoint snapshot: {:?}, context: {:?}", handle, context);
+        let snapshot = self.state_recorder.as_ref().unwrap().snapshot();
+        debug!(
+            "Checkpoint snapshot: {:?}, context: {:?}",
+            snapshot, context
+        );
 
-        Some(CheckpointHandle { handle })
+        let handle = self
+            .offset_state()
+            .update(vec![snapshot])
+            .and_then(|_| self.operator_state.snapshot());
+        match handle {
+            Ok(handle) => Some(handle),
+            Err(e) => panic!(
+                "snapshot kafka offsets on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        }
     }
 }
 
@@ -77,6 +109,16 @@ struct OffsetSnapshot<'a> {
     topic: &'a str,
     partition: i32,
     offset: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    discovered: Vec<DiscoveredOffset>,
+}
+
+/// The offset of a partition picked up by the topic pattern discovery
+#[derive(Serialize, Deserialize)]
+struct DiscoveredOffset {
+    topic: String,
+    partition: i32,
+    offset: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -84,6 +126,11 @@ pub struct KafkaSourceStateRecorder {
     topic: String,
     partition: i32,
     offset: Arc<AtomicI64>,
+    /// offsets of the partitions discovered at runtime and assigned to the task
+    discovered: Arc<Mutex<HashMap<(String, i32), Arc<AtomicI64>>>>,
+    /// offsets of the discovered partitions in the checkpoint of all tasks, a partition's
+    /// offset is moved to the `discovered` once the partition is assigned to the task
+    restored: Arc<Mutex<HashMap<(String, i32), i64>>>,
 }
 
 impl KafkaSourceStateRecorder {
@@ -92,23 +139,51 @@ impl KafkaSourceStateRecorder {
             topic: topic.to_string(),
             partition,
             offset: Arc::new(AtomicI64::new(i64::MIN)),
+            discovered: Arc::new(Mutex::new(HashMap::new())),
+            restored: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Get or register the offset state of a discovered partition, the state is shared
+    /// with the consumer records of the partition
+    pub(crate) fn discovered_state(&self, topic: &str, partition: i32) -> Arc<AtomicI64> {
+        let key = (topic.to_string(), partition);
+        let mut discovered = self.discovered.lock().unwrap();
+        discovered
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let offset = self.restored.lock().unwrap().remove(&key);
+                Arc::new(AtomicI64::new(offset.unwrap_or(i64::MIN)))
+            })
+            .clone()
+    }
+
     pub fn update(&self, offset: i64) {
         self.offset.store(offset, Ordering::Relaxed);
     }
 
+    /// Restore from the offset snapshot of a task, the snapshots of all tasks are restored.
+    /// The offset of the task's partition is taken from the snapshot of the same partition, and
+    /// the offsets of the discovered partitions are kept for the discovery to assign them, the
+    /// latest offset wins if a partition is in the snapshots of several tasks
     pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
         let offset_snapshot: OffsetSnapshot = serde_json::from_str(snapshot_handle)?;
 
-        if !offset_snapshot.topic.eq(self.topic.as_str())
-            || offset_snapshot.partition != self.partition
+        if offset_snapshot.topic.eq(self.topic.as_str())
+            && offset_snapshot.partition == self.partition
         {
-            return Err(anyhow!("Does not belong to the checkpoint of the task"));
+            if let Some(offset) = offset_snapshot.offset {
+                self.offset.fetch_max(offset, Ordering::Relaxed);
+            }
         }
 
-        self.update(offset_snapshot.offset.unwrap_or(i64::MIN));
+        let mut restored = self.restored.lock().unwrap();
+        for discovered_offset in offset_snapshot.discovered {
+            let offset = restored
+                .entry((discovered_offset.topic, discovered_offset.partition))
+                .or_insert(i64::MIN);
+            *offset = discovered_offset.offset.max(*offset);
+        }
         Ok(())
     }
 
@@ -122,10 +197,34 @@ impl KafkaSourceStateRecorder {
             }
         };
 
+        // the restored offsets not assigned yet are kept until the discovery assigns them,
+        // they are never newer than the offsets of the tasks the partitions are assigned to
+        let restored = self.restored.lock().unwrap().clone();
+        let discovered = self
+            .discovered
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, offset)| (key.clone(), offset.load(Ordering::Relaxed)))
+            .chain(restored)
+            .filter_map(|((topic, partition), offset)| {
+                if offset == i64::MIN {
+                    None
+                } else {
+                    Some(DiscoveredOffset {
+                        topic,
+                        partition,
+                        offset,
+                    })
+                }
+            })
+            .collect();
+
         serde_json::to_string(&OffsetSnapshot {
             topic: self.topic.as_str(),
             partition: self.partition,
             offset,
+            discovered,
         })
         .unwrap()
     }
@@ -139,3 +238,31 @@ impl KafkaSourceStateRecorder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::KafkaSourceStateRecorder;
+
+    #[test]
+    pub fn restore_from_all_tasks_test() {
+        let task0 = KafkaSourceStateRecorder::new("events", 0);
+        task0.update(100);
+        task0
+            .discovered_state("events-new", 0)
+            .store(7, std::sync::atomic::Ordering::Relaxed);
+        let task1 = KafkaSourceStateRecorder::new("events", 1);
+        task1.update(200);
+        let snapshots = vec![task0.snapshot(), task1.snapshot()];
+
+        // the discovered partition is assigned to the other task after rescale
+        let restored = KafkaSourceStateRecorder::new("events", 1);
+        for snapshot in &snapshots {
+            restored.update_from_snapshot(snapshot.as_str()).unwrap();
+        }
+        assert_eq!(restored.get(), Some(200));
+        assert!(restored.snapshot().contains("events-new"));
+        let offset_state = restored.discovered_state("events-new", 0);
+        assert_eq!(offset_state.load(std::sync::atomic::Ordering::Relaxed), 7);
+        assert!(restored.restored.lock().unwrap().is_empty());
+    }
+}
@@ -1,11 +1,23 @@
+use std::sync::atomic::AtomicI64;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use futures::StreamExt;
-use rdkafka::consumer::{Consumer, DefaultConsumerContext, StreamConsumer};
-use rdkafka::{ClientConfig, Message, Offset, TopicPartitionList};
+use rdkafka::consumer::{Consumer, ConsumerContext, StreamConsumer};
+use rdkafka::error::KafkaError;
+use rdkafka::statistics::Statistics;
+use rdkafka::{ClientConfig, ClientContext, Message, Offset, TopicPartitionList};
 use rlink::channel::sender::ChannelSender;
 use rlink::core::runtime::JobId;
+use rlink::functions::watermark::is_split_paused;
+use rlink::metrics::Tag;
 use rlink::utils;
+use rlink::utils::hash::hash_code;
 
+use crate::metrics::ConsumerMetrics;
+use crate::security::redact_client_config;
 use crate::source::deserializer::KafkaRecordDeserializer;
+use crate::source::rate_limit::RateLimiter;
 use crate::source::{empty_record, ConsumerRecord};
 
 #[derive(Debug, Clone)]
@@ -16,23 +28,48 @@ pub(crate) struct ConsumerRange {
     pub(crate) end_offset: Option<i64>,
 }
 
-pub(crate) async fn create_kafka_consumer(
-    job_id: JobId,
-    task_number: u16,
-    client_config: ClientConfig,
-    consumer_ranges: ConsumerRange,
-    handover: ChannelSender<ConsumerRecord>,
-    deserializer: Box<dyn KafkaRecordDeserializer>,
-) {
+impl ConsumerRange {
+    /// The `position`, the offset of the next message to fetch, is after the end offset. The
+    /// last offsets of the range may have no message, e.g. the transaction markers or the
+    /// compacted messages, so the end is checked by the position on the partition EOF
+    fn is_end_position(&self, position: i64) -> bool {
+        match self.end_offset {
+            Some(end_offset) => position > end_offset,
+            None => false,
+        }
+    }
+}
+
+/// The interval of the librdkafka statistics which the consumer lag is collected from,
+/// applied when `statistics.interval.ms` is not configured
+const STATISTICS_INTERVAL_MS: &str = "10000";
+
+/// The interval to check whether the paused partition is resumed by the watermark alignment
+const ALIGNMENT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Report the consumer lag of the assigned partition from the librdkafka statistics
+pub(crate) struct KafkaConsumerContext {
+    topic: String,
+    partition: i32,
+    metrics: ConsumerMetrics,
+}
+
+impl ClientContext for KafkaConsumerContext {
+    fn stats(&self, statistics: Statistics) {
+        let partition = statistics
+            .topics
+            .get(self.topic.as_str())
+            .and_then(|topic| topic.partitions.get(&self.partition));
+        if let Some(partition) = partition {
+            self.metrics.lag(partition.consumer_lag);
+        }
+    }
+}
+
+impl ConsumerContext for KafkaConsumerContext {}
+
+pub(crate) async fn create_kafka_consumer(mut kafka_consumer: KafkaConsumerThread) {
     tokio::spawn(async move {
-        let mut kafka_consumer = KafkaConsumerThread::new(
-            job_id,
-            task_number,
-            client_config,
-            consumer_ranges,
-            handover,
-            deserializer,
-        );
         match kafka_consumer.run().await {
             Ok(()) => {}
             Err(e) => {
@@ -49,9 +86,13 @@ pub(crate) struct KafkaConsumerThread {
     client_config: ClientConfig,
     consumer_ranges: ConsumerRange,
     with_end_consumer_ranges: bool,
+    /// the source split of the records, the watermarks are generated per partition
+    split: u32,
 
     sender: ChannelSender<ConsumerRecord>,
     deserializer: Box<dyn KafkaRecordDeserializer>,
+    offset_state: Option<Arc<AtomicI64>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl KafkaConsumerThread {
@@ -64,17 +105,34 @@ impl KafkaConsumerThread {
         deserializer: Box<dyn KafkaRecordDeserializer>,
     ) -> Self {
         let with_end_consumer_ranges = consumer_ranges.end_offset.is_some();
+        let split_key = format!("{}:{}", consumer_ranges.topic, consumer_ranges.partition);
+        let split = hash_code(split_key.as_bytes()).unwrap();
         KafkaConsumerThread {
             job_id,
             task_number,
             client_config,
             consumer_ranges,
             with_end_consumer_ranges,
+            split,
             sender,
             deserializer,
+            offset_state: None,
+            rate_limiter: None,
         }
     }
 
+    /// Record the offsets to the state of a discovered partition instead of the task's own
+    pub fn offset_state(mut self, offset_state: Arc<AtomicI64>) -> Self {
+        self.offset_state = Some(offset_state);
+        self
+    }
+
+    /// Share the limiter with all consumers of the subtask
+    pub fn rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     fn end_check(&self, topic: &str, partition: i32, offset: i64) -> bool {
         if !self.with_end_consumer_ranges {
             return false;
@@ -90,7 +148,42 @@ impl KafkaConsumerThread {
         false
     }
 
+    /// the `offset` is the last one of the range, no more message need to consume
+    fn last_offset_check(&self, offset: i64) -> bool {
+        self.with_end_consumer_ranges && self.consumer_ranges.end_offset.unwrap() <= offset
+    }
+
+    /// the range has no message to consume, eg: the bounded partition is empty
+    fn empty_range_check(&self) -> bool {
+        if !self.with_end_consumer_ranges {
+            return false;
+        }
+
+        let begin_offset = self.consumer_ranges.begin_offset;
+        let end_offset = self.consumer_ranges.end_offset.unwrap();
+        end_offset < 0
+            || begin_offset == Offset::End.to_raw().unwrap()
+            || (begin_offset >= 0 && begin_offset > end_offset)
+    }
+
+    async fn send_end(&self) {
+        self.sender
+            .send(ConsumerRecord::new(empty_record(), 0))
+            .await
+            .expect("kafka consumer handover `Disconnected`");
+        info!(
+            "kafka end offset reached. job_id: {}, task_num: {}",
+            *self.job_id, self.task_number
+        );
+    }
+
     pub async fn run(&mut self) -> anyhow::Result<()> {
+        if self.empty_range_check() {
+            info!("empty kafka consumer range: {:?}", self.consumer_ranges);
+            self.send_end().await;
+            return Ok(());
+        }
+
         let mut assignment = TopicPartitionList::new();
         assignment
             .add_partition_offset(
@@ -105,16 +198,43 @@ impl KafkaConsumerThread {
             .get("group.id")
             .ok_or(anyhow!("`group.id` not found in kafka consumer config"))?;
 
-        let consumer: StreamConsumer<DefaultConsumerContext> = self.client_config.create()?;
+        let tags = vec![
+            Tag::new("job_id", *self.job_id),
+            Tag::new("task_number", self.task_number),
+        ];
+        let metrics = ConsumerMetrics::new(
+            tags,
+            self.consumer_ranges.topic.as_str(),
+            self.consumer_ranges.partition,
+        );
+        let context = KafkaConsumerContext {
+            topic: self.consumer_ranges.topic.clone(),
+            partition: self.consumer_ranges.partition,
+            metrics: metrics.clone(),
+        };
+
+        let mut client_config = self.client_config.clone();
+        if client_config.get("statistics.interval.ms").is_none() {
+            client_config.set("statistics.interval.ms", STATISTICS_INTERVAL_MS);
+        }
+        if self.with_end_consumer_ranges {
+            client_config.set("enable.partition.eof", "true");
+        }
+
+        let consumer: StreamConsumer<KafkaConsumerContext> =
+            client_config.create_with_context(context)?;
         consumer.assign(&assignment)?;
 
         info!(
             "create consumer success. config: {:?}, assign: {:?}, offset range: {:?},job_id: {}, task_num: {}",
-            self.client_config, assignment, self.consumer_ranges, *self.job_id, self.task_number
+            redact_client_config(&self.client_config), assignment, self.consumer_ranges, *self.job_id, self.task_number
         );
 
         let mut message_stream = consumer.stream();
+        let mut fetch_time = Instant::now();
         while let Some(message) = message_stream.next().await {
+            metrics.fetch_latency(fetch_time.elapsed());
+
             match message {
                 Ok(borrowed_message) => {
                     let topic = borrowed_message.topic();
@@ -125,35 +245,93 @@ impl KafkaConsumerThread {
                     let payload = borrowed_message.payload().unwrap_or(&utils::EMPTY_SLICE);
 
                     if self.end_check(topic, partition, offset) {
-                        self.sender
-                            .send(ConsumerRecord::new(empty_record(), 0))
-                            .await
-                            .expect("kafka consumer handover `Disconnected`");
-                        info!(
-                            "kafka end offset reached. job_id: {}, task_num: {}",
-                            *self.job_id, self.task_number
-                        );
+                        self.send_end().await;
                         break;
                     }
 
+                    // the partition runs ahead of its watermark alignment group
+                    while is_split_paused(self.split) {
+                        tokio::time::sleep(ALIGNMENT_CHECK_INTERVAL).await;
+                    }
+
+                    if let Some(rate_limiter) = &self.rate_limiter {
+                        let bytes = (key.len() + payload.len()) as u64;
+                        rate_limiter.acquire(1, bytes).await;
+                    }
+
+                    if let Err(e) = self.deserializer.prepare(payload).await {
+                        warn!(
+                            "prepare the deserializer error. topic: {}, partition: {}, offset: {}, error: {}",
+                            topic, partition, offset, e
+                        );
+                    }
                     let records = self
                         .deserializer
                         .deserialize(timestamp, key, payload, topic, partition, offset);
 
-                    for record in records {
+                    for mut record in records {
+                        record.set_split(self.split);
                         self.sender
-                            .send(ConsumerRecord::new(record, offset))
+                            .send(ConsumerRecord::with_state(
+                                record,
+                                offset,
+                                self.offset_state.clone(),
+                            ))
                             .await
                             .expect("kafka consumer handover `Disconnected`");
                     }
+
+                    if self.last_offset_check(offset) {
+                        self.send_end().await;
+                        break;
+                    }
+                }
+                Err(KafkaError::PartitionEOF(partition)) => {
+                    let position = consumer
+                        .position()?
+                        .find_partition(self.consumer_ranges.topic.as_str(), partition)
+                        .map(|x| x.offset());
+                    if let Some(Offset::Offset(position)) = position {
+                        if self.consumer_ranges.is_end_position(position) {
+                            self.send_end().await;
+                            break;
+                        }
+                    }
                 }
                 Err(e) => warn!(
                     "Kafka consume error. job_id: {}, task_num: {}, error: {}",
                     *self.job_id, self.task_number, e
                 ),
             }
+
+            fetch_time = Instant::now();
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::source::consumer::ConsumerRange;
+
+    #[test]
+    pub fn end_position_test() {
+        // the offsets 8 and 9 are the transaction markers, the last message is 7
+        let range = ConsumerRange {
+            topic: "topic".to_string(),
+            partition: 0,
+            begin_offset: 0,
+            end_offset: Some(9),
+        };
+        // the partition EOF before the end offset, e.g. by the last stable offset
+        assert!(!range.is_end_position(8));
+        assert!(range.is_end_position(10));
+
+        let unbounded = ConsumerRange {
+            end_offset: None,
+            ..range
+        };
+        assert!(!unbounded.is_end_position(10));
+    }
+}
@@ -4,7 +4,14 @@ use rlink::core::element::{FnSchema, Record};
 
 use crate::build_kafka_record;
 
+#[async_trait]
 pub trait KafkaRecordDeserializer: Sync + Send {
+    /// Called before `deserialize` for each message, e.g. fetch the remote metadata which
+    /// the payload depends on, so `deserialize` never blocks the runtime
+    async fn prepare(&mut self, _payload: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     fn deserialize(
         &mut self,
         timestamp: i64,
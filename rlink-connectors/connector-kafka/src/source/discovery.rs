@@ -0,0 +1,253 @@
+use std::collections::HashSet;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rdkafka::consumer::{BaseConsumer, Consumer, DefaultConsumerContext};
+use rdkafka::{ClientConfig, Offset};
+use regex::Regex;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::TaskId;
+use rlink::utils::hash::hash_code;
+
+use crate::source::checkpoint::KafkaSourceStateRecorder;
+use crate::source::consumer::{create_kafka_consumer, ConsumerRange, KafkaConsumerThread};
+use crate::source::deserializer::KafkaRecordDeserializerBuilder;
+use crate::source::rate_limit::RateLimiter;
+use crate::source::ConsumerRecord;
+
+/// Subscribe all topics whose name matches the regex, eg: `events\..*`
+#[derive(Clone, Debug)]
+pub struct TopicPattern {
+    pattern: Regex,
+    discovery_interval: Duration,
+}
+
+impl TopicPattern {
+    pub fn new(pattern: &str) -> anyhow::Result<Self> {
+        let pattern = Regex::new(pattern)?;
+        Ok(TopicPattern {
+            pattern,
+            discovery_interval: Duration::from_secs(60),
+        })
+    }
+
+    /// How often the pattern is re-evaluated to pick up the newly created topics, default 60s
+    pub fn discovery_interval(mut self, interval: Duration) -> Self {
+        self.discovery_interval = interval;
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    pub fn is_match(&self, topic: &str) -> bool {
+        self.pattern.is_match(topic)
+    }
+}
+
+/// Fetch the partitions of all topics whose name matches the `pattern`, sorted by topic name
+pub(crate) fn fetch_matched_partitions(
+    consumer: &BaseConsumer<DefaultConsumerContext>,
+    pattern: &TopicPattern,
+) -> anyhow::Result<Vec<(String, Vec<i32>)>> {
+    let timeout = Duration::from_secs(3);
+    let metadata = consumer
+        .fetch_metadata(None, timeout)
+        .map_err(|e| anyhow!("Failed to fetch metadata. {}", e))?;
+
+    let mut topics: Vec<(String, Vec<i32>)> = metadata
+        .topics()
+        .iter()
+        .filter(|topic| pattern.is_match(topic.name()))
+        .map(|topic| {
+            let partitions = topic.partitions().iter().map(|p| p.id()).collect();
+            (topic.name().to_string(), partitions)
+        })
+        .collect();
+    topics.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(topics)
+}
+
+/// Encode the partition count of each topic as `topic:count,topic:count`, kafka topic names
+/// never contain `:` and `,`. The partitions of a topic are numbered from 0 and never removed,
+/// so the matched partitions are recovered from the counts without listing each of them
+pub(crate) fn encode_partitions(topic_partitions: &[(String, Vec<i32>)]) -> String {
+    let partition_counts: Vec<String> = topic_partitions
+        .iter()
+        .map(|(topic, partitions)| {
+            let count = partitions.iter().max().map(|x| x + 1).unwrap_or(0);
+            format!("{}:{}", topic, count)
+        })
+        .collect();
+    partition_counts.join(",")
+}
+
+pub(crate) fn decode_partitions(value: &str) -> anyhow::Result<HashSet<(String, i32)>> {
+    let mut partitions = HashSet::new();
+    for partition_count in value.split(",").filter(|x| !x.is_empty()) {
+        let (topic, count) = partition_count
+            .rsplit_once(":")
+            .ok_or(anyhow!("illegal topic partition count {}", partition_count))?;
+        for partition in 0..count.parse::<i32>()? {
+            partitions.insert((topic.to_string(), partition));
+        }
+    }
+    Ok(partitions)
+}
+
+/// Periodically re-evaluate the topic pattern and consume the topic partitions created after
+/// the job started. Each new partition is owned by exactly one subtask, chosen by the hash of
+/// the topic partition, so the assignment is stable across all subtasks without coordination.
+///
+/// The new partitions are consumed from the earliest offset, or from the offset restored from
+/// the checkpoint. Their offsets are recorded in the checkpoint of the owner subtask.
+pub(crate) struct TopicDiscovery {
+    task_id: TaskId,
+    client_config: ClientConfig,
+    pattern: TopicPattern,
+    known_partitions: HashSet<(String, i32)>,
+
+    state_recorder: KafkaSourceStateRecorder,
+    deserializer_builder: Arc<dyn KafkaRecordDeserializerBuilder>,
+    handover: ChannelSender<ConsumerRecord>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl TopicDiscovery {
+    pub fn new(
+        task_id: TaskId,
+        client_config: ClientConfig,
+        pattern: TopicPattern,
+        known_partitions: HashSet<(String, i32)>,
+        state_recorder: KafkaSourceStateRecorder,
+        deserializer_builder: Arc<dyn KafkaRecordDeserializerBuilder>,
+        handover: ChannelSender<ConsumerRecord>,
+    ) -> Self {
+        TopicDiscovery {
+            task_id,
+            client_config,
+            pattern,
+            known_partitions,
+            state_recorder,
+            deserializer_builder,
+            handover,
+            rate_limiter: None,
+        }
+    }
+
+    pub fn rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    fn is_owner(&self, topic: &str, partition: i32) -> bool {
+        let key = format!("{}:{}", topic, partition);
+        let hash = hash_code(key.as_bytes()).unwrap();
+        hash % self.task_id.num_tasks() as u32 == self.task_id.task_number() as u32
+    }
+
+    async fn discover(
+        &mut self,
+        consumer: &BaseConsumer<DefaultConsumerContext>,
+    ) -> anyhow::Result<()> {
+        let matched_partitions = fetch_matched_partitions(consumer, &self.pattern)?;
+        for (topic, partitions) in matched_partitions {
+            for partition in partitions {
+                if !self.known_partitions.insert((topic.clone(), partition)) {
+                    continue;
+                }
+                if !self.is_owner(topic.as_str(), partition) {
+                    continue;
+                }
+
+                let offset_state = self
+                    .state_recorder
+                    .discovered_state(topic.as_str(), partition);
+                let begin_offset = match offset_state.load(Ordering::Relaxed) {
+                    i64::MIN => Offset::Beginning.to_raw().unwrap(),
+                    offset => offset,
+                };
+
+                let consumer_range = ConsumerRange {
+                    topic: topic.clone(),
+                    partition,
+                    begin_offset,
+                    end_offset: None,
+                };
+                info!(
+                    "discover kafka partition, pattern: {}, range: {:?}, task_num: {}",
+                    self.pattern.as_str(),
+                    consumer_range,
+                    self.task_id.task_number()
+                );
+
+                let kafka_consumer = KafkaConsumerThread::new(
+                    self.task_id.job_id(),
+                    self.task_id.task_number(),
+                    self.client_config.clone(),
+                    consumer_range,
+                    self.handover.clone(),
+                    self.deserializer_builder.build(),
+                )
+                .offset_state(offset_state)
+                .rate_limiter(self.rate_limiter.clone());
+                create_kafka_consumer(kafka_consumer).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn run(mut self) {
+        let consumer: BaseConsumer<DefaultConsumerContext> = match self.client_config.create() {
+            Ok(consumer) => consumer,
+            Err(e) => {
+                error!("create kafka topic discovery consumer error. {}", e);
+                return;
+            }
+        };
+
+        while !self.handover.is_closed() {
+            if let Err(e) = self.discover(&consumer).await {
+                warn!(
+                    "kafka topic discovery error. pattern: {}, error: {}",
+                    self.pattern.as_str(),
+                    e
+                );
+            }
+            tokio::time::sleep(self.pattern.discovery_interval).await;
+        }
+
+        info!(
+            "kafka topic discovery finished. pattern: {}",
+            self.pattern.as_str()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::discovery::{decode_partitions, encode_partitions, TopicPattern};
+
+    #[test]
+    pub fn pattern_partitions_test() {
+        let partitions = vec![
+            ("events.click".to_string(), vec![1, 0]),
+            ("events.view".to_string(), vec![0]),
+        ];
+        let value = encode_partitions(partitions.as_slice());
+        assert_eq!(value, "events.click:2,events.view:1");
+
+        let decoded = decode_partitions(value.as_str()).unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert!(decoded.contains(&("events.view".to_string(), 0)));
+        assert!(decode_partitions("").unwrap().is_empty());
+
+        let topic_pattern = TopicPattern::new("events\\..*").unwrap();
+        assert!(topic_pattern.is_match("events.click"));
+        assert!(!topic_pattern.is_match("metrics.cpu"));
+    }
+}
@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use rdkafka::consumer::{BaseConsumer, Consumer, DefaultConsumerContext};
@@ -14,14 +15,25 @@ use rlink::core::properties::Properties;
 use rlink::core::runtime::TaskId;
 use rlink::metrics::Tag;
 
+use crate::security::redact_client_config;
+use crate::source::boundary::OffsetBoundary;
 use crate::source::checkpoint::KafkaCheckpointFunction;
-use crate::source::consumer::{create_kafka_consumer, ConsumerRange};
+use crate::source::consumer::{create_kafka_consumer, ConsumerRange, KafkaConsumerThread};
 use crate::source::deserializer::KafkaRecordDeserializerBuilder;
+use crate::source::discovery::{
+    decode_partitions, encode_partitions, fetch_matched_partitions, TopicDiscovery, TopicPattern,
+};
 use crate::source::offset_range::{OffsetRange, PartitionOffset};
+use crate::source::rate_limit::RateLimit;
+use crate::source::startup_mode::StartupMode;
 use crate::source::stream::KafkaRecordStream;
 
 /// Depending on whether the task has `InputSplit`, and whether the client needs to be created
 const CREATE_KAFKA_CONNECTION: &'static str = "create_kafka_connection";
+/// The partition count of each topic matched by the topic pattern when the splits are created
+const PATTERN_PARTITIONS: &'static str = "pattern_partitions";
+/// The end offset of the bounded source resolved when the splits are created
+const BOUNDED_END_OFFSET: &'static str = "bounded_end_offset";
 
 pub struct KafkaInputFormat {
     name: String,
@@ -29,17 +41,23 @@ pub struct KafkaInputFormat {
 
     client_config: ClientConfig,
     topics: Vec<String>,
+    topic_pattern: Option<TopicPattern>,
 
     task_id: TaskId,
     task_topic: String,
     task_partition: i32,
+    task_end_offset: Option<i64>,
+    pattern_partitions: String,
 
     buffer_size: usize,
     offset_range: OffsetRange,
+    startup_mode: StartupMode,
+    bounded: Option<OffsetBoundary>,
+    rate_limit: Option<RateLimit>,
 
     tags: Vec<Tag>,
 
-    deserializer_builder: Box<dyn KafkaRecordDeserializerBuilder>,
+    deserializer_builder: Arc<dyn KafkaRecordDeserializerBuilder>,
     schema: FnSchema,
 
     checkpoint: Option<KafkaCheckpointFunction>,
@@ -49,8 +67,11 @@ impl KafkaInputFormat {
     pub fn new(
         client_config: ClientConfig,
         topics: Vec<String>,
+        topic_pattern: Option<TopicPattern>,
         buffer_size: usize,
         offset_range: OffsetRange,
+        startup_mode: StartupMode,
+        bounded: Option<OffsetBoundary>,
         deserializer_builder: Box<dyn KafkaRecordDeserializerBuilder>,
         parallelism: u16,
         fn_name: String,
@@ -61,26 +82,42 @@ impl KafkaInputFormat {
             parallelism,
             client_config,
             topics,
+            topic_pattern,
             task_id: Default::default(),
             task_topic: "".to_string(),
             task_partition: 0,
+            task_end_offset: None,
+            pattern_partitions: "".to_string(),
             buffer_size,
             offset_range,
+            startup_mode,
+            bounded,
+            rate_limit: None,
             checkpoint: None,
-            deserializer_builder,
+            deserializer_builder: Arc::from(deserializer_builder),
             schema,
             tags: vec![],
         }
     }
 
-    fn consumer_ranges(&mut self, topic: String, partition: i32) -> KafkaResult<ConsumerRange> {
+    pub fn rate_limit(mut self, rate_limit: Option<RateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    fn consumer_ranges(&mut self, topic: String, partition: i32) -> anyhow::Result<ConsumerRange> {
         let (begin_partition, end_partition) = match &self.offset_range {
             OffsetRange::None => {
                 let state = self.checkpoint.as_mut().unwrap().as_state_mut();
-                let begin_offset = state
-                    .get()
-                    .map(|offset| PartitionOffset { partition, offset });
-                (begin_offset, None)
+                let begin_offset = match state.get() {
+                    Some(offset) => offset,
+                    None => self.startup_mode.begin_offset(
+                        &self.client_config,
+                        topic.as_str(),
+                        partition,
+                    )?,
+                };
+                (Some(PartitionOffset::new(partition, begin_offset)), None)
             }
             OffsetRange::Direct {
                 begin_offset,
@@ -152,13 +189,29 @@ impl KafkaInputFormat {
             }
         };
 
+        let end_offset = match &self.bounded {
+            Some(_) => {
+                let end_offset = self.task_end_offset.ok_or(anyhow!(
+                    "bounded end offset of topic({}) partition({}) not found",
+                    topic,
+                    partition
+                ))?;
+                info!(
+                    "bounded kafka source, topic: {}, partition: {}, end offset: {}",
+                    topic, partition, end_offset
+                );
+                Some(end_offset)
+            }
+            None => end_partition.map(|x| x.offset),
+        };
+
         Ok(ConsumerRange {
             topic,
             partition,
             begin_offset: begin_partition
                 .map(|x| x.offset)
                 .unwrap_or(Offset::End.to_raw().unwrap()),
-            end_offset: end_partition.map(|x| x.offset),
+            end_offset,
         })
     }
 }
@@ -177,6 +230,10 @@ impl InputFormat for KafkaInputFormat {
         self.task_id = context.task_id.clone();
         self.task_topic = input_split.properties().get_string("topic").unwrap();
         self.task_partition = input_split.properties().get_i32("partition").unwrap();
+        if let Ok(pattern_partitions) = input_split.properties().get_string(PATTERN_PARTITIONS) {
+            self.pattern_partitions = pattern_partitions;
+        }
+        self.task_end_offset = input_split.properties().get_i64(BOUNDED_END_OFFSET).ok();
 
         let kafka_checkpoint = KafkaCheckpointFunction::new(
             context.application_id.clone(),
@@ -184,6 +241,7 @@ impl InputFormat for KafkaInputFormat {
             self.task_topic.as_str(),
             self.task_partition,
         );
+        kafka_checkpoint.restore_from(context)?;
         self.checkpoint = Some(kafka_checkpoint);
 
         self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
@@ -205,17 +263,42 @@ impl InputFormat for KafkaInputFormat {
         let consumer_ranges = self
             .consumer_ranges(self.task_topic.to_string(), self.task_partition)
             .unwrap();
-        create_kafka_consumer(
+        let rate_limiter = self
+            .rate_limit
+            .as_ref()
+            .map(|rate_limit| Arc::new(rate_limit.create_limiter(self.task_id.num_tasks())));
+        let kafka_consumer = KafkaConsumerThread::new(
             self.task_id.job_id(),
             self.task_id.task_number(),
             client_config,
             consumer_ranges,
-            sender,
+            sender.clone(),
             self.deserializer_builder.build(),
         )
-        .await;
+        .rate_limiter(rate_limiter.clone());
+        create_kafka_consumer(kafka_consumer).await;
 
         let state_recorder = self.checkpoint.as_mut().unwrap().as_state_mut().clone();
+
+        if let Some(topic_pattern) = &self.topic_pattern {
+            if self.bounded.is_some() {
+                warn!("topic discovery is disabled in the bounded kafka source");
+            } else {
+                let known_partitions = decode_partitions(self.pattern_partitions.as_str()).unwrap();
+                let discovery = TopicDiscovery::new(
+                    self.task_id.clone(),
+                    self.client_config.clone(),
+                    topic_pattern.clone(),
+                    known_partitions,
+                    state_recorder.clone(),
+                    self.deserializer_builder.clone(),
+                    sender,
+                )
+                .rate_limiter(rate_limiter);
+                tokio::spawn(discovery.run());
+            }
+        }
+
         Box::pin(KafkaRecordStream::new(receiver, state_recorder))
     }
 
@@ -223,6 +306,11 @@ impl InputFormat for KafkaInputFormat {
         Ok(())
     }
 
+    /// the partitions are read to the end offsets of the `OffsetBoundary`
+    fn bounded(&self) -> bool {
+        self.bounded.is_some()
+    }
+
     fn schema(&self, _input_schema: FnSchema) -> FnSchema {
         self.schema.clone()
     }
@@ -262,29 +350,66 @@ impl InputSplitSource for KafkaInputFormat {
     fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
         let timeout = Duration::from_secs(3);
 
-        info!("kafka config {:?}", self.client_config);
+        info!(
+            "kafka config {:?}",
+            redact_client_config(&self.client_config)
+        );
 
         let consumer: BaseConsumer = self
             .client_config
             .create()
             .map_err(|e| anyhow!("Consumer creation failed. {}", e))?;
 
+        let topic_partitions = match &self.topic_pattern {
+            Some(topic_pattern) => {
+                let matched_partitions = fetch_matched_partitions(&consumer, topic_pattern)?;
+                if matched_partitions.is_empty() {
+                    return Err(rlink::core::Error::from(format!(
+                        "no topic matches the pattern `{}`",
+                        topic_pattern.as_str()
+                    )));
+                }
+                matched_partitions
+            }
+            None => {
+                let mut topic_partitions = Vec::new();
+                for topic in &self.topics {
+                    let metadata = consumer
+                        .fetch_metadata(Some(topic.as_str()), timeout)
+                        .map_err(|e| anyhow!("Failed to fetch metadata. {}", e))?;
+                    let metadata_topic = metadata
+                        .topics()
+                        .get(0)
+                        .ok_or(anyhow!("Topic({}) not found", topic))?;
+                    let partitions = metadata_topic.partitions().iter().map(|p| p.id()).collect();
+                    topic_partitions.push((topic.clone(), partitions));
+                }
+                topic_partitions
+            }
+        };
+
+        let pattern_partitions = if self.topic_pattern.is_some() {
+            Some(encode_partitions(topic_partitions.as_slice()))
+        } else {
+            None
+        };
+
         let mut input_splits = Vec::new();
         let mut index = 0;
-        for topic in &self.topics {
-            let metadata = consumer
-                .fetch_metadata(Some(topic.as_str()), timeout)
-                .map_err(|e| anyhow!("Failed to fetch metadata. {}", e))?;
-            let metadata_topic = metadata
-                .topics()
-                .get(0)
-                .ok_or(anyhow!("Topic({}) not found", topic))?;
-
-            for partition in metadata_topic.partitions() {
+        for (topic, partitions) in &topic_partitions {
+            for partition in partitions {
                 let mut properties = Properties::new();
                 properties.set_str("topic", topic.as_str());
-                properties.set_i32("partition", partition.id());
+                properties.set_i32("partition", *partition);
                 properties.set_bool(CREATE_KAFKA_CONNECTION, true);
+                if let Some(pattern_partitions) = &pattern_partitions {
+                    properties.set_str(PATTERN_PARTITIONS, pattern_partitions.as_str());
+                }
+                // the boundary is resolved once here, so all tasks stop at the same offsets
+                if let Some(boundary) = &self.bounded {
+                    let end_offset = boundary.end_offset(&consumer, topic.as_str(), *partition)?;
+                    properties.set_i64(BOUNDED_END_OFFSET, end_offset);
+                }
 
                 let input_split = InputSplit::new(index, properties);
                 index += 1;
@@ -1,9 +1,21 @@
+use std::sync::atomic::AtomicI64;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rdkafka::consumer::{BaseConsumer, Consumer, DefaultConsumerContext};
+use rdkafka::error::KafkaResult;
+use rdkafka::{Offset, TopicPartitionList};
+
+pub mod boundary;
 pub mod builder;
 pub mod checkpoint;
 pub mod consumer;
 pub mod deserializer;
+pub mod discovery;
 pub mod input_format;
 pub mod offset_range;
+pub mod rate_limit;
+pub mod startup_mode;
 pub mod stream;
 
 #[inline]
@@ -20,10 +32,50 @@ pub(crate) fn is_empty_record(record: &mut rlink::core::element::Record) -> bool
 pub(crate) struct ConsumerRecord {
     record: rlink::core::element::Record,
     offset: i64,
+    /// the offset state of a discovered partition, `None` for the task's own partition
+    offset_state: Option<Arc<AtomicI64>>,
 }
 
 impl ConsumerRecord {
     pub fn new(record: rlink::core::element::Record, offset: i64) -> Self {
-        ConsumerRecord { record, offset }
+        ConsumerRecord {
+            record,
+            offset,
+            offset_state: None,
+        }
+    }
+
+    pub fn with_state(
+        record: rlink::core::element::Record,
+        offset: i64,
+        offset_state: Option<Arc<AtomicI64>>,
+    ) -> Self {
+        ConsumerRecord {
+            record,
+            offset,
+            offset_state,
+        }
     }
 }
+
+/// Look up the earliest offset whose timestamp is greater than or equal to the `timestamp`,
+/// return `None` if there is no such message in the partition
+pub(crate) fn offset_for_time(
+    consumer: &BaseConsumer<DefaultConsumerContext>,
+    topic: &str,
+    partition: i32,
+    timestamp: u64,
+) -> KafkaResult<Option<i64>> {
+    let timeout = Duration::from_secs(3);
+    let mut partition_list = TopicPartitionList::with_capacity(1);
+    partition_list.set_partition_offset(topic, partition, Offset::Offset(timestamp as i64))?;
+
+    let tpl = consumer.offsets_for_times(partition_list, timeout)?;
+    let offset = tpl
+        .find_partition(topic, partition)
+        .and_then(|elem| match elem.offset() {
+            Offset::Offset(offset) => Some(offset),
+            _ => None,
+        });
+    Ok(offset)
+}
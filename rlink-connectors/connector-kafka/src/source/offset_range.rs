@@ -26,6 +26,30 @@ impl PartitionOffset {
     }
 }
 
+/// Encode the partition offsets as `partition:offset,partition:offset`, sorted by partition
+pub(crate) fn encode_partition_offsets(mut partition_offsets: Vec<PartitionOffset>) -> String {
+    partition_offsets.sort_by_key(|x| x.partition);
+    let partition_offsets: Vec<String> = partition_offsets
+        .into_iter()
+        .map(|x| format!("{}:{}", x.partition, x.offset))
+        .collect();
+    partition_offsets.join(",")
+}
+
+pub(crate) fn decode_partition_offsets(value: &str) -> anyhow::Result<Vec<PartitionOffset>> {
+    let mut partition_offsets = Vec::new();
+    for partition_offset in value.split(",").filter(|x| !x.is_empty()) {
+        let (partition, offset) = partition_offset
+            .split_once(":")
+            .ok_or(anyhow!("illegal partition offset {}", partition_offset))?;
+        partition_offsets.push(PartitionOffset::new(
+            i32::from_str(partition)?,
+            i64::from_str(offset)?,
+        ));
+    }
+    Ok(partition_offsets)
+}
+
 #[derive(Clone, Debug)]
 pub enum OffsetRange {
     None,
@@ -0,0 +1,214 @@
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rlink::core::properties::Properties;
+
+use crate::{RATE_LIMIT_BYTES, RATE_LIMIT_RECORDS, RATE_LIMIT_SCOPE};
+
+/// Whom the rate applies to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RateLimitScope {
+    /// each source subtask is limited to the rate
+    Subtask,
+    /// all subtasks together are limited to the rate, it's evenly split across the subtasks
+    Global,
+}
+
+/// Throttle the kafka source to protect the downstream, eg: backfill over a large
+/// retention window. Records and bytes are counted by the kafka messages, the rates are
+/// enforced by token buckets with a burst of one second.
+#[derive(Clone, Debug)]
+pub struct RateLimit {
+    scope: RateLimitScope,
+    records_per_second: Option<u64>,
+    bytes_per_second: Option<u64>,
+}
+
+impl RateLimit {
+    pub fn per_subtask() -> Self {
+        RateLimit {
+            scope: RateLimitScope::Subtask,
+            records_per_second: None,
+            bytes_per_second: None,
+        }
+    }
+
+    pub fn global() -> Self {
+        RateLimit {
+            scope: RateLimitScope::Global,
+            records_per_second: None,
+            bytes_per_second: None,
+        }
+    }
+
+    pub fn records_per_second(mut self, records_per_second: u64) -> Self {
+        self.records_per_second = Some(records_per_second);
+        self
+    }
+
+    pub fn bytes_per_second(mut self, bytes_per_second: u64) -> Self {
+        self.bytes_per_second = Some(bytes_per_second);
+        self
+    }
+
+    pub fn scope(&self) -> RateLimitScope {
+        self.scope
+    }
+
+    /// Create the limiter shared by all consumers of the subtask
+    pub(crate) fn create_limiter(&self, num_tasks: u16) -> RateLimiter {
+        let subtask_rate = |rate: u64| match self.scope {
+            RateLimitScope::Subtask => rate,
+            RateLimitScope::Global => (rate / num_tasks.max(1) as u64).max(1),
+        };
+
+        RateLimiter {
+            records: self
+                .records_per_second
+                .map(|rate| Mutex::new(TokenBucket::new(subtask_rate(rate)))),
+            bytes: self
+                .bytes_per_second
+                .map(|rate| Mutex::new(TokenBucket::new(subtask_rate(rate)))),
+        }
+    }
+}
+
+impl Into<Properties> for RateLimit {
+    fn into(self) -> Properties {
+        let mut properties = Properties::new();
+        match self.scope {
+            RateLimitScope::Subtask => properties.set_str(RATE_LIMIT_SCOPE, "subtask"),
+            RateLimitScope::Global => properties.set_str(RATE_LIMIT_SCOPE, "global"),
+        }
+        if let Some(records_per_second) = self.records_per_second {
+            properties.set_u64(RATE_LIMIT_RECORDS, records_per_second);
+        }
+        if let Some(bytes_per_second) = self.bytes_per_second {
+            properties.set_u64(RATE_LIMIT_BYTES, bytes_per_second);
+        }
+
+        properties
+    }
+}
+
+impl TryFrom<Properties> for RateLimit {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let mut rate_limit = match properties.get_string(RATE_LIMIT_SCOPE) {
+            Ok(scope) => match scope.as_str() {
+                "subtask" => RateLimit::per_subtask(),
+                "global" => RateLimit::global(),
+                _ => return Err(anyhow!("unknown rate limit scope {}", scope)),
+            },
+            Err(_) => RateLimit::per_subtask(),
+        };
+
+        if let Ok(records_per_second) = properties.get_u64(RATE_LIMIT_RECORDS) {
+            rate_limit = rate_limit.records_per_second(records_per_second);
+        }
+        if let Ok(bytes_per_second) = properties.get_u64(RATE_LIMIT_BYTES) {
+            rate_limit = rate_limit.bytes_per_second(bytes_per_second);
+        }
+
+        if rate_limit.records_per_second.is_none() && rate_limit.bytes_per_second.is_none() {
+            return Err(anyhow!(
+                "either `{}` or `{}` is required by the rate limit",
+                RATE_LIMIT_RECORDS,
+                RATE_LIMIT_BYTES
+            ));
+        }
+
+        Ok(rate_limit)
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64) -> Self {
+        let rate = rate as f64;
+        TokenBucket {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take `n` tokens, the bucket may go into debt for a message larger than the burst.
+    /// Return how long the caller should wait until the debt is paid off.
+    fn acquire(&mut self, n: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+
+        self.tokens -= n as f64;
+        if self.tokens >= 0f64 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+pub(crate) struct RateLimiter {
+    records: Option<Mutex<TokenBucket>>,
+    bytes: Option<Mutex<TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn acquire_delay(&self, records: u64, bytes: u64) -> Duration {
+        let records_delay = self
+            .records
+            .as_ref()
+            .map(|bucket| bucket.lock().unwrap().acquire(records))
+            .unwrap_or_default();
+        let bytes_delay = self
+            .bytes
+            .as_ref()
+            .map(|bucket| bucket.lock().unwrap().acquire(bytes))
+            .unwrap_or_default();
+
+        records_delay.max(bytes_delay)
+    }
+
+    /// Wait until the records and bytes are permitted
+    pub async fn acquire(&self, records: u64, bytes: u64) {
+        let delay = self.acquire_delay(records, bytes);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::source::rate_limit::RateLimit;
+
+    #[test]
+    pub fn rate_limiter_test() {
+        let limiter = RateLimit::global()
+            .records_per_second(200)
+            .bytes_per_second(1000)
+            .create_limiter(2);
+
+        // the burst of a subtask is 100 records and 500 bytes
+        assert_eq!(limiter.acquire_delay(100, 100), Duration::from_secs(0));
+        let delay = limiter.acquire_delay(50, 100);
+        assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+
+        let limiter = RateLimit::per_subtask()
+            .bytes_per_second(1000)
+            .create_limiter(2);
+        let delay = limiter.acquire_delay(1, 3000);
+        assert!(delay > Duration::from_millis(1900) && delay <= Duration::from_secs(2));
+    }
+}
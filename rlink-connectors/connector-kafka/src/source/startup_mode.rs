@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use rdkafka::consumer::{BaseConsumer, DefaultConsumerContext};
+use rdkafka::{ClientConfig, Offset};
+use rlink::core::properties::Properties;
+
+use crate::source::offset_for_time;
+use crate::source::offset_range::{
+    decode_partition_offsets, encode_partition_offsets, PartitionOffset,
+};
+use crate::{STARTUP_MODE, STARTUP_OFFSET, STARTUP_TIMESTAMP};
+
+/// Where the kafka source begins to consume each partition when there is no offset
+/// restored from the checkpoint and no `OffsetRange` specified.
+#[derive(Clone, Debug, Default)]
+pub enum StartupMode {
+    /// the earliest offset of each partition
+    Earliest,
+    /// the latest offset of each partition, only new messages are consumed
+    #[default]
+    Latest,
+    /// the committed offsets of the consumer group, `auto.offset.reset` is applied
+    /// when the group has no committed offset
+    GroupOffsets,
+    /// specific offsets of each topic's partitions
+    SpecificOffsets(HashMap<String, Vec<PartitionOffset>>),
+    /// seek to the earliest message whose timestamp(millis) is greater than or equal to it
+    Timestamp(u64),
+}
+
+impl StartupMode {
+    /// Resolve the raw begin offset of the topic partition, it maybe a logical offset
+    /// such as `Offset::Beginning`, `Offset::End` or `Offset::Stored`
+    pub(crate) fn begin_offset(
+        &self,
+        client_config: &ClientConfig,
+        topic: &str,
+        partition: i32,
+    ) -> anyhow::Result<i64> {
+        let offset = match self {
+            Self::Earliest => Offset::Beginning,
+            Self::Latest => Offset::End,
+            Self::GroupOffsets => Offset::Stored,
+            Self::SpecificOffsets(offsets) => {
+                let offset = offsets
+                    .get(topic)
+                    .and_then(|partition_offsets| {
+                        partition_offsets
+                            .iter()
+                            .find(|x| x.partition == partition)
+                            .map(|x| x.offset)
+                    })
+                    .ok_or(anyhow!(
+                        "startup offset of topic({}) partition({}) not found",
+                        topic,
+                        partition
+                    ))?;
+                Offset::Offset(offset)
+            }
+            Self::Timestamp(timestamp) => {
+                let consumer: BaseConsumer<DefaultConsumerContext> = client_config.create()?;
+                match offset_for_time(&consumer, topic, partition, *timestamp)? {
+                    Some(offset) => Offset::Offset(offset),
+                    None => Offset::End,
+                }
+            }
+        };
+
+        offset
+            .to_raw()
+            .ok_or(anyhow!("unsupported startup offset {:?}", offset))
+    }
+}
+
+impl Into<Properties> for StartupMode {
+    fn into(self) -> Properties {
+        let mut properties = Properties::new();
+        match self {
+            Self::Earliest => properties.set_str(STARTUP_MODE, "earliest"),
+            Self::Latest => properties.set_str(STARTUP_MODE, "latest"),
+            Self::GroupOffsets => properties.set_str(STARTUP_MODE, "group"),
+            Self::SpecificOffsets(offsets) => {
+                properties.set_str(STARTUP_MODE, "specific");
+                for (topic, po) in offsets {
+                    properties.set_string(
+                        format!("{}.{}", STARTUP_OFFSET, topic),
+                        encode_partition_offsets(po),
+                    );
+                }
+            }
+            Self::Timestamp(timestamp) => {
+                properties.set_str(STARTUP_MODE, "timestamp");
+                properties.set_u64(STARTUP_TIMESTAMP, timestamp);
+            }
+        }
+
+        properties
+    }
+}
+
+impl TryFrom<Properties> for StartupMode {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let mode = properties.get_string(STARTUP_MODE)?;
+        match mode.as_str() {
+            "earliest" => Ok(Self::Earliest),
+            "latest" => Ok(Self::Latest),
+            "group" => Ok(Self::GroupOffsets),
+            "specific" => {
+                let mut map = HashMap::new();
+                for (topic, offset_str) in properties.to_sub_properties(STARTUP_OFFSET).as_map() {
+                    map.insert(topic.clone(), decode_partition_offsets(offset_str)?);
+                }
+                Ok(Self::SpecificOffsets(map))
+            }
+            "timestamp" => {
+                let timestamp = properties.get_u64(STARTUP_TIMESTAMP)?;
+                Ok(Self::Timestamp(timestamp))
+            }
+            _ => Err(anyhow!("unknown startup mode {}", mode)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::convert::TryFrom;
+
+    use rlink::core::properties::Properties;
+
+    use crate::source::offset_range::PartitionOffset;
+    use crate::source::startup_mode::StartupMode;
+
+    #[test]
+    pub fn startup_mode_properties_test() {
+        let mut offsets = HashMap::new();
+        offsets.insert(
+            "topic-0".to_string(),
+            vec![PartitionOffset::new(5, 71), PartitionOffset::new(2, 121)],
+        );
+
+        let properties: Properties = StartupMode::SpecificOffsets(offsets).into();
+        let startup_mode = StartupMode::try_from(properties).unwrap();
+        match &startup_mode {
+            StartupMode::SpecificOffsets(offsets) => {
+                let po = offsets.get("topic-0").unwrap();
+                assert_eq!((po[0].partition(), po[0].offset()), (2, 121));
+                assert_eq!((po[1].partition(), po[1].offset()), (5, 71));
+            }
+            _ => panic!("unexpected startup mode {:?}", startup_mode),
+        }
+
+        let properties: Properties = StartupMode::Timestamp(1000).into();
+        let startup_mode = StartupMode::try_from(properties).unwrap();
+        assert!(matches!(startup_mode, StartupMode::Timestamp(1000)));
+    }
+}
@@ -2,6 +2,7 @@ use futures::Stream;
 use rlink::channel::receiver::ChannelReceiver;
 use std::borrow::BorrowMut;
 use std::pin::Pin;
+use std::sync::atomic::Ordering;
 use std::task::{Context, Poll};
 
 use rlink::core::element::Element;
@@ -11,7 +12,7 @@ use crate::source::checkpoint::KafkaSourceStateRecorder;
 use crate::source::{is_empty_record, ConsumerRecord};
 
 /// Simulate a Kafka consumption stream as an iterator.
-/// only support one topic and one partition
+/// the task's own partition and the partitions discovered by topic pattern are merged into it
 pub struct KafkaRecordStream {
     receiver: ChannelReceiver<ConsumerRecord>,
     state_recorder: KafkaSourceStateRecorder,
@@ -42,7 +43,12 @@ impl Stream for KafkaRecordStream {
                         return Poll::Ready(None);
                     }
 
-                    self.state_recorder.update(consumer_record.offset);
+                    match &consumer_record.offset_state {
+                        Some(offset_state) => {
+                            offset_state.store(consumer_record.offset, Ordering::Relaxed)
+                        }
+                        None => self.state_recorder.update(consumer_record.offset),
+                    }
 
                     Poll::Ready(Some(Element::Record(consumer_record.record)))
                 }
@@ -0,0 +1,16 @@
+use serbuffer_gen::{Codegen, DataType::*, SchemaBuilder};
+
+fn main() {
+    Codegen::out_dir("buffer_gen")
+        .schema(
+            SchemaBuilder::new("KinesisMessage")
+                .field("timestamp", I64)
+                .field("partition_key", STRING)
+                .field("data", BINARY)
+                .field("stream", STRING)
+                .field("shard_id", STRING)
+                .field("sequence_number", STRING),
+        )
+        .gen()
+        .expect("buffer gen error");
+}
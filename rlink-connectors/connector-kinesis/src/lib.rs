@@ -0,0 +1,132 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod source;
+
+pub mod buffer_gen {
+    include!(concat!(env!("OUT_DIR"), "/buffer_gen/mod.rs"));
+}
+
+pub use source::input_format::KinesisInputFormat;
+
+use std::convert::TryFrom;
+
+use aws_sdk_kinesis::{Client, Endpoint, Region};
+use rlink::core::element::Record;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::kinesis_message;
+
+pub const KINESIS: &str = "kinesis";
+pub const REGION: &str = "region";
+pub const ENDPOINT: &str = "endpoint";
+
+pub const STREAM: &str = "stream";
+pub const START_POSITION: &str = "start.position";
+pub const START_TIMESTAMP: &str = "start.timestamp";
+pub const FAN_OUT_CONSUMER: &str = "fan.out.consumer";
+pub const SHARD_DISCOVERY_INTERVAL: &str = "shard.discovery.interval";
+pub const POLL_INTERVAL: &str = "poll.interval";
+pub const POLL_RECORDS: &str = "poll.records";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const INPUT_FORMAT_FN_NAME_DEFAULT: &str = "KinesisInputFormat";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 50000;
+
+/// Connection settings of the Kinesis client, the credentials are resolved by the default
+/// provider chain of the AWS SDK, eg: the environment variables or the instance profile
+#[derive(Clone, Debug, Default)]
+pub struct KinesisClientConfig {
+    region: Option<String>,
+    endpoint: Option<String>,
+}
+
+impl KinesisClientConfig {
+    pub fn new() -> Self {
+        KinesisClientConfig::default()
+    }
+
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+
+    /// Override the service endpoint, eg: a local kinesalite or LocalStack
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    pub(crate) async fn connect(&self) -> anyhow::Result<Client> {
+        let mut loader = aws_config::from_env();
+        if let Some(region) = &self.region {
+            loader = loader.region(Region::new(region.clone()));
+        }
+        let shared_config = loader.load().await;
+
+        let mut builder = aws_sdk_kinesis::config::Builder::from(&shared_config);
+        if let Some(endpoint) = &self.endpoint {
+            let uri = endpoint
+                .parse()
+                .map_err(|e| anyhow!("illegal kinesis endpoint `{}`. {}", endpoint, e))?;
+            builder = builder.endpoint_resolver(Endpoint::immutable(uri));
+        }
+
+        Ok(Client::from_conf(builder.build()))
+    }
+}
+
+impl TryFrom<Properties> for KinesisClientConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let mut client_config = KinesisClientConfig::new();
+        if let Ok(region) = properties.get_string(REGION) {
+            client_config = client_config.region(region.as_str());
+        }
+        if let Ok(endpoint) = properties.get_string(ENDPOINT) {
+            client_config = client_config.endpoint(endpoint.as_str());
+        }
+        Ok(client_config)
+    }
+}
+
+pub fn build_kinesis_record(
+    timestamp: i64,
+    partition_key: &str,
+    data: &[u8],
+    stream: &str,
+    shard_id: &str,
+    sequence_number: &str,
+) -> Result<Record, std::io::Error> {
+    let message = kinesis_message::Entity {
+        timestamp,
+        partition_key,
+        data,
+        stream,
+        shard_id,
+        sequence_number,
+    };
+
+    // 32 = 20(len(partition_key) + len(data) + len(stream) + len(shard_id) + len(sequence_number)) +
+    //      8(len(timestamp)) +
+    //      4(place_holder)
+    let capacity = partition_key.len()
+        + data.len()
+        + stream.len()
+        + shard_id.len()
+        + sequence_number.len()
+        + 32;
+    let mut record = Record::with_capacity(capacity);
+
+    message.to_buffer(record.as_buffer()).unwrap();
+
+    Ok(record)
+}
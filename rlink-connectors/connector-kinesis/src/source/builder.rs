@@ -0,0 +1,152 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::{Properties, PARALLELISM};
+
+use crate::buffer_gen::kinesis_message;
+use crate::source::consumer::ReadMode;
+use crate::source::deserializer::{
+    DefaultKinesisRecordDeserializer, DefaultKinesisRecordDeserializerBuilder,
+    KinesisRecordDeserializerBuilder,
+};
+use crate::source::start_position::StartPosition;
+use crate::{
+    KinesisClientConfig, KinesisInputFormat, BUFFER_SIZE, FAN_OUT_CONSUMER,
+    INPUT_FORMAT_FN_NAME_DEFAULT, KINESIS, POLL_INTERVAL, POLL_RECORDS, SHARD_DISCOVERY_INTERVAL,
+    SOURCE_CHANNEL_SIZE, START_POSITION, STREAM,
+};
+
+#[derive(Debug)]
+pub struct KinesisInputFormatBuilder {
+    fn_name: Option<String>,
+    parallelism: u16,
+    client_config: KinesisClientConfig,
+    stream: String,
+    start_position: StartPosition,
+    read_mode: ReadMode,
+    discovery_interval: Option<Duration>,
+    buffer_size: Option<usize>,
+}
+
+impl KinesisInputFormatBuilder {
+    pub fn new(client_config: KinesisClientConfig, stream: &str, parallelism: u16) -> Self {
+        KinesisInputFormatBuilder {
+            fn_name: None,
+            parallelism,
+            client_config,
+            stream: stream.to_string(),
+            start_position: StartPosition::default(),
+            read_mode: ReadMode::default(),
+            discovery_interval: None,
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    pub fn start_position(mut self, start_position: StartPosition) -> Self {
+        self.start_position = start_position;
+        self
+    }
+
+    pub fn read_mode(mut self, read_mode: ReadMode) -> Self {
+        self.read_mode = read_mode;
+        self
+    }
+
+    /// How often the shards are listed to pick up the resharding, default 10s
+    pub fn discovery_interval(mut self, interval: Duration) -> Self {
+        self.discovery_interval = Some(interval);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn KinesisRecordDeserializerBuilder>>,
+    ) -> KinesisInputFormat {
+        info!("build kinesis source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn KinesisRecordDeserializerBuilder> =
+                Box::new(DefaultKinesisRecordDeserializerBuilder::<
+                    DefaultKinesisRecordDeserializer,
+                >::new(FnSchema::from(
+                    &kinesis_message::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        let mut input_format = KinesisInputFormat::new(
+            self.client_config,
+            self.stream,
+            buffer_size,
+            deserializer_builder,
+            self.parallelism,
+            fn_name,
+        )
+        .start_position(self.start_position)
+        .read_mode(self.read_mode);
+        if let Some(discovery_interval) = self.discovery_interval {
+            input_format = input_format.discovery_interval(discovery_interval);
+        }
+        input_format
+    }
+}
+
+impl TryFrom<Properties> for KinesisInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let parallelism = properties.get_u16(PARALLELISM)?;
+        let client_config = KinesisClientConfig::try_from(properties.to_sub_properties(KINESIS))?;
+        let stream = properties.get_string(STREAM)?;
+
+        let mut builder =
+            KinesisInputFormatBuilder::new(client_config, stream.as_str(), parallelism);
+
+        builder = builder.fn_name(properties.name());
+
+        if properties.get_string(START_POSITION).is_ok() {
+            let start_position = StartPosition::try_from(properties.clone())?;
+            builder = builder.start_position(start_position);
+        }
+
+        if let Ok(consumer_name) = properties.get_string(FAN_OUT_CONSUMER) {
+            builder = builder.read_mode(ReadMode::FanOut { consumer_name });
+        } else if let ReadMode::Polling {
+            interval,
+            max_records,
+        } = ReadMode::default()
+        {
+            builder = builder.read_mode(ReadMode::Polling {
+                interval: properties.get_duration(POLL_INTERVAL).unwrap_or(interval),
+                max_records: properties.get_i32(POLL_RECORDS).unwrap_or(max_records),
+            });
+        }
+
+        if let Ok(interval) = properties.get_duration(SHARD_DISCOVERY_INTERVAL) {
+            builder = builder.discovery_interval(interval);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::function::Context;
+use rlink::core::runtime::TaskId;
+use rlink::core::state::{ListState, ListStateDescriptor, OperatorStateStore};
+
+/// the union list state of the shard states of all tasks
+const KINESIS_SHARDS: &str = "kinesis_shards";
+
+/// The shard states are checkpointed as the union operator state, every task restores from the
+/// states of all tasks, so the shards reassigned by the changed parallelism are resumed by
+/// their new tasks
+#[derive(Debug, Clone)]
+pub struct KinesisCheckpointFunction {
+    pub(crate) state_recorder: Option<KinesisSourceStateRecorder>,
+    #[allow(dead_code)]
+    pub(crate) application_id: String,
+    #[allow(dead_code)]
+    pub(crate) task_id: TaskId,
+    stream: String,
+    operator_state: OperatorStateStore,
+}
+
+impl KinesisCheckpointFunction {
+    pub fn new(application_id: String, task_id: TaskId, stream: &str) -> Self {
+        KinesisCheckpointFunction {
+            state_recorder: None,
+            application_id,
+            task_id,
+            stream: stream.to_string(),
+            operator_state: OperatorStateStore::new(),
+        }
+    }
+
+    pub fn as_state_mut(&mut self) -> &mut KinesisSourceStateRecorder {
+        self.state_recorder.as_mut().unwrap()
+    }
+
+    /// Restore the shard states of all tasks from the checkpoint of the `context`, it's called
+    /// before `initialize_state`
+    pub fn restore_from(&self, context: &Context) -> anyhow::Result<()> {
+        self.operator_state.restore_from(context)
+    }
+
+    fn shard_state(&self) -> ListState<String> {
+        self.operator_state
+            .union_list_state(&ListStateDescriptor::new(KINESIS_SHARDS))
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for KinesisCheckpointFunction {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+        let state_recorder = KinesisSourceStateRecorder::new(self.stream.as_str());
+        info!("Checkpoint initialize, context: {:?}", context);
+
+        let snapshots = self.shard_state().get().expect("get kinesis shards error");
+        for snapshot in &snapshots {
+            state_recorder
+                .update_from_snapshot(snapshot.as_str())
+                .unwrap();
+        }
+        if !snapshots.is_empty() {
+            info!(
+                "load shard states from the checkpoint({:?}) of {} tasks",
+                context.checkpoint_id,
+                snapshots.len()
+            );
+        }
+        self.state_recorder = Some(state_recorder);
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let snapshot = self.state_recorder.as_ref().unwrap().snapshot();
+        debug!(
+            "Checkpoint snapshot: {:?}, context: {:?}",
+            snapshot, context
+        );
+
+        let handle = self
+            .shard_state()
+            .update(vec![snapshot])
+            .and_then(|_| self.operator_state.snapshot());
+        match handle {
+            Ok(handle) => Some(handle),
+            Err(e) => panic!(
+                "snapshot kinesis shards on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ShardStates {
+    /// the last emitted sequence number of each shard
+    sequence_numbers: HashMap<String, String>,
+    /// the closed shards consumed to the end, their children are ready to consume
+    finished: HashSet<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SequenceSnapshot {
+    stream: String,
+    #[serde(flatten)]
+    states: ShardStates,
+}
+
+#[derive(Debug, Clone)]
+pub struct KinesisSourceStateRecorder {
+    stream: String,
+    states: Arc<Mutex<ShardStates>>,
+}
+
+impl KinesisSourceStateRecorder {
+    pub fn new(stream: &str) -> Self {
+        KinesisSourceStateRecorder {
+            stream: stream.to_string(),
+            states: Arc::new(Mutex::new(ShardStates::default())),
+        }
+    }
+
+    pub fn update(&self, shard_id: &str, sequence_number: &str) {
+        let mut states = self.states.lock().unwrap();
+        match states.sequence_numbers.get_mut(shard_id) {
+            Some(current) => {
+                current.clear();
+                current.push_str(sequence_number);
+            }
+            None => {
+                states
+                    .sequence_numbers
+                    .insert(shard_id.to_string(), sequence_number.to_string());
+            }
+        }
+    }
+
+    /// Mark the shard consumed to the end, the sequence number is no longer needed
+    pub fn finish(&self, shard_id: &str) {
+        let mut states = self.states.lock().unwrap();
+        states.sequence_numbers.remove(shard_id);
+        states.finished.insert(shard_id.to_string());
+    }
+
+    /// Merge the shard states of a task's snapshot, the snapshots of all tasks are merged. The
+    /// latest sequence number wins if a shard is in the snapshots of several tasks, and the
+    /// finished shards are never read again
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let snapshot: SequenceSnapshot = serde_json::from_str(snapshot_handle)?;
+        if !snapshot.stream.eq(self.stream.as_str()) {
+            return Err(anyhow!("Does not belong to the checkpoint of the task"));
+        }
+
+        let mut states = self.states.lock().unwrap();
+        let ShardStates {
+            sequence_numbers,
+            finished,
+        } = &mut *states;
+        finished.extend(snapshot.states.finished);
+        for (shard_id, sequence_number) in snapshot.states.sequence_numbers {
+            match sequence_numbers.get(&shard_id) {
+                Some(current) if !is_after(sequence_number.as_str(), current.as_str()) => {}
+                _ => {
+                    sequence_numbers.insert(shard_id, sequence_number);
+                }
+            }
+        }
+        sequence_numbers.retain(|shard_id, _| !finished.contains(shard_id));
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> String {
+        let states = self.states.lock().unwrap();
+        let snapshot = SequenceSnapshot {
+            stream: self.stream.clone(),
+            states: ShardStates {
+                sequence_numbers: states.sequence_numbers.clone(),
+                finished: states.finished.clone(),
+            },
+        };
+        serde_json::to_string(&snapshot).unwrap()
+    }
+
+    pub fn sequence_number(&self, shard_id: &str) -> Option<String> {
+        self.states
+            .lock()
+            .unwrap()
+            .sequence_numbers
+            .get(shard_id)
+            .cloned()
+    }
+
+    pub fn finished(&self) -> HashSet<String> {
+        self.states.lock().unwrap().finished.clone()
+    }
+}
+
+/// Whether the sequence number `a` is after `b` in the shard, the sequence numbers are the
+/// increasing decimal strings
+fn is_after(a: &str, b: &str) -> bool {
+    (a.len(), a) > (b.len(), b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::KinesisSourceStateRecorder;
+
+    #[test]
+    pub fn state_snapshot_test() {
+        let recorder = KinesisSourceStateRecorder::new("events");
+        recorder.update("shardId-000", "4959");
+        recorder.update("shardId-000", "4960");
+        recorder.update("shardId-001", "5001");
+        recorder.finish("shardId-001");
+
+        let restored = KinesisSourceStateRecorder::new("events");
+        restored
+            .update_from_snapshot(recorder.snapshot().as_str())
+            .unwrap();
+        assert_eq!(
+            restored.sequence_number("shardId-000"),
+            Some("4960".to_string())
+        );
+        assert_eq!(restored.sequence_number("shardId-001"), None);
+        assert!(restored.finished().contains("shardId-001"));
+
+        // the snapshots of the tasks are merged on restore
+        let stale = KinesisSourceStateRecorder::new("events");
+        stale.update("shardId-000", "998");
+        stale.update("shardId-001", "4999");
+        restored
+            .update_from_snapshot(stale.snapshot().as_str())
+            .unwrap();
+        assert_eq!(
+            restored.sequence_number("shardId-000"),
+            Some("4960".to_string())
+        );
+        assert_eq!(restored.sequence_number("shardId-001"), None);
+
+        let other = KinesisSourceStateRecorder::new("metrics");
+        assert!(other
+            .update_from_snapshot(recorder.snapshot().as_str())
+            .is_err());
+    }
+}
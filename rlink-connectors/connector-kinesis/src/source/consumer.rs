@@ -0,0 +1,277 @@
+use std::time::Duration;
+
+use aws_sdk_kinesis::model::SubscribeToShardEventStream;
+use aws_sdk_kinesis::Client;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::JobId;
+
+use crate::source::deserializer::KinesisRecordDeserializer;
+use crate::source::start_position::ShardPosition;
+use crate::source::ConsumerRecord;
+
+pub(crate) async fn create_shard_consumer(mut shard_consumer: ShardConsumer) {
+    tokio::spawn(async move {
+        match shard_consumer.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!(
+                    "run consumer of shard({}) error. {}",
+                    shard_consumer.shard_id, e
+                );
+            }
+        }
+    });
+}
+
+/// How the records of the shards are read
+#[derive(Clone, Debug)]
+pub enum ReadMode {
+    /// `GetRecords` polling, shares the read throughput of the shard with other consumers
+    Polling {
+        interval: Duration,
+        max_records: i32,
+    },
+    /// `SubscribeToShard` by the enhanced fan-out consumer of the name, with a dedicated
+    /// read throughput. The consumer is registered to the stream if absent
+    FanOut { consumer_name: String },
+}
+
+impl Default for ReadMode {
+    fn default() -> Self {
+        // a shard supports up to 5 `GetRecords` calls per second
+        ReadMode::Polling {
+            interval: Duration::from_millis(200),
+            max_records: 10000,
+        }
+    }
+}
+
+/// The `ReadMode` with the fan-out consumer resolved
+#[derive(Clone, Debug)]
+pub(crate) enum ShardReader {
+    Polling {
+        interval: Duration,
+        max_records: i32,
+    },
+    FanOut {
+        consumer_arn: String,
+    },
+}
+
+pub(crate) struct ShardConsumer {
+    job_id: JobId,
+    task_number: u16,
+
+    client: Client,
+    stream: String,
+    shard_id: String,
+    position: ShardPosition,
+    reader: ShardReader,
+
+    sender: ChannelSender<ConsumerRecord>,
+    deserializer: Box<dyn KinesisRecordDeserializer>,
+}
+
+impl ShardConsumer {
+    pub fn new(
+        job_id: JobId,
+        task_number: u16,
+        client: Client,
+        stream: String,
+        shard_id: String,
+        sender: ChannelSender<ConsumerRecord>,
+        deserializer: Box<dyn KinesisRecordDeserializer>,
+    ) -> Self {
+        ShardConsumer {
+            job_id,
+            task_number,
+            client,
+            stream,
+            shard_id,
+            position: ShardPosition::Start(Default::default()),
+            reader: ShardReader::Polling {
+                interval: Duration::from_millis(200),
+                max_records: 10000,
+            },
+            sender,
+            deserializer,
+        }
+    }
+
+    pub fn position(mut self, position: ShardPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn reader(mut self, reader: ShardReader) -> Self {
+        self.reader = reader;
+        self
+    }
+
+    async fn handle_record(&mut self, record: &aws_sdk_kinesis::model::Record) {
+        let sequence_number = record.sequence_number().unwrap_or_default();
+        let timestamp = record
+            .approximate_arrival_timestamp()
+            .and_then(|x| x.to_millis().ok())
+            .unwrap_or(0);
+        let partition_key = record.partition_key().unwrap_or_default();
+        let data = record.data().map(|x| x.as_ref()).unwrap_or_default();
+
+        let records = self.deserializer.deserialize(
+            timestamp,
+            partition_key,
+            data,
+            self.stream.as_str(),
+            self.shard_id.as_str(),
+            sequence_number,
+        );
+        for record in records {
+            self.sender
+                .send(ConsumerRecord::Record {
+                    record,
+                    shard_id: self.shard_id.clone(),
+                    sequence_number: sequence_number.to_string(),
+                })
+                .await
+                .expect("kinesis consumer handover `Disconnected`");
+        }
+
+        self.position = ShardPosition::AfterSequenceNumber(sequence_number.to_string());
+    }
+
+    async fn send_end(&self) {
+        self.sender
+            .send(ConsumerRecord::ShardEnd {
+                shard_id: self.shard_id.clone(),
+            })
+            .await
+            .expect("kinesis consumer handover `Disconnected`");
+        info!(
+            "kinesis shard end reached. shard: {}, job_id: {}, task_num: {}",
+            self.shard_id, *self.job_id, self.task_number
+        );
+    }
+
+    async fn shard_iterator(&self) -> anyhow::Result<Option<String>> {
+        let output = self
+            .client
+            .get_shard_iterator()
+            .stream_name(self.stream.as_str())
+            .shard_id(self.shard_id.as_str())
+            .shard_iterator_type(self.position.shard_iterator_type())
+            .set_starting_sequence_number(self.position.sequence_number().map(|x| x.to_string()))
+            .set_timestamp(self.position.timestamp())
+            .send()
+            .await
+            .map_err(|e| anyhow!("get shard iterator error. {}", e))?;
+        Ok(output.shard_iterator().map(|x| x.to_string()))
+    }
+
+    /// Returns `true` if the shard is closed and consumed to the end
+    async fn poll(&mut self, interval: Duration, max_records: i32) -> anyhow::Result<bool> {
+        let mut shard_iterator = self.shard_iterator().await?;
+        while let Some(iterator) = shard_iterator {
+            if self.sender.is_closed() {
+                return Ok(false);
+            }
+
+            let output = match self
+                .client
+                .get_records()
+                .shard_iterator(iterator)
+                .limit(max_records)
+                .send()
+                .await
+            {
+                Ok(output) => output,
+                Err(e) => {
+                    // maybe throttled or the iterator is expired, resume from the last record
+                    warn!(
+                        "kinesis get records error. shard: {}, error: {}",
+                        self.shard_id, e
+                    );
+                    tokio::time::sleep(interval).await;
+                    shard_iterator = self.shard_iterator().await?;
+                    continue;
+                }
+            };
+
+            for record in output.records().unwrap_or_default() {
+                self.handle_record(record).await;
+            }
+
+            shard_iterator = output.next_shard_iterator().map(|x| x.to_string());
+            tokio::time::sleep(interval).await;
+        }
+
+        // no next iterator, the shard is closed by resharding
+        Ok(true)
+    }
+
+    /// Returns `true` if the shard is closed and consumed to the end
+    async fn subscribe(&mut self, consumer_arn: &str) -> anyhow::Result<bool> {
+        // a subscription expires after 5 minutes, resubscribe from the last record
+        while !self.sender.is_closed() {
+            let mut output = self
+                .client
+                .subscribe_to_shard()
+                .consumer_arn(consumer_arn)
+                .shard_id(self.shard_id.as_str())
+                .starting_position(self.position.starting_position())
+                .send()
+                .await
+                .map_err(|e| anyhow!("subscribe to shard error. {}", e))?;
+
+            loop {
+                let event = match output.event_stream.recv().await {
+                    Ok(Some(event)) => event,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!(
+                            "kinesis subscription error. shard: {}, error: {}",
+                            self.shard_id, e
+                        );
+                        break;
+                    }
+                };
+
+                if let SubscribeToShardEventStream::SubscribeToShardEvent(event) = event {
+                    for record in event.records().unwrap_or_default() {
+                        self.handle_record(record).await;
+                    }
+
+                    match event.continuation_sequence_number() {
+                        Some(continuation) => {
+                            self.position =
+                                ShardPosition::AfterSequenceNumber(continuation.to_string());
+                        }
+                        // the shard is closed and all records are delivered
+                        None => return Ok(true),
+                    }
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        info!(
+            "create kinesis shard consumer. stream: {}, shard: {}, position: {:?}, reader: {:?}, job_id: {}, task_num: {}",
+            self.stream, self.shard_id, self.position, self.reader, *self.job_id, self.task_number
+        );
+
+        let shard_end = match self.reader.clone() {
+            ShardReader::Polling {
+                interval,
+                max_records,
+            } => self.poll(interval, max_records).await?,
+            ShardReader::FanOut { consumer_arn } => self.subscribe(consumer_arn.as_str()).await?,
+        };
+        if shard_end {
+            self.send_end().await;
+        }
+
+        Ok(())
+    }
+}
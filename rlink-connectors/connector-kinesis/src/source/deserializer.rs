@@ -0,0 +1,82 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+
+use crate::build_kinesis_record;
+
+pub trait KinesisRecordDeserializer: Sync + Send {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        partition_key: &str,
+        data: &[u8],
+        stream: &str,
+        shard_id: &str,
+        sequence_number: &str,
+    ) -> Vec<Record>;
+}
+
+pub trait KinesisRecordDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn KinesisRecordDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+#[derive(Default)]
+pub struct DefaultKinesisRecordDeserializer {}
+
+impl KinesisRecordDeserializer for DefaultKinesisRecordDeserializer {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        partition_key: &str,
+        data: &[u8],
+        stream: &str,
+        shard_id: &str,
+        sequence_number: &str,
+    ) -> Vec<Record> {
+        let record = build_kinesis_record(
+            timestamp,
+            partition_key,
+            data,
+            stream,
+            shard_id,
+            sequence_number,
+        )
+        .expect("kinesis message writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultKinesisRecordDeserializerBuilder<T>
+where
+    T: Default + KinesisRecordDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultKinesisRecordDeserializerBuilder<T>
+where
+    T: Default + KinesisRecordDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultKinesisRecordDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> KinesisRecordDeserializerBuilder for DefaultKinesisRecordDeserializerBuilder<T>
+where
+    T: Default + KinesisRecordDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn KinesisRecordDeserializer> {
+        let t: Box<dyn KinesisRecordDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aws_sdk_kinesis::Client;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::TaskId;
+
+use crate::source::checkpoint::KinesisSourceStateRecorder;
+use crate::source::consumer::{create_shard_consumer, ShardConsumer, ShardReader};
+use crate::source::deserializer::KinesisRecordDeserializerBuilder;
+use crate::source::shard::{list_shards, ShardAssigner};
+use crate::source::start_position::{ShardPosition, StartPosition};
+use crate::source::ConsumerRecord;
+
+/// Periodically list the shards of the stream, and start the readers of the shards assigned
+/// to the subtask once their parents are consumed to the end.
+pub(crate) struct ShardDiscovery {
+    task_id: TaskId,
+    client: Client,
+    stream: String,
+    interval: Duration,
+    start_position: StartPosition,
+    reader: ShardReader,
+
+    assigner: ShardAssigner,
+    started: HashSet<String>,
+
+    state_recorder: KinesisSourceStateRecorder,
+    deserializer_builder: Arc<dyn KinesisRecordDeserializerBuilder>,
+    handover: ChannelSender<ConsumerRecord>,
+}
+
+impl ShardDiscovery {
+    pub fn new(
+        task_id: TaskId,
+        client: Client,
+        stream: String,
+        state_recorder: KinesisSourceStateRecorder,
+        deserializer_builder: Arc<dyn KinesisRecordDeserializerBuilder>,
+        reader: ShardReader,
+        handover: ChannelSender<ConsumerRecord>,
+    ) -> Self {
+        let assigner = ShardAssigner::new(task_id.num_tasks(), task_id.task_number());
+        ShardDiscovery {
+            task_id,
+            client,
+            stream,
+            interval: Duration::from_secs(10),
+            start_position: StartPosition::default(),
+            reader,
+            assigner,
+            started: HashSet::new(),
+            state_recorder,
+            deserializer_builder,
+            handover,
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn start_position(mut self, start_position: StartPosition) -> Self {
+        self.start_position = start_position;
+        self
+    }
+
+    async fn discover(&mut self) -> anyhow::Result<()> {
+        let shards = list_shards(&self.client, self.stream.as_str()).await?;
+        let shard_ids: HashSet<&str> = shards.iter().map(|x| x.shard_id.as_str()).collect();
+
+        let finished = self.state_recorder.finished();
+        let ready_shards = self
+            .assigner
+            .ready_shards(shards.as_slice(), &self.started, &finished);
+        for shard in ready_shards {
+            // a child shard continues its parents still in the stream, so it's read from
+            // the beginning rather than the `start_position`
+            let position = match self.state_recorder.sequence_number(shard.shard_id.as_str()) {
+                Some(sequence_number) => ShardPosition::AfterSequenceNumber(sequence_number),
+                None if shard.parents().any(|x| shard_ids.contains(x.as_str())) => {
+                    ShardPosition::Start(StartPosition::TrimHorizon)
+                }
+                None => ShardPosition::Start(self.start_position.clone()),
+            };
+
+            let shard_consumer = ShardConsumer::new(
+                self.task_id.job_id(),
+                self.task_id.task_number(),
+                self.client.clone(),
+                self.stream.clone(),
+                shard.shard_id.clone(),
+                self.handover.clone(),
+                self.deserializer_builder.build(),
+            )
+            .position(position)
+            .reader(self.reader.clone());
+            create_shard_consumer(shard_consumer).await;
+
+            self.started.insert(shard.shard_id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn run(mut self) {
+        while !self.handover.is_closed() {
+            if let Err(e) = self.discover().await {
+                warn!(
+                    "kinesis shard discovery error. stream: {}, error: {}",
+                    self.stream, e
+                );
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+
+        info!("kinesis shard discovery finished. stream: {}", self.stream);
+    }
+}
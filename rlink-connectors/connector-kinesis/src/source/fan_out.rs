@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use aws_sdk_kinesis::model::ConsumerStatus;
+use aws_sdk_kinesis::Client;
+
+const REGISTER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Look up the enhanced fan-out consumer of the stream by name, register it if absent,
+/// and wait until it's active. Every subtask calls it with the same name, so the consumer is
+/// shared and each shard is subscribed by its owner subtask only.
+pub(crate) async fn register_consumer(
+    client: &Client,
+    stream: &str,
+    consumer_name: &str,
+) -> anyhow::Result<String> {
+    let stream_arn = client
+        .describe_stream_summary()
+        .stream_name(stream)
+        .send()
+        .await
+        .map_err(|e| anyhow!("describe stream({}) error. {}", stream, e))?
+        .stream_description_summary()
+        .and_then(|x| x.stream_arn())
+        .map(|x| x.to_string())
+        .ok_or(anyhow!("arn of stream({}) not found", stream))?;
+
+    let described = client
+        .describe_stream_consumer()
+        .stream_arn(stream_arn.as_str())
+        .consumer_name(consumer_name)
+        .send()
+        .await;
+    if described.is_err() {
+        // maybe registered by another subtask concurrently, it's checked by the describe below
+        if let Err(e) = client
+            .register_stream_consumer()
+            .stream_arn(stream_arn.as_str())
+            .consumer_name(consumer_name)
+            .send()
+            .await
+        {
+            warn!("register stream consumer({}) error. {}", consumer_name, e);
+        }
+    }
+
+    let begin = std::time::Instant::now();
+    loop {
+        let description = client
+            .describe_stream_consumer()
+            .stream_arn(stream_arn.as_str())
+            .consumer_name(consumer_name)
+            .send()
+            .await
+            .map_err(|e| anyhow!("describe stream consumer({}) error. {}", consumer_name, e))?;
+        if let Some(description) = description.consumer_description() {
+            if description.consumer_status() == Some(&ConsumerStatus::Active) {
+                let consumer_arn = description.consumer_arn().ok_or(anyhow!(
+                    "arn of stream consumer({}) not found",
+                    consumer_name
+                ))?;
+                return Ok(consumer_arn.to_string());
+            }
+        }
+
+        if begin.elapsed() > REGISTER_TIMEOUT {
+            return Err(anyhow!(
+                "stream consumer({}) is not active in {:?}",
+                consumer_name,
+                REGISTER_TIMEOUT
+            ));
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rlink::channel::named_channel;
+use rlink::channel::sender::ChannelSender;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::properties::Properties;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::Tag;
+
+use crate::source::checkpoint::KinesisCheckpointFunction;
+use crate::source::consumer::{ReadMode, ShardReader};
+use crate::source::deserializer::KinesisRecordDeserializerBuilder;
+use crate::source::discovery::ShardDiscovery;
+use crate::source::fan_out::register_consumer;
+use crate::source::start_position::StartPosition;
+use crate::source::stream::KinesisRecordStream;
+use crate::source::ConsumerRecord;
+use crate::KinesisClientConfig;
+
+pub struct KinesisInputFormat {
+    name: String,
+    parallelism: u16,
+
+    client_config: KinesisClientConfig,
+    stream: String,
+    start_position: StartPosition,
+    read_mode: ReadMode,
+    discovery_interval: Duration,
+
+    task_id: TaskId,
+    buffer_size: usize,
+    tags: Vec<Tag>,
+
+    deserializer_builder: Arc<dyn KinesisRecordDeserializerBuilder>,
+    schema: FnSchema,
+
+    checkpoint: Option<KinesisCheckpointFunction>,
+}
+
+impl KinesisInputFormat {
+    pub fn new(
+        client_config: KinesisClientConfig,
+        stream: String,
+        buffer_size: usize,
+        deserializer_builder: Box<dyn KinesisRecordDeserializerBuilder>,
+        parallelism: u16,
+        fn_name: String,
+    ) -> Self {
+        let schema = deserializer_builder.schema();
+        KinesisInputFormat {
+            name: fn_name,
+            parallelism,
+            client_config,
+            stream,
+            start_position: StartPosition::default(),
+            read_mode: ReadMode::default(),
+            discovery_interval: Duration::from_secs(10),
+            task_id: Default::default(),
+            buffer_size,
+            tags: vec![],
+            deserializer_builder: Arc::from(deserializer_builder),
+            schema,
+            checkpoint: None,
+        }
+    }
+
+    pub fn start_position(mut self, start_position: StartPosition) -> Self {
+        self.start_position = start_position;
+        self
+    }
+
+    pub fn read_mode(mut self, read_mode: ReadMode) -> Self {
+        self.read_mode = read_mode;
+        self
+    }
+
+    pub fn discovery_interval(mut self, discovery_interval: Duration) -> Self {
+        self.discovery_interval = discovery_interval;
+        self
+    }
+
+    async fn create_discovery(
+        &mut self,
+        sender: ChannelSender<ConsumerRecord>,
+    ) -> anyhow::Result<ShardDiscovery> {
+        let client = self.client_config.connect().await?;
+        let reader = match &self.read_mode {
+            ReadMode::Polling {
+                interval,
+                max_records,
+            } => ShardReader::Polling {
+                interval: *interval,
+                max_records: *max_records,
+            },
+            ReadMode::FanOut { consumer_name } => {
+                let consumer_arn =
+                    register_consumer(&client, self.stream.as_str(), consumer_name.as_str())
+                        .await?;
+                ShardReader::FanOut { consumer_arn }
+            }
+        };
+
+        let state_recorder = self.checkpoint.as_mut().unwrap().as_state_mut().clone();
+        let discovery = ShardDiscovery::new(
+            self.task_id.clone(),
+            client,
+            self.stream.clone(),
+            state_recorder,
+            self.deserializer_builder.clone(),
+            reader,
+            sender,
+        )
+        .interval(self.discovery_interval)
+        .start_position(self.start_position.clone());
+        Ok(discovery)
+    }
+}
+
+impl NamedFunction for KinesisInputFormat {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl InputFormat for KinesisInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        info!("kinesis source open");
+
+        self.task_id = context.task_id.clone();
+
+        let checkpoint = KinesisCheckpointFunction::new(
+            context.application_id.clone(),
+            context.task_id,
+            self.stream.as_str(),
+        );
+        checkpoint.restore_from(context)?;
+        self.checkpoint = Some(checkpoint);
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        self.tags.push(Tag::new("stream", self.stream.as_str()));
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) = named_channel(
+            "KinesisSource_Handover",
+            self.tags.clone(),
+            self.buffer_size,
+        );
+
+        let discovery = self
+            .create_discovery(sender)
+            .await
+            .expect("create kinesis shard discovery error");
+        tokio::spawn(discovery.run());
+
+        let state_recorder = self.checkpoint.as_mut().unwrap().as_state_mut().clone();
+        Box::pin(KinesisRecordStream::new(receiver, state_recorder))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for KinesisInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.checkpoint
+            .as_mut()
+            .unwrap()
+            .initialize_state(context, handle)
+            .await;
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        match self.checkpoint.as_mut() {
+            Some(checkpoint) => checkpoint.snapshot_state(context).await,
+            None => None,
+        }
+    }
+}
+
+impl InputSplitSource for KinesisInputFormat {
+    /// The shards are assigned by each subtask's discovery at runtime, so every subtask gets
+    /// a placeholder split
+    fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
+        info!(
+            "kinesis config {:?}, stream: {}",
+            self.client_config, self.stream
+        );
+
+        let input_splits = (0..min_num_splits)
+            .map(|index| InputSplit::new(index, Properties::new()))
+            .collect();
+        Ok(input_splits)
+    }
+}
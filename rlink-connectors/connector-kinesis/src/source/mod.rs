@@ -0,0 +1,21 @@
+pub mod builder;
+pub mod checkpoint;
+pub mod consumer;
+pub mod deserializer;
+pub mod discovery;
+pub mod fan_out;
+pub mod input_format;
+pub mod shard;
+pub mod start_position;
+pub mod stream;
+
+#[derive(Clone, Debug)]
+pub(crate) enum ConsumerRecord {
+    Record {
+        record: rlink::core::element::Record,
+        shard_id: String,
+        sequence_number: String,
+    },
+    /// the closed shard is consumed to the end
+    ShardEnd { shard_id: String },
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, HashSet};
+
+use aws_sdk_kinesis::Client;
+use rlink::utils::hash::hash_code;
+
+/// The lineage of a shard in the stream listing
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ShardInfo {
+    pub(crate) shard_id: String,
+    pub(crate) parent_shard_id: Option<String>,
+    /// the other parent of a merged shard
+    pub(crate) adjacent_parent_shard_id: Option<String>,
+}
+
+impl ShardInfo {
+    pub fn new(shard_id: &str) -> Self {
+        ShardInfo {
+            shard_id: shard_id.to_string(),
+            parent_shard_id: None,
+            adjacent_parent_shard_id: None,
+        }
+    }
+
+    pub fn parent(mut self, parent_shard_id: &str) -> Self {
+        self.parent_shard_id = Some(parent_shard_id.to_string());
+        self
+    }
+
+    pub fn adjacent_parent(mut self, adjacent_parent_shard_id: &str) -> Self {
+        self.adjacent_parent_shard_id = Some(adjacent_parent_shard_id.to_string());
+        self
+    }
+
+    pub fn parents(&self) -> impl Iterator<Item = &String> {
+        self.parent_shard_id
+            .iter()
+            .chain(self.adjacent_parent_shard_id.iter())
+    }
+}
+
+/// List all shards of the stream, including the closed shards within the retention period
+pub(crate) async fn list_shards(client: &Client, stream: &str) -> anyhow::Result<Vec<ShardInfo>> {
+    let mut shards = Vec::new();
+    let mut next_token: Option<String> = None;
+    loop {
+        // the stream name must not be specified together with the next token
+        let request = match &next_token {
+            Some(next_token) => client.list_shards().next_token(next_token),
+            None => client.list_shards().stream_name(stream),
+        };
+        let output = request
+            .send()
+            .await
+            .map_err(|e| anyhow!("list shards of stream({}) error. {}", stream, e))?;
+
+        for shard in output.shards().unwrap_or_default() {
+            let mut shard_info = ShardInfo::new(shard.shard_id().unwrap_or_default());
+            if let Some(parent_shard_id) = shard.parent_shard_id() {
+                shard_info = shard_info.parent(parent_shard_id);
+            }
+            if let Some(adjacent_parent_shard_id) = shard.adjacent_parent_shard_id() {
+                shard_info = shard_info.adjacent_parent(adjacent_parent_shard_id);
+            }
+            shards.push(shard_info);
+        }
+
+        next_token = output.next_token().map(|x| x.to_string());
+        if next_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(shards)
+}
+
+/// Assigns the shards across the subtasks and decides which ones are ready to consume.
+///
+/// A shard is assigned by the hash of its oldest ancestor still in the listing, so the
+/// children of a split stay with the parent in the same subtask and are consumed after it.
+/// The adjacent parent of a merged shard may belong to another subtask, whose progress is
+/// not visible, so only the parents of the same subtask are waited for.
+pub(crate) struct ShardAssigner {
+    num_tasks: u16,
+    task_number: u16,
+}
+
+impl ShardAssigner {
+    pub fn new(num_tasks: u16, task_number: u16) -> Self {
+        ShardAssigner {
+            num_tasks,
+            task_number,
+        }
+    }
+
+    fn root<'a>(shards: &HashMap<&str, &'a ShardInfo>, shard: &'a ShardInfo) -> &'a str {
+        let mut current = shard;
+        // the lineage is acyclic, the depth guard is only against a malformed listing
+        for _ in 0..shards.len() {
+            match current
+                .parent_shard_id
+                .as_ref()
+                .and_then(|parent| shards.get(parent.as_str()))
+            {
+                Some(parent) => current = *parent,
+                None => break,
+            }
+        }
+        current.shard_id.as_str()
+    }
+
+    pub fn is_owner(&self, shards: &[ShardInfo], shard: &ShardInfo) -> bool {
+        let index: HashMap<&str, &ShardInfo> =
+            shards.iter().map(|x| (x.shard_id.as_str(), x)).collect();
+        self.is_owner_by_index(&index, shard)
+    }
+
+    fn is_owner_by_index(&self, index: &HashMap<&str, &ShardInfo>, shard: &ShardInfo) -> bool {
+        let root = Self::root(index, shard);
+        let hash = hash_code(root.as_bytes()).unwrap();
+        hash % self.num_tasks as u32 == self.task_number as u32
+    }
+
+    /// The owned shards which are neither started nor finished, and whose owned parents are
+    /// finished
+    pub fn ready_shards(
+        &self,
+        shards: &[ShardInfo],
+        started: &HashSet<String>,
+        finished: &HashSet<String>,
+    ) -> Vec<ShardInfo> {
+        let index: HashMap<&str, &ShardInfo> =
+            shards.iter().map(|x| (x.shard_id.as_str(), x)).collect();
+
+        shards
+            .iter()
+            .filter(|shard| self.is_owner_by_index(&index, shard))
+            .filter(|shard| {
+                !started.contains(&shard.shard_id) && !finished.contains(&shard.shard_id)
+            })
+            .filter(|shard| {
+                shard
+                    .parents()
+                    .all(|parent| match index.get(parent.as_str()) {
+                        Some(parent) => {
+                            !self.is_owner_by_index(&index, parent)
+                                || finished.contains(&parent.shard_id)
+                        }
+                        // the parent is expired from the stream
+                        None => true,
+                    })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::source::shard::{ShardAssigner, ShardInfo};
+
+    #[test]
+    pub fn ready_shards_test() {
+        let shards = vec![
+            ShardInfo::new("shardId-000"),
+            ShardInfo::new("shardId-001").parent("shardId-000"),
+            ShardInfo::new("shardId-002").parent("shardId-000"),
+            ShardInfo::new("shardId-003"),
+        ];
+
+        let assigners: Vec<ShardAssigner> = (0..3).map(|n| ShardAssigner::new(3, n)).collect();
+
+        // every shard has exactly one owner, the children of a split go with the parent
+        for shard in &shards {
+            let owners: Vec<u16> = (0..3)
+                .filter(|n| assigners[*n as usize].is_owner(&shards, shard))
+                .collect();
+            assert_eq!(owners.len(), 1);
+        }
+        let owner = |shard_id: &str| {
+            let shard = shards.iter().find(|x| x.shard_id.eq(shard_id)).unwrap();
+            (0..3)
+                .find(|n| assigners[*n as usize].is_owner(&shards, shard))
+                .unwrap()
+        };
+        assert_eq!(owner("shardId-001"), owner("shardId-000"));
+        assert_eq!(owner("shardId-002"), owner("shardId-000"));
+
+        // the children wait for the parent
+        let assigner = &assigners[owner("shardId-000") as usize];
+        let mut started = HashSet::new();
+        let mut finished = HashSet::new();
+        let ready: Vec<String> = assigner
+            .ready_shards(&shards, &started, &finished)
+            .into_iter()
+            .map(|x| x.shard_id)
+            .collect();
+        assert!(ready.contains(&"shardId-000".to_string()));
+        assert!(!ready.contains(&"shardId-001".to_string()));
+
+        started.insert("shardId-000".to_string());
+        finished.insert("shardId-000".to_string());
+        let ready: Vec<String> = assigner
+            .ready_shards(&shards, &started, &finished)
+            .into_iter()
+            .map(|x| x.shard_id)
+            .collect();
+        assert!(ready.contains(&"shardId-001".to_string()));
+        assert!(ready.contains(&"shardId-002".to_string()));
+        assert!(!ready.contains(&"shardId-000".to_string()));
+    }
+}
@@ -0,0 +1,80 @@
+use std::convert::TryFrom;
+
+use aws_sdk_kinesis::model::{ShardIteratorType, StartingPosition};
+use aws_sdk_kinesis::types::DateTime;
+use rlink::core::properties::Properties;
+
+use crate::{START_POSITION, START_TIMESTAMP};
+
+/// Where a shard is read from when there is no sequence number restored from the checkpoint.
+/// The child shards discovered after the job started are always read from the beginning.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum StartPosition {
+    /// the oldest record within the retention period
+    TrimHorizon,
+    /// only the records put after the shard is subscribed
+    #[default]
+    Latest,
+    /// the first record arrived at or after the timestamp(millis)
+    AtTimestamp(u64),
+}
+
+/// The position a shard reader begins with
+#[derive(Clone, Debug)]
+pub(crate) enum ShardPosition {
+    Start(StartPosition),
+    AfterSequenceNumber(String),
+}
+
+impl ShardPosition {
+    pub fn shard_iterator_type(&self) -> ShardIteratorType {
+        match self {
+            Self::Start(StartPosition::TrimHorizon) => ShardIteratorType::TrimHorizon,
+            Self::Start(StartPosition::Latest) => ShardIteratorType::Latest,
+            Self::Start(StartPosition::AtTimestamp(_)) => ShardIteratorType::AtTimestamp,
+            Self::AfterSequenceNumber(_) => ShardIteratorType::AfterSequenceNumber,
+        }
+    }
+
+    pub fn sequence_number(&self) -> Option<&str> {
+        match self {
+            Self::AfterSequenceNumber(sequence_number) => Some(sequence_number.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime> {
+        match self {
+            Self::Start(StartPosition::AtTimestamp(timestamp)) => {
+                Some(DateTime::from_millis(*timestamp as i64))
+            }
+            _ => None,
+        }
+    }
+
+    /// The starting position of the enhanced fan-out subscription
+    pub fn starting_position(&self) -> StartingPosition {
+        StartingPosition::builder()
+            .r#type(self.shard_iterator_type())
+            .set_sequence_number(self.sequence_number().map(|x| x.to_string()))
+            .set_timestamp(self.timestamp())
+            .build()
+    }
+}
+
+impl TryFrom<Properties> for StartPosition {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let start_position = properties.get_string(START_POSITION)?;
+        match start_position.to_lowercase().as_str() {
+            "trim_horizon" => Ok(Self::TrimHorizon),
+            "latest" => Ok(Self::Latest),
+            "at_timestamp" => {
+                let timestamp = properties.get_u64(START_TIMESTAMP)?;
+                Ok(Self::AtTimestamp(timestamp))
+            }
+            _ => Err(anyhow!("unknown start position {}", start_position)),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::KinesisSourceStateRecorder;
+use crate::source::ConsumerRecord;
+
+/// Simulate a Kinesis consumption stream as an iterator,
+/// the records of all shards assigned to the subtask are merged into it
+pub struct KinesisRecordStream {
+    receiver: ChannelReceiver<ConsumerRecord>,
+    state_recorder: KinesisSourceStateRecorder,
+}
+
+impl KinesisRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<ConsumerRecord>,
+        state_recorder: KinesisSourceStateRecorder,
+    ) -> Self {
+        KinesisRecordStream {
+            receiver,
+            state_recorder,
+        }
+    }
+}
+
+impl ElementStream for KinesisRecordStream {}
+
+impl Stream for KinesisRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().receiver.poll_recv(cx) {
+                Poll::Ready(Some(ConsumerRecord::Record {
+                    record,
+                    shard_id,
+                    sequence_number,
+                })) => {
+                    self.state_recorder
+                        .update(shard_id.as_str(), sequence_number.as_str());
+                    return Poll::Ready(Some(Element::Record(record)));
+                }
+                // the children of the shard are picked up by the discovery
+                Poll::Ready(Some(ConsumerRecord::ShardEnd { shard_id })) => {
+                    self.state_recorder.finish(shard_id.as_str());
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod sink;
+
+pub use sink::output_format::MongodbOutputFormat;
+
+pub const MONGODB: &str = "mongodb";
+pub const URI: &str = "uri";
+
+pub const DATABASE: &str = "database";
+pub const COLLECTION: &str = "collection";
+pub const MODE: &str = "mode";
+pub const KEY_FIELD: &str = "key.field";
+/// `majority`, the number of nodes or the custom tag
+pub const WRITE_CONCERN: &str = "write.concern";
+pub const JOURNAL: &str = "journal";
+pub const WRITE_TIMEOUT: &str = "write.timeout";
+pub const BUFFER_SIZE: &str = "buffer.size";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 1000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 1000;
+pub const SINK_MAX_RETRIES: usize = 3;
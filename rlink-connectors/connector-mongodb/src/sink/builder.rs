@@ -0,0 +1,193 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use mongodb::options::{Acknowledgment, WriteConcern};
+use rlink::core::properties::Properties;
+
+use crate::sink::mode::WriteMode;
+use crate::{
+    MongodbOutputFormat, BATCH_INTERVAL, BATCH_SIZE, BUFFER_SIZE, COLLECTION, DATABASE, JOURNAL,
+    KEY_FIELD, MAX_RETRIES, MODE, MONGODB, SINK_CHANNEL_SIZE, URI, WRITE_CONCERN, WRITE_TIMEOUT,
+};
+
+pub struct MongodbOutputFormatBuilder {
+    uri: String,
+    database: String,
+    collection: String,
+    write_mode: WriteMode,
+    write_concern: Option<WriteConcern>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+    batch_interval: Option<Duration>,
+    max_retries: Option<usize>,
+}
+
+impl MongodbOutputFormatBuilder {
+    /// The `uri` is in the format of `mongodb://[username:password@]host1[:port1][,...]/`
+    pub fn new(uri: &str, database: &str, collection: &str, write_mode: WriteMode) -> Self {
+        MongodbOutputFormatBuilder {
+            uri: uri.to_string(),
+            database: database.to_string(),
+            collection: collection.to_string(),
+            write_mode,
+            write_concern: None,
+            buffer_size: None,
+            batch_size: None,
+            batch_interval: None,
+            max_retries: None,
+        }
+    }
+
+    /// The write concern of the writes, the default of the server if absent
+    pub fn write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Flush once `batch_size` documents are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Flush the buffered documents at least every `batch_interval`
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = Some(batch_interval);
+        self
+    }
+
+    /// Retry a failed batch up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> MongodbOutputFormat {
+        info!("build mongodb sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let mut output_format = MongodbOutputFormat::new(
+            self.uri,
+            self.database,
+            self.collection,
+            self.write_mode,
+            buffer_size,
+        );
+
+        if let Some(write_concern) = self.write_concern {
+            output_format = output_format.write_concern(write_concern);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(batch_interval) = self.batch_interval {
+            output_format = output_format.batch_interval(batch_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for MongodbOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MongodbOutputFormatBuilder")
+            .field("uri", &redact_uri(self.uri.as_str()))
+            .field("database", &self.database)
+            .field("collection", &self.collection)
+            .field("write_mode", &self.write_mode)
+            .field("write_concern", &self.write_concern)
+            .field("buffer_size", &self.buffer_size)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+/// Hide the credentials in the uri
+fn redact_uri(uri: &str) -> String {
+    match uri.split_once('@') {
+        Some((_, host)) => {
+            let scheme = uri.split("://").next().unwrap_or_default();
+            format!("{}://***@{}", scheme, host)
+        }
+        None => uri.to_string(),
+    }
+}
+
+impl TryFrom<&Properties> for WriteMode {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: &Properties) -> Result<Self, Self::Error> {
+        let mode = properties
+            .get_string(MODE)
+            .unwrap_or_else(|_| "insert".to_string());
+        let write_mode = match mode.to_lowercase().as_str() {
+            "insert" => WriteMode::Insert,
+            "upsert" => WriteMode::Upsert {
+                key_field: properties.get_string(KEY_FIELD)?,
+            },
+            _ => return Err(anyhow!("unknown mongodb write mode `{}`", mode)),
+        };
+        Ok(write_mode)
+    }
+}
+
+impl TryFrom<Properties> for MongodbOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let mongodb_properties = properties.to_sub_properties(MONGODB);
+        let uri = mongodb_properties.get_string(URI)?;
+        let database = properties.get_string(DATABASE)?;
+        let collection = properties.get_string(COLLECTION)?;
+        let write_mode = WriteMode::try_from(&properties)?;
+
+        let mut builder = MongodbOutputFormatBuilder::new(
+            uri.as_str(),
+            database.as_str(),
+            collection.as_str(),
+            write_mode,
+        );
+
+        let w = properties.get_string(WRITE_CONCERN).ok();
+        let journal = properties.get_bool(JOURNAL).ok();
+        let w_timeout = properties.get_duration(WRITE_TIMEOUT).ok();
+        if w.is_some() || journal.is_some() || w_timeout.is_some() {
+            let w = w.map(|w| match w.parse::<u32>() {
+                Ok(nodes) => Acknowledgment::Nodes(nodes),
+                Err(_) => Acknowledgment::from(w),
+            });
+            let write_concern = WriteConcern::builder()
+                .w(w)
+                .journal(journal)
+                .w_timeout(w_timeout)
+                .build();
+            builder = builder.write_concern(write_concern);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(batch_interval) = properties.get_duration(BATCH_INTERVAL) {
+            builder = builder.batch_interval(batch_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
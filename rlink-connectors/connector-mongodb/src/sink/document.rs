@@ -0,0 +1,35 @@
+use mongodb::bson::spec::BinarySubtype;
+use mongodb::bson::{Binary, Bson, Document};
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+
+/// Convert the record to the document with the field names of the `schema`. The unsigned
+/// integers are stored as the wider signed integers, `UInt64` wraps over `i64::MAX`
+pub(crate) fn to_document(schema: &Schema, record: &mut Record) -> std::io::Result<Document> {
+    let reader = record.as_reader(schema.as_type_ids());
+
+    let mut document = Document::new();
+    for (i, field) in schema.fields().iter().enumerate() {
+        let value = match field.data_type() {
+            DataType::Boolean => Bson::Boolean(reader.get_bool(i)?),
+            DataType::Int8 => Bson::Int32(reader.get_i8(i)? as i32),
+            DataType::UInt8 => Bson::Int32(reader.get_u8(i)? as i32),
+            DataType::Int16 => Bson::Int32(reader.get_i16(i)? as i32),
+            DataType::UInt16 => Bson::Int32(reader.get_u16(i)? as i32),
+            DataType::Int32 => Bson::Int32(reader.get_i32(i)?),
+            DataType::UInt32 => Bson::Int64(reader.get_u32(i)? as i64),
+            DataType::Int64 => Bson::Int64(reader.get_i64(i)?),
+            DataType::UInt64 => Bson::Int64(reader.get_u64(i)? as i64),
+            DataType::Float32 => Bson::Double(reader.get_f32(i)? as f64),
+            DataType::Float64 => Bson::Double(reader.get_f64(i)?),
+            DataType::Binary => Bson::Binary(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: reader.get_binary(i)?.to_vec(),
+            }),
+            DataType::String => Bson::String(reader.get_str(i)?.to_string()),
+        };
+        document.insert(field.name(), value);
+    }
+
+    Ok(document)
+}
@@ -0,0 +1,6 @@
+pub mod builder;
+pub mod mode;
+pub mod output_format;
+
+pub(crate) mod document;
+pub(crate) mod writer;
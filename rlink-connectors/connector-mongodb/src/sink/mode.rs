@@ -0,0 +1,221 @@
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::options::WriteConcern;
+
+/// The error code of the duplicate key
+const DUPLICATE_KEY: i32 = 11000;
+
+/// How the documents are written to the collection
+#[derive(Clone, Debug, PartialEq)]
+pub enum WriteMode {
+    /// Insert the documents, the inserts of the existing `_id` are ignored. The replayed
+    /// documents are only deduplicated if the schema has the deterministic `_id` field
+    Insert,
+    /// Replace the document with the same value of the `key_field`, or insert if not found
+    Upsert { key_field: String },
+}
+
+/// The result of the write command
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WriteSummary {
+    /// documents inserted, matched or upserted
+    pub written: u64,
+    /// inserts ignored on the duplicate key
+    pub duplicates: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ReplyError {
+    /// the write concern is not satisfied, the batch can be retried
+    WriteConcern(String),
+    /// the documents are rejected
+    Write(String),
+}
+
+/// Build the `insert` or `update` command of a batch of the documents
+#[derive(Clone, Debug)]
+pub(crate) struct CommandBuilder {
+    collection: String,
+    mode: WriteMode,
+    write_concern: Option<Document>,
+}
+
+impl CommandBuilder {
+    pub fn new(
+        collection: &str,
+        mode: WriteMode,
+        write_concern: Option<&WriteConcern>,
+    ) -> anyhow::Result<Self> {
+        let write_concern = match write_concern {
+            Some(write_concern) => Some(mongodb::bson::to_document(write_concern)?),
+            None => None,
+        };
+        Ok(CommandBuilder {
+            collection: collection.to_string(),
+            mode,
+            write_concern,
+        })
+    }
+
+    /// The unordered bulk write of the `documents`, the other documents of the batch are still
+    /// written if one is rejected
+    pub fn build(&self, documents: &[Document]) -> anyhow::Result<Document> {
+        let mut command = match &self.mode {
+            WriteMode::Insert => doc! {
+                "insert": self.collection.as_str(),
+                "documents": documents.to_vec(),
+                "ordered": false,
+            },
+            WriteMode::Upsert { key_field } => {
+                let mut updates = Vec::with_capacity(documents.len());
+                for document in documents {
+                    let key = document.get(key_field).ok_or_else(|| {
+                        anyhow!("key field `{}` not found in the document", key_field)
+                    })?;
+                    let mut query = Document::new();
+                    query.insert(key_field.as_str(), key.clone());
+                    updates.push(doc! {
+                        "q": query,
+                        "u": document.clone(),
+                        "upsert": true,
+                    });
+                }
+                doc! {
+                    "update": self.collection.as_str(),
+                    "updates": updates,
+                    "ordered": false,
+                }
+            }
+        };
+
+        if let Some(write_concern) = &self.write_concern {
+            command.insert("writeConcern", write_concern.clone());
+        }
+        Ok(command)
+    }
+
+    /// Check the reply of the command, the duplicate key errors of the inserts are the
+    /// documents written before the restart
+    pub fn check_reply(&self, reply: &Document) -> Result<WriteSummary, ReplyError> {
+        let mut summary = WriteSummary {
+            written: get_u64(reply, "n"),
+            duplicates: 0,
+        };
+
+        if let Ok(write_errors) = reply.get_array("writeErrors") {
+            let mut messages = vec![];
+            for write_error in write_errors {
+                let write_error = match write_error {
+                    Bson::Document(write_error) => write_error,
+                    _ => continue,
+                };
+                let code = write_error.get_i32("code").unwrap_or_default();
+                if code == DUPLICATE_KEY && self.mode == WriteMode::Insert {
+                    summary.duplicates += 1;
+                } else {
+                    messages.push(format!(
+                        "index {}, code {}. {}",
+                        write_error.get_i32("index").unwrap_or_default(),
+                        code,
+                        write_error.get_str("errmsg").unwrap_or_default()
+                    ));
+                }
+            }
+            if !messages.is_empty() {
+                return Err(ReplyError::Write(messages.join("; ")));
+            }
+        }
+
+        if let Ok(write_concern_error) = reply.get_document("writeConcernError") {
+            return Err(ReplyError::WriteConcern(
+                write_concern_error
+                    .get_str("errmsg")
+                    .unwrap_or_default()
+                    .to_string(),
+            ));
+        }
+
+        Ok(summary)
+    }
+}
+
+fn get_u64(document: &Document, key: &str) -> u64 {
+    match document.get(key) {
+        Some(Bson::Int32(n)) => *n as u64,
+        Some(Bson::Int64(n)) => *n as u64,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::bson::doc;
+    use mongodb::options::{Acknowledgment, WriteConcern};
+
+    use crate::sink::mode::{CommandBuilder, ReplyError, WriteMode, WriteSummary};
+
+    #[test]
+    pub fn command_builder_test() {
+        let documents = vec![doc! {"id": 1, "name": "a"}, doc! {"id": 2, "name": "b"}];
+
+        let builder = CommandBuilder::new("users", WriteMode::Insert, None).unwrap();
+        assert_eq!(
+            builder.build(documents.as_slice()).unwrap(),
+            doc! {"insert": "users", "documents": documents.clone(), "ordered": false}
+        );
+
+        let write_concern = WriteConcern::builder().w(Acknowledgment::Majority).build();
+        let upsert = WriteMode::Upsert {
+            key_field: "id".to_string(),
+        };
+        let builder = CommandBuilder::new("users", upsert, Some(&write_concern)).unwrap();
+        assert_eq!(
+            builder.build(&documents[..1]).unwrap(),
+            doc! {
+                "update": "users",
+                "updates": [{"q": {"id": 1}, "u": {"id": 1, "name": "a"}, "upsert": true}],
+                "ordered": false,
+                "writeConcern": {"w": "majority"},
+            }
+        );
+
+        let upsert = WriteMode::Upsert {
+            key_field: "uid".to_string(),
+        };
+        let builder = CommandBuilder::new("users", upsert, None).unwrap();
+        assert!(builder.build(documents.as_slice()).is_err());
+    }
+
+    #[test]
+    pub fn check_reply_test() {
+        let reply = doc! {
+            "n": 1,
+            "writeErrors": [{"index": 1, "code": 11000, "errmsg": "E11000 duplicate key"}],
+            "ok": 1.0,
+        };
+        let insert = CommandBuilder::new("users", WriteMode::Insert, None).unwrap();
+        assert_eq!(
+            insert.check_reply(&reply),
+            Ok(WriteSummary {
+                written: 1,
+                duplicates: 1
+            })
+        );
+
+        let upsert = WriteMode::Upsert {
+            key_field: "id".to_string(),
+        };
+        let upsert = CommandBuilder::new("users", upsert, None).unwrap();
+        assert!(matches!(
+            upsert.check_reply(&reply),
+            Err(ReplyError::Write(_))
+        ));
+
+        let reply = doc! {"n": 2, "writeConcernError": {"code": 64, "errmsg": "waiting for replication timed out"}};
+        assert_eq!(
+            insert.check_reply(&reply),
+            Err(ReplyError::WriteConcern(
+                "waiting for replication timed out".to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use mongodb::options::WriteConcern;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
+
+use crate::sink::mode::{CommandBuilder, WriteMode};
+use crate::sink::writer::MongodbWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// Write the records to the collection as the documents with the field names of the schema.
+///
+/// The buffered documents are written before the checkpoint completes, with the upserts of
+/// the deterministic keys the replayed records overwrite the same documents.
+#[derive(NamedFunction)]
+pub struct MongodbOutputFormat {
+    uri: String,
+    database: String,
+    collection: String,
+    write_mode: WriteMode,
+    write_concern: Option<WriteConcern>,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+
+    writer: Option<BatchWriterHandle>,
+}
+
+impl MongodbOutputFormat {
+    pub fn new(
+        uri: String,
+        database: String,
+        collection: String,
+        write_mode: WriteMode,
+        buffer_size: usize,
+    ) -> Self {
+        MongodbOutputFormat {
+            uri,
+            database,
+            collection,
+            write_mode,
+            write_concern: None,
+            buffer_size,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            writer: None,
+        }
+    }
+
+    pub fn write_concern(mut self, write_concern: WriteConcern) -> Self {
+        self.write_concern = Some(write_concern);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[async_trait]
+impl OutputFormat for MongodbOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let schema: Schema = context.input_schema.clone().into();
+        if let WriteMode::Upsert { key_field } = &self.write_mode {
+            if schema.index_of(key_field.as_str()).is_none() {
+                return Err(core::Error::from(format!(
+                    "key field `{}` not found in the input schema",
+                    key_field
+                )));
+            }
+        }
+
+        let command_builder = CommandBuilder::new(
+            self.collection.as_str(),
+            self.write_mode.clone(),
+            self.write_concern.as_ref(),
+        )?;
+
+        let writer = MongodbWriter::new(
+            self.uri.clone(),
+            self.database.clone(),
+            schema,
+            command_builder,
+            self.max_retries,
+        );
+        let writer = BatchWriterThread::new("Mongodb", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), context.task_id.to_tags(), self.buffer_size);
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write mongodb record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for MongodbOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered documents are written in a command on the checkpoint
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
+        None
+    }
+}
@@ -0,0 +1,115 @@
+use mongodb::bson::Document;
+use mongodb::{Client, Database};
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use tokio::time::Instant;
+
+use crate::sink::document::to_document;
+use crate::sink::mode::{CommandBuilder, ReplyError};
+
+pub(crate) struct MongodbWriter {
+    uri: String,
+    database_name: String,
+    schema: Schema,
+    command_builder: CommandBuilder,
+    max_retries: usize,
+
+    database: Option<Database>,
+    documents: Vec<Document>,
+}
+
+impl MongodbWriter {
+    pub fn new(
+        uri: String,
+        database_name: String,
+        schema: Schema,
+        command_builder: CommandBuilder,
+        max_retries: usize,
+    ) -> Self {
+        MongodbWriter {
+            uri,
+            database_name,
+            schema,
+            command_builder,
+            max_retries,
+            database: None,
+            documents: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchWriter for MongodbWriter {
+    async fn open(&mut self) -> anyhow::Result<()> {
+        let client = Client::with_uri_str(self.uri.as_str())
+            .await
+            .map_err(|e| anyhow!("connect to mongodb error. {}", e))?;
+        self.database = Some(client.database(self.database_name.as_str()));
+        Ok(())
+    }
+
+    fn push(&mut self, mut record: Record) -> anyhow::Result<()> {
+        let document = to_document(&self.schema, &mut record)?;
+        self.documents.push(document);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Write the buffered documents in a command, the connection errors and the write concern
+    /// errors are retried, the rejected documents fail the flush
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let database = self.database.as_ref().unwrap();
+        let command = self.command_builder.build(self.documents.as_slice())?;
+
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            let error = match database.run_command(command.clone(), None).await {
+                Ok(reply) => match self.command_builder.check_reply(&reply) {
+                    Ok(summary) => {
+                        metrics.flushed(summary.written, begin.elapsed());
+                        metrics.dropped(summary.duplicates);
+                        self.documents.clear();
+                        return Ok(());
+                    }
+                    Err(ReplyError::WriteConcern(e)) => format!("write concern error. {}", e),
+                    Err(ReplyError::Write(e)) => {
+                        metrics.failure();
+                        return Err(anyhow!(
+                            "write {} documents error. {}",
+                            self.documents.len(),
+                            e
+                        ));
+                    }
+                },
+                Err(e) => e.to_string(),
+            };
+
+            if attempt >= self.max_retries {
+                metrics.failure();
+                return Err(anyhow!(
+                    "write {} documents error after {} retries. {}",
+                    self.documents.len(),
+                    self.max_retries,
+                    error
+                ));
+            }
+
+            attempt += 1;
+            metrics.retry();
+            warn!(
+                "write {} documents error, retry({}/{}). {}",
+                self.documents.len(),
+                attempt,
+                self.max_retries,
+                error
+            );
+            tokio::time::sleep(backoff(attempt)).await;
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use serbuffer_gen::{Codegen, DataType::*, SchemaBuilder};
+
+fn main() {
+    Codegen::out_dir("buffer_gen")
+        .schema(
+            SchemaBuilder::new("MqttMessage")
+                .field("timestamp", I64)
+                .field("topic", STRING)
+                .field("payload", BINARY)
+                .field("qos", I32),
+        )
+        .gen()
+        .expect("buffer gen error");
+}
@@ -0,0 +1,170 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod source;
+
+pub mod buffer_gen {
+    include!(concat!(env!("OUT_DIR"), "/buffer_gen/mod.rs"));
+}
+
+pub use source::input_format::MqttInputFormat;
+
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::element::Record;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::mqtt_message;
+
+pub const MQTT: &str = "mqtt";
+pub const HOST: &str = "host";
+pub const PORT: &str = "port";
+pub const CLIENT_ID: &str = "client.id";
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+pub const VERSION: &str = "version";
+pub const KEEP_ALIVE: &str = "keep.alive";
+
+pub const TOPICS: &str = "topics";
+pub const QOS: &str = "qos";
+pub const SHARE_GROUP: &str = "share.group";
+pub const SESSION_EXPIRY: &str = "session.expiry";
+pub const RECONNECT_INTERVAL: &str = "reconnect.interval";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const INPUT_FORMAT_FN_NAME_DEFAULT: &str = "MqttInputFormat";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 50000;
+
+/// The protocol version of the connection
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MqttVersion {
+    #[default]
+    V311,
+    V5,
+}
+
+impl TryFrom<&str> for MqttVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "3.1.1" | "3" | "4" => Ok(MqttVersion::V311),
+            "5" | "5.0" => Ok(MqttVersion::V5),
+            _ => Err(anyhow!("unknown mqtt version `{}`", value)),
+        }
+    }
+}
+
+/// Connection settings of the broker
+#[derive(Clone)]
+pub struct MqttClientConfig {
+    host: String,
+    port: u16,
+    /// the prefix of the client ids, each subtask connects as `{client_id}-{task_number}`
+    client_id: String,
+    credentials: Option<(String, String)>,
+    version: MqttVersion,
+    keep_alive: Duration,
+}
+
+impl MqttClientConfig {
+    pub fn new(host: &str, port: u16, client_id: &str) -> Self {
+        MqttClientConfig {
+            host: host.to_string(),
+            port,
+            client_id: client_id.to_string(),
+            credentials: None,
+            version: MqttVersion::default(),
+            keep_alive: Duration::from_secs(30),
+        }
+    }
+
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    pub fn version(mut self, version: MqttVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn keep_alive(mut self, keep_alive: Duration) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// The client id of the subtask, it's kept across the restarts to resume the session
+    pub(crate) fn task_client_id(&self, task_number: u16) -> String {
+        format!("{}-{}", self.client_id, task_number)
+    }
+}
+
+impl TryFrom<Properties> for MqttClientConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let host = properties.get_string(HOST)?;
+        let port = properties.get_u16(PORT).unwrap_or(1883);
+        let client_id = properties.get_string(CLIENT_ID)?;
+
+        let mut client_config = MqttClientConfig::new(host.as_str(), port, client_id.as_str());
+        if let Ok(username) = properties.get_string(USERNAME) {
+            let password = properties.get_string(PASSWORD).unwrap_or_default();
+            client_config = client_config.credentials(username.as_str(), password.as_str());
+        }
+        if let Ok(version) = properties.get_string(VERSION) {
+            client_config = client_config.version(MqttVersion::try_from(version.as_str())?);
+        }
+        if let Ok(keep_alive) = properties.get_duration(KEEP_ALIVE) {
+            client_config = client_config.keep_alive(keep_alive);
+        }
+        Ok(client_config)
+    }
+}
+
+impl Debug for MqttClientConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttClientConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("client_id", &self.client_id)
+            .field(
+                "username",
+                &self.credentials.as_ref().map(|(username, _)| username),
+            )
+            .field("version", &self.version)
+            .field("keep_alive", &self.keep_alive)
+            .finish()
+    }
+}
+
+pub fn build_mqtt_record(
+    timestamp: i64,
+    topic: &str,
+    payload: &[u8],
+    qos: i32,
+) -> Result<Record, std::io::Error> {
+    let message = mqtt_message::Entity {
+        timestamp,
+        topic,
+        payload,
+        qos,
+    };
+
+    // 20 = 8(len(payload) + len(topic)) + 8(len(timestamp)) + 4(len(qos))
+    let capacity = payload.len() + topic.len() + 20;
+    let mut record = Record::with_capacity(capacity);
+
+    message.to_buffer(record.as_buffer()).unwrap();
+
+    Ok(record)
+}
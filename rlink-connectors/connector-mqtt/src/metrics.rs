@@ -0,0 +1,2 @@
+pub const CONSUMER_RECEIVED: &str = "Mqtt.Consumer.Received";
+pub const CONSUMER_RECONNECTS: &str = "Mqtt.Consumer.Reconnects";
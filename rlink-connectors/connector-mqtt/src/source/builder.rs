@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::{Properties, PARALLELISM};
+
+use crate::buffer_gen::mqtt_message;
+use crate::source::deserializer::{
+    DefaultMqttRecordDeserializer, DefaultMqttRecordDeserializerBuilder,
+    MqttRecordDeserializerBuilder,
+};
+use crate::source::subscription::{QualityOfService, Subscription};
+use crate::{
+    MqttClientConfig, MqttInputFormat, BUFFER_SIZE, INPUT_FORMAT_FN_NAME_DEFAULT, MQTT, QOS,
+    RECONNECT_INTERVAL, SESSION_EXPIRY, SHARE_GROUP, SOURCE_CHANNEL_SIZE, TOPICS,
+};
+
+#[derive(Debug)]
+pub struct MqttInputFormatBuilder {
+    fn_name: Option<String>,
+    parallelism: u16,
+    client_config: MqttClientConfig,
+    subscription: Subscription,
+    reconnect_interval: Option<Duration>,
+    buffer_size: Option<usize>,
+}
+
+impl MqttInputFormatBuilder {
+    pub fn new(
+        client_config: MqttClientConfig,
+        subscription: Subscription,
+        parallelism: u16,
+    ) -> Self {
+        MqttInputFormatBuilder {
+            fn_name: None,
+            parallelism,
+            client_config,
+            subscription,
+            reconnect_interval: None,
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    /// How long to wait before reconnecting the broker, default 3s
+    pub fn reconnect_interval(mut self, interval: Duration) -> Self {
+        self.reconnect_interval = Some(interval);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn MqttRecordDeserializerBuilder>>,
+    ) -> MqttInputFormat {
+        info!("build mqtt source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn MqttRecordDeserializerBuilder> =
+                Box::new(DefaultMqttRecordDeserializerBuilder::<
+                    DefaultMqttRecordDeserializer,
+                >::new(FnSchema::from(
+                    &mqtt_message::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        let mut input_format = MqttInputFormat::new(
+            self.client_config,
+            self.subscription,
+            buffer_size,
+            deserializer_builder,
+            self.parallelism,
+            fn_name,
+        );
+        if let Some(reconnect_interval) = self.reconnect_interval {
+            input_format = input_format.reconnect_interval(reconnect_interval);
+        }
+        input_format
+    }
+}
+
+impl TryFrom<Properties> for MqttInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let parallelism = properties.get_u16(PARALLELISM)?;
+
+        let client_config = MqttClientConfig::try_from(properties.to_sub_properties(MQTT))?;
+
+        let topics: Vec<String> = properties
+            .get_string(TOPICS)?
+            .trim()
+            .split(',')
+            .map(|x| x.to_string())
+            .collect();
+        let mut subscription = Subscription::new(topics);
+        if let Ok(qos) = properties.get_string(QOS) {
+            subscription = subscription.qos(QualityOfService::try_from(qos.as_str())?);
+        }
+        if let Ok(share_group) = properties.get_string(SHARE_GROUP) {
+            subscription = subscription.share_group(share_group.as_str());
+        }
+        if let Ok(session_expiry) = properties.get_duration(SESSION_EXPIRY) {
+            // a zero expiry disables the session resumption
+            let session_expiry = Some(session_expiry).filter(|x| !x.is_zero());
+            subscription = subscription.session_expiry(session_expiry);
+        }
+
+        let mut builder = MqttInputFormatBuilder::new(client_config, subscription, parallelism);
+
+        builder = builder.fn_name(properties.name());
+
+        if let Ok(reconnect_interval) = properties.get_duration(RECONNECT_INTERVAL) {
+            builder = builder.reconnect_interval(reconnect_interval);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
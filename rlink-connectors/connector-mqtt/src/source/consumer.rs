@@ -0,0 +1,222 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rlink::channel::sender::ChannelSender;
+use rlink::core::element::Record;
+use rlink::core::runtime::JobId;
+use rlink::metrics::{register_counter, Counter, Tag};
+use rumqttc::v5::mqttbytes::v5::{ConnectProperties, Packet as PacketV5};
+use rumqttc::{Event, Packet};
+
+use crate::metrics::{CONSUMER_RECEIVED, CONSUMER_RECONNECTS};
+use crate::source::deserializer::MqttRecordDeserializer;
+use crate::source::subscription::Subscription;
+use crate::{MqttClientConfig, MqttVersion};
+
+/// the capacity of the outgoing requests of the client
+const CLIENT_CAPACITY: usize = 100;
+
+pub(crate) async fn create_mqtt_consumer(mut mqtt_consumer: MqttConsumerThread) {
+    tokio::spawn(async move {
+        match mqtt_consumer.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!("run consumer error. {}", e);
+            }
+        }
+    });
+}
+
+pub(crate) struct MqttConsumerThread {
+    job_id: JobId,
+    task_number: u16,
+
+    client_config: MqttClientConfig,
+    subscription: Subscription,
+    reconnect_interval: Duration,
+
+    sender: ChannelSender<Record>,
+    deserializer: Box<dyn MqttRecordDeserializer>,
+}
+
+impl MqttConsumerThread {
+    pub fn new(
+        job_id: JobId,
+        task_number: u16,
+        client_config: MqttClientConfig,
+        subscription: Subscription,
+        sender: ChannelSender<Record>,
+        deserializer: Box<dyn MqttRecordDeserializer>,
+    ) -> Self {
+        MqttConsumerThread {
+            job_id,
+            task_number,
+            client_config,
+            subscription,
+            reconnect_interval: Duration::from_secs(3),
+            sender,
+            deserializer,
+        }
+    }
+
+    pub fn reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = reconnect_interval;
+        self
+    }
+
+    fn tags(&self) -> Vec<Tag> {
+        vec![
+            Tag::new("job_id", *self.job_id),
+            Tag::new("task_number", self.task_number),
+            Tag::new("topic", self.subscription.topics().join(",")),
+        ]
+    }
+
+    async fn handle_publish(&mut self, topic: &str, payload: &[u8], qos: i32) {
+        // the publish time is not carried by mqtt, the receive time is used
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_millis() as i64)
+            .unwrap_or(0);
+
+        let records = self
+            .deserializer
+            .deserialize(timestamp, topic, payload, qos);
+        for record in records {
+            self.sender
+                .send(record)
+                .await
+                .expect("mqtt consumer handover `Disconnected`");
+        }
+    }
+
+    async fn on_disconnected<E: std::fmt::Display>(&self, e: E, reconnects: &Counter) {
+        reconnects.increment(1);
+        warn!(
+            "mqtt connection error, reconnect after {:?}. job_id: {}, task_num: {}, error: {}",
+            self.reconnect_interval, *self.job_id, self.task_number, e
+        );
+        tokio::time::sleep(self.reconnect_interval).await;
+    }
+
+    async fn run_v311(&mut self) -> anyhow::Result<()> {
+        let client_id = self.client_config.task_client_id(self.task_number);
+        let mut options = rumqttc::MqttOptions::new(
+            client_id,
+            self.client_config.host.as_str(),
+            self.client_config.port,
+        );
+        options.set_keep_alive(self.client_config.keep_alive);
+        // the session is resumed after the reconnection if the broker keeps it
+        options.set_clean_session(self.subscription.get_session_expiry().is_none());
+        if let Some((username, password)) = &self.client_config.credentials {
+            options.set_credentials(username.as_str(), password.as_str());
+        }
+
+        let (client, mut event_loop) = rumqttc::AsyncClient::new(options, CLIENT_CAPACITY);
+
+        let received = register_counter(CONSUMER_RECEIVED, self.tags());
+        let reconnects = register_counter(CONSUMER_RECONNECTS, self.tags());
+        while !self.sender.is_closed() {
+            match event_loop.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(conn_ack))) => {
+                    info!(
+                        "mqtt connected, session present: {}. job_id: {}, task_num: {}",
+                        conn_ack.session_present, *self.job_id, self.task_number
+                    );
+                    if !conn_ack.session_present {
+                        for filter in self.subscription.filters() {
+                            client
+                                .subscribe(filter, self.subscription.get_qos().v311())
+                                .await?;
+                        }
+                    }
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    received.increment(1);
+                    self.handle_publish(
+                        publish.topic.as_str(),
+                        publish.payload.as_ref(),
+                        publish.qos as i32,
+                    )
+                    .await;
+                }
+                Ok(_) => {}
+                // the next poll reconnects the broker
+                Err(e) => self.on_disconnected(e, &reconnects).await,
+            }
+        }
+
+        client.disconnect().await?;
+        Ok(())
+    }
+
+    async fn run_v5(&mut self) -> anyhow::Result<()> {
+        let client_id = self.client_config.task_client_id(self.task_number);
+        let mut options = rumqttc::v5::MqttOptions::new(
+            client_id,
+            self.client_config.host.as_str(),
+            self.client_config.port,
+        );
+        options.set_keep_alive(self.client_config.keep_alive);
+        if let Some((username, password)) = &self.client_config.credentials {
+            options.set_credentials(username.as_str(), password.as_str());
+        }
+        // the session is resumed after the reconnection until it's expired
+        let session_expiry = self.subscription.get_session_expiry();
+        options.set_clean_start(session_expiry.is_none());
+        options.set_connect_properties(ConnectProperties {
+            session_expiry_interval: session_expiry.map(|x| x.as_secs() as u32),
+            ..Default::default()
+        });
+
+        let (client, mut event_loop) = rumqttc::v5::AsyncClient::new(options, CLIENT_CAPACITY);
+
+        let received = register_counter(CONSUMER_RECEIVED, self.tags());
+        let reconnects = register_counter(CONSUMER_RECONNECTS, self.tags());
+        while !self.sender.is_closed() {
+            match event_loop.poll().await {
+                Ok(rumqttc::v5::Event::Incoming(PacketV5::ConnAck(conn_ack))) => {
+                    info!(
+                        "mqtt connected, session present: {}. job_id: {}, task_num: {}",
+                        conn_ack.session_present, *self.job_id, self.task_number
+                    );
+                    if !conn_ack.session_present {
+                        for filter in self.subscription.filters() {
+                            client
+                                .subscribe(filter, self.subscription.get_qos().v5())
+                                .await?;
+                        }
+                    }
+                }
+                Ok(rumqttc::v5::Event::Incoming(PacketV5::Publish(publish))) => {
+                    received.increment(1);
+                    let topic = String::from_utf8_lossy(publish.topic.as_ref()).to_string();
+                    self.handle_publish(
+                        topic.as_str(),
+                        publish.payload.as_ref(),
+                        publish.qos as i32,
+                    )
+                    .await;
+                }
+                Ok(_) => {}
+                // the next poll reconnects the broker
+                Err(e) => self.on_disconnected(e, &reconnects).await,
+            }
+        }
+
+        client.disconnect().await?;
+        Ok(())
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        info!(
+            "create mqtt consumer. config: {:?}, subscription: {:?}, job_id: {}, task_num: {}",
+            self.client_config, self.subscription, *self.job_id, self.task_number
+        );
+
+        match self.client_config.version {
+            MqttVersion::V311 => self.run_v311().await,
+            MqttVersion::V5 => self.run_v5().await,
+        }
+    }
+}
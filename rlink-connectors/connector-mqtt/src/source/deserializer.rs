@@ -0,0 +1,66 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+
+use crate::build_mqtt_record;
+
+pub trait MqttRecordDeserializer: Sync + Send {
+    fn deserialize(&mut self, timestamp: i64, topic: &str, payload: &[u8], qos: i32)
+        -> Vec<Record>;
+}
+
+pub trait MqttRecordDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn MqttRecordDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+#[derive(Default)]
+pub struct DefaultMqttRecordDeserializer {}
+
+impl MqttRecordDeserializer for DefaultMqttRecordDeserializer {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        topic: &str,
+        payload: &[u8],
+        qos: i32,
+    ) -> Vec<Record> {
+        let record = build_mqtt_record(timestamp, topic, payload, qos)
+            .expect("mqtt message writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultMqttRecordDeserializerBuilder<T>
+where
+    T: Default + MqttRecordDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultMqttRecordDeserializerBuilder<T>
+where
+    T: Default + MqttRecordDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultMqttRecordDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> MqttRecordDeserializerBuilder for DefaultMqttRecordDeserializerBuilder<T>
+where
+    T: Default + MqttRecordDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn MqttRecordDeserializer> {
+        let t: Box<dyn MqttRecordDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::properties::Properties;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::Tag;
+
+use crate::source::consumer::{create_mqtt_consumer, MqttConsumerThread};
+use crate::source::deserializer::MqttRecordDeserializerBuilder;
+use crate::source::stream::MqttRecordStream;
+use crate::source::subscription::Subscription;
+use crate::MqttClientConfig;
+
+pub struct MqttInputFormat {
+    name: String,
+    parallelism: u16,
+
+    client_config: MqttClientConfig,
+    subscription: Subscription,
+    reconnect_interval: Duration,
+
+    task_id: TaskId,
+    buffer_size: usize,
+    tags: Vec<Tag>,
+
+    deserializer_builder: Arc<dyn MqttRecordDeserializerBuilder>,
+    schema: FnSchema,
+}
+
+impl MqttInputFormat {
+    pub fn new(
+        client_config: MqttClientConfig,
+        subscription: Subscription,
+        buffer_size: usize,
+        deserializer_builder: Box<dyn MqttRecordDeserializerBuilder>,
+        parallelism: u16,
+        fn_name: String,
+    ) -> Self {
+        let schema = deserializer_builder.schema();
+        MqttInputFormat {
+            name: fn_name,
+            parallelism,
+            client_config,
+            subscription,
+            reconnect_interval: Duration::from_secs(3),
+            task_id: Default::default(),
+            buffer_size,
+            tags: vec![],
+            deserializer_builder: Arc::from(deserializer_builder),
+            schema,
+        }
+    }
+
+    pub fn reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = reconnect_interval;
+        self
+    }
+}
+
+impl NamedFunction for MqttInputFormat {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl InputFormat for MqttInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        info!("mqtt source open");
+
+        self.task_id = context.task_id.clone();
+        self.tags
+            .push(Tag::new("topic", self.subscription.topics().join(",")));
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("MqttSource_Handover", self.tags.clone(), self.buffer_size);
+
+        let mqtt_consumer = MqttConsumerThread::new(
+            self.task_id.job_id(),
+            self.task_id.task_number(),
+            self.client_config.clone(),
+            self.subscription.clone(),
+            sender,
+            self.deserializer_builder.build(),
+        )
+        .reconnect_interval(self.reconnect_interval);
+        create_mqtt_consumer(mqtt_consumer).await;
+
+        Box::pin(MqttRecordStream::new(receiver))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+/// The messages can't be replayed by the broker, there is no state to checkpoint
+#[async_trait]
+impl CheckpointFunction for MqttInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+impl InputSplitSource for MqttInputFormat {
+    fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
+        info!(
+            "mqtt config {:?}, subscription: {:?}",
+            self.client_config, self.subscription
+        );
+
+        // every subtask receives all messages of the non-shared subscription
+        if min_num_splits > 1 && !self.subscription.is_shared() {
+            return Err(rlink::core::Error::from(
+                "the shared subscription is required by the mqtt source with parallelism > 1",
+            ));
+        }
+
+        let input_splits = (0..min_num_splits)
+            .map(|index| InputSplit::new(index, Properties::new()))
+            .collect();
+        Ok(input_splits)
+    }
+}
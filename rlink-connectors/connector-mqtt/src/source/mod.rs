@@ -0,0 +1,6 @@
+pub mod builder;
+pub mod consumer;
+pub mod deserializer;
+pub mod input_format;
+pub mod stream;
+pub mod subscription;
@@ -0,0 +1,32 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::{Element, Record};
+use rlink::core::function::ElementStream;
+
+/// Simulate a MQTT subscription as an iterator.
+pub struct MqttRecordStream {
+    receiver: ChannelReceiver<Record>,
+}
+
+impl MqttRecordStream {
+    pub fn new(receiver: ChannelReceiver<Record>) -> Self {
+        MqttRecordStream { receiver }
+    }
+}
+
+impl ElementStream for MqttRecordStream {}
+
+impl Stream for MqttRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.as_mut().receiver.poll_recv(cx) {
+            Poll::Ready(Some(record)) => Poll::Ready(Some(Element::Record(record))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// The delivery guarantee of the subscriptions
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum QualityOfService {
+    /// QoS 0, the messages are lost while disconnected
+    AtMostOnce,
+    /// QoS 1, the messages are queued by the broker while disconnected if the session is kept
+    #[default]
+    AtLeastOnce,
+}
+
+impl QualityOfService {
+    pub(crate) fn v311(&self) -> rumqttc::QoS {
+        match self {
+            QualityOfService::AtMostOnce => rumqttc::QoS::AtMostOnce,
+            QualityOfService::AtLeastOnce => rumqttc::QoS::AtLeastOnce,
+        }
+    }
+
+    pub(crate) fn v5(&self) -> rumqttc::v5::mqttbytes::QoS {
+        match self {
+            QualityOfService::AtMostOnce => rumqttc::v5::mqttbytes::QoS::AtMostOnce,
+            QualityOfService::AtLeastOnce => rumqttc::v5::mqttbytes::QoS::AtLeastOnce,
+        }
+    }
+}
+
+impl TryFrom<&str> for QualityOfService {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "0" => Ok(QualityOfService::AtMostOnce),
+            "1" => Ok(QualityOfService::AtLeastOnce),
+            _ => Err(anyhow!("unsupported mqtt qos `{}`, expect 0 or 1", value)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Subscription {
+    topics: Vec<String>,
+    qos: QualityOfService,
+    /// the messages are load balanced across the subtasks by the shared subscription
+    share_group: Option<String>,
+    /// how long the broker keeps the session after the disconnection, `None` to start a
+    /// clean session on every connection
+    session_expiry: Option<Duration>,
+}
+
+impl Subscription {
+    pub fn new(topics: Vec<String>) -> Self {
+        Subscription {
+            topics,
+            qos: QualityOfService::default(),
+            share_group: None,
+            session_expiry: Some(Duration::from_secs(3600)),
+        }
+    }
+
+    pub fn qos(mut self, qos: QualityOfService) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    pub fn share_group(mut self, share_group: &str) -> Self {
+        self.share_group = Some(share_group.to_string());
+        self
+    }
+
+    pub fn session_expiry(mut self, session_expiry: Option<Duration>) -> Self {
+        self.session_expiry = session_expiry;
+        self
+    }
+
+    pub fn topics(&self) -> &[String] {
+        self.topics.as_slice()
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.share_group.is_some()
+    }
+
+    pub(crate) fn get_qos(&self) -> QualityOfService {
+        self.qos
+    }
+
+    pub(crate) fn get_session_expiry(&self) -> Option<Duration> {
+        self.session_expiry
+    }
+
+    /// The topic filters to subscribe, prefixed by `$share/{group}/` in the shared subscription
+    pub(crate) fn filters(&self) -> Vec<String> {
+        self.topics
+            .iter()
+            .map(|topic| match &self.share_group {
+                Some(group) => format!("$share/{}/{}", group, topic),
+                None => topic.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::subscription::Subscription;
+
+    #[test]
+    pub fn shared_filters_test() {
+        let topics = vec!["sensors/+/temperature".to_string(), "alarms/#".to_string()];
+
+        let subscription = Subscription::new(topics.clone());
+        assert_eq!(subscription.filters(), topics);
+
+        let subscription = Subscription::new(topics).share_group("rlink");
+        assert_eq!(
+            subscription.filters(),
+            vec![
+                "$share/rlink/sensors/+/temperature".to_string(),
+                "$share/rlink/alarms/#".to_string()
+            ]
+        );
+    }
+}
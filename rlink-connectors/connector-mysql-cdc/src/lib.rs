@@ -0,0 +1,153 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod source;
+
+pub mod buffer_gen {
+    include!(concat!(env!("OUT_DIR"), "/buffer_gen/mod.rs"));
+}
+
+pub use source::input_format::MysqlCdcInputFormat;
+
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use mysql_async::{Conn, OptsBuilder};
+use rlink::core::element::Record;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::mysql_change_record;
+
+pub const MYSQL: &str = "mysql";
+pub const HOST: &str = "host";
+pub const PORT: &str = "port";
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+pub const SERVER_ID: &str = "server.id";
+
+pub const TABLES: &str = "tables";
+pub const STARTUP_MODE: &str = "startup.mode";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const INPUT_FORMAT_FN_NAME_DEFAULT: &str = "MysqlCdcInputFormat";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 50000;
+pub const DEFAULT_PORT: u16 = 3306;
+pub const DEFAULT_SERVER_ID: u32 = 5701;
+
+/// Connection settings of the source. The `server_id` identifies the binlog client as a replica,
+/// it must be unique among the replicas of the server
+#[derive(Clone)]
+pub struct MysqlClientConfig {
+    host: String,
+    port: u16,
+    username: String,
+    password: Option<String>,
+    server_id: u32,
+}
+
+impl MysqlClientConfig {
+    pub fn new(host: &str, port: u16, username: &str) -> Self {
+        MysqlClientConfig {
+            host: host.to_string(),
+            port,
+            username: username.to_string(),
+            password: None,
+            server_id: DEFAULT_SERVER_ID,
+        }
+    }
+
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    pub fn server_id(mut self, server_id: u32) -> Self {
+        self.server_id = server_id;
+        self
+    }
+
+    pub(crate) fn get_server_id(&self) -> u32 {
+        self.server_id
+    }
+
+    pub(crate) async fn connect(&self) -> anyhow::Result<Conn> {
+        let opts = OptsBuilder::default()
+            .ip_or_hostname(self.host.as_str())
+            .tcp_port(self.port)
+            .user(Some(self.username.as_str()))
+            .pass(self.password.as_ref());
+
+        Conn::new(opts)
+            .await
+            .map_err(|e| anyhow!("connect to mysql {}:{} error. {}", self.host, self.port, e))
+    }
+}
+
+impl TryFrom<Properties> for MysqlClientConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let host = properties.get_string(HOST)?;
+        let port = properties.get_u16(PORT).unwrap_or(DEFAULT_PORT);
+        let username = properties.get_string(USERNAME)?;
+
+        let mut client_config = MysqlClientConfig::new(host.as_str(), port, username.as_str());
+        if let Ok(password) = properties.get_string(PASSWORD) {
+            client_config = client_config.password(password.as_str());
+        }
+        if let Ok(server_id) = properties.get_u32(SERVER_ID) {
+            client_config = client_config.server_id(server_id);
+        }
+        Ok(client_config)
+    }
+}
+
+impl Debug for MysqlClientConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MysqlClientConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("server_id", &self.server_id)
+            .finish()
+    }
+}
+
+pub fn build_mysql_change_record(
+    timestamp: i64,
+    op: &str,
+    database: &str,
+    table: &str,
+    before: &str,
+    after: &str,
+    position: &str,
+) -> Result<Record, std::io::Error> {
+    let message = mysql_change_record::Entity {
+        timestamp,
+        op,
+        database,
+        table,
+        before,
+        after,
+        position,
+    };
+
+    // 32 = 24(len(op) + len(database) + len(table) + len(before) + len(after) + len(position))
+    //    + 8(len(timestamp))
+    let capacity =
+        op.len() + database.len() + table.len() + before.len() + after.len() + position.len() + 32;
+    let mut record = Record::with_capacity(capacity);
+
+    message.to_buffer(record.as_buffer()).unwrap();
+
+    Ok(record)
+}
@@ -0,0 +1,38 @@
+use rlink::metrics::{register_counter, register_gauge, Counter, Gauge, Tag};
+
+pub const SNAPSHOT_ROWS: &str = "MysqlCdc.Snapshot.Rows";
+pub const BINLOG_ROWS: &str = "MysqlCdc.Binlog.Rows";
+pub const BINLOG_DELAY: &str = "MysqlCdc.Binlog.Delay";
+
+/// Metrics of the cdc reader, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// rows read by the initial snapshot
+    snapshot_rows: Counter,
+    /// row changes read from the binlog
+    binlog_rows: Counter,
+    /// millis between the event written to the binlog and read by the source
+    binlog_delay: Gauge,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            snapshot_rows: register_counter(SNAPSHOT_ROWS, tags.clone()),
+            binlog_rows: register_counter(BINLOG_ROWS, tags.clone()),
+            binlog_delay: register_gauge(BINLOG_DELAY, tags),
+        }
+    }
+
+    pub fn snapshot_row(&self) {
+        self.snapshot_rows.increment(1);
+    }
+
+    pub fn binlog_row(&self) {
+        self.binlog_rows.increment(1);
+    }
+
+    pub fn binlog_delay(&self, delay: i64) {
+        self.binlog_delay.set(delay.max(0) as f64);
+    }
+}
@@ -0,0 +1,114 @@
+use std::convert::TryFrom;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::mysql_change_record;
+use crate::source::deserializer::{
+    DefaultMysqlChangeDeserializer, DefaultMysqlChangeDeserializerBuilder,
+    MysqlChangeDeserializerBuilder,
+};
+use crate::source::startup_mode::StartupMode;
+use crate::source::table::TableId;
+use crate::{
+    MysqlCdcInputFormat, MysqlClientConfig, BUFFER_SIZE, INPUT_FORMAT_FN_NAME_DEFAULT, MYSQL,
+    SOURCE_CHANNEL_SIZE, STARTUP_MODE, TABLES,
+};
+
+#[derive(Debug)]
+pub struct MysqlCdcInputFormatBuilder {
+    fn_name: Option<String>,
+    client_config: MysqlClientConfig,
+    tables: Vec<TableId>,
+    startup_mode: StartupMode,
+    buffer_size: Option<usize>,
+}
+
+impl MysqlCdcInputFormatBuilder {
+    /// Capture the row changes of the `tables`. The server must write the binlog in the `ROW`
+    /// format with the `FULL` row image to carry the before images
+    pub fn new(client_config: MysqlClientConfig, tables: Vec<TableId>) -> Self {
+        MysqlCdcInputFormatBuilder {
+            fn_name: None,
+            client_config,
+            tables,
+            startup_mode: StartupMode::default(),
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    pub fn startup_mode(mut self, startup_mode: StartupMode) -> Self {
+        self.startup_mode = startup_mode;
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn MysqlChangeDeserializerBuilder>>,
+    ) -> MysqlCdcInputFormat {
+        info!("build mysql cdc source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn MysqlChangeDeserializerBuilder> =
+                Box::new(DefaultMysqlChangeDeserializerBuilder::<
+                    DefaultMysqlChangeDeserializer,
+                >::new(FnSchema::from(
+                    &mysql_change_record::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        MysqlCdcInputFormat::new(
+            self.client_config,
+            self.tables,
+            buffer_size,
+            deserializer_builder,
+            fn_name,
+        )
+        .startup_mode(self.startup_mode)
+    }
+}
+
+impl TryFrom<Properties> for MysqlCdcInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let client_config = MysqlClientConfig::try_from(properties.to_sub_properties(MYSQL))?;
+
+        let mut tables = Vec::new();
+        for table in properties.get_string(TABLES)?.split(',') {
+            tables.push(TableId::try_from(table)?);
+        }
+
+        let mut builder = MysqlCdcInputFormatBuilder::new(client_config, tables);
+
+        builder = builder.fn_name(properties.name());
+
+        if let Ok(startup_mode) = properties.get_string(STARTUP_MODE) {
+            let startup_mode = StartupMode::try_from(startup_mode.as_str())?;
+            builder = builder.startup_mode(startup_mode);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,128 @@
+use mysql_async::Value;
+use serde_json::{Map, Number};
+
+/// The kind of a row change, named as the `op` of Debezium
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    /// a row read by the initial snapshot
+    Read,
+    Create,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Read => "r",
+            Operation::Create => "c",
+            Operation::Update => "u",
+            Operation::Delete => "d",
+        }
+    }
+}
+
+/// A row change of a table, the row images are the column values by the column names
+#[derive(Clone, Debug)]
+pub struct ChangeEvent {
+    /// millis the change is committed, or the snapshot is taken
+    pub timestamp: i64,
+    pub op: Operation,
+    pub database: String,
+    pub table: String,
+    /// the row before the change, only for `Update` and `Delete`
+    pub before: Option<Map<String, serde_json::Value>>,
+    /// the row after the change, except for `Delete`
+    pub after: Option<Map<String, serde_json::Value>>,
+    /// the binlog position of the change, `file:position`
+    pub position: String,
+}
+
+/// Convert a column value to json, the temporal values are formatted as MySQL does
+pub(crate) fn to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::NULL => serde_json::Value::Null,
+        Value::Bytes(bytes) => {
+            serde_json::Value::String(String::from_utf8_lossy(bytes).to_string())
+        }
+        Value::Int(v) => serde_json::Value::Number(Number::from(*v)),
+        Value::UInt(v) => serde_json::Value::Number(Number::from(*v)),
+        Value::Float(v) => Number::from_f64(*v as f64)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::Double(v) => Number::from_f64(*v)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            let mut date_time = format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                year, month, day, hour, minute, second
+            );
+            if *micros > 0 {
+                date_time.push_str(format!(".{:06}", micros).as_str());
+            }
+            serde_json::Value::String(date_time)
+        }
+        Value::Time(negative, days, hours, minutes, seconds, micros) => {
+            let mut time = format!(
+                "{}{:02}:{:02}:{:02}",
+                if *negative { "-" } else { "" },
+                *days * 24 + *hours as u32,
+                minutes,
+                seconds
+            );
+            if *micros > 0 {
+                time.push_str(format!(".{:06}", micros).as_str());
+            }
+            serde_json::Value::String(time)
+        }
+    }
+}
+
+/// Zip the column names and values to a row image, the absent values of the minimal binlog row
+/// image are skipped
+pub(crate) fn to_row_image(
+    columns: &[String],
+    values: Vec<Option<serde_json::Value>>,
+) -> Map<String, serde_json::Value> {
+    let mut row = Map::with_capacity(values.len());
+    for (i, value) in values.into_iter().enumerate() {
+        let value = match value {
+            Some(value) => value,
+            None => continue,
+        };
+        // the columns added after the table is loaded are named by the index
+        let column = match columns.get(i) {
+            Some(column) => column.clone(),
+            None => format!("@{}", i),
+        };
+        row.insert(column, value);
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql_async::Value;
+
+    use crate::source::change::{to_json, to_row_image};
+
+    #[test]
+    pub fn to_json_test() {
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let values = vec![
+            Some(to_json(&Value::Int(1))),
+            Some(to_json(&Value::Bytes(b"rlink".to_vec()))),
+            Some(to_json(&Value::Date(2022, 10, 1, 8, 30, 0, 0))),
+            Some(to_json(&Value::Time(true, 1, 2, 3, 4, 500))),
+            None,
+            Some(to_json(&Value::NULL)),
+        ];
+
+        let row = serde_json::Value::Object(to_row_image(&columns, values));
+        assert_eq!(
+            row.to_string(),
+            r#"{"@2":"2022-10-01 08:30:00","@3":"-26:03:04.000500","@5":null,"id":1,"name":"rlink"}"#
+        );
+    }
+}
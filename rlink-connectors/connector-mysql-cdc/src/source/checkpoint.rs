@@ -0,0 +1,129 @@
+use std::sync::{Arc, Mutex};
+
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::runtime::TaskId;
+
+use crate::source::offset::BinlogOffset;
+
+#[derive(Debug, Clone)]
+pub struct MysqlCdcCheckpointFunction {
+    pub(crate) state_recorder: Option<MysqlCdcStateRecorder>,
+    #[allow(dead_code)]
+    pub(crate) application_id: String,
+    #[allow(dead_code)]
+    pub(crate) task_id: TaskId,
+}
+
+impl MysqlCdcCheckpointFunction {
+    pub fn new(application_id: String, task_id: TaskId) -> Self {
+        MysqlCdcCheckpointFunction {
+            state_recorder: None,
+            application_id,
+            task_id,
+        }
+    }
+
+    pub fn as_state_mut(&mut self) -> &mut MysqlCdcStateRecorder {
+        self.state_recorder.as_mut().unwrap()
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for MysqlCdcCheckpointFunction {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.state_recorder = Some(MysqlCdcStateRecorder::new());
+        info!("Checkpoint initialize, context: {:?}", context);
+
+        if context.checkpoint_id.is_default() || handle.is_none() {
+            return;
+        }
+
+        let handle = handle.as_ref().unwrap();
+
+        let state_cache = self.state_recorder.as_mut().unwrap();
+        state_cache
+            .update_from_snapshot(handle.handle.as_str())
+            .unwrap();
+
+        info!(
+            "load state value from checkpoint({:?}): {:?}",
+            context.checkpoint_id, handle.handle
+        );
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let handle = self.state_recorder.as_ref().unwrap().snapshot();
+        debug!("Checkpoint snapshot: {:?}, context: {:?}", handle, context);
+
+        Some(CheckpointHandle { handle })
+    }
+}
+
+/// The offset after the last record emitted to the stream
+#[derive(Debug, Clone, Default)]
+pub struct MysqlCdcStateRecorder {
+    offset: Arc<Mutex<Option<BinlogOffset>>>,
+}
+
+impl MysqlCdcStateRecorder {
+    pub fn new() -> Self {
+        MysqlCdcStateRecorder::default()
+    }
+
+    pub fn update(&self, offset: BinlogOffset) {
+        *self.offset.lock().unwrap() = Some(offset);
+    }
+
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let offset: Option<BinlogOffset> = serde_json::from_str(snapshot_handle)?;
+        *self.offset.lock().unwrap() = offset;
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> String {
+        let offset = self.offset.lock().unwrap().clone();
+        serde_json::to_string(&offset).unwrap()
+    }
+
+    pub fn get(&self) -> Option<BinlogOffset> {
+        self.offset.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::source::checkpoint::MysqlCdcStateRecorder;
+    use crate::source::offset::{BinlogOffset, GtidSet};
+
+    #[test]
+    pub fn state_snapshot_test() {
+        let recorder = MysqlCdcStateRecorder::new();
+        assert!(recorder.get().is_none());
+
+        let restored = MysqlCdcStateRecorder::new();
+        restored
+            .update_from_snapshot(recorder.snapshot().as_str())
+            .unwrap();
+        assert!(restored.get().is_none());
+
+        let gtid_set = GtidSet::try_from("3e11fa47-71ca-11e1-9e33-c80aa9429562:1-42").unwrap();
+        let mut offset = BinlogOffset::new("mysql-bin.000003".to_string(), 1542, gtid_set);
+        offset.skip_rows = 2;
+        offset.snapshot_completed = true;
+        recorder.update(offset.clone());
+
+        restored
+            .update_from_snapshot(recorder.snapshot().as_str())
+            .unwrap();
+        assert_eq!(restored.get(), Some(offset));
+    }
+}
@@ -0,0 +1,82 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+use serde_json::{Map, Value};
+
+use crate::build_mysql_change_record;
+use crate::source::change::ChangeEvent;
+
+pub trait MysqlChangeDeserializer: Sync + Send {
+    fn deserialize(&mut self, event: &ChangeEvent) -> Vec<Record>;
+}
+
+pub trait MysqlChangeDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn MysqlChangeDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+/// Build the `MysqlChangeRecord` with the row images in json, the absent image is empty
+#[derive(Default)]
+pub struct DefaultMysqlChangeDeserializer {}
+
+impl DefaultMysqlChangeDeserializer {
+    fn image_json(image: &Option<Map<String, Value>>) -> String {
+        match image {
+            Some(image) => serde_json::to_string(image).unwrap(),
+            None => "".to_string(),
+        }
+    }
+}
+
+impl MysqlChangeDeserializer for DefaultMysqlChangeDeserializer {
+    fn deserialize(&mut self, event: &ChangeEvent) -> Vec<Record> {
+        let before = Self::image_json(&event.before);
+        let after = Self::image_json(&event.after);
+
+        let record = build_mysql_change_record(
+            event.timestamp,
+            event.op.as_str(),
+            event.database.as_str(),
+            event.table.as_str(),
+            before.as_str(),
+            after.as_str(),
+            event.position.as_str(),
+        )
+        .expect("mysql change writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultMysqlChangeDeserializerBuilder<T>
+where
+    T: Default + MysqlChangeDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultMysqlChangeDeserializerBuilder<T>
+where
+    T: Default + MysqlChangeDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultMysqlChangeDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> MysqlChangeDeserializerBuilder for DefaultMysqlChangeDeserializerBuilder<T>
+where
+    T: Default + MysqlChangeDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn MysqlChangeDeserializer> {
+        let t: Box<dyn MysqlChangeDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::properties::Properties;
+use rlink::core::runtime::TaskId;
+
+use crate::source::checkpoint::MysqlCdcCheckpointFunction;
+use crate::source::deserializer::MysqlChangeDeserializerBuilder;
+use crate::source::reader::{create_mysql_cdc_reader, MysqlCdcReaderThread};
+use crate::source::startup_mode::StartupMode;
+use crate::source::stream::MysqlCdcRecordStream;
+use crate::source::table::TableId;
+use crate::MysqlClientConfig;
+
+pub struct MysqlCdcInputFormat {
+    name: String,
+
+    client_config: MysqlClientConfig,
+    tables: Vec<TableId>,
+    startup_mode: StartupMode,
+
+    task_id: TaskId,
+
+    buffer_size: usize,
+
+    deserializer_builder: Arc<dyn MysqlChangeDeserializerBuilder>,
+    schema: FnSchema,
+
+    checkpoint: Option<MysqlCdcCheckpointFunction>,
+}
+
+impl MysqlCdcInputFormat {
+    pub fn new(
+        client_config: MysqlClientConfig,
+        tables: Vec<TableId>,
+        buffer_size: usize,
+        deserializer_builder: Box<dyn MysqlChangeDeserializerBuilder>,
+        fn_name: String,
+    ) -> Self {
+        let schema = deserializer_builder.schema();
+        MysqlCdcInputFormat {
+            name: fn_name,
+            client_config,
+            tables,
+            startup_mode: StartupMode::default(),
+            task_id: Default::default(),
+            buffer_size,
+            deserializer_builder: Arc::from(deserializer_builder),
+            schema,
+            checkpoint: None,
+        }
+    }
+
+    pub fn startup_mode(mut self, startup_mode: StartupMode) -> Self {
+        self.startup_mode = startup_mode;
+        self
+    }
+}
+
+impl NamedFunction for MysqlCdcInputFormat {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl InputFormat for MysqlCdcInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        info!("mysql cdc source open");
+
+        self.task_id = context.task_id;
+
+        let checkpoint =
+            MysqlCdcCheckpointFunction::new(context.application_id.clone(), context.task_id);
+        self.checkpoint = Some(checkpoint);
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) = named_channel(
+            "MysqlCdcSource_Handover",
+            self.task_id.to_tags(),
+            self.buffer_size,
+        );
+
+        let state_recorder = self.checkpoint.as_mut().unwrap().as_state_mut().clone();
+
+        let reader = MysqlCdcReaderThread::new(
+            self.task_id,
+            self.client_config.clone(),
+            self.tables.clone(),
+            sender,
+            self.deserializer_builder.build(),
+        )
+        .startup_mode(self.startup_mode)
+        .restored(state_recorder.get());
+        create_mysql_cdc_reader(reader).await;
+
+        Box::pin(MysqlCdcRecordStream::new(receiver, state_recorder))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    /// The binlog is a single ordered stream of the server
+    fn parallelism(&self) -> u16 {
+        1
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for MysqlCdcInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.checkpoint
+            .as_mut()
+            .unwrap()
+            .initialize_state(context, handle)
+            .await;
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        match self.checkpoint.as_mut() {
+            Some(checkpoint) => checkpoint.snapshot_state(context).await,
+            None => None,
+        }
+    }
+}
+
+impl InputSplitSource for MysqlCdcInputFormat {
+    fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
+        info!("mysql config {:?}", self.client_config);
+
+        if min_num_splits != 1 {
+            return Err(rlink::core::Error::from(format!(
+                "mysql cdc source parallelism({}) must be 1",
+                min_num_splits
+            )));
+        }
+
+        Ok(vec![InputSplit::new(0, Properties::new())])
+    }
+}
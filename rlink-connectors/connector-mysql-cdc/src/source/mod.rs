@@ -0,0 +1,23 @@
+pub mod builder;
+pub mod change;
+pub mod checkpoint;
+pub mod deserializer;
+pub mod input_format;
+pub mod offset;
+pub mod reader;
+pub mod startup_mode;
+pub mod stream;
+pub mod table;
+
+use crate::source::offset::BinlogOffset;
+
+#[derive(Clone, Debug)]
+pub(crate) enum ConsumerRecord {
+    /// a record of the row change, and the offset after it
+    Record {
+        record: rlink::core::element::Record,
+        offset: BinlogOffset,
+    },
+    /// the offset moved without row changes, by a commit or the end of the snapshot
+    Progress { offset: BinlogOffset },
+}
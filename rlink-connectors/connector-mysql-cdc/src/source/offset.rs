@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+/// The transactions executed by the servers, formatted as the `Executed_Gtid_Set` of
+/// `SHOW MASTER STATUS`, e.g. `3e11fa47-71ca-11e1-9e33-c80aa9429562:1-5:7-9`
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GtidSet {
+    /// the closed intervals of the transaction numbers by the server uuid
+    intervals: BTreeMap<String, Vec<(u64, u64)>>,
+}
+
+impl GtidSet {
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, uuid: &str, gno: u64) -> bool {
+        match self.intervals.get(uuid) {
+            Some(intervals) => intervals
+                .iter()
+                .any(|(start, end)| *start <= gno && gno <= *end),
+            None => false,
+        }
+    }
+
+    pub fn add(&mut self, uuid: &str, gno: u64) {
+        let intervals = self.intervals.entry(uuid.to_lowercase()).or_default();
+
+        // the first interval touching or after the gno
+        let index = intervals.partition_point(|(_, end)| *end + 1 < gno);
+        if index < intervals.len() && intervals[index].0 <= gno + 1 {
+            let interval = &mut intervals[index];
+            interval.0 = interval.0.min(gno);
+            interval.1 = interval.1.max(gno);
+
+            if index + 1 < intervals.len() && intervals[index + 1].0 <= intervals[index].1 + 1 {
+                let next = intervals.remove(index + 1);
+                intervals[index].1 = intervals[index].1.max(next.1);
+            }
+        } else {
+            intervals.insert(index, (gno, gno));
+        }
+    }
+}
+
+impl TryFrom<&str> for GtidSet {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut gtid_set = GtidSet::default();
+
+        for uuid_set in value.split(',') {
+            let uuid_set = uuid_set.trim();
+            if uuid_set.is_empty() {
+                continue;
+            }
+
+            let mut parts = uuid_set.split(':');
+            let uuid = parts.next().unwrap_or_default().to_lowercase();
+            let intervals = gtid_set.intervals.entry(uuid).or_default();
+            for interval in parts {
+                let (start, end) = match interval.split_once('-') {
+                    Some((start, end)) => (start.parse::<u64>()?, end.parse::<u64>()?),
+                    None => {
+                        let gno = interval.parse::<u64>()?;
+                        (gno, gno)
+                    }
+                };
+                if start > end {
+                    return Err(anyhow!("illegal gtid interval `{}`", interval));
+                }
+                intervals.push((start, end));
+            }
+            intervals.sort_unstable();
+        }
+
+        Ok(gtid_set)
+    }
+}
+
+impl Display for GtidSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let uuid_sets: Vec<String> = self
+            .intervals
+            .iter()
+            .map(|(uuid, intervals)| {
+                let intervals: Vec<String> = intervals
+                    .iter()
+                    .map(|(start, end)| {
+                        if start == end {
+                            start.to_string()
+                        } else {
+                            format!("{}-{}", start, end)
+                        }
+                    })
+                    .collect();
+                format!("{}:{}", uuid, intervals.join(":"))
+            })
+            .collect();
+        write!(f, "{}", uuid_sets.join(","))
+    }
+}
+
+/// Format the 16 bytes server uuid of the gtid event
+pub(crate) fn format_sid(sid: &[u8; 16]) -> String {
+    let hex: Vec<String> = sid.iter().map(|x| format!("{:02x}", x)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex[0..4].concat(),
+        hex[4..6].concat(),
+        hex[6..8].concat(),
+        hex[8..10].concat(),
+        hex[10..16].concat()
+    )
+}
+
+/// The replication progress of the source. The binlog is replayed from the first event of the
+/// transaction in progress, and the row changes of it emitted before are skipped
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BinlogOffset {
+    pub file: String,
+    /// the position of the first event of the transaction in progress
+    pub position: u64,
+    /// the row changes of the transaction in progress emitted already
+    pub skip_rows: u64,
+    /// the transactions committed before the position
+    pub gtid_set: GtidSet,
+    /// the initial snapshot is completed, otherwise it's taken again on restart
+    pub snapshot_completed: bool,
+}
+
+impl BinlogOffset {
+    pub fn new(file: String, position: u64, gtid_set: GtidSet) -> Self {
+        BinlogOffset {
+            file,
+            position,
+            skip_rows: 0,
+            gtid_set,
+            snapshot_completed: false,
+        }
+    }
+}
+
+impl Display for BinlogOffset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use crate::source::offset::{format_sid, GtidSet};
+
+    #[test]
+    pub fn gtid_set_test() {
+        let uuid = "3e11fa47-71ca-11e1-9e33-c80aa9429562";
+        let mut gtid_set =
+            GtidSet::try_from(format!("{}:1-5:7-9,\n{}:3", uuid, uuid.replace('3', "4")).as_str())
+                .unwrap();
+        assert!(gtid_set.contains(uuid, 8));
+        assert!(!gtid_set.contains(uuid, 6));
+
+        gtid_set.add(uuid, 6);
+        gtid_set.add(uuid, 10);
+        gtid_set.add(uuid, 12);
+        assert_eq!(
+            gtid_set.to_string(),
+            format!("{}:1-10:12,{}:3", uuid, uuid.replace('3', "4"))
+        );
+
+        assert!(GtidSet::try_from("").unwrap().is_empty());
+        assert!(GtidSet::try_from(format!("{}:5-1", uuid).as_str()).is_err());
+
+        let sid = [
+            0x3e, 0x11, 0xfa, 0x47, 0x71, 0xca, 0x11, 0xe1, 0x9e, 0x33, 0xc8, 0x0a, 0xa9, 0x42,
+            0x95, 0x62,
+        ];
+        assert_eq!(format_sid(&sid), uuid);
+    }
+}
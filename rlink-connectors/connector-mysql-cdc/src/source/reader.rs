@@ -0,0 +1,344 @@
+use std::convert::TryFrom;
+
+use futures::StreamExt;
+use mysql_async::binlog::events::{EventData, RowsEventData};
+use mysql_async::binlog::row::BinlogRow;
+use mysql_async::binlog::value::BinlogValue;
+use mysql_async::prelude::Queryable;
+use mysql_async::{BinlogRequest, Conn, Row};
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::Tag;
+use rlink::utils::date_time::current_timestamp_millis;
+
+use crate::metrics::SourceMetrics;
+use crate::source::change::{to_json, to_row_image, ChangeEvent, Operation};
+use crate::source::deserializer::MysqlChangeDeserializer;
+use crate::source::offset::{format_sid, BinlogOffset, GtidSet};
+use crate::source::startup_mode::StartupMode;
+use crate::source::table::{TableColumns, TableId};
+use crate::source::ConsumerRecord;
+use crate::MysqlClientConfig;
+
+pub(crate) async fn create_mysql_cdc_reader(mut reader: MysqlCdcReaderThread) {
+    tokio::spawn(async move {
+        match reader.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!("run mysql cdc reader error. {}", e);
+            }
+        }
+    });
+}
+
+/// The binlog position and executed gtid set of the server
+async fn master_status(conn: &mut Conn) -> anyhow::Result<BinlogOffset> {
+    let row: Row = conn
+        .query_first("SHOW MASTER STATUS")
+        .await?
+        .ok_or_else(|| anyhow!("the binlog of the server is not enabled"))?;
+
+    let file: String = row
+        .get("File")
+        .ok_or_else(|| anyhow!("`File` not found in master status"))?;
+    let position: u64 = row
+        .get("Position")
+        .ok_or_else(|| anyhow!("`Position` not found in master status"))?;
+    let gtid_set = match row.get::<Option<String>, _>("Executed_Gtid_Set").flatten() {
+        Some(gtid_set) => GtidSet::try_from(gtid_set.as_str())?,
+        None => GtidSet::default(),
+    };
+
+    Ok(BinlogOffset::new(file, position, gtid_set))
+}
+
+/// Convert the values of the binlog row, the json columns in the binary format are decoded
+fn binlog_image(columns: &[String], row: &BinlogRow) -> serde_json::Map<String, serde_json::Value> {
+    let values = (0..row.len())
+        .map(|i| match row.as_ref(i) {
+            Some(BinlogValue::Value(value)) => Some(to_json(value)),
+            Some(BinlogValue::Jsonb(value)) => {
+                Some(serde_json::Value::try_from(value.clone()).unwrap_or(serde_json::Value::Null))
+            }
+            // the partial json updates are not supported, `binlog_row_value_options` must be empty
+            Some(BinlogValue::JsonDiff(_)) => Some(serde_json::Value::Null),
+            None => None,
+        })
+        .collect();
+    to_row_image(columns, values)
+}
+
+pub(crate) struct MysqlCdcReaderThread {
+    task_id: TaskId,
+
+    client_config: MysqlClientConfig,
+    tables: Vec<TableId>,
+    startup_mode: StartupMode,
+    /// the offset restored from the checkpoint
+    restored: Option<BinlogOffset>,
+
+    sender: ChannelSender<ConsumerRecord>,
+    deserializer: Box<dyn MysqlChangeDeserializer>,
+}
+
+impl MysqlCdcReaderThread {
+    pub fn new(
+        task_id: TaskId,
+        client_config: MysqlClientConfig,
+        tables: Vec<TableId>,
+        sender: ChannelSender<ConsumerRecord>,
+        deserializer: Box<dyn MysqlChangeDeserializer>,
+    ) -> Self {
+        MysqlCdcReaderThread {
+            task_id,
+            client_config,
+            tables,
+            startup_mode: StartupMode::default(),
+            restored: None,
+            sender,
+            deserializer,
+        }
+    }
+
+    pub fn startup_mode(mut self, startup_mode: StartupMode) -> Self {
+        self.startup_mode = startup_mode;
+        self
+    }
+
+    pub fn restored(mut self, restored: Option<BinlogOffset>) -> Self {
+        self.restored = restored;
+        self
+    }
+
+    async fn emit(&mut self, event: ChangeEvent, offset: &BinlogOffset) {
+        let records = self.deserializer.deserialize(&event);
+        for record in records {
+            self.sender
+                .send(ConsumerRecord::Record {
+                    record,
+                    offset: offset.clone(),
+                })
+                .await
+                .expect("mysql cdc reader handover `Disconnected`");
+        }
+    }
+
+    async fn progress(&self, offset: &BinlogOffset) {
+        self.sender
+            .send(ConsumerRecord::Progress {
+                offset: offset.clone(),
+            })
+            .await
+            .expect("mysql cdc reader handover `Disconnected`");
+    }
+
+    /// Read the tables in a transaction started while the tables are locked, so the rows are
+    /// consistent with the binlog position read in the lock. An unfinished snapshot is taken
+    /// again on restart.
+    async fn snapshot(
+        &mut self,
+        conn: &mut Conn,
+        metrics: &SourceMetrics,
+    ) -> anyhow::Result<BinlogOffset> {
+        conn.query_drop("FLUSH TABLES WITH READ LOCK").await?;
+        conn.query_drop("SET SESSION TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .await?;
+        conn.query_drop("START TRANSACTION WITH CONSISTENT SNAPSHOT")
+            .await?;
+        let mut offset = master_status(conn).await?;
+        conn.query_drop("UNLOCK TABLES").await?;
+
+        info!(
+            "mysql snapshot start at {}, gtid: {}",
+            offset, offset.gtid_set
+        );
+
+        let timestamp = current_timestamp_millis() as i64;
+        let position = offset.to_string();
+        for table_id in self.tables.clone() {
+            let sql = format!("SELECT * FROM {}", table_id.quoted());
+            let mut result = conn.exec_iter(sql.as_str(), ()).await?;
+            let mut rows = match result.stream::<Row>().await? {
+                Some(rows) => rows,
+                None => continue,
+            };
+
+            let mut columns: Vec<String> = vec![];
+            while let Some(row) = rows.next().await {
+                let row = row?;
+                if columns.is_empty() {
+                    columns = row
+                        .columns_ref()
+                        .iter()
+                        .map(|x| x.name_str().to_string())
+                        .collect();
+                }
+
+                let values = (0..row.len()).map(|i| row.as_ref(i).map(to_json)).collect();
+                let event = ChangeEvent {
+                    timestamp,
+                    op: Operation::Read,
+                    database: table_id.database.clone(),
+                    table: table_id.table.clone(),
+                    before: None,
+                    after: Some(to_row_image(&columns, values)),
+                    position: position.clone(),
+                };
+                self.emit(event, &offset).await;
+                metrics.snapshot_row();
+            }
+
+            info!("mysql snapshot of `{}` finished", table_id);
+        }
+        conn.query_drop("COMMIT").await?;
+
+        offset.snapshot_completed = true;
+        self.progress(&offset).await;
+
+        Ok(offset)
+    }
+
+    /// Commit the transaction in progress, the next transaction starts at `position`
+    async fn commit(&self, offset: &mut BinlogOffset, position: u64, gtid: Option<(String, u64)>) {
+        offset.position = position;
+        offset.skip_rows = 0;
+        if let Some((uuid, gno)) = gtid {
+            offset.gtid_set.add(uuid.as_str(), gno);
+        }
+        self.progress(offset).await;
+    }
+
+    async fn read_binlog(
+        &mut self,
+        conn: Conn,
+        mut offset: BinlogOffset,
+        metrics: &SourceMetrics,
+    ) -> anyhow::Result<()> {
+        // the column names are queried by another connection, the binlog connection is
+        // occupied by the dump
+        let mut schema_conn = self.client_config.connect().await?;
+        let mut table_columns = TableColumns::default();
+
+        let request = BinlogRequest::new(self.client_config.get_server_id())
+            .with_filename(offset.file.as_bytes().to_vec())
+            .with_pos(offset.position);
+        let mut binlog = conn.get_binlog_stream(request).await?;
+
+        info!(
+            "mysql binlog read from {}, skip rows: {}, gtid: {}",
+            offset, offset.skip_rows, offset.gtid_set
+        );
+
+        // the row changes of the transaction in progress emitted before the restart
+        let mut skip_rows = offset.skip_rows;
+        let mut gtid = None;
+        while let Some(event) = binlog.next().await {
+            let event = event?;
+            let log_pos = event.header().log_pos() as u64;
+            let timestamp = event.header().timestamp() as i64 * 1000;
+
+            match event.read_data()? {
+                // the fake rotate event of the requested position is ignored
+                Some(EventData::RotateEvent(rotate)) if rotate.name() != offset.file.as_str() => {
+                    offset.file = rotate.name().to_string();
+                    offset.position = rotate.position();
+                    offset.skip_rows = 0;
+                    skip_rows = 0;
+                }
+                Some(EventData::GtidEvent(gtid_event)) => {
+                    gtid = Some((format_sid(&gtid_event.sid()), gtid_event.gno()));
+                }
+                Some(EventData::QueryEvent(query_event)) => {
+                    let query = query_event.query();
+                    if query.eq_ignore_ascii_case("BEGIN") {
+                        continue;
+                    }
+
+                    // the DDL statements are committed implicitly
+                    if !query.eq_ignore_ascii_case("COMMIT") {
+                        table_columns.invalidate(query_event.schema().as_ref());
+                    }
+                    self.commit(&mut offset, log_pos, gtid.take()).await;
+                    skip_rows = 0;
+                }
+                Some(EventData::XidEvent(_)) => {
+                    self.commit(&mut offset, log_pos, gtid.take()).await;
+                    skip_rows = 0;
+                }
+                Some(EventData::RowsEvent(rows_event)) => {
+                    let tme = match binlog.get_tme(rows_event.table_id()) {
+                        Some(tme) => tme,
+                        None => {
+                            return Err(anyhow!(
+                                "table map of `{}` not found",
+                                rows_event.table_id()
+                            ))
+                        }
+                    };
+                    let table_id =
+                        TableId::new(tme.database_name().as_ref(), tme.table_name().as_ref());
+                    if !self.tables.contains(&table_id) {
+                        continue;
+                    }
+
+                    let op =
+                        match &rows_event {
+                            RowsEventData::WriteRowsEventV1(_)
+                            | RowsEventData::WriteRowsEvent(_) => Operation::Create,
+                            RowsEventData::DeleteRowsEventV1(_)
+                            | RowsEventData::DeleteRowsEvent(_) => Operation::Delete,
+                            _ => Operation::Update,
+                        };
+                    let columns = table_columns.get(&mut schema_conn, &table_id).await?;
+                    metrics.binlog_delay(current_timestamp_millis() as i64 - timestamp);
+
+                    for row in rows_event.rows(tme) {
+                        let (before, after) = row?;
+                        if skip_rows > 0 {
+                            skip_rows -= 1;
+                            continue;
+                        }
+
+                        offset.skip_rows += 1;
+                        let event = ChangeEvent {
+                            timestamp,
+                            op,
+                            database: table_id.database.clone(),
+                            table: table_id.table.clone(),
+                            before: before.as_ref().map(|x| binlog_image(&columns, x)),
+                            after: after.as_ref().map(|x| binlog_image(&columns, x)),
+                            position: offset.to_string(),
+                        };
+                        self.emit(event, &offset).await;
+                        metrics.binlog_row();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Err(anyhow!("mysql binlog stream closed"))
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let mut tags = self.task_id.to_tags();
+        tags.push(Tag::new("server_id", self.client_config.get_server_id()));
+        let metrics = SourceMetrics::new(tags);
+
+        let mut conn = self.client_config.connect().await?;
+        let offset = match self.restored.take() {
+            Some(offset) if offset.snapshot_completed => offset,
+            _ => match self.startup_mode {
+                StartupMode::Initial => self.snapshot(&mut conn, &metrics).await?,
+                StartupMode::Latest => {
+                    let mut offset = master_status(&mut conn).await?;
+                    offset.snapshot_completed = true;
+                    self.progress(&offset).await;
+                    offset
+                }
+            },
+        };
+
+        self.read_binlog(conn, offset, &metrics).await
+    }
+}
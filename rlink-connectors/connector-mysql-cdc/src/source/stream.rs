@@ -0,0 +1,50 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::MysqlCdcStateRecorder;
+use crate::source::ConsumerRecord;
+
+/// Simulate a MySQL change stream as an iterator.
+pub struct MysqlCdcRecordStream {
+    receiver: ChannelReceiver<ConsumerRecord>,
+    state_recorder: MysqlCdcStateRecorder,
+}
+
+impl MysqlCdcRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<ConsumerRecord>,
+        state_recorder: MysqlCdcStateRecorder,
+    ) -> Self {
+        MysqlCdcRecordStream {
+            receiver,
+            state_recorder,
+        }
+    }
+}
+
+impl ElementStream for MysqlCdcRecordStream {}
+
+impl Stream for MysqlCdcRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().receiver.poll_recv(cx) {
+                Poll::Ready(Some(ConsumerRecord::Record { record, offset })) => {
+                    self.state_recorder.update(offset);
+                    return Poll::Ready(Some(Element::Record(record)));
+                }
+                Poll::Ready(Some(ConsumerRecord::Progress { offset })) => {
+                    self.state_recorder.update(offset);
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use mysql_async::prelude::Queryable;
+use mysql_async::Conn;
+
+/// A table captured by the source, `database.table`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TableId {
+    pub database: String,
+    pub table: String,
+}
+
+impl TableId {
+    pub fn new(database: &str, table: &str) -> Self {
+        TableId {
+            database: database.to_string(),
+            table: table.to_string(),
+        }
+    }
+
+    pub(crate) fn quoted(&self) -> String {
+        format!(
+            "`{}`.`{}`",
+            self.database.replace('`', "``"),
+            self.table.replace('`', "``")
+        )
+    }
+}
+
+impl TryFrom<&str> for TableId {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().split_once('.') {
+            Some((database, table)) if !database.is_empty() && !table.is_empty() => {
+                Ok(TableId::new(database, table))
+            }
+            _ => Err(anyhow!(
+                "illegal table `{}`, expect `database.table`",
+                value
+            )),
+        }
+    }
+}
+
+impl Display for TableId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.database, self.table)
+    }
+}
+
+/// The column names of the tables, the binlog row events only carry the column values
+#[derive(Default)]
+pub(crate) struct TableColumns {
+    columns: HashMap<TableId, Arc<Vec<String>>>,
+}
+
+impl TableColumns {
+    pub async fn get(
+        &mut self,
+        conn: &mut Conn,
+        table_id: &TableId,
+    ) -> anyhow::Result<Arc<Vec<String>>> {
+        if let Some(columns) = self.columns.get(table_id) {
+            return Ok(columns.clone());
+        }
+
+        let columns: Vec<String> = conn
+            .exec(
+                "SELECT COLUMN_NAME FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+                (table_id.database.as_str(), table_id.table.as_str()),
+            )
+            .await?;
+        let columns = Arc::new(columns);
+        self.columns.insert(table_id.clone(), columns.clone());
+
+        Ok(columns)
+    }
+
+    /// Reload the columns of the database after a DDL statement
+    pub fn invalidate(&mut self, database: &str) {
+        self.columns
+            .retain(|table_id, _| table_id.database != database);
+    }
+}
@@ -0,0 +1,156 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod sink;
+pub mod source;
+
+pub mod buffer_gen {
+    include!(concat!(env!("OUT_DIR"), "/buffer_gen/mod.rs"));
+}
+
+pub use sink::output_format::NatsOutputFormat;
+pub use source::input_format::NatsInputFormat;
+
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use async_nats::ConnectOptions;
+use rlink::core::element::Record;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::nats_message;
+
+pub const NATS: &str = "nats";
+pub const URL: &str = "url";
+pub const TOKEN: &str = "token";
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+
+pub const STREAM: &str = "stream";
+pub const SUBJECTS: &str = "subjects";
+pub const DURABLE: &str = "durable";
+pub const DELIVER_POLICY: &str = "deliver.policy";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SUBJECT: &str = "subject";
+pub const BATCH_SIZE: &str = "batch.size";
+
+pub const INPUT_FORMAT_FN_NAME_DEFAULT: &str = "NatsInputFormat";
+pub const OUTPUT_FORMAT_FN_NAME_DEFAULT: &str = "NatsOutputFormat";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 50000;
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 3000;
+
+#[derive(Clone)]
+enum NatsAuth {
+    Token(String),
+    UserPassword(String, String),
+}
+
+/// Connection settings shared by the source and the sink
+#[derive(Clone)]
+pub struct NatsClientConfig {
+    url: String,
+    auth: Option<NatsAuth>,
+}
+
+impl NatsClientConfig {
+    pub fn new(url: &str) -> Self {
+        NatsClientConfig {
+            url: url.to_string(),
+            auth: None,
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.auth = Some(NatsAuth::Token(token.to_string()));
+        self
+    }
+
+    pub fn user_password(mut self, username: &str, password: &str) -> Self {
+        self.auth = Some(NatsAuth::UserPassword(
+            username.to_string(),
+            password.to_string(),
+        ));
+        self
+    }
+
+    pub(crate) async fn connect(&self) -> anyhow::Result<async_nats::jetstream::Context> {
+        let options = match &self.auth {
+            Some(NatsAuth::Token(token)) => ConnectOptions::with_token(token.clone()),
+            Some(NatsAuth::UserPassword(username, password)) => {
+                ConnectOptions::with_user_and_password(username.clone(), password.clone())
+            }
+            None => ConnectOptions::new(),
+        };
+
+        let client = options
+            .connect(self.url.as_str())
+            .await
+            .map_err(|e| anyhow!("connect to nats `{}` error. {}", self.url, e))?;
+        Ok(async_nats::jetstream::new(client))
+    }
+}
+
+impl TryFrom<Properties> for NatsClientConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let url = properties.get_string(URL)?;
+
+        let mut client_config = NatsClientConfig::new(url.as_str());
+        if let Ok(token) = properties.get_string(TOKEN) {
+            client_config = client_config.token(token.as_str());
+        } else if let Ok(username) = properties.get_string(USERNAME) {
+            let password = properties.get_string(PASSWORD)?;
+            client_config = client_config.user_password(username.as_str(), password.as_str());
+        }
+        Ok(client_config)
+    }
+}
+
+impl Debug for NatsClientConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let auth = match &self.auth {
+            Some(NatsAuth::Token(_)) => "token".to_string(),
+            Some(NatsAuth::UserPassword(username, _)) => format!("user({})", username),
+            None => "none".to_string(),
+        };
+        f.debug_struct("NatsClientConfig")
+            .field("url", &self.url)
+            .field("auth", &auth)
+            .finish()
+    }
+}
+
+pub fn build_nats_record(
+    timestamp: i64,
+    subject: &str,
+    payload: &[u8],
+    sequence: i64,
+) -> Result<Record, std::io::Error> {
+    let message = nats_message::Entity {
+        timestamp,
+        subject,
+        payload,
+        sequence,
+    };
+
+    // 24 = 8(len(payload) + len(subject)) + 16(len(timestamp) + len(sequence))
+    let capacity = payload.len() + subject.len() + 24;
+    let mut record = Record::with_capacity(capacity);
+
+    message.to_buffer(record.as_buffer()).unwrap();
+
+    Ok(record)
+}
@@ -0,0 +1,68 @@
+use std::convert::TryFrom;
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    NatsClientConfig, NatsOutputFormat, BATCH_SIZE, BUFFER_SIZE, NATS, SINK_BATCH_SIZE,
+    SINK_CHANNEL_SIZE, SUBJECT,
+};
+
+#[derive(Debug)]
+pub struct NatsOutputFormatBuilder {
+    client_config: NatsClientConfig,
+    subject: Option<String>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+}
+
+impl NatsOutputFormatBuilder {
+    /// The records are published to the `subject` if specified, otherwise to the `subject`
+    /// field of the `NatsMessage` records
+    pub fn new(client_config: NatsClientConfig, subject: Option<String>) -> Self {
+        NatsOutputFormatBuilder {
+            client_config,
+            subject,
+            buffer_size: None,
+            batch_size: None,
+        }
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Publish up to `batch_size` messages before waiting for the acks of the stream
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    pub fn build(self) -> NatsOutputFormat {
+        info!("build nats sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let batch_size = self.batch_size.unwrap_or(SINK_BATCH_SIZE);
+        NatsOutputFormat::new(self.client_config, self.subject, buffer_size, batch_size)
+    }
+}
+
+impl TryFrom<Properties> for NatsOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let client_config = NatsClientConfig::try_from(properties.to_sub_properties(NATS))?;
+
+        let subject = properties.get_string(SUBJECT).ok();
+        let mut builder = NatsOutputFormatBuilder::new(client_config, subject);
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,91 @@
+use rlink::channel::named_channel;
+use rlink::channel::sender::ChannelSender;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::{Element, Record};
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::metrics::Tag;
+
+use crate::sink::producer::NatsProducerThread;
+use crate::NatsClientConfig;
+
+#[derive(NamedFunction)]
+pub struct NatsOutputFormat {
+    client_config: NatsClientConfig,
+    subject: Option<String>,
+
+    buffer_size: usize,
+    batch_size: usize,
+    handover: Option<ChannelSender<Record>>,
+}
+
+impl NatsOutputFormat {
+    pub fn new(
+        client_config: NatsClientConfig,
+        subject: Option<String>,
+        buffer_size: usize,
+        batch_size: usize,
+    ) -> Self {
+        NatsOutputFormat {
+            client_config,
+            subject,
+            buffer_size,
+            batch_size,
+            handover: None,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for NatsOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let mut tags = context.task_id.to_tags();
+        tags.push(Tag::new(
+            "subject",
+            self.subject.as_ref().map(|x| x.as_str()).unwrap_or(""),
+        ));
+
+        let (sender, receiver) = named_channel(self.name(), tags.clone(), self.buffer_size);
+        self.handover = Some(sender);
+
+        let mut nats_producer =
+            NatsProducerThread::new(self.client_config.clone(), self.subject.clone(), receiver)
+                .batch_size(self.batch_size)
+                .tags(tags);
+        tokio::spawn(async move {
+            nats_producer.run().await;
+        });
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        self.handover
+            .as_ref()
+            .unwrap()
+            .send(element.into_record())
+            .await
+            .unwrap();
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for NatsOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
@@ -0,0 +1,122 @@
+use std::future::IntoFuture;
+
+use async_nats::jetstream::context::PublishAckFuture;
+use async_nats::jetstream::Context;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::channel::TryRecvError;
+use rlink::core::element::Record;
+use rlink::metrics::Tag;
+
+use crate::buffer_gen::nats_message;
+use crate::metrics::ProducerMetrics;
+use crate::{NatsClientConfig, SINK_BATCH_SIZE};
+
+pub struct NatsProducerThread {
+    client_config: NatsClientConfig,
+    subject: Option<String>,
+    receiver: ChannelReceiver<Record>,
+    batch_size: usize,
+
+    tags: Vec<Tag>,
+}
+
+impl NatsProducerThread {
+    pub fn new(
+        client_config: NatsClientConfig,
+        subject: Option<String>,
+        receiver: ChannelReceiver<Record>,
+    ) -> Self {
+        NatsProducerThread {
+            client_config,
+            subject,
+            receiver,
+            batch_size: SINK_BATCH_SIZE,
+            tags: vec![],
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    async fn publish(
+        &self,
+        jetstream: &Context,
+        mut record: Record,
+    ) -> anyhow::Result<PublishAckFuture> {
+        let nats_message::Entity {
+            subject, payload, ..
+        } = nats_message::Entity::parse(record.as_buffer()).unwrap();
+
+        let subject = match self.subject.as_ref() {
+            Some(subject) => subject.clone(),
+            None => subject.to_string(),
+        };
+        if subject.is_empty() {
+            return Err(anyhow!("no subject specified for the nats message"));
+        }
+
+        let ack_future = jetstream
+            .publish(subject, payload.to_vec().into())
+            .await
+            .map_err(|e| anyhow!("publish error. {}", e))?;
+        Ok(ack_future)
+    }
+
+    pub async fn run(&mut self) {
+        let jetstream = match self.client_config.connect().await {
+            Ok(jetstream) => jetstream,
+            Err(e) => {
+                error!("run producer error. {}", e);
+                return;
+            }
+        };
+        let metrics = ProducerMetrics::new(self.tags.clone());
+
+        // wait for the first record, then drain the channel up to the batch size
+        while let Some(record) = self.receiver.recv().await {
+            let mut records = vec![record];
+            for _n in 1..self.batch_size {
+                match self.receiver.try_recv() {
+                    Ok(record) => records.push(record),
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+                }
+            }
+
+            let mut acks = FuturesUnordered::new();
+            for record in records {
+                match self.publish(&jetstream, record).await {
+                    Ok(ack_future) => {
+                        metrics.sent();
+                        acks.push(ack_future.into_future());
+                    }
+                    Err(e) => {
+                        metrics.failed();
+                        error!("publish nats message error, the record is discarded. {}", e);
+                    }
+                }
+            }
+
+            // wait for the acks of the stream before publishing the next batch
+            while let Some(ack) = acks.next().await {
+                match ack {
+                    Ok(_ack) => metrics.delivered(),
+                    Err(e) => {
+                        metrics.failed();
+                        error!("nats message is not stored by the stream. {}", e);
+                    }
+                }
+            }
+        }
+
+        info!("nats recv channel closed");
+    }
+}
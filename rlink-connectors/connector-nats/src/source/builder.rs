@@ -0,0 +1,129 @@
+use std::convert::TryFrom;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::nats_message;
+use crate::source::deliver_policy::DeliverPolicy;
+use crate::source::deserializer::{
+    DefaultNatsRecordDeserializer, DefaultNatsRecordDeserializerBuilder,
+    NatsRecordDeserializerBuilder,
+};
+use crate::{
+    NatsClientConfig, NatsInputFormat, BUFFER_SIZE, DELIVER_POLICY, DURABLE,
+    INPUT_FORMAT_FN_NAME_DEFAULT, NATS, SOURCE_CHANNEL_SIZE, STREAM, SUBJECTS,
+};
+
+#[derive(Debug)]
+pub struct NatsInputFormatBuilder {
+    fn_name: Option<String>,
+    client_config: NatsClientConfig,
+    stream: String,
+    subjects: Vec<String>,
+    durable: String,
+    deliver_policy: DeliverPolicy,
+    buffer_size: Option<usize>,
+}
+
+impl NatsInputFormatBuilder {
+    /// Each of the `subjects` is consumed by a subtask with the durable consumer named
+    /// `{durable}-{index}`, so the parallelism is the number of the subjects
+    pub fn new(
+        client_config: NatsClientConfig,
+        stream: &str,
+        subjects: Vec<String>,
+        durable: &str,
+    ) -> Self {
+        NatsInputFormatBuilder {
+            fn_name: None,
+            client_config,
+            stream: stream.to_string(),
+            subjects,
+            durable: durable.to_string(),
+            deliver_policy: DeliverPolicy::default(),
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    pub fn deliver_policy(mut self, deliver_policy: DeliverPolicy) -> Self {
+        self.deliver_policy = deliver_policy;
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn NatsRecordDeserializerBuilder>>,
+    ) -> NatsInputFormat {
+        info!("build nats source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn NatsRecordDeserializerBuilder> =
+                Box::new(DefaultNatsRecordDeserializerBuilder::<
+                    DefaultNatsRecordDeserializer,
+                >::new(FnSchema::from(
+                    &nats_message::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        NatsInputFormat::new(
+            self.client_config,
+            self.stream,
+            self.subjects,
+            self.durable,
+            buffer_size,
+            deserializer_builder,
+            fn_name,
+        )
+        .deliver_policy(self.deliver_policy)
+    }
+}
+
+impl TryFrom<Properties> for NatsInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let client_config = NatsClientConfig::try_from(properties.to_sub_properties(NATS))?;
+
+        let stream = properties.get_string(STREAM)?;
+        let subjects: Vec<String> = properties
+            .get_string(SUBJECTS)?
+            .trim()
+            .split(',')
+            .map(|x| x.to_string())
+            .collect();
+        let durable = properties.get_string(DURABLE)?;
+
+        let mut builder =
+            NatsInputFormatBuilder::new(client_config, stream.as_str(), subjects, durable.as_str());
+
+        builder = builder.fn_name(properties.name());
+
+        if let Ok(deliver_policy) = properties.get_string(DELIVER_POLICY) {
+            let deliver_policy = DeliverPolicy::try_from(deliver_policy.as_str())?;
+            builder = builder.deliver_policy(deliver_policy);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::runtime::TaskId;
+
+#[derive(Debug, Clone)]
+pub struct NatsCheckpointFunction {
+    pub(crate) state_recorder: Option<NatsSourceStateRecorder>,
+    #[allow(dead_code)]
+    pub(crate) application_id: String,
+    #[allow(dead_code)]
+    pub(crate) task_id: TaskId,
+    subject: String,
+}
+
+impl NatsCheckpointFunction {
+    pub fn new(application_id: String, task_id: TaskId, subject: &str) -> Self {
+        NatsCheckpointFunction {
+            state_recorder: None,
+            application_id,
+            task_id,
+            subject: subject.to_string(),
+        }
+    }
+
+    pub fn as_state_mut(&mut self) -> &mut NatsSourceStateRecorder {
+        self.state_recorder.as_mut().unwrap()
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for NatsCheckpointFunction {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.state_recorder = Some(NatsSourceStateRecorder::new(self.subject.as_str()));
+        info!("Checkpoint initialize, context: {:?}", context);
+
+        if context.checkpoint_id.is_default() || handle.is_none() {
+            return;
+        }
+
+        let handle = handle.as_ref().unwrap();
+
+        let state_cache = self.state_recorder.as_mut().unwrap();
+        state_cache
+            .update_from_snapshot(handle.handle.as_str())
+            .unwrap();
+
+        info!(
+            "load state value from checkpoint({:?}): {:?}",
+            context.checkpoint_id, handle.handle
+        );
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let handle = self.state_recorder.as_ref().unwrap().snapshot();
+        debug!("Checkpoint snapshot: {:?}, context: {:?}", handle, context);
+
+        Some(CheckpointHandle { handle })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SequenceSnapshot {
+    subject: String,
+    /// the stream sequence of the last received message by the filter subject
+    sequences: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NatsSourceStateRecorder {
+    subject: String,
+    sequences: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl NatsSourceStateRecorder {
+    pub fn new(subject: &str) -> Self {
+        NatsSourceStateRecorder {
+            subject: subject.to_string(),
+            sequences: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn update(&self, subject: &str, sequence: u64) {
+        let mut sequences = self.sequences.lock().unwrap();
+        match sequences.get_mut(subject) {
+            Some(last) => *last = sequence,
+            None => {
+                sequences.insert(subject.to_string(), sequence);
+            }
+        }
+    }
+
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let snapshot: SequenceSnapshot = serde_json::from_str(snapshot_handle)?;
+        if !snapshot.subject.eq(self.subject.as_str()) {
+            return Err(anyhow!("Does not belong to the checkpoint of the task"));
+        }
+
+        *self.sequences.lock().unwrap() = snapshot.sequences;
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> String {
+        let sequences = self.sequences.lock().unwrap().clone();
+        serde_json::to_string(&SequenceSnapshot {
+            subject: self.subject.clone(),
+            sequences,
+        })
+        .unwrap()
+    }
+
+    pub fn get(&self, subject: &str) -> Option<u64> {
+        self.sequences.lock().unwrap().get(subject).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::NatsSourceStateRecorder;
+
+    #[test]
+    pub fn state_snapshot_test() {
+        let subject = "orders.created";
+        let recorder = NatsSourceStateRecorder::new(subject);
+        assert!(recorder.get(subject).is_none());
+
+        recorder.update(subject, 41);
+        recorder.update(subject, 42);
+        let snapshot = recorder.snapshot();
+
+        let restored = NatsSourceStateRecorder::new(subject);
+        restored.update_from_snapshot(snapshot.as_str()).unwrap();
+        assert_eq!(restored.get(subject), Some(42));
+
+        let other = NatsSourceStateRecorder::new("orders.cancelled");
+        assert!(other.update_from_snapshot(snapshot.as_str()).is_err());
+    }
+}
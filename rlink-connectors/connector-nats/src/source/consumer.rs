@@ -0,0 +1,165 @@
+use async_nats::jetstream::consumer::{pull, AckPolicy, Consumer};
+use async_nats::jetstream::stream::Stream;
+use futures::StreamExt;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::{register_counter, Tag};
+
+use crate::metrics::CONSUMER_RECEIVED;
+use crate::source::deliver_policy::DeliverPolicy;
+use crate::source::deserializer::NatsRecordDeserializer;
+use crate::source::ConsumerRecord;
+use crate::NatsClientConfig;
+
+pub(crate) async fn create_nats_consumer(mut nats_consumer: NatsConsumerThread) {
+    tokio::spawn(async move {
+        match nats_consumer.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!("run consumer error. {}", e);
+            }
+        }
+    });
+}
+
+pub(crate) struct NatsConsumerThread {
+    task_id: TaskId,
+
+    client_config: NatsClientConfig,
+    stream: String,
+    subject: String,
+    /// the name of the durable consumer on the server
+    durable: String,
+    deliver_policy: DeliverPolicy,
+    /// the stream sequence restored from the checkpoint
+    restored: Option<u64>,
+
+    sender: ChannelSender<ConsumerRecord>,
+    deserializer: Box<dyn NatsRecordDeserializer>,
+}
+
+impl NatsConsumerThread {
+    pub fn new(
+        task_id: TaskId,
+        client_config: NatsClientConfig,
+        stream: String,
+        subject: String,
+        durable: String,
+        sender: ChannelSender<ConsumerRecord>,
+        deserializer: Box<dyn NatsRecordDeserializer>,
+    ) -> Self {
+        NatsConsumerThread {
+            task_id,
+            client_config,
+            stream,
+            subject,
+            durable,
+            deliver_policy: DeliverPolicy::default(),
+            restored: None,
+            sender,
+            deserializer,
+        }
+    }
+
+    pub fn deliver_policy(mut self, deliver_policy: DeliverPolicy) -> Self {
+        self.deliver_policy = deliver_policy;
+        self
+    }
+
+    pub fn restored(mut self, restored: Option<u64>) -> Self {
+        self.restored = restored;
+        self
+    }
+
+    /// The durable consumer is recreated to start after the restored sequence, the messages
+    /// after the checkpoint are redelivered no matter they are acknowledged or not
+    async fn consumer(&self, stream: &Stream) -> anyhow::Result<Consumer<pull::Config>> {
+        let mut config = pull::Config {
+            durable_name: Some(self.durable.clone()),
+            filter_subject: self.subject.clone(),
+            ack_policy: AckPolicy::Explicit,
+            deliver_policy: self.deliver_policy.to_jetstream(),
+            ..Default::default()
+        };
+
+        match self.restored {
+            Some(sequence) => {
+                if let Err(e) = stream.delete_consumer(self.durable.as_str()).await {
+                    debug!("delete nats consumer({}) error. {}", self.durable, e);
+                }
+
+                config.deliver_policy =
+                    async_nats::jetstream::consumer::DeliverPolicy::ByStartSequence {
+                        start_sequence: sequence + 1,
+                    };
+                stream
+                    .create_consumer(config)
+                    .await
+                    .map_err(|e| anyhow!("create nats consumer({}) error. {}", self.durable, e))
+            }
+            None => stream
+                .get_or_create_consumer(self.durable.as_str(), config)
+                .await
+                .map_err(|e| anyhow!("create nats consumer({}) error. {}", self.durable, e)),
+        }
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let jetstream = self.client_config.connect().await?;
+        let stream = jetstream
+            .get_stream(self.stream.as_str())
+            .await
+            .map_err(|e| anyhow!("get nats stream({}) error. {}", self.stream, e))?;
+
+        let consumer = self.consumer(&stream).await?;
+        let mut messages = consumer
+            .messages()
+            .await
+            .map_err(|e| anyhow!("pull nats messages error. {}", e))?;
+
+        info!(
+            "create consumer success. config: {:?}, stream: {}, subject: {}, durable: {}, restored: {:?}, job_id: {}, task_num: {}",
+            self.client_config, self.stream, self.subject, self.durable, self.restored, *self.task_id.job_id(), self.task_id.task_number()
+        );
+
+        let mut tags = self.task_id.to_tags();
+        tags.push(Tag::new("subject", self.subject.as_str()));
+        let received_counter = register_counter(CONSUMER_RECEIVED, tags);
+
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(|e| anyhow!("receive nats message error. {}", e))?;
+            received_counter.increment(1);
+
+            let info = message
+                .info()
+                .map_err(|e| anyhow!("parse nats message info error. {}", e))?;
+            let sequence = info.stream_sequence;
+            let timestamp = (info.published.unix_timestamp_nanos() / 1_000_000) as i64;
+
+            let records = self.deserializer.deserialize(
+                timestamp,
+                message.subject.as_str(),
+                message.payload.as_ref(),
+                sequence as i64,
+            );
+            for record in records {
+                self.sender
+                    .send(ConsumerRecord::new(record, self.subject.clone(), sequence))
+                    .await
+                    .expect("nats consumer handover `Disconnected`");
+            }
+
+            // the progress is restored from the checkpoint, the ack only stops the redelivery
+            if let Err(e) = message.ack().await {
+                warn!(
+                    "Nats ack error. job_id: {}, task_num: {}, error: {}",
+                    *self.task_id.job_id(),
+                    self.task_id.task_number(),
+                    e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use std::convert::TryFrom;
+
+/// Where the durable consumer starts when there is no checkpoint to restore
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DeliverPolicy {
+    /// from the first message of the stream
+    #[default]
+    All,
+    /// from the messages published after the consumer is created
+    New,
+}
+
+impl DeliverPolicy {
+    pub(crate) fn to_jetstream(self) -> async_nats::jetstream::consumer::DeliverPolicy {
+        match self {
+            DeliverPolicy::All => async_nats::jetstream::consumer::DeliverPolicy::All,
+            DeliverPolicy::New => async_nats::jetstream::consumer::DeliverPolicy::New,
+        }
+    }
+}
+
+impl TryFrom<&str> for DeliverPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "all" => Ok(DeliverPolicy::All),
+            "new" => Ok(DeliverPolicy::New),
+            _ => Err(anyhow!("unknown nats deliver policy `{}`", value)),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+
+use crate::build_nats_record;
+
+pub trait NatsRecordDeserializer: Sync + Send {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        subject: &str,
+        payload: &[u8],
+        sequence: i64,
+    ) -> Vec<Record>;
+}
+
+pub trait NatsRecordDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn NatsRecordDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+#[derive(Default)]
+pub struct DefaultNatsRecordDeserializer {}
+
+impl NatsRecordDeserializer for DefaultNatsRecordDeserializer {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        subject: &str,
+        payload: &[u8],
+        sequence: i64,
+    ) -> Vec<Record> {
+        let record = build_nats_record(timestamp, subject, payload, sequence)
+            .expect("nats message writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultNatsRecordDeserializerBuilder<T>
+where
+    T: Default + NatsRecordDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultNatsRecordDeserializerBuilder<T>
+where
+    T: Default + NatsRecordDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultNatsRecordDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> NatsRecordDeserializerBuilder for DefaultNatsRecordDeserializerBuilder<T>
+where
+    T: Default + NatsRecordDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn NatsRecordDeserializer> {
+        let t: Box<dyn NatsRecordDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::properties::Properties;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::Tag;
+
+use crate::source::checkpoint::NatsCheckpointFunction;
+use crate::source::consumer::{create_nats_consumer, NatsConsumerThread};
+use crate::source::deliver_policy::DeliverPolicy;
+use crate::source::deserializer::NatsRecordDeserializerBuilder;
+use crate::source::stream::NatsRecordStream;
+use crate::NatsClientConfig;
+
+/// The filter subject consumed by the split
+const SPLIT_SUBJECT: &str = "subject";
+/// The durable consumer name of the split
+const SPLIT_DURABLE: &str = "durable";
+
+pub struct NatsInputFormat {
+    name: String,
+    parallelism: u16,
+
+    client_config: NatsClientConfig,
+    stream: String,
+    subjects: Vec<String>,
+    durable: String,
+    deliver_policy: DeliverPolicy,
+
+    task_id: TaskId,
+    task_subject: String,
+    task_durable: String,
+
+    buffer_size: usize,
+
+    tags: Vec<Tag>,
+
+    deserializer_builder: Arc<dyn NatsRecordDeserializerBuilder>,
+    schema: FnSchema,
+
+    checkpoint: Option<NatsCheckpointFunction>,
+}
+
+impl NatsInputFormat {
+    pub fn new(
+        client_config: NatsClientConfig,
+        stream: String,
+        subjects: Vec<String>,
+        durable: String,
+        buffer_size: usize,
+        deserializer_builder: Box<dyn NatsRecordDeserializerBuilder>,
+        fn_name: String,
+    ) -> Self {
+        let schema = deserializer_builder.schema();
+        NatsInputFormat {
+            name: fn_name,
+            parallelism: subjects.len() as u16,
+            client_config,
+            stream,
+            subjects,
+            durable,
+            deliver_policy: DeliverPolicy::default(),
+            task_id: Default::default(),
+            task_subject: "".to_string(),
+            task_durable: "".to_string(),
+            buffer_size,
+            tags: vec![],
+            deserializer_builder: Arc::from(deserializer_builder),
+            schema,
+            checkpoint: None,
+        }
+    }
+
+    pub fn deliver_policy(mut self, deliver_policy: DeliverPolicy) -> Self {
+        self.deliver_policy = deliver_policy;
+        self
+    }
+}
+
+impl NamedFunction for NatsInputFormat {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl InputFormat for NatsInputFormat {
+    async fn open(&mut self, input_split: InputSplit, context: &Context) -> core::Result<()> {
+        info!("nats source open");
+
+        self.task_id = context.task_id.clone();
+        self.task_subject = input_split.properties().get_string(SPLIT_SUBJECT)?;
+        self.task_durable = input_split.properties().get_string(SPLIT_DURABLE)?;
+
+        let checkpoint = NatsCheckpointFunction::new(
+            context.application_id.clone(),
+            context.task_id,
+            self.task_subject.as_str(),
+        );
+        self.checkpoint = Some(checkpoint);
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        self.tags
+            .push(Tag::new("subject", self.task_subject.as_str()));
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("NatsSource_Handover", self.tags.clone(), self.buffer_size);
+
+        let state_recorder = self.checkpoint.as_mut().unwrap().as_state_mut().clone();
+        let restored = state_recorder.get(self.task_subject.as_str());
+
+        let nats_consumer = NatsConsumerThread::new(
+            self.task_id.clone(),
+            self.client_config.clone(),
+            self.stream.clone(),
+            self.task_subject.clone(),
+            self.task_durable.clone(),
+            sender,
+            self.deserializer_builder.build(),
+        )
+        .deliver_policy(self.deliver_policy)
+        .restored(restored);
+        create_nats_consumer(nats_consumer).await;
+
+        Box::pin(NatsRecordStream::new(receiver, state_recorder))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for NatsInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.checkpoint
+            .as_mut()
+            .unwrap()
+            .initialize_state(context, handle)
+            .await;
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        match self.checkpoint.as_mut() {
+            Some(checkpoint) => checkpoint.snapshot_state(context).await,
+            None => None,
+        }
+    }
+}
+
+impl InputSplitSource for NatsInputFormat {
+    /// Each subject is consumed by a durable consumer of its own, so the stream sequences of a
+    /// split are increasing and the last one is checkpointed
+    fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
+        info!("nats config {:?}", self.client_config);
+
+        if self.subjects.len() != min_num_splits as usize {
+            return Err(rlink::core::Error::from(format!(
+                "nats source parallelism({}) is not equal to the subjects({})",
+                min_num_splits,
+                self.subjects.len()
+            )));
+        }
+
+        let mut input_splits = Vec::with_capacity(self.subjects.len());
+        for (index, subject) in self.subjects.iter().enumerate() {
+            let mut properties = Properties::new();
+            properties.set_str(SPLIT_SUBJECT, subject.as_str());
+            properties.set_string(
+                SPLIT_DURABLE.to_string(),
+                format!("{}-{}", self.durable, index),
+            );
+            input_splits.push(InputSplit::new(index as u16, properties));
+        }
+
+        Ok(input_splits)
+    }
+}
@@ -0,0 +1,24 @@
+pub mod builder;
+pub mod checkpoint;
+pub mod consumer;
+pub mod deliver_policy;
+pub mod deserializer;
+pub mod input_format;
+pub mod stream;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ConsumerRecord {
+    record: rlink::core::element::Record,
+    subject: String,
+    sequence: u64,
+}
+
+impl ConsumerRecord {
+    pub fn new(record: rlink::core::element::Record, subject: String, sequence: u64) -> Self {
+        ConsumerRecord {
+            record,
+            subject,
+            sequence,
+        }
+    }
+}
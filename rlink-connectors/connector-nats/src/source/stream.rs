@@ -0,0 +1,51 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::NatsSourceStateRecorder;
+use crate::source::ConsumerRecord;
+
+/// Simulate a Nats consumption stream as an iterator.
+pub struct NatsRecordStream {
+    receiver: ChannelReceiver<ConsumerRecord>,
+    state_recorder: NatsSourceStateRecorder,
+}
+
+impl NatsRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<ConsumerRecord>,
+        state_recorder: NatsSourceStateRecorder,
+    ) -> Self {
+        NatsRecordStream {
+            receiver,
+            state_recorder,
+        }
+    }
+}
+
+impl ElementStream for NatsRecordStream {}
+
+impl Stream for NatsRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.as_mut().receiver.poll_recv(cx) {
+            Poll::Ready(Some(consumer_record)) => {
+                let ConsumerRecord {
+                    record,
+                    subject,
+                    sequence,
+                } = consumer_record;
+                self.state_recorder.update(subject.as_str(), sequence);
+
+                Poll::Ready(Some(Element::Record(record)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use serbuffer_gen::{Codegen, DataType::*, SchemaBuilder};
+
+fn main() {
+    Codegen::out_dir("buffer_gen")
+        .schema(
+            SchemaBuilder::new("PostgresChangeRecord")
+                .field("timestamp", I64)
+                .field("op", STRING)
+                .field("schema", STRING)
+                .field("table", STRING)
+                .field("before", STRING)
+                .field("after", STRING)
+                .field("lsn", STRING),
+        )
+        .gen()
+        .expect("buffer gen error");
+}
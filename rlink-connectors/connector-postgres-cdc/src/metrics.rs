@@ -0,0 +1,38 @@
+use rlink::metrics::{register_counter, register_gauge, Counter, Gauge, Tag};
+
+pub const SNAPSHOT_ROWS: &str = "PostgresCdc.Snapshot.Rows";
+pub const STREAM_ROWS: &str = "PostgresCdc.Stream.Rows";
+pub const STREAM_DELAY: &str = "PostgresCdc.Stream.Delay";
+
+/// Metrics of the cdc reader, tagged by the task and slot
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// rows read by the initial snapshot
+    snapshot_rows: Counter,
+    /// row changes read from the replication slot
+    stream_rows: Counter,
+    /// millis between the transaction committed and read by the source
+    stream_delay: Gauge,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            snapshot_rows: register_counter(SNAPSHOT_ROWS, tags.clone()),
+            stream_rows: register_counter(STREAM_ROWS, tags.clone()),
+            stream_delay: register_gauge(STREAM_DELAY, tags),
+        }
+    }
+
+    pub fn snapshot_row(&self) {
+        self.snapshot_rows.increment(1);
+    }
+
+    pub fn stream_row(&self) {
+        self.stream_rows.increment(1);
+    }
+
+    pub fn stream_delay(&self, delay: i64) {
+        self.stream_delay.set(delay.max(0) as f64);
+    }
+}
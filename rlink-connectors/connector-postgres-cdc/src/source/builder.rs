@@ -0,0 +1,133 @@
+use std::convert::TryFrom;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::postgres_change_record;
+use crate::source::deserializer::{
+    DefaultPostgresChangeDeserializer, DefaultPostgresChangeDeserializerBuilder,
+    PostgresChangeDeserializerBuilder,
+};
+use crate::source::startup_mode::StartupMode;
+use crate::source::table::TableId;
+use crate::{
+    PostgresCdcInputFormat, PostgresClientConfig, BUFFER_SIZE, INPUT_FORMAT_FN_NAME_DEFAULT,
+    POSTGRES, PUBLICATION, SLOT, SOURCE_CHANNEL_SIZE, STARTUP_MODE, TABLES,
+};
+
+#[derive(Debug)]
+pub struct PostgresCdcInputFormatBuilder {
+    fn_name: Option<String>,
+    client_config: PostgresClientConfig,
+    slot: String,
+    publication: String,
+    tables: Vec<TableId>,
+    startup_mode: StartupMode,
+    buffer_size: Option<usize>,
+}
+
+impl PostgresCdcInputFormatBuilder {
+    /// Capture the row changes of the `tables` by the logical replication `slot` of the
+    /// `publication`, the publication is created for the tables if not exists. The server must
+    /// run with `wal_level = logical`
+    pub fn new(
+        client_config: PostgresClientConfig,
+        slot: &str,
+        publication: &str,
+        tables: Vec<TableId>,
+    ) -> Self {
+        PostgresCdcInputFormatBuilder {
+            fn_name: None,
+            client_config,
+            slot: slot.to_string(),
+            publication: publication.to_string(),
+            tables,
+            startup_mode: StartupMode::default(),
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    pub fn startup_mode(mut self, startup_mode: StartupMode) -> Self {
+        self.startup_mode = startup_mode;
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn PostgresChangeDeserializerBuilder>>,
+    ) -> PostgresCdcInputFormat {
+        info!("build postgres cdc source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn PostgresChangeDeserializerBuilder> =
+                Box::new(DefaultPostgresChangeDeserializerBuilder::<
+                    DefaultPostgresChangeDeserializer,
+                >::new(FnSchema::from(
+                    &postgres_change_record::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        PostgresCdcInputFormat::new(
+            self.client_config,
+            self.slot,
+            self.publication,
+            self.tables,
+            buffer_size,
+            deserializer_builder,
+            fn_name,
+        )
+        .startup_mode(self.startup_mode)
+    }
+}
+
+impl TryFrom<Properties> for PostgresCdcInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let client_config = PostgresClientConfig::try_from(properties.to_sub_properties(POSTGRES))?;
+
+        let slot = properties.get_string(SLOT)?;
+        let publication = properties.get_string(PUBLICATION)?;
+        let mut tables = Vec::new();
+        for table in properties.get_string(TABLES)?.split(',') {
+            tables.push(TableId::try_from(table)?);
+        }
+
+        let mut builder = PostgresCdcInputFormatBuilder::new(
+            client_config,
+            slot.as_str(),
+            publication.as_str(),
+            tables,
+        );
+
+        builder = builder.fn_name(properties.name());
+
+        if let Ok(startup_mode) = properties.get_string(STARTUP_MODE) {
+            let startup_mode = StartupMode::try_from(startup_mode.as_str())?;
+            builder = builder.startup_mode(startup_mode);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,116 @@
+use serde_json::{Map, Number, Value};
+
+const BOOL_OID: u32 = 16;
+const INT8_OID: u32 = 20;
+const INT2_OID: u32 = 21;
+const INT4_OID: u32 = 23;
+const OID_OID: u32 = 26;
+const JSON_OID: u32 = 114;
+const FLOAT4_OID: u32 = 700;
+const FLOAT8_OID: u32 = 701;
+const NUMERIC_OID: u32 = 1700;
+const JSONB_OID: u32 = 3802;
+
+/// The kind of a row change, named as the `op` of Debezium
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    /// a row read by the initial snapshot
+    Read,
+    Create,
+    Update,
+    Delete,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Read => "r",
+            Operation::Create => "c",
+            Operation::Update => "u",
+            Operation::Delete => "d",
+        }
+    }
+}
+
+/// A row change of a table, the row images are the column values by the column names
+#[derive(Clone, Debug)]
+pub struct ChangeEvent {
+    /// millis the transaction is committed, or the snapshot is taken
+    pub timestamp: i64,
+    pub op: Operation,
+    pub schema: String,
+    pub table: String,
+    /// the row before the change, only for `Update` and `Delete`. It has only the key columns
+    /// unless the table is altered to `REPLICA IDENTITY FULL`
+    pub before: Option<Map<String, Value>>,
+    /// the row after the change, except for `Delete`
+    pub after: Option<Map<String, Value>>,
+    /// the lsn of the change, e.g. `16/B374D848`
+    pub lsn: String,
+}
+
+/// Convert the text value of a column by its type, the types without a json counterpart are
+/// kept as the text
+pub(crate) fn to_json(type_oid: u32, text: &str) -> Value {
+    match type_oid {
+        BOOL_OID => Value::Bool(text == "t"),
+        INT2_OID | INT4_OID | INT8_OID | OID_OID => match text.parse::<i64>() {
+            Ok(v) => Value::Number(Number::from(v)),
+            Err(_) => Value::String(text.to_string()),
+        },
+        FLOAT4_OID | FLOAT8_OID | NUMERIC_OID => match text.parse::<f64>() {
+            // `NaN` and `Infinity` are kept as the text
+            Ok(v) => Number::from_f64(v)
+                .map(Value::Number)
+                .unwrap_or_else(|| Value::String(text.to_string())),
+            Err(_) => Value::String(text.to_string()),
+        },
+        JSON_OID | JSONB_OID => {
+            serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+        }
+        _ => Value::String(text.to_string()),
+    }
+}
+
+/// Zip the column names and values to a row image, the absent values(the unchanged toasted
+/// values) are skipped
+pub(crate) fn to_row_image(columns: &[String], values: Vec<Option<Value>>) -> Map<String, Value> {
+    let mut row = Map::with_capacity(values.len());
+    for (column, value) in columns.iter().zip(values) {
+        if let Some(value) = value {
+            row.insert(column.clone(), value);
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::change::{to_json, to_row_image};
+
+    #[test]
+    pub fn to_json_test() {
+        let columns = vec![
+            "id".to_string(),
+            "paid".to_string(),
+            "amount".to_string(),
+            "extra".to_string(),
+            "note".to_string(),
+            "created_at".to_string(),
+        ];
+        let values = vec![
+            Some(to_json(20, "42")),
+            Some(to_json(16, "t")),
+            Some(to_json(1700, "9.5")),
+            Some(to_json(3802, r#"{"tag": "vip"}"#)),
+            None,
+            Some(to_json(1114, "2022-10-01 08:30:00")),
+        ];
+
+        let row = serde_json::Value::Object(to_row_image(&columns, values));
+        assert_eq!(
+            row.to_string(),
+            r#"{"amount":9.5,"created_at":"2022-10-01 08:30:00","extra":{"tag":"vip"},"id":42,"paid":true}"#
+        );
+    }
+}
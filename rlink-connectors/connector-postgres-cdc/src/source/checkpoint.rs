@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::runtime::TaskId;
+
+use crate::source::offset::LsnOffset;
+
+#[derive(Debug, Clone)]
+pub struct PostgresCdcCheckpointFunction {
+    pub(crate) state_recorder: Option<PostgresCdcStateRecorder>,
+    #[allow(dead_code)]
+    pub(crate) application_id: String,
+    #[allow(dead_code)]
+    pub(crate) task_id: TaskId,
+}
+
+impl PostgresCdcCheckpointFunction {
+    pub fn new(application_id: String, task_id: TaskId) -> Self {
+        PostgresCdcCheckpointFunction {
+            state_recorder: None,
+            application_id,
+            task_id,
+        }
+    }
+
+    pub fn as_state_mut(&mut self) -> &mut PostgresCdcStateRecorder {
+        self.state_recorder.as_mut().unwrap()
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PostgresCdcCheckpointFunction {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.state_recorder = Some(PostgresCdcStateRecorder::new());
+        info!("Checkpoint initialize, context: {:?}", context);
+
+        if context.checkpoint_id.is_default() || handle.is_none() {
+            return;
+        }
+
+        let handle = handle.as_ref().unwrap();
+
+        let state_cache = self.state_recorder.as_mut().unwrap();
+        state_cache
+            .update_from_snapshot(handle.handle.as_str())
+            .unwrap();
+
+        info!(
+            "load state value from checkpoint({:?}): {:?}",
+            context.checkpoint_id, handle.handle
+        );
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let state_recorder = self.state_recorder.as_ref().unwrap();
+        let handle = state_recorder.snapshot(context.checkpoint_id.0);
+        debug!("Checkpoint snapshot: {:?}, context: {:?}", handle, context);
+
+        if let Some(completed_checkpoint_id) = context.completed_checkpoint_id {
+            state_recorder.complete(completed_checkpoint_id.0);
+        }
+
+        Some(CheckpointHandle { handle })
+    }
+}
+
+#[derive(Debug, Default)]
+struct LsnState {
+    /// the offset after the last record emitted to the stream
+    offset: Option<LsnOffset>,
+    /// the committed lsn of the checkpoints taken but not completed yet
+    pending: BTreeMap<u64, u64>,
+}
+
+/// Tracks the offset of the stream and the lsn covered by the completed checkpoints.
+///
+/// The slot is confirmed only to the lsn of a completed checkpoint, so the server retains the
+/// wal the source may be restored to.
+#[derive(Debug, Clone, Default)]
+pub struct PostgresCdcStateRecorder {
+    state: Arc<Mutex<LsnState>>,
+    confirmed: Arc<AtomicU64>,
+}
+
+impl PostgresCdcStateRecorder {
+    pub fn new() -> Self {
+        PostgresCdcStateRecorder::default()
+    }
+
+    pub fn update(&self, offset: LsnOffset) {
+        self.state.lock().unwrap().offset = Some(offset);
+    }
+
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let offset: Option<LsnOffset> = serde_json::from_str(snapshot_handle)?;
+        self.state.lock().unwrap().offset = offset;
+        Ok(())
+    }
+
+    /// Snapshot the offset as the part of the checkpoint
+    pub fn snapshot(&self, checkpoint_id: u64) -> String {
+        let mut state = self.state.lock().unwrap();
+        if let Some(offset) = state.offset.as_ref() {
+            if offset.snapshot_completed {
+                let lsn = offset.lsn;
+                state.pending.insert(checkpoint_id, lsn);
+            }
+        }
+
+        serde_json::to_string(&state.offset).unwrap()
+    }
+
+    /// Confirm the lsn of the completed checkpoint
+    pub fn complete(&self, checkpoint_id: u64) {
+        let mut state = self.state.lock().unwrap();
+
+        let uncompleted = state.pending.split_off(&(checkpoint_id + 1));
+        let completed = std::mem::replace(&mut state.pending, uncompleted);
+        if let Some(lsn) = completed.values().max() {
+            self.confirmed.fetch_max(*lsn, Ordering::SeqCst);
+        }
+    }
+
+    pub fn get(&self) -> Option<LsnOffset> {
+        self.state.lock().unwrap().offset.clone()
+    }
+
+    /// The lsn safe to be released by the slot
+    pub fn confirmed(&self) -> u64 {
+        self.confirmed.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::PostgresCdcStateRecorder;
+    use crate::source::offset::LsnOffset;
+
+    #[test]
+    pub fn confirm_on_complete_test() {
+        let recorder = PostgresCdcStateRecorder::new();
+
+        // the lsn of the unfinished snapshot is never confirmed
+        recorder.update(LsnOffset::new(100));
+        recorder.snapshot(1);
+        recorder.complete(1);
+        assert_eq!(recorder.confirmed(), 0);
+
+        let mut offset = LsnOffset::new(200);
+        offset.snapshot_completed = true;
+        recorder.update(offset.clone());
+        recorder.snapshot(2);
+
+        offset.lsn = 300;
+        offset.skip_rows = 2;
+        recorder.update(offset.clone());
+        let handle = recorder.snapshot(3);
+
+        recorder.complete(2);
+        assert_eq!(recorder.confirmed(), 200);
+        recorder.complete(3);
+        assert_eq!(recorder.confirmed(), 300);
+
+        let restored = PostgresCdcStateRecorder::new();
+        restored.update_from_snapshot(handle.as_str()).unwrap();
+        assert_eq!(restored.get(), Some(offset));
+    }
+}
@@ -0,0 +1,82 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+use serde_json::{Map, Value};
+
+use crate::build_postgres_change_record;
+use crate::source::change::ChangeEvent;
+
+pub trait PostgresChangeDeserializer: Sync + Send {
+    fn deserialize(&mut self, event: &ChangeEvent) -> Vec<Record>;
+}
+
+pub trait PostgresChangeDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn PostgresChangeDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+/// Build the `PostgresChangeRecord` with the row images in json, the absent image is empty
+#[derive(Default)]
+pub struct DefaultPostgresChangeDeserializer {}
+
+impl DefaultPostgresChangeDeserializer {
+    fn image_json(image: &Option<Map<String, Value>>) -> String {
+        match image {
+            Some(image) => serde_json::to_string(image).unwrap(),
+            None => "".to_string(),
+        }
+    }
+}
+
+impl PostgresChangeDeserializer for DefaultPostgresChangeDeserializer {
+    fn deserialize(&mut self, event: &ChangeEvent) -> Vec<Record> {
+        let before = Self::image_json(&event.before);
+        let after = Self::image_json(&event.after);
+
+        let record = build_postgres_change_record(
+            event.timestamp,
+            event.op.as_str(),
+            event.schema.as_str(),
+            event.table.as_str(),
+            before.as_str(),
+            after.as_str(),
+            event.lsn.as_str(),
+        )
+        .expect("postgres change writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultPostgresChangeDeserializerBuilder<T>
+where
+    T: Default + PostgresChangeDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultPostgresChangeDeserializerBuilder<T>
+where
+    T: Default + PostgresChangeDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultPostgresChangeDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> PostgresChangeDeserializerBuilder for DefaultPostgresChangeDeserializerBuilder<T>
+where
+    T: Default + PostgresChangeDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn PostgresChangeDeserializer> {
+        let t: Box<dyn PostgresChangeDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
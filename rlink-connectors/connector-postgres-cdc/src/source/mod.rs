@@ -0,0 +1,25 @@
+pub mod builder;
+pub mod change;
+pub mod checkpoint;
+pub mod deserializer;
+pub mod input_format;
+pub mod offset;
+pub(crate) mod pgoutput;
+pub mod reader;
+pub(crate) mod replication;
+pub mod startup_mode;
+pub mod stream;
+pub mod table;
+
+use crate::source::offset::LsnOffset;
+
+#[derive(Clone, Debug)]
+pub(crate) enum ConsumerRecord {
+    /// a record of the row change, and the offset after it
+    Record {
+        record: rlink::core::element::Record,
+        offset: LsnOffset,
+    },
+    /// the offset moved without row changes, by a commit or the end of the snapshot
+    Progress { offset: LsnOffset },
+}
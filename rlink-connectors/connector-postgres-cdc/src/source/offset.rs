@@ -0,0 +1,48 @@
+/// Format the lsn as postgres does, e.g. `16/B374D848`
+pub fn format_lsn(lsn: u64) -> String {
+    format!("{:X}/{:X}", lsn >> 32, lsn & 0xFFFF_FFFF)
+}
+
+pub fn parse_lsn(value: &str) -> anyhow::Result<u64> {
+    let (high, low) = value
+        .split_once('/')
+        .ok_or_else(|| anyhow!("illegal lsn `{}`", value))?;
+    let high = u64::from_str_radix(high, 16)?;
+    let low = u64::from_str_radix(low, 16)?;
+    Ok((high << 32) | low)
+}
+
+/// The replication progress of the source. The transactions committed before the `lsn` are
+/// skipped on replay, and so are the row changes of the transaction in progress emitted before
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LsnOffset {
+    /// the end of the last committed transaction emitted, or the consistent point of the slot
+    pub lsn: u64,
+    /// the row changes of the transaction in progress emitted already
+    pub skip_rows: u64,
+    /// the initial snapshot is completed, otherwise it's taken again on restart
+    pub snapshot_completed: bool,
+}
+
+impl LsnOffset {
+    pub fn new(lsn: u64) -> Self {
+        LsnOffset {
+            lsn,
+            skip_rows: 0,
+            snapshot_completed: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::offset::{format_lsn, parse_lsn};
+
+    #[test]
+    pub fn lsn_test() {
+        assert_eq!(parse_lsn("16/B374D848").unwrap(), 0x16_B374_D848);
+        assert_eq!(format_lsn(0x16_B374_D848), "16/B374D848");
+        assert_eq!(format_lsn(parse_lsn("0/0").unwrap()), "0/0");
+        assert!(parse_lsn("B374D848").is_err());
+    }
+}
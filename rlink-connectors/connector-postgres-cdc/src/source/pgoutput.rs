@@ -0,0 +1,362 @@
+//! Decode the messages of the streaming replication protocol and the `pgoutput` plugin(v1),
+//! see https://www.postgresql.org/docs/current/protocol-logicalrep-message-formats.html
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// Millis from the unix epoch to the postgres epoch(2000-01-01)
+const POSTGRES_EPOCH_MILLIS: i64 = 946_684_800_000;
+
+/// Convert the micros since the postgres epoch to the unix millis
+fn to_unix_millis(micros: i64) -> i64 {
+    micros / 1000 + POSTGRES_EPOCH_MILLIS
+}
+
+fn check_remaining(buf: &Bytes, len: usize) -> anyhow::Result<()> {
+    if buf.remaining() < len {
+        return Err(anyhow!("unexpected end of the replication message"));
+    }
+    Ok(())
+}
+
+fn get_cstring(buf: &mut Bytes) -> anyhow::Result<String> {
+    let end = buf
+        .iter()
+        .position(|x| *x == 0)
+        .ok_or_else(|| anyhow!("unterminated string in the replication message"))?;
+    let value = String::from_utf8_lossy(&buf[..end]).to_string();
+    buf.advance(end + 1);
+    Ok(value)
+}
+
+/// The message wrapped in the `CopyData` of the replication stream
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ReplicationMessage {
+    XLogData { wal_start: u64, data: Bytes },
+    KeepAlive { wal_end: u64, reply: bool },
+}
+
+impl ReplicationMessage {
+    pub fn decode(mut buf: Bytes) -> anyhow::Result<Self> {
+        check_remaining(&buf, 1)?;
+        match buf.get_u8() {
+            b'w' => {
+                check_remaining(&buf, 24)?;
+                let wal_start = buf.get_u64();
+                let _wal_end = buf.get_u64();
+                let _send_time = buf.get_i64();
+                Ok(ReplicationMessage::XLogData {
+                    wal_start,
+                    data: buf,
+                })
+            }
+            b'k' => {
+                check_remaining(&buf, 17)?;
+                let wal_end = buf.get_u64();
+                let _send_time = buf.get_i64();
+                let reply = buf.get_u8() == 1;
+                Ok(ReplicationMessage::KeepAlive { wal_end, reply })
+            }
+            tag => Err(anyhow!("unknown replication message `{}`", tag as char)),
+        }
+    }
+}
+
+/// The standby status update reporting the progress to the server, the wal before the
+/// `flush_lsn` is released by the slot
+pub(crate) fn standby_status_update(write_lsn: u64, flush_lsn: u64, timestamp: i64) -> Bytes {
+    let mut buf = BytesMut::with_capacity(34);
+    buf.put_u8(b'r');
+    buf.put_u64(write_lsn);
+    buf.put_u64(flush_lsn);
+    buf.put_u64(flush_lsn);
+    buf.put_i64((timestamp - POSTGRES_EPOCH_MILLIS) * 1000);
+    buf.put_u8(0);
+    buf.freeze()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Column {
+    pub name: String,
+    pub type_oid: u32,
+}
+
+/// The table definition, sent before the first change of the table in a session and again
+/// after the definition is changed
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Relation {
+    pub id: u32,
+    pub namespace: String,
+    pub name: String,
+    pub columns: Vec<Column>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum TupleValue {
+    Null,
+    /// the unchanged toasted value which is not sent
+    Unchanged,
+    Text(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PgOutputMessage {
+    Begin {
+        /// the lsn of the commit record of the transaction
+        final_lsn: u64,
+        timestamp: i64,
+    },
+    Commit {
+        end_lsn: u64,
+    },
+    Relation(Relation),
+    Insert {
+        relation_id: u32,
+        new: Vec<TupleValue>,
+    },
+    Update {
+        relation_id: u32,
+        /// the old key, or the old row with the `REPLICA IDENTITY FULL`
+        old: Option<Vec<TupleValue>>,
+        new: Vec<TupleValue>,
+    },
+    Delete {
+        relation_id: u32,
+        old: Vec<TupleValue>,
+    },
+    /// origin, type, truncate and the logical decoding messages
+    Other(u8),
+}
+
+fn get_tuple(buf: &mut Bytes) -> anyhow::Result<Vec<TupleValue>> {
+    check_remaining(buf, 2)?;
+    let columns = buf.get_i16();
+
+    let mut tuple = Vec::with_capacity(columns.max(0) as usize);
+    for _ in 0..columns {
+        check_remaining(buf, 1)?;
+        let value = match buf.get_u8() {
+            b'n' => TupleValue::Null,
+            b'u' => TupleValue::Unchanged,
+            b't' => {
+                check_remaining(buf, 4)?;
+                let len = buf.get_i32().max(0) as usize;
+                check_remaining(buf, len)?;
+                let value = String::from_utf8_lossy(&buf[..len]).to_string();
+                buf.advance(len);
+                TupleValue::Text(value)
+            }
+            kind => return Err(anyhow!("unknown tuple data `{}`", kind as char)),
+        };
+        tuple.push(value);
+    }
+    Ok(tuple)
+}
+
+impl PgOutputMessage {
+    pub fn decode(mut buf: Bytes) -> anyhow::Result<Self> {
+        check_remaining(&buf, 1)?;
+        let message = match buf.get_u8() {
+            b'B' => {
+                check_remaining(&buf, 20)?;
+                let final_lsn = buf.get_u64();
+                let timestamp = to_unix_millis(buf.get_i64());
+                let _xid = buf.get_u32();
+                PgOutputMessage::Begin {
+                    final_lsn,
+                    timestamp,
+                }
+            }
+            b'C' => {
+                check_remaining(&buf, 25)?;
+                let _flags = buf.get_u8();
+                let _commit_lsn = buf.get_u64();
+                let end_lsn = buf.get_u64();
+                PgOutputMessage::Commit { end_lsn }
+            }
+            b'R' => {
+                check_remaining(&buf, 4)?;
+                let id = buf.get_u32();
+                let namespace = get_cstring(&mut buf)?;
+                let name = get_cstring(&mut buf)?;
+                check_remaining(&buf, 3)?;
+                let _replica_identity = buf.get_u8();
+                let column_count = buf.get_i16();
+
+                let mut columns = Vec::with_capacity(column_count.max(0) as usize);
+                for _ in 0..column_count {
+                    check_remaining(&buf, 1)?;
+                    let _flags = buf.get_u8();
+                    let name = get_cstring(&mut buf)?;
+                    check_remaining(&buf, 8)?;
+                    let type_oid = buf.get_u32();
+                    let _type_modifier = buf.get_i32();
+                    columns.push(Column { name, type_oid });
+                }
+
+                PgOutputMessage::Relation(Relation {
+                    id,
+                    // the `pg_catalog` namespace is sent as empty
+                    namespace: if namespace.is_empty() {
+                        "pg_catalog".to_string()
+                    } else {
+                        namespace
+                    },
+                    name,
+                    columns,
+                })
+            }
+            b'I' => {
+                check_remaining(&buf, 5)?;
+                let relation_id = buf.get_u32();
+                let _new_tag = buf.get_u8();
+                let new = get_tuple(&mut buf)?;
+                PgOutputMessage::Insert { relation_id, new }
+            }
+            b'U' => {
+                check_remaining(&buf, 5)?;
+                let relation_id = buf.get_u32();
+                let old = match buf.get_u8() {
+                    b'K' | b'O' => {
+                        let old = get_tuple(&mut buf)?;
+                        check_remaining(&buf, 1)?;
+                        let _new_tag = buf.get_u8();
+                        Some(old)
+                    }
+                    _ => None,
+                };
+                let new = get_tuple(&mut buf)?;
+                PgOutputMessage::Update {
+                    relation_id,
+                    old,
+                    new,
+                }
+            }
+            b'D' => {
+                check_remaining(&buf, 5)?;
+                let relation_id = buf.get_u32();
+                let _old_tag = buf.get_u8();
+                let old = get_tuple(&mut buf)?;
+                PgOutputMessage::Delete { relation_id, old }
+            }
+            tag => PgOutputMessage::Other(tag),
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{BufMut, Bytes, BytesMut};
+
+    use crate::source::pgoutput::{
+        Column, PgOutputMessage, Relation, ReplicationMessage, TupleValue,
+    };
+
+    fn put_tuple(buf: &mut BytesMut, values: &[Option<&str>]) {
+        buf.put_i16(values.len() as i16);
+        for value in values {
+            match value {
+                Some(value) => {
+                    buf.put_u8(b't');
+                    buf.put_i32(value.len() as i32);
+                    buf.put_slice(value.as_bytes());
+                }
+                None => buf.put_u8(b'n'),
+            }
+        }
+    }
+
+    #[test]
+    pub fn decode_test() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'w');
+        buf.put_u64(0x16B3748);
+        buf.put_u64(0x16B3800);
+        buf.put_i64(0);
+        buf.put_u8(b'B');
+        buf.put_u64(0x16B3900);
+        buf.put_i64(1_000_000);
+        buf.put_u32(731);
+
+        let data = match ReplicationMessage::decode(buf.freeze()).unwrap() {
+            ReplicationMessage::XLogData { wal_start, data } => {
+                assert_eq!(wal_start, 0x16B3748);
+                data
+            }
+            message => panic!("unexpected message {:?}", message),
+        };
+        assert_eq!(
+            PgOutputMessage::decode(data).unwrap(),
+            PgOutputMessage::Begin {
+                final_lsn: 0x16B3900,
+                timestamp: 946_684_801_000,
+            }
+        );
+
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'R');
+        buf.put_u32(16384);
+        buf.put_slice(b"public\0orders\0");
+        buf.put_u8(b'f');
+        buf.put_i16(2);
+        buf.put_u8(1);
+        buf.put_slice(b"id\0");
+        buf.put_u32(23);
+        buf.put_i32(-1);
+        buf.put_u8(0);
+        buf.put_slice(b"name\0");
+        buf.put_u32(25);
+        buf.put_i32(-1);
+        assert_eq!(
+            PgOutputMessage::decode(buf.freeze()).unwrap(),
+            PgOutputMessage::Relation(Relation {
+                id: 16384,
+                namespace: "public".to_string(),
+                name: "orders".to_string(),
+                columns: vec![
+                    Column {
+                        name: "id".to_string(),
+                        type_oid: 23,
+                    },
+                    Column {
+                        name: "name".to_string(),
+                        type_oid: 25,
+                    },
+                ],
+            })
+        );
+
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'U');
+        buf.put_u32(16384);
+        buf.put_u8(b'O');
+        put_tuple(&mut buf, &[Some("1"), None]);
+        buf.put_u8(b'N');
+        put_tuple(&mut buf, &[Some("1"), Some("rlink")]);
+        assert_eq!(
+            PgOutputMessage::decode(buf.freeze()).unwrap(),
+            PgOutputMessage::Update {
+                relation_id: 16384,
+                old: Some(vec![TupleValue::Text("1".to_string()), TupleValue::Null]),
+                new: vec![
+                    TupleValue::Text("1".to_string()),
+                    TupleValue::Text("rlink".to_string())
+                ],
+            }
+        );
+
+        // the update without the old row
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'U');
+        buf.put_u32(16384);
+        buf.put_u8(b'N');
+        put_tuple(&mut buf, &[Some("2")]);
+        assert!(matches!(
+            PgOutputMessage::decode(buf.freeze()).unwrap(),
+            PgOutputMessage::Update { old: None, .. }
+        ));
+
+        // truncated message
+        assert!(PgOutputMessage::decode(Bytes::from_static(b"I\0\0")).is_err());
+    }
+}
@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bytes::Bytes;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::Tag;
+use rlink::utils::date_time::current_timestamp_millis;
+use serde_json::{Map, Value};
+use tokio::net::TcpStream;
+use tokio_postgres::{Client, SimpleQueryMessage, SimpleQueryRow};
+
+use crate::metrics::SourceMetrics;
+use crate::source::change::{to_json, to_row_image, ChangeEvent, Operation};
+use crate::source::checkpoint::PostgresCdcStateRecorder;
+use crate::source::deserializer::PostgresChangeDeserializer;
+use crate::source::offset::{format_lsn, parse_lsn, LsnOffset};
+use crate::source::pgoutput::{
+    standby_status_update, PgOutputMessage, Relation, ReplicationMessage, TupleValue,
+};
+use crate::source::replication::ReplicationConnection;
+use crate::source::startup_mode::StartupMode;
+use crate::source::table::{quote_ident, TableId};
+use crate::source::ConsumerRecord;
+use crate::PostgresClientConfig;
+
+/// The rows fetched by a round trip of the snapshot cursor
+const SNAPSHOT_FETCH_SIZE: usize = 10000;
+/// The interval reporting the confirmed lsn to the server
+const STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+pub(crate) async fn create_postgres_cdc_reader(mut reader: PostgresCdcReaderThread) {
+    tokio::spawn(async move {
+        match reader.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!("run postgres cdc reader error. {}", e);
+            }
+        }
+    });
+}
+
+fn simple_query_rows(messages: Vec<SimpleQueryMessage>) -> Vec<SimpleQueryRow> {
+    messages
+        .into_iter()
+        .filter_map(|message| match message {
+            SimpleQueryMessage::Row(row) => Some(row),
+            _ => None,
+        })
+        .collect()
+}
+
+fn tuple_image(relation: &Relation, tuple: &[TupleValue]) -> Map<String, Value> {
+    let columns: Vec<String> = relation.columns.iter().map(|x| x.name.clone()).collect();
+    let values = relation
+        .columns
+        .iter()
+        .zip(tuple)
+        .map(|(column, value)| match value {
+            TupleValue::Null => Some(Value::Null),
+            TupleValue::Unchanged => None,
+            TupleValue::Text(text) => Some(to_json(column.type_oid, text.as_str())),
+        })
+        .collect();
+    to_row_image(&columns, values)
+}
+
+pub(crate) struct PostgresCdcReaderThread {
+    task_id: TaskId,
+
+    client_config: PostgresClientConfig,
+    slot: String,
+    publication: String,
+    tables: Vec<TableId>,
+    startup_mode: StartupMode,
+    /// the offset restored from the checkpoint
+    restored: Option<LsnOffset>,
+    state_recorder: PostgresCdcStateRecorder,
+
+    sender: ChannelSender<ConsumerRecord>,
+    deserializer: Box<dyn PostgresChangeDeserializer>,
+}
+
+impl PostgresCdcReaderThread {
+    pub fn new(
+        task_id: TaskId,
+        client_config: PostgresClientConfig,
+        slot: String,
+        publication: String,
+        tables: Vec<TableId>,
+        sender: ChannelSender<ConsumerRecord>,
+        deserializer: Box<dyn PostgresChangeDeserializer>,
+    ) -> Self {
+        PostgresCdcReaderThread {
+            task_id,
+            client_config,
+            slot,
+            publication,
+            tables,
+            startup_mode: StartupMode::default(),
+            restored: None,
+            state_recorder: PostgresCdcStateRecorder::default(),
+            sender,
+            deserializer,
+        }
+    }
+
+    pub fn startup_mode(mut self, startup_mode: StartupMode) -> Self {
+        self.startup_mode = startup_mode;
+        self
+    }
+
+    /// Restore the offset from the recorder, and confirm the lsn completed by the checkpoints
+    pub fn state_recorder(mut self, state_recorder: PostgresCdcStateRecorder) -> Self {
+        self.restored = state_recorder.get();
+        self.state_recorder = state_recorder;
+        self
+    }
+
+    async fn emit(&mut self, event: ChangeEvent, offset: &LsnOffset) {
+        let records = self.deserializer.deserialize(&event);
+        for record in records {
+            self.sender
+                .send(ConsumerRecord::Record {
+                    record,
+                    offset: offset.clone(),
+                })
+                .await
+                .expect("postgres cdc reader handover `Disconnected`");
+        }
+    }
+
+    async fn progress(&self, offset: &LsnOffset) {
+        self.sender
+            .send(ConsumerRecord::Progress {
+                offset: offset.clone(),
+            })
+            .await
+            .expect("postgres cdc reader handover `Disconnected`");
+    }
+
+    /// Report the received lsn and the lsn confirmed by the completed checkpoints
+    fn standby_status(&self, received_lsn: u64) -> Bytes {
+        standby_status_update(
+            received_lsn,
+            self.state_recorder.confirmed(),
+            current_timestamp_millis() as i64,
+        )
+    }
+
+    /// Create the publication of the tables if it's not created by the user
+    async fn ensure_publication(&self, client: &Client) -> anyhow::Result<()> {
+        let rows = client
+            .query(
+                "SELECT 1 FROM pg_publication WHERE pubname = $1",
+                &[&self.publication],
+            )
+            .await?;
+        if !rows.is_empty() {
+            return Ok(());
+        }
+
+        let tables: Vec<String> = self.tables.iter().map(|x| x.quoted()).collect();
+        let sql = format!(
+            "CREATE PUBLICATION {} FOR TABLE {}",
+            quote_ident(self.publication.as_str()),
+            tables.join(", ")
+        );
+        client.simple_query(sql.as_str()).await?;
+        info!("postgres publication `{}` created", self.publication);
+
+        Ok(())
+    }
+
+    async fn slot_exists(&self, client: &Client) -> anyhow::Result<bool> {
+        let rows = client
+            .query(
+                "SELECT 1 FROM pg_replication_slots WHERE slot_name = $1",
+                &[&self.slot],
+            )
+            .await?;
+        Ok(!rows.is_empty())
+    }
+
+    /// Create the slot, returns the consistent point and the name of the exported snapshot
+    async fn create_slot(
+        &self,
+        client: &Client,
+        replication: &mut ReplicationConnection<TcpStream>,
+        export_snapshot: bool,
+    ) -> anyhow::Result<(u64, Option<String>)> {
+        // the slot without a completed snapshot is created again
+        if self.slot_exists(client).await? {
+            client
+                .execute("SELECT pg_drop_replication_slot($1)", &[&self.slot])
+                .await?;
+        }
+
+        let command = format!(
+            "CREATE_REPLICATION_SLOT {} LOGICAL pgoutput {}",
+            quote_ident(self.slot.as_str()),
+            if export_snapshot {
+                "EXPORT_SNAPSHOT"
+            } else {
+                "NOEXPORT_SNAPSHOT"
+            }
+        );
+        let rows = replication.simple_query(command.as_str()).await?;
+        let row = rows
+            .first()
+            .ok_or_else(|| anyhow!("create replication slot `{}` error", self.slot))?;
+
+        let consistent_point = row
+            .get("consistent_point")
+            .ok_or_else(|| anyhow!("`consistent_point` not found"))?;
+        let consistent_point = parse_lsn(consistent_point)?;
+        let snapshot_name = row.get("snapshot_name").map(|x| x.to_string());
+
+        info!(
+            "postgres replication slot `{}` created at {}",
+            self.slot,
+            format_lsn(consistent_point)
+        );
+        Ok((consistent_point, snapshot_name))
+    }
+
+    /// Read the tables by the snapshot exported by the slot, so the rows are consistent with
+    /// the consistent point the streaming starts from. An unfinished snapshot is taken again
+    /// on restart.
+    async fn snapshot(
+        &mut self,
+        client: &Client,
+        replication: &mut ReplicationConnection<TcpStream>,
+        metrics: &SourceMetrics,
+    ) -> anyhow::Result<LsnOffset> {
+        let (consistent_point, snapshot_name) = self.create_slot(client, replication, true).await?;
+        let snapshot_name =
+            snapshot_name.ok_or_else(|| anyhow!("the snapshot of the slot is not exported"))?;
+
+        // the exported snapshot is valid until the next command of the replication connection
+        client
+            .simple_query("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .await?;
+        client
+            .simple_query(format!("SET TRANSACTION SNAPSHOT '{}'", snapshot_name).as_str())
+            .await?;
+
+        let mut offset = LsnOffset::new(consistent_point);
+        let lsn = format_lsn(consistent_point);
+        let timestamp = current_timestamp_millis() as i64;
+        for table_id in self.tables.clone() {
+            let columns: Vec<(String, u32)> = client
+                .query(
+                    "SELECT attname::text, atttypid::int8 FROM pg_attribute \
+                     WHERE attrelid = $1::text::regclass AND attnum > 0 AND NOT attisdropped \
+                     ORDER BY attnum",
+                    &[&table_id.quoted()],
+                )
+                .await?
+                .iter()
+                .map(|row| (row.get::<_, String>(0), row.get::<_, i64>(1) as u32))
+                .collect();
+            let column_names: Vec<String> = columns.iter().map(|x| x.0.clone()).collect();
+
+            let declare = format!(
+                "DECLARE rlink_snapshot CURSOR FOR SELECT * FROM {}",
+                table_id.quoted()
+            );
+            client.simple_query(declare.as_str()).await?;
+
+            let fetch = format!("FETCH {} FROM rlink_snapshot", SNAPSHOT_FETCH_SIZE);
+            loop {
+                let rows = simple_query_rows(client.simple_query(fetch.as_str()).await?);
+                if rows.is_empty() {
+                    break;
+                }
+
+                for row in rows {
+                    let values = columns
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (_, type_oid))| match row.get(i) {
+                            Some(text) => Some(to_json(*type_oid, text)),
+                            None => Some(Value::Null),
+                        })
+                        .collect();
+                    let event = ChangeEvent {
+                        timestamp,
+                        op: Operation::Read,
+                        schema: table_id.schema.clone(),
+                        table: table_id.table.clone(),
+                        before: None,
+                        after: Some(to_row_image(&column_names, values)),
+                        lsn: lsn.clone(),
+                    };
+                    self.emit(event, &offset).await;
+                    metrics.snapshot_row();
+                }
+            }
+
+            client.simple_query("CLOSE rlink_snapshot").await?;
+            info!("postgres snapshot of `{}` finished", table_id);
+        }
+        client.simple_query("COMMIT").await?;
+
+        offset.snapshot_completed = true;
+        self.progress(&offset).await;
+
+        Ok(offset)
+    }
+
+    async fn stream(
+        &mut self,
+        replication: &mut ReplicationConnection<TcpStream>,
+        mut offset: LsnOffset,
+        metrics: &SourceMetrics,
+    ) -> anyhow::Result<()> {
+        let command = format!(
+            "START_REPLICATION SLOT {} LOGICAL {} (\"proto_version\" '1', \"publication_names\" '{}')",
+            quote_ident(self.slot.as_str()),
+            format_lsn(offset.lsn),
+            quote_ident(self.publication.as_str()).replace('\'', "''")
+        );
+        replication.copy_both(command.as_str()).await?;
+
+        info!(
+            "postgres replication start at {}, skip rows: {}",
+            format_lsn(offset.lsn),
+            offset.skip_rows
+        );
+
+        let mut relations: HashMap<u32, Relation> = HashMap::new();
+        let mut status_interval = tokio::time::interval(STATUS_INTERVAL);
+        let mut received_lsn = offset.lsn;
+
+        // the transactions committed before the offset are streamed again since the slot is
+        // confirmed only to the completed checkpoint
+        let mut skipping = false;
+        // the row changes of the transaction in progress emitted before the restart
+        let mut skip_rows = offset.skip_rows;
+        let mut commit_timestamp = 0;
+        loop {
+            let message = tokio::select! {
+                message = replication.recv() => match message? {
+                    Some(message) => Some(message),
+                    None => return Err(anyhow!("postgres replication stream closed")),
+                },
+                _ = status_interval.tick() => None,
+            };
+
+            let (wal_start, data) = match message.map(ReplicationMessage::decode).transpose()? {
+                Some(ReplicationMessage::XLogData { wal_start, data }) => (wal_start, data),
+                Some(ReplicationMessage::KeepAlive { wal_end, reply }) => {
+                    received_lsn = received_lsn.max(wal_end);
+                    if reply {
+                        replication.send(self.standby_status(received_lsn)).await?;
+                    }
+                    continue;
+                }
+                None => {
+                    replication.send(self.standby_status(received_lsn)).await?;
+                    continue;
+                }
+            };
+            received_lsn = received_lsn.max(wal_start);
+
+            let (relation_id, op, before, after) = match PgOutputMessage::decode(data)? {
+                PgOutputMessage::Begin {
+                    final_lsn,
+                    timestamp,
+                } => {
+                    skipping = final_lsn < offset.lsn;
+                    commit_timestamp = timestamp;
+                    continue;
+                }
+                PgOutputMessage::Commit { end_lsn } => {
+                    if !skipping {
+                        offset.lsn = end_lsn;
+                        offset.skip_rows = 0;
+                        skip_rows = 0;
+                        self.progress(&offset).await;
+                    }
+                    skipping = false;
+                    continue;
+                }
+                PgOutputMessage::Relation(relation) => {
+                    relations.insert(relation.id, relation);
+                    continue;
+                }
+                PgOutputMessage::Insert { relation_id, new } => {
+                    (relation_id, Operation::Create, None, Some(new))
+                }
+                PgOutputMessage::Update {
+                    relation_id,
+                    old,
+                    new,
+                } => (relation_id, Operation::Update, old, Some(new)),
+                PgOutputMessage::Delete { relation_id, old } => {
+                    (relation_id, Operation::Delete, Some(old), None)
+                }
+                PgOutputMessage::Other(_) => continue,
+            };
+
+            if skipping {
+                continue;
+            }
+
+            let relation = relations
+                .get(&relation_id)
+                .ok_or_else(|| anyhow!("relation `{}` not found", relation_id))?;
+            let table_id = TableId::new(relation.namespace.as_str(), relation.name.as_str());
+            if !self.tables.contains(&table_id) {
+                continue;
+            }
+
+            if skip_rows > 0 {
+                skip_rows -= 1;
+                continue;
+            }
+
+            offset.skip_rows += 1;
+            let event = ChangeEvent {
+                timestamp: commit_timestamp,
+                op,
+                schema: table_id.schema.clone(),
+                table: table_id.table.clone(),
+                before: before.map(|x| tuple_image(relation, x.as_slice())),
+                after: after.map(|x| tuple_image(relation, x.as_slice())),
+                lsn: format_lsn(wal_start),
+            };
+            self.emit(event, &offset).await;
+            metrics.stream_row();
+            metrics.stream_delay(current_timestamp_millis() as i64 - commit_timestamp);
+        }
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let mut tags = self.task_id.to_tags();
+        tags.push(Tag::new("slot", self.slot.as_str()));
+        let metrics = SourceMetrics::new(tags);
+
+        let client = self.client_config.connect().await?;
+        let mut replication = self.client_config.connect_replication().await?;
+        self.ensure_publication(&client).await?;
+
+        let offset = match self.restored.take() {
+            Some(offset) if offset.snapshot_completed => {
+                if !self.slot_exists(&client).await? {
+                    return Err(anyhow!(
+                        "replication slot `{}` of the checkpoint not found",
+                        self.slot
+                    ));
+                }
+                offset
+            }
+            _ => match self.startup_mode {
+                StartupMode::Initial => self.snapshot(&client, &mut replication, &metrics).await?,
+                StartupMode::Latest => {
+                    let (consistent_point, _) =
+                        self.create_slot(&client, &mut replication, false).await?;
+                    let mut offset = LsnOffset::new(consistent_point);
+                    offset.snapshot_completed = true;
+                    self.progress(&offset).await;
+                    offset
+                }
+            },
+        };
+
+        self.stream(&mut replication, offset, &metrics).await
+    }
+}
@@ -0,0 +1,398 @@
+use bytes::{Buf, Bytes, BytesMut};
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::authentication::md5_hash;
+use postgres_protocol::authentication::sasl::{ChannelBinding, ScramSha256, SCRAM_SHA_256};
+use postgres_protocol::message::backend::{ErrorFields, Header, Message};
+use postgres_protocol::message::frontend;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_postgres::config::Host;
+use tokio_postgres::Config;
+
+/// The `CopyBothResponse` of `START_REPLICATION`, it's not parsed by the `postgres-protocol`
+const COPY_BOTH_RESPONSE_TAG: u8 = b'W';
+
+enum BackendMessage {
+    CopyBothResponse,
+    Message(Message),
+}
+
+/// A row of the simple query, the values are in the text format
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SimpleRow {
+    columns: Vec<String>,
+    values: Vec<Option<String>>,
+}
+
+impl SimpleRow {
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.columns
+            .iter()
+            .position(|x| x.eq(column))
+            .and_then(|index| self.values[index].as_deref())
+    }
+}
+
+/// The connection in the logical replication mode, it runs the replication commands, e.g.
+/// `CREATE_REPLICATION_SLOT`, and streams the WAL of `START_REPLICATION` by the CopyBoth
+/// sub-protocol, which isn't supported by the released `tokio-postgres`
+pub(crate) struct ReplicationConnection<S> {
+    stream: S,
+    read_buf: BytesMut,
+    write_buf: BytesMut,
+}
+
+impl ReplicationConnection<TcpStream> {
+    pub async fn connect(config: &Config) -> anyhow::Result<Self> {
+        let host = match config.get_hosts().first() {
+            Some(Host::Tcp(host)) => host.clone(),
+            _ => return Err(anyhow!("the tcp host of postgres is required")),
+        };
+        let port = config.get_ports().first().cloned().unwrap_or(5432);
+        let user = config
+            .get_user()
+            .ok_or_else(|| anyhow!("the user of postgres is required"))?;
+        let database = config.get_dbname().unwrap_or(user);
+
+        let stream = TcpStream::connect((host.as_str(), port)).await?;
+        let mut connection = ReplicationConnection::new(stream);
+        connection
+            .startup(user, database, config.get_password())
+            .await?;
+        Ok(connection)
+    }
+}
+
+impl<S> ReplicationConnection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    pub(crate) fn new(stream: S) -> Self {
+        ReplicationConnection {
+            stream,
+            read_buf: BytesMut::new(),
+            write_buf: BytesMut::new(),
+        }
+    }
+
+    async fn startup(
+        &mut self,
+        user: &str,
+        database: &str,
+        password: Option<&[u8]>,
+    ) -> anyhow::Result<()> {
+        let parameters = vec![
+            ("user", user),
+            ("database", database),
+            ("replication", "database"),
+            ("client_encoding", "UTF8"),
+        ];
+        frontend::startup_message(parameters, &mut self.write_buf)?;
+        self.flush().await?;
+
+        let password = || password.ok_or_else(|| anyhow!("the password of postgres is required"));
+        let mut scram = None;
+        loop {
+            let message = match self.read().await? {
+                BackendMessage::Message(message) => message,
+                BackendMessage::CopyBothResponse => {
+                    return Err(anyhow!("unexpected CopyBothResponse on startup"));
+                }
+            };
+            match message {
+                Message::AuthenticationCleartextPassword => {
+                    frontend::password_message(password()?, &mut self.write_buf)?;
+                    self.flush().await?;
+                }
+                Message::AuthenticationMd5Password(body) => {
+                    let hash = md5_hash(user.as_bytes(), password()?, body.salt());
+                    frontend::password_message(hash.as_bytes(), &mut self.write_buf)?;
+                    self.flush().await?;
+                }
+                Message::AuthenticationSasl(body) => {
+                    let supported = body
+                        .mechanisms()
+                        .any(|mechanism| Ok(mechanism == SCRAM_SHA_256))?;
+                    if !supported {
+                        return Err(anyhow!("no supported sasl mechanism of postgres"));
+                    }
+                    let client = ScramSha256::new(password()?, ChannelBinding::unsupported());
+                    frontend::sasl_initial_response(
+                        SCRAM_SHA_256,
+                        client.message(),
+                        &mut self.write_buf,
+                    )?;
+                    self.flush().await?;
+                    scram = Some(client);
+                }
+                Message::AuthenticationSaslContinue(body) => {
+                    let client = scram
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("unexpected sasl continue of postgres"))?;
+                    client.update(body.data())?;
+                    frontend::sasl_response(client.message(), &mut self.write_buf)?;
+                    self.flush().await?;
+                }
+                Message::AuthenticationSaslFinal(body) => {
+                    let client = scram
+                        .as_mut()
+                        .ok_or_else(|| anyhow!("unexpected sasl final of postgres"))?;
+                    client.finish(body.data())?;
+                }
+                Message::ErrorResponse(body) => return Err(postgres_error(body.fields())),
+                Message::ReadyForQuery(_) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Run the query or the replication command, and return the rows of it
+    pub async fn simple_query(&mut self, query: &str) -> anyhow::Result<Vec<SimpleRow>> {
+        frontend::query(query, &mut self.write_buf)?;
+        self.flush().await?;
+
+        let mut columns = Vec::new();
+        let mut rows = Vec::new();
+        let mut error = None;
+        loop {
+            let message = match self.read().await? {
+                BackendMessage::Message(message) => message,
+                BackendMessage::CopyBothResponse => {
+                    return Err(anyhow!("unexpected CopyBothResponse of `{}`", query));
+                }
+            };
+            match message {
+                Message::RowDescription(body) => {
+                    columns = body
+                        .fields()
+                        .map(|field| Ok(field.name().to_string()))
+                        .collect()?;
+                }
+                Message::DataRow(body) => {
+                    let values = body
+                        .ranges()
+                        .map(|range| {
+                            Ok(range.map(|range| {
+                                String::from_utf8_lossy(&body.buffer()[range]).into_owned()
+                            }))
+                        })
+                        .collect()?;
+                    rows.push(SimpleRow {
+                        columns: columns.clone(),
+                        values,
+                    });
+                }
+                Message::ErrorResponse(body) => error = Some(postgres_error(body.fields())),
+                Message::ReadyForQuery(_) => {
+                    return match error {
+                        Some(error) => Err(error),
+                        None => Ok(rows),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Start the CopyBoth streaming by the command, e.g. `START_REPLICATION`
+    pub async fn copy_both(&mut self, query: &str) -> anyhow::Result<()> {
+        frontend::query(query, &mut self.write_buf)?;
+        self.flush().await?;
+
+        loop {
+            match self.read().await? {
+                BackendMessage::CopyBothResponse => return Ok(()),
+                BackendMessage::Message(Message::ErrorResponse(body)) => {
+                    return Err(postgres_error(body.fields()));
+                }
+                BackendMessage::Message(_) => {}
+            }
+        }
+    }
+
+    /// The next `CopyData` of the streaming, `None` if the server ends it.
+    ///
+    /// It's cancel safe, the partially received message is kept in the buffer
+    pub async fn recv(&mut self) -> anyhow::Result<Option<Bytes>> {
+        loop {
+            match self.read().await? {
+                BackendMessage::Message(Message::CopyData(body)) => {
+                    return Ok(Some(body.into_bytes()));
+                }
+                BackendMessage::Message(Message::CopyDone) => return Ok(None),
+                BackendMessage::Message(Message::ErrorResponse(body)) => {
+                    return Err(postgres_error(body.fields()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Send the `CopyData` to the server, e.g. the standby status update
+    pub async fn send(&mut self, data: Bytes) -> anyhow::Result<()> {
+        frontend::CopyData::new(data)?.write(&mut self.write_buf);
+        self.flush().await
+    }
+
+    async fn read(&mut self) -> anyhow::Result<BackendMessage> {
+        loop {
+            if let Some(header) = Header::parse(self.read_buf.as_ref())? {
+                let len = header.len() as usize + 1;
+                if header.tag() == COPY_BOTH_RESPONSE_TAG && self.read_buf.len() >= len {
+                    self.read_buf.advance(len);
+                    return Ok(BackendMessage::CopyBothResponse);
+                }
+            }
+            if let Some(message) = Message::parse(&mut self.read_buf)? {
+                return Ok(BackendMessage::Message(message));
+            }
+
+            if self.stream.read_buf(&mut self.read_buf).await? == 0 {
+                return Err(anyhow!("postgres replication connection closed"));
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        self.stream.write_all(self.write_buf.as_ref()).await?;
+        self.write_buf.clear();
+        self.stream.flush().await?;
+        Ok(())
+    }
+}
+
+fn postgres_error(mut fields: ErrorFields<'_>) -> anyhow::Error {
+    let mut code = String::new();
+    let mut message = String::new();
+    loop {
+        match fields.next() {
+            Ok(Some(field)) => {
+                let value = String::from_utf8_lossy(field.value_bytes()).into_owned();
+                match field.type_() {
+                    b'C' => code = value,
+                    b'M' => message = value,
+                    _ => {}
+                }
+            }
+            Ok(None) => break,
+            Err(e) => return anyhow!("parse postgres error response error. {}", e),
+        }
+    }
+    anyhow!("postgres error {}: {}", code, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{BufMut, Bytes, BytesMut};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::source::replication::ReplicationConnection;
+
+    fn message(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        buf.put_u8(tag);
+        buf.put_i32(body.len() as i32 + 4);
+        buf.put_slice(body);
+        buf.to_vec()
+    }
+
+    fn row_description(names: &[&str]) -> Vec<u8> {
+        let mut body = BytesMut::new();
+        body.put_i16(names.len() as i16);
+        for name in names {
+            body.put_slice(name.as_bytes());
+            body.put_u8(0);
+            body.put_i32(0);
+            body.put_i16(0);
+            body.put_i32(25);
+            body.put_i16(-1);
+            body.put_i32(-1);
+            body.put_i16(0);
+        }
+        message(b'T', body.as_ref())
+    }
+
+    fn data_row(values: &[Option<&str>]) -> Vec<u8> {
+        let mut body = BytesMut::new();
+        body.put_i16(values.len() as i16);
+        for value in values {
+            match value {
+                Some(value) => {
+                    body.put_i32(value.len() as i32);
+                    body.put_slice(value.as_bytes());
+                }
+                None => body.put_i32(-1),
+            }
+        }
+        message(b'D', body.as_ref())
+    }
+
+    #[tokio::test]
+    pub async fn replication_connection_test() {
+        let (client, mut server) = tokio::io::duplex(64 * 1024);
+
+        let mut frames = Vec::new();
+        // AuthenticationOk, then ReadyForQuery
+        frames.extend(message(b'R', &0i32.to_be_bytes()));
+        frames.extend(message(b'S', b"server_version\x0014.5\x00"));
+        frames.extend(message(b'Z', b"I"));
+        // the result of CREATE_REPLICATION_SLOT
+        frames.extend(row_description(&[
+            "slot_name",
+            "consistent_point",
+            "snapshot_name",
+            "output_plugin",
+        ]));
+        frames.extend(data_row(&[
+            Some("rlink"),
+            Some("0/16B3748"),
+            None,
+            Some("pgoutput"),
+        ]));
+        frames.extend(message(b'C', b"CREATE_REPLICATION_SLOT\x00"));
+        frames.extend(message(b'Z', b"I"));
+        // the streaming of START_REPLICATION
+        frames.extend(message(b'W', &[0, 0, 0]));
+        frames.extend(message(b'd', b"w-data"));
+        frames.extend(message(b'c', b""));
+        server.write_all(frames.as_slice()).await.unwrap();
+
+        let mut connection = ReplicationConnection::new(client);
+        connection
+            .startup("rlink", "db", Some(b"secret"))
+            .await
+            .unwrap();
+
+        let rows = connection
+            .simple_query("CREATE_REPLICATION_SLOT rlink LOGICAL pgoutput NOEXPORT_SNAPSHOT")
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get("consistent_point"), Some("0/16B3748"));
+        assert_eq!(rows[0].get("snapshot_name"), None);
+
+        connection
+            .copy_both("START_REPLICATION SLOT rlink LOGICAL 0/16B3748")
+            .await
+            .unwrap();
+        assert_eq!(
+            connection.recv().await.unwrap(),
+            Some(Bytes::from_static(b"w-data"))
+        );
+        connection
+            .send(Bytes::from_static(b"r-status"))
+            .await
+            .unwrap();
+        assert_eq!(connection.recv().await.unwrap(), None);
+
+        // the startup message is in the protocol 3.0 with the replication parameter
+        drop(connection);
+        let mut sent = Vec::new();
+        server.read_to_end(&mut sent).await.unwrap();
+        assert_eq!(&sent[4..8], &196608i32.to_be_bytes());
+        let startup_len = i32::from_be_bytes([sent[0], sent[1], sent[2], sent[3]]) as usize;
+        let startup = String::from_utf8_lossy(&sent[8..startup_len]);
+        assert!(startup.contains("replication\0database\0"));
+        assert!(sent.ends_with(&message(b'd', b"r-status")));
+    }
+}
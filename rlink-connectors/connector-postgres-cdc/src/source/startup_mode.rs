@@ -0,0 +1,23 @@
+use std::convert::TryFrom;
+
+/// How the source starts when there is no offset restored from the checkpoint
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum StartupMode {
+    /// read the tables by the snapshot exported by the slot, then stream the changes after it
+    #[default]
+    Initial,
+    /// only the changes after the slot is created
+    Latest,
+}
+
+impl TryFrom<&str> for StartupMode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "initial" => Ok(Self::Initial),
+            "latest" => Ok(Self::Latest),
+            _ => Err(anyhow!("unknown startup mode {}", value)),
+        }
+    }
+}
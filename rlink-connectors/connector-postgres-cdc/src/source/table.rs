@@ -0,0 +1,49 @@
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+/// Quote the identifier for the sql and the replication commands
+pub(crate) fn quote_ident(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// A table captured by the source, `schema.table`
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TableId {
+    pub schema: String,
+    pub table: String,
+}
+
+impl TableId {
+    pub fn new(schema: &str, table: &str) -> Self {
+        TableId {
+            schema: schema.to_string(),
+            table: table.to_string(),
+        }
+    }
+
+    pub(crate) fn quoted(&self) -> String {
+        format!("{}.{}", quote_ident(&self.schema), quote_ident(&self.table))
+    }
+}
+
+impl TryFrom<&str> for TableId {
+    type Error = anyhow::Error;
+
+    /// The table without the schema is in the `public` schema
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim();
+        match value.split_once('.') {
+            Some((schema, table)) if !schema.is_empty() && !table.is_empty() => {
+                Ok(TableId::new(schema, table))
+            }
+            None if !value.is_empty() => Ok(TableId::new("public", value)),
+            _ => Err(anyhow!("illegal table `{}`, expect `schema.table`", value)),
+        }
+    }
+}
+
+impl Display for TableId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.schema, self.table)
+    }
+}
@@ -0,0 +1,36 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod sink;
+
+pub use sink::output_format::PrometheusOutputFormat;
+
+pub const PROMETHEUS: &str = "prometheus";
+pub const URL: &str = "url";
+pub const USERNAME: &str = "username";
+pub const PASSWORD: &str = "password";
+pub const BEARER_TOKEN: &str = "bearer.token";
+
+pub const METRIC_PREFIX: &str = "metric.prefix";
+/// The field names separated by `,`
+pub const LABEL_FIELDS: &str = "label.fields";
+/// The field names separated by `,`, all numeric fields except the timestamp by default
+pub const VALUE_FIELDS: &str = "value.fields";
+pub const TIMESTAMP_FIELD: &str = "timestamp.field";
+/// The `name=value` pairs separated by `,` added to all series
+pub const STATIC_LABELS: &str = "static.labels";
+pub const BUFFER_SIZE: &str = "buffer.size";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 5000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 1000;
+pub const SINK_MAX_RETRIES: usize = 3;
@@ -0,0 +1,231 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    PrometheusOutputFormat, BATCH_INTERVAL, BATCH_SIZE, BEARER_TOKEN, BUFFER_SIZE, LABEL_FIELDS,
+    MAX_RETRIES, METRIC_PREFIX, PASSWORD, PROMETHEUS, SINK_CHANNEL_SIZE, STATIC_LABELS,
+    TIMESTAMP_FIELD, URL, USERNAME, VALUE_FIELDS,
+};
+
+pub struct PrometheusOutputFormatBuilder {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    bearer_token: Option<String>,
+    metric_prefix: Option<String>,
+    label_fields: Vec<String>,
+    value_fields: Vec<String>,
+    timestamp_field: Option<String>,
+    static_labels: Vec<(String, String)>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+    batch_interval: Option<Duration>,
+    max_retries: Option<usize>,
+}
+
+impl PrometheusOutputFormatBuilder {
+    /// The `url` is the remote write endpoint, e.g. `http://127.0.0.1:9090/api/v1/write`
+    pub fn new(url: &str) -> Self {
+        PrometheusOutputFormatBuilder {
+            url: url.to_string(),
+            username: None,
+            password: None,
+            bearer_token: None,
+            metric_prefix: None,
+            label_fields: vec![],
+            value_fields: vec![],
+            timestamp_field: None,
+            static_labels: vec![],
+            buffer_size: None,
+            batch_size: None,
+            batch_interval: None,
+            max_retries: None,
+        }
+    }
+
+    pub fn basic_auth(mut self, username: &str, password: Option<&str>) -> Self {
+        self.username = Some(username.to_string());
+        self.password = password.map(|x| x.to_string());
+        self
+    }
+
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.bearer_token = Some(token.to_string());
+        self
+    }
+
+    /// Prepend the `metric_prefix` to the names of the value fields as the metric names
+    pub fn metric_prefix(mut self, metric_prefix: &str) -> Self {
+        self.metric_prefix = Some(metric_prefix.to_string());
+        self
+    }
+
+    /// Write the field as the label of the series
+    pub fn label_field(mut self, field: &str) -> Self {
+        self.label_fields.push(field.to_string());
+        self
+    }
+
+    /// Write the numeric field as the sample, all numeric fields except the labels and the
+    /// timestamp if no field is given
+    pub fn value_field(mut self, field: &str) -> Self {
+        self.value_fields.push(field.to_string());
+        self
+    }
+
+    /// The millis of the samples, e.g. the window time, the processing time if absent
+    pub fn timestamp_field(mut self, field: &str) -> Self {
+        self.timestamp_field = Some(field.to_string());
+        self
+    }
+
+    /// Add the label to all series
+    pub fn static_label(mut self, name: &str, value: &str) -> Self {
+        self.static_labels
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Flush once `batch_size` samples are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Flush the buffered samples at least every `batch_interval`
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = Some(batch_interval);
+        self
+    }
+
+    /// Retry a failed request up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> PrometheusOutputFormat {
+        info!("build prometheus sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let mut output_format = PrometheusOutputFormat::new(self.url, buffer_size)
+            .label_fields(self.label_fields)
+            .value_fields(self.value_fields)
+            .static_labels(self.static_labels);
+
+        if let Some(username) = self.username {
+            output_format = output_format.basic_auth(username, self.password);
+        }
+        if let Some(bearer_token) = self.bearer_token {
+            output_format = output_format.bearer_auth(bearer_token);
+        }
+        if let Some(metric_prefix) = self.metric_prefix {
+            output_format = output_format.metric_prefix(metric_prefix);
+        }
+        if let Some(timestamp_field) = self.timestamp_field {
+            output_format = output_format.timestamp_field(timestamp_field);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(batch_interval) = self.batch_interval {
+            output_format = output_format.batch_interval(batch_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for PrometheusOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrometheusOutputFormatBuilder")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("bearer_token", &self.bearer_token.as_ref().map(|_| "***"))
+            .field("metric_prefix", &self.metric_prefix)
+            .field("label_fields", &self.label_fields)
+            .field("value_fields", &self.value_fields)
+            .field("timestamp_field", &self.timestamp_field)
+            .field("static_labels", &self.static_labels)
+            .field("buffer_size", &self.buffer_size)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(|x| x.trim()).filter(|x| !x.is_empty())
+}
+
+impl TryFrom<Properties> for PrometheusOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let prometheus_properties = properties.to_sub_properties(PROMETHEUS);
+        let url = prometheus_properties.get_string(URL)?;
+
+        let mut builder = PrometheusOutputFormatBuilder::new(url.as_str());
+
+        if let Ok(username) = prometheus_properties.get_string(USERNAME) {
+            let password = prometheus_properties.get_string(PASSWORD).ok();
+            builder = builder.basic_auth(username.as_str(), password.as_deref());
+        }
+        if let Ok(bearer_token) = prometheus_properties.get_string(BEARER_TOKEN) {
+            builder = builder.bearer_token(bearer_token.as_str());
+        }
+
+        if let Ok(metric_prefix) = properties.get_string(METRIC_PREFIX) {
+            builder = builder.metric_prefix(metric_prefix.as_str());
+        }
+        if let Ok(label_fields) = properties.get_string(LABEL_FIELDS) {
+            for field in split_list(label_fields.as_str()) {
+                builder = builder.label_field(field);
+            }
+        }
+        if let Ok(value_fields) = properties.get_string(VALUE_FIELDS) {
+            for field in split_list(value_fields.as_str()) {
+                builder = builder.value_field(field);
+            }
+        }
+        if let Ok(timestamp_field) = properties.get_string(TIMESTAMP_FIELD) {
+            builder = builder.timestamp_field(timestamp_field.as_str());
+        }
+        if let Ok(static_labels) = properties.get_string(STATIC_LABELS) {
+            for pair in split_list(static_labels.as_str()) {
+                let (name, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("invalid static label `{}`", pair))?;
+                builder = builder.static_label(name.trim(), value.trim());
+            }
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(batch_interval) = properties.get_duration(BATCH_INTERVAL) {
+            builder = builder.batch_interval(batch_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,120 @@
+use prost::Message;
+use reqwest::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::StatusCode;
+
+use crate::sink::proto::WriteRequest;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum WriteError {
+    /// the receiver is unavailable or throttling, the request can be retried
+    Retryable(String),
+    /// the samples are rejected, e.g. out of order or duplicated with another value,
+    /// the request must not be retried
+    Rejected(String),
+}
+
+impl WriteError {
+    fn from_status(status: StatusCode, message: String) -> Self {
+        let message = format!("status {}. {}", status.as_u16(), message);
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            WriteError::Retryable(message)
+        } else {
+            WriteError::Rejected(message)
+        }
+    }
+}
+
+/// The credentials of the receiver
+#[derive(Clone)]
+pub(crate) enum Authorization {
+    Basic(String, Option<String>),
+    Bearer(String),
+}
+
+/// The client of the remote write protocol 0.1.0, the snappy compressed protobuf body
+pub(crate) struct RemoteWriteClient {
+    client: reqwest::Client,
+    url: String,
+    authorization: Option<Authorization>,
+}
+
+impl RemoteWriteClient {
+    pub fn new(url: &str, authorization: Option<Authorization>) -> Self {
+        RemoteWriteClient {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            authorization,
+        }
+    }
+
+    pub fn encode(request: &WriteRequest) -> anyhow::Result<Vec<u8>> {
+        let body = snap::raw::Encoder::new().compress_vec(request.encode_to_vec().as_slice())?;
+        Ok(body)
+    }
+
+    pub async fn write(&self, body: Vec<u8>) -> Result<(), WriteError> {
+        let mut request = self
+            .client
+            .post(self.url.as_str())
+            .header(CONTENT_TYPE, "application/x-protobuf")
+            .header(CONTENT_ENCODING, "snappy")
+            .header("X-Prometheus-Remote-Write-Version", "0.1.0")
+            .body(body);
+        request = match &self.authorization {
+            Some(Authorization::Basic(username, password)) => {
+                request.basic_auth(username, password.as_ref())
+            }
+            Some(Authorization::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        };
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| WriteError::Retryable(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let message = response.text().await.unwrap_or_default();
+        Err(WriteError::from_status(status, message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use reqwest::StatusCode;
+
+    use crate::sink::client::{RemoteWriteClient, WriteError};
+    use crate::sink::proto::{Label, Sample, TimeSeries, WriteRequest};
+
+    #[test]
+    pub fn remote_write_client_test() {
+        let request = WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![Label {
+                    name: "__name__".to_string(),
+                    value: "up".to_string(),
+                }],
+                samples: vec![Sample {
+                    value: 1f64,
+                    timestamp: 1600000000000,
+                }],
+            }],
+        };
+        let body = RemoteWriteClient::encode(&request).unwrap();
+        let decoded = snap::raw::Decoder::new().decompress_vec(&body).unwrap();
+        assert_eq!(WriteRequest::decode(decoded.as_slice()).unwrap(), request);
+
+        assert!(matches!(
+            WriteError::from_status(StatusCode::SERVICE_UNAVAILABLE, "".to_string()),
+            WriteError::Retryable(_)
+        ));
+        assert!(matches!(
+            WriteError::from_status(StatusCode::BAD_REQUEST, "out of order sample".to_string()),
+            WriteError::Rejected(_)
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod builder;
+pub mod output_format;
+pub mod series;
+
+pub(crate) mod client;
+pub(crate) mod proto;
+pub(crate) mod writer;
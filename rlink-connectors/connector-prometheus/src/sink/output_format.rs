@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
+
+use crate::sink::client::{Authorization, RemoteWriteClient};
+use crate::sink::series::SeriesBuilder;
+use crate::sink::writer::PrometheusWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// Write the records as the samples to the remote write endpoint, e.g. Prometheus with
+/// `--web.enable-remote-write-receiver`, Cortex, Thanos or VictoriaMetrics
+#[derive(NamedFunction)]
+pub struct PrometheusOutputFormat {
+    url: String,
+    authorization: Option<Authorization>,
+
+    metric_prefix: String,
+    label_fields: Vec<String>,
+    value_fields: Vec<String>,
+    timestamp_field: Option<String>,
+    static_labels: Vec<(String, String)>,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+
+    writer: Option<BatchWriterHandle>,
+}
+
+impl PrometheusOutputFormat {
+    pub fn new(url: String, buffer_size: usize) -> Self {
+        PrometheusOutputFormat {
+            url,
+            authorization: None,
+            metric_prefix: "".to_string(),
+            label_fields: vec![],
+            value_fields: vec![],
+            timestamp_field: None,
+            static_labels: vec![],
+            buffer_size,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            writer: None,
+        }
+    }
+
+    pub fn basic_auth(mut self, username: String, password: Option<String>) -> Self {
+        self.authorization = Some(Authorization::Basic(username, password));
+        self
+    }
+
+    pub fn bearer_auth(mut self, token: String) -> Self {
+        self.authorization = Some(Authorization::Bearer(token));
+        self
+    }
+
+    pub fn metric_prefix(mut self, metric_prefix: String) -> Self {
+        self.metric_prefix = metric_prefix;
+        self
+    }
+
+    pub fn label_fields(mut self, label_fields: Vec<String>) -> Self {
+        self.label_fields = label_fields;
+        self
+    }
+
+    pub fn value_fields(mut self, value_fields: Vec<String>) -> Self {
+        self.value_fields = value_fields;
+        self
+    }
+
+    pub fn timestamp_field(mut self, timestamp_field: String) -> Self {
+        self.timestamp_field = Some(timestamp_field);
+        self
+    }
+
+    pub fn static_labels(mut self, static_labels: Vec<(String, String)>) -> Self {
+        self.static_labels = static_labels;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[async_trait]
+impl OutputFormat for PrometheusOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let series_builder = SeriesBuilder::new(
+            context.input_schema.clone().into(),
+            self.metric_prefix.as_str(),
+            self.label_fields.as_slice(),
+            self.value_fields.as_slice(),
+            self.timestamp_field.as_deref(),
+            self.static_labels.as_slice(),
+        )?;
+        let client = RemoteWriteClient::new(self.url.as_str(), self.authorization.clone());
+
+        let writer = PrometheusWriter::new(client, series_builder, self.max_retries);
+        let writer = BatchWriterThread::new("Prometheus", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), context.task_id.to_tags(), self.buffer_size);
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write prometheus record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PrometheusOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered samples are sent on the checkpoint, the samples replayed after a restart
+    /// are ignored by the receiver if the same as the written
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
+        None
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{BufferReader, Record};
+use rlink::utils::date_time::current_timestamp_millis;
+
+use crate::sink::proto::{Label, Sample, TimeSeries, WriteRequest};
+
+const METRIC_NAME: &str = "__name__";
+
+/// Map the records to the samples, a sample of each value field with the metric name
+/// `{metric_prefix}{field name}` and the labels of the label fields.
+///
+/// The records of the windowed aggregations are expected, e.g. the counts or the percentiles
+/// of the window with the window time as the timestamp.
+#[derive(Clone, Debug)]
+pub struct SeriesBuilder {
+    schema: Schema,
+    metric_names: Vec<String>,
+    labels: Vec<(String, usize)>,
+    values: Vec<usize>,
+    timestamp: Option<usize>,
+    static_labels: Vec<Label>,
+}
+
+impl SeriesBuilder {
+    /// The `value_fields` are all numeric fields except the labels and the timestamp if empty.
+    /// Without the `timestamp_field` the samples are stamped by the processing time.
+    pub fn new(
+        schema: Schema,
+        metric_prefix: &str,
+        label_fields: &[String],
+        value_fields: &[String],
+        timestamp_field: Option<&str>,
+        static_labels: &[(String, String)],
+    ) -> anyhow::Result<Self> {
+        let index_of = |name: &str| {
+            schema
+                .index_of(name)
+                .ok_or_else(|| anyhow!("field `{}` not found in the input schema", name))
+        };
+        let is_number = |i: usize| {
+            let field = schema.field(i);
+            field.is_numeric() && !matches!(field.data_type(), DataType::Boolean)
+        };
+
+        let timestamp = match timestamp_field {
+            Some(name) => {
+                let i = index_of(name)?;
+                if !is_number(i) {
+                    return Err(anyhow!("timestamp field `{}` is not numeric", name));
+                }
+                Some(i)
+            }
+            None => None,
+        };
+
+        let mut labels = Vec::with_capacity(label_fields.len());
+        for name in label_fields {
+            labels.push((sanitize(name), index_of(name)?));
+        }
+
+        let values: Vec<usize> = if value_fields.is_empty() {
+            (0..schema.fields().len())
+                .filter(|i| {
+                    schema.field(*i).is_numeric()
+                        && !labels.iter().any(|(_, x)| x == i)
+                        && Some(*i) != timestamp
+                })
+                .collect()
+        } else {
+            let mut values = Vec::with_capacity(value_fields.len());
+            for name in value_fields {
+                let i = index_of(name)?;
+                if !schema.field(i).is_numeric() {
+                    return Err(anyhow!("value field `{}` is not numeric", name));
+                }
+                values.push(i);
+            }
+            values
+        };
+        if values.is_empty() {
+            return Err(anyhow!("no value field of the samples"));
+        }
+
+        let metric_names = values
+            .iter()
+            .map(|i| sanitize(format!("{}{}", metric_prefix, schema.field(*i).name()).as_str()))
+            .collect();
+        let static_labels = static_labels
+            .iter()
+            .map(|(name, value)| Label {
+                name: sanitize(name),
+                value: value.clone(),
+            })
+            .collect();
+
+        Ok(SeriesBuilder {
+            schema,
+            metric_names,
+            labels,
+            values,
+            timestamp,
+            static_labels,
+        })
+    }
+
+    /// The labels and the sample of each value field, the labels are sorted by the name
+    pub(crate) fn samples(
+        &self,
+        record: &mut Record,
+    ) -> std::io::Result<Vec<(Vec<Label>, Sample)>> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        let timestamp = match self.timestamp {
+            Some(i) => read_f64(&reader, i, self.schema.field(i).data_type())? as i64,
+            None => current_timestamp_millis() as i64,
+        };
+
+        let mut labels = self.static_labels.clone();
+        for (name, i) in &self.labels {
+            let value = read_string(&reader, *i, self.schema.field(*i).data_type())?;
+            // the empty label is the same as the absent label
+            if !value.is_empty() {
+                labels.push(Label {
+                    name: name.clone(),
+                    value,
+                });
+            }
+        }
+
+        let mut samples = Vec::with_capacity(self.values.len());
+        for (metric_name, i) in self.metric_names.iter().zip(&self.values) {
+            let mut series_labels = Vec::with_capacity(labels.len() + 1);
+            series_labels.push(Label {
+                name: METRIC_NAME.to_string(),
+                value: metric_name.clone(),
+            });
+            series_labels.extend(labels.iter().cloned());
+            series_labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let value = read_f64(&reader, *i, self.schema.field(*i).data_type())?;
+            samples.push((series_labels, Sample { value, timestamp }));
+        }
+        Ok(samples)
+    }
+}
+
+/// The samples grouped by the series
+#[derive(Default)]
+pub(crate) struct SeriesBatch {
+    series: BTreeMap<Vec<Label>, Vec<Sample>>,
+    samples: usize,
+}
+
+impl SeriesBatch {
+    pub fn push(&mut self, labels: Vec<Label>, sample: Sample) {
+        self.series.entry(labels).or_default().push(sample);
+        self.samples += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+        self.samples = 0;
+    }
+
+    /// The request with the samples of each series sorted by the timestamp
+    pub fn to_request(&self) -> WriteRequest {
+        let timeseries = self
+            .series
+            .iter()
+            .map(|(labels, samples)| {
+                let mut samples = samples.clone();
+                samples.sort_by_key(|x| x.timestamp);
+                TimeSeries {
+                    labels: labels.clone(),
+                    samples,
+                }
+            })
+            .collect();
+        WriteRequest { timeseries }
+    }
+}
+
+fn read_f64(reader: &BufferReader, i: usize, data_type: &DataType) -> std::io::Result<f64> {
+    let value = match data_type {
+        DataType::Boolean => {
+            if reader.get_bool(i)? {
+                1f64
+            } else {
+                0f64
+            }
+        }
+        DataType::Int8 => reader.get_i8(i)? as f64,
+        DataType::UInt8 => reader.get_u8(i)? as f64,
+        DataType::Int16 => reader.get_i16(i)? as f64,
+        DataType::UInt16 => reader.get_u16(i)? as f64,
+        DataType::Int32 => reader.get_i32(i)? as f64,
+        DataType::UInt32 => reader.get_u32(i)? as f64,
+        DataType::Int64 => reader.get_i64(i)? as f64,
+        DataType::UInt64 => reader.get_u64(i)? as f64,
+        DataType::Float32 => reader.get_f32(i)? as f64,
+        DataType::Float64 => reader.get_f64(i)?,
+        DataType::Binary | DataType::String => unreachable!(),
+    };
+    Ok(value)
+}
+
+fn read_string(reader: &BufferReader, i: usize, data_type: &DataType) -> std::io::Result<String> {
+    let value = match data_type {
+        DataType::Binary => String::from_utf8_lossy(reader.get_binary(i)?).to_string(),
+        DataType::String => reader.get_str(i)?.to_string(),
+        _ => read_f64(reader, i, data_type)?.to_string(),
+    };
+    Ok(value)
+}
+
+/// Replace the characters not allowed in the metric and label names with `_`
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit()) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::proto::{Label, Sample};
+    use crate::sink::series::{SeriesBatch, SeriesBuilder};
+
+    fn label(name: &str, value: &str) -> Label {
+        Label {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    pub fn series_builder_test() {
+        let schema = Schema::new(vec![
+            Field::new("window_start", DataType::UInt64),
+            Field::new("host", DataType::String),
+            Field::new("count", DataType::Int64),
+            Field::new("p99.latency", DataType::Float64),
+        ]);
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_u64(1600000000000).unwrap();
+        writer.set_str("server-1").unwrap();
+        writer.set_i64(42).unwrap();
+        writer.set_f64(12.5).unwrap();
+
+        let builder = SeriesBuilder::new(
+            schema.clone(),
+            "rlink_",
+            &["host".to_string()],
+            &[],
+            Some("window_start"),
+            &[("job".to_string(), "demo".to_string())],
+        )
+        .unwrap();
+        let samples = builder.samples(&mut record).unwrap();
+        assert_eq!(
+            samples,
+            vec![
+                (
+                    vec![
+                        label("__name__", "rlink_count"),
+                        label("host", "server-1"),
+                        label("job", "demo")
+                    ],
+                    Sample {
+                        value: 42f64,
+                        timestamp: 1600000000000
+                    }
+                ),
+                (
+                    vec![
+                        label("__name__", "rlink_p99_latency"),
+                        label("host", "server-1"),
+                        label("job", "demo")
+                    ],
+                    Sample {
+                        value: 12.5,
+                        timestamp: 1600000000000
+                    }
+                ),
+            ]
+        );
+
+        let mut batch = SeriesBatch::default();
+        for (labels, sample) in samples {
+            batch.push(labels.clone(), sample.clone());
+            batch.push(
+                labels,
+                Sample {
+                    value: sample.value,
+                    timestamp: sample.timestamp - 1000,
+                },
+            );
+        }
+        assert_eq!(batch.len(), 4);
+        let request = batch.to_request();
+        assert_eq!(request.timeseries.len(), 2);
+        assert_eq!(
+            request.timeseries[0].samples[0].timestamp,
+            1600000000000 - 1000
+        );
+
+        assert!(
+            SeriesBuilder::new(schema.clone(), "", &[], &["host".to_string()], None, &[]).is_err()
+        );
+        assert!(SeriesBuilder::new(schema, "", &[], &[], Some("host"), &[]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use tokio::time::Instant;
+
+use crate::sink::client::{RemoteWriteClient, WriteError};
+use crate::sink::series::{SeriesBatch, SeriesBuilder};
+
+pub(crate) struct PrometheusWriter {
+    client: RemoteWriteClient,
+    series_builder: SeriesBuilder,
+    max_retries: usize,
+
+    batch: SeriesBatch,
+}
+
+impl PrometheusWriter {
+    pub fn new(
+        client: RemoteWriteClient,
+        series_builder: SeriesBuilder,
+        max_retries: usize,
+    ) -> Self {
+        PrometheusWriter {
+            client,
+            series_builder,
+            max_retries,
+            batch: SeriesBatch::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchWriter for PrometheusWriter {
+    fn push(&mut self, mut record: Record) -> anyhow::Result<()> {
+        for (labels, sample) in self.series_builder.samples(&mut record)? {
+            self.batch.push(labels, sample);
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.batch.len()
+    }
+
+    /// Send the buffered samples. The rejected samples are dropped as the receiver never
+    /// accepts them on retry, the samples resent after a restart are ignored by the receiver
+    /// if the same as the written
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let body = RemoteWriteClient::encode(&self.batch.to_request())?;
+        let samples = self.batch.len();
+
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            match self.client.write(body.clone()).await {
+                Ok(()) => {
+                    metrics.flushed(samples as u64, begin.elapsed());
+                    break;
+                }
+                Err(WriteError::Rejected(e)) => {
+                    warn!("drop {} rejected samples. {}", samples, e);
+                    metrics.dropped(samples as u64);
+                    break;
+                }
+                Err(WriteError::Retryable(e)) if attempt < self.max_retries => {
+                    attempt += 1;
+                    metrics.retry();
+                    warn!(
+                        "write {} samples error, retry({}/{}). {}",
+                        samples, attempt, self.max_retries, e
+                    );
+                    tokio::time::sleep(backoff(attempt)).await;
+                }
+                Err(WriteError::Retryable(e)) => {
+                    metrics.failure();
+                    return Err(anyhow!(
+                        "write {} samples error after {} retries. {}",
+                        samples,
+                        self.max_retries,
+                        e
+                    ));
+                }
+            }
+        }
+
+        self.batch.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use google_cloud_pubsub::client::google_cloud_auth::credentials::CredentialsFile;
+use google_cloud_pubsub::client::{Client, ClientConfig};
+
+/// The credentials and the project of the client
+#[derive(Clone, Debug, Default)]
+pub struct PubSubClientConfig {
+    /// the project of the credentials by default
+    pub project_id: Option<String>,
+    /// the service account key file, the application default credentials by default, i.e.
+    /// `GOOGLE_APPLICATION_CREDENTIALS`, the gcloud user credentials or the metadata server.
+    /// The emulator of `PUBSUB_EMULATOR_HOST` is connected without the credentials
+    pub credentials_file: Option<String>,
+}
+
+impl PubSubClientConfig {
+    pub(crate) async fn create_client(&self) -> anyhow::Result<Client> {
+        let mut config = match &self.credentials_file {
+            Some(credentials_file) => {
+                let credentials = CredentialsFile::new_from_file(credentials_file.clone())
+                    .await
+                    .map_err(|e| {
+                        anyhow!("read credentials file {} error. {}", credentials_file, e)
+                    })?;
+                ClientConfig::default()
+                    .with_credentials(credentials)
+                    .await?
+            }
+            None => ClientConfig::default().with_auth().await?,
+        };
+        if let Some(project_id) = &self.project_id {
+            config.project_id = Some(project_id.clone());
+        }
+
+        let client = Client::new(config).await?;
+        Ok(client)
+    }
+}
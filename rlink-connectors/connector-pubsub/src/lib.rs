@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod client;
+pub mod metrics;
+pub mod sink;
+pub mod source;
+
+pub use sink::builder::PubSubOutputFormatBuilder;
+pub use sink::output_format::PubSubOutputFormat;
+pub use source::builder::PubSubInputFormatBuilder;
+pub use source::input_format::PubSubInputFormat;
+
+pub const PUBSUB: &str = "pubsub";
+/// the project of the topic and the subscription, the project of the credentials by default
+pub const PROJECT: &str = "project";
+/// the service account key file, the application default credentials by default
+pub const CREDENTIALS_FILE: &str = "credentials.file";
+
+pub const SUBSCRIPTION: &str = "subscription";
+pub const TOPIC: &str = "topic";
+/// the field of the ordering key of the messages, the messages are not ordered by default
+pub const ORDERING_KEY_FIELD: &str = "ordering.key.field";
+/// the max messages of a publish request
+pub const BATCH_SIZE: &str = "batch.size";
+/// the millis to publish the messages less than a batch
+pub const BATCH_FLUSH_INTERVAL: &str = "batch.flush.interval";
+pub const PUBLISHER_WORKERS: &str = "publisher.workers";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 10000;
+
+pub const SINK_BATCH_SIZE: usize = 100;
+pub const SINK_BATCH_FLUSH_INTERVAL_MILLIS: u64 = 100;
+pub const SINK_PUBLISHER_WORKERS: usize = 3;
@@ -0,0 +1,58 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SOURCE_MESSAGES: &str = "PubSub.Source.Messages";
+pub const SOURCE_ERRORS: &str = "PubSub.Source.Errors";
+pub const SOURCE_ACKS: &str = "PubSub.Source.Acks";
+pub const SINK_MESSAGES: &str = "PubSub.Sink.Messages";
+
+/// Metrics of the source, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// messages received
+    messages: Counter,
+    /// messages failed to decode
+    errors: Counter,
+    /// messages acked after the checkpoints
+    acks: Counter,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            messages: register_counter(SOURCE_MESSAGES, tags.clone()),
+            errors: register_counter(SOURCE_ERRORS, tags.clone()),
+            acks: register_counter(SOURCE_ACKS, tags),
+        }
+    }
+
+    pub fn received(&self) {
+        self.messages.increment(1);
+    }
+
+    pub fn error(&self) {
+        self.errors.increment(1);
+    }
+
+    pub fn acked(&self, messages: usize) {
+        self.acks.increment(messages as u64);
+    }
+}
+
+/// Metrics of the sink, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SinkMetrics {
+    /// messages published
+    messages: Counter,
+}
+
+impl SinkMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SinkMetrics {
+            messages: register_counter(SINK_MESSAGES, tags),
+        }
+    }
+
+    pub fn published(&self, messages: usize) {
+        self.messages.increment(messages as u64);
+    }
+}
@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::client::PubSubClientConfig;
+use crate::sink::output_format::{MessageEncoder, PubSubOutputFormat};
+use crate::{
+    BATCH_FLUSH_INTERVAL, BATCH_SIZE, CREDENTIALS_FILE, ORDERING_KEY_FIELD, PROJECT,
+    PUBLISHER_WORKERS, PUBSUB, TOPIC,
+};
+
+pub struct PubSubOutputFormatBuilder {
+    client_config: PubSubClientConfig,
+    topic: String,
+    encoder: Option<MessageEncoder>,
+    ordering_key_field: Option<String>,
+    batch_size: Option<usize>,
+    flush_interval: Option<Duration>,
+    workers: Option<usize>,
+}
+
+impl PubSubOutputFormatBuilder {
+    /// The `topic` id in the project, or the fully qualified name, e.g.
+    /// `projects/{project}/topics/{topic}`
+    pub fn new(topic: &str) -> Self {
+        PubSubOutputFormatBuilder {
+            client_config: PubSubClientConfig::default(),
+            topic: topic.to_string(),
+            encoder: None,
+            ordering_key_field: None,
+            batch_size: None,
+            flush_interval: None,
+            workers: None,
+        }
+    }
+
+    pub fn project(mut self, project: &str) -> Self {
+        self.client_config.project_id = Some(project.to_string());
+        self
+    }
+
+    /// The service account key file, the application default credentials by default
+    pub fn credentials_file(mut self, credentials_file: &str) -> Self {
+        self.client_config.credentials_file = Some(credentials_file.to_string());
+        self
+    }
+
+    /// Encode the records into the message data, the json objects by default
+    pub fn encoder(mut self, encoder: MessageEncoder) -> Self {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    pub fn ordering_key_field(mut self, ordering_key_field: &str) -> Self {
+        self.ordering_key_field = Some(ordering_key_field.to_string());
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = Some(flush_interval);
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    pub fn build(self) -> PubSubOutputFormat {
+        info!(
+            "build pubsub sink with: {:?}, topic: {}, ordering_key_field: {:?}, batch_size: {:?}, \
+            flush_interval: {:?}, workers: {:?}",
+            self.client_config,
+            self.topic,
+            self.ordering_key_field,
+            self.batch_size,
+            self.flush_interval,
+            self.workers
+        );
+
+        let mut output_format = PubSubOutputFormat::new(self.client_config, self.topic);
+        if let Some(encoder) = self.encoder {
+            output_format = output_format.encoder(encoder);
+        }
+        if let Some(ordering_key_field) = self.ordering_key_field {
+            output_format = output_format.ordering_key_field(ordering_key_field);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(flush_interval) = self.flush_interval {
+            output_format = output_format.flush_interval(flush_interval);
+        }
+        if let Some(workers) = self.workers {
+            output_format = output_format.workers(workers);
+        }
+
+        output_format
+    }
+}
+
+impl TryFrom<Properties> for PubSubOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let pubsub_properties = properties.to_sub_properties(PUBSUB);
+        let topic = pubsub_properties.get_string(TOPIC)?;
+
+        let mut builder = PubSubOutputFormatBuilder::new(topic.as_str());
+
+        if let Ok(project) = pubsub_properties.get_string(PROJECT) {
+            builder = builder.project(project.as_str());
+        }
+        if let Ok(credentials_file) = pubsub_properties.get_string(CREDENTIALS_FILE) {
+            builder = builder.credentials_file(credentials_file.as_str());
+        }
+        if let Ok(ordering_key_field) = properties.get_string(ORDERING_KEY_FIELD) {
+            builder = builder.ordering_key_field(ordering_key_field.as_str());
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(flush_interval) = properties.get_duration(BATCH_FLUSH_INTERVAL) {
+            builder = builder.flush_interval(flush_interval);
+        }
+        if let Ok(workers) = properties.get_usize(PUBLISHER_WORKERS) {
+            builder = builder.workers(workers);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,4 @@
+pub mod builder;
+pub mod output_format;
+
+pub(crate) mod json;
@@ -0,0 +1,216 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+use google_cloud_pubsub::publisher::{Awaiter, Publisher, PublisherConfig};
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::{Element, Record};
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+
+use crate::client::PubSubClientConfig;
+use crate::metrics::SinkMetrics;
+use crate::sink::json::write_json;
+use crate::{SINK_BATCH_FLUSH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_PUBLISHER_WORKERS};
+
+/// Encode a record into the data of a message
+pub type MessageEncoder = Arc<dyn Fn(&mut Record) -> anyhow::Result<Vec<u8>> + Send + Sync>;
+
+/// The messages published but not confirmed before the sink waits for them
+const MAX_PENDING: usize = 10000;
+
+/// Publish the records to a topic, the data of the message is the json object of the record
+/// by default.
+///
+/// The messages are batched by up to `batch_size` messages or the `flush_interval`, and
+/// published by the `workers` concurrently. The messages of the same ordering key are
+/// published in order, the message ordering must be enabled on the subscriptions. All
+/// messages are confirmed before the checkpoint completes, so the records are published at
+/// least once.
+#[derive(NamedFunction)]
+pub struct PubSubOutputFormat {
+    client_config: PubSubClientConfig,
+    topic: String,
+    encoder: Option<MessageEncoder>,
+    ordering_key_field: Option<String>,
+    batch_size: usize,
+    flush_interval: Duration,
+    workers: usize,
+
+    schema: Schema,
+    ordering_key_index: Option<usize>,
+    publisher: Option<Publisher>,
+    awaiters: Vec<Awaiter>,
+    metrics: Option<SinkMetrics>,
+}
+
+impl PubSubOutputFormat {
+    pub fn new(client_config: PubSubClientConfig, topic: String) -> Self {
+        PubSubOutputFormat {
+            client_config,
+            topic,
+            encoder: None,
+            ordering_key_field: None,
+            batch_size: SINK_BATCH_SIZE,
+            flush_interval: Duration::from_millis(SINK_BATCH_FLUSH_INTERVAL_MILLIS),
+            workers: SINK_PUBLISHER_WORKERS,
+            schema: Schema::empty(),
+            ordering_key_index: None,
+            publisher: None,
+            awaiters: vec![],
+            metrics: None,
+        }
+    }
+
+    pub fn encoder(mut self, encoder: MessageEncoder) -> Self {
+        self.encoder = Some(encoder);
+        self
+    }
+
+    /// The `String` field of the ordering key
+    pub fn ordering_key_field(mut self, ordering_key_field: String) -> Self {
+        self.ordering_key_field = Some(ordering_key_field);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    fn to_message(&self, record: &mut Record) -> anyhow::Result<PubsubMessage> {
+        let data = match &self.encoder {
+            Some(encoder) => encoder(record)?,
+            None => {
+                let mut data = Vec::new();
+                write_json(&self.schema, record, &mut data)?;
+                data
+            }
+        };
+        let ordering_key = match self.ordering_key_index {
+            Some(index) => {
+                let reader = record.as_reader(self.schema.as_type_ids());
+                reader.get_str(index)?.to_string()
+            }
+            None => String::new(),
+        };
+
+        Ok(PubsubMessage {
+            data,
+            ordering_key,
+            ..Default::default()
+        })
+    }
+
+    /// Wait for the confirmations of the published messages
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        let messages = self.awaiters.len();
+        for awaiter in self.awaiters.drain(..) {
+            awaiter
+                .get()
+                .await
+                .map_err(|e| anyhow!("publish to {} error. {}", self.topic, e))?;
+        }
+        self.metrics.as_ref().unwrap().published(messages);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutputFormat for PubSubOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.clone().into();
+        if let Some(ordering_key_field) = &self.ordering_key_field {
+            let (index, field) = self
+                .schema
+                .column_with_name(ordering_key_field.as_str())
+                .ok_or_else(|| anyhow!("ordering key field `{}` not found", ordering_key_field))?;
+            if field.data_type() != &DataType::String {
+                return Err(core::Error::from(format!(
+                    "ordering key field `{}` must be String",
+                    ordering_key_field
+                )));
+            }
+            self.ordering_key_index = Some(index);
+        }
+
+        let client = self.client_config.create_client().await?;
+        let topic = client.topic(self.topic.as_str());
+        if !topic.exists(None).await.map_err(anyhow::Error::from)? {
+            return Err(core::Error::from(format!(
+                "topic {} not found",
+                topic.fully_qualified_name()
+            )));
+        }
+
+        let config = PublisherConfig {
+            workers: self.workers,
+            flush_interval: self.flush_interval,
+            bundle_size: self.batch_size,
+            ..Default::default()
+        };
+        self.publisher = Some(topic.new_publisher(Some(config)));
+        self.metrics = Some(SinkMetrics::new(context.task_id.to_tags()));
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        let mut record = element.into_record();
+        let message = match self.to_message(&mut record) {
+            Ok(message) => message,
+            Err(e) => {
+                error!("encode record error, the record is discarded. {}", e);
+                return;
+            }
+        };
+
+        let awaiter = self.publisher.as_ref().unwrap().publish(message).await;
+        self.awaiters.push(awaiter);
+        if self.awaiters.len() >= MAX_PENDING {
+            self.flush().await.expect("pubsub sink error");
+        }
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.flush().await?;
+        if let Some(mut publisher) = self.publisher.take() {
+            publisher.shutdown().await;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PubSubOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The published messages are confirmed before the checkpoint completes
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        if let Err(e) = self.flush().await {
+            panic!(
+                "flush on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+        None
+    }
+}
@@ -0,0 +1,96 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use rlink::core::data_types::Schema;
+use rlink::core::properties::Properties;
+
+use crate::client::PubSubClientConfig;
+use crate::source::decoder::{message_decoder, message_schema, MessageDecoder};
+use crate::source::input_format::PubSubInputFormat;
+use crate::{BUFFER_SIZE, CREDENTIALS_FILE, PROJECT, PUBSUB, SUBSCRIPTION};
+
+#[derive(Debug)]
+pub struct PubSubInputFormatBuilder {
+    client_config: PubSubClientConfig,
+    subscription: String,
+    buffer_size: Option<usize>,
+}
+
+impl PubSubInputFormatBuilder {
+    /// The `subscription` id in the project, or the fully qualified name, e.g.
+    /// `projects/{project}/subscriptions/{subscription}`
+    pub fn new(subscription: &str) -> Self {
+        PubSubInputFormatBuilder {
+            client_config: PubSubClientConfig::default(),
+            subscription: subscription.to_string(),
+            buffer_size: None,
+        }
+    }
+
+    pub fn project(mut self, project: &str) -> Self {
+        self.client_config.project_id = Some(project.to_string());
+        self
+    }
+
+    /// The service account key file, the application default credentials by default
+    pub fn credentials_file(mut self, credentials_file: &str) -> Self {
+        self.client_config.credentials_file = Some(credentials_file.to_string());
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The messages are decoded to the records of the `schema` by the `decoder`
+    pub fn build(
+        self,
+        schema: Schema,
+        decoder: MessageDecoder,
+        parallelism: u16,
+    ) -> PubSubInputFormat {
+        info!("build pubsub source with: {:?}", &self);
+
+        let mut input_format = PubSubInputFormat::new(
+            self.client_config,
+            self.subscription,
+            decoder,
+            schema,
+            parallelism,
+        );
+        if let Some(buffer_size) = self.buffer_size {
+            input_format = input_format.buffer_size(buffer_size);
+        }
+
+        input_format
+    }
+
+    /// The messages are decoded to the records of the `message_schema`
+    pub fn build_raw(self, parallelism: u16) -> PubSubInputFormat {
+        self.build(message_schema(), Arc::new(message_decoder), parallelism)
+    }
+}
+
+impl TryFrom<Properties> for PubSubInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let pubsub_properties = properties.to_sub_properties(PUBSUB);
+        let subscription = pubsub_properties.get_string(SUBSCRIPTION)?;
+
+        let mut builder = PubSubInputFormatBuilder::new(subscription.as_str());
+
+        if let Ok(project) = pubsub_properties.get_string(PROJECT) {
+            builder = builder.project(project.as_str());
+        }
+        if let Ok(credentials_file) = pubsub_properties.get_string(CREDENTIALS_FILE) {
+            builder = builder.credentials_file(credentials_file.as_str());
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
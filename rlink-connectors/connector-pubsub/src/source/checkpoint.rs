@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct PendingAcks {
+    /// the messages emitted since the last checkpoint
+    emitted: Vec<String>,
+    /// the messages emitted before the checkpoints, by the checkpoint id
+    checkpointed: BTreeMap<u64, Vec<String>>,
+}
+
+/// Record the ack ids of the emitted messages, the messages are acked once the checkpoint
+/// after them is completed
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AckRecorder {
+    pending: Arc<Mutex<PendingAcks>>,
+}
+
+impl AckRecorder {
+    pub fn new() -> Self {
+        AckRecorder::default()
+    }
+
+    pub fn emit(&self, ack_id: String) {
+        self.pending.lock().unwrap().emitted.push(ack_id);
+    }
+
+    /// Assign the emitted messages to the checkpoint, and take the messages of the earlier
+    /// checkpoints, which must be completed when the barrier of the next arrives
+    pub fn checkpoint(&self, checkpoint_id: u64) -> Vec<String> {
+        let mut pending = self.pending.lock().unwrap();
+        let emitted = std::mem::take(&mut pending.emitted);
+        let later = pending.checkpointed.split_off(&checkpoint_id);
+        let completed = std::mem::replace(&mut pending.checkpointed, later);
+        pending.checkpointed.insert(checkpoint_id, emitted);
+
+        completed.into_values().flatten().collect()
+    }
+
+    /// Take the messages of all checkpoints
+    pub fn take_checkpointed(&self) -> Vec<String> {
+        let mut pending = self.pending.lock().unwrap();
+        std::mem::take(&mut pending.checkpointed)
+            .into_values()
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::AckRecorder;
+
+    #[test]
+    pub fn ack_recorder_test() {
+        let recorder = AckRecorder::new();
+        recorder.emit("a".to_string());
+        recorder.emit("b".to_string());
+        assert!(recorder.checkpoint(1).is_empty());
+
+        recorder.emit("c".to_string());
+        assert_eq!(
+            recorder.checkpoint(2),
+            vec!["a".to_string(), "b".to_string()]
+        );
+
+        recorder.emit("d".to_string());
+        assert_eq!(recorder.take_checkpointed(), vec!["c".to_string()]);
+        assert_eq!(recorder.checkpoint(3), Vec::<String>::new());
+    }
+}
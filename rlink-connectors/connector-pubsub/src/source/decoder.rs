@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::Record;
+
+/// Decode a message into a record, the message is discarded if failed
+pub type MessageDecoder = Arc<dyn Fn(&PubsubMessage) -> anyhow::Result<Record> + Send + Sync>;
+
+/// The schema of the `message_decoder`
+pub fn message_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("message_id", DataType::String),
+        Field::new("ordering_key", DataType::String),
+        Field::new("publish_time", DataType::Int64),
+        Field::new("data", DataType::Binary),
+    ])
+}
+
+/// Decode the message into a record of the `message_schema`, the `publish_time` is in millis
+pub fn message_decoder(message: &PubsubMessage) -> anyhow::Result<Record> {
+    let publish_time = message
+        .publish_time
+        .as_ref()
+        .map(|x| x.seconds * 1000 + x.nanos as i64 / 1_000_000)
+        .unwrap_or_default();
+
+    let schema = message_schema();
+    let mut record = Record::new();
+    let mut writer = record.as_writer(schema.as_type_ids());
+    writer.set_str(message.message_id.as_str())?;
+    writer.set_str(message.ordering_key.as_str())?;
+    writer.set_i64(publish_time)?;
+    writer.set_binary(message.data.as_slice())?;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+
+    use crate::source::decoder::{message_decoder, message_schema};
+
+    #[test]
+    pub fn message_decoder_test() {
+        let message = PubsubMessage {
+            data: b"hello".to_vec(),
+            message_id: "1".to_string(),
+            ordering_key: "k".to_string(),
+            ..Default::default()
+        };
+
+        let schema = message_schema();
+        let mut record = message_decoder(&message).unwrap();
+        let reader = record.as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_str(0).unwrap(), "1");
+        assert_eq!(reader.get_str(1).unwrap(), "k");
+        assert_eq!(reader.get_i64(2).unwrap(), 0);
+        assert_eq!(reader.get_binary(3).unwrap(), b"hello");
+    }
+}
@@ -0,0 +1,161 @@
+use google_cloud_pubsub::subscription::Subscription;
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::metrics::Tag;
+
+use crate::client::PubSubClientConfig;
+use crate::metrics::SourceMetrics;
+use crate::source::checkpoint::AckRecorder;
+use crate::source::decoder::MessageDecoder;
+use crate::source::reader::SubscriptionReader;
+use crate::source::stream::PubSubRecordStream;
+use crate::SOURCE_CHANNEL_SIZE;
+
+/// Pull the messages of a subscription by the streaming pull, the tasks share the
+/// subscription and the messages are balanced by the Pub/Sub.
+///
+/// The messages are acked only after the checkpoint following them is completed, i.e. when
+/// the barrier of the next checkpoint arrives, so the messages not acked before a restart
+/// are redelivered. The ack deadline of the subscription must exceed two checkpoint
+/// intervals, or the messages are redelivered while the job is running.
+#[derive(NamedFunction)]
+pub struct PubSubInputFormat {
+    client_config: PubSubClientConfig,
+    subscription: String,
+    decoder: MessageDecoder,
+    schema: Schema,
+    parallelism: u16,
+    buffer_size: usize,
+
+    tags: Vec<Tag>,
+    client_subscription: Option<Subscription>,
+    ack_recorder: AckRecorder,
+    metrics: Option<SourceMetrics>,
+}
+
+impl PubSubInputFormat {
+    pub fn new(
+        client_config: PubSubClientConfig,
+        subscription: String,
+        decoder: MessageDecoder,
+        schema: Schema,
+        parallelism: u16,
+    ) -> Self {
+        PubSubInputFormat {
+            client_config,
+            subscription,
+            decoder,
+            schema,
+            parallelism,
+            buffer_size: SOURCE_CHANNEL_SIZE,
+            tags: vec![],
+            client_subscription: None,
+            ack_recorder: AckRecorder::new(),
+            metrics: None,
+        }
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    async fn ack(&self, ack_ids: Vec<String>) -> anyhow::Result<()> {
+        if ack_ids.is_empty() {
+            return Ok(());
+        }
+
+        let messages = ack_ids.len();
+        self.client_subscription
+            .as_ref()
+            .unwrap()
+            .ack(ack_ids)
+            .await
+            .map_err(|e| anyhow!("ack {} messages error. {}", messages, e))?;
+        self.metrics.as_ref().unwrap().acked(messages);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl InputFormat for PubSubInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        let client = self.client_config.create_client().await?;
+        let subscription = client.subscription(self.subscription.as_str());
+        if !subscription
+            .exists(None)
+            .await
+            .map_err(anyhow::Error::from)?
+        {
+            return Err(core::Error::from(format!(
+                "subscription {} not found",
+                subscription.fully_qualified_name()
+            )));
+        }
+
+        self.client_subscription = Some(subscription);
+        self.tags = context.task_id.to_tags();
+        self.metrics = Some(SourceMetrics::new(self.tags.clone()));
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("PubSubSource_Handover", self.tags.clone(), self.buffer_size);
+
+        SubscriptionReader {
+            subscription: self.client_subscription.clone().unwrap(),
+            decoder: self.decoder.clone(),
+            sender,
+            metrics: self.metrics.clone().unwrap(),
+        }
+        .spawn();
+
+        Box::pin(PubSubRecordStream::new(receiver, self.ack_recorder.clone()))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        let ack_ids = self.ack_recorder.take_checkpointed();
+        self.ack(ack_ids).await?;
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PubSubInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The messages are tracked by the Pub/Sub, the unacked are redelivered after restore
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let ack_ids = self.ack_recorder.checkpoint(context.checkpoint_id.0);
+        // the messages are redelivered if the ack failed
+        if let Err(e) = self.ack(ack_ids).await {
+            warn!("checkpoint({:?}) {}", context.checkpoint_id, e);
+        }
+        None
+    }
+}
+
+impl InputSplitSource for PubSubInputFormat {}
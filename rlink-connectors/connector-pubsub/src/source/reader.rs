@@ -0,0 +1,65 @@
+use futures::StreamExt;
+use google_cloud_pubsub::subscription::Subscription;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::element::Record;
+
+use crate::metrics::SourceMetrics;
+use crate::source::decoder::MessageDecoder;
+
+pub(crate) enum PubSubEvent {
+    Record {
+        ack_id: String,
+        record: Record,
+    },
+    /// the message failed to decode, it's acked with the checkpoint and never redelivered
+    Discarded(String),
+}
+
+/// Pull the messages of the subscription by the streaming pull, the messages are not acked
+/// by the reader
+pub(crate) struct SubscriptionReader {
+    pub subscription: Subscription,
+    pub decoder: MessageDecoder,
+    pub sender: ChannelSender<PubSubEvent>,
+    pub metrics: SourceMetrics,
+}
+
+impl SubscriptionReader {
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            if let Err(e) = self.read().await {
+                error!(
+                    "pull subscription {} error. {}",
+                    self.subscription.fully_qualified_name(),
+                    e
+                );
+            }
+        });
+    }
+
+    async fn read(&self) -> anyhow::Result<()> {
+        let mut stream = self.subscription.subscribe(None).await?;
+        info!(
+            "pull subscription {}",
+            self.subscription.fully_qualified_name()
+        );
+
+        while let Some(message) = stream.next().await {
+            self.metrics.received();
+            let ack_id = message.ack_id().to_string();
+            let event = match (self.decoder)(&message.message) {
+                Ok(record) => PubSubEvent::Record { ack_id, record },
+                Err(e) => {
+                    self.metrics.error();
+                    warn!("decode message error, the message is discarded. {}", e);
+                    PubSubEvent::Discarded(ack_id)
+                }
+            };
+            self.sender
+                .send(event)
+                .await
+                .map_err(|_e| anyhow!("the pubsub source is closed"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::AckRecorder;
+use crate::source::reader::PubSubEvent;
+
+/// The records of the messages, the ack id is recorded once the record is emitted
+pub struct PubSubRecordStream {
+    receiver: ChannelReceiver<PubSubEvent>,
+    ack_recorder: AckRecorder,
+}
+
+impl PubSubRecordStream {
+    pub(crate) fn new(receiver: ChannelReceiver<PubSubEvent>, ack_recorder: AckRecorder) -> Self {
+        PubSubRecordStream {
+            receiver,
+            ack_recorder,
+        }
+    }
+}
+
+impl ElementStream for PubSubRecordStream {}
+
+impl Stream for PubSubRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().receiver.poll_recv(cx) {
+                Poll::Ready(Some(PubSubEvent::Record { ack_id, record })) => {
+                    self.ack_recorder.emit(ack_id);
+                    return Poll::Ready(Some(Element::Record(record)));
+                }
+                Poll::Ready(Some(PubSubEvent::Discarded(ack_id))) => {
+                    self.ack_recorder.emit(ack_id);
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
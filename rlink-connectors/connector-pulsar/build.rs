@@ -0,0 +1,14 @@
+use serbuffer_gen::{Codegen, DataType::*, SchemaBuilder};
+
+fn main() {
+    Codegen::out_dir("buffer_gen")
+        .schema(
+            SchemaBuilder::new("PulsarMessage")
+                .field("timestamp", I64)
+                .field("key", STRING)
+                .field("payload", BINARY)
+                .field("topic", STRING),
+        )
+        .gen()
+        .expect("buffer gen error");
+}
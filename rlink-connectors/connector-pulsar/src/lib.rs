@@ -0,0 +1,142 @@
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod sink;
+pub mod source;
+
+pub mod buffer_gen {
+    include!(concat!(env!("OUT_DIR"), "/buffer_gen/mod.rs"));
+}
+
+pub use sink::output_format::PulsarOutputFormat;
+pub use source::input_format::PulsarInputFormat;
+
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+
+use pulsar::{Authentication, Pulsar, TokioExecutor};
+use rlink::core::element::Record;
+use rlink::core::properties::Properties;
+
+use crate::buffer_gen::pulsar_message;
+
+pub const PULSAR: &str = "pulsar";
+pub const SERVICE_URL: &str = "service.url";
+pub const AUTH_TOKEN: &str = "auth.token";
+
+pub const TOPICS: &str = "topics";
+pub const SUBSCRIPTION: &str = "subscription";
+pub const SUBSCRIPTION_MODE: &str = "subscription.mode";
+pub const START_POSITION: &str = "start.position";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const BATCH_SIZE: &str = "batch.size";
+pub const MAX_IN_FLIGHT: &str = "max.in.flight";
+
+pub const INPUT_FORMAT_FN_NAME_DEFAULT: &str = "PulsarInputFormat";
+pub const OUTPUT_FORMAT_FN_NAME_DEFAULT: &str = "PulsarOutputFormat";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 50000;
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_MAX_IN_FLIGHT: usize = 10000;
+
+/// Connection settings shared by the source and the sink
+#[derive(Clone)]
+pub struct PulsarClientConfig {
+    service_url: String,
+    auth_token: Option<String>,
+}
+
+impl PulsarClientConfig {
+    pub fn new(service_url: &str) -> Self {
+        PulsarClientConfig {
+            service_url: service_url.to_string(),
+            auth_token: None,
+        }
+    }
+
+    /// Authenticate by the JWT token
+    pub fn auth_token(mut self, token: &str) -> Self {
+        self.auth_token = Some(token.to_string());
+        self
+    }
+
+    pub fn service_url(&self) -> &str {
+        self.service_url.as_str()
+    }
+
+    pub(crate) async fn connect(&self) -> anyhow::Result<Pulsar<TokioExecutor>> {
+        let mut builder = Pulsar::builder(self.service_url.as_str(), TokioExecutor);
+        if let Some(token) = &self.auth_token {
+            builder = builder.with_auth(Authentication {
+                name: "token".to_string(),
+                data: token.as_bytes().to_vec(),
+            });
+        }
+
+        builder
+            .build()
+            .await
+            .map_err(|e| anyhow!("connect to pulsar `{}` error. {}", self.service_url, e))
+    }
+}
+
+impl TryFrom<Properties> for PulsarClientConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let service_url = properties.get_string(SERVICE_URL)?;
+
+        let mut client_config = PulsarClientConfig::new(service_url.as_str());
+        if let Ok(token) = properties.get_string(AUTH_TOKEN) {
+            client_config = client_config.auth_token(token.as_str());
+        }
+        Ok(client_config)
+    }
+}
+
+impl Debug for PulsarClientConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PulsarClientConfig")
+            .field("service_url", &self.service_url)
+            .field("auth_token", &self.auth_token.as_ref().map(|_| "******"))
+            .finish()
+    }
+}
+
+pub fn build_pulsar_record(
+    timestamp: i64,
+    key: &str,
+    payload: &[u8],
+    topic: &str,
+) -> Result<Record, std::io::Error> {
+    let message = pulsar_message::Entity {
+        timestamp,
+        key,
+        payload,
+        topic,
+    };
+
+    // 24 = 12(len(payload) + len(topic) + len(key)) + 8(len(timestamp)) + 4(place_holder)
+    let capacity = payload.len() + topic.len() + key.len() + 24;
+    let mut record = Record::with_capacity(capacity);
+
+    message.to_buffer(record.as_buffer()).unwrap();
+
+    Ok(record)
+}
+
+/// Wait for the pulsar client in the sync callbacks of rlink, eg: `create_input_splits`.
+/// The multi-thread runtime is required.
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
@@ -0,0 +1,39 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const CONSUMER_RECEIVED: &str = "Pulsar.Consumer.Received";
+pub const PRODUCER_SENT: &str = "Pulsar.Producer.Sent";
+pub const PRODUCER_DELIVERED: &str = "Pulsar.Producer.Delivered";
+pub const PRODUCER_FAILED: &str = "Pulsar.Producer.Failed";
+
+/// Delivery counters of the producer task, tagged by the task and topic
+#[derive(Clone)]
+pub(crate) struct ProducerMetrics {
+    /// messages handed over to the client
+    sent: Counter,
+    /// messages acknowledged by the broker
+    delivered: Counter,
+    /// messages failed to send or rejected by the broker
+    failed: Counter,
+}
+
+impl ProducerMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        ProducerMetrics {
+            sent: register_counter(PRODUCER_SENT, tags.clone()),
+            delivered: register_counter(PRODUCER_DELIVERED, tags.clone()),
+            failed: register_counter(PRODUCER_FAILED, tags),
+        }
+    }
+
+    pub fn sent(&self) {
+        self.sent.increment(1);
+    }
+
+    pub fn delivered(&self) {
+        self.delivered.increment(1);
+    }
+
+    pub fn failed(&self) {
+        self.failed.increment(1);
+    }
+}
@@ -0,0 +1,86 @@
+use std::convert::TryFrom;
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    PulsarClientConfig, PulsarOutputFormat, BATCH_SIZE, BUFFER_SIZE, MAX_IN_FLIGHT, PULSAR,
+    SINK_CHANNEL_SIZE, SINK_MAX_IN_FLIGHT, TOPICS,
+};
+
+#[derive(Debug)]
+pub struct PulsarOutputFormatBuilder {
+    client_config: PulsarClientConfig,
+    topic: Option<String>,
+    buffer_size: Option<usize>,
+    batch_size: Option<u32>,
+    max_in_flight: Option<usize>,
+}
+
+impl PulsarOutputFormatBuilder {
+    /// The records are produced to the `topic` if specified, otherwise to the `topic` field
+    /// of the `PulsarMessage` records
+    pub fn new(client_config: PulsarClientConfig, topic: Option<String>) -> Self {
+        PulsarOutputFormatBuilder {
+            client_config,
+            topic,
+            buffer_size: None,
+            batch_size: None,
+            max_in_flight: None,
+        }
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Batch up to `batch_size` messages in the client before sending them to the broker
+    pub fn batch_size(mut self, batch_size: u32) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// The max number of messages waiting for the receipt, the upstream is back-pressured
+    /// when it is reached
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    pub fn build(self) -> PulsarOutputFormat {
+        info!("build pulsar sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let max_in_flight = self.max_in_flight.unwrap_or(SINK_MAX_IN_FLIGHT);
+        PulsarOutputFormat::new(
+            self.client_config,
+            self.topic,
+            buffer_size,
+            self.batch_size,
+            max_in_flight,
+        )
+    }
+}
+
+impl TryFrom<Properties> for PulsarOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let client_config = PulsarClientConfig::try_from(properties.to_sub_properties(PULSAR))?;
+
+        let topic = properties.get_string(TOPICS).ok();
+        let mut builder = PulsarOutputFormatBuilder::new(client_config, topic);
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_u32(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(max_in_flight) = properties.get_usize(MAX_IN_FLIGHT) {
+            builder = builder.max_in_flight(max_in_flight);
+        }
+
+        Ok(builder)
+    }
+}
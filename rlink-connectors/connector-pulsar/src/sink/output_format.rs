@@ -0,0 +1,95 @@
+use rlink::channel::named_channel;
+use rlink::channel::sender::ChannelSender;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::{Element, Record};
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::metrics::Tag;
+
+use crate::sink::producer::PulsarProducerThread;
+use crate::PulsarClientConfig;
+
+#[derive(NamedFunction)]
+pub struct PulsarOutputFormat {
+    client_config: PulsarClientConfig,
+    topic: Option<String>,
+
+    buffer_size: usize,
+    batch_size: Option<u32>,
+    max_in_flight: usize,
+    handover: Option<ChannelSender<Record>>,
+}
+
+impl PulsarOutputFormat {
+    pub fn new(
+        client_config: PulsarClientConfig,
+        topic: Option<String>,
+        buffer_size: usize,
+        batch_size: Option<u32>,
+        max_in_flight: usize,
+    ) -> Self {
+        PulsarOutputFormat {
+            client_config,
+            topic,
+            buffer_size,
+            batch_size,
+            max_in_flight,
+            handover: None,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for PulsarOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let mut tags = context.task_id.to_tags();
+        tags.push(Tag::new(
+            "topic",
+            self.topic.as_ref().map(|x| x.as_str()).unwrap_or(""),
+        ));
+
+        let (sender, receiver) = named_channel(self.name(), tags.clone(), self.buffer_size);
+        self.handover = Some(sender);
+
+        let mut pulsar_producer =
+            PulsarProducerThread::new(self.client_config.clone(), self.topic.clone(), receiver)
+                .batch_size(self.batch_size)
+                .max_in_flight(self.max_in_flight)
+                .tags(tags);
+        tokio::spawn(async move {
+            pulsar_producer.run().await;
+        });
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        self.handover
+            .as_ref()
+            .unwrap()
+            .send(element.into_record())
+            .await
+            .unwrap();
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PulsarOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
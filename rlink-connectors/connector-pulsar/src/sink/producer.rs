@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use pulsar::producer::{ProducerOptions, SendFuture};
+use pulsar::{Producer, Pulsar, TokioExecutor};
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::channel::TryRecvError;
+use rlink::core::element::Record;
+use rlink::metrics::Tag;
+
+use crate::buffer_gen::pulsar_message;
+use crate::metrics::ProducerMetrics;
+use crate::{PulsarClientConfig, SINK_MAX_IN_FLIGHT};
+
+const PRODUCE_BATCH_SIZE: usize = 3000;
+
+pub struct PulsarProducerThread {
+    client_config: PulsarClientConfig,
+    topic: Option<String>,
+    receiver: ChannelReceiver<Record>,
+    /// the max messages batched by the client, `None` to disable the batching
+    batch_size: Option<u32>,
+    max_in_flight: usize,
+
+    /// producers by topic
+    producers: HashMap<String, Producer<TokioExecutor>>,
+
+    tags: Vec<Tag>,
+}
+
+impl PulsarProducerThread {
+    pub fn new(
+        client_config: PulsarClientConfig,
+        topic: Option<String>,
+        receiver: ChannelReceiver<Record>,
+    ) -> Self {
+        PulsarProducerThread {
+            client_config,
+            topic,
+            receiver,
+            batch_size: None,
+            max_in_flight: SINK_MAX_IN_FLIGHT,
+            producers: HashMap::new(),
+            tags: vec![],
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: Option<u32>) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    async fn producer(
+        &mut self,
+        pulsar: &Pulsar<TokioExecutor>,
+        topic: &str,
+    ) -> anyhow::Result<&mut Producer<TokioExecutor>> {
+        if !self.producers.contains_key(topic) {
+            let producer = pulsar
+                .producer()
+                .with_topic(topic)
+                .with_options(ProducerOptions {
+                    batch_size: self.batch_size,
+                    ..Default::default()
+                })
+                .build()
+                .await?;
+            self.producers.insert(topic.to_string(), producer);
+        }
+
+        Ok(self.producers.get_mut(topic).unwrap())
+    }
+
+    async fn send(
+        &mut self,
+        pulsar: &Pulsar<TokioExecutor>,
+        mut record: Record,
+    ) -> anyhow::Result<SendFuture> {
+        let pulsar_message::Entity {
+            timestamp,
+            key,
+            payload,
+            topic,
+        } = pulsar_message::Entity::parse(record.as_buffer()).unwrap();
+
+        let topic = match self.topic.as_ref() {
+            Some(topic) => topic.clone(),
+            None => topic.to_string(),
+        };
+        if topic.is_empty() {
+            return Err(anyhow!("no topic specified for the pulsar message"));
+        }
+
+        let mut message = self
+            .producer(pulsar, topic.as_str())
+            .await?
+            .create_message()
+            .with_content(payload.to_vec());
+        if !key.is_empty() {
+            message = message.with_key(key);
+        }
+        if timestamp > 0 {
+            message = message.event_time(timestamp as u64);
+        }
+
+        let send_future = message.send().await?;
+        Ok(send_future)
+    }
+
+    /// Flush the messages batched in the client, otherwise their receipts never come back
+    async fn flush(&mut self, metrics: &ProducerMetrics) {
+        for (topic, producer) in self.producers.iter_mut() {
+            if let Err(e) = producer.send_batch().await {
+                metrics.failed();
+                error!("flush pulsar topic {} error. {}", topic, e);
+            }
+        }
+    }
+
+    pub async fn run(&mut self) {
+        let pulsar = match self.client_config.connect().await {
+            Ok(pulsar) => pulsar,
+            Err(e) => {
+                error!("run producer error. {}", e);
+                return;
+            }
+        };
+        let metrics = ProducerMetrics::new(self.tags.clone());
+        let mut in_flight = FuturesUnordered::new();
+
+        // wait for the first record, then drain the channel up to the batch size
+        while let Some(record) = self.receiver.recv().await {
+            let mut records = vec![record];
+            for _n in 1..PRODUCE_BATCH_SIZE {
+                match self.receiver.try_recv() {
+                    Ok(record) => records.push(record),
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+                }
+            }
+
+            for record in records {
+                if in_flight.len() >= self.max_in_flight {
+                    self.flush(&metrics).await;
+                    if let Some(receipt) = in_flight.next().await {
+                        on_receipt(receipt, &metrics);
+                    }
+                }
+
+                match self.send(&pulsar, record).await {
+                    Ok(send_future) => {
+                        metrics.sent();
+                        in_flight.push(send_future);
+                    }
+                    Err(e) => {
+                        metrics.failed();
+                        error!("send pulsar message error, the record is discarded. {}", e);
+                    }
+                }
+            }
+
+            // no more records for now, settle the receipts before waiting for the next one
+            self.flush(&metrics).await;
+            while let Some(receipt) = in_flight.next().await {
+                on_receipt(receipt, &metrics);
+            }
+        }
+
+        info!("pulsar recv channel closed");
+    }
+}
+
+fn on_receipt<T, E: std::fmt::Display>(receipt: Result<T, E>, metrics: &ProducerMetrics) {
+    match receipt {
+        Ok(_receipt) => metrics.delivered(),
+        Err(e) => {
+            metrics.failed();
+            error!("pulsar message delivery failed. {}", e);
+        }
+    }
+}
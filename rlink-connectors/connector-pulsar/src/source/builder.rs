@@ -0,0 +1,145 @@
+use std::convert::TryFrom;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::{Properties, PARALLELISM};
+
+use crate::buffer_gen::pulsar_message;
+use crate::source::deserializer::{
+    DefaultPulsarRecordDeserializer, DefaultPulsarRecordDeserializerBuilder,
+    PulsarRecordDeserializerBuilder,
+};
+use crate::source::subscription::{StartPosition, SubscriptionMode};
+use crate::{
+    PulsarClientConfig, PulsarInputFormat, BUFFER_SIZE, INPUT_FORMAT_FN_NAME_DEFAULT, PULSAR,
+    SOURCE_CHANNEL_SIZE, START_POSITION, SUBSCRIPTION, SUBSCRIPTION_MODE, TOPICS,
+};
+
+#[derive(Debug)]
+pub struct PulsarInputFormatBuilder {
+    fn_name: Option<String>,
+    parallelism: u16,
+    client_config: PulsarClientConfig,
+    topics: Vec<String>,
+    subscription: String,
+    subscription_mode: SubscriptionMode,
+    start_position: StartPosition,
+    buffer_size: Option<usize>,
+}
+
+impl PulsarInputFormatBuilder {
+    pub fn new(
+        client_config: PulsarClientConfig,
+        topics: Vec<String>,
+        subscription: &str,
+        parallelism: u16,
+    ) -> Self {
+        PulsarInputFormatBuilder {
+            fn_name: None,
+            parallelism,
+            client_config,
+            topics,
+            subscription: subscription.to_string(),
+            subscription_mode: SubscriptionMode::default(),
+            start_position: StartPosition::default(),
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    pub fn subscription_mode(mut self, subscription_mode: SubscriptionMode) -> Self {
+        self.subscription_mode = subscription_mode;
+        self
+    }
+
+    pub fn start_position(mut self, start_position: StartPosition) -> Self {
+        self.start_position = start_position;
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn PulsarRecordDeserializerBuilder>>,
+    ) -> PulsarInputFormat {
+        info!("build pulsar source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn PulsarRecordDeserializerBuilder> =
+                Box::new(DefaultPulsarRecordDeserializerBuilder::<
+                    DefaultPulsarRecordDeserializer,
+                >::new(FnSchema::from(
+                    &pulsar_message::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        PulsarInputFormat::new(
+            self.client_config,
+            self.topics,
+            self.subscription,
+            buffer_size,
+            deserializer_builder,
+            self.parallelism,
+            fn_name,
+        )
+        .subscription_mode(self.subscription_mode)
+        .start_position(self.start_position)
+    }
+}
+
+impl TryFrom<Properties> for PulsarInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let parallelism = properties.get_u16(PARALLELISM)?;
+
+        let client_config = PulsarClientConfig::try_from(properties.to_sub_properties(PULSAR))?;
+
+        let topics: Vec<String> = properties
+            .get_string(TOPICS)?
+            .trim()
+            .split(',')
+            .map(|x| x.to_string())
+            .collect();
+        let subscription = properties.get_string(SUBSCRIPTION)?;
+
+        let mut builder = PulsarInputFormatBuilder::new(
+            client_config,
+            topics,
+            subscription.as_str(),
+            parallelism,
+        );
+
+        builder = builder.fn_name(properties.name());
+
+        if let Ok(subscription_mode) = properties.get_string(SUBSCRIPTION_MODE) {
+            let subscription_mode = SubscriptionMode::try_from(subscription_mode.as_str())?;
+            builder = builder.subscription_mode(subscription_mode);
+        }
+
+        if let Ok(start_position) = properties.get_string(START_POSITION) {
+            let start_position = StartPosition::try_from(start_position.as_str())?;
+            builder = builder.start_position(start_position);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
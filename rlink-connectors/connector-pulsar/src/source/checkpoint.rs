@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use pulsar::message::proto::MessageIdData;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::function::Context;
+use rlink::core::runtime::TaskId;
+use rlink::core::state::{ListState, ListStateDescriptor, OperatorStateStore};
+
+/// The position of a message in the topic, serializable copy of `MessageIdData`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MessageId {
+    pub ledger_id: u64,
+    pub entry_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partition: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_index: Option<i32>,
+}
+
+impl MessageId {
+    /// The position of the message in its partition topic, for ordering the message ids
+    fn position(&self) -> (u64, u64, i32) {
+        (
+            self.ledger_id,
+            self.entry_id,
+            self.batch_index.unwrap_or(-1),
+        )
+    }
+}
+
+impl From<&MessageIdData> for MessageId {
+    fn from(id: &MessageIdData) -> Self {
+        MessageId {
+            ledger_id: id.ledger_id,
+            entry_id: id.entry_id,
+            partition: id.partition,
+            batch_index: id.batch_index,
+        }
+    }
+}
+
+impl From<MessageId> for MessageIdData {
+    fn from(id: MessageId) -> Self {
+        MessageIdData {
+            ledger_id: id.ledger_id,
+            entry_id: id.entry_id,
+            partition: id.partition,
+            batch_index: id.batch_index,
+            ..Default::default()
+        }
+    }
+}
+
+/// the union list state of the message id snapshots of all tasks
+const PULSAR_MESSAGE_IDS: &str = "pulsar_message_ids";
+
+/// The message ids are checkpointed as the union operator state, every task restores from the
+/// message ids of all tasks, so the topics reassigned by the changed parallelism or partitions
+/// are resumed by their new tasks
+#[derive(Debug, Clone)]
+pub struct PulsarCheckpointFunction {
+    pub(crate) state_recorder: Option<PulsarSourceStateRecorder>,
+    #[allow(dead_code)]
+    pub(crate) application_id: String,
+    #[allow(dead_code)]
+    pub(crate) task_id: TaskId,
+    topic: String,
+    operator_state: OperatorStateStore,
+}
+
+impl PulsarCheckpointFunction {
+    pub fn new(application_id: String, task_id: TaskId, topic: &str) -> Self {
+        PulsarCheckpointFunction {
+            state_recorder: None,
+            application_id,
+            task_id,
+            topic: topic.to_string(),
+            operator_state: OperatorStateStore::new(),
+        }
+    }
+
+    pub fn as_state_mut(&mut self) -> &mut PulsarSourceStateRecorder {
+        self.state_recorder.as_mut().unwrap()
+    }
+
+    /// Restore the message ids of all tasks from the checkpoint of the `context`, it's called
+    /// before `initialize_state`
+    pub fn restore_from(&self, context: &Context) -> anyhow::Result<()> {
+        self.operator_state.restore_from(context)
+    }
+
+    fn message_id_state(&self) -> ListState<String> {
+        self.operator_state
+            .union_list_state(&ListStateDescriptor::new(PULSAR_MESSAGE_IDS))
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PulsarCheckpointFunction {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+        let state_recorder = PulsarSourceStateRecorder::new(self.topic.as_str());
+        info!("Checkpoint initialize, context: {:?}", context);
+
+        let snapshots = self
+            .message_id_state()
+            .get()
+            .expect("get pulsar message ids error");
+        for snapshot in &snapshots {
+            state_recorder
+                .update_from_snapshot(snapshot.as_str())
+                .unwrap();
+        }
+        if !snapshots.is_empty() {
+            info!(
+                "load message id {:?} from the checkpoint({:?}) of {} tasks",
+                state_recorder.get(self.topic.as_str()),
+                context.checkpoint_id,
+                snapshots.len()
+            );
+        }
+        self.state_recorder = Some(state_recorder);
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let snapshot = self.state_recorder.as_ref().unwrap().snapshot();
+        debug!(
+            "Checkpoint snapshot: {:?}, context: {:?}",
+            snapshot, context
+        );
+
+        let handle = self
+            .message_id_state()
+            .update(vec![snapshot])
+            .and_then(|_| self.operator_state.snapshot());
+        match handle {
+            Ok(handle) => Some(handle),
+            Err(e) => panic!(
+                "snapshot pulsar message ids on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MessageIdSnapshot {
+    topic: String,
+    /// the last received message id of each partition topic
+    message_ids: HashMap<String, MessageId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PulsarSourceStateRecorder {
+    topic: String,
+    message_ids: Arc<Mutex<HashMap<String, MessageId>>>,
+}
+
+impl PulsarSourceStateRecorder {
+    pub fn new(topic: &str) -> Self {
+        PulsarSourceStateRecorder {
+            topic: topic.to_string(),
+            message_ids: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn update(&self, topic: &str, message_id: MessageId) {
+        let mut message_ids = self.message_ids.lock().unwrap();
+        match message_ids.get_mut(topic) {
+            Some(id) => *id = message_id,
+            None => {
+                message_ids.insert(topic.to_string(), message_id);
+            }
+        }
+    }
+
+    /// Restore from the snapshot of a task, the snapshots of all tasks are restored. Only the
+    /// snapshots of the same topics are taken, and the latest message id of a partition topic
+    /// wins if it's in the snapshots of several tasks, e.g. the failover consumers
+    pub fn update_from_snapshot(&self, snapshot_handle: &str) -> anyhow::Result<()> {
+        let snapshot: MessageIdSnapshot = serde_json::from_str(snapshot_handle)?;
+        if !snapshot.topic.eq(self.topic.as_str()) {
+            return Ok(());
+        }
+
+        let mut message_ids = self.message_ids.lock().unwrap();
+        for (topic, message_id) in snapshot.message_ids {
+            match message_ids.get(&topic) {
+                Some(current) if message_id.position() <= current.position() => {}
+                _ => {
+                    message_ids.insert(topic, message_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> String {
+        let message_ids = self.message_ids.lock().unwrap().clone();
+        serde_json::to_string(&MessageIdSnapshot {
+            topic: self.topic.clone(),
+            message_ids,
+        })
+        .unwrap()
+    }
+
+    pub fn get(&self, topic: &str) -> Option<MessageId> {
+        self.message_ids.lock().unwrap().get(topic).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::{MessageId, PulsarSourceStateRecorder};
+
+    #[test]
+    pub fn state_snapshot_test() {
+        let topic = "persistent://public/default/events-partition-1";
+        let recorder = PulsarSourceStateRecorder::new(topic);
+        assert!(recorder.get(topic).is_none());
+
+        let message_id = MessageId {
+            ledger_id: 12,
+            entry_id: 345,
+            partition: Some(1),
+            batch_index: None,
+        };
+        recorder.update(topic, message_id.clone());
+        let snapshot = recorder.snapshot();
+
+        let restored = PulsarSourceStateRecorder::new(topic);
+        restored.update_from_snapshot(snapshot.as_str()).unwrap();
+        assert_eq!(restored.get(topic), Some(message_id.clone()));
+
+        // the earlier message id of the failover consumer is ignored
+        let failover = PulsarSourceStateRecorder::new(topic);
+        failover.update(
+            topic,
+            MessageId {
+                ledger_id: 12,
+                entry_id: 300,
+                partition: Some(1),
+                batch_index: None,
+            },
+        );
+        restored
+            .update_from_snapshot(failover.snapshot().as_str())
+            .unwrap();
+        assert_eq!(restored.get(topic), Some(message_id));
+
+        // the snapshots of the other topics are skipped
+        let other = PulsarSourceStateRecorder::new("persistent://public/default/other");
+        other.update_from_snapshot(snapshot.as_str()).unwrap();
+        assert!(other.get(topic).is_none());
+    }
+}
@@ -0,0 +1,167 @@
+use futures::TryStreamExt;
+use pulsar::consumer::ConsumerOptions;
+use pulsar::{Consumer, TokioExecutor};
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::JobId;
+use rlink::metrics::{register_counter, Tag};
+
+use crate::metrics::CONSUMER_RECEIVED;
+use crate::source::checkpoint::MessageId;
+use crate::source::deserializer::PulsarRecordDeserializer;
+use crate::source::subscription::{StartPosition, SubscriptionMode};
+use crate::source::ConsumerRecord;
+use crate::PulsarClientConfig;
+
+pub(crate) async fn create_pulsar_consumer(mut pulsar_consumer: PulsarConsumerThread) {
+    tokio::spawn(async move {
+        match pulsar_consumer.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!("run consumer error. {}", e);
+            }
+        }
+    });
+}
+
+pub(crate) struct PulsarConsumerThread {
+    job_id: JobId,
+    task_number: u16,
+
+    client_config: PulsarClientConfig,
+    /// a partition topic in the `Partition` mode, all topics in the `KeyShared` mode
+    topics: Vec<String>,
+    subscription: String,
+    subscription_mode: SubscriptionMode,
+    start_position: StartPosition,
+    /// the message id restored from the checkpoint, only seeked in the `Partition` mode
+    restored: Option<MessageId>,
+
+    sender: ChannelSender<ConsumerRecord>,
+    deserializer: Box<dyn PulsarRecordDeserializer>,
+}
+
+impl PulsarConsumerThread {
+    pub fn new(
+        job_id: JobId,
+        task_number: u16,
+        client_config: PulsarClientConfig,
+        topics: Vec<String>,
+        subscription: String,
+        sender: ChannelSender<ConsumerRecord>,
+        deserializer: Box<dyn PulsarRecordDeserializer>,
+    ) -> Self {
+        PulsarConsumerThread {
+            job_id,
+            task_number,
+            client_config,
+            topics,
+            subscription,
+            subscription_mode: SubscriptionMode::default(),
+            start_position: StartPosition::default(),
+            restored: None,
+            sender,
+            deserializer,
+        }
+    }
+
+    pub fn subscription_mode(mut self, subscription_mode: SubscriptionMode) -> Self {
+        self.subscription_mode = subscription_mode;
+        self
+    }
+
+    pub fn start_position(mut self, start_position: StartPosition) -> Self {
+        self.start_position = start_position;
+        self
+    }
+
+    pub fn restored(mut self, restored: Option<MessageId>) -> Self {
+        self.restored = restored;
+        self
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let pulsar = self.client_config.connect().await?;
+
+        let consumer_name = format!("rlink-{}-{}", *self.job_id, self.task_number);
+        let options = ConsumerOptions::default()
+            .with_initial_position(self.start_position.initial_position());
+        let mut consumer: Consumer<Vec<u8>, TokioExecutor> = pulsar
+            .consumer()
+            .with_topics(&self.topics)
+            .with_subscription(self.subscription.as_str())
+            .with_subscription_type(self.subscription_mode.sub_type())
+            .with_consumer_name(consumer_name.as_str())
+            .with_options(options)
+            .build()
+            .await?;
+
+        // the seek is inclusive, the restored message itself is skipped below
+        let restored = match self.subscription_mode {
+            SubscriptionMode::Partition => self.restored.take(),
+            SubscriptionMode::KeyShared => None,
+        };
+        if let Some(message_id) = &restored {
+            consumer
+                .seek(None, Some(message_id.clone().into()), None, pulsar.clone())
+                .await?;
+        }
+
+        let topics = self.topics.join(",");
+        info!(
+            "create consumer success. config: {:?}, topics: {}, subscription: {}, mode: {:?}, restored: {:?}, job_id: {}, task_num: {}",
+            self.client_config, topics, self.subscription, self.subscription_mode, restored, *self.job_id, self.task_number
+        );
+
+        let tags = vec![
+            Tag::new("job_id", *self.job_id),
+            Tag::new("task_number", self.task_number),
+            Tag::new("topic", topics.as_str()),
+        ];
+        let received_counter = register_counter(CONSUMER_RECEIVED, tags);
+
+        while let Some(message) = consumer.try_next().await? {
+            received_counter.increment(1);
+
+            let message_id = MessageId::from(message.message_id());
+            if restored.as_ref() == Some(&message_id) {
+                consumer.ack(&message).await?;
+                continue;
+            }
+
+            let metadata = &message.payload.metadata;
+            let timestamp = metadata.event_time.unwrap_or(metadata.publish_time) as i64;
+            let key = metadata.partition_key.as_deref().unwrap_or("");
+            let payload = message.payload.data.as_slice();
+
+            // the state of the `Partition` mode is keyed by the configured topic name, which is
+            // looked up when restoring, rather than the fully qualified name of the message
+            let state_topic = match self.subscription_mode {
+                SubscriptionMode::Partition => self.topics[0].as_str(),
+                SubscriptionMode::KeyShared => message.topic.as_str(),
+            };
+
+            let records =
+                self.deserializer
+                    .deserialize(timestamp, key, payload, message.topic.as_str());
+            for record in records {
+                self.sender
+                    .send(ConsumerRecord::new(
+                        record,
+                        state_topic.to_string(),
+                        message_id.clone(),
+                    ))
+                    .await
+                    .expect("pulsar consumer handover `Disconnected`");
+            }
+
+            if let Err(e) = consumer.ack(&message).await {
+                warn!(
+                    "Pulsar ack error. job_id: {}, task_num: {}, error: {}",
+                    *self.job_id, self.task_number, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
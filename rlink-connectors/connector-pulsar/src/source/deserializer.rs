@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+
+use crate::build_pulsar_record;
+
+pub trait PulsarRecordDeserializer: Sync + Send {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        key: &str,
+        payload: &[u8],
+        topic: &str,
+    ) -> Vec<Record>;
+}
+
+pub trait PulsarRecordDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn PulsarRecordDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+#[derive(Default)]
+pub struct DefaultPulsarRecordDeserializer {}
+
+impl PulsarRecordDeserializer for DefaultPulsarRecordDeserializer {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        key: &str,
+        payload: &[u8],
+        topic: &str,
+    ) -> Vec<Record> {
+        let record = build_pulsar_record(timestamp, key, payload, topic)
+            .expect("pulsar message writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultPulsarRecordDeserializerBuilder<T>
+where
+    T: Default + PulsarRecordDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultPulsarRecordDeserializerBuilder<T>
+where
+    T: Default + PulsarRecordDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultPulsarRecordDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> PulsarRecordDeserializerBuilder for DefaultPulsarRecordDeserializerBuilder<T>
+where
+    T: Default + PulsarRecordDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn PulsarRecordDeserializer> {
+        let t: Box<dyn PulsarRecordDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::properties::Properties;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::Tag;
+
+use crate::source::checkpoint::PulsarCheckpointFunction;
+use crate::source::consumer::{create_pulsar_consumer, PulsarConsumerThread};
+use crate::source::deserializer::PulsarRecordDeserializerBuilder;
+use crate::source::stream::PulsarRecordStream;
+use crate::source::subscription::{StartPosition, SubscriptionMode};
+use crate::{block_on, PulsarClientConfig};
+
+/// The topics consumed by the split, separated by `,`
+const SPLIT_TOPICS: &str = "topics";
+
+pub struct PulsarInputFormat {
+    name: String,
+    parallelism: u16,
+
+    client_config: PulsarClientConfig,
+    topics: Vec<String>,
+    subscription: String,
+    subscription_mode: SubscriptionMode,
+    start_position: StartPosition,
+
+    task_id: TaskId,
+    task_topics: String,
+
+    buffer_size: usize,
+
+    tags: Vec<Tag>,
+
+    deserializer_builder: Arc<dyn PulsarRecordDeserializerBuilder>,
+    schema: FnSchema,
+
+    checkpoint: Option<PulsarCheckpointFunction>,
+}
+
+impl PulsarInputFormat {
+    pub fn new(
+        client_config: PulsarClientConfig,
+        topics: Vec<String>,
+        subscription: String,
+        buffer_size: usize,
+        deserializer_builder: Box<dyn PulsarRecordDeserializerBuilder>,
+        parallelism: u16,
+        fn_name: String,
+    ) -> Self {
+        let schema = deserializer_builder.schema();
+        PulsarInputFormat {
+            name: fn_name,
+            parallelism,
+            client_config,
+            topics,
+            subscription,
+            subscription_mode: SubscriptionMode::default(),
+            start_position: StartPosition::default(),
+            task_id: Default::default(),
+            task_topics: "".to_string(),
+            buffer_size,
+            tags: vec![],
+            deserializer_builder: Arc::from(deserializer_builder),
+            schema,
+            checkpoint: None,
+        }
+    }
+
+    pub fn subscription_mode(mut self, subscription_mode: SubscriptionMode) -> Self {
+        self.subscription_mode = subscription_mode;
+        self
+    }
+
+    pub fn start_position(mut self, start_position: StartPosition) -> Self {
+        self.start_position = start_position;
+        self
+    }
+
+    /// Expand the partitioned topics into the partition topics, a non-partitioned topic is
+    /// kept as is
+    async fn partition_topics(&self) -> anyhow::Result<Vec<String>> {
+        let pulsar = self.client_config.connect().await?;
+
+        let mut partition_topics = Vec::new();
+        for topic in &self.topics {
+            let partition_num = pulsar
+                .lookup_partitioned_topic_number(topic.as_str())
+                .await
+                .map_err(|e| anyhow!("lookup topic({}) partitions error. {}", topic, e))?;
+            if partition_num == 0 {
+                partition_topics.push(topic.clone());
+            } else {
+                for partition in 0..partition_num {
+                    partition_topics.push(format!("{}-partition-{}", topic, partition));
+                }
+            }
+        }
+
+        Ok(partition_topics)
+    }
+}
+
+impl NamedFunction for PulsarInputFormat {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl InputFormat for PulsarInputFormat {
+    async fn open(&mut self, input_split: InputSplit, context: &Context) -> core::Result<()> {
+        info!("pulsar source open");
+
+        self.task_id = context.task_id.clone();
+        self.task_topics = input_split.properties().get_string(SPLIT_TOPICS)?;
+
+        let checkpoint = PulsarCheckpointFunction::new(
+            context.application_id.clone(),
+            context.task_id,
+            self.task_topics.as_str(),
+        );
+        checkpoint.restore_from(context)?;
+        self.checkpoint = Some(checkpoint);
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        self.tags.push(Tag::new("topic", self.task_topics.as_str()));
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("PulsarSource_Handover", self.tags.clone(), self.buffer_size);
+
+        let state_recorder = self.checkpoint.as_mut().unwrap().as_state_mut().clone();
+        let restored = state_recorder.get(self.task_topics.as_str());
+
+        let topics = self
+            .task_topics
+            .split(',')
+            .map(|topic| topic.to_string())
+            .collect();
+        let pulsar_consumer = PulsarConsumerThread::new(
+            self.task_id.job_id(),
+            self.task_id.task_number(),
+            self.client_config.clone(),
+            topics,
+            self.subscription.clone(),
+            sender,
+            self.deserializer_builder.build(),
+        )
+        .subscription_mode(self.subscription_mode.clone())
+        .start_position(self.start_position.clone())
+        .restored(restored);
+        create_pulsar_consumer(pulsar_consumer).await;
+
+        Box::pin(PulsarRecordStream::new(receiver, state_recorder))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for PulsarInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        self.checkpoint
+            .as_mut()
+            .unwrap()
+            .initialize_state(context, handle)
+            .await;
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        match self.checkpoint.as_mut() {
+            Some(checkpoint) => checkpoint.snapshot_state(context).await,
+            None => None,
+        }
+    }
+}
+
+impl InputSplitSource for PulsarInputFormat {
+    fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
+        info!("pulsar config {:?}", self.client_config);
+
+        let split_topics = match self.subscription_mode {
+            SubscriptionMode::Partition => block_on(self.partition_topics())?,
+            // every subtask subscribes all topics by the shared subscription
+            SubscriptionMode::KeyShared => vec![self.topics.join(","); min_num_splits as usize],
+        };
+        if split_topics.is_empty() {
+            return Err(rlink::core::Error::from("no pulsar topic to consume"));
+        }
+        if split_topics.len() > min_num_splits as usize {
+            return Err(rlink::core::Error::from(format!(
+                "pulsar source parallelism({}) is less than the partitions({})",
+                min_num_splits,
+                split_topics.len()
+            )));
+        }
+
+        // the subtasks beyond the partitions are the failover consumers of the partitions
+        let mut input_splits = Vec::with_capacity(min_num_splits as usize);
+        for index in 0..min_num_splits as usize {
+            let mut properties = Properties::new();
+            properties.set_str(
+                SPLIT_TOPICS,
+                split_topics[index % split_topics.len()].as_str(),
+            );
+            input_splits.push(InputSplit::new(index as u16, properties));
+        }
+
+        Ok(input_splits)
+    }
+}
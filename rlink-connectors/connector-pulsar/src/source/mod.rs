@@ -0,0 +1,26 @@
+pub mod builder;
+pub mod checkpoint;
+pub mod consumer;
+pub mod deserializer;
+pub mod input_format;
+pub mod stream;
+pub mod subscription;
+
+use crate::source::checkpoint::MessageId;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ConsumerRecord {
+    record: rlink::core::element::Record,
+    topic: String,
+    message_id: MessageId,
+}
+
+impl ConsumerRecord {
+    pub fn new(record: rlink::core::element::Record, topic: String, message_id: MessageId) -> Self {
+        ConsumerRecord {
+            record,
+            topic,
+            message_id,
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::convert::TryFrom;
+
+use pulsar::consumer::InitialPosition;
+use pulsar::SubType;
+
+/// How the topic is consumed across the subtasks of the source
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum SubscriptionMode {
+    /// Each subtask consumes a partition of the topics by a `Failover` subscription, the
+    /// position is restored from the checkpointed message id of the partition.
+    /// The subtasks beyond the partition count are the standby consumers of the partitions.
+    #[default]
+    Partition,
+    /// All subtasks share a `Key_Shared` subscription of the topics, the messages of the
+    /// same key go to the same subtask. The brokers don't support seeking a single consumer
+    /// of a shared subscription, so the position is resumed from the acknowledged messages
+    /// of the subscription rather than the checkpoint.
+    KeyShared,
+}
+
+impl SubscriptionMode {
+    pub(crate) fn sub_type(&self) -> SubType {
+        match self {
+            Self::Partition => SubType::Failover,
+            Self::KeyShared => SubType::KeyShared,
+        }
+    }
+}
+
+impl TryFrom<&str> for SubscriptionMode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "partition" => Ok(Self::Partition),
+            "key_shared" => Ok(Self::KeyShared),
+            _ => Err(anyhow!("unknown subscription mode {}", value)),
+        }
+    }
+}
+
+/// Where a new subscription begins, the position of an existing subscription or the
+/// restored checkpoint takes precedence over it
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum StartPosition {
+    Earliest,
+    #[default]
+    Latest,
+}
+
+impl StartPosition {
+    pub(crate) fn initial_position(&self) -> InitialPosition {
+        match self {
+            Self::Earliest => InitialPosition::Earliest,
+            Self::Latest => InitialPosition::Latest,
+        }
+    }
+}
+
+impl TryFrom<&str> for StartPosition {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "earliest" => Ok(Self::Earliest),
+            "latest" => Ok(Self::Latest),
+            _ => Err(anyhow!("unknown start position {}", value)),
+        }
+    }
+}
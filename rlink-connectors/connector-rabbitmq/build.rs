@@ -0,0 +1,14 @@
+use serbuffer_gen::{Codegen, DataType::*, SchemaBuilder};
+
+fn main() {
+    Codegen::out_dir("buffer_gen")
+        .schema(
+            SchemaBuilder::new("RabbitmqMessage")
+                .field("timestamp", I64)
+                .field("exchange", STRING)
+                .field("routing_key", STRING)
+                .field("payload", BINARY),
+        )
+        .gen()
+        .expect("buffer gen error");
+}
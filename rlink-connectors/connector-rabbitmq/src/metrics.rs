@@ -0,0 +1,40 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const CONSUMER_RECEIVED: &str = "Rabbitmq.Consumer.Received";
+pub const CONSUMER_ACKED: &str = "Rabbitmq.Consumer.Acked";
+pub const PRODUCER_SENT: &str = "Rabbitmq.Producer.Sent";
+pub const PRODUCER_CONFIRMED: &str = "Rabbitmq.Producer.Confirmed";
+pub const PRODUCER_FAILED: &str = "Rabbitmq.Producer.Failed";
+
+/// Delivery counters of the producer task, tagged by the task and exchange
+#[derive(Clone)]
+pub(crate) struct ProducerMetrics {
+    /// messages published to the channel
+    sent: Counter,
+    /// messages confirmed by the broker
+    confirmed: Counter,
+    /// messages failed to publish or nacked by the broker
+    failed: Counter,
+}
+
+impl ProducerMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        ProducerMetrics {
+            sent: register_counter(PRODUCER_SENT, tags.clone()),
+            confirmed: register_counter(PRODUCER_CONFIRMED, tags.clone()),
+            failed: register_counter(PRODUCER_FAILED, tags),
+        }
+    }
+
+    pub fn sent(&self) {
+        self.sent.increment(1);
+    }
+
+    pub fn confirmed(&self) {
+        self.confirmed.increment(1);
+    }
+
+    pub fn failed(&self) {
+        self.failed.increment(1);
+    }
+}
@@ -0,0 +1,85 @@
+use std::convert::TryFrom;
+
+use rlink::core::properties::Properties;
+
+use crate::{
+    RabbitmqClientConfig, RabbitmqOutputFormat, BATCH_SIZE, BUFFER_SIZE, EXCHANGE, RABBITMQ,
+    ROUTING_KEY, SINK_BATCH_SIZE, SINK_CHANNEL_SIZE,
+};
+
+#[derive(Debug)]
+pub struct RabbitmqOutputFormatBuilder {
+    client_config: RabbitmqClientConfig,
+    exchange: Option<String>,
+    routing_key: Option<String>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+}
+
+impl RabbitmqOutputFormatBuilder {
+    /// The records are published to the `exchange` if specified, otherwise to the `exchange`
+    /// field of the `RabbitmqMessage` records
+    pub fn new(client_config: RabbitmqClientConfig, exchange: Option<String>) -> Self {
+        RabbitmqOutputFormatBuilder {
+            client_config,
+            exchange,
+            routing_key: None,
+            buffer_size: None,
+            batch_size: None,
+        }
+    }
+
+    /// Override the `routing_key` field of the records
+    pub fn routing_key(mut self, routing_key: &str) -> Self {
+        self.routing_key = Some(routing_key.to_string());
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Publish up to `batch_size` messages before waiting for the publisher confirms
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    pub fn build(self) -> RabbitmqOutputFormat {
+        info!("build rabbitmq sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let batch_size = self.batch_size.unwrap_or(SINK_BATCH_SIZE);
+        RabbitmqOutputFormat::new(
+            self.client_config,
+            self.exchange,
+            self.routing_key,
+            buffer_size,
+            batch_size,
+        )
+    }
+}
+
+impl TryFrom<Properties> for RabbitmqOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let client_config = RabbitmqClientConfig::try_from(properties.to_sub_properties(RABBITMQ))?;
+
+        let exchange = properties.get_string(EXCHANGE).ok();
+        let mut builder = RabbitmqOutputFormatBuilder::new(client_config, exchange);
+
+        if let Ok(routing_key) = properties.get_string(ROUTING_KEY) {
+            builder = builder.routing_key(routing_key.as_str());
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+
+        Ok(builder)
+    }
+}
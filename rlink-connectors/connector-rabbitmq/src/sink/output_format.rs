@@ -0,0 +1,98 @@
+use rlink::channel::named_channel;
+use rlink::channel::sender::ChannelSender;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::{Element, Record};
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink::metrics::Tag;
+
+use crate::sink::producer::RabbitmqProducerThread;
+use crate::RabbitmqClientConfig;
+
+#[derive(NamedFunction)]
+pub struct RabbitmqOutputFormat {
+    client_config: RabbitmqClientConfig,
+    exchange: Option<String>,
+    routing_key: Option<String>,
+
+    buffer_size: usize,
+    batch_size: usize,
+    handover: Option<ChannelSender<Record>>,
+}
+
+impl RabbitmqOutputFormat {
+    pub fn new(
+        client_config: RabbitmqClientConfig,
+        exchange: Option<String>,
+        routing_key: Option<String>,
+        buffer_size: usize,
+        batch_size: usize,
+    ) -> Self {
+        RabbitmqOutputFormat {
+            client_config,
+            exchange,
+            routing_key,
+            buffer_size,
+            batch_size,
+            handover: None,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for RabbitmqOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let mut tags = context.task_id.to_tags();
+        tags.push(Tag::new(
+            "exchange",
+            self.exchange.as_ref().map(|x| x.as_str()).unwrap_or(""),
+        ));
+
+        let (sender, receiver) = named_channel(self.name(), tags.clone(), self.buffer_size);
+        self.handover = Some(sender);
+
+        let mut rabbitmq_producer = RabbitmqProducerThread::new(
+            self.client_config.clone(),
+            self.exchange.clone(),
+            self.routing_key.clone(),
+            receiver,
+        )
+        .batch_size(self.batch_size)
+        .tags(tags);
+        tokio::spawn(async move {
+            rabbitmq_producer.run().await;
+        });
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        self.handover
+            .as_ref()
+            .unwrap()
+            .send(element.into_record())
+            .await
+            .unwrap();
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for RabbitmqOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
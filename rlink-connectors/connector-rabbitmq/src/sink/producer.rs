@@ -0,0 +1,162 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use lapin::options::{BasicPublishOptions, ConfirmSelectOptions};
+use lapin::publisher_confirm::{Confirmation, PublisherConfirm};
+use lapin::{BasicProperties, Channel, Connection};
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::channel::TryRecvError;
+use rlink::core::element::Record;
+use rlink::metrics::Tag;
+
+use crate::buffer_gen::rabbitmq_message;
+use crate::metrics::ProducerMetrics;
+use crate::{RabbitmqClientConfig, SINK_BATCH_SIZE};
+
+/// the `delivery_mode` of the messages persisted by the broker
+const PERSISTENT: u8 = 2;
+
+pub struct RabbitmqProducerThread {
+    client_config: RabbitmqClientConfig,
+    exchange: Option<String>,
+    routing_key: Option<String>,
+    receiver: ChannelReceiver<Record>,
+    batch_size: usize,
+
+    tags: Vec<Tag>,
+}
+
+impl RabbitmqProducerThread {
+    pub fn new(
+        client_config: RabbitmqClientConfig,
+        exchange: Option<String>,
+        routing_key: Option<String>,
+        receiver: ChannelReceiver<Record>,
+    ) -> Self {
+        RabbitmqProducerThread {
+            client_config,
+            exchange,
+            routing_key,
+            receiver,
+            batch_size: SINK_BATCH_SIZE,
+            tags: vec![],
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    async fn publish(
+        &self,
+        channel: &Channel,
+        mut record: Record,
+    ) -> anyhow::Result<PublisherConfirm> {
+        let rabbitmq_message::Entity {
+            timestamp,
+            exchange,
+            routing_key,
+            payload,
+        } = rabbitmq_message::Entity::parse(record.as_buffer()).unwrap();
+
+        let exchange = self.exchange.as_deref().unwrap_or(exchange);
+        let routing_key = self.routing_key.as_deref().unwrap_or(routing_key);
+
+        // the amqp timestamp is in seconds
+        let timestamp = if timestamp > 0 {
+            timestamp as u64 / 1000
+        } else {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|x| x.as_secs())
+                .unwrap_or(0)
+        };
+        let properties = BasicProperties::default()
+            .with_delivery_mode(PERSISTENT)
+            .with_timestamp(timestamp);
+
+        let confirm = channel
+            .basic_publish(
+                exchange,
+                routing_key,
+                BasicPublishOptions::default(),
+                payload,
+                properties,
+            )
+            .await?;
+        Ok(confirm)
+    }
+
+    pub async fn run(&mut self) {
+        // the connection is kept until the producer is finished
+        let (_connection, channel) = match self.open_channel().await {
+            Ok(opened) => opened,
+            Err(e) => {
+                error!("run producer error. {}", e);
+                return;
+            }
+        };
+        let metrics = ProducerMetrics::new(self.tags.clone());
+
+        // wait for the first record, then drain the channel up to the batch size
+        while let Some(record) = self.receiver.recv().await {
+            let mut records = vec![record];
+            for _n in 1..self.batch_size {
+                match self.receiver.try_recv() {
+                    Ok(record) => records.push(record),
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+                }
+            }
+
+            let mut confirms = FuturesUnordered::new();
+            for record in records {
+                match self.publish(&channel, record).await {
+                    Ok(confirm) => {
+                        metrics.sent();
+                        confirms.push(confirm);
+                    }
+                    Err(e) => {
+                        metrics.failed();
+                        error!(
+                            "publish rabbitmq message error, the record is discarded. {}",
+                            e
+                        );
+                    }
+                }
+            }
+
+            // settle the confirms of the batch before publishing the next one
+            while let Some(confirmation) = confirms.next().await {
+                match confirmation {
+                    Ok(Confirmation::Ack(_)) => metrics.confirmed(),
+                    Ok(confirmation) => {
+                        metrics.failed();
+                        error!("rabbitmq message is not confirmed. {:?}", confirmation);
+                    }
+                    Err(e) => {
+                        metrics.failed();
+                        error!("rabbitmq message delivery failed. {}", e);
+                    }
+                }
+            }
+        }
+
+        info!("rabbitmq recv channel closed");
+    }
+
+    async fn open_channel(&self) -> anyhow::Result<(Connection, Channel)> {
+        let connection = self.client_config.connect().await?;
+        let channel = connection.create_channel().await?;
+        channel
+            .confirm_select(ConfirmSelectOptions::default())
+            .await?;
+        Ok((connection, channel))
+    }
+}
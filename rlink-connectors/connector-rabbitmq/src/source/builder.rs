@@ -0,0 +1,120 @@
+use std::convert::TryFrom;
+
+use rlink::core::element::FnSchema;
+use rlink::core::properties::{Properties, PARALLELISM};
+
+use crate::buffer_gen::rabbitmq_message;
+use crate::source::deserializer::{
+    DefaultRabbitmqRecordDeserializer, DefaultRabbitmqRecordDeserializerBuilder,
+    RabbitmqRecordDeserializerBuilder,
+};
+use crate::{
+    RabbitmqClientConfig, RabbitmqInputFormat, BUFFER_SIZE, INPUT_FORMAT_FN_NAME_DEFAULT, PREFETCH,
+    QUEUES, RABBITMQ, SOURCE_CHANNEL_SIZE, SOURCE_PREFETCH,
+};
+
+#[derive(Debug)]
+pub struct RabbitmqInputFormatBuilder {
+    fn_name: Option<String>,
+    parallelism: u16,
+    client_config: RabbitmqClientConfig,
+    queues: Vec<String>,
+    prefetch: Option<u16>,
+    buffer_size: Option<usize>,
+}
+
+impl RabbitmqInputFormatBuilder {
+    pub fn new(client_config: RabbitmqClientConfig, queues: Vec<String>, parallelism: u16) -> Self {
+        RabbitmqInputFormatBuilder {
+            fn_name: None,
+            parallelism,
+            client_config,
+            queues,
+            prefetch: None,
+            buffer_size: None,
+        }
+    }
+
+    pub fn fn_name(mut self, name: &str) -> Self {
+        self.fn_name = Some(name.to_string());
+        self
+    }
+
+    /// The max unacknowledged messages of each queue. The messages are acknowledged when the
+    /// checkpoint is completed, so it should cover the messages received in a checkpoint
+    /// interval, otherwise the consumption is paused until the next checkpoint
+    pub fn prefetch(mut self, prefetch: u16) -> Self {
+        self.prefetch = Some(prefetch);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    pub fn build(
+        self,
+        deserializer_builder: Option<Box<dyn RabbitmqRecordDeserializerBuilder>>,
+    ) -> RabbitmqInputFormat {
+        info!("build rabbitmq source with: {:?}", &self);
+
+        let fn_name = self
+            .fn_name
+            .unwrap_or(INPUT_FORMAT_FN_NAME_DEFAULT.to_string());
+        let buffer_size = self.buffer_size.unwrap_or(SOURCE_CHANNEL_SIZE);
+        let prefetch = self.prefetch.unwrap_or(SOURCE_PREFETCH);
+
+        let deserializer_builder = deserializer_builder.unwrap_or_else(|| {
+            let deserializer_builder: Box<dyn RabbitmqRecordDeserializerBuilder> =
+                Box::new(DefaultRabbitmqRecordDeserializerBuilder::<
+                    DefaultRabbitmqRecordDeserializer,
+                >::new(FnSchema::from(
+                    &rabbitmq_message::FIELD_METADATA,
+                )));
+
+            deserializer_builder
+        });
+
+        RabbitmqInputFormat::new(
+            self.client_config,
+            self.queues,
+            buffer_size,
+            deserializer_builder,
+            self.parallelism,
+            fn_name,
+        )
+        .prefetch(prefetch)
+    }
+}
+
+impl TryFrom<Properties> for RabbitmqInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let parallelism = properties.get_u16(PARALLELISM)?;
+
+        let client_config = RabbitmqClientConfig::try_from(properties.to_sub_properties(RABBITMQ))?;
+
+        let queues: Vec<String> = properties
+            .get_string(QUEUES)?
+            .trim()
+            .split(',')
+            .map(|x| x.to_string())
+            .collect();
+
+        let mut builder = RabbitmqInputFormatBuilder::new(client_config, queues, parallelism);
+
+        builder = builder.fn_name(properties.name());
+
+        if let Ok(prefetch) = properties.get_u16(PREFETCH) {
+            builder = builder.prefetch(prefetch);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct AckState {
+    /// the last delivery tag emitted to the downstream by queue
+    delivery_tags: HashMap<String, u64>,
+    /// the delivery tags by queue of the checkpoints taken but not completed yet
+    pending: BTreeMap<u64, HashMap<String, u64>>,
+}
+
+/// Tracks the deliveries covered by the checkpoints.
+///
+/// The messages are acknowledged only when a checkpoint containing them is completed, so the
+/// unacknowledged messages are redelivered by the broker after a failover. There is nothing to
+/// restore from the checkpoint handle.
+#[derive(Debug, Clone, Default)]
+pub struct RabbitmqAckRecorder {
+    state: Arc<Mutex<AckState>>,
+}
+
+impl RabbitmqAckRecorder {
+    pub fn new() -> Self {
+        RabbitmqAckRecorder::default()
+    }
+
+    /// The delivery tags of a channel are increasing, the last one covers the previous ones
+    pub fn update(&self, queue: &str, delivery_tag: u64) {
+        let mut state = self.state.lock().unwrap();
+        match state.delivery_tags.get_mut(queue) {
+            Some(tag) => *tag = delivery_tag,
+            None => {
+                state.delivery_tags.insert(queue.to_string(), delivery_tag);
+            }
+        }
+    }
+
+    /// Mark the deliveries emitted so far as the part of the checkpoint
+    pub fn snapshot(&self, checkpoint_id: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.delivery_tags.is_empty() {
+            return;
+        }
+
+        let delivery_tags = std::mem::take(&mut state.delivery_tags);
+        state.pending.insert(checkpoint_id, delivery_tags);
+    }
+
+    /// Returns the last delivery tag by queue to acknowledge once the checkpoint is completed
+    pub fn complete(&self, checkpoint_id: u64) -> HashMap<String, u64> {
+        let mut state = self.state.lock().unwrap();
+
+        let uncompleted = state.pending.split_off(&(checkpoint_id + 1));
+        let completed = std::mem::replace(&mut state.pending, uncompleted);
+
+        let mut acks = HashMap::new();
+        for (_checkpoint_id, delivery_tags) in completed {
+            acks.extend(delivery_tags);
+        }
+        acks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::RabbitmqAckRecorder;
+
+    #[test]
+    pub fn ack_on_complete_test() {
+        let recorder = RabbitmqAckRecorder::new();
+        recorder.update("orders", 1);
+        recorder.update("orders", 2);
+        recorder.update("payments", 1);
+        recorder.snapshot(1);
+
+        recorder.update("orders", 3);
+        recorder.snapshot(2);
+
+        // the deliveries after the checkpoint are not acknowledged
+        recorder.update("orders", 4);
+
+        let acks = recorder.complete(1);
+        assert_eq!(acks.len(), 2);
+        assert_eq!(acks.get("orders"), Some(&2));
+        assert_eq!(acks.get("payments"), Some(&1));
+
+        // nothing pending for a checkpoint completed twice
+        assert!(recorder.complete(1).is_empty());
+
+        recorder.snapshot(3);
+        let acks = recorder.complete(3);
+        assert_eq!(acks.len(), 1);
+        assert_eq!(acks.get("orders"), Some(&4));
+    }
+}
@@ -0,0 +1,115 @@
+use futures::StreamExt;
+use lapin::Consumer;
+use rlink::channel::sender::ChannelSender;
+use rlink::core::runtime::JobId;
+use rlink::metrics::{register_counter, Tag};
+
+use crate::metrics::CONSUMER_RECEIVED;
+use crate::source::deserializer::RabbitmqRecordDeserializer;
+use crate::source::ConsumerRecord;
+
+pub(crate) async fn create_rabbitmq_consumer(mut rabbitmq_consumer: RabbitmqConsumerThread) {
+    tokio::spawn(async move {
+        match rabbitmq_consumer.run().await {
+            Ok(()) => {}
+            Err(e) => {
+                error!("run consumer error. {}", e);
+            }
+        }
+    });
+}
+
+pub(crate) struct RabbitmqConsumerThread {
+    job_id: JobId,
+    task_number: u16,
+
+    /// the consumers by queue, each on its own channel
+    consumers: Vec<(String, Consumer)>,
+
+    sender: ChannelSender<ConsumerRecord>,
+    deserializer: Box<dyn RabbitmqRecordDeserializer>,
+}
+
+impl RabbitmqConsumerThread {
+    pub fn new(
+        job_id: JobId,
+        task_number: u16,
+        consumers: Vec<(String, Consumer)>,
+        sender: ChannelSender<ConsumerRecord>,
+        deserializer: Box<dyn RabbitmqRecordDeserializer>,
+    ) -> Self {
+        RabbitmqConsumerThread {
+            job_id,
+            task_number,
+            consumers,
+            sender,
+            deserializer,
+        }
+    }
+
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        let queues: Vec<String> = self
+            .consumers
+            .iter()
+            .map(|(queue, _)| queue.clone())
+            .collect();
+        let queues = queues.join(",");
+        info!(
+            "create consumer success. queues: {}, job_id: {}, task_num: {}",
+            queues, *self.job_id, self.task_number
+        );
+
+        let tags = vec![
+            Tag::new("job_id", *self.job_id),
+            Tag::new("task_number", self.task_number),
+            Tag::new("queue", queues.as_str()),
+        ];
+        let received_counter = register_counter(CONSUMER_RECEIVED, tags);
+
+        let streams = std::mem::take(&mut self.consumers)
+            .into_iter()
+            .map(|(queue, consumer)| {
+                consumer
+                    .map(move |delivery| (queue.clone(), delivery))
+                    .boxed()
+            });
+        let mut deliveries = futures::stream::select_all(streams);
+
+        while let Some((queue, delivery)) = deliveries.next().await {
+            let delivery =
+                delivery.map_err(|e| anyhow!("consume queue({}) error. {}", queue, e))?;
+            received_counter.increment(1);
+
+            // the amqp timestamp is in seconds
+            let timestamp = delivery
+                .properties
+                .timestamp()
+                .as_ref()
+                .map(|x| *x as i64 * 1000)
+                .unwrap_or(0);
+
+            let records = self.deserializer.deserialize(
+                timestamp,
+                delivery.exchange.as_str(),
+                delivery.routing_key.as_str(),
+                delivery.data.as_slice(),
+            );
+            for record in records {
+                self.sender
+                    .send(ConsumerRecord::new(
+                        record,
+                        queue.clone(),
+                        delivery.delivery_tag,
+                    ))
+                    .await
+                    .expect("rabbitmq consumer handover `Disconnected`");
+            }
+        }
+
+        info!(
+            "rabbitmq consumer cancelled. queues: {}, job_id: {}, task_num: {}",
+            queues, *self.job_id, self.task_number
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::marker::PhantomData;
+
+use rlink::core::element::{FnSchema, Record};
+
+use crate::build_rabbitmq_record;
+
+pub trait RabbitmqRecordDeserializer: Sync + Send {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+    ) -> Vec<Record>;
+}
+
+pub trait RabbitmqRecordDeserializerBuilder: Send + Sync {
+    fn build(&self) -> Box<dyn RabbitmqRecordDeserializer>;
+    fn schema(&self) -> FnSchema;
+}
+
+#[derive(Default)]
+pub struct DefaultRabbitmqRecordDeserializer {}
+
+impl RabbitmqRecordDeserializer for DefaultRabbitmqRecordDeserializer {
+    fn deserialize(
+        &mut self,
+        timestamp: i64,
+        exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+    ) -> Vec<Record> {
+        let record = build_rabbitmq_record(timestamp, exchange, routing_key, payload)
+            .expect("rabbitmq message writer to Record error");
+        vec![record]
+    }
+}
+
+pub struct DefaultRabbitmqRecordDeserializerBuilder<T>
+where
+    T: Default + RabbitmqRecordDeserializer + 'static,
+{
+    a: PhantomData<T>,
+    schema: FnSchema,
+}
+
+impl<T> DefaultRabbitmqRecordDeserializerBuilder<T>
+where
+    T: Default + RabbitmqRecordDeserializer + 'static,
+{
+    pub fn new(schema: FnSchema) -> Self {
+        DefaultRabbitmqRecordDeserializerBuilder {
+            a: PhantomData,
+            schema,
+        }
+    }
+}
+
+impl<T> RabbitmqRecordDeserializerBuilder for DefaultRabbitmqRecordDeserializerBuilder<T>
+where
+    T: Default + RabbitmqRecordDeserializer + 'static,
+{
+    fn build(&self) -> Box<dyn RabbitmqRecordDeserializer> {
+        let t: Box<dyn RabbitmqRecordDeserializer> = Box::new(T::default());
+        t
+    }
+
+    fn schema(&self) -> FnSchema {
+        self.schema.clone()
+    }
+}
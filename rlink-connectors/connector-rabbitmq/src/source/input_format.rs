@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lapin::options::{BasicAckOptions, BasicConsumeOptions, BasicQosOptions};
+use lapin::types::FieldTable;
+use lapin::{Channel, Connection, Consumer};
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::core::properties::Properties;
+use rlink::core::runtime::TaskId;
+use rlink::metrics::{register_counter, Counter, Tag};
+
+use crate::metrics::CONSUMER_ACKED;
+use crate::source::checkpoint::RabbitmqAckRecorder;
+use crate::source::consumer::{create_rabbitmq_consumer, RabbitmqConsumerThread};
+use crate::source::deserializer::RabbitmqRecordDeserializerBuilder;
+use crate::source::stream::RabbitmqRecordStream;
+use crate::{RabbitmqClientConfig, SOURCE_PREFETCH};
+
+pub struct RabbitmqInputFormat {
+    name: String,
+    parallelism: u16,
+
+    client_config: RabbitmqClientConfig,
+    queues: Vec<String>,
+    prefetch: u16,
+
+    task_id: TaskId,
+    buffer_size: usize,
+    tags: Vec<Tag>,
+
+    deserializer_builder: Arc<dyn RabbitmqRecordDeserializerBuilder>,
+    schema: FnSchema,
+
+    connection: Option<Connection>,
+    /// the channels by queue, the acks must go through the channel of the delivery
+    channels: HashMap<String, Channel>,
+    ack_recorder: RabbitmqAckRecorder,
+    /// the last acknowledged delivery tag by queue
+    acked_tags: HashMap<String, u64>,
+    acked_counter: Option<Counter>,
+}
+
+impl RabbitmqInputFormat {
+    pub fn new(
+        client_config: RabbitmqClientConfig,
+        queues: Vec<String>,
+        buffer_size: usize,
+        deserializer_builder: Box<dyn RabbitmqRecordDeserializerBuilder>,
+        parallelism: u16,
+        fn_name: String,
+    ) -> Self {
+        let schema = deserializer_builder.schema();
+        RabbitmqInputFormat {
+            name: fn_name,
+            parallelism,
+            client_config,
+            queues,
+            prefetch: SOURCE_PREFETCH,
+            task_id: Default::default(),
+            buffer_size,
+            tags: vec![],
+            deserializer_builder: Arc::from(deserializer_builder),
+            schema,
+            connection: None,
+            channels: HashMap::new(),
+            ack_recorder: RabbitmqAckRecorder::new(),
+            acked_tags: HashMap::new(),
+            acked_counter: None,
+        }
+    }
+
+    pub fn prefetch(mut self, prefetch: u16) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    async fn subscribe(&mut self) -> anyhow::Result<Vec<(String, Consumer)>> {
+        let connection = self.client_config.connect().await?;
+
+        let consumer_tag = format!(
+            "rlink-{}-{}",
+            *self.task_id.job_id(),
+            self.task_id.task_number()
+        );
+        let mut consumers = Vec::with_capacity(self.queues.len());
+        for queue in &self.queues {
+            let channel = connection.create_channel().await?;
+            channel
+                .basic_qos(self.prefetch, BasicQosOptions::default())
+                .await?;
+
+            let consumer = channel
+                .basic_consume(
+                    queue.as_str(),
+                    consumer_tag.as_str(),
+                    BasicConsumeOptions::default(),
+                    FieldTable::default(),
+                )
+                .await
+                .map_err(|e| anyhow!("consume queue({}) error. {}", queue, e))?;
+
+            self.channels.insert(queue.clone(), channel);
+            consumers.push((queue.clone(), consumer));
+        }
+
+        self.connection = Some(connection);
+        Ok(consumers)
+    }
+
+    /// Acknowledge the deliveries of the queue up to the `delivery_tag`
+    async fn ack(&mut self, queue: &str, delivery_tag: u64) -> anyhow::Result<()> {
+        let channel = self
+            .channels
+            .get(queue)
+            .ok_or_else(|| anyhow!("no channel of queue({})", queue))?;
+        channel
+            .basic_ack(delivery_tag, BasicAckOptions { multiple: true })
+            .await?;
+
+        // the delivery tags of a channel start from 1 and increase by 1
+        let last_tag = self.acked_tags.insert(queue.to_string(), delivery_tag);
+        if let Some(counter) = &self.acked_counter {
+            counter.increment(delivery_tag - last_tag.unwrap_or(0));
+        }
+        Ok(())
+    }
+}
+
+impl NamedFunction for RabbitmqInputFormat {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl InputFormat for RabbitmqInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        info!("rabbitmq source open");
+
+        self.task_id = context.task_id.clone();
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        self.tags.push(Tag::new("queue", self.queues.join(",")));
+
+        let mut tags = context.task_id.to_tags();
+        tags.extend(self.tags.clone());
+        self.acked_counter = Some(register_counter(CONSUMER_ACKED, tags));
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) = named_channel(
+            "RabbitmqSource_Handover",
+            self.tags.clone(),
+            self.buffer_size,
+        );
+
+        let consumers = self
+            .subscribe()
+            .await
+            .expect("subscribe rabbitmq queues error");
+        let rabbitmq_consumer = RabbitmqConsumerThread::new(
+            self.task_id.job_id(),
+            self.task_id.task_number(),
+            consumers,
+            sender,
+            self.deserializer_builder.build(),
+        );
+        create_rabbitmq_consumer(rabbitmq_consumer).await;
+
+        Box::pin(RabbitmqRecordStream::new(
+            receiver,
+            self.ack_recorder.clone(),
+        ))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        if let Some(connection) = self.connection.take() {
+            // the unacknowledged messages are requeued by the broker
+            if let Err(e) = connection.close(0, "closed by rlink").await {
+                warn!("close rabbitmq connection error. {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for RabbitmqInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+        // the unacknowledged messages are redelivered by the broker, nothing to restore
+        info!("Checkpoint initialize, context: {:?}", context);
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.ack_recorder.snapshot(context.checkpoint_id.0);
+
+        if let Some(completed_checkpoint_id) = context.completed_checkpoint_id {
+            let acks = self.ack_recorder.complete(completed_checkpoint_id.0);
+            for (queue, delivery_tag) in acks {
+                // the messages are redelivered once the channel is closed
+                if let Err(e) = self.ack(queue.as_str(), delivery_tag).await {
+                    warn!(
+                        "rabbitmq ack error. queue: {}, delivery_tag: {}, error: {}",
+                        queue, delivery_tag, e
+                    );
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl InputSplitSource for RabbitmqInputFormat {
+    /// The queues are consumed by all subtasks as the competing consumers, so every subtask
+    /// gets a placeholder split
+    fn create_input_splits(&self, min_num_splits: u16) -> core::Result<Vec<InputSplit>> {
+        info!(
+            "rabbitmq config {:?}, queues: {:?}",
+            self.client_config, self.queues
+        );
+        if self.queues.is_empty() {
+            return Err(rlink::core::Error::from("no rabbitmq queue to consume"));
+        }
+
+        let input_splits = (0..min_num_splits)
+            .map(|index| InputSplit::new(index, Properties::new()))
+            .collect();
+        Ok(input_splits)
+    }
+}
@@ -0,0 +1,23 @@
+pub mod builder;
+pub mod checkpoint;
+pub mod consumer;
+pub mod deserializer;
+pub mod input_format;
+pub mod stream;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ConsumerRecord {
+    record: rlink::core::element::Record,
+    queue: String,
+    delivery_tag: u64,
+}
+
+impl ConsumerRecord {
+    pub fn new(record: rlink::core::element::Record, queue: String, delivery_tag: u64) -> Self {
+        ConsumerRecord {
+            record,
+            queue,
+            delivery_tag,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::RabbitmqAckRecorder;
+use crate::source::ConsumerRecord;
+
+/// Simulate a RabbitMQ consumption stream as an iterator.
+pub struct RabbitmqRecordStream {
+    receiver: ChannelReceiver<ConsumerRecord>,
+    ack_recorder: RabbitmqAckRecorder,
+}
+
+impl RabbitmqRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<ConsumerRecord>,
+        ack_recorder: RabbitmqAckRecorder,
+    ) -> Self {
+        RabbitmqRecordStream {
+            receiver,
+            ack_recorder,
+        }
+    }
+}
+
+impl ElementStream for RabbitmqRecordStream {}
+
+impl Stream for RabbitmqRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.as_mut().receiver.poll_recv(cx) {
+            Poll::Ready(Some(consumer_record)) => {
+                let ConsumerRecord {
+                    record,
+                    queue,
+                    delivery_tag,
+                } = consumer_record;
+                self.ack_recorder.update(queue.as_str(), delivery_tag);
+
+                Poll::Ready(Some(Element::Record(record)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
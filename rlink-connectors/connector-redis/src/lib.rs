@@ -0,0 +1,42 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod lookup;
+pub mod metrics;
+pub mod sink;
+pub mod value;
+
+pub use lookup::backend::RedisLookupBackend;
+pub use lookup::function::RedisLookupFunction;
+pub use lookup::RedisLookup;
+pub use sink::output_format::RedisOutputFormat;
+
+pub const REDIS: &str = "redis";
+pub const URL: &str = "url";
+
+pub const KEY_FIELD: &str = "key.field";
+pub const KEY_PREFIX: &str = "key.prefix";
+pub const MODE: &str = "mode";
+pub const VALUE_FIELD: &str = "value.field";
+pub const SCORE_FIELD: &str = "score.field";
+pub const MEMBER_FIELD: &str = "member.field";
+pub const STREAM_MAX_LEN: &str = "stream.max.len";
+pub const TTL: &str = "ttl";
+pub const BUFFER_SIZE: &str = "buffer.size";
+pub const BATCH_SIZE: &str = "batch.size";
+pub const BATCH_INTERVAL: &str = "batch.interval";
+pub const MAX_RETRIES: &str = "max.retries";
+
+pub const SINK_CHANNEL_SIZE: usize = 50000;
+pub const SINK_BATCH_SIZE: usize = 1000;
+pub const SINK_BATCH_INTERVAL_MILLIS: u64 = 100;
+pub const SINK_MAX_RETRIES: usize = 3;
+
+pub const LOOKUP_CACHE_SIZE: usize = 10000;
+pub const LOOKUP_MAX_RETRIES: usize = 3;
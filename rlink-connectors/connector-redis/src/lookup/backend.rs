@@ -0,0 +1,78 @@
+use redis::aio::MultiplexedConnection;
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::Record;
+use rlink::core::function::Context;
+use rlink::functions::lookup::LookupBackend;
+
+use crate::value::read_bytes;
+
+/// The backend of the `LookupFunction`, get the string value of the key `key_prefix` + the
+/// key fields joined by `:` into the `output_field`.
+///
+/// The lookups share a multiplexed connection, the cache is provided by the `LookupFunction`
+pub struct RedisLookupBackend {
+    url: String,
+    key_prefix: String,
+    output_field: String,
+
+    key_schema: Schema,
+    conn: Option<MultiplexedConnection>,
+}
+
+impl RedisLookupBackend {
+    pub fn new(url: &str, key_prefix: &str, output_field: &str) -> Self {
+        RedisLookupBackend {
+            url: url.to_string(),
+            key_prefix: key_prefix.to_string(),
+            output_field: output_field.to_string(),
+            key_schema: Schema::empty(),
+            conn: None,
+        }
+    }
+
+    fn redis_key(&self, key: &mut Record) -> std::io::Result<String> {
+        let reader = key.as_reader(self.key_schema.as_type_ids());
+
+        let mut values = Vec::with_capacity(self.key_schema.fields().len());
+        for (i, field) in self.key_schema.fields().iter().enumerate() {
+            let value = read_bytes(&reader, i, field)?;
+            values.push(String::from_utf8_lossy(value.as_slice()).to_string());
+        }
+        Ok(format!("{}{}", self.key_prefix, values.join(":")))
+    }
+}
+
+#[async_trait]
+impl LookupBackend for RedisLookupBackend {
+    async fn open(&mut self, key_schema: &Schema, _context: &Context) -> anyhow::Result<()> {
+        let client = redis::Client::open(self.url.as_str())?;
+        self.conn = Some(client.get_multiplexed_tokio_connection().await?);
+        self.key_schema = key_schema.clone();
+        Ok(())
+    }
+
+    async fn lookup(&self, mut key: Record) -> anyhow::Result<Option<Record>> {
+        let redis_key = self.redis_key(&mut key)?;
+
+        let mut conn = self.conn.clone().unwrap();
+        let value: Option<String> = redis::cmd("GET")
+            .arg(redis_key.as_str())
+            .query_async(&mut conn)
+            .await?;
+
+        let schema = self.schema();
+        Ok(value.map(|value| {
+            let mut record = Record::with_capacity(value.len() + 4);
+            let mut writer = record.as_writer(schema.as_type_ids());
+            writer.set_str(value.as_str()).unwrap();
+            record
+        }))
+    }
+
+    fn schema(&self) -> Schema {
+        Schema::new(vec![Field::new(
+            self.output_field.as_str(),
+            DataType::String,
+        )])
+    }
+}
@@ -0,0 +1,70 @@
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+use lru::LruCache;
+use tokio::time::Instant;
+
+/// The LRU cache of the looked up values, the missing keys are cached as `None` so they are not
+/// fetched again until expired
+pub struct LookupCache {
+    cache: Option<LruCache<String, (Option<String>, Instant)>>,
+    ttl: Option<Duration>,
+}
+
+impl LookupCache {
+    /// The cache is disabled if the `capacity` is 0, the entries never expire without the `ttl`
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        LookupCache {
+            cache: NonZeroUsize::new(capacity).map(LruCache::new),
+            ttl,
+        }
+    }
+
+    /// The cached value, `None` if the key is not cached or expired
+    pub fn get(&mut self, key: &str) -> Option<Option<String>> {
+        let cache = self.cache.as_mut()?;
+        let (value, cached_at) = cache.get(key)?;
+        if let Some(ttl) = self.ttl {
+            if cached_at.elapsed() >= ttl {
+                cache.pop(key);
+                return None;
+            }
+        }
+        Some(value.clone())
+    }
+
+    pub fn put(&mut self, key: String, value: Option<String>) {
+        if let Some(cache) = self.cache.as_mut() {
+            cache.put(key, (value, Instant::now()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::lookup::cache::LookupCache;
+
+    #[test]
+    pub fn lookup_cache_test() {
+        let mut cache = LookupCache::new(2, None);
+        cache.put("a".to_string(), Some("1".to_string()));
+        cache.put("b".to_string(), None);
+        assert_eq!(cache.get("a"), Some(Some("1".to_string())));
+        assert_eq!(cache.get("b"), Some(None));
+
+        // `b` is the least recently used
+        cache.put("c".to_string(), Some("3".to_string()));
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(Some("1".to_string())));
+
+        let mut cache = LookupCache::new(2, Some(Duration::from_millis(0)));
+        cache.put("a".to_string(), Some("1".to_string()));
+        assert_eq!(cache.get("a"), None);
+
+        let mut cache = LookupCache::new(0, None);
+        cache.put("a".to_string(), Some("1".to_string()));
+        assert_eq!(cache.get("a"), None);
+    }
+}
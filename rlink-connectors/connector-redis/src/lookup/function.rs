@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::{Element, FnSchema, Record};
+use rlink::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
+use rlink::utils::stream::MemoryStream;
+
+use crate::lookup::cache::LookupCache;
+use crate::lookup::RedisLookup;
+use crate::value::read_bytes;
+use crate::LOOKUP_CACHE_SIZE;
+
+/// Get the value of the key `key_prefix` + the value of the `key_field` into the `output_field`
+#[derive(Clone, Debug)]
+struct Lookup {
+    key_field: String,
+    key_prefix: String,
+    output_field: String,
+}
+
+/// Enrich the records with the values in redis, the looked up values are appended to the
+/// record as the string fields, empty if the key does not exist.
+///
+/// The keys of all lookups of a record are fetched by a single `MGET`.
+#[derive(NamedFunction)]
+pub struct RedisLookupFunction {
+    url: String,
+    lookups: Vec<Lookup>,
+    cache_size: usize,
+    cache_ttl: Option<Duration>,
+
+    schema: Schema,
+    lookup_schema: Schema,
+    key_fields: Vec<usize>,
+    client: Option<RedisLookup>,
+}
+
+impl RedisLookupFunction {
+    pub fn new(url: &str) -> Self {
+        RedisLookupFunction {
+            url: url.to_string(),
+            lookups: vec![],
+            cache_size: LOOKUP_CACHE_SIZE,
+            cache_ttl: None,
+            schema: Schema::empty(),
+            lookup_schema: Schema::empty(),
+            key_fields: vec![],
+            client: None,
+        }
+    }
+
+    pub fn lookup(mut self, key_field: &str, key_prefix: &str, output_field: &str) -> Self {
+        self.lookups.push(Lookup {
+            key_field: key_field.to_string(),
+            key_prefix: key_prefix.to_string(),
+            output_field: output_field.to_string(),
+        });
+        self
+    }
+
+    /// The capacity of the LRU cache, 0 to disable the cache
+    pub fn cache_size(mut self, cache_size: usize) -> Self {
+        self.cache_size = cache_size;
+        self
+    }
+
+    /// Fetch the cached values again after the `cache_ttl`
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = Some(cache_ttl);
+        self
+    }
+
+    /// The string fields of the looked up values
+    fn lookup_schema(&self) -> Schema {
+        let fields = self
+            .lookups
+            .iter()
+            .map(|x| Field::new(x.output_field.as_str(), DataType::String))
+            .collect();
+        Schema::new(fields)
+    }
+
+    fn keys(&self, record: &mut Record) -> std::io::Result<Vec<String>> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+
+        let mut keys = Vec::with_capacity(self.lookups.len());
+        for (lookup, i) in self.lookups.iter().zip(&self.key_fields) {
+            let value = read_bytes(&reader, *i, self.schema.field(*i))?;
+            keys.push(format!(
+                "{}{}",
+                lookup.key_prefix,
+                String::from_utf8_lossy(value.as_slice())
+            ));
+        }
+        Ok(keys)
+    }
+}
+
+#[async_trait]
+impl FlatMapFunction for RedisLookupFunction {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.clone().into();
+
+        let mut key_fields = Vec::with_capacity(self.lookups.len());
+        for lookup in &self.lookups {
+            let i = self
+                .schema
+                .index_of(lookup.key_field.as_str())
+                .ok_or_else(|| {
+                    core::Error::from(format!(
+                        "key field `{}` not found in the input schema",
+                        lookup.key_field
+                    ))
+                })?;
+            key_fields.push(i);
+        }
+        self.key_fields = key_fields;
+        self.lookup_schema = self.lookup_schema();
+
+        let cache = LookupCache::new(self.cache_size, self.cache_ttl);
+        let client =
+            RedisLookup::connect(self.url.as_str(), cache, context.task_id.to_tags()).await?;
+        self.client = Some(client);
+
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+
+        let keys = self
+            .keys(&mut record)
+            .expect("read record with the input schema error");
+        let values = self
+            .client
+            .as_mut()
+            .unwrap()
+            .get_all(&keys)
+            .await
+            .expect("redis lookup error");
+
+        let mut lookup_record = Record::with_capacity(values.len() * 16);
+        let mut writer = lookup_record.as_writer(self.lookup_schema.as_type_ids());
+        for value in &values {
+            writer
+                .set_str(value.as_deref().unwrap_or_default())
+                .unwrap();
+        }
+        record.extend(lookup_record).unwrap();
+
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        let mut schema: Schema = input_schema.into();
+        schema.merge(&self.lookup_schema());
+        FnSchema::from(&schema)
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for RedisLookupFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use redis::aio::MultiplexedConnection;
+use rlink::metrics::Tag;
+use tokio::time::Instant;
+
+use crate::lookup::cache::LookupCache;
+use crate::metrics::LookupMetrics;
+use crate::LOOKUP_MAX_RETRIES;
+
+pub mod backend;
+pub mod cache;
+pub mod function;
+
+/// Look up the string values of the keys, the values are cached and the keys not cached are
+/// fetched by a single `MGET`
+pub struct RedisLookup {
+    conn: MultiplexedConnection,
+    cache: LookupCache,
+    metrics: LookupMetrics,
+}
+
+impl RedisLookup {
+    pub async fn connect(url: &str, cache: LookupCache, tags: Vec<Tag>) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = client.get_multiplexed_tokio_connection().await?;
+
+        Ok(RedisLookup {
+            conn,
+            cache,
+            metrics: LookupMetrics::new(tags),
+        })
+    }
+
+    pub async fn get(&mut self, key: &str) -> anyhow::Result<Option<String>> {
+        let mut values = self.get_all(&[key.to_string()]).await?;
+        Ok(values.pop().flatten())
+    }
+
+    /// The values in the order of the `keys`, `None` if the key does not exist
+    pub async fn get_all(&mut self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        // the keys not cached, with the positions in the `values`
+        let mut misses: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            match self.cache.get(key.as_str()) {
+                Some(value) => values.push(value),
+                None => {
+                    values.push(None);
+                    misses.entry(key.as_str()).or_default().push(i);
+                }
+            }
+        }
+        let miss_count: usize = misses.values().map(|x| x.len()).sum();
+        self.metrics.lookup(keys.len() - miss_count, miss_count);
+
+        if misses.is_empty() {
+            return Ok(values);
+        }
+
+        let miss_keys: Vec<&str> = misses.keys().cloned().collect();
+        let fetched = self.mget(&miss_keys).await?;
+        for (key, value) in miss_keys.into_iter().zip(fetched) {
+            for i in &misses[key] {
+                values[*i] = value.clone();
+            }
+            self.cache.put(key.to_string(), value);
+        }
+
+        Ok(values)
+    }
+
+    async fn mget(&mut self, keys: &[&str]) -> anyhow::Result<Vec<Option<String>>> {
+        let mut cmd = redis::cmd("MGET");
+        cmd.arg(keys);
+
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            match cmd
+                .query_async::<_, Vec<Option<String>>>(&mut self.conn)
+                .await
+            {
+                Ok(values) => {
+                    self.metrics.fetched(begin.elapsed());
+                    return Ok(values);
+                }
+                Err(e) if attempt < LOOKUP_MAX_RETRIES => {
+                    attempt += 1;
+                    warn!(
+                        "lookup {} keys error, retry({}/{}). {}",
+                        keys.len(),
+                        attempt,
+                        LOOKUP_MAX_RETRIES,
+                        e
+                    );
+                    tokio::time::sleep(Duration::from_millis(100 * (1 << attempt))).await;
+                }
+                Err(e) => {
+                    return Err(anyhow!(
+                        "lookup {} keys error after {} retries. {}",
+                        keys.len(),
+                        LOOKUP_MAX_RETRIES,
+                        e
+                    ))
+                }
+            }
+        }
+    }
+}
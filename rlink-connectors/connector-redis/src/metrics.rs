@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use rlink::metrics::{register_counter, register_histogram, Counter, Histogram, Tag};
+
+pub const LOOKUP_HITS: &str = "Redis.Lookup.Hits";
+pub const LOOKUP_MISSES: &str = "Redis.Lookup.Misses";
+pub const LOOKUP_LATENCY: &str = "Redis.Lookup.Latency";
+
+/// Metrics of the lookup, tagged by the task
+#[derive(Clone)]
+pub(crate) struct LookupMetrics {
+    /// keys found in the cache
+    hits: Counter,
+    /// keys fetched from the server
+    misses: Counter,
+    /// millis of a batched `MGET`
+    latency: Histogram,
+}
+
+impl LookupMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        LookupMetrics {
+            hits: register_counter(LOOKUP_HITS, tags.clone()),
+            misses: register_counter(LOOKUP_MISSES, tags.clone()),
+            latency: register_histogram(LOOKUP_LATENCY, tags),
+        }
+    }
+
+    pub fn lookup(&self, hits: usize, misses: usize) {
+        self.hits.increment(hits as u64);
+        self.misses.increment(misses as u64);
+    }
+
+    pub fn fetched(&self, latency: Duration) {
+        self.latency.record(latency.as_secs_f64() * 1000f64);
+    }
+}
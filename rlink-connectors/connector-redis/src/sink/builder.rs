@@ -0,0 +1,188 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::properties::Properties;
+
+use crate::sink::mode::WriteMode;
+use crate::{
+    RedisOutputFormat, BATCH_INTERVAL, BATCH_SIZE, BUFFER_SIZE, KEY_FIELD, KEY_PREFIX, MAX_RETRIES,
+    MEMBER_FIELD, MODE, REDIS, SCORE_FIELD, SINK_CHANNEL_SIZE, STREAM_MAX_LEN, TTL, URL,
+    VALUE_FIELD,
+};
+
+pub struct RedisOutputFormatBuilder {
+    url: String,
+    key_field: String,
+    write_mode: WriteMode,
+    key_prefix: Option<String>,
+    ttl: Option<Duration>,
+    buffer_size: Option<usize>,
+    batch_size: Option<usize>,
+    batch_interval: Option<Duration>,
+    max_retries: Option<usize>,
+}
+
+impl RedisOutputFormatBuilder {
+    /// The `url` is in the format of `redis://[:password@]host:port/db`
+    pub fn new(url: &str, key_field: &str, write_mode: WriteMode) -> Self {
+        RedisOutputFormatBuilder {
+            url: url.to_string(),
+            key_field: key_field.to_string(),
+            write_mode,
+            key_prefix: None,
+            ttl: None,
+            buffer_size: None,
+            batch_size: None,
+            batch_interval: None,
+            max_retries: None,
+        }
+    }
+
+    /// Prepend the `key_prefix` to the value of the key field
+    pub fn key_prefix(mut self, key_prefix: &str) -> Self {
+        self.key_prefix = Some(key_prefix.to_string());
+        self
+    }
+
+    /// Expire the written keys after the `ttl`
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = Some(size);
+        self
+    }
+
+    /// Flush once `batch_size` commands are buffered
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Flush the buffered commands at least every `batch_interval`
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = Some(batch_interval);
+        self
+    }
+
+    /// Retry a failed pipeline up to `max_retries` times before failing the job
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn build(self) -> RedisOutputFormat {
+        info!("build redis sink with: {:?}", &self);
+
+        let buffer_size = self.buffer_size.unwrap_or(SINK_CHANNEL_SIZE);
+        let mut output_format =
+            RedisOutputFormat::new(self.url, self.key_field, self.write_mode, buffer_size);
+
+        if let Some(key_prefix) = self.key_prefix {
+            output_format = output_format.key_prefix(key_prefix);
+        }
+        if let Some(ttl) = self.ttl {
+            output_format = output_format.ttl(ttl);
+        }
+        if let Some(batch_size) = self.batch_size {
+            output_format = output_format.batch_size(batch_size);
+        }
+        if let Some(batch_interval) = self.batch_interval {
+            output_format = output_format.batch_interval(batch_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            output_format = output_format.max_retries(max_retries);
+        }
+
+        output_format
+    }
+}
+
+impl Debug for RedisOutputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisOutputFormatBuilder")
+            .field("url", &redact_url(self.url.as_str()))
+            .field("key_field", &self.key_field)
+            .field("write_mode", &self.write_mode)
+            .field("key_prefix", &self.key_prefix)
+            .field("ttl", &self.ttl)
+            .field("buffer_size", &self.buffer_size)
+            .field("batch_size", &self.batch_size)
+            .field("batch_interval", &self.batch_interval)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+/// Hide the password in the url
+pub(crate) fn redact_url(url: &str) -> String {
+    match url.split_once('@') {
+        Some((_, host)) => {
+            let scheme = url.split("://").next().unwrap_or_default();
+            format!("{}://***@{}", scheme, host)
+        }
+        None => url.to_string(),
+    }
+}
+
+impl TryFrom<&Properties> for WriteMode {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: &Properties) -> Result<Self, Self::Error> {
+        let mode = properties.get_string(MODE)?;
+        let write_mode = match mode.to_lowercase().as_str() {
+            "set" => WriteMode::Set {
+                value_field: properties.get_string(VALUE_FIELD)?,
+            },
+            "hset" => WriteMode::HSet,
+            "zadd" => WriteMode::ZAdd {
+                score_field: properties.get_string(SCORE_FIELD)?,
+                member_field: properties.get_string(MEMBER_FIELD)?,
+            },
+            "xadd" => WriteMode::XAdd {
+                max_len: properties.get_usize(STREAM_MAX_LEN).ok(),
+            },
+            _ => return Err(anyhow!("unknown redis write mode `{}`", mode)),
+        };
+        Ok(write_mode)
+    }
+}
+
+impl TryFrom<Properties> for RedisOutputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let redis_properties = properties.to_sub_properties(REDIS);
+        let url = redis_properties.get_string(URL)?;
+        let key_field = properties.get_string(KEY_FIELD)?;
+        let write_mode = WriteMode::try_from(&properties)?;
+
+        let mut builder =
+            RedisOutputFormatBuilder::new(url.as_str(), key_field.as_str(), write_mode);
+
+        if let Ok(key_prefix) = properties.get_string(KEY_PREFIX) {
+            builder = builder.key_prefix(key_prefix.as_str());
+        }
+        if let Ok(ttl) = properties.get_duration(TTL) {
+            builder = builder.ttl(ttl);
+        }
+
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+        if let Ok(batch_size) = properties.get_usize(BATCH_SIZE) {
+            builder = builder.batch_size(batch_size);
+        }
+        if let Ok(batch_interval) = properties.get_duration(BATCH_INTERVAL) {
+            builder = builder.batch_interval(batch_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+
+        Ok(builder)
+    }
+}
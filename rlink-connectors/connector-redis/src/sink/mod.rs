@@ -0,0 +1,5 @@
+pub mod builder;
+pub mod mode;
+pub mod output_format;
+
+pub(crate) mod writer;
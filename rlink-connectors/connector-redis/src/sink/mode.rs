@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use redis::Cmd;
+use rlink::core::data_types::Schema;
+use rlink::core::element::Record;
+
+use crate::value::{read_bytes, read_f64};
+
+/// The command writing the record to the key of the `key_field`
+#[derive(Clone, Debug, PartialEq)]
+pub enum WriteMode {
+    /// `SET key value`
+    Set { value_field: String },
+    /// `HSET key field value ...` with the other fields of the record
+    HSet,
+    /// `ZADD key score member`
+    ZAdd {
+        score_field: String,
+        member_field: String,
+    },
+    /// `XADD key [MAXLEN ~ max_len] * field value ...` with the other fields of the record.
+    /// The entries are appended again on replay.
+    XAdd { max_len: Option<usize> },
+}
+
+/// The `WriteMode` with the field indexes of the schema
+#[derive(Clone, Debug)]
+enum ResolvedMode {
+    Set {
+        value: usize,
+    },
+    HSet {
+        fields: Vec<usize>,
+    },
+    ZAdd {
+        score: usize,
+        member: usize,
+    },
+    XAdd {
+        fields: Vec<usize>,
+        max_len: Option<usize>,
+    },
+}
+
+/// Build the commands of the records
+#[derive(Clone, Debug)]
+pub(crate) struct CommandBuilder {
+    schema: Schema,
+    key_field: usize,
+    key_prefix: String,
+    mode: ResolvedMode,
+    ttl: Option<Duration>,
+}
+
+impl CommandBuilder {
+    pub fn new(
+        schema: Schema,
+        key_field: &str,
+        key_prefix: &str,
+        mode: &WriteMode,
+        ttl: Option<Duration>,
+    ) -> anyhow::Result<Self> {
+        let index_of = |name: &str| {
+            schema
+                .index_of(name)
+                .ok_or_else(|| anyhow!("field `{}` not found in the input schema", name))
+        };
+
+        let key = index_of(key_field)?;
+        let other_fields: Vec<usize> = (0..schema.fields().len()).filter(|x| *x != key).collect();
+        let mode = match mode {
+            WriteMode::Set { value_field } => ResolvedMode::Set {
+                value: index_of(value_field)?,
+            },
+            WriteMode::HSet => ResolvedMode::HSet {
+                fields: other_fields,
+            },
+            WriteMode::ZAdd {
+                score_field,
+                member_field,
+            } => {
+                let score = index_of(score_field)?;
+                if !schema.field(score).is_numeric() {
+                    return Err(anyhow!("score field `{}` is not numeric", score_field));
+                }
+                ResolvedMode::ZAdd {
+                    score,
+                    member: index_of(member_field)?,
+                }
+            }
+            WriteMode::XAdd { max_len } => ResolvedMode::XAdd {
+                fields: other_fields,
+                max_len: *max_len,
+            },
+        };
+
+        Ok(CommandBuilder {
+            schema,
+            key_field: key,
+            key_prefix: key_prefix.to_string(),
+            mode,
+            ttl,
+        })
+    }
+
+    /// The commands of the record, the key expires after the `ttl` if given
+    pub fn build(&self, record: &mut Record) -> std::io::Result<Vec<Cmd>> {
+        let reader = record.as_reader(self.schema.as_type_ids());
+        let fields = self.schema.fields();
+
+        let mut key = self.key_prefix.as_bytes().to_vec();
+        key.extend(read_bytes(
+            &reader,
+            self.key_field,
+            &fields[self.key_field],
+        )?);
+
+        let mut commands = Vec::with_capacity(2);
+        match &self.mode {
+            ResolvedMode::Set { value } => {
+                let mut cmd = redis::cmd("SET");
+                cmd.arg(key.as_slice())
+                    .arg(read_bytes(&reader, *value, &fields[*value])?);
+                if let Some(ttl) = self.ttl {
+                    cmd.arg("PX").arg(ttl.as_millis() as u64);
+                }
+                // the ttl is set by the command
+                return Ok(vec![cmd]);
+            }
+            ResolvedMode::HSet { fields: indexes } => {
+                let mut cmd = redis::cmd("HSET");
+                cmd.arg(key.as_slice());
+                for i in indexes {
+                    cmd.arg(fields[*i].name())
+                        .arg(read_bytes(&reader, *i, &fields[*i])?);
+                }
+                commands.push(cmd);
+            }
+            ResolvedMode::ZAdd { score, member } => {
+                let mut cmd = redis::cmd("ZADD");
+                cmd.arg(key.as_slice())
+                    .arg(read_f64(&reader, *score, &fields[*score])?)
+                    .arg(read_bytes(&reader, *member, &fields[*member])?);
+                commands.push(cmd);
+            }
+            ResolvedMode::XAdd {
+                fields: indexes,
+                max_len,
+            } => {
+                let mut cmd = redis::cmd("XADD");
+                cmd.arg(key.as_slice());
+                if let Some(max_len) = max_len {
+                    cmd.arg("MAXLEN").arg("~").arg(*max_len);
+                }
+                cmd.arg("*");
+                for i in indexes {
+                    cmd.arg(fields[*i].name())
+                        .arg(read_bytes(&reader, *i, &fields[*i])?);
+                }
+                commands.push(cmd);
+            }
+        }
+
+        if let Some(ttl) = self.ttl {
+            let mut cmd = redis::cmd("PEXPIRE");
+            cmd.arg(key.as_slice()).arg(ttl.as_millis() as u64);
+            commands.push(cmd);
+        }
+
+        Ok(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use redis::Cmd;
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::element::Record;
+
+    use crate::sink::mode::{CommandBuilder, WriteMode};
+
+    fn packed(commands: Vec<Cmd>) -> Vec<Vec<u8>> {
+        commands.iter().map(|x| x.get_packed_command()).collect()
+    }
+
+    #[test]
+    pub fn command_builder_test() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+            Field::new("score", DataType::Float64),
+        ]);
+
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_i64(7).unwrap();
+        writer.set_str("rlink").unwrap();
+        writer.set_f64(1.5).unwrap();
+
+        let set = WriteMode::Set {
+            value_field: "name".to_string(),
+        };
+        let builder = CommandBuilder::new(
+            schema.clone(),
+            "id",
+            "user:",
+            &set,
+            Some(Duration::from_secs(60)),
+        )
+        .unwrap();
+        assert_eq!(
+            packed(builder.build(&mut record).unwrap()),
+            vec![redis::cmd("SET")
+                .arg("user:7")
+                .arg("rlink")
+                .arg("PX")
+                .arg(60000)
+                .get_packed_command()]
+        );
+
+        let builder = CommandBuilder::new(
+            schema.clone(),
+            "id",
+            "user:",
+            &WriteMode::HSet,
+            Some(Duration::from_secs(60)),
+        )
+        .unwrap();
+        assert_eq!(
+            packed(builder.build(&mut record).unwrap()),
+            vec![
+                redis::cmd("HSET")
+                    .arg("user:7")
+                    .arg("name")
+                    .arg("rlink")
+                    .arg("score")
+                    .arg("1.5")
+                    .get_packed_command(),
+                redis::cmd("PEXPIRE")
+                    .arg("user:7")
+                    .arg(60000)
+                    .get_packed_command()
+            ]
+        );
+
+        let zadd = WriteMode::ZAdd {
+            score_field: "score".to_string(),
+            member_field: "name".to_string(),
+        };
+        let builder = CommandBuilder::new(schema.clone(), "id", "rank:", &zadd, None).unwrap();
+        assert_eq!(
+            packed(builder.build(&mut record).unwrap()),
+            vec![redis::cmd("ZADD")
+                .arg("rank:7")
+                .arg(1.5f64)
+                .arg("rlink")
+                .get_packed_command()]
+        );
+
+        let xadd = WriteMode::XAdd { max_len: Some(100) };
+        let builder = CommandBuilder::new(schema.clone(), "id", "", &xadd, None).unwrap();
+        assert_eq!(
+            packed(builder.build(&mut record).unwrap()),
+            vec![redis::cmd("XADD")
+                .arg("7")
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(100)
+                .arg("*")
+                .arg("name")
+                .arg("rlink")
+                .arg("score")
+                .arg("1.5")
+                .get_packed_command()]
+        );
+
+        // the score must be numeric
+        let zadd = WriteMode::ZAdd {
+            score_field: "name".to_string(),
+            member_field: "name".to_string(),
+        };
+        assert!(CommandBuilder::new(schema.clone(), "id", "", &zadd, None).is_err());
+        assert!(CommandBuilder::new(schema, "uid", "", &WriteMode::HSet, None).is_err());
+    }
+}
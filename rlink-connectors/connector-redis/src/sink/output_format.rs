@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::element::Element;
+use rlink::core::function::{Context, NamedFunction, OutputFormat};
+use rlink_connector_common::writer::{BatchWriterHandle, BatchWriterThread};
+
+use crate::sink::mode::{CommandBuilder, WriteMode};
+use crate::sink::writer::RedisWriter;
+use crate::{SINK_BATCH_INTERVAL_MILLIS, SINK_BATCH_SIZE, SINK_MAX_RETRIES};
+
+/// Write the records to the keys of the `key_field` with the command of the `write_mode`
+#[derive(NamedFunction)]
+pub struct RedisOutputFormat {
+    url: String,
+    key_field: String,
+    key_prefix: String,
+    write_mode: WriteMode,
+    ttl: Option<Duration>,
+
+    buffer_size: usize,
+    batch_size: usize,
+    batch_interval: Duration,
+    max_retries: usize,
+
+    writer: Option<BatchWriterHandle>,
+}
+
+impl RedisOutputFormat {
+    pub fn new(url: String, key_field: String, write_mode: WriteMode, buffer_size: usize) -> Self {
+        RedisOutputFormat {
+            url,
+            key_field,
+            key_prefix: "".to_string(),
+            write_mode,
+            ttl: None,
+            buffer_size,
+            batch_size: SINK_BATCH_SIZE,
+            batch_interval: Duration::from_millis(SINK_BATCH_INTERVAL_MILLIS),
+            max_retries: SINK_MAX_RETRIES,
+            writer: None,
+        }
+    }
+
+    pub fn key_prefix(mut self, key_prefix: String) -> Self {
+        self.key_prefix = key_prefix;
+        self
+    }
+
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[async_trait]
+impl OutputFormat for RedisOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let command_builder = CommandBuilder::new(
+            context.input_schema.clone().into(),
+            self.key_field.as_str(),
+            self.key_prefix.as_str(),
+            &self.write_mode,
+            self.ttl,
+        )?;
+
+        let writer = RedisWriter::new(self.url.clone(), command_builder, self.max_retries);
+        let writer = BatchWriterThread::new("Redis", writer)
+            .batch_size(self.batch_size)
+            .batch_interval(self.batch_interval)
+            .spawn(self.name(), context.task_id.to_tags(), self.buffer_size);
+        self.writer = Some(writer);
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if let Err(e) = self.try_write_element(element).await {
+            error!("write redis record error. {}", e);
+        }
+    }
+
+    async fn try_write_element(&mut self, element: Element) -> core::Result<()> {
+        let writer = self.writer.as_ref().unwrap();
+        writer.write(element.into_record()).await?;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for RedisOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The buffered commands are sent in a pipeline on the checkpoint, the `XADD` entries of
+    /// the records replayed after a restart are appended again
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        self.writer.as_ref().unwrap().checkpoint(context).await;
+        None
+    }
+}
@@ -0,0 +1,95 @@
+use redis::aio::MultiplexedConnection;
+use redis::Cmd;
+use rlink::core::element::Record;
+use rlink_connector_common::metrics::SinkMetrics;
+use rlink_connector_common::writer::{backoff, BatchWriter};
+use tokio::time::Instant;
+
+use crate::sink::mode::CommandBuilder;
+
+pub(crate) struct RedisWriter {
+    url: String,
+    command_builder: CommandBuilder,
+    max_retries: usize,
+
+    conn: Option<MultiplexedConnection>,
+    commands: Vec<Cmd>,
+}
+
+impl RedisWriter {
+    pub fn new(url: String, command_builder: CommandBuilder, max_retries: usize) -> Self {
+        RedisWriter {
+            url,
+            command_builder,
+            max_retries,
+            conn: None,
+            commands: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchWriter for RedisWriter {
+    async fn open(&mut self) -> anyhow::Result<()> {
+        let client = redis::Client::open(self.url.as_str())?;
+        let conn = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .map_err(|e| anyhow!("connect to redis error. {}", e))?;
+        self.conn = Some(conn);
+        Ok(())
+    }
+
+    fn push(&mut self, mut record: Record) -> anyhow::Result<()> {
+        let commands = self.command_builder.build(&mut record)?;
+        self.commands.extend(commands);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Send the buffered commands in a pipeline, the whole pipeline is retried on error so the
+    /// entries of `XADD` may be appended twice
+    async fn flush(&mut self, metrics: &SinkMetrics) -> anyhow::Result<()> {
+        let conn = self.conn.as_mut().unwrap();
+        let mut pipeline = redis::pipe();
+        for cmd in self.commands.iter() {
+            pipeline.add_command(cmd.clone()).ignore();
+        }
+
+        let mut attempt = 0;
+        loop {
+            let begin = Instant::now();
+            match pipeline.query_async::<_, ()>(conn).await {
+                Ok(()) => {
+                    metrics.flushed(self.commands.len() as u64, begin.elapsed());
+                    self.commands.clear();
+                    return Ok(());
+                }
+                Err(e) if attempt < self.max_retries => {
+                    attempt += 1;
+                    metrics.retry();
+                    warn!(
+                        "flush {} commands error, retry({}/{}). {}",
+                        self.commands.len(),
+                        attempt,
+                        self.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(backoff(attempt)).await;
+                }
+                Err(e) => {
+                    metrics.failure();
+                    return Err(anyhow!(
+                        "flush {} commands error after {} retries. {}",
+                        self.commands.len(),
+                        self.max_retries,
+                        e
+                    ));
+                }
+            }
+        }
+    }
+}
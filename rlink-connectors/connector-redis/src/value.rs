@@ -0,0 +1,45 @@
+use rlink::core::data_types::{DataType, Field};
+use rlink::core::element::BufferReader;
+
+/// Read the field as the redis argument, the numbers are in the decimal format
+pub fn read_bytes(reader: &BufferReader, index: usize, field: &Field) -> std::io::Result<Vec<u8>> {
+    let value = match field.data_type() {
+        DataType::Boolean => reader.get_bool(index)?.to_string().into_bytes(),
+        DataType::Int8 => reader.get_i8(index)?.to_string().into_bytes(),
+        DataType::UInt8 => reader.get_u8(index)?.to_string().into_bytes(),
+        DataType::Int16 => reader.get_i16(index)?.to_string().into_bytes(),
+        DataType::UInt16 => reader.get_u16(index)?.to_string().into_bytes(),
+        DataType::Int32 => reader.get_i32(index)?.to_string().into_bytes(),
+        DataType::UInt32 => reader.get_u32(index)?.to_string().into_bytes(),
+        DataType::Int64 => reader.get_i64(index)?.to_string().into_bytes(),
+        DataType::UInt64 => reader.get_u64(index)?.to_string().into_bytes(),
+        DataType::Float32 => reader.get_f32(index)?.to_string().into_bytes(),
+        DataType::Float64 => reader.get_f64(index)?.to_string().into_bytes(),
+        DataType::String => reader.get_str(index)?.as_bytes().to_vec(),
+        DataType::Binary => reader.get_binary(index)?.to_vec(),
+    };
+    Ok(value)
+}
+
+/// Read the numeric field as `f64`, e.g. the score of the sorted set
+pub fn read_f64(reader: &BufferReader, index: usize, field: &Field) -> std::io::Result<f64> {
+    let value = match field.data_type() {
+        DataType::Int8 => reader.get_i8(index)? as f64,
+        DataType::UInt8 => reader.get_u8(index)? as f64,
+        DataType::Int16 => reader.get_i16(index)? as f64,
+        DataType::UInt16 => reader.get_u16(index)? as f64,
+        DataType::Int32 => reader.get_i32(index)? as f64,
+        DataType::UInt32 => reader.get_u32(index)? as f64,
+        DataType::Int64 => reader.get_i64(index)? as f64,
+        DataType::UInt64 => reader.get_u64(index)? as f64,
+        DataType::Float32 => reader.get_f32(index)? as f64,
+        DataType::Float64 => reader.get_f64(index)?,
+        data_type => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{:?} field `{}` is not numeric", data_type, field.name()),
+            ))
+        }
+    };
+    Ok(value)
+}
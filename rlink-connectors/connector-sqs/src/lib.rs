@@ -0,0 +1,96 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod source;
+
+pub use source::builder::SqsInputFormatBuilder;
+pub use source::input_format::SqsInputFormat;
+
+use std::convert::TryFrom;
+
+use aws_sdk_sqs::{Client, Endpoint, Region};
+use rlink::core::properties::Properties;
+
+pub const SQS: &str = "sqs";
+pub const REGION: &str = "region";
+pub const ENDPOINT: &str = "endpoint";
+
+/// the queue url, or the queue name of the account
+pub const QUEUE: &str = "queue";
+/// the seconds of the long polling, up to 20
+pub const WAIT_TIME: &str = "wait.time";
+/// the seconds of the visibility timeout of the received messages, extended until deleted
+pub const VISIBILITY_TIMEOUT: &str = "visibility.timeout";
+/// the max messages of a receive, up to 10
+pub const MAX_MESSAGES: &str = "max.messages";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 1000;
+pub const SOURCE_WAIT_TIME_SECONDS: i32 = 20;
+pub const SOURCE_VISIBILITY_TIMEOUT_SECONDS: i32 = 60;
+pub const SOURCE_MAX_MESSAGES: i32 = 10;
+
+/// Connection settings of the SQS client, the credentials are resolved by the default
+/// provider chain of the AWS SDK, eg: the environment variables or the instance profile
+#[derive(Clone, Debug, Default)]
+pub struct SqsClientConfig {
+    region: Option<String>,
+    endpoint: Option<String>,
+}
+
+impl SqsClientConfig {
+    pub fn new() -> Self {
+        SqsClientConfig::default()
+    }
+
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+
+    /// Override the service endpoint, eg: a local ElasticMQ or LocalStack
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    pub(crate) async fn connect(&self) -> anyhow::Result<Client> {
+        let mut loader = aws_config::from_env();
+        if let Some(region) = &self.region {
+            loader = loader.region(Region::new(region.clone()));
+        }
+        let shared_config = loader.load().await;
+
+        let mut builder = aws_sdk_sqs::config::Builder::from(&shared_config);
+        if let Some(endpoint) = &self.endpoint {
+            let uri = endpoint
+                .parse()
+                .map_err(|e| anyhow!("illegal sqs endpoint `{}`. {}", endpoint, e))?;
+            builder = builder.endpoint_resolver(Endpoint::immutable(uri));
+        }
+
+        Ok(Client::from_conf(builder.build()))
+    }
+}
+
+impl TryFrom<Properties> for SqsClientConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let mut client_config = SqsClientConfig::new();
+        if let Ok(region) = properties.get_string(REGION) {
+            client_config = client_config.region(region.as_str());
+        }
+        if let Ok(endpoint) = properties.get_string(ENDPOINT) {
+            client_config = client_config.endpoint(endpoint.as_str());
+        }
+        Ok(client_config)
+    }
+}
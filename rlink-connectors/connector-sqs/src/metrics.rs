@@ -0,0 +1,46 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SOURCE_MESSAGES: &str = "Sqs.Source.Messages";
+pub const SOURCE_ERRORS: &str = "Sqs.Source.Errors";
+pub const SOURCE_DELETED: &str = "Sqs.Source.Deleted";
+pub const SOURCE_EXTENDED: &str = "Sqs.Source.Extended";
+
+/// Metrics of the source, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// messages received
+    messages: Counter,
+    /// messages failed to decode, and the failed requests
+    errors: Counter,
+    /// messages deleted after the checkpoints
+    deleted: Counter,
+    /// visibility timeouts extended
+    extended: Counter,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            messages: register_counter(SOURCE_MESSAGES, tags.clone()),
+            errors: register_counter(SOURCE_ERRORS, tags.clone()),
+            deleted: register_counter(SOURCE_DELETED, tags.clone()),
+            extended: register_counter(SOURCE_EXTENDED, tags),
+        }
+    }
+
+    pub fn received(&self, messages: usize) {
+        self.messages.increment(messages as u64);
+    }
+
+    pub fn error(&self) {
+        self.errors.increment(1);
+    }
+
+    pub fn deleted(&self, messages: usize) {
+        self.deleted.increment(messages as u64);
+    }
+
+    pub fn extended(&self, messages: usize) {
+        self.extended.increment(messages as u64);
+    }
+}
@@ -0,0 +1,114 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use rlink::core::data_types::Schema;
+use rlink::core::properties::Properties;
+
+use crate::source::decoder::{message_decoder, message_schema, MessageDecoder};
+use crate::source::input_format::SqsInputFormat;
+use crate::{
+    SqsClientConfig, BUFFER_SIZE, MAX_MESSAGES, QUEUE, SQS, VISIBILITY_TIMEOUT, WAIT_TIME,
+};
+
+#[derive(Debug)]
+pub struct SqsInputFormatBuilder {
+    client_config: SqsClientConfig,
+    queue: String,
+    max_messages: Option<i32>,
+    wait_time_seconds: Option<i32>,
+    visibility_timeout_seconds: Option<i32>,
+    buffer_size: Option<usize>,
+}
+
+impl SqsInputFormatBuilder {
+    /// The `queue` is the url, or the name of the queue of the account
+    pub fn new(client_config: SqsClientConfig, queue: &str) -> Self {
+        SqsInputFormatBuilder {
+            client_config,
+            queue: queue.to_string(),
+            max_messages: None,
+            wait_time_seconds: None,
+            visibility_timeout_seconds: None,
+            buffer_size: None,
+        }
+    }
+
+    pub fn max_messages(mut self, max_messages: i32) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+
+    pub fn wait_time_seconds(mut self, wait_time_seconds: i32) -> Self {
+        self.wait_time_seconds = Some(wait_time_seconds);
+        self
+    }
+
+    pub fn visibility_timeout_seconds(mut self, visibility_timeout_seconds: i32) -> Self {
+        self.visibility_timeout_seconds = Some(visibility_timeout_seconds);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The messages are decoded to the records of the `schema` by the `decoder`
+    pub fn build(
+        self,
+        schema: Schema,
+        decoder: MessageDecoder,
+        parallelism: u16,
+    ) -> SqsInputFormat {
+        info!("build sqs source with: {:?}", &self);
+
+        let mut input_format =
+            SqsInputFormat::new(self.client_config, self.queue, decoder, schema, parallelism);
+        if let Some(max_messages) = self.max_messages {
+            input_format = input_format.max_messages(max_messages);
+        }
+        if let Some(wait_time_seconds) = self.wait_time_seconds {
+            input_format = input_format.wait_time_seconds(wait_time_seconds);
+        }
+        if let Some(visibility_timeout_seconds) = self.visibility_timeout_seconds {
+            input_format = input_format.visibility_timeout_seconds(visibility_timeout_seconds);
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            input_format = input_format.buffer_size(buffer_size);
+        }
+
+        input_format
+    }
+
+    /// The messages are decoded to the records of the `message_schema`
+    pub fn build_raw(self, parallelism: u16) -> SqsInputFormat {
+        self.build(message_schema(), Arc::new(message_decoder), parallelism)
+    }
+}
+
+impl TryFrom<Properties> for SqsInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let sqs_properties = properties.to_sub_properties(SQS);
+        let client_config = SqsClientConfig::try_from(sqs_properties.clone())?;
+        let queue = sqs_properties.get_string(QUEUE)?;
+
+        let mut builder = SqsInputFormatBuilder::new(client_config, queue.as_str());
+
+        if let Ok(max_messages) = properties.get_usize(MAX_MESSAGES) {
+            builder = builder.max_messages(max_messages as i32);
+        }
+        if let Ok(wait_time) = properties.get_usize(WAIT_TIME) {
+            builder = builder.wait_time_seconds(wait_time as i32);
+        }
+        if let Ok(visibility_timeout) = properties.get_usize(VISIBILITY_TIMEOUT) {
+            builder = builder.visibility_timeout_seconds(visibility_timeout as i32);
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct Receipts {
+    /// the messages received and not deleted, the visibility timeouts are extended
+    in_flight: HashSet<String>,
+    /// the messages emitted since the last checkpoint
+    emitted: Vec<String>,
+    /// the messages emitted before the checkpoints, by the checkpoint id
+    checkpointed: BTreeMap<u64, Vec<String>>,
+}
+
+/// Record the receipt handles of the messages, the messages are deleted once the checkpoint
+/// after them is completed
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ReceiptRecorder {
+    receipts: Arc<Mutex<Receipts>>,
+}
+
+impl ReceiptRecorder {
+    pub fn new() -> Self {
+        ReceiptRecorder::default()
+    }
+
+    pub fn receive(&self, receipt_handle: String) {
+        self.receipts
+            .lock()
+            .unwrap()
+            .in_flight
+            .insert(receipt_handle);
+    }
+
+    pub fn emit(&self, receipt_handle: String) {
+        self.receipts.lock().unwrap().emitted.push(receipt_handle);
+    }
+
+    pub fn in_flight(&self) -> Vec<String> {
+        self.receipts
+            .lock()
+            .unwrap()
+            .in_flight
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Assign the emitted messages to the checkpoint, and take the messages of the earlier
+    /// checkpoints, which must be completed when the barrier of the next arrives
+    pub fn checkpoint(&self, checkpoint_id: u64) -> Vec<String> {
+        let mut receipts = self.receipts.lock().unwrap();
+        let emitted = std::mem::take(&mut receipts.emitted);
+        let later = receipts.checkpointed.split_off(&checkpoint_id);
+        let completed = std::mem::replace(&mut receipts.checkpointed, later);
+        receipts.checkpointed.insert(checkpoint_id, emitted);
+
+        completed.into_values().flatten().collect()
+    }
+
+    /// Take the messages of all checkpoints
+    pub fn take_checkpointed(&self) -> Vec<String> {
+        let mut receipts = self.receipts.lock().unwrap();
+        std::mem::take(&mut receipts.checkpointed)
+            .into_values()
+            .flatten()
+            .collect()
+    }
+
+    /// The messages are deleted, or given up and visible again after the timeout
+    pub fn remove(&self, receipt_handles: &[String]) {
+        let mut receipts = self.receipts.lock().unwrap();
+        for receipt_handle in receipt_handles {
+            receipts.in_flight.remove(receipt_handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source::checkpoint::ReceiptRecorder;
+
+    #[test]
+    pub fn receipt_recorder_test() {
+        let recorder = ReceiptRecorder::new();
+        for receipt_handle in ["a", "b", "c"] {
+            recorder.receive(receipt_handle.to_string());
+        }
+        recorder.emit("a".to_string());
+        recorder.emit("b".to_string());
+        assert!(recorder.checkpoint(1).is_empty());
+
+        recorder.emit("c".to_string());
+        let completed = recorder.checkpoint(2);
+        assert_eq!(completed, vec!["a".to_string(), "b".to_string()]);
+
+        recorder.remove(completed.as_slice());
+        assert_eq!(recorder.in_flight(), vec!["c".to_string()]);
+        assert_eq!(recorder.take_checkpointed(), vec!["c".to_string()]);
+    }
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use aws_sdk_sqs::model::{Message, MessageSystemAttributeName};
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::element::Record;
+
+/// Decode a message into a record, the message is discarded if failed
+pub type MessageDecoder = Arc<dyn Fn(&Message) -> anyhow::Result<Record> + Send + Sync>;
+
+/// The schema of the `message_decoder`
+pub fn message_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("message_id", DataType::String),
+        Field::new("group_id", DataType::String),
+        Field::new("sent_timestamp", DataType::Int64),
+        Field::new("body", DataType::String),
+    ])
+}
+
+/// Decode the message into a record of the `message_schema`, the `group_id` is empty for the
+/// standard queues, and the `sent_timestamp` is in millis
+pub fn message_decoder(message: &Message) -> anyhow::Result<Record> {
+    let attribute = |name: MessageSystemAttributeName| {
+        message
+            .attributes()
+            .and_then(|attributes| attributes.get(&name))
+            .map(|x| x.as_str())
+            .unwrap_or_default()
+    };
+    let sent_timestamp = attribute(MessageSystemAttributeName::SentTimestamp)
+        .parse::<i64>()
+        .unwrap_or_default();
+
+    let schema = message_schema();
+    let mut record = Record::new();
+    let mut writer = record.as_writer(schema.as_type_ids());
+    writer.set_str(message.message_id().unwrap_or_default())?;
+    writer.set_str(attribute(MessageSystemAttributeName::MessageGroupId))?;
+    writer.set_i64(sent_timestamp)?;
+    writer.set_str(message.body().unwrap_or_default())?;
+    Ok(record)
+}
@@ -0,0 +1,190 @@
+use rlink::channel::named_channel;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::FnSchema;
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::metrics::Tag;
+
+use crate::metrics::SourceMetrics;
+use crate::source::checkpoint::ReceiptRecorder;
+use crate::source::decoder::MessageDecoder;
+use crate::source::queue::SqsQueue;
+use crate::source::reader::QueueReader;
+use crate::source::stream::SqsRecordStream;
+use crate::{
+    SqsClientConfig, SOURCE_CHANNEL_SIZE, SOURCE_MAX_MESSAGES, SOURCE_VISIBILITY_TIMEOUT_SECONDS,
+    SOURCE_WAIT_TIME_SECONDS,
+};
+
+/// Long poll the messages of a queue, the tasks share the queue.
+///
+/// The messages are deleted only after the checkpoint following them is completed, i.e.
+/// when the barrier of the next checkpoint arrives, and the visibility timeouts are extended
+/// until then, so the messages not deleted before a restart are received again.
+///
+/// The messages of a group of the FIFO queue are received by a task at a time and emitted in
+/// order, the next messages of the group are received after the deletion
+#[derive(NamedFunction)]
+pub struct SqsInputFormat {
+    client_config: SqsClientConfig,
+    queue: String,
+    decoder: MessageDecoder,
+    schema: Schema,
+    parallelism: u16,
+    max_messages: i32,
+    wait_time_seconds: i32,
+    visibility_timeout_seconds: i32,
+    buffer_size: usize,
+
+    tags: Vec<Tag>,
+    sqs_queue: Option<SqsQueue>,
+    receipt_recorder: ReceiptRecorder,
+    metrics: Option<SourceMetrics>,
+}
+
+impl SqsInputFormat {
+    pub fn new(
+        client_config: SqsClientConfig,
+        queue: String,
+        decoder: MessageDecoder,
+        schema: Schema,
+        parallelism: u16,
+    ) -> Self {
+        SqsInputFormat {
+            client_config,
+            queue,
+            decoder,
+            schema,
+            parallelism,
+            max_messages: SOURCE_MAX_MESSAGES,
+            wait_time_seconds: SOURCE_WAIT_TIME_SECONDS,
+            visibility_timeout_seconds: SOURCE_VISIBILITY_TIMEOUT_SECONDS,
+            buffer_size: SOURCE_CHANNEL_SIZE,
+            tags: vec![],
+            sqs_queue: None,
+            receipt_recorder: ReceiptRecorder::new(),
+            metrics: None,
+        }
+    }
+
+    pub fn max_messages(mut self, max_messages: i32) -> Self {
+        self.max_messages = max_messages.clamp(1, 10);
+        self
+    }
+
+    pub fn wait_time_seconds(mut self, wait_time_seconds: i32) -> Self {
+        self.wait_time_seconds = wait_time_seconds.clamp(0, 20);
+        self
+    }
+
+    pub fn visibility_timeout_seconds(mut self, visibility_timeout_seconds: i32) -> Self {
+        self.visibility_timeout_seconds = visibility_timeout_seconds.max(2);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    async fn delete(&self, receipt_handles: Vec<String>) -> anyhow::Result<()> {
+        if receipt_handles.is_empty() {
+            return Ok(());
+        }
+
+        let deleted = self
+            .sqs_queue
+            .as_ref()
+            .unwrap()
+            .delete(receipt_handles.as_slice())
+            .await;
+        // the messages failed to delete are received again after the visibility timeout
+        self.receipt_recorder.remove(receipt_handles.as_slice());
+        self.metrics.as_ref().unwrap().deleted(deleted?);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl InputFormat for SqsInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        let client = self.client_config.connect().await?;
+        let sqs_queue = SqsQueue::resolve(client, self.queue.as_str()).await?;
+        info!(
+            "open sqs queue {}, fifo: {}",
+            sqs_queue.queue_url(),
+            sqs_queue.is_fifo()
+        );
+
+        self.sqs_queue = Some(sqs_queue);
+        self.tags = context.task_id.to_tags();
+        self.metrics = Some(SourceMetrics::new(self.tags.clone()));
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("SqsSource_Handover", self.tags.clone(), self.buffer_size);
+
+        QueueReader {
+            queue: self.sqs_queue.clone().unwrap(),
+            max_messages: self.max_messages,
+            wait_time_seconds: self.wait_time_seconds,
+            visibility_timeout_seconds: self.visibility_timeout_seconds,
+            decoder: self.decoder.clone(),
+            receipt_recorder: self.receipt_recorder.clone(),
+            sender,
+            metrics: self.metrics.clone().unwrap(),
+        }
+        .spawn();
+
+        Box::pin(SqsRecordStream::new(
+            receiver,
+            self.receipt_recorder.clone(),
+        ))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        let receipt_handles = self.receipt_recorder.take_checkpointed();
+        self.delete(receipt_handles).await?;
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for SqsInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The messages are tracked by the queue, the undeleted are received again after restore
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let receipt_handles = self.receipt_recorder.checkpoint(context.checkpoint_id.0);
+        if let Err(e) = self.delete(receipt_handles).await {
+            warn!(
+                "delete messages on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            );
+        }
+        None
+    }
+}
+
+impl InputSplitSource for SqsInputFormat {}
@@ -0,0 +1,123 @@
+use aws_sdk_sqs::model::{
+    ChangeMessageVisibilityBatchRequestEntry, DeleteMessageBatchRequestEntry, Message,
+    QueueAttributeName,
+};
+use aws_sdk_sqs::Client;
+
+/// The max entries of a batch request
+const MAX_BATCH_ENTRIES: usize = 10;
+
+#[derive(Clone, Debug)]
+pub(crate) struct SqsQueue {
+    client: Client,
+    queue_url: String,
+}
+
+impl SqsQueue {
+    /// The `queue` is the url, or the name of the queue of the account
+    pub async fn resolve(client: Client, queue: &str) -> anyhow::Result<Self> {
+        let queue_url = if queue.starts_with("http://") || queue.starts_with("https://") {
+            queue.to_string()
+        } else {
+            client
+                .get_queue_url()
+                .queue_name(queue)
+                .send()
+                .await?
+                .queue_url()
+                .ok_or_else(|| anyhow!("the url of queue {} not found", queue))?
+                .to_string()
+        };
+        Ok(SqsQueue { client, queue_url })
+    }
+
+    pub fn queue_url(&self) -> &str {
+        self.queue_url.as_str()
+    }
+
+    /// The messages of the FIFO queues are grouped, the messages of a group are received in
+    /// order
+    pub fn is_fifo(&self) -> bool {
+        self.queue_url.ends_with(".fifo")
+    }
+
+    pub async fn receive(
+        &self,
+        max_messages: i32,
+        wait_time_seconds: i32,
+        visibility_timeout_seconds: i32,
+    ) -> anyhow::Result<Vec<Message>> {
+        let output = self
+            .client
+            .receive_message()
+            .queue_url(self.queue_url.as_str())
+            .max_number_of_messages(max_messages)
+            .wait_time_seconds(wait_time_seconds)
+            .visibility_timeout(visibility_timeout_seconds)
+            .attribute_names(QueueAttributeName::All)
+            .message_attribute_names("All")
+            .send()
+            .await?;
+        Ok(output.messages().map(|x| x.to_vec()).unwrap_or_default())
+    }
+
+    /// Delete the messages of the receipt handles, the number of the deleted is returned
+    pub async fn delete(&self, receipt_handles: &[String]) -> anyhow::Result<usize> {
+        let mut deleted = 0;
+        for chunk in receipt_handles.chunks(MAX_BATCH_ENTRIES) {
+            let entries = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, receipt_handle)| {
+                    DeleteMessageBatchRequestEntry::builder()
+                        .id(i.to_string())
+                        .receipt_handle(receipt_handle)
+                        .build()
+                })
+                .collect();
+            let output = self
+                .client
+                .delete_message_batch()
+                .queue_url(self.queue_url.as_str())
+                .set_entries(Some(entries))
+                .send()
+                .await?;
+            deleted += output.successful().map(|x| x.len()).unwrap_or_default();
+            for failed in output.failed().unwrap_or_default() {
+                warn!("delete message error. {:?}", failed.message());
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Extend the visibility timeout of the messages, the number of the extended is returned
+    pub async fn extend(
+        &self,
+        receipt_handles: &[String],
+        visibility_timeout_seconds: i32,
+    ) -> anyhow::Result<usize> {
+        let mut extended = 0;
+        for chunk in receipt_handles.chunks(MAX_BATCH_ENTRIES) {
+            let entries = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, receipt_handle)| {
+                    ChangeMessageVisibilityBatchRequestEntry::builder()
+                        .id(i.to_string())
+                        .receipt_handle(receipt_handle)
+                        .visibility_timeout(visibility_timeout_seconds)
+                        .build()
+                })
+                .collect();
+            let output = self
+                .client
+                .change_message_visibility_batch()
+                .queue_url(self.queue_url.as_str())
+                .set_entries(Some(entries))
+                .send()
+                .await?;
+            extended += output.successful().map(|x| x.len()).unwrap_or_default();
+        }
+        Ok(extended)
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use rlink::channel::sender::ChannelSender;
+use rlink::core::element::Record;
+
+use crate::metrics::SourceMetrics;
+use crate::source::checkpoint::ReceiptRecorder;
+use crate::source::decoder::MessageDecoder;
+use crate::source::queue::SqsQueue;
+
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+pub(crate) enum SqsEvent {
+    Record {
+        receipt_handle: String,
+        record: Record,
+    },
+    /// the message failed to decode, it's deleted with the checkpoint
+    Discarded(String),
+}
+
+/// Long poll the messages of the queue, the messages are received in order and handed over
+/// in order, so the messages of a FIFO group are emitted in order by the task. The
+/// visibility timeouts of the messages in flight are extended on every half of the timeout
+/// until the messages are deleted
+pub(crate) struct QueueReader {
+    pub queue: SqsQueue,
+    pub max_messages: i32,
+    pub wait_time_seconds: i32,
+    pub visibility_timeout_seconds: i32,
+    pub decoder: MessageDecoder,
+    pub receipt_recorder: ReceiptRecorder,
+    pub sender: ChannelSender<SqsEvent>,
+    pub metrics: SourceMetrics,
+}
+
+impl QueueReader {
+    pub fn spawn(self) {
+        let keeper = tokio::spawn(keep_visible(
+            self.queue.clone(),
+            self.receipt_recorder.clone(),
+            self.visibility_timeout_seconds,
+            self.metrics.clone(),
+        ));
+        tokio::spawn(async move {
+            self.run().await;
+            keeper.abort();
+        });
+    }
+
+    async fn run(&self) {
+        info!("long poll queue {}", self.queue.queue_url());
+        loop {
+            let messages = match self
+                .queue
+                .receive(
+                    self.max_messages,
+                    self.wait_time_seconds,
+                    self.visibility_timeout_seconds,
+                )
+                .await
+            {
+                Ok(messages) => messages,
+                Err(e) => {
+                    self.metrics.error();
+                    warn!("receive from {} error. {}", self.queue.queue_url(), e);
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                    continue;
+                }
+            };
+            self.metrics.received(messages.len());
+
+            for message in messages {
+                let receipt_handle = match message.receipt_handle() {
+                    Some(receipt_handle) => receipt_handle.to_string(),
+                    None => continue,
+                };
+                self.receipt_recorder.receive(receipt_handle.clone());
+
+                let event = match (self.decoder)(&message) {
+                    Ok(record) => SqsEvent::Record {
+                        receipt_handle,
+                        record,
+                    },
+                    Err(e) => {
+                        self.metrics.error();
+                        warn!("decode message error, the message is discarded. {}", e);
+                        SqsEvent::Discarded(receipt_handle)
+                    }
+                };
+                if self.sender.send(event).await.is_err() {
+                    info!("the sqs source of {} is closed", self.queue.queue_url());
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn keep_visible(
+    queue: SqsQueue,
+    receipt_recorder: ReceiptRecorder,
+    visibility_timeout_seconds: i32,
+    metrics: SourceMetrics,
+) {
+    let interval = Duration::from_secs((visibility_timeout_seconds as u64 / 2).max(1));
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let receipt_handles = receipt_recorder.in_flight();
+        if receipt_handles.is_empty() {
+            continue;
+        }
+        match queue
+            .extend(receipt_handles.as_slice(), visibility_timeout_seconds)
+            .await
+        {
+            Ok(extended) => metrics.extended(extended),
+            Err(e) => {
+                metrics.error();
+                warn!(
+                    "extend the visibility timeout of {} messages error. {}",
+                    receipt_handles.len(),
+                    e
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use rlink::channel::receiver::ChannelReceiver;
+use rlink::core::element::Element;
+use rlink::core::function::ElementStream;
+
+use crate::source::checkpoint::ReceiptRecorder;
+use crate::source::reader::SqsEvent;
+
+/// The records of the messages, the receipt handle is recorded once the record is emitted
+pub struct SqsRecordStream {
+    receiver: ChannelReceiver<SqsEvent>,
+    receipt_recorder: ReceiptRecorder,
+}
+
+impl SqsRecordStream {
+    pub(crate) fn new(
+        receiver: ChannelReceiver<SqsEvent>,
+        receipt_recorder: ReceiptRecorder,
+    ) -> Self {
+        SqsRecordStream {
+            receiver,
+            receipt_recorder,
+        }
+    }
+}
+
+impl ElementStream for SqsRecordStream {}
+
+impl Stream for SqsRecordStream {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.as_mut().receiver.poll_recv(cx) {
+                Poll::Ready(Some(SqsEvent::Record {
+                    receipt_handle,
+                    record,
+                })) => {
+                    self.receipt_recorder.emit(receipt_handle);
+                    return Poll::Ready(Some(Element::Record(record)));
+                }
+                Poll::Ready(Some(SqsEvent::Discarded(receipt_handle))) => {
+                    self.receipt_recorder.emit(receipt_handle);
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
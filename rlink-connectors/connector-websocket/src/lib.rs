@@ -0,0 +1,32 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod metrics;
+pub mod source;
+
+pub use source::builder::WebSocketInputFormatBuilder;
+pub use source::input_format::WebSocketInputFormat;
+
+pub const WEBSOCKET: &str = "websocket";
+/// e.g. `wss://stream.example.com/ws`
+pub const URL: &str = "url";
+/// the handshake headers, e.g. `websocket.headers.Authorization`
+pub const HEADERS: &str = "headers";
+/// the text messages sent on every connect, separated by `\n`
+pub const SUBSCRIPTIONS: &str = "subscriptions";
+/// the millis between the pings, the connection is broken if nothing is received in two
+/// intervals
+pub const PING_INTERVAL: &str = "ping.interval";
+/// the reconnect attempts in a row, reconnect forever by default
+pub const MAX_RETRIES: &str = "max.retries";
+pub const BUFFER_SIZE: &str = "buffer.size";
+
+pub const SOURCE_CHANNEL_SIZE: usize = 10000;
+pub const SOURCE_PING_INTERVAL_MILLIS: u64 = 15 * 1000;
+pub const SOURCE_MAX_BACKOFF_MILLIS: u64 = 30 * 1000;
@@ -0,0 +1,38 @@
+use rlink::metrics::{register_counter, Counter, Tag};
+
+pub const SOURCE_MESSAGES: &str = "WebSocket.Source.Messages";
+pub const SOURCE_ERRORS: &str = "WebSocket.Source.Errors";
+pub const SOURCE_RECONNECTS: &str = "WebSocket.Source.Reconnects";
+
+/// Metrics of the source, tagged by the task
+#[derive(Clone)]
+pub(crate) struct SourceMetrics {
+    /// data messages received
+    messages: Counter,
+    /// messages failed to decode
+    errors: Counter,
+    /// connections made again after broken
+    reconnects: Counter,
+}
+
+impl SourceMetrics {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        SourceMetrics {
+            messages: register_counter(SOURCE_MESSAGES, tags.clone()),
+            errors: register_counter(SOURCE_ERRORS, tags.clone()),
+            reconnects: register_counter(SOURCE_RECONNECTS, tags),
+        }
+    }
+
+    pub fn received(&self) {
+        self.messages.increment(1);
+    }
+
+    pub fn error(&self) {
+        self.errors.increment(1);
+    }
+
+    pub fn reconnect(&self) {
+        self.reconnects.increment(1);
+    }
+}
@@ -0,0 +1,133 @@
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+use rlink::core::data_types::Schema;
+use rlink::core::properties::Properties;
+
+use crate::source::input_format::{FrameDecoder, WebSocketInputFormat};
+use crate::{BUFFER_SIZE, HEADERS, MAX_RETRIES, PING_INTERVAL, SUBSCRIPTIONS, URL, WEBSOCKET};
+
+pub struct WebSocketInputFormatBuilder {
+    url: String,
+    headers: Vec<(String, String)>,
+    subscriptions: Vec<String>,
+    ping_interval: Option<Duration>,
+    max_retries: Option<usize>,
+    buffer_size: Option<usize>,
+}
+
+impl WebSocketInputFormatBuilder {
+    /// The `url` of the feed, e.g. `wss://stream.example.com/ws`
+    pub fn new(url: &str) -> Self {
+        WebSocketInputFormatBuilder {
+            url: url.to_string(),
+            headers: vec![],
+            subscriptions: vec![],
+            ping_interval: None,
+            max_retries: None,
+            buffer_size: None,
+        }
+    }
+
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Send the text message on every connect
+    pub fn subscription(mut self, subscription: &str) -> Self {
+        self.subscriptions.push(subscription.to_string());
+        self
+    }
+
+    pub fn ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = Some(ping_interval);
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The messages are decoded to the records of the `schema` by the `decoder`
+    pub fn build(self, schema: Schema, decoder: FrameDecoder) -> WebSocketInputFormat {
+        info!("build websocket source with: {:?}", &self);
+
+        let mut input_format = WebSocketInputFormat::new(self.url, decoder, schema);
+        for (key, value) in self.headers {
+            input_format = input_format.header(key, value);
+        }
+        for subscription in self.subscriptions {
+            input_format = input_format.subscription(subscription);
+        }
+        if let Some(ping_interval) = self.ping_interval {
+            input_format = input_format.ping_interval(ping_interval);
+        }
+        if let Some(max_retries) = self.max_retries {
+            input_format = input_format.max_retries(max_retries);
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            input_format = input_format.buffer_size(buffer_size);
+        }
+
+        input_format
+    }
+}
+
+impl Debug for WebSocketInputFormatBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketInputFormatBuilder")
+            .field("url", &self.url)
+            .field(
+                "headers",
+                &self
+                    .headers
+                    .iter()
+                    .map(|(key, _)| key)
+                    .collect::<Vec<&String>>(),
+            )
+            .field("subscriptions", &self.subscriptions)
+            .field("ping_interval", &self.ping_interval)
+            .field("max_retries", &self.max_retries)
+            .field("buffer_size", &self.buffer_size)
+            .finish()
+    }
+}
+
+impl TryFrom<Properties> for WebSocketInputFormatBuilder {
+    type Error = anyhow::Error;
+
+    fn try_from(properties: Properties) -> Result<Self, Self::Error> {
+        let websocket_properties = properties.to_sub_properties(WEBSOCKET);
+        let url = websocket_properties.get_string(URL)?;
+
+        let mut builder = WebSocketInputFormatBuilder::new(url.as_str());
+
+        for (key, value) in websocket_properties.to_sub_properties(HEADERS).as_map() {
+            builder = builder.header(key.as_str(), value.as_str());
+        }
+        if let Ok(subscriptions) = properties.get_string(SUBSCRIPTIONS) {
+            for subscription in subscriptions.lines().filter(|x| !x.trim().is_empty()) {
+                builder = builder.subscription(subscription);
+            }
+        }
+        if let Ok(ping_interval) = properties.get_duration(PING_INTERVAL) {
+            builder = builder.ping_interval(ping_interval);
+        }
+        if let Ok(max_retries) = properties.get_usize(MAX_RETRIES) {
+            builder = builder.max_retries(max_retries);
+        }
+        if let Ok(buffer_size) = properties.get_usize(BUFFER_SIZE) {
+            builder = builder.buffer_size(buffer_size);
+        }
+
+        Ok(builder)
+    }
+}
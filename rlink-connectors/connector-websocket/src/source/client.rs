@@ -0,0 +1,138 @@
+use std::time::{Duration, Instant};
+
+use futures::{SinkExt, StreamExt};
+use rlink::channel::sender::ChannelSender;
+use rlink::core::element::Element;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::metrics::SourceMetrics;
+use crate::source::input_format::FrameDecoder;
+use crate::SOURCE_MAX_BACKOFF_MILLIS;
+
+/// Read the messages of the websocket. The subscriptions are sent on every connect, and a
+/// ping is sent on every `ping_interval`, the connection is taken as broken if nothing is
+/// received in two intervals. The broken connection is made again with the exponential
+/// backoff, up to `max_retries` times in a row or forever
+pub(crate) struct WebSocketClient {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub subscriptions: Vec<String>,
+    pub ping_interval: Duration,
+    pub max_retries: Option<usize>,
+    pub decoder: FrameDecoder,
+    pub sender: ChannelSender<Element>,
+    pub metrics: SourceMetrics,
+}
+
+impl WebSocketClient {
+    pub fn spawn(self) {
+        tokio::spawn(self.run());
+    }
+
+    async fn run(self) {
+        let mut attempt = 0;
+        loop {
+            match self.read(&mut attempt).await {
+                Ok(()) => info!("websocket {} closed", self.url),
+                Err(e) => warn!("read websocket {} error. {}", self.url, e),
+            }
+
+            if self.sender.is_closed() {
+                return;
+            }
+            if let Some(max_retries) = self.max_retries {
+                if attempt >= max_retries {
+                    error!(
+                        "websocket {} is given up after {} retries",
+                        self.url, attempt
+                    );
+                    return;
+                }
+            }
+            let backoff =
+                Duration::from_millis((1000u64 << attempt.min(16)).min(SOURCE_MAX_BACKOFF_MILLIS));
+            attempt += 1;
+            self.metrics.reconnect();
+            info!(
+                "reconnect websocket {} after {:?}, attempt {}",
+                self.url, backoff, attempt
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    async fn read(&self, attempt: &mut usize) -> anyhow::Result<()> {
+        let mut request = self.url.as_str().into_client_request()?;
+        for (key, value) in &self.headers {
+            request.headers_mut().insert(
+                HeaderName::from_bytes(key.as_bytes())?,
+                HeaderValue::from_str(value.as_str())?,
+            );
+        }
+
+        let (stream, _response) = connect_async(request).await?;
+        info!("websocket {} connected", self.url);
+        *attempt = 0;
+
+        let (mut write, mut read) = stream.split();
+        for subscription in &self.subscriptions {
+            write.send(Message::Text(subscription.clone())).await?;
+        }
+
+        let mut ping = tokio::time::interval(self.ping_interval);
+        ping.tick().await;
+        let mut last_received = Instant::now();
+        loop {
+            tokio::select! {
+                message = read.next() => {
+                    let message = match message {
+                        Some(message) => message?,
+                        None => return Ok(()),
+                    };
+                    last_received = Instant::now();
+                    // the pings of the server are answered by the stream
+                    match message {
+                        Message::Text(text) => self.emit(text.as_bytes()).await?,
+                        Message::Binary(data) => self.emit(data.as_slice()).await?,
+                        Message::Close(frame) => {
+                            info!("websocket {} is closed by the server, {:?}", self.url, frame);
+                            return Ok(());
+                        }
+                        _ => {}
+                    }
+                }
+                _ = ping.tick() => {
+                    if last_received.elapsed() > self.ping_interval * 2 {
+                        return Err(anyhow!(
+                            "nothing received in {:?}",
+                            last_received.elapsed()
+                        ));
+                    }
+                    write.send(Message::Ping(vec![])).await?;
+                }
+            }
+        }
+    }
+
+    async fn emit(&self, payload: &[u8]) -> anyhow::Result<()> {
+        self.metrics.received();
+        let records = match (self.decoder)(payload) {
+            Ok(records) => records,
+            Err(e) => {
+                self.metrics.error();
+                warn!("decode message error, the message is discarded. {}", e);
+                return Ok(());
+            }
+        };
+        for record in records {
+            self.sender
+                .send(Element::Record(record))
+                .await
+                .map_err(|_e| anyhow!("the websocket source is closed"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use rlink::channel::named_channel;
+use rlink::channel::utils::ChannelStream;
+use rlink::core;
+use rlink::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use rlink::core::data_types::Schema;
+use rlink::core::element::{FnSchema, Record};
+use rlink::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use rlink::metrics::Tag;
+
+use crate::metrics::SourceMetrics;
+use crate::source::client::WebSocketClient;
+use crate::{SOURCE_CHANNEL_SIZE, SOURCE_PING_INTERVAL_MILLIS};
+
+/// Decode the payload of a text or binary message into the records, the message is discarded
+/// if failed
+pub type FrameDecoder = Arc<dyn Fn(&[u8]) -> anyhow::Result<Vec<Record>> + Send + Sync>;
+
+/// Read the messages of a websocket feed, e.g. the market data, and decode them into the
+/// records by the `decoder`.
+///
+/// The source runs in a single task and is not checkpointed, the messages received while
+/// the connection is broken are lost
+#[derive(NamedFunction)]
+pub struct WebSocketInputFormat {
+    url: String,
+    headers: Vec<(String, String)>,
+    subscriptions: Vec<String>,
+    ping_interval: Duration,
+    max_retries: Option<usize>,
+    buffer_size: usize,
+    decoder: FrameDecoder,
+    schema: Schema,
+
+    tags: Vec<Tag>,
+}
+
+impl WebSocketInputFormat {
+    pub fn new(url: String, decoder: FrameDecoder, schema: Schema) -> Self {
+        WebSocketInputFormat {
+            url,
+            headers: vec![],
+            subscriptions: vec![],
+            ping_interval: Duration::from_millis(SOURCE_PING_INTERVAL_MILLIS),
+            max_retries: None,
+            buffer_size: SOURCE_CHANNEL_SIZE,
+            decoder,
+            schema,
+            tags: vec![],
+        }
+    }
+
+    /// The header of the handshake request, e.g. `Authorization`
+    pub fn header(mut self, key: String, value: String) -> Self {
+        self.headers.push((key, value));
+        self
+    }
+
+    /// The text message sent on every connect, e.g. `{"op":"subscribe","args":["BTC-USD"]}`
+    pub fn subscription(mut self, subscription: String) -> Self {
+        self.subscriptions.push(subscription);
+        self
+    }
+
+    pub fn ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self
+    }
+
+    /// Give up after `max_retries` reconnects in a row, reconnect forever by default
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+}
+
+#[async_trait]
+impl InputFormat for WebSocketInputFormat {
+    async fn open(&mut self, _input_split: InputSplit, context: &Context) -> core::Result<()> {
+        self.tags = context.task_id.to_tags();
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) = named_channel(
+            "WebSocketSource_Handover",
+            self.tags.clone(),
+            self.buffer_size,
+        );
+
+        WebSocketClient {
+            url: self.url.clone(),
+            headers: self.headers.clone(),
+            subscriptions: self.subscriptions.clone(),
+            ping_interval: self.ping_interval,
+            max_retries: self.max_retries,
+            decoder: self.decoder.clone(),
+            sender,
+            metrics: SourceMetrics::new(self.tags.clone()),
+        }
+        .spawn();
+
+        Box::pin(ChannelStream::new(receiver))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        1
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for WebSocketInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+impl InputSplitSource for WebSocketInputFormat {}
@@ -0,0 +1,4 @@
+pub mod builder;
+pub mod input_format;
+
+pub(crate) mod client;
@@ -1,18 +1,99 @@
 use k8s_openapi::api::apps::v1::Deployment;
+use k8s_openapi::api::core::v1::ConfigMap;
 use kube::{
     api::{Api, PostParams},
     Client,
 };
+use rlink::utils::pod_template::{add_volume, apply_pod_template, load_pod_template};
 use serde_json::json;
 
 use crate::config::k8s_config::Config;
 
+/// the directory of the pod templates mounted in the coordinator
+const POD_TEMPLATE_MOUNT_PATH: &str = "/opt/rlink/pod-template";
+const WORKER_POD_TEMPLATE_FILE: &str = "worker-pod-template.yaml";
+
 pub async fn run(cfg: Config) -> anyhow::Result<()> {
+    // check the templates before any resource of the job is created
+    let coordinator_pod_template = match &cfg.coordinator_pod_template {
+        Some(coordinator_pod_template) => Some(load_pod_template(coordinator_pod_template)?),
+        None => None,
+    };
+    if let Some(worker_pod_template) = &cfg.worker_pod_template {
+        load_pod_template(worker_pod_template)?;
+    }
+
     let client = Client::try_default().await?;
 
     let namespace = std::env::var("NAMESPACE").unwrap_or(cfg.namespace.clone().into());
     let deployment: Api<Deployment> = Api::namespaced(client.clone(), &namespace);
 
+    let mut args = vec![
+        format!("image_path={}", cfg.image_path),
+        format!("application_id={}", cfg.cluster_name.clone()),
+        "cluster_mode=kubernetes".to_string(),
+        "manager_type=Coordinator".to_string(),
+        format!("num_task_managers={}", cfg.num_task_managers),
+        format!("v_cores={}", cfg.task_v_cores),
+        format!("memory_mb={}", cfg.task_memory_mb),
+    ];
+    if cfg.worker_pod_template.is_some() {
+        args.push(format!(
+            "worker_pod_template={}/{}",
+            POD_TEMPLATE_MOUNT_PATH, WORKER_POD_TEMPLATE_FILE
+        ));
+    }
+
+    let mut pod = json!({
+        "metadata":{
+            "labels":{
+                "app":"rlink",
+                "commpent":"jobmanager",
+                "type":"rlinl-on-k8s"
+           }
+        } ,
+        "spec":{
+            "containers": [
+                {
+                    "name":"jobmanager",
+                    "image": cfg.image_path,
+                    "limits":{
+                            "cpu":cfg.job_v_cores,
+                            "memory": format!("{}Mi",cfg.job_memory_mb)
+                    },
+                    "args": args
+                }
+            ]
+        }
+    });
+    if let Some(pod_template) = &coordinator_pod_template {
+        pod = apply_pod_template(pod_template, pod);
+    }
+
+    // the worker pod template is mounted in the coordinator by a ConfigMap
+    if let Some(worker_pod_template) = &cfg.worker_pod_template {
+        let config_map_name = format!("{}-pod-template", cfg.cluster_name);
+        create_pod_template_config_map(
+            client.clone(),
+            namespace.as_str(),
+            config_map_name.as_str(),
+            worker_pod_template.as_str(),
+        )
+        .await?;
+
+        add_volume(
+            &mut pod,
+            json!({
+                "name": "pod-template",
+                "configMap": {"name": config_map_name}
+            }),
+            json!({
+                "name": "pod-template",
+                "mountPath": POD_TEMPLATE_MOUNT_PATH
+            }),
+        );
+    }
+
     let d: Deployment = serde_json::from_value(json!({
         "apiVersion": "apps/v1",
         "kind": "Deployment",
@@ -31,36 +112,7 @@ pub async fn run(cfg: Config) -> anyhow::Result<()> {
                         "app":"rlink"
                 }
             },
-            "template":{
-                "metadata":{
-                    "labels":{
-                        "app":"rlink",
-                        "commpent":"jobmanager",
-                        "type":"rlinl-on-k8s"
-                   }
-                } ,
-                "spec":{
-                    "containers": [
-                        {
-                            "name":"jobmanager",
-                            "image": cfg.image_path,
-                            "limits":{
-                                    "cpu":cfg.job_v_cores,
-                                    "memory": format!("{}Mi",cfg.job_memory_mb)
-                            },
-                            "args":[
-                                format!("image_path={}",cfg.image_path),
-                                format!("application_id={}",cfg.cluster_name.clone()),
-                                "cluster_mode=kubernetes",
-                                "manager_type=Coordinator",
-                                format!("num_task_managers={}",cfg.num_task_managers),
-                                format!("v_cores={}",cfg.task_v_cores),
-                                format!("memory_mb={}",cfg.task_memory_mb)
-                                ]
-                        }
-                    ]
-                }
-            }
+            "template": pod
         }
     }))?;
 
@@ -75,3 +127,37 @@ pub async fn run(cfg: Config) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+async fn create_pod_template_config_map(
+    client: Client,
+    namespace: &str,
+    name: &str,
+    pod_template_path: &str,
+) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(pod_template_path)?;
+
+    let config_maps: Api<ConfigMap> = Api::namespaced(client, namespace);
+    let config_map: ConfigMap = serde_json::from_value(json!({
+        "apiVersion": "v1",
+        "kind": "ConfigMap",
+        "metadata": {
+            "name": name,
+            "labels":{
+                "app":"rlink"
+            }
+        },
+        "data": {
+            WORKER_POD_TEMPLATE_FILE: content
+        }
+    }))?;
+
+    let pp = PostParams::default();
+    match config_maps.create(&pp, &config_map).await {
+        Ok(_o) => {}
+        Err(kube::Error::Api(ae)) if ae.code == 409 => {
+            config_maps.replace(name, &pp, &config_map).await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
@@ -10,6 +10,10 @@ pub struct Config {
     pub num_task_managers: usize,
     pub task_v_cores: usize,
     pub task_memory_mb: usize,
+    /// the path of the pod template of the coordinator
+    pub coordinator_pod_template: Option<String>,
+    /// the path of the pod template of the workers
+    pub worker_pod_template: Option<String>,
 }
 
 impl Config {
@@ -23,6 +27,8 @@ impl Config {
             num_task_managers: 1,
             task_v_cores: 1,
             task_memory_mb: 100,
+            coordinator_pod_template: None,
+            worker_pod_template: None,
         };
 
         match parse_arg("cluster_name") {
@@ -59,6 +65,9 @@ impl Config {
             Ok(o) => cfg.num_task_managers = o.parse().expect("num_task_managers must a usize num"),
             _ => {}
         }
+
+        cfg.coordinator_pod_template = parse_arg("coordinator_pod_template").ok();
+        cfg.worker_pod_template = parse_arg("worker_pod_template").ok();
         cfg
     }
 }
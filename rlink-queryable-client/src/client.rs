@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use http::uri::PathAndQuery;
+use rlink::core::cluster::{ResponseCode, StdResponse};
+use rlink::core::element::Record;
+use rlink::core::key_group::assign_key_to_operator;
+use rlink::core::queryable_state::{
+    ListStatesRequest, ListStatesResponse, StateRequest, StateResponse, GET_STATE_PATH,
+    LIST_STATES_PATH,
+};
+use rlink::core::runtime::ClusterDescriptor;
+use rlink::utils::http::client::get;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::transport::Channel;
+
+/// The tasks of a job holding a queryable state
+struct QueryableStateRoute {
+    /// the parallelism of the job
+    num_tasks: u16,
+    /// the channels of the workers by the task number
+    tasks: HashMap<u16, Channel>,
+}
+
+/// Look up the keyed states in the queryable state servers of the workers. The key is held by
+/// the task owning the key group of it, so each query is sent to the worker of that task only
+pub struct QueryableStateClient {
+    /// the web address of the coordinator to discover the workers, e.g. `http://127.0.0.1:8770`
+    coordinator_address: Option<String>,
+    workers: Vec<String>,
+    /// the number of the key groups of the application
+    max_parallelism: u16,
+    routes: HashMap<String, QueryableStateRoute>,
+}
+
+impl QueryableStateClient {
+    /// Discover the workers by the cluster metadata of the coordinator
+    pub fn new(coordinator_address: &str) -> Self {
+        QueryableStateClient {
+            coordinator_address: Some(coordinator_address.trim_end_matches('/').to_string()),
+            workers: vec![],
+            max_parallelism: 0,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Query the queryable state servers of the `workers` directly, e.g. `http://10.0.0.1:23456`
+    pub fn with_workers(workers: Vec<String>) -> Self {
+        QueryableStateClient {
+            coordinator_address: None,
+            workers,
+            max_parallelism: 0,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// The value of the `key` in the queryable state, `None` if the key is absent or expired
+    pub async fn get_state<T>(
+        &mut self,
+        queryable_name: &str,
+        key: &mut Record,
+    ) -> anyhow::Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.get_value(queryable_name, key).await? {
+            Some(value) => Ok(Some(T::deserialize(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The json value of the `key` in the queryable state
+    pub async fn get_value(
+        &mut self,
+        queryable_name: &str,
+        key: &mut Record,
+    ) -> anyhow::Result<Option<Value>> {
+        let key = key.as_buffer().as_slice().to_vec();
+
+        if !self.routes.contains_key(queryable_name) {
+            self.connect().await?;
+        }
+        match self.query(queryable_name, key.as_slice()).await {
+            Ok(value) => Ok(value),
+            Err(e) => {
+                // the tasks may be restarted on the other workers
+                warn!("query state error, rediscover the tasks. {}", e);
+                self.connect().await?;
+                self.query(queryable_name, key.as_slice()).await
+            }
+        }
+    }
+
+    async fn query(&self, queryable_name: &str, key: &[u8]) -> anyhow::Result<Option<Value>> {
+        let route = self
+            .routes
+            .get(queryable_name)
+            .ok_or_else(|| anyhow!("queryable state {} not found", queryable_name))?;
+        let task_number = assign_key_to_operator(key, self.max_parallelism, route.num_tasks);
+        let channel = route.tasks.get(&task_number).ok_or_else(|| {
+            anyhow!(
+                "the task {} of the queryable state {} not found",
+                task_number,
+                queryable_name
+            )
+        })?;
+
+        let request = StateRequest {
+            queryable_name: queryable_name.to_string(),
+            key: key.to_vec(),
+            task_number: Some(task_number as u32),
+        };
+        let mut grpc = Grpc::new(channel.clone());
+        grpc.ready().await.map_err(|e| anyhow!(e))?;
+
+        let codec = ProstCodec::<StateRequest, StateResponse>::default();
+        let path = PathAndQuery::from_static(GET_STATE_PATH);
+        let response = grpc
+            .unary(tonic::Request::new(request), path, codec)
+            .await?
+            .into_inner();
+        if response.found {
+            let value = serde_json::from_str(response.value.as_str())?;
+            Ok(Some(value))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Discover the tasks holding the queryable states in the workers
+    async fn connect(&mut self) -> anyhow::Result<()> {
+        if let Some(coordinator_address) = &self.coordinator_address {
+            self.workers = discover_workers(coordinator_address.as_str()).await?;
+        }
+        if self.workers.is_empty() {
+            return Err(anyhow!("no queryable state server found"));
+        }
+
+        let mut routes: HashMap<String, QueryableStateRoute> = HashMap::new();
+        for worker in &self.workers {
+            let channel = Channel::from_shared(worker.clone())?.connect().await?;
+            let response = list_states(channel.clone()).await?;
+            self.max_parallelism = response.max_parallelism as u16;
+
+            for state in response.states {
+                let route =
+                    routes
+                        .entry(state.queryable_name)
+                        .or_insert_with(|| QueryableStateRoute {
+                            num_tasks: state.num_tasks as u16,
+                            tasks: HashMap::new(),
+                        });
+                route
+                    .tasks
+                    .insert(state.task_number as u16, channel.clone());
+            }
+        }
+        self.routes = routes;
+        Ok(())
+    }
+}
+
+/// The tasks of the worker holding the queryable states
+async fn list_states(channel: Channel) -> anyhow::Result<ListStatesResponse> {
+    let mut grpc = Grpc::new(channel);
+    grpc.ready().await.map_err(|e| anyhow!(e))?;
+
+    let codec = ProstCodec::<ListStatesRequest, ListStatesResponse>::default();
+    let path = PathAndQuery::from_static(LIST_STATES_PATH);
+    let response = grpc
+        .unary(tonic::Request::new(ListStatesRequest {}), path, codec)
+        .await?
+        .into_inner();
+    Ok(response)
+}
+
+/// The queryable state servers of the workers in the cluster metadata of the coordinator
+async fn discover_workers(coordinator_address: &str) -> anyhow::Result<Vec<String>> {
+    let url = format!("{}/api/cluster_metadata", coordinator_address);
+    let resp = get(url.as_str()).await.map_err(|e| anyhow!("{}", e))?;
+
+    let StdResponse { code, data } =
+        serde_json::from_str::<StdResponse<ClusterDescriptor>>(resp.as_str())?;
+    let cluster_descriptor = match (code, data) {
+        (ResponseCode::OK, Some(cluster_descriptor)) => cluster_descriptor,
+        _ => return Err(anyhow!("get cluster metadata error. {}", resp)),
+    };
+
+    let workers = cluster_descriptor
+        .worker_managers
+        .into_iter()
+        .map(|w| w.queryable_state_address)
+        .filter(|address| !address.is_empty())
+        .collect();
+    Ok(workers)
+}
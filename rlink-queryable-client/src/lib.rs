@@ -0,0 +1,12 @@
+//! The client of the queryable states served by the workers of a running application, the
+//! states are exposed by the `queryable` of the state descriptors or of the windowed streams,
+//! and the workers serve them when `SystemProperties::set_queryable_state` is enabled.
+
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate anyhow;
+
+pub mod client;
+
+pub use client::QueryableStateClient;
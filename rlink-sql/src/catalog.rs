@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use rlink::core::data_stream::{DataStream, SinkStream};
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::env::StreamExecutionEnvironment;
+
+use crate::error::{invalid, Result, SqlError};
+
+type SourceFactory = Box<dyn Fn(&mut StreamExecutionEnvironment) -> DataStream>;
+type SinkFactory = Box<dyn Fn(DataStream) -> SinkStream>;
+
+/// The event time column of the source table and the bounded out-of-orderness of the
+/// watermarks generated by it
+#[derive(Clone, Debug)]
+pub struct RowTime {
+    pub column: String,
+    pub out_of_orderness: Duration,
+}
+
+/// A table read by the `FROM` clause, the records are produced by the `DataStream` of the
+/// connector source registered by the factory, e.g.
+/// `|env| env.register_source(KafkaInputFormat::new(..))`
+pub struct SourceTable {
+    name: String,
+    schema: Schema,
+    row_time: Option<RowTime>,
+    factory: SourceFactory,
+}
+
+impl SourceTable {
+    pub fn new<F>(name: &str, schema: Schema, factory: F) -> Self
+    where
+        F: Fn(&mut StreamExecutionEnvironment) -> DataStream + 'static,
+    {
+        SourceTable {
+            name: name.to_string(),
+            schema,
+            row_time: None,
+            factory: Box::new(factory),
+        }
+    }
+
+    /// Declare the `UInt64` millis `column` as the event time of the records, the timestamps
+    /// and the watermarks are assigned right after the source. The time windows of the
+    /// queries must be defined on the event time column
+    pub fn row_time(mut self, column: &str, out_of_orderness: Duration) -> Self {
+        self.row_time = Some(RowTime {
+            column: column.to_string(),
+            out_of_orderness,
+        });
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn get_row_time(&self) -> Option<&RowTime> {
+        self.row_time.as_ref()
+    }
+
+    pub(crate) fn create_stream(&self, env: &mut StreamExecutionEnvironment) -> DataStream {
+        (self.factory)(env)
+    }
+}
+
+/// A table written by the `INSERT INTO` statement, the query results are converted to the
+/// `schema` of the table and written by the connector sink added by the factory, e.g.
+/// `|data_stream| data_stream.add_sink(KafkaOutputFormat::new(..))`
+pub struct SinkTable {
+    name: String,
+    schema: Schema,
+    factory: SinkFactory,
+}
+
+impl SinkTable {
+    pub fn new<F>(name: &str, schema: Schema, factory: F) -> Self
+    where
+        F: Fn(DataStream) -> SinkStream + 'static,
+    {
+        SinkTable {
+            name: name.to_string(),
+            schema,
+            factory: Box::new(factory),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub(crate) fn add_sink(&self, data_stream: DataStream) -> SinkStream {
+        (self.factory)(data_stream)
+    }
+}
+
+/// The tables the sql statements are bound to, the names are case-sensitive
+#[derive(Default)]
+pub struct Catalog {
+    sources: HashMap<String, SourceTable>,
+    sinks: HashMap<String, SinkTable>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Catalog::default()
+    }
+
+    pub fn register_source(&mut self, table: SourceTable) -> Result<()> {
+        if let Some(row_time) = table.get_row_time() {
+            match table.schema().field_with_name(row_time.column.as_str()) {
+                Some(field) if field.data_type() == &DataType::UInt64 => {}
+                Some(_) => {
+                    return invalid(format!(
+                        "the row time column `{}` of table `{}` must be UInt64",
+                        row_time.column,
+                        table.name()
+                    ))
+                }
+                None => return Err(SqlError::ColumnNotFound(row_time.column.clone())),
+            }
+        }
+
+        if self.sources.contains_key(table.name()) {
+            return Err(SqlError::DuplicateTable(table.name().to_string()));
+        }
+        self.sources.insert(table.name().to_string(), table);
+        Ok(())
+    }
+
+    pub fn register_sink(&mut self, table: SinkTable) -> Result<()> {
+        if self.sinks.contains_key(table.name()) {
+            return Err(SqlError::DuplicateTable(table.name().to_string()));
+        }
+        self.sinks.insert(table.name().to_string(), table);
+        Ok(())
+    }
+
+    pub fn source(&self, name: &str) -> Result<&SourceTable> {
+        self.sources
+            .get(name)
+            .ok_or_else(|| SqlError::TableNotFound(name.to_string()))
+    }
+
+    pub fn sink(&self, name: &str) -> Result<&SinkTable> {
+        self.sinks
+            .get(name)
+            .ok_or_else(|| SqlError::TableNotFound(name.to_string()))
+    }
+}
@@ -0,0 +1,35 @@
+use sqlparser::parser::ParserError;
+
+pub type Result<T> = std::result::Result<T, SqlError>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SqlError {
+    #[error(transparent)]
+    Parser(#[from] ParserError),
+
+    #[error("table `{0}` not found in the catalog")]
+    TableNotFound(String),
+
+    #[error("table `{0}` is already registered")]
+    DuplicateTable(String),
+
+    #[error("column `{0}` not found")]
+    ColumnNotFound(String),
+
+    #[error("column `{0}` is ambiguous")]
+    AmbiguousColumn(String),
+
+    #[error("unsupported sql: {0}")]
+    Unsupported(String),
+
+    #[error("invalid sql: {0}")]
+    Invalid(String),
+}
+
+pub(crate) fn unsupported<T, S: ToString>(s: S) -> Result<T> {
+    Err(SqlError::Unsupported(s.to_string()))
+}
+
+pub(crate) fn invalid<T, S: ToString>(s: S) -> Result<T> {
+    Err(SqlError::Invalid(s.to_string()))
+}
@@ -0,0 +1,698 @@
+use std::cmp::Ordering;
+
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::window::{TWindow, Window};
+use sqlparser::ast;
+use sqlparser::ast::{BinaryOperator, FunctionArg, FunctionArgExpr, Ident, UnaryOperator};
+
+use crate::error::{invalid, unsupported, Result, SqlError};
+use crate::value::{is_float, is_numeric, is_unsigned, Value};
+
+/// A column visible to the expressions of the query, the `qualifier` is the alias or the name
+/// of its table
+#[derive(Clone, Debug)]
+pub(crate) struct ScopeColumn {
+    pub qualifier: Option<String>,
+    pub name: String,
+    pub data_type: DataType,
+    /// the event time column of the table, the time windows must be defined on it
+    pub row_time: bool,
+}
+
+/// The columns of the records flowing into an operator of the plan
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Scope {
+    pub columns: Vec<ScopeColumn>,
+}
+
+impl Scope {
+    pub fn new(qualifier: &str, schema: &Schema, row_time: Option<&str>) -> Self {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| ScopeColumn {
+                qualifier: Some(qualifier.to_string()),
+                name: field.name().to_string(),
+                data_type: field.data_type().clone(),
+                row_time: row_time == Some(field.name()),
+            })
+            .collect();
+        Scope { columns }
+    }
+
+    /// The columns of the joined records, the left ones go first
+    pub fn join(&self, right: &Scope) -> Self {
+        let mut columns = self.columns.clone();
+        columns.extend(right.columns.iter().cloned());
+        Scope { columns }
+    }
+
+    /// The index of the column referenced by `name` or `qualifier.name`
+    pub fn resolve(&self, idents: &[Ident]) -> Result<usize> {
+        let (qualifier, name) = match idents {
+            [name] => (None, name.value.as_str()),
+            [qualifier, name] => (Some(qualifier.value.as_str()), name.value.as_str()),
+            _ => return unsupported(format!("column reference `{}`", join_idents(idents))),
+        };
+
+        let mut found = self.columns.iter().enumerate().filter(|(_, column)| {
+            column.name == name && (qualifier.is_none() || column.qualifier.as_deref() == qualifier)
+        });
+        match (found.next(), found.next()) {
+            (Some((index, _)), None) => Ok(index),
+            (Some(_), Some(_)) => Err(SqlError::AmbiguousColumn(join_idents(idents))),
+            (None, _) => Err(SqlError::ColumnNotFound(join_idents(idents))),
+        }
+    }
+}
+
+fn join_idents(idents: &[Ident]) -> String {
+    idents
+        .iter()
+        .map(|x| x.value.as_str())
+        .collect::<Vec<&str>>()
+        .join(".")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BinaryOp {
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
+    Concat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ScalarFunction {
+    Upper,
+    Lower,
+    CharLength,
+    Abs,
+}
+
+/// The compiled expression evaluated on the values of a record
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expression {
+    Column(usize, DataType),
+    Literal(Value, DataType),
+    Binary {
+        op: BinaryOp,
+        left: Box<Expression>,
+        right: Box<Expression>,
+        data_type: DataType,
+    },
+    Not(Box<Expression>),
+    Negative(Box<Expression>, DataType),
+    Cast(Box<Expression>, DataType),
+    Between {
+        expr: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    InList {
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    Function(ScalarFunction, Vec<Expression>, DataType),
+    /// the start millis of the window the aggregated record is fired by
+    WindowStart,
+    /// the end millis of the window the aggregated record is fired by
+    WindowEnd,
+}
+
+impl Expression {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Expression::Column(_, data_type) => data_type.clone(),
+            Expression::Literal(_, data_type) => data_type.clone(),
+            Expression::Binary { data_type, .. } => data_type.clone(),
+            Expression::Not(_) => DataType::Boolean,
+            Expression::Negative(_, data_type) => data_type.clone(),
+            Expression::Cast(_, data_type) => data_type.clone(),
+            Expression::Between { .. } | Expression::InList { .. } => DataType::Boolean,
+            Expression::Function(_, _, data_type) => data_type.clone(),
+            Expression::WindowStart | Expression::WindowEnd => DataType::UInt64,
+        }
+    }
+
+    pub fn eval(&self, row: &[Value], window: Option<&Window>) -> anyhow::Result<Value> {
+        match self {
+            Expression::Column(index, _) => Ok(row[*index].clone()),
+            Expression::Literal(value, _) => Ok(value.clone()),
+            Expression::Binary {
+                op,
+                left,
+                right,
+                data_type,
+            } => {
+                let left = left.eval(row, window)?;
+                match op {
+                    // short-circuit
+                    BinaryOp::And if !left.as_bool()? => Ok(Value::Boolean(false)),
+                    BinaryOp::Or if left.as_bool()? => Ok(Value::Boolean(true)),
+                    _ => {
+                        let right = right.eval(row, window)?;
+                        eval_binary(*op, left, right, data_type)
+                    }
+                }
+            }
+            Expression::Not(expr) => Ok(Value::Boolean(!expr.eval(row, window)?.as_bool()?)),
+            Expression::Negative(expr, _) => match expr.eval(row, window)? {
+                Value::Float(v) => Ok(Value::Float(-v)),
+                v => Ok(Value::Int(-v.as_i64()?)),
+            },
+            Expression::Cast(expr, data_type) => cast(expr.eval(row, window)?, data_type),
+            Expression::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let value = expr.eval(row, window)?;
+                let between = value.compare(&low.eval(row, window)?)? != Ordering::Less
+                    && value.compare(&high.eval(row, window)?)? != Ordering::Greater;
+                Ok(Value::Boolean(between != *negated))
+            }
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let value = expr.eval(row, window)?;
+                let mut contains = false;
+                for item in list {
+                    if value.compare(&item.eval(row, window)?)? == Ordering::Equal {
+                        contains = true;
+                        break;
+                    }
+                }
+                Ok(Value::Boolean(contains != *negated))
+            }
+            Expression::Function(function, args, _) => {
+                let value = args[0].eval(row, window)?;
+                match function {
+                    ScalarFunction::Upper => Ok(Value::String(value.to_string().to_uppercase())),
+                    ScalarFunction::Lower => Ok(Value::String(value.to_string().to_lowercase())),
+                    ScalarFunction::CharLength => {
+                        Ok(Value::UInt(value.to_string().chars().count() as u64))
+                    }
+                    ScalarFunction::Abs => match value {
+                        Value::Float(v) => Ok(Value::Float(v.abs())),
+                        Value::UInt(v) => Ok(Value::UInt(v)),
+                        v => Ok(Value::Int(v.as_i64()?.abs())),
+                    },
+                }
+            }
+            Expression::WindowStart => window
+                .map(|x| Value::UInt(x.min_timestamp()))
+                .ok_or_else(|| anyhow!("the record is not fired by a window")),
+            Expression::WindowEnd => window
+                .map(|x| Value::UInt(x.max_timestamp()))
+                .ok_or_else(|| anyhow!("the record is not fired by a window")),
+        }
+    }
+}
+
+fn eval_binary(
+    op: BinaryOp,
+    left: Value,
+    right: Value,
+    data_type: &DataType,
+) -> anyhow::Result<Value> {
+    let value = match op {
+        BinaryOp::Eq => Value::Boolean(left.compare(&right)? == Ordering::Equal),
+        BinaryOp::NotEq => Value::Boolean(left.compare(&right)? != Ordering::Equal),
+        BinaryOp::Lt => Value::Boolean(left.compare(&right)? == Ordering::Less),
+        BinaryOp::LtEq => Value::Boolean(left.compare(&right)? != Ordering::Greater),
+        BinaryOp::Gt => Value::Boolean(left.compare(&right)? == Ordering::Greater),
+        BinaryOp::GtEq => Value::Boolean(left.compare(&right)? != Ordering::Less),
+        BinaryOp::And | BinaryOp::Or => Value::Boolean(right.as_bool()?),
+        BinaryOp::Concat => Value::String(format!("{}{}", left, right)),
+        _ if is_float(data_type) => {
+            let (l, r) = (left.as_f64()?, right.as_f64()?);
+            Value::Float(match op {
+                BinaryOp::Plus => l + r,
+                BinaryOp::Minus => l - r,
+                BinaryOp::Multiply => l * r,
+                BinaryOp::Divide => l / r,
+                _ => l % r,
+            })
+        }
+        _ if is_unsigned(data_type) => {
+            let (l, r) = (left.as_u64()?, right.as_u64()?);
+            let value = match op {
+                BinaryOp::Plus => l.checked_add(r),
+                BinaryOp::Minus => l.checked_sub(r),
+                BinaryOp::Multiply => l.checked_mul(r),
+                BinaryOp::Divide => l.checked_div(r),
+                _ => l.checked_rem(r),
+            };
+            Value::UInt(value.ok_or_else(|| anyhow!("{} {:?} {} overflows", l, op, r))?)
+        }
+        _ => {
+            let (l, r) = (left.as_i64()?, right.as_i64()?);
+            let value = match op {
+                BinaryOp::Plus => l.checked_add(r),
+                BinaryOp::Minus => l.checked_sub(r),
+                BinaryOp::Multiply => l.checked_mul(r),
+                BinaryOp::Divide => l.checked_div(r),
+                _ => l.checked_rem(r),
+            };
+            Value::Int(value.ok_or_else(|| anyhow!("{} {:?} {} overflows", l, op, r))?)
+        }
+    };
+    Ok(value)
+}
+
+fn cast(value: Value, data_type: &DataType) -> anyhow::Result<Value> {
+    let value = match data_type {
+        DataType::Boolean => Value::Boolean(value.as_bool()?),
+        DataType::String => Value::String(value.to_string()),
+        DataType::Binary => match value {
+            Value::Binary(v) => Value::Binary(v),
+            v => Value::Binary(v.to_string().into_bytes()),
+        },
+        t if is_float(t) => Value::Float(value.as_f64()?),
+        t if is_unsigned(t) => Value::UInt(value.as_u64()?),
+        _ => Value::Int(value.as_i64()?),
+    };
+    Ok(value)
+}
+
+/// The widest type of the numeric operands
+fn numeric_type(left: &DataType, right: &DataType) -> DataType {
+    if is_float(left) || is_float(right) {
+        DataType::Float64
+    } else if is_unsigned(left) && is_unsigned(right) {
+        DataType::UInt64
+    } else {
+        DataType::Int64
+    }
+}
+
+/// The aggregate functions, they're planned by the window aggregation
+pub(crate) fn is_aggregate_function(name: &str) -> bool {
+    matches!(name, "count" | "sum" | "min" | "max" | "avg")
+}
+
+/// The window start and end functions, they're valid in the window aggregation only
+fn window_function(name: &str) -> Option<Expression> {
+    match name {
+        "tumble_start" | "hop_start" | "session_start" => Some(Expression::WindowStart),
+        "tumble_end" | "hop_end" | "session_end" => Some(Expression::WindowEnd),
+        _ => None,
+    }
+}
+
+/// The lowercase name of the function
+pub(crate) fn function_name(function: &ast::Function) -> String {
+    function.name.to_string().to_lowercase()
+}
+
+/// The unnamed expression arguments of the function, `*` is `None`
+pub(crate) fn function_args(function: &ast::Function) -> Result<Vec<Option<&ast::Expr>>> {
+    function
+        .args
+        .iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Ok(Some(expr)),
+            FunctionArg::Unnamed(FunctionArgExpr::Wildcard) => Ok(None),
+            _ => unsupported(format!("function argument `{}`", arg)),
+        })
+        .collect()
+}
+
+/// Compile the sql expressions against the columns of the `scope`.
+///
+/// The expressions of the aggregated records are compiled with the `replacements` of the group
+/// keys and the aggregate calls, the other column references are rejected
+pub(crate) struct ExprCompiler<'a> {
+    scope: &'a Scope,
+    replacements: Vec<(ast::Expr, Expression)>,
+    aggregated: bool,
+}
+
+impl<'a> ExprCompiler<'a> {
+    pub fn new(scope: &'a Scope) -> Self {
+        ExprCompiler {
+            scope,
+            replacements: vec![],
+            aggregated: false,
+        }
+    }
+
+    pub fn aggregated(scope: &'a Scope, replacements: Vec<(ast::Expr, Expression)>) -> Self {
+        ExprCompiler {
+            scope,
+            replacements,
+            aggregated: true,
+        }
+    }
+
+    pub fn compile(&self, expr: &ast::Expr) -> Result<Expression> {
+        if let Some((_, expression)) = self.replacements.iter().find(|(x, _)| x == expr) {
+            return Ok(expression.clone());
+        }
+
+        match expr {
+            ast::Expr::Identifier(ident) => self.column(std::slice::from_ref(ident)),
+            ast::Expr::CompoundIdentifier(idents) => self.column(idents.as_slice()),
+            ast::Expr::Nested(expr) => self.compile(expr),
+            ast::Expr::Value(value) => literal(value),
+            ast::Expr::BinaryOp { left, op, right } => self.binary(left, op, right),
+            ast::Expr::UnaryOp { op, expr } => {
+                let expr = self.compile(expr)?;
+                match op {
+                    UnaryOperator::Not if expr.data_type() == DataType::Boolean => {
+                        Ok(Expression::Not(Box::new(expr)))
+                    }
+                    UnaryOperator::Plus if is_numeric(&expr.data_type()) => Ok(expr),
+                    UnaryOperator::Minus if is_numeric(&expr.data_type()) => {
+                        let data_type = if is_float(&expr.data_type()) {
+                            DataType::Float64
+                        } else {
+                            DataType::Int64
+                        };
+                        Ok(Expression::Negative(Box::new(expr), data_type))
+                    }
+                    _ => invalid(format!("`{}` of {:?}", op, expr.data_type())),
+                }
+            }
+            ast::Expr::Cast {
+                expr, data_type, ..
+            } => {
+                let expr = self.compile(expr)?;
+                Ok(Expression::Cast(Box::new(expr), sql_data_type(data_type)?))
+            }
+            ast::Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => {
+                let expr = self.compile(expr)?;
+                let low = self.compile(low)?;
+                let high = self.compile(high)?;
+                check_comparable(&expr, &low)?;
+                check_comparable(&expr, &high)?;
+                Ok(Expression::Between {
+                    expr: Box::new(expr),
+                    low: Box::new(low),
+                    high: Box::new(high),
+                    negated: *negated,
+                })
+            }
+            ast::Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let expr = self.compile(expr)?;
+                let list = list
+                    .iter()
+                    .map(|x| self.compile(x))
+                    .collect::<Result<Vec<Expression>>>()?;
+                for item in &list {
+                    check_comparable(&expr, item)?;
+                }
+                Ok(Expression::InList {
+                    expr: Box::new(expr),
+                    list,
+                    negated: *negated,
+                })
+            }
+            ast::Expr::Function(function) => self.function(function),
+            _ => unsupported(format!("expression `{}`", expr)),
+        }
+    }
+
+    fn column(&self, idents: &[Ident]) -> Result<Expression> {
+        let index = self.scope.resolve(idents)?;
+        if self.aggregated {
+            return invalid(format!(
+                "column `{}` must appear in the GROUP BY clause or be aggregated",
+                join_idents(idents)
+            ));
+        }
+
+        let data_type = self.scope.columns[index].data_type.clone();
+        Ok(Expression::Column(index, data_type))
+    }
+
+    fn binary(
+        &self,
+        left: &ast::Expr,
+        op: &BinaryOperator,
+        right: &ast::Expr,
+    ) -> Result<Expression> {
+        let left = self.compile(left)?;
+        let right = self.compile(right)?;
+        let (left_type, right_type) = (left.data_type(), right.data_type());
+
+        let (op, data_type) = match op {
+            BinaryOperator::Plus => (BinaryOp::Plus, None),
+            BinaryOperator::Minus => (BinaryOp::Minus, None),
+            BinaryOperator::Multiply => (BinaryOp::Multiply, None),
+            BinaryOperator::Divide => (BinaryOp::Divide, None),
+            BinaryOperator::Modulo => (BinaryOp::Modulo, None),
+            BinaryOperator::Eq => (BinaryOp::Eq, Some(DataType::Boolean)),
+            BinaryOperator::NotEq => (BinaryOp::NotEq, Some(DataType::Boolean)),
+            BinaryOperator::Lt => (BinaryOp::Lt, Some(DataType::Boolean)),
+            BinaryOperator::LtEq => (BinaryOp::LtEq, Some(DataType::Boolean)),
+            BinaryOperator::Gt => (BinaryOp::Gt, Some(DataType::Boolean)),
+            BinaryOperator::GtEq => (BinaryOp::GtEq, Some(DataType::Boolean)),
+            BinaryOperator::And => (BinaryOp::And, Some(DataType::Boolean)),
+            BinaryOperator::Or => (BinaryOp::Or, Some(DataType::Boolean)),
+            BinaryOperator::StringConcat => (BinaryOp::Concat, Some(DataType::String)),
+            _ => return unsupported(format!("operator `{}`", op)),
+        };
+
+        let data_type = match (op, data_type) {
+            (BinaryOp::And | BinaryOp::Or, Some(data_type)) => {
+                if left_type != DataType::Boolean || right_type != DataType::Boolean {
+                    return invalid(format!("{:?} of {:?} and {:?}", op, left_type, right_type));
+                }
+                data_type
+            }
+            (BinaryOp::Concat, Some(data_type)) => data_type,
+            (_, Some(data_type)) => {
+                check_comparable(&left, &right)?;
+                data_type
+            }
+            (_, None) => {
+                if !is_numeric(&left_type) || !is_numeric(&right_type) {
+                    return invalid(format!("{:?} of {:?} and {:?}", op, left_type, right_type));
+                }
+                numeric_type(&left_type, &right_type)
+            }
+        };
+
+        Ok(Expression::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+            data_type,
+        })
+    }
+
+    fn function(&self, function: &ast::Function) -> Result<Expression> {
+        let name = function_name(function);
+        if is_aggregate_function(name.as_str()) {
+            return invalid(format!(
+                "aggregate function `{}` is not allowed here",
+                function
+            ));
+        }
+        if let Some(expression) = window_function(name.as_str()) {
+            if !self.aggregated {
+                return invalid(format!(
+                    "`{}` is allowed in the window aggregation only",
+                    function
+                ));
+            }
+            return Ok(expression);
+        }
+
+        let scalar_function = match name.as_str() {
+            "upper" => ScalarFunction::Upper,
+            "lower" => ScalarFunction::Lower,
+            "char_length" | "length" => ScalarFunction::CharLength,
+            "abs" => ScalarFunction::Abs,
+            _ => return unsupported(format!("function `{}`", function)),
+        };
+
+        let args = match function_args(function)?.as_slice() {
+            [Some(arg)] => vec![self.compile(arg)?],
+            _ => return invalid(format!("`{}` takes one argument", function)),
+        };
+        let arg_type = args[0].data_type();
+        let data_type = match scalar_function {
+            ScalarFunction::Upper | ScalarFunction::Lower => DataType::String,
+            ScalarFunction::CharLength => DataType::UInt64,
+            ScalarFunction::Abs if is_float(&arg_type) => DataType::Float64,
+            ScalarFunction::Abs if is_unsigned(&arg_type) => DataType::UInt64,
+            ScalarFunction::Abs if is_numeric(&arg_type) => DataType::Int64,
+            ScalarFunction::Abs => return invalid(format!("abs of {:?}", arg_type)),
+        };
+
+        Ok(Expression::Function(scalar_function, args, data_type))
+    }
+}
+
+fn check_comparable(left: &Expression, right: &Expression) -> Result<()> {
+    let (left_type, right_type) = (left.data_type(), right.data_type());
+    if left_type == right_type || (is_numeric(&left_type) && is_numeric(&right_type)) {
+        Ok(())
+    } else {
+        invalid(format!("compare {:?} with {:?}", left_type, right_type))
+    }
+}
+
+fn literal(value: &ast::Value) -> Result<Expression> {
+    let (value, data_type) = match value {
+        ast::Value::Boolean(v) => (Value::Boolean(*v), DataType::Boolean),
+        ast::Value::SingleQuotedString(v) => (Value::String(v.clone()), DataType::String),
+        ast::Value::Number(v, _) => {
+            if let Ok(n) = v.parse::<i64>() {
+                (Value::Int(n), DataType::Int64)
+            } else if let Ok(n) = v.parse::<u64>() {
+                (Value::UInt(n), DataType::UInt64)
+            } else if let Ok(n) = v.parse::<f64>() {
+                (Value::Float(n), DataType::Float64)
+            } else {
+                return invalid(format!("number `{}`", v));
+            }
+        }
+        _ => return unsupported(format!("literal `{}`", value)),
+    };
+    Ok(Expression::Literal(value, data_type))
+}
+
+/// The field type of the sql type
+pub(crate) fn sql_data_type(data_type: &ast::DataType) -> Result<DataType> {
+    let data_type = match data_type {
+        ast::DataType::Boolean => DataType::Boolean,
+        ast::DataType::TinyInt(_) => DataType::Int8,
+        ast::DataType::UnsignedTinyInt(_) => DataType::UInt8,
+        ast::DataType::SmallInt(_) => DataType::Int16,
+        ast::DataType::UnsignedSmallInt(_) => DataType::UInt16,
+        ast::DataType::Int(_) | ast::DataType::Integer(_) => DataType::Int32,
+        ast::DataType::UnsignedInt(_) => DataType::UInt32,
+        ast::DataType::BigInt(_) => DataType::Int64,
+        ast::DataType::UnsignedBigInt(_) => DataType::UInt64,
+        ast::DataType::Float(_) | ast::DataType::Real => DataType::Float32,
+        ast::DataType::Double | ast::DataType::DoublePrecision => DataType::Float64,
+        ast::DataType::Varchar(_) | ast::DataType::Char(_) | ast::DataType::Text => {
+            DataType::String
+        }
+        ast::DataType::Binary(_) | ast::DataType::Varbinary(_) | ast::DataType::Bytea => {
+            DataType::Binary
+        }
+        _ => return unsupported(format!("data type `{}`", data_type)),
+    };
+    Ok(data_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use rlink::core::window::{TimeWindow, Window};
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    use crate::error::SqlError;
+    use crate::expr::{ExprCompiler, Expression, Scope};
+    use crate::value::Value;
+
+    fn scope() -> Scope {
+        let schema = Schema::new(vec![
+            Field::new("name", DataType::String),
+            Field::new("value", DataType::Int32),
+            Field::new("price", DataType::Float64),
+            Field::new("ts", DataType::UInt64),
+        ]);
+        Scope::new("t", &schema, Some("ts"))
+    }
+
+    fn row() -> Vec<Value> {
+        vec![
+            Value::String("rlink".to_string()),
+            Value::Int(-3),
+            Value::Float(2.5),
+            Value::UInt(1000),
+        ]
+    }
+
+    fn compile(sql: &str) -> Result<Expression, SqlError> {
+        let expr = Parser::new(&GenericDialect {})
+            .try_with_sql(sql)?
+            .parse_expr()?;
+        ExprCompiler::new(&scope()).compile(&expr)
+    }
+
+    fn eval(sql: &str) -> Value {
+        compile(sql).unwrap().eval(row().as_slice(), None).unwrap()
+    }
+
+    #[test]
+    pub fn expr_eval_test() {
+        assert_eq!(eval("value * 2 + 1"), Value::Int(-5));
+        assert_eq!(eval("t.ts / 10"), Value::Int(100));
+        assert_eq!(eval("price * value"), Value::Float(-7.5));
+        assert_eq!(
+            eval("value < 0 AND NOT name = 'flink'"),
+            Value::Boolean(true)
+        );
+        assert_eq!(eval("ts BETWEEN 500 AND 1000"), Value::Boolean(true));
+        assert_eq!(eval("value IN (1, 2, 3)"), Value::Boolean(false));
+        assert_eq!(
+            eval("upper(name) || '-' || CAST(abs(value) AS VARCHAR)"),
+            Value::String("RLINK-3".to_string())
+        );
+
+        let expr = compile("CAST(price AS BIGINT)").unwrap();
+        assert_eq!(expr.data_type(), DataType::Int64);
+        assert_eq!(expr.eval(row().as_slice(), None).unwrap(), Value::Int(2));
+    }
+
+    #[test]
+    pub fn expr_compile_error_test() {
+        assert!(matches!(
+            compile("unknown + 1"),
+            Err(SqlError::ColumnNotFound(_))
+        ));
+        assert!(matches!(compile("name + 1"), Err(SqlError::Invalid(_))));
+        assert!(matches!(compile("name = 1"), Err(SqlError::Invalid(_))));
+        assert!(matches!(compile("sum(value)"), Err(SqlError::Invalid(_))));
+        assert!(matches!(
+            compile("tumble_start(ts, INTERVAL '1' MINUTE)"),
+            Err(SqlError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    pub fn expr_window_test() {
+        let window = Window::TimeWindow(TimeWindow::new(1000, 2000));
+        assert_eq!(
+            Expression::WindowEnd
+                .eval(row().as_slice(), Some(&window))
+                .unwrap(),
+            Value::UInt(2000)
+        );
+        assert!(Expression::WindowStart
+            .eval(row().as_slice(), None)
+            .is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use rlink::core;
+use rlink::core::data_types::Schema;
+use rlink::core::element::{Element, FnSchema, Record};
+use rlink::core::function::{
+    Context, FilterFunction, FlatMapFunction, JoinFunction, SendableElementStream,
+};
+use rlink::utils::stream::MemoryStream;
+
+use crate::expr::Expression;
+use crate::value::{read_row, write_row, Value};
+
+/// The `WHERE` and `HAVING` clauses, the records failed to evaluate are dropped
+#[derive(Debug, Function)]
+pub struct SqlFilterFunction {
+    predicate: Expression,
+    schema: Schema,
+}
+
+impl SqlFilterFunction {
+    pub(crate) fn new(predicate: Expression) -> Self {
+        SqlFilterFunction {
+            predicate,
+            schema: Schema::empty(),
+        }
+    }
+
+    fn eval(&self, record: &mut Record) -> anyhow::Result<bool> {
+        let window = record.trigger_window();
+        let row = read_row(record, &self.schema)?;
+        self.predicate
+            .eval(row.as_slice(), window.as_ref())?
+            .as_bool()
+    }
+}
+
+#[async_trait]
+impl FilterFunction for SqlFilterFunction {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.first().clone();
+        Ok(())
+    }
+
+    async fn filter(&self, record: &mut Record) -> bool {
+        match self.eval(record) {
+            Ok(v) => v,
+            Err(e) => {
+                warn!("drop the record failed to evaluate the predicate. {}", e);
+                false
+            }
+        }
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+/// The `SELECT` list, the projected records keep the timestamps and the windows of the input
+/// records, the records failed to evaluate are dropped
+#[derive(Debug, Function)]
+pub struct SqlProjectFunction {
+    expressions: Vec<Expression>,
+    output_schema: Schema,
+    schema: Schema,
+}
+
+impl SqlProjectFunction {
+    pub(crate) fn new(expressions: Vec<Expression>, output_schema: Schema) -> Self {
+        SqlProjectFunction {
+            expressions,
+            output_schema,
+            schema: Schema::empty(),
+        }
+    }
+
+    fn project(&self, record: &mut Record) -> anyhow::Result<Record> {
+        let window = record.trigger_window();
+        let row = read_row(record, &self.schema)?;
+
+        let values = self
+            .expressions
+            .iter()
+            .map(|x| x.eval(row.as_slice(), window.as_ref()))
+            .collect::<anyhow::Result<Vec<Value>>>()?;
+
+        let mut projected = write_row(values.as_slice(), &self.output_schema)?;
+        projected.set_timestamp(record.timestamp());
+        if let Some(window) = window {
+            projected.set_window_trigger(window);
+        }
+        Ok(projected)
+    }
+}
+
+#[async_trait]
+impl FlatMapFunction for SqlProjectFunction {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        self.schema = context.input_schema.first().clone();
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+        let records = match self.project(&mut record) {
+            Ok(projected) => vec![projected],
+            Err(e) => {
+                warn!("drop the record failed to evaluate the projection. {}", e);
+                vec![]
+            }
+        };
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::Single(self.output_schema.clone())
+    }
+}
+
+/// The inner join of the `FROM` clause, the joined record has the fields of the left record
+/// followed by the fields of the right one
+#[derive(Debug, NamedFunction)]
+pub struct SqlJoinFunction {
+    left_schema: Schema,
+    right_schema: Schema,
+    joined_schema: Schema,
+    parallelism: u16,
+}
+
+impl SqlJoinFunction {
+    pub(crate) fn new(left_schema: Schema, right_schema: Schema, parallelism: u16) -> Self {
+        let fields = left_schema.fields().iter().chain(right_schema.fields());
+        let joined_schema = Schema::new(fields.cloned().collect());
+        SqlJoinFunction {
+            left_schema,
+            right_schema,
+            joined_schema,
+            parallelism,
+        }
+    }
+
+    fn concat(&self, left: &mut Record, right: &mut Record) -> anyhow::Result<Record> {
+        let mut row = read_row(left, &self.left_schema)?;
+        row.extend(read_row(right, &self.right_schema)?);
+        write_row(row.as_slice(), &self.joined_schema)
+    }
+}
+
+#[async_trait]
+impl JoinFunction for SqlJoinFunction {
+    async fn open(&mut self, _context: &Context) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn join(&self, left: &mut Record, right: &mut Record) -> Record {
+        self.concat(left, right).expect("join the records error")
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _left_schema: FnSchema, _right_schema: FnSchema) -> FnSchema {
+        FnSchema::Single(self.joined_schema.clone())
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
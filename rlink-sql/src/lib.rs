@@ -0,0 +1,20 @@
+#[macro_use]
+extern crate log;
+#[macro_use]
+extern crate rlink_derive;
+#[macro_use]
+extern crate anyhow;
+#[macro_use]
+extern crate async_trait;
+
+pub mod catalog;
+pub mod error;
+pub mod planner;
+
+mod expr;
+mod functions;
+mod value;
+
+pub use catalog::{Catalog, RowTime, SinkTable, SourceTable};
+pub use error::SqlError;
+pub use planner::SqlPlanner;
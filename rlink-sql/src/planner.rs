@@ -0,0 +1,876 @@
+use std::time::Duration;
+
+use rlink::core::data_stream::{
+    DataStream, SinkStream, TDataStream, TKeyedStream, TWindowedStream,
+};
+use rlink::core::data_types::{DataType, Field, Schema};
+use rlink::core::env::StreamExecutionEnvironment;
+use rlink::functions::key_selector::SchemaKeySelector;
+use rlink::functions::reduce::{count, max, min, sum, AggregationDescriptor, SchemaReduceFunction};
+use rlink::functions::watermark::DefaultWatermarkStrategy;
+use rlink::functions::window::{EventTimeSessionWindows, SlidingEventTimeWindows};
+use sqlparser::ast;
+use sqlparser::ast::{
+    BinaryOperator, Ident, JoinConstraint, JoinOperator, ObjectName, Query, Select, SelectItem,
+    SetExpr, Statement, TableFactor, TableWithJoins,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+use crate::catalog::{Catalog, SourceTable};
+use crate::error::{invalid, unsupported, Result, SqlError};
+use crate::expr::{
+    function_args, function_name, is_aggregate_function, BinaryOp, ExprCompiler, Expression, Scope,
+};
+use crate::functions::{SqlFilterFunction, SqlJoinFunction, SqlProjectFunction};
+use crate::value::{is_float, is_numeric};
+
+/// The event time window of the `GROUP BY` clause
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum WindowPlan {
+    /// `TUMBLE(row_time, size)`
+    Tumble(Duration),
+    /// `HOP(row_time, slide, size)`
+    Hop { slide: Duration, size: Duration },
+    /// `SESSION(row_time, gap)`
+    Session(Duration),
+}
+
+#[derive(Debug)]
+pub(crate) enum FromPlan {
+    Table(String),
+    /// The inner equi-join of two tables in the join window
+    Join {
+        left: String,
+        right: String,
+        left_keys: Vec<usize>,
+        right_keys: Vec<usize>,
+        left_schema: Schema,
+        right_schema: Schema,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct AggregatePlan {
+    /// the group keys followed by the arguments of the aggregate functions, they're computed
+    /// before the records are keyed
+    pub pre_projection: Vec<Expression>,
+    pub pre_schema: Schema,
+    pub key_count: usize,
+    pub aggregations: Vec<AggregationDescriptor>,
+    pub window: WindowPlan,
+}
+
+/// The analyzed `INSERT INTO .. SELECT ..` statement, it's built onto the operators as
+/// source -> [join] -> [filter] -> [window aggregate] -> [having filter] -> projection -> sink
+#[derive(Debug)]
+pub(crate) struct QueryPlan {
+    pub sink: String,
+    pub from: FromPlan,
+    pub filter: Option<Expression>,
+    pub aggregate: Option<AggregatePlan>,
+    pub having: Option<Expression>,
+    pub projection: Vec<Expression>,
+    pub output_schema: Schema,
+}
+
+/// Plan the streaming sql statements onto the operators of the `StreamExecutionEnvironment`.
+///
+/// Each statement is an `INSERT INTO sink SELECT .. FROM ..` query of the tables of the
+/// `Catalog`, it supports
+/// - the projections and the `WHERE` filters of the expressions
+/// - the inner equi-join of two tables, the records are joined in the tumbling event time
+///   windows of `join_window`
+/// - the `COUNT`, `SUM`, `MIN`, `MAX` and `AVG` aggregations grouped by the keys and a
+///   `TUMBLE`, `HOP` or `SESSION` window of the row time, and the `HAVING` filters. The window
+///   bounds are selected by `TUMBLE_START`, `TUMBLE_END` and so on
+///
+/// The values are never null, so the outer joins and the unbounded aggregations are not
+/// supported
+pub struct SqlPlanner<'a> {
+    catalog: &'a Catalog,
+    parallelism: u16,
+    join_window: Duration,
+}
+
+impl<'a> SqlPlanner<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        SqlPlanner {
+            catalog,
+            parallelism: 1,
+            join_window: Duration::from_secs(60),
+        }
+    }
+
+    /// The parallelism of the joins and the window aggregations
+    pub fn parallelism(mut self, parallelism: u16) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// The size of the tumbling windows the joined records must be in
+    pub fn join_window(mut self, join_window: Duration) -> Self {
+        self.join_window = join_window;
+        self
+    }
+
+    /// Plan the `;` separated statements, nothing is added to the `env` if any statement is
+    /// invalid
+    pub fn plan(&self, env: &mut StreamExecutionEnvironment, sql: &str) -> Result<Vec<SinkStream>> {
+        let statements = Parser::parse_sql(&GenericDialect {}, sql)?;
+        let plans = statements
+            .iter()
+            .map(|x| self.analyze(x))
+            .collect::<Result<Vec<QueryPlan>>>()?;
+
+        let sink_streams = plans.into_iter().map(|x| self.build(env, x)).collect();
+        Ok(sink_streams)
+    }
+
+    pub(crate) fn analyze(&self, statement: &Statement) -> Result<QueryPlan> {
+        let (table_name, columns, query) = match statement {
+            Statement::Insert {
+                table_name,
+                columns,
+                source,
+                ..
+            } => (table_name, columns, source),
+            _ => return unsupported(format!("statement `{}`, only INSERT INTO", statement)),
+        };
+
+        let sink = self.catalog.sink(object_name(table_name)?.as_str())?;
+        let select = query_select(query)?;
+
+        let (from, scope) = self.analyze_from(select)?;
+        let filter = match &select.selection {
+            Some(selection) => Some(predicate(&ExprCompiler::new(&scope), selection)?),
+            None => None,
+        };
+
+        let projection = select_items(select, &scope)?;
+        let has_aggregate = projection.iter().any(|x| {
+            let mut calls = Vec::new();
+            collect_aggregates(x, &mut calls);
+            !calls.is_empty()
+        });
+
+        let (aggregate, having, projection) = if select.group_by.is_empty() && !has_aggregate {
+            if select.having.is_some() {
+                return invalid("HAVING without GROUP BY");
+            }
+            let compiler = ExprCompiler::new(&scope);
+            let projection = projection
+                .iter()
+                .map(|x| compiler.compile(x))
+                .collect::<Result<Vec<Expression>>>()?;
+            (None, None, projection)
+        } else {
+            self.analyze_aggregate(select, &scope, projection.as_slice())?
+        };
+
+        let output_schema = sink.schema().clone();
+        let projection = sink_projection(projection, columns.as_slice(), &output_schema)?;
+
+        Ok(QueryPlan {
+            sink: sink.name().to_string(),
+            from,
+            filter,
+            aggregate,
+            having,
+            projection,
+            output_schema,
+        })
+    }
+
+    fn analyze_from(&self, select: &Select) -> Result<(FromPlan, Scope)> {
+        let table_with_joins: &TableWithJoins = match select.from.as_slice() {
+            [table_with_joins] => table_with_joins,
+            [] => return invalid("SELECT without FROM"),
+            _ => return unsupported("multiple tables in FROM, use JOIN .. ON"),
+        };
+
+        let (left, left_scope) = self.table_scope(&table_with_joins.relation)?;
+        let join = match table_with_joins.joins.as_slice() {
+            [] => return Ok((FromPlan::Table(left.name().to_string()), left_scope)),
+            [join] => join,
+            _ => return unsupported("more than one JOIN"),
+        };
+
+        let on = match &join.join_operator {
+            JoinOperator::Inner(JoinConstraint::On(on)) => on,
+            _ => return unsupported(format!("join `{}`, only INNER JOIN .. ON", join)),
+        };
+        let (right, right_scope) = self.table_scope(&join.relation)?;
+        if left.get_row_time().is_none() || right.get_row_time().is_none() {
+            return invalid("the joined tables must have the row time");
+        }
+
+        let mut left_keys = Vec::new();
+        let mut right_keys = Vec::new();
+        for (l, r) in equi_join_keys(on)? {
+            let (left_key, right_key) = match (left_scope.resolve(l), right_scope.resolve(r)) {
+                (Ok(left_key), Ok(right_key)) => (left_key, right_key),
+                _ => (left_scope.resolve(r)?, right_scope.resolve(l)?),
+            };
+            let left_type = &left_scope.columns[left_key].data_type;
+            let right_type = &right_scope.columns[right_key].data_type;
+            if left_type != right_type {
+                return invalid(format!(
+                    "the join keys must be the same type, {:?} and {:?}",
+                    left_type, right_type
+                ));
+            }
+            left_keys.push(left_key);
+            right_keys.push(right_key);
+        }
+
+        let from = FromPlan::Join {
+            left: left.name().to_string(),
+            right: right.name().to_string(),
+            left_keys,
+            right_keys,
+            left_schema: left.schema().clone(),
+            right_schema: right.schema().clone(),
+        };
+        Ok((from, left_scope.join(&right_scope)))
+    }
+
+    fn table_scope(&self, table_factor: &TableFactor) -> Result<(&'a SourceTable, Scope)> {
+        let (name, alias) = match table_factor {
+            TableFactor::Table { name, alias, .. } => (name, alias),
+            _ => return unsupported(format!("table `{}`", table_factor)),
+        };
+
+        let table = self.catalog.source(object_name(name)?.as_str())?;
+        let qualifier = match alias {
+            Some(alias) => alias.name.value.clone(),
+            None => table.name().to_string(),
+        };
+        let row_time = table.get_row_time().map(|x| x.column.as_str());
+        let scope = Scope::new(qualifier.as_str(), table.schema(), row_time);
+        Ok((table, scope))
+    }
+
+    fn analyze_aggregate(
+        &self,
+        select: &Select,
+        scope: &Scope,
+        projection: &[ast::Expr],
+    ) -> Result<(Option<AggregatePlan>, Option<Expression>, Vec<Expression>)> {
+        let compiler = ExprCompiler::new(scope);
+
+        let mut window = None;
+        let mut pre_projection = Vec::new();
+        let mut replacements = Vec::new();
+        for expr in &select.group_by {
+            if let Some(window_plan) = group_window(expr, &compiler, scope)? {
+                if window.replace(window_plan).is_some() {
+                    return unsupported("more than one window in GROUP BY");
+                }
+                continue;
+            }
+
+            let key = compiler.compile(expr)?;
+            let data_type = key.data_type();
+            replacements.push((
+                expr.clone(),
+                Expression::Column(pre_projection.len(), data_type),
+            ));
+            pre_projection.push(key);
+        }
+
+        let window = match window {
+            Some(window) => window,
+            None => {
+                return unsupported(
+                    "the aggregations must be grouped by a TUMBLE, HOP or SESSION window",
+                )
+            }
+        };
+        let key_count = pre_projection.len();
+        if key_count == 0 {
+            return unsupported("GROUP BY without any key besides the window");
+        }
+
+        let mut calls = Vec::new();
+        for expr in projection.iter().chain(select.having.iter()) {
+            collect_aggregates(expr, &mut calls);
+        }
+
+        let mut aggregations = Vec::new();
+        for call in calls {
+            let name = function_name(call);
+            if call.distinct || call.over.is_some() {
+                return unsupported(format!("aggregate `{}`", call));
+            }
+
+            let arg = match (name.as_str(), function_args(call)?.as_slice()) {
+                ("count", [_]) => None,
+                (_, [Some(arg)]) => Some(compiler.compile(arg)?),
+                _ => return invalid(format!("`{}` takes one argument", call)),
+            };
+            let arg_index = match arg {
+                Some(arg) => {
+                    if !is_numeric(&arg.data_type()) {
+                        return invalid(format!("`{}` of {:?}", call, arg.data_type()));
+                    }
+                    let index = match pre_projection.iter().position(|x| x == &arg) {
+                        Some(index) => index,
+                        None => {
+                            pre_projection.push(arg);
+                            pre_projection.len() - 1
+                        }
+                    };
+                    Some((index, pre_projection[index].data_type()))
+                }
+                None => None,
+            };
+
+            // the aggregated value is at the index of the reduced record, after the keys
+            let mut aggregate = |descriptor: AggregationDescriptor, data_type: DataType| {
+                aggregations.push(descriptor);
+                Expression::Column(key_count + aggregations.len() - 1, data_type)
+            };
+            let expression = match (name.as_str(), arg_index) {
+                ("count", _) => aggregate(count(), DataType::UInt64),
+                ("sum", Some((index, data_type))) => aggregate(sum(index), data_type),
+                ("min", Some((index, data_type))) => aggregate(min(index), data_type),
+                ("max", Some((index, data_type))) => aggregate(max(index), data_type),
+                ("avg", Some((index, data_type))) => {
+                    let sum_column = aggregate(sum(index), data_type);
+                    let count_column = aggregate(count(), DataType::UInt64);
+                    Expression::Binary {
+                        op: BinaryOp::Divide,
+                        left: Box::new(Expression::Cast(Box::new(sum_column), DataType::Float64)),
+                        right: Box::new(Expression::Cast(
+                            Box::new(count_column),
+                            DataType::Float64,
+                        )),
+                        data_type: DataType::Float64,
+                    }
+                }
+                _ => return invalid(format!("aggregate `{}`", call)),
+            };
+            replacements.push((ast::Expr::Function(call.clone()), expression));
+        }
+
+        let compiler = ExprCompiler::aggregated(scope, replacements);
+        let having = match &select.having {
+            Some(having) => Some(predicate(&compiler, having)?),
+            None => None,
+        };
+        let projection = projection
+            .iter()
+            .map(|x| compiler.compile(x))
+            .collect::<Result<Vec<Expression>>>()?;
+
+        let fields = pre_projection
+            .iter()
+            .enumerate()
+            .map(|(i, x)| Field::new(format!("_c{}", i).as_str(), x.data_type()))
+            .collect();
+        let aggregate = AggregatePlan {
+            pre_projection,
+            pre_schema: Schema::new(fields),
+            key_count,
+            aggregations,
+            window,
+        };
+        Ok((Some(aggregate), having, projection))
+    }
+
+    fn build(&self, env: &mut StreamExecutionEnvironment, plan: QueryPlan) -> SinkStream {
+        let mut data_stream = match plan.from {
+            FromPlan::Table(table) => self.source_stream(env, table.as_str()),
+            FromPlan::Join {
+                left,
+                right,
+                left_keys,
+                right_keys,
+                left_schema,
+                right_schema,
+            } => {
+                let left = self.source_stream(env, left.as_str());
+                let right = self.source_stream(env, right.as_str());
+                left.join(right)
+                    .where_key(SchemaKeySelector::new(left_keys))
+                    .equal_to(SchemaKeySelector::new(right_keys))
+                    .window(SlidingEventTimeWindows::new(
+                        self.join_window,
+                        self.join_window,
+                        None,
+                    ))
+                    .process(SqlJoinFunction::new(
+                        left_schema,
+                        right_schema,
+                        self.parallelism,
+                    ))
+            }
+        };
+
+        if let Some(filter) = plan.filter {
+            data_stream = data_stream.filter(SqlFilterFunction::new(filter));
+        }
+
+        if let Some(aggregate) = plan.aggregate {
+            data_stream = self.window_aggregate(data_stream, aggregate);
+        }
+
+        if let Some(having) = plan.having {
+            data_stream = data_stream.filter(SqlFilterFunction::new(having));
+        }
+
+        let data_stream =
+            data_stream.flat_map(SqlProjectFunction::new(plan.projection, plan.output_schema));
+        let sink = self.catalog.sink(plan.sink.as_str()).unwrap();
+        sink.add_sink(data_stream)
+    }
+
+    fn source_stream(&self, env: &mut StreamExecutionEnvironment, table: &str) -> DataStream {
+        let table = self.catalog.source(table).unwrap();
+        let data_stream = table.create_stream(env);
+        match table.get_row_time() {
+            Some(row_time) => data_stream.assign_timestamps_and_watermarks(
+                DefaultWatermarkStrategy::new()
+                    .for_bounded_out_of_orderness(row_time.out_of_orderness)
+                    .for_schema_timestamp_assigner(row_time.column.as_str()),
+            ),
+            None => data_stream,
+        }
+    }
+
+    fn window_aggregate(&self, data_stream: DataStream, aggregate: AggregatePlan) -> DataStream {
+        let keys: Vec<usize> = (0..aggregate.key_count).collect();
+        let keyed_stream = data_stream
+            .flat_map(SqlProjectFunction::new(
+                aggregate.pre_projection,
+                aggregate.pre_schema,
+            ))
+            .key_by(SchemaKeySelector::new(keys));
+
+        let reduce = SchemaReduceFunction::new(aggregate.aggregations, self.parallelism);
+        match aggregate.window {
+            WindowPlan::Tumble(size) => keyed_stream
+                .window(SlidingEventTimeWindows::new(size, size, None))
+                .reduce(reduce),
+            WindowPlan::Hop { slide, size } => keyed_stream
+                .window(SlidingEventTimeWindows::new(size, slide, None))
+                .reduce(reduce),
+            WindowPlan::Session(gap) => keyed_stream
+                .window(EventTimeSessionWindows::with_gap(gap))
+                .reduce(reduce),
+        }
+    }
+}
+
+fn object_name(name: &ObjectName) -> Result<String> {
+    match name.0.as_slice() {
+        [ident] => Ok(ident.value.clone()),
+        _ => unsupported(format!("table name `{}`", name)),
+    }
+}
+
+fn query_select(query: &Query) -> Result<&Select> {
+    if query.with.is_some() || !query.order_by.is_empty() || query.limit.is_some() {
+        return unsupported(format!("query `{}`, WITH, ORDER BY and LIMIT", query));
+    }
+    match query.body.as_ref() {
+        SetExpr::Select(select) => {
+            if select.distinct.is_some() {
+                return unsupported("SELECT DISTINCT");
+            }
+            Ok(select)
+        }
+        SetExpr::Query(query) => query_select(query),
+        body => unsupported(format!("query `{}`", body)),
+    }
+}
+
+/// The expressions of the `SELECT` list, the wildcards are expanded to the columns
+fn select_items(select: &Select, scope: &Scope) -> Result<Vec<ast::Expr>> {
+    let column_expr = |i: usize| {
+        let column = &scope.columns[i];
+        let mut idents = Vec::new();
+        if let Some(qualifier) = &column.qualifier {
+            idents.push(Ident::new(qualifier.as_str()));
+        }
+        idents.push(Ident::new(column.name.as_str()));
+        ast::Expr::CompoundIdentifier(idents)
+    };
+
+    let mut exprs = Vec::new();
+    for item in &select.projection {
+        match item {
+            SelectItem::UnnamedExpr(expr) => exprs.push(expr.clone()),
+            SelectItem::ExprWithAlias { expr, .. } => exprs.push(expr.clone()),
+            SelectItem::Wildcard(_) => exprs.extend((0..scope.columns.len()).map(column_expr)),
+            SelectItem::QualifiedWildcard(name, _) => {
+                let qualifier = object_name(name)?;
+                let columns = (0..scope.columns.len())
+                    .filter(|i| scope.columns[*i].qualifier.as_ref() == Some(&qualifier))
+                    .map(column_expr)
+                    .collect::<Vec<ast::Expr>>();
+                if columns.is_empty() {
+                    return Err(SqlError::TableNotFound(qualifier));
+                }
+                exprs.extend(columns);
+            }
+        }
+    }
+    Ok(exprs)
+}
+
+fn predicate(compiler: &ExprCompiler, expr: &ast::Expr) -> Result<Expression> {
+    let predicate = compiler.compile(expr)?;
+    if predicate.data_type() != DataType::Boolean {
+        return invalid(format!("the predicate `{}` is not boolean", expr));
+    }
+    Ok(predicate)
+}
+
+/// The `left = right` column pairs of the `AND` conjunction
+fn equi_join_keys(on: &ast::Expr) -> Result<Vec<(&[Ident], &[Ident])>> {
+    fn idents(expr: &ast::Expr) -> Option<&[Ident]> {
+        match expr {
+            ast::Expr::Identifier(ident) => Some(std::slice::from_ref(ident)),
+            ast::Expr::CompoundIdentifier(idents) => Some(idents.as_slice()),
+            ast::Expr::Nested(expr) => idents(expr),
+            _ => None,
+        }
+    }
+
+    match on {
+        ast::Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            let mut keys = equi_join_keys(left)?;
+            keys.extend(equi_join_keys(right)?);
+            Ok(keys)
+        }
+        ast::Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Eq,
+            right,
+        } => match (idents(left), idents(right)) {
+            (Some(left), Some(right)) => Ok(vec![(left, right)]),
+            _ => unsupported(format!(
+                "join condition `{}`, only the column equalities",
+                on
+            )),
+        },
+        ast::Expr::Nested(expr) => equi_join_keys(expr),
+        _ => unsupported(format!(
+            "join condition `{}`, only the column equalities",
+            on
+        )),
+    }
+}
+
+/// The aggregate calls of the expression, the duplicate calls are collected once
+fn collect_aggregates<'e>(expr: &'e ast::Expr, calls: &mut Vec<&'e ast::Function>) {
+    match expr {
+        ast::Expr::Function(function)
+            if is_aggregate_function(function_name(function).as_str()) =>
+        {
+            if !calls.contains(&function) {
+                calls.push(function);
+            }
+        }
+        ast::Expr::Function(function) => {
+            for arg in function_args(function)
+                .unwrap_or_default()
+                .into_iter()
+                .flatten()
+            {
+                collect_aggregates(arg, calls);
+            }
+        }
+        ast::Expr::BinaryOp { left, right, .. } => {
+            collect_aggregates(left, calls);
+            collect_aggregates(right, calls);
+        }
+        ast::Expr::UnaryOp { expr, .. }
+        | ast::Expr::Nested(expr)
+        | ast::Expr::Cast { expr, .. } => collect_aggregates(expr, calls),
+        ast::Expr::Between {
+            expr, low, high, ..
+        } => {
+            collect_aggregates(expr, calls);
+            collect_aggregates(low, calls);
+            collect_aggregates(high, calls);
+        }
+        ast::Expr::InList { expr, list, .. } => {
+            collect_aggregates(expr, calls);
+            for item in list {
+                collect_aggregates(item, calls);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The window of the `TUMBLE`, `HOP` or `SESSION` group expression, it must be defined on the
+/// row time column
+fn group_window(
+    expr: &ast::Expr,
+    compiler: &ExprCompiler,
+    scope: &Scope,
+) -> Result<Option<WindowPlan>> {
+    let function = match expr {
+        ast::Expr::Function(function) => function,
+        _ => return Ok(None),
+    };
+    let name = function_name(function);
+    if !matches!(name.as_str(), "tumble" | "hop" | "session") {
+        return Ok(None);
+    }
+
+    let args = function_args(function)?
+        .into_iter()
+        .collect::<Option<Vec<&ast::Expr>>>()
+        .unwrap_or_default();
+    match args.first().map(|x| compiler.compile(x)).transpose()? {
+        Some(Expression::Column(index, _)) if scope.columns[index].row_time => {}
+        _ => return invalid(format!("`{}` must be on the row time column", function)),
+    }
+
+    let window = match (name.as_str(), args.as_slice()) {
+        ("tumble", [_, size]) => WindowPlan::Tumble(interval(size)?),
+        ("hop", [_, slide, size]) => WindowPlan::Hop {
+            slide: interval(slide)?,
+            size: interval(size)?,
+        },
+        ("session", [_, gap]) => WindowPlan::Session(interval(gap)?),
+        _ => return invalid(format!("window `{}`", function)),
+    };
+    Ok(Some(window))
+}
+
+/// The duration of `INTERVAL '10' SECOND` or `INTERVAL '10 seconds'`
+fn interval(expr: &ast::Expr) -> Result<Duration> {
+    let interval = match expr {
+        ast::Expr::Interval(interval) => interval,
+        _ => return invalid(format!("`{}` is not an interval", expr)),
+    };
+
+    let value = match interval.value.as_ref() {
+        ast::Expr::Value(ast::Value::SingleQuotedString(v)) => v.clone(),
+        ast::Expr::Value(ast::Value::Number(v, _)) => v.clone(),
+        _ => return unsupported(format!("interval `{}`", expr)),
+    };
+    let (n, unit) = match &interval.leading_field {
+        Some(field) => (value.trim().to_string(), field.to_string()),
+        None => match value.split_once(' ') {
+            Some((n, unit)) => (n.trim().to_string(), unit.trim().to_string()),
+            None => return invalid(format!("interval `{}` without unit", expr)),
+        },
+    };
+
+    let n = n
+        .parse::<u64>()
+        .map_err(|_e| SqlError::Invalid(format!("interval `{}`", expr)))?;
+    let unit = unit.to_lowercase();
+    let millis = match unit.trim_end_matches('s') {
+        "millisecond" => 1,
+        "second" => 1000,
+        "minute" => 60 * 1000,
+        "hour" => 60 * 60 * 1000,
+        "day" => 24 * 60 * 60 * 1000,
+        _ => return unsupported(format!("interval unit `{}`", unit)),
+    };
+    if n == 0 {
+        return invalid(format!("interval `{}` must be positive", expr));
+    }
+    Ok(Duration::from_millis(n * millis))
+}
+
+/// Order the projection by the columns of the sink, the values are converted to the field
+/// types when they're written
+fn sink_projection(
+    projection: Vec<Expression>,
+    columns: &[Ident],
+    schema: &Schema,
+) -> Result<Vec<Expression>> {
+    if projection.len() != schema.fields().len() {
+        return invalid(format!(
+            "the query has {} columns but the sink has {}",
+            projection.len(),
+            schema.fields().len()
+        ));
+    }
+
+    let projection = if columns.is_empty() {
+        projection
+    } else {
+        if columns.len() != projection.len() {
+            return invalid("the INSERT columns must be all columns of the sink");
+        }
+        let mut ordered = vec![None; projection.len()];
+        for (column, expression) in columns.iter().zip(projection) {
+            let index = schema
+                .index_of(column.value.as_str())
+                .ok_or_else(|| SqlError::ColumnNotFound(column.value.clone()))?;
+            if ordered[index].replace(expression).is_some() {
+                return invalid(format!("duplicate INSERT column `{}`", column.value));
+            }
+        }
+        ordered.into_iter().map(|x| x.unwrap()).collect()
+    };
+
+    for (expression, field) in projection.iter().zip(schema.fields()) {
+        if !assignable(&expression.data_type(), field.data_type()) {
+            return invalid(format!(
+                "assign {:?} to the column `{}` of {:?}",
+                expression.data_type(),
+                field.name(),
+                field.data_type()
+            ));
+        }
+    }
+    Ok(projection)
+}
+
+fn assignable(from: &DataType, to: &DataType) -> bool {
+    from == to
+        || to == &DataType::String
+        || (is_numeric(from) && is_numeric(to) && (is_float(to) || !is_float(from)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rlink::core::data_types::{DataType, Field, Schema};
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    use crate::catalog::{Catalog, SinkTable, SourceTable};
+    use crate::error::SqlError;
+    use crate::planner::{FromPlan, QueryPlan, SqlPlanner, WindowPlan};
+
+    fn catalog() -> Catalog {
+        let orders = Schema::new(vec![
+            Field::new("user_id", DataType::Int64),
+            Field::new("amount", DataType::Float64),
+            Field::new("ts", DataType::UInt64),
+        ]);
+        let users = Schema::new(vec![
+            Field::new("id", DataType::Int64),
+            Field::new("name", DataType::String),
+            Field::new("ts", DataType::UInt64),
+        ]);
+        let totals = Schema::new(vec![
+            Field::new("user_id", DataType::Int64),
+            Field::new("window_end", DataType::UInt64),
+            Field::new("total", DataType::Float64),
+            Field::new("orders", DataType::UInt64),
+        ]);
+        let names = Schema::new(vec![
+            Field::new("name", DataType::String),
+            Field::new("amount", DataType::Float64),
+        ]);
+
+        let mut catalog = Catalog::new();
+        catalog
+            .register_source(
+                SourceTable::new("orders", orders, |_env| unimplemented!())
+                    .row_time("ts", Duration::from_secs(5)),
+            )
+            .unwrap();
+        catalog
+            .register_source(
+                SourceTable::new("users", users, |_env| unimplemented!())
+                    .row_time("ts", Duration::from_secs(5)),
+            )
+            .unwrap();
+        catalog
+            .register_sink(SinkTable::new("totals", totals, |_s| unimplemented!()))
+            .unwrap();
+        catalog
+            .register_sink(SinkTable::new("names", names, |_s| unimplemented!()))
+            .unwrap();
+        catalog
+    }
+
+    fn analyze(sql: &str) -> Result<QueryPlan, SqlError> {
+        let catalog = catalog();
+        let statements = Parser::parse_sql(&GenericDialect {}, sql)?;
+        SqlPlanner::new(&catalog).analyze(&statements[0])
+    }
+
+    #[test]
+    pub fn plan_window_aggregate_test() {
+        let plan = analyze(
+            "INSERT INTO totals \
+             SELECT user_id, TUMBLE_END(ts, INTERVAL '1' MINUTE), SUM(amount * 2), COUNT(*) \
+             FROM orders WHERE amount > 0 \
+             GROUP BY TUMBLE(ts, INTERVAL '1' MINUTE), user_id \
+             HAVING COUNT(*) > 1",
+        )
+        .unwrap();
+
+        assert!(plan.filter.is_some());
+        assert!(plan.having.is_some());
+        let aggregate = plan.aggregate.unwrap();
+        assert_eq!(
+            aggregate.window,
+            WindowPlan::Tumble(Duration::from_secs(60))
+        );
+        assert_eq!(aggregate.key_count, 1);
+        assert_eq!(aggregate.pre_projection.len(), 2);
+        assert_eq!(aggregate.aggregations.len(), 2);
+        assert_eq!(plan.projection.len(), 4);
+    }
+
+    #[test]
+    pub fn plan_join_test() {
+        let plan = analyze(
+            "INSERT INTO names (amount, name) \
+             SELECT o.amount, u.name FROM orders o JOIN users u ON o.user_id = u.id",
+        )
+        .unwrap();
+
+        match plan.from {
+            FromPlan::Join {
+                left_keys,
+                right_keys,
+                ..
+            } => {
+                assert_eq!(left_keys, vec![0]);
+                assert_eq!(right_keys, vec![0]);
+            }
+            _ => panic!("not a join"),
+        }
+        assert!(plan.aggregate.is_none());
+    }
+
+    #[test]
+    pub fn plan_error_test() {
+        assert!(matches!(
+            analyze("INSERT INTO totals SELECT * FROM unknown"),
+            Err(SqlError::TableNotFound(_))
+        ));
+        assert!(matches!(
+            analyze("INSERT INTO names SELECT user_id FROM orders"),
+            Err(SqlError::Invalid(_))
+        ));
+        assert!(matches!(
+            analyze(
+                "INSERT INTO names SELECT u.name, o.amount \
+                 FROM orders o JOIN users u ON o.user_id = u.id \
+                 GROUP BY TUMBLE(o.ts, INTERVAL '1' MINUTE), u.name"
+            ),
+            Err(SqlError::Invalid(_))
+        ));
+        assert!(matches!(
+            analyze("INSERT INTO totals SELECT user_id, 0, SUM(amount), COUNT(*) FROM orders GROUP BY user_id"),
+            Err(SqlError::Unsupported(_))
+        ));
+        assert!(matches!(
+            analyze("INSERT INTO names SELECT 'a', amount FROM orders o LEFT JOIN users u ON o.user_id = u.id"),
+            Err(SqlError::Unsupported(_))
+        ));
+    }
+}
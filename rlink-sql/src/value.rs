@@ -0,0 +1,187 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use rlink::core::data_types::{DataType, Schema};
+use rlink::core::element::Record;
+
+/// A field value of the record in the expression evaluation, the integers are widened to
+/// `i64` or `u64` and the floats to `f64`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Boolean(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Binary(Vec<u8>),
+}
+
+impl Value {
+    pub fn as_bool(&self) -> anyhow::Result<bool> {
+        match self {
+            Value::Boolean(v) => Ok(*v),
+            Value::Int(v) => Ok(*v != 0),
+            Value::UInt(v) => Ok(*v != 0),
+            Value::Float(v) => Ok(*v != 0.0),
+            Value::String(v) => v
+                .parse::<bool>()
+                .map_err(|_e| anyhow!("cast `{}` to boolean", v)),
+            Value::Binary(_) => Err(anyhow!("cast binary to boolean")),
+        }
+    }
+
+    pub fn as_i64(&self) -> anyhow::Result<i64> {
+        match self {
+            Value::Boolean(v) => Ok(*v as i64),
+            Value::Int(v) => Ok(*v),
+            Value::UInt(v) => i64::try_from(*v).map_err(|_e| anyhow!("{} overflows i64", v)),
+            Value::Float(v) => Ok(*v as i64),
+            Value::String(v) => v
+                .trim()
+                .parse::<i64>()
+                .map_err(|_e| anyhow!("cast `{}` to integer", v)),
+            Value::Binary(_) => Err(anyhow!("cast binary to integer")),
+        }
+    }
+
+    pub fn as_u64(&self) -> anyhow::Result<u64> {
+        match self {
+            Value::Boolean(v) => Ok(*v as u64),
+            Value::Int(v) => u64::try_from(*v).map_err(|_e| anyhow!("{} overflows u64", v)),
+            Value::UInt(v) => Ok(*v),
+            Value::Float(v) => Ok(*v as u64),
+            Value::String(v) => v
+                .trim()
+                .parse::<u64>()
+                .map_err(|_e| anyhow!("cast `{}` to unsigned integer", v)),
+            Value::Binary(_) => Err(anyhow!("cast binary to unsigned integer")),
+        }
+    }
+
+    pub fn as_f64(&self) -> anyhow::Result<f64> {
+        match self {
+            Value::Boolean(v) => Ok(*v as u8 as f64),
+            Value::Int(v) => Ok(*v as f64),
+            Value::UInt(v) => Ok(*v as f64),
+            Value::Float(v) => Ok(*v),
+            Value::String(v) => v
+                .trim()
+                .parse::<f64>()
+                .map_err(|_e| anyhow!("cast `{}` to float", v)),
+            Value::Binary(_) => Err(anyhow!("cast binary to float")),
+        }
+    }
+
+    pub fn as_i128(&self) -> anyhow::Result<i128> {
+        match self {
+            Value::UInt(v) => Ok(*v as i128),
+            _ => self.as_i64().map(|v| v as i128),
+        }
+    }
+
+    /// Compare the values of the same kind, the numbers are compared as `f64` if any is a
+    /// float, otherwise as `i128`
+    pub fn compare(&self, other: &Value) -> anyhow::Result<Ordering> {
+        let ordering = match (self, other) {
+            (Value::Boolean(l), Value::Boolean(r)) => Some(l.cmp(r)),
+            (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+            (Value::Binary(l), Value::Binary(r)) => Some(l.cmp(r)),
+            (Value::Float(_), _) | (_, Value::Float(_)) => {
+                self.as_f64()?.partial_cmp(&other.as_f64()?)
+            }
+            (Value::Int(_) | Value::UInt(_), Value::Int(_) | Value::UInt(_)) => {
+                Some(self.as_i128()?.cmp(&other.as_i128()?))
+            }
+            _ => None,
+        };
+        ordering.ok_or_else(|| anyhow!("compare {} with {}", self, other))
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Boolean(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::UInt(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::String(v) => write!(f, "{}", v),
+            Value::Binary(v) => write!(f, "{}", String::from_utf8_lossy(v)),
+        }
+    }
+}
+
+/// Whether the `data_type` is an integer or a float
+pub(crate) fn is_numeric(data_type: &DataType) -> bool {
+    !matches!(
+        data_type,
+        DataType::Boolean | DataType::String | DataType::Binary
+    )
+}
+
+pub(crate) fn is_float(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Float32 | DataType::Float64)
+}
+
+pub(crate) fn is_unsigned(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64
+    )
+}
+
+/// Read the fields of the record in the order of the `schema`
+pub(crate) fn read_row(record: &mut Record, schema: &Schema) -> anyhow::Result<Vec<Value>> {
+    let reader = record.as_reader(schema.as_type_ids());
+
+    let mut row = Vec::with_capacity(schema.fields().len());
+    for (i, field) in schema.fields().iter().enumerate() {
+        let value = match field.data_type() {
+            DataType::Boolean => Value::Boolean(reader.get_bool(i)?),
+            DataType::Int8 => Value::Int(reader.get_i8(i)? as i64),
+            DataType::UInt8 => Value::UInt(reader.get_u8(i)? as u64),
+            DataType::Int16 => Value::Int(reader.get_i16(i)? as i64),
+            DataType::UInt16 => Value::UInt(reader.get_u16(i)? as u64),
+            DataType::Int32 => Value::Int(reader.get_i32(i)? as i64),
+            DataType::UInt32 => Value::UInt(reader.get_u32(i)? as u64),
+            DataType::Int64 => Value::Int(reader.get_i64(i)?),
+            DataType::UInt64 => Value::UInt(reader.get_u64(i)?),
+            DataType::Float32 => Value::Float(reader.get_f32(i)? as f64),
+            DataType::Float64 => Value::Float(reader.get_f64(i)?),
+            DataType::Binary => Value::Binary(reader.get_binary(i)?.to_vec()),
+            DataType::String => Value::String(reader.get_str(i)?.to_string()),
+        };
+        row.push(value);
+    }
+
+    Ok(row)
+}
+
+/// Write the values as a new record of the `schema`, the values are cast to the field types
+pub(crate) fn write_row(values: &[Value], schema: &Schema) -> anyhow::Result<Record> {
+    let mut record = Record::new();
+    let mut writer = record.as_writer(schema.as_type_ids());
+
+    for (value, field) in values.iter().zip(schema.fields()) {
+        match field.data_type() {
+            DataType::Boolean => writer.set_bool(value.as_bool()?)?,
+            DataType::Int8 => writer.set_i8(value.as_i64()? as i8)?,
+            DataType::UInt8 => writer.set_u8(value.as_u64()? as u8)?,
+            DataType::Int16 => writer.set_i16(value.as_i64()? as i16)?,
+            DataType::UInt16 => writer.set_u16(value.as_u64()? as u16)?,
+            DataType::Int32 => writer.set_i32(value.as_i64()? as i32)?,
+            DataType::UInt32 => writer.set_u32(value.as_u64()? as u32)?,
+            DataType::Int64 => writer.set_i64(value.as_i64()?)?,
+            DataType::UInt64 => writer.set_u64(value.as_u64()?)?,
+            DataType::Float32 => writer.set_f32(value.as_f64()? as f32)?,
+            DataType::Float64 => writer.set_f64(value.as_f64()?)?,
+            DataType::Binary => match value {
+                Value::Binary(v) => writer.set_binary(v.as_slice())?,
+                v => writer.set_binary(v.to_string().as_bytes())?,
+            },
+            DataType::String => writer.set_str(value.to_string().as_str())?,
+        }
+    }
+
+    Ok(record)
+}
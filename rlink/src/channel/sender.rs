@@ -62,4 +62,9 @@ where
             Err(TrySendError::Closed(t)) => Some(t),
         }
     }
+
+    /// The receiver half has been dropped
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
 }
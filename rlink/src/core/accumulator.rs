@@ -0,0 +1,244 @@
+//! The accumulators are the job-wide aggregations of the user values, e.g. the number of the
+//! bad records.
+//!
+//! Each task updates its own local accumulators registered by the `Context`, the values are
+//! shipped to the coordinator by the heartbeats and merged by the name. The merged values are
+//! served by the `/api/accumulators` of the coordinator and passed to
+//! `StreamApp::on_job_finished` when a bounded job finishes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use crate::core::runtime::TaskId;
+
+type SharedAccumulators = BTreeMap<String, Arc<Mutex<AccumulatorValue>>>;
+
+lazy_static! {
+    static ref ACCUMULATORS: Mutex<HashMap<TaskId, SharedAccumulators>> =
+        Mutex::new(HashMap::new());
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub enum AccumulatorValue {
+    Counter(u64),
+    LongSum(i64),
+    DoubleSum(f64),
+    /// the count of each value
+    Histogram(BTreeMap<i64, u64>),
+}
+
+impl AccumulatorValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            AccumulatorValue::Counter(_) => "Counter",
+            AccumulatorValue::LongSum(_) => "LongSum",
+            AccumulatorValue::DoubleSum(_) => "DoubleSum",
+            AccumulatorValue::Histogram(_) => "Histogram",
+        }
+    }
+
+    /// Merge the value of another task, the accumulators of the same name must be the same type
+    pub fn merge(&mut self, other: &AccumulatorValue) -> anyhow::Result<()> {
+        match (self, other) {
+            (AccumulatorValue::Counter(v), AccumulatorValue::Counter(o)) => *v += o,
+            (AccumulatorValue::LongSum(v), AccumulatorValue::LongSum(o)) => *v += o,
+            (AccumulatorValue::DoubleSum(v), AccumulatorValue::DoubleSum(o)) => *v += o,
+            (AccumulatorValue::Histogram(v), AccumulatorValue::Histogram(o)) => {
+                for (value, count) in o {
+                    *v.entry(*value).or_insert(0) += count;
+                }
+            }
+            (v, o) => {
+                return Err(anyhow!(
+                    "merge accumulator {} with {}",
+                    v.type_name(),
+                    o.type_name()
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The accumulators of a task shipped to the coordinator
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TaskAccumulators {
+    pub task_id: TaskId,
+    pub accumulators: BTreeMap<String, AccumulatorValue>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Counter {
+    value: Arc<Mutex<AccumulatorValue>>,
+}
+
+impl Counter {
+    pub fn add(&self, n: u64) {
+        if let AccumulatorValue::Counter(v) = &mut *self.value.lock().unwrap() {
+            *v += n;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LongSum {
+    value: Arc<Mutex<AccumulatorValue>>,
+}
+
+impl LongSum {
+    pub fn add(&self, n: i64) {
+        if let AccumulatorValue::LongSum(v) = &mut *self.value.lock().unwrap() {
+            *v += n;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DoubleSum {
+    value: Arc<Mutex<AccumulatorValue>>,
+}
+
+impl DoubleSum {
+    pub fn add(&self, n: f64) {
+        if let AccumulatorValue::DoubleSum(v) = &mut *self.value.lock().unwrap() {
+            *v += n;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    value: Arc<Mutex<AccumulatorValue>>,
+}
+
+impl Histogram {
+    pub fn add(&self, value: i64) {
+        if let AccumulatorValue::Histogram(v) = &mut *self.value.lock().unwrap() {
+            *v.entry(value).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Get the accumulator of the task by the name, it's created by the `init` value if absent. The
+/// operators of the same task share the accumulator of the same name
+fn register(task_id: TaskId, name: &str, init: AccumulatorValue) -> Arc<Mutex<AccumulatorValue>> {
+    let mut accumulators = ACCUMULATORS.lock().unwrap();
+    let value = accumulators
+        .entry(task_id)
+        .or_default()
+        .entry(name.to_string())
+        .or_insert_with(|| Arc::new(Mutex::new(init.clone())))
+        .clone();
+
+    let type_name = value.lock().unwrap().type_name();
+    if type_name != init.type_name() {
+        panic!(
+            "the accumulator `{}` has been registered as {}",
+            name, type_name
+        );
+    }
+    value
+}
+
+pub(crate) fn register_counter(task_id: TaskId, name: &str) -> Counter {
+    let value = register(task_id, name, AccumulatorValue::Counter(0));
+    Counter { value }
+}
+
+pub(crate) fn register_long_sum(task_id: TaskId, name: &str) -> LongSum {
+    let value = register(task_id, name, AccumulatorValue::LongSum(0));
+    LongSum { value }
+}
+
+pub(crate) fn register_double_sum(task_id: TaskId, name: &str) -> DoubleSum {
+    let value = register(task_id, name, AccumulatorValue::DoubleSum(0.0));
+    DoubleSum { value }
+}
+
+pub(crate) fn register_histogram(task_id: TaskId, name: &str) -> Histogram {
+    let value = register(task_id, name, AccumulatorValue::Histogram(BTreeMap::new()));
+    Histogram { value }
+}
+
+/// The current values of the accumulators of all tasks in this worker
+pub(crate) fn snapshot() -> Vec<TaskAccumulators> {
+    let accumulators = ACCUMULATORS.lock().unwrap();
+    accumulators
+        .iter()
+        .map(|(task_id, values)| TaskAccumulators {
+            task_id: *task_id,
+            accumulators: values
+                .iter()
+                .map(|(name, value)| (name.clone(), value.lock().unwrap().clone()))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Merge the accumulators of the tasks by the name, the conflict ones are dropped
+pub fn merge<'a, I>(task_accumulators: I) -> BTreeMap<String, AccumulatorValue>
+where
+    I: IntoIterator<Item = &'a BTreeMap<String, AccumulatorValue>>,
+{
+    let mut merged: BTreeMap<String, AccumulatorValue> = BTreeMap::new();
+    for accumulators in task_accumulators {
+        for (name, value) in accumulators {
+            match merged.get_mut(name) {
+                Some(merged_value) => {
+                    if let Err(e) = merged_value.merge(value) {
+                        error!("merge accumulator `{}` error. {}", name, e);
+                    }
+                }
+                None => {
+                    merged.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::core::accumulator::{
+        merge, register_counter, register_histogram, snapshot, AccumulatorValue,
+    };
+    use crate::core::runtime::{JobId, TaskId};
+
+    #[test]
+    pub fn accumulator_merge_test() {
+        let task_id = TaskId {
+            job_id: JobId(1000),
+            task_number: 0,
+            num_tasks: 1,
+        };
+
+        let counter = register_counter(task_id, "records");
+        counter.add(2);
+        register_counter(task_id, "records").add(3);
+        let histogram = register_histogram(task_id, "lengths");
+        histogram.add(10);
+
+        let task_accumulators = snapshot()
+            .into_iter()
+            .find(|x| x.task_id == task_id)
+            .unwrap();
+
+        let mut other = BTreeMap::new();
+        other.insert("records".to_string(), AccumulatorValue::Counter(5));
+        let mut lengths = BTreeMap::new();
+        lengths.insert(10, 2);
+        lengths.insert(20, 1);
+        other.insert("lengths".to_string(), AccumulatorValue::Histogram(lengths));
+
+        let merged = merge(vec![&task_accumulators.accumulators, &other]);
+        assert_eq!(merged["records"], AccumulatorValue::Counter(10));
+
+        let mut lengths = BTreeMap::new();
+        lengths.insert(10, 3);
+        lengths.insert(20, 1);
+        assert_eq!(merged["lengths"], AccumulatorValue::Histogram(lengths));
+    }
+}
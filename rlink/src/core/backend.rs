@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 
 /// checkpoint backend storage type
@@ -13,6 +14,16 @@ pub enum CheckpointBackend {
         /// storage table's name, if `None` use default table name
         table: Option<String>,
     },
+    /// storage in the local directory, or a directory mounted on every coordinator node
+    FileSystem { path: String },
+    /// storage in the object store
+    ObjectStore {
+        /// e.g. `s3://bucket/path`, `gs://bucket/path`, `azblob://container/path`
+        url: String,
+        /// the credentials and the retry policy, see `storage::object_storage`
+        #[serde(default)]
+        options: HashMap<String, String>,
+    },
 }
 
 impl Display for CheckpointBackend {
@@ -22,17 +33,52 @@ impl Display for CheckpointBackend {
             CheckpointBackend::MySql { endpoint, table } => {
                 write!(f, "MySql{{endpoint={}}}, table={:?}}}", endpoint, table)
             }
+            CheckpointBackend::FileSystem { path } => write!(f, "FileSystem{{path={}}}", path),
+            CheckpointBackend::ObjectStore { url, .. } => write!(f, "ObjectStore{{url={}}}", url),
         }
     }
 }
 
 /// keyed state backend storage type
-#[derive(Copy, Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(tag = "type", content = "param")]
 pub enum KeyedStateBackend {
     Memory,
     // FsStateBackend(String),
-    // RocksDBStateBackend(String),
+    /// storage in the local rocksdb of the task, the window state and every state of the
+    /// `RuntimeContext` are column families. requires the `rocksdb` feature
+    RocksDB {
+        /// the local directory of the rocksdb, the rocksdb checkpoints are in it too
+        path: String,
+        /// the size in bytes of the lru block cache, the rocksdb default if `None`
+        #[serde(default)]
+        block_cache_size: Option<usize>,
+        /// the size in bytes of the memtable of a column family, the rocksdb default if `None`
+        #[serde(default)]
+        write_buffer_size: Option<usize>,
+        /// upload the rocksdb checkpoints incrementally to the object store, e.g.
+        /// `s3://bucket/path`, only the local checkpoints are kept if `None`
+        #[serde(default)]
+        checkpoint_url: Option<String>,
+        /// the options of the object store, see `storage::object_storage`
+        #[serde(default)]
+        checkpoint_options: HashMap<String, String>,
+    },
+}
+
+impl KeyedStateBackend {
+    /// Check the backend is available in the build, it's checked before the job is started
+    pub fn check(&self) -> anyhow::Result<()> {
+        match self {
+            KeyedStateBackend::Memory => Ok(()),
+            #[cfg(feature = "rocksdb")]
+            KeyedStateBackend::RocksDB { .. } => Ok(()),
+            #[cfg(not(feature = "rocksdb"))]
+            KeyedStateBackend::RocksDB { .. } => Err(anyhow!(
+                "the rocksdb keyed state backend requires the `rocksdb` feature"
+            )),
+        }
+    }
 }
 
 impl Display for KeyedStateBackend {
@@ -40,9 +86,58 @@ impl Display for KeyedStateBackend {
         match self {
             KeyedStateBackend::Memory => write!(f, "Memory"),
             // StateBackend::FsStateBackend(path) => write!(f, "FsStateBackend{{path={}}}", path),
-            // KeyedStateBackend::RocksDBStateBackend(path) => {
-            //     write!(f, "RocksDBStateBackend{{path={}}}", path)
-            // }
+            KeyedStateBackend::RocksDB { path, .. } => write!(f, "RocksDB{{path={}}}", path),
+        }
+    }
+}
+
+/// high availability storage type of the coordinator, the standby coordinators of the
+/// application take over the job by the leader election in it
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "param")]
+pub enum HighAvailabilityBackend {
+    /// storage in the process memory, only the coordinators in the same process are elected
+    Memory,
+    /// storage in the etcd, requires the `ha-etcd` feature
+    Etcd {
+        /// e.g. `http://127.0.0.1:2379`
+        endpoints: Vec<String>,
+        /// the prefix of the keys, `/rlink` if `None`
+        #[serde(default)]
+        namespace: Option<String>,
+    },
+    /// storage in the zookeeper, requires the `ha-zookeeper` feature
+    ZooKeeper {
+        /// e.g. `127.0.0.1:2181,127.0.0.2:2181`
+        servers: String,
+        /// the root path of the nodes, `/rlink` if `None`
+        #[serde(default)]
+        root: Option<String>,
+    },
+    /// storage in the Lease and the ConfigMap of the kubernetes, the coordinators elect by
+    /// the Lease, no external storage is required in the `ClusterMode::Kubernetes`
+    Kubernetes {
+        /// the namespace of the Lease and the ConfigMap, `default` if `None`
+        #[serde(default)]
+        namespace: Option<String>,
+    },
+}
+
+impl Display for HighAvailabilityBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HighAvailabilityBackend::Memory => write!(f, "Memory"),
+            HighAvailabilityBackend::Etcd { endpoints, .. } => {
+                write!(f, "Etcd{{endpoints={:?}}}", endpoints)
+            }
+            HighAvailabilityBackend::ZooKeeper { servers, .. } => {
+                write!(f, "ZooKeeper{{servers={}}}", servers)
+            }
+            HighAvailabilityBackend::Kubernetes { namespace } => write!(
+                f,
+                "Kubernetes{{namespace={}}}",
+                namespace.as_deref().unwrap_or("default")
+            ),
         }
     }
 }
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::core::runtime::{CheckpointId, OperatorId, TaskId};
 use crate::runtime::worker::WorkerTaskContext;
@@ -37,6 +38,84 @@ impl FunctionSnapshotContext {
     }
 }
 
+/// The checkpoint options of the application, set by `SystemProperties::set_checkpoint_config`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointConfig {
+    /// the interval of the `Barrier` emitted by the sources
+    pub interval: Duration,
+    /// the min pause between the completion of a checkpoint and the start of the next one,
+    /// the sources don't inject the barriers in the pause
+    pub min_pause: Duration,
+    /// the pending checkpoint is aborted as a failure if not completed in the timeout
+    pub timeout: Duration,
+    /// the max number of the pending checkpoints, the sources don't inject the barriers of a
+    /// new one when reached
+    pub max_concurrent: usize,
+    /// the job is restarted when the consecutive failed checkpoints exceed it,
+    /// `None` for unlimited
+    pub tolerable_failures: Option<usize>,
+}
+
+impl CheckpointConfig {
+    pub fn new(interval: Duration) -> Self {
+        CheckpointConfig {
+            interval,
+            min_pause: Duration::from_secs(0),
+            timeout: Duration::from_secs(10 * 60),
+            max_concurrent: 1,
+            tolerable_failures: None,
+        }
+    }
+
+    pub fn min_pause(mut self, min_pause: Duration) -> Self {
+        self.min_pause = min_pause;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    pub fn tolerable_failures(mut self, tolerable_failures: usize) -> Self {
+        self.tolerable_failures = Some(tolerable_failures);
+        self
+    }
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        CheckpointConfig::new(Duration::from_secs(30))
+    }
+}
+
+/// How the `Barrier` flows through the channels between the tasks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckpointMode {
+    /// the `Barrier` is queued behind the in-flight elements of the channels
+    Aligned,
+    /// the `Barrier` overtakes the in-flight elements buffered in the network channels, the
+    /// overtaken records are snapshot with the operator state as the channel state and replayed
+    /// on restore. So checkpoints complete quickly even the job is backpressured.
+    ///
+    /// Only the sender side of the channels is snapshot. A task with multiple inputs keeps
+    /// processing the records of the inputs whose `Barrier` has reached, they'd be replayed
+    /// from the channel state on restore, so the `Barrier` doesn't overtake the records to
+    /// such tasks, and their channels are aligned
+    Unaligned,
+}
+
+impl Default for CheckpointMode {
+    fn default() -> Self {
+        CheckpointMode::Aligned
+    }
+}
+
 /// checkpoint handle
 /// identify a checkpoint resource
 /// eg: 1. checkpoint state and save to distribution file system, mark the file path as handle.
@@ -54,6 +133,14 @@ impl Default for CheckpointHandle {
     }
 }
 
+/// The checkpoint handle of a task of the operator in the restored checkpoint, the `num_tasks`
+/// of the `task_id` is the parallelism of the operator in the checkpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OperatorStateHandle {
+    pub task_id: TaskId,
+    pub handle: CheckpointHandle,
+}
+
 /// descriptor a `Checkpoint`
 /// use for network communication between `Coordinator` and `Worker`
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +149,9 @@ pub struct Checkpoint {
     pub task_id: TaskId,
     pub checkpoint_id: CheckpointId,
     pub completed_checkpoint_id: Option<CheckpointId>,
+    /// the duration in millis the task waited for the barriers of all its inputs
+    #[serde(default)]
+    pub alignment_duration: u64,
     pub handle: CheckpointHandle,
 }
 
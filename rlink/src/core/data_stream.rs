@@ -1,16 +1,39 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Formatter};
 use std::rc::Rc;
+use std::time::Duration;
 
+use crate::core::element::{FnSchema, OutputTag};
 use crate::core::env::StreamManager;
+use crate::core::error_policy::ErrorPolicy;
 use crate::core::function::{
-    CoProcessFunction, FilterFunction, FlatMapFunction, InputFormat, KeySelectorFunction,
-    OutputFormat, ReduceFunction,
+    AggregateFunction, BroadcastProcessFunction, CoProcessFunction, FilterFunction,
+    FlatMapFunction, InputFormat, JoinFunction, JoinType, KeySelectorFunction,
+    KeyedCoProcessFunction, KeyedProcessFunction, OutputFormat, Partitioner, ProcessWindowFunction,
+    ReduceFunction,
 };
 use crate::core::operator::{FunctionCreator, StreamOperator};
 use crate::core::runtime::OperatorId;
 use crate::core::watermark::WatermarkStrategy;
-use crate::core::window::WindowAssigner;
-use crate::functions::system::window_base_reduce::WindowBaseReduceFunction;
+use crate::core::window::{Evictor, Trigger, WindowAssigner};
+use crate::dag::stream_graph::ChainingStrategy;
+use crate::functions::async_io::AsyncMapFunction;
+use crate::functions::filter::{SampleFilterFunction, Sampling};
+use crate::functions::flat_map::{
+    BroadcastFlagMapFunction, CustomPartitionFlagMapFunction, RescaleFlagMapFunction,
+    RoundRobinFlagMapFunction, ThrottleFlatMapFunction,
+};
+use crate::functions::reduce::TopN;
+use crate::functions::system::broadcast_co_process::BroadcastCoProcessFunction;
+use crate::functions::system::distinct::DistinctProcessFunction;
+use crate::functions::system::interval_join::IntervalJoinProcessFunction;
+use crate::functions::system::join::{JoinCoProcessFunction, JoinKeySelectorFunction};
+use crate::functions::system::keyed_co_process::KeyedCoProcessAdapterFunction;
+use crate::functions::system::temporal_join::TemporalJoinProcessFunction;
+use crate::functions::system::union::UnionCoProcessFunction;
+use crate::functions::system::window_base_reduce::{WindowBaseReduceFunction, WindowFunction};
+use crate::functions::system::window_join::{
+    WindowJoinAggregateFunction, WindowJoinFlatMapFunction,
+};
 
 /// A DataStream represents a stream of elements of the same type. A DataStream can be transformed
 /// into another DataStream by applying a transformation
@@ -23,6 +46,9 @@ pub trait TDataStream {
     where
         F: FilterFunction + 'static;
 
+    /// Invoke the async I/O of the records concurrently, see `AsyncMapFunction`
+    fn async_map(self, async_map: AsyncMapFunction) -> DataStream;
+
     fn key_by<F>(self, key_selector: F) -> KeyedStream
     where
         F: KeySelectorFunction + 'static;
@@ -31,14 +57,85 @@ pub trait TDataStream {
     where
         W: WatermarkStrategy + 'static;
 
+    /// Drop the records whose keys selected by the `key_selector` have been seen within the
+    /// `ttl`, the seen keys are kept in the keyed states
+    fn distinct_by<F>(self, key_selector: F, ttl: Duration) -> DataStream
+    where
+        F: KeySelectorFunction + 'static;
+
+    /// Merge the records of this stream and the `data_streams` of the same schema into one
+    /// stream, the watermark of the merged stream is the min watermark of all streams
+    fn union(self, data_streams: Vec<DataStream>) -> DataStream;
+
+    /// Limit the rate of the records to `records_per_second` shared by the parallel tasks,
+    /// allowing bursts of up to `burst` records per task. The records are never dropped, the
+    /// task waits for the tokens so the backpressure slows down the upstream
+    fn throttle(self, records_per_second: u64, burst: u64) -> DataStream;
+
+    /// Keep a sample of the records by the `sampling`, the other records are dropped
+    fn sample(self, sampling: Sampling) -> DataStream;
+
     /// Re-balance: Round-robin, Hash, Broadcast
     fn connect<F>(self, data_streams: Vec<CoStream>, f: F) -> ConnectedStreams
     where
         F: CoProcessFunction + 'static;
 
+    /// Distribute the records to the tasks of the next operator round-robin, the next operator
+    /// is never chained so the records are re-balanced even if the parallelism is the same
+    fn rebalance(self) -> DataStream;
+
+    /// Distribute the records round-robin to a subset of the tasks of the next operator, the
+    /// tasks of the next operator are divided into groups by the ratio of the parallelism and
+    /// each task only sends to its own group
+    fn rescale(self) -> DataStream;
+
+    /// Send the records to the tasks of the next operator selected by the `partitioner` of
+    /// their keys
+    fn partition_custom<P, F>(self, partitioner: P, key_selector: F) -> DataStream
+    where
+        P: Partitioner + 'static,
+        F: KeySelectorFunction + 'static;
+
+    /// Send every record to all parallel instances of the operator connected by
+    /// `connect_broadcast`, or of the next operator by `BroadcastStream::into_data_stream`
+    fn broadcast(self) -> BroadcastStream;
+
+    /// Iterate the stream by the `step`, which builds the iteration body from the head stream
+    /// and returns the feedback stream and the output stream. The records of the feedback
+    /// stream are sent back to the head, the feedback stream must have the same schema as this
+    /// stream and the same parallelism as the head.
+    ///
+    /// The iteration ends once the input ends and no feedback record is received in
+    /// `max_wait`, the feedback records aren't checkpointed
+    fn iterate<F>(self, max_wait: Duration, step: F) -> DataStream
+    where
+        F: FnOnce(DataStream) -> (DataStream, DataStream);
+
+    /// Process the records against the broadcast states updated by the `broadcast_stream`
+    fn connect_broadcast<F>(self, broadcast_stream: BroadcastStream, f: F) -> ConnectedStreams
+    where
+        F: BroadcastProcessFunction + 'static;
+
+    /// Process the records of this stream and the `other` stream of a different schema with the
+    /// keyed states and the timers shared by the same key, see `KeyedConnectedStreams`
+    fn connect_keyed(self, other: DataStream) -> KeyedConnectedStreams;
+
+    /// Join the records of the `other` stream with the same key whose timestamps are close to
+    /// the records of this stream, see `IntervalJoinedStreams`
+    fn interval_join(self, other: DataStream) -> IntervalJoinedStreams;
+
+    /// Join the records of the `other` stream with the same key in the same window, see
+    /// `JoinedStreams`
+    fn join(self, other: DataStream) -> JoinedStreams;
+
+    /// Join the records of this stream with the versions of the table rows with the same key
+    /// valid at their timestamps, the versioned table is maintained by the `table` stream, see
+    /// `TemporalJoinedStreams`
+    fn temporal_join(self, table: DataStream) -> TemporalJoinedStreams;
+
     // fn multiplexing(self) -> MultiplexingStream;
 
-    fn add_sink<O>(self, output_format: O)
+    fn add_sink<O>(self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static;
 }
@@ -50,7 +147,7 @@ pub trait TConnectedStreams {
     where
         F: KeySelectorFunction + 'static;
 
-    fn add_sink<O>(self, output_format: O)
+    fn add_sink<O>(self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static;
 }
@@ -59,15 +156,64 @@ pub trait TKeyedStream {
     fn window<W>(self, window_assigner: W) -> WindowedStream
     where
         W: WindowAssigner + 'static;
+
+    /// Process the records with the keyed states and the timers of their keys, see
+    /// `KeyedProcessFunction`
+    fn process<F>(self, keyed_process: F) -> DataStream
+    where
+        F: KeyedProcessFunction + 'static;
+
     fn add_sink<O>(self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static;
 }
 
 pub trait TWindowedStream {
+    /// Fire the windows by the `trigger` instead of the watermark passage, see
+    /// `EventTimeTrigger`
+    fn trigger<T>(self, trigger: T) -> WindowedStream
+    where
+        T: Trigger + 'static;
+
+    /// Evict the elements of the windows before or after the windows are fired, the elements
+    /// are kept in the memory of the task until the windows are purged
+    fn evictor<E>(self, evictor: E) -> WindowedStream
+    where
+        E: Evictor + 'static;
+
+    /// Keep the windows for the `allowed_lateness` after the watermark passes the end of them,
+    /// the late elements within it update and re-fire the windows. Default is zero
+    fn allowed_lateness(self, allowed_lateness: Duration) -> WindowedStream;
+
+    /// Write the elements arrived after the allowed lateness of their windows to the
+    /// `output_format`, they're dropped if it's not set
+    fn side_output_late_data<O>(self, output_format: O) -> WindowedStream
+    where
+        O: OutputFormat + 'static;
+
     fn reduce<F>(self, reduce: F) -> DataStream
     where
         F: ReduceFunction + 'static;
+
+    /// Process the reduced value of each key with the metadata of the window when the window
+    /// is fired, see `ProcessWindowFunction`
+    fn process<P>(self, process: P) -> WindowedStream
+    where
+        P: ProcessWindowFunction + 'static;
+
+    /// Aggregate the records of the windows incrementally, see `AggregateFunction`
+    fn aggregate<F>(self, aggregate: F) -> DataStream
+    where
+        F: AggregateFunction + 'static;
+
+    /// Rank the records of each key in the windows, see `TopN`. The process window function is
+    /// replaced by the ranking
+    fn top_n(self, top_n: TopN) -> DataStream;
+
+    /// Expose the results of the windows to the queryable state server of the workers by the
+    /// `queryable_name`, the value of a key is the list of its windows not dropped yet, e.g.
+    /// `[{"start": 0, "end": 59999, "value": {"count": 2}}]`
+    fn queryable(self, queryable_name: &str) -> WindowedStream;
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -90,6 +236,12 @@ impl From<KeyedStream> for CoStream {
     }
 }
 
+impl From<BroadcastStream> for CoStream {
+    fn from(broadcast_stream: BroadcastStream) -> Self {
+        CoStream::DataStream(DataStream::new(broadcast_stream.broadcast_stream))
+    }
+}
+
 impl Into<StreamBuilder> for CoStream {
     fn into(self) -> StreamBuilder {
         match self {
@@ -108,6 +260,57 @@ impl DataStream {
     pub(crate) fn new(data_stream: StreamBuilder) -> Self {
         DataStream { data_stream }
     }
+
+    /// Set the stable id of the last operator, the state of the operator is restored from the
+    /// savepoint by the uid even if the job is modified
+    pub fn uid(self, uid: &str) -> Self {
+        self.data_stream.set_uid(uid);
+        self
+    }
+
+    /// Set the parallelism of the last operator, the operators after it inherit the parallelism
+    /// unless they have their own. The operator is split from the task of its parent if they're
+    /// chained, it must be set before any operator is added after it
+    pub fn set_parallelism(self, parallelism: u16) -> Self {
+        assert!(parallelism > 0, "the parallelism must be positive");
+        self.data_stream.set_parallelism(parallelism);
+        self
+    }
+
+    /// Neither the parent nor the children are chained with the last operator, each of them is
+    /// run in its own task
+    pub fn disable_chaining(self) -> Self {
+        self.data_stream.set_chaining(ChainingStrategy::Never);
+        self
+    }
+
+    /// The last operator isn't chained with its parent, it starts a new task chaining the
+    /// operators after it
+    pub fn start_new_chain(self) -> Self {
+        self.data_stream.set_chaining(ChainingStrategy::Head);
+        self
+    }
+
+    /// Set the slot sharing group of the last operator and the operators after it, the tasks of
+    /// the different groups are allocated to the separate workers if there are enough, so an
+    /// expensive operator can be isolated from the others
+    pub fn slot_sharing_group(self, slot_sharing_group: &str) -> Self {
+        self.data_stream.set_slot_sharing_group(slot_sharing_group);
+        self
+    }
+
+    /// Set how the records failed by the function of the last operator are handled instead of
+    /// failing the job, see `ErrorPolicy`
+    pub fn error_policy(self, error_policy: ErrorPolicy) -> Self {
+        self.data_stream.set_error_policy(error_policy);
+        self
+    }
+
+    /// Get the stream of the records emitted to the `output_tag` by the last operator, see
+    /// `OutputTag::output`
+    pub fn get_side_output(&self, output_tag: &OutputTag) -> DataStream {
+        self.data_stream.get_side_output(output_tag)
+    }
 }
 
 impl TDataStream for DataStream {
@@ -125,6 +328,10 @@ impl TDataStream for DataStream {
         self.data_stream.filter(filter)
     }
 
+    fn async_map(self, async_map: AsyncMapFunction) -> DataStream {
+        self.data_stream.async_map(async_map)
+    }
+
     fn key_by<F>(self, key_selector: F) -> KeyedStream
     where
         F: KeySelectorFunction + 'static,
@@ -140,6 +347,25 @@ impl TDataStream for DataStream {
             .assign_timestamps_and_watermarks(timestamp_and_watermark_assigner)
     }
 
+    fn distinct_by<F>(self, key_selector: F, ttl: Duration) -> DataStream
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.data_stream.distinct_by(key_selector, ttl)
+    }
+
+    fn union(self, data_streams: Vec<DataStream>) -> DataStream {
+        self.data_stream.union(data_streams)
+    }
+
+    fn throttle(self, records_per_second: u64, burst: u64) -> DataStream {
+        self.data_stream.throttle(records_per_second, burst)
+    }
+
+    fn sample(self, sampling: Sampling) -> DataStream {
+        self.data_stream.sample(sampling)
+    }
+
     fn connect<F>(self, data_streams: Vec<CoStream>, co_process: F) -> ConnectedStreams
     where
         F: CoProcessFunction + 'static,
@@ -147,7 +373,57 @@ impl TDataStream for DataStream {
         self.data_stream.connect(data_streams, co_process)
     }
 
-    fn add_sink<O>(self, output_format: O)
+    fn rebalance(self) -> DataStream {
+        self.data_stream.rebalance()
+    }
+
+    fn rescale(self) -> DataStream {
+        self.data_stream.rescale()
+    }
+
+    fn partition_custom<P, F>(self, partitioner: P, key_selector: F) -> DataStream
+    where
+        P: Partitioner + 'static,
+        F: KeySelectorFunction + 'static,
+    {
+        self.data_stream.partition_custom(partitioner, key_selector)
+    }
+
+    fn broadcast(self) -> BroadcastStream {
+        self.data_stream.broadcast()
+    }
+
+    fn iterate<F>(self, max_wait: Duration, step: F) -> DataStream
+    where
+        F: FnOnce(DataStream) -> (DataStream, DataStream),
+    {
+        self.data_stream.iterate(max_wait, step)
+    }
+
+    fn connect_broadcast<F>(self, broadcast_stream: BroadcastStream, f: F) -> ConnectedStreams
+    where
+        F: BroadcastProcessFunction + 'static,
+    {
+        self.data_stream.connect_broadcast(broadcast_stream, f)
+    }
+
+    fn connect_keyed(self, other: DataStream) -> KeyedConnectedStreams {
+        self.data_stream.connect_keyed(other)
+    }
+
+    fn interval_join(self, other: DataStream) -> IntervalJoinedStreams {
+        self.data_stream.interval_join(other)
+    }
+
+    fn join(self, other: DataStream) -> JoinedStreams {
+        self.data_stream.join(other)
+    }
+
+    fn temporal_join(self, table: DataStream) -> TemporalJoinedStreams {
+        self.data_stream.temporal_join(table)
+    }
+
+    fn add_sink<O>(self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static,
     {
@@ -155,6 +431,25 @@ impl TDataStream for DataStream {
     }
 }
 
+/// A stream broadcast to all parallel instances of the connected operator, see
+/// `TDataStream::connect_broadcast`
+#[derive(Debug)]
+pub struct BroadcastStream {
+    broadcast_stream: StreamBuilder,
+}
+
+impl BroadcastStream {
+    pub(crate) fn new(broadcast_stream: StreamBuilder) -> Self {
+        BroadcastStream { broadcast_stream }
+    }
+
+    /// The broadcast records as a plain stream, every record is sent to all tasks of the next
+    /// operator
+    pub fn into_data_stream(self) -> DataStream {
+        DataStream::new(self.broadcast_stream)
+    }
+}
+
 #[derive(Debug)]
 pub struct ConnectedStreams {
     co_stream: StreamBuilder,
@@ -169,6 +464,12 @@ impl ConnectedStreams {
             parent_pipeline_ids: dependency_pipeline_ids,
         }
     }
+
+    /// Set the stable id of the `CoProcessFunction`, see `DataStream::uid`
+    pub fn uid(self, uid: &str) -> Self {
+        self.co_stream.set_uid(uid);
+        self
+    }
 }
 
 impl TConnectedStreams for ConnectedStreams {
@@ -179,11 +480,11 @@ impl TConnectedStreams for ConnectedStreams {
         self.co_stream.key_by(key_selector)
     }
 
-    fn add_sink<O>(self, output_format: O)
+    fn add_sink<O>(self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static,
     {
-        TDataStream::add_sink(self.co_stream, output_format);
+        TDataStream::add_sink(self.co_stream, output_format)
     }
 }
 
@@ -206,6 +507,13 @@ impl TKeyedStream for KeyedStream {
         self.keyed_stream.window(window_assigner)
     }
 
+    fn process<F>(self, keyed_process: F) -> DataStream
+    where
+        F: KeyedProcessFunction + 'static,
+    {
+        self.keyed_stream.process(keyed_process)
+    }
+
     fn add_sink<O>(self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static,
@@ -214,29 +522,537 @@ impl TKeyedStream for KeyedStream {
     }
 }
 
-#[derive(Debug)]
 pub struct WindowedStream {
     windowed_stream: StreamBuilder,
+    trigger: Option<Box<dyn Trigger>>,
+    evictor: Option<Box<dyn Evictor>>,
+    allowed_lateness: Duration,
+    late_data_output: Option<Box<dyn OutputFormat>>,
+    process: Option<Box<dyn ProcessWindowFunction>>,
+    queryable: Option<String>,
 }
 
 impl WindowedStream {
     pub(crate) fn new(windowed_stream: StreamBuilder) -> Self {
-        WindowedStream { windowed_stream }
+        WindowedStream {
+            windowed_stream,
+            trigger: None,
+            evictor: None,
+            allowed_lateness: Duration::from_millis(0),
+            late_data_output: None,
+            process: None,
+            queryable: None,
+        }
+    }
+}
+
+impl WindowedStream {
+    fn window_function(self, function: WindowFunction) -> DataStream {
+        let parallelism = function.parallelism();
+        let queryable = self.queryable.map(|queryable_name| {
+            let result_schema = function.schema(self.windowed_stream.output_schema());
+            (queryable_name, result_schema.into())
+        });
+        let base_reduce_func = WindowBaseReduceFunction::new(function, self.trigger, self.evictor)
+            .with_allowed_lateness(self.allowed_lateness)
+            .with_late_data_output(self.late_data_output)
+            .with_process_function(self.process)
+            .with_queryable(queryable);
+        self.windowed_stream
+            .window_reduce(parallelism, base_reduce_func)
+    }
+}
+
+impl Debug for WindowedStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WindowedStream")
+            .field("windowed_stream", &self.windowed_stream)
+            .field("trigger", &self.trigger)
+            .field("evictor", &self.evictor)
+            .field("allowed_lateness", &self.allowed_lateness)
+            .field(
+                "late_data_output",
+                &self.late_data_output.as_ref().map(|x| x.name().to_string()),
+            )
+            .field(
+                "process",
+                &self.process.as_ref().map(|x| x.name().to_string()),
+            )
+            .field("queryable", &self.queryable)
+            .finish()
     }
 }
 
 impl TWindowedStream for WindowedStream {
+    fn trigger<T>(mut self, trigger: T) -> WindowedStream
+    where
+        T: Trigger + 'static,
+    {
+        self.trigger = Some(Box::new(trigger));
+        self
+    }
+
+    fn evictor<E>(mut self, evictor: E) -> WindowedStream
+    where
+        E: Evictor + 'static,
+    {
+        self.evictor = Some(Box::new(evictor));
+        self
+    }
+
+    fn allowed_lateness(mut self, allowed_lateness: Duration) -> WindowedStream {
+        self.allowed_lateness = allowed_lateness;
+        self
+    }
+
+    fn side_output_late_data<O>(mut self, output_format: O) -> WindowedStream
+    where
+        O: OutputFormat + 'static,
+    {
+        self.late_data_output = Some(Box::new(output_format));
+        self
+    }
+
+    fn process<P>(mut self, process: P) -> WindowedStream
+    where
+        P: ProcessWindowFunction + 'static,
+    {
+        self.process = Some(Box::new(process));
+        self
+    }
+
     fn reduce<F>(self, reduce: F) -> DataStream
     where
         F: ReduceFunction + 'static,
     {
-        self.windowed_stream.reduce(reduce)
+        self.window_function(WindowFunction::Reduce(Box::new(reduce)))
+    }
+
+    fn aggregate<F>(self, aggregate: F) -> DataStream
+    where
+        F: AggregateFunction + 'static,
+    {
+        self.window_function(WindowFunction::Aggregate(Box::new(aggregate)))
+    }
+
+    fn top_n(mut self, top_n: TopN) -> DataStream {
+        let (aggregate, process, flat_map) = top_n.functions(self.windowed_stream.output_schema());
+        self.process = Some(Box::new(process));
+        self.window_function(WindowFunction::Aggregate(Box::new(aggregate)))
+            .flat_map(flat_map)
+    }
+
+    fn queryable(mut self, queryable_name: &str) -> WindowedStream {
+        self.queryable = Some(queryable_name.to_string());
+        self
+    }
+}
+
+/// The records of the left stream and the right stream of the different schemas processed by a
+/// `KeyedCoProcessFunction`. The keys are selected by `where_key` of the left and `equal_to` of
+/// the right, the keyed states and the timers of a key are shared by both streams
+pub struct KeyedConnectedStreams {
+    left: StreamBuilder,
+    right: StreamBuilder,
+    left_key_selector: Option<Box<dyn KeySelectorFunction>>,
+    right_key_selector: Option<Box<dyn KeySelectorFunction>>,
+}
+
+impl KeyedConnectedStreams {
+    pub(crate) fn new(left: StreamBuilder, right: StreamBuilder) -> Self {
+        KeyedConnectedStreams {
+            left,
+            right,
+            left_key_selector: None,
+            right_key_selector: None,
+        }
+    }
+
+    /// The key selector of the left stream
+    pub fn where_key<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.left_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The key selector of the right stream
+    pub fn equal_to<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.right_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    pub fn process<F>(self, co_process: F) -> DataStream
+    where
+        F: KeyedCoProcessFunction + 'static,
+    {
+        let left_key_selector = self
+            .left_key_selector
+            .expect("KeyedConnectedStreams must have the `where_key` key selector");
+        let right_key_selector = self
+            .right_key_selector
+            .expect("KeyedConnectedStreams must have the `equal_to` key selector");
+
+        let left_schema = self.left.output_schema();
+        let right_schema = self.right.output_schema();
+        let key_schema = left_key_selector.key_schema(left_schema.clone());
+
+        let join_co_process = JoinCoProcessFunction::new(
+            left_key_selector,
+            right_key_selector,
+            left_schema.clone(),
+            right_schema.clone(),
+        );
+        let keyed_process =
+            KeyedCoProcessAdapterFunction::new(co_process, left_schema, right_schema);
+
+        self.left
+            .connect(
+                vec![CoStream::from(DataStream::new(self.right))],
+                join_co_process,
+            )
+            .key_by(JoinKeySelectorFunction::new(key_schema))
+            .process(keyed_process)
+    }
+}
+
+impl Debug for KeyedConnectedStreams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedConnectedStreams")
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .finish()
+    }
+}
+
+/// The records of the left stream joined with the records of the right stream with the same key,
+/// whose timestamps are within `[left.timestamp - lower_bound, left.timestamp + upper_bound]`.
+/// The keys are selected by `where_key` of the left and `equal_to` of the right
+pub struct IntervalJoinedStreams {
+    left: StreamBuilder,
+    right: StreamBuilder,
+    left_key_selector: Option<Box<dyn KeySelectorFunction>>,
+    right_key_selector: Option<Box<dyn KeySelectorFunction>>,
+    lower_bound: Duration,
+    upper_bound: Duration,
+}
+
+impl IntervalJoinedStreams {
+    pub(crate) fn new(left: StreamBuilder, right: StreamBuilder) -> Self {
+        IntervalJoinedStreams {
+            left,
+            right,
+            left_key_selector: None,
+            right_key_selector: None,
+            lower_bound: Duration::from_millis(0),
+            upper_bound: Duration::from_millis(0),
+        }
+    }
+
+    /// The key selector of the left stream
+    pub fn where_key<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.left_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The key selector of the right stream
+    pub fn equal_to<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.right_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The right records within `lower_bound` before and `upper_bound` after the left record are
+    /// joined. Default is zero, only the records with the same timestamp are joined
+    pub fn between(mut self, lower_bound: Duration, upper_bound: Duration) -> Self {
+        self.lower_bound = lower_bound;
+        self.upper_bound = upper_bound;
+        self
+    }
+
+    pub fn process<F>(self, join: F) -> DataStream
+    where
+        F: JoinFunction + 'static,
+    {
+        let left_key_selector = self
+            .left_key_selector
+            .expect("IntervalJoinedStreams must have the `where_key` key selector");
+        let right_key_selector = self
+            .right_key_selector
+            .expect("IntervalJoinedStreams must have the `equal_to` key selector");
+
+        let left_schema = self.left.output_schema();
+        let right_schema = self.right.output_schema();
+        let key_schema = left_key_selector.key_schema(left_schema.clone());
+
+        let co_process = JoinCoProcessFunction::new(
+            left_key_selector,
+            right_key_selector,
+            left_schema.clone(),
+            right_schema.clone(),
+        );
+        let keyed_process = IntervalJoinProcessFunction::new(
+            join,
+            self.lower_bound.as_millis() as u64,
+            self.upper_bound.as_millis() as u64,
+            left_schema,
+            right_schema,
+        );
+
+        self.left
+            .connect(
+                vec![CoStream::from(DataStream::new(self.right))],
+                co_process,
+            )
+            .key_by(JoinKeySelectorFunction::new(key_schema))
+            .process(keyed_process)
+    }
+}
+
+impl Debug for IntervalJoinedStreams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IntervalJoinedStreams")
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .field("lower_bound", &self.lower_bound)
+            .field("upper_bound", &self.upper_bound)
+            .finish()
+    }
+}
+
+/// The records of the left stream joined with the records of the right stream with the same key
+/// in the same window. The keys are selected by `where_key` of the left and `equal_to` of the
+/// right, then the windows are assigned by `window`
+pub struct JoinedStreams {
+    left: StreamBuilder,
+    right: StreamBuilder,
+    left_key_selector: Option<Box<dyn KeySelectorFunction>>,
+    right_key_selector: Option<Box<dyn KeySelectorFunction>>,
+}
+
+impl JoinedStreams {
+    pub(crate) fn new(left: StreamBuilder, right: StreamBuilder) -> Self {
+        JoinedStreams {
+            left,
+            right,
+            left_key_selector: None,
+            right_key_selector: None,
+        }
+    }
+
+    /// The key selector of the left stream
+    pub fn where_key<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.left_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The key selector of the right stream
+    pub fn equal_to<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.right_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The records of both streams are assigned to the windows by the `window_assigner`, the
+    /// windows are fired by the trigger as the `WindowedStream`
+    pub fn window<W>(self, window_assigner: W) -> WindowedJoinedStreams
+    where
+        W: WindowAssigner + 'static,
+    {
+        let left_key_selector = self
+            .left_key_selector
+            .expect("JoinedStreams must have the `where_key` key selector");
+        let right_key_selector = self
+            .right_key_selector
+            .expect("JoinedStreams must have the `equal_to` key selector");
+
+        let left_schema = self.left.output_schema();
+        let right_schema = self.right.output_schema();
+        let key_schema = left_key_selector.key_schema(left_schema.clone());
+
+        let co_process = JoinCoProcessFunction::new(
+            left_key_selector,
+            right_key_selector,
+            left_schema.clone(),
+            right_schema.clone(),
+        );
+        let windowed_stream = self
+            .left
+            .connect(
+                vec![CoStream::from(DataStream::new(self.right))],
+                co_process,
+            )
+            .key_by(JoinKeySelectorFunction::new(key_schema.clone()))
+            .window(window_assigner);
+
+        WindowedJoinedStreams {
+            windowed_stream,
+            join_type: JoinType::Inner,
+            key_schema,
+            left_schema,
+            right_schema,
+        }
+    }
+}
+
+impl Debug for JoinedStreams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JoinedStreams")
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .finish()
+    }
+}
+
+/// The windowed `JoinedStreams`, all records of both streams of the key in the window are
+/// joined by the `JoinFunction` when the window is fired
+#[derive(Debug)]
+pub struct WindowedJoinedStreams {
+    windowed_stream: WindowedStream,
+    join_type: JoinType,
+    key_schema: FnSchema,
+    left_schema: FnSchema,
+    right_schema: FnSchema,
+}
+
+impl WindowedJoinedStreams {
+    /// Default is `JoinType::Inner`
+    pub fn join_type(mut self, join_type: JoinType) -> Self {
+        self.join_type = join_type;
+        self
+    }
+
+    /// See `TWindowedStream::trigger`
+    pub fn trigger<T>(mut self, trigger: T) -> Self
+    where
+        T: Trigger + 'static,
+    {
+        self.windowed_stream = self.windowed_stream.trigger(trigger);
+        self
+    }
+
+    /// See `TWindowedStream::allowed_lateness`
+    pub fn allowed_lateness(mut self, allowed_lateness: Duration) -> Self {
+        self.windowed_stream = self.windowed_stream.allowed_lateness(allowed_lateness);
+        self
+    }
+
+    pub fn process<F>(self, join: F) -> DataStream
+    where
+        F: JoinFunction + 'static,
+    {
+        let aggregate = WindowJoinAggregateFunction::new(join.parallelism());
+        let flat_map = WindowJoinFlatMapFunction::new(
+            join,
+            self.join_type,
+            self.key_schema,
+            self.left_schema,
+            self.right_schema,
+        );
+
+        self.windowed_stream.aggregate(aggregate).flat_map(flat_map)
+    }
+}
+
+/// The records of the left stream joined with the versions of the table rows with the same key
+/// valid at their timestamps, the versioned table is maintained by the right stream, e.g. to
+/// enrich the orders with the exchange rates at the order time. The keys are selected by
+/// `where_key` of the left and `equal_to` of the right, the primary key of the table
+pub struct TemporalJoinedStreams {
+    left: StreamBuilder,
+    table: StreamBuilder,
+    left_key_selector: Option<Box<dyn KeySelectorFunction>>,
+    table_key_selector: Option<Box<dyn KeySelectorFunction>>,
+}
+
+impl TemporalJoinedStreams {
+    pub(crate) fn new(left: StreamBuilder, table: StreamBuilder) -> Self {
+        TemporalJoinedStreams {
+            left,
+            table,
+            left_key_selector: None,
+            table_key_selector: None,
+        }
+    }
+
+    /// The key selector of the left stream
+    pub fn where_key<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.left_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The primary key selector of the table stream
+    pub fn equal_to<F>(mut self, key_selector: F) -> Self
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.table_key_selector = Some(Box::new(key_selector));
+        self
+    }
+
+    /// The left records without the valid version are passed to
+    /// `JoinFunction::left_outer_join`
+    pub fn process<F>(self, join: F) -> DataStream
+    where
+        F: JoinFunction + 'static,
+    {
+        let left_key_selector = self
+            .left_key_selector
+            .expect("TemporalJoinedStreams must have the `where_key` key selector");
+        let table_key_selector = self
+            .table_key_selector
+            .expect("TemporalJoinedStreams must have the `equal_to` key selector");
+
+        let left_schema = self.left.output_schema();
+        let table_schema = self.table.output_schema();
+        let key_schema = left_key_selector.key_schema(left_schema.clone());
+
+        let co_process = JoinCoProcessFunction::new(
+            left_key_selector,
+            table_key_selector,
+            left_schema.clone(),
+            table_schema.clone(),
+        );
+        let keyed_process = TemporalJoinProcessFunction::new(join, left_schema, table_schema);
+
+        self.left
+            .connect(
+                vec![CoStream::from(DataStream::new(self.table))],
+                co_process,
+            )
+            .key_by(JoinKeySelectorFunction::new(key_schema))
+            .process(keyed_process)
+    }
+}
+
+impl Debug for TemporalJoinedStreams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TemporalJoinedStreams")
+            .field("left", &self.left)
+            .field("table", &self.table)
+            .finish()
     }
 }
 
 #[derive(Debug)]
 pub struct SinkStream {
-    #[allow(dead_code)]
     end_stream: StreamBuilder,
 }
 
@@ -244,6 +1060,38 @@ impl SinkStream {
     pub(crate) fn new(end_stream: StreamBuilder) -> Self {
         SinkStream { end_stream }
     }
+
+    /// Set the stable id of the sink, see `DataStream::uid`
+    pub fn uid(self, uid: &str) -> Self {
+        self.end_stream.set_uid(uid);
+        self
+    }
+
+    /// Set the parallelism of the sink, see `DataStream::set_parallelism`
+    pub fn set_parallelism(self, parallelism: u16) -> Self {
+        assert!(parallelism > 0, "the parallelism must be positive");
+        self.end_stream.set_parallelism(parallelism);
+        self
+    }
+
+    /// Run the sink in its own task, see `DataStream::disable_chaining`
+    pub fn disable_chaining(self) -> Self {
+        self.end_stream.set_chaining(ChainingStrategy::Never);
+        self
+    }
+
+    /// Set the slot sharing group of the sink, see `DataStream::slot_sharing_group`
+    pub fn slot_sharing_group(self, slot_sharing_group: &str) -> Self {
+        self.end_stream.set_slot_sharing_group(slot_sharing_group);
+        self
+    }
+
+    /// Set how the records failed by the sink are handled, the dead letter isn't supported, see
+    /// `DataStream::error_policy`
+    pub fn error_policy(self, error_policy: ErrorPolicy) -> Self {
+        self.end_stream.set_error_policy(error_policy);
+        self
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -284,6 +1132,45 @@ impl StreamBuilder {
             stream_manager,
         }
     }
+
+    fn set_uid(&self, uid: &str) {
+        self.stream_manager.set_uid(self.cur_operator_id, uid);
+    }
+
+    fn set_parallelism(&self, parallelism: u16) {
+        self.stream_manager
+            .set_parallelism(self.cur_operator_id, parallelism);
+    }
+
+    fn set_chaining(&self, chaining: ChainingStrategy) {
+        self.stream_manager
+            .set_chaining(self.cur_operator_id, chaining);
+    }
+
+    fn set_slot_sharing_group(&self, slot_sharing_group: &str) {
+        self.stream_manager
+            .set_slot_sharing_group(self.cur_operator_id, slot_sharing_group);
+    }
+
+    fn set_error_policy(&self, error_policy: ErrorPolicy) {
+        self.stream_manager
+            .set_error_policy(self.cur_operator_id, error_policy);
+    }
+
+    fn output_schema(&self) -> FnSchema {
+        self.stream_manager.output_schema(self.cur_operator_id)
+    }
+
+    fn get_side_output(&self, output_tag: &OutputTag) -> DataStream {
+        let operator_id = self
+            .stream_manager
+            .add_side_output(self.cur_operator_id, output_tag.clone());
+
+        DataStream::new(StreamBuilder {
+            cur_operator_id: operator_id,
+            stream_manager: self.stream_manager.clone(),
+        })
+    }
 }
 
 impl TDataStream for StreamBuilder {
@@ -315,6 +1202,10 @@ impl TDataStream for StreamBuilder {
         DataStream::new(self)
     }
 
+    fn async_map(self, async_map: AsyncMapFunction) -> DataStream {
+        self.flat_map(async_map)
+    }
+
     fn key_by<F>(mut self, key_selector: F) -> KeyedStream
     where
         F: KeySelectorFunction + 'static,
@@ -346,6 +1237,31 @@ impl TDataStream for StreamBuilder {
         DataStream::new(self)
     }
 
+    fn distinct_by<F>(self, key_selector: F, ttl: Duration) -> DataStream
+    where
+        F: KeySelectorFunction + 'static,
+    {
+        self.key_by(key_selector)
+            .process(DistinctProcessFunction::new(ttl))
+    }
+
+    fn union(self, data_streams: Vec<DataStream>) -> DataStream {
+        if data_streams.is_empty() {
+            panic!("union must have at least one other stream");
+        }
+
+        let schema = self.output_schema();
+        for data_stream in &data_streams {
+            if data_stream.data_stream.output_schema() != schema {
+                panic!("the streams of union must have the same schema");
+            }
+        }
+
+        let data_streams = data_streams.into_iter().map(CoStream::from).collect();
+        let co_stream = self.connect(data_streams, UnionCoProcessFunction::new());
+        DataStream::new(co_stream.co_stream)
+    }
+
     fn connect<F>(self, data_streams: Vec<CoStream>, co_process: F) -> ConnectedStreams
     where
         F: CoProcessFunction + 'static,
@@ -370,7 +1286,86 @@ impl TDataStream for StreamBuilder {
         ConnectedStreams::new(co_stream, parent_ids)
     }
 
-    fn add_sink<O>(mut self, output_format: O)
+    fn throttle(self, records_per_second: u64, burst: u64) -> DataStream {
+        self.flat_map(ThrottleFlatMapFunction::new(records_per_second, burst))
+    }
+
+    fn sample(self, sampling: Sampling) -> DataStream {
+        self.filter(SampleFilterFunction::new(sampling))
+    }
+
+    fn rebalance(self) -> DataStream {
+        self.flat_map(RoundRobinFlagMapFunction::new())
+    }
+
+    fn rescale(self) -> DataStream {
+        self.flat_map(RescaleFlagMapFunction::new())
+    }
+
+    fn partition_custom<P, F>(self, partitioner: P, key_selector: F) -> DataStream
+    where
+        P: Partitioner + 'static,
+        F: KeySelectorFunction + 'static,
+    {
+        self.flat_map(CustomPartitionFlagMapFunction::new(
+            partitioner,
+            key_selector,
+        ))
+    }
+
+    fn broadcast(self) -> BroadcastStream {
+        let data_stream = self.flat_map(BroadcastFlagMapFunction::new());
+        BroadcastStream::new(data_stream.data_stream)
+    }
+
+    fn iterate<F>(self, max_wait: Duration, step: F) -> DataStream
+    where
+        F: FnOnce(DataStream) -> (DataStream, DataStream),
+    {
+        let schema = self.output_schema();
+        let (head_id, iteration_id) = self
+            .stream_manager
+            .add_iteration_head(self.cur_operator_id, max_wait);
+        let head = DataStream::new(StreamBuilder {
+            cur_operator_id: head_id,
+            stream_manager: self.stream_manager.clone(),
+        });
+
+        let (feedback, output) = step(head);
+        if feedback.data_stream.output_schema() != schema {
+            panic!("the feedback stream must have the same schema as the iteration input");
+        }
+
+        self.stream_manager
+            .add_iteration_tail(feedback.data_stream.cur_operator_id, iteration_id);
+        output
+    }
+
+    fn connect_broadcast<F>(self, broadcast_stream: BroadcastStream, f: F) -> ConnectedStreams
+    where
+        F: BroadcastProcessFunction + 'static,
+    {
+        let data_streams = vec![CoStream::from(broadcast_stream)];
+        self.connect(data_streams, BroadcastCoProcessFunction::new(f))
+    }
+
+    fn connect_keyed(self, other: DataStream) -> KeyedConnectedStreams {
+        KeyedConnectedStreams::new(self, other.data_stream)
+    }
+
+    fn interval_join(self, other: DataStream) -> IntervalJoinedStreams {
+        IntervalJoinedStreams::new(self, other.data_stream)
+    }
+
+    fn join(self, other: DataStream) -> JoinedStreams {
+        JoinedStreams::new(self, other.data_stream)
+    }
+
+    fn temporal_join(self, table: DataStream) -> TemporalJoinedStreams {
+        TemporalJoinedStreams::new(self, table.data_stream)
+    }
+
+    fn add_sink<O>(mut self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static,
     {
@@ -380,6 +1375,8 @@ impl TDataStream for StreamBuilder {
         self.cur_operator_id = self
             .stream_manager
             .add_operator(stream_sink, vec![self.cur_operator_id]);
+
+        SinkStream::new(self)
     }
 }
 
@@ -398,6 +1395,21 @@ impl TKeyedStream for StreamBuilder {
         WindowedStream::new(self)
     }
 
+    fn process<F>(mut self, keyed_process: F) -> DataStream
+    where
+        F: KeyedProcessFunction + 'static,
+    {
+        let parallelism = keyed_process.parallelism();
+        let stream_keyed_process =
+            StreamOperator::new_keyed_process(parallelism, Box::new(keyed_process));
+
+        self.cur_operator_id = self
+            .stream_manager
+            .add_operator(stream_keyed_process, vec![self.cur_operator_id]);
+
+        DataStream::new(self)
+    }
+
     fn add_sink<O>(mut self, output_format: O) -> SinkStream
     where
         O: OutputFormat + 'static,
@@ -413,15 +1425,13 @@ impl TKeyedStream for StreamBuilder {
     }
 }
 
-impl TWindowedStream for StreamBuilder {
-    fn reduce<F>(mut self, reduce: F) -> DataStream
-    where
-        F: ReduceFunction + 'static,
-    {
-        let parallelism = reduce.parallelism();
-        let reduce_func = Box::new(reduce);
-        let base_reduce_func = Box::new(WindowBaseReduceFunction::new(reduce_func));
-        let stream_reduce = StreamOperator::new_reduce(parallelism, base_reduce_func);
+impl StreamBuilder {
+    fn window_reduce(
+        mut self,
+        parallelism: u16,
+        base_reduce_func: WindowBaseReduceFunction,
+    ) -> DataStream {
+        let stream_reduce = StreamOperator::new_reduce(parallelism, Box::new(base_reduce_func));
 
         self.cur_operator_id = self
             .stream_manager
@@ -9,7 +9,7 @@ use serbuffer::FieldMetadata;
 
 use crate::core::data_types::Schema;
 use crate::core::runtime::{ChannelKey, CheckpointId};
-use crate::core::watermark::{MAX_WATERMARK, MIN_WATERMARK};
+use crate::core::watermark::{IDLE_WATERMARK, MAX_WATERMARK, MIN_WATERMARK};
 use crate::core::window::Window;
 
 lazy_static! {
@@ -21,7 +21,7 @@ pub type BufferReader<'a, 'b> = serbuffer::BufferReader<'a, 'b>;
 pub type BufferMutReader<'a, 'b> = serbuffer::BufferMutReader<'a, 'b>;
 pub type BufferWriter<'a, 'b> = serbuffer::BufferWriter<'a, 'b>;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum FnSchema {
     Empty,
     Single(Schema),
@@ -100,6 +100,10 @@ pub struct Record {
     pub(crate) location_windows: Option<Vec<Window>>,
     /// if `Record` comes from window drop, use it to mark the window
     pub(crate) trigger_window: Option<Window>,
+    /// the source split the record is read from, e.g. the kafka partition. it's not serialized
+    pub(crate) split: Option<u32>,
+    /// the name of the `OutputTag` if the record is emitted to a side output
+    pub(crate) output_tag: Option<String>,
 
     pub(crate) values: Buffer,
 }
@@ -132,6 +136,8 @@ impl Record {
             channel_key: ChannelKey::default(),
             location_windows: None,
             trigger_window: None,
+            split: None,
+            output_tag: None,
             values: Buffer::new(),
         }
     }
@@ -143,10 +149,21 @@ impl Record {
             channel_key: ChannelKey::default(),
             location_windows: None,
             trigger_window: None,
+            split: None,
+            output_tag: None,
             values: Buffer::with_capacity(capacity),
         }
     }
 
+    /// The record of the field values serialized by `as_buffer`, e.g. the record kept in a
+    /// keyed state
+    pub fn from_values(values: &[u8], timestamp: u64) -> Self {
+        let mut record = Record::new();
+        record.values = Buffer::from(BytesMut::from(values));
+        record.timestamp = timestamp;
+        record
+    }
+
     pub fn arity(&self) -> usize {
         self.values.len()
     }
@@ -191,6 +208,32 @@ impl Record {
         self.trigger_window.clone()
     }
 
+    /// Mark the source split the record is read from, e.g. the kafka partition, the watermarks
+    /// are generated per split, see `WatermarkStrategy::create_split_watermark_generator`
+    pub fn set_split(&mut self, split: u32) {
+        self.split = Some(split);
+    }
+
+    pub fn split(&self) -> Option<u32> {
+        self.split
+    }
+
+    /// The name of the `OutputTag` the record is emitted to, `None` for the main output
+    pub fn output_tag(&self) -> Option<&str> {
+        self.output_tag.as_deref()
+    }
+
+    /// The event time of the record, see `TimestampAssigner`
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Set the event time of the record derived from another one, e.g. the projected record
+    /// keeps the timestamp of its input record
+    pub fn set_timestamp(&mut self, timestamp: u64) {
+        self.timestamp = timestamp;
+    }
+
     pub fn as_buffer(&mut self) -> &mut Buffer {
         self.values.borrow_mut()
     }
@@ -212,6 +255,45 @@ impl Record {
     }
 }
 
+/// A named side output of the process functions, the records marked by `OutputTag::output` are
+/// separated from the main output and emitted to the stream returned by
+/// `DataStream::get_side_output`.
+///
+/// The records emitted to a tag that is never requested are kept in the main output
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OutputTag {
+    name: String,
+    schema: Schema,
+}
+
+impl OutputTag {
+    pub fn new(name: &str, schema: Schema) -> Self {
+        assert!(
+            !name.is_empty(),
+            "the name of `OutputTag` must not be empty"
+        );
+        OutputTag {
+            name: name.to_string(),
+            schema,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// The schema of the records emitted to the side output
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Mark the record to be emitted to the side output
+    pub fn output(&self, mut record: Record) -> Record {
+        record.output_tag = Some(self.name.clone());
+        record
+    }
+}
+
 impl Partition for Record {
     fn partition(&self) -> u16 {
         self.partition_num
@@ -224,7 +306,8 @@ impl Partition for Record {
 
 impl Serde for Record {
     fn capacity(&self) -> usize {
-        15 + self.values.len()
+        let output_tag_len = self.output_tag.as_ref().map(|x| x.len()).unwrap_or(0);
+        17 + output_tag_len + self.values.len()
     }
 
     fn serialize(&self, bytes: &mut BytesMut) {
@@ -234,6 +317,11 @@ impl Serde for Record {
         bytes.put_u16(self.partition_num);
         bytes.put_u64(self.timestamp);
 
+        // the records of the main output are marked by the empty tag
+        let output_tag = self.output_tag.as_deref().unwrap_or_default();
+        bytes.put_u16(output_tag.len() as u16);
+        bytes.put_slice(output_tag.as_bytes());
+
         bytes.put_u32(value_len as u32);
 
         let data_slice = self.values.as_slice();
@@ -249,6 +337,14 @@ impl Serde for Record {
         let partition_num = bytes.get_u16();
         let timestamp = bytes.get_u64();
 
+        let output_tag_len = bytes.get_u16() as usize;
+        let output_tag = if output_tag_len > 0 {
+            let output_tag = bytes.split_to(output_tag_len);
+            Some(String::from_utf8(output_tag.to_vec()).expect("invalid `Record` output tag"))
+        } else {
+            None
+        };
+
         let value_len = bytes.get_u32() as usize;
         assert_eq!(bytes.remaining(), value_len);
 
@@ -260,6 +356,8 @@ impl Serde for Record {
             channel_key: ChannelKey::default(),
             location_windows: None,
             trigger_window: None,
+            split: None,
+            output_tag,
             values: Buffer::from(values),
         }
     }
@@ -313,6 +411,11 @@ impl Watermark {
         self.timestamp == MAX_WATERMARK.timestamp
     }
 
+    /// All source splits of the task are idle, see `IDLE_WATERMARK`
+    pub(crate) fn is_idle(&self) -> bool {
+        self.timestamp == IDLE_WATERMARK.timestamp
+    }
+
     #[inline(always)]
     pub(crate) fn end(&self) -> bool {
         self.is_max()
@@ -711,7 +814,8 @@ mod tests {
 
     use serbuffer::types;
 
-    use crate::core::element::{Element, Record, Serde, StreamStatus, Watermark};
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{Element, OutputTag, Record, Serde, StreamStatus, Watermark};
 
     #[test]
     pub fn serde_element_record_test() {
@@ -750,6 +854,26 @@ mod tests {
             reader.get_binary(4).unwrap(),
             de_reader.get_binary(4).unwrap()
         );
+        assert_eq!(element_record_de.as_record_mut().output_tag(), None);
+    }
+
+    #[test]
+    pub fn serde_element_side_output_record_test() {
+        let data_types = vec![types::STRING];
+        let schema = Schema::new(vec![Field::new("v", DataType::String)]);
+        let tag = OutputTag::new("late", schema);
+
+        let mut record = Record::new();
+        record.timestamp = 3;
+        record.as_writer(&data_types).set_str("abc").unwrap();
+        let record = tag.output(record);
+
+        let mut data = Element::Record(record).to_bytes();
+        let mut element_record_de = Element::deserialize(data.borrow_mut());
+        let record_de = element_record_de.as_record_mut();
+        assert_eq!(record_de.output_tag(), Some("late"));
+        assert_eq!(record_de.timestamp, 3);
+        assert_eq!(record_de.as_reader(&data_types).get_str(0).unwrap(), "abc");
     }
 
     #[test]
@@ -1,11 +1,18 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
+use std::time::Duration;
 
+use crate::core::accumulator::AccumulatorValue;
 use crate::core::data_stream::{DataStream, StreamBuilder};
+use crate::core::element::{FnSchema, OutputTag};
+use crate::core::error_policy::ErrorPolicy;
 use crate::core::function::InputFormat;
 use crate::core::operator::StreamOperator;
 use crate::core::properties::Properties;
+use crate::core::restart_strategy::RestartStrategy;
 use crate::core::runtime::{ClusterDescriptor, OperatorId};
+use crate::dag::stream_graph::ChainingStrategy;
 use crate::dag::RawStreamGraph;
 use crate::runtime;
 
@@ -24,20 +31,60 @@ pub trait StreamApp: Send + Sync + Clone {
 
     /// Coordinator startup event. Called before Worker's resource allocation and startup
     async fn pre_worker_startup(&self, _cluster_descriptor: &ClusterDescriptor);
+
+    /// Coordinator event of the bounded job finished after all tasks terminated, only invoke
+    /// once on the `Coordinator`
+    async fn on_job_finished(&self, _job_result: &JobResult) {}
+}
+
+/// The result of the finished bounded job
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct JobResult {
+    pub application_id: String,
+    /// the accumulators of all tasks merged by the name, see `core::accumulator`
+    pub accumulators: BTreeMap<String, AccumulatorValue>,
+}
+
+/// How the job is executed, see `SystemProperties::set_execution_mode`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionMode {
+    /// the records are processed continuously and the state is checkpointed
+    Streaming,
+    /// the job of the bounded sources is executed stage by stage without the checkpoints, the
+    /// network input of a stage is blocked and spilled to the disk until the stages before it
+    /// have finished, then the operators of the stage are opened. The window reductions are
+    /// aggregated by the records sorted by the key, the sorted runs over the memory size are
+    /// spilled to the disk and merged
+    Batch,
+}
+
+impl Default for ExecutionMode {
+    fn default() -> Self {
+        ExecutionMode::Streaming
+    }
 }
 
 #[derive(Debug)]
 pub struct StreamExecutionEnvironment {
     pub(crate) stream_manager: Rc<StreamManager>,
+    pub(crate) restart_strategy: Option<RestartStrategy>,
 }
 
 impl StreamExecutionEnvironment {
     pub(crate) fn new() -> Self {
         StreamExecutionEnvironment {
             stream_manager: Rc::new(StreamManager::new()),
+            restart_strategy: None,
         }
     }
 
+    /// Set how the job is restarted after the failures, it takes precedence over
+    /// `SystemProperties::set_restart_strategy`
+    pub fn set_restart_strategy(&mut self, strategy: RestartStrategy) {
+        strategy.validate().expect("illegal restart strategy");
+        self.restart_strategy = Some(strategy);
+    }
+
     pub fn register_source<I>(&mut self, input_format: I) -> DataStream
     where
         I: InputFormat + 'static,
@@ -89,4 +136,71 @@ impl StreamManager {
             .add_operator(operator, parent_operator_ids)
             .expect("add operator error")
     }
+
+    pub fn add_side_output(&self, operator_id: OperatorId, output_tag: OutputTag) -> OperatorId {
+        self.stream_graph
+            .borrow_mut()
+            .add_side_output(operator_id, output_tag)
+            .expect("add side output error")
+    }
+
+    pub fn add_iteration_head(
+        &self,
+        operator_id: OperatorId,
+        max_wait: Duration,
+    ) -> (OperatorId, u32) {
+        self.stream_graph
+            .borrow_mut()
+            .add_iteration_head(operator_id, max_wait)
+            .expect("add iteration head error")
+    }
+
+    pub fn add_iteration_tail(&self, operator_id: OperatorId, iteration_id: u32) -> OperatorId {
+        self.stream_graph
+            .borrow_mut()
+            .add_iteration_tail(operator_id, iteration_id)
+            .expect("add iteration tail error")
+    }
+
+    pub fn output_schema(&self, operator_id: OperatorId) -> FnSchema {
+        self.stream_graph
+            .borrow()
+            .output_schema(operator_id)
+            .expect("operator not found")
+    }
+
+    pub fn set_uid(&self, operator_id: OperatorId, uid: &str) {
+        self.stream_graph
+            .borrow_mut()
+            .set_uid(operator_id, uid)
+            .expect("set operator uid error")
+    }
+
+    pub fn set_parallelism(&self, operator_id: OperatorId, parallelism: u16) {
+        self.stream_graph
+            .borrow_mut()
+            .set_parallelism(operator_id, parallelism)
+            .expect("set operator parallelism error")
+    }
+
+    pub fn set_chaining(&self, operator_id: OperatorId, chaining: ChainingStrategy) {
+        self.stream_graph
+            .borrow_mut()
+            .set_chaining(operator_id, chaining)
+            .expect("set operator chaining error")
+    }
+
+    pub fn set_slot_sharing_group(&self, operator_id: OperatorId, slot_sharing_group: &str) {
+        self.stream_graph
+            .borrow_mut()
+            .set_slot_sharing_group(operator_id, slot_sharing_group)
+            .expect("set operator slot sharing group error")
+    }
+
+    pub fn set_error_policy(&self, operator_id: OperatorId, error_policy: ErrorPolicy) {
+        self.stream_graph
+            .borrow_mut()
+            .set_error_policy(operator_id, error_policy)
+            .expect("set operator error policy error")
+    }
 }
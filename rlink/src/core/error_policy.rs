@@ -0,0 +1,35 @@
+use crate::core::element::OutputTag;
+
+/// How an operator handles the record its function fails on, i.e. the `try_*` method of the
+/// function returns an error for the record, e.g. a malformed message can't be parsed. A panic
+/// of the function always fails the job regardless of the policy. The policy is set by
+/// `DataStream::error_policy` and `SinkStream::error_policy`, it's applied by the map,
+/// flat map, filter, keyed process and sink operators.
+///
+/// The recovered failures are counted by the `record_errors.<operator>` accumulator, where the
+/// `<operator>` is the uid of the operator or `operator-<id>`. A function failed in the middle
+/// of a record may have changed its state partially, which isn't rolled back
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// the task panics and the job fails
+    FailJob,
+    /// drop the failed record
+    SkipAndCount,
+    /// emit the failed input record to the side output, the `OutputTag` must have the input
+    /// schema of the operator and be requested by `DataStream::get_side_output`, otherwise the
+    /// failed records are kept in the main output. Not supported by the sinks
+    DeadLetter(OutputTag),
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        ErrorPolicy::FailJob
+    }
+}
+
+impl ErrorPolicy {
+    /// Whether the failures are recovered from instead of failing the job
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(self, ErrorPolicy::FailJob)
+    }
+}
@@ -4,11 +4,18 @@ use std::sync::Arc;
 
 use futures::Stream;
 
-use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::accumulator::{self, Counter, DoubleSum, Histogram, LongSum};
+use crate::core::checkpoint::{
+    CheckpointFunction, CheckpointHandle, FunctionSnapshotContext, OperatorStateHandle,
+};
 use crate::core::element::{Element, FnSchema, Record};
 use crate::core::properties::Properties;
 use crate::core::runtime::{CheckpointId, OperatorId, TaskId};
+use crate::core::state::{BroadcastContext, ReadOnlyBroadcastContext, RuntimeContext};
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::core::window::ProcessWindowContext;
 use crate::dag::execution_graph::{ExecutionEdge, ExecutionNode};
+use crate::metrics::{MetricGroup, Tag};
 use crate::runtime::worker::WorkerTaskContext;
 
 /// Base class of all operators in the Rust API.
@@ -21,11 +28,18 @@ pub struct Context {
     pub application_id: String,
     pub application_properties: Properties,
     pub operator_id: OperatorId,
+    /// the name of the function of the operator
+    #[serde(default)]
+    pub operator_name: String,
     pub task_id: TaskId,
 
     pub checkpoint_id: CheckpointId,
     pub completed_checkpoint_id: Option<CheckpointId>,
     pub checkpoint_handle: Option<CheckpointHandle>,
+    /// the handles of the tasks of the operator in the restored checkpoint, used to
+    /// redistribute the `OperatorStateStore` and the keyed states when the parallelism is changed
+    #[serde(default)]
+    pub operator_state_handles: Vec<OperatorStateHandle>,
 
     pub input_schema: FnSchema,
     pub output_schema: FnSchema,
@@ -35,6 +49,8 @@ pub struct Context {
 
     #[serde(skip)]
     pub(crate) task_context: Option<Arc<WorkerTaskContext>>,
+    #[serde(skip)]
+    pub(crate) runtime_context: RuntimeContext,
 }
 
 impl Context {
@@ -48,6 +64,68 @@ impl Context {
         )
     }
 
+    /// The keyed states of the operator
+    pub fn runtime_context(&self) -> RuntimeContext {
+        self.runtime_context.clone()
+    }
+
+    /// The index of the parallel instance of the operator, from 0 to `parallelism() - 1`
+    pub fn subtask_index(&self) -> u16 {
+        self.task_id.task_number
+    }
+
+    /// The number of the parallel instances of the operator
+    pub fn parallelism(&self) -> u16 {
+        self.task_id.num_tasks
+    }
+
+    /// The name of the parallel instance for the logs, e.g. `MyFlatMapFunction (2/4)`
+    pub fn task_name(&self) -> String {
+        format!(
+            "{} ({}/{})",
+            self.operator_name,
+            self.subtask_index() + 1,
+            self.parallelism()
+        )
+    }
+
+    /// The times the tasks have been restarted after the failures, 0 for the first run
+    pub fn attempt_number(&self) -> u64 {
+        self.task_context
+            .as_ref()
+            .map(|x| x.cluster_descriptor().coordinator_manager.startup_number)
+            .unwrap_or_default()
+            .saturating_sub(1)
+    }
+
+    /// The metrics registered by the function are tagged by the task and the operator
+    pub fn metric_group(&self) -> MetricGroup {
+        let mut tags = self.task_id.to_tags();
+        tags.push(Tag::new("operator_id", self.operator_id.0));
+        tags.push(Tag::new("operator_name", self.operator_name.as_str()));
+        MetricGroup::new(tags)
+    }
+
+    /// The job-wide counter summed over all tasks by the coordinator, see `core::accumulator`
+    pub fn counter_accumulator(&self, name: &str) -> Counter {
+        accumulator::register_counter(self.task_id, name)
+    }
+
+    /// The job-wide sum of the `i64` values over all tasks
+    pub fn long_sum_accumulator(&self, name: &str) -> LongSum {
+        accumulator::register_long_sum(self.task_id, name)
+    }
+
+    /// The job-wide sum of the `f64` values over all tasks
+    pub fn double_sum_accumulator(&self, name: &str) -> DoubleSum {
+        accumulator::register_double_sum(self.task_id, name)
+    }
+
+    /// The job-wide count of each `i64` value over all tasks
+    pub fn histogram_accumulator(&self, name: &str) -> Histogram {
+        accumulator::register_histogram(self.task_id, name)
+    }
+
     pub(crate) fn task_context(&self) -> Arc<WorkerTaskContext> {
         self.task_context.as_ref().unwrap().clone()
     }
@@ -149,6 +227,12 @@ where
         false
     }
 
+    /// mark the `InputFormat` reads a finite input, the stream reaches the end once it's read.
+    /// The job in the `ExecutionMode::Batch` must have the bounded sources only
+    fn bounded(&self) -> bool {
+        false
+    }
+
     fn schema(&self, input_schema: FnSchema) -> FnSchema;
 
     fn parallelism(&self) -> u16;
@@ -169,6 +253,13 @@ where
 
     async fn write_element(&mut self, element: Element);
 
+    /// The fallible version of `write_element` called for the records, the error is handled
+    /// by the `ErrorPolicy` of the sink
+    async fn try_write_element(&mut self, element: Element) -> crate::core::Result<()> {
+        self.write_element(element).await;
+        Ok(())
+    }
+
     async fn close(&mut self) -> crate::core::Result<()>;
 
     // todo unsupported. `TwoPhaseCommitSinkFunction`
@@ -189,6 +280,29 @@ where
 {
     async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
     async fn flat_map_element(&mut self, element: Element) -> SendableElementStream;
+
+    /// The fallible version of `flat_map_element`, the error is handled by the `ErrorPolicy`
+    /// of the operator
+    async fn try_flat_map_element(
+        &mut self,
+        element: Element,
+    ) -> crate::core::Result<SendableElementStream> {
+        Ok(self.flat_map_element(element).await)
+    }
+
+    /// Emit the records held by the function, e.g. the pending results of the async lookups.
+    /// It's called before the barriers (see `flush_on_barrier`), the watermarks and the stream
+    /// status are forwarded, so the held records are not overtaken by them
+    async fn flush(&mut self) -> Option<SendableElementStream> {
+        None
+    }
+
+    /// Whether the held records are emitted before the barriers, the function keeping them
+    /// across the barriers must checkpoint them by `CheckpointFunction::snapshot_state`
+    fn flush_on_barrier(&self) -> bool {
+        true
+    }
+
     async fn close(&mut self) -> crate::core::Result<()>;
 
     fn schema(&self, input_schema: FnSchema) -> FnSchema;
@@ -203,6 +317,12 @@ where
 
     async fn filter(&self, record: &mut Record) -> bool;
 
+    /// The fallible version of `filter`, the error is handled by the `ErrorPolicy` of the
+    /// operator
+    async fn try_filter(&self, record: &mut Record) -> crate::core::Result<bool> {
+        Ok(self.filter(record).await)
+    }
+
     async fn close(&mut self) -> crate::core::Result<()>;
 }
 
@@ -220,6 +340,12 @@ where
     fn key_schema(&self, input_schema: FnSchema) -> FnSchema;
 }
 
+/// Select the child task of the records by their keys, see `TDataStream::partition_custom`
+pub trait Partitioner: Send + Sync {
+    /// The index of the child task in `[0, num_partitions)` of the key
+    fn partition(&self, key: &mut Record, num_partitions: u16) -> u16;
+}
+
 #[async_trait]
 pub trait ReduceFunction
 where
@@ -229,13 +355,89 @@ where
 
     fn reduce(&self, value: Option<&mut Record>, record: &mut Record) -> Record;
 
+    /// Merge the two reduced values, it's called when the merging windows are merged, e.g. the
+    /// session windows. It's only called if `supports_merge` returns true
+    fn merge(&self, _value: &mut Record, _other: &mut Record) -> Record {
+        unimplemented!("{} doesn't support merging the reduced values", self.name())
+    }
+
+    /// Whether `merge` is implemented, the function of the merging windows must support it,
+    /// otherwise the DAG is failed to build
+    fn supports_merge(&self) -> bool {
+        false
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()>;
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema;
+
+    fn parallelism(&self) -> u16;
+}
+
+/// Aggregate the input records of a window and key incrementally into an accumulator, the
+/// result is computed from the accumulator when the window is fired. The input, accumulator and
+/// result records have their own schemas, e.g. the average of a field accumulates the sum and
+/// count of it
+#[async_trait]
+pub trait AggregateFunction
+where
+    Self: NamedFunction + Send + Sync,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
+
+    /// Create the accumulator of a new window and key
+    fn create_accumulator(&self) -> Record;
+
+    /// Add the record into the accumulator, returns the new accumulator
+    fn add(&self, accumulator: &mut Record, record: &mut Record) -> Record;
+
+    /// The result of the accumulator
+    fn get_result(&self, accumulator: &mut Record) -> Record;
+
+    /// Merge the two accumulators, it's called when the merging windows are merged, e.g. the
+    /// session windows. It's only called if `supports_merge` returns true
+    fn merge(&self, _accumulator: &mut Record, _other: &mut Record) -> Record {
+        unimplemented!("{} doesn't support merging the accumulators", self.name())
+    }
+
+    /// Whether `merge` is implemented, the function of the merging windows must support it,
+    /// otherwise the DAG is failed to build
+    fn supports_merge(&self) -> bool {
+        false
+    }
+
     async fn close(&mut self) -> crate::core::Result<()>;
 
+    /// The schema of the result
     fn schema(&self, input_schema: FnSchema) -> FnSchema;
 
     fn parallelism(&self) -> u16;
 }
 
+/// Process the reduced value of each key when the window is fired, with the metadata of the
+/// window, e.g. to emit the window boundaries into the output records. The reduced value is the
+/// result of the `ReduceFunction` or `AggregateFunction` of the window
+#[async_trait]
+pub trait ProcessWindowFunction
+where
+    Self: NamedFunction + Send + Sync,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
+
+    /// Returns the output value of the key
+    fn process(
+        &self,
+        key: &mut Record,
+        value: &mut Record,
+        context: &mut ProcessWindowContext,
+    ) -> Record;
+
+    async fn close(&mut self) -> crate::core::Result<()>;
+
+    /// The schema of the output value, the `input_schema` is the schema of the reduced value
+    fn schema(&self, input_schema: FnSchema) -> FnSchema;
+}
+
 #[async_trait]
 pub(crate) trait BaseReduceFunction
 where
@@ -243,13 +445,25 @@ where
 {
     async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
 
-    async fn reduce(&mut self, key: Record, record: Record);
+    /// Reduce the record, returns the records of the windows fired by the trigger
+    async fn reduce(&mut self, key: Record, record: Record) -> Vec<Record>;
 
     async fn drop_state(&mut self, watermark_timestamp: u64) -> Vec<Record>;
 
+    /// The time in millis the windows are kept after the watermark passes the end of them
+    fn allowed_lateness(&self) -> u64;
+
+    /// Handle the record arrived after the allowed lateness of its windows
+    async fn late_element(&mut self, record: Record);
+
     async fn close(&mut self) -> crate::core::Result<()>;
 
     fn value_schema(&self, key_schema: FnSchema) -> FnSchema;
+
+    /// Whether the reduced values of the windows can be merged, see `WindowAssigner::is_merging`
+    fn supports_merge(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait]
@@ -270,3 +484,165 @@ where
 
     fn schema(&self, input_schema: FnSchema) -> FnSchema;
 }
+
+/// Process the records of a data stream against the broadcast states, which are updated by the
+/// records of the connected broadcast stream, e.g. the dynamic rules or the feature flags.
+/// The broadcast states are checkpointed by the runtime
+#[async_trait]
+pub trait BroadcastProcessFunction
+where
+    Self: NamedFunction + Send + Sync,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
+
+    /// This method is called for each record of the data stream, the broadcast states are
+    /// read-only so they're kept the same on all parallel instances
+    async fn process_element(
+        &mut self,
+        record: Record,
+        context: &ReadOnlyBroadcastContext,
+    ) -> SendableElementStream;
+
+    /// This method is called for each record of the broadcast stream on every parallel instance
+    async fn process_broadcast_element(
+        &mut self,
+        record: Record,
+        context: &BroadcastContext,
+    ) -> SendableElementStream;
+
+    async fn close(&mut self) -> crate::core::Result<()>;
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema;
+}
+
+/// Process the records of a keyed stream one by one with the keyed states and the timers of the
+/// record's key, e.g. the custom sessionization, the timeouts and the TTL logic.
+///
+/// The keyed states of `Context::runtime_context` and the timers are scoped to the key of the
+/// processed record or the fired timer, and both of them are checkpointed by the runtime
+#[async_trait]
+pub trait KeyedProcessFunction
+where
+    Self: NamedFunction + Send + Sync,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
+
+    /// This method is called for each record of the keyed stream
+    async fn process_element(
+        &mut self,
+        record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream;
+
+    /// The fallible version of `process_element`, the error is handled by the `ErrorPolicy`
+    /// of the operator
+    async fn try_process_element(
+        &mut self,
+        record: Record,
+        timer_service: &mut TimerService,
+    ) -> crate::core::Result<SendableElementStream> {
+        Ok(self.process_element(record, timer_service).await)
+    }
+
+    /// This method is called when a timer of the `timer_service.current_key()` fires, the event
+    /// time timers fire once the watermark passes them
+    async fn on_timer(
+        &mut self,
+        timestamp: u64,
+        time_domain: TimeDomain,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream;
+
+    async fn close(&mut self) -> crate::core::Result<()>;
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema;
+
+    fn parallelism(&self) -> u16;
+}
+
+/// Process the records of two connected streams of the different schemas, keyed by the key
+/// selectors of their own stream, with the keyed states and the timers shared by both streams,
+/// e.g. the control stream enabling the records of the data stream. See
+/// `TDataStream::connect_keyed`.
+///
+/// The records are read by the schema of their own stream, and the states and the timers are
+/// scoped to the key of the processed record or the fired timer like `KeyedProcessFunction`
+#[async_trait]
+pub trait KeyedCoProcessFunction
+where
+    Self: NamedFunction + Send + Sync,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
+
+    /// This method is called for each record of the left stream
+    async fn process_left(
+        &mut self,
+        record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream;
+
+    /// This method is called for each record of the right stream
+    async fn process_right(
+        &mut self,
+        record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream;
+
+    /// This method is called when a timer of the `timer_service.current_key()` fires
+    async fn on_timer(
+        &mut self,
+        timestamp: u64,
+        time_domain: TimeDomain,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream;
+
+    async fn close(&mut self) -> crate::core::Result<()>;
+
+    fn schema(&self, left_schema: FnSchema, right_schema: FnSchema) -> FnSchema;
+
+    fn parallelism(&self) -> u16;
+}
+
+/// The type of the windowed join, see `TDataStream::join`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinType {
+    Inner,
+    /// The left records without any matched right record are emitted by
+    /// `JoinFunction::left_outer_join`
+    LeftOuter,
+    /// The right records without any matched left record are emitted by
+    /// `JoinFunction::right_outer_join`
+    RightOuter,
+}
+
+/// Join the records of the left and the right streams with the same key, see
+/// `TDataStream::interval_join`, `TDataStream::join` and `TDataStream::temporal_join`
+#[async_trait]
+pub trait JoinFunction
+where
+    Self: NamedFunction + Send + Sync,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()>;
+
+    /// This method is called for each pair of the joined records, the timestamp of the output
+    /// record is the later one of the pair
+    fn join(&self, left: &mut Record, right: &mut Record) -> Record;
+
+    /// This method is called for each unmatched left record of the left outer join, the
+    /// unmatched record is dropped if `None`
+    fn left_outer_join(&self, _left: &mut Record) -> Option<Record> {
+        None
+    }
+
+    /// This method is called for each unmatched right record of the right outer join, the
+    /// unmatched record is dropped if `None`
+    fn right_outer_join(&self, _right: &mut Record) -> Option<Record> {
+        None
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()>;
+
+    fn schema(&self, left_schema: FnSchema, right_schema: FnSchema) -> FnSchema;
+
+    fn parallelism(&self) -> u16;
+}
@@ -0,0 +1,105 @@
+use crate::utils::hash::hash_code;
+
+/// The default number of the key groups, it's the upper bound of the keyed job's parallelism
+pub const DEFAULT_MAX_PARALLELISM: u16 = 128;
+
+/// The keys are hashed to `max_parallelism` key groups, and each task of a keyed job owns a
+/// contiguous range of the key groups. The key group of a key never changes, so the keyed
+/// states of a checkpoint can be redistributed to any parallelism not greater than the
+/// `max_parallelism`, which must be the same across the restarts
+pub fn assign_to_key_group(key: &[u8], max_parallelism: u16) -> u16 {
+    let hash_code = hash_code(key).unwrap_or(0);
+    (hash_code % max_parallelism as u32) as u16
+}
+
+/// The task number of the task owning the `key_group`
+pub fn operator_index_for_key_group(max_parallelism: u16, parallelism: u16, key_group: u16) -> u16 {
+    (key_group as u32 * parallelism as u32 / max_parallelism as u32) as u16
+}
+
+/// The task number of the task owning the `key`
+pub fn assign_key_to_operator(key: &[u8], max_parallelism: u16, parallelism: u16) -> u16 {
+    let key_group = assign_to_key_group(key, max_parallelism);
+    operator_index_for_key_group(max_parallelism, parallelism, key_group)
+}
+
+/// The key groups `[start, end)` owned by a task
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyGroupRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl KeyGroupRange {
+    /// The range of the task `task_number`, it's the inverse of `operator_index_for_key_group`
+    pub fn of_operator(max_parallelism: u16, parallelism: u16, task_number: u16) -> Self {
+        let max_parallelism = max_parallelism as u32;
+        let parallelism = parallelism as u32;
+        let task_number = task_number as u32;
+
+        let start = (task_number * max_parallelism + parallelism - 1) / parallelism;
+        let end = ((task_number + 1) * max_parallelism + parallelism - 1) / parallelism;
+        KeyGroupRange {
+            start: start as u16,
+            end: end as u16,
+        }
+    }
+
+    pub fn contains(&self, key_group: u16) -> bool {
+        key_group >= self.start && key_group < self.end
+    }
+
+    /// Whether some key groups are in both ranges
+    pub fn overlaps(&self, other: &KeyGroupRange) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    pub fn len(&self) -> u16 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::key_group::{operator_index_for_key_group, KeyGroupRange};
+
+    #[test]
+    pub fn key_group_range_test() {
+        for max_parallelism in [1u16, 7, 128] {
+            for parallelism in 1..=max_parallelism.min(20) {
+                let mut next_start = 0;
+                for task_number in 0..parallelism {
+                    let range =
+                        KeyGroupRange::of_operator(max_parallelism, parallelism, task_number);
+                    assert_eq!(range.start, next_start);
+                    assert!(!range.is_empty());
+                    for key_group in range.start..range.end {
+                        assert_eq!(
+                            operator_index_for_key_group(max_parallelism, parallelism, key_group),
+                            task_number
+                        );
+                    }
+                    next_start = range.end;
+                }
+                assert_eq!(next_start, max_parallelism);
+            }
+        }
+    }
+
+    #[test]
+    pub fn key_group_range_overlaps_test() {
+        let range = KeyGroupRange::of_operator(128, 2, 1);
+        assert!(range.overlaps(&range));
+        assert!(!range.overlaps(&KeyGroupRange::of_operator(128, 2, 0)));
+
+        // the task of 2 tasks overlaps the 2 tasks of 4 in its key groups
+        let overlapped: Vec<u16> = (0..4)
+            .filter(|task_number| range.overlaps(&KeyGroupRange::of_operator(128, 4, *task_number)))
+            .collect();
+        assert_eq!(overlapped, vec![2, 3]);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod accumulator;
 pub mod backend;
 pub mod checkpoint;
 pub mod cluster;
@@ -6,10 +7,17 @@ pub mod data_types;
 pub mod element;
 pub mod env;
 pub mod error;
+pub mod error_policy;
 pub mod function;
+pub mod key_group;
 pub mod operator;
 pub mod properties;
+#[cfg(feature = "queryable-state")]
+pub mod queryable_state;
+pub mod restart_strategy;
 pub mod runtime;
+pub mod state;
+pub mod timer;
 pub mod watermark;
 pub mod window;
 
@@ -4,7 +4,7 @@ use crate::core::data_types::Schema;
 use crate::core::element::FnSchema;
 use crate::core::function::{
     BaseReduceFunction, CoProcessFunction, FilterFunction, FlatMapFunction, InputFormat,
-    KeySelectorFunction, NamedFunction, OutputFormat,
+    KeySelectorFunction, KeyedProcessFunction, NamedFunction, OutputFormat,
 };
 use crate::core::watermark::WatermarkStrategy;
 use crate::core::window::WindowAssigner;
@@ -88,6 +88,7 @@ pub(crate) enum StreamOperator {
     StreamCoProcess(DefaultStreamOperator<dyn CoProcessFunction>),
     StreamKeyBy(DefaultStreamOperator<dyn KeySelectorFunction>),
     StreamReduce(DefaultStreamOperator<dyn BaseReduceFunction>),
+    StreamKeyedProcess(DefaultStreamOperator<dyn KeyedProcessFunction>),
     StreamWatermarkAssigner(DefaultStreamOperator<dyn WatermarkStrategy>),
     StreamWindowAssigner(DefaultStreamOperator<dyn WindowAssigner>),
     StreamSink(DefaultStreamOperator<dyn OutputFormat>),
@@ -132,6 +133,15 @@ impl StreamOperator {
         StreamOperator::StreamReduce(operator)
     }
 
+    pub fn new_keyed_process(
+        parallelism: u16,
+        keyed_process_fn: Box<dyn KeyedProcessFunction>,
+    ) -> Self {
+        let operator =
+            DefaultStreamOperator::new(parallelism, FunctionCreator::User, keyed_process_fn);
+        StreamOperator::StreamKeyedProcess(operator)
+    }
+
     pub fn new_watermark_assigner(watermark_assigner: Box<dyn WatermarkStrategy>) -> Self {
         let operator = DefaultStreamOperator::new(
             DEFAULT_PARALLELISM,
@@ -160,6 +170,14 @@ impl StreamOperator {
         }
     }
 
+    pub fn is_bounded(&self) -> bool {
+        if let StreamOperator::StreamSource(stream_source) = self {
+            stream_source.operator_fn.bounded()
+        } else {
+            false
+        }
+    }
+
     pub fn is_source(&self) -> bool {
         if let StreamOperator::StreamSource(_stream_source) = self {
             return true;
@@ -233,6 +251,7 @@ impl TStreamOperator for StreamOperator {
             StreamOperator::StreamCoProcess(op) => op.operator_name(),
             StreamOperator::StreamKeyBy(op) => op.operator_name(),
             StreamOperator::StreamReduce(op) => op.operator_name(),
+            StreamOperator::StreamKeyedProcess(op) => op.operator_name(),
             StreamOperator::StreamWatermarkAssigner(op) => op.operator_name(),
             StreamOperator::StreamWindowAssigner(op) => op.operator_name(),
             StreamOperator::StreamSink(op) => op.operator_name(),
@@ -247,6 +266,7 @@ impl TStreamOperator for StreamOperator {
             StreamOperator::StreamCoProcess(op) => op.parallelism(),
             StreamOperator::StreamKeyBy(op) => op.parallelism(),
             StreamOperator::StreamReduce(op) => op.parallelism(),
+            StreamOperator::StreamKeyedProcess(op) => op.parallelism(),
             StreamOperator::StreamWatermarkAssigner(op) => op.parallelism(),
             StreamOperator::StreamWindowAssigner(op) => op.parallelism(),
             StreamOperator::StreamSink(op) => op.parallelism(),
@@ -278,6 +298,11 @@ impl TStreamOperator for StreamOperator {
 
                 FnSchema::Tuple(Schema::empty(), schema)
             }
+            StreamOperator::StreamKeyedProcess(op) => {
+                // the records are keyed by the dependency `KeyBy` of the job
+                op.operator_fn
+                    .schema(FnSchema::Single(input_schema.first().clone()))
+            }
             StreamOperator::StreamWatermarkAssigner(_op) => input_schema,
             StreamOperator::StreamWindowAssigner(_op) => input_schema,
             StreamOperator::StreamSink(op) => op.operator_fn.schema(input_schema),
@@ -292,6 +317,7 @@ impl TStreamOperator for StreamOperator {
             StreamOperator::StreamCoProcess(op) => op.fn_creator(),
             StreamOperator::StreamKeyBy(op) => op.fn_creator(),
             StreamOperator::StreamReduce(op) => op.fn_creator(),
+            StreamOperator::StreamKeyedProcess(op) => op.fn_creator(),
             StreamOperator::StreamWatermarkAssigner(op) => op.fn_creator(),
             StreamOperator::StreamWindowAssigner(op) => op.fn_creator(),
             StreamOperator::StreamSink(op) => op.fn_creator(),
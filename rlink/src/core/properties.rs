@@ -4,8 +4,12 @@ use std::ops::Index;
 use std::str::FromStr;
 use std::time::Duration;
 
-use crate::core::backend::{CheckpointBackend, KeyedStateBackend};
+use crate::core::backend::{CheckpointBackend, HighAvailabilityBackend, KeyedStateBackend};
+use crate::core::checkpoint::{CheckpointConfig, CheckpointMode};
 use crate::core::cluster::MetadataStorageType;
+use crate::core::env::ExecutionMode;
+use crate::core::key_group::DEFAULT_MAX_PARALLELISM;
+use crate::core::restart_strategy::{FailoverStrategy, RestartStrategy};
 
 pub type ClusterMode = crate::runtime::ClusterMode;
 
@@ -34,10 +38,56 @@ pub trait SystemProperties {
     fn set_checkpoint_ttl(&mut self, ttl: Duration);
     fn get_checkpoint_ttl(&self) -> anyhow::Result<Duration>;
 
+    /// set the checkpoint options, the `interval` is set as the checkpoint interval too
+    fn set_checkpoint_config(&mut self, config: CheckpointConfig);
+    /// the checkpoint options, or the default options with the checkpoint interval if absent
+    fn get_checkpoint_config(&self) -> anyhow::Result<CheckpointConfig>;
+
+    fn set_checkpoint_mode(&mut self, mode: CheckpointMode);
+    fn get_checkpoint_mode(&self) -> anyhow::Result<CheckpointMode>;
+
+    /// the savepoint directory the application is restored from, e.g.
+    /// `s3://bucket/savepoints/savepoint-10`
+    fn set_savepoint_path(&mut self, path: &str);
+    fn get_savepoint_path(&self) -> anyhow::Result<String>;
+
     fn get_cluster_mode(&self) -> anyhow::Result<ClusterMode>;
 
     fn set_pub_sub_channel_size(&mut self, channel_size: usize);
     fn get_pub_sub_channel_size(&self) -> anyhow::Result<usize>;
+
+    /// serve the queryable states on the workers, requires the `queryable-state` feature
+    fn set_queryable_state(&mut self, enable: bool);
+    fn get_queryable_state(&self) -> anyhow::Result<bool>;
+
+    /// the number of the key groups, the upper bound of the keyed jobs' parallelism. The keyed
+    /// states are rescaled by the key groups, so it must not be changed across the restarts.
+    ///
+    /// The keys are partitioned to the tasks by their key groups instead of
+    /// `hash % parallelism`, the keyed states of the checkpoints and the savepoints taken by
+    /// the versions partitioning by `hash % parallelism` can't be restored
+    fn set_max_parallelism(&mut self, max_parallelism: u16);
+    /// the max parallelism, or `DEFAULT_MAX_PARALLELISM` if absent
+    fn get_max_parallelism(&self) -> u16;
+
+    /// execute the job in the batch mode, all the sources must be bounded
+    fn set_execution_mode(&mut self, mode: ExecutionMode);
+    /// the execution mode, or `ExecutionMode::Streaming` if absent
+    fn get_execution_mode(&self) -> ExecutionMode;
+
+    /// how the job is restarted after the failures, see `RestartStrategy`
+    fn set_restart_strategy(&mut self, strategy: RestartStrategy);
+    fn get_restart_strategy(&self) -> anyhow::Result<RestartStrategy>;
+
+    /// which tasks are restarted after a failure, see `FailoverStrategy`
+    fn set_failover_strategy(&mut self, strategy: FailoverStrategy);
+    /// the failover strategy, or `FailoverStrategy::Full` if absent
+    fn get_failover_strategy(&self) -> FailoverStrategy;
+
+    /// elect the coordinator and persist its metadata in the backend, so a standby coordinator
+    /// takes over the job once the leader crashed
+    fn set_high_availability(&mut self, backend: HighAvailabilityBackend);
+    fn get_high_availability(&self) -> anyhow::Result<HighAvailabilityBackend>;
 }
 
 pub trait FunctionProperties {
@@ -303,8 +353,17 @@ const SYSTEM_KEYED_STATE_BACKEND: &str = "SYSTEM_KEYED_STATE_BACKEND";
 const SYSTEM_CHECKPOINT: &str = "SYSTEM_CHECKPOINT";
 const SYSTEM_CHECKPOINT_INTERVAL: &str = "SYSTEM_CHECKPOINT_INTERVAL";
 const SYSTEM_CHECKPOINT_TTL: &str = "SYSTEM_CHECKPOINT_TTL";
+const SYSTEM_CHECKPOINT_MODE: &str = "SYSTEM_CHECKPOINT_MODE";
+const SYSTEM_CHECKPOINT_CONFIG: &str = "SYSTEM_CHECKPOINT_CONFIG";
+const SYSTEM_SAVEPOINT_PATH: &str = "SYSTEM_SAVEPOINT_PATH";
 const SYSTEM_CLUSTER_MODE: &str = "SYSTEM_CLUSTER_MODE";
 const SYSTEM_PUB_SUB_CHANNEL_SIZE: &str = "SYSTEM_PUB_SUB_CHANNEL_SIZE";
+const SYSTEM_QUERYABLE_STATE: &str = "SYSTEM_QUERYABLE_STATE";
+const SYSTEM_MAX_PARALLELISM: &str = "SYSTEM_MAX_PARALLELISM";
+const SYSTEM_EXECUTION_MODE: &str = "SYSTEM_EXECUTION_MODE";
+const SYSTEM_RESTART_STRATEGY: &str = "SYSTEM_RESTART_STRATEGY";
+const SYSTEM_FAILOVER_STRATEGY: &str = "SYSTEM_FAILOVER_STRATEGY";
+const SYSTEM_HIGH_AVAILABILITY: &str = "SYSTEM_HIGH_AVAILABILITY";
 
 impl SystemProperties for Properties {
     fn set_application_name(&mut self, application_name: &str) {
@@ -369,6 +428,37 @@ impl SystemProperties for Properties {
         self.get_duration(SYSTEM_CHECKPOINT_TTL)
     }
 
+    fn set_checkpoint_config(&mut self, config: CheckpointConfig) {
+        self.set_checkpoint_interval(config.interval);
+        let value = serde_json::to_string(&config).unwrap();
+        self.set_string(SYSTEM_CHECKPOINT_CONFIG.to_string(), value);
+    }
+
+    fn get_checkpoint_config(&self) -> anyhow::Result<CheckpointConfig> {
+        match self.get_string(SYSTEM_CHECKPOINT_CONFIG) {
+            Ok(value) => serde_json::from_str(value.as_str()).map_err(|e| anyhow!(e)),
+            Err(_e) => self.get_checkpoint_interval().map(CheckpointConfig::new),
+        }
+    }
+
+    fn set_checkpoint_mode(&mut self, mode: CheckpointMode) {
+        let value = serde_json::to_string(&mode).unwrap();
+        self.set_string(SYSTEM_CHECKPOINT_MODE.to_string(), value);
+    }
+
+    fn get_checkpoint_mode(&self) -> anyhow::Result<CheckpointMode> {
+        let value = self.get_string(SYSTEM_CHECKPOINT_MODE)?;
+        serde_json::from_str(value.as_str()).map_err(|e| anyhow!(e))
+    }
+
+    fn set_savepoint_path(&mut self, path: &str) {
+        self.set_str(SYSTEM_SAVEPOINT_PATH, path);
+    }
+
+    fn get_savepoint_path(&self) -> anyhow::Result<String> {
+        self.get_string(SYSTEM_SAVEPOINT_PATH)
+    }
+
     fn get_cluster_mode(&self) -> anyhow::Result<ClusterMode> {
         let value = self.get_string(SYSTEM_CLUSTER_MODE)?;
         ClusterMode::try_from(value.as_str())
@@ -381,6 +471,70 @@ impl SystemProperties for Properties {
     fn get_pub_sub_channel_size(&self) -> anyhow::Result<usize> {
         self.get_usize(SYSTEM_PUB_SUB_CHANNEL_SIZE)
     }
+
+    fn set_queryable_state(&mut self, enable: bool) {
+        self.set_bool(SYSTEM_QUERYABLE_STATE, enable);
+    }
+
+    fn get_queryable_state(&self) -> anyhow::Result<bool> {
+        self.get_bool(SYSTEM_QUERYABLE_STATE)
+    }
+
+    fn set_max_parallelism(&mut self, max_parallelism: u16) {
+        if max_parallelism == 0 {
+            panic!("the `max_parallelism` must be positive");
+        }
+        self.set_u16(SYSTEM_MAX_PARALLELISM, max_parallelism);
+    }
+
+    fn get_max_parallelism(&self) -> u16 {
+        self.get_u16(SYSTEM_MAX_PARALLELISM)
+            .unwrap_or(DEFAULT_MAX_PARALLELISM)
+    }
+
+    fn set_execution_mode(&mut self, mode: ExecutionMode) {
+        let value = serde_json::to_string(&mode).unwrap();
+        self.set_string(SYSTEM_EXECUTION_MODE.to_string(), value);
+    }
+
+    fn get_execution_mode(&self) -> ExecutionMode {
+        self.get_string(SYSTEM_EXECUTION_MODE)
+            .ok()
+            .and_then(|value| serde_json::from_str(value.as_str()).ok())
+            .unwrap_or_default()
+    }
+    fn set_restart_strategy(&mut self, strategy: RestartStrategy) {
+        strategy.validate().expect("illegal restart strategy");
+        let value = serde_json::to_string(&strategy).unwrap();
+        self.set_string(SYSTEM_RESTART_STRATEGY.to_string(), value);
+    }
+
+    fn get_restart_strategy(&self) -> anyhow::Result<RestartStrategy> {
+        let value = self.get_string(SYSTEM_RESTART_STRATEGY)?;
+        serde_json::from_str(value.as_str()).map_err(|e| anyhow!(e))
+    }
+
+    fn set_failover_strategy(&mut self, strategy: FailoverStrategy) {
+        let value = serde_json::to_string(&strategy).unwrap();
+        self.set_string(SYSTEM_FAILOVER_STRATEGY.to_string(), value);
+    }
+
+    fn get_failover_strategy(&self) -> FailoverStrategy {
+        self.get_string(SYSTEM_FAILOVER_STRATEGY)
+            .ok()
+            .and_then(|value| serde_json::from_str(value.as_str()).ok())
+            .unwrap_or_default()
+    }
+
+    fn set_high_availability(&mut self, backend: HighAvailabilityBackend) {
+        let value = serde_json::to_string(&backend).unwrap();
+        self.set_string(SYSTEM_HIGH_AVAILABILITY.to_string(), value);
+    }
+
+    fn get_high_availability(&self) -> anyhow::Result<HighAvailabilityBackend> {
+        let value = self.get_string(SYSTEM_HIGH_AVAILABILITY)?;
+        serde_json::from_str(value.as_str()).map_err(|e| anyhow!(e))
+    }
 }
 
 impl InnerSystemProperties for Properties {
@@ -391,7 +545,13 @@ impl InnerSystemProperties for Properties {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::properties::Properties;
+    use std::time::Duration;
+
+    use crate::core::backend::HighAvailabilityBackend;
+    use crate::core::checkpoint::CheckpointConfig;
+    use crate::core::env::ExecutionMode;
+    use crate::core::properties::{Properties, SystemProperties};
+    use crate::core::restart_strategy::{FailoverStrategy, RestartStrategy};
 
     #[test]
     pub fn row_properties() {
@@ -413,4 +573,61 @@ mod tests {
         println!("{:?}", properties);
         println!("{:?}", sub_properties);
     }
+
+    #[test]
+    pub fn test_checkpoint_config() {
+        let mut properties = Properties::new();
+        properties.set_checkpoint_interval(Duration::from_secs(15));
+        let config = properties.get_checkpoint_config().unwrap();
+        assert_eq!(config, CheckpointConfig::new(Duration::from_secs(15)));
+
+        let config = CheckpointConfig::new(Duration::from_secs(60))
+            .min_pause(Duration::from_secs(10))
+            .max_concurrent(0)
+            .tolerable_failures(3);
+        properties.set_checkpoint_config(config.clone());
+        assert_eq!(
+            properties.get_checkpoint_interval().unwrap(),
+            config.interval
+        );
+        assert_eq!(properties.get_checkpoint_config().unwrap(), config);
+        assert_eq!(config.max_concurrent, 1);
+    }
+
+    #[test]
+    pub fn test_execution_mode() {
+        let mut properties = Properties::new();
+        assert_eq!(properties.get_execution_mode(), ExecutionMode::Streaming);
+
+        properties.set_execution_mode(ExecutionMode::Batch);
+        assert_eq!(properties.get_execution_mode(), ExecutionMode::Batch);
+    }
+
+    #[test]
+    pub fn test_restart_strategy() {
+        let mut properties = Properties::new();
+        assert!(properties.get_restart_strategy().is_err());
+
+        let strategy =
+            RestartStrategy::exponential_backoff(Duration::from_secs(1), Duration::from_secs(60));
+        properties.set_restart_strategy(strategy.clone());
+        assert_eq!(properties.get_restart_strategy().unwrap(), strategy);
+
+        assert_eq!(properties.get_failover_strategy(), FailoverStrategy::Full);
+        properties.set_failover_strategy(FailoverStrategy::Region);
+        assert_eq!(properties.get_failover_strategy(), FailoverStrategy::Region);
+    }
+
+    #[test]
+    pub fn test_high_availability() {
+        let mut properties = Properties::new();
+        assert!(properties.get_high_availability().is_err());
+
+        let backend = HighAvailabilityBackend::Etcd {
+            endpoints: vec!["http://127.0.0.1:2379".to_string()],
+            namespace: None,
+        };
+        properties.set_high_availability(backend.clone());
+        assert_eq!(properties.get_high_availability().unwrap(), backend);
+    }
 }
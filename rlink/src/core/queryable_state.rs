@@ -0,0 +1,66 @@
+//! The gRPC protocol of the queryable state server on the workers. The messages are defined by
+//! the prost derive instead of the generated code, the equivalent proto is:
+//!
+//! ```proto
+//! package rlink;
+//!
+//! service QueryableState {
+//!   rpc GetState(StateRequest) returns (StateResponse);
+//!   rpc ListStates(ListStatesRequest) returns (ListStatesResponse);
+//! }
+//! ```
+
+/// The method path of the `GetState`
+pub const GET_STATE_PATH: &str = "/rlink.QueryableState/GetState";
+/// The method path of the `ListStates`
+pub const LIST_STATES_PATH: &str = "/rlink.QueryableState/ListStates";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StateRequest {
+    /// the name set by the `queryable` of the state descriptor
+    #[prost(string, tag = "1")]
+    pub queryable_name: String,
+    /// the bytes of the key `Record` selected by the key selector
+    #[prost(bytes = "vec", tag = "2")]
+    pub key: Vec<u8>,
+    /// the task owning the key group of the key, all tasks of the worker are looked up if
+    /// it's absent
+    #[prost(uint32, optional, tag = "3")]
+    pub task_number: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StateResponse {
+    /// false if the key is absent or expired in the worker
+    #[prost(bool, tag = "1")]
+    pub found: bool,
+    /// the json representation of the value
+    #[prost(string, tag = "2")]
+    pub value: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListStatesRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListStatesResponse {
+    /// the number of the key groups, see `SystemProperties::get_max_parallelism`
+    #[prost(uint32, tag = "1")]
+    pub max_parallelism: u32,
+    /// the tasks of the worker holding the queryable states
+    #[prost(message, repeated, tag = "2")]
+    pub states: Vec<QueryableStateTask>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct QueryableStateTask {
+    #[prost(string, tag = "1")]
+    pub queryable_name: String,
+    #[prost(uint32, tag = "2")]
+    pub job_id: u32,
+    #[prost(uint32, tag = "3")]
+    pub task_number: u32,
+    /// the parallelism of the job
+    #[prost(uint32, tag = "4")]
+    pub num_tasks: u32,
+}
@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use rand::Rng;
+
+/// How the coordinator restarts the job after a failure, e.g. a worker is lost or the
+/// checkpoints fail consecutively. It's set by `SystemProperties::set_restart_strategy` or
+/// `StreamExecutionEnvironment::set_restart_strategy`, the latter takes precedence.
+///
+/// The job fails and the coordinator exits with an error once the restarts are exhausted
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum RestartStrategy {
+    /// fail the job at the first failure
+    NoRestart,
+    /// restart after the `delay`, the job fails once it has been restarted `max_attempts` times
+    FixedDelay { max_attempts: u32, delay: Duration },
+    /// restart after the backoff, which starts at `initial_backoff` and is multiplied by the
+    /// `multiplier` after each restart up to `max_backoff`. The backoff is randomized by
+    /// `±jitter` of it, and reset to the `initial_backoff` if the job has been running for
+    /// `reset_after` before the failure
+    ExponentialBackoff {
+        initial_backoff: Duration,
+        max_backoff: Duration,
+        multiplier: f64,
+        jitter: f64,
+        reset_after: Duration,
+    },
+    /// restart after the `delay`, the job fails once more than `max_failures` failures occur
+    /// within the `interval`
+    FailureRate {
+        max_failures: u32,
+        interval: Duration,
+        delay: Duration,
+    },
+}
+
+/// Restart immediately without the limit
+impl Default for RestartStrategy {
+    fn default() -> Self {
+        RestartStrategy::FixedDelay {
+            max_attempts: u32::MAX,
+            delay: Duration::from_secs(0),
+        }
+    }
+}
+
+impl RestartStrategy {
+    pub fn fixed_delay(max_attempts: u32, delay: Duration) -> Self {
+        RestartStrategy::FixedDelay {
+            max_attempts,
+            delay,
+        }
+    }
+
+    /// The exponential backoff doubled after each restart with 10% jitter, it's reset after
+    /// the job has been running for 10 times of the `max_backoff`
+    pub fn exponential_backoff(initial_backoff: Duration, max_backoff: Duration) -> Self {
+        RestartStrategy::ExponentialBackoff {
+            initial_backoff,
+            max_backoff,
+            multiplier: 2.0,
+            jitter: 0.1,
+            reset_after: max_backoff * 10,
+        }
+    }
+
+    pub fn failure_rate(max_failures: u32, interval: Duration, delay: Duration) -> Self {
+        RestartStrategy::FailureRate {
+            max_failures,
+            interval,
+            delay,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if let RestartStrategy::ExponentialBackoff {
+            initial_backoff,
+            max_backoff,
+            multiplier,
+            jitter,
+            ..
+        } = self
+        {
+            if initial_backoff > max_backoff {
+                return Err(anyhow!(
+                    "the initial backoff must not be larger than the max backoff"
+                ));
+            }
+            if multiplier.is_nan() || *multiplier < 1.0 {
+                return Err(anyhow!("the backoff multiplier must be at least 1"));
+            }
+            if !(0.0..=1.0).contains(jitter) {
+                return Err(anyhow!("the backoff jitter must be in [0, 1]"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which tasks are restarted after a failure, it's set by
+/// `SystemProperties::set_failover_strategy`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FailoverStrategy {
+    /// restart all workers of the job
+    Full,
+    /// restart only the workers running the pipelined regions of the failed workers, i.e. the
+    /// tasks connected to them by the data exchanges, they restore from the latest completed
+    /// checkpoint while the other regions keep running. A worker running the tasks of several
+    /// regions restarts all of them, so the allocation decides how fine the failover is.
+    ///
+    /// It falls back to `Full` if the regions cover all workers, or the failure isn't caused by
+    /// the lost workers, e.g. the checkpoints fail consecutively
+    Region,
+}
+
+impl Default for FailoverStrategy {
+    fn default() -> Self {
+        FailoverStrategy::Full
+    }
+}
+
+/// Decide whether and when the job is restarted after the failures by the `RestartStrategy`
+#[derive(Debug)]
+pub(crate) struct RestartTracker {
+    strategy: RestartStrategy,
+    /// the restarts of the `FixedDelay`
+    attempts: u32,
+    /// the timestamps of the failures within the interval of the `FailureRate`
+    failures: VecDeque<u64>,
+    /// the next backoff of the `ExponentialBackoff`
+    backoff: Duration,
+}
+
+impl RestartTracker {
+    pub fn new(strategy: RestartStrategy) -> Self {
+        let backoff = match &strategy {
+            RestartStrategy::ExponentialBackoff {
+                initial_backoff, ..
+            } => *initial_backoff,
+            _ => Duration::from_secs(0),
+        };
+        RestartTracker {
+            strategy,
+            attempts: 0,
+            failures: VecDeque::new(),
+            backoff,
+        }
+    }
+
+    pub fn strategy(&self) -> &RestartStrategy {
+        &self.strategy
+    }
+
+    /// The delay to restart the job failed at `failed_at`, the run of the job started at
+    /// `started_at`. `None` if the job must fail. The timestamps are in millis
+    pub fn on_failure(&mut self, started_at: u64, failed_at: u64) -> Option<Duration> {
+        match &self.strategy {
+            RestartStrategy::NoRestart => None,
+            RestartStrategy::FixedDelay {
+                max_attempts,
+                delay,
+            } => {
+                if self.attempts >= *max_attempts {
+                    return None;
+                }
+                self.attempts += 1;
+                Some(*delay)
+            }
+            RestartStrategy::ExponentialBackoff {
+                initial_backoff,
+                max_backoff,
+                multiplier,
+                jitter,
+                reset_after,
+            } => {
+                let running = Duration::from_millis(failed_at.saturating_sub(started_at));
+                if running >= *reset_after {
+                    self.backoff = *initial_backoff;
+                }
+
+                let backoff = self.backoff;
+                self.backoff = backoff.mul_f64(*multiplier).min(*max_backoff);
+
+                if *jitter > 0.0 {
+                    let factor = 1.0 + rand::thread_rng().gen_range(-*jitter..=*jitter);
+                    Some(backoff.mul_f64(factor))
+                } else {
+                    Some(backoff)
+                }
+            }
+            RestartStrategy::FailureRate {
+                max_failures,
+                interval,
+                delay,
+            } => {
+                let interval = interval.as_millis() as u64;
+                while let Some(failure) = self.failures.front() {
+                    if failure + interval <= failed_at {
+                        self.failures.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+
+                self.failures.push_back(failed_at);
+                if self.failures.len() > *max_failures as usize {
+                    None
+                } else {
+                    Some(*delay)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::restart_strategy::{RestartStrategy, RestartTracker};
+
+    #[test]
+    pub fn restart_tracker_test() {
+        let mut tracker = RestartTracker::new(RestartStrategy::NoRestart);
+        assert_eq!(tracker.on_failure(0, 1000), None);
+
+        let delay = Duration::from_secs(5);
+        let mut tracker = RestartTracker::new(RestartStrategy::fixed_delay(2, delay));
+        assert_eq!(tracker.on_failure(0, 1000), Some(delay));
+        assert_eq!(tracker.on_failure(0, 2000), Some(delay));
+        assert_eq!(tracker.on_failure(0, 3000), None);
+
+        // at most 2 failures within 10s
+        let mut tracker = RestartTracker::new(RestartStrategy::failure_rate(
+            2,
+            Duration::from_secs(10),
+            delay,
+        ));
+        assert_eq!(tracker.on_failure(0, 1_000), Some(delay));
+        assert_eq!(tracker.on_failure(0, 5_000), Some(delay));
+        assert_eq!(tracker.on_failure(0, 11_000), Some(delay));
+        assert_eq!(tracker.on_failure(0, 14_000), None);
+    }
+
+    #[test]
+    pub fn exponential_backoff_test() {
+        let strategy = RestartStrategy::ExponentialBackoff {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: 0.0,
+            reset_after: Duration::from_secs(60),
+        };
+        strategy.validate().unwrap();
+
+        let mut tracker = RestartTracker::new(strategy);
+        let backoffs: Vec<u64> = (0..5)
+            .map(|_| tracker.on_failure(0, 1000).unwrap().as_secs())
+            .collect();
+        assert_eq!(backoffs, vec![1, 2, 4, 5, 5]);
+
+        // reset once the job has been running stably
+        assert_eq!(tracker.on_failure(0, 60_000), Some(Duration::from_secs(1)));
+
+        let mut tracker = RestartTracker::new(RestartStrategy::exponential_backoff(
+            Duration::from_secs(10),
+            Duration::from_secs(60),
+        ));
+        let backoff = tracker.on_failure(0, 1000).unwrap();
+        assert!(backoff >= Duration::from_secs(9) && backoff <= Duration::from_secs(11));
+
+        let illegal = RestartStrategy::ExponentialBackoff {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 0.5,
+            jitter: 0.0,
+            reset_after: Duration::from_secs(60),
+        };
+        assert!(illegal.validate().is_err());
+    }
+}
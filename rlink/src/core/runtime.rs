@@ -1,10 +1,12 @@
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::ops::Deref;
 
 use atomic_enum::atomic_enum;
 use bytes::{Buf, BufMut, BytesMut};
 
-use crate::core::checkpoint::CheckpointHandle;
+use crate::core::accumulator::{self, AccumulatorValue};
+use crate::core::checkpoint::{CheckpointHandle, OperatorStateHandle};
 use crate::core::element::Serde;
 use crate::core::function::InputSplit;
 use crate::core::properties::Properties;
@@ -150,6 +152,14 @@ pub struct OperatorDescriptor {
     pub checkpoint_id: CheckpointId,
     pub completed_checkpoint_id: Option<CheckpointId>,
     pub checkpoint_handle: Option<CheckpointHandle>,
+    /// whether the operator keeps the keyed states, which are partitioned by the key groups
+    #[serde(default)]
+    pub keyed_state: bool,
+    /// the handles of the tasks of the operator in the restored checkpoint the task restores
+    /// from, ordered by the task number. They are the tasks whose key groups overlap the task's
+    /// for the keyed states, and all tasks for the operator states
+    #[serde(default)]
+    pub operator_state_handles: Vec<OperatorStateHandle>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -160,6 +170,12 @@ pub struct TaskDescriptor {
     pub daemon: bool,
     /// mark the task is `Terminated` status
     pub terminated: bool,
+    /// the latest accumulators reported by the task
+    #[serde(default)]
+    pub accumulators: BTreeMap<String, AccumulatorValue>,
+    /// the checkpoint of the drained state the task terminated after, on stop with savepoint
+    #[serde(default)]
+    pub drain_checkpoint_id: Option<CheckpointId>,
 }
 
 #[atomic_enum]
@@ -175,6 +191,9 @@ pub enum ManagerStatus {
     Terminating = 3,
     /// All Tasks terminated
     Terminated = 4,
+    /// Stop with savepoint requested, the sources stop consuming and the Tasks terminate
+    /// after all windows fired and checkpointed
+    Draining = 5,
 }
 
 impl Default for ManagerStatus {
@@ -196,6 +215,13 @@ impl ManagerStatus {
             _ => false,
         }
     }
+
+    pub fn is_draining(&self) -> bool {
+        match self {
+            ManagerStatus::Draining => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -236,6 +262,9 @@ pub struct WorkerManagerDescriptor {
     pub task_manager_id: String,
     pub task_manager_address: String,
     pub web_address: String,
+    /// the gRPC address of the queryable state server, empty if disabled
+    #[serde(default)]
+    pub queryable_state_address: String,
     pub task_descriptors: Vec<TaskDescriptor>,
 }
 
@@ -288,11 +317,31 @@ impl ClusterDescriptor {
 
         if task_count == terminated_count {
             self.coordinator_manager.status = ManagerStatus::Terminated;
-        } else if terminated_count > 0 {
+        } else if terminated_count > 0 && !self.coordinator_manager.status.is_draining() {
             self.coordinator_manager.status = ManagerStatus::Terminating;
         }
     }
 
+    /// The accumulators of all tasks merged by the name
+    pub fn accumulators(&self) -> BTreeMap<String, AccumulatorValue> {
+        accumulator::merge(
+            self.worker_managers
+                .iter()
+                .flat_map(|x| x.task_descriptors.iter())
+                .map(|x| &x.accumulators),
+        )
+    }
+
+    /// The latest checkpoint of the drained state reported by the terminated tasks, `None` if
+    /// the job isn't stopped with savepoint
+    pub fn drain_checkpoint_id(&self) -> Option<CheckpointId> {
+        self.worker_managers
+            .iter()
+            .flat_map(|x| x.task_descriptors.iter())
+            .filter_map(|x| x.drain_checkpoint_id)
+            .max()
+    }
+
     pub fn to_string(&self) -> String {
         serde_json::to_string(self).unwrap()
     }
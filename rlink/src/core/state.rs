@@ -0,0 +1,1753 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use bytes::BytesMut;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::backend::KeyedStateBackend;
+use crate::core::checkpoint::{CheckpointHandle, OperatorStateHandle};
+use crate::core::element::{Buffer, Record};
+use crate::core::function::Context;
+use crate::core::key_group::{assign_key_to_operator, assign_to_key_group, KeyGroupRange};
+use crate::core::properties::SystemProperties;
+use crate::core::runtime::{CheckpointId, TaskId};
+use crate::storage::keyed_state::keyed_state_storage;
+use crate::utils::date_time::current_timestamp_millis;
+
+/// When the last access time of a state value is updated
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TtlUpdateType {
+    OnCreateAndWrite,
+    OnReadAndWrite,
+}
+
+/// Whether the expired value is returned if it's not cleaned up yet
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StateVisibility {
+    NeverReturnExpired,
+    ReturnExpiredIfNotCleanedUp,
+}
+
+/// The value of a key expires after the `ttl` since it's last accessed. The expired values are
+/// removed when they are accessed, and the others are removed on `snapshot` in the memory, or by
+/// the compaction of the rocksdb of the keyed state backend
+#[derive(Clone, Copy, Debug)]
+pub struct StateTtlConfig {
+    ttl: Duration,
+    update_type: TtlUpdateType,
+    visibility: StateVisibility,
+}
+
+impl StateTtlConfig {
+    pub fn new(ttl: Duration) -> Self {
+        StateTtlConfig {
+            ttl,
+            update_type: TtlUpdateType::OnCreateAndWrite,
+            visibility: StateVisibility::NeverReturnExpired,
+        }
+    }
+
+    pub fn update_type(mut self, update_type: TtlUpdateType) -> Self {
+        self.update_type = update_type;
+        self
+    }
+
+    pub fn visibility(mut self, visibility: StateVisibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    fn is_expired(&self, timestamp: u64, now: u64) -> bool {
+        now.saturating_sub(timestamp) >= self.ttl.as_millis() as u64
+    }
+}
+
+/// The value of a key and the last access time in milliseconds
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StateEntry {
+    value: Value,
+    #[serde(default)]
+    timestamp: u64,
+}
+
+type StateValues = BTreeMap<Vec<u8>, StateEntry>;
+
+/// The access time of a `StateEntry`, the value isn't parsed
+#[derive(Deserialize)]
+struct StateTimestamp {
+    #[serde(default)]
+    timestamp: u64,
+}
+
+/// Whether the json of a `StateEntry` is expired by the `ttl_config`, the unknown value never
+/// expires
+pub(crate) fn is_expired_entry(ttl_config: &StateTtlConfig, value: &[u8], now: u64) -> bool {
+    serde_json::from_slice::<StateTimestamp>(value)
+        .map(|entry| ttl_config.is_expired(entry.timestamp, now))
+        .unwrap_or(false)
+}
+
+/// The storage of the keyed states beyond the memory, e.g. the rocksdb of the task. The value
+/// of a key is the json of its `StateEntry`
+#[async_trait]
+pub(crate) trait KeyedStateStorage: Debug + Send + Sync {
+    fn get(&self, name: &str, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Write the values of the keys, the `None` value removes the key
+    fn write(&self, name: &str, values: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()>;
+
+    fn entries(&self, name: &str) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    /// the names of the states having values
+    fn names(&self) -> Vec<String>;
+
+    fn clear(&self) -> anyhow::Result<()>;
+
+    /// Remove the values expired by the `ttl_config` in the background, the expired values of
+    /// the state aren't scanned by the `KeyedStateStore`
+    fn set_ttl(&self, name: &str, ttl_config: Option<StateTtlConfig>);
+
+    /// Persist the states of the checkpoint, it's restored by the storage when the job
+    /// restarts from the checkpoint. The checkpoint is acknowledged once it returns
+    async fn checkpoint(&self, checkpoint_id: CheckpointId) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Default)]
+struct KeyedStateStore {
+    current_key: Vec<u8>,
+    states: BTreeMap<String, StateValues>,
+    ttl_configs: BTreeMap<String, StateTtlConfig>,
+    /// the values are kept in the storage if it's set, then the `states` caches the values of
+    /// the current key, and the changed ones are written back once the key is changed
+    storage: Option<Arc<dyn KeyedStateStorage>>,
+    /// the states of the current key read from the storage
+    loaded: BTreeSet<String>,
+    /// the states of the current key changed since they're read from the storage
+    changed: BTreeSet<String>,
+    /// the task of the operator, set on `RuntimeContext::open`
+    task_id: Option<TaskId>,
+}
+
+impl KeyedStateStore {
+    fn set_current_key(&mut self, key: &[u8]) {
+        if self.storage.is_some() && self.current_key.as_slice() != key {
+            self.write_back().expect("write keyed states error");
+            self.states.clear();
+            self.loaded.clear();
+        }
+        self.current_key = key.to_vec();
+    }
+
+    /// Read the value of the current key from the storage if it isn't cached
+    fn load(&mut self, name: &str) {
+        let storage = match &self.storage {
+            Some(storage) if !self.loaded.contains(name) => storage,
+            _ => return,
+        };
+
+        let value = storage
+            .get(name, self.current_key.as_slice())
+            .expect("read keyed states error");
+        if let Some(value) = value {
+            let entry: StateEntry =
+                serde_json::from_slice(value.as_slice()).expect("parse keyed states error");
+            self.states
+                .entry(name.to_string())
+                .or_default()
+                .insert(self.current_key.clone(), entry);
+        }
+        self.loaded.insert(name.to_string());
+    }
+
+    fn set_changed(&mut self, name: &str) {
+        if self.storage.is_some() {
+            self.changed.insert(name.to_string());
+        }
+    }
+
+    /// Write the changed values of the current key to the storage
+    fn write_back(&mut self) -> anyhow::Result<()> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => return Ok(()),
+        };
+
+        for name in std::mem::take(&mut self.changed) {
+            let value = match self
+                .states
+                .get(name.as_str())
+                .and_then(|values| values.get(self.current_key.as_slice()))
+            {
+                Some(entry) => Some(serde_json::to_vec(entry)?),
+                None => None,
+            };
+            storage.write(name.as_str(), vec![(self.current_key.clone(), value)])?;
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, name: &str) -> Option<&Value> {
+        self.load(name);
+        let now = current_timestamp_millis();
+        let ttl_config = self.ttl_configs.get(name).cloned();
+
+        if let Some(ttl_config) = ttl_config {
+            let values = self.states.get_mut(name)?;
+            let entry = values.get_mut(self.current_key.as_slice())?;
+            if ttl_config.is_expired(entry.timestamp, now) {
+                if ttl_config.visibility == StateVisibility::NeverReturnExpired {
+                    values.remove(self.current_key.as_slice());
+                    self.set_changed(name);
+                    return None;
+                }
+            } else if ttl_config.update_type == TtlUpdateType::OnReadAndWrite {
+                entry.timestamp = now;
+                self.set_changed(name);
+            }
+        }
+
+        self.states
+            .get(name)?
+            .get(self.current_key.as_slice())
+            .map(|entry| &entry.value)
+    }
+
+    /// The value of the `key` without touching the access time, for the queryable states
+    fn peek(&self, name: &str, key: &[u8]) -> Option<Value> {
+        let cached = self.states.get(name).and_then(|values| values.get(key));
+        let is_cached = self.storage.is_none()
+            || (key == self.current_key.as_slice() && self.loaded.contains(name));
+        let entry = match (&self.storage, cached) {
+            (_, Some(entry)) => entry.clone(),
+            (Some(storage), None) if !is_cached => {
+                let value = storage.get(name, key).ok()??;
+                serde_json::from_slice(value.as_slice()).ok()?
+            }
+            _ => return None,
+        };
+
+        if let Some(ttl_config) = self.ttl_configs.get(name) {
+            if ttl_config.visibility == StateVisibility::NeverReturnExpired
+                && ttl_config.is_expired(entry.timestamp, current_timestamp_millis())
+            {
+                return None;
+            }
+        }
+        Some(entry.value)
+    }
+
+    fn put(&mut self, name: &str, value: Value) {
+        let key = self.current_key.clone();
+        let entry = StateEntry {
+            value,
+            timestamp: current_timestamp_millis(),
+        };
+        self.states
+            .entry(name.to_string())
+            .or_default()
+            .insert(key, entry);
+        self.set_changed(name);
+    }
+
+    /// The value of the current key for updating it in place, the absent or the expired value
+    /// is replaced by the `default`. The access time is updated as it's a write
+    fn get_mut_or_insert<F>(&mut self, name: &str, default: F) -> &mut Value
+    where
+        F: Fn() -> Value,
+    {
+        self.load(name);
+        self.set_changed(name);
+        let now = current_timestamp_millis();
+        let ttl_config = self.ttl_configs.get(name).cloned();
+
+        if !self.states.contains_key(name) {
+            self.states.insert(name.to_string(), StateValues::new());
+        }
+        let values = self.states.get_mut(name).unwrap();
+        let entry = values
+            .entry(self.current_key.clone())
+            .or_insert_with(|| StateEntry {
+                value: default(),
+                timestamp: now,
+            });
+        if let Some(ttl_config) = ttl_config {
+            if ttl_config.visibility == StateVisibility::NeverReturnExpired
+                && ttl_config.is_expired(entry.timestamp, now)
+            {
+                entry.value = default();
+            }
+        }
+        entry.timestamp = now;
+        &mut entry.value
+    }
+
+    /// The value of the current key for updating it in place, `None` if it's absent or expired
+    fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.get(name)?;
+        self.set_changed(name);
+        let entry = self
+            .states
+            .get_mut(name)?
+            .get_mut(self.current_key.as_slice())?;
+        entry.timestamp = current_timestamp_millis();
+        Some(&mut entry.value)
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.set_changed(name);
+        if let Some(values) = self.states.get_mut(name) {
+            values.remove(self.current_key.as_slice());
+            if values.is_empty() {
+                self.states.remove(name);
+            }
+        }
+    }
+
+    fn register(&mut self, name: &str, ttl_config: Option<StateTtlConfig>) {
+        match ttl_config {
+            Some(ttl_config) => self.ttl_configs.insert(name.to_string(), ttl_config),
+            None => self.ttl_configs.remove(name),
+        };
+        if let Some(storage) = &self.storage {
+            storage.set_ttl(name, ttl_config);
+        }
+    }
+
+    /// Keep the states in the `storage`, the expired values in it are removed by the storage
+    fn set_storage(&mut self, storage: Option<Arc<dyn KeyedStateStorage>>) {
+        if let Some(storage) = &storage {
+            for (name, ttl_config) in &self.ttl_configs {
+                storage.set_ttl(name.as_str(), Some(*ttl_config));
+            }
+        }
+        self.storage = storage;
+        self.states.clear();
+        self.loaded.clear();
+        self.changed.clear();
+    }
+
+    /// Remove the expired values of all keys, the ones in the storage are left to it
+    fn cleanup(&mut self) -> anyhow::Result<()> {
+        if self.storage.is_some() {
+            self.write_back()?;
+            self.states.clear();
+            self.loaded.clear();
+            return Ok(());
+        }
+
+        let now = current_timestamp_millis();
+        for (name, ttl_config) in &self.ttl_configs {
+            if let Some(values) = self.states.get_mut(name) {
+                values.retain(|_key, entry| !ttl_config.is_expired(entry.timestamp, now));
+                if values.is_empty() {
+                    self.states.remove(name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The values of all keys of the state
+    fn entries(&mut self, name: &str) -> anyhow::Result<Vec<(Vec<u8>, StateEntry)>> {
+        self.write_back()?;
+        match &self.storage {
+            Some(storage) => storage
+                .entries(name)?
+                .into_iter()
+                .map(|(key, value)| Ok((key, serde_json::from_slice(value.as_slice())?)))
+                .collect(),
+            None => Ok(self
+                .states
+                .get(name)
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|(key, entry)| (key.clone(), entry.clone()))
+                        .collect()
+                })
+                .unwrap_or_default()),
+        }
+    }
+
+    /// The names of the states having values
+    fn names(&self) -> Vec<String> {
+        match &self.storage {
+            Some(storage) => storage.names(),
+            None => self.states.keys().cloned().collect(),
+        }
+    }
+
+    /// Replace the states of all keys
+    fn replace(&mut self, states: BTreeMap<String, StateValues>) -> anyhow::Result<()> {
+        let storage = match &self.storage {
+            Some(storage) => storage,
+            None => {
+                self.states = states;
+                return Ok(());
+            }
+        };
+
+        storage.clear()?;
+        for (name, values) in states {
+            let values = values
+                .into_iter()
+                .map(|(key, entry)| Ok((key, Some(serde_json::to_vec(&entry)?))))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            storage.write(name.as_str(), values)?;
+        }
+        self.states.clear();
+        self.loaded.clear();
+        self.changed.clear();
+        Ok(())
+    }
+}
+
+/// The checkpoint of the keyed states, the values of every state by the key bytes. The other
+/// fields of the handle are ignored, e.g. the windows of the reduce operators
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct KeyedStateSnapshot {
+    states: BTreeMap<String, Vec<(Vec<u8>, StateEntry)>>,
+    /// the states are in the rocksdb checkpoint of the tasks, they're restored with the rocksdb
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rocksdb_checkpoint_id: Option<CheckpointId>,
+}
+
+/// The checkpoint of the rocksdb keeping the keyed states in the `handles` of the tasks, `None`
+/// if the states are in the handles
+pub(crate) fn rocksdb_checkpoint_id(handles: &[OperatorStateHandle]) -> Option<CheckpointId> {
+    handles
+        .iter()
+        .filter(|x| !x.handle.handle.is_empty())
+        .filter_map(|x| serde_json::from_str::<KeyedStateSnapshot>(x.handle.handle.as_str()).ok())
+        .find_map(|x| x.rocksdb_checkpoint_id)
+}
+
+/// The keyed states in a checkpoint handle of `RuntimeContext::snapshot`, for reading and
+/// writing the states outside a running job, e.g. by the `state_processor`. The value of a
+/// key is the json of the state, e.g. `T` of the `ValueState<T>` and `Vec<T>` of the
+/// `ListState<T>`
+#[derive(Clone, Debug, Default)]
+pub struct KeyedStates {
+    states: BTreeMap<String, StateValues>,
+}
+
+impl KeyedStates {
+    pub fn new() -> Self {
+        KeyedStates::default()
+    }
+
+    pub fn from_handle(handle: &CheckpointHandle) -> anyhow::Result<Self> {
+        let snapshot: KeyedStateSnapshot = if handle.handle.is_empty() {
+            KeyedStateSnapshot::default()
+        } else {
+            serde_json::from_str(handle.handle.as_str())?
+        };
+        if let Some(checkpoint_id) = snapshot.rocksdb_checkpoint_id {
+            return Err(anyhow!(
+                "the keyed states are in the rocksdb checkpoint {:?}",
+                checkpoint_id
+            ));
+        }
+        let states = snapshot
+            .states
+            .into_iter()
+            .map(|(name, values)| (name, values.into_iter().collect()))
+            .collect();
+        Ok(KeyedStates { states })
+    }
+
+    pub fn to_handle(&self) -> anyhow::Result<CheckpointHandle> {
+        let snapshot = KeyedStateSnapshot {
+            states: self
+                .states
+                .iter()
+                .map(|(name, values)| {
+                    let values = values
+                        .iter()
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect();
+                    (name.clone(), values)
+                })
+                .collect(),
+            rocksdb_checkpoint_id: None,
+        };
+        let handle = serde_json::to_string(&snapshot)?;
+        Ok(CheckpointHandle { handle })
+    }
+
+    pub fn state_names(&self) -> Vec<String> {
+        self.states.keys().cloned().collect()
+    }
+
+    /// The keys and the values of the state, the key is the `Record` of the key selector
+    pub fn entries<T>(&self, name: &str) -> anyhow::Result<Vec<(Record, T)>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.states.get(name) {
+            Some(values) => values
+                .iter()
+                .map(|(key, entry)| Ok((key_record(key.as_slice()), from_value(&entry.value)?)))
+                .collect(),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub fn put<T>(&mut self, name: &str, key: &Record, value: &T) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        let entry = StateEntry {
+            value: to_value(value)?,
+            timestamp: current_timestamp_millis(),
+        };
+        self.states
+            .entry(name.to_string())
+            .or_default()
+            .insert(key.values.as_slice().to_vec(), entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str, key: &Record) {
+        if let Some(values) = self.states.get_mut(name) {
+            values.remove(key.values.as_slice());
+            if values.is_empty() {
+                self.states.remove(name);
+            }
+        }
+    }
+
+    /// Move the values of all states in the `other` to self
+    pub fn merge(&mut self, other: KeyedStates) {
+        for (name, values) in other.states {
+            self.states.entry(name).or_default().extend(values);
+        }
+    }
+
+    /// Split the states to the tasks of the `parallelism` by the key groups of the keys
+    pub fn split(self, max_parallelism: u16, parallelism: u16) -> Vec<KeyedStates> {
+        let mut task_states = vec![KeyedStates::new(); parallelism as usize];
+        for (name, values) in self.states {
+            for (key, entry) in values {
+                let task_number =
+                    assign_key_to_operator(key.as_slice(), max_parallelism, parallelism);
+                task_states[task_number as usize]
+                    .states
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(key, entry);
+            }
+        }
+        task_states
+    }
+}
+
+fn key_record(key: &[u8]) -> Record {
+    let mut record = Record::new();
+    record.values = Buffer::from(BytesMut::from(key));
+    record
+}
+
+/// The states looked up by the queryable state server of the worker, registered by
+/// `register_queryable_state`
+pub(crate) trait QueryableStore: Send {
+    /// The identity of the store, a store is registered once under a queryable name
+    fn id(&self) -> usize;
+
+    /// False once the task holding the states is closed
+    fn is_alive(&self) -> bool;
+
+    /// The task holding the states, `None` if the task isn't opened yet
+    fn task_id(&self) -> Option<TaskId>;
+
+    /// The json value of the `key`, the key is the bytes of the `Record` selected by the key
+    /// selector
+    fn get(&self, key: &[u8]) -> Option<Value>;
+}
+
+/// A state registered by the `queryable` of its descriptor
+struct QueryableKeyedState {
+    name: String,
+    store: Weak<Mutex<KeyedStateStore>>,
+}
+
+impl QueryableStore for QueryableKeyedState {
+    fn id(&self) -> usize {
+        self.store.as_ptr() as usize
+    }
+
+    fn is_alive(&self) -> bool {
+        self.store.strong_count() > 0
+    }
+
+    fn task_id(&self) -> Option<TaskId> {
+        self.store.upgrade()?.lock().unwrap().task_id
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Value> {
+        let store = self.store.upgrade()?;
+        let store = store.lock().unwrap();
+        store.peek(self.name.as_str(), key)
+    }
+}
+
+lazy_static! {
+    /// the queryable states of all tasks in the worker by the queryable name
+    static ref QUERYABLE_STATES: Mutex<HashMap<String, Vec<Box<dyn QueryableStore>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Expose the `store` to the queryable state server of the worker by the `queryable_name`
+pub(crate) fn register_queryable_state(queryable_name: &str, store: Box<dyn QueryableStore>) {
+    let mut queryable_states = QUERYABLE_STATES.lock().unwrap();
+    let stores = queryable_states
+        .entry(queryable_name.to_string())
+        .or_default();
+    stores.retain(|x| x.is_alive());
+
+    if !stores.iter().any(|x| x.id() == store.id()) {
+        stores.push(store);
+    }
+}
+
+/// Look up the value of the `key` in the queryable state of the task `task_number` in the
+/// worker, or of all tasks if it's `None`. Only one task of the job holds the key
+pub(crate) fn query_state(
+    queryable_name: &str,
+    task_number: Option<u16>,
+    key: &[u8],
+) -> Option<Value> {
+    let queryable_states = QUERYABLE_STATES.lock().unwrap();
+    queryable_states
+        .get(queryable_name)?
+        .iter()
+        .filter(|store| match task_number {
+            Some(task_number) => store
+                .task_id()
+                .map(|task_id| task_id.task_number == task_number)
+                .unwrap_or(false),
+            None => true,
+        })
+        .find_map(|store| store.get(key))
+}
+
+/// The tasks in the worker holding the queryable states by the queryable name, the client
+/// routes the query of a key to the task owning the key group of it
+pub(crate) fn queryable_state_tasks() -> Vec<(String, TaskId)> {
+    let queryable_states = QUERYABLE_STATES.lock().unwrap();
+    queryable_states
+        .iter()
+        .flat_map(|(queryable_name, stores)| {
+            stores
+                .iter()
+                .filter_map(move |store| store.task_id().map(|x| (queryable_name.clone(), x)))
+        })
+        .collect()
+}
+
+/// The keyed states of an operator, shared by the function and the runnable of the operator.
+/// Every state handle reads and writes the value of the current key, the reduce runnable sets
+/// the key of each record before the record is reduced, the other functions set it by
+/// `set_current_key`. The states of the reduce operators are checkpointed and restored by the
+/// runtime, the other functions include them in their checkpoint by `snapshot` and
+/// `restore_from`
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeContext {
+    store: Arc<Mutex<KeyedStateStore>>,
+}
+
+impl RuntimeContext {
+    pub fn new() -> Self {
+        RuntimeContext::default()
+    }
+
+    /// Scope the states to the `key`, the empty key is the state of the whole task
+    pub fn set_current_key(&self, key: &Record) {
+        self.store
+            .lock()
+            .unwrap()
+            .set_current_key(key.values.as_slice());
+    }
+
+    pub fn value_state<T>(&self, descriptor: &ValueStateDescriptor<T>) -> ValueState<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.register(
+            descriptor.name.as_str(),
+            descriptor.ttl_config,
+            descriptor.queryable.as_ref(),
+        );
+        ValueState {
+            name: descriptor.name.clone(),
+            store: self.store.clone(),
+            a: PhantomData,
+        }
+    }
+
+    pub fn list_state<T>(&self, descriptor: &ListStateDescriptor<T>) -> ListState<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.register(
+            descriptor.name.as_str(),
+            descriptor.ttl_config,
+            descriptor.queryable.as_ref(),
+        );
+        ListState {
+            name: descriptor.name.clone(),
+            store: self.store.clone(),
+            a: PhantomData,
+        }
+    }
+
+    pub fn map_state<K, V>(&self, descriptor: &MapStateDescriptor<K, V>) -> MapState<K, V>
+    where
+        K: Serialize + DeserializeOwned,
+        V: Serialize + DeserializeOwned,
+    {
+        self.register(
+            descriptor.name.as_str(),
+            descriptor.ttl_config,
+            descriptor.queryable.as_ref(),
+        );
+        MapState {
+            name: descriptor.name.clone(),
+            store: self.store.clone(),
+            a: PhantomData,
+        }
+    }
+
+    pub fn reducing_state<T>(&self, descriptor: &ReducingStateDescriptor<T>) -> ReducingState<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.register(
+            descriptor.name.as_str(),
+            descriptor.ttl_config,
+            descriptor.queryable.as_ref(),
+        );
+        ReducingState {
+            name: descriptor.name.clone(),
+            reduce_fn: descriptor.reduce_fn.clone(),
+            store: self.store.clone(),
+        }
+    }
+
+    fn register(&self, name: &str, ttl_config: Option<StateTtlConfig>, queryable: Option<&String>) {
+        self.store.lock().unwrap().register(name, ttl_config);
+        if let Some(queryable_name) = queryable {
+            let state = QueryableKeyedState {
+                name: name.to_string(),
+                store: Arc::downgrade(&self.store),
+            };
+            register_queryable_state(queryable_name.as_str(), Box::new(state));
+        }
+    }
+
+    /// The values of the state of all keys, the key is the `Record` of the key selector
+    pub(crate) fn entries<T>(&self, name: &str) -> anyhow::Result<Vec<(Record, T)>>
+    where
+        T: DeserializeOwned,
+    {
+        let entries = self.store.lock().unwrap().entries(name)?;
+        entries
+            .iter()
+            .map(|(key, entry)| Ok((key_record(key.as_slice()), from_value(&entry.value)?)))
+            .collect()
+    }
+
+    /// Remove the expired values of the states with ttl
+    pub fn cleanup_expired(&self) {
+        if let Err(e) = self.store.lock().unwrap().cleanup() {
+            error!("cleanup the expired keyed states error. {}", e);
+        }
+    }
+
+    /// The states of all keys as a checkpoint handle, the expired values are not included
+    pub fn snapshot(&self) -> anyhow::Result<CheckpointHandle> {
+        let mut store = self.store.lock().unwrap();
+        store.cleanup()?;
+        let mut snapshot = KeyedStateSnapshot::default();
+        for name in store.names() {
+            let values = store.entries(name.as_str())?;
+            if !values.is_empty() {
+                snapshot.states.insert(name, values);
+            }
+        }
+        let handle = serde_json::to_string(&snapshot)?;
+        Ok(CheckpointHandle { handle })
+    }
+
+    /// Replace the states by the checkpoint of `snapshot`, the empty handle clears the states
+    pub fn restore(&self, handle: &CheckpointHandle) -> anyhow::Result<()> {
+        let snapshot: KeyedStateSnapshot = if handle.handle.is_empty() {
+            KeyedStateSnapshot::default()
+        } else {
+            serde_json::from_str(handle.handle.as_str())?
+        };
+
+        let states = snapshot
+            .states
+            .into_iter()
+            .map(|(name, values)| (name, values.into_iter().collect()))
+            .collect();
+        self.store.lock().unwrap().replace(states)
+    }
+
+    /// Restore the states of the keys in the `key_groups` from the checkpoint handles of all
+    /// tasks, the handles are the `snapshot` of the tasks with any parallelism
+    pub fn restore_key_groups(
+        &self,
+        key_groups: &KeyGroupRange,
+        max_parallelism: u16,
+        handles: &[CheckpointHandle],
+    ) -> anyhow::Result<()> {
+        let mut states: BTreeMap<String, StateValues> = BTreeMap::new();
+        for handle in handles.iter().filter(|x| !x.handle.is_empty()) {
+            let snapshot: KeyedStateSnapshot = serde_json::from_str(handle.handle.as_str())?;
+            for (name, values) in snapshot.states {
+                let values = values.into_iter().filter(|(key, _entry)| {
+                    key_groups.contains(assign_to_key_group(key.as_slice(), max_parallelism))
+                });
+                states.entry(name).or_default().extend(values);
+            }
+        }
+
+        self.store.lock().unwrap().replace(states)
+    }
+
+    /// Restore the states of the task's key groups from the checkpoint of the tasks overlapping
+    /// them in the `context`, so the keyed states are redistributed when the parallelism is
+    /// changed
+    pub fn restore_from(&self, context: &Context) -> anyhow::Result<()> {
+        let max_parallelism = context.application_properties.get_max_parallelism();
+        let key_groups = KeyGroupRange::of_operator(
+            max_parallelism,
+            context.task_id.num_tasks(),
+            context.task_id.task_number(),
+        );
+        let handles: Vec<CheckpointHandle> = context
+            .operator_state_handles
+            .iter()
+            .map(|x| x.handle.clone())
+            .collect();
+        self.restore_key_groups(&key_groups, max_parallelism, handles.as_slice())
+    }
+
+    /// Keep the states in the keyed state backend of the application and restore them from
+    /// the checkpoint of the `context`. It's called by the runnables of the keyed operators,
+    /// which checkpoint the states by `checkpoint`
+    pub(crate) fn open(&self, context: &Context) -> anyhow::Result<()> {
+        let backend = context
+            .application_properties
+            .get_keyed_state_backend()
+            .unwrap_or(KeyedStateBackend::Memory);
+        let storage = keyed_state_storage(context, &backend)?;
+        let restored = storage.is_some()
+            && rocksdb_checkpoint_id(context.operator_state_handles.as_slice()).is_some();
+        {
+            let mut store = self.store.lock().unwrap();
+            store.set_storage(storage);
+            store.task_id = Some(context.task_id);
+        }
+
+        // the states in the checkpoint of the rocksdb are restored with the rocksdb
+        if restored {
+            Ok(())
+        } else {
+            self.restore_from(context)
+        }
+    }
+
+    /// The checkpoint of the states, the states in the storage of the keyed state backend are
+    /// persisted by it and the handle only refers to them. The rocksdb of the task keeps the
+    /// window states too, so it's called once the window states are written
+    pub(crate) async fn checkpoint(
+        &self,
+        checkpoint_id: CheckpointId,
+    ) -> anyhow::Result<CheckpointHandle> {
+        let storage = {
+            let mut store = self.store.lock().unwrap();
+            match store.storage.clone() {
+                Some(storage) => {
+                    store.write_back()?;
+                    storage
+                }
+                None => {
+                    drop(store);
+                    return self.snapshot();
+                }
+            }
+        };
+
+        storage.checkpoint(checkpoint_id).await?;
+        let snapshot = KeyedStateSnapshot {
+            states: BTreeMap::new(),
+            rocksdb_checkpoint_id: Some(checkpoint_id),
+        };
+        let handle = serde_json::to_string(&snapshot)?;
+        Ok(CheckpointHandle { handle })
+    }
+}
+
+/// Identify a state of the `RuntimeContext`, the name must be unique in the function
+pub struct ValueStateDescriptor<T> {
+    name: String,
+    ttl_config: Option<StateTtlConfig>,
+    queryable: Option<String>,
+    a: PhantomData<T>,
+}
+
+impl<T> ValueStateDescriptor<T> {
+    pub fn new(name: &str) -> Self {
+        ValueStateDescriptor {
+            name: name.to_string(),
+            ttl_config: None,
+            queryable: None,
+            a: PhantomData,
+        }
+    }
+
+    pub fn ttl(mut self, ttl_config: StateTtlConfig) -> Self {
+        self.ttl_config = Some(ttl_config);
+        self
+    }
+
+    /// Expose the state to the queryable state server of the worker by the `queryable_name`
+    pub fn queryable(mut self, queryable_name: &str) -> Self {
+        self.queryable = Some(queryable_name.to_string());
+        self
+    }
+}
+
+pub struct ListStateDescriptor<T> {
+    name: String,
+    ttl_config: Option<StateTtlConfig>,
+    queryable: Option<String>,
+    a: PhantomData<T>,
+}
+
+impl<T> ListStateDescriptor<T> {
+    pub fn new(name: &str) -> Self {
+        ListStateDescriptor {
+            name: name.to_string(),
+            ttl_config: None,
+            queryable: None,
+            a: PhantomData,
+        }
+    }
+
+    pub fn ttl(mut self, ttl_config: StateTtlConfig) -> Self {
+        self.ttl_config = Some(ttl_config);
+        self
+    }
+
+    /// Expose the state to the queryable state server of the worker by the `queryable_name`
+    pub fn queryable(mut self, queryable_name: &str) -> Self {
+        self.queryable = Some(queryable_name.to_string());
+        self
+    }
+}
+
+pub struct MapStateDescriptor<K, V> {
+    name: String,
+    ttl_config: Option<StateTtlConfig>,
+    queryable: Option<String>,
+    a: PhantomData<(K, V)>,
+}
+
+impl<K, V> MapStateDescriptor<K, V> {
+    pub fn new(name: &str) -> Self {
+        MapStateDescriptor {
+            name: name.to_string(),
+            ttl_config: None,
+            queryable: None,
+            a: PhantomData,
+        }
+    }
+
+    /// The whole map of a key expires
+    pub fn ttl(mut self, ttl_config: StateTtlConfig) -> Self {
+        self.ttl_config = Some(ttl_config);
+        self
+    }
+
+    /// Expose the state to the queryable state server of the worker by the `queryable_name`
+    pub fn queryable(mut self, queryable_name: &str) -> Self {
+        self.queryable = Some(queryable_name.to_string());
+        self
+    }
+}
+
+/// The values added to the state are combined by the `reduce_fn`
+pub struct ReducingStateDescriptor<T> {
+    name: String,
+    reduce_fn: Arc<dyn Fn(T, T) -> T + Send + Sync>,
+    ttl_config: Option<StateTtlConfig>,
+    queryable: Option<String>,
+}
+
+impl<T> ReducingStateDescriptor<T> {
+    pub fn new<F>(name: &str, reduce_fn: F) -> Self
+    where
+        F: Fn(T, T) -> T + Send + Sync + 'static,
+    {
+        ReducingStateDescriptor {
+            name: name.to_string(),
+            reduce_fn: Arc::new(reduce_fn),
+            ttl_config: None,
+            queryable: None,
+        }
+    }
+
+    pub fn ttl(mut self, ttl_config: StateTtlConfig) -> Self {
+        self.ttl_config = Some(ttl_config);
+        self
+    }
+
+    /// Expose the state to the queryable state server of the worker by the `queryable_name`
+    pub fn queryable(mut self, queryable_name: &str) -> Self {
+        self.queryable = Some(queryable_name.to_string());
+        self
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> anyhow::Result<Value> {
+    Ok(serde_json::to_value(value)?)
+}
+
+fn from_value<T: DeserializeOwned>(value: &Value) -> anyhow::Result<T> {
+    Ok(T::deserialize(value)?)
+}
+
+/// A single value of the current key
+#[derive(Clone, Debug)]
+pub struct ValueState<T> {
+    name: String,
+    store: Arc<Mutex<KeyedStateStore>>,
+    a: PhantomData<T>,
+}
+
+impl<T> ValueState<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub fn value(&self) -> anyhow::Result<Option<T>> {
+        let mut store = self.store.lock().unwrap();
+        store.get(self.name.as_str()).map(from_value).transpose()
+    }
+
+    pub fn update(&self, value: T) -> anyhow::Result<()> {
+        let value = to_value(&value)?;
+        self.store.lock().unwrap().put(self.name.as_str(), value);
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.store.lock().unwrap().remove(self.name.as_str());
+    }
+}
+
+/// A list of values of the current key
+#[derive(Clone, Debug)]
+pub struct ListState<T> {
+    name: String,
+    store: Arc<Mutex<KeyedStateStore>>,
+    a: PhantomData<T>,
+}
+
+impl<T> ListState<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// The values in the order of being added, empty if the state is absent
+    pub fn get(&self) -> anyhow::Result<Vec<T>> {
+        let mut store = self.store.lock().unwrap();
+        match store.get(self.name.as_str()) {
+            Some(value) => from_value(value),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub fn add(&self, value: T) -> anyhow::Result<()> {
+        let value = to_value(&value)?;
+        let mut store = self.store.lock().unwrap();
+        match store.get_mut_or_insert(self.name.as_str(), || Value::Array(vec![])) {
+            Value::Array(values) => values.push(value),
+            other => *other = Value::Array(vec![value]),
+        }
+        Ok(())
+    }
+
+    pub fn update(&self, values: Vec<T>) -> anyhow::Result<()> {
+        if values.is_empty() {
+            self.clear();
+            return Ok(());
+        }
+
+        let value = to_value(&values)?;
+        self.store.lock().unwrap().put(self.name.as_str(), value);
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.store.lock().unwrap().remove(self.name.as_str());
+    }
+}
+
+/// A map of the current key, the map keys are compared by their json representation
+#[derive(Clone, Debug)]
+pub struct MapState<K, V> {
+    name: String,
+    store: Arc<Mutex<KeyedStateStore>>,
+    a: PhantomData<(K, V)>,
+}
+
+impl<K, V> MapState<K, V>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    fn map_key(key: &K) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(key)?)
+    }
+
+    pub fn get(&self, key: &K) -> anyhow::Result<Option<V>> {
+        let map_key = Self::map_key(key)?;
+        let mut store = self.store.lock().unwrap();
+        store
+            .get(self.name.as_str())
+            .and_then(|value| value.get(map_key.as_str()))
+            .map(from_value)
+            .transpose()
+    }
+
+    pub fn contains(&self, key: &K) -> anyhow::Result<bool> {
+        let map_key = Self::map_key(key)?;
+        let mut store = self.store.lock().unwrap();
+        Ok(store
+            .get(self.name.as_str())
+            .and_then(|value| value.get(map_key.as_str()))
+            .is_some())
+    }
+
+    pub fn put(&self, key: &K, value: V) -> anyhow::Result<()> {
+        let map_key = Self::map_key(key)?;
+        let value = to_value(&value)?;
+        let mut store = self.store.lock().unwrap();
+        match store.get_mut_or_insert(self.name.as_str(), || Value::Object(Default::default())) {
+            Value::Object(map) => {
+                map.insert(map_key, value);
+            }
+            other => {
+                let mut map = serde_json::Map::new();
+                map.insert(map_key, value);
+                *other = Value::Object(map);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &K) -> anyhow::Result<Option<V>> {
+        let map_key = Self::map_key(key)?;
+        let mut store = self.store.lock().unwrap();
+        let (value, is_empty) = match store.get_mut(self.name.as_str()) {
+            Some(Value::Object(map)) => {
+                let value = map.remove(map_key.as_str());
+                (value, map.is_empty())
+            }
+            _ => return Ok(None),
+        };
+        if is_empty {
+            store.remove(self.name.as_str());
+        }
+        value.as_ref().map(from_value).transpose()
+    }
+
+    pub fn entries(&self) -> anyhow::Result<Vec<(K, V)>> {
+        let mut store = self.store.lock().unwrap();
+        match store.get(self.name.as_str()) {
+            Some(Value::Object(map)) => map
+                .iter()
+                .map(|(key, value)| {
+                    let key = serde_json::from_str(key.as_str())?;
+                    Ok((key, from_value(value)?))
+                })
+                .collect(),
+            _ => Ok(vec![]),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.store.lock().unwrap().remove(self.name.as_str());
+    }
+}
+
+/// An aggregated value of the current key
+#[derive(Clone)]
+pub struct ReducingState<T> {
+    name: String,
+    reduce_fn: Arc<dyn Fn(T, T) -> T + Send + Sync>,
+    store: Arc<Mutex<KeyedStateStore>>,
+}
+
+impl<T> Debug for ReducingState<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReducingState")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<T> ReducingState<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub fn get(&self) -> anyhow::Result<Option<T>> {
+        let mut store = self.store.lock().unwrap();
+        store.get(self.name.as_str()).map(from_value).transpose()
+    }
+
+    pub fn add(&self, value: T) -> anyhow::Result<()> {
+        let mut store = self.store.lock().unwrap();
+        let value = match store.get(self.name.as_str()) {
+            Some(current) => (self.reduce_fn)(from_value(current)?, value),
+            None => value,
+        };
+        store.put(self.name.as_str(), to_value(&value)?);
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        self.store.lock().unwrap().remove(self.name.as_str());
+    }
+}
+
+/// The broadcast states of an operator connected with a broadcast stream. The states are only
+/// updated by the records of the broadcast stream, which are received by all parallel
+/// instances, so the states are the same on every instance
+#[derive(Clone, Debug, Default)]
+pub struct BroadcastContext {
+    states: RuntimeContext,
+}
+
+impl BroadcastContext {
+    pub fn new() -> Self {
+        BroadcastContext::default()
+    }
+
+    pub fn broadcast_state<K, V>(&self, descriptor: &MapStateDescriptor<K, V>) -> MapState<K, V>
+    where
+        K: Serialize + DeserializeOwned,
+        V: Serialize + DeserializeOwned,
+    {
+        self.states.map_state(descriptor)
+    }
+
+    /// The view of the states for the records of the non-broadcast streams
+    pub fn read_only(&self) -> ReadOnlyBroadcastContext {
+        ReadOnlyBroadcastContext {
+            states: self.states.clone(),
+        }
+    }
+
+    pub fn snapshot(&self) -> anyhow::Result<CheckpointHandle> {
+        self.states.snapshot()
+    }
+
+    pub fn restore(&self, handle: &CheckpointHandle) -> anyhow::Result<()> {
+        self.states.restore(handle)
+    }
+}
+
+/// The broadcast states can't be modified by the records of the non-broadcast streams, otherwise
+/// the states of the parallel instances diverge
+#[derive(Clone, Debug)]
+pub struct ReadOnlyBroadcastContext {
+    states: RuntimeContext,
+}
+
+impl ReadOnlyBroadcastContext {
+    pub fn broadcast_state<K, V>(
+        &self,
+        descriptor: &MapStateDescriptor<K, V>,
+    ) -> ReadOnlyBroadcastState<K, V>
+    where
+        K: Serialize + DeserializeOwned,
+        V: Serialize + DeserializeOwned,
+    {
+        ReadOnlyBroadcastState {
+            state: self.states.map_state(descriptor),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReadOnlyBroadcastState<K, V> {
+    state: MapState<K, V>,
+}
+
+impl<K, V> ReadOnlyBroadcastState<K, V>
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    pub fn get(&self, key: &K) -> anyhow::Result<Option<V>> {
+        self.state.get(key)
+    }
+
+    pub fn contains(&self, key: &K) -> anyhow::Result<bool> {
+        self.state.contains(key)
+    }
+
+    pub fn entries(&self) -> anyhow::Result<Vec<(K, V)>> {
+        self.state.entries()
+    }
+}
+
+/// How the entries of an operator `ListState` are redistributed to the tasks on restore
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperatorStateMode {
+    /// the entries of all tasks are concatenated and split evenly to the tasks
+    Split,
+    /// every task gets the entries of all tasks
+    Union,
+}
+
+/// The entries of an operator state in the checkpoint of a task
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct OperatorStateEntries {
+    mode: OperatorStateMode,
+    values: Vec<Value>,
+}
+
+/// The checkpoint of the operator states of a task
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct OperatorStateSnapshot {
+    states: BTreeMap<String, OperatorStateEntries>,
+}
+
+/// The non-keyed states of an operator task, e.g. the partitions and offsets of a source or the
+/// pending transactions of a sink. Each state is a list whose entries are redistributed to the
+/// tasks by its `OperatorStateMode` when the job is restored with a different parallelism
+#[derive(Clone, Debug, Default)]
+pub struct OperatorStateStore {
+    states: RuntimeContext,
+    modes: Arc<Mutex<BTreeMap<String, OperatorStateMode>>>,
+}
+
+impl OperatorStateStore {
+    pub fn new() -> Self {
+        OperatorStateStore::default()
+    }
+
+    /// The state split evenly to the tasks on restore
+    pub fn list_state<T>(&self, descriptor: &ListStateDescriptor<T>) -> ListState<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.register(descriptor.name.as_str(), OperatorStateMode::Split);
+        self.states.list_state(descriptor)
+    }
+
+    /// The state of every task contains the entries of all tasks on restore
+    pub fn union_list_state<T>(&self, descriptor: &ListStateDescriptor<T>) -> ListState<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.register(descriptor.name.as_str(), OperatorStateMode::Union);
+        self.states.list_state(descriptor)
+    }
+
+    fn register(&self, name: &str, mode: OperatorStateMode) {
+        self.modes.lock().unwrap().insert(name.to_string(), mode);
+    }
+
+    pub fn snapshot(&self) -> anyhow::Result<CheckpointHandle> {
+        let modes = self.modes.lock().unwrap();
+        let store = self.states.store.lock().unwrap();
+
+        // the operator states are stored as the states of the empty key
+        let task_key: &[u8] = &[];
+        let mut snapshot = OperatorStateSnapshot::default();
+        for (name, values) in &store.states {
+            let values = match values.get(task_key).map(|entry| &entry.value) {
+                Some(Value::Array(values)) => values.clone(),
+                _ => continue,
+            };
+            let mode = modes.get(name).copied().unwrap_or(OperatorStateMode::Split);
+            snapshot
+                .states
+                .insert(name.clone(), OperatorStateEntries { mode, values });
+        }
+
+        let handle = serde_json::to_string(&snapshot)?;
+        Ok(CheckpointHandle { handle })
+    }
+
+    /// Restore the states of the task `task_number` in `num_tasks` from the checkpoint `handles`
+    /// of all tasks, the `handles` are ordered by the task number of the checkpoint
+    pub fn restore(
+        &self,
+        task_number: u16,
+        num_tasks: u16,
+        handles: &[CheckpointHandle],
+    ) -> anyhow::Result<()> {
+        self.redistribute(task_number, num_tasks, handles, None)
+    }
+
+    /// Restore the states of the task from the checkpoint of the `context`. The split states of
+    /// the task are kept if the parallelism isn't changed, otherwise they are redistributed
+    pub fn restore_from(&self, context: &Context) -> anyhow::Result<()> {
+        let task_id = context.task_id;
+        let handles: Vec<CheckpointHandle> = context
+            .operator_state_handles
+            .iter()
+            .map(|x| x.handle.clone())
+            .collect();
+        let rescaled = context
+            .operator_state_handles
+            .iter()
+            .any(|x| x.task_id.num_tasks() != task_id.num_tasks());
+        let own = if rescaled {
+            None
+        } else {
+            context
+                .operator_state_handles
+                .iter()
+                .position(|x| x.task_id.task_number() == task_id.task_number())
+        };
+        self.redistribute(
+            task_id.task_number(),
+            task_id.num_tasks(),
+            handles.as_slice(),
+            own,
+        )
+    }
+
+    /// Merge the states of the `handles` and pick the task's part, the split states are taken
+    /// from the handle at the index `own` only if it's set
+    fn redistribute(
+        &self,
+        task_number: u16,
+        num_tasks: u16,
+        handles: &[CheckpointHandle],
+        own: Option<usize>,
+    ) -> anyhow::Result<()> {
+        let mut states: BTreeMap<String, OperatorStateEntries> = BTreeMap::new();
+        for (index, handle) in handles.iter().enumerate() {
+            if handle.handle.is_empty() {
+                continue;
+            }
+            let snapshot: OperatorStateSnapshot = serde_json::from_str(handle.handle.as_str())?;
+            for (name, mut entries) in snapshot.states {
+                if entries.mode == OperatorStateMode::Split && own.is_some() && own != Some(index) {
+                    entries.values.clear();
+                }
+                match states.get_mut(&name) {
+                    Some(merged) => merged.values.extend(entries.values),
+                    None => {
+                        states.insert(name, entries);
+                    }
+                }
+            }
+        }
+
+        let mut modes = self.modes.lock().unwrap();
+        let mut store = self.states.store.lock().unwrap();
+        store.states.clear();
+        for (name, entries) in states {
+            let values = match entries.mode {
+                OperatorStateMode::Split if own.is_none() => {
+                    let len = entries.values.len();
+                    let start = len * task_number as usize / num_tasks as usize;
+                    let end = len * (task_number as usize + 1) / num_tasks as usize;
+                    entries.values[start..end].to_vec()
+                }
+                _ => entries.values,
+            };
+            if values.is_empty() {
+                continue;
+            }
+
+            let entry = StateEntry {
+                value: Value::Array(values),
+                timestamp: current_timestamp_millis(),
+            };
+            let mut state_values = StateValues::new();
+            state_values.insert(vec![], entry);
+            store.states.insert(name.clone(), state_values);
+            modes.insert(name, entries.mode);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::core::key_group::{assign_to_key_group, KeyGroupRange};
+    use crate::core::runtime::{JobId, TaskId};
+    use crate::core::state::{
+        is_expired_entry, query_state, queryable_state_tasks, BroadcastContext,
+        ListStateDescriptor, MapStateDescriptor, OperatorStateStore, ReducingStateDescriptor,
+        RuntimeContext, StateTtlConfig, StateVisibility, ValueStateDescriptor,
+    };
+    use std::time::Duration;
+
+    fn key(k: &str) -> Record {
+        let schema = Schema::new(vec![Field::new("k", DataType::String)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_str(k).unwrap();
+        record
+    }
+
+    #[test]
+    pub fn keyed_state_test() {
+        let runtime_context = RuntimeContext::new();
+        let value_state = runtime_context.value_state(&ValueStateDescriptor::<u64>::new("v"));
+        let list_state = runtime_context.list_state(&ListStateDescriptor::<String>::new("l"));
+        let map_state = runtime_context.map_state(&MapStateDescriptor::<u32, String>::new("m"));
+        let reducing_state = runtime_context
+            .reducing_state(&ReducingStateDescriptor::new("r", |a: i64, b: i64| a + b));
+
+        runtime_context.set_current_key(&key("a"));
+        value_state.update(1).unwrap();
+        list_state.add("x".to_string()).unwrap();
+        list_state.add("y".to_string()).unwrap();
+        map_state.put(&7, "seven".to_string()).unwrap();
+        reducing_state.add(2).unwrap();
+        reducing_state.add(3).unwrap();
+
+        runtime_context.set_current_key(&key("b"));
+        assert_eq!(value_state.value().unwrap(), None);
+        assert!(list_state.get().unwrap().is_empty());
+        value_state.update(10).unwrap();
+
+        let handle = runtime_context.snapshot().unwrap();
+        let restored = RuntimeContext::new();
+        restored.restore(&handle).unwrap();
+        let value_state = restored.value_state(&ValueStateDescriptor::<u64>::new("v"));
+        let list_state = restored.list_state(&ListStateDescriptor::<String>::new("l"));
+        let map_state = restored.map_state(&MapStateDescriptor::<u32, String>::new("m"));
+        let reducing_state =
+            restored.reducing_state(&ReducingStateDescriptor::new("r", |a: i64, b: i64| a + b));
+
+        restored.set_current_key(&key("a"));
+        assert_eq!(value_state.value().unwrap(), Some(1));
+        assert_eq!(list_state.get().unwrap(), vec!["x", "y"]);
+        assert_eq!(map_state.get(&7).unwrap(), Some("seven".to_string()));
+        assert_eq!(map_state.remove(&7).unwrap(), Some("seven".to_string()));
+        assert!(map_state.entries().unwrap().is_empty());
+        assert_eq!(reducing_state.get().unwrap(), Some(5));
+
+        restored.set_current_key(&key("b"));
+        assert_eq!(value_state.value().unwrap(), Some(10));
+        value_state.clear();
+        assert_eq!(value_state.value().unwrap(), None);
+    }
+
+    #[test]
+    pub fn state_ttl_test() {
+        let runtime_context = RuntimeContext::new();
+        let expired = StateTtlConfig::new(Duration::from_millis(0));
+        let value_state =
+            runtime_context.value_state(&ValueStateDescriptor::<u64>::new("v").ttl(expired));
+        let list_state = runtime_context.list_state(
+            &ListStateDescriptor::<u64>::new("l")
+                .ttl(expired.visibility(StateVisibility::ReturnExpiredIfNotCleanedUp)),
+        );
+        let live = StateTtlConfig::new(Duration::from_secs(3600));
+        let map_state =
+            runtime_context.map_state(&MapStateDescriptor::<u32, u32>::new("m").ttl(live));
+
+        runtime_context.set_current_key(&key("a"));
+        value_state.update(1).unwrap();
+        list_state.add(2).unwrap();
+        map_state.put(&3, 4).unwrap();
+
+        assert_eq!(value_state.value().unwrap(), None);
+        assert_eq!(list_state.get().unwrap(), vec![2]);
+        assert_eq!(map_state.get(&3).unwrap(), Some(4));
+
+        runtime_context.cleanup_expired();
+        assert!(list_state.get().unwrap().is_empty());
+        assert_eq!(map_state.get(&3).unwrap(), Some(4));
+    }
+
+    #[test]
+    pub fn expired_entry_test() {
+        let ttl_config = StateTtlConfig::new(Duration::from_millis(100));
+        let value = br#"{"value":[1,2],"timestamp":1000}"#;
+        assert!(!is_expired_entry(&ttl_config, value, 1099));
+        assert!(is_expired_entry(&ttl_config, value, 1100));
+        assert!(is_expired_entry(&ttl_config, br#"{"value":1}"#, 100));
+        assert!(!is_expired_entry(&ttl_config, b"unknown", 1100));
+    }
+
+    #[test]
+    pub fn queryable_state_test() {
+        let task0 = RuntimeContext::new();
+        let task1 = RuntimeContext::new();
+        let descriptor = ValueStateDescriptor::<u64>::new("count").queryable("test-count");
+        let state0 = task0.value_state(&descriptor);
+        let state1 = task1.value_state(&descriptor);
+
+        task0.set_current_key(&key("a"));
+        state0.update(1).unwrap();
+        task1.set_current_key(&key("b"));
+        state1.update(2).unwrap();
+
+        let key_a = key("a").values.as_slice().to_vec();
+        let key_b = key("b").values.as_slice().to_vec();
+        let key_c = key("c").values.as_slice().to_vec();
+        assert_eq!(query_state("test-count", None, &key_a), Some(1.into()));
+        assert_eq!(query_state("test-count", None, &key_b), Some(2.into()));
+        assert_eq!(query_state("test-count", None, &key_c), None);
+        assert!(queryable_state_tasks().is_empty());
+
+        for (task_number, task) in [(0, &task0), (1, &task1)] {
+            task.store.lock().unwrap().task_id = Some(TaskId {
+                job_id: JobId(1),
+                task_number,
+                num_tasks: 2,
+            });
+        }
+        assert_eq!(query_state("test-count", Some(1), &key_a), None);
+        assert_eq!(query_state("test-count", Some(1), &key_b), Some(2.into()));
+        let mut tasks: Vec<u16> = queryable_state_tasks()
+            .into_iter()
+            .filter(|(queryable_name, _)| queryable_name.eq("test-count"))
+            .map(|(_, task_id)| task_id.task_number)
+            .collect();
+        tasks.sort();
+        assert_eq!(tasks, vec![0, 1]);
+
+        drop((state1, task1));
+        assert_eq!(query_state("test-count", None, &key_b), None);
+    }
+
+    #[test]
+    pub fn broadcast_state_test() {
+        let descriptor = MapStateDescriptor::<String, u32>::new("rules");
+        let broadcast_context = BroadcastContext::new();
+        let rules = broadcast_context.broadcast_state(&descriptor);
+        rules.put(&"max".to_string(), 10).unwrap();
+
+        let read_only = broadcast_context.read_only().broadcast_state(&descriptor);
+        assert_eq!(read_only.get(&"max".to_string()).unwrap(), Some(10));
+
+        let handle = broadcast_context.snapshot().unwrap();
+        let restored = BroadcastContext::new();
+        restored.restore(&handle).unwrap();
+        let read_only = restored.read_only().broadcast_state(&descriptor);
+        assert_eq!(read_only.entries().unwrap(), vec![("max".to_string(), 10)]);
+    }
+
+    #[test]
+    pub fn operator_state_test() {
+        let offsets = ListStateDescriptor::<u32>::new("offsets");
+        let sequences = ListStateDescriptor::<u32>::new("sequences");
+
+        // checkpoint of 2 tasks
+        let handles: Vec<_> = (0..2u32)
+            .map(|task_number| {
+                let store = OperatorStateStore::new();
+                let offset_state = store.list_state(&offsets);
+                offset_state.add(task_number * 10).unwrap();
+                offset_state.add(task_number * 10 + 1).unwrap();
+                store.union_list_state(&sequences).add(task_number).unwrap();
+                store.snapshot().unwrap()
+            })
+            .collect();
+
+        // restore to 4 tasks
+        let mut restored_offsets = vec![];
+        for task_number in 0..4 {
+            let store = OperatorStateStore::new();
+            store.restore(task_number, 4, handles.as_slice()).unwrap();
+
+            let task_offsets = store.list_state(&offsets).get().unwrap();
+            assert_eq!(task_offsets.len(), 1);
+            restored_offsets.extend(task_offsets);
+
+            let task_sequences = store.union_list_state(&sequences).get().unwrap();
+            assert_eq!(task_sequences, vec![0, 1]);
+        }
+        assert_eq!(restored_offsets, vec![0, 1, 10, 11]);
+
+        // restore to 1 task
+        let store = OperatorStateStore::new();
+        store.restore(0, 1, handles.as_slice()).unwrap();
+        assert_eq!(
+            store.list_state(&offsets).get().unwrap(),
+            vec![0, 1, 10, 11]
+        );
+
+        // the split states of the task are kept if the parallelism isn't changed
+        let store = OperatorStateStore::new();
+        store
+            .redistribute(1, 2, handles.as_slice(), Some(1))
+            .unwrap();
+        assert_eq!(store.list_state(&offsets).get().unwrap(), vec![10, 11]);
+        assert_eq!(
+            store.union_list_state(&sequences).get().unwrap(),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    pub fn key_group_rescale_test() {
+        let max_parallelism = 16;
+        let descriptor = ValueStateDescriptor::<u32>::new("count");
+
+        // checkpoint of 3 tasks
+        let handles: Vec<_> = (0..3u16)
+            .map(|task_number| {
+                let key_groups = KeyGroupRange::of_operator(max_parallelism, 3, task_number);
+                let runtime_context = RuntimeContext::new();
+                let state = runtime_context.value_state(&descriptor);
+                for i in 0..100u32 {
+                    let k = key(format!("k{}", i).as_str());
+                    if key_groups.contains(assign_to_key_group(&k.values, max_parallelism)) {
+                        runtime_context.set_current_key(&k);
+                        state.update(i).unwrap();
+                    }
+                }
+                runtime_context.snapshot().unwrap()
+            })
+            .collect();
+
+        // restore to 2 tasks
+        let mut restored = 0;
+        for task_number in 0..2u16 {
+            let key_groups = KeyGroupRange::of_operator(max_parallelism, 2, task_number);
+            let runtime_context = RuntimeContext::new();
+            runtime_context
+                .restore_key_groups(&key_groups, max_parallelism, handles.as_slice())
+                .unwrap();
+
+            let state = runtime_context.value_state(&descriptor);
+            for i in 0..100u32 {
+                let k = key(format!("k{}", i).as_str());
+                runtime_context.set_current_key(&k);
+                let value = state.value().unwrap();
+                if key_groups.contains(assign_to_key_group(&k.values, max_parallelism)) {
+                    assert_eq!(value, Some(i));
+                    restored += 1;
+                } else {
+                    assert_eq!(value, None);
+                }
+            }
+        }
+        assert_eq!(restored, 100);
+    }
+}
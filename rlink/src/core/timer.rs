@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::core::element::Record;
+use crate::core::state::{RuntimeContext, ValueStateDescriptor};
+use crate::utils::date_time::current_timestamp_millis;
+
+/// the keyed state of the timers, it's checkpointed with the other keyed states
+const TIMERS_STATE: &str = "keyed_process.timers";
+
+/// The time domain of a timer of the `TimerService`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeDomain {
+    EventTime,
+    ProcessingTime,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeyTimers {
+    event_time: Vec<u64>,
+    processing_time: Vec<u64>,
+}
+
+/// The timers of the keys of a `KeyedProcessFunction`, the timers are registered and deleted
+/// for the current key, and a key has at most one timer of a timestamp in each time domain.
+///
+/// The event time timers fire once the watermark passes them, the processing time timers are
+/// checked when the watermark or the stream status reaches
+#[derive(Debug)]
+pub struct TimerService {
+    current_key: Record,
+    current_watermark: u64,
+    event_time_timers: BTreeSet<(u64, Record)>,
+    processing_time_timers: BTreeSet<(u64, Record)>,
+    /// the keys whose timers are changed since the latest checkpoint
+    changed_keys: BTreeSet<Record>,
+}
+
+impl TimerService {
+    pub(crate) fn new() -> Self {
+        TimerService {
+            current_key: Record::new(),
+            current_watermark: 0,
+            event_time_timers: BTreeSet::new(),
+            processing_time_timers: BTreeSet::new(),
+            changed_keys: BTreeSet::new(),
+        }
+    }
+
+    pub(crate) fn set_current_key(&mut self, key: Record) {
+        self.current_key = key;
+    }
+
+    pub(crate) fn set_current_watermark(&mut self, watermark: u64) {
+        self.current_watermark = watermark;
+    }
+
+    /// The key of the processed record or the fired timer
+    pub fn current_key(&self) -> &Record {
+        &self.current_key
+    }
+
+    pub fn current_watermark(&self) -> u64 {
+        self.current_watermark
+    }
+
+    pub fn current_processing_time(&self) -> u64 {
+        current_timestamp_millis()
+    }
+
+    pub fn register_event_time_timer(&mut self, timestamp: u64) {
+        let key = self.current_key.clone();
+        self.event_time_timers.insert((timestamp, key));
+        self.changed_keys.insert(self.current_key.clone());
+    }
+
+    pub fn delete_event_time_timer(&mut self, timestamp: u64) {
+        let key = self.current_key.clone();
+        if self.event_time_timers.remove(&(timestamp, key)) {
+            self.changed_keys.insert(self.current_key.clone());
+        }
+    }
+
+    pub fn register_processing_time_timer(&mut self, timestamp: u64) {
+        let key = self.current_key.clone();
+        self.processing_time_timers.insert((timestamp, key));
+        self.changed_keys.insert(self.current_key.clone());
+    }
+
+    pub fn delete_processing_time_timer(&mut self, timestamp: u64) {
+        let key = self.current_key.clone();
+        if self.processing_time_timers.remove(&(timestamp, key)) {
+            self.changed_keys.insert(self.current_key.clone());
+        }
+    }
+
+    /// Remove the earliest event time timer passed by the current watermark
+    pub(crate) fn poll_event_time_timer(&mut self) -> Option<(u64, Record)> {
+        let timer = Self::poll(&mut self.event_time_timers, self.current_watermark)?;
+        self.changed_keys.insert(timer.1.clone());
+        Some(timer)
+    }
+
+    /// Remove the earliest processing time timer passed by the `time`
+    pub(crate) fn poll_processing_time_timer(&mut self, time: u64) -> Option<(u64, Record)> {
+        let timer = Self::poll(&mut self.processing_time_timers, time)?;
+        self.changed_keys.insert(timer.1.clone());
+        Some(timer)
+    }
+
+    fn poll(timers: &mut BTreeSet<(u64, Record)>, time: u64) -> Option<(u64, Record)> {
+        let timer = timers
+            .iter()
+            .next()
+            .filter(|(timestamp, _)| *timestamp <= time)?;
+        let timer = timer.clone();
+        timers.remove(&timer);
+        Some(timer)
+    }
+
+    /// Write the timers of the changed keys to the keyed states, so they're checkpointed with
+    /// the keyed states and redistributed by the key groups when the parallelism is changed
+    pub(crate) fn snapshot(&mut self, runtime_context: &RuntimeContext) -> anyhow::Result<()> {
+        if self.changed_keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut key_timers: BTreeMap<&Record, KeyTimers> = BTreeMap::new();
+        for (timestamp, key) in &self.event_time_timers {
+            if self.changed_keys.contains(key) {
+                key_timers
+                    .entry(key)
+                    .or_default()
+                    .event_time
+                    .push(*timestamp);
+            }
+        }
+        for (timestamp, key) in &self.processing_time_timers {
+            if self.changed_keys.contains(key) {
+                key_timers
+                    .entry(key)
+                    .or_default()
+                    .processing_time
+                    .push(*timestamp);
+            }
+        }
+
+        let state = runtime_context.value_state(&ValueStateDescriptor::new(TIMERS_STATE));
+        for key in &self.changed_keys {
+            runtime_context.set_current_key(key);
+            match key_timers.remove(key) {
+                Some(timers) => state.update(timers)?,
+                None => state.clear(),
+            }
+        }
+
+        self.changed_keys.clear();
+        Ok(())
+    }
+
+    /// Restore the timers from the keyed states restored by the `runtime_context`
+    pub(crate) fn restore(&mut self, runtime_context: &RuntimeContext) -> anyhow::Result<()> {
+        let entries: Vec<(Record, KeyTimers)> = runtime_context.entries(TIMERS_STATE)?;
+        for (key, timers) in entries {
+            for timestamp in timers.event_time {
+                self.event_time_timers.insert((timestamp, key.clone()));
+            }
+            for timestamp in timers.processing_time {
+                self.processing_time_timers.insert((timestamp, key.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::core::state::RuntimeContext;
+    use crate::core::timer::TimerService;
+
+    fn key(k: &str) -> Record {
+        let schema = Schema::new(vec![Field::new("k", DataType::String)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_str(k).unwrap();
+        record
+    }
+
+    #[test]
+    pub fn timer_service_test() {
+        let mut timer_service = TimerService::new();
+
+        timer_service.set_current_key(key("a"));
+        timer_service.register_event_time_timer(20);
+        timer_service.register_event_time_timer(10);
+        timer_service.register_processing_time_timer(100);
+        timer_service.set_current_key(key("b"));
+        timer_service.register_event_time_timer(15);
+        timer_service.register_event_time_timer(30);
+        timer_service.delete_event_time_timer(30);
+
+        let runtime_context = RuntimeContext::new();
+        timer_service.snapshot(&runtime_context).unwrap();
+        let mut restored = TimerService::new();
+        restored.restore(&runtime_context).unwrap();
+
+        for timer_service in [&mut timer_service, &mut restored] {
+            timer_service.set_current_watermark(15);
+            assert_eq!(timer_service.poll_event_time_timer(), Some((10, key("a"))));
+            assert_eq!(timer_service.poll_event_time_timer(), Some((15, key("b"))));
+            assert_eq!(timer_service.poll_event_time_timer(), None);
+
+            assert_eq!(timer_service.poll_processing_time_timer(99), None);
+            assert_eq!(
+                timer_service.poll_processing_time_timer(100),
+                Some((100, key("a")))
+            );
+        }
+    }
+}
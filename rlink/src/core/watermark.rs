@@ -3,12 +3,15 @@ use std::fmt::Debug;
 use crate::core::checkpoint::CheckpointFunction;
 use crate::core::element::Record;
 use crate::core::function::{Context, NamedFunction};
+use crate::functions::watermark::WatermarkAlignment;
 
 pub const MAX_WATERMARK: Watermark = Watermark {
     timestamp: 253402185600000u64,
 };
 pub const MIN_WATERMARK: Watermark = Watermark { timestamp: 0x0 };
 
+/// The watermark of an idle source split, the split is excluded from the min-watermark
+/// calculation until it produces data again
 pub const IDLE_WATERMARK: Watermark = Watermark {
     timestamp: MAX_WATERMARK.timestamp + 1,
 };
@@ -52,6 +55,9 @@ pub trait TimestampAssigner: Debug + Send + Sync {
     fn extract_timestamp(&mut self, row: &mut Record, previous_element_timestamp: u64) -> u64;
 }
 
+/// The `WatermarkGenerator` generates watermarks either periodically by `on_periodic_emit`, e.g.
+/// `BoundedOutOfOrdernessWatermarks`, or punctuated per record by `on_event`, e.g.
+/// `PunctuatedWatermarks`
 pub trait WatermarkGenerator: Debug + Send + Sync {
     /// Called for every event, allows the watermark generator to examine and remember the event
     /// timestamps, or to emit a watermark based on the event itself.
@@ -71,6 +77,20 @@ pub trait WatermarkStrategy: NamedFunction + CheckpointFunction + Debug + Send +
     /// Instantiates a `WatermarkGenerator` that generates watermarks according to this strategy.
     fn create_watermark_generator(&mut self) -> Box<dyn WatermarkGenerator>;
 
+    /// Instantiates a `WatermarkGenerator` for a source split, e.g. a kafka partition, so the
+    /// watermarks are generated per split and the minimum across the splits is emitted.
+    ///
+    /// Return `None` if the strategy can't create more generators, then all splits share the
+    /// one created by `create_watermark_generator`.
+    fn create_split_watermark_generator(&mut self) -> Option<Box<dyn WatermarkGenerator>> {
+        None
+    }
+
+    /// The watermark alignment group of the source splits, `None` if the splits are not aligned
+    fn watermark_alignment(&self) -> Option<WatermarkAlignment> {
+        None
+    }
+
     /// Instantiates a `TimestampAssigner` for assigning timestamps according to this strategy.
     fn create_timestamp_assigner(&mut self) -> Box<dyn TimestampAssigner>;
 }
@@ -1,7 +1,9 @@
 use std::cmp::{max, min};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 
 use crate::core::checkpoint::CheckpointFunction;
+use crate::core::element::Record;
 use crate::core::function::NamedFunction;
 use crate::utils;
 
@@ -56,21 +58,97 @@ impl TWindow for TimeWindow {
     }
 }
 
+/// The window of every `size` elements of a key, it's fired every `slide` elements. The window
+/// is never completed by the watermark
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub struct CountWindow {
+    size: u64,
+    slide: u64,
+    /// the sequence of the fired window in the task, it's zero for the window of the assigner
+    seq: u64,
+}
+
+impl CountWindow {
+    pub fn new(size: u64, slide: u64) -> Self {
+        CountWindow {
+            size,
+            slide,
+            seq: 0,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn slide(&self) -> u64 {
+        self.slide
+    }
+
+    pub fn is_tumbling(&self) -> bool {
+        self.size == self.slide
+    }
+
+    /// The window fired as the `seq`th window of the task
+    pub(crate) fn fired(&self, seq: u64) -> Self {
+        CountWindow {
+            size: self.size,
+            slide: self.slide,
+            seq,
+        }
+    }
+}
+
+impl TWindow for CountWindow {
+    fn max_timestamp(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn min_timestamp(&self) -> u64 {
+        0
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Window {
     TimeWindow(TimeWindow),
+    /// The window of a session, the intersecting session windows of a key are merged into
+    /// the window covers all of them
+    SessionWindow(TimeWindow),
+    /// The window of the elements of a key by count, see `CountWindow`
+    CountWindow(CountWindow),
+}
+
+impl Window {
+    pub fn is_session_window(&self) -> bool {
+        match self {
+            Window::TimeWindow(_) | Window::CountWindow(_) => false,
+            Window::SessionWindow(_) => true,
+        }
+    }
+
+    pub fn is_count_window(&self) -> bool {
+        match self {
+            Window::TimeWindow(_) | Window::SessionWindow(_) => false,
+            Window::CountWindow(_) => true,
+        }
+    }
 }
 
 impl TWindow for Window {
     fn max_timestamp(&self) -> u64 {
         match self {
             Window::TimeWindow(time_window) => time_window.max_timestamp(),
+            Window::SessionWindow(time_window) => time_window.max_timestamp(),
+            Window::CountWindow(count_window) => count_window.max_timestamp(),
         }
     }
 
     fn min_timestamp(&self) -> u64 {
         match self {
             Window::TimeWindow(time_window) => time_window.min_timestamp(),
+            Window::SessionWindow(time_window) => time_window.min_timestamp(),
+            Window::CountWindow(count_window) => count_window.min_timestamp(),
         }
     }
 }
@@ -91,10 +169,247 @@ impl WindowAssignerContext {
 }
 
 /// A `WindowAssigner` assigns zero or more `Window`s to an element.
+///
+/// Custom windows, e.g. business-calendar windows or per-tenant offsets, are implemented by the
+/// assigner returning `Window::TimeWindow`s, or `Window::SessionWindow`s to merge the
+/// intersecting windows of a key (and `is_merging` returning true), and work with the window
+/// operator, the triggers and the window states as the built-in assigners do.
 pub trait WindowAssigner
 where
     Self: NamedFunction + CheckpointFunction + Debug + Send + Sync,
 {
     /// Returns a collection of windows that should be assigned to the element.
     fn assign_windows(&self, timestamp: u64, context: WindowAssignerContext) -> Vec<Window>;
+
+    /// Returns a collection of windows that should be assigned to the record, the windows of
+    /// the record timestamp by default
+    fn assign_record_windows(
+        &self,
+        record: &mut Record,
+        context: WindowAssignerContext,
+    ) -> Vec<Window> {
+        self.assign_windows(record.timestamp, context)
+    }
+
+    /// Returns the windows of the watermark, the windows end before the start of the earliest
+    /// one of them are completed. The windows of the watermark timestamp by default, or the
+    /// empty window at the timestamp if no window covers it
+    fn assign_watermark_windows(
+        &self,
+        timestamp: u64,
+        context: WindowAssignerContext,
+    ) -> Vec<Window> {
+        let windows = self.assign_windows(timestamp, context);
+        if windows.is_empty() {
+            vec![Window::TimeWindow(TimeWindow::new(timestamp, timestamp))]
+        } else {
+            windows
+        }
+    }
+
+    /// Whether the assigned windows of a key are merged, the window function must support
+    /// merging the reduced values of them
+    fn is_merging(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerResult {
+    /// No action is taken on the window
+    Continue,
+    /// The window is evaluated and emitted, the state of the window is kept
+    Fire,
+    /// The window is evaluated and emitted, then the state of the window is cleared
+    FireAndPurge,
+}
+
+impl TriggerResult {
+    pub fn is_fire(&self) -> bool {
+        match self {
+            TriggerResult::Continue => false,
+            TriggerResult::Fire | TriggerResult::FireAndPurge => true,
+        }
+    }
+
+    pub fn is_purge(&self) -> bool {
+        match self {
+            TriggerResult::FireAndPurge => true,
+            TriggerResult::Continue | TriggerResult::Fire => false,
+        }
+    }
+
+    /// The result with the strongest action of the two
+    pub fn merge(self, other: TriggerResult) -> TriggerResult {
+        match (self, other) {
+            (TriggerResult::FireAndPurge, _) | (_, TriggerResult::FireAndPurge) => {
+                TriggerResult::FireAndPurge
+            }
+            (TriggerResult::Fire, _) | (_, TriggerResult::Fire) => TriggerResult::Fire,
+            _ => TriggerResult::Continue,
+        }
+    }
+}
+
+/// The context of a window for the `Trigger`, the states are kept until the window is purged
+#[derive(Clone, Debug, Default)]
+pub struct TriggerContext {
+    current_watermark: u64,
+    states: HashMap<String, u64>,
+}
+
+impl TriggerContext {
+    pub(crate) fn set_current_watermark(&mut self, current_watermark: u64) {
+        self.current_watermark = current_watermark;
+    }
+
+    pub fn current_watermark(&self) -> u64 {
+        self.current_watermark
+    }
+
+    pub fn current_processing_time(&self) -> u64 {
+        utils::date_time::current_timestamp_millis()
+    }
+
+    pub fn get_state(&self, name: &str) -> Option<u64> {
+        self.states.get(name).cloned()
+    }
+
+    pub fn set_state(&mut self, name: &str, value: u64) {
+        self.states.insert(name.to_string(), value);
+    }
+
+    pub fn remove_state(&mut self, name: &str) -> Option<u64> {
+        self.states.remove(name)
+    }
+}
+
+/// A `Trigger` determines when a window is evaluated and emitted. The trigger is evaluated per
+/// window of the task, all keys of the window are emitted together.
+///
+/// The `on_event_time` is called with every watermark, and the `on_processing_time` is called
+/// with the processing time when the watermark reaches, for all windows of the task. The window
+/// is always purged after the watermark passes the end of the window
+pub trait Trigger
+where
+    Self: NamedFunction + Debug + Send + Sync,
+{
+    fn on_element(
+        &self,
+        record: &Record,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult;
+
+    fn on_event_time(
+        &self,
+        time: u64,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult;
+
+    fn on_processing_time(
+        &self,
+        time: u64,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult;
+
+    /// Clear the states of the trigger in the `context`
+    fn clear(&self, _window: &Window, _context: &mut TriggerContext) {}
+}
+
+/// The context of a window for the `Evictor`
+#[derive(Clone, Debug)]
+pub struct EvictorContext {
+    current_watermark: u64,
+}
+
+impl EvictorContext {
+    pub(crate) fn new(current_watermark: u64) -> Self {
+        EvictorContext { current_watermark }
+    }
+
+    pub fn current_watermark(&self) -> u64 {
+        self.current_watermark
+    }
+
+    pub fn current_processing_time(&self) -> u64 {
+        utils::date_time::current_timestamp_millis()
+    }
+}
+
+/// An `Evictor` removes the elements of a window before or after the window is evaluated by
+/// the trigger firing, the window is evaluated by the remaining elements. The `elements` are
+/// the elements of a key of the window in the arrival order.
+///
+/// The elements of the windows are kept in the memory if the evictor is set
+pub trait Evictor
+where
+    Self: NamedFunction + Debug + Send + Sync,
+{
+    fn evict_before(&self, elements: &mut Vec<Record>, window: &Window, context: &EvictorContext);
+
+    fn evict_after(&self, elements: &mut Vec<Record>, window: &Window, context: &EvictorContext);
+}
+
+/// The context of a window for the `ProcessWindowFunction`, the states are of the current key
+/// and the window, they're kept until the window is purged
+#[derive(Clone, Debug)]
+pub struct ProcessWindowContext {
+    window: Window,
+    current_watermark: u64,
+    current_key: Record,
+    states: BTreeMap<Record, HashMap<String, Vec<u8>>>,
+}
+
+impl ProcessWindowContext {
+    pub(crate) fn new(window: Window) -> Self {
+        ProcessWindowContext {
+            window,
+            current_watermark: 0,
+            current_key: Record::new(),
+            states: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn set_current_watermark(&mut self, current_watermark: u64) {
+        self.current_watermark = current_watermark;
+    }
+
+    pub(crate) fn set_current_key(&mut self, key: &Record) {
+        self.current_key = key.clone();
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn current_watermark(&self) -> u64 {
+        self.current_watermark
+    }
+
+    pub fn current_processing_time(&self) -> u64 {
+        utils::date_time::current_timestamp_millis()
+    }
+
+    pub fn get_state(&self, name: &str) -> Option<&[u8]> {
+        self.states
+            .get(&self.current_key)
+            .and_then(|states| states.get(name))
+            .map(|value| value.as_slice())
+    }
+
+    pub fn set_state(&mut self, name: &str, value: Vec<u8>) {
+        self.states
+            .entry(self.current_key.clone())
+            .or_insert_with(HashMap::new)
+            .insert(name.to_string(), value);
+    }
+
+    pub fn remove_state(&mut self, name: &str) -> Option<Vec<u8>> {
+        self.states
+            .get_mut(&self.current_key)
+            .and_then(|states| states.remove(name))
+    }
 }
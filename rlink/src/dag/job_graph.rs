@@ -8,6 +8,7 @@ use crate::core::operator::DEFAULT_PARALLELISM;
 use crate::core::runtime::{JobId, OperatorId};
 use crate::dag::stream_graph::{StreamGraph, StreamNode};
 use crate::dag::{DagError, OperatorType};
+use crate::functions::flat_map::is_partition_function;
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum JobEdge {
@@ -42,6 +43,27 @@ impl JobNode {
             .is_some()
     }
 
+    /// The job processes the keyed records, the records must be sent to the task owning the
+    /// key group of their key
+    fn is_keyed_job(&self) -> bool {
+        self.stream_nodes.iter().any(|stream_node| {
+            stream_node.operator_type == OperatorType::Reduce
+                || stream_node.operator_type == OperatorType::KeyedProcess
+        })
+    }
+
+    /// The job ends with a partition function before its virtual sink, e.g. the
+    /// `BroadcastFlagMapFunction`, the records must be sent to the child tasks by their
+    /// partitions even if the parallelism is the same
+    fn is_partitioned_job(&self) -> bool {
+        self.stream_nodes
+            .iter()
+            .rev()
+            .find(|stream_node| stream_node.operator_type != OperatorType::Sink)
+            .map(|stream_node| is_partition_function(stream_node.operator_name.as_str()))
+            .unwrap_or(false)
+    }
+
     pub fn is_daemon_job(&self) -> bool {
         self.stream_nodes[0].daemon
     }
@@ -69,6 +91,7 @@ impl JobGraph {
     pub fn build(&mut self, stream_graph: &StreamGraph) -> Result<(), DagError> {
         let mut builder = JobNodeBuilder::new(stream_graph);
         builder.build()?;
+        builder.check_co_location()?;
         for (job_id, job_node) in builder.job_node_indies {
             let node_index = self.dag.add_node(job_node);
             self.job_node_indies.insert(job_id, node_index);
@@ -100,7 +123,7 @@ impl JobGraph {
                 .ok_or(DagError::JobNotFound(*child_job_id))?;
             let child_job_node = self.dag.index(*child_node_index);
 
-            let job_edge = if child_job_node.is_reduce_job() {
+            let job_edge = if child_job_node.is_keyed_job() || job_node.is_partitioned_job() {
                 JobEdge::ReBalance
             } else if job_node.is_reduce_job() {
                 if job_node.parallelism != child_job_node.parallelism {
@@ -227,6 +250,27 @@ impl<'a> JobNodeBuilder<'a> {
         })
     }
 
+    /// The tasks of the same number of the co-located jobs are paired, so the jobs must have the
+    /// same parallelism
+    fn check_co_location(&self) -> Result<(), DagError> {
+        let mut group_parallelism: HashMap<&str, u16> = HashMap::new();
+        for job_node in self.job_node_indies.values() {
+            let groups = job_node
+                .stream_nodes
+                .iter()
+                .filter_map(|x| x.co_location_group.as_deref());
+            for group in groups {
+                let parallelism = group_parallelism
+                    .entry(group)
+                    .or_insert(job_node.parallelism);
+                if *parallelism != job_node.parallelism {
+                    return Err(DagError::CoLocationParallelismConflict(group.to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn build_parallelism(&mut self) {
         while self.get_parent_parallelism() > 0 {}
     }
@@ -5,6 +5,7 @@ pub(crate) mod execution_graph;
 pub(crate) mod job_graph;
 pub(crate) mod metadata;
 pub(crate) mod physic_graph;
+pub(crate) mod region;
 pub(crate) mod stream_graph;
 pub(crate) mod utils;
 
@@ -49,6 +50,7 @@ pub(crate) enum OperatorType {
     CoProcess,
     KeyBy,
     Reduce,
+    KeyedProcess,
     WatermarkAssigner,
     WindowAssigner,
     Sink,
@@ -63,6 +65,7 @@ impl<'a> From<&'a StreamOperator> for OperatorType {
             StreamOperator::StreamCoProcess(_) => OperatorType::CoProcess,
             StreamOperator::StreamKeyBy(_) => OperatorType::KeyBy,
             StreamOperator::StreamReduce(_) => OperatorType::Reduce,
+            StreamOperator::StreamKeyedProcess(_) => OperatorType::KeyedProcess,
             StreamOperator::StreamWatermarkAssigner(_) => OperatorType::WatermarkAssigner,
             StreamOperator::StreamWindowAssigner(_) => OperatorType::WindowAssigner,
             StreamOperator::StreamSink(_) => OperatorType::Sink,
@@ -79,6 +82,7 @@ impl std::fmt::Display for OperatorType {
             OperatorType::CoProcess => write!(f, "CoProcess"),
             OperatorType::KeyBy => write!(f, "KeyBy"),
             OperatorType::Reduce => write!(f, "Reduce"),
+            OperatorType::KeyedProcess => write!(f, "KeyedProcess"),
             OperatorType::WatermarkAssigner => write!(f, "WatermarkAssigner"),
             OperatorType::WindowAssigner => write!(f, "WindowAssigner"),
             OperatorType::Sink => write!(f, "Sink"),
@@ -114,6 +118,18 @@ pub enum DagError {
     JobNotFound(JobId),
     #[error("job parallelism not found")]
     JobParallelismNotFound,
+    #[error("duplicate operator uid `{0}`")]
+    DuplicateUid(String),
+    #[error("the children of the operator have been added. {0:?}")]
+    OperatorChildrenAdded(OperatorId),
+    #[error("the jobs of the co-location group `{0}` have the different parallelism")]
+    CoLocationParallelismConflict(String),
+    #[error("the source `{0}` is unbounded in the batch mode")]
+    UnboundedSource(String),
+    #[error("illegal error policy of the operator {0:?}. {1}")]
+    IllegalErrorPolicy(OperatorId, String),
+    #[error("the function of the operator {0:?} doesn't support merging the windows")]
+    MergeNotSupported(OperatorId),
     #[error(transparent)]
     OtherApiError(#[from] core::Error),
 }
@@ -130,6 +146,9 @@ impl<'a> TryFrom<&'a RawStreamGraph> for DagManager {
     type Error = DagError;
 
     fn try_from(raw_stream_graph: &'a RawStreamGraph) -> Result<Self, Self::Error> {
+        raw_stream_graph.check_dead_letters()?;
+        raw_stream_graph.check_merging_windows()?;
+
         let stream_graph = StreamGraph::new(
             raw_stream_graph.sources.clone(),
             raw_stream_graph.dag.clone(),
@@ -173,6 +192,7 @@ impl DagManager {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::convert::TryFrom;
     use std::ops::Deref;
     use std::time::Duration;
@@ -183,18 +203,27 @@ mod tests {
     use crate::core::data_stream::{TConnectedStreams, TKeyedStream};
     use crate::core::data_stream::{TDataStream, TWindowedStream};
     use crate::core::data_types::{DataType, Field, Schema};
-    use crate::core::element::{Element, FnSchema, Record};
+    use crate::core::element::{Element, FnSchema, OutputTag, Record};
     use crate::core::env::StreamExecutionEnvironment;
+    use crate::core::error_policy::ErrorPolicy;
     use crate::core::function::{
-        CoProcessFunction, Context, FlatMapFunction, InputFormat, InputSplit, InputSplitSource,
-        KeySelectorFunction, NamedFunction, OutputFormat, ReduceFunction, SendableElementStream,
+        BroadcastProcessFunction, CoProcessFunction, Context, FlatMapFunction, InputFormat,
+        InputSplit, InputSplitSource, JoinFunction, JoinType, KeySelectorFunction,
+        KeyedCoProcessFunction, KeyedProcessFunction, NamedFunction, OutputFormat, Partitioner,
+        ReduceFunction, SendableElementStream,
     };
+    use crate::core::operator::{FunctionCreator, StreamOperator};
     use crate::core::properties::Properties;
+    use crate::core::state::{BroadcastContext, ReadOnlyBroadcastContext};
+    use crate::core::timer::{TimeDomain, TimerService};
     use crate::core::watermark::TimestampAssigner;
+    use crate::dag::job_graph::JobEdge;
+    use crate::dag::region::PipelinedRegions;
     use crate::dag::utils::JsonDag;
-    use crate::dag::DagManager;
+    use crate::dag::{DagError, DagManager, OperatorType, TaskId};
+    use crate::functions::reduce::TopN;
     use crate::functions::watermark::DefaultWatermarkStrategy;
-    use crate::functions::window::SlidingEventTimeWindows;
+    use crate::functions::window::{EventTimeSessionWindows, SlidingEventTimeWindows};
     use crate::utils::stream::MemoryStream;
 
     #[test]
@@ -295,6 +324,746 @@ mod tests {
         print_dag(&dag_manager);
     }
 
+    #[test]
+    pub fn data_stream_union_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let ds0 = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new());
+        let ds1 = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new());
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .union(vec![ds0, ds1])
+            .flat_map(MyFlatMapFunction::new())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the watermarks of all parent jobs are aligned by the union job
+        let dag = &dag_manager.job_graph().dag;
+        let union_job = dag
+            .raw_nodes()
+            .iter()
+            .find(|x| {
+                x.weight
+                    .stream_nodes
+                    .iter()
+                    .any(|x| x.operator_name == "UnionCoProcessFunction")
+            })
+            .unwrap();
+        assert_eq!(union_job.weight.parent_job_ids.len(), 3);
+    }
+
+    #[test]
+    pub fn data_stream_broadcast_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let rules = env.register_source(MyInputFormat::new()).broadcast();
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .connect_broadcast(rules, MyBroadcastProcessFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the broadcast job has the same parallelism as the connected job, but not forwarded
+        let dag = &dag_manager.job_graph().dag;
+        let broadcast_edges: Vec<&JobEdge> = dag
+            .raw_edges()
+            .iter()
+            .filter(|edge| {
+                let job_node = &dag[edge.source()];
+                job_node
+                    .stream_nodes
+                    .iter()
+                    .any(|x| x.operator_name == "BroadcastFlagMapFunction")
+            })
+            .map(|edge| &edge.weight)
+            .collect();
+        assert_eq!(broadcast_edges.len(), 1);
+        assert!(matches!(broadcast_edges[0], JobEdge::ReBalance));
+    }
+
+    #[test]
+    pub fn data_stream_partition_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        env.register_source(MyInputFormat::new())
+            .rebalance()
+            .flat_map(MyFlatMapFunction::new())
+            .partition_custom(MyPartitioner {}, MyKeySelectorFunction::new())
+            .flat_map(MyFlatMapFunction::new())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the operators after the partition functions are not chained or forwarded even if
+        // the parallelism is the same
+        let dag = &dag_manager.job_graph().dag;
+        assert_eq!(dag.node_count(), 3);
+        assert!(dag
+            .raw_edges()
+            .iter()
+            .all(|edge| matches!(edge.weight, JobEdge::ReBalance)));
+    }
+
+    struct MyPartitioner {}
+
+    impl Partitioner for MyPartitioner {
+        fn partition(&self, _key: &mut Record, num_partitions: u16) -> u16 {
+            num_partitions - 1
+        }
+    }
+
+    #[test]
+    pub fn data_stream_keyed_process_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .key_by(MyKeySelectorFunction::new())
+            .process(MyKeyedProcessFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the keyed records are sent to the task owning their key group
+        let dag = &dag_manager.job_graph().dag;
+        let keyed_edges: Vec<&JobEdge> = dag
+            .raw_edges()
+            .iter()
+            .filter(|edge| {
+                let job_node = &dag[edge.target()];
+                job_node
+                    .stream_nodes
+                    .iter()
+                    .any(|x| x.operator_type == OperatorType::KeyedProcess)
+            })
+            .map(|edge| &edge.weight)
+            .collect();
+        assert_eq!(keyed_edges.len(), 1);
+        assert!(matches!(keyed_edges[0], JobEdge::ReBalance));
+    }
+
+    #[test]
+    pub fn data_stream_error_policy_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let input_schema = Schema::new(vec![
+            Field::new("a", DataType::Binary),
+            Field::new("b", DataType::Int64),
+        ]);
+        let dead_letter_tag = OutputTag::new("dead_letter", input_schema);
+        let data_stream = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .error_policy(ErrorPolicy::DeadLetter(dead_letter_tag.clone()));
+        data_stream
+            .get_side_output(&dead_letter_tag)
+            .add_sink(MyOutputFormat::new(Properties::new()));
+        data_stream
+            .add_sink(MyOutputFormat::new(Properties::new()))
+            .error_policy(ErrorPolicy::SkipAndCount);
+
+        {
+            let stream_graph = env.stream_manager.stream_graph.borrow();
+            let stream_node = |operator_name: &str| {
+                stream_graph
+                    .dag
+                    .raw_nodes()
+                    .iter()
+                    .map(|x| &x.weight)
+                    .filter(|x| x.operator_name == operator_name)
+                    .last()
+                    .unwrap()
+                    .clone()
+            };
+            // the last sink is of the main output
+            assert_eq!(
+                stream_node("MyFlatMapFunction").error_policy,
+                ErrorPolicy::DeadLetter(dead_letter_tag.clone())
+            );
+            assert_eq!(
+                stream_node("MyOutputFormat").error_policy,
+                ErrorPolicy::SkipAndCount
+            );
+        }
+
+        // the dead letter must have the input schema, and the sources have no error policy
+        {
+            let mut stream_graph = env.stream_manager.stream_graph.borrow_mut();
+            let operator_id = |operator_name: &str| {
+                stream_graph
+                    .dag
+                    .raw_nodes()
+                    .iter()
+                    .find(|x| x.weight.operator_name == operator_name)
+                    .unwrap()
+                    .weight
+                    .id
+            };
+            let flat_map_id = operator_id("MyFlatMapFunction");
+            let source_id = operator_id("MyInputFormat");
+
+            let illegal_tag = OutputTag::new(
+                "illegal",
+                Schema::new(vec![Field::new("a", DataType::String)]),
+            );
+            assert!(matches!(
+                stream_graph.set_error_policy(flat_map_id, ErrorPolicy::DeadLetter(illegal_tag)),
+                Err(DagError::IllegalErrorPolicy(_, _))
+            ));
+            assert!(matches!(
+                stream_graph.set_error_policy(source_id, ErrorPolicy::SkipAndCount),
+                Err(DagError::IllegalErrorPolicy(_, _))
+            ));
+        }
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+    }
+
+    #[test]
+    pub fn data_stream_side_output_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let late_tag = OutputTag::new("late", Schema::new(vec![Field::new("a", DataType::String)]));
+        let data_stream = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .key_by(MyKeySelectorFunction::new())
+            .process(MyKeyedProcessFunction {});
+
+        data_stream
+            .get_side_output(&late_tag)
+            .add_sink(MyOutputFormat::new(Properties::new()));
+        data_stream
+            .flat_map(MyFlatMapFunction::new())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the main output and the side output are selected in two child jobs
+        let dag = &dag_manager.job_graph().dag;
+        let process_job = dag
+            .raw_nodes()
+            .iter()
+            .find(|x| {
+                x.weight
+                    .stream_nodes
+                    .iter()
+                    .any(|x| x.operator_type == OperatorType::KeyedProcess)
+            })
+            .unwrap();
+        assert_eq!(process_job.weight.child_job_ids.len(), 2);
+
+        for child_job_id in &process_job.weight.child_job_ids {
+            let child_job = &dag[dag_manager.job_graph().job_node_indies[child_job_id]];
+            assert_eq!(
+                child_job.stream_nodes[1].operator_name,
+                "SideOutputFlatMapFunction"
+            );
+        }
+    }
+
+    #[test]
+    pub fn data_stream_side_output_after_main_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let late_tag = OutputTag::new("late", Schema::new(vec![Field::new("a", DataType::String)]));
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .key_by(MyKeySelectorFunction::new())
+            .process(MyKeyedProcessFunction {})
+            .flat_map(MyFlatMapFunction::new())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        // the side output is added after the main child chained with the operator
+        {
+            let mut stream_graph = env.stream_manager.stream_graph.borrow_mut();
+            let process_id = stream_graph
+                .dag
+                .raw_nodes()
+                .iter()
+                .find(|x| x.weight.operator_type == OperatorType::KeyedProcess)
+                .unwrap()
+                .weight
+                .id;
+            let side_output_id = stream_graph.add_side_output(process_id, late_tag).unwrap();
+            let sink = StreamOperator::new_sink(
+                FunctionCreator::User,
+                Box::new(MyOutputFormat::new(Properties::new())),
+            );
+            stream_graph
+                .add_operator(sink, vec![side_output_id])
+                .unwrap();
+        }
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the main child is moved behind the tag filter of the main output
+        let dag = &dag_manager.job_graph().dag;
+        let process_job = dag
+            .raw_nodes()
+            .iter()
+            .find(|x| {
+                x.weight
+                    .stream_nodes
+                    .iter()
+                    .any(|x| x.operator_type == OperatorType::KeyedProcess)
+            })
+            .unwrap();
+        assert_eq!(process_job.weight.child_job_ids.len(), 2);
+
+        let mut child_operators: Vec<Vec<String>> = process_job
+            .weight
+            .child_job_ids
+            .iter()
+            .map(|child_job_id| {
+                let child_job = &dag[dag_manager.job_graph().job_node_indies[child_job_id]];
+                child_job.stream_nodes[1..]
+                    .iter()
+                    .map(|x| x.operator_name.clone())
+                    .collect()
+            })
+            .collect();
+        child_operators.sort();
+        let main_operators: Vec<&str> = vec![
+            "SideOutputFlatMapFunction",
+            "MyFlatMapFunction",
+            "MyOutputFormat",
+        ];
+        let side_operators: Vec<&str> = vec!["SideOutputFlatMapFunction", "MyOutputFormat"];
+        assert_eq!(child_operators, vec![main_operators, side_operators]);
+    }
+
+    #[test]
+    pub fn data_stream_dead_letter_not_read_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let input_schema = Schema::new(vec![
+            Field::new("a", DataType::Binary),
+            Field::new("b", DataType::Int64),
+        ]);
+        let dead_letter_tag = OutputTag::new("dead_letter", input_schema);
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .error_policy(ErrorPolicy::DeadLetter(dead_letter_tag))
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager = DagManager::try_from(env.stream_manager.stream_graph.borrow().deref());
+        assert!(matches!(
+            dag_manager,
+            Err(DagError::IllegalErrorPolicy(_, _))
+        ));
+    }
+
+    #[test]
+    pub fn data_stream_session_window_merge_test() {
+        let build = |reduce: MyReduceFunction| {
+            let mut env = StreamExecutionEnvironment::new();
+            env.register_source(MyInputFormat::new())
+                .flat_map(MyFlatMapFunction::new())
+                .assign_timestamps_and_watermarks(
+                    DefaultWatermarkStrategy::new()
+                        .for_bounded_out_of_orderness(Duration::from_secs(1))
+                        .for_timestamp_assigner(MyTimestampAssigner::new()),
+                )
+                .key_by(MyKeySelectorFunction::new())
+                .window(EventTimeSessionWindows::with_gap(Duration::from_secs(60)))
+                .reduce(reduce)
+                .add_sink(MyOutputFormat::new(Properties::new()));
+
+            let stream_graph = env.stream_manager.stream_graph.borrow();
+            DagManager::try_from(stream_graph.deref()).map(|_| ())
+        };
+
+        assert!(build(MyReduceFunction::with_merge()).is_ok());
+        assert!(matches!(
+            build(MyReduceFunction::new()),
+            Err(DagError::MergeNotSupported(_))
+        ));
+    }
+
+    #[test]
+    pub fn data_stream_parallelism_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .set_parallelism(6)
+            .slot_sharing_group("cpu")
+            .flat_map(MyFlatMapFunction::new())
+            .disable_chaining()
+            .add_sink(MyOutputFormat::new(Properties::new()))
+            .set_parallelism(2)
+            .slot_sharing_group("io");
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // source | flat_map | flat_map | sink
+        let dag = &dag_manager.job_graph().dag;
+        let mut jobs: Vec<(usize, u16, Option<String>)> = dag
+            .raw_nodes()
+            .iter()
+            .map(|x| {
+                let job_node = &x.weight;
+                let user_operators = job_node
+                    .stream_nodes
+                    .iter()
+                    .filter(|x| matches!(x.fn_creator, FunctionCreator::User))
+                    .count();
+                let slot_sharing_group = job_node.stream_nodes[0].slot_sharing_group.clone();
+                (user_operators, job_node.parallelism, slot_sharing_group)
+            })
+            .collect();
+        jobs.sort();
+        assert_eq!(
+            jobs,
+            vec![
+                (1, 2, Some("io".to_string())),
+                (1, 3, None),
+                (1, 6, Some("cpu".to_string())),
+                (1, 6, Some("cpu".to_string())),
+            ]
+        );
+
+        // each slot sharing group takes its own worker
+        let workers = dag_manager.physic_graph().alloc_by_instance(3);
+        let worker_groups: Vec<Vec<Option<String>>> = workers
+            .iter()
+            .map(|worker| {
+                let mut groups: Vec<Option<String>> = worker
+                    .task_instances
+                    .iter()
+                    .map(|x| x.stream_nodes[0].slot_sharing_group.clone())
+                    .collect();
+                groups.dedup();
+                groups
+            })
+            .collect();
+        assert_eq!(
+            worker_groups,
+            vec![
+                vec![None],
+                vec![Some("cpu".to_string())],
+                vec![Some("io".to_string())],
+            ]
+        );
+    }
+
+    #[test]
+    pub fn data_stream_region_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        // each task of the forward pipeline is a region
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        // the keyed pipeline is a region
+        env.register_source(MyInputFormat::new())
+            .key_by(MyKeySelectorFunction::new())
+            .process(MyKeyedProcessFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        let regions = PipelinedRegions::new(dag_manager.execution_graph());
+        assert_eq!(regions.num_regions(), 4);
+
+        let mut region_tasks: HashMap<usize, Vec<TaskId>> = HashMap::new();
+        for node in dag_manager.execution_graph().dag.raw_nodes() {
+            let task_id = node.weight.task_id;
+            region_tasks
+                .entry(regions.region(&task_id).unwrap())
+                .or_default()
+                .push(task_id);
+        }
+        let mut region_tasks: Vec<Vec<TaskId>> = region_tasks.into_values().collect();
+        region_tasks.sort_by_key(|x| x.len());
+        let mut keyed_tasks = region_tasks.pop().unwrap();
+        let keyed_half = keyed_tasks.split_off(keyed_tasks.len() / 2);
+
+        let mut worker_tasks = HashMap::new();
+        worker_tasks.insert("w0".to_string(), region_tasks[0].clone());
+        worker_tasks.insert(
+            "w1".to_string(),
+            [region_tasks[1].clone(), keyed_tasks].concat(),
+        );
+        worker_tasks.insert(
+            "w2".to_string(),
+            [region_tasks[2].clone(), keyed_half].concat(),
+        );
+
+        let failover = |failed: &str| {
+            let mut workers: Vec<String> = regions
+                .failover_workers(&worker_tasks, &[failed.to_string()])
+                .into_iter()
+                .collect();
+            workers.sort();
+            workers
+        };
+        assert_eq!(failover("w0"), vec!["w0"]);
+        // the keyed region fails with the worker, and the region of the other worker with it
+        assert_eq!(failover("w1"), vec!["w1", "w2"]);
+        assert_eq!(failover("w2"), vec!["w1", "w2"]);
+    }
+
+    #[test]
+    pub fn data_stream_iterate_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let feedback_tag = OutputTag::new(
+            "feedback",
+            Schema::new(vec![
+                Field::new("a", DataType::Binary),
+                Field::new("b", DataType::Int64),
+            ]),
+        );
+        env.register_source(MyInputFormat::new())
+            .iterate(Duration::from_secs(5), |head| {
+                let body = head.flat_map(MyFlatMapFunction::new());
+                let feedback = body.get_side_output(&feedback_tag).rebalance();
+                (feedback, body)
+            })
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        // the head and the tail of the same task number are in the same worker
+        let workers = dag_manager.physic_graph().alloc_by_instance(3);
+        for worker in &workers {
+            let task_numbers = |operator_name: &str| {
+                let mut task_numbers: Vec<u16> = worker
+                    .task_instances
+                    .iter()
+                    .filter(|x| {
+                        x.stream_nodes
+                            .iter()
+                            .any(|x| x.operator_name == operator_name)
+                    })
+                    .map(|x| x.task_id.task_number)
+                    .collect();
+                task_numbers.sort();
+                task_numbers
+            };
+
+            let head_tasks = task_numbers("IterationHeadInputFormat");
+            assert_eq!(head_tasks.len(), 1);
+            assert_eq!(head_tasks, task_numbers("IterationTailOutputFormat"));
+        }
+
+        // the feedback stream must have the parallelism of the head
+        let mut env = StreamExecutionEnvironment::new();
+        env.register_source(MyInputFormat::new())
+            .iterate(Duration::from_secs(5), |head| {
+                let body = head
+                    .key_by(MyKeySelectorFunction::new())
+                    .process(MyKeyedProcessFunction {});
+                (body.get_side_output(&feedback_tag), body)
+            })
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager = DagManager::try_from(env.stream_manager.stream_graph.borrow().deref());
+        assert!(matches!(
+            dag_manager,
+            Err(DagError::CoLocationParallelismConflict(_))
+        ));
+    }
+
+    #[test]
+    pub fn data_stream_bounded_test() {
+        let mut env = StreamExecutionEnvironment::new();
+        env.register_source(MyInputFormat::new())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        // the batch mode requires the bounded sources only
+        let stream_graph = env.stream_manager.stream_graph.borrow();
+        assert!(matches!(
+            stream_graph.check_bounded(),
+            Err(DagError::UnboundedSource(_))
+        ));
+    }
+
+    #[test]
+    pub fn data_stream_top_n_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .key_by(MyKeySelectorFunction::new())
+            .window(SlidingEventTimeWindows::new(
+                Duration::from_secs(60),
+                Duration::from_secs(20),
+                None,
+            ))
+            .top_n(TopN::new(3, "b").changelog())
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        let stream_nodes = &dag_manager.stream_graph().dag;
+        let top_n_node = stream_nodes
+            .raw_nodes()
+            .iter()
+            .find(|x| x.weight.operator_name == "TopNFlatMapFunction")
+            .unwrap();
+        assert_eq!(top_n_node.weight.operator_type, OperatorType::FlatMap);
+    }
+
+    #[test]
+    pub fn data_stream_interval_join_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let right = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new());
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .interval_join(right)
+            .where_key(MyKeySelectorFunction::new())
+            .equal_to(MyKeySelectorFunction::new())
+            .between(Duration::from_secs(10), Duration::from_secs(60))
+            .process(MyJoinFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        let stream_nodes = &dag_manager.stream_graph().dag;
+        let join_node = stream_nodes
+            .raw_nodes()
+            .iter()
+            .find(|x| x.weight.operator_type == OperatorType::KeyedProcess)
+            .unwrap();
+        assert_eq!(join_node.weight.operator_name, "MyJoinFunction");
+    }
+
+    #[test]
+    pub fn data_stream_window_join_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let right = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new());
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .join(right)
+            .where_key(MyKeySelectorFunction::new())
+            .equal_to(MyKeySelectorFunction::new())
+            .window(SlidingEventTimeWindows::new(
+                Duration::from_secs(60),
+                Duration::from_secs(20),
+                None,
+            ))
+            .join_type(JoinType::LeftOuter)
+            .process(MyJoinFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        let stream_nodes = &dag_manager.stream_graph().dag;
+        let join_node = stream_nodes
+            .raw_nodes()
+            .iter()
+            .find(|x| x.weight.operator_name == "MyJoinFunction")
+            .unwrap();
+        assert_eq!(join_node.weight.operator_type, OperatorType::FlatMap);
+        assert!(stream_nodes
+            .raw_nodes()
+            .iter()
+            .any(|x| x.weight.operator_type == OperatorType::Reduce));
+    }
+
+    #[test]
+    pub fn data_stream_temporal_join_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let table = env
+            .register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new());
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .temporal_join(table)
+            .where_key(MyKeySelectorFunction::new())
+            .equal_to(MyKeySelectorFunction::new())
+            .process(MyJoinFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        let stream_nodes = &dag_manager.stream_graph().dag;
+        let join_node = stream_nodes
+            .raw_nodes()
+            .iter()
+            .find(|x| x.weight.operator_type == OperatorType::KeyedProcess)
+            .unwrap();
+        assert_eq!(join_node.weight.operator_name, "MyJoinFunction");
+    }
+
+    #[test]
+    pub fn data_stream_keyed_connect_test() {
+        let mut env = StreamExecutionEnvironment::new();
+
+        let control = env.register_source(MyInputFormat::new());
+
+        env.register_source(MyInputFormat::new())
+            .flat_map(MyFlatMapFunction::new())
+            .connect_keyed(control)
+            .where_key(MyKeySelectorFunction::new())
+            .equal_to(MyKeySelectorFunction::new())
+            .process(MyKeyedCoProcessFunction {})
+            .add_sink(MyOutputFormat::new(Properties::new()));
+
+        let dag_manager =
+            DagManager::try_from(env.stream_manager.stream_graph.borrow().deref()).unwrap();
+        print_dag(&dag_manager);
+
+        let stream_nodes = &dag_manager.stream_graph().dag;
+        let co_process_node = stream_nodes
+            .raw_nodes()
+            .iter()
+            .find(|x| x.weight.operator_type == OperatorType::KeyedProcess)
+            .unwrap();
+        assert_eq!(
+            co_process_node.weight.operator_name,
+            "MyKeyedCoProcessFunction"
+        );
+    }
+
     fn print_dag(dag_manager: &DagManager) {
         {
             let dag = &dag_manager.stream_graph().dag;
@@ -526,11 +1295,17 @@ mod tests {
     }
 
     #[derive(Serialize, Deserialize, Debug)]
-    pub struct MyReduceFunction {}
+    pub struct MyReduceFunction {
+        merge: bool,
+    }
 
     impl MyReduceFunction {
         pub fn new() -> Self {
-            MyReduceFunction {}
+            MyReduceFunction { merge: false }
+        }
+
+        pub fn with_merge() -> Self {
+            MyReduceFunction { merge: true }
         }
     }
 
@@ -544,6 +1319,14 @@ mod tests {
             record.clone()
         }
 
+        fn merge(&self, _value: &mut Record, other: &mut Record) -> Record {
+            other.clone()
+        }
+
+        fn supports_merge(&self) -> bool {
+            self.merge
+        }
+
         async fn close(&mut self) -> core::Result<()> {
             Ok(())
         }
@@ -683,4 +1466,170 @@ mod tests {
             None
         }
     }
+
+    pub struct MyBroadcastProcessFunction {}
+
+    #[async_trait]
+    impl BroadcastProcessFunction for MyBroadcastProcessFunction {
+        async fn open(&mut self, _context: &Context) -> core::Result<()> {
+            Ok(())
+        }
+
+        async fn process_element(
+            &mut self,
+            _record: Record,
+            _context: &ReadOnlyBroadcastContext,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn process_broadcast_element(
+            &mut self,
+            _record: Record,
+            _context: &BroadcastContext,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn close(&mut self) -> core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, input_schema: FnSchema) -> FnSchema {
+            input_schema
+        }
+    }
+
+    impl NamedFunction for MyBroadcastProcessFunction {
+        fn name(&self) -> &str {
+            "MyBroadcastProcessFunction"
+        }
+    }
+
+    pub struct MyKeyedProcessFunction {}
+
+    #[async_trait]
+    impl KeyedProcessFunction for MyKeyedProcessFunction {
+        async fn open(&mut self, _context: &Context) -> core::Result<()> {
+            Ok(())
+        }
+
+        async fn process_element(
+            &mut self,
+            _record: Record,
+            _timer_service: &mut TimerService,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn on_timer(
+            &mut self,
+            _timestamp: u64,
+            _time_domain: TimeDomain,
+            _timer_service: &mut TimerService,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn close(&mut self) -> core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, input_schema: FnSchema) -> FnSchema {
+            input_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            2
+        }
+    }
+
+    impl NamedFunction for MyKeyedProcessFunction {
+        fn name(&self) -> &str {
+            "MyKeyedProcessFunction"
+        }
+    }
+
+    pub struct MyKeyedCoProcessFunction {}
+
+    #[async_trait]
+    impl KeyedCoProcessFunction for MyKeyedCoProcessFunction {
+        async fn open(&mut self, _context: &Context) -> core::Result<()> {
+            Ok(())
+        }
+
+        async fn process_left(
+            &mut self,
+            _record: Record,
+            _timer_service: &mut TimerService,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn process_right(
+            &mut self,
+            _record: Record,
+            _timer_service: &mut TimerService,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn on_timer(
+            &mut self,
+            _timestamp: u64,
+            _time_domain: TimeDomain,
+            _timer_service: &mut TimerService,
+        ) -> SendableElementStream {
+            unimplemented!()
+        }
+
+        async fn close(&mut self) -> core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, left_schema: FnSchema, _right_schema: FnSchema) -> FnSchema {
+            left_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            2
+        }
+    }
+
+    impl NamedFunction for MyKeyedCoProcessFunction {
+        fn name(&self) -> &str {
+            "MyKeyedCoProcessFunction"
+        }
+    }
+
+    pub struct MyJoinFunction {}
+
+    #[async_trait]
+    impl JoinFunction for MyJoinFunction {
+        async fn open(&mut self, _context: &Context) -> core::Result<()> {
+            Ok(())
+        }
+
+        fn join(&self, left: &mut Record, _right: &mut Record) -> Record {
+            left.clone()
+        }
+
+        async fn close(&mut self) -> core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, left_schema: FnSchema, _right_schema: FnSchema) -> FnSchema {
+            left_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            2
+        }
+    }
+
+    impl NamedFunction for MyJoinFunction {
+        fn name(&self) -> &str {
+            "MyJoinFunction"
+        }
+    }
 }
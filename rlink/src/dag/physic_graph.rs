@@ -1,5 +1,5 @@
 use std::borrow::BorrowMut;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::Index;
 
 use daggy::{Dag, EdgeIndex, NodeIndex, Walker};
@@ -21,6 +21,29 @@ impl ForwardTaskChain {
             .unwrap()
             .task_id
     }
+
+    /// The slot sharing group of the first task of the chain
+    fn slot_sharing_group(&self) -> Option<&str> {
+        self.tasks
+            .iter()
+            .min_by_key(|x| x.task_id.job_id.0)
+            .and_then(|x| x.stream_nodes.first())
+            .and_then(|x| x.slot_sharing_group.as_deref())
+    }
+
+    /// The (co-location group, task number) of the tasks of the chain
+    fn co_locations(&self) -> HashSet<(&str, u16)> {
+        self.tasks
+            .iter()
+            .flat_map(|task| {
+                task.stream_nodes.iter().filter_map(move |x| {
+                    x.co_location_group
+                        .as_deref()
+                        .map(|group| (group, task.task_id.task_number))
+                })
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -45,10 +68,36 @@ impl PhysicGraph {
             })
         }
 
-        let mut i = 0;
+        let mut slot_sharing_groups: BTreeMap<Option<&str>, Vec<&ForwardTaskChain>> =
+            BTreeMap::new();
         for (_first_job_id, forward_task_chain) in &self.task_groups {
             for chain in forward_task_chain {
-                let index = i % task_managers.len();
+                slot_sharing_groups
+                    .entry(chain.slot_sharing_group())
+                    .or_default()
+                    .push(chain);
+            }
+        }
+
+        // each group takes its own workers if there are enough, else all groups share them
+        let num_groups = slot_sharing_groups.len();
+        let isolated = num_groups > 1 && task_managers.len() >= num_groups;
+        let mut i = 0;
+        for (group_index, chains) in slot_sharing_groups.values().enumerate() {
+            let (offset, len) = if isolated {
+                let len = task_managers.len() / num_groups;
+                let offset = group_index * len;
+                if group_index == num_groups - 1 {
+                    (offset, task_managers.len() - offset)
+                } else {
+                    (offset, len)
+                }
+            } else {
+                (0, task_managers.len())
+            };
+
+            for chain in chains {
+                let index = offset + i % len;
                 i += 1;
                 let task_manager = &mut task_managers[index];
 
@@ -68,6 +117,7 @@ impl PhysicGraph {
 
     pub(crate) fn build(&mut self, execution_graph: &ExecutionGraph) {
         let chains = self.merge_forward_task(execution_graph);
+        let chains = self.merge_co_located_task(chains);
         for chain in chains {
             let task_id = chain.task_id();
             let task_groups = self.task_groups.entry(task_id.job_id).or_insert(vec![]);
@@ -75,6 +125,34 @@ impl PhysicGraph {
         }
     }
 
+    /// Merge the chains with the tasks of the same number in a co-location group, so they're
+    /// allocated to the same worker
+    fn merge_co_located_task(&self, chains: Vec<ForwardTaskChain>) -> Vec<ForwardTaskChain> {
+        let mut merged_chains: Vec<ForwardTaskChain> = Vec::new();
+        for chain in chains {
+            let co_locations: HashSet<(String, u16)> = chain
+                .co_locations()
+                .into_iter()
+                .map(|(group, task_number)| (group.to_string(), task_number))
+                .collect();
+
+            let (co_located, mut others): (Vec<ForwardTaskChain>, Vec<ForwardTaskChain>) =
+                merged_chains.into_iter().partition(|x| {
+                    x.co_locations().into_iter().any(|(group, task_number)| {
+                        co_locations.contains(&(group.to_string(), task_number))
+                    })
+                });
+
+            let mut tasks = chain.tasks;
+            for x in co_located {
+                tasks.extend(x.tasks);
+            }
+            others.push(ForwardTaskChain { tasks });
+            merged_chains = others;
+        }
+        merged_chains
+    }
+
     fn merge_forward_task(&mut self, execution_graph: &ExecutionGraph) -> Vec<ForwardTaskChain> {
         let execution_dag = &execution_graph.dag;
 
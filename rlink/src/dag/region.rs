@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dag::execution_graph::ExecutionGraph;
+use crate::dag::TaskId;
+
+/// The pipelined regions of the job, i.e. the connected components of the tasks exchanging
+/// the records. A failed region restores from the latest completed checkpoint on its own,
+/// since no record of it is consumed by the other regions
+#[derive(Clone, Debug)]
+pub(crate) struct PipelinedRegions {
+    /// the region index of each task
+    regions: HashMap<TaskId, usize>,
+    num_regions: usize,
+}
+
+impl PipelinedRegions {
+    pub fn new(execution_graph: &ExecutionGraph) -> Self {
+        let dag = &execution_graph.dag;
+
+        // union-find of the node indexes
+        let mut parents: Vec<usize> = (0..dag.node_count()).collect();
+        for edge in dag.raw_edges() {
+            let source = find(&mut parents, edge.source().index());
+            let target = find(&mut parents, edge.target().index());
+            if source != target {
+                parents[source] = target;
+            }
+        }
+
+        let mut region_indexes = HashMap::new();
+        let mut regions = HashMap::new();
+        for (index, node) in dag.raw_nodes().iter().enumerate() {
+            let root = find(&mut parents, index);
+            let num_regions = region_indexes.len();
+            let region = *region_indexes.entry(root).or_insert(num_regions);
+            regions.insert(node.weight.task_id, region);
+        }
+
+        PipelinedRegions {
+            regions,
+            num_regions: region_indexes.len(),
+        }
+    }
+
+    pub fn num_regions(&self) -> usize {
+        self.num_regions
+    }
+
+    pub fn region(&self, task_id: &TaskId) -> Option<usize> {
+        self.regions.get(task_id).cloned()
+    }
+
+    /// The workers to restart for the `failed_workers`, `worker_tasks` is the tasks of each
+    /// worker. A worker restarts all of its tasks, so the workers running any task of the
+    /// failed regions are restarted, and the regions of their tasks fail too
+    pub fn failover_workers(
+        &self,
+        worker_tasks: &HashMap<String, Vec<TaskId>>,
+        failed_workers: &[String],
+    ) -> HashSet<String> {
+        let mut workers: HashSet<String> = failed_workers.iter().cloned().collect();
+        let mut regions = HashSet::new();
+        loop {
+            let new_regions: HashSet<usize> = workers
+                .iter()
+                .filter_map(|worker| worker_tasks.get(worker))
+                .flat_map(|task_ids| task_ids.iter().filter_map(|x| self.region(x)))
+                .filter(|region| !regions.contains(region))
+                .collect();
+            if new_regions.is_empty() {
+                return workers;
+            }
+            regions.extend(new_regions);
+
+            for (worker, task_ids) in worker_tasks {
+                let failed = task_ids
+                    .iter()
+                    .filter_map(|x| self.region(x))
+                    .any(|region| regions.contains(&region));
+                if failed {
+                    workers.insert(worker.clone());
+                }
+            }
+        }
+    }
+}
+
+/// The root of `x` in the union-find, the path is halved on the way
+fn find(parents: &mut [usize], mut x: usize) -> usize {
+    while parents[x] != x {
+        parents[x] = parents[parents[x]];
+        x = parents[x];
+    }
+    x
+}
@@ -1,16 +1,23 @@
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Index;
+use std::time::Duration;
 
-use daggy::{Dag, EdgeIndex, NodeIndex};
+use daggy::{Dag, EdgeIndex, NodeIndex, Walker};
 
-use crate::core::element::FnSchema;
+use crate::core::element::{FnSchema, OutputTag};
+use crate::core::error_policy::ErrorPolicy;
 use crate::core::operator::{
     DefaultStreamOperator, FunctionCreator, StreamOperator, TStreamOperator, DEFAULT_PARALLELISM,
 };
 use crate::core::runtime::OperatorId;
 use crate::dag::{DagError, OperatorType};
+use crate::functions::flat_map::is_partition_function;
+use crate::functions::system::iteration::{
+    co_location_group, IterationHeadInputFormat, IterationTailOutputFormat,
+};
 use crate::functions::system::keyed_state_flat_map::KeyedStateFlatMapFunction;
+use crate::functions::system::side_output::SideOutputFlatMapFunction;
 use crate::functions::system::system_input_format::SystemInputFormat;
 use crate::functions::system::system_output_format::SystemOutputFormat;
 
@@ -26,6 +33,53 @@ pub struct StreamNode {
     pub(crate) operator_name: String,
     pub(crate) operator_type: OperatorType,
     pub(crate) fn_creator: FunctionCreator,
+
+    /// the stable id of the operator set by the user, the state of the operator is restored
+    /// from the savepoint by it
+    #[serde(default)]
+    pub(crate) uid: Option<String>,
+    /// how the operator is chained with the parent and the children in a job
+    #[serde(default)]
+    pub(crate) chaining: ChainingStrategy,
+    /// the tasks of the different slot sharing groups are allocated to the separate workers if
+    /// there are enough, `None` is the default group
+    #[serde(default)]
+    pub(crate) slot_sharing_group: Option<String>,
+    /// the tasks of the same number of the jobs in the co-location group are allocated to the
+    /// same worker, e.g. the head and the tail of an iteration
+    #[serde(default)]
+    pub(crate) co_location_group: Option<String>,
+    /// how the records failed by the function are handled
+    #[serde(default)]
+    pub(crate) error_policy: ErrorPolicy,
+}
+
+impl StreamNode {
+    /// The key of the operator in the savepoints, the `uid` or the operator id if absent
+    pub(crate) fn state_key(&self) -> String {
+        match &self.uid {
+            Some(uid) => uid.clone(),
+            None => format!("operator-{}", self.id.0),
+        }
+    }
+}
+
+/// How the operator is chained with its parent and children in a job, the chained operators
+/// are run in the same task and the records are passed without the network
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum ChainingStrategy {
+    /// chained with the parent and the children whenever possible
+    Always,
+    /// not chained with the parent, the children can be chained with it
+    Head,
+    /// never chained with the parent or the children
+    Never,
+}
+
+impl Default for ChainingStrategy {
+    fn default() -> Self {
+        ChainingStrategy::Always
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -59,6 +113,10 @@ pub(crate) struct RawStreamGraph {
 
     id_gen: OperatorId,
     operators: HashMap<OperatorId, (NodeIndex, StreamOperator)>,
+    /// the virtual sink shared by all children of the operator with side outputs
+    side_output_sinks: HashMap<OperatorId, OperatorId>,
+    /// the tags read by the side outputs of the operators
+    side_output_tags: HashSet<(OperatorId, String)>,
 
     pub(crate) sources: Vec<NodeIndex>,
     pub(crate) user_sources: Vec<NodeIndex>,
@@ -74,6 +132,8 @@ impl RawStreamGraph {
             stream_edges: Vec::new(),
             id_gen: OperatorId::default(),
             operators: HashMap::new(),
+            side_output_sinks: HashMap::new(),
+            side_output_tags: HashSet::new(),
             sources: Vec::new(),
             user_sources: Vec::new(),
             // sinks: Vec::new(),
@@ -114,6 +174,19 @@ impl RawStreamGraph {
         ))
     }
 
+    fn create_side_output_flat_map(
+        &mut self,
+        parallelism: u16,
+        output_tag: Option<OutputTag>,
+    ) -> StreamOperator {
+        let map_format = Box::new(SideOutputFlatMapFunction::new(output_tag));
+        StreamOperator::StreamFlatMap(DefaultStreamOperator::new(
+            parallelism,
+            FunctionCreator::System,
+            map_format,
+        ))
+    }
+
     fn create_virtual_source(&mut self, parallelism: u16) -> StreamOperator {
         let input_format = Box::new(SystemInputFormat::new());
         StreamOperator::StreamSource(DefaultStreamOperator::new(
@@ -157,6 +230,16 @@ impl RawStreamGraph {
             }
         };
 
+        // the slot sharing group is inherited from the parents if they're in the same group
+        let slot_sharing_group = parent_operator_ids
+            .iter()
+            .map(|x| {
+                let (p_node_index, _) = self.operators.get(x).unwrap();
+                self.dag.index(*p_node_index).slot_sharing_group.clone()
+            })
+            .reduce(|x, y| if x == y { x } else { None })
+            .unwrap_or_default();
+
         let stream_node = StreamNode {
             id: operator_id,
             parent_ids: parent_operator_ids.clone(),
@@ -167,6 +250,11 @@ impl RawStreamGraph {
             operator_name: operator.operator_name().to_string(),
             operator_type: OperatorType::from(&operator),
             fn_creator: operator.fn_creator(),
+            uid: None,
+            chaining: ChainingStrategy::default(),
+            slot_sharing_group,
+            co_location_group: None,
+            error_policy: ErrorPolicy::default(),
         };
 
         let node_index = self.dag.add_node(stream_node.clone());
@@ -204,6 +292,544 @@ impl RawStreamGraph {
         Ok(operator_id)
     }
 
+    /// All the user sources must be bounded in the `ExecutionMode::Batch`
+    pub fn check_bounded(&self) -> Result<(), DagError> {
+        for node_index in &self.user_sources {
+            let stream_node = self.dag.index(*node_index);
+            let (_, operator) = self.operators.get(&stream_node.id).unwrap();
+            if !operator.is_bounded() {
+                return Err(DagError::UnboundedSource(stream_node.operator_name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn output_schema(&self, operator_id: OperatorId) -> Result<FnSchema, DagError> {
+        let (node_index, _operator) = self
+            .operators
+            .get(&operator_id)
+            .ok_or(DagError::OperatorNotFound(operator_id))?;
+        Ok(self.dag[*node_index].output_schema.clone())
+    }
+
+    pub fn set_uid(&mut self, operator_id: OperatorId, uid: &str) -> Result<(), DagError> {
+        let duplicated =
+            self.dag.raw_nodes().iter().any(|node| {
+                node.weight.id != operator_id && node.weight.uid.as_deref() == Some(uid)
+            });
+        if duplicated {
+            return Err(DagError::DuplicateUid(uid.to_string()));
+        }
+
+        let (node_index, _operator) = self
+            .operators
+            .get(&operator_id)
+            .ok_or(DagError::OperatorNotFound(operator_id))?;
+        self.dag[*node_index].uid = Some(uid.to_string());
+        Ok(())
+    }
+
+    /// Set the parallelism of the operator instead of inheriting it from the parent.
+    ///
+    /// The operator is split from the job of its parent if they're chained, else the virtual
+    /// operators at the head of its job are set the same parallelism
+    pub fn set_parallelism(
+        &mut self,
+        operator_id: OperatorId,
+        parallelism: u16,
+    ) -> Result<(), DagError> {
+        let node_index = self.last_node_index(operator_id)?;
+        if self.dag[node_index].parallelism == parallelism {
+            return Ok(());
+        }
+
+        for node_index in self.isolate(node_index, parallelism)? {
+            self.dag[node_index].parallelism = parallelism;
+        }
+        Ok(())
+    }
+
+    /// Set how the operator is chained, the operator chained with a user operator before it is
+    /// split into a new job unless it's `ChainingStrategy::Always`
+    pub fn set_chaining(
+        &mut self,
+        operator_id: OperatorId,
+        chaining: ChainingStrategy,
+    ) -> Result<(), DagError> {
+        let node_index = self.last_node_index(operator_id)?;
+        if chaining != ChainingStrategy::Always && self.is_chained_with_user_operator(node_index) {
+            let parallelism = self.dag[node_index].parallelism;
+            self.split_chain(node_index, parallelism)?;
+        }
+
+        self.dag[node_index].chaining = chaining;
+        Ok(())
+    }
+
+    /// Set the slot sharing group of the operator and the operators after it, the operator is
+    /// split from the job of its parent if they're chained in another group
+    pub fn set_slot_sharing_group(
+        &mut self,
+        operator_id: OperatorId,
+        slot_sharing_group: &str,
+    ) -> Result<(), DagError> {
+        let node_index = self.last_node_index(operator_id)?;
+        let slot_sharing_group = Some(slot_sharing_group.to_string());
+
+        let node_indies = if self
+            .job_prefix(node_index)
+            .iter()
+            .any(|x| self.dag[*x].slot_sharing_group != slot_sharing_group)
+        {
+            let parallelism = self.dag[node_index].parallelism;
+            self.isolate(node_index, parallelism)?
+        } else {
+            vec![node_index]
+        };
+
+        for node_index in node_indies {
+            self.dag[node_index].slot_sharing_group = slot_sharing_group.clone();
+        }
+        Ok(())
+    }
+
+    /// The records of a dead letter are only dropped from the main output, so its tag must be
+    /// read by a side output of the operator
+    pub fn check_dead_letters(&self) -> Result<(), DagError> {
+        for (node_index, _operator) in self.operators.values() {
+            let stream_node = self.dag.index(*node_index);
+            if let ErrorPolicy::DeadLetter(output_tag) = &stream_node.error_policy {
+                let side_output = (stream_node.id, output_tag.name().to_string());
+                if !self.side_output_tags.contains(&side_output) {
+                    return Err(DagError::IllegalErrorPolicy(
+                        stream_node.id,
+                        format!(
+                            "the dead letter `{}` is not read by a side output",
+                            output_tag.name()
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The reduce operator of the merging windows must support merging the reduced values
+    pub fn check_merging_windows(&self) -> Result<(), DagError> {
+        for (node_index, operator) in self.operators.values() {
+            let reduce_operator = match operator {
+                StreamOperator::StreamReduce(reduce_operator) => reduce_operator,
+                _ => continue,
+            };
+            let stream_node = self.dag.index(*node_index);
+            let merging = stream_node.parent_ids.iter().any(|parent_id| {
+                match self.operators.get(parent_id) {
+                    Some((_, StreamOperator::StreamWindowAssigner(window_operator))) => {
+                        window_operator.operator_fn.is_merging()
+                    }
+                    _ => false,
+                }
+            });
+            if merging && !reduce_operator.operator_fn.supports_merge() {
+                return Err(DagError::MergeNotSupported(stream_node.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set how the records failed by the function of the operator are handled, see `ErrorPolicy`
+    pub fn set_error_policy(
+        &mut self,
+        operator_id: OperatorId,
+        error_policy: ErrorPolicy,
+    ) -> Result<(), DagError> {
+        let (node_index, _operator) = self
+            .operators
+            .get(&operator_id)
+            .ok_or(DagError::OperatorNotFound(operator_id))?;
+        let stream_node = &mut self.dag[*node_index];
+
+        let supported = match &error_policy {
+            ErrorPolicy::FailJob => true,
+            ErrorPolicy::SkipAndCount => [
+                OperatorType::FlatMap,
+                OperatorType::Filter,
+                OperatorType::KeyedProcess,
+                OperatorType::Sink,
+            ]
+            .contains(&stream_node.operator_type),
+            ErrorPolicy::DeadLetter(_) => [
+                OperatorType::FlatMap,
+                OperatorType::Filter,
+                OperatorType::KeyedProcess,
+            ]
+            .contains(&stream_node.operator_type),
+        };
+        if !supported {
+            return Err(DagError::IllegalErrorPolicy(
+                operator_id,
+                format!("unsupported by {:?}", stream_node.operator_type),
+            ));
+        }
+
+        if let ErrorPolicy::DeadLetter(output_tag) = &error_policy {
+            let input_schema = match &stream_node.input_schema {
+                FnSchema::Single(schema) | FnSchema::Tuple(schema, _) => Some(schema),
+                FnSchema::Empty => None,
+            };
+            if input_schema != Some(output_tag.schema()) {
+                return Err(DagError::IllegalErrorPolicy(
+                    operator_id,
+                    format!(
+                        "the dead letter `{}` must have the input schema of the operator",
+                        output_tag.name()
+                    ),
+                ));
+            }
+        }
+
+        stream_node.error_policy = error_policy;
+        Ok(())
+    }
+
+    /// The node of the operator, the job settings can only be changed before the children are
+    /// added
+    fn last_node_index(&self, operator_id: OperatorId) -> Result<NodeIndex, DagError> {
+        let (node_index, _operator) = self
+            .operators
+            .get(&operator_id)
+            .ok_or(DagError::OperatorNotFound(operator_id))?;
+
+        if self
+            .dag
+            .children(*node_index)
+            .iter(&self.dag)
+            .next()
+            .is_some()
+        {
+            return Err(DagError::OperatorChildrenAdded(operator_id));
+        }
+
+        Ok(*node_index)
+    }
+
+    /// The operator and the operators before it in the same job, the head of the job is the last
+    fn job_prefix(&self, node_index: NodeIndex) -> Vec<NodeIndex> {
+        let mut node_indies = vec![node_index];
+        let mut stream_node = self.dag.index(node_index);
+        while stream_node.operator_type != OperatorType::Source {
+            let (p_node_index, _) = self.operators.get(&stream_node.parent_ids[0]).unwrap();
+            node_indies.push(*p_node_index);
+            stream_node = self.dag.index(*p_node_index);
+        }
+        node_indies
+    }
+
+    fn is_chained_with_user_operator(&self, node_index: NodeIndex) -> bool {
+        self.job_prefix(node_index)[1..].iter().any(|x| {
+            let stream_node = self.dag.index(*x);
+            matches!(stream_node.fn_creator, FunctionCreator::User)
+                && stream_node.operator_type != OperatorType::WindowAssigner
+        })
+    }
+
+    /// The nodes of the job owned by the operator only, which take the settings of the
+    /// operator. They're the operator with the virtual source of its job if nothing else is
+    /// chained before it, or the window assigner chained with the reduce. Otherwise the
+    /// operator is split into a new job of the `parallelism`
+    fn isolate(
+        &mut self,
+        node_index: NodeIndex,
+        parallelism: u16,
+    ) -> Result<Vec<NodeIndex>, DagError> {
+        let job_prefix = self.job_prefix(node_index);
+        let owned = job_prefix[1..].iter().all(|x| {
+            let stream_node = self.dag.index(*x);
+            match stream_node.operator_type {
+                OperatorType::WindowAssigner => true,
+                // the virtual source not shared with the other child jobs of the virtual sinks
+                OperatorType::Source => {
+                    matches!(stream_node.fn_creator, FunctionCreator::System)
+                        && stream_node.parent_ids.iter().all(|p_operator_id| {
+                            let (p_node_index, _) = self.operators.get(p_operator_id).unwrap();
+                            self.dag.children(*p_node_index).iter(&self.dag).count() == 1
+                        })
+                }
+                _ => false,
+            }
+        });
+
+        if owned {
+            Ok(job_prefix)
+        } else {
+            let vir_node_index = self.split_chain(node_index, parallelism)?;
+            Ok(vec![node_index, vir_node_index])
+        }
+    }
+
+    /// Move the operator from the job of its parent to a new job of the `parallelism`, the
+    /// parent -> operator edge is replaced by parent -> virtual sink -> virtual source -> operator
+    fn split_chain(
+        &mut self,
+        node_index: NodeIndex,
+        parallelism: u16,
+    ) -> Result<NodeIndex, DagError> {
+        let stream_node = self.dag.index(node_index).clone();
+        let p_operator_id = stream_node.parent_ids[0];
+        let (p_node_index, _) = *self.operators.get(&p_operator_id).unwrap();
+        let p_parallelism = self.dag.index(p_node_index).parallelism;
+
+        let edge_index = self.dag.find_edge(p_node_index, node_index).unwrap();
+        self.dag.remove_edge(edge_index);
+        // the last edge is moved to the index of the removed one
+        self.stream_edges.pop();
+
+        let vir_sink = self.create_virtual_sink(p_parallelism);
+        let vir_operator_id = self.add_operator0(vir_sink, vec![p_operator_id], p_parallelism)?;
+
+        let vir_source = self.create_virtual_source(parallelism);
+        let vir_operator_id = self.add_operator0(vir_source, vec![vir_operator_id], parallelism)?;
+        let (vir_node_index, _) = *self.operators.get(&vir_operator_id).unwrap();
+
+        let stream_edge = StreamEdge {
+            edge_id: format!("{:?}->{:?}", vir_operator_id.0, stream_node.id.0),
+            source_id: vir_operator_id,
+            target_id: stream_node.id,
+        };
+        let edge_index = self
+            .dag
+            .add_edge(vir_node_index, node_index, stream_edge)
+            .map_err(|_e| DagError::WouldCycle)?;
+        self.stream_edges.push(edge_index);
+
+        self.dag[node_index].parent_ids = vec![vir_operator_id];
+        Ok(vir_node_index)
+    }
+
+    /// Move the edge `from` -> `child` to `to` -> `child`, the parent of the child is replaced
+    fn move_edge(
+        &mut self,
+        from: NodeIndex,
+        child: NodeIndex,
+        to: NodeIndex,
+    ) -> Result<(), DagError> {
+        let edge_index = self.dag.find_edge(from, child).unwrap();
+        self.dag.remove_edge(edge_index);
+        // the last edge is moved to the index of the removed one
+        self.stream_edges.pop();
+
+        let from_id = self.dag.index(from).id;
+        let to_id = self.dag.index(to).id;
+        let child_id = self.dag.index(child).id;
+        let stream_edge = StreamEdge {
+            edge_id: format!("{:?}->{:?}", to_id.0, child_id.0),
+            source_id: to_id,
+            target_id: child_id,
+        };
+        let edge_index = self
+            .dag
+            .add_edge(to, child, stream_edge)
+            .map_err(|_e| DagError::WouldCycle)?;
+        self.stream_edges.push(edge_index);
+
+        for parent_id in self.dag[child].parent_ids.iter_mut() {
+            if *parent_id == from_id {
+                *parent_id = to_id;
+            }
+        }
+        Ok(())
+    }
+
+    /// Split the records of the `output_tag` from the output of the operator.
+    ///
+    /// All children of an operator with side outputs are fed by a shared virtual sink, which
+    /// sends every record to all child jobs, and each child job selects its own records by the
+    /// `SideOutputFlatMapFunction`
+    pub fn add_side_output(
+        &mut self,
+        p_operator_id: OperatorId,
+        output_tag: OutputTag,
+    ) -> Result<OperatorId, DagError> {
+        self.move_main_children(p_operator_id)?;
+        self.side_output_tags
+            .insert((p_operator_id, output_tag.name().to_string()));
+
+        let (vir_operator_id, parallelism) = self.add_side_output_source(p_operator_id)?;
+        let side_output_map = self.create_side_output_flat_map(parallelism, Some(output_tag));
+        self.add_operator0(side_output_map, vec![vir_operator_id], parallelism)
+    }
+
+    /// The children added before the first side output read all records of the operator, they're
+    /// moved behind the main output so that the tag filter is always on the main output
+    fn move_main_children(&mut self, p_operator_id: OperatorId) -> Result<(), DagError> {
+        if self.side_output_sinks.contains_key(&p_operator_id) {
+            return Ok(());
+        }
+
+        let (p_node_index, _) = *self
+            .operators
+            .get(&p_operator_id)
+            .ok_or(DagError::OperatorNotFound(p_operator_id))?;
+        let main_children: Vec<NodeIndex> = self
+            .dag
+            .children(p_node_index)
+            .iter(&self.dag)
+            .map(|(_edge_index, node_index)| node_index)
+            .collect();
+        if main_children.is_empty() {
+            return Ok(());
+        }
+
+        if self.is_reduce_parent(p_operator_id) {
+            // the child of a reduce is a virtual sink and the output is read from the state by
+            // the virtual flat map of the child job, so the virtual sink is shared and the tag
+            // filter is chained after the virtual flat map
+            let vir_sink_index = main_children[0];
+            let vir_sink_id = self.dag.index(vir_sink_index).id;
+            self.side_output_sinks.insert(p_operator_id, vir_sink_id);
+
+            let (_, vir_source_index) = self
+                .dag
+                .children(vir_sink_index)
+                .walk_next(&self.dag)
+                .unwrap();
+            let (_, vir_map_index) = self
+                .dag
+                .children(vir_source_index)
+                .walk_next(&self.dag)
+                .unwrap();
+            let vir_map_children: Vec<NodeIndex> = self
+                .dag
+                .children(vir_map_index)
+                .iter(&self.dag)
+                .map(|(_edge_index, node_index)| node_index)
+                .collect();
+
+            let vir_map = self.dag.index(vir_map_index);
+            let (vir_map_id, parallelism) = (vir_map.id, vir_map.parallelism);
+            let main_output_map = self.create_side_output_flat_map(parallelism, None);
+            let main_output_id =
+                self.add_operator0(main_output_map, vec![vir_map_id], parallelism)?;
+            let (main_output_index, _) = *self.operators.get(&main_output_id).unwrap();
+            for child in vir_map_children {
+                self.move_edge(vir_map_index, child, main_output_index)?;
+            }
+        } else {
+            let (vir_operator_id, parallelism) = self.add_side_output_source(p_operator_id)?;
+            let main_output_map = self.create_side_output_flat_map(parallelism, None);
+            let main_output_id =
+                self.add_operator0(main_output_map, vec![vir_operator_id], parallelism)?;
+            let (main_output_index, _) = *self.operators.get(&main_output_id).unwrap();
+            for child in main_children {
+                self.move_edge(p_node_index, child, main_output_index)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create a child job reading the output of the operator with side outputs
+    fn add_side_output_source(
+        &mut self,
+        p_operator_id: OperatorId,
+    ) -> Result<(OperatorId, u16), DagError> {
+        let (p_node_index, _) = self
+            .operators
+            .get(&p_operator_id)
+            .ok_or(DagError::OperatorNotFound(p_operator_id))?;
+        let p_parallelism = self.dag.index(*p_node_index).parallelism;
+
+        let vir_sink_id = match self.side_output_sinks.get(&p_operator_id) {
+            Some(vir_sink_id) => *vir_sink_id,
+            None => {
+                let vir_sink = self.create_virtual_sink(p_parallelism);
+                let vir_sink_id =
+                    self.add_operator0(vir_sink, vec![p_operator_id], p_parallelism)?;
+                self.side_output_sinks.insert(p_operator_id, vir_sink_id);
+                vir_sink_id
+            }
+        };
+
+        // all child jobs have the same parallelism as required by the shared virtual sink
+        let vir_source = self.create_virtual_source(p_parallelism);
+        let vir_operator_id = self.add_operator0(vir_source, vec![vir_sink_id], p_parallelism)?;
+
+        let vir_operator_id = if self.is_reduce_parent(p_operator_id) {
+            let vir_map = self.create_virtual_flat_map(p_parallelism);
+            self.add_operator0(vir_map, vec![vir_operator_id], p_parallelism)?
+        } else {
+            vir_operator_id
+        };
+
+        Ok((vir_operator_id, p_parallelism))
+    }
+
+    /// Start an iteration on the output of the operator, the head of the iteration is a virtual
+    /// source reading the output and the feedback records. Returns the head and the iteration id
+    pub fn add_iteration_head(
+        &mut self,
+        p_operator_id: OperatorId,
+        max_wait: Duration,
+    ) -> Result<(OperatorId, u32), DagError> {
+        let p_operator_id = self.main_output(p_operator_id)?;
+        let (p_node_index, _) = self
+            .operators
+            .get(&p_operator_id)
+            .ok_or(DagError::OperatorNotFound(p_operator_id))?;
+        let p_parallelism = self.dag.index(*p_node_index).parallelism;
+
+        let vir_sink = self.create_virtual_sink(p_parallelism);
+        let vir_sink_id = self.add_operator0(vir_sink, vec![p_operator_id], p_parallelism)?;
+        let iteration_id = vir_sink_id.0;
+
+        let head_format = Box::new(IterationHeadInputFormat::new(iteration_id, max_wait));
+        let head = StreamOperator::StreamSource(DefaultStreamOperator::new(
+            p_parallelism,
+            FunctionCreator::System,
+            head_format,
+        ));
+        let head_id = self.add_operator0(head, vec![vir_sink_id], p_parallelism)?;
+        let (head_node_index, _) = self.operators.get(&head_id).unwrap();
+        self.dag[*head_node_index].co_location_group = Some(co_location_group(iteration_id));
+
+        let head_id = if self.is_reduce_parent(p_operator_id) {
+            let vir_map = self.create_virtual_flat_map(p_parallelism);
+            self.add_operator0(vir_map, vec![head_id], p_parallelism)?
+        } else {
+            head_id
+        };
+
+        Ok((head_id, iteration_id))
+    }
+
+    /// Close the iteration by the feedback stream of the operator, the records are sent back
+    /// to the iteration head by the tail sink
+    pub fn add_iteration_tail(
+        &mut self,
+        p_operator_id: OperatorId,
+        iteration_id: u32,
+    ) -> Result<OperatorId, DagError> {
+        let tail_format = Box::new(IterationTailOutputFormat::new(iteration_id));
+        let tail = StreamOperator::new_sink(FunctionCreator::User, tail_format);
+        let tail_id = self.add_operator(tail, vec![p_operator_id])?;
+
+        let (tail_node_index, _) = self.operators.get(&tail_id).unwrap();
+        self.dag[*tail_node_index].co_location_group = Some(co_location_group(iteration_id));
+        Ok(tail_id)
+    }
+
+    /// The operator emitting the main output of the operator, the main output of an operator
+    /// with side outputs is selected in a child job like a side output
+    fn main_output(&mut self, p_operator_id: OperatorId) -> Result<OperatorId, DagError> {
+        if !self.side_output_sinks.contains_key(&p_operator_id) {
+            return Ok(p_operator_id);
+        }
+
+        let (vir_operator_id, parallelism) = self.add_side_output_source(p_operator_id)?;
+        let main_output_map = self.create_side_output_flat_map(parallelism, None);
+        self.add_operator0(main_output_map, vec![vir_operator_id], parallelism)
+    }
+
     pub fn add_operator(
         &mut self,
         operator: StreamOperator,
@@ -219,15 +845,21 @@ impl RawStreamGraph {
                 self.add_operator0(operator, parent_operator_ids, parallelism)
             }
         } else if parent_operator_ids.len() == 1 {
-            let p_operator_id = parent_operator_ids[0];
+            let p_operator_id = self.main_output(parent_operator_ids[0])?;
+            let parent_operator_ids = vec![p_operator_id];
             let (p_node_index, _) = self.operators.get(&p_operator_id).unwrap();
             let p_stream_node = self.dag.index(*p_node_index);
 
             let p_parallelism = p_stream_node.parallelism;
             let p_operator_type = p_stream_node.operator_type;
+            let p_chaining = p_stream_node.chaining;
+
+            let p_partitioned = is_partition_function(p_stream_node.operator_name.as_str());
 
             let pipeline =
-                self.is_pipeline(operator_type, parallelism, p_operator_type, p_parallelism)?;
+                self.is_pipeline(operator_type, parallelism, p_operator_type, p_parallelism)?
+                    && p_chaining != ChainingStrategy::Never
+                    && !p_partitioned;
             if pipeline {
                 // tow types of parallelism inherit
                 // 1. Forward:  source->map
@@ -261,6 +893,7 @@ impl RawStreamGraph {
             let mut p_reduce_operator_id = None;
             let mut left_parent_parallelism = 0;
             for p_operator_id in parent_operator_ids {
+                let p_operator_id = self.main_output(p_operator_id)?;
                 let (p_node_index, _) = self.operators.get(&p_operator_id).unwrap();
                 let p_stream_node = self.dag.index(*p_node_index);
 
@@ -357,6 +990,19 @@ impl RawStreamGraph {
     ) -> Result<bool, DagError> {
         match parent_operator_type {
             OperatorType::Source => match operator_type {
+                OperatorType::FlatMap
+                | OperatorType::Filter
+                | OperatorType::WatermarkAssigner
+                | OperatorType::KeyBy
+                | OperatorType::KeyedProcess
+                | OperatorType::Sink => Ok(true),
+                OperatorType::Source => Err(DagError::SourceNotAtStarting),
+                _ => Ok(false),
+            },
+            OperatorType::FlatMap
+            | OperatorType::Filter
+            | OperatorType::WatermarkAssigner
+            | OperatorType::KeyedProcess => match operator_type {
                 OperatorType::FlatMap
                 | OperatorType::Filter
                 | OperatorType::WatermarkAssigner
@@ -365,17 +1011,6 @@ impl RawStreamGraph {
                 OperatorType::Source => Err(DagError::SourceNotAtStarting),
                 _ => Ok(false),
             },
-            OperatorType::FlatMap | OperatorType::Filter | OperatorType::WatermarkAssigner => {
-                match operator_type {
-                    OperatorType::FlatMap
-                    | OperatorType::Filter
-                    | OperatorType::WatermarkAssigner
-                    | OperatorType::KeyBy
-                    | OperatorType::Sink => Ok(true),
-                    OperatorType::Source => Err(DagError::SourceNotAtStarting),
-                    _ => Ok(false),
-                }
-            }
             OperatorType::CoProcess => match operator_type {
                 OperatorType::KeyBy => Ok(true),
                 OperatorType::Source => Err(DagError::SourceNotAtStarting),
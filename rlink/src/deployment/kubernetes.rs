@@ -5,16 +5,19 @@ use kube::{
     api::{Api, DeleteParams, ListParams, PostParams},
     Client,
 };
-use serde_json::json;
+use serde_json::{json, Value};
 
 use crate::core::runtime::ClusterDescriptor;
 use crate::core::{cluster::TaskResourceInfo, env::StreamApp};
 use crate::deployment::TResourceManager;
 use crate::runtime::context::Context;
+use crate::utils::pod_template::{apply_pod_template, load_pod_template};
 
 pub(crate) struct KubernetesResourceManager {
     context: Arc<Context>,
     cluster_descriptor: Option<ClusterDescriptor>,
+    /// the template of the worker pods
+    pod_template: Option<Value>,
 }
 
 impl KubernetesResourceManager {
@@ -22,19 +25,33 @@ impl KubernetesResourceManager {
         KubernetesResourceManager {
             context,
             cluster_descriptor: None,
+            pod_template: None,
         }
     }
 }
 
 #[async_trait]
 impl TResourceManager for KubernetesResourceManager {
-    fn prepare(&mut self, _context: &Context, job_descriptor: &ClusterDescriptor) {
+    fn prepare(
+        &mut self,
+        _context: &Context,
+        job_descriptor: &ClusterDescriptor,
+    ) -> anyhow::Result<()> {
         self.cluster_descriptor = Some(job_descriptor.clone());
+
+        let worker_pod_template = self.context.worker_pod_template.as_str();
+        if !worker_pod_template.is_empty() {
+            let pod_template = load_pod_template(worker_pod_template)?;
+            info!("worker pod template loaded from {}", worker_pod_template);
+            self.pod_template = Some(pod_template);
+        }
+        Ok(())
     }
 
     async fn worker_allocate<S>(
         &self,
         _stream_app_clone: &S,
+        task_manager_ids: &[String],
     ) -> anyhow::Result<Vec<TaskResourceInfo>>
     where
         S: StreamApp + 'static,
@@ -56,6 +73,9 @@ impl TResourceManager for KubernetesResourceManager {
         let coordinator_address = coordinator_manager.web_address.as_str();
 
         for task_manager_descriptor in &cluster_descriptor.worker_managers {
+            if !task_manager_ids.contains(&task_manager_descriptor.task_manager_id) {
+                continue;
+            }
             let task_manager_id = task_manager_descriptor.task_manager_id.clone();
             let task_manager_name = format!(
                 "{}-{}",
@@ -71,6 +91,7 @@ impl TResourceManager for KubernetesResourceManager {
                 job_deploy_id.as_str(),
                 image_path,
                 limits,
+                self.pod_template.as_ref(),
             )
             .await;
             match pod_uid {
@@ -127,10 +148,11 @@ async fn allocate_worker(
     job_deploy_id: &str,
     image_path: &str,
     limits: &ContainerLimits,
+    pod_template: Option<&Value>,
 ) -> anyhow::Result<String> {
     let client = Client::try_default().await?;
     let pods: Api<Pod> = Api::namespaced(client, namespace);
-    let p: Pod = serde_json::from_value(json!(
+    let pod = json!(
         {
             "apiVersion": "v1",
             "kind": "Pod",
@@ -171,7 +193,12 @@ async fn allocate_worker(
                 "restartPolicy":"OnFailure"
             }
         }
-    ))?;
+    );
+    let pod = match pod_template {
+        Some(pod_template) => apply_pod_template(pod_template, pod),
+        None => pod,
+    };
+    let p: Pod = serde_json::from_value(pod)?;
 
     let pp = PostParams::default();
     let mut uid = String::new();
@@ -205,7 +232,10 @@ async fn stop_worker(namespace: &str, task_ids: Vec<String>) -> anyhow::Result<(
     Ok(())
 }
 
-async fn get_job_deploy_id(namespace: &str, cluster_name: &str) -> anyhow::Result<String> {
+pub(crate) async fn get_job_deploy_id(
+    namespace: &str,
+    cluster_name: &str,
+) -> anyhow::Result<String> {
     info!(
         "get application {} deploy id on namespace :{}",
         cluster_name, namespace
@@ -228,6 +258,6 @@ async fn get_job_deploy_id(namespace: &str, cluster_name: &str) -> anyhow::Resul
     Ok(uid)
 }
 
-fn parse_name(name: &str) -> String {
+pub(crate) fn parse_name(name: &str) -> String {
     return name.replace("_", "-");
 }
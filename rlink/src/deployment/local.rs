@@ -25,16 +25,28 @@ impl LocalResourceManager {
 
 #[async_trait]
 impl TResourceManager for LocalResourceManager {
-    fn prepare(&mut self, _context: &Context, cluster_descriptor: &ClusterDescriptor) {
+    fn prepare(
+        &mut self,
+        _context: &Context,
+        cluster_descriptor: &ClusterDescriptor,
+    ) -> anyhow::Result<()> {
         self.cluster_descriptor = Some(cluster_descriptor.clone());
+        Ok(())
     }
 
-    async fn worker_allocate<S>(&self, stream_app: &S) -> anyhow::Result<Vec<TaskResourceInfo>>
+    async fn worker_allocate<S>(
+        &self,
+        stream_app: &S,
+        task_manager_ids: &[String],
+    ) -> anyhow::Result<Vec<TaskResourceInfo>>
     where
         S: StreamApp + 'static,
     {
         let cluster_descriptor = self.cluster_descriptor.as_ref().unwrap();
         for task_manager_descriptor in &cluster_descriptor.worker_managers {
+            if !task_manager_ids.contains(&task_manager_descriptor.task_manager_id) {
+                continue;
+            }
             let resource = Resource::new(
                 cluster_descriptor.coordinator_manager.memory_mb,
                 cluster_descriptor.coordinator_manager.v_cores,
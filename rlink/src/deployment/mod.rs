@@ -28,11 +28,20 @@ impl Resource {
 
 #[async_trait]
 pub(crate) trait TResourceManager {
-    fn prepare(&mut self, context: &Context, job_descriptor: &ClusterDescriptor);
+    /// Prepare the resources of the job, e.g. load the worker pod template of the kubernetes
+    fn prepare(
+        &mut self,
+        context: &Context,
+        job_descriptor: &ClusterDescriptor,
+    ) -> anyhow::Result<()>;
 
-    /// worker resource allocate
+    /// worker resource allocate, only the workers of the `task_manager_ids` are allocated
     /// Return a resource location.
-    async fn worker_allocate<S>(&self, stream_app: &S) -> anyhow::Result<Vec<TaskResourceInfo>>
+    async fn worker_allocate<S>(
+        &self,
+        stream_app: &S,
+        task_manager_ids: &[String],
+    ) -> anyhow::Result<Vec<TaskResourceInfo>>
     where
         S: StreamApp + 'static;
 
@@ -67,7 +76,11 @@ impl ResourceManager {
 
 #[async_trait]
 impl TResourceManager for ResourceManager {
-    fn prepare(&mut self, context: &Context, job_descriptor: &ClusterDescriptor) {
+    fn prepare(
+        &mut self,
+        context: &Context,
+        job_descriptor: &ClusterDescriptor,
+    ) -> anyhow::Result<()> {
         match self {
             ResourceManager::LocalResourceManager(rm) => rm.prepare(context, job_descriptor),
             ResourceManager::StandaloneResourceManager(rm) => rm.prepare(context, job_descriptor),
@@ -76,15 +89,27 @@ impl TResourceManager for ResourceManager {
         }
     }
 
-    async fn worker_allocate<S>(&self, stream_app: &S) -> anyhow::Result<Vec<TaskResourceInfo>>
+    async fn worker_allocate<S>(
+        &self,
+        stream_app: &S,
+        task_manager_ids: &[String],
+    ) -> anyhow::Result<Vec<TaskResourceInfo>>
     where
         S: StreamApp + 'static,
     {
         match self {
-            ResourceManager::LocalResourceManager(rm) => rm.worker_allocate(stream_app).await,
-            ResourceManager::StandaloneResourceManager(rm) => rm.worker_allocate(stream_app).await,
-            ResourceManager::YarnResourceManager(rm) => rm.worker_allocate(stream_app).await,
-            ResourceManager::KubernetesResourceManager(rm) => rm.worker_allocate(stream_app).await,
+            ResourceManager::LocalResourceManager(rm) => {
+                rm.worker_allocate(stream_app, task_manager_ids).await
+            }
+            ResourceManager::StandaloneResourceManager(rm) => {
+                rm.worker_allocate(stream_app, task_manager_ids).await
+            }
+            ResourceManager::YarnResourceManager(rm) => {
+                rm.worker_allocate(stream_app, task_manager_ids).await
+            }
+            ResourceManager::KubernetesResourceManager(rm) => {
+                rm.worker_allocate(stream_app, task_manager_ids).await
+            }
         }
     }
 
@@ -26,13 +26,19 @@ impl StandaloneResourceManager {
 
 #[async_trait]
 impl TResourceManager for StandaloneResourceManager {
-    fn prepare(&mut self, _context: &Context, cluster_descriptor: &ClusterDescriptor) {
+    fn prepare(
+        &mut self,
+        _context: &Context,
+        cluster_descriptor: &ClusterDescriptor,
+    ) -> anyhow::Result<()> {
         self.cluster_descriptor = Some(cluster_descriptor.clone());
+        Ok(())
     }
 
     async fn worker_allocate<S>(
         &self,
         _stream_app_clone: &S,
+        task_manager_ids: &[String],
     ) -> anyhow::Result<Vec<TaskResourceInfo>>
     where
         S: StreamApp + 'static,
@@ -49,6 +55,9 @@ impl TResourceManager for StandaloneResourceManager {
         let application_id = self.context.application_id.as_str();
         let mut task_args = Vec::new();
         for task_manager_descriptor in &cluster_descriptor.worker_managers {
+            if !task_manager_ids.contains(&task_manager_descriptor.task_manager_id) {
+                continue;
+            }
             let resource = Resource::new(
                 cluster_descriptor.coordinator_manager.memory_mb,
                 cluster_descriptor.coordinator_manager.v_cores,
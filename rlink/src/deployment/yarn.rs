@@ -30,13 +30,22 @@ impl YarnResourceManager {
 
 #[async_trait]
 impl TResourceManager for YarnResourceManager {
-    fn prepare(&mut self, context: &Context, job_descriptor: &ClusterDescriptor) {
+    fn prepare(
+        &mut self,
+        context: &Context,
+        job_descriptor: &ClusterDescriptor,
+    ) -> anyhow::Result<()> {
         self.cluster_descriptor = Some(job_descriptor.clone());
 
         self.yarn_command = Some(YarnCliCommand::new(&context, job_descriptor));
+        Ok(())
     }
 
-    async fn worker_allocate<S>(&self, _stream_app: &S) -> anyhow::Result<Vec<TaskResourceInfo>>
+    async fn worker_allocate<S>(
+        &self,
+        _stream_app: &S,
+        task_manager_ids: &[String],
+    ) -> anyhow::Result<Vec<TaskResourceInfo>>
     where
         S: StreamApp + 'static,
     {
@@ -44,6 +53,9 @@ impl TResourceManager for YarnResourceManager {
 
         let mut task_args = Vec::new();
         for task_manager_descriptor in &cluster_descriptor.worker_managers {
+            if !task_manager_ids.contains(&task_manager_descriptor.task_manager_id) {
+                continue;
+            }
             let mut args = HashMap::new();
             args.insert(
                 "cluster_mode".to_string(),
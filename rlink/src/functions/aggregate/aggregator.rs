@@ -0,0 +1,815 @@
+use std::fmt::Debug;
+
+use crate::core::data_types::{DataType, Field, Schema};
+use crate::core::element::{BufferReader, BufferWriter};
+use crate::functions::aggregate::hyper_log_log::{
+    estimate, get_hyper_log_log_capacity, HyperLogLog,
+};
+use crate::functions::column_locate::{ColumnLocate, ColumnLocateBuilder};
+use crate::functions::percentile::{HdrHistogram, QuantileSketch, TDigest};
+use crate::functions::reduce::BasicAggType;
+
+/// The precision of `distinct_count`, about 1.6% standard error with 4KB registers
+pub const DEFAULT_DISTINCT_COUNT_PRECISION: u8 = 12;
+
+pub fn count() -> AggregatorDescriptor {
+    AggregatorDescriptor::Count
+}
+
+pub fn sum<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::Sum(column.build())
+}
+
+pub fn max<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::Max(column.build())
+}
+
+pub fn min<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::Min(column.build())
+}
+
+pub fn avg<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::Avg(column.build())
+}
+
+/// The approximate distinct count estimated by HyperLogLog
+pub fn distinct_count<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::DistinctCount(column.build(), DEFAULT_DISTINCT_COUNT_PRECISION)
+}
+
+/// The approximate distinct count estimated by HyperLogLog of `2^precision` registers
+pub fn distinct_count_with_precision<T: ColumnLocateBuilder>(
+    column: T,
+    precision: u8,
+) -> AggregatorDescriptor {
+    // check the precision when the job is built
+    get_hyper_log_log_capacity(precision);
+    AggregatorDescriptor::DistinctCount(column.build(), precision)
+}
+
+/// The value of the record with the smallest timestamp, the earliest arrived one of the ties
+pub fn first_value<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::FirstValue(column.build())
+}
+
+/// The value of the record with the largest timestamp, the latest arrived one of the ties
+pub fn last_value<T: ColumnLocateBuilder>(column: T) -> AggregatorDescriptor {
+    AggregatorDescriptor::LastValue(column.build())
+}
+
+/// The t-digest of a numeric field as `Binary`, see `TDigest::from_bytes`
+pub fn t_digest<T: ColumnLocateBuilder>(column: T, compression: f64) -> AggregatorDescriptor {
+    AggregatorDescriptor::TDigest(column.build(), TDigest::new(compression), None)
+}
+
+/// The quantile in [0, 1] of a numeric field as `Float64` estimated by the t-digest
+pub fn t_digest_quantile<T: ColumnLocateBuilder>(
+    column: T,
+    quantile: f64,
+    compression: f64,
+) -> AggregatorDescriptor {
+    AggregatorDescriptor::TDigest(column.build(), TDigest::new(compression), Some(quantile))
+}
+
+/// The HDR histogram of a numeric field as `Binary`, see `HdrHistogram::from_bytes`. The
+/// counts of the histogram grow with the range and the digits, and are read per record, so a
+/// narrow range is preferred
+pub fn hdr_histogram<T: ColumnLocateBuilder>(
+    column: T,
+    significant_digits: u8,
+    highest_trackable_value: u64,
+) -> AggregatorDescriptor {
+    let histogram = HdrHistogram::new(significant_digits, highest_trackable_value);
+    AggregatorDescriptor::HdrHistogram(column.build(), histogram, None)
+}
+
+/// The quantile in [0, 1] of a numeric field as `Float64` estimated by the HDR histogram
+pub fn hdr_histogram_quantile<T: ColumnLocateBuilder>(
+    column: T,
+    quantile: f64,
+    significant_digits: u8,
+    highest_trackable_value: u64,
+) -> AggregatorDescriptor {
+    let histogram = HdrHistogram::new(significant_digits, highest_trackable_value);
+    AggregatorDescriptor::HdrHistogram(column.build(), histogram, Some(quantile))
+}
+
+#[derive(Clone, Debug)]
+pub enum AggregatorDescriptor {
+    Count,
+    Sum(ColumnLocate),
+    Max(ColumnLocate),
+    Min(ColumnLocate),
+    Avg(ColumnLocate),
+    DistinctCount(ColumnLocate, u8),
+    FirstValue(ColumnLocate),
+    LastValue(ColumnLocate),
+    /// the empty sketch and the quantile of the result, the sketch itself if `None`
+    TDigest(ColumnLocate, TDigest, Option<f64>),
+    HdrHistogram(ColumnLocate, HdrHistogram, Option<f64>),
+}
+
+impl AggregatorDescriptor {
+    pub fn to_aggregator(&self, schema: &Schema) -> Box<dyn Aggregator> {
+        match self {
+            Self::Count => Box::new(CountAggregator::new()),
+            Self::Sum(column_locate) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(NumberAggregator::new(index, BasicAggType::Sum, field))
+            }
+            Self::Max(column_locate) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(NumberAggregator::new(index, BasicAggType::Max, field))
+            }
+            Self::Min(column_locate) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(NumberAggregator::new(index, BasicAggType::Min, field))
+            }
+            Self::Avg(column_locate) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(AvgAggregator::new(index, field))
+            }
+            Self::DistinctCount(column_locate, precision) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(DistinctCountAggregator::new(index, field, *precision))
+            }
+            Self::FirstValue(column_locate) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(ValueAggregator::new(index, field, false))
+            }
+            Self::LastValue(column_locate) => {
+                let (index, field) = column_locate.to_column(schema);
+                Box::new(ValueAggregator::new(index, field, true))
+            }
+            Self::TDigest(column_locate, sketch, quantile) => {
+                let (index, field) = column_locate.to_column(schema);
+                let agg =
+                    SketchAggregator::new(index, field, "t_digest", sketch.clone(), *quantile);
+                Box::new(agg)
+            }
+            Self::HdrHistogram(column_locate, sketch, quantile) => {
+                let (index, field) = column_locate.to_column(schema);
+                let agg = SketchAggregator::new(index, field, "hdr", sketch.clone(), *quantile);
+                Box::new(agg)
+            }
+        }
+    }
+}
+
+/// An aggregation accumulating the records into its accumulator fields, the result is computed
+/// from the accumulator. The accumulators of the aggregations are fused into one record, so
+/// the fields of an aggregation start from the `index` of the accumulator record
+pub trait Aggregator: Debug + Send + Sync {
+    fn accumulator_fields(&self) -> &[Field];
+
+    fn output_field(&self) -> &Field;
+
+    /// Write the accumulator of no record
+    fn create_accumulator(&self, writer: &mut BufferWriter);
+
+    /// Write the accumulator with the `record` of the `timestamp` added
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        record: &BufferReader,
+        timestamp: u64,
+    );
+
+    /// Write the merged accumulator of the two accumulators, e.g. the accumulators of the
+    /// merged session windows
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    );
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize);
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The numeric value widened to 64 bits
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+enum Number {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
+impl Number {
+    /// The data type the values of the `data_type` are widened to
+    fn widen(data_type: &DataType) -> DataType {
+        match data_type {
+            DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => DataType::Int64,
+            DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+                DataType::UInt64
+            }
+            DataType::Float32 | DataType::Float64 => DataType::Float64,
+            _ => panic!("un-support DataType {:?}", data_type),
+        }
+    }
+
+    fn read(reader: &BufferReader, index: usize, data_type: &DataType) -> Self {
+        match data_type {
+            DataType::Int8 => Number::Int(reader.get_i8(index).unwrap() as i64),
+            DataType::Int16 => Number::Int(reader.get_i16(index).unwrap() as i64),
+            DataType::Int32 => Number::Int(reader.get_i32(index).unwrap() as i64),
+            DataType::Int64 => Number::Int(reader.get_i64(index).unwrap()),
+            DataType::UInt8 => Number::UInt(reader.get_u8(index).unwrap() as u64),
+            DataType::UInt16 => Number::UInt(reader.get_u16(index).unwrap() as u64),
+            DataType::UInt32 => Number::UInt(reader.get_u32(index).unwrap() as u64),
+            DataType::UInt64 => Number::UInt(reader.get_u64(index).unwrap()),
+            DataType::Float32 => Number::Float(reader.get_f32(index).unwrap() as f64),
+            DataType::Float64 => Number::Float(reader.get_f64(index).unwrap()),
+            _ => panic!("un-support DataType {:?}", data_type),
+        }
+    }
+
+    fn write(self, writer: &mut BufferWriter) {
+        match self {
+            Number::Int(v) => writer.set_i64(v).unwrap(),
+            Number::UInt(v) => writer.set_u64(v).unwrap(),
+            Number::Float(v) => writer.set_f64(v).unwrap(),
+        }
+    }
+
+    /// The identity of the aggregation on the values of the widened `data_type`
+    fn identity(agg_type: BasicAggType, data_type: &DataType) -> Self {
+        match (agg_type, data_type) {
+            (BasicAggType::Sum, DataType::Int64) => Number::Int(0),
+            (BasicAggType::Sum, DataType::UInt64) => Number::UInt(0),
+            (BasicAggType::Sum, _) => Number::Float(0.0),
+            (BasicAggType::Max, DataType::Int64) => Number::Int(i64::MIN),
+            (BasicAggType::Max, DataType::UInt64) => Number::UInt(u64::MIN),
+            (BasicAggType::Max, _) => Number::Float(f64::NEG_INFINITY),
+            (BasicAggType::Min, DataType::Int64) => Number::Int(i64::MAX),
+            (BasicAggType::Min, DataType::UInt64) => Number::UInt(u64::MAX),
+            (BasicAggType::Min, _) => Number::Float(f64::INFINITY),
+        }
+    }
+
+    fn aggregate(self, agg_type: BasicAggType, other: Number) -> Self {
+        match (agg_type, self, other) {
+            (BasicAggType::Sum, Number::Int(a), Number::Int(b)) => Number::Int(a + b),
+            (BasicAggType::Sum, Number::UInt(a), Number::UInt(b)) => Number::UInt(a + b),
+            (BasicAggType::Sum, Number::Float(a), Number::Float(b)) => Number::Float(a + b),
+            (BasicAggType::Sum, a, b) => panic!("sum the different numbers {:?}, {:?}", a, b),
+            (BasicAggType::Max, a, b) => {
+                if b > a {
+                    b
+                } else {
+                    a
+                }
+            }
+            (BasicAggType::Min, a, b) => {
+                if b < a {
+                    b
+                } else {
+                    a
+                }
+            }
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(v) => v as f64,
+            Number::UInt(v) => v as f64,
+            Number::Float(v) => v,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+pub struct CountAggregator {
+    accumulator_fields: Vec<Field>,
+    output_field: Field,
+}
+
+impl CountAggregator {
+    pub fn new() -> Self {
+        let output_field = Field::new("count", DataType::UInt64);
+        CountAggregator {
+            accumulator_fields: vec![output_field.clone()],
+            output_field,
+        }
+    }
+}
+
+impl Aggregator for CountAggregator {
+    fn accumulator_fields(&self) -> &[Field] {
+        self.accumulator_fields.as_slice()
+    }
+
+    fn output_field(&self) -> &Field {
+        &self.output_field
+    }
+
+    fn create_accumulator(&self, writer: &mut BufferWriter) {
+        writer.set_u64(0).unwrap();
+    }
+
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        _record: &BufferReader,
+        _timestamp: u64,
+    ) {
+        writer
+            .set_u64(accumulator.get_u64(index).unwrap() + 1)
+            .unwrap();
+    }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    ) {
+        let count = accumulator.get_u64(index).unwrap() + other.get_u64(index).unwrap();
+        writer.set_u64(count).unwrap();
+    }
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        writer.set_u64(accumulator.get_u64(index).unwrap()).unwrap();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The sum, max or min of a numeric field, the result is widened to `Int64`, `UInt64` or
+/// `Float64`
+#[derive(Debug)]
+pub struct NumberAggregator {
+    column_index: usize,
+    agg_type: BasicAggType,
+    input_data_type: DataType,
+
+    accumulator_fields: Vec<Field>,
+    output_field: Field,
+}
+
+impl NumberAggregator {
+    pub fn new(column_index: usize, agg_type: BasicAggType, input_field: &Field) -> Self {
+        let output_field = Field::new(
+            format!("{}({})", agg_type, input_field.name()).as_str(),
+            Number::widen(input_field.data_type()),
+        );
+        NumberAggregator {
+            column_index,
+            agg_type,
+            input_data_type: input_field.data_type().clone(),
+            accumulator_fields: vec![output_field.clone()],
+            output_field,
+        }
+    }
+
+    fn read_accumulator(&self, accumulator: &BufferReader, index: usize) -> Number {
+        Number::read(accumulator, index, self.output_field.data_type())
+    }
+}
+
+impl Aggregator for NumberAggregator {
+    fn accumulator_fields(&self) -> &[Field] {
+        self.accumulator_fields.as_slice()
+    }
+
+    fn output_field(&self) -> &Field {
+        &self.output_field
+    }
+
+    fn create_accumulator(&self, writer: &mut BufferWriter) {
+        Number::identity(self.agg_type, self.output_field.data_type()).write(writer);
+    }
+
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        record: &BufferReader,
+        _timestamp: u64,
+    ) {
+        let value = Number::read(record, self.column_index, &self.input_data_type);
+        self.read_accumulator(accumulator, index)
+            .aggregate(self.agg_type, value)
+            .write(writer);
+    }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    ) {
+        let other = self.read_accumulator(other, index);
+        self.read_accumulator(accumulator, index)
+            .aggregate(self.agg_type, other)
+            .write(writer);
+    }
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        self.read_accumulator(accumulator, index).write(writer);
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The average of a numeric field as `Float64`, accumulated by the sum and the count
+#[derive(Debug)]
+pub struct AvgAggregator {
+    column_index: usize,
+    input_data_type: DataType,
+
+    accumulator_fields: Vec<Field>,
+    output_field: Field,
+}
+
+impl AvgAggregator {
+    pub fn new(column_index: usize, input_field: &Field) -> Self {
+        // check the field is numeric
+        Number::widen(input_field.data_type());
+
+        let name = format!("avg({})", input_field.name());
+        AvgAggregator {
+            column_index,
+            input_data_type: input_field.data_type().clone(),
+            accumulator_fields: vec![
+                Field::new(format!("{}.sum", name).as_str(), DataType::Float64),
+                Field::new(format!("{}.count", name).as_str(), DataType::UInt64),
+            ],
+            output_field: Field::new(name.as_str(), DataType::Float64),
+        }
+    }
+}
+
+impl Aggregator for AvgAggregator {
+    fn accumulator_fields(&self) -> &[Field] {
+        self.accumulator_fields.as_slice()
+    }
+
+    fn output_field(&self) -> &Field {
+        &self.output_field
+    }
+
+    fn create_accumulator(&self, writer: &mut BufferWriter) {
+        writer.set_f64(0.0).unwrap();
+        writer.set_u64(0).unwrap();
+    }
+
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        record: &BufferReader,
+        _timestamp: u64,
+    ) {
+        let value = Number::read(record, self.column_index, &self.input_data_type).as_f64();
+        writer
+            .set_f64(accumulator.get_f64(index).unwrap() + value)
+            .unwrap();
+        writer
+            .set_u64(accumulator.get_u64(index + 1).unwrap() + 1)
+            .unwrap();
+    }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    ) {
+        let sum = accumulator.get_f64(index).unwrap() + other.get_f64(index).unwrap();
+        let count = accumulator.get_u64(index + 1).unwrap() + other.get_u64(index + 1).unwrap();
+        writer.set_f64(sum).unwrap();
+        writer.set_u64(count).unwrap();
+    }
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        let sum = accumulator.get_f64(index).unwrap();
+        let count = accumulator.get_u64(index + 1).unwrap();
+        let avg = if count == 0 { 0.0 } else { sum / count as f64 };
+        writer.set_f64(avg).unwrap();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The approximate distinct count of a field as `UInt64`, accumulated by the HyperLogLog
+/// registers of the raw bytes of the field
+#[derive(Debug)]
+pub struct DistinctCountAggregator {
+    column_index: usize,
+    precision: u8,
+
+    accumulator_fields: Vec<Field>,
+    output_field: Field,
+}
+
+impl DistinctCountAggregator {
+    pub fn new(column_index: usize, input_field: &Field, precision: u8) -> Self {
+        let name = format!("distinct_count({})", input_field.name());
+        DistinctCountAggregator {
+            column_index,
+            precision,
+            accumulator_fields: vec![Field::new(
+                format!("{}.registers", name).as_str(),
+                DataType::Binary,
+            )],
+            output_field: Field::new(name.as_str(), DataType::UInt64),
+        }
+    }
+
+    fn registers(&self, accumulator: &BufferReader, index: usize) -> Vec<u8> {
+        accumulator.get_binary(index).unwrap().to_vec()
+    }
+}
+
+impl Aggregator for DistinctCountAggregator {
+    fn accumulator_fields(&self) -> &[Field] {
+        self.accumulator_fields.as_slice()
+    }
+
+    fn output_field(&self) -> &Field {
+        &self.output_field
+    }
+
+    fn create_accumulator(&self, writer: &mut BufferWriter) {
+        let registers = vec![0u8; get_hyper_log_log_capacity(self.precision)];
+        writer.set_binary(registers.as_slice()).unwrap();
+    }
+
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        record: &BufferReader,
+        _timestamp: u64,
+    ) {
+        let mut registers = self.registers(accumulator, index);
+        HyperLogLog::new(registers.as_mut_slice())
+            .add(record.get_bytes_raw(self.column_index).unwrap());
+        writer.set_binary(registers.as_slice()).unwrap();
+    }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    ) {
+        let mut registers = self.registers(accumulator, index);
+        HyperLogLog::new(registers.as_mut_slice()).merge(other.get_binary(index).unwrap());
+        writer.set_binary(registers.as_slice()).unwrap();
+    }
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        let registers = accumulator.get_binary(index).unwrap();
+        writer.set_u64(estimate(registers)).unwrap();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The first or last value of a field by the timestamps of the records, the result has the
+/// data type of the field
+#[derive(Debug)]
+pub struct ValueAggregator {
+    column_index: usize,
+    last: bool,
+
+    accumulator_fields: Vec<Field>,
+    output_field: Field,
+}
+
+impl ValueAggregator {
+    pub fn new(column_index: usize, input_field: &Field, last: bool) -> Self {
+        let name = if last {
+            format!("last_value({})", input_field.name())
+        } else {
+            format!("first_value({})", input_field.name())
+        };
+        ValueAggregator {
+            column_index,
+            last,
+            accumulator_fields: vec![
+                Field::new(format!("{}.present", name).as_str(), DataType::Boolean),
+                Field::new(format!("{}.timestamp", name).as_str(), DataType::UInt64),
+                Field::new(format!("{}.value", name).as_str(), DataType::Binary),
+            ],
+            output_field: Field::new(name.as_str(), input_field.data_type().clone()),
+        }
+    }
+
+    /// Whether the value of the `timestamp` replaces the accumulated one
+    fn replace(&self, accumulator: &BufferReader, index: usize, timestamp: u64) -> bool {
+        if !accumulator.get_bool(index).unwrap() {
+            return true;
+        }
+
+        let accumulated_timestamp = accumulator.get_u64(index + 1).unwrap();
+        if self.last {
+            timestamp >= accumulated_timestamp
+        } else {
+            timestamp < accumulated_timestamp
+        }
+    }
+
+    fn write(&self, writer: &mut BufferWriter, present: bool, timestamp: u64, value: &[u8]) {
+        writer.set_bool(present).unwrap();
+        writer.set_u64(timestamp).unwrap();
+        writer.set_binary(value).unwrap();
+    }
+
+    fn copy(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        self.write(
+            writer,
+            accumulator.get_bool(index).unwrap(),
+            accumulator.get_u64(index + 1).unwrap(),
+            accumulator.get_binary(index + 2).unwrap(),
+        );
+    }
+}
+
+impl Aggregator for ValueAggregator {
+    fn accumulator_fields(&self) -> &[Field] {
+        self.accumulator_fields.as_slice()
+    }
+
+    fn output_field(&self) -> &Field {
+        &self.output_field
+    }
+
+    fn create_accumulator(&self, writer: &mut BufferWriter) {
+        self.write(writer, false, 0, &[]);
+    }
+
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        record: &BufferReader,
+        timestamp: u64,
+    ) {
+        if self.replace(accumulator, index, timestamp) {
+            let value = record.get_bytes_raw(self.column_index).unwrap();
+            self.write(writer, true, timestamp, value);
+        } else {
+            self.copy(writer, accumulator, index);
+        }
+    }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    ) {
+        let other_present = other.get_bool(index).unwrap();
+        if other_present && self.replace(accumulator, index, other.get_u64(index + 1).unwrap()) {
+            self.copy(writer, other, index);
+        } else {
+            self.copy(writer, accumulator, index);
+        }
+    }
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        let value = accumulator.get_binary(index + 2).unwrap();
+        writer.set_bytes_raw(value).unwrap();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The quantile sketch of a numeric field, the result is the serialized sketch as `Binary` to
+/// be merged downstream, e.g. across the partitions, or the estimated quantile as `Float64`
+#[derive(Debug)]
+pub struct SketchAggregator<S: QuantileSketch + Debug> {
+    column_index: usize,
+    input_data_type: DataType,
+    sketch: S,
+    quantile: Option<f64>,
+
+    accumulator_fields: Vec<Field>,
+    output_field: Field,
+}
+
+impl<S: QuantileSketch + Debug> SketchAggregator<S> {
+    pub fn new(
+        column_index: usize,
+        input_field: &Field,
+        sketch_name: &str,
+        sketch: S,
+        quantile: Option<f64>,
+    ) -> Self {
+        // check the field is numeric
+        Number::widen(input_field.data_type());
+
+        let output_field = match quantile {
+            Some(q) => {
+                if !(0.0..=1.0).contains(&q) {
+                    panic!("the quantile must be in [0, 1], got {}", q);
+                }
+                let name = format!("p{}({})", q * 100.0, input_field.name());
+                Field::new(name.as_str(), DataType::Float64)
+            }
+            None => {
+                let name = format!("{}({})", sketch_name, input_field.name());
+                Field::new(name.as_str(), DataType::Binary)
+            }
+        };
+        SketchAggregator {
+            column_index,
+            input_data_type: input_field.data_type().clone(),
+            sketch,
+            quantile,
+            accumulator_fields: vec![Field::new(
+                format!("{}.sketch", output_field.name()).as_str(),
+                DataType::Binary,
+            )],
+            output_field,
+        }
+    }
+
+    fn read_sketch(&self, accumulator: &BufferReader, index: usize) -> S {
+        S::from_bytes(accumulator.get_binary(index).unwrap()).unwrap()
+    }
+}
+
+impl<S: QuantileSketch + Debug> Aggregator for SketchAggregator<S> {
+    fn accumulator_fields(&self) -> &[Field] {
+        self.accumulator_fields.as_slice()
+    }
+
+    fn output_field(&self) -> &Field {
+        &self.output_field
+    }
+
+    fn create_accumulator(&self, writer: &mut BufferWriter) {
+        writer
+            .set_binary(self.sketch.to_bytes().as_slice())
+            .unwrap();
+    }
+
+    fn add(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        index: usize,
+        record: &BufferReader,
+        _timestamp: u64,
+    ) {
+        let value = Number::read(record, self.column_index, &self.input_data_type).as_f64();
+        let mut sketch = self.read_sketch(accumulator, index);
+        sketch.add(value);
+        writer.set_binary(sketch.to_bytes().as_slice()).unwrap();
+    }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        accumulator: &BufferReader,
+        other: &BufferReader,
+        index: usize,
+    ) {
+        let mut sketch = self.read_sketch(accumulator, index);
+        sketch.merge(&self.read_sketch(other, index));
+        writer.set_binary(sketch.to_bytes().as_slice()).unwrap();
+    }
+
+    fn get_result(&self, writer: &mut BufferWriter, accumulator: &BufferReader, index: usize) {
+        match self.quantile {
+            Some(q) => {
+                let sketch = self.read_sketch(accumulator, index);
+                writer.set_f64(sketch.quantile(q).unwrap_or(0.0)).unwrap();
+            }
+            None => {
+                let bytes = accumulator.get_binary(index).unwrap();
+                writer.set_binary(bytes).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::utils::hash::hash_code_64;
+
+pub const MIN_PRECISION: u8 = 4;
+pub const MAX_PRECISION: u8 = 16;
+
+/// The size of the registers in bytes, one byte per register
+pub fn get_hyper_log_log_capacity(precision: u8) -> usize {
+    if !(MIN_PRECISION..=MAX_PRECISION).contains(&precision) {
+        panic!(
+            "the precision of HyperLogLog must be in [{}, {}], got {}",
+            MIN_PRECISION, MAX_PRECISION, precision
+        );
+    }
+    1 << precision
+}
+
+/// Estimate the distinct count of the values added into the `2^precision` registers, the
+/// standard error is about `1.04 / sqrt(2^precision)`, e.g. 1.6% of the precision 12
+pub struct HyperLogLog<'a> {
+    registers: &'a mut [u8],
+    precision: u32,
+}
+
+impl<'a> HyperLogLog<'a> {
+    pub fn new(registers: &'a mut [u8]) -> Self {
+        if !registers.len().is_power_of_two() {
+            panic!("the registers size must be the power of two");
+        }
+        let precision = registers.len().trailing_zeros();
+        HyperLogLog {
+            registers,
+            precision,
+        }
+    }
+
+    pub fn add(&mut self, value: &[u8]) {
+        let hash = hash_code_64(value).unwrap();
+
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rank = ((hash << self.precision).leading_zeros() + 1).min(64 - self.precision + 1);
+        if rank as u8 > self.registers[index] {
+            self.registers[index] = rank as u8;
+        }
+    }
+
+    pub fn merge(&mut self, other: &[u8]) {
+        for (register, other) in self.registers.iter_mut().zip(other) {
+            if *other > *register {
+                *register = *other;
+            }
+        }
+    }
+
+    pub fn estimate(&self) -> u64 {
+        estimate(self.registers)
+    }
+}
+
+pub fn estimate(registers: &[u8]) -> u64 {
+    let m = registers.len() as f64;
+    let alpha = match registers.len() {
+        16 => 0.673,
+        32 => 0.697,
+        64 => 0.709,
+        _ => 0.7213 / (1.0 + 1.079 / m),
+    };
+
+    let sum: f64 = registers.iter().map(|x| 2f64.powi(-(*x as i32))).sum();
+    let raw_estimate = alpha * m * m / sum;
+
+    // linear counting for the small cardinalities
+    let zeros = registers.iter().filter(|x| **x == 0).count();
+    if raw_estimate <= 2.5 * m && zeros > 0 {
+        (m * (m / zeros as f64).ln()).round() as u64
+    } else {
+        raw_estimate.round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::aggregate::hyper_log_log::{
+        estimate, get_hyper_log_log_capacity, HyperLogLog,
+    };
+
+    fn registers() -> Vec<u8> {
+        vec![0u8; get_hyper_log_log_capacity(12)]
+    }
+
+    #[test]
+    pub fn hyper_log_log_test() {
+        let mut left = registers();
+        let mut right = registers();
+        {
+            let mut left = HyperLogLog::new(left.as_mut_slice());
+            let mut right = HyperLogLog::new(right.as_mut_slice());
+            for i in 0..100_000u64 {
+                left.add(i.to_le_bytes().as_ref());
+                // the duplicates don't change the estimate
+                left.add(i.to_le_bytes().as_ref());
+                right.add((i + 50_000).to_le_bytes().as_ref());
+            }
+
+            let error = (left.estimate() as f64 - 100_000.0).abs() / 100_000.0;
+            assert!(error < 0.05, "{}", left.estimate());
+        }
+
+        HyperLogLog::new(left.as_mut_slice()).merge(right.as_slice());
+        let error = (estimate(left.as_slice()) as f64 - 150_000.0).abs() / 150_000.0;
+        assert!(error < 0.05, "{}", estimate(left.as_slice()));
+
+        let mut small = registers();
+        let mut hll = HyperLogLog::new(small.as_mut_slice());
+        for i in 0..10u64 {
+            hll.add(i.to_le_bytes().as_ref());
+        }
+        assert!((9..=11).contains(&hll.estimate()));
+    }
+}
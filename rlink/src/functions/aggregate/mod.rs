@@ -0,0 +1,6 @@
+pub mod aggregator;
+pub mod hyper_log_log;
+pub mod schema_aggregate;
+
+pub use aggregator::*;
+pub use schema_aggregate::*;
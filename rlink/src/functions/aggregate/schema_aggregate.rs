@@ -0,0 +1,425 @@
+use std::borrow::BorrowMut;
+
+use crate::core::data_types::{Field, Schema};
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{
+    AggregateFunction, Context, KeyedProcessFunction, NamedFunction, SendableElementStream,
+};
+use crate::core::state::{ValueState, ValueStateDescriptor};
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::functions::aggregate::{Aggregator, AggregatorDescriptor};
+use crate::utils::stream::MemoryStream;
+
+/// The aggregators fused into one accumulator record, each record is read once by all of them
+#[derive(Debug)]
+struct Aggregators {
+    schema: Schema,
+    accumulator_schema: Schema,
+    result_schema: Schema,
+
+    aggregators: Vec<Box<dyn Aggregator>>,
+}
+
+impl Aggregators {
+    fn new(descriptors: &[AggregatorDescriptor], schema: &Schema) -> Self {
+        let aggregators: Vec<Box<dyn Aggregator>> = descriptors
+            .iter()
+            .map(|x| x.to_aggregator(schema))
+            .collect();
+
+        let accumulator_fields: Vec<Field> = aggregators
+            .iter()
+            .flat_map(|x| x.accumulator_fields().to_vec())
+            .collect();
+        let result_fields: Vec<Field> = aggregators
+            .iter()
+            .map(|x| x.output_field().clone())
+            .collect();
+
+        Aggregators {
+            schema: schema.clone(),
+            accumulator_schema: Schema::new(accumulator_fields),
+            result_schema: Schema::new(result_fields),
+            aggregators,
+        }
+    }
+
+    fn create_accumulator(&self) -> Record {
+        let mut accumulator = Record::new();
+        let mut writer = accumulator.as_writer(self.accumulator_schema.as_type_ids());
+        for aggregator in &self.aggregators {
+            aggregator.create_accumulator(writer.borrow_mut());
+        }
+        accumulator
+    }
+
+    fn add(&self, accumulator: &mut Record, record: &mut Record) -> Record {
+        let timestamp = record.timestamp();
+        let record_reader = record.as_reader(self.schema.as_type_ids());
+        let accumulator_reader = accumulator.as_reader(self.accumulator_schema.as_type_ids());
+
+        let mut accumulator_rt = Record::new();
+        let mut writer = accumulator_rt.as_writer(self.accumulator_schema.as_type_ids());
+        let mut index = 0;
+        for aggregator in &self.aggregators {
+            aggregator.add(
+                writer.borrow_mut(),
+                &accumulator_reader,
+                index,
+                &record_reader,
+                timestamp,
+            );
+            index += aggregator.accumulator_fields().len();
+        }
+        accumulator_rt
+    }
+
+    fn merge(&self, accumulator: &mut Record, other: &mut Record) -> Record {
+        let accumulator_reader = accumulator.as_reader(self.accumulator_schema.as_type_ids());
+        let other_reader = other.as_reader(self.accumulator_schema.as_type_ids());
+
+        let mut accumulator_rt = Record::new();
+        let mut writer = accumulator_rt.as_writer(self.accumulator_schema.as_type_ids());
+        let mut index = 0;
+        for aggregator in &self.aggregators {
+            aggregator.merge(
+                writer.borrow_mut(),
+                &accumulator_reader,
+                &other_reader,
+                index,
+            );
+            index += aggregator.accumulator_fields().len();
+        }
+        accumulator_rt
+    }
+
+    fn get_result(&self, accumulator: &mut Record) -> Record {
+        let accumulator_reader = accumulator.as_reader(self.accumulator_schema.as_type_ids());
+
+        let mut result = Record::new();
+        let mut writer = result.as_writer(self.result_schema.as_type_ids());
+        let mut index = 0;
+        for aggregator in &self.aggregators {
+            aggregator.get_result(writer.borrow_mut(), &accumulator_reader, index);
+            index += aggregator.accumulator_fields().len();
+        }
+        result
+    }
+}
+
+/// Aggregate the records of the windows by the built-in aggregations in one pass, the result
+/// has a field per aggregation, e.g.
+/// `SchemaAggregateFunction::new(vec![count(), avg("latency"), distinct_count("user")], 0)`
+#[derive(Debug)]
+pub struct SchemaAggregateFunction {
+    parallelism: u16,
+    descriptors: Vec<AggregatorDescriptor>,
+    aggregators: Option<Aggregators>,
+}
+
+impl SchemaAggregateFunction {
+    pub fn new(descriptors: Vec<AggregatorDescriptor>, parallelism: u16) -> Self {
+        SchemaAggregateFunction {
+            parallelism,
+            descriptors,
+            aggregators: None,
+        }
+    }
+
+    fn aggregators(&self) -> &Aggregators {
+        self.aggregators.as_ref().unwrap()
+    }
+}
+
+#[async_trait]
+impl AggregateFunction for SchemaAggregateFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let schema = context.input_schema.first();
+        self.aggregators = Some(Aggregators::new(self.descriptors.as_slice(), schema));
+        Ok(())
+    }
+
+    fn create_accumulator(&self) -> Record {
+        self.aggregators().create_accumulator()
+    }
+
+    fn add(&self, accumulator: &mut Record, record: &mut Record) -> Record {
+        self.aggregators().add(accumulator, record)
+    }
+
+    fn get_result(&self, accumulator: &mut Record) -> Record {
+        self.aggregators().get_result(accumulator)
+    }
+
+    fn merge(&self, accumulator: &mut Record, other: &mut Record) -> Record {
+        self.aggregators().merge(accumulator, other)
+    }
+
+    fn supports_merge(&self) -> bool {
+        true
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        let aggregators = Aggregators::new(self.descriptors.as_slice(), input_schema.first());
+        FnSchema::Single(aggregators.result_schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl NamedFunction for SchemaAggregateFunction {
+    fn name(&self) -> &str {
+        "SchemaAggregateFunction"
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// the accumulator of the key
+const ROLLING_ACCUMULATOR: &str = "rolling_aggregate.accumulator";
+
+/// Aggregate the records of each key since the start of the stream by the built-in
+/// aggregations, each record is emitted with the aggregated values of its key up to it
+/// appended, see `TKeyedStream::process`
+pub struct RollingAggregateFunction {
+    descriptors: Vec<AggregatorDescriptor>,
+    aggregators: Option<Aggregators>,
+    accumulator: Option<ValueState<Vec<u8>>>,
+}
+
+impl RollingAggregateFunction {
+    pub fn new(descriptors: Vec<AggregatorDescriptor>) -> Self {
+        RollingAggregateFunction {
+            descriptors,
+            aggregators: None,
+            accumulator: None,
+        }
+    }
+
+    fn aggregate(&self, mut record: Record) -> anyhow::Result<Record> {
+        let aggregators = self.aggregators.as_ref().unwrap();
+        let state = self.accumulator.as_ref().unwrap();
+
+        let mut accumulator = match state.value()? {
+            Some(values) => Record::from_values(values.as_slice(), 0),
+            None => aggregators.create_accumulator(),
+        };
+        let mut accumulator = aggregators.add(&mut accumulator, &mut record);
+        state.update(accumulator.as_buffer().as_slice().to_vec())?;
+
+        record.extend(aggregators.get_result(&mut accumulator))?;
+        Ok(record)
+    }
+}
+
+#[async_trait]
+impl KeyedProcessFunction for RollingAggregateFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let schema = context.input_schema.first();
+        self.aggregators = Some(Aggregators::new(self.descriptors.as_slice(), schema));
+
+        let descriptor = ValueStateDescriptor::new(ROLLING_ACCUMULATOR);
+        self.accumulator = Some(context.runtime_context().value_state(&descriptor));
+        Ok(())
+    }
+
+    async fn process_element(
+        &mut self,
+        record: Record,
+        _timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let record = self.aggregate(record).expect("rolling aggregate error");
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn on_timer(
+        &mut self,
+        _timestamp: u64,
+        _time_domain: TimeDomain,
+        _timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        Box::pin(MemoryStream::new(vec![]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        let schema = input_schema.first();
+        let aggregators = Aggregators::new(self.descriptors.as_slice(), schema);
+
+        let fields = schema
+            .fields()
+            .iter()
+            .chain(aggregators.result_schema.fields());
+        FnSchema::Single(Schema::new(fields.cloned().collect()))
+    }
+
+    fn parallelism(&self) -> u16 {
+        // inherit the parallelism of the upstream
+        0
+    }
+}
+
+impl NamedFunction for RollingAggregateFunction {
+    fn name(&self) -> &str {
+        "RollingAggregateFunction"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::core::state::{RuntimeContext, ValueStateDescriptor};
+    use crate::functions::aggregate::schema_aggregate::{
+        Aggregators, RollingAggregateFunction, ROLLING_ACCUMULATOR,
+    };
+    use crate::functions::aggregate::{
+        avg, count, distinct_count, first_value, hdr_histogram_quantile, last_value, max, min, sum,
+        t_digest, t_digest_quantile, AggregatorDescriptor,
+    };
+    use crate::functions::percentile::TDigest;
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("user", DataType::String),
+            Field::new("latency", DataType::Int32),
+            Field::new("bytes", DataType::UInt16),
+        ])
+    }
+
+    fn record(user: &str, latency: i32, bytes: u16, timestamp: u64) -> Record {
+        let schema = schema();
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema.as_type_ids());
+        writer.set_str(user).unwrap();
+        writer.set_i32(latency).unwrap();
+        writer.set_u16(bytes).unwrap();
+        record.set_timestamp(timestamp);
+        record
+    }
+
+    fn descriptors() -> Vec<AggregatorDescriptor> {
+        vec![
+            count(),
+            sum("latency"),
+            min("latency"),
+            max("bytes"),
+            avg("latency"),
+            distinct_count("user"),
+            first_value("user"),
+            last_value(1),
+        ]
+    }
+
+    #[test]
+    pub fn aggregators_test() {
+        let aggregators = Aggregators::new(descriptors().as_slice(), &schema());
+        let result_schema = aggregators.result_schema.clone();
+        assert_eq!(
+            result_schema.as_type_ids(),
+            Schema::new(vec![
+                Field::new("count", DataType::UInt64),
+                Field::new("sum(latency)", DataType::Int64),
+                Field::new("min(latency)", DataType::Int64),
+                Field::new("max(bytes)", DataType::UInt64),
+                Field::new("avg(latency)", DataType::Float64),
+                Field::new("distinct_count(user)", DataType::UInt64),
+                Field::new("first_value(user)", DataType::String),
+                Field::new("last_value(latency)", DataType::Int32),
+            ])
+            .as_type_ids()
+        );
+
+        let mut left = aggregators.create_accumulator();
+        for r in [record("a", 10, 100, 3), record("b", -4, 20, 1)].iter_mut() {
+            left = aggregators.add(&mut left, r);
+        }
+        let mut right = aggregators.create_accumulator();
+        for r in [record("a", 6, 300, 2), record("c", 8, 0, 5)].iter_mut() {
+            right = aggregators.add(&mut right, r);
+        }
+
+        let mut merged = aggregators.merge(&mut left, &mut right);
+        let mut result = aggregators.get_result(&mut merged);
+        let reader = result.as_reader(result_schema.as_type_ids());
+        assert_eq!(reader.get_u64(0).unwrap(), 4);
+        assert_eq!(reader.get_i64(1).unwrap(), 20);
+        assert_eq!(reader.get_i64(2).unwrap(), -4);
+        assert_eq!(reader.get_u64(3).unwrap(), 300);
+        assert_eq!(reader.get_f64(4).unwrap(), 5.0);
+        assert_eq!(reader.get_u64(5).unwrap(), 3);
+        assert_eq!(reader.get_str(6).unwrap(), "b");
+        assert_eq!(reader.get_i32(7).unwrap(), 8);
+    }
+
+    #[test]
+    pub fn sketch_aggregators_test() {
+        let descriptors = vec![
+            t_digest_quantile("latency", 0.5, 100.0),
+            hdr_histogram_quantile("latency", 0.9, 3, 3_600_000),
+            t_digest("latency", 100.0),
+        ];
+        let aggregators = Aggregators::new(descriptors.as_slice(), &schema());
+
+        let mut left = aggregators.create_accumulator();
+        let mut right = aggregators.create_accumulator();
+        for i in 1..=1000 {
+            let accumulator = if i % 2 == 0 { &mut left } else { &mut right };
+            *accumulator = aggregators.add(accumulator, &mut record("a", i, 0, 0));
+        }
+
+        let mut merged = aggregators.merge(&mut left, &mut right);
+        let mut result = aggregators.get_result(&mut merged);
+        let result_schema = aggregators.result_schema.clone();
+        let reader = result.as_reader(result_schema.as_type_ids());
+        assert!((reader.get_f64(0).unwrap() - 500.0).abs() < 10.0);
+        assert_eq!(reader.get_f64(1).unwrap(), 900.0);
+
+        let digest = TDigest::from_bytes(reader.get_binary(2).unwrap()).unwrap();
+        assert_eq!(digest.count(), 1000);
+    }
+
+    #[test]
+    pub fn rolling_aggregate_test() {
+        let runtime_context = RuntimeContext::new();
+        let mut function = RollingAggregateFunction::new(vec![count(), sum("latency")]);
+        function.aggregators = Some(Aggregators::new(function.descriptors.as_slice(), &schema()));
+        function.accumulator =
+            Some(runtime_context.value_state(&ValueStateDescriptor::new(ROLLING_ACCUMULATOR)));
+
+        let output_schema = Schema::new(vec![
+            Field::new("user", DataType::String),
+            Field::new("latency", DataType::Int32),
+            Field::new("bytes", DataType::UInt16),
+            Field::new("count", DataType::UInt64),
+            Field::new("sum(latency)", DataType::Int64),
+        ]);
+        let key_schema = Schema::new(vec![Field::new("user", DataType::String)]);
+        let aggregate = |user: &str, latency: i32| {
+            let mut key = Record::new();
+            key.as_writer(key_schema.as_type_ids())
+                .set_str(user)
+                .unwrap();
+            runtime_context.set_current_key(&key);
+
+            let mut record = function.aggregate(record(user, latency, 0, 0)).unwrap();
+            let reader = record.as_reader(output_schema.as_type_ids());
+            (reader.get_u64(3).unwrap(), reader.get_i64(4).unwrap())
+        };
+
+        assert_eq!(aggregate("a", 1), (1, 1));
+        assert_eq!(aggregate("a", 2), (2, 3));
+        assert_eq!(aggregate("b", 5), (1, 5));
+        assert_eq!(aggregate("a", 3), (3, 6));
+    }
+}
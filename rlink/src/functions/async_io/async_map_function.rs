@@ -0,0 +1,366 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::select_all;
+use tokio::task::{JoinError, JoinHandle};
+use tokio::time::error::Elapsed;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema, Record};
+use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
+use crate::functions::async_io::AsyncFunction;
+use crate::functions::system::join::bytes_record;
+use crate::utils::stream::MemoryStream;
+
+/// The order the results of the `AsyncMapFunction` are emitted in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsyncOutputMode {
+    /// The results are emitted in the order of the input records
+    Ordered,
+    /// The results are emitted once the invocations complete, but they're never overtaken by
+    /// the watermarks
+    Unordered,
+}
+
+type InvocationResult = Result<anyhow::Result<Vec<Record>>, Elapsed>;
+
+struct PendingInvocation {
+    record: Record,
+    handle: JoinHandle<InvocationResult>,
+}
+
+/// The in-flight records of the checkpoint, the timestamps and the values
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct InFlightSnapshot {
+    records: Vec<(u64, Vec<u8>)>,
+}
+
+/// Invoke the `AsyncFunction` for the records concurrently, up to `capacity` invocations are
+/// in flight and the results are emitted by the `AsyncOutputMode`.
+///
+/// The in-flight records are kept across the barriers and checkpointed, they're invoked again
+/// when the task is restored from the checkpoint. The pending results are emitted before the
+/// watermarks and the stream status are forwarded
+pub struct AsyncMapFunction {
+    function: Arc<dyn AsyncFunction>,
+    capacity: usize,
+    timeout: Duration,
+    output_mode: AsyncOutputMode,
+
+    pending: VecDeque<PendingInvocation>,
+}
+
+impl AsyncMapFunction {
+    pub fn new<F>(function: F) -> Self
+    where
+        F: AsyncFunction + 'static,
+    {
+        AsyncMapFunction {
+            function: Arc::new(function),
+            capacity: 100,
+            timeout: Duration::from_secs(10),
+            output_mode: AsyncOutputMode::Ordered,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// The max invocations in flight, the input is blocked once it's reached
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// The invocation not completed in the `timeout` is passed to `AsyncFunction::timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn output_mode(mut self, output_mode: AsyncOutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+
+    fn invoke(&mut self, record: Record) {
+        let function = self.function.clone();
+        let timeout = self.timeout;
+        let input = record.clone();
+        let handle = tokio::spawn(async move {
+            let invocation = function.invoke(input);
+            tokio::time::timeout(timeout, invocation).await
+        });
+        self.pending.push_back(PendingInvocation { record, handle });
+    }
+
+    /// Take the completed invocations by the output mode until at most `max_pending` are in
+    /// flight, the earliest one of the output mode is awaited if there are more
+    async fn poll_completed(&mut self, max_pending: usize) -> Vec<Record> {
+        let mut records = Vec::new();
+        loop {
+            let completed = match self.output_mode {
+                AsyncOutputMode::Ordered => self
+                    .pending
+                    .front()
+                    .filter(|x| x.handle.is_finished())
+                    .map(|_| 0),
+                AsyncOutputMode::Unordered => {
+                    self.pending.iter().position(|x| x.handle.is_finished())
+                }
+            };
+
+            let (record, result) = match completed {
+                Some(index) => {
+                    let pending = self.pending.remove(index).unwrap();
+                    (pending.record, pending.handle.await)
+                }
+                None if self.pending.len() > max_pending => match self.output_mode {
+                    AsyncOutputMode::Ordered => {
+                        let pending = self.pending.pop_front().unwrap();
+                        (pending.record, pending.handle.await)
+                    }
+                    AsyncOutputMode::Unordered => {
+                        let handles = self.pending.iter_mut().map(|x| &mut x.handle);
+                        let (result, index, remaining) = select_all(handles).await;
+                        drop(remaining);
+                        (self.pending.remove(index).unwrap().record, result)
+                    }
+                },
+                None => break,
+            };
+
+            records.extend(self.complete(record, result));
+        }
+        records
+    }
+
+    fn complete(&self, record: Record, result: Result<InvocationResult, JoinError>) -> Vec<Record> {
+        let timestamp = record.timestamp;
+        let records = match result.expect("async invocation task panic") {
+            Ok(records) => records.expect("async invocation error"),
+            Err(_elapsed) => self
+                .function
+                .timeout(record)
+                .expect("async invocation timeout"),
+        };
+
+        records
+            .into_iter()
+            .map(|mut record| {
+                record.timestamp = timestamp;
+                record
+            })
+            .collect()
+    }
+
+    fn snapshot_in_flight(&self) -> anyhow::Result<CheckpointHandle> {
+        let snapshot = InFlightSnapshot {
+            records: self
+                .pending
+                .iter()
+                .map(|x| (x.record.timestamp, x.record.values.as_slice().to_vec()))
+                .collect(),
+        };
+        Ok(CheckpointHandle {
+            handle: serde_json::to_string(&snapshot)?,
+        })
+    }
+
+    /// Invoke the in-flight records of the checkpoint again
+    fn restore_in_flight(&mut self, handle: &CheckpointHandle) -> anyhow::Result<usize> {
+        if handle.handle.is_empty() {
+            return Ok(0);
+        }
+
+        let snapshot: InFlightSnapshot = serde_json::from_str(handle.handle.as_str())?;
+        let len = snapshot.records.len();
+        for (timestamp, values) in snapshot.records {
+            self.invoke(bytes_record(values.as_slice(), timestamp));
+        }
+        Ok(len)
+    }
+}
+
+#[async_trait]
+impl FlatMapFunction for AsyncMapFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let function = Arc::get_mut(&mut self.function).expect("the function is shared");
+        function.open(context).await?;
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        self.invoke(element.into_record());
+
+        let records = self.poll_completed(self.capacity).await;
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn flush(&mut self) -> Option<SendableElementStream> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let records = self.poll_completed(0).await;
+        Some(Box::pin(MemoryStream::new(records)))
+    }
+
+    fn flush_on_barrier(&self) -> bool {
+        false
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        if let Some(function) = Arc::get_mut(&mut self.function) {
+            function.close().await?;
+        }
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        self.function.schema(input_schema)
+    }
+}
+
+impl NamedFunction for AsyncMapFunction {
+    fn name(&self) -> &str {
+        "AsyncMapFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for AsyncMapFunction {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            let len = self
+                .restore_in_flight(handle)
+                .expect("restore the in-flight records error");
+            info!(
+                "restore {} in-flight records from checkpoint({:?})",
+                len, context.checkpoint_id
+            );
+        }
+    }
+
+    /// The in-flight records are kept across the barrier, see `flush_on_barrier`
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        match self.snapshot_in_flight() {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                error!("snapshot the in-flight records error. {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{FnSchema, Record};
+    use crate::core::function::Context;
+    use crate::functions::async_io::{AsyncFunction, AsyncMapFunction, AsyncOutputMode};
+
+    /// Emit the record after the delay of its value in millis
+    struct DelayFunction {}
+
+    #[async_trait]
+    impl AsyncFunction for DelayFunction {
+        async fn open(&mut self, _context: &Context) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn invoke(&self, mut record: Record) -> anyhow::Result<Vec<Record>> {
+            let delay = delay(&mut record);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(vec![record])
+        }
+
+        fn timeout(&self, _record: Record) -> anyhow::Result<Vec<Record>> {
+            Ok(vec![])
+        }
+
+        fn schema(&self, input_schema: FnSchema) -> FnSchema {
+            input_schema
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![Field::new("delay", DataType::UInt64)])
+    }
+
+    fn record(delay: u64) -> Record {
+        let mut record = Record::new();
+        record
+            .as_writer(schema().as_type_ids())
+            .set_u64(delay)
+            .unwrap();
+        record.timestamp = delay;
+        record
+    }
+
+    fn delay(record: &mut Record) -> u64 {
+        record.as_reader(schema().as_type_ids()).get_u64(0).unwrap()
+    }
+
+    async fn invoke_all(function: &mut AsyncMapFunction, delays: &[u64]) -> Vec<u64> {
+        for delay in delays {
+            function.invoke(record(*delay));
+        }
+        function
+            .poll_completed(0)
+            .await
+            .iter_mut()
+            .map(delay)
+            .collect()
+    }
+
+    #[tokio::test]
+    pub async fn async_map_output_mode_test() {
+        let mut function = AsyncMapFunction::new(DelayFunction {});
+        assert_eq!(
+            invoke_all(&mut function, &[300, 0, 150]).await,
+            vec![300, 0, 150]
+        );
+
+        let mut function =
+            AsyncMapFunction::new(DelayFunction {}).output_mode(AsyncOutputMode::Unordered);
+        assert_eq!(
+            invoke_all(&mut function, &[300, 0, 150]).await,
+            vec![0, 150, 300]
+        );
+    }
+
+    #[tokio::test]
+    pub async fn async_map_timeout_test() {
+        let mut function =
+            AsyncMapFunction::new(DelayFunction {}).timeout(Duration::from_millis(50));
+        assert_eq!(invoke_all(&mut function, &[0, 500]).await, vec![0]);
+    }
+
+    #[tokio::test]
+    pub async fn async_map_in_flight_test() {
+        let mut function = AsyncMapFunction::new(DelayFunction {});
+        function.invoke(record(100));
+        function.invoke(record(0));
+        let handle = function.snapshot_in_flight().unwrap();
+
+        let mut restored = AsyncMapFunction::new(DelayFunction {});
+        assert_eq!(restored.restore_in_flight(&handle).unwrap(), 2);
+        let mut records = restored.poll_completed(0).await;
+        assert_eq!(records.len(), 2);
+        assert_eq!(delay(&mut records[0]), 100);
+        assert_eq!(records[0].timestamp, 100);
+    }
+}
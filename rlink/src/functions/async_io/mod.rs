@@ -0,0 +1,30 @@
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::Context;
+
+pub mod async_map_function;
+pub use async_map_function::{AsyncMapFunction, AsyncOutputMode};
+
+/// The user code of the async I/O, e.g. the RPC or the database calls, see `AsyncMapFunction`.
+///
+/// The invocations of a task are issued concurrently, so `invoke` takes `&self` and the client
+/// should be shared by them, e.g. by a pool
+#[async_trait]
+pub trait AsyncFunction: Send + Sync {
+    async fn open(&mut self, context: &Context) -> anyhow::Result<()>;
+
+    /// Invoke the external call of the record, the returned records are emitted with the
+    /// timestamp of the input record
+    async fn invoke(&self, record: Record) -> anyhow::Result<Vec<Record>>;
+
+    /// The records emitted if the invocation isn't completed in the timeout, the job fails by
+    /// default
+    fn timeout(&self, _record: Record) -> anyhow::Result<Vec<Record>> {
+        Err(anyhow!("async invocation timeout"))
+    }
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema;
+}
@@ -1 +1,4 @@
 pub mod range_window_filter;
+
+pub mod sample_filter;
+pub use sample_filter::{SampleFilterFunction, Sampling};
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::Record;
+use crate::core::function::{Context, FilterFunction, NamedFunction};
+
+/// How the records are sampled by `TDataStream::sample`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sampling {
+    /// keep each record independently with the probability in (0, 1]
+    Probability(f64),
+    /// keep the first record of every `n` records of each task
+    EveryN(u64),
+}
+
+pub struct SampleFilterFunction {
+    sampling: Sampling,
+    counter: AtomicU64,
+}
+
+impl SampleFilterFunction {
+    pub fn new(sampling: Sampling) -> Self {
+        match sampling {
+            Sampling::Probability(probability) => {
+                if !(probability > 0.0 && probability <= 1.0) {
+                    panic!(
+                        "the sampling probability must be in (0, 1], got {}",
+                        probability
+                    );
+                }
+            }
+            Sampling::EveryN(n) => {
+                if n == 0 {
+                    panic!("the sampling interval must be positive");
+                }
+            }
+        }
+
+        SampleFilterFunction {
+            sampling,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn sample(&self) -> bool {
+        match self.sampling {
+            Sampling::Probability(probability) => rand::random::<f64>() < probability,
+            Sampling::EveryN(n) => self.counter.fetch_add(1, Ordering::Relaxed) % n == 0,
+        }
+    }
+}
+
+#[async_trait]
+impl FilterFunction for SampleFilterFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    async fn filter(&self, _record: &mut Record) -> bool {
+        self.sample()
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+}
+
+impl NamedFunction for SampleFilterFunction {
+    fn name(&self) -> &str {
+        "SampleFilterFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for SampleFilterFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::filter::sample_filter::{SampleFilterFunction, Sampling};
+
+    #[test]
+    pub fn sample_filter_test() {
+        let every_n = SampleFilterFunction::new(Sampling::EveryN(3));
+        let kept: Vec<bool> = (0..7).map(|_| every_n.sample()).collect();
+        assert_eq!(kept, vec![true, false, false, true, false, false, true]);
+
+        let all = SampleFilterFunction::new(Sampling::Probability(1.0));
+        assert!((0..1000).all(|_| all.sample()));
+
+        let half = SampleFilterFunction::new(Sampling::Probability(0.5));
+        let kept = (0..10_000).filter(|_| half.sample()).count();
+        assert!((4_500..=5_500).contains(&kept), "{}", kept);
+    }
+}
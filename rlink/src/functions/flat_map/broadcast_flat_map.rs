@@ -3,6 +3,8 @@ use crate::core::element::{Element, FnSchema};
 use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
 use crate::utils::stream::MemoryStream;
 
+pub(crate) const BROADCAST_FUNCTION_NAME: &str = "BroadcastFlagMapFunction";
+
 pub struct BroadcastFlagMapFunction {
     child_job_parallelism: u16,
 }
@@ -50,7 +52,7 @@ impl FlatMapFunction for BroadcastFlagMapFunction {
 
 impl NamedFunction for BroadcastFlagMapFunction {
     fn name(&self) -> &str {
-        "BroadcastFlagMapFunction"
+        BROADCAST_FUNCTION_NAME
     }
 }
 
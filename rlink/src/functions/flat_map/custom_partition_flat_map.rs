@@ -0,0 +1,96 @@
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema};
+use crate::core::function::{
+    Context, FlatMapFunction, KeySelectorFunction, NamedFunction, Partitioner,
+    SendableElementStream,
+};
+use crate::utils::stream::MemoryStream;
+
+pub(crate) const CUSTOM_PARTITION_FUNCTION_NAME: &str = "CustomPartitionFlagMapFunction";
+
+/// Flag the child task of the records by the `Partitioner` of their keys, see
+/// `TDataStream::partition_custom`
+pub struct CustomPartitionFlagMapFunction<P, F> {
+    partitioner: P,
+    key_selector: F,
+    child_job_parallelism: u16,
+}
+
+impl<P, F> CustomPartitionFlagMapFunction<P, F>
+where
+    P: Partitioner,
+    F: KeySelectorFunction,
+{
+    pub fn new(partitioner: P, key_selector: F) -> Self {
+        CustomPartitionFlagMapFunction {
+            partitioner,
+            key_selector,
+            child_job_parallelism: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl<P, F> FlatMapFunction for CustomPartitionFlagMapFunction<P, F>
+where
+    P: Partitioner,
+    F: KeySelectorFunction,
+{
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.child_job_parallelism = context.children.len() as u16;
+        self.key_selector.open(context).await
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+
+        let mut key = self.key_selector.get_key(&mut record).await;
+        let partition_num = self
+            .partitioner
+            .partition(&mut key, self.child_job_parallelism);
+        if partition_num >= self.child_job_parallelism {
+            panic!(
+                "the partition {} out of the child parallelism {}",
+                partition_num, self.child_job_parallelism
+            );
+        }
+
+        record.partition_num = partition_num;
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.key_selector.close().await
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        input_schema
+    }
+}
+
+impl<P, F> NamedFunction for CustomPartitionFlagMapFunction<P, F> {
+    fn name(&self) -> &str {
+        CUSTOM_PARTITION_FUNCTION_NAME
+    }
+}
+
+#[async_trait]
+impl<P, F> CheckpointFunction for CustomPartitionFlagMapFunction<P, F>
+where
+    P: Partitioner,
+    F: KeySelectorFunction,
+{
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
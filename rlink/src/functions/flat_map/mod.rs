@@ -1,5 +1,31 @@
+use crate::functions::flat_map::broadcast_flat_map::BROADCAST_FUNCTION_NAME;
+use crate::functions::flat_map::custom_partition_flat_map::CUSTOM_PARTITION_FUNCTION_NAME;
+use crate::functions::flat_map::rescale_flat_map::RESCALE_FUNCTION_NAME;
+use crate::functions::flat_map::round_robin_flat_map::ROUND_ROBIN_FUNCTION_NAME;
+
 pub mod broadcast_flat_map;
 pub use broadcast_flat_map::BroadcastFlagMapFunction;
 
 pub mod round_robin_flat_map;
 pub use round_robin_flat_map::RoundRobinFlagMapFunction;
+
+pub mod rescale_flat_map;
+pub use rescale_flat_map::RescaleFlagMapFunction;
+
+pub mod custom_partition_flat_map;
+pub use custom_partition_flat_map::CustomPartitionFlagMapFunction;
+
+pub mod throttle_flat_map;
+pub use throttle_flat_map::ThrottleFlatMapFunction;
+
+/// The function flags the child task of the records by the `partition_num`, the operator after
+/// it is never chained and the records are sent to the child tasks by the partitions
+pub(crate) fn is_partition_function(operator_name: &str) -> bool {
+    [
+        BROADCAST_FUNCTION_NAME,
+        ROUND_ROBIN_FUNCTION_NAME,
+        RESCALE_FUNCTION_NAME,
+        CUSTOM_PARTITION_FUNCTION_NAME,
+    ]
+    .contains(&operator_name)
+}
@@ -0,0 +1,109 @@
+use std::ops::Range;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema};
+use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
+use crate::utils::stream::MemoryStream;
+
+pub(crate) const RESCALE_FUNCTION_NAME: &str = "RescaleFlagMapFunction";
+
+/// Distribute the records round-robin to a subset of the child tasks, the child tasks are
+/// divided into groups by the ratio of the parallelism and each task only sends to its own group
+pub struct RescaleFlagMapFunction {
+    partitions: Range<u16>,
+    partition_num: u16,
+}
+
+impl RescaleFlagMapFunction {
+    pub fn new() -> Self {
+        RescaleFlagMapFunction {
+            partitions: 0..0,
+            partition_num: 0,
+        }
+    }
+}
+
+/// The child tasks of the task `task_number` of `num_tasks`, there's at least one
+fn rescale_partitions(task_number: u16, num_tasks: u16, child_parallelism: u16) -> Range<u16> {
+    let (task_number, num_tasks) = (task_number as u32, num_tasks as u32);
+    let child_parallelism = child_parallelism as u32;
+
+    let start = task_number * child_parallelism / num_tasks;
+    let end = (task_number + 1) * child_parallelism / num_tasks;
+    start as u16..end.max(start + 1) as u16
+}
+
+#[async_trait]
+impl FlatMapFunction for RescaleFlagMapFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.partitions = rescale_partitions(
+            context.task_id.task_number,
+            context.task_id.num_tasks,
+            context.children.len() as u16,
+        );
+        self.partition_num = self.partitions.start;
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+
+        if self.partition_num == self.partitions.end {
+            self.partition_num = self.partitions.start;
+        }
+
+        record.partition_num = self.partition_num;
+        self.partition_num += 1;
+
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        input_schema
+    }
+}
+
+impl NamedFunction for RescaleFlagMapFunction {
+    fn name(&self) -> &str {
+        RESCALE_FUNCTION_NAME
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for RescaleFlagMapFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::flat_map::rescale_flat_map::rescale_partitions;
+
+    #[test]
+    pub fn rescale_partitions_test() {
+        // scale up: 2 -> 6
+        assert_eq!(rescale_partitions(0, 2, 6), 0..3);
+        assert_eq!(rescale_partitions(1, 2, 6), 3..6);
+
+        // scale down: 4 -> 2
+        assert_eq!(rescale_partitions(0, 4, 2), 0..1);
+        assert_eq!(rescale_partitions(1, 4, 2), 0..1);
+        assert_eq!(rescale_partitions(2, 4, 2), 1..2);
+        assert_eq!(rescale_partitions(3, 4, 2), 1..2);
+    }
+}
@@ -3,6 +3,8 @@ use crate::core::element::{Element, FnSchema};
 use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
 use crate::utils::stream::MemoryStream;
 
+pub(crate) const ROUND_ROBIN_FUNCTION_NAME: &str = "RoundRobinFlagMapFunction";
+
 pub struct RoundRobinFlagMapFunction {
     child_job_parallelism: u16,
     partition_num: u16,
@@ -48,7 +50,7 @@ impl FlatMapFunction for RoundRobinFlagMapFunction {
 
 impl NamedFunction for RoundRobinFlagMapFunction {
     fn name(&self) -> &str {
-        "RoundRobinFlagMapFunction"
+        ROUND_ROBIN_FUNCTION_NAME
     }
 }
 
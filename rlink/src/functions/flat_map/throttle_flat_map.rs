@@ -0,0 +1,155 @@
+use std::time::{Duration, Instant};
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema};
+use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
+use crate::utils::stream::MemoryStream;
+
+/// The token bucket refilled by `rate` tokens per second up to `capacity` tokens
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, capacity: f64, now: Instant) -> Self {
+        TokenBucket {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Take one token, or return how long to wait until the next token is available. The token
+    /// is reserved anyway, so the caller emits the record after the waiting
+    fn acquire(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(-self.tokens / self.rate))
+        }
+    }
+}
+
+/// Limit the rate of the records to `records_per_second` of the operator, which is shared
+/// equally by the parallel tasks. Up to `burst` records of each task are passed without
+/// waiting after an idle period, the task is suspended once the tokens are used up, so the
+/// backpressure slows down the upstream operators instead of dropping the records
+pub struct ThrottleFlatMapFunction {
+    records_per_second: u64,
+    burst: u64,
+    bucket: Option<TokenBucket>,
+}
+
+impl ThrottleFlatMapFunction {
+    pub fn new(records_per_second: u64, burst: u64) -> Self {
+        if records_per_second == 0 {
+            panic!("the records per second of throttle must be positive");
+        }
+        ThrottleFlatMapFunction {
+            records_per_second,
+            burst: burst.max(1),
+            bucket: None,
+        }
+    }
+}
+
+#[async_trait]
+impl FlatMapFunction for ThrottleFlatMapFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let num_tasks = context.task_id.num_tasks().max(1) as f64;
+        self.bucket = Some(TokenBucket::new(
+            self.records_per_second as f64 / num_tasks,
+            self.burst as f64,
+            Instant::now(),
+        ));
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let record = element.into_record();
+
+        let wait = self
+            .bucket
+            .as_mut()
+            .and_then(|bucket| bucket.acquire(Instant::now()));
+        if let Some(wait) = wait {
+            tokio::time::sleep(wait).await;
+        }
+
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        input_schema
+    }
+}
+
+impl NamedFunction for ThrottleFlatMapFunction {
+    fn name(&self) -> &str {
+        "ThrottleFlatMapFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for ThrottleFlatMapFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::functions::flat_map::throttle_flat_map::TokenBucket;
+
+    #[test]
+    pub fn token_bucket_test() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, 3.0, now);
+
+        // the burst is passed without waiting
+        for _ in 0..3 {
+            assert_eq!(bucket.acquire(now), None);
+        }
+
+        // then each record waits for 100ms more than the previous one
+        let wait = bucket.acquire(now).unwrap();
+        assert!((wait.as_secs_f64() - 0.1).abs() < 1e-9);
+        let wait = bucket.acquire(now).unwrap();
+        assert!((wait.as_secs_f64() - 0.2).abs() < 1e-9);
+
+        // the tokens are refilled up to the burst after an idle period
+        let later = now + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert_eq!(bucket.acquire(later), None);
+        }
+        assert!(bucket.acquire(later).is_some());
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::core::element::Record;
+
+struct CacheEntry {
+    value: Option<Record>,
+    cached_at: Instant,
+    seq: u64,
+}
+
+/// The looked up records by the key bytes, the missing keys are cached as `None` so they are
+/// not looked up again until expired. The earliest cached entry is evicted once it's full
+pub struct LookupCache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    entries: HashMap<Vec<u8>, CacheEntry>,
+    /// the keys in the order of caching, the `seq` of the replaced entries are stale
+    order: VecDeque<(u64, Vec<u8>)>,
+    seq: u64,
+}
+
+impl LookupCache {
+    /// The cache is disabled if the `capacity` is 0, the entries never expire without the `ttl`
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        LookupCache {
+            capacity,
+            ttl,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            seq: 0,
+        }
+    }
+
+    /// The cached value, `None` if the key is not cached or expired
+    pub fn get(&mut self, key: &[u8]) -> Option<Option<Record>> {
+        let entry = self.entries.get(key)?;
+        if let Some(ttl) = self.ttl {
+            if entry.cached_at.elapsed() >= ttl {
+                self.entries.remove(key);
+                return None;
+            }
+        }
+        Some(entry.value.clone())
+    }
+
+    pub fn put(&mut self, key: Vec<u8>, value: Option<Record>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            match self.order.pop_front() {
+                Some((seq, evicted)) => {
+                    if self.entries.get(&evicted).map(|x| x.seq) == Some(seq) {
+                        self.entries.remove(&evicted);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        self.seq += 1;
+        self.order.push_back((self.seq, key.clone()));
+        self.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                cached_at: Instant::now(),
+                seq: self.seq,
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::element::Record;
+    use crate::functions::lookup::cache::LookupCache;
+
+    #[test]
+    pub fn lookup_cache_test() {
+        let mut cache = LookupCache::new(2, None);
+        cache.put(b"a".to_vec(), Some(Record::new()));
+        cache.put(b"b".to_vec(), None);
+        assert_eq!(cache.get(b"a"), Some(Some(Record::new())));
+        assert_eq!(cache.get(b"b"), Some(None));
+
+        // `a` is the earliest cached
+        cache.put(b"c".to_vec(), None);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(b"a"), None);
+        assert_eq!(cache.get(b"b"), Some(None));
+
+        // the replaced `b` is cached after `c`
+        cache.put(b"b".to_vec(), Some(Record::new()));
+        cache.put(b"d".to_vec(), None);
+        assert_eq!(cache.get(b"c"), None);
+        assert_eq!(cache.get(b"b"), Some(Some(Record::new())));
+
+        let mut cache = LookupCache::new(2, Some(Duration::from_millis(0)));
+        cache.put(b"a".to_vec(), None);
+        assert_eq!(cache.get(b"a"), None);
+
+        let mut cache = LookupCache::new(0, None);
+        cache.put(b"a".to_vec(), None);
+        assert!(cache.is_empty());
+    }
+}
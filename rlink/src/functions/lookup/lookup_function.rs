@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::{DataType, Field, Schema};
+use crate::core::element::{Element, FnSchema, Record};
+use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
+use crate::functions::column_locate::{ColumnLocate, ColumnLocateBuilder};
+use crate::functions::lookup::cache::LookupCache;
+use crate::functions::lookup::LookupBackend;
+use crate::utils::stream::MemoryStream;
+
+/// What to emit if the key is not found in the backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissPolicy {
+    /// Append the null fields, the zero numbers, the empty strings and the empty binaries
+    Null,
+    /// Drop the record
+    Drop,
+}
+
+enum LookupResult {
+    Cached(Option<Record>),
+    Pending(JoinHandle<anyhow::Result<Option<Record>>>),
+}
+
+struct PendingLookup {
+    key: Vec<u8>,
+    record: Record,
+    result: LookupResult,
+}
+
+/// Enrich the records with the records of the same key in the external store, the fields of
+/// the looked up records are appended to the input records.
+///
+/// Up to `concurrency` lookups are issued concurrently and the records are emitted in the
+/// order of the input. The pending records are emitted before the barriers and the
+/// watermarks are forwarded, so a checkpoint never misses them.
+pub struct LookupFunction {
+    key_columns: Vec<ColumnLocate>,
+    backend: Arc<dyn LookupBackend>,
+    concurrency: usize,
+    timeout: Duration,
+    cache_size: usize,
+    cache_ttl: Option<Duration>,
+    miss_policy: MissPolicy,
+
+    schema: Schema,
+    key_schema: Schema,
+    key_indices: Vec<usize>,
+    null_record: Record,
+    cache: LookupCache,
+    pending: VecDeque<PendingLookup>,
+}
+
+impl LookupFunction {
+    /// Look up the key of the `key_columns` in the `backend`
+    pub fn new<T, B>(key_columns: Vec<T>, backend: B) -> Self
+    where
+        T: ColumnLocateBuilder,
+        B: LookupBackend + 'static,
+    {
+        LookupFunction {
+            key_columns: key_columns.into_iter().map(|x| x.build()).collect(),
+            backend: Arc::new(backend),
+            concurrency: 100,
+            timeout: Duration::from_secs(10),
+            cache_size: 0,
+            cache_ttl: None,
+            miss_policy: MissPolicy::Null,
+            schema: Schema::empty(),
+            key_schema: Schema::empty(),
+            key_indices: vec![],
+            null_record: Record::new(),
+            cache: LookupCache::new(0, None),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// The max lookups in flight, 1 to look up the records one by one
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// The job fails if a lookup is not completed in the `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Cache up to `cache_size` keys for the `cache_ttl`, the missing keys are cached too
+    pub fn cache(mut self, cache_size: usize, cache_ttl: Duration) -> Self {
+        self.cache_size = cache_size;
+        self.cache_ttl = Some(cache_ttl);
+        self
+    }
+
+    pub fn miss_policy(mut self, miss_policy: MissPolicy) -> Self {
+        self.miss_policy = miss_policy;
+        self
+    }
+
+    fn key(&self, record: &mut Record) -> Record {
+        let mut key = Record::with_capacity(record.len());
+        let mut writer = key.as_writer(self.key_schema.as_type_ids());
+        let reader = record.as_reader(self.schema.as_type_ids());
+        for index in &self.key_indices {
+            writer
+                .set_bytes_raw(reader.get_bytes_raw(*index).unwrap())
+                .unwrap();
+        }
+        key
+    }
+
+    /// Take the completed lookups in order until at most `max_pending` are in flight, the
+    /// earliest is awaited if there are more
+    async fn poll_completed(&mut self, max_pending: usize) -> Vec<Record> {
+        let mut records = Vec::new();
+        while let Some(front) = self.pending.front() {
+            let completed = match &front.result {
+                LookupResult::Cached(_) => true,
+                LookupResult::Pending(handle) => handle.is_finished(),
+            };
+            if !completed && self.pending.len() <= max_pending {
+                break;
+            }
+
+            let pending = self.pending.pop_front().unwrap();
+            if let Some(record) = self.complete(pending).await {
+                records.push(record);
+            }
+        }
+        records
+    }
+
+    async fn complete(&mut self, pending: PendingLookup) -> Option<Record> {
+        let value = match pending.result {
+            LookupResult::Cached(value) => value,
+            LookupResult::Pending(handle) => {
+                let value = handle
+                    .await
+                    .expect("lookup task panic")
+                    .expect("lookup error");
+                self.cache.put(pending.key, value.clone());
+                value
+            }
+        };
+
+        let value = match value {
+            Some(value) => value,
+            None => match self.miss_policy {
+                MissPolicy::Null => self.null_record.clone(),
+                MissPolicy::Drop => return None,
+            },
+        };
+
+        let mut record = pending.record;
+        record.extend(value).unwrap();
+        Some(record)
+    }
+}
+
+/// The record of the zero values of the `schema`
+fn null_record(schema: &Schema) -> Record {
+    let mut record = Record::new();
+    let mut writer = record.as_writer(schema.as_type_ids());
+    for field in schema.fields() {
+        match field.data_type() {
+            DataType::Boolean => writer.set_bool(false),
+            DataType::Int8 => writer.set_i8(0),
+            DataType::UInt8 => writer.set_u8(0),
+            DataType::Int16 => writer.set_i16(0),
+            DataType::UInt16 => writer.set_u16(0),
+            DataType::Int32 => writer.set_i32(0),
+            DataType::UInt32 => writer.set_u32(0),
+            DataType::Int64 => writer.set_i64(0),
+            DataType::UInt64 => writer.set_u64(0),
+            DataType::Float32 => writer.set_f32(0f32),
+            DataType::Float64 => writer.set_f64(0f64),
+            DataType::Binary => writer.set_binary(&[]),
+            DataType::String => writer.set_str(""),
+        }
+        .unwrap();
+    }
+    record
+}
+
+#[async_trait]
+impl FlatMapFunction for LookupFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.schema = context.input_schema.clone().into();
+
+        let mut key_fields: Vec<Field> = Vec::with_capacity(self.key_columns.len());
+        let mut key_indices = Vec::with_capacity(self.key_columns.len());
+        for key_column in &self.key_columns {
+            let (index, field) = key_column.to_column(&self.schema);
+            key_indices.push(index);
+            key_fields.push(field.clone());
+        }
+        self.key_schema = Schema::new(key_fields);
+        self.key_indices = key_indices;
+
+        let backend = Arc::get_mut(&mut self.backend).expect("the backend is shared");
+        backend.open(&self.key_schema, context).await?;
+
+        self.null_record = null_record(&self.backend.schema());
+        self.cache = LookupCache::new(self.cache_size, self.cache_ttl);
+
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+        let key = self.key(&mut record);
+        let cache_key = key.values.as_slice().to_vec();
+
+        let result = match self.cache.get(cache_key.as_slice()) {
+            Some(value) => LookupResult::Cached(value),
+            None => {
+                let backend = self.backend.clone();
+                let timeout = self.timeout;
+                LookupResult::Pending(tokio::spawn(async move {
+                    tokio::time::timeout(timeout, backend.lookup(key))
+                        .await
+                        .map_err(|_e| anyhow!("lookup timeout after {:?}", timeout))?
+                }))
+            }
+        };
+        self.pending.push_back(PendingLookup {
+            key: cache_key,
+            record,
+            result,
+        });
+
+        let records = self.poll_completed(self.concurrency).await;
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn flush(&mut self) -> Option<SendableElementStream> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let records = self.poll_completed(0).await;
+        Some(Box::pin(MemoryStream::new(records)))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        if let Some(backend) = Arc::get_mut(&mut self.backend) {
+            backend.close().await?;
+        }
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        let mut schema: Schema = input_schema.into();
+        schema.merge(&self.backend.schema());
+        FnSchema::from(&schema)
+    }
+}
+
+impl NamedFunction for LookupFunction {
+    fn name(&self) -> &str {
+        "LookupFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for LookupFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    /// The pending lookups are flushed before the barrier, nothing is held by the checkpoint
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::functions::lookup::lookup_function::null_record;
+
+    #[test]
+    pub fn null_record_test() {
+        let schema = Schema::new(vec![
+            Field::new("name", DataType::String),
+            Field::new("age", DataType::Int32),
+        ]);
+        let mut record = null_record(&schema);
+        let reader = record.as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_str(0).unwrap(), "");
+        assert_eq!(reader.get_i32(1).unwrap(), 0);
+    }
+}
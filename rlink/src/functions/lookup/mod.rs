@@ -0,0 +1,27 @@
+use crate::core::data_types::Schema;
+use crate::core::element::Record;
+use crate::core::function::Context;
+
+pub mod cache;
+pub mod lookup_function;
+pub use lookup_function::{LookupFunction, MissPolicy};
+
+/// The external store of the dimension records, e.g. a table, a redis or a http service.
+///
+/// The lookups of a task are issued concurrently, so `lookup` takes `&self` and the backend
+/// should share its connections, e.g. by a pool
+#[async_trait]
+pub trait LookupBackend: Send + Sync {
+    /// Connect to the store, the keys are the records of the `key_schema`
+    async fn open(&mut self, key_schema: &Schema, context: &Context) -> anyhow::Result<()>;
+
+    /// Look up the record of the `key`, `None` if the key is not found
+    async fn lookup(&self, key: Record) -> anyhow::Result<Option<Record>>;
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// The schema of the looked up records, the fields are appended to the input records
+    fn schema(&self) -> Schema;
+}
@@ -1,7 +1,10 @@
+pub mod aggregate;
+pub mod async_io;
 pub mod column_locate;
 pub mod filter;
 pub mod flat_map;
 pub mod key_selector;
+pub mod lookup;
 pub mod percentile;
 pub mod reduce;
 pub mod sink;
@@ -0,0 +1,270 @@
+use bytes::{Buf, BufMut, BytesMut};
+
+/// The High Dynamic Range histogram of the non-negative integer values in
+/// `[0, highest_trackable_value]`, e.g. the latencies in microseconds. The values are counted
+/// in the buckets whose widths keep `significant_digits` decimal digits of the values, so the
+/// quantiles have the relative error of `10^-significant_digits` at most. The values larger
+/// than `highest_trackable_value` are counted as it. The histograms are mergeable even if their
+/// configurations are different
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct HdrHistogram {
+    significant_digits: u8,
+    highest_trackable_value: u64,
+
+    sub_bucket_half_count_magnitude: u32,
+    sub_bucket_half_count: u64,
+    sub_bucket_mask: u64,
+
+    counts: Vec<u64>,
+    total_count: u64,
+    min: u64,
+    max: u64,
+}
+
+impl HdrHistogram {
+    pub fn new(significant_digits: u8, highest_trackable_value: u64) -> Self {
+        if !(1..=5).contains(&significant_digits) {
+            panic!(
+                "the significant digits of HDR histogram must be in [1, 5], got {}",
+                significant_digits
+            );
+        }
+        if highest_trackable_value < 2 {
+            panic!("the highest trackable value of HDR histogram must be at least 2");
+        }
+
+        let largest_single_unit_resolution = 2 * 10u64.pow(significant_digits as u32);
+        let sub_bucket_count_magnitude = 64 - (largest_single_unit_resolution - 1).leading_zeros();
+        let sub_bucket_half_count_magnitude = sub_bucket_count_magnitude.max(1) - 1;
+        let sub_bucket_count = 1u64 << (sub_bucket_half_count_magnitude + 1);
+        let sub_bucket_half_count = sub_bucket_count / 2;
+
+        // the buckets of the doubled value ranges until the highest trackable value
+        let mut smallest_untrackable_value = sub_bucket_count;
+        let mut bucket_count = 1;
+        while smallest_untrackable_value <= highest_trackable_value {
+            if smallest_untrackable_value > u64::MAX / 2 {
+                bucket_count += 1;
+                break;
+            }
+            smallest_untrackable_value <<= 1;
+            bucket_count += 1;
+        }
+        let counts_len = (bucket_count + 1) * sub_bucket_half_count as usize;
+
+        HdrHistogram {
+            significant_digits,
+            highest_trackable_value,
+            sub_bucket_half_count_magnitude,
+            sub_bucket_half_count,
+            sub_bucket_mask: sub_bucket_count - 1,
+            counts: vec![0; counts_len],
+            total_count: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    pub fn significant_digits(&self) -> u8 {
+        self.significant_digits
+    }
+
+    pub fn highest_trackable_value(&self) -> u64 {
+        self.highest_trackable_value
+    }
+
+    pub fn count(&self) -> u64 {
+        self.total_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_count == 0
+    }
+
+    pub fn record(&mut self, value: u64) {
+        self.record_n(value, 1);
+    }
+
+    pub fn record_n(&mut self, value: u64, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let value = value.min(self.highest_trackable_value);
+        let index = self.counts_index(value);
+        self.counts[index] += count;
+        self.total_count += count;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    pub fn merge(&mut self, other: &HdrHistogram) {
+        if self.counts.len() == other.counts.len()
+            && self.significant_digits == other.significant_digits
+        {
+            for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+                *count += *other;
+            }
+            self.total_count += other.total_count;
+            if !other.is_empty() {
+                self.min = self.min.min(other.min);
+                self.max = self.max.max(other.max);
+            }
+        } else {
+            for (index, count) in other.counts.iter().enumerate() {
+                if *count > 0 {
+                    self.record_n(other.value_from_index(index), *count);
+                }
+            }
+        }
+    }
+
+    fn bucket_index(&self, value: u64) -> u32 {
+        let leading_zero_count_base = 64 - self.sub_bucket_half_count_magnitude - 1;
+        leading_zero_count_base - (value | self.sub_bucket_mask).leading_zeros()
+    }
+
+    fn counts_index(&self, value: u64) -> usize {
+        let bucket_index = self.bucket_index(value);
+        let sub_bucket_index = value >> bucket_index;
+        let bucket_base_index = (bucket_index as u64 + 1) << self.sub_bucket_half_count_magnitude;
+        (bucket_base_index + sub_bucket_index - self.sub_bucket_half_count) as usize
+    }
+
+    /// The lowest value counted by the index
+    fn value_from_index(&self, index: usize) -> u64 {
+        let index = index as u64;
+        let mut bucket_index = (index >> self.sub_bucket_half_count_magnitude) as i64 - 1;
+        let mut sub_bucket_index =
+            (index & (self.sub_bucket_half_count - 1)) + self.sub_bucket_half_count;
+        if bucket_index < 0 {
+            sub_bucket_index -= self.sub_bucket_half_count;
+            bucket_index = 0;
+        }
+        sub_bucket_index << bucket_index
+    }
+
+    /// The highest value counted by the same index as the `value`
+    fn highest_equivalent_value(&self, value: u64) -> u64 {
+        let bucket_index = self.bucket_index(value);
+        let sub_bucket_index = value >> bucket_index;
+        let adjusted_bucket_index = if sub_bucket_index > self.sub_bucket_mask {
+            bucket_index + 1
+        } else {
+            bucket_index
+        };
+        let lowest_equivalent_value = sub_bucket_index << bucket_index;
+        lowest_equivalent_value + (1u64 << adjusted_bucket_index) - 1
+    }
+
+    /// The estimated value of the quantile in [0, 1], `None` if no value is recorded
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        if q <= 0.0 {
+            return Some(self.min as f64);
+        }
+
+        let count_at_quantile = ((q.min(1.0) * self.total_count as f64).ceil() as u64).max(1);
+        let mut total = 0;
+        for (index, count) in self.counts.iter().enumerate() {
+            total += *count;
+            if total >= count_at_quantile {
+                let value = self.highest_equivalent_value(self.value_from_index(index));
+                return Some(value.min(self.max) as f64);
+            }
+        }
+        Some(self.max as f64)
+    }
+
+    /// Serialize the histogram with the non-zero counts only, see `from_bytes`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let non_zeros = self.counts.iter().filter(|x| **x > 0).count();
+        let mut buffer = BytesMut::with_capacity(29 + non_zeros * 12);
+        buffer.put_u8(self.significant_digits);
+        buffer.put_u64_le(self.highest_trackable_value);
+        buffer.put_u64_le(self.min);
+        buffer.put_u64_le(self.max);
+        buffer.put_u32_le(non_zeros as u32);
+        for (index, count) in self.counts.iter().enumerate() {
+            if *count > 0 {
+                buffer.put_u32_le(index as u32);
+                buffer.put_u64_le(*count);
+            }
+        }
+        buffer.to_vec()
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.remaining() < 29 {
+            return Err(anyhow!("HDR histogram bytes too short"));
+        }
+
+        let significant_digits = bytes.get_u8();
+        let highest_trackable_value = bytes.get_u64_le();
+        if !(1..=5).contains(&significant_digits) || highest_trackable_value < 2 {
+            return Err(anyhow!(
+                "illegal HDR histogram configuration ({}, {})",
+                significant_digits,
+                highest_trackable_value
+            ));
+        }
+
+        let mut histogram = HdrHistogram::new(significant_digits, highest_trackable_value);
+        histogram.min = bytes.get_u64_le();
+        histogram.max = bytes.get_u64_le();
+        let non_zeros = bytes.get_u32_le() as usize;
+        if bytes.remaining() < non_zeros * 12 {
+            return Err(anyhow!("HDR histogram bytes too short"));
+        }
+        for _ in 0..non_zeros {
+            let index = bytes.get_u32_le() as usize;
+            let count = bytes.get_u64_le();
+            if index >= histogram.counts.len() {
+                return Err(anyhow!("HDR histogram index {} out of range", index));
+            }
+            histogram.counts[index] += count;
+            histogram.total_count += count;
+        }
+        Ok(histogram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::percentile::hdr_histogram::HdrHistogram;
+
+    #[test]
+    pub fn hdr_histogram_test() {
+        let mut histogram = HdrHistogram::new(3, 3_600_000_000);
+        assert_eq!(histogram.quantile(0.5), None);
+
+        for i in 1..=100_000u64 {
+            histogram.record(i);
+        }
+        assert_eq!(histogram.count(), 100_000);
+        assert_eq!(histogram.quantile(0.0), Some(1.0));
+        assert_eq!(histogram.quantile(1.0), Some(100_000.0));
+        for q in [0.01, 0.5, 0.9, 0.99, 0.999] {
+            let expected = q * 100_000.0;
+            let estimate = histogram.quantile(q).unwrap();
+            assert!(
+                (estimate - expected).abs() <= expected * 0.001,
+                "{}",
+                estimate
+            );
+        }
+
+        // the values out of the range are counted as the highest trackable value
+        let mut small = HdrHistogram::new(2, 1000);
+        small.record(7);
+        small.record(5000);
+        assert_eq!(small.quantile(1.0), Some(1000.0));
+
+        // the histograms of the different configurations are merged by the values
+        let mut merged = HdrHistogram::from_bytes(histogram.to_bytes().as_slice()).unwrap();
+        merged.merge(&small);
+        assert_eq!(merged.count(), 100_002);
+        assert_eq!(merged.quantile(0.0), Some(1.0));
+    }
+}
@@ -1,3 +1,69 @@
+pub mod hdr_histogram;
+pub mod t_digest;
+
+pub use hdr_histogram::HdrHistogram;
+pub use t_digest::TDigest;
+
+/// A mergeable sketch of the distribution of the values estimating the quantiles, the sketches
+/// are kept in the accumulators and the keyed states as bytes
+pub trait QuantileSketch: Clone + Send + Sync {
+    fn add(&mut self, value: f64);
+
+    fn merge(&mut self, other: &Self);
+
+    /// The estimated value of the quantile in [0, 1], `None` if no value is added
+    fn quantile(&self, q: f64) -> Option<f64>;
+
+    fn to_bytes(&self) -> Vec<u8>;
+
+    fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self>;
+}
+
+impl QuantileSketch for TDigest {
+    fn add(&mut self, value: f64) {
+        TDigest::add(self, value)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        TDigest::merge(self, other)
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        TDigest::quantile(self, q)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        TDigest::to_bytes(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        TDigest::from_bytes(bytes)
+    }
+}
+
+impl QuantileSketch for HdrHistogram {
+    /// The negative values are recorded as 0 and the fractions are rounded
+    fn add(&mut self, value: f64) {
+        self.record(value.max(0.0).round() as u64)
+    }
+
+    fn merge(&mut self, other: &Self) {
+        HdrHistogram::merge(self, other)
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        HdrHistogram::quantile(self, q)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        HdrHistogram::to_bytes(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        HdrHistogram::from_bytes(bytes)
+    }
+}
+
 pub fn get_percentile_capacity(scale: &'static [f64]) -> usize {
     (scale.len() + 1) << 3
 }
@@ -121,9 +187,13 @@ impl<'a> PercentileWriter<'a> {
         return None;
     }
 
+    /// Add the counters of the `percentile`, the counters are 8 bytes each
     pub fn merge(&mut self, percentile: &PercentileReader) {
-        for i in 0..self.count_container.len() {
-            self.count_container[i] += percentile.count_container[i];
+        let mut index = 0;
+        while index < self.count_container.len() {
+            let n = self.read(index) + percentile.read(index);
+            self.write(index, n);
+            index += 8;
         }
     }
 }
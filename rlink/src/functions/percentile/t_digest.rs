@@ -0,0 +1,296 @@
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+
+use bytes::{Buf, BufMut, BytesMut};
+
+/// The compression of `TDigest::default`, about 0.1% error of the extreme quantiles and 1% of
+/// the median with at most ~100 centroids
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+impl Centroid {
+    fn add(&mut self, other: &Centroid) {
+        let weight = self.weight + other.weight;
+        self.mean += (other.mean - self.mean) * other.weight / weight;
+        self.weight = weight;
+    }
+}
+
+/// The merging t-digest of Dunning, a sketch of the distribution of the values estimating the
+/// quantiles with the relative accuracy at the tails. The larger `compression` the more
+/// centroids and the more accurate. The digests are mergeable, e.g. the digests of the window
+/// panes or of the partitions before rescaling
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TDigest {
+    compression: f64,
+    /// the merged centroids ordered by the means
+    centroids: Vec<Centroid>,
+    /// the values added since the latest compression
+    unmerged: Vec<Centroid>,
+    count: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        TDigest::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        if compression < 10.0 {
+            panic!(
+                "the compression of t-digest must be at least 10, got {}",
+                compression
+            );
+        }
+        TDigest {
+            compression,
+            centroids: vec![],
+            unmerged: vec![],
+            count: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn compression(&self) -> f64 {
+        self.compression
+    }
+
+    /// The count of the added values
+    pub fn count(&self) -> u64 {
+        self.count as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0.0
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.add_centroid(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+    }
+
+    fn add_centroid(&mut self, centroid: Centroid) {
+        self.count += centroid.weight;
+        self.min = self.min.min(centroid.mean);
+        self.max = self.max.max(centroid.mean);
+        self.unmerged.push(centroid);
+
+        if self.unmerged.len() >= self.buffer_size() {
+            self.compress();
+        }
+    }
+
+    fn buffer_size(&self) -> usize {
+        (self.compression * 5.0) as usize
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        for centroid in other.centroids.iter().chain(other.unmerged.iter()) {
+            self.add_centroid(centroid.clone());
+        }
+        if !other.is_empty() {
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+    }
+
+    /// Merge the unmerged values into the centroids, each centroid is limited by the `k1` scale
+    /// function `k(q) = compression / 2π * asin(2q - 1)`
+    pub fn compress(&mut self) {
+        if self.unmerged.is_empty() {
+            return;
+        }
+
+        let mut centroids = std::mem::take(&mut self.centroids);
+        centroids.append(&mut self.unmerged);
+        centroids.sort_by(|a, b| a.mean.partial_cmp(&b.mean).unwrap_or(Ordering::Equal));
+
+        let total = self.count;
+        let mut merged = Vec::with_capacity(self.compression as usize);
+        let mut centroids = centroids.into_iter();
+        let mut current = centroids.next().unwrap();
+        let mut weight_so_far = 0.0;
+        let mut q_limit = self.q_limit(0.0);
+        for centroid in centroids {
+            let q = (weight_so_far + current.weight + centroid.weight) / total;
+            if q <= q_limit {
+                current.add(&centroid);
+            } else {
+                weight_so_far += current.weight;
+                merged.push(current);
+                q_limit = self.q_limit(weight_so_far / total);
+                current = centroid;
+            }
+        }
+        merged.push(current);
+
+        self.centroids = merged;
+    }
+
+    /// The largest quantile the centroid started at `q0` can reach
+    fn q_limit(&self, q0: f64) -> f64 {
+        let k = self.compression / (2.0 * PI) * (2.0 * q0 - 1.0).asin() + 1.0;
+        if k >= self.compression / 4.0 {
+            return 1.0;
+        }
+        ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    /// The estimated value of the quantile in [0, 1], `None` if no value is added
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        if !self.unmerged.is_empty() {
+            let mut digest = self.clone();
+            digest.compress();
+            return digest.quantile(q);
+        }
+
+        if q <= 0.0 {
+            return Some(self.min);
+        }
+        if q >= 1.0 {
+            return Some(self.max);
+        }
+
+        let centroids = &self.centroids;
+        let index = q * self.count;
+
+        // between the min and the center of the first centroid
+        let first = &centroids[0];
+        if index < first.weight / 2.0 {
+            return Some(self.min + (first.mean - self.min) * index / (first.weight / 2.0));
+        }
+
+        // between the centers of the adjacent centroids
+        let mut center = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let distance = (pair[0].weight + pair[1].weight) / 2.0;
+            if center + distance > index {
+                let z = (index - center) / distance;
+                return Some(pair[0].mean + (pair[1].mean - pair[0].mean) * z);
+            }
+            center += distance;
+        }
+
+        // between the center of the last centroid and the max
+        let last = centroids.last().unwrap();
+        let z = ((index - center) / (last.weight / 2.0)).min(1.0);
+        Some(last.mean + (self.max - last.mean) * z)
+    }
+
+    /// Serialize the digest for the accumulators of the records, see `from_bytes`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.centroids.len() + self.unmerged.len();
+        let mut buffer = BytesMut::with_capacity(40 + len * 16);
+        buffer.put_f64_le(self.compression);
+        buffer.put_f64_le(self.count);
+        buffer.put_f64_le(self.min);
+        buffer.put_f64_le(self.max);
+        for centroids in [&self.centroids, &self.unmerged] {
+            buffer.put_u32_le(centroids.len() as u32);
+            for centroid in centroids {
+                buffer.put_f64_le(centroid.mean);
+                buffer.put_f64_le(centroid.weight);
+            }
+        }
+        buffer.to_vec()
+    }
+
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.remaining() < 36 {
+            return Err(anyhow!("t-digest bytes too short"));
+        }
+
+        let compression = bytes.get_f64_le();
+        if compression.is_nan() || compression < 10.0 {
+            return Err(anyhow!("illegal t-digest compression {}", compression));
+        }
+
+        let mut digest = TDigest::new(compression);
+        digest.count = bytes.get_f64_le();
+        digest.min = bytes.get_f64_le();
+        digest.max = bytes.get_f64_le();
+        for merged in [true, false] {
+            if bytes.remaining() < 4 {
+                return Err(anyhow!("t-digest bytes too short"));
+            }
+            let len = bytes.get_u32_le() as usize;
+            if bytes.remaining() < len * 16 {
+                return Err(anyhow!("t-digest bytes too short"));
+            }
+
+            let centroids: Vec<Centroid> = (0..len)
+                .map(|_| Centroid {
+                    mean: bytes.get_f64_le(),
+                    weight: bytes.get_f64_le(),
+                })
+                .collect();
+            if merged {
+                digest.centroids = centroids;
+            } else {
+                digest.unmerged = centroids;
+            }
+        }
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::percentile::t_digest::TDigest;
+
+    #[test]
+    pub fn t_digest_test() {
+        let mut digest = TDigest::new(100.0);
+        assert_eq!(digest.quantile(0.5), None);
+
+        // the values are added out of order
+        for i in 0..10_000 {
+            digest.add(((i * 7919) % 10_000) as f64);
+        }
+        assert_eq!(digest.count(), 10_000);
+        assert_eq!(digest.quantile(0.0), Some(0.0));
+        assert_eq!(digest.quantile(1.0), Some(9_999.0));
+        for q in [0.01, 0.5, 0.9, 0.99, 0.999] {
+            let estimate = digest.quantile(q).unwrap();
+            assert!(
+                (estimate - q * 10_000.0).abs() < 10_000.0 * 0.01,
+                "{}",
+                estimate
+            );
+        }
+
+        // the merged digests of the two halves estimate as the whole one
+        let mut left = TDigest::new(100.0);
+        let mut right = TDigest::new(100.0);
+        for i in 0..10_000 {
+            if i % 2 == 0 {
+                left.add(i as f64);
+            } else {
+                right.add(i as f64);
+            }
+        }
+        let mut right = TDigest::from_bytes(right.to_bytes().as_slice()).unwrap();
+        right.merge(&left);
+        assert_eq!(right.count(), 10_000);
+        let estimate = right.quantile(0.99).unwrap();
+        assert!((estimate - 9_900.0).abs() < 100.0, "{}", estimate);
+    }
+}
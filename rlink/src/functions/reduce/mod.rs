@@ -1,2 +1,5 @@
 pub mod schema_reduce;
+pub mod top_n;
+
 pub use schema_reduce::*;
+pub use top_n::*;
@@ -6,7 +6,7 @@ use crate::core::data_types::{DataType, Field, Schema};
 use crate::core::element::{BufferMutReader, BufferReader, BufferWriter, FnSchema, Record};
 use crate::core::function::{Context, NamedFunction, ReduceFunction};
 use crate::functions::column_locate::{ColumnLocate, ColumnLocateBuilder};
-use crate::functions::percentile::{get_percentile_capacity, PercentileWriter};
+use crate::functions::percentile::{get_percentile_capacity, PercentileReader, PercentileWriter};
 
 pub fn count() -> AggregationDescriptor {
     AggregationDescriptor::Count
@@ -75,6 +75,14 @@ pub trait Aggregation: Debug + Send + Sync {
         value_index: usize,
         record_reader: &mut BufferReader,
     );
+    /// Merge the two aggregated values, e.g. the values of the merged session windows
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        value_reader: &mut BufferMutReader,
+        other_reader: &mut BufferMutReader,
+        value_index: usize,
+    );
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -118,6 +126,18 @@ impl Aggregation for CountAggregation {
         };
         writer.set_u64(agg_value).unwrap();
     }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        value_reader: &mut BufferMutReader,
+        other_reader: &mut BufferMutReader,
+        value_index: usize,
+    ) {
+        let agg_value =
+            value_reader.get_u64(value_index).unwrap() + other_reader.get_u64(value_index).unwrap();
+        writer.set_u64(agg_value).unwrap();
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -217,6 +237,26 @@ impl<T: ValueAgg> BasicAggregation<T> {
             value_agg: T::default(),
         }
     }
+
+    fn aggregate(&self, basic_value: T, value: T) -> T {
+        match self.agg_type {
+            BasicAggType::Sum => basic_value + value,
+            BasicAggType::Max => {
+                if basic_value > value {
+                    basic_value
+                } else {
+                    value
+                }
+            }
+            BasicAggType::Min => {
+                if basic_value > value {
+                    value
+                } else {
+                    basic_value
+                }
+            }
+        }
+    }
 }
 
 impl<T: ValueAgg> Aggregation for BasicAggregation<T> {
@@ -240,28 +280,25 @@ impl<T: ValueAgg> Aggregation for BasicAggregation<T> {
         let agg_value = match value_reader {
             Some(value_reader) => {
                 let basic_value = self.value_agg.read_value(value_reader, value_index);
-                match self.agg_type {
-                    BasicAggType::Sum => basic_value + record_value,
-                    BasicAggType::Max => {
-                        if basic_value > record_value {
-                            basic_value
-                        } else {
-                            record_value
-                        }
-                    }
-                    BasicAggType::Min => {
-                        if basic_value > record_value {
-                            record_value
-                        } else {
-                            basic_value
-                        }
-                    }
-                }
+                self.aggregate(basic_value, record_value)
             }
             None => record_value,
         };
         self.value_agg.write_record(writer, agg_value)
     }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        value_reader: &mut BufferMutReader,
+        other_reader: &mut BufferMutReader,
+        value_index: usize,
+    ) {
+        let basic_value = self.value_agg.read_value(value_reader, value_index);
+        let other_value = self.value_agg.read_value(other_reader, value_index);
+        let agg_value = self.aggregate(basic_value, other_value);
+        self.value_agg.write_record(writer, agg_value)
+    }
 }
 
 pub trait ValueAgg: Add<Output = Self> + PartialOrd + Default + Debug + Send + Sync {
@@ -482,6 +519,23 @@ impl Aggregation for PctAggregation {
             }
         }
     }
+
+    fn merge(
+        &self,
+        writer: &mut BufferWriter,
+        value_reader: &mut BufferMutReader,
+        other_reader: &mut BufferMutReader,
+        value_index: usize,
+    ) {
+        let other_value = other_reader.get_binary_mut(value_index).unwrap();
+        let other_percentile = PercentileReader::new(self.scale, other_value);
+
+        let stat_value = value_reader.get_binary_mut(value_index).unwrap();
+        let mut percentile = PercentileWriter::new(self.scale, stat_value);
+        percentile.merge(&other_percentile);
+
+        writer.set_binary(stat_value).unwrap();
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -575,6 +629,27 @@ impl ReduceFunction for SchemaReduceFunction {
         record_rt
     }
 
+    fn merge(&self, value: &mut Record, other: &mut Record) -> Record {
+        let mut record_rt = Record::with_capacity(self.val_len);
+        let mut writer = record_rt.as_writer(self.val_schema.as_type_ids());
+
+        let mut value_reader = value.as_reader_mut(self.val_schema.as_type_ids());
+        let mut other_reader = other.as_reader_mut(self.val_schema.as_type_ids());
+        for index in 0..self.agg_operators.len() {
+            self.agg_operators[index].merge(
+                writer.borrow_mut(),
+                value_reader.borrow_mut(),
+                other_reader.borrow_mut(),
+                index,
+            )
+        }
+        record_rt
+    }
+
+    fn supports_merge(&self) -> bool {
+        true
+    }
+
     async fn close(&mut self) -> crate::core::Result<()> {
         Ok(())
     }
@@ -0,0 +1,527 @@
+use std::cmp::Ordering;
+
+use bytes::{Buf, BufMut, BytesMut};
+use serbuffer::types;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::{DataType, Field, Schema};
+use crate::core::element::{Element, FnSchema, Record};
+use crate::core::function::{
+    AggregateFunction, Context, FlatMapFunction, NamedFunction, ProcessWindowFunction,
+    SendableElementStream,
+};
+use crate::core::window::ProcessWindowContext;
+use crate::functions::column_locate::{ColumnLocate, ColumnLocateBuilder};
+use crate::functions::system::join::bytes_record;
+use crate::utils::stream::MemoryStream;
+
+/// the ranking of the key emitted by the last firing of the window
+const RANKING_STATE: &str = "top_n.ranking";
+/// the types of the ranking and the rows of the key, see `rows_schema`
+const ROWS_TYPES: [u8; 1] = [types::BINARY];
+
+/// The kind of the Top-N output row, see `TopN::changelog`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowKind {
+    Insert = 0,
+    /// The row replaced at the rank, followed by the `UpdateAfter` row
+    UpdateBefore = 1,
+    UpdateAfter = 2,
+    Delete = 3,
+}
+
+impl From<u8> for RowKind {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => RowKind::Insert,
+            1 => RowKind::UpdateBefore,
+            2 => RowKind::UpdateAfter,
+            _ => RowKind::Delete,
+        }
+    }
+}
+
+/// The Top-N records of each key in the window ranked by a numeric column, see
+/// `TWindowedStream::top_n`.
+///
+/// The output rows are the `row_kind`, the 1-based `rank` and the fields of the ranked record.
+/// The full ranking is emitted as the `Insert` rows whenever the window is fired, or only the
+/// changed ranks are emitted as the `UpdateBefore`/`UpdateAfter` rows if `changelog`
+#[derive(Clone, Debug)]
+pub struct TopN {
+    n: usize,
+    rank_column: ColumnLocate,
+    ascending: bool,
+    changelog: bool,
+    parallelism: u16,
+}
+
+impl TopN {
+    /// The `n` records with the largest values of the `rank_column`
+    pub fn new<T: ColumnLocateBuilder>(n: usize, rank_column: T) -> Self {
+        TopN {
+            n,
+            rank_column: rank_column.build(),
+            ascending: false,
+            changelog: false,
+            parallelism: 0,
+        }
+    }
+
+    /// Rank the records by the smallest values instead
+    pub fn ascending(mut self) -> Self {
+        self.ascending = true;
+        self
+    }
+
+    /// Emit the changes of the ranking since the last firing of the window, e.g. the windows
+    /// fired early by the `ContinuousEventTimeTrigger`
+    pub fn changelog(mut self) -> Self {
+        self.changelog = true;
+        self
+    }
+
+    /// Default is the parallelism of the upstream
+    pub fn parallelism(mut self, parallelism: u16) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// The functions of the window Top-N. The `input_schema` is the schema of the keyed stream
+    pub(crate) fn functions(
+        &self,
+        input_schema: FnSchema,
+    ) -> (
+        TopNAggregateFunction,
+        TopNProcessWindowFunction,
+        TopNFlatMapFunction,
+    ) {
+        let (record_schema, key_schema): (Schema, Schema) = input_schema.into();
+        let (column_index, field) = self.rank_column.to_column(&record_schema);
+
+        let aggregate = TopNAggregateFunction {
+            n: self.n,
+            ascending: self.ascending,
+            record_types: record_schema.as_type_ids().to_vec(),
+            column_index,
+            data_type: field.data_type().clone(),
+            parallelism: self.parallelism,
+        };
+        let process = TopNProcessWindowFunction {
+            changelog: self.changelog,
+        };
+        let flat_map = TopNFlatMapFunction::new(&key_schema, &record_schema);
+
+        (aggregate, process, flat_map)
+    }
+}
+
+fn rows_schema() -> Schema {
+    Schema::new(vec![Field::new("rows", DataType::Binary)])
+}
+
+fn rows_record(rows: &[u8]) -> Record {
+    let mut record = Record::with_capacity(rows.len() + 8);
+    record.as_writer(&ROWS_TYPES).set_binary(rows).unwrap();
+    record
+}
+
+fn put_record(rows: &mut BytesMut, record: &Record) {
+    let values = record.values.as_slice();
+    rows.put_u64(record.timestamp);
+    rows.put_u32(values.len() as u32);
+    rows.put_slice(values);
+}
+
+fn get_record(rows: &mut &[u8]) -> Record {
+    let timestamp = rows.get_u64();
+    let len = rows.get_u32() as usize;
+    let record = bytes_record(&rows[..len], timestamp);
+    rows.advance(len);
+    record
+}
+
+fn get_records(mut rows: &[u8]) -> Vec<Record> {
+    let mut records = Vec::new();
+    while rows.has_remaining() {
+        records.push(get_record(&mut rows));
+    }
+    records
+}
+
+fn put_records(records: &[Record]) -> Record {
+    let mut rows = BytesMut::new();
+    for record in records {
+        put_record(&mut rows, record);
+    }
+    bytes_record(rows.as_ref(), 0)
+}
+
+/// Keep the Top-N records of the key in the window, the accumulator is the ranked records
+pub(crate) struct TopNAggregateFunction {
+    n: usize,
+    ascending: bool,
+    record_types: Vec<u8>,
+    column_index: usize,
+    data_type: DataType,
+    parallelism: u16,
+}
+
+impl TopNAggregateFunction {
+    fn rank_value(&self, record: &mut Record) -> f64 {
+        let reader = record.as_reader(self.record_types.as_slice());
+        let index = self.column_index;
+        match self.data_type {
+            DataType::Int8 => reader.get_i8(index).unwrap() as f64,
+            DataType::UInt8 => reader.get_u8(index).unwrap() as f64,
+            DataType::Int16 => reader.get_i16(index).unwrap() as f64,
+            DataType::UInt16 => reader.get_u16(index).unwrap() as f64,
+            DataType::Int32 => reader.get_i32(index).unwrap() as f64,
+            DataType::UInt32 => reader.get_u32(index).unwrap() as f64,
+            DataType::Int64 => reader.get_i64(index).unwrap() as f64,
+            DataType::UInt64 => reader.get_u64(index).unwrap() as f64,
+            DataType::Float32 => reader.get_f32(index).unwrap() as f64,
+            DataType::Float64 => reader.get_f64(index).unwrap(),
+            _ => panic!("un-support rank DataType {:?}", self.data_type),
+        }
+    }
+
+    /// Sort the records by the rank values and keep the top `n`, the earlier record wins the tie
+    fn rank(&self, records: Vec<Record>) -> Vec<Record> {
+        let mut ranked: Vec<(f64, Record)> = records
+            .into_iter()
+            .map(|mut record| (self.rank_value(&mut record), record))
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| {
+            let ordering = a.partial_cmp(b).unwrap_or(Ordering::Equal);
+            if self.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+        ranked.truncate(self.n);
+        ranked.into_iter().map(|(_, record)| record).collect()
+    }
+}
+
+#[async_trait]
+impl AggregateFunction for TopNAggregateFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn create_accumulator(&self) -> Record {
+        Record::new()
+    }
+
+    fn add(&self, accumulator: &mut Record, record: &mut Record) -> Record {
+        let mut records = get_records(accumulator.values.as_slice());
+        let mut record = record.clone();
+        record.location_windows = None;
+        records.push(record);
+
+        put_records(self.rank(records).as_slice())
+    }
+
+    fn get_result(&self, accumulator: &mut Record) -> Record {
+        rows_record(accumulator.values.as_slice())
+    }
+
+    fn merge(&self, accumulator: &mut Record, other: &mut Record) -> Record {
+        let mut records = get_records(accumulator.values.as_slice());
+        records.extend(get_records(other.values.as_slice()));
+
+        put_records(self.rank(records).as_slice())
+    }
+
+    fn supports_merge(&self) -> bool {
+        true
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&rows_schema())
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl NamedFunction for TopNAggregateFunction {
+    fn name(&self) -> &str {
+        "TopNAggregateFunction"
+    }
+}
+
+/// Convert the ranking of the key to the output rows, each row is the kind, the rank and the
+/// ranked record. In the changelog mode the ranking is compared with the last firing of the
+/// window, only the changed ranks are emitted
+pub(crate) struct TopNProcessWindowFunction {
+    changelog: bool,
+}
+
+impl TopNProcessWindowFunction {
+    /// The changed ranks of the `ranking`, all ranks are changed if not `changelog`
+    fn changes<'a>(
+        &self,
+        ranking: &'a [Record],
+        last_ranking: &'a [Record],
+    ) -> Vec<(RowKind, usize, &'a Record)> {
+        if !self.changelog {
+            return ranking
+                .iter()
+                .enumerate()
+                .map(|(rank, record)| (RowKind::Insert, rank, record))
+                .collect();
+        }
+
+        let mut changes = Vec::new();
+        for rank in 0..ranking.len().max(last_ranking.len()) {
+            match (last_ranking.get(rank), ranking.get(rank)) {
+                (None, Some(record)) => changes.push((RowKind::Insert, rank, record)),
+                (Some(last_record), Some(record)) if last_record != record => {
+                    changes.push((RowKind::UpdateBefore, rank, last_record));
+                    changes.push((RowKind::UpdateAfter, rank, record));
+                }
+                (Some(last_record), None) => changes.push((RowKind::Delete, rank, last_record)),
+                _ => {}
+            }
+        }
+        changes
+    }
+}
+
+#[async_trait]
+impl ProcessWindowFunction for TopNProcessWindowFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn process(
+        &self,
+        _key: &mut Record,
+        value: &mut Record,
+        context: &mut ProcessWindowContext,
+    ) -> Record {
+        let reader = value.as_reader(&ROWS_TYPES);
+        let ranking_bytes = reader.get_binary(0).unwrap();
+        let ranking = get_records(ranking_bytes);
+
+        let last_ranking = if self.changelog {
+            let last_ranking = context
+                .get_state(RANKING_STATE)
+                .map(get_records)
+                .unwrap_or_default();
+            context.set_state(RANKING_STATE, ranking_bytes.to_vec());
+            last_ranking
+        } else {
+            vec![]
+        };
+
+        let mut rows = BytesMut::new();
+        for (kind, rank, record) in self.changes(ranking.as_slice(), last_ranking.as_slice()) {
+            rows.put_u8(kind as u8);
+            rows.put_u64(rank as u64 + 1);
+            put_record(&mut rows, record);
+        }
+        rows_record(rows.as_ref())
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&rows_schema())
+    }
+}
+
+impl NamedFunction for TopNProcessWindowFunction {
+    fn name(&self) -> &str {
+        "TopNProcessWindowFunction"
+    }
+}
+
+/// Expand the rows of the key emitted by the window, the input records are the keys followed
+/// by the rows of `TopNProcessWindowFunction`
+pub(crate) struct TopNFlatMapFunction {
+    /// the types of the input record, the key fields and the rows
+    input_types: Vec<u8>,
+    output_schema: Schema,
+}
+
+impl TopNFlatMapFunction {
+    fn new(key_schema: &Schema, record_schema: &Schema) -> Self {
+        let mut input_types = key_schema.as_type_ids().to_vec();
+        input_types.push(types::BINARY);
+
+        let mut output_schema = Schema::new(vec![
+            Field::new("row_kind", DataType::UInt8),
+            Field::new("rank", DataType::UInt64),
+        ]);
+        output_schema.merge(record_schema);
+
+        TopNFlatMapFunction {
+            input_types,
+            output_schema,
+        }
+    }
+
+    fn expand(mut rows: &[u8]) -> Vec<Record> {
+        let mut records = Vec::new();
+        while rows.has_remaining() {
+            let kind = rows.get_u8();
+            let rank = rows.get_u64();
+            let ranked_record = get_record(&mut rows);
+
+            let mut record = Record::with_capacity(ranked_record.len() + 9);
+            let mut writer = record.as_writer(&[types::U8, types::U64]);
+            writer.set_u8(kind).unwrap();
+            writer.set_u64(rank).unwrap();
+            record.timestamp = ranked_record.timestamp;
+            record.extend(ranked_record).unwrap();
+            records.push(record);
+        }
+        records
+    }
+}
+
+#[async_trait]
+impl FlatMapFunction for TopNFlatMapFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+        let reader = record.as_reader(self.input_types.as_slice());
+        let rows = reader.get_binary(self.input_types.len() - 1).unwrap();
+
+        Box::pin(MemoryStream::new(Self::expand(rows)))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.output_schema)
+    }
+}
+
+impl NamedFunction for TopNFlatMapFunction {
+    fn name(&self) -> &str {
+        "TopNFlatMapFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for TopNFlatMapFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serbuffer::types;
+
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{FnSchema, Record};
+    use crate::core::function::{AggregateFunction, ProcessWindowFunction};
+    use crate::core::window::{ProcessWindowContext, Window};
+    use crate::functions::reduce::top_n::{RowKind, TopN, TopNFlatMapFunction};
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("player", DataType::String),
+            Field::new("score", DataType::UInt32),
+        ])
+    }
+
+    fn record(player: &str, score: u32) -> Record {
+        let mut record = Record::new();
+        let mut writer = record.as_writer(schema().as_type_ids());
+        writer.set_str(player).unwrap();
+        writer.set_u32(score).unwrap();
+        record
+    }
+
+    fn rows(mut value: Record) -> Vec<(RowKind, u64, Record)> {
+        let mut output_schema = Schema::new(vec![
+            Field::new("row_kind", DataType::UInt8),
+            Field::new("rank", DataType::UInt64),
+        ]);
+        output_schema.merge(&schema());
+
+        let reader = value.as_reader(&[types::BINARY]);
+        TopNFlatMapFunction::expand(reader.get_binary(0).unwrap())
+            .into_iter()
+            .map(|mut row| {
+                let reader = row.as_reader(output_schema.as_type_ids());
+                let kind = RowKind::from(reader.get_u8(0).unwrap());
+                let rank = reader.get_u64(1).unwrap();
+                let ranked = record(reader.get_str(2).unwrap(), reader.get_u32(3).unwrap());
+                (kind, rank, ranked)
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn top_n_test() {
+        let key_schema = Schema::new(vec![Field::new("game", DataType::String)]);
+        let input_schema = FnSchema::Tuple(schema(), key_schema);
+        let (aggregate, process, _flat_map) =
+            TopN::new(2, "score").changelog().functions(input_schema);
+
+        let mut accumulator = aggregate.create_accumulator();
+        for (player, score) in [("a", 10), ("b", 30), ("c", 20)] {
+            accumulator = aggregate.add(&mut accumulator, &mut record(player, score));
+        }
+
+        let mut context = ProcessWindowContext::new(Window::default());
+        let mut key = Record::new();
+        let value = process.process(
+            &mut key,
+            &mut aggregate.get_result(&mut accumulator),
+            &mut context,
+        );
+        assert_eq!(
+            rows(value),
+            vec![
+                (RowKind::Insert, 1, record("b", 30)),
+                (RowKind::Insert, 2, record("c", 20)),
+            ]
+        );
+
+        // only the changed rank is emitted by the next firing
+        accumulator = aggregate.add(&mut accumulator, &mut record("d", 25));
+        let value = process.process(
+            &mut key,
+            &mut aggregate.get_result(&mut accumulator),
+            &mut context,
+        );
+        assert_eq!(
+            rows(value),
+            vec![
+                (RowKind::UpdateBefore, 2, record("c", 20)),
+                (RowKind::UpdateAfter, 2, record("d", 25)),
+            ]
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub mod print;
 pub use print::*;
+
+pub mod two_phase_commit;
+pub use two_phase_commit::*;
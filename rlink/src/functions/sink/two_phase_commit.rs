@@ -0,0 +1,432 @@
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema, Record};
+use crate::core::function::{Context, NamedFunction, OutputFormat};
+use crate::core::state::{ListState, ListStateDescriptor, OperatorStateStore};
+
+/// The operator states of the transactions, the pending and open transactions are split to the
+/// tasks on rescale, any task can commit or abort them. The sequence is the union of all tasks,
+/// so the transaction ids stay unique
+const PENDING_TRANSACTIONS: &str = "pending_transactions";
+const OPEN_TRANSACTIONS: &str = "open_transactions";
+const TRANSACTION_SEQUENCE: &str = "transaction_sequence";
+
+/// The sink writing the records between two checkpoints in a transaction. The transaction is
+/// pre-committed on the barrier of the checkpoint, and committed once the checkpoint is
+/// completed, so the records are visible exactly once.
+///
+/// The transactions are managed and checkpointed by the `TwoPhaseCommitOutputFormat`
+#[async_trait]
+pub trait TwoPhaseCommitSink: Send + Sync {
+    /// The handle of a transaction, it's checkpointed until the transaction is committed
+    type Transaction: Clone + Debug + Serialize + DeserializeOwned + Send + Sync;
+
+    async fn open(&mut self, context: &Context) -> anyhow::Result<()>;
+
+    /// Begin a transaction, the `transaction_id` is unique and stable across the restarts
+    async fn begin_transaction(
+        &mut self,
+        transaction_id: String,
+    ) -> anyhow::Result<Self::Transaction>;
+
+    async fn write(
+        &mut self,
+        transaction: &mut Self::Transaction,
+        record: Record,
+    ) -> anyhow::Result<()>;
+
+    /// Flush the records of the transaction on the barrier, nothing can be written to it after
+    async fn pre_commit(&mut self, transaction: &mut Self::Transaction) -> anyhow::Result<()>;
+
+    /// Commit the pre-committed transaction. The transaction may be committed again after
+    /// restore, the commit must be idempotent
+    async fn commit(&mut self, transaction: Self::Transaction) -> anyhow::Result<()>;
+
+    /// Abort the transaction not pre-committed, e.g. the transaction open on the failure
+    async fn abort(&mut self, transaction: Self::Transaction) -> anyhow::Result<()>;
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// A transaction pre-committed on the checkpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+struct PendingTransaction<T> {
+    checkpoint_id: u64,
+    transaction: T,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "T: Serialize + DeserializeOwned")]
+struct TransactionState<T> {
+    /// the transaction open on the checkpoint
+    current: Option<T>,
+    /// the transactions open on the restored checkpoint, they're aborted after restore
+    #[serde(default)]
+    restored_open: Vec<T>,
+    /// the transactions waiting for their checkpoints to complete
+    pending: Vec<PendingTransaction<T>>,
+    /// the sequence of the next transaction id
+    next_sequence: u64,
+}
+
+impl<T> TransactionState<T> {
+    fn new() -> Self {
+        TransactionState {
+            current: None,
+            restored_open: vec![],
+            pending: vec![],
+            next_sequence: 0,
+        }
+    }
+
+    /// Take the pending transactions of the checkpoints not after the `checkpoint_id`
+    fn take_committable(&mut self, checkpoint_id: u64) -> Vec<T> {
+        let (committable, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|x| x.checkpoint_id <= checkpoint_id);
+        self.pending = pending;
+        committable.into_iter().map(|x| x.transaction).collect()
+    }
+}
+
+/// Drive the transactions of the `TwoPhaseCommitSink`.
+///
+/// The transaction pre-committed on a checkpoint is committed once the checkpoint is reported
+/// completed by the barrier of a later checkpoint, it's kept pending until then, since the
+/// checkpoints may be aborted or still in flight. After restore, the pending transactions of
+/// the checkpoint are committed and the open ones are aborted.
+///
+/// The transactions are checkpointed as the operator states, so they are redistributed to the
+/// tasks when the job is restored with a different parallelism.
+pub struct TwoPhaseCommitOutputFormat<S: TwoPhaseCommitSink> {
+    sink: S,
+    name: String,
+
+    transaction_prefix: String,
+    state: TransactionState<S::Transaction>,
+    operator_state: OperatorStateStore,
+}
+
+impl<S: TwoPhaseCommitSink> TwoPhaseCommitOutputFormat<S> {
+    pub fn new(name: &str, sink: S) -> Self {
+        TwoPhaseCommitOutputFormat {
+            sink,
+            name: name.to_string(),
+            transaction_prefix: String::new(),
+            state: TransactionState::new(),
+            operator_state: OperatorStateStore::new(),
+        }
+    }
+
+    fn pending_state(&self) -> ListState<PendingTransaction<S::Transaction>> {
+        self.operator_state
+            .list_state(&ListStateDescriptor::new(PENDING_TRANSACTIONS))
+    }
+
+    fn open_state(&self) -> ListState<S::Transaction> {
+        self.operator_state
+            .list_state(&ListStateDescriptor::new(OPEN_TRANSACTIONS))
+    }
+
+    fn sequence_state(&self) -> ListState<u64> {
+        self.operator_state
+            .union_list_state(&ListStateDescriptor::new(TRANSACTION_SEQUENCE))
+    }
+
+    async fn begin_transaction(&mut self) -> anyhow::Result<()> {
+        let transaction_id = format!("{}-{}", self.transaction_prefix, self.state.next_sequence);
+        self.state.next_sequence += 1;
+
+        let transaction = self.sink.begin_transaction(transaction_id).await?;
+        self.state.current = Some(transaction);
+        Ok(())
+    }
+
+    async fn commit(&mut self, checkpoint_id: u64) -> anyhow::Result<()> {
+        for transaction in self.state.take_committable(checkpoint_id) {
+            debug!("commit transaction {:?}", transaction);
+            self.sink.commit(transaction).await?;
+        }
+        Ok(())
+    }
+
+    /// Pre-commit the open transaction on the checkpoint, and begin the next one
+    async fn pre_commit(&mut self, checkpoint_id: u64) -> anyhow::Result<()> {
+        if let Some(mut transaction) = self.state.current.take() {
+            self.sink.pre_commit(&mut transaction).await?;
+            self.state.pending.push(PendingTransaction {
+                checkpoint_id,
+                transaction,
+            });
+        }
+        self.begin_transaction().await
+    }
+
+    /// Commit the transactions of the completed checkpoints, then pre-commit the open
+    /// transaction on the checkpoint
+    async fn checkpoint(
+        &mut self,
+        checkpoint_id: u64,
+        completed_checkpoint_id: Option<u64>,
+    ) -> anyhow::Result<()> {
+        if let Some(completed_checkpoint_id) = completed_checkpoint_id {
+            self.commit(completed_checkpoint_id)
+                .await
+                .map_err(|e| anyhow!("commit transactions error. {}", e))?;
+        }
+        self.pre_commit(checkpoint_id)
+            .await
+            .map_err(|e| anyhow!("pre-commit transaction error. {}", e))
+    }
+
+    /// Commit the pending transactions and abort the open ones after restore
+    async fn recover(&mut self) -> anyhow::Result<()> {
+        self.commit(u64::MAX).await?;
+        for transaction in std::mem::take(&mut self.state.restored_open) {
+            info!("abort the transaction {:?} after restore", transaction);
+            self.sink.abort(transaction).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S: TwoPhaseCommitSink> OutputFormat for TwoPhaseCommitOutputFormat<S> {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.transaction_prefix = format!(
+            "{}-{}-{}",
+            context.application_id,
+            context.task_id.job_id().0,
+            context.task_id.task_number()
+        );
+        self.sink.open(context).await?;
+
+        self.operator_state.restore_from(context)?;
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+        self.recover().await?;
+        self.begin_transaction().await?;
+
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        let record = element.into_record();
+        let transaction = self.state.current.as_mut().unwrap();
+        if let Err(e) = self.sink.write(transaction, record).await {
+            let transaction = self.state.current.take().unwrap();
+            if let Err(abort_e) = self.sink.abort(transaction).await {
+                error!("abort transaction error. {}", abort_e);
+            }
+            panic!("write transaction error. {}", e);
+        }
+    }
+
+    /// The stream is ended, the open transaction is committed as there is no more checkpoint
+    async fn close(&mut self) -> crate::core::Result<()> {
+        if let Some(mut transaction) = self.state.current.take() {
+            self.sink.pre_commit(&mut transaction).await?;
+            self.state.pending.push(PendingTransaction {
+                checkpoint_id: u64::MAX,
+                transaction,
+            });
+        }
+        self.commit(u64::MAX).await?;
+        self.sink.close().await?;
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::Empty
+    }
+}
+
+impl<S: TwoPhaseCommitSink> NamedFunction for TwoPhaseCommitOutputFormat<S> {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[async_trait]
+impl<S: TwoPhaseCommitSink> CheckpointFunction for TwoPhaseCommitOutputFormat<S> {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+        // the states of the task are redistributed from the checkpoint of all tasks
+        let pending = self
+            .pending_state()
+            .get()
+            .expect("get pending transactions error");
+        let restored_open = self
+            .open_state()
+            .get()
+            .expect("get open transactions error");
+        let sequences = self.sequence_state().get().expect("get sequence error");
+        if pending.is_empty() && restored_open.is_empty() && sequences.is_empty() {
+            return;
+        }
+
+        info!(
+            "restore {} pending and {} open transactions from checkpoint({:?})",
+            pending.len(),
+            restored_open.len(),
+            context.checkpoint_id
+        );
+        self.state.pending = pending;
+        self.state.restored_open = restored_open;
+        self.state.next_sequence = sequences.into_iter().max().unwrap_or_default();
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let completed_checkpoint_id = context.completed_checkpoint_id.map(|x| x.0);
+        if let Err(e) = self
+            .checkpoint(context.checkpoint_id.0, completed_checkpoint_id)
+            .await
+        {
+            panic!("checkpoint({:?}) error. {}", context.checkpoint_id, e);
+        }
+
+        let snapshot = self
+            .pending_state()
+            .update(self.state.pending.clone())
+            .and_then(|_| {
+                let open = self.state.current.iter().cloned().collect();
+                self.open_state().update(open)
+            })
+            .and_then(|_| self.sequence_state().update(vec![self.state.next_sequence]))
+            .and_then(|_| self.operator_state.snapshot());
+        match snapshot {
+            Ok(handle) => Some(handle),
+            Err(e) => panic!(
+                "snapshot transactions on checkpoint({:?}) error. {}",
+                context.checkpoint_id, e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::core::element::Record;
+    use crate::core::function::Context;
+    use crate::functions::sink::two_phase_commit::{
+        PendingTransaction, TransactionState, TwoPhaseCommitOutputFormat, TwoPhaseCommitSink,
+    };
+
+    #[test]
+    pub fn take_committable_test() {
+        let mut state = TransactionState::new();
+        for checkpoint_id in [1, 2, 3] {
+            state.pending.push(PendingTransaction {
+                checkpoint_id,
+                transaction: format!("txn-{}", checkpoint_id),
+            });
+        }
+
+        assert_eq!(
+            state.take_committable(2),
+            vec!["txn-1".to_string(), "txn-2".to_string()]
+        );
+        assert!(state.take_committable(2).is_empty());
+
+        let json = serde_json::to_string(&state).unwrap();
+        let mut state: TransactionState<String> = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(state.take_committable(u64::MAX), vec!["txn-3".to_string()]);
+    }
+
+    struct LogSink {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl TwoPhaseCommitSink for LogSink {
+        type Transaction = String;
+
+        async fn open(&mut self, _context: &Context) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn begin_transaction(&mut self, transaction_id: String) -> anyhow::Result<String> {
+            Ok(transaction_id)
+        }
+
+        async fn write(
+            &mut self,
+            _transaction: &mut String,
+            _record: Record,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn pre_commit(&mut self, transaction: &mut String) -> anyhow::Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("pre_commit {}", transaction));
+            Ok(())
+        }
+
+        async fn commit(&mut self, transaction: String) -> anyhow::Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("commit {}", transaction));
+            Ok(())
+        }
+
+        async fn abort(&mut self, transaction: String) -> anyhow::Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("abort {}", transaction));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    pub async fn commit_on_completed_checkpoint_test() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut output_format =
+            TwoPhaseCommitOutputFormat::new("LogSink", LogSink { log: log.clone() });
+        output_format.transaction_prefix = "t".to_string();
+        output_format.begin_transaction().await.unwrap();
+
+        // no checkpoint is reported completed, the pre-committed transactions are kept pending
+        output_format.checkpoint(1, None).await.unwrap();
+        output_format.checkpoint(2, None).await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["pre_commit t-0".to_string(), "pre_commit t-1".to_string()]
+        );
+        assert_eq!(output_format.state.pending.len(), 2);
+
+        // only the transactions of the completed checkpoint are committed
+        log.lock().unwrap().clear();
+        output_format.checkpoint(3, Some(1)).await.unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["commit t-0".to_string(), "pre_commit t-2".to_string()]
+        );
+        let pending: Vec<u64> = output_format
+            .state
+            .pending
+            .iter()
+            .map(|x| x.checkpoint_id)
+            .collect();
+        assert_eq!(pending, vec![2, 3]);
+    }
+}
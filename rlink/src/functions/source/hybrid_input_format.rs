@@ -0,0 +1,610 @@
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+
+use futures::{FutureExt, Stream, StreamExt};
+use tokio::sync::Mutex;
+
+use crate::core::checkpoint::{
+    CheckpointFunction, CheckpointHandle, FunctionSnapshotContext, OperatorStateHandle,
+};
+use crate::core::data_types::Schema;
+use crate::core::element::{Element, FnSchema};
+use crate::core::function::{
+    Context, ElementStream, InputFormat, InputSplit, InputSplitSource, NamedFunction,
+    SendableElementStream,
+};
+use crate::functions::column_locate::{ColumnLocate, ColumnLocateBuilder};
+
+/// The position of the bounded source when it reaches the end, the streaming source is
+/// created from it, e.g. the kafka source consumes from the `end_timestamp`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SwitchContext {
+    /// the max timestamp of the records read by the bounded source
+    pub end_timestamp: Option<u64>,
+    /// the max watermark emitted by the bounded source
+    pub end_watermark: Option<u64>,
+}
+
+impl SwitchContext {
+    fn update_timestamp(&mut self, timestamp: u64) {
+        self.end_timestamp = Some(self.end_timestamp.map_or(timestamp, |x| x.max(timestamp)));
+    }
+
+    fn update_watermark(&mut self, watermark: u64) {
+        self.end_watermark = Some(self.end_watermark.map_or(watermark, |x| x.max(watermark)));
+    }
+}
+
+/// Create the streaming source when the bounded source reaches the end
+pub type StreamingSourceFactory = Arc<dyn Fn(&SwitchContext) -> Box<dyn InputFormat> + Send + Sync>;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct HybridProgress {
+    switched: bool,
+    switch_context: SwitchContext,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HybridState {
+    #[serde(flatten)]
+    progress: HybridProgress,
+    /// the checkpoint handle of the current source
+    handle: Option<String>,
+}
+
+/// Read a bounded source, e.g. the history files on the object store, then switch to a
+/// streaming source created by the `SwitchContext` of the bounded source, e.g. the kafka
+/// consumes from the max timestamp of the files, so the backfill and the live tail are run
+/// in a single job.
+///
+/// Each task switches independently when its part of the bounded source is read. The
+/// watermarks of the streaming source before the `end_watermark` are dropped, and the
+/// current stage with the state of the current source is checkpointed.
+pub struct HybridInputFormat {
+    bounded: Arc<Mutex<Box<dyn InputFormat>>>,
+    streaming_factory: StreamingSourceFactory,
+    streaming: Arc<Mutex<Option<Box<dyn InputFormat>>>>,
+    timestamp_column: Option<ColumnLocate>,
+    schema: FnSchema,
+    parallelism: u16,
+
+    input_split: Option<InputSplit>,
+    context: Option<Context>,
+    restored_handle: Option<CheckpointHandle>,
+    progress: Arc<std::sync::Mutex<HybridProgress>>,
+}
+
+impl HybridInputFormat {
+    /// The `bounded` and the created streaming source must have the same schema, the
+    /// parallelism of the `bounded` is the parallelism of both
+    pub fn new<F>(bounded: Box<dyn InputFormat>, streaming_factory: F) -> Self
+    where
+        F: Fn(&SwitchContext) -> Box<dyn InputFormat> + Send + Sync + 'static,
+    {
+        let schema = bounded.schema(FnSchema::Empty);
+        let parallelism = bounded.parallelism();
+        HybridInputFormat {
+            bounded: Arc::new(Mutex::new(bounded)),
+            streaming_factory: Arc::new(streaming_factory),
+            streaming: Arc::new(Mutex::new(None)),
+            timestamp_column: None,
+            schema,
+            parallelism,
+            input_split: None,
+            context: None,
+            restored_handle: None,
+            progress: Arc::new(std::sync::Mutex::new(HybridProgress::default())),
+        }
+    }
+
+    /// The `u64` timestamp column of the records, the max timestamp read by the bounded
+    /// source is the `SwitchContext::end_timestamp`
+    pub fn timestamp_column<T: ColumnLocateBuilder>(mut self, column: T) -> Self {
+        self.timestamp_column = Some(column.build());
+        self
+    }
+
+    /// The context of the inner source, its operator states are restored from the handle of
+    /// the task as the handles of the other tasks are wrapped in their hybrid states
+    fn inner_context(context: &Context, handle: Option<CheckpointHandle>) -> Context {
+        let mut context = context.clone();
+        context.operator_state_handles = handle
+            .iter()
+            .map(|handle| OperatorStateHandle {
+                task_id: context.task_id,
+                handle: handle.clone(),
+            })
+            .collect();
+        context.checkpoint_handle = handle;
+        context
+    }
+}
+
+impl InputSplitSource for HybridInputFormat {}
+
+#[async_trait]
+impl InputFormat for HybridInputFormat {
+    async fn open(
+        &mut self,
+        input_split: InputSplit,
+        context: &Context,
+    ) -> crate::core::Result<()> {
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        let progress = self.progress.lock().unwrap().clone();
+        let inner_context = Self::inner_context(context, self.restored_handle.take());
+        if progress.switched {
+            info!(
+                "hybrid source resume the streaming source from {:?}",
+                progress.switch_context
+            );
+            let mut streaming = (self.streaming_factory)(&progress.switch_context);
+            streaming.open(input_split.clone(), &inner_context).await?;
+            *self.streaming.lock().await = Some(streaming);
+        } else {
+            self.bounded
+                .lock()
+                .await
+                .open(input_split.clone(), &inner_context)
+                .await?;
+        }
+
+        self.input_split = Some(input_split);
+        self.context = Some(context.clone());
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let switched = self.progress.lock().unwrap().switched;
+        let phase = if switched {
+            let mut streaming = self.streaming.lock().await;
+            Phase::Streaming(streaming.as_mut().unwrap().element_stream().await)
+        } else {
+            Phase::Bounded(self.bounded.lock().await.element_stream().await)
+        };
+
+        let schema = self.schema.first().clone();
+        let timestamp_index = self
+            .timestamp_column
+            .as_ref()
+            .map(|column| column.to_column(&schema).0);
+
+        Box::pin(HybridStream {
+            phase,
+            schema,
+            timestamp_index,
+            bounded: self.bounded.clone(),
+            streaming_factory: self.streaming_factory.clone(),
+            streaming: self.streaming.clone(),
+            input_split: self.input_split.clone().unwrap(),
+            context: self.context.clone().unwrap(),
+            progress: self.progress.clone(),
+        })
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        let switched = self.progress.lock().unwrap().switched;
+        if switched {
+            if let Some(streaming) = self.streaming.lock().await.as_mut() {
+                streaming.close().await?;
+            }
+        } else {
+            self.bounded.lock().await.close().await?;
+        }
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.schema.clone()
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl NamedFunction for HybridInputFormat {
+    fn name(&self) -> &str {
+        "HybridInputFormat"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for HybridInputFormat {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            if !handle.handle.is_empty() {
+                let state: HybridState = serde_json::from_str(handle.handle.as_str())
+                    .expect("parse hybrid source state error");
+                info!(
+                    "restore hybrid source from checkpoint({:?}), {:?}",
+                    context.checkpoint_id, state.progress
+                );
+
+                *self.progress.lock().unwrap() = state.progress;
+                self.restored_handle = state.handle.map(|handle| CheckpointHandle { handle });
+            }
+        }
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let progress = self.progress.lock().unwrap().clone();
+        let handle = if progress.switched {
+            match self.streaming.lock().await.as_mut() {
+                Some(streaming) => streaming.snapshot_state(context).await,
+                None => None,
+            }
+        } else {
+            self.bounded.lock().await.snapshot_state(context).await
+        };
+
+        let state = HybridState {
+            progress,
+            handle: handle.map(|x| x.handle),
+        };
+        Some(CheckpointHandle {
+            handle: serde_json::to_string(&state).unwrap(),
+        })
+    }
+}
+
+impl Debug for HybridInputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HybridInputFormat")
+            .field("timestamp_column", &self.timestamp_column)
+            .field("parallelism", &self.parallelism)
+            .finish()
+    }
+}
+
+type SwitchFuture =
+    Pin<Box<dyn Future<Output = crate::core::Result<SendableElementStream>> + Send>>;
+
+enum Phase {
+    Bounded(SendableElementStream),
+    Switching(SwitchFuture),
+    Streaming(SendableElementStream),
+    End,
+}
+
+struct HybridStream {
+    phase: Phase,
+    schema: Schema,
+    timestamp_index: Option<usize>,
+
+    bounded: Arc<Mutex<Box<dyn InputFormat>>>,
+    streaming_factory: StreamingSourceFactory,
+    streaming: Arc<Mutex<Option<Box<dyn InputFormat>>>>,
+    input_split: InputSplit,
+    context: Context,
+    progress: Arc<std::sync::Mutex<HybridProgress>>,
+}
+
+impl HybridStream {
+    fn switch(&self) -> SwitchFuture {
+        let bounded = self.bounded.clone();
+        let streaming_factory = self.streaming_factory.clone();
+        let streaming = self.streaming.clone();
+        let context = HybridInputFormat::inner_context(&self.context, None);
+        let input_split = self.input_split.clone();
+        let progress = self.progress.clone();
+
+        async move {
+            bounded.lock().await.close().await?;
+
+            let switch_context = progress.lock().unwrap().switch_context.clone();
+            info!(
+                "hybrid source switch to the streaming source by {:?}",
+                switch_context
+            );
+
+            let mut source = streaming_factory(&switch_context);
+            source.open(input_split, &context).await?;
+            let stream = source.element_stream().await;
+
+            *streaming.lock().await = Some(source);
+            progress.lock().unwrap().switched = true;
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn track(&self, element: &mut Element) {
+        match element {
+            Element::Record(record) => {
+                if let Some(index) = self.timestamp_index {
+                    let reader = record.as_reader(self.schema.as_type_ids());
+                    if let Ok(timestamp) = reader.get_u64(index) {
+                        self.progress
+                            .lock()
+                            .unwrap()
+                            .switch_context
+                            .update_timestamp(timestamp);
+                    }
+                }
+            }
+            Element::Watermark(watermark) => self
+                .progress
+                .lock()
+                .unwrap()
+                .switch_context
+                .update_watermark(watermark.timestamp),
+            _ => {}
+        }
+    }
+
+    fn is_late_watermark(&self, element: &Element) -> bool {
+        match element {
+            Element::Watermark(watermark) => {
+                let end_watermark = self.progress.lock().unwrap().switch_context.end_watermark;
+                end_watermark.map_or(false, |x| watermark.timestamp < x)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ElementStream for HybridStream {}
+
+impl Stream for HybridStream {
+    type Item = Element;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.phase {
+                Phase::Bounded(stream) => match stream.poll_next_unpin(cx) {
+                    Poll::Ready(Some(mut element)) => {
+                        this.track(&mut element);
+                        return Poll::Ready(Some(element));
+                    }
+                    Poll::Ready(None) => this.phase = Phase::Switching(this.switch()),
+                    Poll::Pending => return Poll::Pending,
+                },
+                Phase::Switching(future) => match future.poll_unpin(cx) {
+                    Poll::Ready(Ok(stream)) => this.phase = Phase::Streaming(stream),
+                    Poll::Ready(Err(e)) => {
+                        error!("hybrid source switch to the streaming source error. {}", e);
+                        this.phase = Phase::End;
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                Phase::Streaming(stream) => match stream.poll_next_unpin(cx) {
+                    Poll::Ready(Some(element)) => {
+                        if !this.is_late_watermark(&element) {
+                            return Poll::Ready(Some(element));
+                        }
+                    }
+                    Poll::Ready(None) => this.phase = Phase::End,
+                    Poll::Pending => return Poll::Pending,
+                },
+                Phase::End => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::Poll;
+
+    use futures::{Stream, StreamExt};
+    use tokio::sync::Mutex;
+
+    use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{Element, FnSchema, Record};
+    use crate::core::function::{
+        Context, ElementStream, InputFormat, InputSplit, InputSplitSource, NamedFunction,
+        SendableElementStream,
+    };
+    use crate::core::properties::Properties;
+    use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+    use crate::core::state::RuntimeContext;
+    use crate::functions::source::hybrid_input_format::{
+        HybridProgress, HybridStream, Phase, SwitchContext,
+    };
+
+    struct VecStream(std::vec::IntoIter<Element>);
+
+    impl ElementStream for VecStream {}
+
+    impl Stream for VecStream {
+        type Item = Element;
+
+        fn poll_next(
+            mut self: Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.0.next())
+        }
+    }
+
+    struct VecInputFormat {
+        elements: Vec<Element>,
+    }
+
+    impl NamedFunction for VecInputFormat {
+        fn name(&self) -> &str {
+            "VecInputFormat"
+        }
+    }
+
+    #[async_trait]
+    impl CheckpointFunction for VecInputFormat {
+        async fn initialize_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+            _handle: &Option<CheckpointHandle>,
+        ) {
+        }
+
+        async fn snapshot_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+        ) -> Option<CheckpointHandle> {
+            None
+        }
+    }
+
+    impl InputSplitSource for VecInputFormat {}
+
+    #[async_trait]
+    impl InputFormat for VecInputFormat {
+        async fn open(
+            &mut self,
+            _input_split: InputSplit,
+            _context: &Context,
+        ) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        async fn element_stream(&mut self) -> SendableElementStream {
+            Box::pin(VecStream(self.elements.clone().into_iter()))
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+            FnSchema::Single(schema())
+        }
+
+        fn parallelism(&self) -> u16 {
+            1
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![Field::new("ts", DataType::UInt64)])
+    }
+
+    fn record(timestamp: u64) -> Element {
+        let mut record = Record::new();
+        record
+            .as_writer(schema().as_type_ids())
+            .set_u64(timestamp)
+            .unwrap();
+        Element::Record(record)
+    }
+
+    fn context() -> Context {
+        Context {
+            application_id: "app".to_string(),
+            application_properties: Properties::new(),
+            operator_id: OperatorId(0),
+            operator_name: "HybridInputFormat".to_string(),
+            task_id: TaskId {
+                job_id: JobId(0),
+                task_number: 0,
+                num_tasks: 1,
+            },
+            checkpoint_id: CheckpointId::default(),
+            completed_checkpoint_id: None,
+            checkpoint_handle: None,
+            operator_state_handles: vec![],
+            input_schema: FnSchema::Empty,
+            output_schema: FnSchema::Empty,
+            children: vec![],
+            parents: vec![],
+            task_context: None,
+            runtime_context: RuntimeContext::default(),
+        }
+    }
+
+    /// The timestamp of the records and the watermarks of the element stream
+    fn timestamps(elements: &[Element]) -> Vec<(bool, u64)> {
+        let schema = schema();
+        elements
+            .iter()
+            .map(|element| match element {
+                Element::Record(record) => {
+                    let reader = record.as_reader(schema.as_type_ids());
+                    (true, reader.get_u64(0).unwrap())
+                }
+                Element::Watermark(watermark) => (false, watermark.timestamp),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    pub async fn switch_to_streaming_test() {
+        let mut bounded = VecInputFormat {
+            elements: vec![
+                record(10),
+                record(30),
+                Element::new_watermark(25),
+                record(20),
+            ],
+        };
+        let bounded_stream = bounded.element_stream().await;
+        let bounded: Box<dyn InputFormat> = Box::new(bounded);
+
+        let switch_contexts = Arc::new(std::sync::Mutex::new(Vec::<SwitchContext>::new()));
+        let factory_contexts = switch_contexts.clone();
+        let progress = Arc::new(std::sync::Mutex::new(HybridProgress::default()));
+
+        let stream = HybridStream {
+            phase: Phase::Bounded(bounded_stream),
+            schema: schema(),
+            timestamp_index: Some(0),
+            bounded: Arc::new(Mutex::new(bounded)),
+            streaming_factory: Arc::new(move |switch_context: &SwitchContext| {
+                factory_contexts
+                    .lock()
+                    .unwrap()
+                    .push(switch_context.clone());
+                let streaming: Box<dyn InputFormat> = Box::new(VecInputFormat {
+                    elements: vec![
+                        Element::new_watermark(20),
+                        record(40),
+                        Element::new_watermark(40),
+                    ],
+                });
+                streaming
+            }),
+            streaming: Arc::new(Mutex::new(None)),
+            input_split: InputSplit::new(0, Properties::new()),
+            context: context(),
+            progress: progress.clone(),
+        };
+        let elements: Vec<Element> = stream.collect().await;
+
+        // the watermark of the streaming source before the end watermark is dropped
+        assert_eq!(
+            timestamps(elements.as_slice()),
+            vec![
+                (true, 10),
+                (true, 30),
+                (false, 25),
+                (true, 20),
+                (true, 40),
+                (false, 40)
+            ]
+        );
+
+        let switch_contexts = switch_contexts.lock().unwrap();
+        assert_eq!(switch_contexts.len(), 1);
+        assert_eq!(switch_contexts[0].end_timestamp, Some(30));
+        assert_eq!(switch_contexts[0].end_watermark, Some(25));
+        assert!(progress.lock().unwrap().switched);
+    }
+}
@@ -1,2 +1,7 @@
+pub mod hybrid_input_format;
+pub mod split;
+pub mod text_input_format;
 pub mod vec_input_format;
+pub use hybrid_input_format::*;
+pub use text_input_format::*;
 pub use vec_input_format::*;
@@ -0,0 +1,136 @@
+//! The unified source of the splits, e.g. the kafka partitions, the files or the kinesis
+//! shards. A connector only implements how the splits are discovered by the
+//! `SplitEnumerator` and how they are read by the `SplitReader`, the `SplitSource` takes care
+//! of the assignment of the splits to the tasks and the checkpoint of the progress.
+
+use std::fmt::Debug;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::core::element::Record;
+use crate::core::function::Context;
+use crate::utils::hash::hash_code;
+
+pub mod split_source;
+pub use split_source::SplitSource;
+
+pub(crate) mod split_state;
+pub(crate) mod split_stream;
+
+/// A portion of the source data. The split carries the progress of the reading, e.g. the
+/// offset of a partition, so it's checkpointed and restored as it is
+pub trait SourceSplit:
+    Clone + Debug + Serialize + DeserializeOwned + Send + Sync + 'static
+{
+    /// The unique id of the split in the source
+    fn split_id(&self) -> String;
+
+    /// The splits of the same key are assigned to the same task, e.g. the shards of a
+    /// lineage. It's the `split_id` by default
+    fn assign_key(&self) -> String {
+        self.split_id()
+    }
+}
+
+/// Discover the splits of the source. It runs on the coordinator to assign the splits when
+/// the job starts, and periodically in the tasks to pick up the splits created later
+#[async_trait]
+pub trait SplitEnumerator: Send + Sync + 'static {
+    type Split: SourceSplit;
+
+    /// All splits of the source, the splits known by the tasks are ignored
+    async fn discover(&self) -> anyhow::Result<Vec<Self::Split>>;
+}
+
+/// Read the records of the splits assigned to a task
+#[async_trait]
+pub trait SplitReader: Send + Sync + 'static {
+    type Split: SourceSplit;
+
+    async fn open(&mut self, context: &Context) -> anyhow::Result<()>;
+
+    /// Start reading the `split` from its progress
+    async fn add_split(&mut self, split: Self::Split) -> anyhow::Result<()>;
+
+    /// Fetch the next records of the splits. An empty fetch is returned if nothing is read
+    /// in a while, e.g. the poll timeout, so the new splits are added in time
+    async fn fetch(&mut self) -> anyhow::Result<SplitFetch<Self::Split>>;
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// A record with the progress of its split after the record
+#[derive(Clone, Debug)]
+pub struct SplitRecord<S> {
+    pub record: Record,
+    pub split: S,
+}
+
+impl<S> SplitRecord<S> {
+    pub fn new(record: Record, split: S) -> Self {
+        SplitRecord { record, split }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SplitFetch<S> {
+    Records(Vec<SplitRecord<S>>),
+    /// The split of the id is read to the end, it's not read again after restore
+    Finished(String),
+}
+
+impl<S> SplitFetch<S> {
+    pub fn empty() -> Self {
+        SplitFetch::Records(vec![])
+    }
+}
+
+/// The task number the split is assigned to, by the hash of the `assign_key`
+pub fn assign_task<S: SourceSplit>(split: &S, num_tasks: u16) -> u16 {
+    let hash = hash_code(split.assign_key().as_bytes()).unwrap();
+    (hash % num_tasks as u32) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::source::split::{assign_task, SourceSplit};
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    struct ShardSplit {
+        shard_id: String,
+        parent_shard_id: Option<String>,
+    }
+
+    impl SourceSplit for ShardSplit {
+        fn split_id(&self) -> String {
+            self.shard_id.clone()
+        }
+
+        fn assign_key(&self) -> String {
+            self.parent_shard_id
+                .clone()
+                .unwrap_or_else(|| self.shard_id.clone())
+        }
+    }
+
+    #[test]
+    pub fn assign_task_test() {
+        let parent = ShardSplit {
+            shard_id: "shard-0".to_string(),
+            parent_shard_id: None,
+        };
+        let child = ShardSplit {
+            shard_id: "shard-1".to_string(),
+            parent_shard_id: Some("shard-0".to_string()),
+        };
+
+        for num_tasks in 1..16 {
+            let task_number = assign_task(&parent, num_tasks);
+            assert!(task_number < num_tasks);
+            assert_eq!(task_number, assign_task(&child, num_tasks));
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::channel::sender::ChannelSender;
+use crate::channel::{bounded, named_channel, Receiver, Sender, TryRecvError};
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::Schema;
+use crate::core::element::FnSchema;
+use crate::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use crate::core::properties::Properties;
+use crate::core::runtime::TaskId;
+use crate::functions::source::split::split_state::{SplitSourceState, SplitStateRecorder};
+use crate::functions::source::split::split_stream::{SplitEvent, SplitStream};
+use crate::functions::source::split::{
+    assign_task, SourceSplit, SplitEnumerator, SplitFetch, SplitReader,
+};
+use crate::metrics::Tag;
+
+/// The property of the input split, the json of the splits assigned to the task
+const SPLITS: &str = "splits";
+
+/// Read the splits of the `SplitEnumerator` by the `SplitReader`s of the tasks.
+///
+/// The splits are discovered on the coordinator when the job starts, and assigned to the
+/// tasks by the hash of the `assign_key`. With the `discovery_interval`, the tasks discover
+/// the splits periodically and read the new splits assigned to them, the assignment is
+/// stable across the tasks without the coordination.
+///
+/// The progress of the splits is checkpointed once the records are emitted, and the splits
+/// are read from the progress after restore. Without the `discovery_interval`, the source is
+/// bounded and ends once all splits of the task are finished.
+pub struct SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    enumerator: Arc<E>,
+    reader: Option<R>,
+    schema: Schema,
+    parallelism: u16,
+    discovery_interval: Option<Duration>,
+    buffer_size: usize,
+
+    task_id: TaskId,
+    tags: Vec<Tag>,
+    state_recorder: SplitStateRecorder<E::Split>,
+}
+
+impl<E, R> SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    pub fn new(enumerator: E, reader: R, schema: Schema, parallelism: u16) -> Self {
+        SplitSource {
+            enumerator: Arc::new(enumerator),
+            reader: Some(reader),
+            schema,
+            parallelism,
+            discovery_interval: None,
+            buffer_size: 10000,
+            task_id: TaskId::default(),
+            tags: vec![],
+            state_recorder: SplitStateRecorder::new(),
+        }
+    }
+
+    /// Discover the new splits in the tasks every `discovery_interval`
+    pub fn discovery_interval(mut self, discovery_interval: Duration) -> Self {
+        self.discovery_interval = Some(discovery_interval);
+        self
+    }
+
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+}
+
+/// Wait for the enumerator in the sync `create_input_splits`, the multi-thread runtime is
+/// required
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+/// Discover the splits periodically and hand over the new splits assigned to the task
+async fn discover_splits<E: SplitEnumerator>(
+    enumerator: Arc<E>,
+    task_id: TaskId,
+    discovery_interval: Duration,
+    state_recorder: SplitStateRecorder<E::Split>,
+    split_sender: Sender<E::Split>,
+) {
+    loop {
+        tokio::time::sleep(discovery_interval).await;
+
+        let splits = match enumerator.discover().await {
+            Ok(splits) => splits,
+            Err(e) => {
+                warn!("discover splits error. {}", e);
+                continue;
+            }
+        };
+        for split in splits {
+            if assign_task(&split, task_id.num_tasks()) != task_id.task_number()
+                || state_recorder.contains(split.split_id().as_str())
+            {
+                continue;
+            }
+
+            info!("discover split {:?}", split);
+            state_recorder.update(split.clone());
+            if split_sender.send(split).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Read the splits until all splits are finished and no more split will be handed over
+async fn read_splits<R: SplitReader>(
+    mut reader: R,
+    mut split_receiver: Receiver<R::Split>,
+    sender: ChannelSender<SplitEvent<R::Split>>,
+) {
+    let mut reading = HashSet::new();
+    let mut discovering = true;
+    loop {
+        // add the handed over splits before the next fetch
+        while discovering {
+            match split_receiver.try_recv() {
+                Ok(split) => {
+                    reading.insert(split.split_id());
+                    if let Err(e) = reader.add_split(split).await {
+                        let _ = sender.send(SplitEvent::Failed(e.to_string())).await;
+                        return;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => discovering = false,
+            }
+        }
+        if !discovering && reading.is_empty() {
+            break;
+        }
+
+        let events = match reader.fetch().await {
+            Ok(SplitFetch::Records(records)) => {
+                records.into_iter().map(SplitEvent::Record).collect()
+            }
+            Ok(SplitFetch::Finished(split_id)) => {
+                reading.remove(&split_id);
+                vec![SplitEvent::Finished(split_id)]
+            }
+            Err(e) => vec![SplitEvent::Failed(e.to_string())],
+        };
+        for event in events {
+            if sender.send(event).await.is_err() {
+                info!("the split source is closed");
+                return;
+            }
+        }
+    }
+
+    if let Err(e) = reader.close().await {
+        warn!("close split reader error. {}", e);
+    }
+    info!("all splits are finished");
+}
+
+impl<E, R> InputSplitSource for SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    fn create_input_splits(&self, min_num_splits: u16) -> crate::core::Result<Vec<InputSplit>> {
+        let splits = block_on(self.enumerator.discover())?;
+        info!("discover {} splits", splits.len());
+
+        let mut task_splits: Vec<Vec<E::Split>> = vec![vec![]; min_num_splits as usize];
+        for split in splits {
+            task_splits[assign_task(&split, min_num_splits) as usize].push(split);
+        }
+
+        let mut input_splits = Vec::with_capacity(min_num_splits as usize);
+        for (task_number, splits) in task_splits.into_iter().enumerate() {
+            if splits.is_empty() {
+                warn!("no split is assigned to the task {}", task_number);
+            }
+
+            let mut properties = Properties::new();
+            let splits = serde_json::to_string(&splits)
+                .map_err(|e| anyhow!("serialize splits error. {}", e))?;
+            properties.set_string(SPLITS.to_string(), splits);
+            input_splits.push(InputSplit::new(task_number as u16, properties));
+        }
+        Ok(input_splits)
+    }
+}
+
+#[async_trait]
+impl<E, R> InputFormat for SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    async fn open(
+        &mut self,
+        input_split: InputSplit,
+        context: &Context,
+    ) -> crate::core::Result<()> {
+        self.task_id = context.task_id;
+        self.tags = context.task_id.to_tags();
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+
+        // the restored progress takes precedence over the assigned splits
+        let splits = input_split.properties().get_string(SPLITS)?;
+        let splits: Vec<E::Split> = serde_json::from_str(splits.as_str())
+            .map_err(|e| anyhow!("parse the assigned splits error. {}", e))?;
+        for split in splits {
+            if !self.state_recorder.contains(split.split_id().as_str()) {
+                self.state_recorder.update(split);
+            }
+        }
+
+        self.reader.as_mut().unwrap().open(context).await?;
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) =
+            named_channel("SplitSource_Handover", self.tags.clone(), self.buffer_size);
+
+        let splits = self.state_recorder.splits();
+        let (split_sender, split_receiver) = bounded(splits.len().max(1));
+        for split in splits {
+            split_sender.send(split).await.unwrap();
+        }
+
+        if let Some(discovery_interval) = self.discovery_interval {
+            tokio::spawn(discover_splits(
+                self.enumerator.clone(),
+                self.task_id,
+                discovery_interval,
+                self.state_recorder.clone(),
+                split_sender,
+            ));
+        }
+
+        let reader = self.reader.take().unwrap();
+        tokio::spawn(read_splits(reader, split_receiver, sender));
+
+        Box::pin(SplitStream::new(receiver, self.state_recorder.clone()))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl<E, R> NamedFunction for SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    fn name(&self) -> &str {
+        "SplitSource"
+    }
+}
+
+#[async_trait]
+impl<E, R> CheckpointFunction for SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            if !handle.handle.is_empty() {
+                let state: SplitSourceState<E::Split> =
+                    serde_json::from_str(handle.handle.as_str())
+                        .expect("parse split source state error");
+                info!(
+                    "restore {} splits and {} finished splits from checkpoint({:?})",
+                    state.splits.len(),
+                    state.finished.len(),
+                    context.checkpoint_id
+                );
+                self.state_recorder.restore(state);
+            }
+        }
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        let state = self.state_recorder.snapshot();
+        let handle = serde_json::to_string(&state).unwrap();
+        Some(CheckpointHandle { handle })
+    }
+}
+
+impl<E, R> Debug for SplitSource<E, R>
+where
+    E: SplitEnumerator,
+    R: SplitReader<Split = E::Split>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SplitSource")
+            .field("parallelism", &self.parallelism)
+            .field("discovery_interval", &self.discovery_interval)
+            .finish()
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use crate::functions::source::split::SourceSplit;
+
+/// The checkpoint of the splits read by a task
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "S: SourceSplit")]
+pub(crate) struct SplitSourceState<S> {
+    pub splits: Vec<S>,
+    pub finished: Vec<String>,
+}
+
+#[derive(Debug)]
+struct SplitStates<S> {
+    splits: BTreeMap<String, S>,
+    finished: BTreeSet<String>,
+}
+
+/// Record the progress of the splits of the emitted records
+#[derive(Clone, Debug)]
+pub(crate) struct SplitStateRecorder<S> {
+    states: Arc<Mutex<SplitStates<S>>>,
+}
+
+impl<S: SourceSplit> SplitStateRecorder<S> {
+    pub fn new() -> Self {
+        SplitStateRecorder {
+            states: Arc::new(Mutex::new(SplitStates {
+                splits: BTreeMap::new(),
+                finished: BTreeSet::new(),
+            })),
+        }
+    }
+
+    pub fn restore(&self, state: SplitSourceState<S>) {
+        let mut states = self.states.lock().unwrap();
+        for split in state.splits {
+            states.splits.insert(split.split_id(), split);
+        }
+        states.finished.extend(state.finished);
+    }
+
+    /// Whether the split is being read or finished
+    pub fn contains(&self, split_id: &str) -> bool {
+        let states = self.states.lock().unwrap();
+        states.splits.contains_key(split_id) || states.finished.contains(split_id)
+    }
+
+    /// Record the progress of the split, or the start of a new split
+    pub fn update(&self, split: S) {
+        let mut states = self.states.lock().unwrap();
+        if !states.finished.contains(split.split_id().as_str()) {
+            states.splits.insert(split.split_id(), split);
+        }
+    }
+
+    pub fn finish(&self, split_id: &str) {
+        let mut states = self.states.lock().unwrap();
+        states.splits.remove(split_id);
+        states.finished.insert(split_id.to_string());
+    }
+
+    /// The splits not finished
+    pub fn splits(&self) -> Vec<S> {
+        self.states
+            .lock()
+            .unwrap()
+            .splits
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> SplitSourceState<S> {
+        let states = self.states.lock().unwrap();
+        SplitSourceState {
+            splits: states.splits.values().cloned().collect(),
+            finished: states.finished.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::source::split::split_state::{SplitSourceState, SplitStateRecorder};
+    use crate::functions::source::split::SourceSplit;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct PartitionSplit {
+        partition: i32,
+        offset: i64,
+    }
+
+    impl SourceSplit for PartitionSplit {
+        fn split_id(&self) -> String {
+            self.partition.to_string()
+        }
+    }
+
+    #[test]
+    pub fn split_state_recorder_test() {
+        let recorder = SplitStateRecorder::new();
+        recorder.restore(SplitSourceState {
+            splits: vec![PartitionSplit {
+                partition: 0,
+                offset: 10,
+            }],
+            finished: vec!["2".to_string()],
+        });
+        assert!(recorder.contains("0"));
+        assert!(recorder.contains("2"));
+        assert!(!recorder.contains("1"));
+
+        recorder.update(PartitionSplit {
+            partition: 1,
+            offset: 0,
+        });
+        recorder.update(PartitionSplit {
+            partition: 0,
+            offset: 11,
+        });
+        // the finished split is not read again
+        recorder.update(PartitionSplit {
+            partition: 2,
+            offset: 0,
+        });
+        recorder.finish("1");
+
+        let state = recorder.snapshot();
+        assert_eq!(
+            state.splits,
+            vec![PartitionSplit {
+                partition: 0,
+                offset: 11
+            }]
+        );
+        assert_eq!(state.finished, vec!["1".to_string(), "2".to_string()]);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let state: SplitSourceState<PartitionSplit> = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(state.splits.len(), 1);
+    }
+}
@@ -0,0 +1,60 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use crate::channel::receiver::ChannelReceiver;
+use crate::core::element::Element;
+use crate::core::function::ElementStream;
+use crate::functions::source::split::split_state::SplitStateRecorder;
+use crate::functions::source::split::{SourceSplit, SplitRecord};
+
+pub(crate) enum SplitEvent<S> {
+    Record(SplitRecord<S>),
+    Finished(String),
+    Failed(String),
+}
+
+/// Emit the records of the splits, the progress of the split is recorded once its record is
+/// emitted
+pub(crate) struct SplitStream<S> {
+    receiver: ChannelReceiver<SplitEvent<S>>,
+    state_recorder: SplitStateRecorder<S>,
+}
+
+impl<S: SourceSplit> SplitStream<S> {
+    pub fn new(
+        receiver: ChannelReceiver<SplitEvent<S>>,
+        state_recorder: SplitStateRecorder<S>,
+    ) -> Self {
+        SplitStream {
+            receiver,
+            state_recorder,
+        }
+    }
+}
+
+impl<S: SourceSplit> ElementStream for SplitStream<S> {}
+
+impl<S: SourceSplit> Stream for SplitStream<S> {
+    type Item = Element;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            return match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some(SplitEvent::Record(split_record))) => {
+                    self.state_recorder.update(split_record.split);
+                    Poll::Ready(Some(Element::Record(split_record.record)))
+                }
+                Poll::Ready(Some(SplitEvent::Finished(split_id))) => {
+                    info!("split {} finished", split_id);
+                    self.state_recorder.finish(split_id.as_str());
+                    continue;
+                }
+                Poll::Ready(Some(SplitEvent::Failed(e))) => panic!("read splits error. {}", e),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+}
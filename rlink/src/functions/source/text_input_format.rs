@@ -0,0 +1,247 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::net::TcpStream;
+
+use crate::channel::named_channel;
+use crate::channel::sender::ChannelSender;
+use crate::channel::utils::ChannelStream;
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::{DataType, Field, Schema};
+use crate::core::element::{Element, FnSchema, Record};
+use crate::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use crate::metrics::Tag;
+
+/// Decode a line into a record, the line is discarded if failed
+pub type LineDecoder = Arc<dyn Fn(&str) -> anyhow::Result<Record> + Send + Sync>;
+
+/// The schema of the `line_decoder`, a `line` field of `String`
+pub fn line_schema() -> Schema {
+    Schema::new(vec![Field::new("line", DataType::String)])
+}
+
+/// Decode the line into a record of the `line_schema`
+pub fn line_decoder(line: &str) -> anyhow::Result<Record> {
+    let schema = line_schema();
+    let mut record = Record::new();
+    let mut writer = record.as_writer(schema.as_type_ids());
+    writer.set_str(line)?;
+    Ok(record)
+}
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug)]
+enum TextSource {
+    Socket(String),
+    Stdin,
+}
+
+/// Read the text lines of a socket or the stdin, and decode the lines into records by the
+/// `decoder`. The source runs in a single task and is not checkpointed, it's designed for
+/// the examples and the local debugging, e.g. feed it by `nc -lk 9999`
+pub struct TextInputFormat {
+    source: TextSource,
+    schema: Schema,
+    decoder: LineDecoder,
+    max_retries: usize,
+
+    tags: Vec<Tag>,
+}
+
+impl TextInputFormat {
+    /// Connect to the `addr`, e.g. `127.0.0.1:9999`, and read the lines until the connection
+    /// is closed
+    pub fn socket<F>(addr: &str, schema: Schema, decoder: F) -> Self
+    where
+        F: Fn(&str) -> anyhow::Result<Record> + Send + Sync + 'static,
+    {
+        TextInputFormat::new(TextSource::Socket(addr.to_string()), schema, decoder)
+    }
+
+    /// Read the lines of the stdin until the EOF
+    pub fn stdin<F>(schema: Schema, decoder: F) -> Self
+    where
+        F: Fn(&str) -> anyhow::Result<Record> + Send + Sync + 'static,
+    {
+        TextInputFormat::new(TextSource::Stdin, schema, decoder)
+    }
+
+    fn new<F>(source: TextSource, schema: Schema, decoder: F) -> Self
+    where
+        F: Fn(&str) -> anyhow::Result<Record> + Send + Sync + 'static,
+    {
+        TextInputFormat {
+            source,
+            schema,
+            decoder: Arc::new(decoder),
+            max_retries: 0,
+            tags: vec![],
+        }
+    }
+
+    /// Reconnect the socket up to `max_retries` times every second once the connection is
+    /// closed or failed
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+async fn read_lines<R>(
+    reader: R,
+    decoder: &LineDecoder,
+    sender: &ChannelSender<Element>,
+) -> anyhow::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        let record = match decoder(line.as_str()) {
+            Ok(record) => record,
+            Err(e) => {
+                warn!("decode line error, the line is discarded. {}", e);
+                continue;
+            }
+        };
+        sender
+            .send(Element::Record(record))
+            .await
+            .map_err(|_e| anyhow!("the text source is closed"))?;
+    }
+    Ok(())
+}
+
+async fn read_socket(
+    addr: String,
+    max_retries: usize,
+    decoder: LineDecoder,
+    sender: ChannelSender<Element>,
+) {
+    let mut attempt = 0;
+    loop {
+        let result = match TcpStream::connect(addr.as_str()).await {
+            Ok(stream) => {
+                info!("socket {} connected", addr);
+                attempt = 0;
+                read_lines(stream, &decoder, &sender).await
+            }
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(()) => info!("socket {} closed", addr),
+            Err(e) => warn!("read socket {} error. {}", addr, e),
+        }
+
+        if attempt >= max_retries {
+            return;
+        }
+        attempt += 1;
+        info!("reconnect socket {} ({}/{})", addr, attempt, max_retries);
+        tokio::time::sleep(RECONNECT_INTERVAL).await;
+    }
+}
+
+impl InputSplitSource for TextInputFormat {}
+
+#[async_trait]
+impl InputFormat for TextInputFormat {
+    async fn open(
+        &mut self,
+        _input_split: InputSplit,
+        context: &Context,
+    ) -> crate::core::Result<()> {
+        self.tags = context.task_id.to_tags();
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let (sender, receiver) = named_channel("TextSource_Handover", self.tags.clone(), 1024);
+
+        let decoder = self.decoder.clone();
+        match self.source.clone() {
+            TextSource::Socket(addr) => {
+                tokio::spawn(read_socket(addr, self.max_retries, decoder, sender));
+            }
+            TextSource::Stdin => {
+                tokio::spawn(async move {
+                    if let Err(e) = read_lines(tokio::io::stdin(), &decoder, &sender).await {
+                        warn!("read stdin error. {}", e);
+                    }
+                });
+            }
+        }
+
+        Box::pin(ChannelStream::new(receiver))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    /// the stdin is read until the EOF, the socket is reconnected
+    fn bounded(&self) -> bool {
+        matches!(self.source, TextSource::Stdin)
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        1
+    }
+}
+
+impl NamedFunction for TextInputFormat {
+    fn name(&self) -> &str {
+        match self.source {
+            TextSource::Socket(_) => "SocketTextInputFormat",
+            TextSource::Stdin => "StdinTextInputFormat",
+        }
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for TextInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+impl Debug for TextInputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextInputFormat")
+            .field("source", &self.source)
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::functions::source::text_input_format::{line_decoder, line_schema};
+
+    #[test]
+    pub fn line_decoder_test() {
+        let schema = line_schema();
+        let mut record = line_decoder("hello rlink").unwrap();
+        let reader = record.as_reader(schema.as_type_ids());
+        assert_eq!(reader.get_str(0).unwrap(), "hello rlink");
+    }
+}
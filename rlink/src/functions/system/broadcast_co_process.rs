@@ -0,0 +1,88 @@
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{
+    BroadcastProcessFunction, CoProcessFunction, Context, NamedFunction, SendableElementStream,
+};
+use crate::core::state::BroadcastContext;
+
+/// Run the `BroadcastProcessFunction` as the `CoProcessFunction` of the data stream connected
+/// with the broadcast stream, the broadcast stream is the only right stream
+pub struct BroadcastCoProcessFunction<F> {
+    function: F,
+    broadcast_context: BroadcastContext,
+}
+
+impl<F: BroadcastProcessFunction> BroadcastCoProcessFunction<F> {
+    pub fn new(function: F) -> Self {
+        BroadcastCoProcessFunction {
+            function,
+            broadcast_context: BroadcastContext::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl<F: BroadcastProcessFunction> CoProcessFunction for BroadcastCoProcessFunction<F> {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
+        self.function.open(context).await
+    }
+
+    async fn process_left(&mut self, record: Record) -> SendableElementStream {
+        let context = self.broadcast_context.read_only();
+        self.function.process_element(record, &context).await
+    }
+
+    async fn process_right(&mut self, _stream_seq: usize, record: Record) -> SendableElementStream {
+        self.function
+            .process_broadcast_element(record, &self.broadcast_context)
+            .await
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.function.close().await
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        self.function.schema(input_schema)
+    }
+}
+
+impl<F: BroadcastProcessFunction> NamedFunction for BroadcastCoProcessFunction<F> {
+    fn name(&self) -> &str {
+        self.function.name()
+    }
+}
+
+#[async_trait]
+impl<F: BroadcastProcessFunction> CheckpointFunction for BroadcastCoProcessFunction<F> {
+    async fn initialize_state(
+        &mut self,
+        context: &FunctionSnapshotContext,
+        handle: &Option<CheckpointHandle>,
+    ) {
+        if let Some(handle) = handle {
+            self.broadcast_context
+                .restore(handle)
+                .expect("restore broadcast states error");
+            info!(
+                "restore broadcast states from checkpoint({:?})",
+                context.checkpoint_id
+            );
+        }
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        match self.broadcast_context.snapshot() {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                error!("snapshot broadcast states error. {}", e);
+                None
+            }
+        }
+    }
+}
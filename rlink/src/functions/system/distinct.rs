@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{Context, KeyedProcessFunction, NamedFunction, SendableElementStream};
+use crate::core::state::{StateTtlConfig, ValueState, ValueStateDescriptor};
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::utils::stream::MemoryStream;
+
+/// the keys seen within the retention
+const SEEN_KEYS: &str = "distinct.seen";
+
+/// Drop the records whose keys have been seen within the `ttl`, the retention of a key starts
+/// from its first record and isn't extended by the duplicates. See `TDataStream::distinct_by`
+pub(crate) struct DistinctProcessFunction {
+    ttl: Duration,
+    seen: Option<ValueState<bool>>,
+}
+
+impl DistinctProcessFunction {
+    pub fn new(ttl: Duration) -> Self {
+        DistinctProcessFunction { ttl, seen: None }
+    }
+
+    /// Whether the current key has been seen, the key is marked as seen if not
+    fn is_duplicate(&self) -> anyhow::Result<bool> {
+        let seen = self.seen.as_ref().unwrap();
+        if seen.value()?.is_some() {
+            return Ok(true);
+        }
+        seen.update(true)?;
+        Ok(false)
+    }
+}
+
+#[async_trait]
+impl KeyedProcessFunction for DistinctProcessFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let descriptor = ValueStateDescriptor::new(SEEN_KEYS).ttl(StateTtlConfig::new(self.ttl));
+        self.seen = Some(context.runtime_context().value_state(&descriptor));
+        Ok(())
+    }
+
+    async fn process_element(
+        &mut self,
+        record: Record,
+        _timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let records = if self.is_duplicate().expect("distinct state error") {
+            vec![]
+        } else {
+            vec![record]
+        };
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn on_timer(
+        &mut self,
+        _timestamp: u64,
+        _time_domain: TimeDomain,
+        _timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        Box::pin(MemoryStream::new(vec![]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        input_schema
+    }
+
+    fn parallelism(&self) -> u16 {
+        // inherit the parallelism of the upstream
+        0
+    }
+}
+
+impl NamedFunction for DistinctProcessFunction {
+    fn name(&self) -> &str {
+        "DistinctProcessFunction"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::core::state::{RuntimeContext, StateTtlConfig, ValueStateDescriptor};
+    use crate::functions::system::distinct::{DistinctProcessFunction, SEEN_KEYS};
+
+    fn key(k: &str) -> Record {
+        let schema = Schema::new(vec![Field::new("k", DataType::String)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_str(k).unwrap();
+        record
+    }
+
+    fn distinct_function(
+        runtime_context: &RuntimeContext,
+        ttl: Duration,
+    ) -> DistinctProcessFunction {
+        let mut function = DistinctProcessFunction::new(ttl);
+        let descriptor = ValueStateDescriptor::new(SEEN_KEYS).ttl(StateTtlConfig::new(ttl));
+        function.seen = Some(runtime_context.value_state(&descriptor));
+        function
+    }
+
+    #[test]
+    pub fn distinct_test() {
+        let runtime_context = RuntimeContext::new();
+        let function = distinct_function(&runtime_context, Duration::from_secs(3600));
+
+        runtime_context.set_current_key(&key("a"));
+        assert!(!function.is_duplicate().unwrap());
+        assert!(function.is_duplicate().unwrap());
+        runtime_context.set_current_key(&key("b"));
+        assert!(!function.is_duplicate().unwrap());
+
+        // the key is forgotten after the retention
+        let runtime_context = RuntimeContext::new();
+        let function = distinct_function(&runtime_context, Duration::from_millis(0));
+        runtime_context.set_current_key(&key("a"));
+        assert!(!function.is_duplicate().unwrap());
+        assert!(!function.is_duplicate().unwrap());
+    }
+}
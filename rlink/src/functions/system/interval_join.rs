@@ -0,0 +1,276 @@
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{
+    Context, JoinFunction, KeyedProcessFunction, NamedFunction, SendableElementStream,
+};
+use crate::core::state::{MapState, MapStateDescriptor};
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::functions::system::join::{bytes_record, decode_join_record, JoinSide};
+use crate::utils::stream::MemoryStream;
+
+/// the buffered records of the left side by the timestamp
+const LEFT_BUFFER: &str = "interval_join.left";
+/// the buffered records of the right side by the timestamp
+const RIGHT_BUFFER: &str = "interval_join.right";
+
+type RecordBuffer = MapState<u64, Vec<Vec<u8>>>;
+
+/// Join the left record with the right records whose timestamps are within
+/// `[left.timestamp - lower_bound, left.timestamp + upper_bound]`.
+///
+/// The records of both sides are buffered in the keyed states until the watermark passes the
+/// latest timestamp they can be joined with, the records behind the watermark are dropped
+pub(crate) struct IntervalJoinProcessFunction<F> {
+    function: F,
+    lower_bound: u64,
+    upper_bound: u64,
+    left_schema: FnSchema,
+    right_schema: FnSchema,
+
+    left_buffer: Option<RecordBuffer>,
+    right_buffer: Option<RecordBuffer>,
+}
+
+impl<F: JoinFunction> IntervalJoinProcessFunction<F> {
+    pub fn new(
+        function: F,
+        lower_bound: u64,
+        upper_bound: u64,
+        left_schema: FnSchema,
+        right_schema: FnSchema,
+    ) -> Self {
+        IntervalJoinProcessFunction {
+            function,
+            lower_bound,
+            upper_bound,
+            left_schema,
+            right_schema,
+            left_buffer: None,
+            right_buffer: None,
+        }
+    }
+
+    /// Join the `record` of the `side` with the buffered records of the other side
+    fn join(&self, side: JoinSide, record: &mut Record) -> anyhow::Result<Vec<Record>> {
+        let timestamp = record.timestamp;
+        let (other_buffer, min_timestamp, max_timestamp) = match side {
+            JoinSide::Left => (
+                self.right_buffer.as_ref().unwrap(),
+                timestamp.saturating_sub(self.lower_bound),
+                timestamp.saturating_add(self.upper_bound),
+            ),
+            JoinSide::Right => (
+                self.left_buffer.as_ref().unwrap(),
+                timestamp.saturating_sub(self.upper_bound),
+                timestamp.saturating_add(self.lower_bound),
+            ),
+        };
+
+        let mut records = Vec::new();
+        for (other_timestamp, values) in other_buffer.entries()? {
+            if other_timestamp < min_timestamp || other_timestamp > max_timestamp {
+                continue;
+            }
+            for value in values {
+                let mut other = bytes_record(value.as_slice(), other_timestamp);
+                let mut joined = match side {
+                    JoinSide::Left => self.function.join(record, &mut other),
+                    JoinSide::Right => self.function.join(&mut other, record),
+                };
+                joined.timestamp = timestamp.max(other_timestamp);
+                records.push(joined);
+            }
+        }
+        Ok(records)
+    }
+
+    fn buffer(&self, side: JoinSide, record: &Record) -> anyhow::Result<()> {
+        let buffer = match side {
+            JoinSide::Left => self.left_buffer.as_ref().unwrap(),
+            JoinSide::Right => self.right_buffer.as_ref().unwrap(),
+        };
+        let mut values = buffer.get(&record.timestamp)?.unwrap_or_default();
+        values.push(record.values.as_slice().to_vec());
+        buffer.put(&record.timestamp, values)
+    }
+
+    /// The time the record can't be joined by the other side anymore
+    fn cleanup_time(&self, side: JoinSide, timestamp: u64) -> u64 {
+        match side {
+            JoinSide::Left => timestamp.saturating_add(self.upper_bound),
+            JoinSide::Right => timestamp.saturating_add(self.lower_bound),
+        }
+    }
+}
+
+#[async_trait]
+impl<F: JoinFunction> KeyedProcessFunction for IntervalJoinProcessFunction<F> {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let runtime_context = context.runtime_context();
+        self.left_buffer = Some(runtime_context.map_state(&MapStateDescriptor::new(LEFT_BUFFER)));
+        self.right_buffer = Some(runtime_context.map_state(&MapStateDescriptor::new(RIGHT_BUFFER)));
+
+        self.function.open(context).await
+    }
+
+    async fn process_element(
+        &mut self,
+        mut record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let (side, mut record) = decode_join_record(&mut record);
+        if record.timestamp < timer_service.current_watermark() {
+            return Box::pin(MemoryStream::new(vec![]));
+        }
+
+        let records = self.join(side, &mut record).expect("interval join error");
+        self.buffer(side, &record)
+            .expect("buffer the interval join record error");
+        timer_service.register_event_time_timer(self.cleanup_time(side, record.timestamp));
+
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn on_timer(
+        &mut self,
+        timestamp: u64,
+        _time_domain: TimeDomain,
+        _timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        // the timer is the cleanup time of the left records, the right records or both
+        if timestamp >= self.upper_bound {
+            let left_timestamp = timestamp - self.upper_bound;
+            self.left_buffer
+                .as_ref()
+                .unwrap()
+                .remove(&left_timestamp)
+                .expect("cleanup the interval join buffer error");
+        }
+        if timestamp >= self.lower_bound {
+            let right_timestamp = timestamp - self.lower_bound;
+            self.right_buffer
+                .as_ref()
+                .unwrap()
+                .remove(&right_timestamp)
+                .expect("cleanup the interval join buffer error");
+        }
+
+        Box::pin(MemoryStream::new(vec![]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.function.close().await
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.function
+            .schema(self.left_schema.clone(), self.right_schema.clone())
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.function.parallelism()
+    }
+}
+
+impl<F: JoinFunction> NamedFunction for IntervalJoinProcessFunction<F> {
+    fn name(&self) -> &str {
+        self.function.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{FnSchema, Record};
+    use crate::core::function::{Context, JoinFunction, NamedFunction};
+    use crate::core::state::{MapStateDescriptor, RuntimeContext};
+    use crate::functions::system::interval_join::{
+        IntervalJoinProcessFunction, LEFT_BUFFER, RIGHT_BUFFER,
+    };
+    use crate::functions::system::join::JoinSide;
+
+    struct ConcatJoinFunction {}
+
+    #[async_trait]
+    impl JoinFunction for ConcatJoinFunction {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn join(&self, left: &mut Record, right: &mut Record) -> Record {
+            let mut record = left.clone();
+            record.extend(right.clone()).unwrap();
+            record
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, left_schema: FnSchema, _right_schema: FnSchema) -> FnSchema {
+            left_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            1
+        }
+    }
+
+    impl NamedFunction for ConcatJoinFunction {
+        fn name(&self) -> &str {
+            "ConcatJoinFunction"
+        }
+    }
+
+    fn record(v: &str, timestamp: u64) -> Record {
+        let schema = Schema::new(vec![Field::new("v", DataType::String)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_str(v).unwrap();
+        record.timestamp = timestamp;
+        record
+    }
+
+    #[test]
+    pub fn interval_join_test() {
+        let mut function = IntervalJoinProcessFunction::new(
+            ConcatJoinFunction {},
+            10,
+            20,
+            FnSchema::Empty,
+            FnSchema::Empty,
+        );
+        let runtime_context = RuntimeContext::new();
+        runtime_context.set_current_key(&record("k", 0));
+        function.left_buffer =
+            Some(runtime_context.map_state(&MapStateDescriptor::new(LEFT_BUFFER)));
+        function.right_buffer =
+            Some(runtime_context.map_state(&MapStateDescriptor::new(RIGHT_BUFFER)));
+
+        for timestamp in [89, 90, 120, 121] {
+            function
+                .buffer(JoinSide::Right, &record("r", timestamp))
+                .unwrap();
+        }
+
+        // the right records within [100 - 10, 100 + 20] are joined
+        let joined = function
+            .join(JoinSide::Left, &mut record("l", 100))
+            .unwrap();
+        let timestamps: Vec<u64> = joined.iter().map(|x| x.timestamp).collect();
+        assert_eq!(timestamps, vec![100, 120]);
+
+        function.buffer(JoinSide::Left, &record("l", 100)).unwrap();
+        assert_eq!(function.cleanup_time(JoinSide::Left, 100), 120);
+        assert_eq!(function.cleanup_time(JoinSide::Right, 100), 110);
+
+        // the left record joins the right records within [t - 20, t + 10]
+        let joined = function
+            .join(JoinSide::Right, &mut record("r", 81))
+            .unwrap();
+        assert_eq!(joined.len(), 1);
+        assert_eq!(joined[0].timestamp, 100);
+        let joined = function
+            .join(JoinSide::Right, &mut record("r", 79))
+            .unwrap();
+        assert!(joined.is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! The feedback edge of the iterative streams, see `TDataStream::iterate`.
+//!
+//! The records of the feedback stream are sent back to the iteration head by an in-process
+//! channel of each iteration and task number, the head and the tail tasks of the same number
+//! are allocated to the same worker by their co-location group.
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::Poll;
+use std::time::Duration;
+
+use futures::Stream;
+use tokio::time::Sleep;
+
+use crate::channel::{named_channel, ElementReceiver, ElementSender};
+use crate::core;
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema};
+use crate::core::function::{
+    Context, ElementStream, InputFormat, InputSplit, InputSplitSource, NamedFunction, OutputFormat,
+    SendableElementStream,
+};
+use crate::core::properties::SystemProperties;
+use crate::functions::system::system_input_format::SystemInputFormat;
+use crate::metrics::Tag;
+use crate::pub_sub::DEFAULT_CHANNEL_SIZE;
+
+type FeedbackChannels = HashMap<(u32, u16), (ElementSender, Option<ElementReceiver>)>;
+
+lazy_static! {
+    static ref FEEDBACK_CHANNELS: Mutex<FeedbackChannels> = Mutex::new(HashMap::new());
+}
+
+/// The co-location group of the head and the tail jobs of the iteration
+pub(crate) fn co_location_group(iteration_id: u32) -> String {
+    format!("iteration-{}", iteration_id)
+}
+
+fn insert(
+    channels: &mut FeedbackChannels,
+    iteration_id: u32,
+    task_number: u16,
+    channel_size: usize,
+) -> &mut (ElementSender, Option<ElementReceiver>) {
+    channels
+        .entry((iteration_id, task_number))
+        .or_insert_with(|| {
+            let (sender, receiver) = named_channel(
+                "Iteration_Feedback",
+                vec![
+                    Tag::new("iteration_id", iteration_id),
+                    Tag::new("task_number", task_number),
+                ],
+                channel_size,
+            );
+            (sender, Some(receiver))
+        })
+}
+
+fn feedback_sender(iteration_id: u32, task_number: u16, channel_size: usize) -> ElementSender {
+    let mut channels = FEEDBACK_CHANNELS.lock().unwrap();
+    let (sender, _receiver) = insert(&mut channels, iteration_id, task_number, channel_size);
+    sender.clone()
+}
+
+fn feedback_receiver(
+    iteration_id: u32,
+    task_number: u16,
+    channel_size: usize,
+) -> Option<ElementReceiver> {
+    let mut channels = FEEDBACK_CHANNELS.lock().unwrap();
+    let (_sender, receiver) = insert(&mut channels, iteration_id, task_number, channel_size);
+    receiver.take()
+}
+
+fn channel_size(context: &Context) -> usize {
+    context
+        .application_properties
+        .get_pub_sub_channel_size()
+        .unwrap_or(DEFAULT_CHANNEL_SIZE)
+}
+
+/// The virtual source of the iteration body, it reads the records of the iteration input and
+/// the feedback records of its task.
+///
+/// The end of the input is held until no feedback record is received in `max_wait`, so the
+/// records still circulating in the iteration are processed before the task ends. The feedback
+/// records are neither aligned by the barriers nor checkpointed, and they may be late for the
+/// watermarks of the input
+pub(crate) struct IterationHeadInputFormat {
+    iteration_id: u32,
+    max_wait: Duration,
+
+    input: SystemInputFormat,
+    feedback_receiver: Option<ElementReceiver>,
+}
+
+impl IterationHeadInputFormat {
+    pub fn new(iteration_id: u32, max_wait: Duration) -> Self {
+        IterationHeadInputFormat {
+            iteration_id,
+            max_wait,
+            input: SystemInputFormat::new(),
+            feedback_receiver: None,
+        }
+    }
+}
+
+#[async_trait]
+impl InputFormat for IterationHeadInputFormat {
+    async fn open(&mut self, input_split: InputSplit, context: &Context) -> core::Result<()> {
+        self.input.open(input_split, context).await?;
+
+        let receiver = feedback_receiver(
+            self.iteration_id,
+            context.task_id.task_number,
+            channel_size(context),
+        )
+        .ok_or_else(|| {
+            anyhow!(
+                "duplicate iteration head {}, task_number={}",
+                self.iteration_id,
+                context.task_id.task_number
+            )
+        })?;
+        self.feedback_receiver = Some(receiver);
+
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let input = self.input.element_stream().await;
+        let feedback = self
+            .feedback_receiver
+            .take()
+            .expect("the iteration head is not opened");
+        Box::pin(IterationHeadStream::new(input, feedback, self.max_wait))
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        self.input.close().await
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        input_schema
+    }
+
+    /// ignore
+    fn parallelism(&self) -> u16 {
+        0
+    }
+}
+
+impl InputSplitSource for IterationHeadInputFormat {}
+
+impl NamedFunction for IterationHeadInputFormat {
+    fn name(&self) -> &str {
+        "IterationHeadInputFormat"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for IterationHeadInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+/// Merge the feedback records into the input, the feedback records go first
+struct IterationHeadStream {
+    input: SendableElementStream,
+    feedback: ElementReceiver,
+    max_wait: Duration,
+
+    /// the end `StreamStatus` of the input, held until the feedback is idle for `max_wait`
+    end_status: VecDeque<Element>,
+    idle_timeout: Option<Pin<Box<Sleep>>>,
+}
+
+impl IterationHeadStream {
+    fn new(input: SendableElementStream, feedback: ElementReceiver, max_wait: Duration) -> Self {
+        IterationHeadStream {
+            input,
+            feedback,
+            max_wait,
+            end_status: VecDeque::new(),
+            idle_timeout: None,
+        }
+    }
+
+    fn reset_idle_timeout(&mut self) {
+        self.idle_timeout = Some(Box::pin(tokio::time::sleep(self.max_wait)));
+    }
+}
+
+impl ElementStream for IterationHeadStream {}
+
+impl Stream for IterationHeadStream {
+    type Item = Element;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let s = self.get_mut();
+
+        loop {
+            if let Poll::Ready(Some(element)) = s.feedback.poll_recv(cx) {
+                if s.idle_timeout.is_some() {
+                    s.reset_idle_timeout();
+                }
+                return Poll::Ready(Some(element));
+            }
+
+            match s.input.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Element::StreamStatus(stream_status))) if stream_status.end => {
+                    s.end_status.push_back(Element::StreamStatus(stream_status));
+                    if s.idle_timeout.is_none() {
+                        s.reset_idle_timeout();
+                    }
+                    // poll the input again to be woken by its next element
+                    continue;
+                }
+                Poll::Ready(element) => return Poll::Ready(element),
+                Poll::Pending => {}
+            }
+
+            if let Some(idle_timeout) = s.idle_timeout.as_mut() {
+                if idle_timeout.as_mut().poll(cx).is_ready() {
+                    let element = s.end_status.pop_front();
+                    if s.end_status.is_empty() {
+                        s.idle_timeout = None;
+                    }
+                    if element.is_some() {
+                        return Poll::Ready(element);
+                    }
+                }
+            }
+
+            return Poll::Pending;
+        }
+    }
+}
+
+/// The sink of the feedback stream, the records are sent back to the iteration head of the
+/// same task number
+pub(crate) struct IterationTailOutputFormat {
+    iteration_id: u32,
+    sender: Option<ElementSender>,
+}
+
+impl IterationTailOutputFormat {
+    pub fn new(iteration_id: u32) -> Self {
+        IterationTailOutputFormat {
+            iteration_id,
+            sender: None,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputFormat for IterationTailOutputFormat {
+    async fn open(&mut self, context: &Context) -> core::Result<()> {
+        let sender = feedback_sender(
+            self.iteration_id,
+            context.task_id.task_number,
+            channel_size(context),
+        );
+        self.sender = Some(sender);
+        Ok(())
+    }
+
+    async fn write_element(&mut self, element: Element) {
+        if !element.is_record() {
+            return;
+        }
+
+        let sender = self.sender.as_ref().unwrap();
+        if let Err(_e) = sender.send(element).await {
+            error!(
+                "iteration {} feedback channel has closed",
+                self.iteration_id
+            );
+        }
+    }
+
+    async fn close(&mut self) -> core::Result<()> {
+        Ok(())
+    }
+}
+
+impl NamedFunction for IterationTailOutputFormat {
+    fn name(&self) -> &str {
+        "IterationTailOutputFormat"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for IterationTailOutputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
@@ -0,0 +1,230 @@
+use bytes::BytesMut;
+use serbuffer::types;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::{DataType, Field, Schema};
+use crate::core::element::{Buffer, FnSchema, Record};
+use crate::core::function::{
+    CoProcessFunction, Context, KeySelectorFunction, NamedFunction, SendableElementStream,
+};
+use crate::utils::stream::MemoryStream;
+
+/// the types of the join record: the side, the key and the values of the original record
+const JOIN_RECORD_TYPES: [u8; 3] = [types::U8, types::BINARY, types::BINARY];
+
+/// The side of the joined streams a join record comes from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum JoinSide {
+    Left = 0,
+    Right = 1,
+}
+
+fn join_schema() -> Schema {
+    Schema::new(vec![
+        Field::new("side", DataType::UInt8),
+        Field::new("key", DataType::Binary),
+        Field::new("value", DataType::Binary),
+    ])
+}
+
+/// Restore the record from the bytes of its values, e.g. the key or the buffered record
+pub(crate) fn bytes_record(bytes: &[u8], timestamp: u64) -> Record {
+    let mut record = Record::new();
+    record.values = Buffer::from(BytesMut::from(bytes));
+    record.timestamp = timestamp;
+    record
+}
+
+/// Wrap the record of either side with its join key, so the records of both sides are keyed by
+/// the same `JoinKeySelectorFunction` and processed by the same keyed operator
+pub(crate) fn encode_join_record(side: JoinSide, key: &Record, record: &Record) -> Record {
+    let mut join_record = Record::with_capacity(key.len() + record.len() + 16);
+    join_record.timestamp = record.timestamp;
+
+    let mut writer = join_record.as_writer(&JOIN_RECORD_TYPES);
+    writer.set_u8(side as u8).unwrap();
+    writer.set_binary(key.values.as_slice()).unwrap();
+    writer.set_binary(record.values.as_slice()).unwrap();
+
+    join_record
+}
+
+/// The side and the original record of the join record
+pub(crate) fn decode_join_record(join_record: &mut Record) -> (JoinSide, Record) {
+    let timestamp = join_record.timestamp;
+    let reader = join_record.as_reader(&JOIN_RECORD_TYPES);
+    let side = if reader.get_u8(0).unwrap() == JoinSide::Left as u8 {
+        JoinSide::Left
+    } else {
+        JoinSide::Right
+    };
+    let record = bytes_record(reader.get_binary(2).unwrap(), timestamp);
+    (side, record)
+}
+
+/// Key the records of both sides by the key selectors of their own side, and wrap them as the
+/// join records. The left stream is the connected one, the right stream is the only other
+pub(crate) struct JoinCoProcessFunction {
+    left_key_selector: Box<dyn KeySelectorFunction>,
+    right_key_selector: Box<dyn KeySelectorFunction>,
+    left_schema: FnSchema,
+    right_schema: FnSchema,
+}
+
+impl JoinCoProcessFunction {
+    pub fn new(
+        left_key_selector: Box<dyn KeySelectorFunction>,
+        right_key_selector: Box<dyn KeySelectorFunction>,
+        left_schema: FnSchema,
+        right_schema: FnSchema,
+    ) -> Self {
+        JoinCoProcessFunction {
+            left_key_selector,
+            right_key_selector,
+            left_schema,
+            right_schema,
+        }
+    }
+}
+
+#[async_trait]
+impl CoProcessFunction for JoinCoProcessFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        // the key selectors read the records by the schema of their own side
+        let mut left_context = context.clone();
+        left_context.input_schema = self.left_schema.clone();
+        self.left_key_selector.open(&left_context).await?;
+
+        let mut right_context = context.clone();
+        right_context.input_schema = self.right_schema.clone();
+        self.right_key_selector.open(&right_context).await
+    }
+
+    async fn process_left(&mut self, mut record: Record) -> SendableElementStream {
+        let key = self.left_key_selector.get_key(&mut record).await;
+        let join_record = encode_join_record(JoinSide::Left, &key, &record);
+        Box::pin(MemoryStream::new(vec![join_record]))
+    }
+
+    async fn process_right(
+        &mut self,
+        _stream_seq: usize,
+        mut record: Record,
+    ) -> SendableElementStream {
+        let key = self.right_key_selector.get_key(&mut record).await;
+        let join_record = encode_join_record(JoinSide::Right, &key, &record);
+        Box::pin(MemoryStream::new(vec![join_record]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.left_key_selector.close().await?;
+        self.right_key_selector.close().await
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&join_schema())
+    }
+}
+
+impl NamedFunction for JoinCoProcessFunction {
+    fn name(&self) -> &str {
+        "JoinCoProcessFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for JoinCoProcessFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+/// Key the join records by the key of their own side
+pub(crate) struct JoinKeySelectorFunction {
+    key_schema: FnSchema,
+}
+
+impl JoinKeySelectorFunction {
+    pub fn new(key_schema: FnSchema) -> Self {
+        JoinKeySelectorFunction { key_schema }
+    }
+}
+
+#[async_trait]
+impl KeySelectorFunction for JoinKeySelectorFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    async fn get_key(&self, record: &mut Record) -> Record {
+        let reader = record.as_reader(&JOIN_RECORD_TYPES);
+        bytes_record(reader.get_binary(1).unwrap(), 0)
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn key_schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.key_schema.clone()
+    }
+}
+
+impl NamedFunction for JoinKeySelectorFunction {
+    fn name(&self) -> &str {
+        "JoinKeySelectorFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for JoinKeySelectorFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::functions::system::join::{decode_join_record, encode_join_record, JoinSide};
+
+    #[test]
+    pub fn join_record_test() {
+        let schema = Schema::new(vec![Field::new("v", DataType::String)]);
+        let mut record = Record::new();
+        record
+            .as_writer(schema.as_type_ids())
+            .set_str("v1")
+            .unwrap();
+        record.timestamp = 100;
+        let mut key = Record::new();
+        key.as_writer(schema.as_type_ids()).set_str("k1").unwrap();
+
+        let mut join_record = encode_join_record(JoinSide::Right, &key, &record);
+        let (side, decoded) = decode_join_record(&mut join_record);
+        assert_eq!(side, JoinSide::Right);
+        assert_eq!(decoded, record);
+        assert_eq!(decoded.timestamp, 100);
+    }
+}
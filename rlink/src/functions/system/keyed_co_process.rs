@@ -0,0 +1,73 @@
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{
+    Context, KeyedCoProcessFunction, KeyedProcessFunction, NamedFunction, SendableElementStream,
+};
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::functions::system::join::{decode_join_record, JoinSide};
+
+/// Dispatch the join records of the connected streams to the `KeyedCoProcessFunction` by their
+/// side, both sides are keyed by the same `JoinKeySelectorFunction` so they share the keyed
+/// states and the timers
+pub(crate) struct KeyedCoProcessAdapterFunction<F> {
+    function: F,
+    left_schema: FnSchema,
+    right_schema: FnSchema,
+}
+
+impl<F: KeyedCoProcessFunction> KeyedCoProcessAdapterFunction<F> {
+    pub fn new(function: F, left_schema: FnSchema, right_schema: FnSchema) -> Self {
+        KeyedCoProcessAdapterFunction {
+            function,
+            left_schema,
+            right_schema,
+        }
+    }
+}
+
+#[async_trait]
+impl<F: KeyedCoProcessFunction> KeyedProcessFunction for KeyedCoProcessAdapterFunction<F> {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.function.open(context).await
+    }
+
+    async fn process_element(
+        &mut self,
+        mut record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        match decode_join_record(&mut record) {
+            (JoinSide::Left, record) => self.function.process_left(record, timer_service).await,
+            (JoinSide::Right, record) => self.function.process_right(record, timer_service).await,
+        }
+    }
+
+    async fn on_timer(
+        &mut self,
+        timestamp: u64,
+        time_domain: TimeDomain,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        self.function
+            .on_timer(timestamp, time_domain, timer_service)
+            .await
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.function.close().await
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.function
+            .schema(self.left_schema.clone(), self.right_schema.clone())
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.function.parallelism()
+    }
+}
+
+impl<F: KeyedCoProcessFunction> NamedFunction for KeyedCoProcessAdapterFunction<F> {
+    fn name(&self) -> &str {
+        self.function.name()
+    }
+}
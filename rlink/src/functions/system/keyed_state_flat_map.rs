@@ -54,7 +54,7 @@ impl FlatMapFunction for KeyedStateFlatMapFunction {
         let window = record.trigger_window.unwrap();
 
         let state_key = StateKey::new(window.clone(), self.parent_job_id, self.task_number);
-        let reducing_state = ReducingState::new(&state_key, self.state_mode);
+        let reducing_state = ReducingState::new(&state_key, self.state_mode.clone());
         match reducing_state {
             Some(reducing_state) => {
                 let state_iter = reducing_state.iter();
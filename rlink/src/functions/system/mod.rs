@@ -1,4 +1,14 @@
+pub mod broadcast_co_process;
+pub mod distinct;
+pub mod interval_join;
+pub mod iteration;
+pub mod join;
+pub mod keyed_co_process;
 pub mod keyed_state_flat_map;
+pub mod side_output;
 pub mod system_input_format;
 pub mod system_output_format;
-pub mod window_base_reduce;
\ No newline at end of file
+pub mod temporal_join;
+pub mod union;
+pub mod window_base_reduce;
+pub mod window_join;
@@ -0,0 +1,98 @@
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{Element, FnSchema, OutputTag, Record};
+use crate::core::function::{Context, FlatMapFunction, NamedFunction, SendableElementStream};
+use crate::utils::stream::MemoryStream;
+
+/// Select the records of a side output, or of the main output if the `output_tag` is `None`,
+/// from the output of an operator with side outputs. Every child job of the operator starts with
+/// it, see `RawStreamGraph::add_side_output`
+pub(crate) struct SideOutputFlatMapFunction {
+    output_tag: Option<OutputTag>,
+}
+
+impl SideOutputFlatMapFunction {
+    pub fn new(output_tag: Option<OutputTag>) -> Self {
+        SideOutputFlatMapFunction { output_tag }
+    }
+
+    fn select(&self, mut record: Record) -> Option<Record> {
+        let output_tag = self.output_tag.as_ref().map(|x| x.name());
+        if record.output_tag() == output_tag {
+            record.output_tag = None;
+            Some(record)
+        } else {
+            None
+        }
+    }
+}
+
+#[async_trait]
+impl FlatMapFunction for SideOutputFlatMapFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let records: Vec<Record> = self.select(element.into_record()).into_iter().collect();
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        match &self.output_tag {
+            Some(output_tag) => FnSchema::from(output_tag.schema()),
+            None => input_schema,
+        }
+    }
+}
+
+impl NamedFunction for SideOutputFlatMapFunction {
+    fn name(&self) -> &str {
+        "SideOutputFlatMapFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for SideOutputFlatMapFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{OutputTag, Record};
+    use crate::functions::system::side_output::SideOutputFlatMapFunction;
+
+    #[test]
+    pub fn side_output_select_test() {
+        let schema = Schema::new(vec![Field::new("v", DataType::String)]);
+        let late = OutputTag::new("late", schema.clone());
+        let error = OutputTag::new("error", schema);
+
+        let main_output = SideOutputFlatMapFunction::new(None);
+        let late_output = SideOutputFlatMapFunction::new(Some(late.clone()));
+
+        assert!(main_output.select(Record::new()).is_some());
+        assert!(main_output.select(late.output(Record::new())).is_none());
+
+        assert!(late_output.select(Record::new()).is_none());
+        assert!(late_output.select(error.output(Record::new())).is_none());
+        let record = late_output.select(late.output(Record::new())).unwrap();
+        assert_eq!(record.output_tag(), None);
+    }
+}
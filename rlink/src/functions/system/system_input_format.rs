@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Poll;
@@ -8,6 +9,7 @@ use crate::channel::ElementReceiver;
 use crate::core;
 use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
 use crate::core::element::{Element, FnSchema};
+use crate::core::env::ExecutionMode;
 use crate::core::function::{
     Context, ElementStream, InputFormat, InputSplit, InputSplitSource, NamedFunction,
     SendableElementStream,
@@ -15,12 +17,15 @@ use crate::core::function::{
 use crate::core::properties::SystemProperties;
 use crate::core::runtime::TaskId;
 use crate::dag::execution_graph::ExecutionEdge;
+use crate::pub_sub::spill::{SpillBuffer, DEFAULT_SPILL_MEMORY_SIZE};
 use crate::pub_sub::{memory, network, DEFAULT_CHANNEL_SIZE};
 use crate::runtime::worker::WorkerTaskContext;
 
 pub(crate) struct SystemInputFormat {
     memory_receiver: Option<ElementReceiver>,
     network_receiver: Option<ElementReceiver>,
+    /// the network parents the input is blocked by in the `ExecutionMode::Batch`
+    blocking_parents: HashSet<TaskId>,
 
     task_id: TaskId,
     context: Option<Context>,
@@ -31,6 +36,7 @@ impl SystemInputFormat {
         SystemInputFormat {
             memory_receiver: None,
             network_receiver: None,
+            blocking_parents: HashSet::new(),
             task_id: TaskId::default(),
             context: None,
         }
@@ -87,6 +93,10 @@ impl InputFormat for SystemInputFormat {
                 channel_size,
             );
             self.network_receiver = Some(rx);
+
+            if context.application_properties.get_execution_mode() == ExecutionMode::Batch {
+                self.blocking_parents = network_jobs.into_iter().collect();
+            }
         }
 
         Ok(())
@@ -115,10 +125,18 @@ impl InputFormat for SystemInputFormat {
             receivers.push(n);
         }
 
-        Box::pin(InputChannelStream::new(
-            receivers,
-            self.context.as_ref().unwrap().task_context(),
-        ))
+        let input =
+            InputChannelStream::new(receivers, self.context.as_ref().unwrap().task_context());
+        if self.blocking_parents.is_empty() {
+            Box::pin(input)
+        } else {
+            let blocking_parents = std::mem::take(&mut self.blocking_parents);
+            Box::pin(BlockingInputStream::new(
+                input,
+                blocking_parents,
+                &self.task_id,
+            ))
+        }
         // match receivers.len() {
         //     0 => panic!("unsupported"),
         //     1 => Box::new(ChannelIterator::new(receivers.remove(0))),
@@ -298,3 +316,62 @@ impl Stream for InputChannelStream {
         Poll::Pending
     }
 }
+
+/// The blocking input of a stage in the `ExecutionMode::Batch`, the elements are buffered
+/// until all the network parents have ended, so the stage starts processing once the stages
+/// before it have finished. The elements over the `DEFAULT_SPILL_MEMORY_SIZE` bytes are
+/// spilled to the disk
+struct BlockingInputStream {
+    input: InputChannelStream,
+    /// the network parents not ended yet
+    running_parents: HashSet<TaskId>,
+    buffer: SpillBuffer,
+}
+
+impl BlockingInputStream {
+    fn new(input: InputChannelStream, running_parents: HashSet<TaskId>, task_id: &TaskId) -> Self {
+        BlockingInputStream {
+            input,
+            running_parents,
+            buffer: SpillBuffer::new(task_id, DEFAULT_SPILL_MEMORY_SIZE),
+        }
+    }
+}
+
+impl ElementStream for BlockingInputStream {}
+
+impl Stream for BlockingInputStream {
+    type Item = Element;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let s = self.get_mut();
+
+        while !s.running_parents.is_empty() {
+            match Pin::new(&mut s.input).poll_next(cx) {
+                Poll::Ready(Some(element)) => {
+                    if let Element::StreamStatus(stream_status) = &element {
+                        if stream_status.end {
+                            let source_task_id = &stream_status.channel_key.source_task_id;
+                            if s.running_parents.remove(source_task_id) {
+                                info!("blocking input parent {:?} finished", source_task_id);
+                            }
+                        }
+                    }
+                    s.buffer
+                        .push(element)
+                        .expect("buffer the blocking input error");
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        match s.buffer.pop().expect("read the blocking input error") {
+            Some(element) => Poll::Ready(Some(element)),
+            None => Pin::new(&mut s.input).poll_next(cx),
+        }
+    }
+}
@@ -1,20 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use bytes::BytesMut;
 
 use crate::channel::ElementSender;
-use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
-use crate::core::element::{Element, FnSchema, Partition, StreamStatus};
+use crate::core::checkpoint::{
+    CheckpointFunction, CheckpointHandle, CheckpointMode, FunctionSnapshotContext,
+};
+use crate::core::element::{Element, FnSchema, Partition, Serde, StreamStatus};
 use crate::core::function::{Context, NamedFunction, OutputFormat};
 use crate::core::properties::SystemProperties;
 use crate::core::runtime::{ChannelKey, JobId, TaskId};
 use crate::dag::execution_graph::ExecutionEdge;
 use crate::pub_sub::{memory, network, ChannelType, DEFAULT_CHANNEL_SIZE};
 
+/// The records overtaken by the `Barrier` of the unaligned checkpoint
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChannelState {
+    /// Vec<(TaskId(child), serialized records)>
+    in_flight_records: Vec<(TaskId, Vec<Vec<u8>>)>,
+}
+
 /// support job's Multiplexing, but only one channel mode(memory/network) support
 pub(crate) struct SystemOutputFormat {
     task_id: TaskId,
     channel_type: ChannelType,
     // Vec<JobId(self), Vec<(TaskId(child), ElementSender)>)>
     job_senders: Vec<(JobId, Vec<(TaskId, ElementSender)>)>,
+
+    checkpoint_mode: CheckpointMode,
+    /// the children with multiple input tasks, the `Barrier` doesn't overtake the elements to
+    /// them even in the unaligned mode, as their side of the channels isn't snapshot
+    aligned_tasks: HashSet<TaskId>,
+    /// the elements taken from the network channels by the unaligned checkpoint, they are
+    /// sent after the `Barrier`
+    in_flight_elements: HashMap<TaskId, Vec<Element>>,
 }
 
 impl SystemOutputFormat {
@@ -23,6 +42,89 @@ impl SystemOutputFormat {
             task_id: TaskId::default(),
             channel_type: ChannelType::Memory,
             job_senders: Vec::new(),
+            checkpoint_mode: CheckpointMode::Aligned,
+            aligned_tasks: HashSet::new(),
+            in_flight_elements: HashMap::new(),
+        }
+    }
+
+    fn is_unaligned(&self) -> bool {
+        match self.channel_type {
+            ChannelType::Network => self.checkpoint_mode == CheckpointMode::Unaligned,
+            ChannelType::Memory => false,
+        }
+    }
+
+    /// Whether the `Barrier` overtakes the in-flight elements to the task
+    fn is_unaligned_task(&self, task_id: &TaskId) -> bool {
+        self.is_unaligned() && !self.aligned_tasks.contains(task_id)
+    }
+
+    fn network_sender(&self, target_task_id: &TaskId) -> Option<&ElementSender> {
+        self.job_senders
+            .iter()
+            .flat_map(|(_job_id, task_senders)| task_senders.iter())
+            .find(|(task_id, _sender)| task_id.eq(target_task_id))
+            .map(|(_task_id, sender)| sender)
+    }
+
+    /// Take the elements buffered in the network channels to the unaligned tasks, the records
+    /// are the channel state and all elements are kept to be sent after the `Barrier`
+    fn take_channel_state(&mut self) -> ChannelState {
+        let mut channel_state = ChannelState::default();
+        for (_job_id, task_senders) in &self.job_senders {
+            for (task_id, _sender) in task_senders {
+                if !self.is_unaligned_task(task_id) {
+                    continue;
+                }
+                let channel_key = ChannelKey {
+                    source_task_id: self.task_id,
+                    target_task_id: *task_id,
+                };
+                let elements = network::drain_network_channel(&channel_key);
+                let records = elements
+                    .iter()
+                    .filter(|element| element.is_record())
+                    .map(|element| {
+                        let mut bytes = BytesMut::with_capacity(element.capacity());
+                        element.serialize(&mut bytes);
+                        bytes.to_vec()
+                    })
+                    .collect();
+
+                channel_state.in_flight_records.push((*task_id, records));
+                // the overtaken `Barrier` of the previous checkpoint is superseded
+                self.in_flight_elements
+                    .entry(*task_id)
+                    .or_insert_with(Vec::new)
+                    .extend(elements.into_iter().filter(|element| !element.is_barrier()));
+            }
+        }
+        channel_state
+    }
+
+    async fn replay_channel_state(&self, channel_state: ChannelState) {
+        for (task_id, records) in channel_state.in_flight_records {
+            let sender = match self.network_sender(&task_id) {
+                Some(sender) => sender,
+                None => {
+                    warn!(
+                        "the channel to {:?} not found, channel state dropped",
+                        task_id
+                    );
+                    continue;
+                }
+            };
+
+            info!(
+                "replay {} in-flight records to {:?}",
+                records.len(),
+                task_id
+            );
+            for record in records {
+                let element = Element::deserialize(&mut BytesMut::from(record.as_slice()));
+                sender.send(element).await.unwrap();
+            }
         }
     }
 }
@@ -48,6 +150,10 @@ impl OutputFormat for SystemOutputFormat {
             .application_properties
             .get_pub_sub_channel_size()
             .unwrap_or(DEFAULT_CHANNEL_SIZE);
+        self.checkpoint_mode = context
+            .application_properties
+            .get_checkpoint_mode()
+            .unwrap_or_default();
 
         let mut memory_jobs = Vec::new();
         let mut network_jobs = Vec::new();
@@ -130,7 +236,25 @@ impl OutputFormat for SystemOutputFormat {
 
                 self.job_senders.push((job_id, task_senders));
             }
+
+            if self.checkpoint_mode == CheckpointMode::Unaligned {
+                let dag_metadata = context.task_context().dag_metadata();
+                self.aligned_tasks = network_jobs
+                    .iter()
+                    .filter(|task_id| dag_metadata.execution_parents(task_id).len() > 1)
+                    .cloned()
+                    .collect();
+                if !self.aligned_tasks.is_empty() {
+                    warn!(
+                        "the channels to the tasks with multiple inputs are aligned. {:?}",
+                        self.aligned_tasks
+                    );
+                }
+            }
         }
+
+        self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
+            .await;
         Ok(())
     }
 
@@ -159,6 +283,19 @@ impl OutputFormat for SystemOutputFormat {
                     }
                 }
             }
+            ChannelType::Network if element.is_barrier() && self.is_unaligned() => {
+                let partition = element.partition() as usize;
+                for (_job, task_senders) in &self.job_senders {
+                    let (task_id, sender) = task_senders.get(partition).unwrap();
+                    sender.send(element.clone()).await.unwrap();
+
+                    if let Some(elements) = self.in_flight_elements.remove(task_id) {
+                        for element in elements {
+                            sender.send(element).await.unwrap();
+                        }
+                    }
+                }
+            }
             ChannelType::Network => {
                 if self.job_senders.len() == 1 {
                     let (_job_id, task_senders) = &self.job_senders[0];
@@ -205,17 +342,137 @@ impl NamedFunction for SystemOutputFormat {
 
 #[async_trait]
 impl CheckpointFunction for SystemOutputFormat {
+    /// Replay the records overtaken by the `Barrier` of the restored checkpoint, even the
+    /// checkpoint mode has been changed to `Aligned`
     async fn initialize_state(
         &mut self,
         _context: &FunctionSnapshotContext,
-        _handle: &Option<CheckpointHandle>,
+        handle: &Option<CheckpointHandle>,
     ) {
+        let channel_state = match handle {
+            Some(handle) if !handle.handle.is_empty() => {
+                serde_json::from_str::<ChannelState>(handle.handle.as_str())
+                    .expect("parse channel state error")
+            }
+            _ => return,
+        };
+        self.replay_channel_state(channel_state).await;
     }
 
+    /// Take the in-flight elements of the network channels, the `Barrier` is sent before them
     async fn snapshot_state(
         &mut self,
-        _context: &FunctionSnapshotContext,
+        context: &FunctionSnapshotContext,
     ) -> Option<CheckpointHandle> {
-        None
+        if !self.is_unaligned() {
+            return None;
+        }
+
+        let channel_state = self.take_channel_state();
+        debug!(
+            "checkpoint {:?} overtakes {} in-flight records",
+            context.checkpoint_id,
+            channel_state
+                .in_flight_records
+                .iter()
+                .map(|(_task_id, records)| records.len())
+                .sum::<usize>()
+        );
+
+        let handle = serde_json::to_string(&channel_state).unwrap();
+        Some(CheckpointHandle { handle })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::core::checkpoint::CheckpointMode;
+    use crate::core::element::{Element, Partition, Record};
+    use crate::core::function::OutputFormat;
+    use crate::core::runtime::{ChannelKey, CheckpointId, JobId, TaskId};
+    use crate::functions::system::system_output_format::{ChannelState, SystemOutputFormat};
+    use crate::pub_sub::{network, ChannelType};
+
+    fn element(timestamp: u64, partition: u16) -> Element {
+        let mut record = Record::new();
+        record.timestamp = timestamp;
+        let mut element = Element::Record(record);
+        element.set_partition(partition);
+        element
+    }
+
+    fn timestamps(elements: &[Element]) -> Vec<u64> {
+        elements
+            .iter()
+            .filter(|element| element.is_record())
+            .map(|element| element.as_record().timestamp)
+            .collect()
+    }
+
+    #[tokio::test]
+    pub async fn unaligned_checkpoint_test() {
+        let task_id = TaskId {
+            job_id: JobId(807),
+            task_number: 0,
+            num_tasks: 1,
+        };
+        let child_task_ids: Vec<TaskId> = (0..2)
+            .map(|task_number| TaskId {
+                job_id: JobId(808),
+                task_number,
+                num_tasks: 2,
+            })
+            .collect();
+        let task_senders = network::publish(&task_id, &child_task_ids, 16)
+            .into_iter()
+            .map(|(channel_key, sender)| (channel_key.target_task_id, sender))
+            .collect();
+        let channel_key = |target_task_id: TaskId| ChannelKey {
+            source_task_id: task_id,
+            target_task_id,
+        };
+
+        // the child task 1 has multiple inputs, its channel is aligned
+        let mut output_format = SystemOutputFormat {
+            task_id,
+            channel_type: ChannelType::Network,
+            job_senders: vec![(JobId(808), task_senders)],
+            checkpoint_mode: CheckpointMode::Unaligned,
+            aligned_tasks: HashSet::from([child_task_ids[1]]),
+            in_flight_elements: HashMap::new(),
+        };
+
+        output_format.write_element(element(1, 0)).await;
+        output_format.write_element(element(2, 0)).await;
+        output_format.write_element(element(3, 1)).await;
+
+        let channel_state = output_format.take_channel_state();
+        assert_eq!(channel_state.in_flight_records.len(), 1);
+        let (overtaken_task_id, records) = &channel_state.in_flight_records[0];
+        assert_eq!(*overtaken_task_id, child_task_ids[0]);
+        assert_eq!(records.len(), 2);
+        assert!(network::drain_network_channel(&channel_key(child_task_ids[0])).is_empty());
+
+        // the `Barrier` overtakes the in-flight records of the unaligned channel only
+        for partition in 0..2 {
+            let mut barrier = Element::new_barrier(CheckpointId(1));
+            barrier.set_partition(partition);
+            output_format.write_element(barrier).await;
+        }
+        let elements = network::drain_network_channel(&channel_key(child_task_ids[0]));
+        assert!(elements[0].is_barrier());
+        assert_eq!(timestamps(&elements), vec![1, 2]);
+        let elements = network::drain_network_channel(&channel_key(child_task_ids[1]));
+        assert_eq!(timestamps(&elements[..1]), vec![3]);
+        assert!(elements[1].is_barrier());
+
+        // the overtaken records are replayed on restore
+        let handle = serde_json::to_string(&channel_state).unwrap();
+        let channel_state: ChannelState = serde_json::from_str(handle.as_str()).unwrap();
+        output_format.replay_channel_state(channel_state).await;
+        let elements = network::drain_network_channel(&channel_key(child_task_ids[0]));
+        assert_eq!(timestamps(&elements), vec![1, 2]);
     }
 }
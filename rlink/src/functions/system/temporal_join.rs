@@ -0,0 +1,263 @@
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{
+    Context, JoinFunction, KeyedProcessFunction, NamedFunction, SendableElementStream,
+};
+use crate::core::state::{MapState, MapStateDescriptor};
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::functions::system::join::{bytes_record, decode_join_record, JoinSide};
+use crate::utils::stream::MemoryStream;
+
+/// the versions of the table row by the event time
+const VERSIONS: &str = "temporal_join.versions";
+/// the buffered probe records by the timestamp
+const PROBES: &str = "temporal_join.probes";
+
+/// Join the probe records of the left stream with the version of the table row of the same key
+/// valid at the timestamp of the probe record, that is the latest version not after it. The
+/// versioned table is maintained by the right stream in the keyed states.
+///
+/// The probe records are buffered until the watermark passes them, so all versions valid at
+/// their timestamps have arrived. The late probe records are joined with the current versions
+/// immediately. The versions are dropped once a newer version is valid at the watermark
+pub(crate) struct TemporalJoinProcessFunction<F> {
+    function: F,
+    left_schema: FnSchema,
+    right_schema: FnSchema,
+
+    versions: Option<MapState<u64, Vec<u8>>>,
+    probes: Option<MapState<u64, Vec<Vec<u8>>>>,
+}
+
+impl<F: JoinFunction> TemporalJoinProcessFunction<F> {
+    pub fn new(function: F, left_schema: FnSchema, right_schema: FnSchema) -> Self {
+        TemporalJoinProcessFunction {
+            function,
+            left_schema,
+            right_schema,
+            versions: None,
+            probes: None,
+        }
+    }
+
+    /// The timestamp of the version valid at the `timestamp`
+    fn valid_version(&self, timestamp: u64) -> anyhow::Result<Option<u64>> {
+        let versions = self.versions.as_ref().unwrap().entries()?;
+        Ok(versions
+            .into_iter()
+            .map(|(version, _)| version)
+            .filter(|version| *version <= timestamp)
+            .max())
+    }
+
+    /// Join the probe record with the version valid at its timestamp, the probe record without
+    /// the valid version is passed to `JoinFunction::left_outer_join`
+    fn join(&self, probe: &mut Record) -> anyhow::Result<Option<Record>> {
+        let joined = match self.valid_version(probe.timestamp)? {
+            Some(version) => {
+                let values = self.versions.as_ref().unwrap().get(&version)?.unwrap();
+                let mut row = bytes_record(values.as_slice(), version);
+                Some(self.function.join(probe, &mut row))
+            }
+            None => self.function.left_outer_join(probe),
+        };
+
+        Ok(joined.map(|mut joined| {
+            joined.timestamp = probe.timestamp;
+            joined
+        }))
+    }
+
+    /// Drop the versions replaced by the version valid at the `watermark`
+    fn cleanup_versions(&self, watermark: u64) -> anyhow::Result<()> {
+        if let Some(valid_version) = self.valid_version(watermark)? {
+            let versions = self.versions.as_ref().unwrap();
+            for (version, _) in versions.entries()? {
+                if version < valid_version {
+                    versions.remove(&version)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<F: JoinFunction> KeyedProcessFunction for TemporalJoinProcessFunction<F> {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        let runtime_context = context.runtime_context();
+        self.versions = Some(runtime_context.map_state(&MapStateDescriptor::new(VERSIONS)));
+        self.probes = Some(runtime_context.map_state(&MapStateDescriptor::new(PROBES)));
+
+        self.function.open(context).await
+    }
+
+    async fn process_element(
+        &mut self,
+        mut record: Record,
+        timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let (side, mut record) = decode_join_record(&mut record);
+        let timestamp = record.timestamp;
+
+        let mut records = Vec::new();
+        match side {
+            JoinSide::Left if timestamp < timer_service.current_watermark() => {
+                if let Some(joined) = self.join(&mut record).expect("temporal join error") {
+                    records.push(joined);
+                }
+            }
+            JoinSide::Left => {
+                let probes = self.probes.as_ref().unwrap();
+                let mut values = probes
+                    .get(&timestamp)
+                    .expect("get the temporal join probes error")
+                    .unwrap_or_default();
+                values.push(record.values.as_slice().to_vec());
+                probes
+                    .put(&timestamp, values)
+                    .expect("buffer the temporal join probe error");
+                timer_service.register_event_time_timer(timestamp);
+            }
+            JoinSide::Right => {
+                self.versions
+                    .as_ref()
+                    .unwrap()
+                    .put(&timestamp, record.values.as_slice().to_vec())
+                    .expect("update the temporal join version error");
+                timer_service.register_event_time_timer(timestamp);
+            }
+        }
+
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn on_timer(
+        &mut self,
+        timestamp: u64,
+        _time_domain: TimeDomain,
+        _timer_service: &mut TimerService,
+    ) -> SendableElementStream {
+        let probes = self
+            .probes
+            .as_ref()
+            .unwrap()
+            .remove(&timestamp)
+            .expect("remove the temporal join probes error")
+            .unwrap_or_default();
+
+        let mut records = Vec::with_capacity(probes.len());
+        for values in probes {
+            let mut probe = bytes_record(values.as_slice(), timestamp);
+            if let Some(joined) = self.join(&mut probe).expect("temporal join error") {
+                records.push(joined);
+            }
+        }
+
+        self.cleanup_versions(timestamp)
+            .expect("cleanup the temporal join versions error");
+
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.function.close().await
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.function
+            .schema(self.left_schema.clone(), self.right_schema.clone())
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.function.parallelism()
+    }
+}
+
+impl<F: JoinFunction> NamedFunction for TemporalJoinProcessFunction<F> {
+    fn name(&self) -> &str {
+        self.function.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{FnSchema, Record};
+    use crate::core::function::{Context, JoinFunction, NamedFunction};
+    use crate::core::state::{MapStateDescriptor, RuntimeContext};
+    use crate::functions::system::temporal_join::{TemporalJoinProcessFunction, PROBES, VERSIONS};
+
+    struct RateJoinFunction {}
+
+    #[async_trait]
+    impl JoinFunction for RateJoinFunction {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn join(&self, _left: &mut Record, right: &mut Record) -> Record {
+            right.clone()
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, _left_schema: FnSchema, right_schema: FnSchema) -> FnSchema {
+            right_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            1
+        }
+    }
+
+    impl NamedFunction for RateJoinFunction {
+        fn name(&self) -> &str {
+            "RateJoinFunction"
+        }
+    }
+
+    fn record(v: &str, timestamp: u64) -> Record {
+        let schema = Schema::new(vec![Field::new("v", DataType::String)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_str(v).unwrap();
+        record.timestamp = timestamp;
+        record
+    }
+
+    #[test]
+    pub fn temporal_join_test() {
+        let mut function =
+            TemporalJoinProcessFunction::new(RateJoinFunction {}, FnSchema::Empty, FnSchema::Empty);
+        let runtime_context = RuntimeContext::new();
+        runtime_context.set_current_key(&record("USD", 0));
+        function.versions = Some(runtime_context.map_state(&MapStateDescriptor::new(VERSIONS)));
+        function.probes = Some(runtime_context.map_state(&MapStateDescriptor::new(PROBES)));
+
+        let versions = function.versions.as_ref().unwrap();
+        for (rate, timestamp) in [("6.9", 100), ("7.0", 200), ("7.1", 300)] {
+            versions
+                .put(
+                    &timestamp,
+                    record(rate, timestamp).values.as_slice().to_vec(),
+                )
+                .unwrap();
+        }
+
+        // the probe record is joined with the latest version not after it
+        let joined = function.join(&mut record("order", 250)).unwrap().unwrap();
+        assert_eq!(joined, record("7.0", 0));
+        assert_eq!(joined.timestamp, 250);
+        let joined = function.join(&mut record("order", 300)).unwrap().unwrap();
+        assert_eq!(joined, record("7.1", 0));
+
+        // no version is valid before the first one
+        assert!(function.join(&mut record("order", 99)).unwrap().is_none());
+
+        // the versions replaced at the watermark are dropped
+        function.cleanup_versions(250).unwrap();
+        assert_eq!(function.valid_version(150).unwrap(), None);
+        assert_eq!(function.valid_version(250).unwrap(), Some(200));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{CoProcessFunction, Context, NamedFunction, SendableElementStream};
+use crate::utils::stream::MemoryStream;
+
+/// Forward the records of all the connected streams of the same schema as one stream, see
+/// `TDataStream::union`. The watermark of the merged stream is the min watermark of all inputs,
+/// which is aligned by the virtual source of the connected streams
+pub(crate) struct UnionCoProcessFunction {}
+
+impl UnionCoProcessFunction {
+    pub fn new() -> Self {
+        UnionCoProcessFunction {}
+    }
+}
+
+#[async_trait]
+impl CoProcessFunction for UnionCoProcessFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    async fn process_left(&mut self, record: Record) -> SendableElementStream {
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn process_right(&mut self, _stream_seq: usize, record: Record) -> SendableElementStream {
+        Box::pin(MemoryStream::new(vec![record]))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        input_schema
+    }
+}
+
+impl NamedFunction for UnionCoProcessFunction {
+    fn name(&self) -> &str {
+        "UnionCoProcessFunction"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for UnionCoProcessFunction {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
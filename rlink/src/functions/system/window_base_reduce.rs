@@ -1,42 +1,299 @@
 use std::borrow::BorrowMut;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 
 use metrics::Gauge;
+use serbuffer::types;
+use serde_json::{Map, Value};
 
 use crate::core::backend::KeyedStateBackend;
 use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
-use crate::core::element::{FnSchema, Record};
-use crate::core::function::{BaseReduceFunction, Context, NamedFunction, ReduceFunction};
+use crate::core::data_types::{DataType, Schema};
+use crate::core::element::{Barrier, Element, FnSchema, Record};
+use crate::core::function::{
+    AggregateFunction, BaseReduceFunction, Context, NamedFunction, OutputFormat,
+    ProcessWindowFunction, ReduceFunction,
+};
 use crate::core::properties::SystemProperties;
-use crate::core::runtime::CheckpointId;
-use crate::core::window::{TWindow, Window};
+use crate::core::runtime::{CheckpointId, JobId, TaskId};
+use crate::core::state::{register_queryable_state, QueryableStore};
+use crate::core::window::{
+    CountWindow, Evictor, EvictorContext, ProcessWindowContext, TWindow, Trigger, TriggerContext,
+    TriggerResult, Window,
+};
+use crate::functions::window::trigger::EventTimeTrigger;
 use crate::metrics::register_gauge;
 use crate::runtime::worker::runnable::reduce_runnable::ReduceCheckpointHandle;
-use crate::storage::keyed_state::{TWindowState, WindowState};
-use crate::utils::date_time::timestamp_str;
+use crate::storage::keyed_state::mem_reducing_state::MemoryReducingState;
+use crate::storage::keyed_state::mem_storage::{append_drop_window, StorageKey};
+use crate::storage::keyed_state::{
+    ResultFunction, StateKey, TReducingState, TWindowState, WindowState,
+};
+use crate::utils::date_time::{current_timestamp_millis, timestamp_str};
+
+/// The user function reduces the records of the windows
+pub(crate) enum WindowFunction {
+    Reduce(Box<dyn ReduceFunction>),
+    /// The window state keeps the accumulators, they're converted to the results when the
+    /// windows are fired
+    Aggregate(Box<dyn AggregateFunction>),
+}
+
+impl WindowFunction {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        match self {
+            WindowFunction::Reduce(reduce) => reduce.open(context).await,
+            WindowFunction::Aggregate(aggregate) => aggregate.open(context).await,
+        }
+    }
+
+    fn reduce(&self, value: Option<&mut Record>, record: &mut Record) -> Record {
+        match self {
+            WindowFunction::Reduce(reduce) => reduce.reduce(value, record),
+            WindowFunction::Aggregate(aggregate) => match value {
+                Some(accumulator) => aggregate.add(accumulator, record),
+                None => {
+                    let mut accumulator = aggregate.create_accumulator();
+                    aggregate.add(&mut accumulator, record)
+                }
+            },
+        }
+    }
+
+    fn merge(&self, value: &mut Record, other: &mut Record) -> Record {
+        match self {
+            WindowFunction::Reduce(reduce) => reduce.merge(value, other),
+            WindowFunction::Aggregate(aggregate) => aggregate.merge(value, other),
+        }
+    }
+
+    fn supports_merge(&self) -> bool {
+        match self {
+            WindowFunction::Reduce(reduce) => reduce.supports_merge(),
+            WindowFunction::Aggregate(aggregate) => aggregate.supports_merge(),
+        }
+    }
+
+    fn get_result(&self, value: &mut Record) -> Record {
+        match self {
+            WindowFunction::Reduce(_reduce) => value.clone(),
+            WindowFunction::Aggregate(aggregate) => aggregate.get_result(value),
+        }
+    }
+
+    pub fn schema(&self, input_schema: FnSchema) -> FnSchema {
+        match self {
+            WindowFunction::Reduce(reduce) => reduce.schema(input_schema),
+            WindowFunction::Aggregate(aggregate) => aggregate.schema(input_schema),
+        }
+    }
+
+    pub fn parallelism(&self) -> u16 {
+        match self {
+            WindowFunction::Reduce(reduce) => reduce.parallelism(),
+            WindowFunction::Aggregate(aggregate) => aggregate.parallelism(),
+        }
+    }
+}
+
+/// the data types of the element counter of the count windows
+const COUNT_TYPES: [u8; 1] = [types::U64];
+
+fn count_of(record: &mut Record) -> u64 {
+    record.as_reader(&COUNT_TYPES).get_u64(0).unwrap()
+}
+
+fn count_record(count: u64) -> Record {
+    let mut record = Record::with_capacity(8);
+    record.as_writer(&COUNT_TYPES).set_u64(count).unwrap();
+    record
+}
+
+/// The reduced values of the windows by the key, mirrored from the window state for the
+/// queryable state server of the worker
+#[derive(Default)]
+struct QueryableWindowValues {
+    task_id: Option<TaskId>,
+    windows: HashMap<Window, HashMap<Vec<u8>, Record>>,
+}
+
+/// The window aggregates exposed by `TWindowedStream::queryable`, the value of a key is the
+/// list of its windows with the results in them
+struct QueryableWindowState {
+    /// the schema of the results of the window function
+    schema: Schema,
+    values: Weak<Mutex<QueryableWindowValues>>,
+}
+
+impl QueryableStore for QueryableWindowState {
+    fn id(&self) -> usize {
+        self.values.as_ptr() as usize
+    }
+
+    fn is_alive(&self) -> bool {
+        self.values.strong_count() > 0
+    }
+
+    fn task_id(&self) -> Option<TaskId> {
+        self.values.upgrade()?.lock().unwrap().task_id
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Value> {
+        let values = self.values.upgrade()?;
+        let values = values.lock().unwrap();
+        let mut windows: Vec<(&Window, &Record)> = values
+            .windows
+            .iter()
+            .filter_map(|(window, values)| values.get(key).map(|value| (window, value)))
+            .collect();
+        if windows.is_empty() {
+            return None;
+        }
+        windows.sort_by_key(|(window, _value)| window.min_timestamp());
+
+        let windows = windows
+            .into_iter()
+            .map(|(window, value)| {
+                let mut window_value = Map::with_capacity(3);
+                window_value.insert("start".to_string(), window.min_timestamp().into());
+                window_value.insert("end".to_string(), window.max_timestamp().into());
+                window_value.insert(
+                    "value".to_string(),
+                    to_json(&self.schema, &mut value.clone()).ok()?,
+                );
+                Some(Value::Object(window_value))
+            })
+            .collect::<Option<Vec<Value>>>()?;
+        Some(Value::Array(windows))
+    }
+}
+
+/// The json object of the `record` with the field names of the `schema`
+fn to_json(schema: &Schema, record: &mut Record) -> anyhow::Result<Value> {
+    let reader = record.as_reader(schema.as_type_ids());
+
+    let mut object = Map::with_capacity(schema.fields().len());
+    for (i, field) in schema.fields().iter().enumerate() {
+        let value = match field.data_type() {
+            DataType::Boolean => Value::from(reader.get_bool(i)?),
+            DataType::Int8 => Value::from(reader.get_i8(i)?),
+            DataType::UInt8 => Value::from(reader.get_u8(i)?),
+            DataType::Int16 => Value::from(reader.get_i16(i)?),
+            DataType::UInt16 => Value::from(reader.get_u16(i)?),
+            DataType::Int32 => Value::from(reader.get_i32(i)?),
+            DataType::UInt32 => Value::from(reader.get_u32(i)?),
+            DataType::Int64 => Value::from(reader.get_i64(i)?),
+            DataType::UInt64 => Value::from(reader.get_u64(i)?),
+            DataType::Float32 => Value::from(reader.get_f32(i)?),
+            DataType::Float64 => Value::from(reader.get_f64(i)?),
+            DataType::Binary => {
+                Value::from(String::from_utf8_lossy(reader.get_binary(i)?).to_string())
+            }
+            DataType::String => Value::from(reader.get_str(i)?),
+        };
+        object.insert(field.name().to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
 
 pub(crate) struct WindowBaseReduceFunction {
-    reduce: Box<dyn ReduceFunction>,
+    function: WindowFunction,
+    trigger: Box<dyn Trigger>,
+    evictor: Option<Box<dyn Evictor>>,
+    /// the time in millis the windows are kept after the watermark passes the end of them
+    allowed_lateness: u64,
+    late_data_output: Option<Box<dyn OutputFormat>>,
+    process: Option<Box<dyn ProcessWindowFunction>>,
 
     state: Option<WindowState>,
+    trigger_contexts: HashMap<Window, TriggerContext>,
+    /// the elements of the windows by key, they're kept only if the evictor is set
+    window_elements: HashMap<Window, BTreeMap<Record, Vec<Record>>>,
+    process_contexts: HashMap<Window, ProcessWindowContext>,
+    current_watermark: u64,
+
+    /// the element counters of the keys of the count windows
+    count_state: Option<MemoryReducingState>,
+    /// the reduced values of the keys of the tumbling count windows
+    count_values: Option<MemoryReducingState>,
+    /// the latest elements of the keys of the sliding count windows
+    count_elements: BTreeMap<Record, VecDeque<Record>>,
+    /// the sequence of the fired count windows
+    count_window_seq: u64,
+    job_id: JobId,
+    task_number: u16,
 
     window_checkpoints: BTreeMap<CheckpointId, HashMap<Window, bool>>,
     skip_windows: Vec<Window>,
 
+    /// the queryable name and the schema of the results of the window function
+    queryable: Option<(String, Schema)>,
+    queryable_values: Option<Arc<Mutex<QueryableWindowValues>>>,
+
     windows_gauge: Gauge,
 }
 
 impl WindowBaseReduceFunction {
-    pub fn new(reduce: Box<dyn ReduceFunction>) -> Self {
+    pub fn new(
+        function: WindowFunction,
+        trigger: Option<Box<dyn Trigger>>,
+        evictor: Option<Box<dyn Evictor>>,
+    ) -> Self {
         WindowBaseReduceFunction {
-            reduce,
+            function,
+            trigger: trigger.unwrap_or_else(|| Box::new(EventTimeTrigger::new())),
+            evictor,
+            allowed_lateness: 0,
+            late_data_output: None,
+            process: None,
             state: None,
+            trigger_contexts: HashMap::new(),
+            window_elements: HashMap::new(),
+            process_contexts: HashMap::new(),
+            current_watermark: 0,
+            count_state: None,
+            count_values: None,
+            count_elements: BTreeMap::new(),
+            count_window_seq: 0,
+            job_id: JobId::default(),
+            task_number: 0,
             window_checkpoints: BTreeMap::new(),
             skip_windows: Vec::new(),
+            queryable: None,
+            queryable_values: None,
             windows_gauge: Gauge::noop(),
         }
     }
 
+    /// Expose the results of the windows to the queryable state server by the queryable name,
+    /// the results are in the `schema`
+    pub fn with_queryable(mut self, queryable: Option<(String, Schema)>) -> Self {
+        self.queryable = queryable;
+        self
+    }
+
+    pub fn with_allowed_lateness(mut self, allowed_lateness: Duration) -> Self {
+        self.allowed_lateness = allowed_lateness.as_millis() as u64;
+        self
+    }
+
+    pub fn with_late_data_output(
+        mut self,
+        late_data_output: Option<Box<dyn OutputFormat>>,
+    ) -> Self {
+        self.late_data_output = late_data_output;
+        self
+    }
+
+    pub fn with_process_function(
+        mut self,
+        process: Option<Box<dyn ProcessWindowFunction>>,
+    ) -> Self {
+        self.process = process;
+        self
+    }
+
     fn filter_skip_window(&self, windows: &mut Vec<Window>) -> Vec<Window> {
         windows
             .iter()
@@ -45,6 +302,308 @@ impl WindowBaseReduceFunction {
             .collect()
     }
 
+    /// Keep the element for the evictor, the elements of the key in the session windows merged
+    /// into the `window` are moved to it
+    fn add_element(&mut self, window: &Window, key: &Record, record: &Record) {
+        if window.is_session_window() {
+            let merged_windows: Vec<Window> = self
+                .window_elements
+                .keys()
+                .filter(|w| {
+                    *w != window
+                        && w.is_session_window()
+                        && w.min_timestamp() >= window.min_timestamp()
+                        && w.max_timestamp() <= window.max_timestamp()
+                })
+                .cloned()
+                .collect();
+            for merged_window in merged_windows {
+                let elements_by_key = self.window_elements.get_mut(&merged_window).unwrap();
+                if let Some(mut elements) = elements_by_key.remove(key) {
+                    if elements_by_key.is_empty() {
+                        self.window_elements.remove(&merged_window);
+                    }
+                    self.window_elements
+                        .entry(window.clone())
+                        .or_insert_with(BTreeMap::new)
+                        .entry(key.clone())
+                        .or_insert_with(Vec::new)
+                        .append(&mut elements);
+                }
+            }
+        }
+
+        let mut element = record.clone();
+        element.location_windows = None;
+        self.window_elements
+            .entry(window.clone())
+            .or_insert_with(BTreeMap::new)
+            .entry(key.clone())
+            .or_insert_with(Vec::new)
+            .push(element);
+    }
+
+    /// Evict the elements of the window before it's fired, the state of the window is rebuilt
+    /// by the remaining elements. Return false if no element remains
+    fn evict_before(&mut self, window: &Window) -> bool {
+        let evictor = match &self.evictor {
+            Some(evictor) => evictor,
+            None => return true,
+        };
+        let elements_by_key = match self.window_elements.get_mut(window) {
+            Some(elements_by_key) => elements_by_key,
+            None => return true,
+        };
+
+        let context = EvictorContext::new(self.current_watermark);
+        let state = self.state.as_mut().unwrap();
+        let function = &self.function;
+        state.purge_window(window);
+
+        let mut element_count = 0;
+        for (key, elements) in elements_by_key.iter_mut() {
+            evictor.evict_before(elements, window, &context);
+            for element in elements.iter() {
+                let mut element = element.clone();
+                element.set_location_windows(vec![window.clone()]);
+                state.merge(
+                    key.clone(),
+                    &mut element,
+                    |val1, val2| function.reduce(val1, val2),
+                    |val1, val2| function.merge(val1, val2),
+                );
+            }
+            element_count += elements.len();
+        }
+        // the keys with all elements evicted are removed from the queryable state too
+        let keys: Vec<Record> = match self.queryable_values {
+            Some(_) => elements_by_key.keys().cloned().collect(),
+            None => vec![],
+        };
+        elements_by_key.retain(|_key, elements| !elements.is_empty());
+
+        for key in keys {
+            self.update_queryable_values(&key, &[window.clone()]);
+        }
+
+        element_count > 0
+    }
+
+    fn evict_after(&mut self, window: &Window) {
+        if let (Some(evictor), Some(elements_by_key)) =
+            (&self.evictor, self.window_elements.get_mut(window))
+        {
+            let context = EvictorContext::new(self.current_watermark);
+            for elements in elements_by_key.values_mut() {
+                evictor.evict_after(elements, window, &context);
+            }
+            elements_by_key.retain(|_key, elements| !elements.is_empty());
+        }
+    }
+
+    /// Hand over the state of the window to the downstream, the accumulators of the aggregate
+    /// function are converted to the results, then processed by the process window function.
+    /// The window is removed if `drop` is true
+    fn hand_over_window(&mut self, window: &Window, drop: bool) {
+        let function = &self.function;
+        let process = &self.process;
+        let mut context = match process {
+            Some(_) => {
+                let context = self
+                    .process_contexts
+                    .entry(window.clone())
+                    .or_insert_with(|| ProcessWindowContext::new(window.clone()));
+                context.set_current_watermark(self.current_watermark);
+                Some(context)
+            }
+            None => None,
+        };
+
+        let mut get_result = |key: &Record, value: &mut Record| {
+            let mut value = function.get_result(value);
+            match (process, context.as_deref_mut()) {
+                (Some(process), Some(context)) => {
+                    context.set_current_key(key);
+                    process.process(&mut key.clone(), &mut value, context)
+                }
+                _ => value,
+            }
+        };
+        let result_fun: Option<ResultFunction> = match (function, process) {
+            (WindowFunction::Reduce(_), None) => None,
+            _ => Some(&mut get_result),
+        };
+
+        let state = self.state.as_mut().unwrap();
+        if drop {
+            state.drop_window(window, result_fun);
+        } else {
+            state.fire_window(window, result_fun);
+        }
+    }
+
+    /// Apply the trigger result to the window, return true if the window is fired
+    fn apply_trigger_result(&mut self, window: &Window, result: TriggerResult) -> bool {
+        match result {
+            TriggerResult::Continue => false,
+            TriggerResult::Fire => {
+                if !self.evict_before(window) {
+                    return false;
+                }
+                self.hand_over_window(window, false);
+                self.evict_after(window);
+                true
+            }
+            TriggerResult::FireAndPurge => {
+                let fired = self.evict_before(window);
+                if fired {
+                    self.hand_over_window(window, true);
+                } else {
+                    self.state.as_mut().unwrap().purge_window(window);
+                }
+                self.clear_window(window);
+                fired
+            }
+        }
+    }
+
+    /// Clear the trigger states and the elements of the purged window, and mark it completed
+    /// in the checkpoints
+    fn clear_window(&mut self, window: &Window) {
+        if let Some(mut context) = self.trigger_contexts.remove(window) {
+            self.trigger.clear(window, &mut context);
+        }
+        self.window_elements.remove(window);
+        self.process_contexts.remove(window);
+        self.window_checkpoints
+            .iter_mut()
+            .for_each(|(_checkpoint_id, windows)| {
+                windows.get_mut(window).map(|x| *x = true);
+            });
+    }
+
+    /// Reduce the record into the count window of the key, the window of the key is fired
+    /// every `slide` elements, and handed over to the downstream as a new window.
+    ///
+    /// The trigger, evictor and allowed lateness don't apply to the count windows
+    fn reduce_count_window(
+        &mut self,
+        key: Record,
+        mut record: Record,
+        window: &CountWindow,
+    ) -> Option<Window> {
+        let count_state = self.count_state.as_mut().unwrap();
+        let count = count_state.get_mut(&key).map(count_of).unwrap_or(0) + 1;
+        count_state.insert(key.clone(), count_record(count));
+
+        let function = &self.function;
+        let fired = count % window.slide() == 0;
+        let mut value = if window.is_tumbling() {
+            let count_values = self.count_values.as_mut().unwrap();
+            let value = function.reduce(count_values.get_mut(&key), &mut record);
+            if !fired {
+                count_values.insert(key, value);
+                return None;
+            }
+            count_values.remove(&key);
+            value
+        } else {
+            let elements = self
+                .count_elements
+                .entry(key.clone())
+                .or_insert_with(VecDeque::new);
+            elements.push_back(record);
+            if elements.len() as u64 > window.size() {
+                elements.pop_front();
+            }
+            if !fired {
+                return None;
+            }
+
+            let mut value: Option<Record> = None;
+            for element in elements.iter_mut() {
+                value = Some(function.reduce(value.as_mut(), element));
+            }
+            value.unwrap()
+        };
+
+        self.count_window_seq += 1;
+        let fired_window = Window::CountWindow(window.fired(self.count_window_seq));
+
+        let mut value = function.get_result(&mut value);
+        if let Some(process) = &self.process {
+            let mut context = ProcessWindowContext::new(fired_window.clone());
+            context.set_current_watermark(self.current_watermark);
+            context.set_current_key(&key);
+            value = process.process(&mut key.clone(), &mut value, &mut context);
+        }
+
+        let state_key = StateKey::new(fired_window.clone(), self.job_id, self.task_number);
+        let mut state = MemoryReducingState::new(&state_key);
+        state.insert(key, value);
+        let storage_key = StorageKey::new(self.job_id, self.task_number);
+        append_drop_window(storage_key, fired_window.clone(), state);
+
+        Some(fired_window)
+    }
+
+    /// Mirror the results of the key in the `windows` to the queryable state, the key is
+    /// removed from the session windows merged into them
+    fn update_queryable_values(&mut self, key: &Record, windows: &[Window]) {
+        let values = match &self.queryable_values {
+            Some(values) => values,
+            None => return,
+        };
+        let function = &self.function;
+        let state = self.state.as_mut().unwrap();
+        let key_bytes = key.values.as_slice();
+
+        let mut values = values.lock().unwrap();
+        for window in windows {
+            if window.is_session_window() {
+                for (w, keys) in values.windows.iter_mut() {
+                    if w != window
+                        && w.is_session_window()
+                        && w.min_timestamp() <= window.max_timestamp()
+                        && window.min_timestamp() <= w.max_timestamp()
+                    {
+                        keys.remove(key_bytes);
+                    }
+                }
+            }
+
+            match state.get(window, key) {
+                Some(mut value) => {
+                    let result = function.get_result(&mut value);
+                    values
+                        .windows
+                        .entry(window.clone())
+                        .or_default()
+                        .insert(key_bytes.to_vec(), result);
+                }
+                None => {
+                    if let Some(keys) = values.windows.get_mut(window) {
+                        keys.remove(key_bytes);
+                    }
+                }
+            }
+        }
+        values.windows.retain(|_window, keys| !keys.is_empty());
+    }
+
+    /// Remove the purged and dropped windows from the queryable state
+    fn retain_queryable_windows(&self) {
+        if let Some(values) = &self.queryable_values {
+            let windows: HashSet<Window> =
+                self.state.as_ref().unwrap().windows().into_iter().collect();
+            values
+                .lock()
+                .unwrap()
+                .windows
+                .retain(|window, _keys| windows.contains(window));
+        }
+    }
+
     fn can_skip_window(&self, window: &Window) -> bool {
         self.skip_windows
             .iter()
@@ -60,7 +619,16 @@ impl WindowBaseReduceFunction {
 impl BaseReduceFunction for WindowBaseReduceFunction {
     async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
         let task_id = context.task_id;
-        let application_id = context.application_id.clone();
+        self.job_id = task_id.job_id();
+        self.task_number = task_id.task_number();
+
+        let count_state_key = StateKey::new(
+            Window::CountWindow(CountWindow::default()),
+            self.job_id,
+            self.task_number,
+        );
+        self.count_state = Some(MemoryReducingState::new(&count_state_key));
+        self.count_values = Some(MemoryReducingState::new(&count_state_key));
 
         self.windows_gauge =
             register_gauge(format!("ReduceWindow_{}", self.name()), task_id.to_tags());
@@ -69,48 +637,150 @@ impl BaseReduceFunction for WindowBaseReduceFunction {
             .application_properties
             .get_keyed_state_backend()
             .unwrap_or(KeyedStateBackend::Memory);
-        self.state = Some(WindowState::new(
-            application_id,
-            task_id.job_id(),
-            task_id.task_number(),
-            state_mode,
-        ));
+        self.state = Some(WindowState::new(context, state_mode)?);
         self.initialize_state(&context.checkpoint_context(), &context.checkpoint_handle)
             .await;
 
-        self.reduce.open(context).await
+        if let Some((queryable_name, schema)) = &self.queryable {
+            let values = Arc::new(Mutex::new(QueryableWindowValues {
+                task_id: Some(task_id),
+                windows: HashMap::new(),
+            }));
+            let state = QueryableWindowState {
+                schema: schema.clone(),
+                values: Arc::downgrade(&values),
+            };
+            register_queryable_state(queryable_name.as_str(), Box::new(state));
+            self.queryable_values = Some(values);
+        }
+
+        if let Some(late_data_output) = self.late_data_output.as_mut() {
+            late_data_output.open(context).await?;
+        }
+
+        if let Some(process) = self.process.as_mut() {
+            process.open(context).await?;
+        }
+
+        self.function.open(context).await
     }
 
-    async fn reduce(&mut self, key: Record, mut record: Record) {
+    async fn reduce(&mut self, key: Record, mut record: Record) -> Vec<Record> {
         // check skip window
         if self.skip_windows.len() > 0 {
             if let Some(windows) = record.location_windows.borrow_mut() {
                 let filter_windows = self.filter_skip_window(windows);
                 if filter_windows.len() == 0 {
-                    return;
+                    return vec![];
                 }
 
                 record.location_windows = Some(filter_windows);
             }
         }
 
+        if let Some(Window::CountWindow(count_window)) = record.min_location_window().cloned() {
+            return self
+                .reduce_count_window(key, record, &count_window)
+                .map(|fire_window| {
+                    let mut fire_record = Record::new();
+                    fire_record.trigger_window = Some(fire_window);
+                    vec![fire_record]
+                })
+                .unwrap_or_default();
+        }
+
+        let element_key = self.evictor.as_ref().map(|_| key.clone());
+        let queryable_key = self.queryable_values.as_ref().map(|_| key.clone());
+
         let state = self.state.as_mut().unwrap();
-        let reduce_func = &self.reduce;
-        let window_count = state.merge(key, record, |val1, val2| reduce_func.reduce(val1, val2));
+        let function = &self.function;
+        let windows = state.merge(
+            key,
+            &mut record,
+            |val1, val2| function.reduce(val1, val2),
+            |val1, val2| function.merge(val1, val2),
+        );
+
+        if let Some(queryable_key) = queryable_key {
+            self.update_queryable_values(&queryable_key, windows.as_slice());
+        }
+
+        if let Some(element_key) = element_key {
+            for window in &windows {
+                self.add_element(window, &element_key, &record);
+            }
+        }
+
+        let mut fire_windows = Vec::new();
+        for window in windows {
+            let trigger = &self.trigger;
+            let context = self
+                .trigger_contexts
+                .entry(window.clone())
+                .or_insert_with(TriggerContext::default);
+            context.set_current_watermark(self.current_watermark);
+            let result = trigger.on_element(&record, &window, context);
+            if self.apply_trigger_result(&window, result) {
+                fire_windows.push(window);
+            }
+        }
+
+        let window_count = self.state.as_ref().unwrap().len();
         self.windows_gauge.set(window_count as f64);
+
+        fire_windows
+            .into_iter()
+            .map(|fire_window| {
+                let mut fire_record = Record::new();
+                fire_record.trigger_window = Some(fire_window);
+                fire_record
+            })
+            .collect()
     }
 
     async fn drop_state(&mut self, watermark_timestamp: u64) -> Vec<Record> {
-        let state = self.state.as_mut().unwrap();
+        self.current_watermark = watermark_timestamp;
+        let processing_time = current_timestamp_millis();
+
         let mut drop_windows = Vec::new();
-        let mut window_count = 0;
-        for window in state.windows() {
-            if window.max_timestamp() <= watermark_timestamp {
-                drop_windows.push(window.clone());
-                window_count = state.drop_window(&window);
+        for window in self.state.as_ref().unwrap().windows() {
+            let trigger = &self.trigger;
+            let context = self
+                .trigger_contexts
+                .entry(window.clone())
+                .or_insert_with(TriggerContext::default);
+            context.set_current_watermark(watermark_timestamp);
+            let result = trigger
+                .on_processing_time(processing_time, &window, context)
+                .merge(trigger.on_event_time(watermark_timestamp, &window, context));
+
+            // the window is purged once the watermark passes the end of it and the allowed lateness
+            if window.max_timestamp().saturating_add(self.allowed_lateness) <= watermark_timestamp {
+                if result.is_fire() {
+                    if self.apply_trigger_result(&window, TriggerResult::FireAndPurge) {
+                        drop_windows.push(window);
+                    }
+                } else {
+                    self.state.as_mut().unwrap().purge_window(&window);
+                    self.clear_window(&window);
+                }
+            } else if self.apply_trigger_result(&window, result) {
+                drop_windows.push(window);
             }
         }
 
+        self.retain_queryable_windows();
+
+        // the trigger and process states of the merged session windows
+        let window_count = self.state.as_ref().unwrap().len();
+        if self.trigger_contexts.len() > window_count || self.process_contexts.len() > window_count
+        {
+            let windows: HashSet<Window> =
+                self.state.as_ref().unwrap().windows().into_iter().collect();
+            self.trigger_contexts.retain(|w, _| windows.contains(w));
+            self.process_contexts.retain(|w, _| windows.contains(w));
+        }
+
         self.windows_gauge.set(window_count as f64);
 
         if drop_windows.len() > 0 {
@@ -120,14 +790,6 @@ impl BaseReduceFunction for WindowBaseReduceFunction {
                 drop_windows.len()
             );
 
-            self.window_checkpoints
-                .iter_mut()
-                .for_each(|(_checkpoint_id, windows)| {
-                    drop_windows.iter().for_each(|w| {
-                        windows.get_mut(w).map(|x| *x = true);
-                    });
-                });
-
             drop_windows.sort_by_key(|w| w.max_timestamp());
 
             drop_windows
@@ -143,12 +805,35 @@ impl BaseReduceFunction for WindowBaseReduceFunction {
         }
     }
 
+    fn allowed_lateness(&self) -> u64 {
+        self.allowed_lateness
+    }
+
+    async fn late_element(&mut self, mut record: Record) {
+        if let Some(late_data_output) = self.late_data_output.as_mut() {
+            record.location_windows = None;
+            late_data_output
+                .write_element(Element::Record(record))
+                .await;
+        }
+    }
+
     async fn close(&mut self) -> crate::core::Result<()> {
+        if let Some(late_data_output) = self.late_data_output.as_mut() {
+            late_data_output.close().await?;
+        }
+        if let Some(process) = self.process.as_mut() {
+            process.close().await?;
+        }
         Ok(())
     }
 
     fn value_schema(&self, input_schema: FnSchema) -> FnSchema {
-        self.reduce.schema(input_schema)
+        let value_schema = self.function.schema(input_schema);
+        match &self.process {
+            Some(process) => process.schema(value_schema),
+            None => value_schema,
+        }
         // let value_schema = self.reduce.schema();
         // match input_schema {
         //     Schema::Single(_record_schema) => value_schema,
@@ -160,6 +845,10 @@ impl BaseReduceFunction for WindowBaseReduceFunction {
         //     Schema::Empty => panic!("unreached!"),
         // }
     }
+
+    fn supports_merge(&self) -> bool {
+        self.function.supports_merge()
+    }
 }
 
 impl NamedFunction for WindowBaseReduceFunction {
@@ -179,7 +868,14 @@ impl CheckpointFunction for WindowBaseReduceFunction {
             let handle = ReduceCheckpointHandle::from(handle.handle.as_str());
             let current_windows = handle.into_windows();
 
-            self.skip_windows = current_windows;
+            // the windows restored with the state, e.g. by the rocksdb, go on with the records
+            // after the checkpoint, the others miss the records before it
+            let restored_windows: HashSet<Window> =
+                self.state.as_ref().unwrap().windows().into_iter().collect();
+            self.skip_windows = current_windows
+                .into_iter()
+                .filter(|w| !restored_windows.contains(w))
+                .collect();
             info!("skip windows: {:?}", self.skip_windows)
         }
     }
@@ -188,7 +884,9 @@ impl CheckpointFunction for WindowBaseReduceFunction {
         &mut self,
         context: &FunctionSnapshotContext,
     ) -> Option<CheckpointHandle> {
-        let windows = self.state.as_ref().unwrap().windows();
+        let state = self.state.as_mut().unwrap();
+        state.snapshot(Barrier::new(context.checkpoint_id));
+        let windows = state.windows();
         let mut windows_map = HashMap::with_capacity(windows.len());
         windows.iter().for_each(|w| {
             windows_map.insert(w.clone(), false);
@@ -236,3 +934,259 @@ impl CheckpointFunction for WindowBaseReduceFunction {
         Some(CheckpointHandle { handle })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+    use crate::core::element::{Element, FnSchema, Record};
+    use serbuffer::types;
+
+    use crate::core::function::{
+        BaseReduceFunction, Context, NamedFunction, OutputFormat, ProcessWindowFunction,
+        ReduceFunction,
+    };
+    use crate::core::runtime::JobId;
+    use crate::core::window::{ProcessWindowContext, TWindow, TimeWindow, Window};
+    use crate::functions::system::window_base_reduce::{
+        count_of, count_record, WindowBaseReduceFunction, WindowFunction,
+    };
+    use crate::storage::keyed_state::mem_reducing_state::MemoryReducingState;
+    use crate::storage::keyed_state::mem_storage::remove_drop_window;
+    use crate::storage::keyed_state::mem_window_state::MemoryWindowState;
+    use crate::storage::keyed_state::{TReducingState, TWindowState, WindowState};
+
+    struct CountReduceFunction {}
+
+    impl NamedFunction for CountReduceFunction {
+        fn name(&self) -> &str {
+            "CountReduceFunction"
+        }
+    }
+
+    #[async_trait]
+    impl ReduceFunction for CountReduceFunction {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn reduce(&self, value: Option<&mut Record>, _record: &mut Record) -> Record {
+            count_record(value.map(|v| count_of(v)).unwrap_or(0) + 1)
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, input_schema: FnSchema) -> FnSchema {
+            input_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            1
+        }
+    }
+
+    struct LateDataOutputFormat {
+        records: Arc<Mutex<Vec<Record>>>,
+    }
+
+    impl NamedFunction for LateDataOutputFormat {
+        fn name(&self) -> &str {
+            "LateDataOutputFormat"
+        }
+    }
+
+    #[async_trait]
+    impl CheckpointFunction for LateDataOutputFormat {
+        async fn initialize_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+            _handle: &Option<CheckpointHandle>,
+        ) {
+        }
+
+        async fn snapshot_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+        ) -> Option<CheckpointHandle> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl OutputFormat for LateDataOutputFormat {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        async fn write_element(&mut self, element: Element) {
+            if let Element::Record(record) = element {
+                self.records.lock().unwrap().push(record);
+            }
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// the window boundaries, the count of the key and the times the window is fired
+    const PROCESS_TYPES: [u8; 4] = [types::U64, types::U64, types::U64, types::U64];
+
+    struct WindowProcessFunction {}
+
+    impl NamedFunction for WindowProcessFunction {
+        fn name(&self) -> &str {
+            "WindowProcessFunction"
+        }
+    }
+
+    #[async_trait]
+    impl ProcessWindowFunction for WindowProcessFunction {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn process(
+            &self,
+            _key: &mut Record,
+            value: &mut Record,
+            context: &mut ProcessWindowContext,
+        ) -> Record {
+            let fires = context
+                .get_state("fires")
+                .map(|v| u64::from_le_bytes(v.try_into().unwrap()))
+                .unwrap_or(0)
+                + 1;
+            context.set_state("fires", fires.to_le_bytes().to_vec());
+
+            let mut record = Record::new();
+            let mut writer = record.as_writer(&PROCESS_TYPES);
+            writer.set_u64(context.window().min_timestamp()).unwrap();
+            writer.set_u64(context.window().max_timestamp()).unwrap();
+            writer.set_u64(count_of(value)).unwrap();
+            writer.set_u64(fires).unwrap();
+            record
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, input_schema: FnSchema) -> FnSchema {
+            input_schema
+        }
+    }
+
+    fn window_record(window: &Window) -> Record {
+        let mut record = Record::new();
+        record.set_location_windows(vec![window.clone()]);
+        record
+    }
+
+    /// The function of the time windows with the memory state of the task 0 of the job
+    fn reduce_function(job_id: JobId, function: WindowFunction) -> WindowBaseReduceFunction {
+        let mut reduce_function = WindowBaseReduceFunction::new(function, None, None);
+        reduce_function.state = Some(WindowState::MemoryWindowState(MemoryWindowState::new(
+            "app".to_string(),
+            job_id,
+            0,
+        )));
+        reduce_function
+    }
+
+    fn fired_count(job_id: JobId, window: &Window, key: &Record) -> u64 {
+        let mut fired_state = remove_drop_window(job_id, 0, window.clone()).unwrap();
+        count_of(fired_state.get_mut(key).unwrap())
+    }
+
+    #[tokio::test]
+    pub async fn allowed_lateness_test() {
+        let job_id = JobId(819);
+        let key = count_record(1);
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+        let late_records = Arc::new(Mutex::new(Vec::new()));
+
+        let function = WindowFunction::Reduce(Box::new(CountReduceFunction {}));
+        let mut function = reduce_function(job_id, function)
+            .with_allowed_lateness(Duration::from_millis(10))
+            .with_late_data_output(Some(Box::new(LateDataOutputFormat {
+                records: late_records.clone(),
+            })));
+        assert_eq!(function.allowed_lateness(), 10);
+
+        assert!(function
+            .reduce(key.clone(), window_record(&window))
+            .await
+            .is_empty());
+
+        // the window is fired on time but kept for the allowed lateness
+        let fired = function.drop_state(10).await;
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].trigger_window, Some(window.clone()));
+        assert_eq!(fired_count(job_id, &window, &key), 1);
+
+        // the late record within the allowed lateness fires the window again
+        let fired = function.reduce(key.clone(), window_record(&window)).await;
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired_count(job_id, &window, &key), 2);
+
+        // the window is purged once the watermark passes the allowed lateness
+        assert!(function.drop_state(20).await.is_empty());
+        assert_eq!(function.state.as_ref().unwrap().len(), 0);
+
+        // the record after the window purged goes to the late data side output
+        function.late_element(window_record(&window)).await;
+        let late_records = late_records.lock().unwrap();
+        assert_eq!(late_records.len(), 1);
+        assert_eq!(late_records[0].location_windows, None);
+    }
+
+    fn processed(fired_state: &mut MemoryReducingState, key: &Record) -> Vec<u64> {
+        let reader = fired_state.get_mut(key).unwrap().as_reader(&PROCESS_TYPES);
+        (0..4).map(|i| reader.get_u64(i).unwrap()).collect()
+    }
+
+    #[tokio::test]
+    pub async fn process_window_function_test() {
+        let job_id = JobId(822);
+        let key = count_record(1);
+        let other_key = count_record(2);
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+
+        let function = WindowFunction::Reduce(Box::new(CountReduceFunction {}));
+        let mut function = reduce_function(job_id, function)
+            .with_allowed_lateness(Duration::from_millis(10))
+            .with_process_function(Some(Box::new(WindowProcessFunction {})));
+
+        function.reduce(key.clone(), window_record(&window)).await;
+        function.reduce(key.clone(), window_record(&window)).await;
+        function
+            .reduce(other_key.clone(), window_record(&window))
+            .await;
+
+        // the output values are processed with the window metadata
+        assert_eq!(function.drop_state(10).await.len(), 1);
+        let mut fired_state = remove_drop_window(job_id, 0, window.clone()).unwrap();
+        assert_eq!(processed(&mut fired_state, &key), vec![0, 10, 2, 1]);
+        assert_eq!(processed(&mut fired_state, &other_key), vec![0, 10, 1, 1]);
+
+        // the state of the window and key is kept for the late firing
+        assert_eq!(
+            function
+                .reduce(key.clone(), window_record(&window))
+                .await
+                .len(),
+            1
+        );
+        let mut fired_state = remove_drop_window(job_id, 0, window.clone()).unwrap();
+        assert_eq!(processed(&mut fired_state, &key), vec![0, 10, 3, 2]);
+
+        // the process context is cleared with the purged window
+        assert!(function.drop_state(20).await.is_empty());
+        assert!(function.process_contexts.is_empty());
+    }
+}
@@ -0,0 +1,348 @@
+use bytes::{Buf, BufMut, BytesMut};
+use serbuffer::types;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::{DataType, Field, Schema};
+use crate::core::element::{Element, FnSchema, Record};
+use crate::core::function::{
+    AggregateFunction, Context, FlatMapFunction, JoinFunction, JoinType, NamedFunction,
+    SendableElementStream,
+};
+use crate::functions::system::join::{bytes_record, decode_join_record, JoinSide};
+use crate::utils::stream::MemoryStream;
+
+fn window_join_schema() -> Schema {
+    Schema::new(vec![Field::new("records", DataType::Binary)])
+}
+
+/// Append the record of the side to the entries, each entry is the side, the timestamp, the
+/// length and the values of the record
+fn put_entry(entries: &mut BytesMut, side: JoinSide, record: &Record) {
+    let values = record.values.as_slice();
+    entries.put_u8(side as u8);
+    entries.put_u64(record.timestamp);
+    entries.put_u32(values.len() as u32);
+    entries.put_slice(values);
+}
+
+fn get_entries(mut entries: &[u8]) -> Vec<(JoinSide, Record)> {
+    let mut records = Vec::new();
+    while entries.has_remaining() {
+        let side = if entries.get_u8() == JoinSide::Left as u8 {
+            JoinSide::Left
+        } else {
+            JoinSide::Right
+        };
+        let timestamp = entries.get_u64();
+        let len = entries.get_u32() as usize;
+        records.push((side, bytes_record(&entries[..len], timestamp)));
+        entries.advance(len);
+    }
+    records
+}
+
+/// Collect the join records of both sides of the key in the window, the accumulator is the
+/// entries of the collected records, they're joined by `WindowJoinFlatMapFunction` when the
+/// window is fired
+pub(crate) struct WindowJoinAggregateFunction {
+    parallelism: u16,
+}
+
+impl WindowJoinAggregateFunction {
+    pub fn new(parallelism: u16) -> Self {
+        WindowJoinAggregateFunction { parallelism }
+    }
+}
+
+#[async_trait]
+impl AggregateFunction for WindowJoinAggregateFunction {
+    async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn create_accumulator(&self) -> Record {
+        Record::new()
+    }
+
+    fn add(&self, accumulator: &mut Record, record: &mut Record) -> Record {
+        let (side, record) = decode_join_record(record);
+        let mut entry = BytesMut::with_capacity(record.len() + 13);
+        put_entry(&mut entry, side, &record);
+
+        let mut accumulator = accumulator.clone();
+        accumulator
+            .extend(bytes_record(entry.as_ref(), 0))
+            .expect("append the window join record error");
+        accumulator
+    }
+
+    fn get_result(&self, accumulator: &mut Record) -> Record {
+        let mut result = Record::with_capacity(accumulator.len() + 8);
+        result
+            .as_writer(window_join_schema().as_type_ids())
+            .set_binary(accumulator.values.as_slice())
+            .unwrap();
+        result
+    }
+
+    fn merge(&self, accumulator: &mut Record, other: &mut Record) -> Record {
+        let mut accumulator = accumulator.clone();
+        accumulator
+            .extend(other.clone())
+            .expect("merge the window join records error");
+        accumulator
+    }
+
+    fn supports_merge(&self) -> bool {
+        true
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&window_join_schema())
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl NamedFunction for WindowJoinAggregateFunction {
+    fn name(&self) -> &str {
+        "WindowJoinAggregateFunction"
+    }
+}
+
+/// Join the collected records of each key and window by the user `JoinFunction`. The input
+/// records are the keys followed by the entries of `WindowJoinAggregateFunction`
+pub(crate) struct WindowJoinFlatMapFunction<F> {
+    function: F,
+    join_type: JoinType,
+    left_schema: FnSchema,
+    right_schema: FnSchema,
+
+    /// the types of the input record, the key fields and the entries
+    input_types: Vec<u8>,
+}
+
+impl<F: JoinFunction> WindowJoinFlatMapFunction<F> {
+    pub fn new(
+        function: F,
+        join_type: JoinType,
+        key_schema: FnSchema,
+        left_schema: FnSchema,
+        right_schema: FnSchema,
+    ) -> Self {
+        let mut input_types = key_schema.first().as_type_ids().to_vec();
+        input_types.push(types::BINARY);
+
+        WindowJoinFlatMapFunction {
+            function,
+            join_type,
+            left_schema,
+            right_schema,
+            input_types,
+        }
+    }
+
+    /// Join the records of a key in the window. All records have the same key, so a record is
+    /// unmatched only if the other side is empty
+    fn join(&self, records: Vec<(JoinSide, Record)>) -> Vec<Record> {
+        let (mut lefts, mut rights): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .partition(|(side, _record)| *side == JoinSide::Left);
+
+        let mut joined_records = Vec::new();
+        for (_, left) in lefts.iter_mut() {
+            for (_, right) in rights.iter_mut() {
+                let mut joined = self.function.join(left, right);
+                joined.timestamp = left.timestamp.max(right.timestamp);
+                joined_records.push(joined);
+            }
+        }
+
+        match self.join_type {
+            JoinType::LeftOuter if rights.is_empty() => {
+                for (_, left) in lefts.iter_mut() {
+                    if let Some(mut joined) = self.function.left_outer_join(left) {
+                        joined.timestamp = left.timestamp;
+                        joined_records.push(joined);
+                    }
+                }
+            }
+            JoinType::RightOuter if lefts.is_empty() => {
+                for (_, right) in rights.iter_mut() {
+                    if let Some(mut joined) = self.function.right_outer_join(right) {
+                        joined.timestamp = right.timestamp;
+                        joined_records.push(joined);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        joined_records
+    }
+}
+
+#[async_trait]
+impl<F: JoinFunction> FlatMapFunction for WindowJoinFlatMapFunction<F> {
+    async fn open(&mut self, context: &Context) -> crate::core::Result<()> {
+        self.function.open(context).await
+    }
+
+    async fn flat_map_element(&mut self, element: Element) -> SendableElementStream {
+        let mut record = element.into_record();
+        let reader = record.as_reader(self.input_types.as_slice());
+        let entries = reader.get_binary(self.input_types.len() - 1).unwrap();
+
+        let records = self.join(get_entries(entries));
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        self.function.close().await
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        self.function
+            .schema(self.left_schema.clone(), self.right_schema.clone())
+    }
+}
+
+impl<F: JoinFunction> NamedFunction for WindowJoinFlatMapFunction<F> {
+    fn name(&self) -> &str {
+        self.function.name()
+    }
+}
+
+#[async_trait]
+impl<F: JoinFunction> CheckpointFunction for WindowJoinFlatMapFunction<F> {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{FnSchema, Record};
+    use crate::core::function::{
+        AggregateFunction, Context, JoinFunction, JoinType, NamedFunction,
+    };
+    use crate::functions::system::join::{encode_join_record, JoinSide};
+    use crate::functions::system::window_join::{
+        get_entries, WindowJoinAggregateFunction, WindowJoinFlatMapFunction,
+    };
+
+    struct ConcatJoinFunction {}
+
+    #[async_trait]
+    impl JoinFunction for ConcatJoinFunction {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn join(&self, left: &mut Record, right: &mut Record) -> Record {
+            let mut record = left.clone();
+            record.extend(right.clone()).unwrap();
+            record
+        }
+
+        fn left_outer_join(&self, left: &mut Record) -> Option<Record> {
+            Some(left.clone())
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self, left_schema: FnSchema, _right_schema: FnSchema) -> FnSchema {
+            left_schema
+        }
+
+        fn parallelism(&self) -> u16 {
+            1
+        }
+    }
+
+    impl NamedFunction for ConcatJoinFunction {
+        fn name(&self) -> &str {
+            "ConcatJoinFunction"
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![Field::new("v", DataType::String)])
+    }
+
+    fn record(v: &str, timestamp: u64) -> Record {
+        let mut record = Record::new();
+        record.as_writer(schema().as_type_ids()).set_str(v).unwrap();
+        record.timestamp = timestamp;
+        record
+    }
+
+    fn accumulate(records: Vec<(JoinSide, Record)>) -> Record {
+        let aggregate = WindowJoinAggregateFunction::new(1);
+        let key = record("k", 0);
+        let mut accumulator = aggregate.create_accumulator();
+        for (side, record) in records {
+            let mut join_record = encode_join_record(side, &key, &record);
+            accumulator = aggregate.add(&mut accumulator, &mut join_record);
+        }
+        accumulator
+    }
+
+    fn join_function(join_type: JoinType) -> WindowJoinFlatMapFunction<ConcatJoinFunction> {
+        WindowJoinFlatMapFunction::new(
+            ConcatJoinFunction {},
+            join_type,
+            FnSchema::from(&schema()),
+            FnSchema::Empty,
+            FnSchema::Empty,
+        )
+    }
+
+    #[test]
+    pub fn window_join_test() {
+        let accumulator = accumulate(vec![
+            (JoinSide::Left, record("l1", 10)),
+            (JoinSide::Right, record("r1", 20)),
+            (JoinSide::Left, record("l2", 30)),
+        ]);
+        let entries = get_entries(accumulator.values.as_slice());
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1], (JoinSide::Right, record("r1", 20)));
+        assert_eq!(entries[1].1.timestamp, 20);
+
+        let joined = join_function(JoinType::Inner).join(entries);
+        let timestamps: Vec<u64> = joined.iter().map(|x| x.timestamp).collect();
+        assert_eq!(timestamps, vec![20, 30]);
+
+        // the left records are emitted without the right records only in the left outer join
+        let accumulator = accumulate(vec![(JoinSide::Left, record("l1", 10))]);
+        let entries = get_entries(accumulator.values.as_slice());
+        assert!(join_function(JoinType::Inner)
+            .join(entries.clone())
+            .is_empty());
+        assert!(join_function(JoinType::RightOuter)
+            .join(entries.clone())
+            .is_empty());
+        let joined = join_function(JoinType::LeftOuter).join(entries);
+        assert_eq!(joined, vec![record("l1", 10)]);
+    }
+}
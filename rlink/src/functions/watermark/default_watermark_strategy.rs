@@ -1,4 +1,5 @@
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 use std::time::Duration;
 
 use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
@@ -7,12 +8,46 @@ use crate::core::watermark::{TimestampAssigner, WatermarkGenerator, WatermarkStr
 use crate::functions::column_locate::ColumnLocateBuilder;
 use crate::functions::watermark::watermarks_with_idleness::WatermarksWithIdleness;
 use crate::functions::watermark::{
-    BoundedOutOfOrdernessWatermarks, SchemaTimestampAssigner, TimePeriodicWatermarks,
+    BoundedOutOfOrdernessWatermarks, PunctuatedWatermarks, SchemaTimestampAssigner,
+    TimePeriodicWatermarks, WatermarkAlignment, WatermarkExtractor,
 };
 
+type GeneratorSupplier = Arc<dyn Fn() -> Box<dyn WatermarkGenerator> + Send + Sync>;
+
+enum WatermarkGeneratorFactory {
+    /// the generator is created once and shared by all splits
+    Instance(Option<Box<dyn WatermarkGenerator>>),
+    /// a generator is created for every split
+    Supplier(GeneratorSupplier),
+}
+
+impl WatermarkGeneratorFactory {
+    fn create(&mut self) -> Option<Box<dyn WatermarkGenerator>> {
+        match self {
+            WatermarkGeneratorFactory::Instance(generator) => generator.take(),
+            WatermarkGeneratorFactory::Supplier(supplier) => Some(supplier()),
+        }
+    }
+
+    fn wrap<F>(self, wrapper: F) -> Self
+    where
+        F: Fn(Box<dyn WatermarkGenerator>) -> Box<dyn WatermarkGenerator> + Send + Sync + 'static,
+    {
+        match self {
+            WatermarkGeneratorFactory::Instance(generator) => {
+                WatermarkGeneratorFactory::Instance(generator.map(wrapper))
+            }
+            WatermarkGeneratorFactory::Supplier(supplier) => {
+                WatermarkGeneratorFactory::Supplier(Arc::new(move || wrapper(supplier())))
+            }
+        }
+    }
+}
+
 pub struct DefaultWatermarkStrategy {
-    watermark_generator: Option<Box<dyn WatermarkGenerator>>,
+    watermark_generator: Option<WatermarkGeneratorFactory>,
     timestamp_assigner: Option<Box<dyn TimestampAssigner>>,
+    watermark_alignment: Option<WatermarkAlignment>,
 }
 
 impl DefaultWatermarkStrategy {
@@ -20,51 +55,102 @@ impl DefaultWatermarkStrategy {
         DefaultWatermarkStrategy {
             watermark_generator: None,
             timestamp_assigner: None,
+            watermark_alignment: None,
         }
     }
 
     pub fn for_bounded_out_of_orderness(mut self, out_of_orderness_millis: Duration) -> Self {
-        self.watermark_generator = Some(Box::new(BoundedOutOfOrdernessWatermarks::new(
-            out_of_orderness_millis,
+        self.watermark_generator = Some(WatermarkGeneratorFactory::Supplier(Arc::new(
+            move || -> Box<dyn WatermarkGenerator> {
+                Box::new(BoundedOutOfOrdernessWatermarks::new(
+                    out_of_orderness_millis,
+                ))
+            },
         )));
         self
     }
 
-    pub fn wrap_time_periodic(mut self, process_period: Duration, event_period: Duration) -> Self {
-        if let Some(watermarks) = self.watermark_generator.take() {
-            self.watermark_generator = Some(Box::new(TimePeriodicWatermarks::new(
+    /// The timestamps are ascending in each split, it's bounded out of orderness of zero
+    pub fn for_monotonous_timestamps(self) -> Self {
+        self.for_bounded_out_of_orderness(Duration::from_millis(0))
+    }
+
+    /// Generate the watermark per record by the `extractor`, instead of periodically
+    pub fn for_punctuated(mut self, extractor: WatermarkExtractor) -> Self {
+        self.watermark_generator = Some(WatermarkGeneratorFactory::Supplier(Arc::new(
+            move || -> Box<dyn WatermarkGenerator> {
+                Box::new(PunctuatedWatermarks::new(extractor))
+            },
+        )));
+        self
+    }
+
+    pub fn wrap_time_periodic(self, process_period: Duration, event_period: Duration) -> Self {
+        self.wrap(move |watermarks| {
+            Box::new(TimePeriodicWatermarks::new(
                 watermarks,
                 process_period,
                 event_period,
-            )));
-            self
-        } else {
-            panic!("no WatermarkGenerator for wrapper");
-        }
+            ))
+        })
     }
 
-    pub fn wrap_idleness(mut self, idle_timeout: Duration) -> Self {
-        if let Some(watermarks) = self.watermark_generator.take() {
-            self.watermark_generator = Some(Box::new(WatermarksWithIdleness::new(
-                watermarks,
-                idle_timeout,
-            )));
+    /// Mark the source split idle when it produces no data for the `idle_timeout`, the idle
+    /// split is excluded from the min-watermark calculation, so an empty partition doesn't
+    /// stall the windows
+    pub fn with_idleness(self, idle_timeout: Duration) -> Self {
+        self.wrap(move |watermarks| Box::new(WatermarksWithIdleness::new(watermarks, idle_timeout)))
+    }
+
+    /// Same as `with_idleness`
+    pub fn wrap_idleness(self, idle_timeout: Duration) -> Self {
+        self.with_idleness(idle_timeout)
+    }
+
+    fn wrap<F>(mut self, wrapper: F) -> Self
+    where
+        F: Fn(Box<dyn WatermarkGenerator>) -> Box<dyn WatermarkGenerator> + Send + Sync + 'static,
+    {
+        if let Some(factory) = self.watermark_generator.take() {
+            self.watermark_generator = Some(factory.wrap(wrapper));
             self
         } else {
             panic!("no WatermarkGenerator for wrapper");
         }
     }
 
+    /// Pause the source splits running ahead of the minimum watermark of the `group` beyond
+    /// the `max_drift`, until the other splits catch up
+    pub fn with_watermark_alignment(mut self, group: &str, max_drift: Duration) -> Self {
+        self.watermark_alignment = Some(WatermarkAlignment::new(group, max_drift));
+        self
+    }
+
     pub fn for_schema_timestamp_assigner<T: ColumnLocateBuilder>(mut self, column: T) -> Self {
         self.timestamp_assigner = Some(Box::new(SchemaTimestampAssigner::new(column)));
         self
     }
 
+    /// The `generator` is shared by all splits of the source
     pub fn for_watermark_generator<T>(mut self, generator: T) -> Self
     where
         T: WatermarkGenerator + 'static,
     {
-        self.watermark_generator = Some(Box::new(generator));
+        self.watermark_generator = Some(WatermarkGeneratorFactory::Instance(Some(Box::new(
+            generator,
+        ))));
+        self
+    }
+
+    /// A generator is created by the `supplier` for every split of the source
+    pub fn for_watermark_generator_supplier<F, T>(mut self, supplier: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: WatermarkGenerator + 'static,
+    {
+        self.watermark_generator = Some(WatermarkGeneratorFactory::Supplier(Arc::new(
+            move || -> Box<dyn WatermarkGenerator> { Box::new(supplier()) },
+        )));
         self
     }
 
@@ -79,7 +165,21 @@ impl DefaultWatermarkStrategy {
 
 impl WatermarkStrategy for DefaultWatermarkStrategy {
     fn create_watermark_generator(&mut self) -> Box<dyn WatermarkGenerator> {
-        self.watermark_generator.take().unwrap()
+        self.watermark_generator
+            .as_mut()
+            .and_then(|factory| factory.create())
+            .unwrap()
+    }
+
+    fn create_split_watermark_generator(&mut self) -> Option<Box<dyn WatermarkGenerator>> {
+        match self.watermark_generator.as_mut() {
+            Some(WatermarkGeneratorFactory::Supplier(supplier)) => Some(supplier()),
+            _ => None,
+        }
+    }
+
+    fn watermark_alignment(&self) -> Option<WatermarkAlignment> {
+        self.watermark_alignment.clone()
     }
 
     fn create_timestamp_assigner(&mut self) -> Box<dyn TimestampAssigner> {
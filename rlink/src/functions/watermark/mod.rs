@@ -4,10 +4,16 @@ pub use schema_timestamp_assigner::SchemaTimestampAssigner;
 pub mod bounded_out_of_orderness_watermarks;
 pub use bounded_out_of_orderness_watermarks::BoundedOutOfOrdernessWatermarks;
 
+pub mod punctuated_watermarks;
+pub use punctuated_watermarks::{PunctuatedWatermarks, WatermarkExtractor};
+
 pub mod time_periodic_watermarks;
 pub use time_periodic_watermarks::TimePeriodicWatermarks;
 
 pub mod watermarks_with_idleness;
 
+pub mod watermark_alignment;
+pub use watermark_alignment::{is_split_paused, WatermarkAlignment};
+
 pub mod default_watermark_strategy;
 pub use default_watermark_strategy::DefaultWatermarkStrategy;
@@ -0,0 +1,67 @@
+use crate::core::element::Record;
+use crate::core::watermark::{Watermark, WatermarkGenerator};
+
+/// Extract the watermark timestamp carried by the record, `None` if the record doesn't carry a
+/// watermark
+pub type WatermarkExtractor = fn(record: &mut Record, event_timestamp: u64) -> Option<u64>;
+
+/// Generate the watermark per record by the special records in the stream, e.g. the end
+/// marker of a batch, nothing is generated periodically
+#[derive(Debug)]
+pub struct PunctuatedWatermarks {
+    extractor: WatermarkExtractor,
+    max_watermark: u64,
+}
+
+impl PunctuatedWatermarks {
+    pub fn new(extractor: WatermarkExtractor) -> Self {
+        PunctuatedWatermarks {
+            extractor,
+            max_watermark: 0,
+        }
+    }
+}
+
+impl WatermarkGenerator for PunctuatedWatermarks {
+    fn on_event(&mut self, record: &mut Record, event_timestamp: u64) -> Option<Watermark> {
+        match (self.extractor)(record, event_timestamp) {
+            Some(timestamp) if timestamp > self.max_watermark => {
+                self.max_watermark = timestamp;
+                Some(Watermark::new(timestamp))
+            }
+            _ => None,
+        }
+    }
+
+    fn on_periodic_emit(&mut self) -> Option<Watermark> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::element::Record;
+    use crate::core::watermark::{Watermark, WatermarkGenerator};
+    use crate::functions::watermark::PunctuatedWatermarks;
+
+    #[test]
+    pub fn punctuated_watermarks_test() {
+        // every 10th millisecond is a punctuation
+        let mut watermarks = PunctuatedWatermarks::new(|_record, event_timestamp| {
+            if event_timestamp % 10 == 0 {
+                Some(event_timestamp)
+            } else {
+                None
+            }
+        });
+
+        let mut record = Record::new();
+        assert_eq!(watermarks.on_event(&mut record, 15), None);
+        assert_eq!(
+            watermarks.on_event(&mut record, 20),
+            Some(Watermark::new(20))
+        );
+        assert_eq!(watermarks.on_event(&mut record, 10), None);
+        assert_eq!(watermarks.on_periodic_emit(), None);
+    }
+}
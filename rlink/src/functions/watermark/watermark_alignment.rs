@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::core::watermark::{Watermark, IDLE_WATERMARK};
+
+lazy_static! {
+    static ref ALIGNED_SPLITS: RwLock<HashMap<u32, AlignedSplit>> = RwLock::new(HashMap::new());
+}
+
+/// The watermark alignment group of the source splits, a split whose watermark runs ahead of
+/// the minimum watermark of the group beyond the `max_drift` is paused until the others catch
+/// up, so the state of the joins and windows is bounded during the backfills.
+///
+/// The splits are aligned across the sources of the same group in the worker process, the
+/// sources pause the consumption of a split if `is_split_paused`
+#[derive(Clone, Debug)]
+pub struct WatermarkAlignment {
+    group: String,
+    max_drift: u64,
+}
+
+impl WatermarkAlignment {
+    pub fn new(group: &str, max_drift: Duration) -> Self {
+        WatermarkAlignment {
+            group: group.to_string(),
+            max_drift: max_drift.as_millis() as u64,
+        }
+    }
+
+    pub fn group(&self) -> &str {
+        self.group.as_str()
+    }
+
+    pub fn max_drift(&self) -> u64 {
+        self.max_drift
+    }
+}
+
+#[derive(Debug)]
+struct AlignedSplit {
+    group: String,
+    watermark: u64,
+    paused: bool,
+}
+
+/// Update the watermarks of the splits, and pause the splits of the group running ahead
+pub(crate) fn update_split_watermarks(
+    alignment: &WatermarkAlignment,
+    watermarks: Vec<(u32, Watermark)>,
+) {
+    let mut aligned_splits = ALIGNED_SPLITS.write().unwrap();
+    for (split, watermark) in watermarks {
+        let aligned_split = aligned_splits.entry(split).or_insert_with(|| AlignedSplit {
+            group: alignment.group.clone(),
+            watermark: 0,
+            paused: false,
+        });
+        aligned_split.watermark = watermark.timestamp;
+    }
+
+    // the idle split is excluded from the group's minimum watermark
+    let min_watermark = aligned_splits
+        .values()
+        .filter(|x| x.group.eq(&alignment.group) && x.watermark != IDLE_WATERMARK.timestamp)
+        .map(|x| x.watermark)
+        .min();
+    let min_watermark = match min_watermark {
+        Some(min_watermark) => min_watermark,
+        None => return,
+    };
+
+    for aligned_split in aligned_splits.values_mut() {
+        if aligned_split.group.eq(&alignment.group) {
+            aligned_split.paused = aligned_split.watermark != IDLE_WATERMARK.timestamp
+                && aligned_split.watermark > min_watermark.saturating_add(alignment.max_drift);
+        }
+    }
+}
+
+/// Remove the splits from the alignment, e.g. the task is closed
+pub(crate) fn remove_splits(splits: Vec<u32>) {
+    let mut aligned_splits = ALIGNED_SPLITS.write().unwrap();
+    for split in splits {
+        aligned_splits.remove(&split);
+    }
+}
+
+/// Whether the split runs too far ahead of its alignment group, the source should stop
+/// consuming the split until it's resumed
+pub fn is_split_paused(split: u32) -> bool {
+    ALIGNED_SPLITS
+        .read()
+        .unwrap()
+        .get(&split)
+        .map(|x| x.paused)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::watermark::{Watermark, IDLE_WATERMARK};
+    use crate::functions::watermark::watermark_alignment::{
+        is_split_paused, remove_splits, update_split_watermarks, WatermarkAlignment,
+    };
+
+    #[test]
+    pub fn watermark_alignment_test() {
+        let alignment = WatermarkAlignment::new("alignment_test", Duration::from_millis(100));
+
+        update_split_watermarks(
+            &alignment,
+            vec![(9001, Watermark::new(1000)), (9002, Watermark::new(1100))],
+        );
+        update_split_watermarks(&alignment, vec![(9003, Watermark::new(1200))]);
+        assert!(!is_split_paused(9001));
+        assert!(!is_split_paused(9002));
+        assert!(is_split_paused(9003));
+
+        // the idle split doesn't hold back the group
+        update_split_watermarks(&alignment, vec![(9001, IDLE_WATERMARK)]);
+        assert!(!is_split_paused(9001));
+        assert!(!is_split_paused(9003));
+
+        remove_splits(vec![9001, 9002, 9003]);
+        assert!(!is_split_paused(9003));
+    }
+}
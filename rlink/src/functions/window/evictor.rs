@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use crate::core::element::Record;
+use crate::core::function::NamedFunction;
+use crate::core::window::{Evictor, EvictorContext, Window};
+
+/// Compute the delta between the element and the last element of the window
+pub type DeltaFunction = fn(element: &mut Record, last_element: &mut Record) -> f64;
+
+/// Keep at most `max_count` latest elements of each key
+#[derive(Debug)]
+pub struct CountEvictor {
+    max_count: usize,
+    do_evict_after: bool,
+}
+
+impl CountEvictor {
+    /// Evict the elements before the window is evaluated
+    pub fn of(max_count: usize) -> Self {
+        CountEvictor {
+            max_count,
+            do_evict_after: false,
+        }
+    }
+
+    /// Evict the elements after the window is evaluated
+    pub fn of_after(max_count: usize) -> Self {
+        CountEvictor {
+            max_count,
+            do_evict_after: true,
+        }
+    }
+
+    fn evict(&self, elements: &mut Vec<Record>) {
+        if elements.len() > self.max_count {
+            let evict_count = elements.len() - self.max_count;
+            elements.drain(..evict_count);
+        }
+    }
+}
+
+impl Evictor for CountEvictor {
+    fn evict_before(&self, elements: &mut Vec<Record>, _window: &Window, _ctx: &EvictorContext) {
+        if !self.do_evict_after {
+            self.evict(elements);
+        }
+    }
+
+    fn evict_after(&self, elements: &mut Vec<Record>, _window: &Window, _ctx: &EvictorContext) {
+        if self.do_evict_after {
+            self.evict(elements);
+        }
+    }
+}
+
+impl NamedFunction for CountEvictor {
+    fn name(&self) -> &str {
+        "CountEvictor"
+    }
+}
+
+/// Keep the elements of each key within `window_size` of the latest element timestamp
+#[derive(Debug)]
+pub struct TimeEvictor {
+    window_size: u64,
+    do_evict_after: bool,
+}
+
+impl TimeEvictor {
+    /// Evict the elements before the window is evaluated
+    pub fn of(window_size: Duration) -> Self {
+        TimeEvictor {
+            window_size: window_size.as_millis() as u64,
+            do_evict_after: false,
+        }
+    }
+
+    /// Evict the elements after the window is evaluated
+    pub fn of_after(window_size: Duration) -> Self {
+        TimeEvictor {
+            window_size: window_size.as_millis() as u64,
+            do_evict_after: true,
+        }
+    }
+
+    fn evict(&self, elements: &mut Vec<Record>) {
+        let current_time = match elements.iter().map(|x| x.timestamp).max() {
+            Some(current_time) => current_time,
+            None => return,
+        };
+        if current_time < self.window_size {
+            return;
+        }
+
+        let evict_cutoff = current_time - self.window_size;
+        elements.retain(|element| element.timestamp > evict_cutoff);
+    }
+}
+
+impl Evictor for TimeEvictor {
+    fn evict_before(&self, elements: &mut Vec<Record>, _window: &Window, _ctx: &EvictorContext) {
+        if !self.do_evict_after {
+            self.evict(elements);
+        }
+    }
+
+    fn evict_after(&self, elements: &mut Vec<Record>, _window: &Window, _ctx: &EvictorContext) {
+        if self.do_evict_after {
+            self.evict(elements);
+        }
+    }
+}
+
+impl NamedFunction for TimeEvictor {
+    fn name(&self) -> &str {
+        "TimeEvictor"
+    }
+}
+
+/// Evict the elements of each key whose delta to the last element is not less than the
+/// `threshold`
+#[derive(Debug)]
+pub struct DeltaEvictor {
+    threshold: f64,
+    delta_function: DeltaFunction,
+    do_evict_after: bool,
+}
+
+impl DeltaEvictor {
+    /// Evict the elements before the window is evaluated
+    pub fn of(threshold: f64, delta_function: DeltaFunction) -> Self {
+        DeltaEvictor {
+            threshold,
+            delta_function,
+            do_evict_after: false,
+        }
+    }
+
+    /// Evict the elements after the window is evaluated
+    pub fn of_after(threshold: f64, delta_function: DeltaFunction) -> Self {
+        DeltaEvictor {
+            threshold,
+            delta_function,
+            do_evict_after: true,
+        }
+    }
+
+    fn evict(&self, elements: &mut Vec<Record>) {
+        let mut last_element = match elements.last() {
+            Some(last_element) => last_element.clone(),
+            None => return,
+        };
+
+        let mut retained = Vec::with_capacity(elements.len());
+        for mut element in elements.drain(..) {
+            if (self.delta_function)(&mut element, &mut last_element) < self.threshold {
+                retained.push(element);
+            }
+        }
+        *elements = retained;
+    }
+}
+
+impl Evictor for DeltaEvictor {
+    fn evict_before(&self, elements: &mut Vec<Record>, _window: &Window, _ctx: &EvictorContext) {
+        if !self.do_evict_after {
+            self.evict(elements);
+        }
+    }
+
+    fn evict_after(&self, elements: &mut Vec<Record>, _window: &Window, _ctx: &EvictorContext) {
+        if self.do_evict_after {
+            self.evict(elements);
+        }
+    }
+}
+
+impl NamedFunction for DeltaEvictor {
+    fn name(&self) -> &str {
+        "DeltaEvictor"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::element::Record;
+    use crate::core::window::{Evictor, EvictorContext, Window};
+    use crate::functions::window::evictor::{CountEvictor, DeltaEvictor, TimeEvictor};
+
+    fn elements(timestamps: &[u64]) -> Vec<Record> {
+        timestamps
+            .iter()
+            .map(|timestamp| {
+                let mut record = Record::new();
+                record.timestamp = *timestamp;
+                record
+            })
+            .collect()
+    }
+
+    fn timestamps(elements: &Vec<Record>) -> Vec<u64> {
+        elements.iter().map(|x| x.timestamp).collect()
+    }
+
+    #[test]
+    pub fn evictor_test() {
+        let window = Window::default();
+        let context = EvictorContext::new(0);
+
+        let mut records = elements(&[1, 2, 3, 4]);
+        CountEvictor::of(2).evict_before(&mut records, &window, &context);
+        assert_eq!(timestamps(&records), vec![3, 4]);
+
+        let mut records = elements(&[1, 2, 3, 4]);
+        CountEvictor::of_after(2).evict_before(&mut records, &window, &context);
+        assert_eq!(records.len(), 4);
+
+        let mut records = elements(&[10, 20, 30, 40]);
+        TimeEvictor::of(Duration::from_millis(20)).evict_before(&mut records, &window, &context);
+        assert_eq!(timestamps(&records), vec![30, 40]);
+
+        let mut records = elements(&[10, 35, 30, 40]);
+        let evictor = DeltaEvictor::of(8.0, |element, last_element| {
+            (last_element.timestamp - element.timestamp) as f64
+        });
+        evictor.evict_before(&mut records, &window, &context);
+        assert_eq!(timestamps(&records), vec![35, 40]);
+    }
+}
@@ -1,8 +1,16 @@
+pub mod evictor;
+pub mod trigger;
+pub use evictor::*;
+pub use trigger::*;
+
 use std::time::Duration;
 
 use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::element::Record;
 use crate::core::function::NamedFunction;
-use crate::core::window::{TWindow, TimeWindow, Window, WindowAssigner, WindowAssignerContext};
+use crate::core::window::{
+    CountWindow, TWindow, TimeWindow, Window, WindowAssigner, WindowAssignerContext,
+};
 
 /// window offset
 pub struct Offset {
@@ -100,14 +108,248 @@ impl CheckpointFunction for SlidingEventTimeWindows {
     }
 }
 
+/// Assign all records to the single global window, the window is never completed by the
+/// watermark, so a trigger must be set to fire it, e.g. `CountTrigger`
+#[derive(Debug, Default)]
+pub struct GlobalWindows {}
+
+impl GlobalWindows {
+    pub fn create() -> Self {
+        GlobalWindows {}
+    }
+
+    pub fn global_window() -> Window {
+        Window::TimeWindow(TimeWindow::new(0, u64::MAX))
+    }
+}
+
+impl WindowAssigner for GlobalWindows {
+    fn assign_windows(&self, _timestamp: u64, _context: WindowAssignerContext) -> Vec<Window> {
+        vec![Self::global_window()]
+    }
+}
+
+impl NamedFunction for GlobalWindows {
+    fn name(&self) -> &str {
+        "GlobalWindows"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for GlobalWindows {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+/// Assign the records of each key to the windows of every `size` elements, the window of a key
+/// is fired every `slide` elements with the latest `size` elements, it's the tumbling window if
+/// `slide` equals to `size`. The elements are counted by key in the window operator
+#[derive(Debug)]
+pub struct CountWindows {
+    window: CountWindow,
+}
+
+impl CountWindows {
+    pub fn of(size: u64) -> Self {
+        CountWindows::of_sliding(size, size)
+    }
+
+    pub fn of_sliding(size: u64, slide: u64) -> Self {
+        if size == 0 || slide == 0 || slide > size {
+            panic!("CountWindows parameters must satisfy 0 < slide <= size")
+        }
+        CountWindows {
+            window: CountWindow::new(size, slide),
+        }
+    }
+}
+
+impl WindowAssigner for CountWindows {
+    fn assign_windows(&self, _timestamp: u64, _context: WindowAssignerContext) -> Vec<Window> {
+        vec![Window::CountWindow(self.window.clone())]
+    }
+}
+
+impl NamedFunction for CountWindows {
+    fn name(&self) -> &str {
+        "CountWindows"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for CountWindows {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+/// Extract the session gap of the record
+pub type SessionWindowTimeGapExtractor = fn(&mut Record) -> Duration;
+
+#[derive(Debug)]
+enum SessionGap {
+    Fixed(u64),
+    Dynamic(SessionWindowTimeGapExtractor),
+}
+
+/// Assign the record to the session window `[timestamp, timestamp + gap)`, the session windows
+/// of a key are merged if they intersect, so a session ends after a gap of inactivity
+#[derive(Debug)]
+pub struct EventTimeSessionWindows {
+    gap: SessionGap,
+}
+
+impl EventTimeSessionWindows {
+    pub fn with_gap(gap: Duration) -> Self {
+        let gap = gap.as_millis() as u64;
+        if gap == 0 {
+            panic!("EventTimeSessionWindows parameters must satisfy gap > 0")
+        }
+        EventTimeSessionWindows {
+            gap: SessionGap::Fixed(gap),
+        }
+    }
+
+    /// The gap of each session window is extracted from the record
+    pub fn with_dynamic_gap(extractor: SessionWindowTimeGapExtractor) -> Self {
+        EventTimeSessionWindows {
+            gap: SessionGap::Dynamic(extractor),
+        }
+    }
+
+    fn session_window(timestamp: u64, gap: u64) -> Window {
+        Window::SessionWindow(TimeWindow::new(timestamp, timestamp + gap.max(1)))
+    }
+}
+
+impl WindowAssigner for EventTimeSessionWindows {
+    /// Only the start of the window is significant for the watermark, the dynamic gap is
+    /// unknown without the record
+    fn assign_windows(&self, timestamp: u64, _context: WindowAssignerContext) -> Vec<Window> {
+        let gap = match self.gap {
+            SessionGap::Fixed(gap) => gap,
+            SessionGap::Dynamic(_) => 1,
+        };
+        vec![Self::session_window(timestamp, gap)]
+    }
+
+    fn assign_record_windows(
+        &self,
+        record: &mut Record,
+        _context: WindowAssignerContext,
+    ) -> Vec<Window> {
+        let gap = match self.gap {
+            SessionGap::Fixed(gap) => gap,
+            SessionGap::Dynamic(extractor) => extractor(record).as_millis() as u64,
+        };
+        vec![Self::session_window(record.timestamp, gap)]
+    }
+
+    fn is_merging(&self) -> bool {
+        true
+    }
+}
+
+impl NamedFunction for EventTimeSessionWindows {
+    fn name(&self) -> &str {
+        "EventTimeSessionWindows"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for EventTimeSessionWindows {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
-    use crate::core::window::{WindowAssigner, WindowAssignerContext};
-    use crate::functions::window::{Offset, SlidingEventTimeWindows};
+    use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+    use crate::core::element::Record;
+    use crate::core::function::NamedFunction;
+    use crate::core::window::{
+        CountWindow, TWindow, TimeWindow, Window, WindowAssigner, WindowAssignerContext,
+    };
+    use crate::functions::window::{
+        CountWindows, EventTimeSessionWindows, GlobalWindows, Offset, SlidingEventTimeWindows,
+    };
     use crate::utils::date_time::current_timestamp_millis;
 
+    /// The hourly windows of the first half of each hour
+    #[derive(Debug)]
+    struct HalfHourWindows {}
+
+    impl WindowAssigner for HalfHourWindows {
+        fn assign_windows(&self, timestamp: u64, _context: WindowAssignerContext) -> Vec<Window> {
+            let start = timestamp - timestamp % 3_600_000;
+            if timestamp - start < 1_800_000 {
+                vec![Window::TimeWindow(TimeWindow::new(
+                    start,
+                    start + 1_800_000,
+                ))]
+            } else {
+                vec![]
+            }
+        }
+    }
+
+    impl NamedFunction for HalfHourWindows {
+        fn name(&self) -> &str {
+            "HalfHourWindows"
+        }
+    }
+
+    #[async_trait]
+    impl CheckpointFunction for HalfHourWindows {
+        async fn initialize_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+            _handle: &Option<CheckpointHandle>,
+        ) {
+        }
+
+        async fn snapshot_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+        ) -> Option<CheckpointHandle> {
+            None
+        }
+    }
+
     #[test]
     pub fn window_assigner_test() {
         let time_windows = SlidingEventTimeWindows::new(
@@ -122,4 +364,57 @@ mod tests {
 
         println!("{:?}", windows);
     }
+
+    #[test]
+    pub fn session_window_assigner_test() {
+        let mut record = Record::new();
+        record.timestamp = 1000;
+
+        let session_windows = EventTimeSessionWindows::with_gap(Duration::from_secs(5));
+        let windows = session_windows.assign_record_windows(&mut record, WindowAssignerContext {});
+        assert_eq!(
+            windows,
+            vec![Window::SessionWindow(TimeWindow::new(1000, 6000))]
+        );
+
+        let session_windows = EventTimeSessionWindows::with_dynamic_gap(|record| {
+            Duration::from_millis(record.timestamp)
+        });
+        let windows = session_windows.assign_record_windows(&mut record, WindowAssignerContext {});
+        assert_eq!(
+            windows,
+            vec![Window::SessionWindow(TimeWindow::new(1000, 2000))]
+        );
+    }
+
+    #[test]
+    pub fn custom_window_assigner_test() {
+        let assigner = HalfHourWindows {};
+        assert_eq!(
+            assigner.assign_watermark_windows(3_600_000 + 10, WindowAssignerContext {}),
+            vec![Window::TimeWindow(TimeWindow::new(3_600_000, 5_400_000))]
+        );
+        // the watermark out of the windows
+        assert_eq!(
+            assigner.assign_watermark_windows(5_400_000 + 10, WindowAssignerContext {}),
+            vec![Window::TimeWindow(TimeWindow::new(5_400_010, 5_400_010))]
+        );
+
+        let global_windows = GlobalWindows::create();
+        assert_eq!(
+            global_windows.assign_watermark_windows(1000, WindowAssignerContext {}),
+            vec![GlobalWindows::global_window()]
+        );
+    }
+
+    #[test]
+    pub fn count_window_assigner_test() {
+        let windows = CountWindows::of_sliding(3, 1).assign_windows(1000, WindowAssignerContext {});
+        assert_eq!(windows, vec![Window::CountWindow(CountWindow::new(3, 1))]);
+        assert!(windows[0].is_count_window());
+        // the count window is never completed by the watermark
+        assert_eq!(windows[0].max_timestamp(), u64::MAX);
+
+        assert!(CountWindow::new(3, 3).is_tumbling());
+    }
 }
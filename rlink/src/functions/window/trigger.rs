@@ -0,0 +1,363 @@
+use std::time::Duration;
+
+use crate::core::element::Record;
+use crate::core::function::NamedFunction;
+use crate::core::window::{TWindow, Trigger, TriggerContext, TriggerResult, Window};
+
+/// the state of the `EventTimeTrigger`, it's set once the watermark passes the end of the window
+const ON_TIME_FIRED: &str = "event_time.on_time_fired";
+/// the state of the `CountTrigger`, the number of the elements since the latest firing
+const COUNT: &str = "count.count";
+/// the state of the `ProcessingTimeIntervalTrigger`, the next processing time to fire
+const NEXT_FIRE_TIME: &str = "processing_time_interval.next_fire_time";
+
+/// Fire the window once the watermark passes the end of the window, it's the default trigger.
+///
+/// The early firing trigger fires the window before the watermark passes the end of the window,
+/// and the late firing trigger fires the window for the late elements afterwards, the late
+/// element fires the window if there is no late firing trigger
+#[derive(Debug, Default)]
+pub struct EventTimeTrigger {
+    early_trigger: Option<Box<dyn Trigger>>,
+    late_trigger: Option<Box<dyn Trigger>>,
+}
+
+impl EventTimeTrigger {
+    pub fn new() -> Self {
+        EventTimeTrigger::default()
+    }
+
+    pub fn with_early_firing<T>(mut self, trigger: T) -> Self
+    where
+        T: Trigger + 'static,
+    {
+        self.early_trigger = Some(Box::new(trigger));
+        self
+    }
+
+    pub fn with_late_firing<T>(mut self, trigger: T) -> Self
+    where
+        T: Trigger + 'static,
+    {
+        self.late_trigger = Some(Box::new(trigger));
+        self
+    }
+}
+
+impl Trigger for EventTimeTrigger {
+    fn on_element(
+        &self,
+        record: &Record,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        if context.get_state(ON_TIME_FIRED).is_some() {
+            match &self.late_trigger {
+                Some(late_trigger) => late_trigger.on_element(record, window, context),
+                None => TriggerResult::Fire,
+            }
+        } else {
+            match &self.early_trigger {
+                Some(early_trigger) => early_trigger.on_element(record, window, context),
+                None => TriggerResult::Continue,
+            }
+        }
+    }
+
+    fn on_event_time(
+        &self,
+        time: u64,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        if context.get_state(ON_TIME_FIRED).is_some() {
+            return match &self.late_trigger {
+                Some(late_trigger) => late_trigger.on_event_time(time, window, context),
+                None => TriggerResult::Continue,
+            };
+        }
+
+        if time >= window.max_timestamp() {
+            if let Some(early_trigger) = &self.early_trigger {
+                early_trigger.clear(window, context);
+            }
+            context.set_state(ON_TIME_FIRED, 1);
+            return TriggerResult::Fire;
+        }
+
+        match &self.early_trigger {
+            Some(early_trigger) => early_trigger.on_event_time(time, window, context),
+            None => TriggerResult::Continue,
+        }
+    }
+
+    fn on_processing_time(
+        &self,
+        time: u64,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        let trigger = if context.get_state(ON_TIME_FIRED).is_some() {
+            &self.late_trigger
+        } else {
+            &self.early_trigger
+        };
+        match trigger {
+            Some(trigger) => trigger.on_processing_time(time, window, context),
+            None => TriggerResult::Continue,
+        }
+    }
+
+    fn clear(&self, window: &Window, context: &mut TriggerContext) {
+        if let Some(early_trigger) = &self.early_trigger {
+            early_trigger.clear(window, context);
+        }
+        if let Some(late_trigger) = &self.late_trigger {
+            late_trigger.clear(window, context);
+        }
+        context.remove_state(ON_TIME_FIRED);
+    }
+}
+
+impl NamedFunction for EventTimeTrigger {
+    fn name(&self) -> &str {
+        "EventTimeTrigger"
+    }
+}
+
+/// Fire the window once the number of the elements since the latest firing reaches `max_count`
+#[derive(Debug)]
+pub struct CountTrigger {
+    max_count: u64,
+}
+
+impl CountTrigger {
+    pub fn of(max_count: u64) -> Self {
+        if max_count == 0 {
+            panic!("CountTrigger parameters must satisfy max_count > 0")
+        }
+        CountTrigger { max_count }
+    }
+}
+
+impl Trigger for CountTrigger {
+    fn on_element(
+        &self,
+        _record: &Record,
+        _window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        let count = context.get_state(COUNT).unwrap_or(0) + 1;
+        if count >= self.max_count {
+            context.remove_state(COUNT);
+            TriggerResult::Fire
+        } else {
+            context.set_state(COUNT, count);
+            TriggerResult::Continue
+        }
+    }
+
+    fn on_event_time(
+        &self,
+        _time: u64,
+        _window: &Window,
+        _context: &mut TriggerContext,
+    ) -> TriggerResult {
+        TriggerResult::Continue
+    }
+
+    fn on_processing_time(
+        &self,
+        _time: u64,
+        _window: &Window,
+        _context: &mut TriggerContext,
+    ) -> TriggerResult {
+        TriggerResult::Continue
+    }
+
+    fn clear(&self, _window: &Window, context: &mut TriggerContext) {
+        context.remove_state(COUNT);
+    }
+}
+
+impl NamedFunction for CountTrigger {
+    fn name(&self) -> &str {
+        "CountTrigger"
+    }
+}
+
+/// Fire the window periodically by the processing time `interval` since the first element of
+/// the window, the firing is checked when the watermark reaches
+#[derive(Debug)]
+pub struct ProcessingTimeIntervalTrigger {
+    interval: u64,
+}
+
+impl ProcessingTimeIntervalTrigger {
+    pub fn every(interval: Duration) -> Self {
+        let interval = interval.as_millis() as u64;
+        if interval == 0 {
+            panic!("ProcessingTimeIntervalTrigger parameters must satisfy interval > 0")
+        }
+        ProcessingTimeIntervalTrigger { interval }
+    }
+
+    fn next_fire_time(&self, time: u64) -> u64 {
+        time - time % self.interval + self.interval
+    }
+}
+
+impl Trigger for ProcessingTimeIntervalTrigger {
+    fn on_element(
+        &self,
+        _record: &Record,
+        _window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        if context.get_state(NEXT_FIRE_TIME).is_none() {
+            let next_fire_time = self.next_fire_time(context.current_processing_time());
+            context.set_state(NEXT_FIRE_TIME, next_fire_time);
+        }
+        TriggerResult::Continue
+    }
+
+    fn on_event_time(
+        &self,
+        _time: u64,
+        _window: &Window,
+        _context: &mut TriggerContext,
+    ) -> TriggerResult {
+        TriggerResult::Continue
+    }
+
+    fn on_processing_time(
+        &self,
+        time: u64,
+        _window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        match context.get_state(NEXT_FIRE_TIME) {
+            Some(next_fire_time) if time >= next_fire_time => {
+                context.set_state(NEXT_FIRE_TIME, self.next_fire_time(time));
+                TriggerResult::Fire
+            }
+            _ => TriggerResult::Continue,
+        }
+    }
+
+    fn clear(&self, _window: &Window, context: &mut TriggerContext) {
+        context.remove_state(NEXT_FIRE_TIME);
+    }
+}
+
+impl NamedFunction for ProcessingTimeIntervalTrigger {
+    fn name(&self) -> &str {
+        "ProcessingTimeIntervalTrigger"
+    }
+}
+
+/// Purge the window whenever the nested trigger fires
+#[derive(Debug)]
+pub struct PurgingTrigger {
+    trigger: Box<dyn Trigger>,
+}
+
+impl PurgingTrigger {
+    pub fn of<T>(trigger: T) -> Self
+    where
+        T: Trigger + 'static,
+    {
+        PurgingTrigger {
+            trigger: Box::new(trigger),
+        }
+    }
+
+    fn purging(result: TriggerResult) -> TriggerResult {
+        if result.is_fire() {
+            TriggerResult::FireAndPurge
+        } else {
+            result
+        }
+    }
+}
+
+impl Trigger for PurgingTrigger {
+    fn on_element(
+        &self,
+        record: &Record,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        Self::purging(self.trigger.on_element(record, window, context))
+    }
+
+    fn on_event_time(
+        &self,
+        time: u64,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        Self::purging(self.trigger.on_event_time(time, window, context))
+    }
+
+    fn on_processing_time(
+        &self,
+        time: u64,
+        window: &Window,
+        context: &mut TriggerContext,
+    ) -> TriggerResult {
+        Self::purging(self.trigger.on_processing_time(time, window, context))
+    }
+
+    fn clear(&self, window: &Window, context: &mut TriggerContext) {
+        self.trigger.clear(window, context)
+    }
+}
+
+impl NamedFunction for PurgingTrigger {
+    fn name(&self) -> &str {
+        "PurgingTrigger"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::element::Record;
+    use crate::core::window::{TimeWindow, Trigger, TriggerContext, TriggerResult, Window};
+    use crate::functions::window::trigger::{CountTrigger, EventTimeTrigger};
+
+    #[test]
+    pub fn early_firing_trigger_test() {
+        let trigger = EventTimeTrigger::new().with_early_firing(CountTrigger::of(2));
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+        let record = Record::new();
+        let mut context = TriggerContext::default();
+
+        let results: Vec<TriggerResult> = (0..3)
+            .map(|_| trigger.on_element(&record, &window, &mut context))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                TriggerResult::Continue,
+                TriggerResult::Fire,
+                TriggerResult::Continue
+            ]
+        );
+
+        assert_eq!(
+            trigger.on_event_time(5, &window, &mut context),
+            TriggerResult::Continue
+        );
+        assert_eq!(
+            trigger.on_event_time(10, &window, &mut context),
+            TriggerResult::Fire
+        );
+
+        // the late element fires the window without the late firing trigger
+        assert_eq!(
+            trigger.on_element(&record, &window, &mut context),
+            TriggerResult::Fire
+        );
+    }
+}
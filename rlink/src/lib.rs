@@ -21,4 +21,5 @@ pub mod channel;
 pub mod core;
 pub mod functions;
 pub mod metrics;
+pub mod state_processor;
 pub mod utils;
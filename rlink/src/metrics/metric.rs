@@ -1,4 +1,4 @@
-use metrics::{Counter, Gauge, Key, KeyName, Label};
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Label};
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct Tag(pub(crate) String, pub(crate) String);
@@ -48,3 +48,56 @@ where
         Gauge::noop()
     }
 }
+
+pub fn register_histogram<K>(name: K, tags: Vec<Tag>) -> Histogram
+where
+    K: ToString,
+{
+    let tags: Vec<Label> = tags.into_iter().map(|t| Label::new(t.0, t.1)).collect();
+
+    let key = Key::from_parts(KeyName::from(name.to_string()), tags);
+
+    if let Some(recorder) = metrics::try_recorder() {
+        recorder.register_histogram(&key)
+    } else {
+        Histogram::noop()
+    }
+}
+
+/// The metrics registered with the same tags, e.g. the tags of a task of an operator, see
+/// `Context::metric_group`
+#[derive(Clone, Debug, Default)]
+pub struct MetricGroup {
+    tags: Vec<Tag>,
+}
+
+impl MetricGroup {
+    pub fn new(tags: Vec<Tag>) -> Self {
+        MetricGroup { tags }
+    }
+
+    pub fn tags(&self) -> &[Tag] {
+        self.tags.as_slice()
+    }
+
+    pub fn counter<K>(&self, name: K) -> Counter
+    where
+        K: ToString,
+    {
+        register_counter(name, self.tags.clone())
+    }
+
+    pub fn gauge<K>(&self, name: K) -> Gauge
+    where
+        K: ToString,
+    {
+        register_gauge(name, self.tags.clone())
+    }
+
+    pub fn histogram<K>(&self, name: K) -> Histogram
+    where
+        K: ToString,
+    {
+        register_histogram(name, self.tags.clone())
+    }
+}
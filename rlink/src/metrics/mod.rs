@@ -8,7 +8,10 @@ use tokio::sync::RwLock;
 use crate::utils::process::sys_info_metric_task;
 pub use metric::register_counter;
 pub use metric::register_gauge;
+pub use metric::register_histogram;
+pub use metric::MetricGroup;
 pub use metric::Tag;
+pub use metrics::{Counter, Gauge, Histogram};
 
 #[derive(Clone)]
 pub(crate) struct MetricHandle {
@@ -1,5 +1,6 @@
 pub mod memory;
 pub mod network;
+pub(crate) mod spill;
 
 pub(crate) const DEFAULT_CHANNEL_SIZE: usize = 10240;
 
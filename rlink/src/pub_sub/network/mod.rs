@@ -12,6 +12,7 @@ pub(crate) mod client;
 pub(crate) mod server;
 
 pub(crate) use client::subscribe;
+pub(crate) use server::drain_network_channel;
 pub(crate) use server::publish;
 pub(crate) use server::Server;
 
@@ -93,6 +93,18 @@ fn remove_network_channel(key: &ChannelKey) {
     network_channels.remove(key);
 }
 
+/// Take all elements buffered in the channel, so the `Barrier` can be sent before them
+pub(crate) fn drain_network_channel(key: &ChannelKey) -> Vec<Element> {
+    let network_channels: &DashMap<ChannelKey, ElementReceiver> = &*NETWORK_CHANNELS;
+    let mut elements = Vec::new();
+    if let Some(mut receiver) = network_channels.get_mut(key) {
+        while let Ok(element) = receiver.try_recv() {
+            elements.push(element);
+        }
+    }
+    elements
+}
+
 /// Check whether all channels have been removed.
 /// Used to determine whether the `TaskManager` instance can be closed.
 #[allow(dead_code)]
@@ -0,0 +1,464 @@
+use std::borrow::BorrowMut;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+use bytes::{BufMut, BytesMut};
+
+use crate::core::element::{Element, Record, Serde};
+use crate::core::runtime::{ChannelKey, OperatorId, TaskId};
+use crate::core::window::Window;
+
+/// the bytes of the elements kept in memory before the others are spilled to the disk
+pub(crate) const DEFAULT_SPILL_MEMORY_SIZE: usize = 64 * 1024 * 1024;
+
+/// The fields of the element dropped by the `Serde`, they're spilled with the element
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SpillExtras {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location_windows: Option<Vec<Window>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trigger_window: Option<Window>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drop_windows: Option<Vec<Window>>,
+    downstream: bool,
+}
+
+/// A FIFO buffer of the elements, the elements over the `memory_size` bytes are spilled to a
+/// file in the temp directory, e.g. the blocking input of a stage in the `ExecutionMode::Batch`.
+///
+/// All elements must be pushed before the first pop, the file is removed once it's dropped
+pub(crate) struct SpillBuffer {
+    path: PathBuf,
+    memory: VecDeque<Element>,
+    memory_bytes: usize,
+    memory_size: usize,
+    writer: Option<BufWriter<File>>,
+    reader: Option<BufReader<File>>,
+    /// the number of the elements in the file not read
+    spilled: usize,
+}
+
+impl SpillBuffer {
+    pub fn new(task_id: &TaskId, memory_size: usize) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "rlink-spill-{}-{}-{}",
+            std::process::id(),
+            task_id.job_id().0,
+            task_id.task_number()
+        ));
+        SpillBuffer {
+            path,
+            memory: VecDeque::new(),
+            memory_bytes: 0,
+            memory_size,
+            writer: None,
+            reader: None,
+            spilled: 0,
+        }
+    }
+
+    pub fn push(&mut self, element: Element) -> anyhow::Result<()> {
+        if self.reader.is_some() {
+            return Err(anyhow!("push the element to the spill buffer being read"));
+        }
+
+        let capacity = element.capacity();
+        if self.writer.is_none() && self.memory_bytes + capacity <= self.memory_size {
+            self.memory_bytes += capacity;
+            self.memory.push_back(element);
+            return Ok(());
+        }
+
+        if self.writer.is_none() {
+            info!(
+                "the buffer exceeds {} bytes, spill to {:?}",
+                self.memory_size, self.path
+            );
+            self.writer = Some(BufWriter::new(File::create(&self.path)?));
+        }
+        let frame = encode(&element)?;
+        self.writer.as_mut().unwrap().write_all(frame.as_ref())?;
+        self.spilled += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> anyhow::Result<Option<Element>> {
+        if let Some(element) = self.memory.pop_front() {
+            self.memory_bytes -= element.capacity();
+            return Ok(Some(element));
+        }
+        if self.spilled == 0 {
+            return Ok(None);
+        }
+
+        if self.reader.is_none() {
+            if let Some(mut writer) = self.writer.take() {
+                writer.flush()?;
+            }
+            self.reader = Some(BufReader::new(File::open(&self.path)?));
+        }
+        let element = decode(self.reader.as_mut().unwrap())?;
+        self.spilled -= 1;
+        Ok(Some(element))
+    }
+}
+
+impl Drop for SpillBuffer {
+    fn drop(&mut self) {
+        self.writer.take();
+        self.reader.take();
+        if self.path.exists() {
+            if let Err(e) = std::fs::remove_file(&self.path) {
+                warn!("remove the spill file {:?} error. {}", self.path, e);
+            }
+        }
+    }
+}
+
+/// A run of the `ExternalSorter` sorted by the keys
+enum SortedRun {
+    Memory(std::vec::IntoIter<(Record, Record)>),
+    File {
+        reader: BufReader<File>,
+        remaining: usize,
+    },
+}
+
+impl SortedRun {
+    fn next(&mut self) -> anyhow::Result<Option<(Record, Record)>> {
+        match self {
+            SortedRun::Memory(records) => Ok(records.next()),
+            SortedRun::File { reader, remaining } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                let key = decode(reader)?.into_record();
+                let record = decode(reader)?.into_record();
+                Ok(Some((key, record)))
+            }
+        }
+    }
+}
+
+/// An external merge sort of the records by their keys, e.g. the sort-based aggregation of the
+/// `ExecutionMode::Batch`. Once the records exceed the `memory_size` bytes they're sorted and
+/// spilled to a file in the temp directory as a run, then the runs are merged by `pop`.
+///
+/// The records of the same key are popped in the pushed order. All records must be pushed
+/// before the first pop, the files are removed once it's dropped
+pub(crate) struct ExternalSorter {
+    path_prefix: String,
+    memory: Vec<(Record, Record)>,
+    memory_bytes: usize,
+    memory_size: usize,
+    /// the files of the spilled runs and the number of the records in them
+    run_files: Vec<(PathBuf, usize)>,
+    /// the runs being merged in the pushed order and their first records
+    runs: Option<Vec<(SortedRun, Option<(Record, Record)>)>>,
+}
+
+impl ExternalSorter {
+    pub fn new(task_id: &TaskId, operator_id: OperatorId, memory_size: usize) -> Self {
+        let path_prefix = std::env::temp_dir()
+            .join(format!(
+                "rlink-sort-{}-{}-{}-{}",
+                std::process::id(),
+                task_id.job_id().0,
+                task_id.task_number(),
+                operator_id.0
+            ))
+            .to_string_lossy()
+            .to_string();
+        ExternalSorter {
+            path_prefix,
+            memory: Vec::new(),
+            memory_bytes: 0,
+            memory_size,
+            run_files: Vec::new(),
+            runs: None,
+        }
+    }
+
+    pub fn push(&mut self, key: Record, record: Record) -> anyhow::Result<()> {
+        if self.runs.is_some() {
+            return Err(anyhow!("push the record to the sorter being merged"));
+        }
+
+        self.memory_bytes += key.capacity() + record.capacity();
+        self.memory.push((key, record));
+        if self.memory_bytes > self.memory_size {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// The number of the runs spilled to the disk
+    pub fn spilled_runs(&self) -> usize {
+        self.run_files.len()
+    }
+
+    fn sort_memory(&mut self) -> Vec<(Record, Record)> {
+        let mut records = std::mem::take(&mut self.memory);
+        self.memory_bytes = 0;
+        // the stable sort keeps the order of the records of the same key
+        records.sort_by(|(x, _), (y, _)| x.cmp(y));
+        records
+    }
+
+    fn spill(&mut self) -> anyhow::Result<()> {
+        let records = self.sort_memory();
+        let path = PathBuf::from(format!("{}-{}", self.path_prefix, self.run_files.len()));
+        let len = records.len();
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for (key, record) in records {
+            writer.write_all(encode(&Element::Record(key))?.as_ref())?;
+            writer.write_all(encode(&Element::Record(record))?.as_ref())?;
+        }
+        writer.flush()?;
+
+        debug!("spill the sorted run of {} records to {:?}", len, path);
+        self.run_files.push((path, len));
+        Ok(())
+    }
+
+    /// Pop the record with the minimum key, the key is popped with it
+    pub fn pop(&mut self) -> anyhow::Result<Option<(Record, Record)>> {
+        if self.runs.is_none() {
+            let mut runs = Vec::with_capacity(self.run_files.len() + 1);
+            for (path, remaining) in &self.run_files {
+                let reader = BufReader::new(File::open(path)?);
+                runs.push(SortedRun::File {
+                    reader,
+                    remaining: *remaining,
+                });
+            }
+            // the records in memory are pushed after the spilled ones
+            runs.push(SortedRun::Memory(self.sort_memory().into_iter()));
+
+            let mut heads = Vec::with_capacity(runs.len());
+            for mut run in runs {
+                let head = run.next()?;
+                heads.push((run, head));
+            }
+            self.runs = Some(heads);
+        }
+
+        let runs = self.runs.as_mut().unwrap();
+        // the first run of the minimum key is popped, so the pushed order of a key is kept
+        let mut min: Option<usize> = None;
+        for (index, (_run, head)) in runs.iter().enumerate() {
+            if let Some((key, _record)) = head {
+                let less = match min {
+                    Some(min) => key < &runs[min].1.as_ref().unwrap().0,
+                    None => true,
+                };
+                if less {
+                    min = Some(index);
+                }
+            }
+        }
+
+        match min {
+            Some(index) => {
+                let (run, head) = &mut runs[index];
+                let next = run.next()?;
+                Ok(std::mem::replace(head, next))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl Drop for ExternalSorter {
+    fn drop(&mut self) {
+        self.runs.take();
+        for (path, _len) in &self.run_files {
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("remove the sorted run {:?} error. {}", path, e);
+            }
+        }
+    }
+}
+
+/// The frame of the element: the length and the `Serde` bytes of the element, the channel key,
+/// then the length and the json of the `SpillExtras`
+fn encode(element: &Element) -> anyhow::Result<BytesMut> {
+    let (channel_key, extras) = match element {
+        Element::Record(record) => (
+            record.channel_key,
+            SpillExtras {
+                location_windows: record.location_windows.clone(),
+                trigger_window: record.trigger_window.clone(),
+                ..Default::default()
+            },
+        ),
+        Element::Watermark(watermark) => (
+            watermark.channel_key,
+            SpillExtras {
+                location_windows: watermark.location_windows.clone(),
+                drop_windows: watermark.drop_windows.clone(),
+                downstream: watermark.downstream,
+                ..Default::default()
+            },
+        ),
+        Element::StreamStatus(stream_status) => (stream_status.channel_key, SpillExtras::default()),
+        Element::Barrier(_barrier) => (ChannelKey::default(), SpillExtras::default()),
+    };
+    let extras = serde_json::to_vec(&extras)?;
+
+    let element = element.to_bytes();
+    let mut frame =
+        BytesMut::with_capacity(4 + element.len() + channel_key.capacity() + 4 + extras.len());
+    frame.put_u32(element.len() as u32);
+    frame.put_slice(element.as_ref());
+    channel_key.serialize(frame.borrow_mut());
+    frame.put_u32(extras.len() as u32);
+    frame.put_slice(extras.as_slice());
+    Ok(frame)
+}
+
+fn decode<R: Read>(reader: &mut R) -> anyhow::Result<Element> {
+    let mut element_bytes = read_bytes(reader)?;
+    let mut element = Element::deserialize(element_bytes.borrow_mut());
+
+    let mut channel_key_bytes = vec![0u8; ChannelKey::default().capacity()];
+    reader.read_exact(channel_key_bytes.as_mut_slice())?;
+    let channel_key =
+        ChannelKey::deserialize(BytesMut::from(channel_key_bytes.as_slice()).borrow_mut());
+    element.set_channel_key(channel_key);
+
+    let extras: SpillExtras = serde_json::from_slice(read_bytes(reader)?.as_ref())?;
+    match &mut element {
+        Element::Record(record) => {
+            record.location_windows = extras.location_windows;
+            record.trigger_window = extras.trigger_window;
+        }
+        Element::Watermark(watermark) => {
+            watermark.location_windows = extras.location_windows;
+            watermark.drop_windows = extras.drop_windows;
+            watermark.downstream = extras.downstream;
+        }
+        _ => {}
+    }
+    Ok(element)
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> anyhow::Result<BytesMut> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0u8; u32::from_be_bytes(len) as usize];
+    reader.read_exact(bytes.as_mut_slice())?;
+    Ok(BytesMut::from(bytes.as_slice()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::element::{Element, Record};
+    use crate::core::runtime::{ChannelKey, JobId, OperatorId, TaskId};
+    use crate::core::window::{TimeWindow, Window};
+    use crate::pub_sub::spill::{ExternalSorter, SpillBuffer};
+
+    #[test]
+    pub fn spill_buffer_test() {
+        let task_id = TaskId {
+            job_id: JobId(1),
+            task_number: 0,
+            num_tasks: 1,
+        };
+        let channel_key = ChannelKey {
+            source_task_id: TaskId {
+                job_id: JobId(0),
+                task_number: 1,
+                num_tasks: 2,
+            },
+            target_task_id: task_id,
+        };
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+
+        // only the first records are kept in memory
+        let mut buffer = SpillBuffer::new(&task_id, 40);
+        for timestamp in 0..10 {
+            let mut record = Record::new();
+            record.timestamp = timestamp;
+            record.channel_key = channel_key;
+            record.location_windows = Some(vec![window.clone()]);
+            buffer.push(Element::Record(record)).unwrap();
+        }
+        let mut stream_status = Element::new_stream_status(10, true);
+        stream_status.set_channel_key(channel_key);
+        buffer.push(stream_status).unwrap();
+        assert!(buffer.path.exists());
+
+        for timestamp in 0..10 {
+            let element = buffer.pop().unwrap().unwrap();
+            let record = element.as_record();
+            assert_eq!(record.timestamp, timestamp);
+            assert_eq!(record.channel_key, channel_key);
+            assert_eq!(record.location_windows, Some(vec![window.clone()]));
+        }
+        let element = buffer.pop().unwrap().unwrap();
+        assert!(element.as_stream_status().end);
+        assert_eq!(element.as_stream_status().channel_key, channel_key);
+        assert!(buffer.pop().unwrap().is_none());
+        assert!(buffer.push(Element::Record(Record::new())).is_err());
+
+        let path = buffer.path.clone();
+        drop(buffer);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    pub fn external_sorter_test() {
+        let task_id = TaskId {
+            job_id: JobId(1),
+            task_number: 0,
+            num_tasks: 1,
+        };
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+
+        // the records are spilled as the runs of 2 records
+        let mut sorter = ExternalSorter::new(&task_id, OperatorId(2), 60);
+        for (seq, key) in [3u8, 1, 2, 1, 3, 2, 1, 3, 2, 1].iter().enumerate() {
+            let mut record = Record::from_values(&[*key, seq as u8], seq as u64);
+            record.location_windows = Some(vec![window.clone()]);
+            sorter
+                .push(Record::from_values(&[*key], 0), record)
+                .unwrap();
+        }
+        assert!(sorter.spilled_runs() > 1);
+
+        let mut popped = Vec::new();
+        while let Some((key, record)) = sorter.pop().unwrap() {
+            assert_eq!(key.values.as_slice()[0], record.values.as_slice()[0]);
+            assert_eq!(record.location_windows, Some(vec![window.clone()]));
+            popped.push((record.values.as_slice()[0], record.timestamp));
+        }
+        // sorted by the key, and the records of a key are in the pushed order
+        assert_eq!(
+            popped,
+            vec![
+                (1, 1),
+                (1, 3),
+                (1, 6),
+                (1, 9),
+                (2, 2),
+                (2, 5),
+                (2, 8),
+                (3, 0),
+                (3, 4),
+                (3, 7)
+            ]
+        );
+        assert!(sorter.push(Record::new(), Record::new()).is_err());
+
+        let paths: Vec<_> = sorter.run_files.iter().map(|(x, _)| x.clone()).collect();
+        assert!(paths.iter().all(|x| x.exists()));
+        drop(sorter);
+        assert!(paths.iter().all(|x| !x.exists()));
+    }
+}
@@ -7,6 +7,7 @@ use std::time::Duration;
 use tokio::task::JoinHandle;
 
 use crate::core::env::StreamApp;
+use crate::core::properties::SystemProperties;
 use crate::core::runtime::{ClusterDescriptor, ManagerStatus, WorkerManagerDescriptor};
 use crate::dag::metadata::DagMetadata;
 use crate::pub_sub::network;
@@ -14,6 +15,8 @@ use crate::runtime::context::Context;
 use crate::runtime::timer::{start_window_timer, WindowTimer};
 use crate::runtime::worker::checkpoint::CheckpointPublish;
 use crate::runtime::worker::heart_beat::HeartbeatPublish;
+#[cfg(feature = "queryable-state")]
+use crate::runtime::worker::queryable_state::queryable_state_launch;
 use crate::runtime::worker::web_server::web_launch;
 use crate::runtime::worker::WorkerTaskContext;
 use crate::runtime::{worker, HeartBeatStatus, HeartbeatItem};
@@ -34,6 +37,8 @@ where
     let web_address = web_serve(context.clone()).await;
     info!("serve worker web ui {}", web_address);
 
+    let queryable_state_address = queryable_state_serve(&cluster_descriptor, context.deref()).await;
+
     let checkpoint_publish = start_checkpoint_task(&cluster_descriptor).await;
     info!("start checkpoint timer");
 
@@ -45,6 +50,7 @@ where
         context.deref(),
         server_addr,
         web_address.as_str(),
+        queryable_state_address.as_str(),
     )
     .await;
     info!("start heartbeat timer and register worker to coordinator");
@@ -112,11 +118,50 @@ async fn web_serve(context: Arc<Context>) -> String {
     address
 }
 
+#[cfg(feature = "queryable-state")]
+async fn queryable_state_serve(
+    cluster_descriptor: &ClusterDescriptor,
+    context: &Context,
+) -> String {
+    let application_properties = &cluster_descriptor
+        .coordinator_manager
+        .application_properties;
+    if !application_properties
+        .get_queryable_state()
+        .unwrap_or(false)
+    {
+        return "".to_string();
+    }
+
+    let max_parallelism = application_properties.get_max_parallelism();
+    let address = queryable_state_launch(context.bind_ip.to_string(), max_parallelism).await;
+    info!("serve queryable state {}", address);
+    address
+}
+
+#[cfg(not(feature = "queryable-state"))]
+async fn queryable_state_serve(
+    cluster_descriptor: &ClusterDescriptor,
+    _context: &Context,
+) -> String {
+    let application_properties = &cluster_descriptor
+        .coordinator_manager
+        .application_properties;
+    if application_properties
+        .get_queryable_state()
+        .unwrap_or(false)
+    {
+        warn!("the queryable state is ignored without the `queryable-state` feature");
+    }
+    "".to_string()
+}
+
 async fn start_heartbeat_task(
     cluster_descriptor: &ClusterDescriptor,
     context: &Context,
     bind_addr: SocketAddr,
     web_addr: &str,
+    queryable_state_addr: &str,
 ) -> Arc<HeartbeatPublish> {
     let coordinator_address = cluster_descriptor.coordinator_manager.web_address.clone();
     let task_manager_id = context.task_manager_id.clone();
@@ -127,6 +172,7 @@ async fn start_heartbeat_task(
     let status = HeartbeatItem::WorkerAddrs {
         address: bind_addr.to_string(),
         web_address: web_addr.to_string(),
+        queryable_state_address: queryable_state_addr.to_string(),
     };
     heartbeat_publish.report(status).await;
 
@@ -66,6 +66,8 @@ pub(crate) struct Context {
 
     /// on k8s args
     pub image_path: String,
+    /// the path of the worker pod template, empty if absent
+    pub worker_pod_template: String,
 }
 
 impl Context {
@@ -85,6 +87,7 @@ impl Context {
         v_cores: u32,
         exclusion_nodes: String,
         image_path: String,
+        worker_pod_template: String,
     ) -> Self {
         Context {
             application_id,
@@ -102,6 +105,7 @@ impl Context {
             v_cores,
             exclusion_nodes,
             image_path,
+            worker_pod_template,
         }
     }
 
@@ -249,6 +253,14 @@ impl Context {
             _ => String::new(),
         };
 
+        let worker_pod_template = match cluster_mode {
+            ClusterMode::Kubernetes => match manager_type {
+                ManagerType::Coordinator => parse_arg("worker_pod_template").unwrap_or_default(),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+
         Ok(Context::new(
             application_id,
             task_manager_id,
@@ -265,6 +277,7 @@ impl Context {
             v_cores,
             exclusion_nodes,
             image_path,
+            worker_pod_template,
         ))
     }
 }
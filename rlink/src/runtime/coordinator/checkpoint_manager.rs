@@ -1,16 +1,24 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, RwLock};
 
 use crate::channel::{bounded, Receiver, Sender};
-use crate::core::checkpoint::Checkpoint;
+use crate::core::backend::CheckpointBackend;
+use crate::core::checkpoint::{Checkpoint, CheckpointConfig};
 use crate::core::properties::SystemProperties;
 use crate::core::runtime::{CheckpointId, ClusterDescriptor, JobId, OperatorId};
 use crate::dag::metadata::DagMetadata;
 use crate::runtime::context::Context;
+use crate::runtime::coordinator::checkpoint_stats::{
+    CheckpointStatsTracker, OperatorCheckpointStats,
+};
 use crate::storage::checkpoint::{CheckpointEntity, CheckpointStorage, TCheckpointStorage};
+use crate::storage::savepoint::{
+    read_savepoint, savepoint_url, write_savepoint, SavepointMetadata, SavepointOperator,
+};
+use crate::utils::date_time::current_timestamp_millis;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct OperatorCheckpoint {
@@ -18,6 +26,8 @@ pub(crate) struct OperatorCheckpoint {
     operator_id: OperatorId,
     operator_name: String,
     parallelism: u16,
+    /// the key of the operator in the savepoints, see `StreamNode::state_key`
+    state_key: String,
 
     /// Map<task_num, Checkpoint>
     current_cks: HashMap<u16, Checkpoint>,
@@ -29,12 +39,14 @@ impl OperatorCheckpoint {
         operator_id: OperatorId,
         operator_name: String,
         parallelism: u16,
+        state_key: String,
     ) -> Self {
         OperatorCheckpoint {
             job_id,
             operator_id,
             operator_name,
             parallelism,
+            state_key,
             current_cks: HashMap::with_capacity(parallelism as usize),
         }
     }
@@ -65,23 +77,71 @@ impl Clone for OperatorCheckpoint {
             operator_id: self.operator_id,
             operator_name: self.operator_name.clone(),
             parallelism: self.parallelism,
+            state_key: self.state_key.clone(),
             current_cks: self.current_cks.clone(),
         }
     }
 }
 
+/// A savepoint waiting for the next aligned checkpoint, or for the job drained if `on_stop`
+struct SavepointRequest {
+    url: String,
+    options: HashMap<String, String>,
+    on_stop: bool,
+    sender: oneshot::Sender<anyhow::Result<String>>,
+}
+
+/// A checkpoint reported by some of the tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct PendingCheckpoint {
+    /// the time in millis the checkpoint is triggered
+    trigger_timestamp: u64,
+    operator_cks: HashMap<OperatorId, OperatorCheckpoint>,
+}
+
+impl PendingCheckpoint {
+    fn unreached_operators(&self) -> Vec<&OperatorCheckpoint> {
+        self.operator_cks
+            .values()
+            .filter(|operator_checkpoint| !operator_checkpoint.is_align())
+            .collect()
+    }
+
+    #[inline]
+    fn is_align(&self) -> bool {
+        self.unreached_operators().is_empty()
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct CheckpointAlignManager {
     application_name: String,
     application_id: String,
     checkpoint_ttl: Duration,
+    config: CheckpointConfig,
 
-    current_ck_id: CheckpointId,
+    /// all operators of the application without any task reported
     operator_cks: HashMap<OperatorId, OperatorCheckpoint>,
+    /// the checkpoints in progress, at most `CheckpointConfig::max_concurrent`
+    pending_cks: BTreeMap<CheckpointId, PendingCheckpoint>,
+    /// the aborted or declined checkpoints after the latest completed one, the sources asking
+    /// for them are declined. They are removed after the timeout, when no source asks anymore
+    declined_ck_ids: BTreeSet<CheckpointId>,
+    /// the aborted checkpoints since the latest completed one
+    consecutive_failures: usize,
+    completed_ck_id: CheckpointId,
+    /// the time in millis the latest checkpoint completed
+    completed_timestamp: u64,
     finish_operator_cks: HashMap<OperatorId, OperatorCheckpoint>,
+    stats: CheckpointStatsTracker,
 
     #[serde(skip_serializing, skip_deserializing)]
     storage: Option<CheckpointStorage>,
+    /// the object store options of the savepoints if not specified, same as the checkpoint
+    #[serde(skip_serializing, skip_deserializing)]
+    savepoint_options: HashMap<String, String>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pending_savepoints: Vec<SavepointRequest>,
 }
 
 impl CheckpointAlignManager {
@@ -91,17 +151,27 @@ impl CheckpointAlignManager {
         cluster_descriptor: &ClusterDescriptor,
         checkpoint_ttl: Duration,
     ) -> Self {
-        let checkpoint_backend = cluster_descriptor
+        let application_properties = &cluster_descriptor
             .coordinator_manager
-            .application_properties
+            .application_properties;
+        let checkpoint_backend = application_properties
             .get_checkpoint()
             .map(|x| Some(x))
             .unwrap_or(None);
         let storage = checkpoint_backend
             .as_ref()
             .map(|ck_backend| CheckpointStorage::new(ck_backend));
+        let savepoint_options = match checkpoint_backend {
+            Some(CheckpointBackend::ObjectStore { options, .. }) => options,
+            _ => HashMap::new(),
+        };
+        let config = application_properties
+            .get_checkpoint_config()
+            .unwrap_or_default();
+        info!("checkpoint config: {:?}", config);
 
         let mut operator_cks = HashMap::new();
+        let mut operator_stats = Vec::new();
         for node in dag_manager.job_graph().nodes() {
             let job_node = node.deref();
             let parallelism = job_node.parallelism;
@@ -110,58 +180,71 @@ impl CheckpointAlignManager {
             for stream_node in &job_node.stream_nodes {
                 let operator_id = stream_node.id;
                 let operator_name = stream_node.operator_name.clone();
-
-                let operator_ck =
-                    OperatorCheckpoint::new(job_id, operator_id, operator_name, parallelism);
+                operator_stats.push(OperatorCheckpointStats::new(
+                    operator_id,
+                    operator_name.clone(),
+                    parallelism,
+                ));
+
+                let operator_ck = OperatorCheckpoint::new(
+                    job_id,
+                    operator_id,
+                    operator_name,
+                    parallelism,
+                    stream_node.state_key(),
+                );
 
                 operator_cks.insert(operator_id, operator_ck);
             }
         }
 
         CheckpointAlignManager {
-            application_name: cluster_descriptor
-                .coordinator_manager
-                .application_properties
-                .get_application_name()
-                .clone(),
+            application_name: application_properties.get_application_name().clone(),
             application_id: context.application_id.clone(),
             checkpoint_ttl,
-            current_ck_id: CheckpointId::default(),
+            config,
             operator_cks,
+            pending_cks: BTreeMap::new(),
+            declined_ck_ids: BTreeSet::new(),
+            consecutive_failures: 0,
+            completed_ck_id: CheckpointId::default(),
+            completed_timestamp: 0,
             finish_operator_cks: HashMap::new(),
+            stats: CheckpointStatsTracker::new(operator_stats),
             storage,
+            savepoint_options,
+            pending_savepoints: Vec::new(),
         }
     }
 
     pub async fn apply(&mut self, ck: Checkpoint) -> anyhow::Result<()> {
         let checkpoint_id = ck.checkpoint_id;
-        if self.current_ck_id.0 > checkpoint_id.0 {
+        if checkpoint_id.0 <= self.completed_ck_id.0
+            || self.declined_ck_ids.contains(&checkpoint_id)
+        {
             warn!(
-                "checkpoint_id={:?} late. current checkpoint_id={:?}, operator={:?}, task_id={:?}",
-                ck.checkpoint_id, self.current_ck_id, ck.operator_id, ck.task_id,
+                "checkpoint_id={:?} late. completed checkpoint_id={:?}, operator={:?}, task_id={:?}",
+                ck.checkpoint_id, self.completed_ck_id, ck.operator_id, ck.task_id,
             );
 
             return Ok(());
-        } else if self.current_ck_id.0 < checkpoint_id.0 {
-            if !self.current_ck_id.is_default() {
-                let unreached_operators = self.unreached_operators();
-                if unreached_operators.len() > 0 {
-                    warn!(
-                        "the new checkpoint reached, found un-align checkpoint_id={:?}",
-                        self.current_ck_id,
-                    );
-                    debug!(
-                        "un-align operators: {}",
-                        serde_json::to_string(&unreached_operators).unwrap()
-                    );
-                }
-            }
-
-            self.next_checkpoint(checkpoint_id);
         }
 
-        match self.operator_cks.get_mut(&ck.operator_id) {
+        self.abort_timeout_checkpoints();
+
+        let pending_checkpoint = match self.pending_cks.get_mut(&checkpoint_id) {
+            Some(pending_checkpoint) => pending_checkpoint,
+            None => {
+                warn!(
+                    "checkpoint_id={:?} isn't triggered, operator={:?}, task_id={:?}",
+                    ck.checkpoint_id, ck.operator_id, ck.task_id,
+                );
+                return Ok(());
+            }
+        };
+        match pending_checkpoint.operator_cks.get_mut(&ck.operator_id) {
             Some(operator_checkpoint) => {
+                self.stats.report_ack(&ck);
                 operator_checkpoint.apply(ck);
             }
             None => {
@@ -169,14 +252,19 @@ impl CheckpointAlignManager {
             }
         }
 
-        if self.is_align() {
-            let complete_checkpoint_id = self.current_ck_id;
-            let complete_operator_cks = self.operator_cks.clone();
+        if pending_checkpoint.is_align() {
+            let complete_checkpoint_id = checkpoint_id;
+            let complete_operator_cks = self
+                .pending_cks
+                .remove(&complete_checkpoint_id)
+                .unwrap()
+                .operator_cks;
 
             debug!(
                 "complete checkpoint_id={:?}, checkpoints: {:?}",
                 complete_checkpoint_id, complete_operator_cks
             );
+            self.complete_checkpoint(complete_checkpoint_id);
             self.finish_operator_cks = complete_operator_cks;
 
             match self.storage.as_mut() {
@@ -202,43 +290,272 @@ impl CheckpointAlignManager {
                 }
                 None => {}
             }
+
+            self.complete_savepoints().await;
         }
 
         Ok(())
     }
 
-    fn unreached_operators(&self) -> Vec<&OperatorCheckpoint> {
-        let align_operators: Vec<&OperatorCheckpoint> = self
-            .operator_cks
+    /// Decide whether the barrier of the checkpoint is injected, it's asked by all sources and
+    /// the first request starts the checkpoint, so the later ones get the same answer. The
+    /// checkpoint is declined in the min pause after the latest completed checkpoint or when
+    /// the `max_concurrent` checkpoints are in progress
+    fn trigger_checkpoint(&mut self, checkpoint_id: CheckpointId) -> bool {
+        if self.pending_cks.contains_key(&checkpoint_id) {
+            return true;
+        }
+        if checkpoint_id <= self.completed_ck_id || self.declined_ck_ids.contains(&checkpoint_id) {
+            return false;
+        }
+
+        self.abort_timeout_checkpoints();
+
+        let current_timestamp = current_timestamp_millis();
+        let timeout = self.config.timeout.as_millis() as u64;
+        let min_pause = self.config.min_pause.as_millis() as u64;
+        let declined_reason = if checkpoint_id.0 + timeout < current_timestamp {
+            Some("triggered after the timeout".to_string())
+        } else if !self.completed_ck_id.is_default()
+            && current_timestamp < self.completed_timestamp + min_pause
+        {
+            Some(format!(
+                "in the min pause {}ms after checkpoint_id={:?}",
+                min_pause, self.completed_ck_id
+            ))
+        } else if self.pending_cks.len() >= self.config.max_concurrent {
+            Some(format!(
+                "{} checkpoints in progress",
+                self.pending_cks.len()
+            ))
+        } else {
+            None
+        };
+
+        if let Some(reason) = declined_reason {
+            info!("checkpoint_id={:?} declined, {}", checkpoint_id, reason);
+            self.declined_ck_ids.insert(checkpoint_id);
+            self.stats.report_failed(checkpoint_id, reason.as_str());
+            return false;
+        }
+
+        let pending_checkpoint = PendingCheckpoint {
+            trigger_timestamp: current_timestamp,
+            operator_cks: self.operator_cks.clone(),
+        };
+        self.pending_cks.insert(checkpoint_id, pending_checkpoint);
+        self.stats.report_pending(checkpoint_id);
+        true
+    }
+
+    /// The older pending checkpoints are subsumed by the completed one
+    fn complete_checkpoint(&mut self, checkpoint_id: CheckpointId) {
+        let pending_cks = self.pending_cks.split_off(&checkpoint_id);
+        for subsumed_ck_id in self.pending_cks.keys() {
+            debug!("checkpoint_id={:?} subsumed", subsumed_ck_id);
+            self.stats.report_failed(*subsumed_ck_id, "subsumed");
+        }
+        self.pending_cks = pending_cks;
+        self.declined_ck_ids = self.declined_ck_ids.split_off(&checkpoint_id);
+
+        self.completed_ck_id = checkpoint_id;
+        self.completed_timestamp = current_timestamp_millis();
+        self.consecutive_failures = 0;
+        self.stats.report_completed(checkpoint_id);
+    }
+
+    fn abort_checkpoint(&mut self, checkpoint_id: CheckpointId, reason: &str) {
+        if let Some(pending_checkpoint) = self.pending_cks.remove(&checkpoint_id) {
+            self.declined_ck_ids.insert(checkpoint_id);
+            self.consecutive_failures += 1;
+            self.stats.report_failed(checkpoint_id, reason);
+
+            warn!(
+                "checkpoint_id={:?} aborted, {}. consecutive failures: {}",
+                checkpoint_id, reason, self.consecutive_failures
+            );
+            debug!(
+                "un-align operators: {}",
+                serde_json::to_string(&pending_checkpoint.unreached_operators()).unwrap()
+            );
+        }
+    }
+
+    fn abort_timeout_checkpoints(&mut self) {
+        let timeout = self.config.timeout.as_millis() as u64;
+        let current_timestamp = current_timestamp_millis();
+        let timeout_ck_ids: Vec<CheckpointId> = self
+            .pending_cks
             .iter()
-            .filter(|(_, operator_checkpoint)| !operator_checkpoint.is_align())
-            .map(|(_, operator_checkpoint)| operator_checkpoint)
+            .filter(|(_, v)| v.trigger_timestamp + timeout < current_timestamp)
+            .map(|(checkpoint_id, _)| *checkpoint_id)
             .collect();
-        align_operators
+        for checkpoint_id in timeout_ck_ids {
+            self.abort_checkpoint(checkpoint_id, "timeout");
+        }
+
+        // a checkpoint isn't triggered after the timeout, see `trigger_checkpoint`
+        let expired_ck_id = CheckpointId(current_timestamp.saturating_sub(timeout));
+        self.declined_ck_ids = self.declined_ck_ids.split_off(&expired_ck_id);
     }
 
-    #[inline]
-    fn is_align(&self) -> bool {
-        self.unreached_operators().len() == 0
-    }
-
-    fn next_checkpoint(&mut self, checkpoint_id: CheckpointId) {
-        self.current_ck_id = checkpoint_id;
-        self.operator_cks = {
-            let mut operator_cks = HashMap::new();
-            for (operator_id, operator_checkpoint) in &self.operator_cks {
-                operator_cks.insert(
-                    operator_id.clone(),
-                    OperatorCheckpoint::new(
-                        operator_checkpoint.job_id.clone(),
-                        operator_checkpoint.operator_id.clone(),
-                        operator_checkpoint.operator_name.clone(),
-                        operator_checkpoint.parallelism,
-                    ),
-                );
+    /// Abort the timeout checkpoints, return true if the consecutive failures exceed the
+    /// tolerable failures. The in progress checkpoints are discarded then, as the job is
+    /// going to restart
+    fn check_timeout(&mut self) -> bool {
+        self.abort_timeout_checkpoints();
+
+        let tolerable_failures = match self.config.tolerable_failures {
+            Some(tolerable_failures) => tolerable_failures,
+            None => return false,
+        };
+        if self.consecutive_failures <= tolerable_failures {
+            return false;
+        }
+
+        error!(
+            "consecutive failed checkpoints {} exceed the tolerable failures {}",
+            self.consecutive_failures, tolerable_failures
+        );
+        let pending_ck_ids: Vec<CheckpointId> = self.pending_cks.keys().cloned().collect();
+        for checkpoint_id in &pending_ck_ids {
+            self.stats
+                .report_failed(*checkpoint_id, "discarded by the job restart");
+        }
+        self.declined_ck_ids.extend(pending_ck_ids);
+        self.pending_cks.clear();
+        self.consecutive_failures = 0;
+        true
+    }
+
+    /// Write the savepoints requested before the checkpoint completed
+    async fn complete_savepoints(&mut self) {
+        let (savepoints, pending_savepoints): (Vec<_>, Vec<_>) = self
+            .pending_savepoints
+            .drain(..)
+            .partition(|savepoint| !savepoint.on_stop);
+        self.pending_savepoints = pending_savepoints;
+        self.write_savepoints(savepoints).await;
+    }
+
+    /// Write the stop savepoints with the checkpoint of the drained state, all tasks have been
+    /// drained and checkpointed before terminated. The savepoints fail if the checkpoint isn't
+    /// completed, e.g. it's the `drain_checkpoint_id` reported by the tasks
+    async fn complete_stop_savepoints(&mut self, drain_checkpoint_id: Option<CheckpointId>) {
+        let (savepoints, pending_savepoints): (Vec<_>, Vec<_>) = self
+            .pending_savepoints
+            .drain(..)
+            .partition(|savepoint| savepoint.on_stop);
+        self.pending_savepoints = pending_savepoints;
+
+        let err = match drain_checkpoint_id {
+            _ if self.finish_operator_cks.is_empty() => {
+                Some("no checkpoint completed before the job stopped".to_string())
+            }
+            Some(drain_checkpoint_id) if self.completed_ck_id < drain_checkpoint_id => {
+                Some(format!(
+                    "the drained checkpoint_id={:?} isn't completed",
+                    drain_checkpoint_id
+                ))
             }
-            operator_cks
+            _ => None,
+        };
+        if let Some(err) = err {
+            for savepoint in savepoints {
+                savepoint.sender.send(Err(anyhow!("{}", err))).ok();
+            }
+            return;
         }
+        self.write_savepoints(savepoints).await;
+    }
+
+    /// Whether the checkpoint is completed, or it's aborted or subsumed
+    fn is_finished(&self, checkpoint_id: CheckpointId) -> bool {
+        self.completed_ck_id >= checkpoint_id || self.declined_ck_ids.contains(&checkpoint_id)
+    }
+
+    async fn write_savepoints(&self, savepoints: Vec<SavepointRequest>) {
+        if savepoints.is_empty() {
+            return;
+        }
+
+        let checkpoint_id = self
+            .finish_operator_cks
+            .values()
+            .flat_map(|v| v.current_cks.values())
+            .map(|ck| ck.checkpoint_id)
+            .next()
+            .unwrap_or(self.completed_ck_id);
+        let metadata = SavepointMetadata {
+            application_name: self.application_name.clone(),
+            application_id: self.application_id.clone(),
+            checkpoint_id,
+            timestamp: current_timestamp_millis(),
+            operators: self
+                .finish_operator_cks
+                .values()
+                .map(|v| SavepointOperator {
+                    state_key: v.state_key.clone(),
+                    operator_name: v.operator_name.clone(),
+                    parallelism: v.parallelism,
+                    checkpoints: v.current_cks.values().cloned().collect(),
+                })
+                .collect(),
+        };
+
+        for savepoint in savepoints {
+            let url = savepoint_url(savepoint.url.as_str(), metadata.checkpoint_id);
+            let result = write_savepoint(url.as_str(), &savepoint.options, &metadata)
+                .await
+                .map(|_| url);
+            match &result {
+                Ok(url) => info!("savepoint {} completed", url),
+                Err(e) => error!("savepoint {} error. {}", savepoint.url, e),
+            }
+            savepoint.sender.send(result).ok();
+        }
+    }
+
+    fn add_savepoint(
+        &mut self,
+        url: String,
+        options: HashMap<String, String>,
+        on_stop: bool,
+    ) -> oneshot::Receiver<anyhow::Result<String>> {
+        let options = if options.is_empty() {
+            self.savepoint_options.clone()
+        } else {
+            options
+        };
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending_savepoints.push(SavepointRequest {
+            url,
+            options,
+            on_stop,
+            sender,
+        });
+        receiver
+    }
+
+    /// Load the checkpoints of the operators from the savepoint, the operators are matched by
+    /// the `uid` so the application can be modified since the savepoint is taken
+    pub async fn load_savepoint(
+        &self,
+        url: &str,
+    ) -> anyhow::Result<HashMap<OperatorId, Vec<Checkpoint>>> {
+        let metadata = read_savepoint(url, &self.savepoint_options).await?;
+        info!(
+            "restore from the savepoint {} of the application {}, checkpoint_id={:?}",
+            url, metadata.application_id, metadata.checkpoint_id
+        );
+
+        let state_keys = self
+            .operator_cks
+            .iter()
+            .map(|(operator_id, v)| (*operator_id, (v.state_key.clone(), v.parallelism)))
+            .collect();
+        Ok(metadata.operator_checkpoints(&state_keys))
     }
 
     pub async fn load(&mut self) -> anyhow::Result<HashMap<OperatorId, Vec<Checkpoint>>> {
@@ -289,10 +606,18 @@ impl Clone for CheckpointAlignManager {
             application_name: self.application_name.clone(),
             application_id: self.application_id.to_string(),
             checkpoint_ttl: self.checkpoint_ttl,
-            current_ck_id: CheckpointId::default(),
+            config: self.config.clone(),
             operator_cks: self.operator_cks.clone(),
+            pending_cks: self.pending_cks.clone(),
+            declined_ck_ids: self.declined_ck_ids.clone(),
+            consecutive_failures: self.consecutive_failures,
+            completed_ck_id: self.completed_ck_id,
+            completed_timestamp: self.completed_timestamp,
             finish_operator_cks: self.finish_operator_cks.clone(),
+            stats: self.stats.clone(),
             storage: None,
+            savepoint_options: self.savepoint_options.clone(),
+            pending_savepoints: Vec::new(),
         }
     }
 }
@@ -354,8 +679,207 @@ impl CheckpointManager {
         ck_align_manager.deref().clone()
     }
 
+    /// Whether the sources inject the barrier of the checkpoint, see
+    /// `CheckpointAlignManager::trigger_checkpoint`
+    pub async fn trigger_checkpoint(&self, checkpoint_id: CheckpointId) -> bool {
+        let mut ck_align_manager = self.ck_align_manager_task.write().await;
+        ck_align_manager.trigger_checkpoint(checkpoint_id)
+    }
+
+    /// The statistics of the latest checkpoints
+    pub async fn checkpoint_stats(&self) -> CheckpointStatsTracker {
+        let ck_align_manager = self.ck_align_manager_task.read().await;
+        ck_align_manager.stats.clone()
+    }
+
+    /// The latest completed checkpoint, `None` if no checkpoint is completed
+    pub async fn completed_checkpoint_id(&self) -> Option<CheckpointId> {
+        let ck_align_manager = self.ck_align_manager_task.read().await;
+        if ck_align_manager.completed_ck_id.is_default() {
+            None
+        } else {
+            Some(ck_align_manager.completed_ck_id)
+        }
+    }
+
     pub async fn load(&mut self) -> anyhow::Result<HashMap<OperatorId, Vec<Checkpoint>>> {
         let mut ck_align_manager = self.ck_align_manager_task.write().await;
         ck_align_manager.load().await
     }
+
+    pub async fn load_savepoint(
+        &self,
+        url: &str,
+    ) -> anyhow::Result<HashMap<OperatorId, Vec<Checkpoint>>> {
+        let ck_align_manager = self.ck_align_manager_task.read().await;
+        ck_align_manager.load_savepoint(url).await
+    }
+
+    /// Take a savepoint to the `url` with the next completed checkpoint, the receiver gets
+    /// the savepoint directory
+    pub async fn trigger_savepoint(
+        &self,
+        url: String,
+        options: HashMap<String, String>,
+    ) -> oneshot::Receiver<anyhow::Result<String>> {
+        let mut ck_align_manager = self.ck_align_manager_task.write().await;
+        ck_align_manager.add_savepoint(url, options, false)
+    }
+
+    /// Take a savepoint to the `url` when the job is drained and all tasks terminated
+    pub async fn trigger_stop_savepoint(
+        &self,
+        url: String,
+        options: HashMap<String, String>,
+    ) -> oneshot::Receiver<anyhow::Result<String>> {
+        let mut ck_align_manager = self.ck_align_manager_task.write().await;
+        ck_align_manager.add_savepoint(url, options, true)
+    }
+
+    /// Abort the timeout checkpoints, return true if the job should be restarted
+    pub async fn check_timeout(&self) -> bool {
+        let mut ck_align_manager = self.ck_align_manager_task.write().await;
+        ck_align_manager.check_timeout()
+    }
+
+    /// Write the stop savepoints once the `drain_checkpoint_id` is completed, the checkpoint
+    /// reports of the terminated tasks may be still in flight. It's given up after the
+    /// checkpoint timeout
+    pub async fn complete_stop_savepoints(&self, drain_checkpoint_id: Option<CheckpointId>) {
+        if let Some(drain_checkpoint_id) = drain_checkpoint_id {
+            let timeout = self.ck_align_manager_task.read().await.config.timeout;
+            let deadline = tokio::time::Instant::now() + timeout;
+            loop {
+                let is_finished = {
+                    let mut ck_align_manager = self.ck_align_manager_task.write().await;
+                    ck_align_manager.abort_timeout_checkpoints();
+                    ck_align_manager.is_finished(drain_checkpoint_id)
+                };
+                if is_finished || tokio::time::Instant::now() >= deadline {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+
+        let mut ck_align_manager = self.ck_align_manager_task.write().await;
+        ck_align_manager
+            .complete_stop_savepoints(drain_checkpoint_id)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+    use std::time::Duration;
+
+    use crate::core::checkpoint::{Checkpoint, CheckpointConfig, CheckpointHandle};
+    use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+    use crate::runtime::coordinator::checkpoint_manager::{
+        CheckpointAlignManager, OperatorCheckpoint,
+    };
+    use crate::runtime::coordinator::checkpoint_stats::CheckpointStatsTracker;
+    use crate::storage::savepoint::read_savepoint;
+
+    fn ck_align_manager() -> CheckpointAlignManager {
+        CheckpointAlignManager {
+            application_name: "app".to_string(),
+            application_id: "app-1".to_string(),
+            checkpoint_ttl: Duration::from_secs(60),
+            config: CheckpointConfig::default(),
+            operator_cks: HashMap::new(),
+            pending_cks: BTreeMap::new(),
+            declined_ck_ids: BTreeSet::new(),
+            consecutive_failures: 0,
+            completed_ck_id: CheckpointId::default(),
+            completed_timestamp: 0,
+            finish_operator_cks: HashMap::new(),
+            stats: CheckpointStatsTracker::new(vec![]),
+            storage: None,
+            savepoint_options: HashMap::new(),
+            pending_savepoints: vec![],
+        }
+    }
+
+    fn operator_checkpoint(checkpoint_id: CheckpointId) -> OperatorCheckpoint {
+        let mut operator_checkpoint = OperatorCheckpoint::new(
+            JobId(0),
+            OperatorId(0),
+            "source".to_string(),
+            1,
+            "source".to_string(),
+        );
+        operator_checkpoint.apply(Checkpoint {
+            operator_id: OperatorId(0),
+            task_id: TaskId {
+                job_id: JobId(0),
+                task_number: 0,
+                num_tasks: 1,
+            },
+            checkpoint_id,
+            completed_checkpoint_id: None,
+            alignment_duration: 0,
+            handle: CheckpointHandle {
+                handle: "offset-10".to_string(),
+            },
+        });
+        operator_checkpoint
+    }
+
+    #[tokio::test]
+    pub async fn stop_savepoint_test() {
+        let root =
+            std::env::temp_dir().join(format!("rlink-stop-savepoint-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let url = format!("file://{}", root.to_string_lossy());
+
+        let mut manager = ck_align_manager();
+
+        // no checkpoint completed, the stop savepoint fails and the other one keeps pending
+        let stop_receiver = manager.add_savepoint(url.clone(), HashMap::new(), true);
+        manager.add_savepoint(url.clone(), HashMap::new(), false);
+        manager.complete_stop_savepoints(None).await;
+        let err = stop_receiver.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("no checkpoint completed"));
+        assert_eq!(manager.pending_savepoints.len(), 1);
+        assert!(!manager.pending_savepoints[0].on_stop);
+
+        manager
+            .finish_operator_cks
+            .insert(OperatorId(0), operator_checkpoint(CheckpointId(1)));
+        manager.completed_ck_id = CheckpointId(1);
+
+        // the drained checkpoint isn't reported by the terminated tasks
+        assert!(!manager.is_finished(CheckpointId(2)));
+        let stop_receiver = manager.add_savepoint(url.clone(), HashMap::new(), true);
+        manager
+            .complete_stop_savepoints(Some(CheckpointId(2)))
+            .await;
+        let err = stop_receiver.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("isn't completed"));
+
+        // the drained checkpoint is completed
+        assert!(manager.is_finished(CheckpointId(1)));
+        let stop_receiver = manager.add_savepoint(url.clone(), HashMap::new(), true);
+        manager
+            .complete_stop_savepoints(Some(CheckpointId(1)))
+            .await;
+        let savepoint_url = stop_receiver.await.unwrap().unwrap();
+        assert!(savepoint_url.ends_with("savepoint-1"));
+        assert_eq!(manager.pending_savepoints.len(), 1);
+
+        let metadata = read_savepoint(savepoint_url.as_str(), &HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(metadata.checkpoint_id, CheckpointId(1));
+        assert_eq!(metadata.operators.len(), 1);
+        assert_eq!(metadata.operators[0].state_key, "source");
+        assert_eq!(
+            metadata.operators[0].checkpoints[0].handle.handle,
+            "offset-10"
+        );
+
+        std::fs::remove_dir_all(root).ok();
+    }
 }
@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+
+use crate::core::checkpoint::Checkpoint;
+use crate::core::runtime::{CheckpointId, OperatorId};
+use crate::utils::date_time::current_timestamp_millis;
+
+/// the number of the latest checkpoints kept in the history
+pub(crate) const CHECKPOINT_STATS_HISTORY_SIZE: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckpointStatus {
+    InProgress,
+    Completed,
+    Failed,
+}
+
+/// The acknowledgements of the tasks of an operator
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OperatorCheckpointStats {
+    pub operator_id: OperatorId,
+    pub operator_name: String,
+    pub parallelism: u16,
+    /// the number of the tasks acknowledged
+    pub acknowledged: u16,
+    /// the max duration in millis from the trigger to the acknowledgement of the tasks
+    pub ack_latency: u64,
+    /// the total bytes of the checkpoint handles of the tasks
+    pub state_size: u64,
+    /// the max barrier alignment duration in millis of the tasks
+    pub alignment_duration: u64,
+}
+
+impl OperatorCheckpointStats {
+    pub fn new(operator_id: OperatorId, operator_name: String, parallelism: u16) -> Self {
+        OperatorCheckpointStats {
+            operator_id,
+            operator_name,
+            parallelism,
+            acknowledged: 0,
+            ack_latency: 0,
+            state_size: 0,
+            alignment_duration: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointStats {
+    pub checkpoint_id: CheckpointId,
+    pub status: CheckpointStatus,
+    /// the time in millis the barrier injected by the sources
+    pub trigger_timestamp: u64,
+    /// the time in millis the latest task acknowledged
+    pub latest_ack_timestamp: u64,
+    /// the time in millis the checkpoint completed or failed
+    pub end_timestamp: Option<u64>,
+    pub state_size: u64,
+    pub alignment_duration: u64,
+    pub failure_reason: Option<String>,
+    pub operators: Vec<OperatorCheckpointStats>,
+}
+
+impl CheckpointStats {
+    fn new(checkpoint_id: CheckpointId, operators: Vec<OperatorCheckpointStats>) -> Self {
+        CheckpointStats {
+            checkpoint_id,
+            status: CheckpointStatus::InProgress,
+            trigger_timestamp: checkpoint_id.0,
+            latest_ack_timestamp: 0,
+            end_timestamp: None,
+            state_size: 0,
+            alignment_duration: 0,
+            failure_reason: None,
+            operators,
+        }
+    }
+
+    /// The duration in millis from the trigger to the end, or to the latest acknowledgement
+    /// if it's in progress
+    pub fn duration(&self) -> u64 {
+        self.end_timestamp
+            .unwrap_or(self.latest_ack_timestamp)
+            .saturating_sub(self.trigger_timestamp)
+    }
+
+    fn ack(&mut self, ck: &Checkpoint) {
+        let timestamp = current_timestamp_millis();
+        let ack_latency = timestamp.saturating_sub(self.trigger_timestamp);
+        let state_size = ck.handle.handle.len() as u64;
+
+        self.latest_ack_timestamp = timestamp;
+        self.state_size += state_size;
+        self.alignment_duration = self.alignment_duration.max(ck.alignment_duration);
+
+        if let Some(operator) = self
+            .operators
+            .iter_mut()
+            .find(|x| x.operator_id == ck.operator_id)
+        {
+            operator.acknowledged += 1;
+            operator.ack_latency = operator.ack_latency.max(ack_latency);
+            operator.state_size += state_size;
+            operator.alignment_duration = operator.alignment_duration.max(ck.alignment_duration);
+        }
+    }
+
+    fn end(&mut self, status: CheckpointStatus, failure_reason: Option<String>) {
+        self.status = status;
+        self.end_timestamp = Some(current_timestamp_millis());
+        self.failure_reason = failure_reason;
+    }
+}
+
+/// The statistics of the checkpoints, the latest `CHECKPOINT_STATS_HISTORY_SIZE` of them are
+/// kept in the history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointStatsTracker {
+    #[serde(skip_serializing, skip_deserializing)]
+    operators: Vec<OperatorCheckpointStats>,
+
+    pub completed_count: u64,
+    pub failed_count: u64,
+    pub latest_completed: Option<CheckpointStats>,
+    pub latest_failed: Option<CheckpointStats>,
+    /// the latest checkpoints, the newest one is the last
+    pub history: VecDeque<CheckpointStats>,
+}
+
+impl CheckpointStatsTracker {
+    pub fn new(operators: Vec<OperatorCheckpointStats>) -> Self {
+        CheckpointStatsTracker {
+            operators,
+            completed_count: 0,
+            failed_count: 0,
+            latest_completed: None,
+            latest_failed: None,
+            history: VecDeque::with_capacity(CHECKPOINT_STATS_HISTORY_SIZE),
+        }
+    }
+
+    fn get_mut(&mut self, checkpoint_id: CheckpointId) -> Option<&mut CheckpointStats> {
+        self.history
+            .iter_mut()
+            .rev()
+            .find(|x| x.checkpoint_id == checkpoint_id)
+    }
+
+    fn push(&mut self, stats: CheckpointStats) {
+        if self.history.len() == CHECKPOINT_STATS_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(stats);
+    }
+
+    /// Start tracking the checkpoint reported the first time
+    pub fn report_pending(&mut self, checkpoint_id: CheckpointId) {
+        if self.get_mut(checkpoint_id).is_none() {
+            let stats = CheckpointStats::new(checkpoint_id, self.operators.clone());
+            self.push(stats);
+        }
+    }
+
+    pub fn report_ack(&mut self, ck: &Checkpoint) {
+        if let Some(stats) = self.get_mut(ck.checkpoint_id) {
+            stats.ack(ck);
+        }
+    }
+
+    pub fn report_completed(&mut self, checkpoint_id: CheckpointId) {
+        if let Some(stats) = self.get_mut(checkpoint_id) {
+            stats.end(CheckpointStatus::Completed, None);
+            let stats = stats.clone();
+            self.completed_count += 1;
+            self.latest_completed = Some(stats);
+        }
+    }
+
+    /// Record the failed checkpoint, it's added to the history if never reported, e.g. it's
+    /// declined by the min pause
+    pub fn report_failed(&mut self, checkpoint_id: CheckpointId, reason: &str) {
+        self.report_pending(checkpoint_id);
+        if let Some(stats) = self.get_mut(checkpoint_id) {
+            if stats.status != CheckpointStatus::InProgress {
+                return;
+            }
+            stats.end(CheckpointStatus::Failed, Some(reason.to_string()));
+            let stats = stats.clone();
+            self.failed_count += 1;
+            self.latest_failed = Some(stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::checkpoint::{Checkpoint, CheckpointHandle};
+    use crate::core::runtime::{CheckpointId, OperatorId, TaskId};
+    use crate::runtime::coordinator::checkpoint_stats::{
+        CheckpointStatsTracker, CheckpointStatus, OperatorCheckpointStats,
+        CHECKPOINT_STATS_HISTORY_SIZE,
+    };
+
+    fn checkpoint(checkpoint_id: u64, handle: &str, alignment_duration: u64) -> Checkpoint {
+        Checkpoint {
+            operator_id: OperatorId(1),
+            task_id: TaskId::default(),
+            checkpoint_id: CheckpointId(checkpoint_id),
+            completed_checkpoint_id: None,
+            alignment_duration,
+            handle: CheckpointHandle {
+                handle: handle.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    pub fn checkpoint_stats_test() {
+        let operator = OperatorCheckpointStats::new(OperatorId(1), "map".to_string(), 2);
+        let mut tracker = CheckpointStatsTracker::new(vec![operator]);
+
+        tracker.report_pending(CheckpointId(1));
+        tracker.report_ack(&checkpoint(1, "abc", 5));
+        tracker.report_ack(&checkpoint(1, "de", 10));
+        tracker.report_completed(CheckpointId(1));
+
+        let completed = tracker.latest_completed.as_ref().unwrap();
+        assert_eq!(completed.status, CheckpointStatus::Completed);
+        assert_eq!(completed.state_size, 5);
+        assert_eq!(completed.alignment_duration, 10);
+        assert_eq!(completed.operators[0].acknowledged, 2);
+
+        tracker.report_pending(CheckpointId(2));
+        tracker.report_failed(CheckpointId(2), "timeout");
+        tracker.report_failed(CheckpointId(2), "timeout");
+        assert_eq!(tracker.failed_count, 1);
+        let failed = tracker.latest_failed.as_ref().unwrap();
+        assert_eq!(failed.failure_reason.as_deref(), Some("timeout"));
+
+        for checkpoint_id in 3..30 {
+            tracker.report_pending(CheckpointId(checkpoint_id));
+        }
+        assert_eq!(tracker.history.len(), CHECKPOINT_STATS_HISTORY_SIZE);
+        assert_eq!(
+            tracker.history.back().unwrap().checkpoint_id,
+            CheckpointId(29)
+        );
+    }
+}
@@ -2,29 +2,53 @@ use std::time::Duration;
 
 use crate::core::cluster::MetadataStorageType;
 use crate::core::runtime::ManagerStatus;
+use crate::runtime::coordinator::checkpoint_manager::CheckpointManager;
+use crate::runtime::coordinator::high_availability::HighAvailabilityServices;
 use crate::storage::metadata::{loop_read_cluster_descriptor, MetadataStorage};
 use crate::utils;
 
+#[derive(Debug)]
 pub enum HeartbeatResult {
-    Timeout,
+    /// the heartbeats of the workers are lost
+    Timeout(Vec<String>),
     End,
+    /// the consecutive failed checkpoints exceed the tolerable failures
+    CheckpointFailure,
+    /// the coordinator isn't the leader any more, the job is taken over by the new leader
+    LeadershipLost,
 }
 
 /// heartbeat timeout check
 pub(crate) async fn start_heartbeat_timer(
     metadata_storage_mode: MetadataStorageType,
+    checkpoint_manager: &CheckpointManager,
+    high_availability: Option<&HighAvailabilityServices>,
 ) -> HeartbeatResult {
     let metadata_storage = MetadataStorage::new(&metadata_storage_mode);
     loop {
         tokio::time::sleep(Duration::from_secs(3)).await;
 
+        if let Some(high_availability) = high_availability {
+            if !high_availability.is_leader() {
+                return HeartbeatResult::LeadershipLost;
+            }
+            high_availability
+                .update_checkpoint(checkpoint_manager.completed_checkpoint_id().await)
+                .await;
+        }
+
         let cluster_descriptor = loop_read_cluster_descriptor(&metadata_storage).await;
 
         if cluster_descriptor.coordinator_manager.status == ManagerStatus::Terminated {
             return HeartbeatResult::End;
         }
 
+        if checkpoint_manager.check_timeout().await {
+            return HeartbeatResult::CheckpointFailure;
+        }
+
         let current_timestamp = utils::date_time::current_timestamp().as_millis() as u64;
+        let mut timeout_workers = Vec::new();
         for task_manager_descriptor in &cluster_descriptor.worker_managers {
             if current_timestamp < task_manager_descriptor.latest_heart_beat_ts {
                 warn!(
@@ -51,9 +75,12 @@ pub(crate) async fn start_heartbeat_timer(
                     dur.as_secs(),
                     task_manager_descriptor.task_manager_address
                 );
-                return HeartbeatResult::Timeout;
+                timeout_workers.push(task_manager_descriptor.task_manager_id.clone());
             }
         }
+        if !timeout_workers.is_empty() {
+            return HeartbeatResult::Timeout(timeout_workers);
+        }
 
         debug!(
             "all({}) task is final",
@@ -0,0 +1,239 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::Mutex;
+
+use crate::core::backend::HighAvailabilityBackend;
+use crate::core::cluster::TaskResourceInfo;
+use crate::core::runtime::CheckpointId;
+use crate::dag::metadata::DagMetadata;
+use crate::runtime::context::Context;
+use crate::storage::high_availability::{
+    CoordinatorMetadata, HighAvailabilityStorage, JobStatus, LeaderInfo, THighAvailabilityStorage,
+    LEADER_LEASE_TTL,
+};
+use crate::utils::generator::gen_with_ts;
+use crate::utils::hash::hash_code_64;
+
+/// The leader election of the coordinators and the persistence of the leader's metadata.
+/// The standby coordinators block in the campaign until the leader is lost, then the new
+/// leader stops the workers of the old one and restarts the job from the latest completed
+/// checkpoint. Once the job is terminated the leader saves the terminal status, then the
+/// standby coordinators exit instead of rerunning the job
+#[derive(Clone)]
+pub(crate) struct HighAvailabilityServices {
+    storage: Arc<Mutex<HighAvailabilityStorage>>,
+    leader: Arc<Mutex<LeaderInfo>>,
+    is_leader: Arc<AtomicBool>,
+    /// the latest metadata of the leader
+    metadata: Arc<Mutex<Option<CoordinatorMetadata>>>,
+    /// whether the latest metadata is saved, it's saved again with the next update if not
+    saved: Arc<AtomicBool>,
+}
+
+impl HighAvailabilityServices {
+    pub async fn new(backend: &HighAvailabilityBackend, context: &Context) -> anyhow::Result<Self> {
+        let storage =
+            HighAvailabilityStorage::new(backend, context.application_id.as_str()).await?;
+        let leader = LeaderInfo {
+            candidate_id: format!(
+                "{}-{}-{}",
+                context.bind_ip,
+                std::process::id(),
+                gen_with_ts()
+            ),
+            web_address: "".to_string(),
+        };
+
+        Ok(HighAvailabilityServices {
+            storage: Arc::new(Mutex::new(storage)),
+            leader: Arc::new(Mutex::new(leader)),
+            is_leader: Arc::new(AtomicBool::new(false)),
+            metadata: Arc::new(Mutex::new(None)),
+            saved: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Blocking util the coordinator is the leader, then renew the leadership in background.
+    /// The standby returns without the leadership once the job is terminated by the leader,
+    /// the terminal status is loaded by `recover`
+    pub async fn campaign(&self) {
+        let interval = LEADER_LEASE_TTL / 3;
+        loop {
+            match self.try_acquire_leader().await {
+                Ok(true) => break,
+                Ok(false) => {
+                    let storage = self.storage.lock().await;
+                    match storage.load().await {
+                        Ok(Some(metadata)) if metadata.status.is_terminated() => {
+                            info!("standby, the job is terminated as {:?}", metadata.status);
+                            return;
+                        }
+                        Ok(_) => {}
+                        Err(e) => error!("load the coordinator metadata error. {}", e),
+                    }
+                    if let Ok(Some(leader)) = storage.get_leader().await {
+                        debug!(
+                            "standby, the leader is {} with the web ui {}",
+                            leader.candidate_id, leader.web_address
+                        );
+                    }
+                }
+                Err(e) => {
+                    error!("try acquire the leadership error. {}", e);
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+
+        self.is_leader.store(true, Ordering::SeqCst);
+        info!(
+            "the coordinator {} is elected as the leader",
+            self.leader.lock().await.candidate_id
+        );
+
+        let ha = self.clone();
+        tokio::spawn(async move {
+            let mut renewed_at = Instant::now();
+            while ha.is_leader() {
+                tokio::time::sleep(interval).await;
+                match ha.try_acquire_leader().await {
+                    Ok(true) => renewed_at = Instant::now(),
+                    Ok(false) => {
+                        error!("the leadership is taken over by the other coordinator");
+                        ha.is_leader.store(false, Ordering::SeqCst);
+                    }
+                    Err(e) => {
+                        error!("renew the leadership error. {}", e);
+                        if renewed_at.elapsed() > LEADER_LEASE_TTL {
+                            error!("the leadership expires");
+                            ha.is_leader.store(false, Ordering::SeqCst);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    async fn try_acquire_leader(&self) -> anyhow::Result<bool> {
+        let leader = self.leader.lock().await.clone();
+        let mut storage = self.storage.lock().await;
+        storage.try_acquire_leader(&leader, LEADER_LEASE_TTL).await
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.is_leader.load(Ordering::SeqCst)
+    }
+
+    /// The metadata of the previous leader, the job isn't rerun if its status is terminated
+    pub async fn recover(&self) -> anyhow::Result<Option<CoordinatorMetadata>> {
+        let storage = self.storage.lock().await;
+        storage.load().await
+    }
+
+    /// Publish the web address of the leader and the job, the leader info is updated with the
+    /// next renewal
+    pub async fn publish(
+        &self,
+        web_address: &str,
+        dag_metadata: &DagMetadata,
+        startup_number: u64,
+    ) {
+        self.leader.lock().await.web_address = web_address.to_string();
+
+        let metadata = CoordinatorMetadata {
+            status: JobStatus::Running,
+            startup_number,
+            dag_hash: dag_hash(dag_metadata),
+            worker_resources: Vec::new(),
+            completed_checkpoint_id: None,
+        };
+        self.save(metadata).await;
+    }
+
+    /// Save the workers once they're allocated, the new leader stops them before the restart
+    pub async fn update_workers(&self, startup_number: u64, worker_resources: &[TaskResourceInfo]) {
+        let metadata = match self.metadata.lock().await.clone() {
+            Some(mut metadata) => {
+                metadata.startup_number = startup_number;
+                metadata.worker_resources = worker_resources.to_vec();
+                metadata
+            }
+            None => {
+                warn!("the metadata isn't published");
+                return;
+            }
+        };
+        self.save(metadata).await;
+    }
+
+    /// Save the latest completed checkpoint if it's changed
+    pub async fn update_checkpoint(&self, completed_checkpoint_id: Option<CheckpointId>) {
+        let metadata = match self.metadata.lock().await.clone() {
+            Some(metadata)
+                if metadata.completed_checkpoint_id == completed_checkpoint_id
+                    && self.saved.load(Ordering::SeqCst) =>
+            {
+                return;
+            }
+            Some(mut metadata) => {
+                metadata.completed_checkpoint_id = completed_checkpoint_id;
+                metadata
+            }
+            None => return,
+        };
+        self.save(metadata).await;
+    }
+
+    async fn save(&self, metadata: CoordinatorMetadata) {
+        if !self.is_leader() {
+            return;
+        }
+
+        let leader = self.leader.lock().await.clone();
+        let rt = {
+            let mut storage = self.storage.lock().await;
+            storage.save(&leader, &metadata).await
+        };
+        if let Err(e) = &rt {
+            error!("save the coordinator metadata error. {}", e);
+        }
+        self.saved.store(rt.is_ok(), Ordering::SeqCst);
+        *self.metadata.lock().await = Some(metadata);
+    }
+
+    /// Save the terminal status while it's still the leader, then give up the leadership. The
+    /// standby coordinators exit by the status instead of rerunning the job
+    pub async fn terminate(&self, status: JobStatus) {
+        self.saved.store(false, Ordering::SeqCst);
+        match self.metadata.lock().await.clone() {
+            Some(mut metadata) => {
+                metadata.status = status;
+                metadata.worker_resources = Vec::new();
+                self.save(metadata).await;
+            }
+            None => warn!("the metadata isn't published"),
+        }
+        if !self.saved.load(Ordering::SeqCst) {
+            error!("the job is {:?}, but the status isn't saved", status);
+        }
+        self.release().await;
+    }
+
+    /// Give up the leadership, e.g. the job is terminated by the previous leader
+    pub async fn release(&self) {
+        self.is_leader.store(false, Ordering::SeqCst);
+
+        let leader = self.leader.lock().await.clone();
+        let mut storage = self.storage.lock().await;
+        if let Err(e) = storage.release_leader(&leader).await {
+            error!("release the leadership error. {}", e);
+        }
+    }
+}
+
+/// The hash of the job graph, it's compared by the new leader
+pub(crate) fn dag_hash(dag_metadata: &DagMetadata) -> u64 {
+    hash_code_64(dag_metadata.to_string().as_bytes()).unwrap_or_default()
+}
@@ -1,33 +1,44 @@
 use std::borrow::BorrowMut;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::core::checkpoint::CheckpointHandle;
+use crate::core::checkpoint::{Checkpoint, CheckpointHandle, OperatorStateHandle};
 use crate::core::cluster::MetadataStorageType;
 use crate::core::cluster::TaskResourceInfo;
-use crate::core::env::{StreamApp, StreamExecutionEnvironment};
+use crate::core::env::{ExecutionMode, JobResult, StreamApp, StreamExecutionEnvironment};
+use crate::core::key_group::KeyGroupRange;
 use crate::core::properties::{InnerSystemProperties, Properties, SystemProperties};
-use crate::core::runtime::{ClusterDescriptor, ManagerStatus};
+use crate::core::restart_strategy::{FailoverStrategy, RestartTracker};
+use crate::core::runtime::{
+    ClusterDescriptor, ManagerStatus, OperatorId, TaskId, WorkerManagerDescriptor,
+};
 use crate::dag::metadata::DagMetadata;
+use crate::dag::region::PipelinedRegions;
 use crate::dag::DagManager;
 use crate::deployment::TResourceManager;
 use crate::metrics::register_gauge;
 use crate::runtime::context::Context;
 use crate::runtime::coordinator::checkpoint_manager::CheckpointManager;
 use crate::runtime::coordinator::heart_beat_manager::HeartbeatResult;
+use crate::runtime::coordinator::high_availability::{dag_hash, HighAvailabilityServices};
 use crate::runtime::coordinator::task_distribution::build_cluster_descriptor;
 use crate::runtime::coordinator::web_server::web_launch;
+use crate::storage::high_availability::JobStatus;
 use crate::storage::metadata::{
-    loop_read_cluster_descriptor, loop_save_cluster_descriptor, loop_update_application_status,
-    MetadataStorage,
+    loop_read_cluster_descriptor, loop_reset_workers, loop_save_cluster_descriptor,
+    loop_update_application_status, MetadataStorage,
 };
-use crate::utils::date_time::timestamp_str;
+use crate::utils::date_time::{current_timestamp_millis, timestamp_str};
+use crate::utils::process::parse_arg;
 use metrics::Gauge;
 
 pub mod checkpoint_manager;
+pub mod checkpoint_stats;
 pub mod heart_beat_manager;
+pub mod high_availability;
 pub mod task_distribution;
 pub mod web_server;
 
@@ -66,27 +77,74 @@ where
         info!("coordinator start with mode {}", self.context.manager_type);
 
         let application_properties = self.prepare_properties().await;
+        if let Ok(keyed_state_backend) = application_properties.get_keyed_state_backend() {
+            keyed_state_backend.check()?;
+        }
 
-        let dag_manager = {
+        // blocking util the coordinator is the leader
+        let high_availability = self.elect_leader(&application_properties).await?;
+        let recovered_metadata = match &high_availability {
+            Some(high_availability) => high_availability.recover().await?,
+            None => None,
+        };
+        // the job is terminated by the previous leader, it isn't rerun
+        if let (Some(high_availability), Some(recovered_metadata)) =
+            (&high_availability, &recovered_metadata)
+        {
+            if recovered_metadata.status.is_terminated() {
+                high_availability.release().await;
+                return match recovered_metadata.status {
+                    JobStatus::Finished => {
+                        info!("the job is finished by the previous leader");
+                        Ok(())
+                    }
+                    _ => Err(anyhow!("the job is failed by the previous leader")),
+                };
+            }
+        }
+
+        let (dag_manager, restart_strategy) = {
             let mut stream_env = StreamExecutionEnvironment::new();
             self.stream_app
                 .build_stream(&application_properties, stream_env.borrow_mut());
 
             let dag_manager = {
                 let raw_stream_graph = stream_env.stream_manager.stream_graph.borrow();
+                if application_properties.get_execution_mode() == ExecutionMode::Batch {
+                    raw_stream_graph.check_bounded()?;
+                }
                 DagManager::try_from(raw_stream_graph.deref())?
             };
-            dag_manager
+            let restart_strategy = stream_env
+                .restart_strategy
+                .clone()
+                .or_else(|| application_properties.get_restart_strategy().ok())
+                .unwrap_or_default();
+            (dag_manager, restart_strategy)
         };
         info!("DagManager build success");
+        info!("restart strategy: {:?}", restart_strategy);
+
+        let failover_strategy = application_properties.get_failover_strategy();
+        let regions = PipelinedRegions::new(dag_manager.execution_graph());
+        info!(
+            "failover strategy: {:?}, {} pipelined regions",
+            failover_strategy,
+            regions.num_regions()
+        );
 
         let dag_metadata = DagMetadata::from(&dag_manager);
         debug!("DagMetadata: {}", dag_metadata.to_string());
+        if let Some(recovered_metadata) = &recovered_metadata {
+            if recovered_metadata.dag_hash != dag_hash(&dag_metadata) {
+                warn!("the job graph is changed since the previous leader");
+            }
+        }
 
         let mut cluster_descriptor = self.build_metadata(&dag_manager, &application_properties);
         debug!("ApplicationDescriptor : {}", cluster_descriptor.to_string());
 
-        let ck_manager = self
+        let mut ck_manager = self
             .build_checkpoint_manager(
                 &dag_metadata,
                 &application_properties,
@@ -95,20 +153,61 @@ where
             .await;
         info!("start CheckpointManager align task");
 
-        self.web_serve(cluster_descriptor.borrow_mut(), ck_manager, dag_metadata)
-            .await;
+        self.web_serve(
+            cluster_descriptor.borrow_mut(),
+            ck_manager.clone(),
+            dag_metadata.clone(),
+        )
+        .await;
         info!(
             "serve coordinator web ui {}",
             &cluster_descriptor.coordinator_manager.web_address
         );
 
         self.resource_manager
-            .prepare(&self.context, &cluster_descriptor);
+            .prepare(&self.context, &cluster_descriptor)?;
         info!("ResourceManager prepared");
 
+        // take over the job from the previous leader
+        if let Some(recovered_metadata) = recovered_metadata {
+            cluster_descriptor.coordinator_manager.startup_number =
+                recovered_metadata.startup_number;
+            self.stop_all_worker_tasks(recovered_metadata.worker_resources)
+                .await;
+            info!(
+                "stop the workers of the previous leader, restart from the checkpoint {:?}",
+                recovered_metadata.completed_checkpoint_id
+            );
+        }
+
+        if let Some(high_availability) = &high_availability {
+            high_availability
+                .publish(
+                    cluster_descriptor.coordinator_manager.web_address.as_str(),
+                    &dag_metadata,
+                    cluster_descriptor.coordinator_manager.startup_number,
+                )
+                .await;
+        }
+
         self.gauge_startup(&cluster_descriptor);
 
+        let all_workers: Vec<String> = cluster_descriptor
+            .worker_managers
+            .iter()
+            .map(|x| x.task_manager_id.clone())
+            .collect();
+        let worker_tasks: HashMap<String, Vec<TaskId>> = cluster_descriptor
+            .worker_managers
+            .iter()
+            .map(|x| {
+                let task_ids = x.task_descriptors.iter().map(|x| x.task_id).collect();
+                (x.task_manager_id.clone(), task_ids)
+            })
+            .collect();
+
         // loop restart all tasks when some task is failure
+        let mut restart_tracker = RestartTracker::new(restart_strategy);
         loop {
             self.gauge_startup_number(cluster_descriptor.borrow_mut());
 
@@ -122,28 +221,168 @@ where
             info!("pre-worker startup event");
 
             // allocate all worker's resources
-            let worker_task_ids = self.allocate_worker().await;
+            let mut worker_task_ids = self.allocate_worker(&all_workers).await;
             info!("allocate workers success");
+            if let Some(high_availability) = &high_availability {
+                high_availability
+                    .update_workers(
+                        cluster_descriptor.coordinator_manager.startup_number,
+                        &worker_task_ids,
+                    )
+                    .await;
+            }
 
             // blocking util all worker's status is `Register` status
             self.waiting_worker_status_fine().await;
             info!("all worker status is fine");
+            let mut started_at = current_timestamp_millis();
+
+            // restart the regions of the lost workers, until all workers have to restart
+            let heartbeat_result = loop {
+                // heartbeat check. blocking util heartbeat timeout
+                let heartbeat_result = heart_beat_manager::start_heartbeat_timer(
+                    self.metadata_storage_mode.clone(),
+                    &ck_manager,
+                    high_availability.as_ref(),
+                )
+                .await;
+                info!("heartbeat timer has interrupted");
 
-            // heartbeat check. blocking util heartbeat timeout
-            let heartbeat_result =
-                heart_beat_manager::start_heartbeat_timer(self.metadata_storage_mode.clone()).await;
-            info!("heartbeat timer has interrupted");
+                let failover_workers = match &heartbeat_result {
+                    HeartbeatResult::Timeout(workers)
+                        if failover_strategy == FailoverStrategy::Region =>
+                    {
+                        Some(regions.failover_workers(&worker_tasks, workers))
+                    }
+                    _ => None,
+                };
+                let failover_workers: Vec<String> = match failover_workers {
+                    Some(workers) if workers.len() < all_workers.len() => all_workers
+                        .iter()
+                        .filter(|x| workers.contains(*x))
+                        .cloned()
+                        .collect(),
+                    _ => break heartbeat_result,
+                };
+
+                let (stopping, running) = worker_task_ids
+                    .into_iter()
+                    .partition(|x| failover_workers.contains(&x.task_manager_id));
+                worker_task_ids = running;
+                self.stop_all_worker_tasks(stopping).await;
+                info!(
+                    "stop the workers {:?} of the failed regions",
+                    failover_workers
+                );
+
+                match restart_tracker.on_failure(started_at, current_timestamp_millis()) {
+                    Some(delay) => {
+                        info!(
+                            "restart the workers {:?} after {:?} by {:?}",
+                            failover_workers, delay, heartbeat_result
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    None => {
+                        self.stop_all_worker_tasks(worker_task_ids).await;
+                        if let Some(high_availability) = &high_availability {
+                            high_availability.terminate(JobStatus::Failed).await;
+                        }
+                        return Err(anyhow!(
+                            "the job failed by {:?}, the restarts are exhausted by {:?}",
+                            heartbeat_result,
+                            restart_tracker.strategy()
+                        ));
+                    }
+                }
+
+                // the restarted tasks are a new attempt, so their local states, e.g. the rocksdb
+                // of the failed attempt, are discarded and restored from the checkpoint
+                self.gauge_startup_number(cluster_descriptor.borrow_mut());
+                self.reset_workers(&cluster_descriptor, &mut ck_manager, &failover_workers)
+                    .await;
+                info!("reset the workers to the latest completed checkpoint");
+
+                worker_task_ids.extend(self.allocate_worker(&failover_workers).await);
+                info!("allocate workers success");
+                if let Some(high_availability) = &high_availability {
+                    high_availability
+                        .update_workers(
+                            cluster_descriptor.coordinator_manager.startup_number,
+                            &worker_task_ids,
+                        )
+                        .await;
+                }
+
+                self.waiting_worker_status_fine().await;
+                info!("all worker status is fine");
+                started_at = current_timestamp_millis();
+            };
+
+            // the workers are stopped by the new leader
+            if let HeartbeatResult::LeadershipLost = heartbeat_result {
+                return Err(anyhow!(
+                    "the coordinator lost the leadership, the job is taken over by the new leader"
+                ));
+            }
 
             // heartbeat timeout and stop all worker's tasks
             self.stop_all_worker_tasks(worker_task_ids).await;
             info!("stop all workers");
 
             if let HeartbeatResult::End = heartbeat_result {
+                let metadata_storage = MetadataStorage::new(&self.metadata_storage_mode);
+                let drain_checkpoint_id = loop_read_cluster_descriptor(&metadata_storage)
+                    .await
+                    .drain_checkpoint_id();
+                ck_manager
+                    .complete_stop_savepoints(drain_checkpoint_id)
+                    .await;
+                self.job_finished().await;
+                if let Some(high_availability) = &high_availability {
+                    high_availability.terminate(JobStatus::Finished).await;
+                }
                 return Ok(());
             }
+
+            match restart_tracker.on_failure(started_at, current_timestamp_millis()) {
+                Some(delay) => {
+                    info!(
+                        "restart the job after {:?} by {:?}",
+                        delay, heartbeat_result
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                None => {
+                    if let Some(high_availability) = &high_availability {
+                        high_availability.terminate(JobStatus::Failed).await;
+                    }
+                    return Err(anyhow!(
+                        "the job failed by {:?}, the restarts are exhausted by {:?}",
+                        heartbeat_result,
+                        restart_tracker.strategy()
+                    ));
+                }
+            }
         }
     }
 
+    async fn job_finished(&self) {
+        let metadata_storage = MetadataStorage::new(&self.metadata_storage_mode);
+        let cluster_descriptor = loop_read_cluster_descriptor(&metadata_storage).await;
+
+        let job_result = JobResult {
+            application_id: cluster_descriptor
+                .coordinator_manager
+                .application_id
+                .clone(),
+            accumulators: cluster_descriptor.accumulators(),
+        };
+        info!("job finished. {:?}", job_result);
+
+        self.stream_app.on_job_finished(&job_result).await;
+    }
+
     async fn prepare_properties(&self) -> Properties {
         let mut application_properties = Properties::new();
         application_properties.set_cluster_mode(self.context.cluster_mode);
@@ -167,6 +406,23 @@ where
         application_properties
     }
 
+    /// Campaign for the leadership if the high availability is enabled, the standby
+    /// coordinator blocks util the leader is lost
+    async fn elect_leader(
+        &self,
+        application_properties: &Properties,
+    ) -> anyhow::Result<Option<HighAvailabilityServices>> {
+        let backend = match application_properties.get_high_availability() {
+            Ok(backend) => backend,
+            Err(_e) => return Ok(None),
+        };
+
+        let high_availability = HighAvailabilityServices::new(&backend, &self.context).await?;
+        info!("campaign for the leadership with the {} backend", backend);
+        high_availability.campaign().await;
+        Ok(Some(high_availability))
+    }
+
     fn build_metadata(
         &mut self,
         dag_manager: &DagManager,
@@ -207,32 +463,28 @@ where
             checkpoint_ttl,
         )
         .await;
-        let operator_checkpoints = ck_manager.load().await.expect("load checkpoints error");
+        // the savepoint is set by the application properties or the `savepoint_path` arg
+        let savepoint_path = application_properties
+            .get_savepoint_path()
+            .or_else(|_e| parse_arg("savepoint_path"));
+        let operator_checkpoints = match savepoint_path {
+            Ok(savepoint_path) => ck_manager
+                .load_savepoint(savepoint_path.as_str())
+                .await
+                .expect("load savepoint error"),
+            Err(_e) => ck_manager.load().await.expect("load checkpoints error"),
+        };
         if operator_checkpoints.len() == 0 {
             return ck_manager;
         }
 
+        let max_parallelism = application_properties.get_max_parallelism();
         for task_manager_descriptor in &mut cluster_descriptor.worker_managers {
-            for task_descriptor in &mut task_manager_descriptor.task_descriptors {
-                let task_number = task_descriptor.task_id.task_number;
-                for operator in &mut task_descriptor.operators {
-                    let cks = operator_checkpoints.get(&operator.operator_id).unwrap();
-                    if cks.len() == 0 {
-                        debug!("operator {:?} checkpoint not found", operator.operator_id);
-                        continue;
-                    }
-
-                    let ck = cks
-                        .iter()
-                        .find(|ck| ck.task_id.task_number == task_number)
-                        .unwrap();
-                    operator.checkpoint_id = ck.checkpoint_id;
-                    operator.checkpoint_handle = Some(CheckpointHandle {
-                        handle: ck.handle.handle.clone(),
-                    });
-                    info!("operator {:?} checkpoint loaded", operator);
-                }
-            }
+            restore_checkpoints(
+                task_manager_descriptor,
+                &operator_checkpoints,
+                max_parallelism,
+            );
         }
 
         ck_manager
@@ -257,13 +509,51 @@ where
         cluster_descriptor.coordinator_manager.web_address = address;
     }
 
-    async fn allocate_worker(&self) -> Vec<TaskResourceInfo> {
+    async fn allocate_worker(&self, task_manager_ids: &[String]) -> Vec<TaskResourceInfo> {
         self.resource_manager
-            .worker_allocate(&self.stream_app)
+            .worker_allocate(&self.stream_app, task_manager_ids)
             .await
             .expect("try allocate worker error")
     }
 
+    /// Reset the workers of the failed regions to restart in the attempt of the `startup_number`,
+    /// their tasks restore from the latest completed checkpoint
+    async fn reset_workers(
+        &self,
+        cluster_descriptor: &ClusterDescriptor,
+        ck_manager: &mut CheckpointManager,
+        task_manager_ids: &[String],
+    ) {
+        let operator_checkpoints = loop_fn!(ck_manager.load().await, Duration::from_secs(2));
+        let max_parallelism = cluster_descriptor
+            .coordinator_manager
+            .application_properties
+            .get_max_parallelism();
+
+        let worker_managers = cluster_descriptor
+            .worker_managers
+            .iter()
+            .filter(|x| task_manager_ids.contains(&x.task_manager_id))
+            .map(|x| {
+                let mut task_manager_descriptor = x.clone();
+                restore_checkpoints(
+                    &mut task_manager_descriptor,
+                    &operator_checkpoints,
+                    max_parallelism,
+                );
+                task_manager_descriptor
+            })
+            .collect();
+
+        let metadata_storage = MetadataStorage::new(&self.metadata_storage_mode);
+        loop_reset_workers(
+            &metadata_storage,
+            cluster_descriptor.coordinator_manager.startup_number,
+            worker_managers,
+        )
+        .await;
+    }
+
     async fn waiting_worker_status_fine(&self) {
         let mut metadata_storage = MetadataStorage::new(&self.metadata_storage_mode);
         loop {
@@ -338,3 +628,72 @@ where
             .set(cluster_descriptor.coordinator_manager.startup_number as f64);
     }
 }
+
+/// Restore the operators of the worker's tasks from the `operator_checkpoints`
+fn restore_checkpoints(
+    task_manager_descriptor: &mut WorkerManagerDescriptor,
+    operator_checkpoints: &HashMap<OperatorId, Vec<Checkpoint>>,
+    max_parallelism: u16,
+) {
+    for task_descriptor in &mut task_manager_descriptor.task_descriptors {
+        let task_id = task_descriptor.task_id;
+        let task_number = task_id.task_number;
+        for operator in &mut task_descriptor.operators {
+            let cks = match operator_checkpoints.get(&operator.operator_id) {
+                Some(cks) => cks,
+                None => {
+                    debug!("operator {:?} checkpoint not found", operator.operator_id);
+                    continue;
+                }
+            };
+
+            operator.operator_state_handles =
+                operator_state_handles(task_id, operator.keyed_state, cks, max_parallelism);
+
+            let ck = cks.iter().find(|ck| ck.task_id.task_number == task_number);
+            let ck = match ck {
+                Some(ck) => ck,
+                None => {
+                    debug!("operator {:?} checkpoint not found", operator.operator_id);
+                    continue;
+                }
+            };
+            operator.checkpoint_id = ck.checkpoint_id;
+            operator.checkpoint_handle = Some(CheckpointHandle {
+                handle: ck.handle.handle.clone(),
+            });
+            info!("operator {:?} checkpoint loaded", operator);
+        }
+    }
+}
+
+/// The handles of the operator's tasks in the checkpoint `cks` the task `task_id` restores from.
+/// The keyed states are restored from the tasks whose key groups overlap the task's, which is
+/// the task itself if the parallelism isn't changed, while the operator states are
+/// redistributed from all tasks
+fn operator_state_handles(
+    task_id: TaskId,
+    keyed_state: bool,
+    cks: &[Checkpoint],
+    max_parallelism: u16,
+) -> Vec<OperatorStateHandle> {
+    let key_groups =
+        KeyGroupRange::of_operator(max_parallelism, task_id.num_tasks, task_id.task_number);
+    let mut handles: Vec<OperatorStateHandle> = cks
+        .iter()
+        .filter(|ck| {
+            !keyed_state
+                || key_groups.overlaps(&KeyGroupRange::of_operator(
+                    max_parallelism,
+                    ck.task_id.num_tasks,
+                    ck.task_id.task_number,
+                ))
+        })
+        .map(|ck| OperatorStateHandle {
+            task_id: ck.task_id,
+            handle: ck.handle.clone(),
+        })
+        .collect();
+    handles.sort_by_key(|x| x.task_id.task_number);
+    handles
+}
@@ -1,9 +1,11 @@
+use std::collections::BTreeMap;
+
 use crate::core::properties::Properties;
 use crate::core::runtime::{
     CheckpointId, ClusterDescriptor, CoordinatorManagerDescriptor, ManagerStatus,
     OperatorDescriptor, TaskDescriptor, WorkerManagerDescriptor,
 };
-use crate::dag::DagManager;
+use crate::dag::{DagManager, OperatorType};
 use crate::runtime::context::Context;
 use crate::runtime::HeartBeatStatus;
 
@@ -28,6 +30,9 @@ pub(crate) fn build_cluster_descriptor(
                     checkpoint_id: CheckpointId::default(),
                     completed_checkpoint_id: None,
                     checkpoint_handle: None,
+                    keyed_state: stream_node.operator_type == OperatorType::Reduce
+                        || stream_node.operator_type == OperatorType::KeyedProcess,
+                    operator_state_handles: vec![],
                 })
                 .collect();
 
@@ -37,6 +42,8 @@ pub(crate) fn build_cluster_descriptor(
                 input_split: task_instance.input_split.clone(),
                 daemon: task_instance.daemon,
                 terminated: false,
+                accumulators: BTreeMap::new(),
+                drain_checkpoint_id: None,
             };
             task_descriptors.push(task_descriptor);
         }
@@ -48,6 +55,7 @@ pub(crate) fn build_cluster_descriptor(
             task_manager_id: task_manager_instance.worker_manager_id.clone(),
             task_manager_address: "".to_string(),
             web_address: "".to_string(),
+            queryable_state_address: "".to_string(),
             task_descriptors,
         };
         worker_managers.push(task_manager_descriptor);
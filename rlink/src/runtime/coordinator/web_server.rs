@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Buf;
 use hyper::http::header;
@@ -16,7 +18,7 @@ use rand::{Rng, SeedableRng};
 use crate::channel::{bounded, Sender};
 use crate::core::checkpoint::Checkpoint;
 use crate::core::cluster::{MetadataStorageType, StdResponse};
-use crate::core::runtime::ManagerStatus;
+use crate::core::runtime::{CheckpointId, ManagerStatus};
 use crate::dag::metadata::DagMetadata;
 use crate::metrics::metric_handle;
 use crate::metrics::worker_proxy::collect_worker_metrics;
@@ -116,6 +118,8 @@ async fn route(req: Request<Body>, web_context: Arc<WebContext>) -> anyhow::Resu
                 "/api/context" => get_context(req, web_context).await,
                 "/api/cluster_metadata" => get_cluster_metadata(req, web_context).await,
                 "/api/checkpoints" => get_checkpoint(req, web_context).await,
+                "/api/checkpoint_stats" => get_checkpoint_stats(req, web_context).await,
+                "/api/accumulators" => get_accumulators(req, web_context).await,
                 "/api/dag_metadata" => get_dag_metadata(req, web_context).await,
                 "/api/dag/stream_graph" => get_stream_graph(req, web_context).await,
                 "/api/dag/job_graph" => get_job_graph(req, web_context).await,
@@ -128,6 +132,9 @@ async fn route(req: Request<Body>, web_context: Arc<WebContext>) -> anyhow::Resu
             match path {
                 "/api/heartbeat" => heartbeat(req, web_context).await,
                 "/api/checkpoint" => checkpoint(req, web_context).await,
+                "/api/checkpoint/trigger" => trigger_checkpoint(req, web_context).await,
+                "/api/savepoint" => savepoint(req, web_context).await,
+                "/api/stop" => stop_with_savepoint(req, web_context).await,
                 _ => page_not_found().await,
             }
         } else {
@@ -172,6 +179,16 @@ async fn get_cluster_metadata(
     as_ok_json(&StdResponse::ok(Some(cluster_descriptor)))
 }
 
+/// The accumulators of all tasks merged by the name
+async fn get_accumulators(
+    _req: Request<Body>,
+    context: Arc<WebContext>,
+) -> anyhow::Result<Response<Body>> {
+    let metadata_storage = MetadataStorage::new(&context.metadata_mode);
+    let cluster_descriptor = metadata_storage.load().await?;
+    as_ok_json(&StdResponse::ok(Some(cluster_descriptor.accumulators())))
+}
+
 async fn get_checkpoint(
     _req: Request<Body>,
     context: Arc<WebContext>,
@@ -180,6 +197,14 @@ async fn get_checkpoint(
     as_ok_json(&StdResponse::ok(Some(cks)))
 }
 
+async fn get_checkpoint_stats(
+    _req: Request<Body>,
+    context: Arc<WebContext>,
+) -> anyhow::Result<Response<Body>> {
+    let stats = context.checkpoint_manager.checkpoint_stats().await;
+    as_ok_json(&StdResponse::ok(Some(stats)))
+}
+
 async fn get_dag_metadata(
     _req: Request<Body>,
     context: Arc<WebContext>,
@@ -261,6 +286,77 @@ async fn checkpoint(
     as_ok_json(&StdResponse::ok(Some(resp.to_string())))
 }
 
+/// Whether the source injects the barrier of the checkpoint
+async fn trigger_checkpoint(
+    req: Request<Body>,
+    context: Arc<WebContext>,
+) -> anyhow::Result<Response<Body>> {
+    let whole_body = hyper::body::aggregate(req).await?;
+    let checkpoint_id: CheckpointId = serde_json::from_reader(whole_body.reader())?;
+
+    let triggered = context
+        .checkpoint_manager
+        .trigger_checkpoint(checkpoint_id)
+        .await;
+    as_ok_json(&StdResponse::ok(Some(triggered)))
+}
+
+/// the max duration waiting for the next completed checkpoint of the savepoint
+const SAVEPOINT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavepointRequest {
+    /// the directory of the savepoints, e.g. `s3://bucket/savepoints`
+    path: String,
+    /// the object store options, the options of the checkpoint are used if empty
+    #[serde(default)]
+    options: HashMap<String, String>,
+}
+
+async fn savepoint(req: Request<Body>, context: Arc<WebContext>) -> anyhow::Result<Response<Body>> {
+    let whole_body = hyper::body::aggregate(req).await?;
+    let SavepointRequest { path, options } = serde_json::from_reader(whole_body.reader())?;
+
+    info!("trigger savepoint to {}", path);
+    let receiver = context
+        .checkpoint_manager
+        .trigger_savepoint(path, options)
+        .await;
+    let resp: StdResponse<String> = match tokio::time::timeout(SAVEPOINT_TIMEOUT, receiver).await {
+        Ok(Ok(result)) => result.into(),
+        Ok(Err(_e)) => StdResponse::err("the savepoint is cancelled"),
+        Err(_e) => StdResponse::err("the savepoint timeout"),
+    };
+    as_ok_json(&resp)
+}
+
+/// Drain the job and stop it with a savepoint, the response is sent when the job stopped
+async fn stop_with_savepoint(
+    req: Request<Body>,
+    context: Arc<WebContext>,
+) -> anyhow::Result<Response<Body>> {
+    let whole_body = hyper::body::aggregate(req).await?;
+    let SavepointRequest { path, options } = serde_json::from_reader(whole_body.reader())?;
+
+    info!("stop with savepoint to {}", path);
+    let receiver = context
+        .checkpoint_manager
+        .trigger_stop_savepoint(path, options)
+        .await;
+
+    let metadata_storage = MetadataStorage::new(&context.metadata_mode);
+    metadata_storage
+        .update_coordinator_status(ManagerStatus::Draining)
+        .await?;
+
+    let resp: StdResponse<String> = match tokio::time::timeout(SAVEPOINT_TIMEOUT, receiver).await {
+        Ok(Ok(result)) => result.into(),
+        Ok(Err(_e)) => StdResponse::err("the savepoint is cancelled"),
+        Err(_e) => StdResponse::err("the job is not stopped in time"),
+    };
+    as_ok_json(&resp)
+}
+
 async fn static_file(
     req: Request<Body>,
     context: Arc<WebContext>,
@@ -1,8 +1,9 @@
 use std::convert::TryFrom;
 use std::sync::Arc;
 
+use crate::core::accumulator::TaskAccumulators;
 use crate::core::env::StreamApp;
-use crate::core::runtime::{HeartBeatStatus, TaskId};
+use crate::core::runtime::{CheckpointId, HeartBeatStatus, TaskId};
 use crate::metrics::install_recorder;
 use crate::utils::panic::panic_notify;
 
@@ -92,11 +93,20 @@ pub enum HeartbeatItem {
     WorkerAddrs {
         address: String,
         web_address: String,
+        /// empty if the queryable state server is disabled
+        #[serde(default)]
+        queryable_state_address: String,
     },
     HeartBeatStatus(HeartBeatStatus),
     TaskEnd {
         task_id: TaskId,
+        /// the checkpoint of the drained state the task terminated after, on stop with
+        /// savepoint
+        #[serde(default)]
+        checkpoint_id: Option<CheckpointId>,
     },
+    /// the current values of the accumulators of the tasks in the worker
+    Accumulators(Vec<TaskAccumulators>),
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
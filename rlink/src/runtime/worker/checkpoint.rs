@@ -3,11 +3,13 @@ use std::fmt::{Debug, Formatter};
 use crate::channel::{bounded, Receiver, Sender, TrySendError};
 use crate::core::checkpoint::Checkpoint;
 use crate::core::cluster::StdResponse;
+use crate::core::runtime::CheckpointId;
 use crate::utils::date_time;
 use crate::utils::http::client::post;
 
 #[derive(Clone, Default)]
 pub struct CheckpointPublish {
+    coordinator_address: String,
     sender: Option<Sender<Checkpoint>>,
 }
 
@@ -20,9 +22,10 @@ impl Debug for CheckpointPublish {
 impl CheckpointPublish {
     pub async fn new(coordinator_address: String) -> Self {
         let (sender, receiver) = bounded::<Checkpoint>(100);
-        Self::start_report_checkpoint(coordinator_address, receiver).await;
+        Self::start_report_checkpoint(coordinator_address.clone(), receiver).await;
 
         Self {
+            coordinator_address,
             sender: Some(sender),
         }
     }
@@ -40,6 +43,24 @@ impl CheckpointPublish {
         });
     }
 
+    /// Ask the coordinator whether the barrier of the checkpoint is injected, the checkpoint
+    /// is declined if the coordinator is unreachable
+    pub(crate) async fn trigger(&self, checkpoint_id: CheckpointId) -> bool {
+        let url = format!("{}/api/checkpoint/trigger", self.coordinator_address);
+        let body = serde_json::to_string(&checkpoint_id).unwrap();
+
+        match post::<StdResponse<bool>>(url, body).await {
+            Ok(resp) => resp.data.unwrap_or(false),
+            Err(e) => {
+                error!(
+                    "trigger checkpoint error, checkpoint_id={:?}. {}",
+                    checkpoint_id, e
+                );
+                false
+            }
+        }
+    }
+
     pub(crate) fn report(&self, ck: Checkpoint) -> Option<Checkpoint> {
         debug!("report checkpoint: {:?}", &ck);
         match self.sender.as_ref().unwrap().try_send(ck) {
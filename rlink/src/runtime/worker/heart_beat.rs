@@ -2,6 +2,7 @@ use std::fmt::{Debug, Formatter};
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use crate::core::accumulator;
 use crate::core::cluster::StdResponse;
 use crate::core::runtime::AtomicManagerStatus;
 use crate::core::runtime::{HeartBeatStatus, ManagerStatus};
@@ -80,6 +81,12 @@ impl HeartbeatPublish {
             change_items.push(HeartbeatItem::HeartBeatStatus(status));
         }
 
+        // the latest values are shipped by every heartbeat, including the `TaskEnd` one
+        let accumulators = accumulator::snapshot();
+        if !accumulators.is_empty() {
+            change_items.push(HeartbeatItem::Accumulators(accumulators));
+        }
+
         let request = HeartbeatRequest {
             task_manager_id: self.task_manager_id.to_string(),
             change_items,
@@ -101,7 +108,9 @@ impl HeartbeatPublish {
 
                 if let Some(coordinator_status) = resp.data {
                     match coordinator_status {
-                        ManagerStatus::Terminating | ManagerStatus::Terminated => {
+                        ManagerStatus::Terminating
+                        | ManagerStatus::Terminated
+                        | ManagerStatus::Draining => {
                             info!("coordinator status: {:?}", coordinator_status)
                         }
                         _ => {}
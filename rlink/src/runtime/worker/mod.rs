@@ -17,12 +17,15 @@ use crate::runtime::worker::checkpoint::CheckpointPublish;
 use crate::runtime::worker::heart_beat::HeartbeatPublish;
 use crate::runtime::worker::runnable::co_process_runnable::CoProcessRunnable;
 use crate::runtime::worker::runnable::{
-    FilterRunnable, FlatMapRunnable, KeyByRunnable, ReduceRunnable, Runnable, RunnableContext,
-    SinkRunnable, SourceRunnable, WatermarkAssignerRunnable, WindowAssignerRunnable,
+    FilterRunnable, FlatMapRunnable, KeyByRunnable, KeyedProcessRunnable, ReduceRunnable, Runnable,
+    RunnableContext, SinkRunnable, SourceRunnable, WatermarkAssignerRunnable,
+    WindowAssignerRunnable,
 };
 
 pub mod checkpoint;
 pub mod heart_beat;
+#[cfg(feature = "queryable-state")]
+pub mod queryable_state;
 pub mod runnable;
 pub mod web_server;
 
@@ -70,7 +73,6 @@ impl WorkerTaskContext {
         self.context.clone()
     }
 
-    #[allow(unused)]
     pub fn dag_metadata(&self) -> Arc<DagMetadata> {
         self.dag_metadata.clone()
     }
@@ -214,6 +216,18 @@ where
                     let op: Box<dyn Runnable> = Box::new(op);
                     op
                 }
+                StreamOperator::StreamKeyedProcess(stream_operator) => {
+                    let stream_key_by =
+                        self.get_dependency_key_by(operators.borrow_mut(), job_node.job_id);
+                    let op = KeyedProcessRunnable::new(
+                        operator_id,
+                        stream_key_by,
+                        stream_operator,
+                        None,
+                    );
+                    let op: Box<dyn Runnable> = Box::new(op);
+                    op
+                }
                 StreamOperator::StreamWatermarkAssigner(stream_operator) => {
                     let op = WatermarkAssignerRunnable::new(operator_id, stream_operator, None);
                     let op: Box<dyn Runnable> = Box::new(op);
@@ -0,0 +1,146 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use futures::future::BoxFuture;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Server};
+use rand::prelude::StdRng;
+use rand::Rng;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::server::{Grpc, UnaryService};
+use tonic::{Request, Response, Status};
+
+use crate::channel::{bounded, Sender};
+use crate::core::queryable_state::{
+    ListStatesRequest, ListStatesResponse, QueryableStateTask, StateRequest, StateResponse,
+    GET_STATE_PATH, LIST_STATES_PATH,
+};
+use crate::core::state::{query_state, queryable_state_tasks};
+
+/// Serve the queryable states of the worker's tasks over gRPC, return the address of the server.
+/// The keys are hashed to the `max_parallelism` key groups by the clients to find the owner tasks
+pub(crate) async fn queryable_state_launch(bind_ip: String, max_parallelism: u16) -> String {
+    let (tx, mut rx) = bounded(1);
+
+    tokio::spawn(async move {
+        serve_with_rand_port(bind_ip, max_parallelism, tx).await;
+    });
+
+    let bind_addr: SocketAddr = rx.recv().await.unwrap();
+    format!("http://{}", bind_addr.to_string())
+}
+
+#[derive(Clone)]
+struct GetState;
+
+impl UnaryService<StateRequest> for GetState {
+    type Response = StateResponse;
+    type Future = BoxFuture<'static, Result<Response<StateResponse>, Status>>;
+
+    fn call(&mut self, request: Request<StateRequest>) -> Self::Future {
+        let request = request.into_inner();
+        let task_number = request.task_number.map(|x| x as u16);
+        let response = match query_state(
+            request.queryable_name.as_str(),
+            task_number,
+            request.key.as_slice(),
+        ) {
+            Some(value) => StateResponse {
+                found: true,
+                value: value.to_string(),
+            },
+            None => StateResponse::default(),
+        };
+        Box::pin(futures::future::ready(Ok(Response::new(response))))
+    }
+}
+
+#[derive(Clone)]
+struct ListStates {
+    max_parallelism: u16,
+}
+
+impl UnaryService<ListStatesRequest> for ListStates {
+    type Response = ListStatesResponse;
+    type Future = BoxFuture<'static, Result<Response<ListStatesResponse>, Status>>;
+
+    fn call(&mut self, _request: Request<ListStatesRequest>) -> Self::Future {
+        let states = queryable_state_tasks()
+            .into_iter()
+            .map(|(queryable_name, task_id)| QueryableStateTask {
+                queryable_name,
+                job_id: task_id.job_id().0,
+                task_number: task_id.task_number() as u32,
+                num_tasks: task_id.num_tasks() as u32,
+            })
+            .collect();
+        let response = ListStatesResponse {
+            max_parallelism: self.max_parallelism as u32,
+            states,
+        };
+        Box::pin(futures::future::ready(Ok(Response::new(response))))
+    }
+}
+
+async fn serve_with_rand_port(
+    bind_ip: String,
+    max_parallelism: u16,
+    bind_addr_tx: Sender<SocketAddr>,
+) {
+    let mut rng: StdRng = rand::SeedableRng::from_entropy();
+    for _ in 0..30 {
+        let port = rng.gen_range(10000..30000);
+        let address = format!("{}:{}", bind_ip.as_str(), port);
+        let socket_addr = SocketAddr::from_str(address.as_str()).unwrap();
+
+        match serve(&socket_addr, max_parallelism, bind_addr_tx.clone()).await {
+            Ok(_) => error!("queryable state server stop"),
+            Err(e) => info!("try bind failure> {}", e),
+        }
+    }
+
+    error!("no port can be bound");
+}
+
+async fn serve(
+    bind_addr: &SocketAddr,
+    max_parallelism: u16,
+    bind_addr_tx: Sender<SocketAddr>,
+) -> anyhow::Result<()> {
+    let make_service = make_service_fn(move |_conn| async move {
+        Ok::<_, Infallible>(service_fn(
+            move |request: hyper::Request<Body>| async move {
+                Ok::<_, Infallible>(handle(request, max_parallelism).await)
+            },
+        ))
+    });
+
+    let server = Server::try_bind(bind_addr)?
+        .http2_only(true)
+        .serve(make_service);
+
+    bind_addr_tx.send(bind_addr.clone()).await.unwrap();
+
+    if let Err(e) = server.await {
+        error!("queryable state server error: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn handle(request: hyper::Request<Body>, max_parallelism: u16) -> hyper::Response<BoxBody> {
+    match request.uri().path() {
+        GET_STATE_PATH => {
+            let mut grpc = Grpc::new(ProstCodec::<StateResponse, StateRequest>::default());
+            grpc.unary(GetState, request).await
+        }
+        LIST_STATES_PATH => {
+            let mut grpc =
+                Grpc::new(ProstCodec::<ListStatesResponse, ListStatesRequest>::default());
+            grpc.unary(ListStates { max_parallelism }, request).await
+        }
+        path => Status::unimplemented(format!("{} is not found", path)).to_http(),
+    }
+}
@@ -149,6 +149,7 @@ impl Runnable for CoProcessRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
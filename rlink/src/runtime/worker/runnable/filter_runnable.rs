@@ -5,7 +5,7 @@ use crate::core::element::Element;
 use crate::core::function::FilterFunction;
 use crate::core::operator::DefaultStreamOperator;
 use crate::core::runtime::OperatorId;
-use crate::runtime::worker::runnable::{Runnable, RunnableContext};
+use crate::runtime::worker::runnable::{RecordErrorHandler, Runnable, RunnableContext};
 
 pub(crate) struct FilterRunnable {
     operator_id: OperatorId,
@@ -13,6 +13,7 @@ pub(crate) struct FilterRunnable {
     next_runnable: Option<Box<dyn Runnable>>,
 
     context: Option<RunnableContext>,
+    record_error: RecordErrorHandler,
 }
 
 impl FilterRunnable {
@@ -28,6 +29,7 @@ impl FilterRunnable {
             stream_filter,
             next_runnable,
             context: None,
+            record_error: RecordErrorHandler::fail_job(operator_id),
         }
     }
 }
@@ -38,6 +40,7 @@ impl Runnable for FilterRunnable {
         self.next_runnable.as_mut().unwrap().open(context).await?;
 
         self.context = Some(context.clone());
+        self.record_error = context.record_error_handler(self.operator_id);
 
         let fun_context = context.to_fun_context(self.operator_id);
         self.stream_filter.operator_fn.open(&fun_context).await?;
@@ -48,8 +51,22 @@ impl Runnable for FilterRunnable {
     async fn run(&mut self, mut element: Element) {
         match element.borrow_mut() {
             Element::Record(record) => {
-                if self.stream_filter.operator_fn.as_mut().filter(record).await {
-                    self.next_runnable.as_mut().unwrap().run(element).await;
+                let input = self.record_error.is_dead_letter().then(|| record.clone());
+                let result = self
+                    .stream_filter
+                    .operator_fn
+                    .as_mut()
+                    .try_filter(record)
+                    .await;
+                match result {
+                    Ok(true) => self.next_runnable.as_mut().unwrap().run(element).await,
+                    Ok(false) => {}
+                    Err(e) => {
+                        if let Some(dead_letter) = self.record_error.on_error(input, &e) {
+                            let next_runnable = self.next_runnable.as_mut().unwrap();
+                            next_runnable.run(Element::Record(dead_letter)).await;
+                        }
+                    }
                 }
             }
             Element::Barrier(barrier) => {
@@ -90,6 +107,7 @@ impl Runnable for FilterRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
@@ -9,7 +9,7 @@ use crate::core::function::FlatMapFunction;
 use crate::core::operator::DefaultStreamOperator;
 use crate::core::runtime::{OperatorId, TaskId};
 use crate::metrics::register_counter;
-use crate::runtime::worker::runnable::{Runnable, RunnableContext};
+use crate::runtime::worker::runnable::{RecordErrorHandler, Runnable, RunnableContext};
 
 pub(crate) struct FlatMapRunnable {
     operator_id: OperatorId,
@@ -19,6 +19,7 @@ pub(crate) struct FlatMapRunnable {
     next_runnable: Option<Box<dyn Runnable>>,
 
     context: Option<RunnableContext>,
+    record_error: RecordErrorHandler,
 
     counter: Counter,
 }
@@ -37,9 +38,23 @@ impl FlatMapRunnable {
             stream_map,
             next_runnable,
             context: None,
+            record_error: RecordErrorHandler::fail_job(operator_id),
             counter: Counter::noop(),
         }
     }
+
+    /// Forward the records held by the function
+    async fn flush(&mut self) {
+        if let Some(mut elements) = self.stream_map.operator_fn.flush().await {
+            let mut len = 0;
+            while let Some(ele) = elements.next().await {
+                self.next_runnable.as_mut().unwrap().run(ele).await;
+                len += 1;
+            }
+
+            self.counter.increment(len);
+        }
+    }
 }
 
 #[async_trait]
@@ -50,6 +65,7 @@ impl Runnable for FlatMapRunnable {
         self.context = Some(context.clone());
 
         self.task_id = context.task_context.task_descriptor.task_id;
+        self.record_error = context.record_error_handler(self.operator_id);
 
         let fun_context = context.to_fun_context(self.operator_id);
         self.stream_map.operator_fn.open(&fun_context).await?;
@@ -64,13 +80,24 @@ impl Runnable for FlatMapRunnable {
 
     async fn run(&mut self, mut element: Element) {
         match element.borrow_mut() {
-            Element::Record(_record) => {
-                let mut elements = self
+            Element::Record(record) => {
+                let input = self.record_error.is_dead_letter().then(|| record.clone());
+                let result = self
                     .stream_map
                     .operator_fn
                     .as_mut()
-                    .flat_map_element(element)
+                    .try_flat_map_element(element)
                     .await;
+                let mut elements = match result {
+                    Ok(elements) => elements,
+                    Err(e) => {
+                        if let Some(dead_letter) = self.record_error.on_error(input, &e) {
+                            let next_runnable = self.next_runnable.as_mut().unwrap();
+                            next_runnable.run(Element::Record(dead_letter)).await;
+                        }
+                        return;
+                    }
+                };
 
                 let mut len = 0;
                 while let Some(ele) = elements.next().await {
@@ -81,6 +108,10 @@ impl Runnable for FlatMapRunnable {
                 self.counter.increment(len);
             }
             Element::Barrier(barrier) => {
+                if self.stream_map.operator_fn.flush_on_barrier() {
+                    self.flush().await;
+                }
+
                 let checkpoint_id = barrier.checkpoint_id;
                 let snapshot_context = {
                     let context = self.context.as_ref().unwrap();
@@ -91,12 +122,14 @@ impl Runnable for FlatMapRunnable {
                 self.next_runnable.as_mut().unwrap().run(element).await;
             }
             _ => {
+                self.flush().await;
                 self.next_runnable.as_mut().unwrap().run(element).await;
             }
         }
     }
 
     async fn close(&mut self) -> anyhow::Result<()> {
+        self.flush().await;
         self.stream_map.operator_fn.close().await?;
         self.next_runnable.as_mut().unwrap().close().await
     }
@@ -118,6 +151,7 @@ impl Runnable for FlatMapRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
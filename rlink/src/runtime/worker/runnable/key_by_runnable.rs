@@ -5,12 +5,12 @@ use metrics::Counter;
 use crate::core::checkpoint::{Checkpoint, CheckpointHandle, FunctionSnapshotContext};
 use crate::core::element::{Element, Partition};
 use crate::core::function::KeySelectorFunction;
+use crate::core::key_group::assign_key_to_operator;
 use crate::core::operator::DefaultStreamOperator;
 use crate::core::runtime::{OperatorId, TaskId};
 use crate::metrics::register_counter;
 
 use crate::runtime::worker::runnable::{Runnable, RunnableContext};
-use crate::utils;
 
 pub(crate) struct KeyByRunnable {
     operator_id: OperatorId,
@@ -19,6 +19,7 @@ pub(crate) struct KeyByRunnable {
     stream_key_by: DefaultStreamOperator<dyn KeySelectorFunction>,
     next_runnable: Option<Box<dyn Runnable>>,
     partition_size: u16,
+    max_parallelism: u16,
 
     context: Option<RunnableContext>,
 
@@ -37,6 +38,7 @@ impl KeyByRunnable {
             stream_key_by,
             next_runnable,
             partition_size: 0,
+            max_parallelism: 0,
             context: None,
             counter: Counter::noop(),
         }
@@ -55,6 +57,14 @@ impl Runnable for KeyByRunnable {
 
         // todo set self.partition_size = Reduce.partition
         self.partition_size = context.child_parallelism() as u16;
+        self.max_parallelism = context.max_parallelism();
+        if self.partition_size > self.max_parallelism {
+            return Err(anyhow!(
+                "the parallelism {} of the keyed job exceeds the max parallelism {}",
+                self.partition_size,
+                self.max_parallelism
+            ));
+        }
 
         self.counter = register_counter(
             format!("KeyBy_{}", self.stream_key_by.operator_fn.as_ref().name()),
@@ -74,15 +84,14 @@ impl Runnable for KeyByRunnable {
                     .get_key(record.borrow_mut())
                     .await;
 
-                let hash_code = utils::hash::hash_code(key_row.values.as_slice()).unwrap_or(0);
-                let partition_num = hash_code % self.partition_size as u32;
-                // info!(
-                //     "partition: {}, hash code: {}, partition_size: {}",
-                //     partition_num,
-                //     hash_code,
-                //     self.partition_size,
-                // );
-                record.set_partition(partition_num as u16);
+                // the key is partitioned to the task owning its key group, it differs from the
+                // former `hash % parallelism`, see `SystemProperties::set_max_parallelism`
+                let partition_num = assign_key_to_operator(
+                    key_row.values.as_slice(),
+                    self.max_parallelism,
+                    self.partition_size,
+                );
+                record.set_partition(partition_num);
 
                 self.next_runnable.as_mut().unwrap().run(element).await;
 
@@ -126,6 +135,7 @@ impl Runnable for KeyByRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
@@ -0,0 +1,251 @@
+use std::borrow::BorrowMut;
+
+use futures::StreamExt;
+use metrics::Counter;
+
+use crate::core::checkpoint::{Checkpoint, FunctionSnapshotContext};
+use crate::core::element::{Element, Record};
+use crate::core::function::{KeySelectorFunction, KeyedProcessFunction, SendableElementStream};
+use crate::core::operator::DefaultStreamOperator;
+use crate::core::runtime::{OperatorId, TaskId};
+use crate::core::state::RuntimeContext;
+use crate::core::timer::{TimeDomain, TimerService};
+use crate::metrics::register_counter;
+use crate::runtime::worker::runnable::{RecordErrorHandler, Runnable, RunnableContext};
+use crate::utils::date_time::current_timestamp_millis;
+
+pub(crate) struct KeyedProcessRunnable {
+    operator_id: OperatorId,
+    task_id: TaskId,
+
+    context: Option<RunnableContext>,
+    runtime_context: RuntimeContext,
+    timer_service: TimerService,
+
+    stream_key_by: Option<DefaultStreamOperator<dyn KeySelectorFunction>>,
+    stream_keyed_process: DefaultStreamOperator<dyn KeyedProcessFunction>,
+    next_runnable: Option<Box<dyn Runnable>>,
+    record_error: RecordErrorHandler,
+
+    counter: Counter,
+    timer_counter: Counter,
+}
+
+impl KeyedProcessRunnable {
+    pub fn new(
+        operator_id: OperatorId,
+        stream_key_by: Option<DefaultStreamOperator<dyn KeySelectorFunction>>,
+        stream_keyed_process: DefaultStreamOperator<dyn KeyedProcessFunction>,
+        next_runnable: Option<Box<dyn Runnable>>,
+    ) -> Self {
+        info!("Create KeyedProcessRunnable");
+
+        KeyedProcessRunnable {
+            operator_id,
+            task_id: TaskId::default(),
+            context: None,
+            runtime_context: RuntimeContext::new(),
+            timer_service: TimerService::new(),
+            stream_key_by,
+            stream_keyed_process,
+            next_runnable,
+            record_error: RecordErrorHandler::fail_job(operator_id),
+            counter: Counter::noop(),
+            timer_counter: Counter::noop(),
+        }
+    }
+
+    async fn forward(&mut self, mut element_stream: SendableElementStream) {
+        while let Some(element) = element_stream.next().await {
+            self.next_runnable.as_mut().unwrap().run(element).await;
+        }
+    }
+
+    async fn fire_event_time_timers(&mut self) {
+        while let Some((timestamp, key)) = self.timer_service.poll_event_time_timer() {
+            self.on_timer(timestamp, key, TimeDomain::EventTime).await;
+        }
+    }
+
+    async fn fire_processing_time_timers(&mut self) {
+        let time = current_timestamp_millis();
+        while let Some((timestamp, key)) = self.timer_service.poll_processing_time_timer(time) {
+            self.on_timer(timestamp, key, TimeDomain::ProcessingTime)
+                .await;
+        }
+    }
+
+    async fn on_timer(&mut self, timestamp: u64, key: Record, time_domain: TimeDomain) {
+        self.runtime_context.set_current_key(&key);
+        self.timer_service.set_current_key(key);
+
+        let element_stream = self
+            .stream_keyed_process
+            .operator_fn
+            .on_timer(timestamp, time_domain, &mut self.timer_service)
+            .await;
+        self.timer_counter.increment(1);
+
+        self.forward(element_stream).await;
+    }
+}
+
+#[async_trait]
+impl Runnable for KeyedProcessRunnable {
+    async fn open(&mut self, context: &RunnableContext) -> anyhow::Result<()> {
+        self.next_runnable.as_mut().unwrap().open(context).await?;
+
+        self.task_id = context.task_context.task_descriptor.task_id;
+
+        self.context = Some(context.clone());
+        self.record_error = context.record_error_handler(self.operator_id);
+
+        let fun_context = context.to_fun_context(self.operator_id);
+        self.runtime_context = fun_context.runtime_context();
+        self.runtime_context.open(&fun_context)?;
+        self.timer_service.restore(&self.runtime_context)?;
+
+        if let Some(stream_key_by) = self.stream_key_by.as_mut() {
+            stream_key_by.operator_fn.open(&fun_context).await?;
+        }
+        self.stream_keyed_process
+            .operator_fn
+            .open(&fun_context)
+            .await?;
+
+        let fn_name = self.stream_keyed_process.operator_fn.as_ref().name();
+
+        self.counter =
+            register_counter(format!("KeyedProcess_{}", fn_name), self.task_id.to_tags());
+
+        self.timer_counter = register_counter(
+            format!("KeyedProcess_Timer_{}", fn_name),
+            self.task_id.to_tags(),
+        );
+
+        info!("KeyedProcessRunnable Opened. task_id={:?}", self.task_id);
+        Ok(())
+    }
+
+    async fn run(&mut self, element: Element) {
+        match element {
+            Element::Record(mut record) => {
+                let key = match &self.stream_key_by {
+                    Some(stream_key_by) => {
+                        stream_key_by.operator_fn.get_key(record.borrow_mut()).await
+                    }
+                    None => Record::with_capacity(0),
+                };
+                self.runtime_context.set_current_key(&key);
+                self.timer_service.set_current_key(key);
+
+                let input = self.record_error.is_dead_letter().then(|| record.clone());
+                let result = self
+                    .stream_keyed_process
+                    .operator_fn
+                    .try_process_element(record, &mut self.timer_service)
+                    .await;
+                self.counter.increment(1);
+
+                match result {
+                    Ok(element_stream) => self.forward(element_stream).await,
+                    Err(e) => {
+                        if let Some(dead_letter) = self.record_error.on_error(input, &e) {
+                            let next_runnable = self.next_runnable.as_mut().unwrap();
+                            next_runnable.run(Element::Record(dead_letter)).await;
+                        }
+                    }
+                }
+            }
+            Element::Watermark(watermark) => {
+                // the idle watermark doesn't advance the event time
+                if !watermark.is_idle() {
+                    self.timer_service
+                        .set_current_watermark(watermark.timestamp);
+                    self.fire_event_time_timers().await;
+                }
+                self.fire_processing_time_timers().await;
+
+                self.next_runnable
+                    .as_mut()
+                    .unwrap()
+                    .run(Element::Watermark(watermark))
+                    .await;
+            }
+            Element::StreamStatus(stream_status) => {
+                self.fire_processing_time_timers().await;
+
+                self.next_runnable
+                    .as_mut()
+                    .unwrap()
+                    .run(Element::StreamStatus(stream_status))
+                    .await;
+            }
+            Element::Barrier(barrier) => {
+                let checkpoint_id = barrier.checkpoint_id;
+                let snapshot_context = {
+                    let context = self.context.as_ref().unwrap();
+                    context.checkpoint_context(self.operator_id, checkpoint_id, None)
+                };
+                self.checkpoint(snapshot_context).await;
+
+                self.next_runnable
+                    .as_mut()
+                    .unwrap()
+                    .run(Element::Barrier(barrier))
+                    .await;
+            }
+        }
+    }
+
+    async fn close(&mut self) -> anyhow::Result<()> {
+        if let Some(stream_key_by) = self.stream_key_by.as_mut() {
+            stream_key_by.operator_fn.close().await?;
+        }
+        self.stream_keyed_process.operator_fn.close().await?;
+        self.next_runnable.as_mut().unwrap().close().await
+    }
+
+    fn set_next_runnable(&mut self, next_runnable: Option<Box<dyn Runnable>>) {
+        self.next_runnable = next_runnable;
+    }
+
+    async fn checkpoint(&mut self, snapshot_context: FunctionSnapshotContext) {
+        // the timers are checkpointed as the keyed states, so they're redistributed by the key
+        // groups with the other keyed states when the parallelism is changed
+        let handle = match self.timer_service.snapshot(&self.runtime_context) {
+            Ok(_) => {
+                self.runtime_context
+                    .checkpoint(snapshot_context.checkpoint_id)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        // the checkpoint isn't acknowledged if the states aren't persisted, so it's expired
+        let handle = match handle {
+            Ok(handle) => handle,
+            Err(e) => {
+                error!(
+                    "snapshot keyed states on checkpoint({:?}) error. {}",
+                    snapshot_context.checkpoint_id, e
+                );
+                return;
+            }
+        };
+
+        let ck = Checkpoint {
+            operator_id: snapshot_context.operator_id,
+            task_id: snapshot_context.task_id,
+            checkpoint_id: snapshot_context.checkpoint_id,
+            completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
+            handle,
+        };
+        snapshot_context.report(ck).map(|ck| {
+            error!(
+                "{:?} submit checkpoint error. maybe report channel is full, checkpoint: {:?}",
+                snapshot_context.operator_id, ck
+            )
+        });
+    }
+}
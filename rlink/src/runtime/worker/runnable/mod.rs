@@ -2,10 +2,13 @@ use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::core::accumulator;
 use crate::core::checkpoint::FunctionSnapshotContext;
 use crate::core::element::Element;
+use crate::core::env::ExecutionMode;
 use crate::core::properties::SystemProperties;
 use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+use crate::core::state::RuntimeContext;
 use crate::dag::execution_graph::{ExecutionEdge, ExecutionNode};
 use crate::dag::job_graph::{JobEdge, JobNode};
 use crate::dag::metadata::DagMetadata;
@@ -16,6 +19,8 @@ pub mod co_process_runnable;
 pub mod filter_runnable;
 pub mod flat_map_runnable;
 pub mod key_by_runnable;
+pub mod keyed_process_runnable;
+pub mod record_error;
 pub mod reduce_runnable;
 pub mod sink_runnable;
 pub mod source_runnable;
@@ -25,6 +30,8 @@ pub mod window_assigner_runnable;
 pub(crate) use filter_runnable::FilterRunnable;
 pub(crate) use flat_map_runnable::FlatMapRunnable;
 pub(crate) use key_by_runnable::KeyByRunnable;
+pub(crate) use keyed_process_runnable::KeyedProcessRunnable;
+pub(crate) use record_error::RecordErrorHandler;
 pub(crate) use reduce_runnable::ReduceRunnable;
 pub(crate) use sink_runnable::SinkRunnable;
 pub(crate) use source_runnable::SourceRunnable;
@@ -76,10 +83,12 @@ impl RunnableContext {
             application_id: coordinator_manager.application_id.clone(),
             application_properties: coordinator_manager.application_properties.clone(),
             operator_id,
+            operator_name: stream_node.operator_name.clone(),
             task_id: self.task_context.task_descriptor.task_id.clone(),
             checkpoint_id: operator.checkpoint_id,
             completed_checkpoint_id: operator.completed_checkpoint_id,
             checkpoint_handle: operator.checkpoint_handle.clone(),
+            operator_state_handles: operator.operator_state_handles.clone(),
 
             input_schema: stream_node.input_schema.clone(),
             output_schema: stream_node.output_schema.clone(),
@@ -88,6 +97,7 @@ impl RunnableContext {
             children,
 
             task_context: Some(self.task_context.clone()),
+            runtime_context: RuntimeContext::new(),
         }
     }
 
@@ -115,6 +125,22 @@ impl RunnableContext {
             .unwrap_or(default_value)
     }
 
+    pub(crate) fn execution_mode(&self) -> ExecutionMode {
+        self.task_context
+            .cluster_descriptor
+            .coordinator_manager
+            .application_properties
+            .get_execution_mode()
+    }
+
+    pub(crate) fn max_parallelism(&self) -> u16 {
+        self.task_context
+            .cluster_descriptor
+            .coordinator_manager
+            .application_properties
+            .get_max_parallelism()
+    }
+
     #[allow(dead_code)]
     pub(crate) fn parent_parallelism(&self) -> u16 {
         let ps = self.parents_parallelism();
@@ -149,11 +175,26 @@ impl RunnableContext {
         self.dag_metadata().child_jobs(self.job_id())
     }
 
-    #[allow(dead_code)]
     pub(crate) fn stream_node(&self, operator_id: OperatorId) -> &StreamNode {
         self.dag_metadata().stream_node(operator_id).unwrap()
     }
 
+    /// The handler of the records failed by the function of the operator, see `ErrorPolicy`
+    pub(crate) fn record_error_handler(&self, operator_id: OperatorId) -> RecordErrorHandler {
+        let stream_node = self.stream_node(operator_id);
+        let error_policy = stream_node.error_policy.clone();
+        let counter = if error_policy.is_recoverable() {
+            let name = format!("record_errors.{}", stream_node.state_key());
+            Some(accumulator::register_counter(
+                self.task_context.task_descriptor.task_id,
+                name.as_str(),
+            ))
+        } else {
+            None
+        };
+        RecordErrorHandler::new(operator_id, error_policy, counter)
+    }
+
     #[allow(dead_code)]
     pub(crate) fn job_node(&self) -> &JobNode {
         self.dag_metadata().job_node(self.job_id()).unwrap()
@@ -0,0 +1,91 @@
+use crate::core::accumulator::Counter;
+use crate::core::element::Record;
+use crate::core::error::Error;
+use crate::core::error_policy::ErrorPolicy;
+use crate::core::runtime::OperatorId;
+
+/// Apply the `ErrorPolicy` of an operator to the records failed by its function
+pub(crate) struct RecordErrorHandler {
+    operator_id: OperatorId,
+    error_policy: ErrorPolicy,
+    /// the `record_errors.<operator>` accumulator, only for the recoverable policies
+    counter: Option<Counter>,
+}
+
+impl RecordErrorHandler {
+    pub fn new(
+        operator_id: OperatorId,
+        error_policy: ErrorPolicy,
+        counter: Option<Counter>,
+    ) -> Self {
+        RecordErrorHandler {
+            operator_id,
+            error_policy,
+            counter,
+        }
+    }
+
+    /// The `FailJob` handler of the operators created before the runnable is opened
+    pub fn fail_job(operator_id: OperatorId) -> Self {
+        RecordErrorHandler::new(operator_id, ErrorPolicy::FailJob, None)
+    }
+
+    /// Whether the input record is required by `on_error`, so it must be kept by the caller
+    pub fn is_dead_letter(&self) -> bool {
+        matches!(self.error_policy, ErrorPolicy::DeadLetter(_))
+    }
+
+    /// Handle the failed record, returns the dead letter to be emitted if any
+    pub fn on_error(&self, record: Option<Record>, error: &Error) -> Option<Record> {
+        if let Some(counter) = &self.counter {
+            counter.add(1);
+        }
+
+        match &self.error_policy {
+            ErrorPolicy::FailJob => {
+                panic!("{:?} process record error. {}", self.operator_id, error)
+            }
+            ErrorPolicy::SkipAndCount => {
+                warn!("{:?} skip the failed record. {}", self.operator_id, error);
+                None
+            }
+            ErrorPolicy::DeadLetter(output_tag) => {
+                warn!(
+                    "{:?} emit the failed record to the dead letter `{}`. {}",
+                    self.operator_id,
+                    output_tag.name(),
+                    error
+                );
+                record.map(|record| output_tag.output(record))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::{OutputTag, Record};
+    use crate::core::error::Error;
+    use crate::core::error_policy::ErrorPolicy;
+    use crate::core::runtime::OperatorId;
+    use crate::runtime::worker::runnable::record_error::RecordErrorHandler;
+
+    #[test]
+    pub fn record_error_handler_test() {
+        let operator_id = OperatorId(1);
+
+        let skip = RecordErrorHandler::new(operator_id, ErrorPolicy::SkipAndCount, None);
+        let error = Error::from("malformed record");
+        assert_eq!(skip.on_error(Some(Record::new()), &error), None);
+
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64)]);
+        let tag = OutputTag::new("dead_letter", schema);
+        let dead_letter = RecordErrorHandler::new(operator_id, ErrorPolicy::DeadLetter(tag), None);
+        assert!(dead_letter.is_dead_letter());
+        let record = dead_letter
+            .on_error(Some(Record::new()), &Error::from("bad record"))
+            .unwrap();
+        assert_eq!(record.output_tag(), Some("dead_letter"));
+    }
+}
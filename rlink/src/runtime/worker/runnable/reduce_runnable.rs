@@ -1,14 +1,15 @@
-use std::borrow::BorrowMut;
-
 use metrics::Counter;
 
 use crate::core::checkpoint::{Checkpoint, CheckpointHandle, FunctionSnapshotContext};
 use crate::core::element::{Element, Record};
+use crate::core::env::ExecutionMode;
 use crate::core::function::{BaseReduceFunction, KeySelectorFunction};
 use crate::core::operator::DefaultStreamOperator;
 use crate::core::runtime::{CheckpointId, OperatorId, TaskId};
+use crate::core::state::RuntimeContext;
 use crate::core::window::{TWindow, Window};
 use crate::metrics::register_counter;
+use crate::pub_sub::spill::{ExternalSorter, DEFAULT_SPILL_MEMORY_SIZE};
 use crate::runtime::worker::runnable::{Runnable, RunnableContext};
 
 pub(crate) struct ReduceRunnable {
@@ -16,6 +17,7 @@ pub(crate) struct ReduceRunnable {
     task_id: TaskId,
 
     context: Option<RunnableContext>,
+    runtime_context: RuntimeContext,
 
     stream_key_by: Option<DefaultStreamOperator<dyn KeySelectorFunction>>,
     stream_reduce: DefaultStreamOperator<dyn BaseReduceFunction>,
@@ -25,6 +27,10 @@ pub(crate) struct ReduceRunnable {
     limited_watermark_window: Window,
     completed_checkpoint_id: Option<CheckpointId>,
 
+    /// the records sorted with their keys in the `ExecutionMode::Batch`, they're reduced by
+    /// the key at the end of the input
+    batch_sorter: Option<ExternalSorter>,
+
     counter: Counter,
     expire_counter: Counter,
 }
@@ -40,17 +46,85 @@ impl ReduceRunnable {
             operator_id,
             task_id: TaskId::default(),
             context: None,
+            runtime_context: RuntimeContext::new(),
             stream_key_by,
             stream_reduce,
             next_runnable,
             limited_watermark_window: Window::default(),
             completed_checkpoint_id: None,
+            batch_sorter: None,
             counter: Counter::noop(),
             expire_counter: Counter::noop(),
         }
     }
 }
 
+impl ReduceRunnable {
+    async fn get_key(&mut self, record: &mut Record) -> Record {
+        match &self.stream_key_by {
+            Some(stream_key_by) => stream_key_by.operator_fn.get_key(record).await,
+            None => Record::with_capacity(0),
+        }
+    }
+
+    /// The sort-based aggregation of the batch mode, the records are sorted by the key with
+    /// the external sort and reduced key by key. All windows of a key are fired before the next
+    /// key is reduced, so only the state of one key is kept instead of the state of all keys
+    async fn reduce_sorted(&mut self) {
+        // the records after the end are sorted by a new sorter
+        let sorter =
+            ExternalSorter::new(&self.task_id, self.operator_id, DEFAULT_SPILL_MEMORY_SIZE);
+        let mut sorter = match self.batch_sorter.as_mut() {
+            Some(batch_sorter) => std::mem::replace(batch_sorter, sorter),
+            None => return,
+        };
+        info!(
+            "reduce the sorted records of {} spilled runs",
+            sorter.spilled_runs()
+        );
+
+        let mut current_key: Option<Record> = None;
+        while let Some((key, record)) = sorter.pop().expect("merge the sorted records error") {
+            if current_key.as_ref() != Some(&key) {
+                self.fire_all_windows().await;
+                current_key = Some(key.clone());
+            }
+
+            self.runtime_context.set_current_key(&key);
+            let fire_events = self
+                .stream_reduce
+                .operator_fn
+                .as_mut()
+                .reduce(key, record)
+                .await;
+            for fire_event in fire_events {
+                self.next_runnable
+                    .as_mut()
+                    .unwrap()
+                    .run(Element::from(fire_event))
+                    .await;
+            }
+        }
+        self.fire_all_windows().await;
+    }
+
+    async fn fire_all_windows(&mut self) {
+        let drop_events = self
+            .stream_reduce
+            .operator_fn
+            .as_mut()
+            .drop_state(u64::MAX)
+            .await;
+        for drop_event in drop_events {
+            self.next_runnable
+                .as_mut()
+                .unwrap()
+                .run(Element::from(drop_event))
+                .await;
+        }
+    }
+}
+
 #[async_trait]
 impl Runnable for ReduceRunnable {
     async fn open(&mut self, context: &RunnableContext) -> anyhow::Result<()> {
@@ -61,6 +135,8 @@ impl Runnable for ReduceRunnable {
         self.context = Some(context.clone());
 
         let fun_context = context.to_fun_context(self.operator_id);
+        self.runtime_context = fun_context.runtime_context();
+        self.runtime_context.open(&fun_context)?;
         self.stream_reduce.operator_fn.open(&fun_context).await?;
         match self.stream_key_by.as_mut() {
             Some(s) => {
@@ -79,21 +155,44 @@ impl Runnable for ReduceRunnable {
         self.expire_counter =
             register_counter(format!("Reduce_Expire_{}", fn_name), self.task_id.to_tags());
 
+        if context.execution_mode() == ExecutionMode::Batch {
+            self.batch_sorter = Some(ExternalSorter::new(
+                &self.task_id,
+                self.operator_id,
+                DEFAULT_SPILL_MEMORY_SIZE,
+            ));
+        }
+
         info!("ReduceRunnable Opened. task_id={:?}", self.task_id);
         Ok(())
     }
 
     async fn run(&mut self, element: Element) {
         match element {
+            Element::Record(mut record) if self.batch_sorter.is_some() => {
+                let key = self.get_key(&mut record).await;
+                self.batch_sorter
+                    .as_mut()
+                    .unwrap()
+                    .push(key, record)
+                    .expect("spill the sorted records error");
+                self.counter.increment(1);
+            }
             Element::Record(mut record) => {
-                // Record expiration check
+                // Record expiration check, the windows are kept for the allowed lateness
                 let min_window_timestamp = self.limited_watermark_window.min_timestamp();
+                let allowed_lateness = self.stream_reduce.operator_fn.allowed_lateness();
                 let acceptable = record
                     .max_location_window()
-                    .map(|window| window.min_timestamp() >= min_window_timestamp)
+                    .map(|window| window.min_timestamp() + allowed_lateness >= min_window_timestamp)
                     .unwrap_or(true);
                 if !acceptable {
                     self.expire_counter.increment(1);
+                    self.stream_reduce
+                        .operator_fn
+                        .as_mut()
+                        .late_element(record)
+                        .await;
                     // let n = self.expire_counter.increment(1);
                     // if n & 1048575 == 1 {
                     //     error!(
@@ -105,21 +204,28 @@ impl Runnable for ReduceRunnable {
                     return;
                 }
 
-                let key = match &self.stream_key_by {
-                    Some(stream_key_by) => {
-                        stream_key_by.operator_fn.get_key(record.borrow_mut()).await
-                    }
-                    None => Record::with_capacity(0),
-                };
+                let key = self.get_key(&mut record).await;
+                self.runtime_context.set_current_key(&key);
 
-                self.stream_reduce
+                let fire_events = self
+                    .stream_reduce
                     .operator_fn
                     .as_mut()
                     .reduce(key, record)
                     .await;
 
                 self.counter.increment(1);
+
+                for fire_event in fire_events {
+                    self.next_runnable
+                        .as_mut()
+                        .unwrap()
+                        .run(Element::from(fire_event))
+                        .await;
+                }
             }
+            // the windows are fired by the sorted aggregation in the batch mode
+            Element::Watermark(_watermark) if self.batch_sorter.is_some() => {}
             Element::Watermark(watermark) => match watermark.min_location_windows() {
                 Some(min_watermark_window) => {
                     self.limited_watermark_window = min_watermark_window.clone();
@@ -161,6 +267,10 @@ impl Runnable for ReduceRunnable {
                     .await;
             }
             Element::StreamStatus(stream_status) => {
+                if stream_status.end && self.batch_sorter.is_some() {
+                    self.reduce_sorted().await;
+                }
+
                 self.next_runnable
                     .as_mut()
                     .unwrap()
@@ -194,7 +304,30 @@ impl Runnable for ReduceRunnable {
             .await
             .unwrap_or(CheckpointHandle::default());
 
-        let fn_handle = ReduceCheckpointHandle::from(handle.handle.as_str());
+        // the rocksdb keeping the window states and the keyed states is checkpointed once both
+        // of them are written, the checkpoint isn't acknowledged if it isn't persisted
+        let keyed_states = match self
+            .runtime_context
+            .checkpoint(snapshot_context.checkpoint_id)
+            .await
+        {
+            Ok(keyed_states) => keyed_states,
+            Err(e) => {
+                error!(
+                    "snapshot the keyed states on checkpoint({:?}) error. {}",
+                    snapshot_context.checkpoint_id, e
+                );
+                return;
+            }
+        };
+
+        let mut fn_handle = ReduceCheckpointHandle::from(handle.handle.as_str());
+        if let Err(e) = fn_handle.set_keyed_states(&keyed_states) {
+            panic!(
+                "snapshot the keyed states on checkpoint({:?}) error. {}",
+                snapshot_context.checkpoint_id, e
+            );
+        }
         self.completed_checkpoint_id = fn_handle.completed_checkpoint_id;
 
         let ck = Checkpoint {
@@ -202,8 +335,9 @@ impl Runnable for ReduceRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: self.completed_checkpoint_id,
+            alignment_duration: 0,
             handle: CheckpointHandle {
-                handle: fn_handle.to_windows_string(),
+                handle: fn_handle.to_string(),
             },
         };
         snapshot_context.report(ck).map(|ck| {
@@ -221,6 +355,10 @@ pub(crate) struct ReduceCheckpointHandle {
     completed_checkpoint_id: Option<CheckpointId>,
     #[serde(rename = "windows")]
     current_windows: Vec<Window>,
+    /// the `RuntimeContext::snapshot` of the task, it's flattened so the handle is read as the
+    /// keyed states by the `RuntimeContext::restore_from` and the `state_processor`
+    #[serde(flatten)]
+    keyed_states: serde_json::Map<String, serde_json::Value>,
 }
 
 impl ReduceCheckpointHandle {
@@ -231,11 +369,13 @@ impl ReduceCheckpointHandle {
         ReduceCheckpointHandle {
             completed_checkpoint_id,
             current_windows,
+            keyed_states: serde_json::Map::new(),
         }
     }
 
-    pub fn to_windows_string(&self) -> String {
-        serde_json::to_string(&self.current_windows).unwrap()
+    pub fn set_keyed_states(&mut self, keyed_states: &CheckpointHandle) -> anyhow::Result<()> {
+        self.keyed_states = serde_json::from_str(keyed_states.handle.as_str())?;
+        Ok(())
     }
 
     pub fn into_windows(self) -> Vec<Window> {
@@ -255,12 +395,229 @@ impl<'a> From<&'a str> for ReduceCheckpointHandle {
             ReduceCheckpointHandle::default()
         } else if handle.starts_with("[") {
             let windows: Vec<Window> = serde_json::from_str(handle).unwrap();
-            ReduceCheckpointHandle {
-                completed_checkpoint_id: None,
-                current_windows: windows,
-            }
+            ReduceCheckpointHandle::new(None, windows)
         } else {
             serde_json::from_str(handle).unwrap()
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+    use crate::core::element::{Element, FnSchema, Record};
+    use crate::core::function::{BaseReduceFunction, Context, KeySelectorFunction, NamedFunction};
+    use crate::core::operator::{DefaultStreamOperator, FunctionCreator};
+    use crate::core::runtime::OperatorId;
+    use crate::core::state::{KeyedStates, RuntimeContext, ValueStateDescriptor};
+    use crate::pub_sub::spill::ExternalSorter;
+    use crate::runtime::worker::runnable::reduce_runnable::{
+        ReduceCheckpointHandle, ReduceRunnable,
+    };
+    use crate::runtime::worker::runnable::{Runnable, RunnableContext};
+
+    /// Select the first field as the key
+    struct FirstByteKeySelector;
+
+    impl NamedFunction for FirstByteKeySelector {
+        fn name(&self) -> &str {
+            "FirstByteKeySelector"
+        }
+    }
+
+    #[async_trait]
+    impl CheckpointFunction for FirstByteKeySelector {
+        async fn initialize_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+            _handle: &Option<CheckpointHandle>,
+        ) {
+        }
+
+        async fn snapshot_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+        ) -> Option<CheckpointHandle> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl KeySelectorFunction for FirstByteKeySelector {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        async fn get_key(&self, record: &mut Record) -> Record {
+            Record::from_values(&record.values.as_slice()[..1], 0)
+        }
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn key_schema(&self, input_schema: FnSchema) -> FnSchema {
+            input_schema
+        }
+    }
+
+    /// Log the reduced records and the fired windows
+    struct LogReduceFunction {
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl NamedFunction for LogReduceFunction {
+        fn name(&self) -> &str {
+            "LogReduceFunction"
+        }
+    }
+
+    #[async_trait]
+    impl CheckpointFunction for LogReduceFunction {
+        async fn initialize_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+            _handle: &Option<CheckpointHandle>,
+        ) {
+        }
+
+        async fn snapshot_state(
+            &mut self,
+            _context: &FunctionSnapshotContext,
+        ) -> Option<CheckpointHandle> {
+            None
+        }
+    }
+
+    #[async_trait]
+    impl BaseReduceFunction for LogReduceFunction {
+        async fn open(&mut self, _context: &Context) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        async fn reduce(&mut self, key: Record, record: Record) -> Vec<Record> {
+            self.log.lock().unwrap().push(format!(
+                "reduce {} {}",
+                key.values.as_slice()[0],
+                record.timestamp()
+            ));
+            vec![]
+        }
+
+        async fn drop_state(&mut self, _watermark_timestamp: u64) -> Vec<Record> {
+            self.log.lock().unwrap().push("fire".to_string());
+            vec![]
+        }
+
+        fn allowed_lateness(&self) -> u64 {
+            0
+        }
+
+        async fn late_element(&mut self, _record: Record) {}
+
+        async fn close(&mut self) -> crate::core::Result<()> {
+            Ok(())
+        }
+
+        fn value_schema(&self, key_schema: FnSchema) -> FnSchema {
+            key_schema
+        }
+    }
+
+    struct NoopRunnable;
+
+    #[async_trait]
+    impl Runnable for NoopRunnable {
+        async fn open(&mut self, _context: &RunnableContext) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn run(&mut self, _element: Element) {}
+
+        async fn close(&mut self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn set_next_runnable(&mut self, _next_runnable: Option<Box<dyn Runnable>>) {}
+
+        async fn checkpoint(&mut self, _snapshot_context: FunctionSnapshotContext) {}
+    }
+
+    #[tokio::test]
+    pub async fn reduce_sorted_spill_test() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runnable = ReduceRunnable::new(
+            OperatorId(1),
+            Some(DefaultStreamOperator::<dyn KeySelectorFunction>::new(
+                1,
+                FunctionCreator::User,
+                Box::new(FirstByteKeySelector),
+            )),
+            DefaultStreamOperator::<dyn BaseReduceFunction>::new(
+                1,
+                FunctionCreator::User,
+                Box::new(LogReduceFunction { log: log.clone() }),
+            ),
+            Some(Box::new(NoopRunnable)),
+        );
+        // the small memory spills the runs of 2 records
+        runnable.batch_sorter = Some(ExternalSorter::new(
+            &runnable.task_id,
+            runnable.operator_id,
+            60,
+        ));
+
+        for (seq, key) in [2u8, 1, 2, 1, 1].iter().enumerate() {
+            let record = Record::from_values(&[*key, seq as u8], seq as u64);
+            runnable.run(Element::Record(record)).await;
+        }
+        assert!(runnable.batch_sorter.as_ref().unwrap().spilled_runs() > 1);
+
+        runnable.run(Element::new_stream_status(10, true)).await;
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "fire",
+                "reduce 1 1",
+                "reduce 1 3",
+                "reduce 1 4",
+                "fire",
+                "reduce 2 0",
+                "reduce 2 2",
+                "fire"
+            ]
+        );
+    }
+
+    #[test]
+    pub fn reduce_checkpoint_handle_test() {
+        let runtime_context = RuntimeContext::new();
+        let value_state = runtime_context.value_state(&ValueStateDescriptor::<u64>::new("v"));
+        runtime_context.set_current_key(&Record::new());
+        value_state.update(7).unwrap();
+
+        let mut handle = ReduceCheckpointHandle::from("[]");
+        handle
+            .set_keyed_states(&runtime_context.snapshot().unwrap())
+            .unwrap();
+        let handle = handle.to_string();
+
+        let restored = RuntimeContext::new();
+        restored
+            .restore(&CheckpointHandle {
+                handle: handle.clone(),
+            })
+            .unwrap();
+        let value_state = restored.value_state(&ValueStateDescriptor::<u64>::new("v"));
+        restored.set_current_key(&Record::new());
+        assert_eq!(value_state.value().unwrap(), Some(7));
+
+        let handle = ReduceCheckpointHandle::from(handle.as_str());
+        assert!(handle.into_windows().is_empty());
+        let handle = ReduceCheckpointHandle::new(None, vec![]).to_string();
+        let keyed_states = KeyedStates::from_handle(&CheckpointHandle { handle }).unwrap();
+        assert!(keyed_states.state_names().is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use crate::core::operator::{DefaultStreamOperator, FunctionCreator, TStreamOpera
 use crate::core::runtime::{OperatorId, TaskId};
 use crate::dag::job_graph::JobEdge;
 use crate::metrics::register_counter;
-use crate::runtime::worker::runnable::{Runnable, RunnableContext};
+use crate::runtime::worker::runnable::{RecordErrorHandler, Runnable, RunnableContext};
 
 pub(crate) struct SinkRunnable {
     operator_id: OperatorId,
@@ -18,6 +18,7 @@ pub(crate) struct SinkRunnable {
     context: Option<RunnableContext>,
 
     stream_sink: DefaultStreamOperator<dyn OutputFormat>,
+    record_error: RecordErrorHandler,
 
     counter: Counter,
 }
@@ -34,6 +35,7 @@ impl SinkRunnable {
             child_parallelism: 0,
             context: None,
             stream_sink,
+            record_error: RecordErrorHandler::fail_job(operator_id),
             counter: Counter::noop(),
         }
     }
@@ -45,6 +47,7 @@ impl Runnable for SinkRunnable {
         self.context = Some(context.clone());
 
         self.task_id = context.task_context.task_descriptor.task_id;
+        self.record_error = context.record_error_handler(self.operator_id);
         let child_jobs = context.child_jobs();
         self.child_parallelism = if child_jobs.len() > 1 {
             unimplemented!()
@@ -77,10 +80,16 @@ impl Runnable for SinkRunnable {
     async fn run(&mut self, element: Element) {
         match element {
             Element::Record(record) => {
-                self.stream_sink
+                let result = self
+                    .stream_sink
                     .operator_fn
-                    .write_element(Element::Record(record))
+                    .try_write_element(Element::Record(record))
                     .await;
+                if let Err(e) = result {
+                    // the dead letter isn't supported by the sinks
+                    self.record_error.on_error(None, &e);
+                    return;
+                }
 
                 self.counter.increment(1);
             }
@@ -144,6 +153,7 @@ impl Runnable for SinkRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
@@ -13,15 +13,18 @@ use crate::channel::sender::ChannelSender;
 use crate::channel::utils::ChannelStream;
 use crate::core::checkpoint::{Checkpoint, CheckpointHandle, FunctionSnapshotContext};
 use crate::core::element::{Element, StreamStatus, Watermark};
+use crate::core::env::ExecutionMode;
 use crate::core::function::{ElementStream, InputFormat};
 use crate::core::operator::{DefaultStreamOperator, FunctionCreator, TStreamOperator};
 use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
 use crate::core::watermark::MAX_WATERMARK;
+use crate::dag::execution_graph::ExecutionEdge;
 use crate::metrics::register_counter;
 use crate::runtime::timer::TimerChannel;
 use crate::runtime::worker::runnable::{Runnable, RunnableContext};
 use crate::runtime::worker::WorkerTaskContext;
 use crate::runtime::HeartbeatItem;
+use crate::utils::date_time::current_timestamp_millis;
 
 pub(crate) struct SourceRunnable {
     operator_id: OperatorId,
@@ -29,6 +32,9 @@ pub(crate) struct SourceRunnable {
 
     task_id: TaskId,
     daemon_task: bool,
+    /// the task of a stage in the `ExecutionMode::Batch` opens the operators after its blocking
+    /// input is complete, i.e. the stages before it have finished
+    deferred_open: bool,
 
     stream_source: DefaultStreamOperator<dyn InputFormat>,
     next_runnable: Option<Box<dyn Runnable>>,
@@ -38,6 +44,8 @@ pub(crate) struct SourceRunnable {
 
     waiting_end_flags: usize,
     barrier_alignment: AlignManager,
+    /// the alignment duration of the latest checkpoint
+    alignment_duration: u64,
     stream_status_alignment: AlignManager,
     watermark_manager: WatermarkManager,
 
@@ -55,6 +63,7 @@ impl SourceRunnable {
             context: None,
             task_id: TaskId::default(),
             daemon_task: false,
+            deferred_open: false,
 
             stream_source,
             next_runnable,
@@ -64,6 +73,7 @@ impl SourceRunnable {
 
             waiting_end_flags: 0,
             barrier_alignment: AlignManager::default(),
+            alignment_duration: 0,
             stream_status_alignment: AlignManager::default(),
             watermark_manager: WatermarkManager::default(),
             counter: Counter::noop(),
@@ -86,10 +96,15 @@ impl SourceRunnable {
                     break;
                 }
 
-                if daemon_task && task_context.get_coordinator_status().is_terminating() {
+                let coordinator_status = task_context.get_coordinator_status();
+                if daemon_task && coordinator_status.is_terminating() {
                     info!("[{}] daemon source stop by coordinator stop", op_name);
                     break;
                 }
+                if coordinator_status.is_draining() {
+                    info!("[{}] source stop by coordinator draining", op_name);
+                    break;
+                }
             }
 
             running.store(false, Ordering::Relaxed);
@@ -137,10 +152,22 @@ impl SourceRunnable {
         let mut checkpoint_timer = self.checkpoint_timer.take().unwrap();
         tokio::spawn(async move {
             while let Some(window_time) = checkpoint_timer.recv().await {
-                let barrier = Element::new_barrier(CheckpointId(window_time));
-                if let Err(_e) = sender.send(barrier).await {
-                    error!("[{}] channel has closed", op_name);
-                    break;
+                let checkpoint_id = CheckpointId(window_time);
+                if task_context
+                    .checkpoint_publish()
+                    .trigger(checkpoint_id)
+                    .await
+                {
+                    let barrier = Element::new_barrier(checkpoint_id);
+                    if let Err(_e) = sender.send(barrier).await {
+                        error!("[{}] channel has closed", op_name);
+                        break;
+                    }
+                } else {
+                    debug!(
+                        "[{}] checkpoint_id={:?} isn't triggered",
+                        op_name, checkpoint_id
+                    );
                 }
 
                 let running = running.load(Ordering::Relaxed);
@@ -159,10 +186,12 @@ impl SourceRunnable {
         self.context.as_ref().unwrap().task_context.clone()
     }
 
-    async fn report_end_status(&self) {
+    /// Report the task terminated, the `checkpoint_id` is the checkpoint of the drained state
+    async fn report_end_status(&self, checkpoint_id: Option<CheckpointId>) {
         let heartbeat_publish = self.task_context().heartbeat_publish();
         let status = HeartbeatItem::TaskEnd {
             task_id: self.task_id,
+            checkpoint_id,
         };
         heartbeat_publish.report(status).await;
     }
@@ -176,8 +205,18 @@ impl Runnable for SourceRunnable {
         self.task_id = context.task_context.task_descriptor.task_id;
         self.daemon_task = context.task_context.task_descriptor.daemon;
 
+        // the stage is scheduled after the stages of its network input in the batch mode
+        self.deferred_open = context.execution_mode() == ExecutionMode::Batch
+            && matches!(self.stream_source.fn_creator(), FunctionCreator::System)
+            && context
+                .parent_executions(&self.task_id)
+                .iter()
+                .any(|(_node, edge)| **edge == ExecutionEdge::Network);
+
         // first open next, then open self
-        self.next_runnable.as_mut().unwrap().open(context).await?;
+        if !self.deferred_open {
+            self.next_runnable.as_mut().unwrap().open(context).await?;
+        }
 
         let input_split = context.task_context.task_descriptor.input_split.clone();
         let fun_context = context.to_fun_context(self.operator_id);
@@ -192,13 +231,16 @@ impl Runnable for SourceRunnable {
                 .expect("register StreamStatus timer error");
             self.stream_status_timer = Some(stream_status_timer);
 
-            let checkpoint_period = context.checkpoint_interval(Duration::from_secs(30));
-            let checkpoint_timer = context
-                .task_context
-                .window_timer
-                .register("Checkpoint Event Timer", checkpoint_period)
-                .expect("register Checkpoint timer error");
-            self.checkpoint_timer = Some(checkpoint_timer);
+            // no checkpoint in the batch mode, the failed job is executed again
+            if context.execution_mode() == ExecutionMode::Streaming {
+                let checkpoint_period = context.checkpoint_interval(Duration::from_secs(30));
+                let checkpoint_timer = context
+                    .task_context
+                    .window_timer
+                    .register("Checkpoint Event Timer", checkpoint_period)
+                    .expect("register Checkpoint timer error");
+                self.checkpoint_timer = Some(checkpoint_timer);
+            }
         }
 
         let parent_execution_size = context.parent_executions(&self.task_id).len();
@@ -264,8 +306,10 @@ impl Runnable for SourceRunnable {
 
                 self.poll_stream_status(task_context.clone(), sender.clone(), running.clone())
                     .await;
-                self.poll_checkpoint(task_context.clone(), sender.clone(), running.clone())
-                    .await;
+                if self.checkpoint_timer.is_some() {
+                    self.poll_checkpoint(task_context.clone(), sender.clone(), running.clone())
+                        .await;
+                }
 
                 let stream: Pin<Box<dyn ElementStream + Send>> =
                     Box::pin(ChannelStream::new(receiver));
@@ -274,7 +318,25 @@ impl Runnable for SourceRunnable {
             FunctionCreator::System => self.stream_source.operator_fn.element_stream().await,
         };
 
+        // the blocking input yields the first element once the stages before have finished
+        let mut first_element = None;
+        if self.deferred_open {
+            first_element = element_stream.next().await;
+            info!("the blocking input is complete, open the operators of the stage");
+
+            let context = self.context.clone().unwrap();
+            self.next_runnable
+                .as_mut()
+                .unwrap()
+                .open(&context)
+                .await
+                .expect("open the operators of the stage error");
+        }
+        let mut element_stream = futures::stream::iter(first_element).chain(element_stream);
+
         let mut end_flags = 0;
+        // when draining, the task ends after the next checkpoint of the drained state
+        let mut end_pending = false;
         while let Some(element) = element_stream.next().await {
             match element {
                 Element::Record(_) => {
@@ -285,6 +347,7 @@ impl Runnable for SourceRunnable {
                     let is_barrier_align = self.barrier_alignment.apply(barrier.checkpoint_id.0);
                     if is_barrier_align {
                         debug!("barrier align and checkpoint");
+                        self.alignment_duration = self.barrier_alignment.alignment_duration();
                         let checkpoint_id = barrier.checkpoint_id;
                         let snapshot_context = {
                             let context = self.context.as_ref().unwrap();
@@ -297,6 +360,15 @@ impl Runnable for SourceRunnable {
                             .unwrap()
                             .run(Element::Barrier(barrier))
                             .await;
+
+                        if end_pending {
+                            info!(
+                                "drained state checkpointed, checkpoint_id={:?}",
+                                checkpoint_id
+                            );
+                            end_pending = false;
+                            self.report_end_status(Some(checkpoint_id)).await;
+                        }
                     }
                 }
                 Element::Watermark(watermark) => match self.watermark_manager.apply(watermark) {
@@ -339,7 +411,11 @@ impl Runnable for SourceRunnable {
 
                     if parent_job_terminated {
                         info!("all parents job stop on stream_status event");
-                        self.report_end_status().await;
+                        if self.task_context().get_coordinator_status().is_draining() {
+                            end_pending = true;
+                        } else {
+                            self.report_end_status(None).await;
+                        }
                     }
                 }
             }
@@ -371,6 +447,7 @@ impl Runnable for SourceRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: self.alignment_duration,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
@@ -388,6 +465,8 @@ struct AlignManager {
 
     batch_id: u64,
     reached_size: usize,
+    /// the time in millis the first event of the batch reached
+    first_reached_timestamp: u64,
 }
 
 impl AlignManager {
@@ -396,6 +475,7 @@ impl AlignManager {
             parent_execution_size,
             batch_id: 0,
             reached_size: 0,
+            first_reached_timestamp: 0,
         }
     }
 
@@ -416,6 +496,7 @@ impl AlignManager {
         } else if self.batch_id < batch_id {
             self.batch_id = batch_id;
             self.reached_size = 1;
+            self.first_reached_timestamp = current_timestamp_millis();
 
             self.reached_size == self.parent_execution_size
         } else {
@@ -426,6 +507,14 @@ impl AlignManager {
             false
         }
     }
+
+    /// The duration in millis since the first event of the current batch reached
+    pub fn alignment_duration(&self) -> u64 {
+        if self.first_reached_timestamp == 0 {
+            return 0;
+        }
+        current_timestamp_millis().saturating_sub(self.first_reached_timestamp)
+    }
 }
 
 #[derive(Debug)]
@@ -445,7 +534,9 @@ impl ParentWatermark {
     pub fn update_watermark(&mut self, watermark: Watermark) {
         match &self.latest_watermark {
             Some(w) => {
-                if watermark.timestamp > MAX_WATERMARK.timestamp {
+                if watermark.timestamp > MAX_WATERMARK.timestamp
+                    || w.timestamp > MAX_WATERMARK.timestamp
+                {
                     // special `Watermark`, don't need to compare, just assign
                     self.latest_watermark = Some(watermark);
                 } else if watermark.timestamp >= w.timestamp {
@@ -513,7 +604,7 @@ impl WatermarkManager {
         self.min_watermark()
     }
 
-    /// find the min watermark
+    /// find the min watermark, `None` if any task hasn't reached or all tasks are idle
     pub fn min_watermark(&self) -> Option<Watermark> {
         let mut min_watermark: Option<&Watermark> = None;
         for (_job_id, watermarks) in &self.reached_watermarks {
@@ -524,6 +615,8 @@ impl WatermarkManager {
                 }
 
                 match &p_watermark.latest_watermark {
+                    // the idle task is excluded
+                    Some(watermark) if watermark.is_idle() => {}
                     Some(watermark) => {
                         if let Some(w) = min_watermark {
                             if watermark.timestamp < w.timestamp {
@@ -576,6 +669,7 @@ mod tests {
 
     use crate::core::element::{StreamStatus, Watermark};
     use crate::core::runtime::{ChannelKey, JobId, TaskId};
+    use crate::core::watermark::IDLE_WATERMARK;
     use crate::runtime::worker::runnable::source_runnable::WatermarkManager;
 
     fn gen_watermark(timestamp: u64, job_id: u32, task_number: u16, num_tasks: u16) -> Watermark {
@@ -635,5 +729,19 @@ mod tests {
             let w = watermark_manager.apply(watermark);
             assert_eq!(w.unwrap().timestamp, 9);
         }
+
+        {
+            let watermark = gen_watermark(IDLE_WATERMARK.timestamp, 1, 1, 2);
+            let w = watermark_manager.apply(watermark);
+            assert_eq!(w.unwrap().timestamp, 10);
+
+            let watermark = gen_watermark(IDLE_WATERMARK.timestamp, 1, 0, 2);
+            let w = watermark_manager.apply(watermark);
+            assert_eq!(w, None);
+
+            let watermark = gen_watermark(12, 1, 1, 2);
+            let w = watermark_manager.apply(watermark);
+            assert_eq!(w.unwrap().timestamp, 12);
+        }
     }
 }
@@ -1,23 +1,133 @@
 use std::borrow::BorrowMut;
+use std::collections::HashMap;
 
 use metrics::{Counter, Gauge};
 
 use crate::core::checkpoint::{Checkpoint, CheckpointHandle, FunctionSnapshotContext};
-use crate::core::element::Element;
+use crate::core::element::{Element, Record};
 use crate::core::operator::DefaultStreamOperator;
 use crate::core::runtime::{OperatorId, TaskId};
 use crate::core::watermark::{
-    TimestampAssigner, Watermark, WatermarkGenerator, WatermarkStrategy, MAX_WATERMARK,
-    MIN_WATERMARK,
+    TimestampAssigner, Watermark, WatermarkGenerator, WatermarkStrategy, IDLE_WATERMARK,
+    MAX_WATERMARK, MIN_WATERMARK,
 };
+use crate::functions::watermark::watermark_alignment::{remove_splits, update_split_watermarks};
+use crate::functions::watermark::WatermarkAlignment;
 use crate::metrics::{register_counter, register_gauge};
 use crate::runtime::worker::runnable::{Runnable, RunnableContext};
 
+/// The watermark generators of the source splits, the minimum of the split watermarks is
+/// emitted, so a slow split doesn't incorrectly advance the event time.
+///
+/// The records without split, and all records if the strategy can't create generators per
+/// split, share the generator created by `WatermarkStrategy::create_watermark_generator`
+struct SplitWatermarkGenerators {
+    generators: HashMap<Option<u32>, SplitWatermarkGenerator>,
+    shared_generator: Option<Box<dyn WatermarkGenerator>>,
+    split_supported: bool,
+}
+
+struct SplitWatermarkGenerator {
+    generator: Box<dyn WatermarkGenerator>,
+    watermark: Watermark,
+}
+
+impl SplitWatermarkGenerators {
+    fn new(shared_generator: Box<dyn WatermarkGenerator>) -> Self {
+        SplitWatermarkGenerators {
+            generators: HashMap::new(),
+            shared_generator: Some(shared_generator),
+            split_supported: true,
+        }
+    }
+
+    fn generator(
+        &mut self,
+        split: Option<u32>,
+        watermark_strategy: &mut dyn WatermarkStrategy,
+    ) -> &mut SplitWatermarkGenerator {
+        let split = if self.split_supported { split } else { None };
+        if !self.generators.contains_key(&split) {
+            let generator = match split {
+                Some(_) => watermark_strategy.create_split_watermark_generator(),
+                None => self.shared_generator.take(),
+            };
+            match generator {
+                Some(generator) => {
+                    let generator = SplitWatermarkGenerator {
+                        generator,
+                        watermark: MIN_WATERMARK,
+                    };
+                    self.generators.insert(split, generator);
+                }
+                None => {
+                    self.split_supported = false;
+                    return self.generator(None, watermark_strategy);
+                }
+            }
+        }
+        self.generators.get_mut(&split).unwrap()
+    }
+
+    /// The minimum of the split watermarks if the watermark of the record's split is updated
+    fn on_event(
+        &mut self,
+        record: &mut Record,
+        event_timestamp: u64,
+        watermark_strategy: &mut dyn WatermarkStrategy,
+    ) -> Option<Watermark> {
+        let generator = self.generator(record.split, watermark_strategy);
+        let watermark = generator.generator.on_event(record, event_timestamp)?;
+        generator.watermark = watermark;
+
+        Some(self.min_watermark())
+    }
+
+    fn on_periodic_emit(&mut self) -> Watermark {
+        if self.generators.is_empty() {
+            if let Some(generator) = self.shared_generator.as_mut() {
+                return generator.on_periodic_emit().unwrap_or(MIN_WATERMARK);
+            }
+        }
+
+        // the punctuated generator emits nothing periodically, keep the latest watermark
+        for generator in self.generators.values_mut() {
+            if let Some(watermark) = generator.generator.on_periodic_emit() {
+                generator.watermark = watermark;
+            }
+        }
+        self.min_watermark()
+    }
+
+    fn split_watermarks(&self) -> Vec<(u32, Watermark)> {
+        self.generators
+            .iter()
+            .filter_map(|(split, generator)| split.map(|split| (split, generator.watermark)))
+            .collect()
+    }
+
+    /// The minimum watermark of the splits except the idle ones, `IDLE_WATERMARK` if all
+    /// splits are idle
+    fn min_watermark(&self) -> Watermark {
+        if self.generators.is_empty() {
+            return MIN_WATERMARK;
+        }
+
+        self.generators
+            .values()
+            .map(|generator| generator.watermark)
+            .filter(|watermark| watermark.timestamp != IDLE_WATERMARK.timestamp)
+            .min_by_key(|watermark| watermark.timestamp)
+            .unwrap_or(IDLE_WATERMARK)
+    }
+}
+
 pub(crate) struct WatermarkAssignerRunnable {
     operator_id: OperatorId,
     task_id: TaskId,
 
-    watermark_generator: Box<dyn WatermarkGenerator>,
+    watermark_generators: SplitWatermarkGenerators,
+    watermark_alignment: Option<WatermarkAlignment>,
     timestamp_assigner: Box<dyn TimestampAssigner>,
     watermark_strategy: DefaultStreamOperator<dyn WatermarkStrategy>,
 
@@ -41,7 +151,10 @@ impl WatermarkAssignerRunnable {
         WatermarkAssignerRunnable {
             operator_id,
             task_id: TaskId::default(),
-            watermark_generator: watermark_strategy.operator_fn.create_watermark_generator(),
+            watermark_generators: SplitWatermarkGenerators::new(
+                watermark_strategy.operator_fn.create_watermark_generator(),
+            ),
+            watermark_alignment: watermark_strategy.operator_fn.watermark_alignment(),
             timestamp_assigner: watermark_strategy.operator_fn.create_timestamp_assigner(),
             watermark_strategy,
             next_runnable,
@@ -53,7 +166,10 @@ impl WatermarkAssignerRunnable {
     }
 
     fn update_watermark_progress(&mut self, watermark: Watermark) {
-        if watermark.timestamp > MAX_WATERMARK.timestamp {
+        // the watermark never goes back, eg: a new split starts from the `MIN_WATERMARK`
+        if watermark.timestamp > MAX_WATERMARK.timestamp
+            || watermark.timestamp < self.watermark.timestamp
+        {
             return;
         }
 
@@ -92,9 +208,11 @@ impl Runnable for WatermarkAssignerRunnable {
                 let timestamp = self.timestamp_assigner.extract_timestamp(record, 0);
                 record.timestamp = timestamp;
 
-                let watermark = self
-                    .watermark_generator
-                    .on_event(record.borrow_mut(), timestamp);
+                let watermark = self.watermark_generators.on_event(
+                    record.borrow_mut(),
+                    timestamp,
+                    self.watermark_strategy.operator_fn.as_mut(),
+                );
 
                 if record.timestamp < self.watermark.timestamp {
                     self.expire_counter.increment(1);
@@ -131,13 +249,20 @@ impl Runnable for WatermarkAssignerRunnable {
                         .run(watermark_ele)
                         .await;
                 } else {
-                    let watermark = self
-                        .watermark_generator
-                        .on_periodic_emit()
-                        .unwrap_or(MIN_WATERMARK.clone());
-                    self.update_watermark_progress(watermark);
+                    let watermark = self.watermark_generators.on_periodic_emit();
+                    if let Some(watermark_alignment) = &self.watermark_alignment {
+                        let split_watermarks = self.watermark_generators.split_watermarks();
+                        update_split_watermarks(watermark_alignment, split_watermarks);
+                    }
 
-                    let watermark_ele = Element::new_watermark(self.watermark.timestamp);
+                    let watermark_ele = if watermark.timestamp == IDLE_WATERMARK.timestamp {
+                        // all splits are idle, the downstream excludes the task from the
+                        // min-watermark calculation
+                        Element::new_watermark(IDLE_WATERMARK.timestamp)
+                    } else {
+                        self.update_watermark_progress(watermark);
+                        Element::new_watermark(self.watermark.timestamp)
+                    };
                     self.next_runnable
                         .as_mut()
                         .unwrap()
@@ -166,6 +291,16 @@ impl Runnable for WatermarkAssignerRunnable {
     }
 
     async fn close(&mut self) -> anyhow::Result<()> {
+        if self.watermark_alignment.is_some() {
+            let splits = self
+                .watermark_generators
+                .split_watermarks()
+                .into_iter()
+                .map(|(split, _)| split)
+                .collect();
+            remove_splits(splits);
+        }
+
         self.next_runnable.as_mut().unwrap().close().await
     }
 
@@ -186,6 +321,7 @@ impl Runnable for WatermarkAssignerRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
@@ -196,3 +332,79 @@ impl Runnable for WatermarkAssignerRunnable {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::core::element::Record;
+    use crate::core::watermark::{WatermarkStrategy, IDLE_WATERMARK};
+    use crate::functions::watermark::{BoundedOutOfOrdernessWatermarks, DefaultWatermarkStrategy};
+    use crate::runtime::worker::runnable::watermark_assigner_runnable::SplitWatermarkGenerators;
+
+    fn split_record(split: u32) -> Record {
+        let mut record = Record::new();
+        record.set_split(split);
+        record
+    }
+
+    fn on_events(
+        generators: &mut SplitWatermarkGenerators,
+        strategy: &mut DefaultWatermarkStrategy,
+        events: &[(u32, u64)],
+    ) {
+        for (split, timestamp) in events {
+            let mut record = split_record(*split);
+            generators.on_event(&mut record, *timestamp, strategy);
+        }
+    }
+
+    #[test]
+    pub fn split_watermark_test() {
+        let mut strategy =
+            DefaultWatermarkStrategy::new().for_bounded_out_of_orderness(Duration::from_millis(0));
+        let mut generators = SplitWatermarkGenerators::new(strategy.create_watermark_generator());
+
+        on_events(&mut generators, &mut strategy, &[(1, 100), (2, 50)]);
+        assert_eq!(generators.on_periodic_emit().timestamp, 49);
+
+        on_events(&mut generators, &mut strategy, &[(2, 200)]);
+        assert_eq!(generators.on_periodic_emit().timestamp, 99);
+
+        // the idle split is excluded
+        let mut strategy = DefaultWatermarkStrategy::new()
+            .for_bounded_out_of_orderness(Duration::from_millis(0))
+            .with_idleness(Duration::from_millis(0));
+        let mut generators = SplitWatermarkGenerators::new(strategy.create_watermark_generator());
+
+        on_events(&mut generators, &mut strategy, &[(1, 100), (2, 50)]);
+        assert_eq!(generators.on_periodic_emit().timestamp, 49);
+        on_events(&mut generators, &mut strategy, &[(1, 200)]);
+        generators.on_periodic_emit();
+        std::thread::sleep(Duration::from_millis(5));
+        on_events(&mut generators, &mut strategy, &[(1, 300)]);
+        assert_eq!(generators.on_periodic_emit().timestamp, 299);
+
+        std::thread::sleep(Duration::from_millis(5));
+        generators.on_periodic_emit();
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(generators.on_periodic_emit(), IDLE_WATERMARK);
+
+        // the punctuated watermark is kept until the next punctuation
+        let mut strategy = DefaultWatermarkStrategy::new()
+            .for_punctuated(|_record, event_timestamp| Some(event_timestamp));
+        let mut generators = SplitWatermarkGenerators::new(strategy.create_watermark_generator());
+
+        on_events(&mut generators, &mut strategy, &[(1, 100), (2, 50)]);
+        assert_eq!(generators.on_periodic_emit().timestamp, 50);
+
+        // the generator is shared by all splits
+        let mut strategy = DefaultWatermarkStrategy::new().for_watermark_generator(
+            BoundedOutOfOrdernessWatermarks::new(Duration::from_millis(0)),
+        );
+        let mut generators = SplitWatermarkGenerators::new(strategy.create_watermark_generator());
+
+        on_events(&mut generators, &mut strategy, &[(1, 100), (2, 50)]);
+        assert_eq!(generators.on_periodic_emit().timestamp, 99);
+    }
+}
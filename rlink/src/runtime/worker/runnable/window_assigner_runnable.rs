@@ -52,16 +52,21 @@ impl Runnable for WindowAssignerRunnable {
                 let windows = self
                     .stream_window
                     .operator_fn
-                    .assign_windows(record.timestamp, WindowAssignerContext {});
+                    .assign_record_windows(record, WindowAssignerContext {});
                 record.set_location_windows(windows);
 
                 self.next_runnable.as_mut().unwrap().run(element).await;
             }
             Element::Watermark(watermark) => {
+                // the idleness only matters to the min-watermark calculation of the downstream
+                if watermark.is_idle() {
+                    return;
+                }
+
                 let windows = self
                     .stream_window
                     .operator_fn
-                    .assign_windows(watermark.timestamp, WindowAssignerContext {});
+                    .assign_watermark_windows(watermark.timestamp, WindowAssignerContext {});
                 watermark.set_location_windows(windows);
 
                 self.next_runnable.as_mut().unwrap().run(element).await;
@@ -104,6 +109,7 @@ impl Runnable for WindowAssignerRunnable {
             task_id: snapshot_context.task_id,
             checkpoint_id: snapshot_context.checkpoint_id,
             completed_checkpoint_id: snapshot_context.completed_checkpoint_id,
+            alignment_duration: 0,
             handle,
         };
         snapshot_context.report(ck).map(|ck| {
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+use serde_json::Value;
+
+use crate::core::checkpoint::{CheckpointFunction, CheckpointHandle, FunctionSnapshotContext};
+use crate::core::data_types::Schema;
+use crate::core::element::{FnSchema, Record};
+use crate::core::function::{
+    Context, InputFormat, InputSplit, InputSplitSource, NamedFunction, SendableElementStream,
+};
+use crate::state_processor::SavepointReader;
+use crate::utils::stream::MemoryStream;
+
+/// Map a key and the json value of a keyed state to the `Record` of the `schema`
+pub type KeyedStateMapper = fn(&Record, &Value) -> anyhow::Result<Record>;
+
+/// Read the keyed state of an operator in a savepoint as a bounded source, every task reads
+/// the states of the task with the same task number in the savepoint, so the `parallelism` is
+/// the parallelism of the operator in the savepoint
+pub struct KeyedStateInputFormat {
+    savepoint_url: String,
+    options: HashMap<String, String>,
+    state_key: String,
+    state_name: String,
+
+    schema: Schema,
+    mapper: KeyedStateMapper,
+    parallelism: u16,
+
+    records: Vec<Record>,
+}
+
+impl KeyedStateInputFormat {
+    pub fn new(
+        savepoint_url: &str,
+        options: HashMap<String, String>,
+        state_key: &str,
+        state_name: &str,
+        schema: Schema,
+        mapper: KeyedStateMapper,
+        parallelism: u16,
+    ) -> Self {
+        KeyedStateInputFormat {
+            savepoint_url: savepoint_url.to_string(),
+            options,
+            state_key: state_key.to_string(),
+            state_name: state_name.to_string(),
+            schema,
+            mapper,
+            parallelism,
+            records: vec![],
+        }
+    }
+}
+
+impl InputSplitSource for KeyedStateInputFormat {}
+
+#[async_trait]
+impl InputFormat for KeyedStateInputFormat {
+    async fn open(
+        &mut self,
+        _input_split: InputSplit,
+        context: &Context,
+    ) -> crate::core::Result<()> {
+        let reader = SavepointReader::read(self.savepoint_url.as_str(), &self.options).await?;
+        let keyed_states = reader.keyed_states(self.state_key.as_str())?;
+        if keyed_states.len() != self.parallelism as usize {
+            return Err(anyhow!(
+                "the parallelism of operator `{}` in the savepoint is {}, not {}",
+                self.state_key,
+                keyed_states.len(),
+                self.parallelism
+            )
+            .into());
+        }
+
+        let task_number = context.task_id.task_number() as usize;
+        let entries = keyed_states[task_number].entries::<Value>(self.state_name.as_str())?;
+        for (key, value) in entries {
+            self.records.push((self.mapper)(&key, &value)?);
+        }
+        info!(
+            "read {} keyed states of `{}` in operator `{}`",
+            self.records.len(),
+            self.state_name,
+            self.state_key
+        );
+        Ok(())
+    }
+
+    async fn element_stream(&mut self) -> SendableElementStream {
+        let records = std::mem::take(&mut self.records);
+        Box::pin(MemoryStream::new(records))
+    }
+
+    async fn close(&mut self) -> crate::core::Result<()> {
+        Ok(())
+    }
+
+    fn bounded(&self) -> bool {
+        true
+    }
+
+    fn schema(&self, _input_schema: FnSchema) -> FnSchema {
+        FnSchema::from(&self.schema)
+    }
+
+    fn parallelism(&self) -> u16 {
+        self.parallelism
+    }
+}
+
+impl NamedFunction for KeyedStateInputFormat {
+    fn name(&self) -> &str {
+        "KeyedStateInputFormat"
+    }
+}
+
+#[async_trait]
+impl CheckpointFunction for KeyedStateInputFormat {
+    async fn initialize_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+        _handle: &Option<CheckpointHandle>,
+    ) {
+    }
+
+    async fn snapshot_state(
+        &mut self,
+        _context: &FunctionSnapshotContext,
+    ) -> Option<CheckpointHandle> {
+        None
+    }
+}
+
+impl Debug for KeyedStateInputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedStateInputFormat")
+            .field("savepoint_url", &self.savepoint_url)
+            .field("state_key", &self.state_key)
+            .field("state_name", &self.state_name)
+            .finish()
+    }
+}
@@ -0,0 +1,22 @@
+//! The state processor reads and writes the states of a savepoint or a completed checkpoint
+//! outside a running job, e.g. to bootstrap the states of a new application, fix the corrupt
+//! entries, or migrate the states to a new format. The keyed states are the checkpoint handles
+//! of `RuntimeContext::snapshot`, and the operators are identified by the state key of the
+//! savepoint, the `uid` of the operator or `operator-{operator_id}`.
+//!
+//! ```ignore
+//! let reader = SavepointReader::read("s3://bucket/savepoints/savepoint-10", &options).await?;
+//! let mut writer = reader.into_writer();
+//! writer.transform_keyed_state("counter", "count", |_key, count: u64| Some(count * 2))?;
+//! writer.write("s3://bucket/savepoints", &options).await?;
+//! ```
+
+mod keyed_state_input_format;
+mod savepoint_reader;
+mod savepoint_writer;
+
+pub use keyed_state_input_format::{KeyedStateInputFormat, KeyedStateMapper};
+pub use savepoint_reader::SavepointReader;
+pub use savepoint_writer::SavepointWriter;
+
+pub use crate::storage::savepoint::{SavepointMetadata, SavepointOperator};
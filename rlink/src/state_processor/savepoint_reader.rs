@@ -0,0 +1,138 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::de::DeserializeOwned;
+
+use crate::core::backend::CheckpointBackend;
+use crate::core::checkpoint::Checkpoint;
+use crate::core::element::Record;
+use crate::core::runtime::{CheckpointId, OperatorId};
+use crate::core::state::KeyedStates;
+use crate::state_processor::SavepointWriter;
+use crate::storage::checkpoint::{CheckpointStorage, TCheckpointStorage};
+use crate::storage::savepoint::{read_savepoint, SavepointMetadata, SavepointOperator};
+use crate::utils::date_time::current_timestamp_millis;
+
+/// Read the states of a savepoint or a completed checkpoint
+#[derive(Clone, Debug)]
+pub struct SavepointReader {
+    metadata: SavepointMetadata,
+}
+
+impl SavepointReader {
+    pub fn new(metadata: SavepointMetadata) -> Self {
+        SavepointReader { metadata }
+    }
+
+    /// Read the savepoint in the directory `url`, e.g. `s3://bucket/savepoints/savepoint-10`
+    pub async fn read(url: &str, options: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let metadata = read_savepoint(url, options).await?;
+        Ok(SavepointReader::new(metadata))
+    }
+
+    /// Read the completed checkpoint of the application in the checkpoint storage, the latest
+    /// one if the `checkpoint_id` is `None`. The operators of a checkpoint are not mapped by
+    /// the `uid`, their state keys are `operator-{operator_id}`
+    pub async fn read_checkpoint(
+        checkpoint_backend: &CheckpointBackend,
+        application_name: &str,
+        application_id: &str,
+        checkpoint_id: Option<CheckpointId>,
+    ) -> anyhow::Result<Self> {
+        let mut storage = CheckpointStorage::new(checkpoint_backend);
+        let checkpoints = match checkpoint_id {
+            Some(checkpoint_id) => {
+                storage
+                    .load_by_checkpoint_id(application_name, application_id, checkpoint_id)
+                    .await?
+            }
+            None => storage.load(application_name, application_id).await?,
+        };
+        if checkpoints.is_empty() {
+            return Err(anyhow!(
+                "no completed checkpoint of application `{}` found",
+                application_id
+            ));
+        }
+
+        let checkpoint_id = checkpoints
+            .iter()
+            .map(|ck| ck.checkpoint_id)
+            .max()
+            .unwrap_or_default();
+        let mut operator_checkpoints: BTreeMap<OperatorId, Vec<Checkpoint>> = BTreeMap::new();
+        for ck in checkpoints {
+            operator_checkpoints
+                .entry(ck.operator_id)
+                .or_default()
+                .push(ck);
+        }
+
+        let operators = operator_checkpoints
+            .into_iter()
+            .map(|(operator_id, mut checkpoints)| {
+                checkpoints.sort_by_key(|ck| ck.task_id.task_number);
+                SavepointOperator {
+                    state_key: format!("operator-{}", operator_id.0),
+                    operator_name: String::new(),
+                    parallelism: checkpoints.len() as u16,
+                    checkpoints,
+                }
+            })
+            .collect();
+
+        let metadata = SavepointMetadata {
+            application_name: application_name.to_string(),
+            application_id: application_id.to_string(),
+            checkpoint_id,
+            timestamp: current_timestamp_millis(),
+            operators,
+        };
+        Ok(SavepointReader::new(metadata))
+    }
+
+    pub fn metadata(&self) -> &SavepointMetadata {
+        &self.metadata
+    }
+
+    pub fn operator(&self, state_key: &str) -> Option<&SavepointOperator> {
+        self.metadata
+            .operators
+            .iter()
+            .find(|x| x.state_key.eq(state_key))
+    }
+
+    /// The keyed states of every task of the operator, ordered by the task number
+    pub fn keyed_states(&self, state_key: &str) -> anyhow::Result<Vec<KeyedStates>> {
+        let operator = self
+            .operator(state_key)
+            .ok_or_else(|| anyhow!("operator `{}` not found in the savepoint", state_key))?;
+
+        let mut checkpoints: Vec<&Checkpoint> = operator.checkpoints.iter().collect();
+        checkpoints.sort_by_key(|ck| ck.task_id.task_number);
+        checkpoints
+            .into_iter()
+            .map(|ck| KeyedStates::from_handle(&ck.handle))
+            .collect()
+    }
+
+    /// The keys and the values of the state `state_name` of all tasks of the operator
+    pub fn read_keyed_state<T>(
+        &self,
+        state_key: &str,
+        state_name: &str,
+    ) -> anyhow::Result<Vec<(Record, T)>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut entries = vec![];
+        for keyed_states in self.keyed_states(state_key)? {
+            entries.extend(keyed_states.entries(state_name)?);
+        }
+        Ok(entries)
+    }
+
+    /// Modify the states of the savepoint and write a new one
+    pub fn into_writer(self) -> SavepointWriter {
+        SavepointWriter::from_metadata(self.metadata)
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::core::checkpoint::Checkpoint;
+use crate::core::element::Record;
+use crate::core::key_group::DEFAULT_MAX_PARALLELISM;
+use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+use crate::core::state::KeyedStates;
+use crate::storage::savepoint::{
+    savepoint_url, write_savepoint, SavepointMetadata, SavepointOperator,
+};
+use crate::utils::date_time::current_timestamp_millis;
+
+/// Write a new savepoint from the states of an existing one or from scratch.
+///
+/// The keyed states of an operator are split to the tasks by the key groups, so the
+/// `max_parallelism` must be the same as the application restored from the savepoint
+#[derive(Clone, Debug)]
+pub struct SavepointWriter {
+    metadata: SavepointMetadata,
+    max_parallelism: u16,
+}
+
+impl SavepointWriter {
+    /// Bootstrap the states of a new application
+    pub fn new(application_name: &str) -> Self {
+        let timestamp = current_timestamp_millis();
+        let metadata = SavepointMetadata {
+            application_name: application_name.to_string(),
+            application_id: String::new(),
+            checkpoint_id: CheckpointId(timestamp),
+            timestamp,
+            operators: vec![],
+        };
+        SavepointWriter::from_metadata(metadata)
+    }
+
+    pub fn from_metadata(metadata: SavepointMetadata) -> Self {
+        SavepointWriter {
+            metadata,
+            max_parallelism: DEFAULT_MAX_PARALLELISM,
+        }
+    }
+
+    pub fn max_parallelism(mut self, max_parallelism: u16) -> Self {
+        self.max_parallelism = max_parallelism;
+        self
+    }
+
+    pub fn metadata(&self) -> &SavepointMetadata {
+        &self.metadata
+    }
+
+    /// Drop the states of the operator
+    pub fn remove_operator(&mut self, state_key: &str) -> &mut Self {
+        self.metadata
+            .operators
+            .retain(|x| x.state_key.ne(state_key));
+        self
+    }
+
+    /// Take the keyed states of all tasks of the operator as one
+    fn take_keyed_states(&mut self, state_key: &str) -> anyhow::Result<Option<KeyedStates>> {
+        let index = self
+            .metadata
+            .operators
+            .iter()
+            .position(|x| x.state_key.eq(state_key));
+        let operator = match index {
+            Some(index) => self.metadata.operators.remove(index),
+            None => return Ok(None),
+        };
+
+        let mut keyed_states = KeyedStates::new();
+        for ck in &operator.checkpoints {
+            keyed_states.merge(KeyedStates::from_handle(&ck.handle)?);
+        }
+        Ok(Some(keyed_states))
+    }
+
+    /// Set the keyed states of the operator with the `parallelism`, the existing states are
+    /// replaced
+    pub fn with_keyed_states(
+        &mut self,
+        state_key: &str,
+        operator_name: &str,
+        parallelism: u16,
+        keyed_states: KeyedStates,
+    ) -> anyhow::Result<&mut Self> {
+        if parallelism == 0 || parallelism > self.max_parallelism {
+            return Err(anyhow!(
+                "the parallelism {} is out of the range [1, {}]",
+                parallelism,
+                self.max_parallelism
+            ));
+        }
+
+        self.remove_operator(state_key);
+
+        let checkpoint_id = self.metadata.checkpoint_id;
+        let task_states = keyed_states.split(self.max_parallelism, parallelism);
+        let mut checkpoints = Vec::with_capacity(task_states.len());
+        for (task_number, task_state) in task_states.into_iter().enumerate() {
+            checkpoints.push(Checkpoint {
+                operator_id: OperatorId::default(),
+                task_id: TaskId {
+                    job_id: JobId::default(),
+                    task_number: task_number as u16,
+                    num_tasks: parallelism,
+                },
+                checkpoint_id,
+                completed_checkpoint_id: None,
+                alignment_duration: 0,
+                handle: task_state.to_handle()?,
+            });
+        }
+
+        self.metadata.operators.push(SavepointOperator {
+            state_key: state_key.to_string(),
+            operator_name: operator_name.to_string(),
+            parallelism,
+            checkpoints,
+        });
+        Ok(self)
+    }
+
+    /// Add the values of the state `state_name` to the operator, the operator is created if
+    /// absent, or rescaled to the `parallelism` with its existing states
+    pub fn with_keyed_state<T>(
+        &mut self,
+        state_key: &str,
+        operator_name: &str,
+        parallelism: u16,
+        state_name: &str,
+        entries: Vec<(Record, T)>,
+    ) -> anyhow::Result<&mut Self>
+    where
+        T: Serialize,
+    {
+        let mut keyed_states = self.take_keyed_states(state_key)?.unwrap_or_default();
+        for (key, value) in entries {
+            keyed_states.put(state_name, &key, &value)?;
+        }
+        self.with_keyed_states(state_key, operator_name, parallelism, keyed_states)
+    }
+
+    /// Update the values of the state `state_name` of the operator by `f`, the value is removed
+    /// if `f` returns `None`
+    pub fn transform_keyed_state<T, F>(
+        &mut self,
+        state_key: &str,
+        state_name: &str,
+        mut f: F,
+    ) -> anyhow::Result<&mut Self>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnMut(&Record, T) -> Option<T>,
+    {
+        let operator = self
+            .metadata
+            .operators
+            .iter()
+            .find(|x| x.state_key.eq(state_key))
+            .ok_or_else(|| anyhow!("operator `{}` not found in the savepoint", state_key))?;
+        let operator_name = operator.operator_name.clone();
+        let parallelism = operator.parallelism;
+
+        let mut keyed_states = self.take_keyed_states(state_key)?.unwrap_or_default();
+        for (key, value) in keyed_states.entries::<T>(state_name)? {
+            match f(&key, value) {
+                Some(value) => keyed_states.put(state_name, &key, &value)?,
+                None => keyed_states.remove(state_name, &key),
+            }
+        }
+        self.with_keyed_states(state_key, operator_name.as_str(), parallelism, keyed_states)
+    }
+
+    /// Write the savepoint to `{url}/savepoint-{checkpoint_id}`, and return the directory
+    pub async fn write(
+        &self,
+        url: &str,
+        options: &HashMap<String, String>,
+    ) -> anyhow::Result<String> {
+        let savepoint_url = savepoint_url(url, self.metadata.checkpoint_id);
+        write_savepoint(savepoint_url.as_str(), options, &self.metadata).await?;
+        info!(
+            "write savepoint with {} operators to {}",
+            self.metadata.operators.len(),
+            savepoint_url
+        );
+        Ok(savepoint_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::state_processor::{SavepointReader, SavepointWriter};
+
+    fn key(k: u32) -> Record {
+        let schema = Schema::new(vec![Field::new("k", DataType::UInt32)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_u32(k).unwrap();
+        record
+    }
+
+    #[tokio::test]
+    pub async fn savepoint_writer_test() {
+        let root = std::env::temp_dir().join(format!("rlink-state-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let url = format!("file://{}", root.to_string_lossy());
+
+        let entries = (0..10).map(|k| (key(k), k as u64)).collect();
+        let mut writer = SavepointWriter::new("app");
+        writer
+            .with_keyed_state("counter", "counter", 3, "count", entries)
+            .unwrap();
+        let savepoint_url = writer.write(url.as_str(), &HashMap::new()).await.unwrap();
+
+        let reader = SavepointReader::read(savepoint_url.as_str(), &HashMap::new())
+            .await
+            .unwrap();
+        assert_eq!(reader.operator("counter").unwrap().checkpoints.len(), 3);
+        let mut counts = reader
+            .read_keyed_state::<u64>("counter", "count")
+            .unwrap()
+            .into_iter()
+            .map(|(_key, count)| count)
+            .collect::<Vec<u64>>();
+        counts.sort();
+        assert_eq!(counts, (0..10).collect::<Vec<u64>>());
+
+        // drop the odd counts and double the others
+        let mut writer = reader.into_writer();
+        writer
+            .transform_keyed_state("counter", "count", |_key, count: u64| {
+                if count % 2 == 0 {
+                    Some(count * 2)
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        let keyed_states = SavepointReader::new(writer.metadata().clone())
+            .keyed_states("counter")
+            .unwrap();
+        assert_eq!(keyed_states.len(), 3);
+        let mut counts: Vec<u64> = keyed_states
+            .iter()
+            .flat_map(|states| states.entries::<u64>("count").unwrap())
+            .map(|(_key, count)| count)
+            .collect();
+        counts.sort();
+        assert_eq!(counts, vec![0, 4, 8, 12, 16]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
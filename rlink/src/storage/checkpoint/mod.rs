@@ -3,9 +3,11 @@ use crate::core::checkpoint::Checkpoint;
 use crate::core::runtime::CheckpointId;
 use crate::storage::checkpoint::memory_checkpoint_storage::MemoryCheckpointStorage;
 use crate::storage::checkpoint::mysql_checkpoint_storage::MySqlCheckpointStorage;
+use crate::storage::checkpoint::object_store_checkpoint_storage::ObjectStoreCheckpointStorage;
 
 pub mod memory_checkpoint_storage;
 pub mod mysql_checkpoint_storage;
+pub mod object_store_checkpoint_storage;
 
 pub struct CheckpointEntity {
     application_name: String,
@@ -33,6 +35,41 @@ impl CheckpointEntity {
     }
 }
 
+/// the version of the `CheckpointMetadata` format
+pub const CHECKPOINT_METADATA_VERSION: u32 = 1;
+
+/// The metadata file of a checkpoint, it fully describes the checkpoint of all operators, so
+/// a new coordinator can restore the application from the metadata only
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointMetadata {
+    pub version: u32,
+    pub application_name: String,
+    pub application_id: String,
+    pub checkpoint_id: CheckpointId,
+    /// the time in millis the checkpoint is saved
+    pub timestamp: u64,
+    pub operators: Vec<Checkpoint>,
+}
+
+impl CheckpointMetadata {
+    pub fn new(
+        application_name: String,
+        application_id: String,
+        checkpoint_id: CheckpointId,
+        timestamp: u64,
+        operators: Vec<Checkpoint>,
+    ) -> Self {
+        CheckpointMetadata {
+            version: CHECKPOINT_METADATA_VERSION,
+            application_name,
+            application_id,
+            checkpoint_id,
+            timestamp,
+            operators,
+        }
+    }
+}
+
 #[async_trait]
 pub trait TCheckpointStorage {
     async fn save(&mut self, ck: CheckpointEntity) -> anyhow::Result<()>;
@@ -54,6 +91,7 @@ pub trait TCheckpointStorage {
 pub enum CheckpointStorage {
     MemoryCheckpointStorage(MemoryCheckpointStorage),
     MySqlCheckpointStorage(MySqlCheckpointStorage),
+    ObjectStoreCheckpointStorage(ObjectStoreCheckpointStorage),
 }
 
 impl CheckpointStorage {
@@ -68,6 +106,16 @@ impl CheckpointStorage {
                     table.clone(),
                 ))
             }
+            CheckpointBackend::FileSystem { path } => {
+                let storage = ObjectStoreCheckpointStorage::with_local_path(path.as_str())
+                    .expect("create file system checkpoint storage error");
+                CheckpointStorage::ObjectStoreCheckpointStorage(storage)
+            }
+            CheckpointBackend::ObjectStore { url, options } => {
+                let storage = ObjectStoreCheckpointStorage::new(url.as_str(), options)
+                    .expect("create object store checkpoint storage error");
+                CheckpointStorage::ObjectStoreCheckpointStorage(storage)
+            }
         }
     }
 }
@@ -78,6 +126,7 @@ impl TCheckpointStorage for CheckpointStorage {
         match self {
             CheckpointStorage::MemoryCheckpointStorage(storage) => storage.save(ck).await,
             CheckpointStorage::MySqlCheckpointStorage(storage) => storage.save(ck).await,
+            CheckpointStorage::ObjectStoreCheckpointStorage(storage) => storage.save(ck).await,
         }
     }
 
@@ -93,6 +142,9 @@ impl TCheckpointStorage for CheckpointStorage {
             CheckpointStorage::MySqlCheckpointStorage(storage) => {
                 storage.load(application_name, application_id).await
             }
+            CheckpointStorage::ObjectStoreCheckpointStorage(storage) => {
+                storage.load(application_name, application_id).await
+            }
         }
     }
 
@@ -113,6 +165,11 @@ impl TCheckpointStorage for CheckpointStorage {
                     .load_by_checkpoint_id(application_name, application_id, checkpoint_id)
                     .await
             }
+            CheckpointStorage::ObjectStoreCheckpointStorage(storage) => {
+                storage
+                    .load_by_checkpoint_id(application_name, application_id, checkpoint_id)
+                    .await
+            }
         }
     }
 }
@@ -56,6 +56,7 @@ impl MySqlCheckpointStorage {
                         },
                         checkpoint_id: CheckpointId(checkpoint_id),
                         completed_checkpoint_id,
+                        alignment_duration: 0,
                         handle: CheckpointHandle { handle },
                     }
                 },
@@ -225,6 +226,7 @@ mod tests {
                     task_id: task_id0,
                     checkpoint_id,
                     completed_checkpoint_id: None,
+                    alignment_duration: 0,
                     handle: CheckpointHandle {
                         handle: "h0".to_string(),
                     },
@@ -234,6 +236,7 @@ mod tests {
                     task_id: task_id1,
                     checkpoint_id,
                     completed_checkpoint_id: None,
+                    alignment_duration: 0,
                     handle: CheckpointHandle {
                         handle: "h1".to_string(),
                     },
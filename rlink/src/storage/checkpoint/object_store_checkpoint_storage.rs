@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::TryStreamExt;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::ObjectStore;
+
+use crate::core::checkpoint::Checkpoint;
+use crate::core::runtime::CheckpointId;
+use crate::storage::checkpoint::{CheckpointEntity, CheckpointMetadata, TCheckpointStorage};
+use crate::storage::object_storage;
+use crate::utils::date_time::current_timestamp_millis;
+
+const CHECKPOINT_PREFIX: &str = "checkpoint-";
+const CHECKPOINT_SUFFIX: &str = ".json";
+
+/// Store the checkpoints of an application as the json `CheckpointMetadata`, e.g.
+/// `s3://bucket/path/{application_name}/{application_id}/checkpoint-{checkpoint_id}.json`
+pub struct ObjectStoreCheckpointStorage {
+    store: Arc<dyn ObjectStore>,
+    base_path: Path,
+}
+
+impl ObjectStoreCheckpointStorage {
+    pub fn new(url: &str, options: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let (store, base_path) = object_storage::parse_url(url, options)?;
+        Ok(ObjectStoreCheckpointStorage { store, base_path })
+    }
+
+    /// Store the checkpoints in the local directory, it's created if absent
+    pub fn with_local_path(path: &str) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(path)?;
+        let store = LocalFileSystem::new_with_prefix(path)?;
+        Ok(ObjectStoreCheckpointStorage {
+            store: Arc::new(store),
+            base_path: Path::default(),
+        })
+    }
+
+    fn application_path(&self, application_name: &str, application_id: &str) -> Path {
+        self.base_path.child(application_name).child(application_id)
+    }
+
+    fn checkpoint_path(application_path: &Path, checkpoint_id: CheckpointId) -> Path {
+        application_path.child(format!(
+            "{}{}{}",
+            CHECKPOINT_PREFIX, checkpoint_id.0, CHECKPOINT_SUFFIX
+        ))
+    }
+
+    fn parse_checkpoint_id(path: &Path) -> Option<CheckpointId> {
+        path.filename()
+            .and_then(|x| x.strip_prefix(CHECKPOINT_PREFIX))
+            .and_then(|x| x.strip_suffix(CHECKPOINT_SUFFIX))
+            .and_then(|x| x.parse().ok())
+            .map(CheckpointId)
+    }
+
+    async fn checkpoint_ids(&self, application_path: &Path) -> anyhow::Result<Vec<CheckpointId>> {
+        let objects: Vec<_> = self
+            .store
+            .list(Some(application_path))
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut checkpoint_ids: Vec<CheckpointId> = objects
+            .iter()
+            .filter_map(|x| Self::parse_checkpoint_id(&x.location))
+            .collect();
+        checkpoint_ids.sort_by_key(|x| x.0);
+        Ok(checkpoint_ids)
+    }
+
+    async fn read(&self, path: &Path) -> anyhow::Result<Vec<Checkpoint>> {
+        match self.store.get(path).await {
+            Ok(result) => {
+                let bytes = result.bytes().await?;
+                let cks = match serde_json::from_slice::<CheckpointMetadata>(bytes.as_ref()) {
+                    Ok(metadata) => metadata.operators,
+                    // the operator checkpoints only, written by the earlier versions
+                    Err(_e) => serde_json::from_slice(bytes.as_ref())?,
+                };
+                Ok(cks)
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl TCheckpointStorage for ObjectStoreCheckpointStorage {
+    async fn save(&mut self, ck: CheckpointEntity) -> anyhow::Result<()> {
+        let CheckpointEntity {
+            application_name,
+            application_id,
+            checkpoint_id,
+            finish_cks,
+            ttl,
+        } = ck;
+
+        let application_path =
+            self.application_path(application_name.as_str(), application_id.as_str());
+        let path = Self::checkpoint_path(&application_path, checkpoint_id);
+        let metadata = CheckpointMetadata::new(
+            application_name.clone(),
+            application_id,
+            checkpoint_id,
+            current_timestamp_millis(),
+            finish_cks,
+        );
+        let payload = serde_json::to_vec(&metadata)?;
+        self.store.put(&path, Bytes::from(payload)).await?;
+
+        if checkpoint_id.0 < ttl {
+            return Ok(());
+        }
+
+        let checkpoint_id_ttl = checkpoint_id.0 - ttl;
+        for id in self.checkpoint_ids(&application_path).await? {
+            if id.0 < checkpoint_id_ttl {
+                let path = Self::checkpoint_path(&application_path, id);
+                self.store.delete(&path).await?;
+            }
+        }
+
+        info!(
+            "checkpoint save success, application_name={:?}, checkpoint_id={:?}",
+            application_name, checkpoint_id
+        );
+        Ok(())
+    }
+
+    async fn load(
+        &mut self,
+        application_name: &str,
+        application_id: &str,
+    ) -> anyhow::Result<Vec<Checkpoint>> {
+        let application_path = self.application_path(application_name, application_id);
+        match self.checkpoint_ids(&application_path).await?.last() {
+            Some(checkpoint_id) => {
+                let path = Self::checkpoint_path(&application_path, *checkpoint_id);
+                self.read(&path).await
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    async fn load_by_checkpoint_id(
+        &mut self,
+        application_name: &str,
+        application_id: &str,
+        checkpoint_id: CheckpointId,
+    ) -> anyhow::Result<Vec<Checkpoint>> {
+        let application_path = self.application_path(application_name, application_id);
+        let path = Self::checkpoint_path(&application_path, checkpoint_id);
+        self.read(&path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::core::checkpoint::{Checkpoint, CheckpointHandle};
+    use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+    use crate::storage::checkpoint::object_store_checkpoint_storage::ObjectStoreCheckpointStorage;
+    use crate::storage::checkpoint::{CheckpointEntity, CheckpointMetadata, TCheckpointStorage};
+
+    fn checkpoint_entity(checkpoint_id: CheckpointId, ttl: u64) -> CheckpointEntity {
+        let task_id = TaskId {
+            job_id: JobId(5u32),
+            task_number: 0,
+            num_tasks: 1,
+        };
+        CheckpointEntity::new(
+            "test_app_name".to_string(),
+            "test_app_id".to_string(),
+            checkpoint_id,
+            vec![Checkpoint {
+                operator_id: OperatorId(1),
+                task_id,
+                checkpoint_id,
+                completed_checkpoint_id: None,
+                alignment_duration: 0,
+                handle: CheckpointHandle {
+                    handle: format!("h{}", checkpoint_id.0),
+                },
+            }],
+            ttl,
+        )
+    }
+
+    #[tokio::test]
+    pub async fn object_store_storage_test() {
+        let dir = std::env::temp_dir().join(format!("rlink-ck-{}", std::process::id()));
+        let url = format!("file://{}", dir.to_string_lossy());
+        let mut storage = ObjectStoreCheckpointStorage::new(url.as_str(), &HashMap::new()).unwrap();
+
+        for id in [100, 200, 300] {
+            storage
+                .save(checkpoint_entity(CheckpointId(id), 150))
+                .await
+                .unwrap();
+        }
+
+        let cks = storage.load("test_app_name", "test_app_id").await.unwrap();
+        assert_eq!(cks.len(), 1);
+        assert_eq!(cks[0].handle.handle, "h300");
+
+        // expired by the ttl
+        let cks = storage
+            .load_by_checkpoint_id("test_app_name", "test_app_id", CheckpointId(100))
+            .await
+            .unwrap();
+        assert!(cks.is_empty());
+
+        let cks = storage
+            .load_by_checkpoint_id("test_app_name", "test_app_id", CheckpointId(200))
+            .await
+            .unwrap();
+        assert_eq!(cks.len(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    pub async fn file_system_storage_test() {
+        let dir = std::env::temp_dir().join(format!("rlink-fs-ck-{}", std::process::id()));
+        let mut storage =
+            ObjectStoreCheckpointStorage::with_local_path(dir.to_str().unwrap()).unwrap();
+
+        // the operator checkpoints only, written by the earlier versions
+        let legacy = checkpoint_entity(CheckpointId(100), 1000).finish_cks;
+        let legacy_path = dir.join("test_app_name/test_app_id/checkpoint-100.json");
+        std::fs::create_dir_all(legacy_path.parent().unwrap()).unwrap();
+        std::fs::write(&legacy_path, serde_json::to_vec(&legacy).unwrap()).unwrap();
+
+        let cks = storage.load("test_app_name", "test_app_id").await.unwrap();
+        assert_eq!(cks[0].handle.handle, "h100");
+
+        storage
+            .save(checkpoint_entity(CheckpointId(200), 1000))
+            .await
+            .unwrap();
+        let payload = std::fs::read(dir.join("test_app_name/test_app_id/checkpoint-200.json"));
+        let metadata: CheckpointMetadata = serde_json::from_slice(&payload.unwrap()).unwrap();
+        assert_eq!(metadata.application_id, "test_app_id");
+        assert_eq!(metadata.operators[0].handle.handle, "h200");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
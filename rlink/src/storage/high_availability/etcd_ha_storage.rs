@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use etcd_client::{
+    Client, Compare, CompareOp, LeaseKeepAliveStream, LeaseKeeper, PutOptions, Txn, TxnOp,
+};
+
+use crate::storage::high_availability::{
+    CoordinatorMetadata, LeaderInfo, THighAvailabilityStorage,
+};
+
+/// The lease of the leader key and its keep-alive stream, the stream is opened once the lease
+/// is granted and reused by the renewals
+struct LeaderLease {
+    id: i64,
+    keeper: LeaseKeeper,
+    stream: LeaseKeepAliveStream,
+}
+
+/// The leader key is attached to a lease, it's removed by the etcd once the lease expires
+pub struct EtcdHighAvailabilityStorage {
+    client: Client,
+    leader_key: String,
+    metadata_key: String,
+    /// the lease of the leader key if the candidate is the leader
+    lease: Option<LeaderLease>,
+}
+
+impl EtcdHighAvailabilityStorage {
+    pub async fn new(
+        endpoints: &[String],
+        namespace: &str,
+        application_id: &str,
+    ) -> anyhow::Result<Self> {
+        let client = Client::connect(endpoints, None).await?;
+        let prefix = format!("{}/{}", namespace.trim_end_matches('/'), application_id);
+        Ok(EtcdHighAvailabilityStorage {
+            client,
+            leader_key: format!("{}/leader", prefix),
+            metadata_key: format!("{}/metadata", prefix),
+            lease: None,
+        })
+    }
+
+    /// Renew the lease, and update the leader info if the leader key is still attached to it
+    async fn renew(&mut self, lease: &mut LeaderLease, value: &str) -> anyhow::Result<bool> {
+        let lease_id = lease.id;
+        lease.keeper.keep_alive().await?;
+        let alive = match lease.stream.message().await? {
+            Some(resp) => resp.ttl() > 0,
+            None => false,
+        };
+        if !alive {
+            return Ok(false);
+        }
+
+        let txn = Txn::new()
+            .when(vec![Compare::lease(
+                self.leader_key.as_str(),
+                CompareOp::Equal,
+                lease_id,
+            )])
+            .and_then(vec![TxnOp::put(
+                self.leader_key.as_str(),
+                value,
+                Some(PutOptions::new().with_lease(lease_id)),
+            )]);
+        let resp = self.client.txn(txn).await?;
+        Ok(resp.succeeded())
+    }
+}
+
+#[async_trait]
+impl THighAvailabilityStorage for EtcdHighAvailabilityStorage {
+    async fn try_acquire_leader(
+        &mut self,
+        leader: &LeaderInfo,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let value = serde_json::to_string(leader)?;
+
+        // the lease is dropped with its stream if the renewal fails
+        if let Some(mut lease) = self.lease.take() {
+            if self.renew(&mut lease, value.as_str()).await? {
+                self.lease = Some(lease);
+                return Ok(true);
+            }
+            warn!("the leader lease {} of etcd is lost", lease.id);
+        }
+
+        let ttl = ttl.as_secs().max(1) as i64;
+        let lease_id = self.client.lease_grant(ttl, None).await?.id();
+
+        // create the leader key if it's absent
+        let txn = Txn::new()
+            .when(vec![Compare::create_revision(
+                self.leader_key.as_str(),
+                CompareOp::Equal,
+                0,
+            )])
+            .and_then(vec![TxnOp::put(
+                self.leader_key.as_str(),
+                value,
+                Some(PutOptions::new().with_lease(lease_id)),
+            )]);
+        let resp = self.client.txn(txn).await?;
+        if resp.succeeded() {
+            let (keeper, stream) = self.client.lease_keep_alive(lease_id).await?;
+            self.lease = Some(LeaderLease {
+                id: lease_id,
+                keeper,
+                stream,
+            });
+            Ok(true)
+        } else {
+            self.client.lease_revoke(lease_id).await?;
+            Ok(false)
+        }
+    }
+
+    async fn get_leader(&self) -> anyhow::Result<Option<LeaderInfo>> {
+        let mut client = self.client.clone();
+        let resp = client.get(self.leader_key.as_str(), None).await?;
+        match resp.kvs().first() {
+            Some(kv) => Ok(Some(serde_json::from_slice(kv.value())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn release_leader(&mut self, _leader: &LeaderInfo) -> anyhow::Result<()> {
+        // the leader key is removed with the lease
+        if let Some(lease) = self.lease.take() {
+            self.client.lease_revoke(lease.id).await?;
+        }
+        Ok(())
+    }
+
+    async fn save(
+        &mut self,
+        leader: &LeaderInfo,
+        metadata: &CoordinatorMetadata,
+    ) -> anyhow::Result<()> {
+        let lease_id = self.lease.as_ref().map(|lease| lease.id).ok_or(anyhow!(
+            "the candidate {} isn't the leader",
+            leader.candidate_id
+        ))?;
+
+        // fenced by the leader key, the deposed leader can't overwrite the metadata
+        let value = serde_json::to_string(metadata)?;
+        let txn = Txn::new()
+            .when(vec![Compare::lease(
+                self.leader_key.as_str(),
+                CompareOp::Equal,
+                lease_id,
+            )])
+            .and_then(vec![TxnOp::put(self.metadata_key.as_str(), value, None)]);
+        let resp = self.client.txn(txn).await?;
+        if !resp.succeeded() {
+            return Err(anyhow!(
+                "the candidate {} isn't the leader",
+                leader.candidate_id
+            ));
+        }
+        Ok(())
+    }
+
+    async fn load(&self) -> anyhow::Result<Option<CoordinatorMetadata>> {
+        let mut client = self.client.clone();
+        let resp = client.get(self.metadata_key.as_str(), None).await?;
+        match resp.kvs().first() {
+            Some(kv) => Ok(Some(serde_json::from_slice(kv.value())?)),
+            None => Ok(None),
+        }
+    }
+}
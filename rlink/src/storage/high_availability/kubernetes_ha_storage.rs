@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use k8s_openapi::api::coordination::v1::{Lease, LeaseSpec};
+use k8s_openapi::api::core::v1::ConfigMap;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{MicroTime, ObjectMeta, OwnerReference};
+use k8s_openapi::chrono::{self, DateTime, Utc};
+use kube::api::{Api, PostParams};
+use kube::Client;
+
+use crate::deployment::kubernetes::{get_job_deploy_id, parse_name};
+use crate::storage::high_availability::{
+    CoordinatorMetadata, LeaderInfo, THighAvailabilityStorage,
+};
+
+/// the annotation of the lease holding the `LeaderInfo` of the holder
+const LEADER_ANNOTATION: &str = "rlink/leader";
+/// the key of the `CoordinatorMetadata` in the ConfigMap
+const METADATA_KEY: &str = "metadata";
+/// the key of the candidate id of the leader writing the metadata in the ConfigMap
+const LEADER_KEY: &str = "leader";
+
+/// The leader is elected by the `coordination.k8s.io/v1` Lease, the holder renews the
+/// `renewTime` of it, and the other candidates take it over once it isn't renewed in the
+/// `leaseDurationSeconds`. All updates of the Lease are the optimistic replacements by the
+/// `resourceVersion`, so only one of the concurrent candidates wins.
+/// The metadata is kept in a ConfigMap with the candidate id of the leader writing it, the new
+/// leader claims the ConfigMap once it takes over the Lease. The metadata is only written by the
+/// leader in the ConfigMap by the replacement of the `resourceVersion` it's read with, so the
+/// writes of a deposed leader fail after the claim.
+/// Both objects are owned by the Deployment of the application, they're removed with the
+/// application
+pub struct KubernetesHighAvailabilityStorage {
+    leases: Api<Lease>,
+    config_maps: Api<ConfigMap>,
+    lease_name: String,
+    config_map_name: String,
+    /// the uid of the application Deployment, empty if it isn't found
+    job_deploy_id: String,
+    application_id: String,
+}
+
+impl KubernetesHighAvailabilityStorage {
+    pub async fn new(namespace: &str, application_id: &str) -> anyhow::Result<Self> {
+        let client = Client::try_default().await?;
+        let job_deploy_id = get_job_deploy_id(namespace, application_id).await?;
+        let name = parse_name(application_id);
+
+        Ok(KubernetesHighAvailabilityStorage {
+            leases: Api::namespaced(client.clone(), namespace),
+            config_maps: Api::namespaced(client, namespace),
+            lease_name: format!("{}-leader", name),
+            config_map_name: format!("{}-ha-metadata", name),
+            job_deploy_id,
+            application_id: application_id.to_string(),
+        })
+    }
+
+    fn object_meta(&self, name: &str) -> ObjectMeta {
+        let owner_references = if self.job_deploy_id.is_empty() {
+            None
+        } else {
+            Some(vec![OwnerReference {
+                api_version: "apps/v1".to_string(),
+                kind: "Deployment".to_string(),
+                name: self.application_id.clone(),
+                uid: self.job_deploy_id.clone(),
+                controller: Some(false),
+                block_owner_deletion: Some(false),
+            }])
+        };
+
+        let mut labels = BTreeMap::new();
+        labels.insert("app".to_string(), "rlink".to_string());
+
+        ObjectMeta {
+            name: Some(name.to_string()),
+            labels: Some(labels),
+            owner_references,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the lease if it isn't updated by the others since it's read, return false on
+    /// the conflict
+    async fn replace_lease(&self, lease: &Lease) -> anyhow::Result<bool> {
+        match self
+            .leases
+            .replace(self.lease_name.as_str(), &PostParams::default(), lease)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(kube::Error::Api(e)) if e.code == 409 => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replace or create the ConfigMap if it isn't updated by the others since it's read,
+    /// return false on the conflict
+    async fn write_config_map(&self, config_map: ConfigMap, exists: bool) -> anyhow::Result<bool> {
+        let result = if exists {
+            self.config_maps
+                .replace(
+                    self.config_map_name.as_str(),
+                    &PostParams::default(),
+                    &config_map,
+                )
+                .await
+        } else {
+            self.config_maps
+                .create(&PostParams::default(), &config_map)
+                .await
+        };
+        match result {
+            Ok(_) => Ok(true),
+            Err(kube::Error::Api(e)) if e.code == 409 => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the `candidate_id` as the leader of the ConfigMap after the Lease is taken over,
+    /// the metadata writes of the previous leader are rejected since then
+    async fn claim_metadata(&self, candidate_id: &str) -> anyhow::Result<()> {
+        loop {
+            let (mut config_map, exists) = self.get_config_map().await?;
+            config_map
+                .data
+                .get_or_insert_with(BTreeMap::new)
+                .insert(LEADER_KEY.to_string(), candidate_id.to_string());
+            if self.write_config_map(config_map, exists).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The ConfigMap of the metadata and whether it exists, a new one is returned if absent
+    async fn get_config_map(&self) -> anyhow::Result<(ConfigMap, bool)> {
+        match self
+            .config_maps
+            .get_opt(self.config_map_name.as_str())
+            .await?
+        {
+            Some(config_map) => Ok((config_map, true)),
+            None => {
+                let config_map = ConfigMap {
+                    metadata: self.object_meta(self.config_map_name.as_str()),
+                    ..Default::default()
+                };
+                Ok((config_map, false))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl THighAvailabilityStorage for KubernetesHighAvailabilityStorage {
+    async fn try_acquire_leader(
+        &mut self,
+        leader: &LeaderInfo,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let now = Utc::now();
+        let mut annotations = BTreeMap::new();
+        annotations.insert(
+            LEADER_ANNOTATION.to_string(),
+            serde_json::to_string(leader)?,
+        );
+
+        let mut lease = match self.leases.get_opt(self.lease_name.as_str()).await? {
+            Some(lease) => lease,
+            None => {
+                let mut metadata = self.object_meta(self.lease_name.as_str());
+                metadata.annotations = Some(annotations);
+                let lease = Lease {
+                    metadata,
+                    spec: Some(LeaseSpec {
+                        holder_identity: Some(leader.candidate_id.clone()),
+                        lease_duration_seconds: Some(ttl.as_secs().max(1) as i32),
+                        acquire_time: Some(MicroTime(now)),
+                        renew_time: Some(MicroTime(now)),
+                        lease_transitions: Some(0),
+                    }),
+                };
+                return match self.leases.create(&PostParams::default(), &lease).await {
+                    Ok(_) => {
+                        self.claim_metadata(leader.candidate_id.as_str()).await?;
+                        Ok(true)
+                    }
+                    Err(kube::Error::Api(e)) if e.code == 409 => Ok(false),
+                    Err(e) => Err(e.into()),
+                };
+            }
+        };
+
+        let spec = lease.spec.get_or_insert_with(LeaseSpec::default);
+        let is_holder = spec.holder_identity.as_deref() == Some(leader.candidate_id.as_str());
+        if !is_holder {
+            if !is_expired(spec, now) {
+                return Ok(false);
+            }
+            spec.acquire_time = Some(MicroTime(now));
+            spec.lease_transitions = Some(spec.lease_transitions.unwrap_or(0) + 1);
+        }
+        spec.holder_identity = Some(leader.candidate_id.clone());
+        spec.lease_duration_seconds = Some(ttl.as_secs().max(1) as i32);
+        spec.renew_time = Some(MicroTime(now));
+        lease
+            .metadata
+            .annotations
+            .get_or_insert_with(BTreeMap::new)
+            .extend(annotations);
+
+        let acquired = self.replace_lease(&lease).await?;
+        if acquired && !is_holder {
+            self.claim_metadata(leader.candidate_id.as_str()).await?;
+        }
+        Ok(acquired)
+    }
+
+    async fn get_leader(&self) -> anyhow::Result<Option<LeaderInfo>> {
+        let lease = match self.leases.get_opt(self.lease_name.as_str()).await? {
+            Some(lease) => lease,
+            None => return Ok(None),
+        };
+        let holder_identity = match &lease.spec {
+            Some(spec) if !is_expired(spec, Utc::now()) => spec.holder_identity.clone(),
+            _ => None,
+        };
+
+        Ok(holder_identity.map(|candidate_id| {
+            lease
+                .metadata
+                .annotations
+                .as_ref()
+                .and_then(|x| x.get(LEADER_ANNOTATION))
+                .and_then(|x| serde_json::from_str::<LeaderInfo>(x).ok())
+                .filter(|x| x.candidate_id.eq(&candidate_id))
+                .unwrap_or(LeaderInfo {
+                    candidate_id,
+                    web_address: "".to_string(),
+                })
+        }))
+    }
+
+    async fn release_leader(&mut self, leader: &LeaderInfo) -> anyhow::Result<()> {
+        let mut lease = match self.leases.get_opt(self.lease_name.as_str()).await? {
+            Some(lease) => lease,
+            None => return Ok(()),
+        };
+        if let Some(spec) = lease.spec.as_mut() {
+            if spec.holder_identity.as_deref() == Some(leader.candidate_id.as_str()) {
+                spec.holder_identity = None;
+                spec.renew_time = None;
+                self.replace_lease(&lease).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn save(
+        &mut self,
+        leader: &LeaderInfo,
+        metadata: &CoordinatorMetadata,
+    ) -> anyhow::Result<()> {
+        // the leader is checked in the ConfigMap read with the `resourceVersion`, so the write
+        // conflicts with the claim of a new leader in between, and it's checked again
+        let metadata = serde_json::to_string(metadata)?;
+        loop {
+            let (mut config_map, exists) = self.get_config_map().await?;
+            set_metadata(
+                &mut config_map,
+                leader.candidate_id.as_str(),
+                metadata.clone(),
+            )?;
+            if self.write_config_map(config_map, exists).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn load(&self) -> anyhow::Result<Option<CoordinatorMetadata>> {
+        let config_map = self
+            .config_maps
+            .get_opt(self.config_map_name.as_str())
+            .await?;
+        let value = config_map
+            .and_then(|x| x.data)
+            .and_then(|mut x| x.remove(METADATA_KEY));
+        match value {
+            Some(value) => Ok(Some(serde_json::from_str(value.as_str())?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Set the metadata of the ConfigMap if it's claimed by the `candidate_id`
+fn set_metadata(
+    config_map: &mut ConfigMap,
+    candidate_id: &str,
+    metadata: String,
+) -> anyhow::Result<()> {
+    let data = config_map.data.get_or_insert_with(BTreeMap::new);
+    if data.get(LEADER_KEY).map(|x| x.as_str()) != Some(candidate_id) {
+        return Err(anyhow!("the candidate {} isn't the leader", candidate_id));
+    }
+    data.insert(METADATA_KEY.to_string(), metadata);
+    Ok(())
+}
+
+/// Whether the lease isn't held or it isn't renewed in the `leaseDurationSeconds`
+fn is_expired(spec: &LeaseSpec, now: DateTime<Utc>) -> bool {
+    if spec.holder_identity.is_none() {
+        return true;
+    }
+    match (&spec.renew_time, spec.lease_duration_seconds) {
+        (Some(renew_time), Some(lease_duration_seconds)) => {
+            renew_time.0 + chrono::Duration::seconds(lease_duration_seconds as i64) <= now
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::api::coordination::v1::LeaseSpec;
+    use k8s_openapi::api::core::v1::ConfigMap;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::MicroTime;
+    use k8s_openapi::chrono::{self, Utc};
+
+    use crate::storage::high_availability::kubernetes_ha_storage::{
+        is_expired, set_metadata, LEADER_KEY, METADATA_KEY,
+    };
+
+    #[test]
+    pub fn lease_expired_test() {
+        let now = Utc::now();
+        let mut spec = LeaseSpec {
+            holder_identity: Some("c0".to_string()),
+            lease_duration_seconds: Some(15),
+            renew_time: Some(MicroTime(now)),
+            ..Default::default()
+        };
+        assert!(!is_expired(&spec, now));
+        assert!(is_expired(&spec, now + chrono::Duration::seconds(15)));
+
+        spec.renew_time = None;
+        assert!(is_expired(&spec, now));
+
+        spec.renew_time = Some(MicroTime(now));
+        spec.holder_identity = None;
+        assert!(is_expired(&spec, now));
+    }
+
+    #[test]
+    pub fn set_metadata_test() {
+        let mut config_map = ConfigMap::default();
+        assert!(set_metadata(&mut config_map, "c0", "m0".to_string()).is_err());
+
+        let mut data = BTreeMap::new();
+        data.insert(LEADER_KEY.to_string(), "c0".to_string());
+        config_map.data = Some(data);
+        set_metadata(&mut config_map, "c0", "m0".to_string()).unwrap();
+
+        // claimed by the new leader
+        let data = config_map.data.as_mut().unwrap();
+        data.insert(LEADER_KEY.to_string(), "c1".to_string());
+        assert!(set_metadata(&mut config_map, "c0", "m1".to_string()).is_err());
+        assert_eq!(
+            config_map.data.unwrap().get(METADATA_KEY),
+            Some(&"m0".to_string())
+        );
+    }
+}
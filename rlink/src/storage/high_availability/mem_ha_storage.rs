@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use crate::storage::high_availability::{
+    CoordinatorMetadata, LeaderInfo, THighAvailabilityStorage,
+};
+
+#[derive(Default)]
+struct MemoryEntry {
+    /// the leader and the time its leadership expires
+    leader: Option<(LeaderInfo, Instant)>,
+    metadata: Option<CoordinatorMetadata>,
+}
+
+lazy_static! {
+    /// Map<application_id, MemoryEntry>
+    static ref HA_STORAGE: Mutex<HashMap<String, MemoryEntry>> = Mutex::new(HashMap::new());
+}
+
+pub struct MemoryHighAvailabilityStorage {
+    application_id: String,
+}
+
+impl MemoryHighAvailabilityStorage {
+    pub fn new(application_id: &str) -> Self {
+        MemoryHighAvailabilityStorage {
+            application_id: application_id.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl THighAvailabilityStorage for MemoryHighAvailabilityStorage {
+    async fn try_acquire_leader(
+        &mut self,
+        leader: &LeaderInfo,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let mut lock = HA_STORAGE.lock().await;
+        let entry = lock.entry(self.application_id.clone()).or_default();
+
+        let now = Instant::now();
+        let acquired = match &entry.leader {
+            Some((current, expire_at)) => {
+                current.candidate_id.eq(&leader.candidate_id) || *expire_at <= now
+            }
+            None => true,
+        };
+        if acquired {
+            entry.leader = Some((leader.clone(), now + ttl));
+        }
+        Ok(acquired)
+    }
+
+    async fn get_leader(&self) -> anyhow::Result<Option<LeaderInfo>> {
+        let lock = HA_STORAGE.lock().await;
+        let now = Instant::now();
+        Ok(lock
+            .get(&self.application_id)
+            .and_then(|entry| entry.leader.as_ref())
+            .filter(|(_leader, expire_at)| *expire_at > now)
+            .map(|(leader, _expire_at)| leader.clone()))
+    }
+
+    async fn release_leader(&mut self, leader: &LeaderInfo) -> anyhow::Result<()> {
+        let mut lock = HA_STORAGE.lock().await;
+        if let Some(entry) = lock.get_mut(&self.application_id) {
+            let is_leader = entry
+                .leader
+                .as_ref()
+                .map(|(current, _expire_at)| current.candidate_id.eq(&leader.candidate_id))
+                .unwrap_or(false);
+            if is_leader {
+                entry.leader = None;
+            }
+        }
+        Ok(())
+    }
+
+    async fn save(
+        &mut self,
+        leader: &LeaderInfo,
+        metadata: &CoordinatorMetadata,
+    ) -> anyhow::Result<()> {
+        let mut lock = HA_STORAGE.lock().await;
+        let entry = lock.entry(self.application_id.clone()).or_default();
+
+        let is_leader = entry
+            .leader
+            .as_ref()
+            .map(|(current, expire_at)| {
+                current.candidate_id.eq(&leader.candidate_id) && *expire_at > Instant::now()
+            })
+            .unwrap_or(false);
+        if !is_leader {
+            return Err(anyhow!(
+                "the candidate {} isn't the leader",
+                leader.candidate_id
+            ));
+        }
+
+        entry.metadata = Some(metadata.clone());
+        Ok(())
+    }
+
+    async fn load(&self) -> anyhow::Result<Option<CoordinatorMetadata>> {
+        let lock = HA_STORAGE.lock().await;
+        Ok(lock
+            .get(&self.application_id)
+            .and_then(|entry| entry.metadata.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::storage::high_availability::mem_ha_storage::MemoryHighAvailabilityStorage;
+    use crate::storage::high_availability::{
+        CoordinatorMetadata, JobStatus, LeaderInfo, THighAvailabilityStorage,
+    };
+
+    fn leader(candidate_id: &str) -> LeaderInfo {
+        LeaderInfo {
+            candidate_id: candidate_id.to_string(),
+            web_address: "".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    pub async fn leader_election_test() {
+        let mut storage0 = MemoryHighAvailabilityStorage::new("leader_election_test");
+        let mut storage1 = MemoryHighAvailabilityStorage::new("leader_election_test");
+        let leader0 = leader("c0");
+        let leader1 = leader("c1");
+        let ttl = Duration::from_millis(100);
+
+        assert!(storage0.try_acquire_leader(&leader0, ttl).await.unwrap());
+        assert!(!storage1.try_acquire_leader(&leader1, ttl).await.unwrap());
+        assert!(storage0.try_acquire_leader(&leader0, ttl).await.unwrap());
+        assert_eq!(storage1.get_leader().await.unwrap(), Some(leader0.clone()));
+
+        // the leadership expires without the renewal
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(storage1.get_leader().await.unwrap(), None);
+        assert!(storage1.try_acquire_leader(&leader1, ttl).await.unwrap());
+        assert!(!storage0.try_acquire_leader(&leader0, ttl).await.unwrap());
+
+        storage1.release_leader(&leader0).await.unwrap();
+        assert_eq!(storage0.get_leader().await.unwrap(), Some(leader1.clone()));
+        storage1.release_leader(&leader1).await.unwrap();
+        assert_eq!(storage0.get_leader().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    pub async fn terminated_status_test() {
+        let mut storage0 = MemoryHighAvailabilityStorage::new("terminated_status_test");
+        let mut storage1 = MemoryHighAvailabilityStorage::new("terminated_status_test");
+        let leader0 = leader("c0");
+        let leader1 = leader("c1");
+        let ttl = Duration::from_secs(10);
+
+        let mut metadata = CoordinatorMetadata {
+            status: JobStatus::Running,
+            startup_number: 1,
+            dag_hash: 0,
+            worker_resources: Vec::new(),
+            completed_checkpoint_id: None,
+        };
+        assert!(storage0.try_acquire_leader(&leader0, ttl).await.unwrap());
+        storage0.save(&leader0, &metadata).await.unwrap();
+        assert!(storage1.save(&leader1, &metadata).await.is_err());
+
+        // the terminal status is saved before the leadership is released
+        metadata.status = JobStatus::Finished;
+        storage0.save(&leader0, &metadata).await.unwrap();
+        storage0.release_leader(&leader0).await.unwrap();
+
+        assert!(storage1.try_acquire_leader(&leader1, ttl).await.unwrap());
+        let recovered = storage1.load().await.unwrap().unwrap();
+        assert!(recovered.status.is_terminated());
+        assert_eq!(recovered.startup_number, 1);
+    }
+}
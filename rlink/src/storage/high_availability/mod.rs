@@ -0,0 +1,268 @@
+use std::time::Duration;
+
+use crate::core::backend::HighAvailabilityBackend;
+use crate::core::cluster::TaskResourceInfo;
+use crate::core::runtime::CheckpointId;
+#[cfg(feature = "ha-etcd")]
+use crate::storage::high_availability::etcd_ha_storage::EtcdHighAvailabilityStorage;
+use crate::storage::high_availability::kubernetes_ha_storage::KubernetesHighAvailabilityStorage;
+use crate::storage::high_availability::mem_ha_storage::MemoryHighAvailabilityStorage;
+#[cfg(feature = "ha-zookeeper")]
+use crate::storage::high_availability::zookeeper_ha_storage::ZooKeeperHighAvailabilityStorage;
+
+#[cfg(feature = "ha-etcd")]
+pub mod etcd_ha_storage;
+pub mod kubernetes_ha_storage;
+pub mod mem_ha_storage;
+#[cfg(feature = "ha-zookeeper")]
+pub mod zookeeper_ha_storage;
+
+/// The root of the keys if the namespace isn't specified
+pub(crate) const DEFAULT_HA_NAMESPACE: &str = "/rlink";
+
+/// The namespace of the Lease and the ConfigMap if it isn't specified
+pub(crate) const DEFAULT_K8S_NAMESPACE: &str = "default";
+
+/// The leadership expires once it isn't renewed in the ttl, the standby coordinators take over
+/// the job after it
+pub(crate) const LEADER_LEASE_TTL: Duration = Duration::from_secs(15);
+
+/// The coordinator holding the leadership
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderInfo {
+    /// the unique id of the coordinator process
+    pub candidate_id: String,
+    /// the web address of the coordinator, it's empty until the web server is launched
+    pub web_address: String,
+}
+
+/// The status of the job saved by the leader, the coordinators exit once it's terminated
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum JobStatus {
+    Running,
+    Finished,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_terminated(&self) -> bool {
+        !matches!(self, JobStatus::Running)
+    }
+}
+
+impl Default for JobStatus {
+    fn default() -> Self {
+        JobStatus::Running
+    }
+}
+
+/// The metadata of the leader coordinator, the new leader takes over the job by it.
+///
+/// It's kept small, the job graph is rebuilt by the new leader and the checkpoints are loaded
+/// from the checkpoint storage
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct CoordinatorMetadata {
+    #[serde(default)]
+    pub status: JobStatus,
+    /// the times the job is started, it's continued by the new leader
+    pub startup_number: u64,
+    /// the hash of the job graph, the change of the graph is warned by the new leader
+    pub dag_hash: u64,
+    /// the resources of the running workers, they're stopped before the job restarts
+    pub worker_resources: Vec<TaskResourceInfo>,
+    /// the pointer to the latest completed checkpoint, its metadata is kept in the checkpoint
+    /// storage
+    pub completed_checkpoint_id: Option<CheckpointId>,
+}
+
+/// The storage of the leader election and the coordinator metadata of an application
+#[async_trait]
+pub(crate) trait THighAvailabilityStorage {
+    /// Try to be the leader, or renew the leadership if it's held by the same candidate. The
+    /// leadership expires once it isn't renewed in the `ttl`. Return whether the candidate is
+    /// the leader
+    async fn try_acquire_leader(
+        &mut self,
+        leader: &LeaderInfo,
+        ttl: Duration,
+    ) -> anyhow::Result<bool>;
+
+    /// the current leader
+    async fn get_leader(&self) -> anyhow::Result<Option<LeaderInfo>>;
+
+    /// give up the leadership if it's held by the candidate
+    async fn release_leader(&mut self, leader: &LeaderInfo) -> anyhow::Result<()>;
+
+    /// save the metadata if the candidate is the leader
+    async fn save(
+        &mut self,
+        leader: &LeaderInfo,
+        metadata: &CoordinatorMetadata,
+    ) -> anyhow::Result<()>;
+
+    /// load the metadata saved by the leaders
+    async fn load(&self) -> anyhow::Result<Option<CoordinatorMetadata>>;
+}
+
+pub(crate) enum HighAvailabilityStorage {
+    MemoryHighAvailabilityStorage(MemoryHighAvailabilityStorage),
+    #[cfg(feature = "ha-etcd")]
+    EtcdHighAvailabilityStorage(EtcdHighAvailabilityStorage),
+    #[cfg(feature = "ha-zookeeper")]
+    ZooKeeperHighAvailabilityStorage(ZooKeeperHighAvailabilityStorage),
+    KubernetesHighAvailabilityStorage(KubernetesHighAvailabilityStorage),
+}
+
+impl HighAvailabilityStorage {
+    pub async fn new(
+        backend: &HighAvailabilityBackend,
+        application_id: &str,
+    ) -> anyhow::Result<Self> {
+        match backend {
+            HighAvailabilityBackend::Memory => {
+                Ok(HighAvailabilityStorage::MemoryHighAvailabilityStorage(
+                    MemoryHighAvailabilityStorage::new(application_id),
+                ))
+            }
+            #[cfg(feature = "ha-etcd")]
+            HighAvailabilityBackend::Etcd {
+                endpoints,
+                namespace,
+            } => {
+                let namespace = namespace.as_deref().unwrap_or(DEFAULT_HA_NAMESPACE);
+                let storage =
+                    EtcdHighAvailabilityStorage::new(endpoints, namespace, application_id).await?;
+                Ok(HighAvailabilityStorage::EtcdHighAvailabilityStorage(
+                    storage,
+                ))
+            }
+            #[cfg(not(feature = "ha-etcd"))]
+            HighAvailabilityBackend::Etcd { .. } => Err(anyhow!(
+                "the etcd high availability backend requires the `ha-etcd` feature"
+            )),
+            #[cfg(feature = "ha-zookeeper")]
+            HighAvailabilityBackend::ZooKeeper { servers, root } => {
+                let root = root.as_deref().unwrap_or(DEFAULT_HA_NAMESPACE);
+                let storage =
+                    ZooKeeperHighAvailabilityStorage::new(servers, root, application_id).await?;
+                Ok(HighAvailabilityStorage::ZooKeeperHighAvailabilityStorage(
+                    storage,
+                ))
+            }
+            #[cfg(not(feature = "ha-zookeeper"))]
+            HighAvailabilityBackend::ZooKeeper { .. } => Err(anyhow!(
+                "the zookeeper high availability backend requires the `ha-zookeeper` feature"
+            )),
+            HighAvailabilityBackend::Kubernetes { namespace } => {
+                let namespace = namespace.as_deref().unwrap_or(DEFAULT_K8S_NAMESPACE);
+                let storage =
+                    KubernetesHighAvailabilityStorage::new(namespace, application_id).await?;
+                Ok(HighAvailabilityStorage::KubernetesHighAvailabilityStorage(
+                    storage,
+                ))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl THighAvailabilityStorage for HighAvailabilityStorage {
+    async fn try_acquire_leader(
+        &mut self,
+        leader: &LeaderInfo,
+        ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        match self {
+            HighAvailabilityStorage::MemoryHighAvailabilityStorage(storage) => {
+                storage.try_acquire_leader(leader, ttl).await
+            }
+            #[cfg(feature = "ha-etcd")]
+            HighAvailabilityStorage::EtcdHighAvailabilityStorage(storage) => {
+                storage.try_acquire_leader(leader, ttl).await
+            }
+            #[cfg(feature = "ha-zookeeper")]
+            HighAvailabilityStorage::ZooKeeperHighAvailabilityStorage(storage) => {
+                storage.try_acquire_leader(leader, ttl).await
+            }
+            HighAvailabilityStorage::KubernetesHighAvailabilityStorage(storage) => {
+                storage.try_acquire_leader(leader, ttl).await
+            }
+        }
+    }
+
+    async fn get_leader(&self) -> anyhow::Result<Option<LeaderInfo>> {
+        match self {
+            HighAvailabilityStorage::MemoryHighAvailabilityStorage(storage) => {
+                storage.get_leader().await
+            }
+            #[cfg(feature = "ha-etcd")]
+            HighAvailabilityStorage::EtcdHighAvailabilityStorage(storage) => {
+                storage.get_leader().await
+            }
+            #[cfg(feature = "ha-zookeeper")]
+            HighAvailabilityStorage::ZooKeeperHighAvailabilityStorage(storage) => {
+                storage.get_leader().await
+            }
+            HighAvailabilityStorage::KubernetesHighAvailabilityStorage(storage) => {
+                storage.get_leader().await
+            }
+        }
+    }
+
+    async fn release_leader(&mut self, leader: &LeaderInfo) -> anyhow::Result<()> {
+        match self {
+            HighAvailabilityStorage::MemoryHighAvailabilityStorage(storage) => {
+                storage.release_leader(leader).await
+            }
+            #[cfg(feature = "ha-etcd")]
+            HighAvailabilityStorage::EtcdHighAvailabilityStorage(storage) => {
+                storage.release_leader(leader).await
+            }
+            #[cfg(feature = "ha-zookeeper")]
+            HighAvailabilityStorage::ZooKeeperHighAvailabilityStorage(storage) => {
+                storage.release_leader(leader).await
+            }
+            HighAvailabilityStorage::KubernetesHighAvailabilityStorage(storage) => {
+                storage.release_leader(leader).await
+            }
+        }
+    }
+
+    async fn save(
+        &mut self,
+        leader: &LeaderInfo,
+        metadata: &CoordinatorMetadata,
+    ) -> anyhow::Result<()> {
+        match self {
+            HighAvailabilityStorage::MemoryHighAvailabilityStorage(storage) => {
+                storage.save(leader, metadata).await
+            }
+            #[cfg(feature = "ha-etcd")]
+            HighAvailabilityStorage::EtcdHighAvailabilityStorage(storage) => {
+                storage.save(leader, metadata).await
+            }
+            #[cfg(feature = "ha-zookeeper")]
+            HighAvailabilityStorage::ZooKeeperHighAvailabilityStorage(storage) => {
+                storage.save(leader, metadata).await
+            }
+            HighAvailabilityStorage::KubernetesHighAvailabilityStorage(storage) => {
+                storage.save(leader, metadata).await
+            }
+        }
+    }
+
+    async fn load(&self) -> anyhow::Result<Option<CoordinatorMetadata>> {
+        match self {
+            HighAvailabilityStorage::MemoryHighAvailabilityStorage(storage) => storage.load().await,
+            #[cfg(feature = "ha-etcd")]
+            HighAvailabilityStorage::EtcdHighAvailabilityStorage(storage) => storage.load().await,
+            #[cfg(feature = "ha-zookeeper")]
+            HighAvailabilityStorage::ZooKeeperHighAvailabilityStorage(storage) => {
+                storage.load().await
+            }
+            HighAvailabilityStorage::KubernetesHighAvailabilityStorage(storage) => {
+                storage.load().await
+            }
+        }
+    }
+}
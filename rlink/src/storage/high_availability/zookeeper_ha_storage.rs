@@ -0,0 +1,237 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use zookeeper::{
+    Acl, CreateMode, WatchedEvent, Watcher, ZkError, ZkResult, ZooKeeper, ZooKeeperExt,
+};
+
+use crate::storage::high_availability::{
+    CoordinatorMetadata, LeaderInfo, THighAvailabilityStorage, LEADER_LEASE_TTL,
+};
+
+struct LoggingWatcher;
+
+impl Watcher for LoggingWatcher {
+    fn handle(&self, event: WatchedEvent) {
+        debug!("zookeeper event {:?}", event);
+    }
+}
+
+/// The leader node is ephemeral, it's removed by the zookeeper once the session expires, so
+/// the leadership expires with the session timeout of `LEADER_LEASE_TTL` instead of the `ttl`.
+///
+/// The new leader bumps the version of the metadata node once it creates the leader node, and
+/// the metadata is written by the version read before the leadership is checked, so the writes
+/// of a deposed leader fail after the takeover
+pub struct ZooKeeperHighAvailabilityStorage {
+    zk: Arc<ZooKeeper>,
+    leader_path: String,
+    metadata_path: String,
+}
+
+impl ZooKeeperHighAvailabilityStorage {
+    pub async fn new(servers: &str, root: &str, application_id: &str) -> anyhow::Result<Self> {
+        let servers = servers.to_string();
+        let parent_path = format!("{}/{}", root.trim_end_matches('/'), application_id);
+
+        let zk_parent_path = parent_path.clone();
+        let zk = tokio::task::spawn_blocking(move || -> ZkResult<ZooKeeper> {
+            let zk = ZooKeeper::connect(servers.as_str(), LEADER_LEASE_TTL, LoggingWatcher)?;
+            zk.ensure_path(zk_parent_path.as_str())?;
+            Ok(zk)
+        })
+        .await??;
+
+        Ok(ZooKeeperHighAvailabilityStorage {
+            zk: Arc::new(zk),
+            leader_path: format!("{}/leader", parent_path),
+            metadata_path: format!("{}/metadata", parent_path),
+        })
+    }
+
+    /// Run the blocking zookeeper operation
+    async fn blocking<F, T>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&ZooKeeper) -> ZkResult<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let zk = self.zk.clone();
+        let rt = tokio::task::spawn_blocking(move || f(zk.as_ref())).await?;
+        rt.map_err(|e| anyhow!("zookeeper error. {}", e))
+    }
+
+    /// Whether the leader node is held by the candidate
+    fn is_leader(zk: &ZooKeeper, leader_path: &str, candidate_id: &str) -> ZkResult<bool> {
+        match zk.get_data(leader_path, false) {
+            Ok((data, _stat)) => {
+                let current: Option<LeaderInfo> = serde_json::from_slice(data.as_slice()).ok();
+                Ok(current
+                    .map(|x| x.candidate_id.eq(candidate_id))
+                    .unwrap_or(false))
+            }
+            Err(ZkError::NoNode) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The data and the version of the metadata node, `None` if it's absent
+    fn get_metadata(zk: &ZooKeeper, metadata_path: &str) -> ZkResult<Option<(Vec<u8>, i32)>> {
+        match zk.get_data(metadata_path, false) {
+            Ok((data, stat)) => Ok(Some((data, stat.version))),
+            Err(ZkError::NoNode) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the metadata node if it isn't changed since the `version` is read, the node is
+    /// created if the `version` is `None`. Return false on the conflict
+    fn write_metadata(
+        zk: &ZooKeeper,
+        metadata_path: &str,
+        value: Vec<u8>,
+        version: Option<i32>,
+    ) -> ZkResult<bool> {
+        let result = match version {
+            Some(version) => zk.set_data(metadata_path, value, Some(version)).map(|_| ()),
+            None => {
+                let acl = Acl::open_unsafe().clone();
+                zk.create(metadata_path, value, acl, CreateMode::Persistent)
+                    .map(|_| ())
+            }
+        };
+        match result {
+            Ok(()) => Ok(true),
+            Err(ZkError::BadVersion) | Err(ZkError::NoNode) | Err(ZkError::NodeExists) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Bump the version of the metadata node after the leader node is created, it's created
+    /// empty if absent
+    fn claim_metadata(zk: &ZooKeeper, metadata_path: &str) -> ZkResult<()> {
+        loop {
+            let (data, version) = match Self::get_metadata(zk, metadata_path)? {
+                Some((data, version)) => (data, Some(version)),
+                None => (vec![], None),
+            };
+            if Self::write_metadata(zk, metadata_path, data, version)? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl THighAvailabilityStorage for ZooKeeperHighAvailabilityStorage {
+    async fn try_acquire_leader(
+        &mut self,
+        leader: &LeaderInfo,
+        _ttl: Duration,
+    ) -> anyhow::Result<bool> {
+        let value = serde_json::to_vec(leader)?;
+        let candidate_id = leader.candidate_id.clone();
+        let leader_path = self.leader_path.clone();
+        let metadata_path = self.metadata_path.clone();
+
+        self.blocking(move |zk| {
+            let acl = Acl::open_unsafe().clone();
+            match zk.create(
+                leader_path.as_str(),
+                value.clone(),
+                acl,
+                CreateMode::Ephemeral,
+            ) {
+                Ok(_) => {
+                    Self::claim_metadata(zk, metadata_path.as_str())?;
+                    Ok(true)
+                }
+                Err(ZkError::NodeExists) => {
+                    if Self::is_leader(zk, leader_path.as_str(), candidate_id.as_str())? {
+                        // update the leader info, e.g. the web address
+                        zk.set_data(leader_path.as_str(), value, None)?;
+                        Ok(true)
+                    } else {
+                        Ok(false)
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        })
+        .await
+    }
+
+    async fn get_leader(&self) -> anyhow::Result<Option<LeaderInfo>> {
+        let leader_path = self.leader_path.clone();
+        let data = self
+            .blocking(move |zk| match zk.get_data(leader_path.as_str(), false) {
+                Ok((data, _stat)) => Ok(Some(data)),
+                Err(ZkError::NoNode) => Ok(None),
+                Err(e) => Err(e),
+            })
+            .await?;
+        match data {
+            Some(data) => Ok(Some(serde_json::from_slice(data.as_slice())?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn release_leader(&mut self, leader: &LeaderInfo) -> anyhow::Result<()> {
+        let candidate_id = leader.candidate_id.clone();
+        let leader_path = self.leader_path.clone();
+        self.blocking(move |zk| {
+            if Self::is_leader(zk, leader_path.as_str(), candidate_id.as_str())? {
+                zk.delete(leader_path.as_str(), None)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn save(
+        &mut self,
+        leader: &LeaderInfo,
+        metadata: &CoordinatorMetadata,
+    ) -> anyhow::Result<()> {
+        let value = serde_json::to_vec(metadata)?;
+        let candidate_id = leader.candidate_id.clone();
+        let leader_path = self.leader_path.clone();
+        let metadata_path = self.metadata_path.clone();
+
+        let saved = self
+            .blocking(move |zk| loop {
+                // the version is read before the leadership is checked, so the write conflicts
+                // with the claim of a new leader taking over in between, and it's checked again
+                let version = Self::get_metadata(zk, metadata_path.as_str())?.map(|x| x.1);
+                if !Self::is_leader(zk, leader_path.as_str(), candidate_id.as_str())? {
+                    return Ok(false);
+                }
+                if Self::write_metadata(zk, metadata_path.as_str(), value.clone(), version)? {
+                    return Ok(true);
+                }
+            })
+            .await?;
+        if !saved {
+            return Err(anyhow!(
+                "the candidate {} isn't the leader",
+                leader.candidate_id
+            ));
+        }
+        Ok(())
+    }
+
+    async fn load(&self) -> anyhow::Result<Option<CoordinatorMetadata>> {
+        let metadata_path = self.metadata_path.clone();
+        let data = self
+            .blocking(move |zk| match zk.get_data(metadata_path.as_str(), false) {
+                Ok((data, _stat)) => Ok(Some(data)),
+                Err(ZkError::NoNode) => Ok(None),
+                Err(e) => Err(e),
+            })
+            .await?;
+        // the metadata node claimed by the first leader is empty until it's saved
+        match data {
+            Some(data) if !data.is_empty() => Ok(Some(serde_json::from_slice(data.as_slice())?)),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::TryStreamExt;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use tokio::io::AsyncWriteExt;
+
+use crate::core::runtime::{CheckpointId, JobId};
+use crate::storage::object_storage;
+
+const SHARED_DIR: &str = "shared";
+const METADATA_FILE: &str = "_metadata";
+/// the immutable files of the rocksdb, shared by the checkpoints
+const SHARED_FILE_SUFFIX: &str = ".sst";
+
+/// The files of a checkpoint, the shared files are in the `shared` directory and the private
+/// files are in the directory of the checkpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IncrementalCheckpointMetadata {
+    pub checkpoint_id: CheckpointId,
+    /// the local file and its object in the `shared` directory, the object is prefixed by the
+    /// checkpoint uploading it, as a rocksdb restored by the key groups reuses the file names
+    pub shared_files: BTreeMap<String, String>,
+    pub private_files: Vec<String>,
+}
+
+/// Upload the local checkpoints of a task to the object store, e.g.
+/// `s3://bucket/path/{application_id}/{job_id}-{task_number}/chk-{checkpoint_id}/_metadata`.
+/// The shared files uploaded by the previous checkpoints are not uploaded again, a shared
+/// file is deleted when no retained checkpoint references it
+pub struct IncrementalCheckpointUploader {
+    store: Arc<dyn ObjectStore>,
+    base_path: Path,
+    retained_checkpoints: usize,
+
+    /// the references of the objects in the `shared` directory
+    shared_refs: BTreeMap<String, usize>,
+    /// the objects of the local shared files uploaded
+    shared_objects: BTreeMap<String, String>,
+    checkpoints: VecDeque<IncrementalCheckpointMetadata>,
+}
+
+impl IncrementalCheckpointUploader {
+    pub fn new(
+        url: &str,
+        options: &HashMap<String, String>,
+        application_id: &str,
+        job_id: JobId,
+        task_number: u16,
+        retained_checkpoints: usize,
+    ) -> anyhow::Result<Self> {
+        let (store, base_path) = object_storage::parse_url(url, options)?;
+        let base_path = base_path
+            .child(application_id)
+            .child(format!("{}-{}", job_id.0, task_number));
+        Ok(IncrementalCheckpointUploader {
+            store,
+            base_path,
+            retained_checkpoints: retained_checkpoints.max(1),
+            shared_refs: BTreeMap::new(),
+            shared_objects: BTreeMap::new(),
+            checkpoints: VecDeque::new(),
+        })
+    }
+
+    fn shared_path(&self, object: &str) -> Path {
+        self.base_path.child(SHARED_DIR).child(object)
+    }
+
+    fn checkpoint_path(&self, checkpoint_id: CheckpointId, file: &str) -> Path {
+        self.base_path
+            .child(format!("chk-{}", checkpoint_id.0))
+            .child(file)
+    }
+
+    /// Upload the files of the local checkpoint directory not uploaded yet, the checkpoint is
+    /// restorable once it returns
+    pub async fn upload(
+        &mut self,
+        checkpoint_id: CheckpointId,
+        local_path: &std::path::Path,
+    ) -> anyhow::Result<IncrementalCheckpointMetadata> {
+        let mut metadata = IncrementalCheckpointMetadata {
+            checkpoint_id,
+            shared_files: BTreeMap::new(),
+            private_files: vec![],
+        };
+
+        let mut uploaded_bytes = 0;
+        for entry in std::fs::read_dir(local_path)? {
+            let entry = entry?;
+            let file = match entry.file_name().to_str() {
+                Some(file) => file.to_string(),
+                None => continue,
+            };
+
+            let path = if file.ends_with(SHARED_FILE_SUFFIX) {
+                if let Some(object) = self.shared_objects.get(file.as_str()) {
+                    metadata.shared_files.insert(file, object.clone());
+                    continue;
+                }
+                let object = format!("{}-{}", checkpoint_id.0, file);
+                let path = self.shared_path(object.as_str());
+                metadata.shared_files.insert(file, object);
+                path
+            } else {
+                let path = self.checkpoint_path(checkpoint_id, file.as_str());
+                metadata.private_files.push(file);
+                path
+            };
+
+            uploaded_bytes += self.upload_file(entry.path().as_path(), &path).await?;
+        }
+
+        let payload = serde_json::to_vec(&metadata)?;
+        self.store
+            .put(
+                &self.checkpoint_path(checkpoint_id, METADATA_FILE),
+                Bytes::from(payload),
+            )
+            .await?;
+        self.retain(metadata.clone());
+
+        info!(
+            "incremental checkpoint {:?} uploaded {} bytes, shared files {}, private files {}",
+            checkpoint_id,
+            uploaded_bytes,
+            metadata.shared_files.len(),
+            metadata.private_files.len()
+        );
+
+        while self.checkpoints.len() > self.retained_checkpoints {
+            let expired = self.checkpoints.pop_front().unwrap();
+            self.release(expired).await?;
+        }
+
+        Ok(metadata)
+    }
+
+    /// The metadata of the uploaded checkpoint, `None` if it isn't uploaded
+    pub async fn load_metadata(
+        &self,
+        checkpoint_id: CheckpointId,
+    ) -> anyhow::Result<Option<IncrementalCheckpointMetadata>> {
+        let payload = match self
+            .store
+            .get(&self.checkpoint_path(checkpoint_id, METADATA_FILE))
+            .await
+        {
+            Ok(result) => result.bytes().await?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Some(serde_json::from_slice(payload.as_ref())?))
+    }
+
+    /// Download the checkpoint to the local directory, which is created once all files are
+    /// downloaded. Returns `None` if the checkpoint isn't uploaded
+    pub async fn download(
+        &self,
+        checkpoint_id: CheckpointId,
+        local_path: &std::path::Path,
+    ) -> anyhow::Result<Option<IncrementalCheckpointMetadata>> {
+        let metadata = match self.load_metadata(checkpoint_id).await? {
+            Some(metadata) => metadata,
+            None => return Ok(None),
+        };
+
+        let download_path = local_path.with_extension("download");
+        if download_path.exists() {
+            std::fs::remove_dir_all(&download_path)?;
+        }
+        std::fs::create_dir_all(&download_path)?;
+
+        let mut downloaded_bytes = 0;
+        for (file, object) in &metadata.shared_files {
+            let path = self.shared_path(object.as_str());
+            downloaded_bytes += self
+                .download_file(&path, download_path.join(file).as_path())
+                .await?;
+        }
+        for file in &metadata.private_files {
+            let path = self.checkpoint_path(checkpoint_id, file.as_str());
+            downloaded_bytes += self
+                .download_file(&path, download_path.join(file).as_path())
+                .await?;
+        }
+        std::fs::rename(&download_path, local_path)?;
+
+        info!(
+            "incremental checkpoint {:?} downloaded {} bytes to {:?}",
+            checkpoint_id, downloaded_bytes, local_path
+        );
+        Ok(Some(metadata))
+    }
+
+    /// Track the checkpoint, so its shared files are reused by the next checkpoints and deleted
+    /// once it's expired, e.g. the checkpoint the rocksdb is restored from
+    pub fn retain(&mut self, metadata: IncrementalCheckpointMetadata) {
+        for (file, object) in &metadata.shared_files {
+            *self.shared_refs.entry(object.clone()).or_insert(0) += 1;
+            self.shared_objects.insert(file.clone(), object.clone());
+        }
+        self.checkpoints.push_back(metadata);
+    }
+
+    /// Stream the local file to the object, the file isn't loaded in the memory
+    async fn upload_file(&self, local_path: &std::path::Path, path: &Path) -> anyhow::Result<u64> {
+        let mut file = tokio::fs::File::open(local_path).await?;
+        let (multipart_id, mut writer) = self.store.put_multipart(path).await?;
+        let result = async {
+            let size = tokio::io::copy(&mut file, &mut writer).await?;
+            writer.shutdown().await?;
+            Ok::<u64, anyhow::Error>(size)
+        }
+        .await;
+        if result.is_err() {
+            if let Err(e) = self.store.abort_multipart(path, &multipart_id).await {
+                warn!("abort the upload of {} error. {}", path, e);
+            }
+        }
+        result
+    }
+
+    async fn download_file(
+        &self,
+        path: &Path,
+        local_path: &std::path::Path,
+    ) -> anyhow::Result<u64> {
+        let mut stream = self.store.get(path).await?.into_stream();
+        let mut file = tokio::fs::File::create(local_path).await?;
+        let mut size = 0;
+        while let Some(bytes) = stream.try_next().await? {
+            size += bytes.len() as u64;
+            file.write_all(bytes.as_ref()).await?;
+        }
+        file.sync_all().await?;
+        Ok(size)
+    }
+
+    /// Delete the checkpoint and the shared files referenced only by it
+    async fn release(&mut self, metadata: IncrementalCheckpointMetadata) -> anyhow::Result<()> {
+        for (file, object) in &metadata.shared_files {
+            let unreferenced = match self.shared_refs.get_mut(object.as_str()) {
+                Some(refs) => {
+                    *refs -= 1;
+                    *refs == 0
+                }
+                None => false,
+            };
+            if unreferenced {
+                self.shared_refs.remove(object.as_str());
+                if self.shared_objects.get(file.as_str()) == Some(object) {
+                    self.shared_objects.remove(file.as_str());
+                }
+                self.store
+                    .delete(&self.shared_path(object.as_str()))
+                    .await?;
+            }
+        }
+
+        for file in metadata
+            .private_files
+            .iter()
+            .map(|x| x.as_str())
+            .chain(std::iter::once(METADATA_FILE))
+        {
+            let path = self.checkpoint_path(metadata.checkpoint_id, file);
+            self.store.delete(&path).await?;
+        }
+
+        debug!(
+            "release incremental checkpoint {:?}",
+            metadata.checkpoint_id
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use object_store::path::Path;
+
+    use crate::core::runtime::{CheckpointId, JobId};
+    use crate::storage::keyed_state::incremental_checkpoint::IncrementalCheckpointUploader;
+
+    #[tokio::test]
+    pub async fn incremental_checkpoint_test() {
+        let root = std::env::temp_dir().join(format!("rlink-incr-{}", std::process::id()));
+        let local = root.join("local");
+        let url = format!("file://{}", root.join("remote").to_string_lossy());
+        std::fs::create_dir_all(root.join("remote")).unwrap();
+
+        let mut uploader = IncrementalCheckpointUploader::new(
+            url.as_str(),
+            &HashMap::new(),
+            "app",
+            JobId(1),
+            0,
+            1,
+        )
+        .unwrap();
+
+        let write = |checkpoint_id: u64, files: &[&str]| {
+            let dir = local.join(checkpoint_id.to_string());
+            std::fs::create_dir_all(&dir).unwrap();
+            for file in files {
+                std::fs::write(dir.join(file), file.as_bytes()).unwrap();
+            }
+            dir
+        };
+
+        let dir = write(1, &["1.sst", "2.sst", "MANIFEST-1"]);
+        let metadata = uploader.upload(CheckpointId(1), &dir).await.unwrap();
+        assert_eq!(metadata.shared_files.len(), 2);
+
+        let dir = write(2, &["2.sst", "3.sst", "MANIFEST-2"]);
+        uploader.upload(CheckpointId(2), &dir).await.unwrap();
+
+        // the checkpoint 1 is released, `1.sst` is referenced by no checkpoint
+        assert!(uploader.shared_refs.get("1-1.sst").is_none());
+        assert_eq!(uploader.shared_refs.get("1-2.sst"), Some(&1));
+        assert_eq!(uploader.shared_refs.get("2-3.sst"), Some(&1));
+
+        let exists = |path: Path| {
+            let store = uploader.store.clone();
+            async move { store.head(&path).await.is_ok() }
+        };
+        assert!(!exists(uploader.shared_path("1-1.sst")).await);
+        assert!(exists(uploader.shared_path("1-2.sst")).await);
+        assert!(!exists(uploader.checkpoint_path(CheckpointId(1), "MANIFEST-1")).await);
+        assert!(exists(uploader.checkpoint_path(CheckpointId(2), "_metadata")).await);
+
+        let restored = root.join("restored");
+        assert!(uploader
+            .download(CheckpointId(1), &restored)
+            .await
+            .unwrap()
+            .is_none());
+        let metadata = uploader
+            .download(CheckpointId(2), &restored)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.shared_files.len(), 2);
+        assert_eq!(std::fs::read(restored.join("2.sst")).unwrap(), b"2.sst");
+        assert_eq!(
+            std::fs::read(restored.join("MANIFEST-2")).unwrap(),
+            b"MANIFEST-2"
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -33,6 +33,20 @@ impl MemoryReducingState {
 
         state
     }
+
+    /// Convert the values of the keys in place
+    pub(crate) fn map_values<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Record, &mut Record) -> Record,
+    {
+        for (key, value) in self.kv.iter_mut() {
+            *value = f(key, value);
+        }
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &Record> {
+        self.kv.keys()
+    }
 }
 
 impl TReducingState for MemoryReducingState {
@@ -44,6 +58,10 @@ impl TReducingState for MemoryReducingState {
         self.kv.insert(key, val);
     }
 
+    fn remove(&mut self, key: &Record) -> Option<Record> {
+        self.kv.remove(key)
+    }
+
     fn flush(&mut self) {}
 
     fn snapshot(&mut self) {}
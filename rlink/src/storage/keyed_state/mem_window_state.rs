@@ -1,12 +1,13 @@
-use std::borrow::BorrowMut;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::core::element::{Barrier, Record};
 use crate::core::runtime::JobId;
 use crate::core::window::Window;
 use crate::storage::keyed_state::mem_reducing_state::MemoryReducingState;
 use crate::storage::keyed_state::mem_storage::{append_drop_window, StorageKey};
-use crate::storage::keyed_state::{StateKey, TReducingState, TWindowState};
+use crate::storage::keyed_state::{
+    merging_session_windows, ResultFunction, StateKey, TReducingState, TWindowState,
+};
 
 #[derive(Clone)]
 pub struct MemoryWindowState {
@@ -16,6 +17,8 @@ pub struct MemoryWindowState {
     task_number: u16,
 
     windows: HashMap<Window, MemoryReducingState>,
+    /// The session windows of each key, the merging windows are looked up by the key
+    session_windows: HashMap<Record, HashSet<Window>>,
 }
 
 impl MemoryWindowState {
@@ -25,6 +28,7 @@ impl MemoryWindowState {
             job_id,
             task_number,
             windows: HashMap::new(),
+            session_windows: HashMap::new(),
         }
     }
 
@@ -58,6 +62,72 @@ impl MemoryWindowState {
             }
         }
     }
+
+    /// Move the values of the key in the session windows intersecting the `window` to the
+    /// window covers them, the emptied windows are removed. Returns the covered window
+    fn merge_session_windows<M>(&mut self, window: &Window, key: &Record, merge_fun: M) -> Window
+    where
+        M: Fn(&mut Record, &mut Record) -> Record,
+    {
+        let key_windows = match self.session_windows.get_mut(key) {
+            Some(key_windows) => key_windows,
+            None => return window.clone(),
+        };
+        let (merged_window, merging_windows) = merging_session_windows(window, key_windows.iter());
+        for w in merging_windows.iter().filter(|w| **w != merged_window) {
+            key_windows.remove(w);
+        }
+
+        let mut merged_value: Option<Record> = None;
+        for w in merging_windows.iter().filter(|w| **w != merged_window) {
+            let state = self.windows.get_mut(w).unwrap();
+            let mut value = state.remove(key).unwrap();
+            if state.len() == 0 {
+                self.windows.remove(w);
+            }
+
+            merged_value = match merged_value {
+                Some(mut merged_value) => Some(merge_fun(&mut merged_value, &mut value)),
+                None => Some(value),
+            };
+        }
+
+        if let Some(mut merged_value) = merged_value {
+            let (job_id, task_number) = (self.job_id, self.task_number);
+            let state = self
+                .windows
+                .entry(merged_window.clone())
+                .or_insert_with(|| {
+                    let state_key = StateKey::new(merged_window.clone(), job_id, task_number);
+                    MemoryReducingState::new(&state_key)
+                });
+            match state.get_mut(key) {
+                Some(state_record) => {
+                    let new_val = merge_fun(state_record, &mut merged_value);
+                    *state_record = new_val;
+                }
+                None => state.insert(key.clone(), merged_value),
+            }
+        }
+
+        merged_window
+    }
+
+    /// Remove the window, and the window from the session windows of its keys
+    fn remove_window(&mut self, window: &Window) -> Option<MemoryReducingState> {
+        let state = self.windows.remove(window)?;
+        if window.is_session_window() {
+            for key in state.keys() {
+                if let Some(key_windows) = self.session_windows.get_mut(key) {
+                    key_windows.remove(window);
+                    if key_windows.is_empty() {
+                        self.session_windows.remove(key);
+                    }
+                }
+            }
+        }
+        Some(state)
+    }
 }
 
 impl TWindowState for MemoryWindowState {
@@ -70,28 +140,60 @@ impl TWindowState for MemoryWindowState {
         windows
     }
 
-    fn merge<F>(&mut self, key: Record, mut record: Record, reduce_fun: F) -> usize
+    fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    fn merge<F, M>(
+        &mut self,
+        key: Record,
+        record: &mut Record,
+        reduce_fun: F,
+        merge_fun: M,
+    ) -> Vec<Window>
     where
         F: Fn(Option<&mut Record>, &mut Record) -> Record,
+        M: Fn(&mut Record, &mut Record) -> Record,
     {
-        let windows = record.location_windows();
+        let windows = record.location_windows().clone();
 
-        if windows.len() == 1 {
-            let window = &windows[0].clone();
-            self.merge_value(window, key, record.borrow_mut(), reduce_fun);
+        if windows.iter().any(|w| w.is_session_window()) {
+            let mut merged_windows = Vec::with_capacity(windows.len());
+            for window in &windows {
+                let window = self.merge_session_windows(window, &key, &merge_fun);
+                self.merge_value(&window, key.clone(), record, |value, record| {
+                    reduce_fun(value, record)
+                });
+                self.session_windows
+                    .entry(key.clone())
+                    .or_insert_with(HashSet::new)
+                    .insert(window.clone());
+                merged_windows.push(window);
+            }
+            merged_windows
+        } else if windows.len() == 1 {
+            self.merge_value(&windows[0], key, record, reduce_fun);
+            windows
         } else {
-            for window in &windows.clone() {
-                self.merge_value(window, key.clone(), record.borrow_mut(), |value, record| {
+            for window in &windows {
+                self.merge_value(window, key.clone(), record, |value, record| {
                     reduce_fun(value, record)
                 })
             }
+            windows
         }
-        self.windows.len()
     }
 
-    fn drop_window(&mut self, window: &Window) -> usize {
-        match self.windows.remove(&window) {
-            Some(state) => {
+    fn get(&mut self, window: &Window, key: &Record) -> Option<Record> {
+        self.windows.get_mut(window)?.get_mut(key).cloned()
+    }
+
+    fn drop_window(&mut self, window: &Window, result_fun: Option<ResultFunction>) -> usize {
+        match self.remove_window(window) {
+            Some(mut state) => {
+                if let Some(result_fun) = result_fun {
+                    state.map_values(result_fun);
+                }
                 let state_key = StorageKey::new(self.job_id, self.task_number);
                 append_drop_window(state_key, window.clone(), state);
             }
@@ -100,5 +202,132 @@ impl TWindowState for MemoryWindowState {
         self.windows.len()
     }
 
+    fn fire_window(&mut self, window: &Window, result_fun: Option<ResultFunction>) {
+        if let Some(state) = self.windows.get(window) {
+            let mut state = state.clone();
+            if let Some(result_fun) = result_fun {
+                state.map_values(result_fun);
+            }
+            let state_key = StorageKey::new(self.job_id, self.task_number);
+            append_drop_window(state_key, window.clone(), state);
+        }
+    }
+
+    fn purge_window(&mut self, window: &Window) -> usize {
+        self.remove_window(window);
+        self.windows.len()
+    }
+
     fn snapshot(&mut self, _barrier: Barrier) {}
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::core::data_types::{DataType, Field, Schema};
+    use crate::core::element::Record;
+    use crate::core::runtime::JobId;
+    use crate::core::window::{TWindow, TimeWindow, Window};
+    use crate::storage::keyed_state::mem_storage::remove_drop_window;
+    use crate::storage::keyed_state::mem_window_state::MemoryWindowState;
+    use crate::storage::keyed_state::{ResultFunction, TReducingState, TWindowState};
+
+    fn count(value: &mut Record) -> u64 {
+        let schema = Schema::new(vec![Field::new("count", DataType::UInt64)]);
+        value.as_reader(schema.as_type_ids()).get_u64(0).unwrap()
+    }
+
+    fn count_record(n: u64) -> Record {
+        let schema = Schema::new(vec![Field::new("count", DataType::UInt64)]);
+        let mut record = Record::new();
+        record.as_writer(schema.as_type_ids()).set_u64(n).unwrap();
+        record
+    }
+
+    fn session_record(start: u64, end: u64) -> Record {
+        let mut record = Record::new();
+        record.set_location_windows(vec![Window::SessionWindow(TimeWindow::new(start, end))]);
+        record
+    }
+
+    #[test]
+    pub fn session_window_merge_test() {
+        let mut state = MemoryWindowState::new("app".to_string(), JobId(0), 0);
+        let key = count_record(1);
+        let other_key = count_record(2);
+
+        let mut merge = |key: &Record, mut record: Record| {
+            state.merge(
+                key.clone(),
+                &mut record,
+                |value, _record| count_record(value.map(|v| count(v)).unwrap_or(0) + 1),
+                |value, other| count_record(count(value) + count(other)),
+            )
+        };
+        merge(&key, session_record(0, 10));
+        merge(&key, session_record(20, 30));
+        merge(&other_key, session_record(0, 10));
+        merge(&key, session_record(40, 50));
+        assert_eq!(state.len(), 3);
+
+        // bridge the sessions [0, 10) and [20, 30) of the key
+        let windows = merge(&key, session_record(8, 20));
+        assert_eq!(windows, vec![Window::SessionWindow(TimeWindow::new(0, 30))]);
+        assert_eq!(state.len(), 3);
+
+        let mut windows = state.windows();
+        windows.sort_by_key(|w| w.max_timestamp());
+        assert_eq!(
+            windows,
+            vec![
+                Window::SessionWindow(TimeWindow::new(0, 10)),
+                Window::SessionWindow(TimeWindow::new(0, 30)),
+                Window::SessionWindow(TimeWindow::new(40, 50)),
+            ]
+        );
+
+        let merged_window = Window::SessionWindow(TimeWindow::new(0, 30));
+        let merged_state = state.windows.get_mut(&merged_window).unwrap();
+        assert_eq!(count(merged_state.get_mut(&key).unwrap()), 3);
+        let other_window = Window::SessionWindow(TimeWindow::new(0, 10));
+        let other_state = state.windows.get_mut(&other_window).unwrap();
+        assert_eq!(count(other_state.get_mut(&other_key).unwrap()), 1);
+
+        // the session windows of the key are indexed until they're purged
+        assert_eq!(state.session_windows[&key].len(), 2);
+        state.purge_window(&merged_window);
+        state.purge_window(&Window::SessionWindow(TimeWindow::new(40, 50)));
+        assert!(!state.session_windows.contains_key(&key));
+        assert_eq!(state.session_windows[&other_key].len(), 1);
+    }
+
+    #[test]
+    pub fn fire_window_result_test() {
+        let job_id = JobId(1);
+        let mut state = MemoryWindowState::new("app".to_string(), job_id, 0);
+        let key = count_record(1);
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+
+        for _ in 0..2 {
+            let mut record = Record::new();
+            record.set_location_windows(vec![window.clone()]);
+            state.merge(
+                key.clone(),
+                &mut record,
+                |value, _record| count_record(value.map(|v| count(v)).unwrap_or(0) + 1),
+                |value, other| count_record(count(value) + count(other)),
+            );
+        }
+
+        let mut double = |_key: &Record, value: &mut Record| count_record(count(value) * 2);
+        let result_fun: Option<ResultFunction> = Some(&mut double);
+        state.fire_window(&window, result_fun);
+        let mut fired_state = remove_drop_window(job_id, 0, window.clone()).unwrap();
+        assert_eq!(count(fired_state.get_mut(&key).unwrap()), 4);
+
+        // the accumulator is kept in the window
+        state.drop_window(&window, None);
+        let mut dropped_state = remove_drop_window(job_id, 0, window).unwrap();
+        assert_eq!(count(dropped_state.get_mut(&key).unwrap()), 2);
+        assert_eq!(state.len(), 0);
+    }
+}
@@ -1,16 +1,92 @@
 use std::collections::btree_map::IntoIter;
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use crate::core::backend::KeyedStateBackend;
 use crate::core::element::{Barrier, Record};
+use crate::core::function::Context;
 use crate::core::runtime::JobId;
+use crate::core::state::KeyedStateStorage;
 use crate::core::window::Window;
 use crate::storage::keyed_state::mem_reducing_state::MemoryReducingState;
+#[cfg(feature = "rocksdb")]
+use crate::storage::keyed_state::mem_storage::remove_drop_window;
 use crate::storage::keyed_state::mem_window_state::MemoryWindowState;
+#[cfg(feature = "rocksdb")]
+use crate::storage::keyed_state::rocksdb_keyed_state::RocksDBKeyedState;
+#[cfg(feature = "rocksdb")]
+use crate::storage::keyed_state::rocksdb_reducing_state::{
+    RocksDBReducingState, RocksDBStateIterator,
+};
+#[cfg(feature = "rocksdb")]
+use crate::storage::keyed_state::rocksdb_storage::RocksDBStorage;
+#[cfg(feature = "rocksdb")]
+use crate::storage::keyed_state::rocksdb_window_state::RocksDBWindowState;
 
+pub mod incremental_checkpoint;
 pub mod mem_reducing_state;
 pub mod mem_storage;
 pub mod mem_window_state;
+#[cfg(feature = "rocksdb")]
+pub(crate) mod rocksdb_keyed_state;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb_reducing_state;
+#[cfg(feature = "rocksdb")]
+pub(crate) mod rocksdb_storage;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb_window_state;
+
+/// The storage of the `RuntimeContext` states of the operator in the `context` by the keyed
+/// state `backend`, `None` if the states are kept in the memory
+pub(crate) fn keyed_state_storage(
+    context: &Context,
+    backend: &KeyedStateBackend,
+) -> anyhow::Result<Option<Arc<dyn KeyedStateStorage>>> {
+    match backend {
+        KeyedStateBackend::Memory => Ok(None),
+        #[cfg(feature = "rocksdb")]
+        KeyedStateBackend::RocksDB { .. } => {
+            let storage = RocksDBStorage::open(context, backend)?;
+            Ok(Some(Arc::new(RocksDBKeyedState::new(
+                storage,
+                context.operator_id,
+            ))))
+        }
+        #[cfg(not(feature = "rocksdb"))]
+        KeyedStateBackend::RocksDB { .. } => Err(anyhow!(
+            "the keyed states of the operator {:?} in the rocksdb requires the `rocksdb` feature",
+            context.operator_id
+        )),
+    }
+}
+
+/// Convert the reduced value of the key to the result when the window is handed over to the
+/// downstream, e.g. the accumulator of the `AggregateFunction`
+pub type ResultFunction<'a> = &'a mut dyn FnMut(&Record, &mut Record) -> Record;
+
+/// The session windows in `windows` intersecting the session `window`, and the window covers
+/// all of them. The other windows are ignored
+pub(crate) fn merging_session_windows<'a, I>(window: &Window, windows: I) -> (Window, Vec<Window>)
+where
+    I: Iterator<Item = &'a Window>,
+{
+    let time_window = match window {
+        Window::SessionWindow(time_window) => time_window,
+        Window::TimeWindow(_) | Window::CountWindow(_) => return (window.clone(), vec![]),
+    };
+
+    let mut merged_window = time_window.clone();
+    let mut merging_windows = Vec::new();
+    for w in windows {
+        if let Window::SessionWindow(session_window) = w {
+            if time_window.intersects(session_window.clone()) {
+                merged_window = merged_window.cover(session_window.clone());
+                merging_windows.push(w.clone());
+            }
+        }
+    }
+    (Window::SessionWindow(merged_window), merging_windows)
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct StateKey {
@@ -31,6 +107,8 @@ impl StateKey {
 
 pub enum StateIterator {
     BTreeMap(Window, IntoIter<Record, Record>),
+    #[cfg(feature = "rocksdb")]
+    RocksDB(RocksDBStateIterator),
 }
 
 impl Iterator for StateIterator {
@@ -44,6 +122,8 @@ impl Iterator for StateIterator {
                 key.trigger_window = Some(window.clone());
                 key
             }),
+            #[cfg(feature = "rocksdb")]
+            StateIterator::RocksDB(iter) => iter.next(),
         }
     }
 }
@@ -52,6 +132,7 @@ impl Iterator for StateIterator {
 pub trait TReducingState {
     fn get_mut(&mut self, key: &Record) -> Option<&mut Record>;
     fn insert(&mut self, key: Record, val: Record);
+    fn remove(&mut self, key: &Record) -> Option<Record>;
     fn flush(&mut self);
     fn snapshot(&mut self);
     fn close(self);
@@ -62,6 +143,8 @@ pub trait TReducingState {
 
 pub enum ReducingState {
     MemoryReducingState(MemoryReducingState),
+    #[cfg(feature = "rocksdb")]
+    RocksDBReducingState(RocksDBReducingState),
 }
 
 impl ReducingState {
@@ -69,6 +152,22 @@ impl ReducingState {
         match mode {
             KeyedStateBackend::Memory => MemoryReducingState::from(state_key)
                 .map(|state| ReducingState::MemoryReducingState(state)),
+            // the fired windows are copied to the memory, see `TWindowState::fire_window`
+            #[cfg(feature = "rocksdb")]
+            KeyedStateBackend::RocksDB { .. } => match remove_drop_window(
+                state_key.job_id,
+                state_key.task_number,
+                state_key.window.clone(),
+            ) {
+                Some(state) => Some(ReducingState::MemoryReducingState(state)),
+                None => RocksDBReducingState::from(state_key)
+                    .map(|state| ReducingState::RocksDBReducingState(state)),
+            },
+            #[cfg(not(feature = "rocksdb"))]
+            KeyedStateBackend::RocksDB { .. } => {
+                error!("the rocksdb keyed state backend requires the `rocksdb` feature");
+                None
+            }
         }
     }
 }
@@ -77,48 +176,72 @@ impl TReducingState for ReducingState {
     fn get_mut(&mut self, key: &Record) -> Option<&mut Record> {
         match self {
             ReducingState::MemoryReducingState(state) => state.get_mut(key),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.get_mut(key),
         }
     }
 
     fn insert(&mut self, key: Record, val: Record) {
         match self {
             ReducingState::MemoryReducingState(state) => state.insert(key, val),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.insert(key, val),
+        }
+    }
+
+    fn remove(&mut self, key: &Record) -> Option<Record> {
+        match self {
+            ReducingState::MemoryReducingState(state) => state.remove(key),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.remove(key),
         }
     }
 
     fn flush(&mut self) {
         match self {
             ReducingState::MemoryReducingState(state) => state.flush(),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.flush(),
         }
     }
 
     fn snapshot(&mut self) {
         match self {
             ReducingState::MemoryReducingState(state) => state.snapshot(),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.snapshot(),
         }
     }
 
     fn close(self) {
         match self {
             ReducingState::MemoryReducingState(state) => state.close(),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.close(),
         }
     }
 
     fn destroy(self) {
         match self {
             ReducingState::MemoryReducingState(state) => state.destroy(),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.destroy(),
         }
     }
 
     fn iter(self) -> StateIterator {
         match self {
             ReducingState::MemoryReducingState(state) => state.iter(),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.iter(),
         }
     }
 
     fn len(&self) -> usize {
         match self {
             ReducingState::MemoryReducingState(state) => state.len(),
+            #[cfg(feature = "rocksdb")]
+            ReducingState::RocksDBReducingState(state) => state.len(),
         }
     }
 }
@@ -126,11 +249,35 @@ impl TReducingState for ReducingState {
 pub trait TWindowState {
     fn windows(&self) -> Vec<Window>;
 
-    fn merge<F>(&mut self, key: Record, record: Record, reduce_fun: F) -> usize
+    /// The number of the windows
+    fn len(&self) -> usize;
+
+    /// Reduce the record into the state of its windows, the session windows of the key are
+    /// merged by the `merge_fun` if they intersect the window of the record.
+    /// Returns the windows the record reduced into
+    fn merge<F, M>(
+        &mut self,
+        key: Record,
+        record: &mut Record,
+        reduce_fun: F,
+        merge_fun: M,
+    ) -> Vec<Window>
     where
-        F: Fn(Option<&mut Record>, &mut Record) -> Record;
+        F: Fn(Option<&mut Record>, &mut Record) -> Record,
+        M: Fn(&mut Record, &mut Record) -> Record;
+
+    /// The reduced value of the key in the window
+    fn get(&mut self, window: &Window, key: &Record) -> Option<Record>;
+
+    /// Remove the window and hand over its state to the downstream, the values are converted
+    /// by the `result_fun` if it's set
+    fn drop_window(&mut self, window: &Window, result_fun: Option<ResultFunction>) -> usize;
+
+    /// Hand over a copy of the window state to the downstream, the window is kept
+    fn fire_window(&mut self, window: &Window, result_fun: Option<ResultFunction>);
 
-    fn drop_window(&mut self, window: &Window) -> usize;
+    /// Remove the window and discard its state
+    fn purge_window(&mut self, window: &Window) -> usize;
 
     fn snapshot(&mut self, barrier: Barrier);
 }
@@ -138,20 +285,33 @@ pub trait TWindowState {
 #[derive(Clone)]
 pub enum WindowState {
     MemoryWindowState(MemoryWindowState),
+    #[cfg(feature = "rocksdb")]
+    RocksDBWindowState(RocksDBWindowState),
 }
 
 impl WindowState {
-    pub fn new(
-        application_id: String,
-        job_id: JobId,
-        task_number: u16,
-        mode: KeyedStateBackend,
-    ) -> Self {
-        match mode {
-            KeyedStateBackend::Memory => WindowState::MemoryWindowState(MemoryWindowState::new(
-                application_id,
-                job_id,
-                task_number,
+    /// The window state of the task in the `context`, it's restored from the checkpoint of the
+    /// `context` if the states are kept in the rocksdb
+    pub fn new(context: &Context, mode: KeyedStateBackend) -> anyhow::Result<Self> {
+        let application_id = context.application_id.clone();
+        let job_id = context.task_id.job_id();
+        let task_number = context.task_id.task_number();
+        match &mode {
+            KeyedStateBackend::Memory => Ok(WindowState::MemoryWindowState(
+                MemoryWindowState::new(application_id, job_id, task_number),
+            )),
+            #[cfg(feature = "rocksdb")]
+            KeyedStateBackend::RocksDB { .. } => {
+                let storage = RocksDBStorage::open(context, &mode)?;
+                Ok(WindowState::RocksDBWindowState(RocksDBWindowState::new(
+                    job_id,
+                    task_number,
+                    storage,
+                )))
+            }
+            #[cfg(not(feature = "rocksdb"))]
+            KeyedStateBackend::RocksDB { .. } => Err(anyhow!(
+                "the rocksdb keyed state backend requires the `rocksdb` feature"
             )),
         }
     }
@@ -161,27 +321,78 @@ impl TWindowState for WindowState {
     fn windows(&self) -> Vec<Window> {
         match self {
             WindowState::MemoryWindowState(state) => state.windows(),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.windows(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            WindowState::MemoryWindowState(state) => state.len(),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.len(),
         }
     }
 
-    fn merge<F>(&mut self, key: Record, record: Record, reduce_fun: F) -> usize
+    fn merge<F, M>(
+        &mut self,
+        key: Record,
+        record: &mut Record,
+        reduce_fun: F,
+        merge_fun: M,
+    ) -> Vec<Window>
     where
         F: Fn(Option<&mut Record>, &mut Record) -> Record,
+        M: Fn(&mut Record, &mut Record) -> Record,
     {
         match self {
-            WindowState::MemoryWindowState(state) => state.merge(key, record, reduce_fun),
+            WindowState::MemoryWindowState(state) => {
+                state.merge(key, record, reduce_fun, merge_fun)
+            }
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => {
+                state.merge(key, record, reduce_fun, merge_fun)
+            }
+        }
+    }
+
+    fn get(&mut self, window: &Window, key: &Record) -> Option<Record> {
+        match self {
+            WindowState::MemoryWindowState(state) => state.get(window, key),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.get(window, key),
+        }
+    }
+
+    fn drop_window(&mut self, window: &Window, result_fun: Option<ResultFunction>) -> usize {
+        match self {
+            WindowState::MemoryWindowState(state) => state.drop_window(window, result_fun),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.drop_window(window, result_fun),
+        }
+    }
+
+    fn fire_window(&mut self, window: &Window, result_fun: Option<ResultFunction>) {
+        match self {
+            WindowState::MemoryWindowState(state) => state.fire_window(window, result_fun),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.fire_window(window, result_fun),
         }
     }
 
-    fn drop_window(&mut self, window: &Window) -> usize {
+    fn purge_window(&mut self, window: &Window) -> usize {
         match self {
-            WindowState::MemoryWindowState(state) => state.drop_window(window),
+            WindowState::MemoryWindowState(state) => state.purge_window(window),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.purge_window(window),
         }
     }
 
     fn snapshot(&mut self, barrier: Barrier) {
         match self {
             WindowState::MemoryWindowState(state) => state.snapshot(barrier),
+            #[cfg(feature = "rocksdb")]
+            WindowState::RocksDBWindowState(state) => state.snapshot(barrier),
         }
     }
 }
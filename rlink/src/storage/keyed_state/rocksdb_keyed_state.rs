@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::core::runtime::{CheckpointId, OperatorId};
+use crate::core::state::{KeyedStateStorage, StateTtlConfig};
+use crate::storage::keyed_state::rocksdb_storage::RocksDBStorage;
+
+/// The `RuntimeContext` states of an operator in the rocksdb of the task, every state is a
+/// column family named by the operator and the state
+pub(crate) struct RocksDBKeyedState {
+    storage: Arc<RocksDBStorage>,
+    prefix: String,
+}
+
+impl RocksDBKeyedState {
+    pub fn new(storage: Arc<RocksDBStorage>, operator_id: OperatorId) -> Self {
+        RocksDBKeyedState {
+            storage,
+            prefix: format!("keyed-{}-", operator_id.0),
+        }
+    }
+
+    fn cf_name(&self, name: &str) -> String {
+        format!("{}{}", self.prefix, name)
+    }
+}
+
+impl Debug for RocksDBKeyedState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RocksDBKeyedState")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl KeyedStateStorage for RocksDBKeyedState {
+    fn get(&self, name: &str, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        self.storage.get_keyed(self.cf_name(name).as_str(), key)
+    }
+
+    fn write(&self, name: &str, values: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> anyhow::Result<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        self.storage
+            .write_keyed(self.cf_name(name).as_str(), values)
+    }
+
+    fn entries(&self, name: &str) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.storage.keyed_entries(self.cf_name(name).as_str())
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.storage
+            .cf_names(self.prefix.as_str())
+            .into_iter()
+            .map(|cf_name| cf_name[self.prefix.len()..].to_string())
+            .collect()
+    }
+
+    fn clear(&self) -> anyhow::Result<()> {
+        for cf_name in self.storage.cf_names(self.prefix.as_str()) {
+            self.storage.drop_cf(cf_name.as_str())?;
+        }
+        Ok(())
+    }
+
+    fn set_ttl(&self, name: &str, ttl_config: Option<StateTtlConfig>) {
+        self.storage
+            .set_ttl(self.cf_name(name).as_str(), ttl_config);
+    }
+
+    async fn checkpoint(&self, checkpoint_id: CheckpointId) -> anyhow::Result<()> {
+        self.storage.checkpoint(checkpoint_id)?;
+        self.storage.upload(checkpoint_id).await?;
+        if let Err(e) = self.storage.prune_checkpoints() {
+            warn!("prune rocksdb checkpoints error. {}", e);
+        }
+        Ok(())
+    }
+}
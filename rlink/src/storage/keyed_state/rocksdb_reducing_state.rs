@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Arc;
+
+use crate::core::element::Record;
+use crate::core::window::Window;
+use crate::storage::keyed_state::rocksdb_storage::RocksDBStorage;
+use crate::storage::keyed_state::{StateIterator, StateKey, TReducingState};
+
+/// the number of the key values read from the rocksdb at a time by the iterator
+pub(crate) const SCAN_BATCH_SIZE: usize = 1024;
+
+/// The state of a dropped window in the rocksdb, the values read by `get_mut` are cached
+/// and written back by `flush`
+pub struct RocksDBReducingState {
+    state_key: StateKey,
+    storage: Arc<RocksDBStorage>,
+    cache: BTreeMap<Record, Record>,
+    /// the cached keys absent in the rocksdb
+    new_keys: BTreeSet<Record>,
+}
+
+impl RocksDBReducingState {
+    pub fn from(state_key: &StateKey) -> Option<RocksDBReducingState> {
+        let storage = RocksDBStorage::get(state_key.job_id, state_key.task_number)
+            .filter(|storage| storage.contains_window(&state_key.window));
+        match storage {
+            Some(storage) => {
+                debug!("open rocksdb state {:?}", state_key);
+                Some(RocksDBReducingState {
+                    state_key: state_key.clone(),
+                    storage,
+                    cache: BTreeMap::new(),
+                    new_keys: BTreeSet::new(),
+                })
+            }
+            None => {
+                error!("can not found state {:?}", state_key);
+                None
+            }
+        }
+    }
+}
+
+impl TReducingState for RocksDBReducingState {
+    fn get_mut(&mut self, key: &Record) -> Option<&mut Record> {
+        if !self.cache.contains_key(key) {
+            let value = self
+                .storage
+                .get_value(&self.state_key.window, key)
+                .expect("read rocksdb state error");
+            self.cache.insert(key.clone(), value?);
+        }
+        self.cache.get_mut(key)
+    }
+
+    fn insert(&mut self, key: Record, val: Record) {
+        if self.get_mut(&key).is_none() {
+            self.new_keys.insert(key.clone());
+        }
+        self.cache.insert(key, val);
+    }
+
+    fn remove(&mut self, key: &Record) -> Option<Record> {
+        let value = self.get_mut(key).map(|value| value.clone());
+        self.cache.remove(key);
+        self.new_keys.remove(key);
+        self.storage
+            .delete_value(&self.state_key.window, key)
+            .expect("delete rocksdb state error");
+        value
+    }
+
+    fn flush(&mut self) {
+        let window = &self.state_key.window;
+        self.new_keys.clear();
+        for (key, value) in std::mem::take(&mut self.cache) {
+            self.storage
+                .put_value(window, &key, &value)
+                .expect("write rocksdb state error");
+        }
+    }
+
+    fn snapshot(&mut self) {
+        self.flush();
+    }
+
+    fn close(mut self) {
+        self.flush();
+    }
+
+    fn destroy(self) {
+        self.storage
+            .purge_window(&self.state_key.window)
+            .expect("drop rocksdb state error");
+    }
+
+    fn iter(mut self) -> StateIterator {
+        self.flush();
+        StateIterator::RocksDB(RocksDBStateIterator::new(
+            self.storage,
+            self.state_key.window,
+        ))
+    }
+
+    fn len(&self) -> usize {
+        self.storage.count(&self.state_key.window) + self.new_keys.len()
+    }
+}
+
+/// Read the key values of the window in batches, the window is dropped from the rocksdb
+/// after all key values are read
+pub struct RocksDBStateIterator {
+    storage: Arc<RocksDBStorage>,
+    window: Window,
+    last_key: Option<Vec<u8>>,
+    batch: VecDeque<(Record, Record)>,
+    end: bool,
+}
+
+impl RocksDBStateIterator {
+    fn new(storage: Arc<RocksDBStorage>, window: Window) -> Self {
+        RocksDBStateIterator {
+            storage,
+            window,
+            last_key: None,
+            batch: VecDeque::new(),
+            end: false,
+        }
+    }
+}
+
+impl Iterator for RocksDBStateIterator {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.batch.is_empty() && !self.end {
+            let batch = self
+                .storage
+                .scan(&self.window, self.last_key.as_deref(), SCAN_BATCH_SIZE)
+                .expect("read rocksdb state error");
+            if batch.len() < SCAN_BATCH_SIZE {
+                self.end = true;
+                self.storage
+                    .purge_window(&self.window)
+                    .expect("drop rocksdb state error");
+            }
+            self.last_key = batch
+                .last()
+                .map(|(key, _value)| key.values.as_slice().to_vec());
+            self.batch.extend(batch);
+        }
+
+        self.batch.pop_front().map(|(mut key, value)| {
+            key.extend(value).expect("key value merge error");
+            key.trigger_window = Some(self.window.clone());
+            key
+        })
+    }
+}
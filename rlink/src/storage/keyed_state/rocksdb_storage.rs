@@ -0,0 +1,1030 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use bytes::BytesMut;
+use dashmap::DashMap;
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::compaction_filter::Decision;
+use rocksdb::{
+    BlockBasedOptions, Cache, ColumnFamilyDescriptor, DBWithThreadMode, Direction, IteratorMode,
+    MultiThreaded, Options, WriteBatch, DB,
+};
+
+use crate::core::backend::KeyedStateBackend;
+use crate::core::element::{Buffer, Record};
+use crate::core::function::Context;
+use crate::core::key_group::{assign_to_key_group, KeyGroupRange};
+use crate::core::properties::SystemProperties;
+use crate::core::runtime::{CheckpointId, JobId};
+use crate::core::state::{is_expired_entry, rocksdb_checkpoint_id, StateTtlConfig};
+use crate::core::window::{CountWindow, TWindow, TimeWindow, Window};
+use crate::storage::keyed_state::incremental_checkpoint::IncrementalCheckpointUploader;
+use crate::storage::keyed_state::mem_storage::StorageKey;
+use crate::utils::date_time::current_timestamp_millis;
+
+/// the number of the rocksdb checkpoints kept on the local disk or in the object store
+const RETAINED_CHECKPOINTS: usize = 3;
+
+/// the column family of the window state, the key is the window followed by the record key
+const WINDOW_CF: &str = "window";
+/// the column family of the windows and the number of their keys, the key is the window
+const WINDOW_META_CF: &str = "window-meta";
+/// the length of the window at the head of the keys in the `WINDOW_CF`
+const WINDOW_KEY_LEN: usize = 17;
+/// the number of the key values written to the rocksdb in a batch on the restore
+const RESTORE_BATCH_SIZE: usize = 1024;
+
+lazy_static! {
+    /// the rocksdb of the tasks and the attempt they're opened by
+    static ref ROCKSDB_STORAGE: DashMap<StorageKey, (u64, Arc<RocksDBStorage>)> = DashMap::new();
+}
+
+/// The window of the window state and the number of its keys, it's kept in the `WINDOW_META_CF`
+/// with the values, so the rocksdb checkpoint has the windows of the state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct WindowMeta {
+    window: Window,
+    count: usize,
+    /// the window is dropped from the window state, its values are left for the
+    /// `RocksDBReducingState`. The dropped windows are discarded on the restore
+    #[serde(default)]
+    dropped: bool,
+}
+
+/// The checkpoint the rocksdb of a task is restored from
+struct RestoreSource {
+    checkpoint_id: CheckpointId,
+    /// the number of the tasks in the checkpoint
+    num_tasks: u16,
+    /// the tasks in the checkpoint whose key groups overlap the task's
+    task_numbers: Vec<u16>,
+    max_parallelism: u16,
+}
+
+/// The rocksdb of a task, it keeps the states of all operators of the task. The window state
+/// is in the `WINDOW_CF`, and every state of the `RuntimeContext` is a column family
+pub(crate) struct RocksDBStorage {
+    /// the directory of the task
+    path: PathBuf,
+    db: DBWithThreadMode<MultiThreaded>,
+    cf_options: Options,
+    /// the ttl of the keyed states by the column family, the expired values are removed by the
+    /// compaction filter of the column family
+    ttl_configs: Arc<DashMap<String, StateTtlConfig>>,
+    /// the column families created, the rocksdb doesn't list them once it's opened
+    cf_names: Mutex<BTreeSet<String>>,
+    /// the windows in the `WINDOW_META_CF`, so the window size is read without a scan
+    windows: Mutex<HashMap<Window, WindowMeta>>,
+    /// upload the checkpoints to the `checkpoint_url` of the backend if it's set
+    uploader: Option<tokio::sync::Mutex<IncrementalCheckpointUploader>>,
+}
+
+impl RocksDBStorage {
+    /// Open the rocksdb of the task, it's shared by the states of the task in the same
+    /// attempt. The rocksdb is restored from the checkpoint the job restarts from, the keys
+    /// of the other tasks' key groups are left out if the parallelism is changed. Otherwise
+    /// the rocksdb left on the disk is discarded, as it may have the writes after the
+    /// checkpoint. The checkpoints absent on the local disk are downloaded from the
+    /// `checkpoint_url` of the backend
+    pub fn open(
+        context: &Context,
+        backend: &KeyedStateBackend,
+    ) -> anyhow::Result<Arc<RocksDBStorage>> {
+        RocksDBStorage::open_attempt(context, backend, context.attempt_number())
+    }
+
+    /// Open the rocksdb of the task in the `attempt`, the rocksdb of a previous attempt is
+    /// never reused, e.g. the tasks restarted by the region failover are a new attempt
+    fn open_attempt(
+        context: &Context,
+        backend: &KeyedStateBackend,
+        attempt: u64,
+    ) -> anyhow::Result<Arc<RocksDBStorage>> {
+        let (path, block_cache_size, write_buffer_size, checkpoint_url, checkpoint_options) =
+            match backend {
+                KeyedStateBackend::RocksDB {
+                    path,
+                    block_cache_size,
+                    write_buffer_size,
+                    checkpoint_url,
+                    checkpoint_options,
+                } => (
+                    path,
+                    *block_cache_size,
+                    *write_buffer_size,
+                    checkpoint_url,
+                    checkpoint_options,
+                ),
+                KeyedStateBackend::Memory => {
+                    return Err(anyhow!("the keyed state backend isn't the rocksdb"));
+                }
+            };
+
+        let job_id = context.task_id.job_id();
+        let task_number = context.task_id.task_number();
+        let storage_key = StorageKey::new(job_id, task_number);
+        if let Some(storage) = ROCKSDB_STORAGE.get(&storage_key) {
+            let (storage_attempt, storage) = storage.value();
+            if *storage_attempt == attempt {
+                return Ok(storage.clone());
+            }
+        }
+        // the rocksdb of the previous attempt is closed once it's dropped
+        ROCKSDB_STORAGE.remove(&storage_key);
+
+        let new_uploader = |task_number: u16| match checkpoint_url {
+            Some(url) => IncrementalCheckpointUploader::new(
+                url.as_str(),
+                checkpoint_options,
+                context.application_id.as_str(),
+                job_id,
+                task_number,
+                RETAINED_CHECKPOINTS,
+            )
+            .map(Some),
+            None => Ok(None),
+        };
+        let mut uploader = new_uploader(task_number)?;
+
+        let root = PathBuf::from(path).join(context.application_id.as_str());
+        let path = task_path(&root, job_id, task_number);
+        let db_path = path.join("db");
+        let restore_path = path.join("restore");
+        for dir in [&db_path, &restore_path] {
+            if dir.exists() {
+                std::fs::remove_dir_all(dir)?;
+            }
+        }
+
+        let handles = context.operator_state_handles.as_slice();
+        let restore = rocksdb_checkpoint_id(handles).map(|checkpoint_id| RestoreSource {
+            checkpoint_id,
+            num_tasks: handles[0].task_id.num_tasks(),
+            task_numbers: handles.iter().map(|x| x.task_id.task_number()).collect(),
+            max_parallelism: context.application_properties.get_max_parallelism(),
+        });
+        // the checkpoint of the task is the rocksdb if the parallelism isn't changed
+        let restored = match &restore {
+            Some(restore) if restore.num_tasks == context.task_id.num_tasks() => {
+                let checkpoint_id = restore.checkpoint_id;
+                let mut checkpoint_path =
+                    locate_checkpoint(&root, job_id, task_number, checkpoint_id);
+                if let Some(uploader) = uploader.as_mut() {
+                    // the uploaded files of the checkpoint are reused by the next checkpoints
+                    let metadata = match &checkpoint_path {
+                        Some(_) => block_on(uploader.load_metadata(checkpoint_id))?,
+                        None => {
+                            let local_path =
+                                path.join("checkpoints").join(checkpoint_id.0.to_string());
+                            let metadata = block_on(uploader.download(checkpoint_id, &local_path))?;
+                            if metadata.is_some() {
+                                checkpoint_path = Some(local_path);
+                            }
+                            metadata
+                        }
+                    };
+                    if let Some(metadata) = metadata {
+                        uploader.retain(metadata);
+                    }
+                }
+                match checkpoint_path {
+                    Some(checkpoint_path) => {
+                        link_dir(checkpoint_path.as_path(), db_path.as_path())?;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        };
+
+        let mut cf_options = Options::default();
+        if let Some(write_buffer_size) = write_buffer_size {
+            cf_options.set_write_buffer_size(write_buffer_size);
+        }
+        if let Some(block_cache_size) = block_cache_size {
+            let mut block_options = BlockBasedOptions::default();
+            block_options.set_block_cache(&Cache::new_lru_cache(block_cache_size)?);
+            cf_options.set_block_based_table_factory(&block_options);
+        }
+
+        let mut db_options = cf_options.clone();
+        db_options.create_if_missing(true);
+        db_options.create_missing_column_families(true);
+
+        let mut cf_names: BTreeSet<String> = if restored {
+            DB::list_cf(&db_options, &db_path)?.into_iter().collect()
+        } else {
+            BTreeSet::new()
+        };
+        cf_names.insert(WINDOW_CF.to_string());
+        cf_names.insert(WINDOW_META_CF.to_string());
+        let ttl_configs = Arc::new(DashMap::new());
+        let cf_descriptors = cf_names.iter().map(|name| {
+            ColumnFamilyDescriptor::new(
+                name.as_str(),
+                column_family_options(&cf_options, name.as_str(), &ttl_configs),
+            )
+        });
+        let db = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(
+            &db_options,
+            &db_path,
+            cf_descriptors,
+        )?;
+        info!("open rocksdb state {:?}", db_path);
+
+        let storage = RocksDBStorage {
+            path,
+            db,
+            cf_options,
+            ttl_configs,
+            cf_names: Mutex::new(cf_names),
+            windows: Mutex::new(HashMap::new()),
+            uploader: uploader.map(tokio::sync::Mutex::new),
+        };
+        match &restore {
+            Some(restore) if restored => {
+                info!(
+                    "rocksdb state restored from the checkpoint {:?}",
+                    restore.checkpoint_id
+                );
+            }
+            Some(restore) => {
+                let key_groups = KeyGroupRange::of_operator(
+                    restore.max_parallelism,
+                    context.task_id.num_tasks(),
+                    task_number,
+                );
+                let mut checkpoint_paths = Vec::new();
+                for restore_task in restore.task_numbers.iter().cloned() {
+                    let checkpoint_id = restore.checkpoint_id;
+                    let checkpoint_path =
+                        match locate_checkpoint(&root, job_id, restore_task, checkpoint_id) {
+                            Some(checkpoint_path) => Some(checkpoint_path),
+                            None => match new_uploader(restore_task)? {
+                                Some(uploader) => {
+                                    let local_path = restore_path.join(restore_task.to_string());
+                                    block_on(uploader.download(checkpoint_id, &local_path))?
+                                        .map(|_metadata| local_path)
+                                }
+                                None => None,
+                            },
+                        };
+                    match checkpoint_path {
+                        Some(checkpoint_path) => {
+                            checkpoint_paths.push((restore_task, checkpoint_path))
+                        }
+                        None => warn!(
+                            "the rocksdb checkpoint {:?} of the task {} not found",
+                            checkpoint_id, restore_task
+                        ),
+                    }
+                }
+                storage.restore_key_groups(checkpoint_paths, restore, &key_groups)?;
+                if restore_path.exists() {
+                    std::fs::remove_dir_all(&restore_path)?;
+                }
+            }
+            None => {}
+        }
+        storage.load_windows()?;
+
+        let storage = Arc::new(storage);
+        ROCKSDB_STORAGE.insert(storage_key, (attempt, storage.clone()));
+        Ok(storage)
+    }
+
+    pub fn get(job_id: JobId, task_number: u16) -> Option<Arc<RocksDBStorage>> {
+        ROCKSDB_STORAGE
+            .get(&StorageKey::new(job_id, task_number))
+            .map(|storage| storage.value().1.clone())
+    }
+
+    /// Pick the keys of the `key_groups` from the checkpoints of the tasks
+    fn restore_key_groups(
+        &self,
+        checkpoint_paths: Vec<(u16, PathBuf)>,
+        restore: &RestoreSource,
+        key_groups: &KeyGroupRange,
+    ) -> anyhow::Result<()> {
+        let mut windows: HashMap<Window, WindowMeta> = HashMap::new();
+        for (task_number, checkpoint_path) in checkpoint_paths {
+            let options = Options::default();
+            let cf_names = DB::list_cf(&options, &checkpoint_path)?;
+            let checkpoint =
+                DB::open_cf_for_read_only(&options, &checkpoint_path, cf_names.iter(), false)?;
+
+            // the values of the dropped windows are discarded
+            let mut dropped_windows = HashMap::new();
+            if let Some(cf) = checkpoint.cf_handle(WINDOW_META_CF) {
+                for item in checkpoint.iterator_cf(cf, IteratorMode::Start) {
+                    let (key, value) = item?;
+                    let meta: WindowMeta = serde_json::from_slice(value.as_ref())?;
+                    dropped_windows.insert(key.to_vec(), meta.dropped);
+                }
+            }
+
+            for name in cf_names
+                .iter()
+                .filter(|name| name.as_str() != WINDOW_META_CF && name.as_str() != "default")
+            {
+                let cf = match checkpoint.cf_handle(name.as_str()) {
+                    Some(cf) => cf,
+                    None => continue,
+                };
+                let target = self.create_cf(name.as_str())?;
+                let is_window = name.as_str() == WINDOW_CF;
+
+                let mut batch = WriteBatch::default();
+                for item in checkpoint.iterator_cf(cf, IteratorMode::Start) {
+                    let (key, value) = item?;
+                    let record_key = if is_window {
+                        let window_key = &key[..WINDOW_KEY_LEN];
+                        if dropped_windows.get(window_key).cloned().unwrap_or(true) {
+                            continue;
+                        }
+                        &key[WINDOW_KEY_LEN..]
+                    } else {
+                        key.as_ref()
+                    };
+                    if !key_groups
+                        .contains(assign_to_key_group(record_key, restore.max_parallelism))
+                    {
+                        continue;
+                    }
+
+                    if is_window {
+                        let window = parse_window(&key[..WINDOW_KEY_LEN])?;
+                        windows
+                            .entry(window.clone())
+                            .or_insert_with(|| WindowMeta {
+                                window,
+                                count: 0,
+                                dropped: false,
+                            })
+                            .count += 1;
+                    }
+                    batch.put_cf(&target, key.as_ref(), value.as_ref());
+                    if batch.len() >= RESTORE_BATCH_SIZE {
+                        self.db.write(std::mem::take(&mut batch))?;
+                    }
+                }
+                self.db.write(batch)?;
+            }
+            info!(
+                "rocksdb state of the key groups {:?} restored from the checkpoint {:?} of the task {}",
+                key_groups, restore.checkpoint_id, task_number
+            );
+        }
+
+        let cf = self.cf(WINDOW_META_CF)?;
+        let mut batch = WriteBatch::default();
+        for meta in windows.values() {
+            batch.put_cf(&cf, window_key(&meta.window), serde_json::to_vec(meta)?);
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Read the windows of the `WINDOW_META_CF`, the dropped windows are discarded
+    fn load_windows(&self) -> anyhow::Result<()> {
+        let mut metas = Vec::new();
+        {
+            let cf = self.cf(WINDOW_META_CF)?;
+            for item in self.db.iterator_cf(&cf, IteratorMode::Start) {
+                let (_key, value) = item?;
+                let meta: WindowMeta = serde_json::from_slice(value.as_ref())?;
+                metas.push(meta);
+            }
+        }
+
+        for meta in metas {
+            if meta.dropped {
+                self.purge_window(&meta.window)?;
+            } else {
+                self.windows
+                    .lock()
+                    .unwrap()
+                    .insert(meta.window.clone(), meta);
+            }
+        }
+        Ok(())
+    }
+
+    /// The windows of the window state
+    pub fn windows(&self) -> Vec<Window> {
+        self.windows
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|meta| !meta.dropped)
+            .map(|meta| meta.window.clone())
+            .collect()
+    }
+
+    pub fn contains_window(&self, window: &Window) -> bool {
+        self.windows.lock().unwrap().contains_key(window)
+    }
+
+    /// The number of the keys of the window
+    pub fn count(&self, window: &Window) -> usize {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(window)
+            .map(|meta| meta.count)
+            .unwrap_or_default()
+    }
+
+    pub fn get_value(&self, window: &Window, key: &Record) -> anyhow::Result<Option<Record>> {
+        let cf = self.cf(WINDOW_CF)?;
+        let value = self.db.get_cf(&cf, window_record_key(window, key))?;
+        Ok(value.map(|value| to_record(value.as_slice())))
+    }
+
+    /// Write the value of the key, the key is added to the window if it's absent
+    pub fn put_value(&self, window: &Window, key: &Record, value: &Record) -> anyhow::Result<()> {
+        let cf = self.cf(WINDOW_CF)?;
+        let record_key = window_record_key(window, key);
+        let exists = self.db.get_pinned_cf(&cf, record_key.as_slice())?.is_some();
+
+        let mut batch = WriteBatch::default();
+        batch.put_cf(&cf, record_key.as_slice(), value.values.as_slice());
+        if !exists {
+            self.update_count(&mut batch, window, |count| count + 1)?;
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Reduce the value of the key by the `reduce_fun`, the key is added to the window if
+    /// it's absent
+    pub fn update_value<F>(
+        &self,
+        window: &Window,
+        key: &Record,
+        reduce_fun: F,
+    ) -> anyhow::Result<()>
+    where
+        F: FnOnce(Option<Record>) -> Record,
+    {
+        let cf = self.cf(WINDOW_CF)?;
+        let record_key = window_record_key(window, key);
+        let value = self
+            .db
+            .get_cf(&cf, record_key.as_slice())?
+            .map(|value| to_record(value.as_slice()));
+        let exists = value.is_some();
+        let value = reduce_fun(value);
+
+        let mut batch = WriteBatch::default();
+        batch.put_cf(&cf, record_key.as_slice(), value.values.as_slice());
+        if !exists {
+            self.update_count(&mut batch, window, |count| count + 1)?;
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Delete the value of the key, the window is removed once all keys are deleted.
+    /// Returns the number of the keys left in the window
+    pub fn delete_value(&self, window: &Window, key: &Record) -> anyhow::Result<usize> {
+        let cf = self.cf(WINDOW_CF)?;
+        let record_key = window_record_key(window, key);
+        if self.db.get_pinned_cf(&cf, record_key.as_slice())?.is_none() {
+            return Ok(self.count(window));
+        }
+
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(&cf, record_key.as_slice());
+        let count = self.update_count(&mut batch, window, |count| count.saturating_sub(1))?;
+        self.db.write(batch)?;
+        Ok(count)
+    }
+
+    /// Mark the window dropped, its values are kept until they're read by the
+    /// `RocksDBReducingState`
+    pub fn drop_window(&self, window: &Window) -> anyhow::Result<()> {
+        let mut windows = self.windows.lock().unwrap();
+        if let Some(meta) = windows.get_mut(window) {
+            meta.dropped = true;
+            let cf = self.cf(WINDOW_META_CF)?;
+            self.db
+                .put_cf(&cf, window_key(window), serde_json::to_vec(meta)?)?;
+        }
+        Ok(())
+    }
+
+    /// Delete the window and all its values
+    pub fn purge_window(&self, window: &Window) -> anyhow::Result<()> {
+        let window_cf = self.cf(WINDOW_CF)?;
+        let meta_cf = self.cf(WINDOW_META_CF)?;
+        let start = window_key(window);
+        let end = next_window_key(start);
+
+        let mut batch = WriteBatch::default();
+        batch.delete_range_cf(&window_cf, start, end);
+        batch.delete_cf(&meta_cf, start);
+        self.db.write(batch)?;
+        self.windows.lock().unwrap().remove(window);
+        Ok(())
+    }
+
+    /// Update the number of the keys of the window in the `batch`, the window is removed once
+    /// it has no key. Returns the new number
+    fn update_count<F>(
+        &self,
+        batch: &mut WriteBatch,
+        window: &Window,
+        update_fun: F,
+    ) -> anyhow::Result<usize>
+    where
+        F: FnOnce(usize) -> usize,
+    {
+        let cf = self.cf(WINDOW_META_CF)?;
+        let mut windows = self.windows.lock().unwrap();
+        let meta = windows.entry(window.clone()).or_insert_with(|| WindowMeta {
+            window: window.clone(),
+            count: 0,
+            dropped: false,
+        });
+        meta.count = update_fun(meta.count);
+
+        let count = meta.count;
+        if count == 0 {
+            batch.delete_cf(&cf, window_key(window));
+            windows.remove(window);
+        } else {
+            batch.put_cf(&cf, window_key(window), serde_json::to_vec(meta)?);
+        }
+        Ok(count)
+    }
+
+    /// At most `limit` key values of the window after the `from` key
+    pub fn scan(
+        &self,
+        window: &Window,
+        from: Option<&[u8]>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(Record, Record)>> {
+        let cf = self.cf(WINDOW_CF)?;
+        let prefix = window_key(window);
+        let start = match from {
+            Some(from) => [prefix.as_slice(), from].concat(),
+            None => prefix.to_vec(),
+        };
+
+        let mut key_values = Vec::with_capacity(limit);
+        let mode = IteratorMode::From(start.as_slice(), Direction::Forward);
+        for item in self.db.iterator_cf(&cf, mode) {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_slice()) {
+                break;
+            }
+            let record_key = &key[WINDOW_KEY_LEN..];
+            if from.map(|from| from == record_key).unwrap_or(false) {
+                continue;
+            }
+            key_values.push((to_record(record_key), to_record(value.as_ref())));
+            if key_values.len() >= limit {
+                break;
+            }
+        }
+        Ok(key_values)
+    }
+
+    /// The value of the key in the column family of a keyed state
+    pub fn get_keyed(&self, cf_name: &str, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+        if !self.cf_names.lock().unwrap().contains(cf_name) {
+            return Ok(None);
+        }
+        let cf = self.cf(cf_name)?;
+        Ok(self.db.get_cf(&cf, key)?)
+    }
+
+    /// Write the values of the keys in the column family of a keyed state, the `None` value
+    /// deletes the key
+    pub fn write_keyed(
+        &self,
+        cf_name: &str,
+        values: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    ) -> anyhow::Result<()> {
+        let cf = self.create_cf(cf_name)?;
+        let mut batch = WriteBatch::default();
+        for (key, value) in values {
+            match value {
+                Some(value) => batch.put_cf(&cf, key, value),
+                None => batch.delete_cf(&cf, key),
+            }
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// All key values in the column family of a keyed state
+    pub fn keyed_entries(&self, cf_name: &str) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        if !self.cf_names.lock().unwrap().contains(cf_name) {
+            return Ok(vec![]);
+        }
+        let cf = self.cf(cf_name)?;
+        let mut entries = Vec::new();
+        for item in self.db.iterator_cf(&cf, IteratorMode::Start) {
+            let (key, value) = item?;
+            entries.push((key.to_vec(), value.to_vec()));
+        }
+        Ok(entries)
+    }
+
+    /// The column families with the `prefix`
+    pub fn cf_names(&self, prefix: &str) -> Vec<String> {
+        self.cf_names
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    /// Remove the values of the keyed state expired by the `ttl_config` on the compaction
+    pub fn set_ttl(&self, cf_name: &str, ttl_config: Option<StateTtlConfig>) {
+        match ttl_config {
+            Some(ttl_config) => {
+                self.ttl_configs.insert(cf_name.to_string(), ttl_config);
+            }
+            None => {
+                self.ttl_configs.remove(cf_name);
+            }
+        }
+    }
+
+    pub fn drop_cf(&self, cf_name: &str) -> anyhow::Result<()> {
+        if self.cf_names.lock().unwrap().remove(cf_name) {
+            self.db.drop_cf(cf_name)?;
+        }
+        Ok(())
+    }
+
+    /// Create a rocksdb checkpoint of all states in the local directory, the pending writes of
+    /// the states must be written before
+    pub fn checkpoint(&self, checkpoint_id: CheckpointId) -> anyhow::Result<PathBuf> {
+        let checkpoints_path = self.path.join("checkpoints");
+        std::fs::create_dir_all(&checkpoints_path)?;
+
+        let checkpoint_path = checkpoints_path.join(checkpoint_id.0.to_string());
+        if !checkpoint_path.exists() {
+            Checkpoint::new(&self.db)?.create_checkpoint(&checkpoint_path)?;
+        }
+        Ok(checkpoint_path)
+    }
+
+    /// Upload the checkpoint to the `checkpoint_url` of the backend, the checkpoint is
+    /// restorable on the other nodes once it returns. It's a no-op without the url
+    pub async fn upload(&self, checkpoint_id: CheckpointId) -> anyhow::Result<()> {
+        if let Some(uploader) = &self.uploader {
+            let checkpoint_path = self.checkpoint(checkpoint_id)?;
+            uploader
+                .lock()
+                .await
+                .upload(checkpoint_id, &checkpoint_path)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Remove the local checkpoints except the latest ones
+    pub fn prune_checkpoints(&self) -> anyhow::Result<()> {
+        let checkpoints_path = self.path.join("checkpoints");
+        let mut checkpoint_ids: Vec<u64> = std::fs::read_dir(&checkpoints_path)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().to_str().and_then(|x| x.parse().ok()))
+            .collect();
+        checkpoint_ids.sort_unstable();
+        if checkpoint_ids.len() > RETAINED_CHECKPOINTS {
+            let expired = checkpoint_ids.len() - RETAINED_CHECKPOINTS;
+            for checkpoint_id in &checkpoint_ids[..expired] {
+                std::fs::remove_dir_all(checkpoints_path.join(checkpoint_id.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn create_cf(&self, cf_name: &str) -> anyhow::Result<Arc<rocksdb::BoundColumnFamily<'_>>> {
+        let mut cf_names = self.cf_names.lock().unwrap();
+        if !cf_names.contains(cf_name) {
+            let cf_options = column_family_options(&self.cf_options, cf_name, &self.ttl_configs);
+            self.db.create_cf(cf_name, &cf_options)?;
+            cf_names.insert(cf_name.to_string());
+        }
+        self.cf(cf_name)
+    }
+
+    fn cf(&self, cf_name: &str) -> anyhow::Result<Arc<rocksdb::BoundColumnFamily<'_>>> {
+        self.db
+            .cf_handle(cf_name)
+            .ok_or_else(|| anyhow!("rocksdb column family {} not found", cf_name))
+    }
+}
+
+/// The options of the column family, the keyed states have the compaction filter removing the
+/// values expired by the ttl of the state, it's registered after the column family is opened
+fn column_family_options(
+    cf_options: &Options,
+    cf_name: &str,
+    ttl_configs: &Arc<DashMap<String, StateTtlConfig>>,
+) -> Options {
+    let mut cf_options = cf_options.clone();
+    if cf_name != WINDOW_CF && cf_name != WINDOW_META_CF {
+        let cf_name = cf_name.to_string();
+        let ttl_configs = ttl_configs.clone();
+        cf_options.set_compaction_filter("state-ttl", move |_level, _key, value| {
+            let expired = ttl_configs
+                .get(cf_name.as_str())
+                .map(|ttl_config| {
+                    is_expired_entry(ttl_config.value(), value, current_timestamp_millis())
+                })
+                .unwrap_or(false);
+            if expired {
+                Decision::Remove
+            } else {
+                Decision::Keep
+            }
+        });
+    }
+    cf_options
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+/// The directory of the rocksdb and the local checkpoints of the task
+fn task_path(root: &Path, job_id: JobId, task_number: u16) -> PathBuf {
+    root.join(format!("{}-{}", job_id.0, task_number))
+}
+
+/// The local directory of the rocksdb checkpoint of the task
+fn locate_checkpoint(
+    root: &Path,
+    job_id: JobId,
+    task_number: u16,
+    checkpoint_id: CheckpointId,
+) -> Option<PathBuf> {
+    let checkpoint_path = task_path(root, job_id, task_number)
+        .join("checkpoints")
+        .join(checkpoint_id.0.to_string());
+    if checkpoint_path.exists() {
+        Some(checkpoint_path)
+    } else {
+        None
+    }
+}
+
+/// Copy the rocksdb checkpoint to the `target` directory, the immutable sst files are hard
+/// linked if they're on the same disk
+fn link_dir(source: &Path, target: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(target)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target_file = target.join(entry.file_name());
+        let is_sst = entry
+            .path()
+            .extension()
+            .map(|x| x == "sst")
+            .unwrap_or(false);
+        if !is_sst || std::fs::hard_link(entry.path(), &target_file).is_err() {
+            std::fs::copy(entry.path(), &target_file)?;
+        }
+    }
+    Ok(())
+}
+
+/// The window at the head of the keys, the kind of the window followed by the big-endian
+/// timestamps, so the keys of a window are contiguous
+fn window_key(window: &Window) -> [u8; WINDOW_KEY_LEN] {
+    let (kind, start, end) = match window {
+        Window::TimeWindow(time_window) => (0u8, time_window.start(), time_window.end()),
+        Window::SessionWindow(time_window) => (1u8, time_window.start(), time_window.end()),
+        // only the window of the assigner is in the window state
+        Window::CountWindow(count_window) => (2u8, count_window.size(), count_window.slide()),
+    };
+
+    let mut key = [0u8; WINDOW_KEY_LEN];
+    key[0] = kind;
+    key[1..9].copy_from_slice(&start.to_be_bytes());
+    key[9..].copy_from_slice(&end.to_be_bytes());
+    key
+}
+
+/// The first key after all keys of the window
+fn next_window_key(mut key: [u8; WINDOW_KEY_LEN]) -> [u8; WINDOW_KEY_LEN] {
+    for byte in key.iter_mut().rev() {
+        if *byte == u8::MAX {
+            *byte = 0;
+        } else {
+            *byte += 1;
+            break;
+        }
+    }
+    key
+}
+
+fn parse_window(key: &[u8]) -> anyhow::Result<Window> {
+    if key.len() < WINDOW_KEY_LEN {
+        return Err(anyhow!("illegal rocksdb window key {:?}", key));
+    }
+    let start = u64::from_be_bytes(key[1..9].try_into()?);
+    let end = u64::from_be_bytes(key[9..WINDOW_KEY_LEN].try_into()?);
+    match key[0] {
+        0 => Ok(Window::TimeWindow(TimeWindow::new(start, end))),
+        1 => Ok(Window::SessionWindow(TimeWindow::new(start, end))),
+        2 => Ok(Window::CountWindow(CountWindow::new(start, end))),
+        kind => Err(anyhow!("illegal rocksdb window kind {}", kind)),
+    }
+}
+
+fn window_record_key(window: &Window, key: &Record) -> Vec<u8> {
+    [window_key(window).as_slice(), key.values.as_slice()].concat()
+}
+
+fn to_record(values: &[u8]) -> Record {
+    let mut record = Record::new();
+    record.values = Buffer::from(BytesMut::from(values));
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::core::backend::KeyedStateBackend;
+    use crate::core::checkpoint::{CheckpointHandle, OperatorStateHandle};
+    use crate::core::element::FnSchema;
+    use crate::core::function::Context;
+    use crate::core::properties::Properties;
+    use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+    use crate::core::state::RuntimeContext;
+    use crate::core::window::{TWindow, TimeWindow, Window};
+    use crate::storage::keyed_state::mem_storage::StorageKey;
+    use crate::storage::keyed_state::rocksdb_storage::{
+        next_window_key, parse_window, to_record, window_key, RocksDBStorage, ROCKSDB_STORAGE,
+    };
+
+    #[test]
+    pub fn window_key_test() {
+        let window = Window::SessionWindow(TimeWindow::new(1000, 2000));
+        let key = window_key(&window);
+        assert_eq!(parse_window(&key).unwrap(), window);
+
+        let next = next_window_key(key);
+        assert!(next > key);
+        let next_window = parse_window(&next).unwrap();
+        assert_eq!(next_window.max_timestamp(), 2001);
+
+        let time_window = window_key(&Window::TimeWindow(TimeWindow::new(1000, 2000)));
+        assert!(time_window < key);
+    }
+
+    fn context(task_id: TaskId) -> Context {
+        Context {
+            application_id: "app".to_string(),
+            application_properties: Properties::new(),
+            operator_id: OperatorId(1),
+            operator_name: "MyReduceFunction".to_string(),
+            task_id,
+            checkpoint_id: CheckpointId::default(),
+            completed_checkpoint_id: None,
+            checkpoint_handle: None,
+            operator_state_handles: vec![],
+            input_schema: FnSchema::Empty,
+            output_schema: FnSchema::Empty,
+            children: vec![],
+            parents: vec![],
+            task_context: None,
+            runtime_context: RuntimeContext::default(),
+        }
+    }
+
+    #[test]
+    pub fn restart_attempt_restore_test() {
+        let root = std::env::temp_dir().join(format!("rlink-rocksdb-{}", std::process::id()));
+        let backend = KeyedStateBackend::RocksDB {
+            path: root.to_str().unwrap().to_string(),
+            block_cache_size: None,
+            write_buffer_size: None,
+            checkpoint_url: None,
+            checkpoint_options: HashMap::new(),
+        };
+        let task_id = TaskId {
+            job_id: JobId(851),
+            task_number: 0,
+            num_tasks: 1,
+        };
+        let value = |storage: &RocksDBStorage| storage.get_keyed("v", b"k").unwrap();
+
+        let mut context = context(task_id);
+        let storage = RocksDBStorage::open_attempt(&context, &backend, 0).unwrap();
+        storage
+            .write_keyed("v", vec![(b"k".to_vec(), Some(b"1".to_vec()))])
+            .unwrap();
+        storage.checkpoint(CheckpointId(1)).unwrap();
+        // written after the checkpoint by the failed attempt
+        storage
+            .write_keyed("v", vec![(b"k".to_vec(), Some(b"2".to_vec()))])
+            .unwrap();
+        drop(storage);
+
+        context.operator_state_handles = vec![OperatorStateHandle {
+            task_id,
+            handle: CheckpointHandle {
+                handle: r#"{"states":{},"rocksdb_checkpoint_id":1}"#.to_string(),
+            },
+        }];
+
+        // the same attempt shares the live rocksdb
+        let storage = RocksDBStorage::open_attempt(&context, &backend, 0).unwrap();
+        assert_eq!(value(&storage), Some(b"2".to_vec()));
+        drop(storage);
+
+        // the restarted attempt restores from the checkpoint
+        let storage = RocksDBStorage::open_attempt(&context, &backend, 1).unwrap();
+        assert_eq!(value(&storage), Some(b"1".to_vec()));
+        drop(storage);
+
+        ROCKSDB_STORAGE.remove(&StorageKey::new(task_id.job_id, task_id.task_number));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    pub fn rescale_restore_test() {
+        let root = std::env::temp_dir().join(format!("rlink-rescale-{}", std::process::id()));
+        let backend = KeyedStateBackend::RocksDB {
+            path: root.to_str().unwrap().to_string(),
+            block_cache_size: None,
+            write_buffer_size: None,
+            checkpoint_url: None,
+            checkpoint_options: HashMap::new(),
+        };
+        let job_id = JobId(801);
+        let window = Window::TimeWindow(TimeWindow::new(0, 10));
+        let dropped_window = Window::TimeWindow(TimeWindow::new(10, 20));
+
+        let mut handles = Vec::new();
+        for task_number in 0..2 {
+            let task_id = TaskId {
+                job_id,
+                task_number,
+                num_tasks: 2,
+            };
+            let storage = RocksDBStorage::open_attempt(&context(task_id), &backend, 0).unwrap();
+            let key = format!("k{}", task_number);
+            let record_key = to_record(key.as_bytes());
+            storage
+                .write_keyed("count", vec![(key.into_bytes(), Some(b"1".to_vec()))])
+                .unwrap();
+            storage
+                .put_value(&window, &record_key, &to_record(b"v"))
+                .unwrap();
+            storage
+                .put_value(&dropped_window, &record_key, &to_record(b"v"))
+                .unwrap();
+            storage.drop_window(&dropped_window).unwrap();
+            storage.checkpoint(CheckpointId(1)).unwrap();
+
+            handles.push(OperatorStateHandle {
+                task_id,
+                handle: CheckpointHandle {
+                    handle: r#"{"states":{},"rocksdb_checkpoint_id":1}"#.to_string(),
+                },
+            });
+            ROCKSDB_STORAGE.remove(&StorageKey::new(job_id, task_number));
+        }
+
+        // the task of the rescaled job picks the keys of both tasks from their checkpoints,
+        // the keyed states are restored to their column families
+        let task_id = TaskId {
+            job_id,
+            task_number: 0,
+            num_tasks: 1,
+        };
+        let mut context = context(task_id);
+        context.operator_state_handles = handles;
+        let storage = RocksDBStorage::open_attempt(&context, &backend, 0).unwrap();
+        for key in ["k0", "k1"] {
+            let value = storage.get_keyed("count", key.as_bytes()).unwrap();
+            assert_eq!(value, Some(b"1".to_vec()));
+            let value = storage
+                .get_value(&window, &to_record(key.as_bytes()))
+                .unwrap();
+            assert!(value.is_some());
+        }
+        // the values of the dropped window are discarded
+        assert_eq!(storage.windows(), vec![window.clone()]);
+        assert_eq!(storage.count(&window), 2);
+        drop(storage);
+
+        ROCKSDB_STORAGE.remove(&StorageKey::new(job_id, 0));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
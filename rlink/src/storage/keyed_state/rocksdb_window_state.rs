@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::core::element::{Barrier, Record};
+use crate::core::runtime::JobId;
+use crate::core::window::Window;
+use crate::storage::keyed_state::mem_reducing_state::MemoryReducingState;
+use crate::storage::keyed_state::mem_storage::{append_drop_window, StorageKey};
+use crate::storage::keyed_state::rocksdb_reducing_state::SCAN_BATCH_SIZE;
+use crate::storage::keyed_state::rocksdb_storage::RocksDBStorage;
+use crate::storage::keyed_state::{
+    merging_session_windows, ResultFunction, StateKey, TReducingState, TWindowState,
+};
+
+/// The window state in the rocksdb, so the state can be larger than memory. The windows are
+/// restored with the rocksdb
+#[derive(Clone)]
+pub struct RocksDBWindowState {
+    job_id: JobId,
+    task_number: u16,
+    storage: Arc<RocksDBStorage>,
+    windows: HashSet<Window>,
+}
+
+impl RocksDBWindowState {
+    pub(crate) fn new(job_id: JobId, task_number: u16, storage: Arc<RocksDBStorage>) -> Self {
+        let windows = storage.windows().into_iter().collect();
+        RocksDBWindowState {
+            job_id,
+            task_number,
+            storage,
+            windows,
+        }
+    }
+
+    fn merge_value<F>(&mut self, window: &Window, key: &Record, record: &mut Record, reduce_fun: F)
+    where
+        F: Fn(Option<&mut Record>, &mut Record) -> Record,
+    {
+        self.storage
+            .update_value(window, key, |value| match value {
+                Some(mut value) => reduce_fun(Some(&mut value), record),
+                None => reduce_fun(None, record),
+            })
+            .expect("write rocksdb state error");
+        if !self.windows.contains(window) {
+            self.windows.insert(window.clone());
+        }
+    }
+
+    /// Move the values of the key in the session windows intersecting the `window` to the
+    /// window covers them, the emptied windows are dropped. Returns the covered window
+    fn merge_session_windows<M>(&mut self, window: &Window, key: &Record, merge_fun: M) -> Window
+    where
+        M: Fn(&mut Record, &mut Record) -> Record,
+    {
+        let mut key_windows = Vec::new();
+        for w in self.windows.iter().filter(|w| w.is_session_window()) {
+            let value = self
+                .storage
+                .get_value(w, key)
+                .expect("read rocksdb state error");
+            if value.is_some() {
+                key_windows.push(w.clone());
+            }
+        }
+        let (merged_window, merging_windows) = merging_session_windows(window, key_windows.iter());
+
+        let mut merged_value: Option<Record> = None;
+        for w in merging_windows.iter().filter(|w| **w != merged_window) {
+            let mut value = self
+                .storage
+                .get_value(w, key)
+                .expect("read rocksdb state error")
+                .unwrap();
+            let count = self
+                .storage
+                .delete_value(w, key)
+                .expect("delete rocksdb state error");
+            if count == 0 {
+                self.windows.remove(w);
+            }
+
+            merged_value = match merged_value {
+                Some(mut merged_value) => Some(merge_fun(&mut merged_value, &mut value)),
+                None => Some(value),
+            };
+        }
+
+        if let Some(mut merged_value) = merged_value {
+            self.merge_value(
+                &merged_window,
+                key,
+                &mut merged_value,
+                |value, record| match value {
+                    Some(value) => merge_fun(value, record),
+                    None => record.clone(),
+                },
+            );
+        }
+
+        merged_window
+    }
+}
+
+impl TWindowState for RocksDBWindowState {
+    fn windows(&self) -> Vec<Window> {
+        self.windows.iter().cloned().collect()
+    }
+
+    fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    fn merge<F, M>(
+        &mut self,
+        key: Record,
+        record: &mut Record,
+        reduce_fun: F,
+        merge_fun: M,
+    ) -> Vec<Window>
+    where
+        F: Fn(Option<&mut Record>, &mut Record) -> Record,
+        M: Fn(&mut Record, &mut Record) -> Record,
+    {
+        let windows = record.location_windows().clone();
+        let mut merged_windows = Vec::with_capacity(windows.len());
+        for window in &windows {
+            let window = if window.is_session_window() {
+                self.merge_session_windows(window, &key, &merge_fun)
+            } else {
+                window.clone()
+            };
+            self.merge_value(&window, &key, record, |value, record| {
+                reduce_fun(value, record)
+            });
+            merged_windows.push(window);
+        }
+        merged_windows
+    }
+
+    fn get(&mut self, window: &Window, key: &Record) -> Option<Record> {
+        self.storage
+            .get_value(window, key)
+            .expect("read rocksdb state error")
+    }
+
+    /// The dropped window stays in the rocksdb until it's read by the `RocksDBReducingState`,
+    /// or it's copied to the memory if the values are converted by the `result_fun`
+    fn drop_window(&mut self, window: &Window, result_fun: Option<ResultFunction>) -> usize {
+        if result_fun.is_some() {
+            self.fire_window(window, result_fun);
+            return self.purge_window(window);
+        }
+
+        if self.windows.remove(window) {
+            self.storage
+                .drop_window(window)
+                .expect("drop rocksdb state error");
+        }
+        self.windows.len()
+    }
+
+    /// The state of the fired window is copied to the memory, as the window stays in the
+    /// rocksdb
+    fn fire_window(&mut self, window: &Window, mut result_fun: Option<ResultFunction>) {
+        if !self.windows.contains(window) {
+            return;
+        }
+
+        let state_key = StateKey::new(window.clone(), self.job_id, self.task_number);
+        let mut state = MemoryReducingState::new(&state_key);
+        let mut from: Option<Vec<u8>> = None;
+        loop {
+            let key_values = self
+                .storage
+                .scan(window, from.as_deref(), SCAN_BATCH_SIZE)
+                .expect("read rocksdb state error");
+            from = key_values
+                .last()
+                .map(|(key, _value)| key.values.as_slice().to_vec());
+            let end = key_values.len() < SCAN_BATCH_SIZE;
+            for (key, mut value) in key_values {
+                let value = match result_fun.as_mut() {
+                    Some(result_fun) => result_fun(&key, &mut value),
+                    None => value,
+                };
+                state.insert(key, value);
+            }
+            if end {
+                break;
+            }
+        }
+        let storage_key = StorageKey::new(self.job_id, self.task_number);
+        append_drop_window(storage_key, window.clone(), state);
+    }
+
+    fn purge_window(&mut self, window: &Window) -> usize {
+        if self.windows.remove(window) {
+            self.storage
+                .purge_window(window)
+                .expect("drop rocksdb state error");
+        }
+        self.windows.len()
+    }
+
+    /// The window states are written to the rocksdb directly, the rocksdb is checkpointed and
+    /// uploaded by the `RuntimeContext` of the task
+    fn snapshot(&mut self, _barrier: Barrier) {}
+}
@@ -2,7 +2,7 @@ use std::borrow::BorrowMut;
 
 use tokio::sync::Mutex;
 
-use crate::core::runtime::{ClusterDescriptor, ManagerStatus};
+use crate::core::runtime::{ClusterDescriptor, ManagerStatus, WorkerManagerDescriptor};
 use crate::runtime::HeartbeatItem;
 use crate::storage::metadata::TMetadataStorage;
 use crate::utils::date_time::current_timestamp_millis;
@@ -76,23 +76,40 @@ impl TMetadataStorage for MemoryMetadataStorage {
                 HeartbeatItem::WorkerAddrs {
                     address,
                     web_address,
+                    queryable_state_address,
                 } => {
                     task_manager_descriptor.task_manager_address = address;
                     task_manager_descriptor.web_address = web_address;
+                    task_manager_descriptor.queryable_state_address = queryable_state_address;
                 }
                 HeartbeatItem::HeartBeatStatus(status) => {
                     task_manager_descriptor.latest_heart_beat_status = status;
                 }
-                HeartbeatItem::TaskEnd { task_id } => {
+                HeartbeatItem::TaskEnd {
+                    task_id,
+                    checkpoint_id,
+                } => {
                     for task_descriptor in &mut task_manager_descriptor.task_descriptors {
                         if task_descriptor.task_id.eq(&task_id) {
                             task_descriptor.terminated = true;
+                            task_descriptor.drain_checkpoint_id = checkpoint_id;
                         }
                     }
 
                     exist_task_end_hb = true;
                     info!("Receiver `TaskEnd` heartbeat from {:?}", task_id);
                 }
+                HeartbeatItem::Accumulators(task_accumulators) => {
+                    for task_accumulators in task_accumulators {
+                        let task_descriptor = task_manager_descriptor
+                            .task_descriptors
+                            .iter_mut()
+                            .find(|x| x.task_id.eq(&task_accumulators.task_id));
+                        if let Some(task_descriptor) = task_descriptor {
+                            task_descriptor.accumulators = task_accumulators.accumulators;
+                        }
+                    }
+                }
             }
         }
 
@@ -111,4 +128,34 @@ impl TMetadataStorage for MemoryMetadataStorage {
 
         Ok(cluster_descriptor.coordinator_manager.status)
     }
+
+    async fn reset_workers(
+        &self,
+        startup_number: u64,
+        worker_managers: Vec<WorkerManagerDescriptor>,
+    ) -> anyhow::Result<()> {
+        let mut lock = METADATA_STORAGE.lock().await;
+        let cluster_descriptor = (&mut *lock)
+            .as_mut()
+            .ok_or(anyhow!("ClusterDescriptor not found"))?;
+
+        for worker_manager in worker_managers {
+            let task_manager_descriptor = cluster_descriptor
+                .worker_managers
+                .iter_mut()
+                .find(|w| {
+                    w.task_manager_id
+                        .eq(worker_manager.task_manager_id.as_str())
+                })
+                .ok_or(anyhow!(
+                    "TaskManager not found, task_manager_id={}",
+                    worker_manager.task_manager_id
+                ))?;
+            *task_manager_descriptor = worker_manager;
+        }
+        cluster_descriptor.coordinator_manager.startup_number = startup_number;
+        cluster_descriptor.coordinator_manager.status = ManagerStatus::Migration;
+
+        Ok(())
+    }
 }
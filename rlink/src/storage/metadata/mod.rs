@@ -1,5 +1,5 @@
 use crate::core::cluster::MetadataStorageType;
-use crate::core::runtime::{ClusterDescriptor, ManagerStatus};
+use crate::core::runtime::{ClusterDescriptor, ManagerStatus, WorkerManagerDescriptor};
 use crate::runtime::HeartbeatItem;
 use crate::storage::metadata::mem_metadata_storage::MemoryMetadataStorage;
 
@@ -26,6 +26,14 @@ pub trait TMetadataStorage {
         heartbeat_items: Vec<HeartbeatItem>,
         worker_manager_status: ManagerStatus,
     ) -> anyhow::Result<ManagerStatus>;
+
+    /// replace the descriptors of the restarting workers and the `startup_number` of their
+    /// attempt, and change the coordinator's status to `Migration` until they're registered
+    async fn reset_workers(
+        &self,
+        startup_number: u64,
+        worker_managers: Vec<WorkerManagerDescriptor>,
+    ) -> anyhow::Result<()>;
 }
 
 #[derive(Clone)]
@@ -80,6 +88,18 @@ impl TMetadataStorage for MetadataStorage {
             }
         }
     }
+
+    async fn reset_workers(
+        &self,
+        startup_number: u64,
+        worker_managers: Vec<WorkerManagerDescriptor>,
+    ) -> anyhow::Result<()> {
+        match self {
+            MetadataStorage::MemoryMetadataStorage(storage) => {
+                storage.reset_workers(startup_number, worker_managers).await
+            }
+        }
+    }
 }
 
 pub(crate) async fn loop_read_cluster_descriptor(
@@ -112,3 +132,16 @@ pub(crate) async fn loop_update_application_status(
         std::time::Duration::from_secs(2)
     );
 }
+
+pub(crate) async fn loop_reset_workers(
+    metadata_storage: &MetadataStorage,
+    startup_number: u64,
+    worker_managers: Vec<WorkerManagerDescriptor>,
+) {
+    loop_fn!(
+        metadata_storage
+            .reset_workers(startup_number, worker_managers.clone())
+            .await,
+        std::time::Duration::from_secs(2)
+    );
+}
@@ -1,3 +1,6 @@
 pub mod checkpoint;
+pub mod high_availability;
 pub mod keyed_state;
 pub mod metadata;
+pub mod object_storage;
+pub mod savepoint;
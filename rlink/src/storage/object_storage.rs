@@ -0,0 +1,218 @@
+//! Create the `ObjectStore` from the url, e.g. `s3://bucket/path`, `gs://bucket/path`,
+//! `azblob://container/path` and `file:///path`.
+//!
+//! The credentials are loaded from the environment variables(`AWS_*`, `GOOGLE_*`, `AZURE_*`)
+//! and the instance metadata if absent in the options. The schemes not supported by the
+//! `object_store`, e.g. `hdfs://`, can be registered by [`register_object_store`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::{BackoffConfig, ObjectStore, RetryConfig};
+
+pub const AWS_ACCESS_KEY_ID: &str = "aws.access_key_id";
+pub const AWS_SECRET_ACCESS_KEY: &str = "aws.secret_access_key";
+pub const AWS_SESSION_TOKEN: &str = "aws.session_token";
+pub const AWS_REGION: &str = "aws.region";
+/// the endpoint of the s3 compatible storage, e.g. `http://localhost:9000` of minio
+pub const AWS_ENDPOINT: &str = "aws.endpoint";
+pub const AWS_ALLOW_HTTP: &str = "aws.allow_http";
+
+pub const GCS_SERVICE_ACCOUNT_PATH: &str = "gcs.service_account_path";
+
+pub const AZURE_ACCOUNT: &str = "azure.account";
+pub const AZURE_ACCESS_KEY: &str = "azure.access_key";
+pub const AZURE_USE_EMULATOR: &str = "azure.use_emulator";
+
+pub const RETRY_MAX_RETRIES: &str = "retry.max_retries";
+/// the max duration of retrying a request
+pub const RETRY_TIMEOUT: &str = "retry.timeout";
+pub const RETRY_INIT_BACKOFF: &str = "retry.backoff.init";
+pub const RETRY_MAX_BACKOFF: &str = "retry.backoff.max";
+
+lazy_static! {
+    static ref CUSTOM_STORES: Mutex<HashMap<String, Arc<dyn ObjectStore>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Register the store for the `scheme://authority` of the url, e.g. a hdfs store for
+/// `hdfs://namenode:8020`. The registered store takes precedence over the builtin.
+pub fn register_object_store(url: &str, store: Arc<dyn ObjectStore>) -> anyhow::Result<()> {
+    let url = ObjectStoreUrl::parse(url)?;
+    CUSTOM_STORES.lock().unwrap().insert(url.root(), store);
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectStoreUrl {
+    pub scheme: String,
+    /// the bucket, the container or the `host:port`
+    pub authority: String,
+    /// the path in the store without the leading `/`
+    pub path: String,
+}
+
+impl ObjectStoreUrl {
+    pub fn parse(url: &str) -> anyhow::Result<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| anyhow!("invalid object store url `{}`", url))?;
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        if scheme.is_empty() || (authority.is_empty() && scheme != "file") {
+            return Err(anyhow!("invalid object store url `{}`", url));
+        }
+
+        Ok(ObjectStoreUrl {
+            scheme: scheme.to_lowercase(),
+            authority: authority.to_string(),
+            path: path.trim_matches('/').to_string(),
+        })
+    }
+
+    pub fn root(&self) -> String {
+        format!("{}://{}", self.scheme, self.authority)
+    }
+}
+
+/// Create the store and the path in it by the url
+pub fn parse_url(
+    url: &str,
+    options: &HashMap<String, String>,
+) -> anyhow::Result<(Arc<dyn ObjectStore>, Path)> {
+    let url = ObjectStoreUrl::parse(url)?;
+    let path = Path::from(url.path.as_str());
+
+    if let Some(store) = CUSTOM_STORES.lock().unwrap().get(&url.root()) {
+        return Ok((store.clone(), path));
+    }
+
+    let retry = retry_config(options)?;
+    let store: Arc<dyn ObjectStore> = match url.scheme.as_str() {
+        "s3" | "s3a" => {
+            let mut builder = AmazonS3Builder::from_env()
+                .with_bucket_name(url.authority.as_str())
+                .with_retry(retry);
+            if let Some(v) = options.get(AWS_ACCESS_KEY_ID) {
+                builder = builder.with_access_key_id(v);
+            }
+            if let Some(v) = options.get(AWS_SECRET_ACCESS_KEY) {
+                builder = builder.with_secret_access_key(v);
+            }
+            if let Some(v) = options.get(AWS_SESSION_TOKEN) {
+                builder = builder.with_token(v);
+            }
+            if let Some(v) = options.get(AWS_REGION) {
+                builder = builder.with_region(v);
+            }
+            if let Some(v) = options.get(AWS_ENDPOINT) {
+                builder = builder.with_endpoint(v);
+            }
+            if let Some(v) = options.get(AWS_ALLOW_HTTP) {
+                builder = builder.with_allow_http(parse_bool(AWS_ALLOW_HTTP, v)?);
+            }
+            Arc::new(builder.build()?)
+        }
+        "gs" | "gcs" => {
+            let mut builder = GoogleCloudStorageBuilder::from_env()
+                .with_bucket_name(url.authority.as_str())
+                .with_retry(retry);
+            if let Some(v) = options.get(GCS_SERVICE_ACCOUNT_PATH) {
+                builder = builder.with_service_account_path(v);
+            }
+            Arc::new(builder.build()?)
+        }
+        "azblob" | "az" | "abfs" => {
+            let mut builder = MicrosoftAzureBuilder::from_env()
+                .with_container_name(url.authority.as_str())
+                .with_retry(retry);
+            if let Some(v) = options.get(AZURE_ACCOUNT) {
+                builder = builder.with_account(v);
+            }
+            if let Some(v) = options.get(AZURE_ACCESS_KEY) {
+                builder = builder.with_access_key(v);
+            }
+            if let Some(v) = options.get(AZURE_USE_EMULATOR) {
+                builder = builder.with_use_emulator(parse_bool(AZURE_USE_EMULATOR, v)?);
+            }
+            Arc::new(builder.build()?)
+        }
+        "file" => Arc::new(LocalFileSystem::new()),
+        _ => {
+            return Err(anyhow!(
+                "no object store for `{}`, register it by `register_object_store`",
+                url.root()
+            ))
+        }
+    };
+
+    Ok((store, path))
+}
+
+fn retry_config(options: &HashMap<String, String>) -> anyhow::Result<RetryConfig> {
+    let mut retry = RetryConfig::default();
+    if let Some(v) = options.get(RETRY_MAX_RETRIES) {
+        retry.max_retries = v
+            .parse()
+            .map_err(|_e| anyhow!("invalid `{}`: {}", RETRY_MAX_RETRIES, v))?;
+    }
+    if let Some(v) = options.get(RETRY_TIMEOUT) {
+        retry.retry_timeout = parse_millis(RETRY_TIMEOUT, v)?;
+    }
+
+    let mut backoff = BackoffConfig::default();
+    if let Some(v) = options.get(RETRY_INIT_BACKOFF) {
+        backoff.init_backoff = parse_millis(RETRY_INIT_BACKOFF, v)?;
+    }
+    if let Some(v) = options.get(RETRY_MAX_BACKOFF) {
+        backoff.max_backoff = parse_millis(RETRY_MAX_BACKOFF, v)?;
+    }
+    retry.backoff = backoff;
+
+    Ok(retry)
+}
+
+fn parse_bool(key: &str, value: &str) -> anyhow::Result<bool> {
+    value
+        .parse()
+        .map_err(|_e| anyhow!("invalid `{}`: {}", key, value))
+}
+
+/// The duration in millis
+fn parse_millis(key: &str, value: &str) -> anyhow::Result<Duration> {
+    value
+        .parse()
+        .map(Duration::from_millis)
+        .map_err(|_e| anyhow!("invalid `{}`: {}", key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::storage::object_storage::{parse_url, ObjectStoreUrl};
+
+    #[test]
+    pub fn object_store_url_test() {
+        let url = ObjectStoreUrl::parse("s3://my-bucket/rlink/checkpoint/").unwrap();
+        assert_eq!(url.scheme, "s3");
+        assert_eq!(url.authority, "my-bucket");
+        assert_eq!(url.path, "rlink/checkpoint");
+        assert_eq!(url.root(), "s3://my-bucket");
+
+        let url = ObjectStoreUrl::parse("file:///tmp/rlink").unwrap();
+        assert_eq!(url.authority, "");
+        assert_eq!(url.path, "tmp/rlink");
+
+        assert!(ObjectStoreUrl::parse("/tmp/rlink").is_err());
+        assert!(ObjectStoreUrl::parse("s3:///rlink").is_err());
+
+        let e = parse_url("hdfs://namenode:8020/rlink", &HashMap::new()).err();
+        assert!(e.is_some());
+    }
+}
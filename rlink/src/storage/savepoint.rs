@@ -0,0 +1,204 @@
+//! The savepoint is a checkpoint written to the path chosen by the user, e.g.
+//! `s3://bucket/savepoints/savepoint-{checkpoint_id}/_metadata`. The checkpoints of the
+//! operators are keyed by the `uid` of the operators, so the application can be modified and
+//! restored from the savepoint as long as the `uid` of the stateful operators are kept.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::core::checkpoint::Checkpoint;
+use crate::core::runtime::{CheckpointId, OperatorId};
+use crate::storage::object_storage;
+
+const METADATA_FILE: &str = "_metadata";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavepointOperator {
+    /// the `uid` of the operator, or `operator-{operator_id}` if the `uid` is not set
+    pub state_key: String,
+    pub operator_name: String,
+    pub parallelism: u16,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavepointMetadata {
+    pub application_name: String,
+    pub application_id: String,
+    pub checkpoint_id: CheckpointId,
+    /// the time in millis the savepoint is taken
+    pub timestamp: u64,
+    pub operators: Vec<SavepointOperator>,
+}
+
+impl SavepointMetadata {
+    /// Map the checkpoints of the savepoint to the operators by the state key, the operators
+    /// not found in the savepoint start with empty state. The checkpoints of all tasks are kept
+    /// if the parallelism is changed, the keyed and operator states are redistributed from them
+    pub fn operator_checkpoints(
+        &self,
+        state_keys: &HashMap<OperatorId, (String, u16)>,
+    ) -> HashMap<OperatorId, Vec<Checkpoint>> {
+        let mut savepoint_operators: HashMap<&str, &SavepointOperator> = self
+            .operators
+            .iter()
+            .map(|x| (x.state_key.as_str(), x))
+            .collect();
+
+        let mut operator_checkpoints = HashMap::new();
+        for (operator_id, (state_key, parallelism)) in state_keys {
+            let savepoint_operator = match savepoint_operators.remove(state_key.as_str()) {
+                Some(savepoint_operator) => savepoint_operator,
+                None => {
+                    info!("operator `{}` not found in the savepoint", state_key);
+                    continue;
+                }
+            };
+
+            if savepoint_operator.parallelism != *parallelism {
+                info!(
+                    "the parallelism of operator `{}` changed from {} to {}, the state is redistributed",
+                    state_key, savepoint_operator.parallelism, parallelism
+                );
+            }
+
+            let checkpoints = savepoint_operator
+                .checkpoints
+                .iter()
+                .map(|ck| {
+                    let mut ck = ck.clone();
+                    ck.operator_id = *operator_id;
+                    ck
+                })
+                .collect();
+            operator_checkpoints.insert(*operator_id, checkpoints);
+        }
+
+        for state_key in savepoint_operators.keys() {
+            warn!(
+                "the state of operator `{}` in the savepoint is not restored",
+                state_key
+            );
+        }
+
+        operator_checkpoints
+    }
+}
+
+/// The directory of the savepoint in the `url`
+pub fn savepoint_url(url: &str, checkpoint_id: CheckpointId) -> String {
+    format!(
+        "{}/savepoint-{}",
+        url.trim_end_matches('/'),
+        checkpoint_id.0
+    )
+}
+
+/// Write the metadata to the savepoint directory
+pub async fn write_savepoint(
+    url: &str,
+    options: &HashMap<String, String>,
+    metadata: &SavepointMetadata,
+) -> anyhow::Result<()> {
+    let (store, path) = object_storage::parse_url(url, options)?;
+    let payload = serde_json::to_vec(metadata)?;
+    store
+        .put(&path.child(METADATA_FILE), Bytes::from(payload))
+        .await?;
+    Ok(())
+}
+
+/// Read the metadata from the savepoint directory
+pub async fn read_savepoint(
+    url: &str,
+    options: &HashMap<String, String>,
+) -> anyhow::Result<SavepointMetadata> {
+    let (store, path) = object_storage::parse_url(url, options)?;
+    let payload = store.get(&path.child(METADATA_FILE)).await?.bytes().await?;
+    let metadata = serde_json::from_slice(payload.as_ref())?;
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::core::checkpoint::{Checkpoint, CheckpointHandle};
+    use crate::core::runtime::{CheckpointId, JobId, OperatorId, TaskId};
+    use crate::storage::savepoint::{
+        read_savepoint, savepoint_url, write_savepoint, SavepointMetadata, SavepointOperator,
+    };
+
+    fn checkpoint(operator_id: u32, task_number: u16) -> Checkpoint {
+        Checkpoint {
+            operator_id: OperatorId(operator_id),
+            task_id: TaskId {
+                job_id: JobId(operator_id),
+                task_number,
+                num_tasks: 2,
+            },
+            checkpoint_id: CheckpointId(10),
+            completed_checkpoint_id: None,
+            alignment_duration: 0,
+            handle: CheckpointHandle {
+                handle: format!("{}-{}", operator_id, task_number),
+            },
+        }
+    }
+
+    #[tokio::test]
+    pub async fn savepoint_test() {
+        let root = std::env::temp_dir().join(format!("rlink-savepoint-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let url = savepoint_url(
+            format!("file://{}", root.to_string_lossy()).as_str(),
+            CheckpointId(10),
+        );
+
+        let metadata = SavepointMetadata {
+            application_name: "app".to_string(),
+            application_id: "app-1".to_string(),
+            checkpoint_id: CheckpointId(10),
+            timestamp: 0,
+            operators: vec![
+                SavepointOperator {
+                    state_key: "reduce".to_string(),
+                    operator_name: "reduce".to_string(),
+                    parallelism: 2,
+                    checkpoints: vec![checkpoint(3, 0), checkpoint(3, 1)],
+                },
+                SavepointOperator {
+                    state_key: "source".to_string(),
+                    operator_name: "source".to_string(),
+                    parallelism: 2,
+                    checkpoints: vec![checkpoint(0, 0), checkpoint(0, 1)],
+                },
+            ],
+        };
+        write_savepoint(url.as_str(), &HashMap::new(), &metadata)
+            .await
+            .unwrap();
+        let metadata = read_savepoint(url.as_str(), &HashMap::new()).await.unwrap();
+
+        // a filter is inserted before the reduce, the reduce is restored by the uid
+        let mut state_keys = HashMap::new();
+        state_keys.insert(OperatorId(0), ("source".to_string(), 2));
+        state_keys.insert(OperatorId(1), ("operator-1".to_string(), 2));
+        state_keys.insert(OperatorId(4), ("reduce".to_string(), 2));
+
+        let operator_checkpoints = metadata.operator_checkpoints(&state_keys);
+        assert_eq!(operator_checkpoints.len(), 2);
+        let reduce_cks = operator_checkpoints.get(&OperatorId(4)).unwrap();
+        assert_eq!(reduce_cks.len(), 2);
+        assert!(reduce_cks.iter().all(|ck| ck.operator_id == OperatorId(4)));
+        assert_eq!(reduce_cks[0].handle.handle, "3-0");
+
+        // the checkpoints of all tasks are kept for the redistribution on rescale
+        state_keys.insert(OperatorId(4), ("reduce".to_string(), 4));
+        let operator_checkpoints = metadata.operator_checkpoints(&state_keys);
+        assert_eq!(operator_checkpoints.get(&OperatorId(4)).unwrap().len(), 2);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -6,3 +6,8 @@ pub fn hash_code(v: &[u8]) -> std::io::Result<u32> {
     let mut cursor = Cursor::new(v);
     murmur3_32(&mut cursor, 0x19264330)
 }
+
+pub fn hash_code_64(v: &[u8]) -> std::io::Result<u64> {
+    let mut cursor = Cursor::new(v);
+    murmur3_x64_128(&mut cursor, 0x19264330).map(|x| x as u64)
+}
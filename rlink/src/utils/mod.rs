@@ -5,6 +5,7 @@ pub mod hash;
 pub mod http;
 pub mod ip;
 pub mod panic;
+pub mod pod_template;
 pub mod process;
 pub mod stream;
 pub mod thread;
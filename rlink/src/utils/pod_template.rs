@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::core::cluster::read_config_from_path;
+
+/// The container of the pod template merged with the rlink container, the other containers of
+/// the template are kept as the sidecars
+pub const MAIN_CONTAINER_NAME: &str = "rlink-main";
+
+/// Parse the pod template of the kubernetes from the yaml or json `content`, the template is
+/// a `Pod` with the `metadata` and the `spec`
+pub fn parse_pod_template(content: &str) -> anyhow::Result<Value> {
+    let template: Value =
+        serde_yaml::from_str(content).map_err(|e| anyhow!("parse pod template error {}", e))?;
+    if !template.is_object() {
+        return Err(anyhow!("the pod template must be an object"));
+    }
+    Ok(template)
+}
+
+/// load the pod template from the path
+pub fn load_pod_template(path: &str) -> anyhow::Result<Value> {
+    let content = read_config_from_path(PathBuf::from(path))
+        .map_err(|e| anyhow!("read pod template {} error {}", path, e))?;
+    parse_pod_template(content.as_str())
+}
+
+/// Overlay the `pod` generated by rlink on the `template`.
+///
+/// The objects are merged recursively and the other values of the `pod` win, e.g. the labels
+/// and the annotations of both are kept, while the `ownerReferences` are replaced. The first
+/// container of the `pod` is merged on the `MAIN_CONTAINER_NAME` container of the template,
+/// so its `env`, `volumeMounts` and `securityContext` are kept, and the other containers of
+/// the template are appended as the sidecars
+pub fn apply_pod_template(template: &Value, mut pod: Value) -> Value {
+    let mut template = template.clone();
+    if let Value::Object(template) = &mut template {
+        template.remove("apiVersion");
+        template.remove("kind");
+    }
+
+    let template_containers = template
+        .pointer_mut("/spec/containers")
+        .map(|x| x.take())
+        .and_then(|x| match x {
+            Value::Array(containers) => Some(containers),
+            _ => None,
+        })
+        .unwrap_or_default();
+    let (main_containers, sidecars): (Vec<Value>, Vec<Value>) = template_containers
+        .into_iter()
+        .partition(|x| x["name"].as_str() == Some(MAIN_CONTAINER_NAME));
+
+    if let Some(Value::Array(containers)) = pod.pointer_mut("/spec/containers") {
+        if let (Some(main_container), Some(container)) =
+            (main_containers.into_iter().next(), containers.first_mut())
+        {
+            let generated = container.take();
+            *container = main_container;
+            merge(container, generated);
+        }
+        containers.extend(sidecars);
+    }
+
+    merge(&mut template, pod);
+    template
+}
+
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Append the `volume` to the pod and mount it to the first container by the `volume_mount`
+pub fn add_volume(pod: &mut Value, volume: Value, volume_mount: Value) {
+    push(pod, "/spec/volumes", volume);
+    push(pod, "/spec/containers/0/volumeMounts", volume_mount);
+}
+
+fn push(value: &mut Value, pointer: &str, element: Value) {
+    let (parent, key) = pointer.rsplit_once('/').unwrap();
+    let parent = match value.pointer_mut(parent) {
+        Some(Value::Object(parent)) => parent,
+        _ => return,
+    };
+    match parent
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Array(Vec::new()))
+    {
+        Value::Array(array) => array.push(element),
+        other => *other = Value::Array(vec![element]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::utils::pod_template::{add_volume, apply_pod_template, parse_pod_template};
+
+    #[test]
+    pub fn apply_pod_template_test() {
+        let template = parse_pod_template(
+            r#"
+apiVersion: v1
+kind: Pod
+metadata:
+  labels:
+    team: data
+  annotations:
+    prometheus.io/scrape: "true"
+spec:
+  serviceAccountName: rlink
+  nodeSelector:
+    disktype: ssd
+  tolerations:
+    - key: dedicated
+      operator: Equal
+      value: rlink
+      effect: NoSchedule
+  securityContext:
+    runAsUser: 1000
+  volumes:
+    - name: data
+      emptyDir: {}
+  containers:
+    - name: rlink-main
+      image: ignored
+      env:
+        - name: RUST_LOG
+          value: info
+      volumeMounts:
+        - name: data
+          mountPath: /data
+    - name: log-agent
+      image: fluent-bit
+"#,
+        )
+        .unwrap();
+
+        let pod = json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": {
+                "name": "worker-0",
+                "labels": {"app": "rlink"}
+            },
+            "spec": {
+                "containers": [{
+                    "name": "worker-0",
+                    "image": "rlink:1.0",
+                    "args": ["manager_type=Worker"]
+                }],
+                "restartPolicy": "OnFailure"
+            }
+        });
+
+        let mut pod = apply_pod_template(&template, pod);
+        assert_eq!(pod["kind"], "Pod");
+        assert_eq!(pod["metadata"]["name"], "worker-0");
+        assert_eq!(
+            pod["metadata"]["labels"],
+            json!({"team": "data", "app": "rlink"})
+        );
+        assert_eq!(
+            pod["metadata"]["annotations"]["prometheus.io/scrape"],
+            "true"
+        );
+        assert_eq!(pod["spec"]["serviceAccountName"], "rlink");
+        assert_eq!(pod["spec"]["nodeSelector"]["disktype"], "ssd");
+        assert_eq!(pod["spec"]["tolerations"][0]["key"], "dedicated");
+        assert_eq!(pod["spec"]["securityContext"]["runAsUser"], 1000);
+        assert_eq!(pod["spec"]["restartPolicy"], "OnFailure");
+
+        let containers = pod["spec"]["containers"].as_array().unwrap();
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0]["name"], "worker-0");
+        assert_eq!(containers[0]["image"], "rlink:1.0");
+        assert_eq!(containers[0]["env"][0]["name"], "RUST_LOG");
+        assert_eq!(containers[0]["volumeMounts"][0]["mountPath"], "/data");
+        assert_eq!(containers[1]["name"], "log-agent");
+
+        add_volume(
+            &mut pod,
+            json!({"name": "pod-template", "configMap": {"name": "pod-template"}}),
+            json!({"name": "pod-template", "mountPath": "/opt/rlink/pod-template"}),
+        );
+        assert_eq!(pod["spec"]["volumes"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            pod["spec"]["containers"][0]["volumeMounts"][1]["name"],
+            "pod-template"
+        );
+    }
+}